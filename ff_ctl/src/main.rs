@@ -0,0 +1,206 @@
+use std::net::SocketAddr;
+use std::str::FromStr;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::Value;
+use structopt::StructOpt;
+use ff_standard_lib::messages::data_server_messaging::{FundForgeError, StrategyStreamInfo};
+use ff_standard_lib::standardized_types::accounts::{Account, AccountSummary};
+use ff_standard_lib::standardized_types::base_data::base_data_type::BaseDataType;
+use ff_standard_lib::standardized_types::datavendor_enum::DataVendor;
+use ff_standard_lib::standardized_types::download_jobs::{DownloadJobRequest, DownloadPriority};
+use ff_standard_lib::standardized_types::resolution::Resolution;
+use ff_standard_lib::standardized_types::orders::Order;
+use ff_standard_lib::standardized_types::position::Position;
+use ff_standard_lib::strategies::client_features::other_requests::{cancel_download, download_jobs, enqueue_download, list_strategy_streams};
+use ff_standard_lib::strategies::handlers::control_server::control_request;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "ff_ctl", about = "Inspect and control running fund forge strategies")]
+enum Command {
+    /// List the strategies serving controls
+    List,
+    /// Show the accounts, open positions and working orders of a strategy
+    Show {
+        strategy: String,
+    },
+    /// Flatten all positions, on every account of the strategy unless --account is given
+    Flatten {
+        strategy: String,
+        #[structopt(long)]
+        account: Option<String>,
+    },
+    /// Cancel all working orders, on every account of the strategy unless --account is given
+    CancelAll {
+        strategy: String,
+        #[structopt(long)]
+        account: Option<String>,
+    },
+    /// Send StrategyControls::Pause to the strategy
+    Pause {
+        strategy: String,
+    },
+    /// Send StrategyControls::Continue to the strategy, ending reduce only mode
+    Resume {
+        strategy: String,
+    },
+    /// Put the strategy in reduce only mode, entries are refused while exits and cancels are still sent
+    ReduceOnly {
+        strategy: String,
+    },
+    /// Queue a historical download job on the data server, --from and --to are UTC dates or times
+    Download {
+        symbol: String,
+        #[structopt(long)]
+        vendor: DataVendor,
+        #[structopt(long, parse(try_from_str = BaseDataType::from_str))]
+        data_type: BaseDataType,
+        #[structopt(long)]
+        resolution: Resolution,
+        #[structopt(long, parse(try_from_str = parse_time))]
+        from: DateTime<Utc>,
+        #[structopt(long, parse(try_from_str = parse_time))]
+        to: Option<DateTime<Utc>>,
+        #[structopt(long, default_value = "normal")]
+        priority: DownloadPriority,
+    },
+    /// List the queued, running and recently finished download jobs of the data server
+    Jobs,
+    /// Cancel a queued or running download job
+    CancelDownload {
+        id: u64,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(Command::from_args()).await {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(command: Command) -> Result<(), FundForgeError> {
+    match command {
+        Command::List => {
+            let streams = list_strategy_streams().await?;
+            if streams.is_empty() {
+                println!("No strategies are serving controls");
+            }
+            for stream in streams {
+                println!("{:>5}  {:<24} {:<18} {:<22} since {}", stream.stream_name, stream.name, format!("{:?}", stream.mode), stream.control_address, stream.registered);
+            }
+        }
+        Command::Show { strategy } => {
+            let address = control_address(&strategy).await?;
+            print_snapshot(&control_request(&address, "GET", "/snapshot").await?);
+        }
+        Command::Flatten { strategy, account } => {
+            let address = control_address(&strategy).await?;
+            let response = control_request(&address, "POST", &with_account("/flatten", account)).await?;
+            print_accounts("Flattening", &response["flattened"]);
+        }
+        Command::CancelAll { strategy, account } => {
+            let address = control_address(&strategy).await?;
+            let response = control_request(&address, "POST", &with_account("/cancel_all", account)).await?;
+            print_accounts("Cancelling orders on", &response["cancelled"]);
+        }
+        Command::Pause { strategy } => {
+            let address = control_address(&strategy).await?;
+            control_request(&address, "POST", "/pause").await?;
+            println!("Pause sent");
+        }
+        Command::Resume { strategy } => {
+            let address = control_address(&strategy).await?;
+            control_request(&address, "POST", "/continue").await?;
+            println!("Continue sent");
+        }
+        Command::ReduceOnly { strategy } => {
+            let address = control_address(&strategy).await?;
+            control_request(&address, "POST", "/reduce_only").await?;
+            println!("Reduce only set");
+        }
+        Command::Download { symbol, vendor, data_type, resolution, from, to, priority } => {
+            let mut request = DownloadJobRequest::new(symbol, vendor, data_type, resolution, from).with_priority(priority);
+            if let Some(to) = to {
+                request = request.with_to(to);
+            }
+            println!("{}", enqueue_download(request).await?);
+        }
+        Command::Jobs => {
+            let jobs = download_jobs().await?;
+            if jobs.is_empty() {
+                println!("No download jobs");
+            }
+            for job in jobs {
+                println!("{}", job);
+            }
+        }
+        Command::CancelDownload { id } => {
+            println!("{}", cancel_download(id).await?);
+        }
+    }
+    Ok(())
+}
+
+/// Parses a UTC time, or a date as midnight UTC.
+fn parse_time(time: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::from_str(time) {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc());
+    }
+    DateTime::<Utc>::from_str(time).map_err(|e| format!("Invalid time {}: {}, expected eg 2024-06-01 or 2024-06-01T14:30:00Z", time, e))
+}
+
+fn with_account(path: &str, account: Option<String>) -> String {
+    match account {
+        Some(account) => format!("{}?account={}", path, account),
+        None => path.to_string(),
+    }
+}
+
+/// Resolves a control address, connection id or strategy name to the control address.
+async fn control_address(strategy: &str) -> Result<String, FundForgeError> {
+    if strategy.parse::<SocketAddr>().is_ok() {
+        return Ok(strategy.to_string());
+    }
+    let streams = list_strategy_streams().await?;
+    let matches: Vec<&StrategyStreamInfo> = streams.iter()
+        .filter(|stream| stream.name == strategy || stream.stream_name.to_string() == strategy)
+        .collect();
+    match matches.as_slice() {
+        [stream] => Ok(stream.control_address.clone()),
+        [] => Err(FundForgeError::ClientSideErrorDebug(format!("No strategy named {} is serving controls, see `ff_ctl list`", strategy))),
+        _ => Err(FundForgeError::ClientSideErrorDebug(format!("{} strategies are named {}, use the connection id from `ff_ctl list`", matches.len(), strategy))),
+    }
+}
+
+fn print_accounts(action: &str, accounts: &Value) {
+    let accounts: Vec<Account> = serde_json::from_value(accounts.clone()).unwrap_or_default();
+    for account in accounts {
+        println!("{} {}", action, account);
+    }
+}
+
+fn print_snapshot(snapshot: &Value) {
+    println!("{} {}", snapshot["name"].as_str().unwrap_or_default(), snapshot["mode"]);
+    let accounts = snapshot["accounts"].as_array().cloned().unwrap_or_default();
+    for account in accounts {
+        let summary: Option<AccountSummary> = serde_json::from_value(account["summary"].clone()).ok();
+        match summary {
+            Some(summary) => println!("\n{}  balance: {} {:?}, buying power: {}, margin used: {}", summary.account, summary.balance, summary.currency, summary.buying_power, summary.margin_used),
+            None => println!("\n{}", account["account"]),
+        }
+
+        let positions: Vec<Position> = serde_json::from_value(account["positions"].clone()).unwrap_or_default();
+        println!("  Positions: {}", positions.len());
+        for position in positions {
+            println!("    {:<16} {:<6} {:>10} @ {:<12} open pnl: {}", position.symbol_code, format!("{:?}", position.side), position.quantity_open, position.average_price, position.open_pnl);
+        }
+
+        let orders: Vec<Order> = serde_json::from_value(account["working_orders"].clone()).unwrap_or_default();
+        println!("  Working Orders: {}", orders.len());
+        for order in orders {
+            let price = order.limit_price.or(order.trigger_price).map(|price| price.to_string()).unwrap_or_else(|| "market".to_string());
+            println!("    {:<16} {:<16} {:<5} {:<16} {:>10} @ {:<12} {}", order.id, order.symbol_code, format!("{:?}", order.side), format!("{:?}", order.order_type), order.quantity_open, price, order.tag);
+        }
+    }
+}
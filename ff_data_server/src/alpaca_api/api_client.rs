@@ -0,0 +1,187 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::sync::{broadcast, mpsc, OnceCell};
+use ff_standard_lib::messages::data_server_messaging::FundForgeError;
+use ff_standard_lib::server_launch_options::ServerLaunchOptions;
+use ff_standard_lib::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use ff_standard_lib::standardized_types::broker_enum::Brokerage;
+use ff_standard_lib::standardized_types::enums::MarketType;
+use ff_standard_lib::standardized_types::subscriptions::SymbolName;
+use crate::alpaca_api::broker_adapter::AlpacaBrokerAdapter;
+use crate::alpaca_api::models::AlpacaAsset;
+use crate::alpaca_api::settings::AlpacaSettings;
+use crate::server_features::secrets::load_credentials;
+use crate::alpaca_api::stream::{handle_alpaca_stream, AlpacaStreamCommand};
+use crate::rate_limiter::RateLimiter;
+use crate::server_features::rest_brokerage::{register_rest_brokerage, RestBrokerage};
+
+lazy_static! {
+    pub static ref ALPACA_IS_CONNECTED: AtomicBool = AtomicBool::new(false);
+}
+
+pub(crate) static ALPACA_CLIENT: OnceCell<Arc<AlpacaClient>> = OnceCell::const_new();
+pub fn get_alpaca_client() -> Option<Arc<AlpacaClient>> {
+    ALPACA_CLIENT.get().cloned()
+}
+
+/// Client for the Alpaca trading and market data apis, the same api keys are used for both.
+///
+/// # Properties
+/// * `rate_limiter` - 200 requests per minute, shared by trading and market data requests.
+/// * `assets` - The active US equities, keyed by ticker, fund forge uses the Alpaca ticker as the symbol name.
+pub struct AlpacaClient {
+    pub client: Client,
+    pub rate_limiter: Arc<RateLimiter>,
+    pub settings: AlpacaSettings,
+    pub assets: DashMap<SymbolName, AlpacaAsset>,
+    pub trade_broadcasters: Arc<DashMap<SymbolName, broadcast::Sender<BaseDataEnum>>>,
+    pub bar_broadcasters: Arc<DashMap<SymbolName, broadcast::Sender<BaseDataEnum>>>,
+    pub stream_command_sender: mpsc::Sender<AlpacaStreamCommand>,
+}
+
+impl AlpacaClient {
+    pub fn trading_url(&self, path: &str) -> String {
+        format!("{}{}", self.settings.trading_endpoint(), path)
+    }
+
+    pub fn data_url(&self, path: &str) -> String {
+        format!("{}{}", self.settings.data_endpoint(), path)
+    }
+
+    /// Returns the response body, a 401 is returned as `FundForgeError::InvalidApiKey` and any other error status as a `ServerErrorDebug` containing the Alpaca error message.
+    async fn execute(&self, request: RequestBuilder) -> Result<String, FundForgeError> {
+        self.rate_limiter.acquire().await;
+        let response = request.send().await
+            .map_err(|e| FundForgeError::ServerErrorDebug(format!("Alpaca request failed: {}", e)))?;
+        let status = response.status();
+        let body = response.text().await
+            .map_err(|e| FundForgeError::ServerErrorDebug(format!("Alpaca response could not be read: {}", e)))?;
+        if status == StatusCode::UNAUTHORIZED {
+            return Err(FundForgeError::InvalidApiKey);
+        }
+        if !status.is_success() {
+            let message = serde_json::from_str::<Value>(&body).ok()
+                .and_then(|json| json["message"].as_str().map(|message| message.to_string()))
+                .unwrap_or(body);
+            return Err(FundForgeError::ServerErrorDebug(format!("Alpaca {}: {}", status, message)));
+        }
+        Ok(body)
+    }
+
+    fn parse<T: DeserializeOwned>(body: &str) -> Result<T, FundForgeError> {
+        serde_json::from_str(body).map_err(|e| FundForgeError::ServerErrorDebug(format!("Alpaca response could not be parsed: {}", e)))
+    }
+
+    pub(crate) async fn get<T: DeserializeOwned>(&self, url: &str) -> Result<T, FundForgeError> {
+        let body = self.execute(self.client.get(url)).await?;
+        Self::parse(&body)
+    }
+
+    pub(crate) async fn post<T: DeserializeOwned>(&self, url: &str, json: &Value) -> Result<T, FundForgeError> {
+        let body = self.execute(self.client.post(url).json(json)).await?;
+        Self::parse(&body)
+    }
+
+    pub(crate) async fn patch<T: DeserializeOwned>(&self, url: &str, json: &Value) -> Result<T, FundForgeError> {
+        let body = self.execute(self.client.patch(url).json(json)).await?;
+        Self::parse(&body)
+    }
+
+    pub(crate) async fn delete(&self, url: &str) -> Result<(), FundForgeError> {
+        self.execute(self.client.delete(url)).await.map(|_| ())
+    }
+
+    /// Loads the active, tradable US equities listed on the exchanges we support.
+    pub(crate) async fn load_assets(&self) -> Result<(), FundForgeError> {
+        let assets: Vec<AlpacaAsset> = self.get(&self.trading_url("/v2/assets?status=active&asset_class=us_equity")).await?;
+        self.assets.clear();
+        for asset in assets {
+            if asset.tradable && asset.exchange().is_some() {
+                self.assets.insert(asset.symbol.clone(), asset);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn market_type(&self, symbol_name: &SymbolName) -> Option<MarketType> {
+        self.assets.get(symbol_name).and_then(|asset| asset.market_type())
+    }
+}
+
+pub(crate) async fn alpaca_init(options: ServerLaunchOptions) {
+    if options.disable_alpaca_server != 0 {
+        ALPACA_IS_CONNECTED.store(false, Ordering::SeqCst);
+        return;
+    }
+    let contents = match load_credentials(&options.data_folder, "alpaca_credentials", "alpaca_credentials.toml") {
+        Some(contents) => contents,
+        None => {
+            ALPACA_IS_CONNECTED.store(false, Ordering::SeqCst);
+            return;
+        }
+    };
+
+    let settings: AlpacaSettings = match AlpacaSettings::from_toml(&contents) {
+        Some(s) => s,
+        None => {
+            ALPACA_IS_CONNECTED.store(false, Ordering::SeqCst);
+            eprintln!("No alpaca settings retrieved");
+            return;
+        }
+    };
+
+    let mut headers = HeaderMap::new();
+    let (key_id, secret_key) = match (HeaderValue::from_str(&settings.api_key_id), HeaderValue::from_str(&settings.api_secret_key)) {
+        (Ok(key_id), Ok(secret_key)) => (key_id, secret_key),
+        _ => {
+            eprintln!("Alpaca api keys contain invalid characters");
+            return;
+        }
+    };
+    headers.insert("APCA-API-KEY-ID", key_id);
+    headers.insert("APCA-API-SECRET-KEY", secret_key);
+    let client = match Client::builder()
+        .default_headers(headers)
+        .timeout(Duration::from_secs(30))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Alpaca client failed to build: {}", e);
+            return;
+        }
+    };
+
+    let (stream_command_sender, stream_command_receiver) = mpsc::channel(100);
+    let alpaca_client = Arc::new(AlpacaClient {
+        client,
+        rate_limiter: RateLimiter::new(200, Duration::from_secs(60)),
+        settings,
+        assets: DashMap::new(),
+        trade_broadcasters: Arc::new(DashMap::new()),
+        bar_broadcasters: Arc::new(DashMap::new()),
+        stream_command_sender,
+    });
+
+    if let Err(e) = alpaca_client.load_assets().await {
+        eprintln!("Alpaca failed to load assets: {}", e);
+        return;
+    }
+    println!("Alpaca: Loaded {} assets", alpaca_client.assets.len());
+
+    tokio::spawn(handle_alpaca_stream(alpaca_client.clone(), stream_command_receiver));
+    let _ = ALPACA_CLIENT.set(alpaca_client.clone());
+    ALPACA_IS_CONNECTED.store(true, Ordering::SeqCst);
+
+    match RestBrokerage::connect(AlpacaBrokerAdapter::new(alpaca_client)).await {
+        Ok(brokerage) => register_rest_brokerage(Brokerage::Alpaca, brokerage),
+        Err(e) => eprintln!("Alpaca brokerage failed to connect: {}", e),
+    }
+}
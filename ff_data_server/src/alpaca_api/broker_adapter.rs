@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal_macros::dec;
+use serde_json::{json, Value};
+use ff_standard_lib::messages::data_server_messaging::FundForgeError;
+use ff_standard_lib::product_maps::alpaca::maps::{get_equity_symbol_info, ALPACA_QUANTITY_DECIMALS};
+use ff_standard_lib::standardized_types::accounts::{AccountId, AccountInfo, Currency};
+use ff_standard_lib::standardized_types::broker_enum::Brokerage;
+use ff_standard_lib::standardized_types::enums::{OrderSide, PositionSide};
+use ff_standard_lib::standardized_types::new_types::{Price, Volume};
+use ff_standard_lib::standardized_types::orders::{Order, OrderType, OrderUpdateType, TimeInForce};
+use ff_standard_lib::standardized_types::subscriptions::SymbolName;
+use ff_standard_lib::standardized_types::symbol_info::{CommissionInfo, SymbolInfo};
+use crate::alpaca_api::api_client::AlpacaClient;
+use crate::alpaca_api::models::{AlpacaAccount, AlpacaOrder, AlpacaPosition};
+use crate::server_features::rest_brokerage::{BrokerOrderEvent, BrokerOrderId, BrokerOrderStatus, BrokerPosition, RestBrokerageAdapter};
+
+/// The last state we saw of an order, Alpaca reports cumulative fills so the changes are converted into fill events.
+struct TrackedOrder {
+    /// The live Alpaca order id, this changes each time the order is replaced.
+    current_id: BrokerOrderId,
+    submitted_at: DateTime<Utc>,
+    status: String,
+    filled_quantity: Volume,
+    filled_value: Price,
+    /// The fills of the orders earlier in the replace chain.
+    replaced_quantity: Volume,
+    replaced_value: Price,
+}
+
+impl TrackedOrder {
+    fn new(current_id: BrokerOrderId, submitted_at: DateTime<Utc>) -> Self {
+        TrackedOrder {
+            current_id,
+            submitted_at,
+            status: String::new(),
+            filled_quantity: dec!(0),
+            filled_value: dec!(0),
+            replaced_quantity: dec!(0),
+            replaced_value: dec!(0),
+        }
+    }
+}
+
+/// Alpaca paper and live equities trading through the generic `RestBrokerage`.
+/// A replaced order keeps reporting its events under the original id, the `replaced_by` chain is followed when polling.
+pub struct AlpacaBrokerAdapter {
+    client: Arc<AlpacaClient>,
+    /// Orders placed by fund forge, keyed by the original Alpaca order id.
+    tracked_orders: DashMap<BrokerOrderId, TrackedOrder>,
+}
+
+impl AlpacaBrokerAdapter {
+    pub fn new(client: Arc<AlpacaClient>) -> Self {
+        AlpacaBrokerAdapter {
+            client,
+            tracked_orders: DashMap::new(),
+        }
+    }
+
+    fn current_id(&self, broker_order_id: &BrokerOrderId) -> BrokerOrderId {
+        self.tracked_orders.get(broker_order_id)
+            .map(|tracked| tracked.current_id.clone())
+            .unwrap_or_else(|| broker_order_id.clone())
+    }
+}
+
+#[async_trait]
+impl RestBrokerageAdapter for AlpacaBrokerAdapter {
+    fn brokerage(&self) -> Brokerage {
+        Brokerage::Alpaca
+    }
+
+    /// Positions and orders share the 200 requests per minute limit with the market data downloads.
+    fn poll_interval(&self) -> Duration {
+        Duration::from_secs(2)
+    }
+
+    async fn authenticate(&self) -> Result<(), FundForgeError> {
+        self.client.get::<AlpacaAccount>(&self.client.trading_url("/v2/account")).await.map(|_| ())
+    }
+
+    /// Alpaca has a single account per api key.
+    async fn accounts(&self) -> Result<Vec<AccountInfo>, FundForgeError> {
+        let account: AlpacaAccount = self.client.get(&self.client.trading_url("/v2/account")).await?;
+        Ok(vec![AccountInfo {
+            account_id: account.account_number,
+            brokerage: Brokerage::Alpaca,
+            cash_value: account.equity,
+            cash_available: account.buying_power,
+            currency: Currency::USD,
+            open_pnl: dec!(0),
+            booked_pnl: dec!(0),
+            day_open_pnl: account.equity - account.last_equity,
+            day_booked_pnl: dec!(0),
+            cash_used: (account.equity - account.cash).max(dec!(0)),
+            positions: vec![],
+            is_hedging: false,
+            buy_limit: None,
+            sell_limit: None,
+            max_orders: None,
+            daily_max_loss: None,
+            daily_max_loss_reset_time: None,
+            leverage: account.multiplier.to_u32().unwrap_or(1),
+        }])
+    }
+
+    async fn symbol_names(&self) -> Result<Vec<SymbolName>, FundForgeError> {
+        Ok(self.client.assets.iter().map(|asset| asset.key().clone()).collect())
+    }
+
+    async fn symbol_info(&self, symbol_name: &SymbolName) -> Result<SymbolInfo, FundForgeError> {
+        match self.client.assets.contains_key(symbol_name) {
+            true => Ok(get_equity_symbol_info(symbol_name)),
+            false => Err(FundForgeError::ClientSideErrorDebug(format!("Alpaca symbol not found: {}", symbol_name))),
+        }
+    }
+
+    /// Alpaca equities trading is commission free.
+    async fn commission_info(&self, _symbol_name: &SymbolName) -> Result<CommissionInfo, FundForgeError> {
+        Ok(CommissionInfo {
+            per_side: dec!(0),
+            currency: Currency::USD,
+        })
+    }
+
+    fn to_broker_symbol(&self, symbol_name: &SymbolName) -> Result<String, FundForgeError> {
+        match self.client.assets.contains_key(symbol_name) {
+            true => Ok(symbol_name.clone()),
+            false => Err(FundForgeError::ClientSideErrorDebug(format!("Alpaca symbol not found or not tradable: {}", symbol_name))),
+        }
+    }
+
+    fn from_broker_symbol(&self, broker_symbol: &str) -> Result<SymbolName, FundForgeError> {
+        Ok(broker_symbol.to_string())
+    }
+
+    async fn submit_order(&self, _account_id: &AccountId, order: &Order) -> Result<BrokerOrderId, String> {
+        let fractionable = self.client.assets.get(&order.symbol_name).map(|asset| asset.fractionable).unwrap_or(false);
+        let body = order_request(order, fractionable)?;
+        let alpaca_order: AlpacaOrder = self.client.post(&self.client.trading_url("/v2/orders"), &body).await
+            .map_err(|e| e.to_string())?;
+        let submitted_at = alpaca_order.submitted_at.unwrap_or_else(Utc::now);
+        self.tracked_orders.insert(alpaca_order.id.clone(), TrackedOrder::new(alpaca_order.id.clone(), submitted_at));
+        Ok(alpaca_order.id)
+    }
+
+    async fn cancel_order(&self, _account_id: &AccountId, broker_order_id: &BrokerOrderId) -> Result<(), String> {
+        let url = self.client.trading_url(&format!("/v2/orders/{}", self.current_id(broker_order_id)));
+        self.client.delete(&url).await.map_err(|e| e.to_string())
+    }
+
+    async fn modify_order(&self, _account_id: &AccountId, broker_order_id: &BrokerOrderId, update: &OrderUpdateType) -> Result<(), String> {
+        let body = match update {
+            OrderUpdateType::LimitPrice(price) => json!({"limit_price": price.to_string()}),
+            OrderUpdateType::TriggerPrice(price) => json!({"stop_price": price.to_string()}),
+            OrderUpdateType::Quantity(quantity) => json!({"qty": quantity.round_dp(ALPACA_QUANTITY_DECIMALS).normalize().to_string()}),
+        };
+        let url = self.client.trading_url(&format!("/v2/orders/{}", self.current_id(broker_order_id)));
+        self.client.patch::<AlpacaOrder>(&url, &body).await.map(|_| ()).map_err(|e| e.to_string())
+    }
+
+    async fn positions(&self, _account_id: &AccountId) -> Result<Vec<BrokerPosition>, FundForgeError> {
+        let positions: Vec<AlpacaPosition> = self.client.get(&self.client.trading_url("/v2/positions")).await?;
+        Ok(positions.into_iter().map(|position| BrokerPosition {
+            symbol_name: position.symbol,
+            side: match position.side.as_str() {
+                "short" => PositionSide::Short,
+                _ => PositionSide::Long,
+            },
+            quantity: position.qty.abs(),
+            average_price: position.avg_entry_price,
+            open_pnl: position.unrealized_pl,
+        }).collect())
+    }
+
+    async fn poll_order_events(&self, _account_id: &AccountId) -> Result<Vec<BrokerOrderEvent>, FundForgeError> {
+        let after = match self.tracked_orders.iter().map(|tracked| tracked.submitted_at).min() {
+            Some(time) => time - chrono::Duration::seconds(1),
+            None => return Ok(vec![]),
+        };
+        let url = self.client.trading_url(&format!("/v2/orders?status=all&direction=asc&limit=500&after={}", urlencoding::encode(&after.to_rfc3339())));
+        let orders: Vec<AlpacaOrder> = self.client.get(&url).await?;
+        let orders: HashMap<String, AlpacaOrder> = orders.into_iter().map(|order| (order.id.clone(), order)).collect();
+
+        let mut events = vec![];
+        let mut complete = vec![];
+        for mut tracked in self.tracked_orders.iter_mut() {
+            let broker_order_id = tracked.key().clone();
+            let mut order = match orders.get(&tracked.current_id) {
+                Some(order) => order,
+                None => continue,
+            };
+            while let (true, Some(replacement)) = (order.status == "replaced", order.replaced_by.as_ref().and_then(|id| orders.get(id))) {
+                events.extend(order_events(&broker_order_id, &mut tracked, order));
+                tracked.replaced_quantity = tracked.filled_quantity;
+                tracked.replaced_value = tracked.filled_value;
+                tracked.current_id = replacement.id.clone();
+                order = replacement;
+            }
+            events.extend(order_events(&broker_order_id, &mut tracked, order));
+            if is_terminal(&order.status) {
+                complete.push(broker_order_id);
+            }
+        }
+        for broker_order_id in complete {
+            self.tracked_orders.remove(&broker_order_id);
+        }
+        Ok(events)
+    }
+}
+
+fn is_terminal(status: &str) -> bool {
+    matches!(status, "filled" | "canceled" | "expired" | "rejected" | "done_for_day")
+}
+
+/// Compares the order with the last state we saw and returns the events for the change.
+/// The cumulative fill quantity and average price are converted into the quantity and price of the new fills.
+fn order_events(broker_order_id: &BrokerOrderId, tracked: &mut TrackedOrder, order: &AlpacaOrder) -> Vec<BrokerOrderEvent> {
+    let time = order.updated_at.unwrap_or_else(Utc::now);
+    let event = |status: BrokerOrderStatus, fill_price: Option<Price>, fill_quantity: Volume| BrokerOrderEvent {
+        broker_order_id: broker_order_id.clone(),
+        status,
+        fill_price,
+        fill_quantity,
+        time,
+        // the order polling only reports the cumulative fill, not the fills
+        fill_id: None,
+        liquidity: None,
+        commission: None,
+    };
+
+    let mut events = vec![];
+    if tracked.status.is_empty() && order.status != "rejected" {
+        events.push(event(BrokerOrderStatus::Accepted, None, dec!(0)));
+    }
+
+    let filled_quantity = tracked.replaced_quantity + order.filled_qty;
+    let filled_value = tracked.replaced_value + order.filled_qty * order.filled_avg_price.unwrap_or_default();
+    if filled_quantity > tracked.filled_quantity {
+        let fill_quantity = filled_quantity - tracked.filled_quantity;
+        let fill_price = (filled_value - tracked.filled_value) / fill_quantity;
+        let status = match order.status.as_str() {
+            "filled" => BrokerOrderStatus::Filled,
+            _ => BrokerOrderStatus::PartiallyFilled,
+        };
+        events.push(event(status, Some(fill_price), fill_quantity));
+        tracked.filled_quantity = filled_quantity;
+        tracked.filled_value = filled_value;
+    }
+
+    if tracked.status != order.status {
+        match order.status.as_str() {
+            "canceled" | "expired" | "done_for_day" => events.push(event(BrokerOrderStatus::Cancelled, None, dec!(0))),
+            "rejected" => events.push(event(BrokerOrderStatus::Rejected("Rejected by Alpaca".to_string()), None, dec!(0))),
+            _ => {}
+        }
+        tracked.status = order.status.clone();
+    }
+    events
+}
+
+/// The json body for `POST /v2/orders`.
+///
+/// Market orders are sent as day orders, they fill immediately during the session and Alpaca only accepts fractional quantities on day orders.
+/// Other orders with a fractional quantity must use `TimeInForce::Day`, and the asset must be fractionable.
+fn order_request(order: &Order, fractionable: bool) -> Result<Value, String> {
+    if order.is_fractional() {
+        if !fractionable {
+            return Err(format!("Alpaca does not support fractional quantities for: {}", order.symbol_name));
+        }
+        if !order.order_type.supports_fractional_quantity() {
+            return Err(format!("Alpaca does not support fractional quantities for {:?} orders", order.order_type));
+        }
+        if order.order_type != OrderType::Market && order.time_in_force != TimeInForce::Day {
+            return Err("Alpaca fractional orders must use TimeInForce::Day".to_string());
+        }
+    }
+
+    let order_type = match order.order_type {
+        OrderType::Market => "market",
+        OrderType::Limit => "limit",
+        OrderType::StopMarket => "stop",
+        OrderType::StopLimit => "stop_limit",
+        _ => return Err(format!("Alpaca does not support {:?} orders", order.order_type)),
+    };
+    let time_in_force = match (&order.order_type, &order.time_in_force) {
+        (OrderType::Market, _) | (_, TimeInForce::Day) => "day",
+        (_, TimeInForce::GTC) => "gtc",
+        (_, TimeInForce::IOC) => "ioc",
+        (_, TimeInForce::FOK) => "fok",
+        (_, TimeInForce::Time(_)) => return Err("Alpaca does not support good till time orders".to_string()),
+    };
+    let quantity = order.quantity_open.round_dp(ALPACA_QUANTITY_DECIMALS).normalize();
+    if quantity <= dec!(0) {
+        return Err(format!("Invalid order quantity: {}", order.quantity_open));
+    }
+    let side = match order.side {
+        OrderSide::Buy => "buy",
+        OrderSide::Sell => "sell",
+    };
+
+    let mut body = json!({
+        "symbol": order.symbol_name,
+        "qty": quantity.to_string(),
+        "side": side,
+        "type": order_type,
+        "time_in_force": time_in_force,
+    });
+    if matches!(order.order_type, OrderType::Limit | OrderType::StopLimit) {
+        let limit_price = order.limit_price.ok_or_else(|| "Limit price required".to_string())?;
+        body["limit_price"] = json!(limit_price.to_string());
+    }
+    if matches!(order.order_type, OrderType::StopMarket | OrderType::StopLimit) {
+        let stop_price = order.trigger_price.ok_or_else(|| "Trigger price required".to_string())?;
+        body["stop_price"] = json!(stop_price.to_string());
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff_standard_lib::standardized_types::accounts::Account;
+
+    fn account() -> Account {
+        Account::new(Brokerage::Alpaca, "PA123".to_string())
+    }
+
+    fn alpaca_order(status: &str, filled_qty: Volume, filled_avg_price: Option<Price>) -> AlpacaOrder {
+        AlpacaOrder {
+            id: "a1".to_string(),
+            client_order_id: "c1".to_string(),
+            symbol: "AAPL".to_string(),
+            status: status.to_string(),
+            filled_qty,
+            filled_avg_price,
+            submitted_at: None,
+            updated_at: None,
+            replaced_by: None,
+        }
+    }
+
+    #[test]
+    fn test_fractional_order_request() {
+        let order = Order::market_order("AAPL".to_string(), None, &account(), dec!(0.5), OrderSide::Buy, "Entry".to_string(), "1".to_string(), Utc::now(), None);
+        let body = order_request(&order, true).unwrap();
+        assert_eq!(body["qty"], "0.5");
+        assert_eq!(body["type"], "market");
+        assert_eq!(body["time_in_force"], "day");
+        assert!(order_request(&order, false).is_err());
+
+        let limit = Order::limit_order("AAPL".to_string(), None, &account(), dec!(0.25), OrderSide::Sell, "Exit".to_string(), "2".to_string(), Utc::now(), dec!(190.5), TimeInForce::GTC, None);
+        assert!(order_request(&limit, true).is_err());
+        let limit = Order::limit_order("AAPL".to_string(), None, &account(), dec!(2), OrderSide::Sell, "Exit".to_string(), "2".to_string(), Utc::now(), dec!(190.5), TimeInForce::GTC, None);
+        let body = order_request(&limit, false).unwrap();
+        assert_eq!(body["time_in_force"], "gtc");
+        assert_eq!(body["limit_price"], "190.5");
+    }
+
+    #[test]
+    fn test_order_events_from_cumulative_fills() {
+        let broker_order_id = "a1".to_string();
+        let mut tracked = TrackedOrder::new(broker_order_id.clone(), Utc::now());
+
+        let events = order_events(&broker_order_id, &mut tracked, &alpaca_order("new", dec!(0), None));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].status, BrokerOrderStatus::Accepted);
+
+        let events = order_events(&broker_order_id, &mut tracked, &alpaca_order("partially_filled", dec!(10), Some(dec!(100))));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].status, BrokerOrderStatus::PartiallyFilled);
+        assert_eq!(events[0].fill_quantity, dec!(10));
+
+        // 10 @ 100 then 10 @ 110 reports an average of 105 for 20
+        let events = order_events(&broker_order_id, &mut tracked, &alpaca_order("filled", dec!(20), Some(dec!(105))));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].status, BrokerOrderStatus::Filled);
+        assert_eq!(events[0].fill_quantity, dec!(10));
+        assert_eq!(events[0].fill_price, Some(dec!(110)));
+
+        // nothing changed
+        assert!(order_events(&broker_order_id, &mut tracked, &alpaca_order("filled", dec!(20), Some(dec!(105)))).is_empty());
+    }
+}
@@ -0,0 +1,6 @@
+pub mod api_client;
+pub mod settings;
+pub mod models;
+pub mod broker_adapter;
+pub mod vendor_api_response;
+pub mod stream;
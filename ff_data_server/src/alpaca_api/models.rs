@@ -0,0 +1,177 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde_derive::Deserialize;
+use ff_standard_lib::standardized_types::base_data::candle::Candle;
+use ff_standard_lib::standardized_types::base_data::tick::{Aggressor, Tick};
+use ff_standard_lib::standardized_types::enums::{Exchange, MarketType};
+use ff_standard_lib::standardized_types::resolution::Resolution;
+use ff_standard_lib::standardized_types::subscriptions::{CandleType, Symbol};
+
+// The subset of the Alpaca v2 trading and market data json we use, numbers which Alpaca sends as strings are parsed straight into decimals.
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AlpacaAccount {
+    pub id: String,
+    pub account_number: String,
+    pub currency: String,
+    pub cash: Decimal,
+    pub equity: Decimal,
+    pub last_equity: Decimal,
+    pub buying_power: Decimal,
+    pub multiplier: Decimal,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AlpacaAsset {
+    pub symbol: String,
+    pub exchange: String,
+    pub status: String,
+    pub tradable: bool,
+    #[serde(default)]
+    pub fractionable: bool,
+    #[serde(default)]
+    pub shortable: bool,
+}
+
+impl AlpacaAsset {
+    /// Returns None for exchanges we do not support, eg OTC.
+    pub fn exchange(&self) -> Option<Exchange> {
+        match self.exchange.as_str() {
+            "NASDAQ" => Some(Exchange::NASDAQ),
+            "NYSE" => Some(Exchange::NYSE),
+            "ARCA" | "NYSEARCA" => Some(Exchange::ARCA),
+            "AMEX" => Some(Exchange::AMEX),
+            "BATS" => Some(Exchange::BATS),
+            _ => None,
+        }
+    }
+
+    pub fn market_type(&self) -> Option<MarketType> {
+        self.exchange().map(MarketType::Equities)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AlpacaOrder {
+    pub id: String,
+    pub client_order_id: String,
+    pub symbol: String,
+    pub status: String,
+    pub filled_qty: Decimal,
+    pub filled_avg_price: Option<Decimal>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub replaced_by: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AlpacaPosition {
+    pub symbol: String,
+    /// Negative for short positions.
+    pub qty: Decimal,
+    pub side: String,
+    pub avg_entry_price: Decimal,
+    pub unrealized_pl: Decimal,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AlpacaBar {
+    /// The bar open time.
+    #[serde(rename = "t")]
+    pub time: DateTime<Utc>,
+    #[serde(rename = "o")]
+    pub open: Decimal,
+    #[serde(rename = "h")]
+    pub high: Decimal,
+    #[serde(rename = "l")]
+    pub low: Decimal,
+    #[serde(rename = "c")]
+    pub close: Decimal,
+    #[serde(rename = "v")]
+    pub volume: Decimal,
+}
+
+impl AlpacaBar {
+    pub fn to_candle(&self, symbol: Symbol, resolution: Resolution) -> Candle {
+        Candle {
+            symbol,
+            high: self.high,
+            low: self.low,
+            open: self.open,
+            close: self.close,
+            volume: self.volume,
+            ask_volume: Decimal::ZERO,
+            bid_volume: Decimal::ZERO,
+            range: self.high - self.low,
+            time: self.time.to_string(),
+            is_closed: true,
+            resolution,
+            candle_type: CandleType::CandleStick,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AlpacaTrade {
+    #[serde(rename = "t")]
+    pub time: DateTime<Utc>,
+    #[serde(rename = "p")]
+    pub price: Decimal,
+    #[serde(rename = "s")]
+    pub size: Decimal,
+}
+
+impl AlpacaTrade {
+    /// Equity trade prints do not include the aggressor side.
+    pub fn to_tick(&self, symbol: Symbol) -> Tick {
+        Tick::new(symbol, self.price, self.time.to_string(), self.size, Aggressor::None)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AlpacaBarsPage {
+    #[serde(default)]
+    pub bars: Option<Vec<AlpacaBar>>,
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AlpacaTradesPage {
+    #[serde(default)]
+    pub trades: Option<Vec<AlpacaTrade>>,
+    pub next_page_token: Option<String>,
+}
+
+/// A message from the market data websocket, each frame is a json array of these.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "T")]
+pub enum AlpacaStreamMessage {
+    #[serde(rename = "success")]
+    Success { msg: String },
+    #[serde(rename = "error")]
+    Error { code: i64, msg: String },
+    #[serde(rename = "subscription")]
+    Subscription {
+        #[serde(default)]
+        trades: Vec<String>,
+        #[serde(default)]
+        bars: Vec<String>,
+    },
+    #[serde(rename = "b")]
+    Bar {
+        #[serde(rename = "S")]
+        symbol: String,
+        #[serde(flatten)]
+        bar: AlpacaBar,
+    },
+    #[serde(rename = "t")]
+    Trade {
+        #[serde(rename = "S")]
+        symbol: String,
+        #[serde(flatten)]
+        trade: AlpacaTrade,
+    },
+    #[serde(other)]
+    Other,
+}
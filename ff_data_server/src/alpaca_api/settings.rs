@@ -0,0 +1,72 @@
+use std::fs;
+use std::path::PathBuf;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub enum AlpacaApiMode {
+    Live,
+    Paper,
+}
+
+/// The market data feed, IEX is free with every account, SIP is the consolidated feed of all US exchanges and requires a data subscription.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub enum AlpacaFeed {
+    Iex,
+    Sip,
+}
+
+impl AlpacaFeed {
+    pub fn as_param(&self) -> &'static str {
+        match self {
+            AlpacaFeed::Iex => "iex",
+            AlpacaFeed::Sip => "sip",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AlpacaSettings {
+    pub(crate) api_key_id: String,
+    pub(crate) api_secret_key: String,
+    pub(crate) mode: AlpacaApiMode,
+    pub(crate) feed: AlpacaFeed,
+}
+
+impl AlpacaSettings {
+    pub fn from_file(path: PathBuf) -> Option<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("Error reading alpaca settings file: {}", e);
+                return None;
+            }
+        };
+        Self::from_toml(&contents)
+    }
+
+    pub fn from_toml(contents: &str) -> Option<Self> {
+        let settings: AlpacaSettings = match toml::from_str(contents) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("Error parsing alpaca settings: {}", e);
+                return None;
+            }
+        };
+        Some(settings)
+    }
+
+    pub fn trading_endpoint(&self) -> &'static str {
+        match self.mode {
+            AlpacaApiMode::Live => "https://api.alpaca.markets",
+            AlpacaApiMode::Paper => "https://paper-api.alpaca.markets",
+        }
+    }
+
+    pub fn data_endpoint(&self) -> &'static str {
+        "https://data.alpaca.markets"
+    }
+
+    pub fn stream_endpoint(&self) -> String {
+        format!("wss://stream.data.alpaca.markets/v2/{}", self.feed.as_param())
+    }
+}
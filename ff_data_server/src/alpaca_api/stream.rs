@@ -0,0 +1,152 @@
+use std::sync::Arc;
+use std::time::Duration;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::protocol::Message;
+use ff_standard_lib::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use ff_standard_lib::standardized_types::datavendor_enum::DataVendor;
+use ff_standard_lib::standardized_types::resolution::Resolution;
+use ff_standard_lib::standardized_types::subscriptions::{Symbol, SymbolName};
+use crate::alpaca_api::api_client::AlpacaClient;
+use crate::alpaca_api::models::AlpacaStreamMessage;
+use crate::server_features::webhooks::notify_reconnected;
+use crate::subscribe_server_shutdown;
+
+pub enum AlpacaStreamCommand {
+    Subscribe {
+        trades: Vec<SymbolName>,
+        bars: Vec<SymbolName>,
+    },
+    Unsubscribe {
+        trades: Vec<SymbolName>,
+        bars: Vec<SymbolName>,
+    },
+}
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Live trades and minute bars from the Alpaca market data websocket.
+/// The free plan allows one market data connection per account, so one socket is shared by every subscription and only connected while there is a subscriber.
+/// After a disconnect the stream reconnects and subscribes to every broadcaster of the `AlpacaClient` again.
+pub(crate) async fn handle_alpaca_stream(client: Arc<AlpacaClient>, mut commands: mpsc::Receiver<AlpacaStreamCommand>) {
+    let mut shutdown = subscribe_server_shutdown();
+    let mut dropped = false;
+    loop {
+        if client.trade_broadcasters.is_empty() && client.bar_broadcasters.is_empty() {
+            // nothing is streamed, the next connection is not a reconnect
+            dropped = false;
+            tokio::select! {
+                command = commands.recv() => if command.is_none() {
+                    return;
+                },
+                _ = shutdown.recv() => return,
+            }
+            continue;
+        }
+
+        let (mut socket, _) = match connect_async(client.settings.stream_endpoint()).await {
+            Ok(socket) => socket,
+            Err(e) => {
+                eprintln!("Alpaca stream failed to connect: {}", e);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+
+        let auth = json!({"action": "auth", "key": client.settings.api_key_id, "secret": client.settings.api_secret_key});
+        let trades: Vec<SymbolName> = client.trade_broadcasters.iter().map(|entry| entry.key().clone()).collect();
+        let bars: Vec<SymbolName> = client.bar_broadcasters.iter().map(|entry| entry.key().clone()).collect();
+        let subscribe = json!({"action": "subscribe", "trades": trades, "bars": bars});
+        if socket.send(Message::Text(auth.to_string())).await.is_err() || socket.send(Message::Text(subscribe.to_string())).await.is_err() {
+            eprintln!("Alpaca stream failed to subscribe");
+            tokio::time::sleep(RECONNECT_DELAY).await;
+            continue;
+        }
+        println!("Alpaca: Stream connected");
+        if dropped {
+            notify_reconnected("Alpaca Stream".to_string());
+            dropped = false;
+        }
+
+        loop {
+            tokio::select! {
+                message = socket.next() => match message {
+                    Some(Ok(Message::Text(text))) => handle_stream_text(&client, &text),
+                    Some(Ok(Message::Ping(payload))) => {
+                        let _ = socket.send(Message::Pong(payload)).await;
+                    }
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Err(e)) => {
+                        eprintln!("Alpaca stream error: {}", e);
+                        break;
+                    }
+                    Some(Ok(_)) => {}
+                },
+                command = commands.recv() => {
+                    let request = match command {
+                        Some(AlpacaStreamCommand::Subscribe { trades, bars }) => json!({"action": "subscribe", "trades": trades, "bars": bars}),
+                        Some(AlpacaStreamCommand::Unsubscribe { trades, bars }) => json!({"action": "unsubscribe", "trades": trades, "bars": bars}),
+                        None => return,
+                    };
+                    if let Err(e) = socket.send(Message::Text(request.to_string())).await {
+                        eprintln!("Alpaca stream failed to send subscription: {}", e);
+                        break;
+                    }
+                },
+                _ = shutdown.recv() => {
+                    let _ = socket.close(None).await;
+                    return;
+                }
+            }
+        }
+        eprintln!("Alpaca: Stream disconnected, reconnecting");
+        dropped = true;
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+fn handle_stream_text(client: &AlpacaClient, text: &str) {
+    let messages: Vec<AlpacaStreamMessage> = match serde_json::from_str(text) {
+        Ok(messages) => messages,
+        Err(e) => {
+            eprintln!("Alpaca stream message could not be parsed: {}: {}", e, text);
+            return;
+        }
+    };
+    for message in messages {
+        match message {
+            AlpacaStreamMessage::Bar { symbol, bar } => {
+                let market_type = match client.market_type(&symbol) {
+                    Some(market_type) => market_type,
+                    None => continue,
+                };
+                let candle = bar.to_candle(Symbol::new(symbol.clone(), DataVendor::Alpaca, market_type), Resolution::Minutes(1));
+                let is_closed = match client.bar_broadcasters.get(&symbol) {
+                    Some(broadcaster) => broadcaster.send(BaseDataEnum::Candle(candle)).is_err(),
+                    None => false,
+                };
+                if is_closed {
+                    client.bar_broadcasters.remove(&symbol);
+                }
+            }
+            AlpacaStreamMessage::Trade { symbol, trade } => {
+                let market_type = match client.market_type(&symbol) {
+                    Some(market_type) => market_type,
+                    None => continue,
+                };
+                let tick = trade.to_tick(Symbol::new(symbol.clone(), DataVendor::Alpaca, market_type));
+                let is_closed = match client.trade_broadcasters.get(&symbol) {
+                    Some(broadcaster) => broadcaster.send(BaseDataEnum::Tick(tick)).is_err(),
+                    None => false,
+                };
+                if is_closed {
+                    client.trade_broadcasters.remove(&symbol);
+                }
+            }
+            AlpacaStreamMessage::Error { code, msg } => eprintln!("Alpaca stream error {}: {}", code, msg),
+            AlpacaStreamMessage::Success { .. } | AlpacaStreamMessage::Subscription { .. } | AlpacaStreamMessage::Other => {}
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use std::sync::atomic::Ordering;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc};
+use indicatif::{ProgressBar, ProgressStyle};
+use tokio::sync::broadcast;
+use ff_standard_lib::messages::data_server_messaging::{DataServerResponse, FundForgeError};
+use ff_standard_lib::product_maps::alpaca::maps::get_equity_symbol_info;
+use ff_standard_lib::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use ff_standard_lib::standardized_types::base_data::base_data_type::BaseDataType;
+use ff_standard_lib::standardized_types::base_data::traits::BaseData;
+use ff_standard_lib::standardized_types::datavendor_enum::DataVendor;
+use ff_standard_lib::standardized_types::enums::{Exchange, MarketType, PrimarySubscription, StrategyMode};
+use ff_standard_lib::standardized_types::resolution::Resolution;
+use ff_standard_lib::standardized_types::subscriptions::{DataSubscription, Symbol, SymbolName};
+use ff_standard_lib::StreamName;
+use crate::alpaca_api::api_client::{AlpacaClient, ALPACA_IS_CONNECTED};
+use crate::alpaca_api::models::{AlpacaBarsPage, AlpacaTradesPage};
+use crate::alpaca_api::stream::AlpacaStreamCommand;
+use crate::server_features::server_side_datavendor::VendorApiResponse;
+use crate::stream_tasks::{subscribe_stream, unsubscribe_stream};
+use crate::update_functions::DATA_STORAGE;
+
+const PAGE_LIMIT: u32 = 10000;
+
+#[async_trait]
+impl VendorApiResponse for AlpacaClient {
+    async fn symbols_response(&self, _mode: StrategyMode, _stream_name: StreamName, market_type: MarketType, _time: Option<DateTime<Utc>>, callback_id: u64) -> DataServerResponse {
+        let symbols: Vec<Symbol> = self.assets.iter()
+            .filter_map(|asset| asset.market_type().map(|asset_market| (asset.key().clone(), asset_market)))
+            .filter(|(_, asset_market)| *asset_market == market_type)
+            .map(|(symbol_name, asset_market)| Symbol::new(symbol_name, DataVendor::Alpaca, asset_market))
+            .collect();
+        DataServerResponse::Symbols {
+            callback_id,
+            symbols,
+            market_type,
+        }
+    }
+
+    async fn resolutions_response(&self, mode: StrategyMode, _stream_name: StreamName, market_type: MarketType, callback_id: u64) -> DataServerResponse {
+        let subscription_resolutions_types = match mode {
+            StrategyMode::Backtest => vec![PrimarySubscription::new(Resolution::Ticks(1), BaseDataType::Ticks), PrimarySubscription::new(Resolution::Minutes(1), BaseDataType::Candles), PrimarySubscription::new(Resolution::Hours(1), BaseDataType::Candles)],
+            StrategyMode::LivePaperTrading | StrategyMode::Live => vec![PrimarySubscription::new(Resolution::Ticks(1), BaseDataType::Ticks), PrimarySubscription::new(Resolution::Minutes(1), BaseDataType::Candles)],
+        };
+
+        DataServerResponse::Resolutions {
+            callback_id,
+            market_type,
+            subscription_resolutions_types,
+        }
+    }
+
+    async fn markets_response(&self, _mode: StrategyMode, _stream_name: StreamName, callback_id: u64) -> DataServerResponse {
+        DataServerResponse::Markets {
+            callback_id,
+            markets: vec![
+                MarketType::Equities(Exchange::NASDAQ),
+                MarketType::Equities(Exchange::NYSE),
+                MarketType::Equities(Exchange::ARCA),
+                MarketType::Equities(Exchange::AMEX),
+                MarketType::Equities(Exchange::BATS),
+            ],
+        }
+    }
+
+    async fn decimal_accuracy_response(&self, _mode: StrategyMode, _stream_name: StreamName, symbol_name: SymbolName, callback_id: u64) -> DataServerResponse {
+        if !self.assets.contains_key(&symbol_name) {
+            return DataServerResponse::Error {
+                callback_id,
+                error: FundForgeError::ClientSideErrorDebug(format!("Alpaca Symbol not found: {}", symbol_name)),
+            };
+        }
+        DataServerResponse::DecimalAccuracy {
+            callback_id,
+            accuracy: get_equity_symbol_info(&symbol_name).decimal_accuracy,
+        }
+    }
+
+    async fn tick_size_response(&self, _mode: StrategyMode, _stream_name: StreamName, symbol_name: SymbolName, callback_id: u64) -> DataServerResponse {
+        if !self.assets.contains_key(&symbol_name) {
+            return DataServerResponse::Error {
+                callback_id,
+                error: FundForgeError::ClientSideErrorDebug(format!("Alpaca Symbol not found: {}", symbol_name)),
+            };
+        }
+        DataServerResponse::TickSize {
+            callback_id,
+            tick_size: get_equity_symbol_info(&symbol_name).tick_size,
+        }
+    }
+
+    /// Live trades are streamed as ticks and live minute bars as candles, both share the single Alpaca websocket.
+    async fn data_feed_subscribe(&self, stream_name: StreamName, subscription: DataSubscription) -> DataServerResponse {
+        if !ALPACA_IS_CONNECTED.load(Ordering::SeqCst) {
+            return DataServerResponse::SubscribeResponse {
+                success: false,
+                subscription,
+                reason: Some("Alpaca is not connected".to_string()),
+            };
+        }
+        let broadcasters = match subscription.subscription_resolution_type() {
+            primary if primary == PrimarySubscription::new(Resolution::Ticks(1), BaseDataType::Ticks) => &self.trade_broadcasters,
+            primary if primary == PrimarySubscription::new(Resolution::Minutes(1), BaseDataType::Candles) => &self.bar_broadcasters,
+            _ => return DataServerResponse::SubscribeResponse {
+                success: false,
+                reason: Some(format!("Live Alpaca subscription does not support: {}, subscribe to ticks or 1 minute candles and use a consolidator", subscription)),
+                subscription,
+            }
+        };
+        if !self.assets.contains_key(&subscription.symbol.name) {
+            return DataServerResponse::SubscribeResponse {
+                success: false,
+                reason: Some(format!("Alpaca Symbol not found: {}", subscription.symbol.name)),
+                subscription,
+            };
+        }
+
+        let symbol_name = subscription.symbol.name.clone();
+        let receiver = match broadcasters.get(&symbol_name) {
+            Some(broadcaster) => Some(broadcaster.value().subscribe()),
+            None => None,
+        };
+        match receiver {
+            Some(receiver) => subscribe_stream(&stream_name, subscription.clone(), receiver).await,
+            None => {
+                let (sender, receiver) = broadcast::channel(500);
+                broadcasters.insert(symbol_name.clone(), sender);
+                subscribe_stream(&stream_name, subscription.clone(), receiver).await;
+                let command = match subscription.base_data_type {
+                    BaseDataType::Ticks => AlpacaStreamCommand::Subscribe { trades: vec![symbol_name], bars: vec![] },
+                    _ => AlpacaStreamCommand::Subscribe { trades: vec![], bars: vec![symbol_name] },
+                };
+                let _ = self.stream_command_sender.send(command).await;
+            }
+        }
+        DataServerResponse::SubscribeResponse {
+            success: true,
+            subscription,
+            reason: None,
+        }
+    }
+
+    async fn data_feed_unsubscribe(&self, stream_name: StreamName, subscription: DataSubscription) -> DataServerResponse {
+        unsubscribe_stream(&stream_name, &subscription).await;
+        let symbol_name = subscription.symbol.name.clone();
+        let broadcasters = match subscription.base_data_type {
+            BaseDataType::Ticks => &self.trade_broadcasters,
+            _ => &self.bar_broadcasters,
+        };
+        let no_receivers = broadcasters.get(&symbol_name).map(|broadcaster| broadcaster.receiver_count() == 0).unwrap_or(false);
+        if no_receivers {
+            broadcasters.remove(&symbol_name);
+            let command = match subscription.base_data_type {
+                BaseDataType::Ticks => AlpacaStreamCommand::Unsubscribe { trades: vec![symbol_name], bars: vec![] },
+                _ => AlpacaStreamCommand::Unsubscribe { trades: vec![], bars: vec![symbol_name] },
+            };
+            let _ = self.stream_command_sender.send(command).await;
+        }
+        DataServerResponse::UnSubscribeResponse {
+            success: true,
+            subscription,
+            reason: None,
+        }
+    }
+
+    async fn base_data_types_response(&self, _mode: StrategyMode, _stream_name: StreamName, callback_id: u64) -> DataServerResponse {
+        DataServerResponse::BaseDataTypes {
+            callback_id,
+            base_data_types: vec![BaseDataType::Ticks, BaseDataType::Candles],
+        }
+    }
+
+    /// The Alpaca connection is shared by all strategies, streams are unsubscribed individually.
+    async fn logout_command_vendors(&self, _stream_name: StreamName) {}
+
+    async fn session_market_hours_response(&self, _mode: StrategyMode, _stream_name: StreamName, symbol_name: SymbolName, _date_time: DateTime<Utc>, callback_id: u64) -> DataServerResponse {
+        DataServerResponse::Error {
+            callback_id,
+            error: FundForgeError::ServerErrorDebug(format!("Alpaca session market hours are not served by the data server, use the product_maps::alpaca presets for: {}", symbol_name)),
+        }
+    }
+
+    /// Downloads trades as ticks or 1 minute and 1 hour bars as candles from the feed in the Alpaca settings, the data is saved one day at a time.
+    async fn update_historical_data(
+        &self,
+        symbol: Symbol,
+        base_data_type: BaseDataType,
+        resolution: Resolution,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        _from_back: bool,
+        progress_bar: ProgressBar,
+    ) -> Result<(), FundForgeError> {
+        let data_storage = DATA_STORAGE.get().unwrap();
+        let (path, timeframe) = match (&base_data_type, &resolution) {
+            (BaseDataType::Ticks, Resolution::Ticks(1)) => ("trades", None),
+            (BaseDataType::Candles, Resolution::Minutes(1)) => ("bars", Some("1Min")),
+            (BaseDataType::Candles, Resolution::Hours(1)) => ("bars", Some("1Hour")),
+            _ => return Err(FundForgeError::ClientSideErrorDebug(format!("Alpaca does not support historical {} {}", resolution, base_data_type))),
+        };
+
+        let num_days = (to - from).num_days().max(1);
+        progress_bar.set_length(num_days as u64);
+        progress_bar.set_style(
+            ProgressStyle::default_bar()
+                .template("{prefix:.green} [{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {msg} ({eta})")
+                .unwrap()
+                .progress_chars("=>-")
+        );
+        progress_bar.set_message(format!("Downloading: ({}: {}) from: {}, to {}", resolution, base_data_type, from, to));
+
+        let mut base_url = format!(
+            "{}?start={}&end={}&limit={}&feed={}",
+            self.data_url(&format!("/v2/stocks/{}/{}", urlencoding::encode(&symbol.name), path)),
+            urlencoding::encode(&from.to_rfc3339()),
+            urlencoding::encode(&to.to_rfc3339()),
+            PAGE_LIMIT,
+            self.settings.feed.as_param(),
+        );
+        if let Some(timeframe) = timeframe {
+            base_url.push_str(&format!("&timeframe={}&adjustment=raw", timeframe));
+        }
+
+        let mut day_data: Vec<BaseDataEnum> = vec![];
+        let mut page_token: Option<String> = None;
+        loop {
+            let url = match &page_token {
+                Some(token) => format!("{}&page_token={}", base_url, urlencoding::encode(token)),
+                None => base_url.clone(),
+            };
+            let (data, next_page_token) = match base_data_type {
+                BaseDataType::Ticks => {
+                    let page: AlpacaTradesPage = self.get(&url).await?;
+                    let data: Vec<BaseDataEnum> = page.trades.unwrap_or_default().iter()
+                        .map(|trade| BaseDataEnum::Tick(trade.to_tick(symbol.clone())))
+                        .collect();
+                    (data, page.next_page_token)
+                }
+                _ => {
+                    let page: AlpacaBarsPage = self.get(&url).await?;
+                    let data: Vec<BaseDataEnum> = page.bars.unwrap_or_default().iter()
+                        .map(|bar| BaseDataEnum::Candle(bar.to_candle(symbol.clone(), resolution.clone())))
+                        .collect();
+                    (data, page.next_page_token)
+                }
+            };
+
+            for base_data in data {
+                let is_new_day = day_data.last().map(|last| last.time_utc().day() != base_data.time_utc().day()).unwrap_or(false);
+                if is_new_day {
+                    if let Err(e) = data_storage.save_data_bulk(std::mem::take(&mut day_data)).await {
+                        progress_bar.set_message(format!("Error saving data batch: {}", e));
+                        return Err(FundForgeError::ServerErrorDebug(format!("Error saving Alpaca data: {}", e)));
+                    }
+                    progress_bar.inc(1);
+                }
+                day_data.push(base_data);
+            }
+
+            page_token = match next_page_token {
+                Some(token) if !token.is_empty() => Some(token),
+                _ => break,
+            };
+        }
+
+        if !day_data.is_empty() {
+            if let Err(e) = data_storage.save_data_bulk(day_data).await {
+                progress_bar.set_message(format!("Error saving final data batch: {}", e));
+                return Err(FundForgeError::ServerErrorDebug(format!("Error saving Alpaca data: {}", e)));
+            }
+        }
+        progress_bar.finish_and_clear();
+        Ok(())
+    }
+}
@@ -6,7 +6,7 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicU16, AtomicUsize, Ordering};
 use tokio::net::{TcpListener, TcpStream};
 use tokio_rustls::server::TlsStream;
-use ff_standard_lib::messages::data_server_messaging::{DataServerRequest, DataServerResponse};
+use ff_standard_lib::messages::data_server_messaging::{DataServerRequest, DataServerResponse, FundForgeError};
 use ff_standard_lib::standardized_types::enums::StrategyMode;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use ff_standard_lib::standardized_types::bytes_trait::Bytes;
@@ -142,6 +142,7 @@ async fn handle_async_connection(mut tls_stream: TlsStream<TcpStream>, stream_na
     const LENGTH: usize = 4;
     let mut length_bytes = [0u8; LENGTH];
     let mut mode = StrategyMode::Backtest;
+    let mut data_only = false;
     while let Ok(_) = tls_stream.read_exact(&mut length_bytes).await {
         // Parse the length from the header
         let msg_length = u32::from_be_bytes(length_bytes) as usize;
@@ -159,6 +160,17 @@ async fn handle_async_connection(mut tls_stream: TlsStream<TcpStream>, stream_na
         // Parse the request from the message body
         let request = match DataServerRequest::from_bytes(&message_body) {
             Ok(req) => req,
+            // tell the strategy why it is refused, the response footer has our version so the strategy can report the mismatch
+            Err(FundForgeError::IncompatibleVersion(reason)) => {
+                eprintln!("Server: Refused incompatible connection: {}", reason);
+                let response = DataServerResponse::Error { callback_id: 0, error: FundForgeError::IncompatibleVersion(reason) };
+                let bytes = response.to_bytes();
+                let mut prefixed_msg = Vec::with_capacity(8 + bytes.len());
+                prefixed_msg.extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+                prefixed_msg.extend_from_slice(&bytes);
+                let _ = tls_stream.write_all(&prefixed_msg).await;
+                return;
+            }
             Err(_e) => {
                 //eprintln!("Server: Failed to parse request: {:?}", _e);
                 return;
@@ -171,6 +183,11 @@ async fn handle_async_connection(mut tls_stream: TlsStream<TcpStream>, stream_na
                 mode = registered_mode;
                 break;
             },
+            // a data only connection reads the historical store, it never registers a live stream
+            DataServerRequest::RegisterDataOnly => {
+                data_only = true;
+                break;
+            },
             _ => eprintln!("Server: Strategy Did not register a Strategy mode")
         }
     }
@@ -198,6 +215,7 @@ async fn handle_async_connection(mut tls_stream: TlsStream<TcpStream>, stream_na
 
     manage_async_requests(
         mode,
+        data_only,
         tls_stream,
         stream_name
     ).await;
@@ -1,5 +1,4 @@
-use std::path::PathBuf;
-use std::fs;
+use crate::server_features::secrets::load_credentials;
 use base64::engine::general_purpose;
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
 use tokio::net::TcpStream;
@@ -116,18 +115,10 @@ fn generate_signature(secret_key: &str, timestamp: &str) -> Result<String, Box<d
 }
 
 pub fn get_bitget_credentials() -> Option<BitGetCredentials> {
-    let file_path = PathBuf::from(get_data_folder())
-        .join("credentials")
-        .join("bitget_credentials")
-        .join("active")
-        .join("bitget_credentials.toml");
-
-    if file_path.is_file() && file_path.extension().and_then(|s| s.to_str()) == Some("toml") {
-        if let Ok(contents) = fs::read_to_string(file_path) {
-            // Parse the TOML content into BitGetCredentials struct
-            if let Ok(credentials) = toml::from_str::<BitGetCredentials>(&contents) {
-                return Some(credentials);
-            }
+    if let Some(contents) = load_credentials(get_data_folder(), "bitget_credentials", "bitget_credentials.toml") {
+        // Parse the TOML content into BitGetCredentials struct
+        if let Ok(credentials) = toml::from_str::<BitGetCredentials>(&contents) {
+            return Some(credentials);
         }
     }
 
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 use ff_standard_lib::messages::data_server_messaging::FundForgeError;
-use std::fs;
+use crate::server_features::secrets::load_credentials;
 use dashmap::DashMap;
 use databento::{HistoricalClient, LiveClient};
 use databento::dbn::{SType, Schema};
@@ -57,21 +57,8 @@ impl DataBentoClient {
     }
 
     pub fn get_api_key(options: &ServerLaunchOptions) -> Result<String, FundForgeError> {
-        let path = options.data_folder.clone()
-            .join("credentials")
-            .join("databento_credentials")
-            .join("active")
-            .join("databento_credentials.toml");
-
-        if !path.exists() {
-            return Err(FundForgeError::ServerErrorDebug(
-                "No Data Bento credentials toml".to_string(),
-            ));
-        }
-
-        // Read the toml file
-        let content = fs::read_to_string(&path).map_err(|e| {
-            FundForgeError::ServerErrorDebug(format!("Failed to read file: {}", e))
+        let content = load_credentials(&options.data_folder, "databento_credentials", "databento_credentials.toml").ok_or_else(|| {
+            FundForgeError::ServerErrorDebug("No Data Bento credentials toml".to_string())
         })?;
 
         // Parse the toml content
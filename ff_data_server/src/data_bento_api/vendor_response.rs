@@ -1,22 +1,26 @@
-use std::num::NonZeroU64;
 use async_trait::async_trait;
 use ff_standard_lib::messages::data_server_messaging::{DataServerResponse, FundForgeError};
 use crate::server_features::server_side_datavendor::VendorApiResponse;
+use ff_standard_lib::standardized_types::base_data::base_data_enum::BaseDataEnum;
 use ff_standard_lib::standardized_types::base_data::base_data_type::BaseDataType;
+use ff_standard_lib::standardized_types::base_data::depth::Depth;
+use ff_standard_lib::standardized_types::books::BookLevel;
 use ff_standard_lib::standardized_types::enums::{FuturesExchange, MarketType, PrimarySubscription, StrategyMode};
 use ff_standard_lib::standardized_types::resolution::Resolution;
 use ff_standard_lib::standardized_types::subscriptions::{DataSubscription, Symbol, SymbolName};
 use ff_standard_lib::StreamName;
-use chrono::{DateTime, Utc};
-use databento::dbn::{Schema, TradeMsg};
+use chrono::{DateTime, TimeZone, Utc};
+use databento::dbn::{Mbp10Msg, SType, Schema, UNDEF_PRICE};
 use databento::historical::symbology::{SymbologyClient};
 use databento::historical::timeseries::GetRangeParams;
-use indicatif::{ProgressBar};
-use time::macros::{datetime};
+use indicatif::{ProgressBar, ProgressStyle};
+use rust_decimal::Decimal;
+use time::OffsetDateTime;
 use ff_standard_lib::product_maps::rithmic::maps::{get_futures_exchange, get_futures_symbol_info};
 use ff_standard_lib::standardized_types::datavendor_enum::DataVendor;
 use crate::data_bento_api::api_client::DataBentoClient;
 use crate::rithmic_api::products::get_futures_symbols;
+use crate::update_functions::DATA_STORAGE;
 
 #[async_trait]
 impl VendorApiResponse for DataBentoClient {
@@ -89,7 +93,7 @@ impl VendorApiResponse for DataBentoClient {
     async fn resolutions_response(&self, _mode: StrategyMode, _stream_name: StreamName, market_type: MarketType, callback_id: u64) -> DataServerResponse {
         DataServerResponse::Resolutions {
             callback_id,
-            subscription_resolutions_types: vec![PrimarySubscription::new(Resolution::Ticks(1), BaseDataType::Ticks), PrimarySubscription::new(Resolution::Instant, BaseDataType::Quotes), PrimarySubscription::new(Resolution::Seconds(1), BaseDataType::Candles), PrimarySubscription::new(Resolution::Minutes(1), BaseDataType::Candles)],
+            subscription_resolutions_types: vec![PrimarySubscription::new(Resolution::Ticks(1), BaseDataType::Ticks), PrimarySubscription::new(Resolution::Instant, BaseDataType::Quotes), PrimarySubscription::new(Resolution::Seconds(1), BaseDataType::Candles), PrimarySubscription::new(Resolution::Minutes(1), BaseDataType::Candles), PrimarySubscription::new(Resolution::Instant, BaseDataType::Depth)],
             market_type
         }
     }
@@ -149,7 +153,7 @@ impl VendorApiResponse for DataBentoClient {
     async fn base_data_types_response(&self,  _mode: StrategyMode, _stream_name: StreamName, callback_id: u64) -> DataServerResponse {
         DataServerResponse::BaseDataTypes {
             callback_id,
-            base_data_types: vec![BaseDataType::Ticks, BaseDataType::Quotes, BaseDataType::Candles],
+            base_data_types: vec![BaseDataType::Ticks, BaseDataType::Quotes, BaseDataType::Candles, BaseDataType::Depth],
         }
     }
 
@@ -172,36 +176,95 @@ impl VendorApiResponse for DataBentoClient {
     Symbology provides methods that help find and resolve symbols across different symbology systems.
     Batch provides a means of submitting and querying for details of batch download requests.
     */
-    #[allow(unused)]
-    async fn update_historical_data(&self, symbol: Symbol, base_data_type: BaseDataType, resolution: Resolution, from: DateTime<Utc>, to: DateTime<Utc>, from_back: bool ,progress_bar: ProgressBar) -> Result<(), FundForgeError> {
-
-        let schema = match base_data_type {
-            BaseDataType::Quotes => {}
-            _ => return Err(FundForgeError::ClientSideErrorDebug(format!("{} Invalid Data Type for: {}", base_data_type, DataVendor::DataBento)))
-        };
+    async fn update_historical_data(&self, symbol: Symbol, base_data_type: BaseDataType, resolution: Resolution, from: DateTime<Utc>, to: DateTime<Utc>, _from_back: bool ,progress_bar: ProgressBar) -> Result<(), FundForgeError> {
+        if base_data_type != BaseDataType::Depth || resolution != Resolution::Instant {
+            progress_bar.finish_and_clear();
+            return Err(FundForgeError::ClientSideErrorDebug(format!("{} {} Invalid Data Type for: {}", resolution, base_data_type, DataVendor::DataBento)))
+        }
+        let data_storage = DATA_STORAGE.get().unwrap();
+        let to = to.min(Utc::now());
 
-        let ranges = GetRangeParams::builder()
-            .dataset("GLBX.MDP3")
-            .date_time_range((
-                datetime!(2023-06-06 00:00 UTC),
-                datetime!(2023-06-10 12:10 UTC),
-            ))
-            .symbols(symbol.name)
-            .schema(Schema::Trades)
-            .limit(NonZeroU64::new(1))
-            .build();
+        let num_hours = (to - from).num_hours().max(1);
+        progress_bar.set_length(num_hours as u64);
+        progress_bar.set_style(
+            ProgressStyle::default_bar()
+                .template("{prefix:.green} [{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {msg} ({eta})")
+                .unwrap()
+                .progress_chars("=>-")
+        );
+        progress_bar.set_message(format!("Downloading: ({}: {}) from: {}, to {}", resolution, base_data_type, from, to));
 
-        let mut client = self.historical_client.lock().await;
+        // MBP-10 is an update for every change to the top 10 levels, the windows are an hour so a busy session is not held in memory
+        let mut window_start = from;
+        while window_start < to {
+            let window_end = (window_start + chrono::Duration::hours(1)).min(to);
+            let params = GetRangeParams::builder()
+                .dataset(DATASET)
+                .date_time_range((to_offset_date_time(window_start)?, to_offset_date_time(window_end)?))
+                .symbols(format!("{}.c.0", symbol.name))
+                .stype_in(SType::Continuous)
+                .schema(Schema::Mbp10)
+                .build();
 
-        let mut decoder = client
-            .timeseries()
-            .get_range(&ranges).await.unwrap();
+            let mut client = self.historical_client.lock().await;
+            let mut decoder = client.timeseries().get_range(&params).await
+                .map_err(|e| FundForgeError::ServerErrorDebug(format!("Failed to request {} depth from {}: {}", symbol.name, DataVendor::DataBento, e)))?;
 
-        match decoder.decode_record::<TradeMsg>().await {
-            Ok(trade) => println!("{trade:#?}"),
-            Err(e) => eprintln!("Failed to decode: {}", e)
-        };
+            let mut data: Vec<BaseDataEnum> = vec![];
+            loop {
+                match decoder.decode_record::<Mbp10Msg>().await {
+                    Ok(Some(msg)) => {
+                        if let Some(depth) = mbp10_to_depth(&symbol, msg) {
+                            data.push(BaseDataEnum::Depth(depth));
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        progress_bar.set_message(format!("Failed to decode depth: {}", e));
+                        break;
+                    }
+                }
+            }
+            drop(client);
 
+            if let Err(e) = data_storage.save_data_bulk(data).await {
+                progress_bar.set_message(format!("Error saving data batch: {}", e));
+                return Err(FundForgeError::ServerErrorDebug(format!("Error saving Data Bento depth: {}", e)));
+            }
+            progress_bar.inc(1);
+            window_start = window_end;
+        }
+        progress_bar.finish_and_clear();
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+const DATASET: &str = "GLBX.MDP3";
+
+fn to_offset_date_time(time: DateTime<Utc>) -> Result<OffsetDateTime, FundForgeError> {
+    OffsetDateTime::from_unix_timestamp_nanos(time.timestamp_nanos_opt().unwrap_or_default() as i128)
+        .map_err(|e| FundForgeError::ServerErrorDebug(format!("Invalid time {}: {}", time, e)))
+}
+
+/// Converts the last record of a book event to a `Depth` snapshot, trades and the partial records of an event are skipped.
+fn mbp10_to_depth(symbol: &Symbol, msg: &Mbp10Msg) -> Option<Depth> {
+    if !msg.flags.is_last() || msg.action as u8 == b'T' {
+        return None;
+    }
+    let price = |px: i64| match px {
+        UNDEF_PRICE => None,
+        px => Some(Decimal::from_i128_with_scale(px as i128, 9).normalize()),
+    };
+    let mut bids = vec![];
+    let mut asks = vec![];
+    for level in msg.levels.iter() {
+        if let (Some(bid), true) = (price(level.bid_px), level.bid_sz > 0) {
+            bids.push(BookLevel::new(bids.len() as u16, bid, Decimal::from(level.bid_sz)));
+        }
+        if let (Some(ask), true) = (price(level.ask_px), level.ask_sz > 0) {
+            asks.push(BookLevel::new(asks.len() as u16, ask, Decimal::from(level.ask_sz)));
+        }
+    }
+    let time = Utc.timestamp_nanos(msg.hd.ts_event as i64);
+    Some(Depth::new(symbol.clone(), bids, asks, time.to_string()))
+}
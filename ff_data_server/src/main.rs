@@ -18,8 +18,22 @@ use ff_standard_lib::database::hybrid_storage::{HybridStorage};
 use ff_standard_lib::server_launch_options::ServerLaunchOptions;
 use crate::data_bento_api::api_client::{data_bento_init};
 use crate::oanda_api::api_client::{oanda_init};
+use crate::alpaca_api::api_client::alpaca_init;
+use crate::polygon_api::api_client::polygon_init;
 use crate::rithmic_api::api_client::{RithmicBrokerageClient, RITHMIC_CLIENTS};
 use crate::update_functions::run_update_schedule;
+use crate::server_features::secrets::init_secrets;
+use crate::server_features::secrets_cli::SecretsCli;
+use crate::server_features::fixtures_cli::FixturesCli;
+use crate::server_features::scan_cli::ScanCli;
+use crate::server_features::data_quality::init_data_quality;
+use crate::server_features::live_recorder::{init_live_recorder, stop_live_recorders};
+use crate::server_features::continuous_contracts::init_continuous_contracts;
+use crate::server_features::webhooks::init_webhooks;
+use crate::server_features::symbol_aliases::init_symbol_aliases;
+use crate::server_features::levels::init_levels;
+use crate::server_features::intents::init_intents;
+use crate::server_features::download_jobs::run_download_jobs;
 
 pub mod request_handlers;
 mod stream_listener;
@@ -32,6 +46,8 @@ pub mod server_side_datavendor;
 pub mod bitget_api;
 pub mod stream_tasks;
 pub mod oanda_api;
+pub mod alpaca_api;
+pub mod polygon_api;
 pub mod server_features;
 pub mod update_functions;
 use crate::update_functions::DATA_STORAGE;
@@ -68,13 +84,46 @@ pub fn get_data_folder() -> &'static PathBuf {
 
 #[tokio::main]
 async fn main() -> io::Result<()> {
+    // `ff_data_server secrets ...` manages the credentials store and exits without starting the server
+    if std::env::args().nth(1).as_deref() == Some("secrets") {
+        if let Err(e) = SecretsCli::from_iter(std::env::args().skip(1)).run() {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    // `ff_data_server fixtures ...` writes a small fixture dataset and exits without starting the server
+    if std::env::args().nth(1).as_deref() == Some("fixtures") {
+        if let Err(e) = FixturesCli::from_iter(std::env::args().skip(1)).run().await {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    // `ff_data_server scan ...` scans the historical store and exits without starting the server
+    if std::env::args().nth(1).as_deref() == Some("scan") {
+        if let Err(e) = ScanCli::from_iter(std::env::args().skip(1)).run().await {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let options = ServerLaunchOptions::from_args();
     let _ = DATA_FOLDER.set(options.data_folder.clone());
     println!("Data Folder: {:?}", get_data_folder());
+    init_secrets(&options.data_folder);
     let _ = DATA_STORAGE.set(Arc::new(HybridStorage::new(Duration::from_secs(450), options.clone(), options.max_downloads, options.update_seconds)));
 
     // Start the background task for cache management
     HybridStorage::start_cache_management(DATA_STORAGE.get().unwrap().clone());
+    init_data_quality(&options);
+    init_live_recorder(&options);
+    init_continuous_contracts(&options.data_folder);
+    init_webhooks(&options.data_folder);
+    init_symbol_aliases(&options.data_folder);
+    init_levels(&options.data_folder);
+    init_intents(&options.data_folder);
 
     let cert = Path::join(&options.ssl_auth_folder, "cert.pem");
     let key = Path::join(&options.ssl_auth_folder, "key.pem");
@@ -90,6 +139,8 @@ async fn main() -> io::Result<()> {
 
     RithmicBrokerageClient::init_rithmic_apis(options.clone()).await;
     oanda_init(options.clone()).await;
+    alpaca_init(options.clone()).await;
+    polygon_init(options.clone()).await;
     match data_bento_init(options.clone()).await {
         Ok(_) =>{
             eprintln!("Data Bento Initialized");
@@ -105,6 +156,7 @@ async fn main() -> io::Result<()> {
     sleep(Duration::from_secs(5)).await;
 
     run_update_schedule(DATA_STORAGE.get().unwrap().clone());
+    run_download_jobs(DATA_STORAGE.get().unwrap().clone());
 
     // Wait for Ctrl+C
     signal::ctrl_c().await.expect("Failed to listen for ctrl-c");
@@ -113,6 +165,7 @@ async fn main() -> io::Result<()> {
         Ok(_) => eprintln!("Shutdown Signal Sent"),
         Err(e) =>  eprintln!("Shutdown Signal Failed: {}", e),
     }
+    stop_live_recorders().await;
 
     // Perform logout
     logout_apis().await;
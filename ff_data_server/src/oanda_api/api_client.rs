@@ -4,6 +4,7 @@ use tokio::sync::{broadcast, OnceCell, Semaphore};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use crate::oanda_api::settings::{OandaApiMode, OandaSettings};
+use crate::server_features::secrets::load_credentials;
 use crate::rate_limiter::RateLimiter;
 use dashmap::DashMap;
 use lazy_static::lazy_static;
@@ -94,18 +95,15 @@ pub(crate) async fn oanda_init(options: ServerLaunchOptions) {
         OANDA_IS_CONNECTED.store(false, Ordering::SeqCst);
         return;
     }
-    let path = options.data_folder.clone()
-        .join("credentials")
-        .join("oanda_credentials")
-        .join("active")
-        .join("oanda_credentials.toml");
-
-    if !path.exists() {
-        OANDA_IS_CONNECTED.store(false, Ordering::SeqCst);
-        return;
-    }
+    let contents = match load_credentials(&options.data_folder, "oanda_credentials", "oanda_credentials.toml") {
+        Some(contents) => contents,
+        None => {
+            OANDA_IS_CONNECTED.store(false, Ordering::SeqCst);
+            return;
+        }
+    };
 
-    let settings: OandaSettings = match OandaSettings::from_file(path) {
+    let settings: OandaSettings = match OandaSettings::from_toml(&contents) {
         Some(s) => s,
         None => {
             OANDA_IS_CONNECTED.store(false, Ordering::SeqCst);
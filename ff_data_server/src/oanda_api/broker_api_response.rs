@@ -18,6 +18,7 @@ use crate::oanda_api::models::order::order_related;
 use crate::oanda_api::models::order::order_related::{OrderPositionFill};
 use crate::oanda_api::models::transaction_related::ClientExtensions;
 use crate::request_handlers::RESPONSE_SENDERS;
+use crate::server_features::webhooks::notify_webhooks;
 
 #[async_trait]
 impl BrokerApiResponse for OandaClient {
@@ -55,6 +56,12 @@ impl BrokerApiResponse for OandaClient {
                 symbol_info: info.clone(),
             }
         }
+        if let Some(symbol_info) = self.cross_symbol_info(&symbol_name) {
+            return DataServerResponse::SymbolInfo {
+                callback_id,
+                symbol_info,
+            }
+        }
         DataServerResponse::Error {
             callback_id,
             error: FundForgeError::ClientSideErrorDebug(format!("Symbol not found: {}", symbol_name)),
@@ -110,6 +117,10 @@ impl BrokerApiResponse for OandaClient {
                         tag: "Exit Short, Before Enter Long".to_string(),
                         symbol_code: order.symbol_code.clone(),
                         exchange: order.exchange.clone(),
+                        display_quantity: None,
+                        closed_market_policy: None,
+                        day_expiry: None,
+                        broker_order_id: None,
                     };
                     match self.other_orders(stream_name.clone(), mode, exit_long_order).await {
                         Ok(_) => {}
@@ -148,6 +159,10 @@ impl BrokerApiResponse for OandaClient {
                         tag: "Exit Long, Before Enter Short".to_string(),
                         symbol_code: order.symbol_code.clone(),
                         exchange: order.exchange.clone(),
+                        display_quantity: None,
+                        closed_market_policy: None,
+                        day_expiry: None,
+                        broker_order_id: None,
                     };
                     match self.other_orders(stream_name.clone(), mode, exit_long_order).await {
                         Ok(_) => {}
@@ -190,6 +205,18 @@ impl BrokerApiResponse for OandaClient {
             });
         };
 
+        if order.display_quantity.is_some() {
+            return Err(OrderUpdateEvent::OrderRejected {
+                account: order.account,
+                symbol_name: order.symbol_name.to_string(),
+                symbol_code: order.symbol_name,
+                order_id: order.id,
+                reason: "Oanda does not support display quantity (iceberg) orders".to_string(),
+                tag: order.tag,
+                time: Utc::now().to_string(),
+            });
+        }
+
         // Format quantity as string with sign
         let units = match order.side {
             OrderSide::Buy => order.quantity_open,
@@ -409,6 +436,7 @@ impl BrokerApiResponse for OandaClient {
                         match response.json::<serde_json::Value>().await {
                             Ok(create_response) => {
                                 // Send order accepted event
+                                order.broker_order_id = create_response["orderCreateTransaction"]["id"].as_str().map(|id| id.to_string());
                                 let accept_event = OrderUpdateEvent::OrderAccepted {
                                     order_id: order.id.clone(),
                                     account: order.account.clone(),
@@ -416,6 +444,7 @@ impl BrokerApiResponse for OandaClient {
                                     symbol_code: order.symbol_name.clone(),
                                     tag: order.tag.clone(),
                                     time: Utc::now().to_string(),
+                                    broker_order_id: order.broker_order_id.clone(),
                                 };
                                 order.state = OrderState::Accepted;
                                 //send to the stream receiver
@@ -442,6 +471,10 @@ impl BrokerApiResponse for OandaClient {
                                         Some(p) => p,
                                         None => return Ok(())
                                     };
+                                    let fill_id = fill["id"].as_str().map(|id| id.to_string());
+                                    let commission = fill["commission"]
+                                        .as_str()
+                                        .and_then(|c| Decimal::from_str(c).ok());
 
                                     let fill_event = match quantity == order.quantity_open {
                                         true => {
@@ -456,6 +489,9 @@ impl BrokerApiResponse for OandaClient {
                                                 side: order.side.clone(),
                                                 tag: order.tag.clone(),
                                                 time: Utc::now().to_string(),
+                                                fill_id,
+                                                liquidity: None,
+                                                commission,
                                             }
                                         },
                                         false => {
@@ -470,17 +506,22 @@ impl BrokerApiResponse for OandaClient {
                                                 side: order.side.clone(),
                                                 tag: order.tag.clone(),
                                                 time: Utc::now().to_string(),
+                                                fill_id,
+                                                liquidity: None,
+                                                commission,
                                             }
                                         },
                                     };
                                     order.quantity_open -= quantity;
                                     order.quantity_filled += quantity;
 
+                                    let fill_update = DataServerResponse::OrderUpdates {
+                                        event: fill_event,
+                                        time: Utc::now().to_string(),
+                                    };
+                                    notify_webhooks(&fill_update);
                                     if let Some(stream_receiver) = RESPONSE_SENDERS.get(&stream_name) {
-                                        stream_receiver.send(DataServerResponse::OrderUpdates {
-                                            event: fill_event,
-                                            time: Utc::now().to_string(),
-                                        }).await;
+                                        stream_receiver.send(fill_update).await;
                                     }
                                 }
 
@@ -610,6 +651,10 @@ impl BrokerApiResponse for OandaClient {
                     tag,
                     symbol_code: position.symbol_name.clone(),
                     exchange: None,
+                    display_quantity: None,
+                    closed_market_policy: None,
+                    day_expiry: None,
+                    broker_order_id: None,
                 };
                 let _ = self.other_orders(0, StrategyMode::Live, exit_order).await;
             }
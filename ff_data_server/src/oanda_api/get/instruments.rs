@@ -3,7 +3,8 @@ use ff_standard_lib::standardized_types::subscriptions::SymbolName;
 use rust_decimal::Decimal;
 use ff_standard_lib::standardized_types::enums::MarketType;
 use serde_json::Value;
-use ff_standard_lib::helpers::converters::fund_forge_formatted_symbol_name;
+use ff_standard_lib::standardized_types::datavendor_enum::DataVendor;
+use ff_standard_lib::standardized_types::symbol_aliases::canonical_symbol_name;
 use std::str::FromStr;
 use crate::oanda_api::api_client::OandaClient;
 use crate::oanda_api::models::account::enums::GuaranteedStopLossOrderMode;
@@ -129,7 +130,7 @@ impl OandaInstrument {
         };
 
         // Format the symbol
-        let symbol_name = fund_forge_formatted_symbol_name(&name);
+        let symbol_name = canonical_symbol_name(&DataVendor::Oanda, &name);
 
         let minimum_guaranteed_stop_loss_distance = instrument["minimumGuaranteedStopLossDistance"]
             .as_str()
@@ -14,6 +14,7 @@ use crate::oanda_api::get::accounts::account_changes::get_account_changes;
 use crate::oanda_api::get::positions::parse_oanda_position;
 use crate::oanda_api::models::order::order_related::OandaOrderState;
 use crate::request_handlers::RESPONSE_SENDERS;
+use crate::server_features::webhooks::notify_webhooks;
 
 pub fn handle_account_updates(client: Arc<OandaClient>) {
     tokio::spawn(async move {
@@ -91,6 +92,7 @@ pub fn handle_account_updates(client: Arc<OandaClient>) {
                                             time: Utc::now().to_string(),
                                             open_pnl,
                                         };
+                                        notify_webhooks(&message);
                                         for stream_name in RESPONSE_SENDERS.iter() {
                                             match stream_name.value().send(message.clone()).await {
                                                 Ok(_) => {}
@@ -117,6 +119,7 @@ pub fn handle_account_updates(client: Arc<OandaClient>) {
                                 cash_available: account_info.cash_available,
                                 cash_used: account_info.cash_used,
                             };
+                            notify_webhooks(&account_updates);
                             for stream_name in RESPONSE_SENDERS.iter() {
                                 match stream_name.value().send(account_updates.clone()).await {
                                     Ok(_) => {}
@@ -163,6 +166,9 @@ pub fn handle_account_updates(client: Arc<OandaClient>) {
                                                 quantity: Default::default(),
                                                 tag: order.tag.clone(),
                                                 time: Utc::now().to_string(),
+                                                fill_id: None,
+                                                liquidity: None,
+                                                commission: None,
                                             },
                                             time: Utc::now().to_string(),
                                         }
@@ -176,6 +182,7 @@ pub fn handle_account_updates(client: Arc<OandaClient>) {
                                                 order_id: order.key().clone(),
                                                 tag: order.tag.clone(),
                                                 time: Utc::now().to_string(),
+                                                broker_order_id: order.broker_order_id.clone(),
                                             },
                                             time: Utc::now().to_string(),
                                         }
@@ -197,6 +204,7 @@ pub fn handle_account_updates(client: Arc<OandaClient>) {
                                     }
                                     _ => continue
                                 };
+                                notify_webhooks(&message);
                                 for stream_name in RESPONSE_SENDERS.iter() {
                                     match stream_name.value().send(message.clone()).await {
                                         Ok(_) => {}
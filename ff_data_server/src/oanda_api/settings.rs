@@ -23,7 +23,11 @@ impl OandaSettings {
                 return None;
             }
         };
-        let settings: OandaSettings = match toml::from_str(&contents) {
+        Self::from_toml(&contents)
+    }
+
+    pub fn from_toml(contents: &str) -> Option<Self> {
+        let settings: OandaSettings = match toml::from_str(contents) {
             Ok(s) => s,
             Err(e) => {
                 eprintln!("Error parsing oanda settings: {}", e);
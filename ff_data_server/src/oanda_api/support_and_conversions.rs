@@ -4,6 +4,8 @@ use chrono::{DateTime, Duration, Utc};
 use structopt::StructOpt;
 use ff_standard_lib::standardized_types::resolution::Resolution;
 use ff_standard_lib::standardized_types::subscriptions::{CandleType, Symbol, SymbolName};
+use ff_standard_lib::standardized_types::datavendor_enum::DataVendor;
+use ff_standard_lib::standardized_types::symbol_aliases::{canonical_symbol_name, vendor_symbol_name};
 use serde_json::Value;
 use ff_standard_lib::standardized_types::base_data::quotebar::QuoteBar;
 use rust_decimal::Decimal;
@@ -70,14 +72,9 @@ impl FromStr for Interval {
     }
 }
 
+/// The Oanda instrument name of a symbol name.
 pub(crate) async fn oanda_clean_instrument(symbol_name: &SymbolName) -> SymbolName {
-    symbol_name
-        .replace("/", "_")
-        .replace(":", "_")
-        .replace("?", "_")
-        .replace("-", "_")
-        .replace(" ", "_")
-        .to_uppercase()
+    vendor_symbol_name(&DataVendor::Oanda, &canonical_symbol_name(&DataVendor::Oanda, symbol_name))
 }
 
 pub(crate) fn oanda_quotebar_from_candle(candle: &Value, symbol: Symbol, resolution: Resolution) -> Result<QuoteBar, Box<dyn std::error::Error + Send + Sync>> {
@@ -24,6 +24,9 @@ use crate::oanda_api::support_and_conversions::oanda_clean_instrument;
 use crate::oanda_api::support_and_conversions::{add_time_to_date, resolution_to_oanda_interval};
 use crate::stream_tasks::{subscribe_stream, unsubscribe_stream};
 use crate::update_functions::DATA_STORAGE;
+use crate::server_features::cross_rates::{cross_rate_receiver, start_cross_rate_feed};
+use ff_standard_lib::helpers::cross_rates::{cross_symbol_info, find_cross_legs};
+use ff_standard_lib::standardized_types::symbol_info::SymbolInfo;
 
 #[async_trait]
 impl VendorApiResponse for OandaClient {
@@ -68,6 +71,11 @@ impl VendorApiResponse for OandaClient {
                 callback_id,
                 accuracy: instrument.display_precision.clone(),
             }
+        } else if let Some(info) = self.cross_symbol_info(&symbol_name) {
+            DataServerResponse::DecimalAccuracy {
+                callback_id,
+                accuracy: info.decimal_accuracy,
+            }
         } else {
             DataServerResponse::Error {
                 callback_id,
@@ -79,7 +87,10 @@ impl VendorApiResponse for OandaClient {
     async fn tick_size_response(&self, _mode: StrategyMode, _stream_name: StreamName, symbol_name: SymbolName, callback_id: u64) -> DataServerResponse {
         let instrument = match self.instruments_map.get(&symbol_name) {
             Some(i) => i,
-            None => return DataServerResponse::Error{callback_id, error: FundForgeError::ClientSideErrorDebug(format!("Instrument not found: {}", symbol_name))},
+            None => return match self.cross_symbol_info(&symbol_name) {
+                Some(info) => DataServerResponse::TickSize{callback_id, tick_size: info.tick_size},
+                None => DataServerResponse::Error{callback_id, error: FundForgeError::ClientSideErrorDebug(format!("Instrument not found: {}", symbol_name))},
+            },
         };
 
         // Using string formatting with error handling
@@ -142,32 +153,24 @@ impl VendorApiResponse for OandaClient {
             };
         }
 
-        let mut is_subscribed = true;
-        if let Some(broadcaster) = self.quote_feed_broadcasters.get(&subscription.symbol.name) {
-            let receiver = broadcaster.value().subscribe();
-            subscribe_stream(&stream_name, subscription.clone(), receiver).await;
-        } else {
-            if self.quote_feed_broadcasters.len() == 20 {
-                return DataServerResponse::UnSubscribeResponse {
-                    success: false,
+        let receiver = match self.instruments_map.contains_key(&subscription.symbol.name) {
+            true => self.quote_receiver(&subscription.symbol.name).await,
+            false => self.triangulated_quote_receiver(&subscription).await,
+        };
+        match receiver {
+            Ok(receiver) => {
+                subscribe_stream(&stream_name, subscription.clone(), receiver).await;
+                DataServerResponse::SubscribeResponse {
+                    success: true,
                     subscription,
-                    reason: Some("Max number of subscriptions reached".to_string()),
-                };
+                    reason: None,
+                }
             }
-            let (sender, receiver) = broadcast::channel(500);
-            self.quote_feed_broadcasters.insert(subscription.symbol.name.clone(), sender);
-            subscribe_stream(&stream_name, subscription.clone(), receiver).await;
-            is_subscribed = false;
-        }
-
-        if !is_subscribed {
-            let keys: Vec<SymbolName> = self.quote_feed_broadcasters.iter().map(|entry| entry.key().clone()).collect();
-            let _ = self.quote_subscription_sender.send(keys).await;
-        }
-        DataServerResponse::SubscribeResponse {
-            success: true,
-            subscription,
-            reason: None,
+            Err(reason) => DataServerResponse::UnSubscribeResponse {
+                success: false,
+                subscription,
+                reason: Some(reason),
+            },
         }
     }
 
@@ -385,4 +388,39 @@ impl VendorApiResponse for OandaClient {
         progress_bar.finish_and_clear();
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+impl OandaClient {
+    /// The symbol info of a cross which is not an Oanda instrument but can be triangulated from two instruments.
+    pub(crate) fn cross_symbol_info(&self, symbol_name: &SymbolName) -> Option<SymbolInfo> {
+        find_cross_legs(symbol_name, |name| self.instruments_map.contains_key(name))?;
+        cross_symbol_info(symbol_name)
+    }
+
+    /// Returns a receiver for the live quotes of the instrument, the instrument is added to the price stream if it is not streaming.
+    async fn quote_receiver(&self, symbol_name: &SymbolName) -> Result<broadcast::Receiver<BaseDataEnum>, String> {
+        if let Some(broadcaster) = self.quote_feed_broadcasters.get(symbol_name) {
+            return Ok(broadcaster.value().subscribe());
+        }
+        if self.quote_feed_broadcasters.len() == 20 {
+            return Err("Max number of subscriptions reached".to_string());
+        }
+        let (sender, receiver) = broadcast::channel(500);
+        self.quote_feed_broadcasters.insert(symbol_name.clone(), sender);
+        let keys: Vec<SymbolName> = self.quote_feed_broadcasters.iter().map(|entry| entry.key().clone()).collect();
+        let _ = self.quote_subscription_sender.send(keys).await;
+        Ok(receiver)
+    }
+
+    /// Returns a receiver for a cross Oanda does not provide, triangulated from two instruments which share a pivot currency.
+    async fn triangulated_quote_receiver(&self, subscription: &DataSubscription) -> Result<broadcast::Receiver<BaseDataEnum>, String> {
+        if let Some(receiver) = cross_rate_receiver(subscription) {
+            return Ok(receiver);
+        }
+        let legs = find_cross_legs(&subscription.symbol.name, |name| self.instruments_map.contains_key(name))
+            .ok_or_else(|| format!("Oanda Symbol not found: {}", subscription.symbol.name))?;
+        let base_receiver = self.quote_receiver(&legs.base_leg).await?;
+        let quote_receiver = self.quote_receiver(&legs.quote_leg).await?;
+        Ok(start_cross_rate_feed(subscription.clone(), legs, base_receiver, quote_receiver))
+    }
+}
@@ -0,0 +1,183 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use reqwest::{Client, StatusCode};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::sync::OnceCell;
+use ff_standard_lib::messages::data_server_messaging::FundForgeError;
+use ff_standard_lib::server_launch_options::ServerLaunchOptions;
+use ff_standard_lib::standardized_types::enums::MarketType;
+use ff_standard_lib::standardized_types::subscriptions::SymbolName;
+use crate::polygon_api::models::{PolygonPage, PolygonTicker, PolygonTickerDetails};
+use crate::polygon_api::settings::PolygonSettings;
+use crate::server_features::secrets::load_credentials;
+use crate::polygon_api::tickers::{exchange_from_mic, from_polygon_ticker, market_type_from_name};
+use crate::rate_limiter::RateLimiter;
+
+lazy_static! {
+    pub static ref POLYGON_IS_CONNECTED: AtomicBool = AtomicBool::new(false);
+}
+
+pub(crate) static POLYGON_CLIENT: OnceCell<Arc<PolygonClient>> = OnceCell::const_new();
+pub fn get_polygon_client() -> Option<Arc<PolygonClient>> {
+    POLYGON_CLIENT.get().cloned()
+}
+
+/// Client for the Polygon.io rest api, Polygon is only used for historical data, it has no live feed or brokerage in fund forge.
+///
+/// # Properties
+/// * `rate_limiter` - `requests_per_minute` from the settings.
+/// * `market_types` - The market type for each stock, forex and index symbol, loaded from the reference data at start up, options are added as they are used.
+/// * `tickers_loaded` - True once the reference data has loaded, with the free plan this takes a few minutes.
+pub struct PolygonClient {
+    pub client: Client,
+    pub rate_limiter: Arc<RateLimiter>,
+    pub settings: PolygonSettings,
+    pub market_types: DashMap<SymbolName, MarketType>,
+    pub tickers_loaded: AtomicBool,
+}
+
+impl PolygonClient {
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.settings.endpoint(), path)
+    }
+
+    /// A 401 or 403 is returned as `FundForgeError::InvalidApiKey`, 403 is also returned when the plan does not include the data requested.
+    pub(crate) async fn get<T: DeserializeOwned>(&self, url: &str) -> Result<T, FundForgeError> {
+        self.rate_limiter.acquire().await;
+        let response = self.client.get(url).send().await
+            .map_err(|e| FundForgeError::ServerErrorDebug(format!("Polygon request failed: {}", e)))?;
+        let status = response.status();
+        let body = response.text().await
+            .map_err(|e| FundForgeError::ServerErrorDebug(format!("Polygon response could not be read: {}", e)))?;
+        if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+            eprintln!("Polygon {}: {}", status, body);
+            return Err(FundForgeError::InvalidApiKey);
+        }
+        if !status.is_success() {
+            let message = serde_json::from_str::<Value>(&body).ok()
+                .and_then(|json| json["message"].as_str().or(json["error"].as_str()).map(|message| message.to_string()))
+                .unwrap_or(body);
+            return Err(FundForgeError::ServerErrorDebug(format!("Polygon {}: {}", status, message)));
+        }
+        serde_json::from_str(&body).map_err(|e| FundForgeError::ServerErrorDebug(format!("Polygon response could not be parsed: {}", e)))
+    }
+
+    /// Loads the active tickers for a Polygon market, "stocks", "fx" or "indices", into `market_types`.
+    /// Stocks listed on exchanges we do not support are skipped, an index never replaces a stock with the same name.
+    pub(crate) async fn load_tickers(&self, market: &str) -> Result<(), FundForgeError> {
+        let mut url = self.url(&format!("/v3/reference/tickers?market={}&active=true&limit=1000", market));
+        loop {
+            let page: PolygonPage<PolygonTicker> = self.get(&url).await?;
+            for ticker in page.results {
+                let market_type = match ticker.market.as_str() {
+                    "fx" => MarketType::Forex,
+                    "indices" => {
+                        self.market_types.entry(from_polygon_ticker(&ticker.ticker)).or_insert(MarketType::Index);
+                        continue;
+                    }
+                    _ => match ticker.primary_exchange.as_deref().and_then(exchange_from_mic) {
+                        Some(exchange) => MarketType::Equities(exchange),
+                        None => continue,
+                    },
+                };
+                self.market_types.insert(from_polygon_ticker(&ticker.ticker), market_type);
+            }
+            url = match page.next_url {
+                Some(next_url) => next_url,
+                None => return Ok(()),
+            };
+        }
+    }
+
+    /// Options and forex are known from the symbol name, for stocks we look up the listing exchange once and cache it.
+    /// Indices are only known once the index tickers have loaded.
+    pub async fn market_type(&self, symbol_name: &SymbolName) -> Result<MarketType, FundForgeError> {
+        if let Some(market_type) = self.market_types.get(symbol_name) {
+            return Ok(*market_type);
+        }
+        let market_type = match market_type_from_name(symbol_name) {
+            Some(market_type) => market_type,
+            None => {
+                let details: PolygonTickerDetails = self.get(&self.url(&format!("/v3/reference/tickers/{}", urlencoding::encode(symbol_name)))).await?;
+                match details.results.primary_exchange.as_deref().and_then(exchange_from_mic) {
+                    Some(exchange) => MarketType::Equities(exchange),
+                    None => return Err(FundForgeError::ClientSideErrorDebug(format!("Polygon symbol is not listed on a supported exchange: {}", symbol_name))),
+                }
+            }
+        };
+        self.market_types.insert(symbol_name.clone(), market_type);
+        Ok(market_type)
+    }
+}
+
+pub(crate) async fn polygon_init(options: ServerLaunchOptions) {
+    if options.disable_polygon_server != 0 {
+        POLYGON_IS_CONNECTED.store(false, Ordering::SeqCst);
+        return;
+    }
+    let contents = match load_credentials(&options.data_folder, "polygon_credentials", "polygon_credentials.toml") {
+        Some(contents) => contents,
+        None => {
+            POLYGON_IS_CONNECTED.store(false, Ordering::SeqCst);
+            return;
+        }
+    };
+
+    let settings: PolygonSettings = match PolygonSettings::from_toml(&contents) {
+        Some(s) => s,
+        None => {
+            POLYGON_IS_CONNECTED.store(false, Ordering::SeqCst);
+            eprintln!("No polygon settings retrieved");
+            return;
+        }
+    };
+
+    let mut headers = HeaderMap::new();
+    match HeaderValue::from_str(&format!("Bearer {}", settings.api_key)) {
+        Ok(value) => headers.insert(AUTHORIZATION, value),
+        Err(_) => {
+            eprintln!("Polygon api key contains invalid characters");
+            return;
+        }
+    };
+    let client = match Client::builder()
+        .default_headers(headers)
+        .timeout(Duration::from_secs(60))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Polygon client failed to build: {}", e);
+            return;
+        }
+    };
+
+    let polygon_client = Arc::new(PolygonClient {
+        client,
+        rate_limiter: RateLimiter::new(settings.requests_per_minute.max(1), Duration::from_secs(60)),
+        settings,
+        market_types: DashMap::new(),
+        tickers_loaded: AtomicBool::new(false),
+    });
+
+    let _ = POLYGON_CLIENT.set(polygon_client.clone());
+    POLYGON_IS_CONNECTED.store(true, Ordering::SeqCst);
+    println!("Polygon: Client initialized");
+
+    // The reference data takes a few minutes with the free plan, so we load it in the background instead of delaying the server start up.
+    tokio::spawn(async move {
+        for market in ["stocks", "fx", "indices"] {
+            if let Err(e) = polygon_client.load_tickers(market).await {
+                eprintln!("Polygon failed to load {} tickers: {}", market, e);
+                return;
+            }
+        }
+        polygon_client.tickers_loaded.store(true, Ordering::SeqCst);
+        println!("Polygon: Loaded {} tickers", polygon_client.market_types.len());
+    });
+}
@@ -0,0 +1,5 @@
+pub mod api_client;
+pub mod settings;
+pub mod models;
+pub mod tickers;
+pub mod vendor_api_response;
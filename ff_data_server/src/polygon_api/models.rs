@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde_derive::Deserialize;
+use ff_standard_lib::standardized_types::base_data::candle::Candle;
+use ff_standard_lib::standardized_types::base_data::tick::{Aggressor, Tick};
+use ff_standard_lib::standardized_types::resolution::Resolution;
+use ff_standard_lib::standardized_types::subscriptions::{CandleType, Symbol};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PolygonAggregate {
+    /// The bar open time in unix milliseconds.
+    #[serde(rename = "t")]
+    pub time: i64,
+    #[serde(rename = "o")]
+    pub open: Decimal,
+    #[serde(rename = "h")]
+    pub high: Decimal,
+    #[serde(rename = "l")]
+    pub low: Decimal,
+    #[serde(rename = "c")]
+    pub close: Decimal,
+    /// Forex aggregates have no volume.
+    #[serde(rename = "v", default)]
+    pub volume: Decimal,
+}
+
+impl PolygonAggregate {
+    pub fn to_candle(&self, symbol: Symbol, resolution: Resolution) -> Option<Candle> {
+        let time = DateTime::<Utc>::from_timestamp_millis(self.time)?;
+        Some(Candle {
+            symbol,
+            high: self.high,
+            low: self.low,
+            open: self.open,
+            close: self.close,
+            volume: self.volume,
+            ask_volume: Decimal::ZERO,
+            bid_volume: Decimal::ZERO,
+            range: self.high - self.low,
+            time: time.to_string(),
+            is_closed: true,
+            resolution,
+            candle_type: CandleType::CandleStick,
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PolygonTrade {
+    /// The time the trade was reported to the SIP in unix nanoseconds.
+    pub sip_timestamp: i64,
+    pub price: Decimal,
+    pub size: Decimal,
+}
+
+impl PolygonTrade {
+    /// Trade prints do not include the aggressor side.
+    pub fn to_tick(&self, symbol: Symbol) -> Tick {
+        let time = DateTime::<Utc>::from_timestamp_nanos(self.sip_timestamp);
+        Tick::new(symbol, self.price, time.to_string(), self.size, Aggressor::None)
+    }
+}
+
+/// A page of a list endpoint, `next_url` already includes the query so it is requested as is.
+#[derive(Deserialize, Debug)]
+pub struct PolygonPage<T> {
+    #[serde(default = "Vec::new")]
+    pub results: Vec<T>,
+    pub next_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PolygonTicker {
+    pub ticker: String,
+    pub market: String,
+    /// The ISO 10383 MIC of the listing exchange, only set for stocks.
+    pub primary_exchange: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PolygonTickerDetails {
+    pub results: PolygonTicker,
+}
@@ -0,0 +1,44 @@
+use std::fs;
+use std::path::PathBuf;
+use serde_derive::{Deserialize, Serialize};
+
+fn default_requests_per_minute() -> usize {
+    5
+}
+
+/// # Properties
+/// * `requests_per_minute` - 5 for the free plan, the paid plans are unlimited but a limit is still useful to share bandwidth with the other vendors.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PolygonSettings {
+    pub(crate) api_key: String,
+    #[serde(default = "default_requests_per_minute")]
+    pub(crate) requests_per_minute: usize,
+}
+
+impl PolygonSettings {
+    pub fn from_file(path: PathBuf) -> Option<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("Error reading polygon settings file: {}", e);
+                return None;
+            }
+        };
+        Self::from_toml(&contents)
+    }
+
+    pub fn from_toml(contents: &str) -> Option<Self> {
+        let settings: PolygonSettings = match toml::from_str(contents) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("Error parsing polygon settings: {}", e);
+                return None;
+            }
+        };
+        Some(settings)
+    }
+
+    pub fn endpoint(&self) -> &'static str {
+        "https://api.polygon.io"
+    }
+}
@@ -0,0 +1,111 @@
+use ff_standard_lib::standardized_types::enums::{Exchange, MarketType};
+use ff_standard_lib::standardized_types::subscriptions::SymbolName;
+
+/// Returns true for names in the `OptionContract::symbol_name()` format `{underlying}{yymmdd}{C|P}{strike * 1000 padded to 8}`.
+pub fn is_option_symbol(symbol_name: &str) -> bool {
+    let bytes = symbol_name.as_bytes();
+    if bytes.len() < 16 {
+        return false;
+    }
+    let (underlying, contract) = bytes.split_at(bytes.len() - 15);
+    !underlying.is_empty()
+        && contract[..6].iter().all(u8::is_ascii_digit)
+        && (contract[6] == b'C' || contract[6] == b'P')
+        && contract[7..].iter().all(u8::is_ascii_digit)
+}
+
+/// Returns true for currency pairs in the `BASE-QUOTE` format, eg "EUR-USD".
+pub fn is_forex_symbol(symbol_name: &str) -> bool {
+    match symbol_name.split_once('-') {
+        Some((base, quote)) => {
+            base.len() == 3 && quote.len() == 3 && base.chars().chain(quote.chars()).all(|c| c.is_ascii_uppercase())
+        }
+        None => false,
+    }
+}
+
+/// The market type we can tell from the name alone, stocks need the listing exchange from `PolygonClient::market_type()`.
+pub fn market_type_from_name(symbol_name: &str) -> Option<MarketType> {
+    if is_option_symbol(symbol_name) {
+        Some(MarketType::Options)
+    } else if is_forex_symbol(symbol_name) {
+        Some(MarketType::Forex)
+    } else {
+        None
+    }
+}
+
+pub fn to_polygon_ticker(symbol_name: &SymbolName, market_type: &MarketType) -> Option<String> {
+    match market_type {
+        MarketType::Equities(_) => Some(symbol_name.clone()),
+        MarketType::Forex => Some(format!("C:{}", symbol_name.replace('-', ""))),
+        MarketType::Options => Some(format!("O:{}", symbol_name)),
+        MarketType::Index => Some(format!("I:{}", symbol_name)),
+        _ => None,
+    }
+}
+
+pub fn from_polygon_ticker(ticker: &str) -> SymbolName {
+    if let Some(pair) = ticker.strip_prefix("C:") {
+        if pair.len() == 6 {
+            return format!("{}-{}", &pair[..3], &pair[3..]);
+        }
+        return pair.to_string();
+    }
+    match ticker.strip_prefix("O:").or(ticker.strip_prefix("I:")) {
+        Some(name) => name.to_string(),
+        None => ticker.to_string(),
+    }
+}
+
+/// Maps the ISO 10383 MIC of the listing exchange, returns None for exchanges we do not support, eg OTC.
+pub fn exchange_from_mic(mic: &str) -> Option<Exchange> {
+    match mic {
+        "XNAS" => Some(Exchange::NASDAQ),
+        "XNYS" => Some(Exchange::NYSE),
+        "ARCX" => Some(Exchange::ARCA),
+        "XASE" => Some(Exchange::AMEX),
+        "BATS" => Some(Exchange::BATS),
+        _ => None,
+    }
+}
+
+pub fn exchange_to_mic(exchange: &Exchange) -> &'static str {
+    match exchange {
+        Exchange::NASDAQ => "XNAS",
+        Exchange::NYSE => "XNYS",
+        Exchange::ARCA => "ARCX",
+        Exchange::AMEX => "XASE",
+        Exchange::BATS => "BATS",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_market_type_from_name() {
+        assert_eq!(market_type_from_name("SPY241220C00500000"), Some(MarketType::Options));
+        assert_eq!(market_type_from_name("ES241220P05900000"), Some(MarketType::Options));
+        assert_eq!(market_type_from_name("EUR-USD"), Some(MarketType::Forex));
+        assert_eq!(market_type_from_name("AAPL"), None);
+        assert_eq!(market_type_from_name("BRK.B"), None);
+        assert_eq!(market_type_from_name("NAS100-USD"), None);
+    }
+
+    #[test]
+    fn test_polygon_ticker_round_trip() {
+        let names = [
+            ("AAPL".to_string(), MarketType::Equities(Exchange::NASDAQ), "AAPL"),
+            ("EUR-USD".to_string(), MarketType::Forex, "C:EURUSD"),
+            ("SPY241220C00500000".to_string(), MarketType::Options, "O:SPY241220C00500000"),
+            ("VIX".to_string(), MarketType::Index, "I:VIX"),
+        ];
+        for (name, market_type, ticker) in names {
+            assert_eq!(to_polygon_ticker(&name, &market_type).unwrap(), ticker);
+            assert_eq!(from_polygon_ticker(ticker), name);
+        }
+        assert_eq!(to_polygon_ticker(&"ES".to_string(), &MarketType::Futures(ff_standard_lib::standardized_types::enums::FuturesExchange::CME)), None);
+    }
+}
@@ -0,0 +1,262 @@
+use std::sync::atomic::Ordering;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc};
+use indicatif::{ProgressBar, ProgressStyle};
+use rust_decimal_macros::dec;
+use ff_standard_lib::messages::data_server_messaging::{DataServerResponse, FundForgeError};
+use ff_standard_lib::product_maps::alpaca::maps::get_equity_symbol_info;
+use ff_standard_lib::product_maps::oanda::maps::OANDA_SYMBOL_INFO;
+use ff_standard_lib::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use ff_standard_lib::standardized_types::base_data::base_data_type::BaseDataType;
+use ff_standard_lib::standardized_types::base_data::traits::BaseData;
+use ff_standard_lib::standardized_types::datavendor_enum::DataVendor;
+use ff_standard_lib::standardized_types::enums::{Exchange, MarketType, PrimarySubscription, StrategyMode};
+use ff_standard_lib::standardized_types::new_types::Price;
+use ff_standard_lib::standardized_types::resolution::Resolution;
+use ff_standard_lib::standardized_types::subscriptions::{DataSubscription, Symbol, SymbolName};
+use ff_standard_lib::StreamName;
+use crate::polygon_api::api_client::PolygonClient;
+use crate::polygon_api::models::{PolygonAggregate, PolygonPage, PolygonTrade};
+use crate::polygon_api::tickers::to_polygon_ticker;
+use crate::server_features::server_side_datavendor::VendorApiResponse;
+use crate::update_functions::DATA_STORAGE;
+
+const PAGE_LIMIT: u32 = 50000;
+
+impl PolygonClient {
+    /// Returns the tick size and decimal accuracy, forex uses the Oanda symbol info since the symbol names are the same.
+    async fn price_precision(&self, symbol_name: &SymbolName) -> Result<(Price, u32), FundForgeError> {
+        match self.market_type(symbol_name).await? {
+            MarketType::Forex => match OANDA_SYMBOL_INFO.get(symbol_name) {
+                Some(info) => Ok((info.tick_size, info.decimal_accuracy)),
+                None => match symbol_name.contains("JPY") {
+                    true => Ok((dec!(0.001), 3)),
+                    false => Ok((dec!(0.00001), 5)),
+                },
+            },
+            MarketType::Options | MarketType::Index => Ok((dec!(0.01), 2)),
+            _ => {
+                let info = get_equity_symbol_info(symbol_name);
+                Ok((info.tick_size, info.decimal_accuracy))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl VendorApiResponse for PolygonClient {
+    /// Options are not listed, use the option chain or build the names with `OptionContract::symbol_name()`.
+    async fn symbols_response(&self, _mode: StrategyMode, _stream_name: StreamName, market_type: MarketType, _time: Option<DateTime<Utc>>, callback_id: u64) -> DataServerResponse {
+        if !self.tickers_loaded.load(Ordering::SeqCst) {
+            return DataServerResponse::Error {
+                callback_id,
+                error: FundForgeError::ServerErrorDebug("Polygon tickers are still loading, try again in a few minutes".to_string()),
+            };
+        }
+        let symbols: Vec<Symbol> = self.market_types.iter()
+            .filter(|entry| *entry.value() == market_type)
+            .map(|entry| Symbol::new(entry.key().clone(), DataVendor::Polygon, market_type))
+            .collect();
+        DataServerResponse::Symbols {
+            callback_id,
+            symbols,
+            market_type,
+        }
+    }
+
+    /// Polygon is a historical vendor, the resolutions are the same in every mode.
+    async fn resolutions_response(&self, _mode: StrategyMode, _stream_name: StreamName, market_type: MarketType, callback_id: u64) -> DataServerResponse {
+        let mut subscription_resolutions_types = vec![
+            PrimarySubscription::new(Resolution::Seconds(1), BaseDataType::Candles),
+            PrimarySubscription::new(Resolution::Minutes(1), BaseDataType::Candles),
+            PrimarySubscription::new(Resolution::Hours(1), BaseDataType::Candles),
+        ];
+        if market_type != MarketType::Forex && market_type != MarketType::Index {
+            subscription_resolutions_types.insert(0, PrimarySubscription::new(Resolution::Ticks(1), BaseDataType::Ticks));
+        }
+
+        DataServerResponse::Resolutions {
+            callback_id,
+            market_type,
+            subscription_resolutions_types,
+        }
+    }
+
+    async fn markets_response(&self, _mode: StrategyMode, _stream_name: StreamName, callback_id: u64) -> DataServerResponse {
+        DataServerResponse::Markets {
+            callback_id,
+            markets: vec![
+                MarketType::Equities(Exchange::NASDAQ),
+                MarketType::Equities(Exchange::NYSE),
+                MarketType::Equities(Exchange::ARCA),
+                MarketType::Equities(Exchange::AMEX),
+                MarketType::Equities(Exchange::BATS),
+                MarketType::Options,
+                MarketType::Forex,
+                MarketType::Index,
+            ],
+        }
+    }
+
+    async fn decimal_accuracy_response(&self, _mode: StrategyMode, _stream_name: StreamName, symbol_name: SymbolName, callback_id: u64) -> DataServerResponse {
+        match self.price_precision(&symbol_name).await {
+            Ok((_, accuracy)) => DataServerResponse::DecimalAccuracy {
+                callback_id,
+                accuracy,
+            },
+            Err(error) => DataServerResponse::Error {
+                callback_id,
+                error,
+            },
+        }
+    }
+
+    async fn tick_size_response(&self, _mode: StrategyMode, _stream_name: StreamName, symbol_name: SymbolName, callback_id: u64) -> DataServerResponse {
+        match self.price_precision(&symbol_name).await {
+            Ok((tick_size, _)) => DataServerResponse::TickSize {
+                callback_id,
+                tick_size,
+            },
+            Err(error) => DataServerResponse::Error {
+                callback_id,
+                error,
+            },
+        }
+    }
+
+    async fn data_feed_subscribe(&self, _stream_name: StreamName, subscription: DataSubscription) -> DataServerResponse {
+        DataServerResponse::SubscribeResponse {
+            success: false,
+            subscription,
+            reason: Some("Polygon is a historical data vendor, it can not be used for live subscriptions".to_string()),
+        }
+    }
+
+    async fn data_feed_unsubscribe(&self, _stream_name: StreamName, subscription: DataSubscription) -> DataServerResponse {
+        DataServerResponse::UnSubscribeResponse {
+            success: false,
+            subscription,
+            reason: Some("Polygon is a historical data vendor, it has no live subscriptions".to_string()),
+        }
+    }
+
+    async fn base_data_types_response(&self, mode: StrategyMode, _stream_name: StreamName, callback_id: u64) -> DataServerResponse {
+        let base_data_types = match mode {
+            StrategyMode::Backtest => vec![BaseDataType::Ticks, BaseDataType::Candles],
+            StrategyMode::Live | StrategyMode::LivePaperTrading => vec![],
+        };
+        DataServerResponse::BaseDataTypes {
+            callback_id,
+            base_data_types,
+        }
+    }
+
+    /// There is nothing to clean up, Polygon has no live streams.
+    async fn logout_command_vendors(&self, _stream_name: StreamName) {}
+
+    async fn session_market_hours_response(&self, _mode: StrategyMode, _stream_name: StreamName, symbol_name: SymbolName, _date_time: DateTime<Utc>, callback_id: u64) -> DataServerResponse {
+        DataServerResponse::Error {
+            callback_id,
+            error: FundForgeError::ServerErrorDebug(format!("Polygon session market hours are not served by the data server: {}", symbol_name)),
+        }
+    }
+
+    /// Downloads trades as ticks for stocks and options, or 1 second, 1 minute and 1 hour aggregates as candles for every market, the data is saved one day at a time.
+    /// Prices are not adjusted for splits, so backtests fill at the prices that were traded.
+    async fn update_historical_data(
+        &self,
+        symbol: Symbol,
+        base_data_type: BaseDataType,
+        resolution: Resolution,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        _from_back: bool,
+        progress_bar: ProgressBar,
+    ) -> Result<(), FundForgeError> {
+        let data_storage = DATA_STORAGE.get().unwrap();
+        let ticker = match to_polygon_ticker(&symbol.name, &symbol.market_type) {
+            Some(ticker) => urlencoding::encode(&ticker).into_owned(),
+            None => return Err(FundForgeError::ClientSideErrorDebug(format!("Polygon does not support market type: {}", symbol.market_type))),
+        };
+        let to = to.min(Utc::now());
+        let first_url = match (&base_data_type, &resolution, &symbol.market_type) {
+            (BaseDataType::Ticks, Resolution::Ticks(1), MarketType::Equities(_) | MarketType::Options) => self.url(&format!(
+                "/v3/trades/{}?timestamp.gte={}&timestamp.lt={}&order=asc&sort=timestamp&limit={}",
+                ticker,
+                from.timestamp_nanos_opt().unwrap_or_default(),
+                to.timestamp_nanos_opt().unwrap_or_default(),
+                PAGE_LIMIT,
+            )),
+            (BaseDataType::Candles, Resolution::Seconds(1) | Resolution::Minutes(1) | Resolution::Hours(1), _) => {
+                let timespan = match resolution {
+                    Resolution::Seconds(_) => "second",
+                    Resolution::Minutes(_) => "minute",
+                    _ => "hour",
+                };
+                self.url(&format!(
+                    "/v2/aggs/ticker/{}/range/1/{}/{}/{}?adjusted=false&sort=asc&limit={}",
+                    ticker,
+                    timespan,
+                    from.timestamp_millis(),
+                    to.timestamp_millis(),
+                    PAGE_LIMIT,
+                ))
+            }
+            _ => return Err(FundForgeError::ClientSideErrorDebug(format!("Polygon does not support historical {} {} for {}", resolution, base_data_type, symbol.market_type))),
+        };
+
+        let num_days = (to - from).num_days().max(1);
+        progress_bar.set_length(num_days as u64);
+        progress_bar.set_style(
+            ProgressStyle::default_bar()
+                .template("{prefix:.green} [{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {msg} ({eta})")
+                .unwrap()
+                .progress_chars("=>-")
+        );
+        progress_bar.set_message(format!("Downloading: ({}: {}) from: {}, to {}", resolution, base_data_type, from, to));
+
+        let mut day_data: Vec<BaseDataEnum> = vec![];
+        let mut url = Some(first_url);
+        while let Some(page_url) = url {
+            let (data, next_url) = match base_data_type {
+                BaseDataType::Ticks => {
+                    let page: PolygonPage<PolygonTrade> = self.get(&page_url).await?;
+                    let data: Vec<BaseDataEnum> = page.results.iter()
+                        .map(|trade| BaseDataEnum::Tick(trade.to_tick(symbol.clone())))
+                        .collect();
+                    (data, page.next_url)
+                }
+                _ => {
+                    let page: PolygonPage<PolygonAggregate> = self.get(&page_url).await?;
+                    let data: Vec<BaseDataEnum> = page.results.iter()
+                        .filter_map(|aggregate| aggregate.to_candle(symbol.clone(), resolution.clone()))
+                        .map(BaseDataEnum::Candle)
+                        .collect();
+                    (data, page.next_url)
+                }
+            };
+
+            for base_data in data {
+                let is_new_day = day_data.last().map(|last| last.time_utc().day() != base_data.time_utc().day()).unwrap_or(false);
+                if is_new_day {
+                    if let Err(e) = data_storage.save_data_bulk(std::mem::take(&mut day_data)).await {
+                        progress_bar.set_message(format!("Error saving data batch: {}", e));
+                        return Err(FundForgeError::ServerErrorDebug(format!("Error saving Polygon data: {}", e)));
+                    }
+                    progress_bar.inc(1);
+                }
+                day_data.push(base_data);
+            }
+            url = next_url;
+        }
+
+        if !day_data.is_empty() {
+            if let Err(e) = data_storage.save_data_bulk(day_data).await {
+                progress_bar.set_message(format!("Error saving final data batch: {}", e));
+                return Err(FundForgeError::ServerErrorDebug(format!("Error saving Polygon data: {}", e)));
+            }
+        }
+        progress_bar.finish_and_clear();
+        Ok(())
+    }
+}
@@ -17,12 +17,23 @@ use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::time::timeout;
 use tokio_rustls::server::TlsStream;
 use crate::server_side_brokerage::{account_info_response, accounts_response, commission_info_response, live_market_order, symbol_info_response, symbol_names_response, live_enter_long, live_exit_long, live_exit_short, live_enter_short, other_orders, cancel_order, flatten_all_for, update_order, cancel_orders_on_account, exchange_rate_response, front_month_info_response};
-use crate::server_side_datavendor::{base_data_types_response, decimal_accuracy_response, markets_response, resolutions_response, symbols_response, tick_size_response};
+use crate::server_side_datavendor::{base_data_types_response, decimal_accuracy_response, markets_response, option_chain_response, resolutions_response, symbols_response, tick_size_response};
 use ff_standard_lib::standardized_types::enums::StrategyMode;
 use ff_standard_lib::standardized_types::orders::{Order, OrderRequest, OrderType, OrderUpdateEvent};
 use ff_standard_lib::StreamName;
 use crate::{stream_listener, subscribe_server_shutdown};
 use crate::stream_tasks::deregister_streamer;
+use crate::server_features::subscription_stats::report_subscription_stats;
+use crate::server_features::strategy_streams::{deregister_strategy_stream, register_strategy_stream, strategy_streams_response};
+use crate::server_features::indicator_streams::{deregister_indicator_streams, indicator_streams_response, publish_indicator_values, remove_indicator_stream, subscribe_indicator_stream_response, unsubscribe_indicator_stream};
+use crate::server_features::data_quality::data_quality_response;
+use crate::server_features::live_recorder::recent_live_data_response;
+use crate::server_features::scanner::scan_response;
+use crate::server_features::symbol_aliases::symbol_aliases_response;
+use crate::server_features::levels::{levels_response, remove_level_response, save_level_response};
+use crate::server_features::intents::{claim_intent_response, intents_response, remove_intent_response, save_intent_response};
+use crate::server_features::download_jobs::{cancel_download_response, download_jobs_response, enqueue_download_response};
+use crate::server_features::continuous_contracts::{is_continuous, underlying_symbol_name, update_continuous_contracts};
 use crate::update_functions::{pre_subscribe_updates, MULTIBAR};
 use crate::update_functions::DATA_STORAGE;
 
@@ -71,7 +82,8 @@ pub async fn compressed_file_response(
 
     if to_time.date_naive() >= Utc::now().date_naive() {
 
-        let tasks: Vec<_> = subscriptions.iter().map(|subscription| {
+        // continuous contracts are built from the contracts already in storage, they can not be updated from the vendor
+        let tasks: Vec<_> = subscriptions.iter().filter(|subscription| !is_continuous(subscription)).map(|subscription| {
             pre_subscribe_updates(
                 DATA_STORAGE.get().unwrap().clone(),
                 subscription.symbol.clone(),
@@ -83,6 +95,8 @@ pub async fn compressed_file_response(
         futures::future::join_all(tasks).await;
     }
 
+    update_continuous_contracts(&subscriptions).await;
+
     //todo i need to debug this and determine cause of time outs
     match data_storage.get_compressed_files_in_range(subscriptions, from_time, to_time).await {
         Ok(data) => {
@@ -104,6 +118,7 @@ pub async fn compressed_file_response(
 
 pub async fn manage_async_requests(
     strategy_mode: StrategyMode,
+    data_only: bool,
     stream: TlsStream<TcpStream>,
     stream_name: StreamName,
 ) {
@@ -121,7 +136,10 @@ pub async fn manage_async_requests(
         let mut receiver = read_half;
         let mut length_bytes = [0u8; LENGTH];
         let message_bar = MULTIBAR.add(ProgressBar::new_spinner());
-        let bright_green_prefix = format!("\x1b[92mStrategy Connected: {}\x1b[0m", stream_name);
+        let bright_green_prefix = match data_only {
+            true => format!("\x1b[92mData Client Connected: {}\x1b[0m", stream_name),
+            false => format!("\x1b[92mStrategy Connected: {}\x1b[0m", stream_name),
+        };
         // Set the colored prefix
         message_bar.set_prefix(bright_green_prefix);
         message_bar.set_style(
@@ -158,10 +176,23 @@ pub async fn manage_async_requests(
             let mode = strategy_mode.clone();
             let sender = response_sender.clone();
 
+            if data_only && !request.is_data_only_request() {
+                let error = FundForgeError::ServerErrorDebug(format!("Data only connections can only make data requests: {:?}", request));
+                let response = match request.callback_id() {
+                    Some(callback_id) => DataServerResponse::Error { callback_id, error },
+                    None => DataServerResponse::AsyncError { error },
+                };
+                if let Err(e) = sender.send(response).await {
+                    eprintln!("Failed to send refusal to data only connection: {}", e);
+                }
+                continue;
+            }
+
             tokio::spawn(async move {
                 // Handle the request and generate a response
                 match request {
                     DataServerRequest::Register(_) => {},
+                    DataServerRequest::RegisterDataOnly => {},
                     DataServerRequest::ExchangeRate {
                         callback_id,
                         from_currency,
@@ -181,7 +212,7 @@ pub async fn manage_async_requests(
                         callback_id,
                         symbol_name
                     } => handle_callback(
-                        || decimal_accuracy_response(data_vendor, mode, stream_name, symbol_name, callback_id),
+                        || decimal_accuracy_response(data_vendor, mode, stream_name, underlying_symbol_name(symbol_name), callback_id),
                         sender.clone(),
                         callback_id
                     ).await,
@@ -191,7 +222,7 @@ pub async fn manage_async_requests(
                         brokerage,
                         callback_id
                     } => handle_callback(
-                        || symbol_info_response(brokerage, mode, stream_name, symbol_name, callback_id),
+                        || symbol_info_response(brokerage, mode, stream_name, underlying_symbol_name(symbol_name), callback_id),
                         sender.clone(),
                         callback_id
                     ).await,
@@ -267,7 +298,7 @@ pub async fn manage_async_requests(
                         data_vendor,
                         symbol_name,
                     } => handle_callback(
-                        || tick_size_response(data_vendor, mode, stream_name, symbol_name, callback_id),
+                        || tick_size_response(data_vendor, mode, stream_name, underlying_symbol_name(symbol_name), callback_id),
                         sender.clone(),callback_id).await,
 
                     DataServerRequest::Accounts {
@@ -299,7 +330,7 @@ pub async fn manage_async_requests(
 
                     DataServerRequest::CommissionInfo { callback_id, brokerage, symbol_name } => {
                         handle_callback(
-                            || commission_info_response(mode, brokerage, symbol_name, stream_name, callback_id),
+                            || commission_info_response(mode, brokerage, underlying_symbol_name(symbol_name), stream_name, callback_id),
                             sender.clone(),callback_id).await
                     }
 
@@ -309,6 +340,12 @@ pub async fn manage_async_requests(
                             sender.clone(),callback_id).await
                     }
 
+                    DataServerRequest::OptionChain { callback_id, data_vendor, underlying, expiry } => {
+                        handle_callback(
+                            || option_chain_response(data_vendor, mode, stream_name, underlying, expiry, callback_id),
+                            sender.clone(),callback_id).await
+                    }
+
                     DataServerRequest::StreamRequest {
                         request
                     } => {
@@ -340,13 +377,105 @@ pub async fn manage_async_requests(
                     DataServerRequest::RegisterStreamer { .. } => {
                         //no need to handle here
                     }
+                    DataServerRequest::RegisterStrategyStream { info } => register_strategy_stream(stream_name, info),
+                    DataServerRequest::StrategyStreams { callback_id } => handle_callback(
+                        || strategy_streams_response(callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::DataQuality { callback_id, symbol_name } => handle_callback(
+                        || data_quality_response(symbol_name, callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::RecentLiveData { callback_id, subscriptions, from_time } => handle_callback(
+                        || recent_live_data_response(subscriptions, from_time, callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::Scan { callback_id, symbols, base_data_type, resolution, conditions, time } => handle_callback(
+                        || scan_response(symbols, base_data_type, resolution, conditions, time, callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::PublishIndicatorValues { values } => publish_indicator_values(stream_name, values).await,
+                    DataServerRequest::RemoveIndicatorStream { indicator_name } => remove_indicator_stream(stream_name, indicator_name),
+                    DataServerRequest::IndicatorStreams { callback_id } => handle_callback(
+                        || indicator_streams_response(callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::SubscribeIndicatorStream { callback_id, stream_name: publisher, indicator_name } => handle_callback(
+                        || subscribe_indicator_stream_response(stream_name, publisher, indicator_name, callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::UnsubscribeIndicatorStream { stream_name: publisher, indicator_name } => unsubscribe_indicator_stream(stream_name, publisher, indicator_name),
+                    DataServerRequest::SymbolAliases { callback_id } => handle_callback(
+                        || symbol_aliases_response(callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::Levels { callback_id, symbol_name } => handle_callback(
+                        || levels_response(symbol_name, callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::SaveLevel { callback_id, level } => handle_callback(
+                        || save_level_response(level, callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::RemoveLevel { callback_id, symbol_name, id } => handle_callback(
+                        || remove_level_response(symbol_name, id, callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::Intents { callback_id, symbol_name } => handle_callback(
+                        || intents_response(symbol_name, callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::SaveIntent { callback_id, intent } => handle_callback(
+                        || save_intent_response(intent, callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::RemoveIntent { callback_id, symbol_name, id } => handle_callback(
+                        || remove_intent_response(symbol_name, id, callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::ClaimIntent { callback_id, symbol_name, id, time } => handle_callback(
+                        || claim_intent_response(symbol_name, id, time, callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::EnqueueDownload { callback_id, request } => handle_callback(
+                        || enqueue_download_response(request, callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::DownloadJobs { callback_id } => handle_callback(
+                        || download_jobs_response(callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
+                    DataServerRequest::CancelDownload { callback_id, id } => handle_callback(
+                        || cancel_download_response(id, callback_id),
+                        sender.clone(),
+                        callback_id
+                    ).await,
                 }
             });
         }
         // Deregister when disconnected
         if strategy_mode != StrategyMode::Backtest {
             deregister_streamer(&stream_name).await;
+            report_subscription_stats(&stream_name);
         }
+        deregister_strategy_stream(&stream_name);
+        deregister_indicator_streams(&stream_name);
         write_task.abort();
         RESPONSE_SENDERS.remove(&stream_name);
         message_bar.finish_and_clear();
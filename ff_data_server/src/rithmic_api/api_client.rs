@@ -1,5 +1,4 @@
 use std::collections::BTreeMap;
-use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -45,6 +44,7 @@ use ff_standard_lib::standardized_types::base_data::base_data_type::BaseDataType
 use crate::{get_data_folder, rithmic_api, subscribe_server_shutdown};
 use crate::rithmic_api::client_base::api_base::{RithmicApiClient, TEMPLATE_VERSION};
 use crate::rithmic_api::client_base::credentials::RithmicCredentials;
+use crate::server_features::secrets::{credential_files, load_credentials};
 use crate::rithmic_api::client_base::rithmic_proto_objects::rti::request_login::SysInfraType;
 #[allow(unused_imports)]
 use crate::rithmic_api::client_base::rithmic_proto_objects::rti::{request_tick_bar_replay, RequestAccountList, RequestAccountRmsInfo, RequestFrontMonthContract, RequestHeartbeat, RequestNewOrder, RequestPnLPositionUpdates, RequestReferenceData, RequestShowOrders, RequestSubscribeForOrderUpdates, RequestTickBarReplay, RequestTimeBarReplay, RequestTradeRoutes};
@@ -219,27 +219,9 @@ impl RithmicBrokerageClient {
     }
 
 
+    /// The credential file names from both the secrets store and the active folder.
     pub fn get_rithmic_tomls() -> Vec<String> {
-        let mut toml_files = Vec::new();
-        let dir = PathBuf::from(get_data_folder())
-            .join("credentials")
-            .join("rithmic_credentials")
-            .join("active")
-            .to_string_lossy()
-            .into_owned();
-
-        for entry in fs::read_dir(dir).unwrap() {
-            let entry = entry.unwrap();
-            let path = entry.path();
-
-            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("toml") {
-                if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
-                    toml_files.push(file_name.to_string());
-                }
-            }
-        }
-
-        toml_files
+        credential_files(get_data_folder(), "rithmic_credentials")
     }
 
     pub async fn connect_plant(&self, system: SysInfraType) -> Result<SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>, FundForgeError> {
@@ -297,14 +279,9 @@ impl RithmicBrokerageClient {
         match broker {
             Brokerage::Rithmic(system) => {
                 let file = system.file_string();
-                let data_folder = match get_data_folder().to_str() {
-                    Some(s) => s.to_string(),
-                    None => String::from("Invalid UTF-8 sequence"), // Handle the error case as needed
-                };
-                let file_path = format!("{}/credentials/rithmic_credentials/active/{}", data_folder, file);
-                match RithmicCredentials::load_credentials_from_file(&file_path) {
-                    Ok(file) => Ok(file),
-                    Err(_e) => Err(FundForgeError::ServerErrorDebug(format!("Failed to load credentials for: {}", broker)))
+                match load_credentials(get_data_folder(), "rithmic_credentials", &file).map(|contents| toml::from_str::<RithmicCredentials>(&contents)) {
+                    Some(Ok(credentials)) => Ok(credentials),
+                    _ => Err(FundForgeError::ServerErrorDebug(format!("Failed to load credentials for: {}", broker)))
                 }
             },
             _ => Err(FundForgeError::ServerErrorDebug(format!("{} Incorrect brokerage to load rithmic credentials", broker)))
@@ -746,6 +723,11 @@ impl RithmicBrokerageClient {
             }
         };
 
+        // Rithmic has no display quantity on new order requests, we don't want to show the full size when the strategy expects an iceberg.
+        if order.display_quantity.is_some() {
+            return Err(Self::reject_order(&order, "Rithmic does not support display quantity (iceberg) orders".to_string()));
+        }
+
         let order_type = match order.order_type {
             OrderType::Limit => 1,
             OrderType::Market => 2,
@@ -979,6 +961,7 @@ impl RithmicBrokerageClient {
                         }else {
                             (num as i32, rithmic_api::client_base::rithmic_proto_objects::rti::request_time_bar_replay::BarType::MinuteBar.into())
                         }
+                    Resolution::Hours(num) => (num as i32 * 60, rithmic_api::client_base::rithmic_proto_objects::rti::request_time_bar_replay::BarType::MinuteBar.into()),
                     _ => return
                 };
                 //println!("Requesting candles for {} {} {} {} {} {}", symbol_name, exchange, window_start, window_end, num, res_type);
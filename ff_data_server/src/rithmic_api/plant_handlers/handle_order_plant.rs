@@ -24,6 +24,7 @@ use crate::rithmic_api::api_client::RithmicBrokerageClient;
 use crate::rithmic_api::client_base::rithmic_proto_objects::rti::request_login::SysInfraType;
 use crate::rithmic_api::plant_handlers::create_datetime;
 use crate::rithmic_api::plant_handlers::handler_loop::send_updates;
+use crate::server_features::webhooks::notify_webhooks;
 use ff_standard_lib::product_maps::rithmic::maps::find_base_symbol;
 
 type BasketId = String;
@@ -597,11 +598,13 @@ pub async fn match_order_plant_id(
                                 order_id: order_id.clone(),
                                 tag,
                                 time: time.clone(),
+                                broker_order_id: Some(basket_id.clone()),
                             };
                             send_order_update(client.brokerage, &order_id, event, time).await;
                             if let Some(account_map) = client.open_orders.get(&account_id) {
                                 if let Some(mut open_order) = account_map.get_mut(&order_id) {
                                     open_order.state = OrderState::Accepted;
+                                    open_order.broker_order_id = Some(basket_id.clone());
                                     open_order.symbol_code = symbol_code.clone();
                                 }
                             }
@@ -645,6 +648,9 @@ pub async fn match_order_plant_id(
                                         quantity: fill_quantity,
                                         tag,
                                         time: time.clone(),
+                                        fill_id: msg.fill_id.clone(),
+                                        liquidity: None,
+                                        commission: None,
                                     };
                                     send_order_update(client.brokerage, &order_id, event, time).await;
                                     if let Some(account_map) = client.open_orders.get(&account_id) {
@@ -661,6 +667,9 @@ pub async fn match_order_plant_id(
                                         quantity: fill_quantity,
                                         tag,
                                         time: time.clone(),
+                                        fill_id: msg.fill_id.clone(),
+                                        liquidity: None,
+                                        commission: None,
                                     };
                                     send_order_update(client.brokerage, &order_id, event, time).await;
                                 }
@@ -841,9 +850,10 @@ pub async fn match_order_plant_id(
 }
 
 async fn send_order_update(brokerage: Brokerage, order_id: &OrderId, event: OrderUpdateEvent, time: String) {
+    let order_event = DataServerResponse::OrderUpdates{event, time};
+    notify_webhooks(&order_event);
     if let Some(broker_map) = ID_TO_STREAM_NAME_MAP.get(&brokerage) {
         if let Some(stream_name) = broker_map.value().get(order_id) {
-            let order_event = DataServerResponse::OrderUpdates{event, time};
             if let Some(sender) = RESPONSE_SENDERS.get(&stream_name.value()) {
                 match sender.send(order_event).await {
                     Ok(_) => {}
@@ -12,6 +12,7 @@ use tokio::time::sleep;
 #[allow(unused_imports)]
 use ff_standard_lib::standardized_types::broker_enum::Brokerage;
 use crate::rithmic_api::api_client::{RithmicBrokerageClient};
+use crate::server_features::webhooks::notify_webhooks;
 use futures::stream::{SplitSink, SplitStream};
 use tokio::net::TcpStream;
 use tokio::sync::Mutex;
@@ -154,6 +155,7 @@ pub async fn shutdown_plant(
 }
 
 pub async fn send_updates(event: DataServerResponse) {
+    notify_webhooks(&event);
     for stream_name in RESPONSE_SENDERS.iter() {
         match stream_name.value().send(event.clone()).await {
             Ok(_) => {}
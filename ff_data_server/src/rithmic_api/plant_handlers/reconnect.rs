@@ -5,6 +5,7 @@ use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc, Weekday};
 use tokio::time::sleep;
 use crate::rithmic_api::api_client::RithmicBrokerageClient;
 use crate::rithmic_api::plant_handlers::handler_loop::handle_rithmic_responses;
+use crate::server_features::webhooks::notify_reconnected;
 
 pub(crate) async fn attempt_reconnect(
     client: Arc<RithmicBrokerageClient>,
@@ -33,7 +34,9 @@ pub(crate) async fn attempt_reconnect(
             match client.connect_plant(plant).await {
                 Ok(new_connection) => {
                     println!("Reconnected successfully");
+                    notify_reconnected(format!("Rithmic {} {:?}", client.system, plant));
                     handle_rithmic_responses(client.clone(), new_connection, plant);
+                    return;
                 }
                 Err(e) => {
                     eprintln!(
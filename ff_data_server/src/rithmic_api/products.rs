@@ -158,6 +158,9 @@ lazy_static! {
                     tick_size: dec!($tick_size),
                     decimal_accuracy: $accuracy,
                     base_currency: None,
+                    is_synthetic: false,
+                    is_reference: false,
+                    display_precision: None,
                 });
             };
         }
@@ -67,6 +67,9 @@ impl VendorApiResponse for RithmicBrokerageClient {
                 //todo, we need a better way to handle historical, primary data sources, we need a way to check for each symbol, which historical data is available.
                 // to achieve this this fn should be split, resolutions should also be determined by symbol name when historical data is requested, so we can check the data we actually have available.
                 resolutions.push(PrimarySubscription::new(Resolution::Ticks(1), BaseDataType::Ticks));
+                resolutions.push(PrimarySubscription::new(Resolution::Seconds(1), BaseDataType::Candles));
+                resolutions.push(PrimarySubscription::new(Resolution::Minutes(1), BaseDataType::Candles));
+                resolutions.push(PrimarySubscription::new(Resolution::Hours(1), BaseDataType::Candles));
             }
             StrategyMode::LivePaperTrading |  StrategyMode::Live => {
                 resolutions.push(PrimarySubscription::new(Resolution::Ticks(1), BaseDataType::Ticks));
@@ -363,6 +366,12 @@ impl VendorApiResponse for RithmicBrokerageClient {
             }
         };
 
+        // the history plant only replays trades, candles carry the bid and ask volume of the trades but there is no history of bid and ask prices for quote bars or quotes
+        if base_data_type != BaseDataType::Ticks && base_data_type != BaseDataType::Candles {
+            progress_bar.finish_and_clear();
+            return Err(FundForgeError::ClientSideErrorDebug(format!("{}, Rithmic history is only available for Ticks and Candles, not {}", symbol_name, base_data_type)))
+        }
+
         if base_data_type == BaseDataType::Ticks && resolution != Resolution::Ticks(1) {
             progress_bar.finish_and_clear();
             return Err(FundForgeError::ClientSideErrorDebug(format!("{}, Ticks data can only be requested with 1 tick resolution", symbol_name)))
@@ -0,0 +1,72 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
+use serde_derive::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use ff_standard_lib::database::continuous_contracts::{continuous_root, ContinuousContractSettings};
+use ff_standard_lib::standardized_types::subscriptions::{DataSubscription, SymbolName};
+use crate::update_functions::DATA_STORAGE;
+
+#[derive(Clone, Serialize, Deserialize, Default)]
+struct ContinuousContractsFile {
+    #[serde(default)]
+    contracts: Vec<ContinuousContractSettings>,
+}
+
+static SETTINGS: OnceCell<DashMap<SymbolName, ContinuousContractSettings>> = OnceCell::new();
+
+lazy_static! {
+    static ref BUILD_LOCKS: DashMap<DataSubscription, Arc<Mutex<()>>> = DashMap::new();
+}
+
+/// Reads the adjustment method and roll rule of each root symbol from `continuous_contracts.toml`, other symbols are back adjusted and roll on volume.
+pub fn init_continuous_contracts(data_folder: &Path) {
+    let settings = DashMap::new();
+    let path = data_folder.join("continuous_contracts.toml");
+    if let Ok(contents) = fs::read_to_string(&path) {
+        match toml::from_str::<ContinuousContractsFile>(&contents) {
+            Ok(file) => {
+                for contract in file.contracts {
+                    settings.insert(contract.symbol.clone(), contract);
+                }
+            }
+            Err(e) => eprintln!("Continuous Contracts: Error parsing {:?}: {}", path, e),
+        }
+    }
+    let _ = SETTINGS.set(settings);
+}
+
+/// Returns the root symbol for a continuous contract, otherwise the symbol name.
+pub(crate) fn underlying_symbol_name(symbol_name: SymbolName) -> SymbolName {
+    match continuous_root(&symbol_name) {
+        Some(root) => root.to_string(),
+        None => symbol_name,
+    }
+}
+
+pub(crate) fn is_continuous(subscription: &DataSubscription) -> bool {
+    continuous_root(&subscription.symbol.name).is_some()
+}
+
+/// Rebuilds the continuous contracts in the subscriptions if their contracts have newer data.
+pub(crate) async fn update_continuous_contracts(subscriptions: &[DataSubscription]) {
+    let Some(storage) = DATA_STORAGE.get() else {
+        return;
+    };
+    for subscription in subscriptions.iter().filter(|subscription| is_continuous(subscription)) {
+        let root = underlying_symbol_name(subscription.symbol.name.clone());
+        let settings = SETTINGS.get()
+            .and_then(|settings| settings.get(&root).map(|settings| settings.value().clone()))
+            .unwrap_or_else(|| ContinuousContractSettings::new(root));
+
+        // strategies requesting the same contract wait for a single rebuild
+        let lock = BUILD_LOCKS.entry(subscription.clone()).or_insert_with(|| Arc::new(Mutex::new(()))).clone();
+        let _guard = lock.lock().await;
+        if let Err(e) = storage.update_continuous_contract(&subscription.symbol, &subscription.resolution, &subscription.base_data_type, &settings).await {
+            eprintln!("Continuous Contracts: Failed to update {}: {}", subscription, e);
+        }
+    }
+}
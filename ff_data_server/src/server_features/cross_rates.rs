@@ -0,0 +1,76 @@
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use ff_standard_lib::helpers::cross_rates::{triangulate, CrossRateLegs};
+use ff_standard_lib::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use ff_standard_lib::standardized_types::base_data::quote::Quote;
+use ff_standard_lib::standardized_types::subscriptions::DataSubscription;
+use crate::subscribe_server_shutdown;
+
+lazy_static! {
+    static ref CROSS_RATE_BROADCASTERS: DashMap<DataSubscription, broadcast::Sender<BaseDataEnum>> = DashMap::new();
+}
+
+/// Returns a receiver for the cross if its feed is running.
+pub(crate) fn cross_rate_receiver(subscription: &DataSubscription) -> Option<broadcast::Receiver<BaseDataEnum>> {
+    CROSS_RATE_BROADCASTERS.get(subscription).map(|broadcaster| broadcaster.value().subscribe())
+}
+
+/// Starts the synthetic feed for the cross from the receivers of the two legs and returns a receiver for it.
+pub(crate) fn start_cross_rate_feed(
+    subscription: DataSubscription,
+    legs: CrossRateLegs,
+    base_receiver: broadcast::Receiver<BaseDataEnum>,
+    quote_receiver: broadcast::Receiver<BaseDataEnum>,
+) -> broadcast::Receiver<BaseDataEnum> {
+    let (sender, receiver) = broadcast::channel(500);
+    CROSS_RATE_BROADCASTERS.insert(subscription.clone(), sender.clone());
+    tokio::spawn(cross_rate_feed(subscription, legs, base_receiver, quote_receiver, sender));
+    receiver
+}
+
+async fn cross_rate_feed(
+    subscription: DataSubscription,
+    legs: CrossRateLegs,
+    mut base_receiver: broadcast::Receiver<BaseDataEnum>,
+    mut quote_receiver: broadcast::Receiver<BaseDataEnum>,
+    sender: broadcast::Sender<BaseDataEnum>,
+) {
+    let mut shutdown_receiver = subscribe_server_shutdown();
+    let mut base_quote: Option<Quote> = None;
+    let mut quote_quote: Option<Quote> = None;
+    let mut last_sent: Option<Quote> = None;
+
+    loop {
+        let data = tokio::select! {
+            data = base_receiver.recv() => data,
+            data = quote_receiver.recv() => data,
+            _ = shutdown_receiver.recv() => break,
+        };
+        match data {
+            Ok(BaseDataEnum::Quote(quote)) if quote.symbol.name == legs.base_leg => base_quote = Some(quote),
+            Ok(BaseDataEnum::Quote(quote)) if quote.symbol.name == legs.quote_leg => quote_quote = Some(quote),
+            Ok(_) | Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        }
+
+        let (Some(base_quote), Some(quote_quote)) = (&base_quote, &quote_quote) else {
+            continue;
+        };
+        let Some(cross) = triangulate(&legs, &subscription.symbol, base_quote, quote_quote) else {
+            continue;
+        };
+        if let Some(last) = &last_sent {
+            if last.bid == cross.bid && last.ask == cross.ask {
+                continue;
+            }
+        }
+        last_sent = Some(cross.clone());
+        if sender.send(BaseDataEnum::Quote(cross)).is_err() {
+            // no strategy is receiving the cross
+            break;
+        }
+    }
+    CROSS_RATE_BROADCASTERS.remove(&subscription);
+}
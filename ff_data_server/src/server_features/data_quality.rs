@@ -0,0 +1,81 @@
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use ff_standard_lib::messages::data_server_messaging::DataServerResponse;
+use ff_standard_lib::server_launch_options::ServerLaunchOptions;
+use ff_standard_lib::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use ff_standard_lib::standardized_types::base_data::data_quality::{DataQualityFilter, DataQualityMetrics, OutlierAction};
+use ff_standard_lib::standardized_types::subscriptions::{DataSubscription, SymbolName};
+use crate::update_functions::DATA_STORAGE;
+
+static LIVE_DATA_QUALITY: OnceCell<DataQualityFilter> = OnceCell::new();
+
+lazy_static! {
+    static ref CLEANERS: DashMap<DataSubscription, broadcast::Sender<BaseDataEnum>> = DashMap::new();
+}
+
+/// Cleans the live vendor streams with the `--outliers` option, each streamed subscription is cleaned once for every strategy and the live recorder.
+pub fn init_data_quality(options: &ServerLaunchOptions) {
+    if options.outlier_action == OutlierAction::Off {
+        return;
+    }
+    let _ = LIVE_DATA_QUALITY.set(DataQualityFilter::new(options.outlier_action, options.outlier_threshold, options.outlier_min_band, true));
+    println!(
+        "Data Quality: Outliers beyond {} deviations or {} of the price: {}",
+        options.outlier_threshold, options.outlier_min_band, options.outlier_action
+    );
+}
+
+/// Returns a receiver for the cleaned stream of the subscription, or the vendor receiver if cleaning is disabled.
+pub(crate) fn clean_live_data(subscription: &DataSubscription, receiver: broadcast::Receiver<BaseDataEnum>) -> broadcast::Receiver<BaseDataEnum> {
+    if LIVE_DATA_QUALITY.get().is_none() {
+        return receiver;
+    }
+    if let Some(sender) = CLEANERS.get(subscription) {
+        if sender.receiver_count() > 0 {
+            return sender.subscribe();
+        }
+    }
+    let (sender, cleaned_receiver) = broadcast::channel(500);
+    CLEANERS.insert(subscription.clone(), sender.clone());
+    tokio::spawn(cleaner(subscription.clone(), receiver, sender));
+    cleaned_receiver
+}
+
+async fn cleaner(subscription: DataSubscription, mut receiver: broadcast::Receiver<BaseDataEnum>, sender: broadcast::Sender<BaseDataEnum>) {
+    let Some(filter) = LIVE_DATA_QUALITY.get() else {
+        return;
+    };
+    loop {
+        match receiver.recv().await {
+            Ok(data) => {
+                let Some(data) = filter.clean(data) else {
+                    continue;
+                };
+                if sender.send(data).is_err() {
+                    break;
+                }
+            }
+            Err(RecvError::Lagged(count)) => eprintln!("Data Quality: {} skipped {} messages", subscription, count),
+            Err(RecvError::Closed) => break,
+        }
+    }
+    CLEANERS.remove_if(&subscription, |_, current| current.same_channel(&sender));
+}
+
+pub(crate) async fn data_quality_response(symbol_name: Option<SymbolName>, callback_id: u64) -> DataServerResponse {
+    let mut metrics: Vec<DataQualityMetrics> = vec![];
+    if let Some(storage) = DATA_STORAGE.get() {
+        metrics.extend(storage.data_quality.metrics(symbol_name.as_ref()));
+    }
+    if let Some(filter) = LIVE_DATA_QUALITY.get() {
+        metrics.extend(filter.metrics(symbol_name.as_ref()));
+    }
+    metrics.sort_by(|a, b| (&a.subscription, a.live).cmp(&(&b.subscription, b.live)));
+    DataServerResponse::DataQuality {
+        callback_id,
+        metrics,
+    }
+}
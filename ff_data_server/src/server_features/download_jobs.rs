@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use indicatif::ProgressBar;
+use lazy_static::lazy_static;
+use tokio::sync::{oneshot, Notify};
+use tokio::task;
+use tokio::task::AbortHandle;
+use ff_standard_lib::database::hybrid_storage::HybridStorage;
+use ff_standard_lib::messages::data_server_messaging::{DataServerResponse, FundForgeError};
+use ff_standard_lib::standardized_types::base_data::base_data_type::BaseDataType;
+use ff_standard_lib::standardized_types::download_jobs::{DownloadJob, DownloadJobRequest, DownloadJobStatus};
+use ff_standard_lib::standardized_types::resolution::Resolution;
+use ff_standard_lib::standardized_types::subscriptions::{Symbol, SymbolName};
+use crate::subscribe_server_shutdown;
+use crate::update_functions::{vendor_client, vendor_market_type, MULTIBAR};
+
+const FINISHED_JOBS_KEPT: usize = 200;
+
+/// The jobs of the server in the order they were queued.
+#[derive(Default)]
+struct JobQueue {
+    next_id: u64,
+    jobs: BTreeMap<u64, DownloadJob>,
+}
+
+impl JobQueue {
+    /// Validates the request and queues it.
+    fn enqueue(&mut self, request: DownloadJobRequest, time: DateTime<Utc>) -> Result<DownloadJob, FundForgeError> {
+        let from = parse_time(&request.from)?;
+        if let Some(to) = &request.to {
+            if parse_time(to)? <= from {
+                return Err(FundForgeError::ClientSideErrorDebug(format!("The download ends at {} before it starts at {}", to, request.from)));
+            }
+        }
+        self.next_id += 1;
+        let job = DownloadJob {
+            id: self.next_id,
+            request,
+            status: DownloadJobStatus::Queued,
+            queued_at: time.to_string(),
+            started_at: None,
+            finished_at: None,
+        };
+        self.jobs.insert(job.id, job.clone());
+        Ok(job)
+    }
+
+    /// The id of the next job to start, the highest priority then the first queued, skipping the jobs which are `busy`.
+    fn next_job(&self, busy: impl Fn(&DownloadJobRequest) -> bool) -> Option<u64> {
+        self.jobs.values()
+            .filter(|job| job.status == DownloadJobStatus::Queued && !busy(&job.request))
+            .max_by(|a, b| a.request.priority.cmp(&b.request.priority).then(b.id.cmp(&a.id)))
+            .map(|job| job.id)
+    }
+
+    fn start(&mut self, id: u64, time: DateTime<Utc>) -> Option<DownloadJob> {
+        let job = self.jobs.get_mut(&id)?;
+        job.status = DownloadJobStatus::Running;
+        job.started_at = Some(time.to_string());
+        Some(job.clone())
+    }
+
+    /// Finishes a running job, a job cancelled while it was running stays cancelled.
+    fn finish(&mut self, id: u64, status: DownloadJobStatus, time: DateTime<Utc>) {
+        if let Some(job) = self.jobs.get_mut(&id) {
+            if job.status == DownloadJobStatus::Running {
+                job.status = status;
+                job.finished_at = Some(time.to_string());
+            }
+        }
+        self.prune();
+    }
+
+    /// Cancels a queued or running job, returns the job and true if it was running.
+    fn cancel(&mut self, id: u64, time: DateTime<Utc>) -> Result<(DownloadJob, bool), FundForgeError> {
+        let job = self.jobs.get_mut(&id)
+            .ok_or_else(|| FundForgeError::ClientSideErrorDebug(format!("No download job with id {}", id)))?;
+        if job.status.is_finished() {
+            return Err(FundForgeError::ClientSideErrorDebug(format!("Download job {} has already finished: {}", id, job.status)));
+        }
+        let was_running = job.status == DownloadJobStatus::Running;
+        job.status = DownloadJobStatus::Cancelled;
+        job.finished_at = Some(time.to_string());
+        let job = job.clone();
+        self.prune();
+        Ok((job, was_running))
+    }
+
+    /// Removes the oldest finished jobs past the number kept.
+    fn prune(&mut self) {
+        let finished: Vec<u64> = self.jobs.values().filter(|job| job.status.is_finished()).map(|job| job.id).collect();
+        for id in finished.iter().take(finished.len().saturating_sub(FINISHED_JOBS_KEPT)) {
+            self.jobs.remove(id);
+        }
+    }
+
+    fn jobs(&self) -> Vec<DownloadJob> {
+        self.jobs.values().cloned().collect()
+    }
+}
+
+fn parse_time(time: &str) -> Result<DateTime<Utc>, FundForgeError> {
+    DateTime::<Utc>::from_str(time).map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Invalid download time {}: {}", time, e)))
+}
+
+fn download_key(request: &DownloadJobRequest) -> (SymbolName, BaseDataType, Resolution) {
+    (request.symbol_name.clone(), request.base_data_type, request.resolution)
+}
+
+lazy_static! {
+    static ref JOBS: Mutex<JobQueue> = Mutex::new(JobQueue::default());
+    static ref RUNNING: DashMap<u64, AbortHandle> = DashMap::new();
+    static ref JOBS_CHANGED: Notify = Notify::new();
+}
+
+/// Starts the queued jobs as download slots become free until the server shuts down.
+pub fn run_download_jobs(storage: Arc<HybridStorage>) {
+    let mut shutdown_receiver = subscribe_server_shutdown();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(5));
+        loop {
+            tokio::select! {
+                _ = shutdown_receiver.recv() => {
+                    for running in RUNNING.iter() {
+                        running.value().abort();
+                    }
+                    RUNNING.clear();
+                    break;
+                }
+                _ = JOBS_CHANGED.notified() => start_jobs(&storage),
+                _ = interval.tick() => start_jobs(&storage),
+            }
+        }
+    });
+}
+
+fn start_jobs(storage: &Arc<HybridStorage>) {
+    loop {
+        let Ok(permit) = storage.download_semaphore.clone().try_acquire_owned() else {
+            return;
+        };
+        let job = {
+            let mut jobs = JOBS.lock().unwrap();
+            let busy = |request: &DownloadJobRequest| storage.download_tasks.get(&download_key(request)).map_or(false, |task| !task.is_finished());
+            match jobs.next_job(busy) {
+                Some(id) => jobs.start(id, Utc::now()),
+                None => None,
+            }
+        };
+        let Some(job) = job else {
+            return;
+        };
+        let key = download_key(&job.request);
+        let download_tasks = storage.download_tasks.clone();
+        let task_key = key.clone();
+        // the job must be registered before it can finish and remove itself
+        let (registered, is_registered) = oneshot::channel::<()>();
+        let handle = task::spawn(async move {
+            let _ = is_registered.await;
+            let status = match download(&job).await {
+                Ok(()) => DownloadJobStatus::Completed,
+                Err(e) => DownloadJobStatus::Failed { reason: e.to_string() },
+            };
+            JOBS.lock().unwrap().finish(job.id, status, Utc::now());
+            RUNNING.remove(&job.id);
+            download_tasks.remove(&task_key);
+            drop(permit);
+            JOBS_CHANGED.notify_one();
+        });
+        RUNNING.insert(job.id, handle.abort_handle());
+        storage.download_tasks.insert(key, handle);
+        let _ = registered.send(());
+    }
+}
+
+async fn download(job: &DownloadJob) -> Result<(), FundForgeError> {
+    let request = &job.request;
+    let client = vendor_client(request.data_vendor)
+        .ok_or_else(|| FundForgeError::ServerErrorDebug(format!("{} is not connected", request.data_vendor)))?;
+    let market_type = vendor_market_type(request.data_vendor, &request.symbol_name).await
+        .ok_or_else(|| FundForgeError::ServerErrorDebug(format!("{} does not list {}", request.data_vendor, request.symbol_name)))?;
+    let from = parse_time(&request.from)?;
+    let to = match &request.to {
+        Some(to) => parse_time(to)?,
+        None => Utc::now(),
+    };
+    let symbol = Symbol::new(request.symbol_name.clone(), request.data_vendor, market_type);
+    let progress_bar = MULTIBAR.add(ProgressBar::new(1));
+    progress_bar.set_prefix(format!("Download Job {}: {}", job.id, request.symbol_name));
+    client.update_historical_data(symbol, request.base_data_type, request.resolution, from, to, false, progress_bar).await
+}
+
+pub(crate) async fn enqueue_download_response(request: DownloadJobRequest, callback_id: u64) -> DataServerResponse {
+    if vendor_client(request.data_vendor).is_none() {
+        let error = FundForgeError::ServerErrorDebug(format!("{} is not connected to the data server", request.data_vendor));
+        return DataServerResponse::Error { callback_id, error };
+    }
+    let result = JOBS.lock().unwrap().enqueue(request, Utc::now());
+    match result {
+        Ok(job) => {
+            JOBS_CHANGED.notify_one();
+            DataServerResponse::DownloadJob { callback_id, job }
+        }
+        Err(error) => DataServerResponse::Error { callback_id, error },
+    }
+}
+
+pub(crate) async fn download_jobs_response(callback_id: u64) -> DataServerResponse {
+    DataServerResponse::DownloadJobs { callback_id, jobs: JOBS.lock().unwrap().jobs() }
+}
+
+pub(crate) async fn cancel_download_response(id: u64, callback_id: u64) -> DataServerResponse {
+    let result = JOBS.lock().unwrap().cancel(id, Utc::now());
+    match result {
+        Ok((job, was_running)) => {
+            if was_running {
+                if let Some((_, running)) = RUNNING.remove(&id) {
+                    running.abort();
+                }
+            }
+            DataServerResponse::DownloadJob { callback_id, job }
+        }
+        Err(error) => DataServerResponse::Error { callback_id, error },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+    use ff_standard_lib::standardized_types::datavendor_enum::DataVendor;
+    use ff_standard_lib::standardized_types::download_jobs::DownloadPriority;
+
+    fn request(symbol_name: &str, priority: DownloadPriority) -> DownloadJobRequest {
+        DownloadJobRequest::new(symbol_name.to_string(), DataVendor::Rithmic, BaseDataType::Candles, Resolution::Minutes(1), Utc::now() - ChronoDuration::days(5))
+            .with_priority(priority)
+    }
+
+    #[test]
+    fn test_job_queue_order() {
+        let mut queue = JobQueue::default();
+        let time = Utc::now();
+        let first = queue.enqueue(request("MNQ", DownloadPriority::Normal), time).unwrap();
+        let second = queue.enqueue(request("MES", DownloadPriority::Normal), time).unwrap();
+        let urgent = queue.enqueue(request("NQ", DownloadPriority::High), time).unwrap();
+        queue.enqueue(request("ES", DownloadPriority::Low), time).unwrap();
+
+        assert_eq!(queue.next_job(|_| false), Some(urgent.id));
+        // a symbol already downloading is skipped
+        assert_eq!(queue.next_job(|request| request.symbol_name == "NQ"), Some(first.id));
+        queue.start(urgent.id, time);
+        queue.start(first.id, time);
+        assert_eq!(queue.next_job(|_| false), Some(second.id));
+
+        queue.finish(urgent.id, DownloadJobStatus::Completed, time);
+        let (cancelled, was_running) = queue.cancel(first.id, time).unwrap();
+        assert!(was_running);
+        assert_eq!(cancelled.status, DownloadJobStatus::Cancelled);
+        // a cancelled job stays cancelled when its aborted download finishes
+        queue.finish(first.id, DownloadJobStatus::Failed { reason: "aborted".to_string() }, time);
+        assert_eq!(queue.jobs[&first.id].status, DownloadJobStatus::Cancelled);
+        assert!(queue.cancel(urgent.id, time).is_err());
+        assert!(queue.cancel(99, time).is_err());
+    }
+
+    #[test]
+    fn test_invalid_requests_are_refused() {
+        let mut queue = JobQueue::default();
+        let mut invalid = request("MNQ", DownloadPriority::Normal);
+        invalid.from = "yesterday".to_string();
+        assert!(queue.enqueue(invalid, Utc::now()).is_err());
+        let backwards = request("MNQ", DownloadPriority::Normal).with_to(Utc::now() - ChronoDuration::days(10));
+        assert!(queue.enqueue(backwards, Utc::now()).is_err());
+        assert!(queue.jobs().is_empty());
+    }
+
+    #[test]
+    fn test_finished_jobs_are_pruned() {
+        let mut queue = JobQueue::default();
+        let time = Utc::now();
+        for _ in 0..FINISHED_JOBS_KEPT + 5 {
+            let job = queue.enqueue(request("MNQ", DownloadPriority::Normal), time).unwrap();
+            queue.cancel(job.id, time).unwrap();
+        }
+        let queued = queue.enqueue(request("MES", DownloadPriority::Normal), time).unwrap();
+        assert_eq!(queue.jobs().len(), FINISHED_JOBS_KEPT + 1);
+        assert_eq!(queue.jobs().first().map(|job| job.id), Some(6));
+        assert_eq!(queue.jobs().last().map(|job| job.id), Some(queued.id));
+    }
+}
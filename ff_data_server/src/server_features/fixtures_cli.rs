@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::NaiveDate;
+use structopt::StructOpt;
+use ff_standard_lib::database::fixtures::FixtureSymbol;
+use ff_standard_lib::database::hybrid_storage::HybridStorage;
+use ff_standard_lib::messages::data_server_messaging::FundForgeError;
+use ff_standard_lib::server_launch_options::ServerLaunchOptions;
+use ff_standard_lib::standardized_types::base_data::base_data_type::BaseDataType;
+use ff_standard_lib::standardized_types::datavendor_enum::DataVendor;
+use ff_standard_lib::standardized_types::enums::{Exchange, FuturesExchange, MarketType};
+use ff_standard_lib::standardized_types::resolution::Resolution;
+use ff_standard_lib::standardized_types::subscriptions::Symbol;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "fixtures", about = "Write a small fixture dataset for tests and examples")]
+pub struct FixturesCli {
+    /// The data folder the fixture is written to
+    #[structopt(long = "data_folder", default_value = "./fixture_data", parse(from_os_str))]
+    pub data_folder: PathBuf,
+
+    /// The first day of the fixture, eg 2024-06-03
+    #[structopt(long)]
+    pub start: NaiveDate,
+
+    /// The number of weekdays in the fixture
+    #[structopt(long, default_value = "3")]
+    pub days: u32,
+
+    #[structopt(subcommand)]
+    pub command: FixturesCommand,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum FixturesCommand {
+    /// Synthesize random walk quotes for EUR-USD and AUD-USD and ticks for EUR-USD, the data can be redistributed freely
+    Synthesize {
+        /// The same seed always writes the same data
+        #[structopt(long, default_value = "1")]
+        seed: u64,
+    },
+    /// Copy every nth data point of a symbol from another data folder, the data keeps the licence of its vendor
+    Subsample {
+        /// The data folder to copy from
+        #[structopt(long, parse(from_os_str))]
+        from: PathBuf,
+        #[structopt(long)]
+        vendor: DataVendor,
+        /// Forex, CFD, Crypto, ETF, Futures or Equities
+        #[structopt(long = "market_type", parse(try_from_str = parse_market_type))]
+        market_type: MarketType,
+        #[structopt(long)]
+        symbol: String,
+        #[structopt(long = "data_type", parse(try_from_str = BaseDataType::from_str))]
+        data_type: BaseDataType,
+        /// The stored resolution, ticks and quotes are stored as 1-I
+        #[structopt(long, default_value = "1-I")]
+        resolution: Resolution,
+        #[structopt(long, default_value = "1")]
+        every: usize,
+    },
+}
+
+/// The exchange is not part of the storage path, only the market type name is parsed.
+pub(crate) fn parse_market_type(market_type: &str) -> Result<MarketType, String> {
+    match market_type.to_lowercase().as_str() {
+        "forex" => Ok(MarketType::Forex),
+        "cfd" => Ok(MarketType::CFD),
+        "crypto" => Ok(MarketType::Crypto),
+        "etf" => Ok(MarketType::ETF),
+        "index" => Ok(MarketType::Index),
+        "futures" => Ok(MarketType::Futures(FuturesExchange::CME)),
+        "equities" => Ok(MarketType::Equities(Exchange::NASDAQ)),
+        _ => Err(format!("Unknown market type: {}", market_type)),
+    }
+}
+
+pub(crate) fn storage(data_folder: PathBuf) -> HybridStorage {
+    let options = ServerLaunchOptions {
+        data_folder,
+        ..Default::default()
+    };
+    HybridStorage::new(Duration::from_secs(450), options, 1, 900)
+}
+
+impl FixturesCli {
+    pub async fn run(self) -> Result<(), FundForgeError> {
+        let fixture = storage(self.data_folder.clone());
+        let saved = match self.command {
+            FixturesCommand::Synthesize { seed } => {
+                fixture.save_synthetic_fixture(&FixtureSymbol::defaults(), self.start, self.days, seed).await
+                    .map_err(|e| FundForgeError::ServerErrorDebug(format!("Failed to save the fixture: {}", e)))?
+            }
+            FixturesCommand::Subsample { from, vendor, market_type, symbol, data_type, resolution, every } => {
+                let source = storage(from);
+                let symbol = Symbol::new(symbol, vendor, market_type);
+                let saved = source.save_subsampled_fixture(&fixture, &symbol, &resolution, &data_type, self.start, self.days, every).await?;
+                if saved == 0 {
+                    return Err(FundForgeError::ClientSideErrorDebug(format!("No {} {} data found for {} from {}", resolution, data_type, symbol.name, self.start)));
+                }
+                saved
+            }
+        };
+        println!("Saved {} data points to {:?}", saved, self.data_folder.join("historical"));
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use ff_standard_lib::messages::data_server_messaging::{DataServerResponse, FundForgeError, IndicatorStreamInfo};
+use ff_standard_lib::strategies::indicators::indicators_trait::IndicatorName;
+use ff_standard_lib::strategies::indicators::indicator_values::IndicatorValues;
+use ff_standard_lib::StreamName;
+use crate::request_handlers::RESPONSE_SENDERS;
+use crate::server_features::strategy_streams::strategy_name;
+
+/// The most values held for each indicator stream.
+pub(crate) const INDICATOR_STREAM_HISTORY: usize = 5000;
+
+type IndicatorStreamKey = (StreamName, IndicatorName);
+
+lazy_static! {
+    static ref INDICATOR_STREAMS: DashMap<IndicatorStreamKey, VecDeque<IndicatorValues>> = DashMap::new();
+    static ref INDICATOR_SUBSCRIBERS: DashMap<IndicatorStreamKey, Vec<StreamName>> = DashMap::new();
+}
+
+/// Holds the values published by the strategy connection and sends them to the subscribers of each indicator.
+pub(crate) async fn publish_indicator_values(stream_name: StreamName, values: Vec<IndicatorValues>) {
+    let mut updates: Vec<(IndicatorStreamKey, Vec<IndicatorValues>)> = vec![];
+    for value in values {
+        let key = (stream_name, value.name.clone());
+        {
+            let mut history = INDICATOR_STREAMS.entry(key.clone()).or_default();
+            // requests are handled concurrently, a batch can arrive before the batch published ahead of it
+            let position = history.iter().rposition(|held| held.time_utc() <= value.time_utc()).map_or(0, |index| index + 1);
+            history.insert(position, value.clone());
+            while history.len() > INDICATOR_STREAM_HISTORY {
+                history.pop_front();
+            }
+        }
+        match updates.iter_mut().find(|(update_key, _)| update_key == &key) {
+            Some((_, update)) => update.push(value),
+            None => updates.push((key, vec![value])),
+        }
+    }
+    for (key, values) in updates {
+        let subscribers = match INDICATOR_SUBSCRIBERS.get(&key) {
+            Some(subscribers) => subscribers.value().clone(),
+            None => continue,
+        };
+        for subscriber in subscribers {
+            let sender = match RESPONSE_SENDERS.get(&subscriber) {
+                Some(sender) => sender.value().clone(),
+                None => continue,
+            };
+            let update = DataServerResponse::IndicatorStreamUpdate { stream_name, values: values.clone() };
+            if let Err(e) = sender.send(update).await {
+                eprintln!("Failed to send indicator update to {}: {}", subscriber, e);
+            }
+        }
+    }
+}
+
+pub(crate) fn remove_indicator_stream(stream_name: StreamName, indicator_name: IndicatorName) {
+    let key = (stream_name, indicator_name);
+    INDICATOR_STREAMS.remove(&key);
+    INDICATOR_SUBSCRIBERS.remove(&key);
+}
+
+pub(crate) async fn indicator_streams_response(callback_id: u64) -> DataServerResponse {
+    let mut streams: Vec<IndicatorStreamInfo> = INDICATOR_STREAMS.iter()
+        .filter_map(|entry| {
+            let (stream_name, indicator_name) = entry.key();
+            let last = entry.value().back()?;
+            Some(IndicatorStreamInfo {
+                stream_name: *stream_name,
+                strategy_name: strategy_name(stream_name),
+                indicator_name: indicator_name.clone(),
+                subscription: last.subscription.clone(),
+                values: entry.value().len() as u64,
+            })
+        })
+        .collect();
+    streams.sort_by(|a, b| (a.stream_name, &a.indicator_name).cmp(&(b.stream_name, &b.indicator_name)));
+    DataServerResponse::IndicatorStreams {
+        callback_id,
+        streams,
+    }
+}
+
+/// Subscribes the connection to the indicator stream and returns the values held for it.
+pub(crate) async fn subscribe_indicator_stream_response(subscriber: StreamName, stream_name: StreamName, indicator_name: IndicatorName, callback_id: u64) -> DataServerResponse {
+    let key = (stream_name, indicator_name);
+    let values: Vec<IndicatorValues> = match INDICATOR_STREAMS.get(&key) {
+        Some(history) => history.value().iter().cloned().collect(),
+        None => return DataServerResponse::Error {
+            callback_id,
+            error: FundForgeError::ServerErrorDebug(format!("No indicator stream: {} from connection {}", key.1, stream_name)),
+        },
+    };
+    let mut subscribers = INDICATOR_SUBSCRIBERS.entry(key).or_default();
+    if !subscribers.contains(&subscriber) {
+        subscribers.push(subscriber);
+    }
+    DataServerResponse::IndicatorHistory {
+        callback_id,
+        values,
+    }
+}
+
+pub(crate) fn unsubscribe_indicator_stream(subscriber: StreamName, stream_name: StreamName, indicator_name: IndicatorName) {
+    if let Some(mut subscribers) = INDICATOR_SUBSCRIBERS.get_mut(&(stream_name, indicator_name)) {
+        subscribers.retain(|name| *name != subscriber);
+    }
+}
+
+/// Drops the indicators published by the connection and its subscriptions.
+pub(crate) fn deregister_indicator_streams(stream_name: &StreamName) {
+    INDICATOR_STREAMS.retain(|(publisher, _), _| publisher != stream_name);
+    INDICATOR_SUBSCRIBERS.retain(|(publisher, _), subscribers| {
+        subscribers.retain(|subscriber| subscriber != stream_name);
+        publisher != stream_name && !subscribers.is_empty()
+    });
+}
@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
+use ff_standard_lib::messages::data_server_messaging::{DataServerResponse, FundForgeError};
+use ff_standard_lib::standardized_types::intents::{Intent, IntentStatus};
+use ff_standard_lib::standardized_types::subscriptions::SymbolName;
+
+static INTENTS_FOLDER: OnceCell<PathBuf> = OnceCell::new();
+
+lazy_static! {
+    static ref INTENTS: DashMap<SymbolName, Vec<Intent>> = DashMap::new();
+}
+
+/// Loads the intents saved in `intents/{symbol_name}.json` of the data folder.
+pub fn init_intents(data_folder: &Path) {
+    let folder = data_folder.join("intents");
+    if let Ok(entries) = fs::read_dir(&folder) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().map_or(true, |extension| extension != "json") {
+                continue;
+            }
+            match fs::read_to_string(&path).map(|contents| serde_json::from_str::<Vec<Intent>>(&contents)) {
+                Ok(Ok(intents)) => {
+                    if let Some(symbol_name) = intents.first().map(|intent| intent.symbol_name.clone()) {
+                        INTENTS.insert(symbol_name, intents);
+                    }
+                }
+                Ok(Err(e)) => eprintln!("Intents: Error parsing {:?}: {}", path, e),
+                Err(e) => eprintln!("Intents: Error reading {:?}: {}", path, e),
+            }
+        }
+    }
+    let _ = INTENTS_FOLDER.set(folder);
+}
+
+/// Writes the intents of the symbol to its file, the file is removed when the symbol has no intents.
+fn save_intents(symbol_name: &SymbolName, intents: &[Intent]) -> Result<(), FundForgeError> {
+    let Some(folder) = INTENTS_FOLDER.get() else {
+        return Err(FundForgeError::ServerErrorDebug("Intents have not been initialized".to_string()));
+    };
+    let path = folder.join(format!("{}.json", symbol_name));
+    if intents.is_empty() {
+        return match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(FundForgeError::ServerErrorDebug(format!("Failed to remove {:?}: {}", path, e))),
+        };
+    }
+    fs::create_dir_all(folder).map_err(|e| FundForgeError::ServerErrorDebug(format!("Failed to create {:?}: {}", folder, e)))?;
+    let json = serde_json::to_string_pretty(intents).map_err(|e| FundForgeError::ServerErrorDebug(format!("Failed to serialize intents: {}", e)))?;
+    // Write to a temporary file first so a failed write can not corrupt the existing intents
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, json).map_err(|e| FundForgeError::ServerErrorDebug(format!("Failed to write {:?}: {}", temp_path, e)))?;
+    fs::rename(&temp_path, &path).map_err(|e| FundForgeError::ServerErrorDebug(format!("Failed to replace {:?}: {}", path, e)))
+}
+
+pub(crate) async fn intents_response(symbol_name: SymbolName, callback_id: u64) -> DataServerResponse {
+    DataServerResponse::Intents {
+        callback_id,
+        intents: INTENTS.get(&symbol_name).map(|intents| intents.value().clone()).unwrap_or_default(),
+    }
+}
+
+pub(crate) async fn save_intent_response(intent: Intent, callback_id: u64) -> DataServerResponse {
+    let symbol_name = intent.symbol_name.clone();
+    let result = {
+        let mut intents = INTENTS.entry(symbol_name.clone()).or_default();
+        match intents.iter_mut().find(|existing| existing.id == intent.id) {
+            Some(existing) => *existing = intent,
+            None => intents.push(intent),
+        }
+        save_intents(&symbol_name, &intents)
+    };
+    match result {
+        Ok(()) => intents_response(symbol_name, callback_id).await,
+        Err(error) => DataServerResponse::Error { callback_id, error },
+    }
+}
+
+pub(crate) async fn remove_intent_response(symbol_name: SymbolName, id: String, callback_id: u64) -> DataServerResponse {
+    let result = match INTENTS.get_mut(&symbol_name) {
+        Some(mut intents) => {
+            intents.retain(|intent| intent.id != id);
+            save_intents(&symbol_name, &intents)
+        }
+        None => Ok(()),
+    };
+    match result {
+        Ok(()) => intents_response(symbol_name, callback_id).await,
+        Err(error) => DataServerResponse::Error { callback_id, error },
+    }
+}
+
+/// Marks the intent as triggered under the lock of its symbol, so of two strategies executing the same intents only the first claim succeeds.
+pub(crate) async fn claim_intent_response(symbol_name: SymbolName, id: String, time: String, callback_id: u64) -> DataServerResponse {
+    let Some(mut intents) = INTENTS.get_mut(&symbol_name) else {
+        return DataServerResponse::IntentClaim { callback_id, claimed: false };
+    };
+    let Some(index) = intents.iter().position(|intent| intent.id == id && intent.is_resting()) else {
+        return DataServerResponse::IntentClaim { callback_id, claimed: false };
+    };
+    intents[index].status = IntentStatus::Triggered { time };
+    match save_intents(&symbol_name, &intents) {
+        Ok(()) => DataServerResponse::IntentClaim { callback_id, claimed: true },
+        Err(error) => {
+            // an unsaved claim would fire again after a restart, so the intent is left resting and no order is sent
+            intents[index].status = IntentStatus::Resting;
+            DataServerResponse::Error { callback_id, error }
+        }
+    }
+}
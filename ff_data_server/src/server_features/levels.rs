@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
+use ff_standard_lib::messages::data_server_messaging::{DataServerResponse, FundForgeError};
+use ff_standard_lib::standardized_types::levels::Level;
+use ff_standard_lib::standardized_types::subscriptions::SymbolName;
+
+static LEVELS_FOLDER: OnceCell<PathBuf> = OnceCell::new();
+
+lazy_static! {
+    static ref LEVELS: DashMap<SymbolName, Vec<Level>> = DashMap::new();
+}
+
+/// Loads the levels saved in `levels/{symbol_name}.json` of the data folder, the files can be edited by hand while the server is stopped.
+pub fn init_levels(data_folder: &Path) {
+    let folder = data_folder.join("levels");
+    if let Ok(entries) = fs::read_dir(&folder) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().map_or(true, |extension| extension != "json") {
+                continue;
+            }
+            match fs::read_to_string(&path).map(|contents| serde_json::from_str::<Vec<Level>>(&contents)) {
+                Ok(Ok(levels)) => {
+                    if let Some(symbol_name) = levels.first().map(|level| level.symbol_name.clone()) {
+                        LEVELS.insert(symbol_name, levels);
+                    }
+                }
+                Ok(Err(e)) => eprintln!("Levels: Error parsing {:?}: {}", path, e),
+                Err(e) => eprintln!("Levels: Error reading {:?}: {}", path, e),
+            }
+        }
+    }
+    let _ = LEVELS_FOLDER.set(folder);
+}
+
+/// Writes the levels of the symbol to its file, the file is removed when the symbol has no levels.
+fn save_levels(symbol_name: &SymbolName, levels: &[Level]) -> Result<(), FundForgeError> {
+    let Some(folder) = LEVELS_FOLDER.get() else {
+        return Err(FundForgeError::ServerErrorDebug("Levels have not been initialized".to_string()));
+    };
+    let path = folder.join(format!("{}.json", symbol_name));
+    if levels.is_empty() {
+        return match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(FundForgeError::ServerErrorDebug(format!("Failed to remove {:?}: {}", path, e))),
+        };
+    }
+    fs::create_dir_all(folder).map_err(|e| FundForgeError::ServerErrorDebug(format!("Failed to create {:?}: {}", folder, e)))?;
+    let json = serde_json::to_string_pretty(levels).map_err(|e| FundForgeError::ServerErrorDebug(format!("Failed to serialize levels: {}", e)))?;
+    // Write to a temporary file first so a failed write can not corrupt the existing levels
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, json).map_err(|e| FundForgeError::ServerErrorDebug(format!("Failed to write {:?}: {}", temp_path, e)))?;
+    fs::rename(&temp_path, &path).map_err(|e| FundForgeError::ServerErrorDebug(format!("Failed to replace {:?}: {}", path, e)))
+}
+
+pub(crate) async fn levels_response(symbol_name: SymbolName, callback_id: u64) -> DataServerResponse {
+    DataServerResponse::Levels {
+        callback_id,
+        levels: LEVELS.get(&symbol_name).map(|levels| levels.value().clone()).unwrap_or_default(),
+    }
+}
+
+pub(crate) async fn save_level_response(level: Level, callback_id: u64) -> DataServerResponse {
+    let symbol_name = level.symbol_name.clone();
+    let result = {
+        let mut levels = LEVELS.entry(symbol_name.clone()).or_default();
+        match levels.iter_mut().find(|existing| existing.id == level.id) {
+            Some(existing) => *existing = level,
+            None => levels.push(level),
+        }
+        save_levels(&symbol_name, &levels)
+    };
+    match result {
+        Ok(()) => levels_response(symbol_name, callback_id).await,
+        Err(error) => DataServerResponse::Error { callback_id, error },
+    }
+}
+
+pub(crate) async fn remove_level_response(symbol_name: SymbolName, id: String, callback_id: u64) -> DataServerResponse {
+    let result = match LEVELS.get_mut(&symbol_name) {
+        Some(mut levels) => {
+            levels.retain(|level| level.id != id);
+            save_levels(&symbol_name, &levels)
+        }
+        None => Ok(()),
+    };
+    match result {
+        Ok(()) => levels_response(symbol_name, callback_id).await,
+        Err(error) => DataServerResponse::Error { callback_id, error },
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use ff_standard_lib::database::hybrid_storage::HybridStorage;
+use ff_standard_lib::messages::data_server_messaging::{DataServerResponse, FundForgeError};
+use ff_standard_lib::server_launch_options::ServerLaunchOptions;
+use ff_standard_lib::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use ff_standard_lib::standardized_types::base_data::base_data_type::BaseDataType;
+use ff_standard_lib::standardized_types::base_data::traits::BaseData;
+use ff_standard_lib::standardized_types::subscriptions::DataSubscription;
+use crate::stream_tasks::is_streamed;
+use crate::subscribe_server_shutdown;
+use crate::update_functions::{pre_subscribe_updates, DATA_STORAGE};
+
+struct RecorderSettings {
+    flush_interval: Duration,
+}
+
+static RECORDER_SETTINGS: OnceCell<RecorderSettings> = OnceCell::new();
+
+lazy_static! {
+    static ref RECORDERS: DashMap<DataSubscription, JoinHandle<()>> = DashMap::new();
+    static ref RECENT_DATA: DashMap<DataSubscription, VecDeque<BaseDataEnum>> = DashMap::new();
+}
+
+/// Records the live ticks, quotes and depth streamed to strategies into the historical store every `--record_flush` seconds, enabled with `--record_live 1`.
+pub fn init_live_recorder(options: &ServerLaunchOptions) {
+    if options.record_live == 0 {
+        return;
+    }
+    let _ = RECORDER_SETTINGS.set(RecorderSettings {
+        flush_interval: Duration::from_secs(options.record_flush_seconds.max(1)),
+    });
+    println!("Live Recorder: Recording streamed ticks, quotes and depth every {} seconds", options.record_flush_seconds.max(1));
+}
+
+/// Starts recording the subscription if recording is enabled and it is not already recorded.
+pub(crate) fn record_live_data(subscription: &DataSubscription, receiver: &broadcast::Receiver<BaseDataEnum>) {
+    let Some(settings) = RECORDER_SETTINGS.get() else {
+        return;
+    };
+    match subscription.base_data_type {
+        BaseDataType::Ticks | BaseDataType::Quotes | BaseDataType::Depth => {}
+        _ => return,
+    }
+    if let Some(recorder) = RECORDERS.get(subscription) {
+        if !recorder.is_finished() {
+            return;
+        }
+    }
+    let handle = tokio::spawn(recorder(subscription.clone(), receiver.resubscribe(), settings.flush_interval));
+    RECORDERS.insert(subscription.clone(), handle);
+}
+
+async fn recorder(subscription: DataSubscription, mut receiver: broadcast::Receiver<BaseDataEnum>, flush_interval: Duration) {
+    let Some(storage) = DATA_STORAGE.get().cloned() else {
+        return;
+    };
+    let mut shutdown_receiver = subscribe_server_shutdown();
+    let update = pre_subscribe_updates(storage.clone(), subscription.symbol.clone(), subscription.resolution, subscription.base_data_type);
+    tokio::pin!(update);
+    let mut updated = false;
+    let mut flush = tokio::time::interval_at(Instant::now() + flush_interval, flush_interval);
+    let mut buffer: Vec<BaseDataEnum> = vec![];
+    let mut skipped: u64 = 0;
+
+    loop {
+        tokio::select! {
+            _ = &mut update, if !updated => updated = true,
+            data = receiver.recv() => match data {
+                Ok(data) => {
+                    keep_recent(&subscription, data.clone(), flush_interval);
+                    buffer.push(data);
+                }
+                Err(RecvError::Lagged(count)) => skipped += count,
+                Err(RecvError::Closed) => break,
+            },
+            _ = flush.tick() => {
+                if skipped > 0 {
+                    eprintln!("Live Recorder: {} skipped {} messages, the recording has a gap", subscription, skipped);
+                    skipped = 0;
+                }
+                if updated {
+                    save(&storage, &subscription, &mut buffer).await;
+                }
+                if !is_streamed(&subscription).await {
+                    break;
+                }
+            }
+            _ = shutdown_receiver.recv() => break,
+        }
+    }
+
+    // without the update the recording would leave a gap before it, the data is saved by the next update from the vendor instead
+    if updated {
+        save(&storage, &subscription, &mut buffer).await;
+    }
+    RECENT_DATA.remove(&subscription);
+}
+
+/// Keeps the data of the last 2 flush intervals, so data saved by a flush during a warm up request is still returned by the request.
+fn keep_recent(subscription: &DataSubscription, data: BaseDataEnum, flush_interval: Duration) {
+    let retain_from = Utc::now() - chrono::Duration::from_std(flush_interval * 2).unwrap_or(chrono::Duration::zero());
+    let mut recent = RECENT_DATA.entry(subscription.clone()).or_default();
+    recent.push_back(data);
+    while recent.front().map_or(false, |data| data.time_closed_utc() < retain_from) {
+        recent.pop_front();
+    }
+}
+
+/// The recorded data of the subscriptions closed after `from_time`, subscriptions which are not recorded return no data.
+pub(crate) async fn recent_live_data_response(subscriptions: Vec<DataSubscription>, from_time: String, callback_id: u64) -> DataServerResponse {
+    let from_time = match DateTime::<Utc>::from_str(&from_time) {
+        Ok(time) => time,
+        Err(e) => return DataServerResponse::Error {
+            callback_id,
+            error: FundForgeError::ServerErrorDebug(format!("Invalid from time: {}", e))
+        }
+    };
+    let mut data: Vec<BaseDataEnum> = vec![];
+    for subscription in &subscriptions {
+        if let Some(recent) = RECENT_DATA.get(subscription) {
+            data.extend(recent.iter().filter(|data| data.time_closed_utc() > from_time).cloned());
+        }
+    }
+    DataServerResponse::RecentLiveData {
+        callback_id,
+        payload: BaseDataEnum::vec_to_bytes(data),
+    }
+}
+
+async fn save(storage: &HybridStorage, subscription: &DataSubscription, buffer: &mut Vec<BaseDataEnum>) {
+    if buffer.is_empty() {
+        return;
+    }
+    let data = std::mem::take(buffer);
+    if let Err(e) = storage.save_data_bulk(data).await {
+        eprintln!("Live Recorder: Failed to save {}: {}", subscription, e);
+    }
+}
+
+/// Waits for the recorders to save their buffers after the shutdown signal.
+pub async fn stop_live_recorders() {
+    let subscriptions: Vec<DataSubscription> = RECORDERS.iter().map(|entry| entry.key().clone()).collect();
+    for subscription in subscriptions {
+        if let Some((_, recorder)) = RECORDERS.remove(&subscription) {
+            if tokio::time::timeout(Duration::from_secs(30), recorder).await.is_err() {
+                eprintln!("Live Recorder: Timed out saving {}", subscription);
+            }
+        }
+    }
+}
@@ -1,2 +1,20 @@
 pub mod server_side_brokerage;
 pub mod server_side_datavendor;
+pub mod rest_brokerage;
+pub mod secrets;
+pub mod secrets_cli;
+pub mod fixtures_cli;
+pub mod scan_cli;
+pub mod strategy_streams;
+pub mod indicator_streams;
+pub mod live_recorder;
+pub mod continuous_contracts;
+pub mod cross_rates;
+pub mod data_quality;
+pub mod scanner;
+pub mod webhooks;
+pub mod subscription_stats;
+pub mod symbol_aliases;
+pub mod levels;
+pub mod intents;
+pub mod download_jobs;
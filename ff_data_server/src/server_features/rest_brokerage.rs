@@ -0,0 +1,845 @@
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use uuid::Uuid;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal_macros::dec;
+use ff_standard_lib::messages::data_server_messaging::{DataServerResponse, FundForgeError};
+use ff_standard_lib::standardized_types::accounts::{Account, AccountId, AccountInfo};
+use ff_standard_lib::standardized_types::broker_enum::Brokerage;
+use ff_standard_lib::standardized_types::enums::{Liquidity, OrderSide, PositionSide, StrategyMode};
+use ff_standard_lib::standardized_types::new_types::{Price, Volume};
+use ff_standard_lib::standardized_types::orders::{Order, OrderId, OrderState, OrderType, OrderUpdateEvent, OrderUpdateType};
+use ff_standard_lib::standardized_types::subscriptions::SymbolName;
+use ff_standard_lib::standardized_types::symbol_info::{CommissionInfo, SymbolInfo};
+use ff_standard_lib::StreamName;
+use crate::request_handlers::RESPONSE_SENDERS;
+use crate::server_features::server_side_brokerage::BrokerApiResponse;
+use crate::server_features::webhooks::notify_webhooks;
+
+lazy_static! {
+    static ref REST_BROKERAGES: DashMap<Brokerage, Arc<dyn BrokerApiResponse>> = DashMap::new();
+}
+
+pub fn register_rest_brokerage(brokerage: Brokerage, client: Arc<dyn BrokerApiResponse>) {
+    REST_BROKERAGES.insert(brokerage, client);
+}
+
+pub fn get_rest_brokerage(brokerage: &Brokerage) -> Option<Arc<dyn BrokerApiResponse>> {
+    REST_BROKERAGES.get(brokerage).map(|client| client.value().clone())
+}
+
+/// The id the broker assigned to an order.
+pub type BrokerOrderId = String;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BrokerPosition {
+    pub symbol_name: SymbolName,
+    pub side: PositionSide,
+    pub quantity: Volume,
+    pub average_price: Price,
+    pub open_pnl: Price,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BrokerOrderStatus {
+    Accepted,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected(String),
+}
+
+/// A change in the state of an order, returned by `RestBrokerageAdapter::poll_order_events()`.
+/// `fill_price` and `fill_quantity` are for this fill only, not the order total.
+/// `fill_id`, `liquidity` and `commission` are passed to the strategy with the fill, leave them None if the api does not report them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BrokerOrderEvent {
+    pub broker_order_id: BrokerOrderId,
+    pub status: BrokerOrderStatus,
+    pub fill_price: Option<Price>,
+    pub fill_quantity: Volume,
+    pub time: DateTime<Utc>,
+    pub fill_id: Option<String>,
+    pub liquidity: Option<Liquidity>,
+    pub commission: Option<Price>,
+}
+
+/// The calls a REST brokerage must support to be used through `RestBrokerage`.
+///
+/// Orders are passed with the fund forge symbol name, use `to_broker_symbol()` to convert it for the api.
+/// Order methods return `Err(reason)` when the broker refuses the request, the reason is forwarded to the strategy in the rejection event.
+///
+/// To connect a new broker:
+/// 1. Implement the adapter and run `conformance::run_conformance_suite()` against the brokers paper environment.
+/// 2. Add the `Brokerage` variant, any brokerage without a built in client is routed to `get_rest_brokerage()` by server_side_brokerage.rs.
+/// 3. Call `RestBrokerage::connect(adapter)` and `register_rest_brokerage()` when the server starts.
+#[async_trait]
+pub trait RestBrokerageAdapter: Send + Sync + 'static {
+    fn brokerage(&self) -> Brokerage;
+
+    /// How often positions and order events are polled.
+    fn poll_interval(&self) -> Duration {
+        Duration::from_secs(1)
+    }
+
+    /// Called once when connecting and again if a request fails with `FundForgeError::InvalidApiKey`.
+    async fn authenticate(&self) -> Result<(), FundForgeError>;
+
+    async fn accounts(&self) -> Result<Vec<AccountInfo>, FundForgeError>;
+
+    async fn symbol_names(&self) -> Result<Vec<SymbolName>, FundForgeError>;
+
+    async fn symbol_info(&self, symbol_name: &SymbolName) -> Result<SymbolInfo, FundForgeError>;
+
+    async fn commission_info(&self, symbol_name: &SymbolName) -> Result<CommissionInfo, FundForgeError> {
+        Err(FundForgeError::ServerErrorDebug(format!("{} does not provide commission info for: {}", self.brokerage(), symbol_name)))
+    }
+
+    /// Converts the fund forge symbol name to the brokers symbol, eg `AAPL` or `ESZ4`.
+    fn to_broker_symbol(&self, symbol_name: &SymbolName) -> Result<String, FundForgeError>;
+
+    fn from_broker_symbol(&self, broker_symbol: &str) -> Result<SymbolName, FundForgeError>;
+
+    /// Submits the order and returns the brokers id for the order, the order type is always one of Market, Limit, StopMarket, StopLimit or MarketIfTouched.
+    async fn submit_order(&self, account_id: &AccountId, order: &Order) -> Result<BrokerOrderId, String>;
+
+    async fn cancel_order(&self, account_id: &AccountId, broker_order_id: &BrokerOrderId) -> Result<(), String>;
+
+    async fn modify_order(&self, account_id: &AccountId, broker_order_id: &BrokerOrderId, update: &OrderUpdateType) -> Result<(), String>;
+
+    /// Returns the open positions for the account, flat positions can be omitted.
+    async fn positions(&self, account_id: &AccountId) -> Result<Vec<BrokerPosition>, FundForgeError>;
+
+    /// Returns the order events since the last call, each event must only be returned once.
+    /// Brokers with a streaming api can buffer events from the stream and drain the buffer here.
+    async fn poll_order_events(&self, account_id: &AccountId) -> Result<Vec<BrokerOrderEvent>, FundForgeError>;
+}
+
+/// Implements `BrokerApiResponse` for any `RestBrokerageAdapter`.
+pub struct RestBrokerage<A: RestBrokerageAdapter> {
+    adapter: Arc<A>,
+    accounts: DashMap<AccountId, AccountInfo>,
+    positions: DashMap<AccountId, DashMap<SymbolName, BrokerPosition>>,
+    open_orders: DashMap<BrokerOrderId, Order>,
+    broker_order_ids: DashMap<OrderId, BrokerOrderId>,
+    order_streams: DashMap<OrderId, StreamName>,
+}
+
+impl<A: RestBrokerageAdapter> RestBrokerage<A> {
+    /// Authenticates, loads the accounts and starts polling positions and order events.
+    pub async fn connect(adapter: A) -> Result<Arc<Self>, FundForgeError> {
+        adapter.authenticate().await?;
+        let accounts = adapter.accounts().await?;
+        let brokerage = Arc::new(RestBrokerage {
+            adapter: Arc::new(adapter),
+            accounts: accounts.into_iter().map(|info| (info.account_id.clone(), info)).collect(),
+            positions: DashMap::new(),
+            open_orders: DashMap::new(),
+            broker_order_ids: DashMap::new(),
+            order_streams: DashMap::new(),
+        });
+        println!("{}: Connected with {} accounts", brokerage.adapter.brokerage(), brokerage.accounts.len());
+
+        let poll_brokerage = brokerage.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_brokerage.adapter.poll_interval());
+            loop {
+                interval.tick().await;
+                poll_brokerage.poll().await;
+            }
+        });
+        Ok(brokerage)
+    }
+
+    async fn poll(&self) {
+        let account_ids: Vec<AccountId> = self.accounts.iter().map(|account| account.key().clone()).collect();
+        for account_id in account_ids {
+            match self.adapter.poll_order_events(&account_id).await {
+                Ok(events) => {
+                    for event in events {
+                        self.handle_order_event(event).await;
+                    }
+                }
+                Err(FundForgeError::InvalidApiKey) => self.reauthenticate().await,
+                Err(e) => eprintln!("{}: Failed to poll order events for {}: {}", self.adapter.brokerage(), account_id, e),
+            }
+            match self.adapter.positions(&account_id).await {
+                Ok(positions) => self.update_positions(&account_id, positions).await,
+                Err(FundForgeError::InvalidApiKey) => self.reauthenticate().await,
+                Err(e) => eprintln!("{}: Failed to poll positions for {}: {}", self.adapter.brokerage(), account_id, e),
+            }
+        }
+    }
+
+    async fn reauthenticate(&self) {
+        if let Err(e) = self.adapter.authenticate().await {
+            eprintln!("{}: Failed to re-authenticate: {}", self.adapter.brokerage(), e);
+        }
+    }
+
+    async fn handle_order_event(&self, event: BrokerOrderEvent) {
+        let (update, order_id, is_complete) = match self.open_orders.get_mut(&event.broker_order_id) {
+            Some(mut order) => {
+                let update = apply_order_event(&mut order, &event);
+                (update, order.id.clone(), is_order_complete(&order.state))
+            }
+            // orders placed outside of fund forge
+            None => return,
+        };
+        self.send_order_update(&order_id, update).await;
+        if is_complete {
+            self.open_orders.remove(&event.broker_order_id);
+            self.broker_order_ids.remove(&order_id);
+            self.order_streams.remove(&order_id);
+        }
+    }
+
+    async fn update_positions(&self, account_id: &AccountId, positions: Vec<BrokerPosition>) {
+        let account = Account::new(self.adapter.brokerage(), account_id.clone());
+        let previous = self.positions.entry(account_id.clone()).or_insert_with(DashMap::new).clone();
+        let mut updates = vec![];
+        for position in &positions {
+            if previous.get(&position.symbol_name).map(|last| last.value() != position).unwrap_or(true) {
+                updates.push(position.clone());
+            }
+        }
+        for last in previous.iter() {
+            if !positions.iter().any(|position| &position.symbol_name == last.key()) {
+                updates.push(BrokerPosition {
+                    symbol_name: last.key().clone(),
+                    side: PositionSide::Flat,
+                    quantity: dec!(0),
+                    average_price: dec!(0),
+                    open_pnl: dec!(0),
+                });
+            }
+        }
+        if let Some(account_positions) = self.positions.get(account_id) {
+            account_positions.clear();
+            for position in positions {
+                account_positions.insert(position.symbol_name.clone(), position);
+            }
+        }
+
+        let time = Utc::now().to_string();
+        for position in updates {
+            let update = DataServerResponse::LivePositionUpdates {
+                symbol_name: position.symbol_name.clone(),
+                symbol_code: position.symbol_name,
+                account: account.clone(),
+                open_quantity: position.quantity.to_f64().unwrap_or_default(),
+                average_price: position.average_price.to_f64().unwrap_or_default(),
+                side: position.side,
+                open_pnl: position.open_pnl.to_f64().unwrap_or_default(),
+                time: time.clone(),
+            };
+            notify_webhooks(&update);
+            for sender in RESPONSE_SENDERS.iter() {
+                if let Err(e) = sender.value().send(update.clone()).await {
+                    eprintln!("{}: Failed to forward position update to strategy stream: {}", self.adapter.brokerage(), e);
+                }
+            }
+        }
+    }
+
+    async fn send_order_update(&self, order_id: &OrderId, event: OrderUpdateEvent) {
+        let update = DataServerResponse::OrderUpdates { event, time: Utc::now().to_string() };
+        notify_webhooks(&update);
+        let stream_name = match self.order_streams.get(order_id) {
+            Some(stream_name) => *stream_name.value(),
+            None => return,
+        };
+        if let Some(sender) = RESPONSE_SENDERS.get(&stream_name) {
+            if let Err(e) = sender.value().send(update).await {
+                eprintln!("{}: Failed to forward order update to strategy stream {}: {}", self.adapter.brokerage(), stream_name, e);
+            }
+        }
+    }
+
+    fn position(&self, account_id: &AccountId, symbol_name: &SymbolName) -> Option<BrokerPosition> {
+        self.positions.get(account_id)
+            .and_then(|positions| positions.get(symbol_name).map(|position| position.value().clone()))
+    }
+
+    async fn submit(&self, stream_name: StreamName, mut order: Order) -> Result<(), OrderUpdateEvent> {
+        if let Err(e) = self.adapter.to_broker_symbol(&order.symbol_name) {
+            return Err(order_rejected(&order, e.to_string()));
+        }
+        if is_position_order(&order.order_type) {
+            let position = self.position(&order.account.account_id, &order.symbol_name);
+            let (side, quantity) = position_order_quantity(&order.order_type, order.quantity_open, position.as_ref())
+                .map_err(|reason| order_rejected(&order, reason))?;
+            order.side = side;
+            order.quantity_open = quantity;
+            order.order_type = OrderType::Market;
+        }
+        let broker_order_id = self.adapter.submit_order(&order.account.account_id, &order).await
+            .map_err(|reason| order_rejected(&order, reason))?;
+        self.order_streams.insert(order.id.clone(), stream_name);
+        self.broker_order_ids.insert(order.id.clone(), broker_order_id.clone());
+        self.open_orders.insert(broker_order_id, order);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<A: RestBrokerageAdapter> BrokerApiResponse for RestBrokerage<A> {
+    #[allow(unused)]
+    async fn symbol_names_response(&self, mode: StrategyMode, time: Option<DateTime<Utc>>, stream_name: StreamName, callback_id: u64) -> DataServerResponse {
+        match self.adapter.symbol_names().await {
+            Ok(symbol_names) => DataServerResponse::SymbolNames { callback_id, symbol_names },
+            Err(error) => DataServerResponse::Error { callback_id, error },
+        }
+    }
+
+    #[allow(unused)]
+    async fn account_info_response(&self, mode: StrategyMode, stream_name: StreamName, account_id: AccountId, callback_id: u64) -> DataServerResponse {
+        match self.adapter.accounts().await {
+            Ok(accounts) => {
+                for info in accounts {
+                    self.accounts.insert(info.account_id.clone(), info);
+                }
+            }
+            Err(e) => eprintln!("{}: Failed to refresh accounts, using cached account info: {}", self.adapter.brokerage(), e),
+        }
+        match self.accounts.get(&account_id) {
+            Some(account_info) => DataServerResponse::AccountInfo { callback_id, account_info: account_info.clone() },
+            None => DataServerResponse::Error { callback_id, error: FundForgeError::ClientSideErrorDebug(format!("No account found for id: {}", account_id)) },
+        }
+    }
+
+    #[allow(unused)]
+    async fn symbol_info_response(&self, mode: StrategyMode, stream_name: StreamName, symbol_name: SymbolName, callback_id: u64) -> DataServerResponse {
+        match self.adapter.symbol_info(&symbol_name).await {
+            Ok(symbol_info) => DataServerResponse::SymbolInfo { callback_id, symbol_info },
+            Err(error) => DataServerResponse::Error { callback_id, error },
+        }
+    }
+
+    #[allow(unused)]
+    async fn accounts_response(&self, mode: StrategyMode, stream_name: StreamName, callback_id: u64) -> DataServerResponse {
+        let accounts = self.accounts.iter().map(|account| account.key().clone()).collect();
+        DataServerResponse::Accounts { callback_id, accounts }
+    }
+
+    async fn logout_command(&self, stream_name: StreamName) {
+        self.order_streams.retain(|_, order_stream| *order_stream != stream_name);
+    }
+
+    #[allow(unused)]
+    async fn commission_info_response(&self, mode: StrategyMode, stream_name: StreamName, symbol_name: SymbolName, callback_id: u64) -> DataServerResponse {
+        match self.adapter.commission_info(&symbol_name).await {
+            Ok(commission_info) => DataServerResponse::CommissionInfo { callback_id, commission_info },
+            Err(error) => DataServerResponse::Error { callback_id, error },
+        }
+    }
+
+    #[allow(unused)]
+    async fn live_market_order(&self, stream_name: StreamName, mode: StrategyMode, order: Order) -> Result<(), OrderUpdateEvent> {
+        self.submit(stream_name, order).await
+    }
+
+    #[allow(unused)]
+    async fn live_enter_long(&self, stream_name: StreamName, mode: StrategyMode, order: Order) -> Result<(), OrderUpdateEvent> {
+        self.submit(stream_name, order).await
+    }
+
+    #[allow(unused)]
+    async fn live_enter_short(&self, stream_name: StreamName, mode: StrategyMode, order: Order) -> Result<(), OrderUpdateEvent> {
+        self.submit(stream_name, order).await
+    }
+
+    #[allow(unused)]
+    async fn live_exit_short(&self, stream_name: StreamName, mode: StrategyMode, order: Order) -> Result<(), OrderUpdateEvent> {
+        self.submit(stream_name, order).await
+    }
+
+    #[allow(unused)]
+    async fn live_exit_long(&self, stream_name: StreamName, mode: StrategyMode, order: Order) -> Result<(), OrderUpdateEvent> {
+        self.submit(stream_name, order).await
+    }
+
+    #[allow(unused)]
+    async fn other_orders(&self, stream_name: StreamName, mode: StrategyMode, order: Order) -> Result<(), OrderUpdateEvent> {
+        self.submit(stream_name, order).await
+    }
+
+    async fn cancel_orders_on_account(&self, account: Account) {
+        let order_ids: Vec<OrderId> = self.open_orders.iter()
+            .filter(|order| order.value().account == account)
+            .map(|order| order.value().id.clone())
+            .collect();
+        for order_id in order_ids {
+            self.cancel_order(account.clone(), order_id).await;
+        }
+    }
+
+    async fn cancel_order(&self, account: Account, order_id: OrderId) {
+        let broker_order_id = match self.broker_order_ids.get(&order_id) {
+            Some(broker_order_id) => broker_order_id.value().clone(),
+            None => return,
+        };
+        // the cancellation event is sent when it is returned by `poll_order_events()`
+        if let Err(reason) = self.adapter.cancel_order(&account.account_id, &broker_order_id).await {
+            let event = OrderUpdateEvent::OrderUpdateRejected {
+                account,
+                order_id: order_id.clone(),
+                reason: format!("Cancel rejected: {}", reason),
+                time: Utc::now().to_string(),
+            };
+            self.send_order_update(&order_id, event).await;
+        }
+    }
+
+    async fn flatten_all_for(&self, account: Account) {
+        self.cancel_orders_on_account(account.clone()).await;
+        let positions: Vec<BrokerPosition> = match self.positions.get(&account.account_id) {
+            Some(positions) => positions.iter().map(|position| position.value().clone()).collect(),
+            None => return,
+        };
+        for position in positions {
+            let side = match position.side {
+                PositionSide::Long => OrderSide::Sell,
+                PositionSide::Short => OrderSide::Buy,
+                PositionSide::Flat => continue,
+            };
+            let order = Order::market_order(position.symbol_name.clone(), None, &account, position.quantity, side, "Flatten All".to_string(), Uuid::new_v4().to_string(), Utc::now(), None);
+            if let Err(reason) = self.adapter.submit_order(&account.account_id, &order).await {
+                eprintln!("{}: Failed to flatten {} on {}: {}", self.adapter.brokerage(), position.symbol_name, account, reason);
+            }
+        }
+    }
+
+    async fn update_order(&self, account: Account, order_id: OrderId, update: OrderUpdateType) -> Result<(), OrderUpdateEvent> {
+        let broker_order_id = match self.broker_order_ids.get(&order_id) {
+            Some(broker_order_id) => broker_order_id.value().clone(),
+            None => return Err(OrderUpdateEvent::OrderUpdateRejected { account, order_id, reason: "Order not found".to_string(), time: Utc::now().to_string() }),
+        };
+        if let Err(reason) = self.adapter.modify_order(&account.account_id, &broker_order_id, &update).await {
+            return Err(OrderUpdateEvent::OrderUpdateRejected { account, order_id, reason, time: Utc::now().to_string() });
+        }
+        // REST brokers confirm the modification in the response, so we send the update now
+        let event = match self.open_orders.get_mut(&broker_order_id) {
+            Some(mut order) => {
+                match &update {
+                    OrderUpdateType::LimitPrice(price) => order.limit_price = Some(*price),
+                    OrderUpdateType::TriggerPrice(price) => order.trigger_price = Some(*price),
+                    OrderUpdateType::Quantity(quantity) => order.quantity_open = *quantity,
+                }
+                OrderUpdateEvent::OrderUpdated {
+                    account,
+                    symbol_name: order.symbol_name.clone(),
+                    symbol_code: order.symbol_code.clone(),
+                    order_id: order_id.clone(),
+                    update_type: update,
+                    text: "Order Updated".to_string(),
+                    tag: order.tag.clone(),
+                    time: Utc::now().to_string(),
+                }
+            }
+            None => return Ok(()),
+        };
+        self.send_order_update(&order_id, event).await;
+        Ok(())
+    }
+}
+
+fn is_position_order(order_type: &OrderType) -> bool {
+    matches!(order_type, OrderType::EnterLong | OrderType::EnterShort | OrderType::ExitLong | OrderType::ExitShort)
+}
+
+fn is_order_complete(state: &OrderState) -> bool {
+    matches!(state, OrderState::Filled | OrderState::Cancelled | OrderState::Expired | OrderState::Rejected(_))
+}
+
+/// Converts a position based order into a market order side and quantity, entering reverses any opposing position and exits are limited to the open quantity.
+pub(crate) fn position_order_quantity(order_type: &OrderType, quantity: Volume, position: Option<&BrokerPosition>) -> Result<(OrderSide, Volume), String> {
+    let (side, open_quantity) = match position {
+        Some(position) => (position.side, position.quantity),
+        None => (PositionSide::Flat, dec!(0)),
+    };
+    match (order_type, side) {
+        (OrderType::EnterLong, PositionSide::Short) => Ok((OrderSide::Buy, quantity + open_quantity)),
+        (OrderType::EnterLong, _) => Ok((OrderSide::Buy, quantity)),
+        (OrderType::EnterShort, PositionSide::Long) => Ok((OrderSide::Sell, quantity + open_quantity)),
+        (OrderType::EnterShort, _) => Ok((OrderSide::Sell, quantity)),
+        (OrderType::ExitLong, PositionSide::Long) => Ok((OrderSide::Sell, quantity.min(open_quantity))),
+        (OrderType::ExitLong, _) => Err("No long position to exit".to_string()),
+        (OrderType::ExitShort, PositionSide::Short) => Ok((OrderSide::Buy, quantity.min(open_quantity))),
+        (OrderType::ExitShort, _) => Err("No short position to exit".to_string()),
+        (order_type, _) => Err(format!("{:?} is not a position order type", order_type)),
+    }
+}
+
+/// Updates the order state and fill quantities and returns the event for the strategy.
+pub(crate) fn apply_order_event(order: &mut Order, event: &BrokerOrderEvent) -> OrderUpdateEvent {
+    let time = event.time.to_string();
+    let account = order.account.clone();
+    let symbol_name = order.symbol_name.clone();
+    let symbol_code = order.symbol_code.clone();
+    let order_id = order.id.clone();
+    let tag = order.tag.clone();
+    match &event.status {
+        BrokerOrderStatus::Accepted => {
+            order.state = OrderState::Accepted;
+            order.broker_order_id = Some(event.broker_order_id.clone());
+            OrderUpdateEvent::OrderAccepted { account, symbol_name, symbol_code, order_id, tag, time, broker_order_id: Some(event.broker_order_id.clone()) }
+        }
+        BrokerOrderStatus::PartiallyFilled | BrokerOrderStatus::Filled => {
+            let price = event.fill_price.unwrap_or_default();
+            let filled = order.quantity_filled + event.fill_quantity;
+            if filled > dec!(0) {
+                let previous = order.average_fill_price.unwrap_or_default() * order.quantity_filled;
+                order.average_fill_price = Some((previous + price * event.fill_quantity) / filled);
+            }
+            order.quantity_filled = filled;
+            order.quantity_open = (order.quantity_open - event.fill_quantity).max(dec!(0));
+            let side = order.side;
+            let quantity = event.fill_quantity;
+            if event.status == BrokerOrderStatus::Filled {
+                order.state = OrderState::Filled;
+                order.quantity_open = dec!(0);
+                order.time_filled_utc = Some(time.clone());
+                OrderUpdateEvent::OrderFilled { account, symbol_name, symbol_code, order_id, side, price, quantity, tag, time, fill_id: event.fill_id.clone(), liquidity: event.liquidity, commission: event.commission }
+            } else {
+                order.state = OrderState::PartiallyFilled;
+                OrderUpdateEvent::OrderPartiallyFilled { account, symbol_name, symbol_code, order_id, side, price, quantity, tag, time, fill_id: event.fill_id.clone(), liquidity: event.liquidity, commission: event.commission }
+            }
+        }
+        BrokerOrderStatus::Cancelled => {
+            order.state = OrderState::Cancelled;
+            OrderUpdateEvent::OrderCancelled { account, symbol_name, symbol_code, order_id, reason: "Cancelled".to_string(), tag, time }
+        }
+        BrokerOrderStatus::Rejected(reason) => {
+            order.state = OrderState::Rejected(reason.clone());
+            OrderUpdateEvent::OrderRejected { account, symbol_name, symbol_code, order_id, reason: reason.clone(), tag, time }
+        }
+    }
+}
+
+fn order_rejected(order: &Order, reason: String) -> OrderUpdateEvent {
+    OrderUpdateEvent::OrderRejected {
+        account: order.account.clone(),
+        symbol_name: order.symbol_name.clone(),
+        symbol_code: order.symbol_code.clone(),
+        order_id: order.id.clone(),
+        reason,
+        tag: order.tag.clone(),
+        time: Utc::now().to_string(),
+    }
+}
+
+/// The conformance suite every `RestBrokerageAdapter` must pass before it is merged.
+/// Run it from a test in the adapters module against the brokers paper or sandbox environment.
+/// ```ignore
+/// #[tokio::test]
+/// #[ignore] // needs paper trading credentials
+/// async fn test_adapter_conformance() {
+///     let adapter = MyBrokerAdapter::from_file("credentials.toml").unwrap();
+///     let config = ConformanceConfig::new("PAPER123".to_string(), "AAPL".to_string(), dec!(1), dec!(1));
+///     let report = run_conformance_suite(&adapter, config).await;
+///     assert!(report.is_success(), "{}", report);
+/// }
+/// ```
+pub mod conformance {
+    use std::fmt::{Display, Formatter};
+    use ff_standard_lib::standardized_types::orders::TimeInForce;
+    use super::*;
+
+    pub struct ConformanceConfig {
+        pub account_id: AccountId,
+        pub symbol_name: SymbolName,
+        pub quantity: Volume,
+        /// A buy limit price far enough below the market that the test order will not fill.
+        pub resting_limit_price: Price,
+        /// How long to wait for the order events after each request.
+        pub event_timeout: Duration,
+    }
+
+    impl ConformanceConfig {
+        pub fn new(account_id: AccountId, symbol_name: SymbolName, quantity: Volume, resting_limit_price: Price) -> Self {
+            ConformanceConfig {
+                account_id,
+                symbol_name,
+                quantity,
+                resting_limit_price,
+                event_timeout: Duration::from_secs(10),
+            }
+        }
+    }
+
+    #[derive(Default, Debug)]
+    pub struct ConformanceReport {
+        pub passed: Vec<&'static str>,
+        pub failed: Vec<(&'static str, String)>,
+    }
+
+    impl ConformanceReport {
+        pub fn is_success(&self) -> bool {
+            self.failed.is_empty()
+        }
+
+        fn check(&mut self, name: &'static str, result: Result<(), String>) -> bool {
+            match result {
+                Ok(_) => {
+                    self.passed.push(name);
+                    true
+                }
+                Err(e) => {
+                    self.failed.push((name, e));
+                    false
+                }
+            }
+        }
+    }
+
+    impl Display for ConformanceReport {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            writeln!(f, "Conformance: {} passed, {} failed", self.passed.len(), self.failed.len())?;
+            for (name, reason) in &self.failed {
+                writeln!(f, "FAILED {}: {}", name, reason)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Places and cancels a resting limit order, so it must only be run on a paper or sandbox account.
+    pub async fn run_conformance_suite<A: RestBrokerageAdapter>(adapter: &A, config: ConformanceConfig) -> ConformanceReport {
+        let mut report = ConformanceReport::default();
+        if !report.check("authenticate", adapter.authenticate().await.map_err(|e| e.to_string())) {
+            return report;
+        }
+
+        let accounts = adapter.accounts().await;
+        report.check("accounts", match &accounts {
+            Ok(accounts) => match accounts.iter().find(|info| info.account_id == config.account_id) {
+                Some(info) if info.brokerage != adapter.brokerage() => Err(format!("Account brokerage is {}, expected {}", info.brokerage, adapter.brokerage())),
+                Some(_) => Ok(()),
+                None => Err(format!("Account not found: {}", config.account_id)),
+            },
+            Err(e) => Err(e.to_string()),
+        });
+
+        report.check("symbol_names", match adapter.symbol_names().await {
+            Ok(names) if names.contains(&config.symbol_name) => Ok(()),
+            Ok(_) => Err(format!("Symbol names do not contain: {}", config.symbol_name)),
+            Err(e) => Err(e.to_string()),
+        });
+
+        report.check("symbol_mapping", match adapter.to_broker_symbol(&config.symbol_name) {
+            Ok(broker_symbol) => match adapter.from_broker_symbol(&broker_symbol) {
+                Ok(symbol_name) if symbol_name == config.symbol_name => Ok(()),
+                Ok(symbol_name) => Err(format!("{} mapped to {} and back to {}", config.symbol_name, broker_symbol, symbol_name)),
+                Err(e) => Err(e.to_string()),
+            },
+            Err(e) => Err(e.to_string()),
+        });
+
+        report.check("symbol_info", match adapter.symbol_info(&config.symbol_name).await {
+            Ok(info) if info.tick_size <= dec!(0) => Err(format!("Tick size must be positive: {}", info.tick_size)),
+            Ok(info) if info.symbol_name != config.symbol_name => Err(format!("Symbol info is for {}", info.symbol_name)),
+            Ok(_) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        });
+
+        report.check("positions", adapter.positions(&config.account_id).await.map(|_| ()).map_err(|e| e.to_string()));
+        // drain any events from before the test
+        let _ = adapter.poll_order_events(&config.account_id).await;
+
+        let account = Account::new(adapter.brokerage(), config.account_id.clone());
+        let order = Order::limit_order(config.symbol_name.clone(), None, &account, config.quantity, OrderSide::Buy, "Conformance".to_string(), Uuid::new_v4().to_string(), Utc::now(), config.resting_limit_price, TimeInForce::Day, None);
+        let broker_order_id = match adapter.submit_order(&config.account_id, &order).await {
+            Ok(broker_order_id) => {
+                report.passed.push("submit_order");
+                broker_order_id
+            }
+            Err(e) => {
+                report.failed.push(("submit_order", e));
+                return report;
+            }
+        };
+        report.check("order_accepted", wait_for_status(adapter, &config, &broker_order_id, |status| *status == BrokerOrderStatus::Accepted).await);
+
+        let update = OrderUpdateType::LimitPrice(config.resting_limit_price - adapter.symbol_info(&config.symbol_name).await.map(|info| info.tick_size).unwrap_or_default());
+        report.check("modify_order", adapter.modify_order(&config.account_id, &broker_order_id, &update).await);
+
+        report.check("cancel_order", adapter.cancel_order(&config.account_id, &broker_order_id).await);
+        report.check("order_cancelled", wait_for_status(adapter, &config, &broker_order_id, |status| *status == BrokerOrderStatus::Cancelled).await);
+
+        report.check("cancel_unknown_order", match adapter.cancel_order(&config.account_id, &"fund_forge_unknown_order".to_string()).await {
+            Ok(_) => Err("Cancelling an unknown order should return Err".to_string()),
+            Err(_) => Ok(()),
+        });
+        report
+    }
+
+    async fn wait_for_status<A: RestBrokerageAdapter>(adapter: &A, config: &ConformanceConfig, broker_order_id: &BrokerOrderId, is_status: impl Fn(&BrokerOrderStatus) -> bool) -> Result<(), String> {
+        let deadline = tokio::time::Instant::now() + config.event_timeout;
+        let mut received = vec![];
+        while tokio::time::Instant::now() < deadline {
+            match adapter.poll_order_events(&config.account_id).await {
+                Ok(events) => {
+                    for event in events.into_iter().filter(|event| &event.broker_order_id == broker_order_id) {
+                        if is_status(&event.status) {
+                            return Ok(());
+                        }
+                        received.push(event.status);
+                    }
+                }
+                Err(e) => return Err(e.to_string()),
+            }
+            tokio::time::sleep(adapter.poll_interval().min(Duration::from_millis(500))).await;
+        }
+        Err(format!("Timed out waiting for the order event, received: {:?}", received))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use ff_standard_lib::standardized_types::accounts::Currency;
+    use super::*;
+    use super::conformance::{run_conformance_suite, ConformanceConfig};
+
+    /// An in memory broker which accepts every order and never fills resting orders.
+    struct MockAdapter {
+        next_id: AtomicU64,
+        working: Mutex<Vec<BrokerOrderId>>,
+        events: Mutex<Vec<BrokerOrderEvent>>,
+    }
+
+    impl MockAdapter {
+        fn new() -> Self {
+            MockAdapter { next_id: AtomicU64::new(1), working: Mutex::new(vec![]), events: Mutex::new(vec![]) }
+        }
+
+        fn push_event(&self, broker_order_id: &BrokerOrderId, status: BrokerOrderStatus) {
+            self.events.lock().unwrap().push(BrokerOrderEvent { broker_order_id: broker_order_id.clone(), status, fill_price: None, fill_quantity: dec!(0), time: Utc::now(), fill_id: None, liquidity: None, commission: None });
+        }
+    }
+
+    #[async_trait]
+    impl RestBrokerageAdapter for MockAdapter {
+        fn brokerage(&self) -> Brokerage {
+            Brokerage::Test
+        }
+
+        async fn authenticate(&self) -> Result<(), FundForgeError> {
+            Ok(())
+        }
+
+        async fn accounts(&self) -> Result<Vec<AccountInfo>, FundForgeError> {
+            Ok(vec![AccountInfo {
+                account_id: "Paper".to_string(),
+                brokerage: Brokerage::Test,
+                cash_value: dec!(100000),
+                cash_available: dec!(100000),
+                currency: Currency::USD,
+                open_pnl: dec!(0),
+                booked_pnl: dec!(0),
+                day_open_pnl: dec!(0),
+                day_booked_pnl: dec!(0),
+                cash_used: dec!(0),
+                positions: vec![],
+                is_hedging: false,
+                buy_limit: None,
+                sell_limit: None,
+                max_orders: None,
+                daily_max_loss: None,
+                daily_max_loss_reset_time: None,
+                leverage: 1,
+            }])
+        }
+
+        async fn symbol_names(&self) -> Result<Vec<SymbolName>, FundForgeError> {
+            Ok(vec!["AAPL".to_string()])
+        }
+
+        async fn symbol_info(&self, symbol_name: &SymbolName) -> Result<SymbolInfo, FundForgeError> {
+            Ok(SymbolInfo::new(symbol_name.clone(), None, Currency::USD, dec!(0.01), dec!(0.01), 2))
+        }
+
+        fn to_broker_symbol(&self, symbol_name: &SymbolName) -> Result<String, FundForgeError> {
+            Ok(format!("US.{}", symbol_name))
+        }
+
+        fn from_broker_symbol(&self, broker_symbol: &str) -> Result<SymbolName, FundForgeError> {
+            broker_symbol.strip_prefix("US.").map(|symbol| symbol.to_string()).ok_or_else(|| FundForgeError::ClientSideErrorDebug(broker_symbol.to_string()))
+        }
+
+        async fn submit_order(&self, _account_id: &AccountId, _order: &Order) -> Result<BrokerOrderId, String> {
+            let broker_order_id = self.next_id.fetch_add(1, Ordering::SeqCst).to_string();
+            self.working.lock().unwrap().push(broker_order_id.clone());
+            self.push_event(&broker_order_id, BrokerOrderStatus::Accepted);
+            Ok(broker_order_id)
+        }
+
+        async fn cancel_order(&self, _account_id: &AccountId, broker_order_id: &BrokerOrderId) -> Result<(), String> {
+            let mut working = self.working.lock().unwrap();
+            match working.iter().position(|id| id == broker_order_id) {
+                Some(index) => {
+                    working.remove(index);
+                    self.push_event(broker_order_id, BrokerOrderStatus::Cancelled);
+                    Ok(())
+                }
+                None => Err(format!("Order not found: {}", broker_order_id)),
+            }
+        }
+
+        async fn modify_order(&self, _account_id: &AccountId, broker_order_id: &BrokerOrderId, _update: &OrderUpdateType) -> Result<(), String> {
+            match self.working.lock().unwrap().contains(broker_order_id) {
+                true => Ok(()),
+                false => Err(format!("Order not found: {}", broker_order_id)),
+            }
+        }
+
+        async fn positions(&self, _account_id: &AccountId) -> Result<Vec<BrokerPosition>, FundForgeError> {
+            Ok(vec![])
+        }
+
+        async fn poll_order_events(&self, _account_id: &AccountId) -> Result<Vec<BrokerOrderEvent>, FundForgeError> {
+            Ok(self.events.lock().unwrap().drain(..).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_adapter_passes_conformance_suite() {
+        let config = ConformanceConfig::new("Paper".to_string(), "AAPL".to_string(), dec!(1), dec!(1));
+        let report = run_conformance_suite(&MockAdapter::new(), config).await;
+        assert!(report.is_success(), "{}", report);
+        assert_eq!(report.passed.len(), 12);
+    }
+
+    #[test]
+    fn test_position_order_quantity() {
+        let short = BrokerPosition { symbol_name: "AAPL".to_string(), side: PositionSide::Short, quantity: dec!(3), average_price: dec!(100), open_pnl: dec!(0) };
+        assert_eq!(position_order_quantity(&OrderType::EnterLong, dec!(2), Some(&short)), Ok((OrderSide::Buy, dec!(5))));
+        assert_eq!(position_order_quantity(&OrderType::EnterShort, dec!(2), Some(&short)), Ok((OrderSide::Sell, dec!(2))));
+        assert_eq!(position_order_quantity(&OrderType::ExitShort, dec!(5), Some(&short)), Ok((OrderSide::Buy, dec!(3))));
+        assert!(position_order_quantity(&OrderType::ExitLong, dec!(1), Some(&short)).is_err());
+        assert!(position_order_quantity(&OrderType::ExitShort, dec!(1), None).is_err());
+    }
+
+    #[test]
+    fn test_apply_order_event_fills() {
+        let account = Account::new(Brokerage::Test, "Paper".to_string());
+        let mut order = Order::market_order("AAPL".to_string(), None, &account, dec!(10), OrderSide::Buy, "Entry".to_string(), "1".to_string(), Utc::now(), None);
+        let fill = |status, price, quantity| BrokerOrderEvent { broker_order_id: "1".to_string(), status, fill_price: Some(price), fill_quantity: quantity, time: Utc::now(), fill_id: None, liquidity: None, commission: None };
+
+        let event = apply_order_event(&mut order, &fill(BrokerOrderStatus::PartiallyFilled, dec!(100), dec!(4)));
+        assert!(matches!(event, OrderUpdateEvent::OrderPartiallyFilled { quantity, .. } if quantity == dec!(4)));
+        assert_eq!(order.quantity_open, dec!(6));
+
+        let event = apply_order_event(&mut order, &fill(BrokerOrderStatus::Filled, dec!(101), dec!(6)));
+        assert!(matches!(event, OrderUpdateEvent::OrderFilled { price, .. } if price == dec!(101)));
+        assert_eq!(order.state, OrderState::Filled);
+        assert_eq!(order.quantity_filled, dec!(10));
+        assert_eq!(order.average_fill_price, Some(dec!(100.6)));
+        assert!(is_order_complete(&order.state));
+    }
+}
@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use structopt::StructOpt;
+use ff_standard_lib::database::scanner::ScanCondition;
+use ff_standard_lib::messages::data_server_messaging::FundForgeError;
+use ff_standard_lib::standardized_types::base_data::base_data_type::BaseDataType;
+use ff_standard_lib::standardized_types::datavendor_enum::DataVendor;
+use ff_standard_lib::standardized_types::enums::MarketType;
+use ff_standard_lib::standardized_types::resolution::Resolution;
+use ff_standard_lib::standardized_types::subscriptions::Symbol;
+use crate::server_features::fixtures_cli::{parse_market_type, storage};
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "scan", about = "Scan the historical store for the symbols which meet every condition")]
+pub struct ScanCli {
+    /// The data folder of the server
+    #[structopt(long = "data_folder", default_value = "./data", parse(from_os_str))]
+    pub data_folder: PathBuf,
+
+    #[structopt(long)]
+    pub vendor: DataVendor,
+
+    /// Forex, CFD, Crypto, ETF, Futures or Equities
+    #[structopt(long = "market_type", parse(try_from_str = parse_market_type))]
+    pub market_type: MarketType,
+
+    /// A comma separated list of symbols
+    #[structopt(long, use_delimiter = true, required = true)]
+    pub symbols: Vec<String>,
+
+    #[structopt(long = "data_type", parse(try_from_str = BaseDataType::from_str))]
+    pub data_type: BaseDataType,
+
+    /// The stored resolution, ticks and quotes are stored as 1-I
+    #[structopt(long, default_value = "1-I")]
+    pub resolution: Resolution,
+
+    /// A condition, eg "atr(14,5-M) > 0.0005", "day_range > 0.01", "day_change% < -1", "day_volume >= 10000" or "price < 1.2", repeat for more conditions
+    #[structopt(long = "condition", required = true, parse(try_from_str = ScanCondition::from_str))]
+    pub conditions: Vec<ScanCondition>,
+
+    /// The scan time, eg 2024-06-05T08:00:00Z, now if not set
+    #[structopt(long)]
+    pub time: Option<DateTime<Utc>>,
+}
+
+impl ScanCli {
+    pub async fn run(self) -> Result<(), FundForgeError> {
+        let storage = storage(self.data_folder.clone());
+        let symbols: Vec<Symbol> = self.symbols.iter()
+            .map(|symbol| Symbol::new(symbol.trim().to_string(), self.vendor, self.market_type.clone()))
+            .collect();
+        let time = self.time.unwrap_or_else(Utc::now);
+        let matches = storage.scan(&symbols, &self.data_type, &self.resolution, &self.conditions, time).await?;
+        let conditions: Vec<String> = self.conditions.iter().map(|condition| condition.to_string()).collect();
+        println!("{} of {} symbols met {} at {}", matches.len(), symbols.len(), conditions.join(" and "), time);
+        for scan_match in matches {
+            println!("{}", scan_match);
+        }
+        Ok(())
+    }
+}
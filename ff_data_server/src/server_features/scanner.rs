@@ -0,0 +1,43 @@
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use ff_standard_lib::database::scanner::ScanCondition;
+use ff_standard_lib::messages::data_server_messaging::{DataServerResponse, FundForgeError};
+use ff_standard_lib::standardized_types::base_data::base_data_type::BaseDataType;
+use ff_standard_lib::standardized_types::resolution::Resolution;
+use ff_standard_lib::standardized_types::subscriptions::Symbol;
+use crate::update_functions::DATA_STORAGE;
+
+// Serves `DataServerRequest::Scan` from the historical store, the `scan` command runs the same scan without starting the server.
+
+pub(crate) async fn scan_response(
+    symbols: Vec<Symbol>,
+    base_data_type: BaseDataType,
+    resolution: Resolution,
+    conditions: Vec<ScanCondition>,
+    time: String,
+    callback_id: u64
+) -> DataServerResponse {
+    let time = match DateTime::<Utc>::from_str(&time) {
+        Ok(time) => time,
+        Err(e) => return DataServerResponse::Error {
+            callback_id,
+            error: FundForgeError::ServerErrorDebug(format!("Invalid scan time: {}", e))
+        }
+    };
+    let Some(storage) = DATA_STORAGE.get() else {
+        return DataServerResponse::Error {
+            callback_id,
+            error: FundForgeError::ServerErrorDebug("Data storage is not initialized".to_string())
+        }
+    };
+    match storage.scan(&symbols, &base_data_type, &resolution, &conditions, time).await {
+        Ok(matches) => DataServerResponse::Scan {
+            callback_id,
+            matches,
+        },
+        Err(error) => DataServerResponse::Error {
+            callback_id,
+            error,
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use base64::engine::general_purpose;
+use base64::Engine;
+use once_cell::sync::OnceCell;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_derive::{Deserialize, Serialize};
+use ff_standard_lib::messages::data_server_messaging::FundForgeError;
+
+/// The environment variable holding the passphrase for the store.
+pub const SECRETS_KEY_ENV: &str = "FUND_FORGE_SECRETS_KEY";
+const SECRETS_FILE: &str = "secrets.ffsecrets";
+const STORE_VERSION: u8 = 1;
+const PBKDF2_ITERATIONS: u32 = 600_000;
+const SALT_LEN: usize = 16;
+
+static SECRETS: OnceCell<SecretsStore> = OnceCell::new();
+
+/// The encrypted file format, the binary fields are base64.
+#[derive(Serialize, Deserialize)]
+struct SealedStore {
+    version: u8,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// The decrypted credentials, keyed by `{vendor}_credentials/{file_name}`, the values are the toml file contents.
+#[derive(Default, Debug, PartialEq)]
+pub struct SecretsStore {
+    entries: BTreeMap<String, String>,
+}
+
+fn secrets_error(message: impl Into<String>) -> FundForgeError {
+    FundForgeError::ServerErrorDebug(message.into())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<LessSafeKey, FundForgeError> {
+    let iterations = NonZeroU32::new(iterations).ok_or_else(|| secrets_error("Secrets store has an invalid iteration count"))?;
+    let mut key = [0u8; 32];
+    pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, iterations, salt, passphrase.as_bytes(), &mut key);
+    let key = UnboundKey::new(&CHACHA20_POLY1305, &key).map_err(|_| secrets_error("Failed to create secrets key"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+impl SecretsStore {
+    pub fn path(data_folder: &Path) -> PathBuf {
+        data_folder.join("credentials").join(SECRETS_FILE)
+    }
+
+    /// Opens the store, a missing file is an empty store.
+    pub fn open(path: &Path, passphrase: &str) -> Result<Self, FundForgeError> {
+        if !path.exists() {
+            return Ok(SecretsStore::default());
+        }
+        let contents = fs::read_to_string(path).map_err(|e| secrets_error(format!("Failed to read secrets store: {}", e)))?;
+        let sealed: SealedStore = toml::from_str(&contents).map_err(|e| secrets_error(format!("Failed to parse secrets store: {}", e)))?;
+        SecretsStore::unseal(&sealed, passphrase)
+    }
+
+    /// Encrypts the store with a new salt and nonce and replaces the file.
+    pub fn save(&self, path: &Path, passphrase: &str) -> Result<(), FundForgeError> {
+        let sealed = self.seal(passphrase, PBKDF2_ITERATIONS)?;
+        let contents = toml::to_string(&sealed).map_err(|e| secrets_error(format!("Failed to serialize secrets store: {}", e)))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| secrets_error(format!("Failed to create credentials folder: {}", e)))?;
+        }
+        // Write to a temporary file first so a failed write can not corrupt the existing store
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, contents).map_err(|e| secrets_error(format!("Failed to write secrets store: {}", e)))?;
+        fs::rename(&temp_path, path).map_err(|e| secrets_error(format!("Failed to replace secrets store: {}", e)))
+    }
+
+    fn seal(&self, passphrase: &str, iterations: u32) -> Result<SealedStore, FundForgeError> {
+        let random = SystemRandom::new();
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        random.fill(&mut salt).map_err(|_| secrets_error("Failed to generate salt"))?;
+        random.fill(&mut nonce).map_err(|_| secrets_error("Failed to generate nonce"))?;
+
+        let mut data = toml::to_string(&self.entries).map_err(|e| secrets_error(format!("Failed to serialize secrets: {}", e)))?.into_bytes();
+        derive_key(passphrase, &salt, iterations)?
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from([STORE_VERSION]), &mut data)
+            .map_err(|_| secrets_error("Failed to encrypt secrets"))?;
+
+        Ok(SealedStore {
+            version: STORE_VERSION,
+            iterations,
+            salt: general_purpose::STANDARD.encode(salt),
+            nonce: general_purpose::STANDARD.encode(nonce),
+            ciphertext: general_purpose::STANDARD.encode(data),
+        })
+    }
+
+    fn unseal(sealed: &SealedStore, passphrase: &str) -> Result<Self, FundForgeError> {
+        if sealed.version != STORE_VERSION {
+            return Err(secrets_error(format!("Unsupported secrets store version: {}", sealed.version)));
+        }
+        let decode = |value: &str| general_purpose::STANDARD.decode(value).map_err(|_| secrets_error("Secrets store is corrupt"));
+        let salt = decode(&sealed.salt)?;
+        let nonce: [u8; NONCE_LEN] = decode(&sealed.nonce)?.try_into().map_err(|_| secrets_error("Secrets store is corrupt"))?;
+        let mut data = decode(&sealed.ciphertext)?;
+
+        let plaintext = derive_key(passphrase, &salt, sealed.iterations)?
+            .open_in_place(Nonce::assume_unique_for_key(nonce), Aad::from([sealed.version]), &mut data)
+            .map_err(|_| FundForgeError::InvalidApiKey)?;
+        let plaintext = std::str::from_utf8(plaintext).map_err(|_| secrets_error("Secrets store is corrupt"))?;
+        let entries = toml::from_str(plaintext).map_err(|e| secrets_error(format!("Failed to parse secrets: {}", e)))?;
+        Ok(SecretsStore { entries })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.entries.get(name)
+    }
+
+    /// Adds or replaces the credentials, the contents must be valid toml.
+    pub fn insert(&mut self, name: String, contents: String) -> Result<(), FundForgeError> {
+        if name.split('/').count() != 2 || !name.ends_with(".toml") {
+            return Err(secrets_error(format!("Credential names are `{{vendor}}_credentials/{{file_name}}.toml`, found: {}", name)));
+        }
+        contents.parse::<toml::Table>().map_err(|e| secrets_error(format!("Credentials for {} are not valid toml: {}", name, e)))?;
+        self.entries.insert(name, contents);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.entries.remove(name).is_some()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.entries.keys()
+    }
+}
+
+/// Opens the secrets store if it exists, call this before the vendors are initialized.
+/// If the store exists and the passphrase is missing or wrong, the vendors fall back to the plaintext files.
+pub fn init_secrets(data_folder: &Path) {
+    let path = SecretsStore::path(data_folder);
+    if !path.exists() {
+        return;
+    }
+    let passphrase = match std::env::var(SECRETS_KEY_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => {
+            eprintln!("Secrets store found but {} is not set, using plaintext credentials only", SECRETS_KEY_ENV);
+            return;
+        }
+    };
+    match SecretsStore::open(&path, &passphrase) {
+        Ok(store) => {
+            println!("Secrets: Loaded {} credentials", store.entries.len());
+            let _ = SECRETS.set(store);
+        }
+        Err(FundForgeError::InvalidApiKey) => eprintln!("Secrets store could not be decrypted, check {}", SECRETS_KEY_ENV),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+/// Returns the credentials toml for `credentials/{vendor_dir}/active/{file_name}`, from the secrets store if it is there, otherwise the plaintext file,
+/// with any environment overrides applied.
+pub fn load_credentials(data_folder: &Path, vendor_dir: &str, file_name: &str) -> Option<String> {
+    let name = format!("{}/{}", vendor_dir, file_name);
+    let contents = match SECRETS.get().and_then(|store| store.get(&name)) {
+        Some(contents) => contents.clone(),
+        None => fs::read_to_string(data_folder.join("credentials").join(vendor_dir).join("active").join(file_name)).ok()?,
+    };
+    match apply_env_overrides(vendor_dir, file_name, &contents, std::env::vars()) {
+        Ok(contents) => Some(contents),
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    }
+}
+
+/// The credentials file names for a vendor with more than one file, eg one per Rithmic system, from both the secrets store and the active folder.
+pub fn credential_files(data_folder: &Path, vendor_dir: &str) -> Vec<String> {
+    let mut files: Vec<String> = match SECRETS.get() {
+        Some(store) => store.names()
+            .filter_map(|name| name.strip_prefix(&format!("{}/", vendor_dir)).map(|file| file.to_string()))
+            .collect(),
+        None => vec![],
+    };
+    if let Ok(entries) = fs::read_dir(data_folder.join("credentials").join(vendor_dir).join("active")) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("toml") {
+                if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
+                    files.push(file_name.to_string());
+                }
+            }
+        }
+    }
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// The environment variable prefix for a credentials file, `FF_{VENDOR}_`, or `FF_{VENDOR}_{FILE_STEM}_` when the file is not named after the vendor.
+fn env_prefix(vendor_dir: &str, file_name: &str) -> String {
+    let vendor = vendor_dir.trim_end_matches("_credentials");
+    let file_stem = file_name.trim_end_matches(".toml");
+    match file_stem == vendor_dir {
+        true => format!("FF_{}_", vendor.to_uppercase()),
+        false => format!("FF_{}_{}_", vendor.to_uppercase(), file_stem.to_uppercase()),
+    }
+}
+
+/// Replaces the fields of the toml with the matching environment variables, keeping the type of existing boolean and number fields.
+fn apply_env_overrides(vendor_dir: &str, file_name: &str, contents: &str, vars: impl Iterator<Item = (String, String)>) -> Result<String, FundForgeError> {
+    let prefix = env_prefix(vendor_dir, file_name);
+    let mut overrides = vars.filter_map(|(key, value)| key.strip_prefix(&prefix).map(|field| (field.to_lowercase(), value))).peekable();
+    if overrides.peek().is_none() {
+        return Ok(contents.to_string());
+    }
+
+    let mut table: toml::Table = contents.parse()
+        .map_err(|e| secrets_error(format!("Credentials for {}/{} are not valid toml: {}", vendor_dir, file_name, e)))?;
+    for (field, value) in overrides {
+        let value = match table.get(&field) {
+            Some(toml::Value::Boolean(_)) => value.parse().map(toml::Value::Boolean).ok(),
+            Some(toml::Value::Integer(_)) => value.parse().map(toml::Value::Integer).ok(),
+            Some(toml::Value::Float(_)) => value.parse().map(toml::Value::Float).ok(),
+            _ => Some(toml::Value::String(value)),
+        };
+        match value {
+            Some(value) => {
+                table.insert(field, value);
+            }
+            None => return Err(secrets_error(format!("Invalid value for {}{}", prefix, field.to_uppercase()))),
+        }
+    }
+    toml::to_string(&table).map_err(|e| secrets_error(format!("Failed to serialize credentials: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_round_trip() {
+        let mut store = SecretsStore::default();
+        store.insert("oanda_credentials/oanda_credentials.toml".to_string(), "api_key = \"abc\"\nmode = \"Practice\"\n".to_string()).unwrap();
+        assert!(store.insert("oanda_credentials.toml".to_string(), "api_key = \"abc\"".to_string()).is_err());
+        assert!(store.insert("oanda_credentials/oanda_credentials.toml".to_string(), "api_key = ".to_string()).is_err());
+
+        let sealed = store.seal("passphrase", 1000).unwrap();
+        assert!(!sealed.ciphertext.contains("abc"));
+        assert_eq!(SecretsStore::unseal(&sealed, "passphrase").unwrap(), store);
+        assert!(matches!(SecretsStore::unseal(&sealed, "wrong"), Err(FundForgeError::InvalidApiKey)));
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let contents = "api_key = \"file_key\"\nmode = \"Practice\"\nsubscribe_data = true\n";
+        let vars = vec![
+            ("FF_OANDA_API_KEY".to_string(), "env_key".to_string()),
+            ("FF_BITGET_API_KEY".to_string(), "other".to_string()),
+        ];
+        let table: toml::Table = apply_env_overrides("oanda_credentials", "oanda_credentials.toml", contents, vars.into_iter()).unwrap().parse().unwrap();
+        assert_eq!(table["api_key"].as_str(), Some("env_key"));
+        assert_eq!(table["mode"].as_str(), Some("Practice"));
+
+        let vars = vec![("FF_RITHMIC_APEX_SUBSCRIBE_DATA".to_string(), "false".to_string())];
+        let table: toml::Table = apply_env_overrides("rithmic_credentials", "Apex.toml", contents, vars.into_iter()).unwrap().parse().unwrap();
+        assert_eq!(table["subscribe_data"].as_bool(), Some(false));
+
+        let vars = vec![("FF_RITHMIC_APEX_SUBSCRIBE_DATA".to_string(), "maybe".to_string())];
+        assert!(apply_env_overrides("rithmic_credentials", "Apex.toml", contents, vars.into_iter()).is_err());
+    }
+}
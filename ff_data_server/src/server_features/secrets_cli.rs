@@ -0,0 +1,141 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+use ff_standard_lib::messages::data_server_messaging::FundForgeError;
+use crate::server_features::secrets::{SecretsStore, SECRETS_KEY_ENV};
+
+/// The environment variable holding the new passphrase for `rotate-key`.
+const SECRETS_NEW_KEY_ENV: &str = "FUND_FORGE_SECRETS_NEW_KEY";
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "secrets", about = "Manage the encrypted vendor credentials store")]
+pub struct SecretsCli {
+    /// The data folder containing the credentials folder
+    #[structopt(long = "data_folder", default_value = "./data", parse(from_os_str))]
+    pub data_folder: PathBuf,
+
+    #[structopt(subcommand)]
+    pub command: SecretsCommand,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum SecretsCommand {
+    /// List the names of the stored credentials, the contents are never printed
+    List,
+    /// Add or replace credentials, eg `oanda_credentials/oanda_credentials.toml`, the toml is read from --file or stdin
+    Set {
+        name: String,
+        #[structopt(long, parse(from_os_str))]
+        file: Option<PathBuf>,
+    },
+    /// Remove stored credentials
+    Remove {
+        name: String,
+    },
+    /// Import every plaintext file in the `credentials/*/active` folders
+    Import {
+        /// Delete the plaintext files once the store is saved
+        #[structopt(long)]
+        delete: bool,
+    },
+    /// Re-encrypt the store with a new passphrase from `FUND_FORGE_SECRETS_NEW_KEY` or a prompt
+    RotateKey,
+}
+
+fn passphrase(env_var: &str, message: &str) -> Result<String, FundForgeError> {
+    let passphrase = match std::env::var(env_var) {
+        Ok(passphrase) => passphrase,
+        // Read from the terminal without echoing the passphrase.
+        Err(_) => rpassword::prompt_password(format!("{}: ", message)).map_err(|e| FundForgeError::ServerErrorDebug(e.to_string()))?,
+    };
+    match passphrase.is_empty() {
+        true => Err(FundForgeError::ClientSideErrorDebug("The secrets passphrase can not be empty".to_string())),
+        false => Ok(passphrase),
+    }
+}
+
+/// The plaintext credential files as (store name, path).
+fn plaintext_files(credentials_folder: &Path) -> Vec<(String, PathBuf)> {
+    let mut files = vec![];
+    let Ok(vendor_dirs) = fs::read_dir(credentials_folder) else {
+        return files;
+    };
+    for vendor_dir in vendor_dirs.flatten() {
+        let vendor_name = vendor_dir.file_name().to_string_lossy().to_string();
+        let Ok(entries) = fs::read_dir(vendor_dir.path().join("active")) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("toml") {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                files.push((format!("{}/{}", vendor_name, file_name), path));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+impl SecretsCli {
+    pub fn run(self) -> Result<(), FundForgeError> {
+        let path = SecretsStore::path(&self.data_folder);
+        let key = passphrase(SECRETS_KEY_ENV, "Secrets passphrase")?;
+        let mut store = SecretsStore::open(&path, &key).map_err(|e| match e {
+            FundForgeError::InvalidApiKey => FundForgeError::ClientSideErrorDebug("The secrets passphrase is incorrect".to_string()),
+            e => e,
+        })?;
+
+        match self.command {
+            SecretsCommand::List => {
+                for name in store.names() {
+                    println!("{}", name);
+                }
+                return Ok(());
+            }
+            SecretsCommand::Set { name, file } => {
+                let contents = match file {
+                    Some(file) => fs::read_to_string(&file).map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to read {:?}: {}", file, e)))?,
+                    None => {
+                        let mut contents = String::new();
+                        io::stdin().read_to_string(&mut contents).map_err(|e| FundForgeError::ClientSideErrorDebug(e.to_string()))?;
+                        contents
+                    }
+                };
+                store.insert(name.clone(), contents)?;
+                store.save(&path, &key)?;
+                println!("Saved: {}", name);
+            }
+            SecretsCommand::Remove { name } => {
+                if !store.remove(&name) {
+                    return Err(FundForgeError::ClientSideErrorDebug(format!("No stored credentials named: {}", name)));
+                }
+                store.save(&path, &key)?;
+                println!("Removed: {}", name);
+            }
+            SecretsCommand::Import { delete } => {
+                let files = plaintext_files(&self.data_folder.join("credentials"));
+                for (name, file) in &files {
+                    let contents = fs::read_to_string(file).map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to read {:?}: {}", file, e)))?;
+                    store.insert(name.clone(), contents)?;
+                    println!("Imported: {}", name);
+                }
+                store.save(&path, &key)?;
+                if delete {
+                    for (_, file) in &files {
+                        if let Err(e) = fs::remove_file(file) {
+                            eprintln!("Failed to delete {:?}: {}", file, e);
+                        }
+                    }
+                }
+            }
+            SecretsCommand::RotateKey => {
+                let new_key = passphrase(SECRETS_NEW_KEY_ENV, "New secrets passphrase")?;
+                store.save(&path, &new_key)?;
+                println!("Secrets store re-encrypted, update {} to the new passphrase", SECRETS_KEY_ENV);
+            }
+        }
+        Ok(())
+    }
+}
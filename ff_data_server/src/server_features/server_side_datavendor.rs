@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use indicatif::{ProgressBar};
 use ff_standard_lib::messages::data_server_messaging::{DataServerResponse, FundForgeError};
 use ff_standard_lib::standardized_types::base_data::base_data_type::BaseDataType;
@@ -228,4 +228,27 @@ pub trait VendorApiResponse: Sync + Send {
         from_back: bool,
         progress_bar: ProgressBar,
     ) -> Result<(), FundForgeError>;
+
+    /// return `DataServerResponse::OptionChain` or `DataServerResponse::Error(FundForgeError)`
+    /// `FundForgeError::ServerSideErrorDebug` or `FundForgeError::ClientSideErrorDebug` depending on who caused this problem.
+    ///
+    /// Returns every contract for the `underlying` expiring on `expiry`, or all expiries if `expiry` is None.
+    /// The greeks are passed through as supplied by the vendor, leave them as None if the vendor does not supply them.
+    ///
+    /// Vendors without options data can use the default implementation.
+    async fn option_chain_response(
+        &self,
+        _mode: StrategyMode,
+        // The `stream_name` is just the u16 port number of the strategy which the server is connecting to,
+        // it is used to link the streaming port to a async port, you just need to know it represents a single strategy instance.
+        _stream_name: StreamName,
+        underlying: SymbolName,
+        _expiry: Option<NaiveDate>,
+        callback_id: u64
+    ) -> DataServerResponse {
+        DataServerResponse::Error {
+            callback_id,
+            error: FundForgeError::ClientSideErrorDebug(format!("Option chains are not supported by this vendor: {}", underlying))
+        }
+    }
 }
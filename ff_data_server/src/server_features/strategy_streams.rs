@@ -0,0 +1,32 @@
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use ff_standard_lib::messages::data_server_messaging::{DataServerResponse, StrategyStreamInfo};
+use ff_standard_lib::StreamName;
+
+lazy_static! {
+    static ref STRATEGY_STREAMS: DashMap<StreamName, StrategyStreamInfo> = DashMap::new();
+}
+
+/// Registers the control endpoint a strategy announced after `strategy.serve_controls()`, used by `ff_ctl` to find the running strategies.
+pub(crate) fn register_strategy_stream(stream_name: StreamName, mut info: StrategyStreamInfo) {
+    info.stream_name = stream_name;
+    STRATEGY_STREAMS.insert(stream_name, info);
+}
+
+pub(crate) fn deregister_strategy_stream(stream_name: &StreamName) {
+    STRATEGY_STREAMS.remove(stream_name);
+}
+
+/// The name of the strategy connected as `stream_name`, None if it does not serve controls.
+pub(crate) fn strategy_name(stream_name: &StreamName) -> Option<String> {
+    STRATEGY_STREAMS.get(stream_name).map(|info| info.name.clone())
+}
+
+pub(crate) async fn strategy_streams_response(callback_id: u64) -> DataServerResponse {
+    let mut streams: Vec<StrategyStreamInfo> = STRATEGY_STREAMS.iter().map(|entry| entry.value().clone()).collect();
+    streams.sort_by_key(|info| info.stream_name);
+    DataServerResponse::StrategyStreams {
+        callback_id,
+        streams,
+    }
+}
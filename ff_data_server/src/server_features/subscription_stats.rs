@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use ff_standard_lib::messages::data_server_messaging::{DataServerResponse, StreamRequest};
+use ff_standard_lib::standardized_types::subscriptions::DataSubscription;
+use ff_standard_lib::StreamName;
+
+/// The subscribes of a subscription already held before a warning.
+const DUPLICATE_WARNING: u64 = 3;
+/// The active subscriptions of a strategy before the first warning.
+const ACTIVE_WARNING: usize = 25;
+
+struct ActiveSubscription {
+    since: DateTime<Utc>,
+    subscribes: u64,
+}
+
+struct SubscriptionStats {
+    connected: DateTime<Utc>,
+    subscribes: u64,
+    unsubscribes: u64,
+    refused: u64,
+    /// The subscriptions which became active, a subscribe of an active subscription does not start a new one.
+    lifetimes: u64,
+    active: HashMap<DataSubscription, ActiveSubscription>,
+    /// The total time the unsubscribed subscriptions were active.
+    closed_duration: Duration,
+    next_active_warning: usize,
+}
+
+impl SubscriptionStats {
+    fn new(time: DateTime<Utc>) -> Self {
+        SubscriptionStats {
+            connected: time,
+            subscribes: 0,
+            unsubscribes: 0,
+            refused: 0,
+            lifetimes: 0,
+            active: HashMap::new(),
+            closed_duration: Duration::zero(),
+            next_active_warning: ACTIVE_WARNING,
+        }
+    }
+
+    /// Records a successful subscribe and returns the leak warnings it raised.
+    fn subscribed(&mut self, subscription: &DataSubscription, time: DateTime<Utc>) -> Vec<String> {
+        self.subscribes += 1;
+        let mut warnings = vec![];
+        let active = self.active.entry(subscription.clone()).or_insert_with(|| {
+            self.lifetimes += 1;
+            ActiveSubscription { since: time, subscribes: 0 }
+        });
+        active.subscribes += 1;
+        if active.subscribes == DUPLICATE_WARNING {
+            warnings.push(format!("subscribed to {} {} times without unsubscribing", subscription, active.subscribes));
+        }
+        if self.active.len() >= self.next_active_warning {
+            warnings.push(format!("holds {} active subscriptions, {} subscribes and {} unsubscribes", self.active.len(), self.subscribes, self.unsubscribes));
+            self.next_active_warning *= 2;
+        }
+        warnings
+    }
+
+    fn unsubscribed(&mut self, subscription: &DataSubscription, time: DateTime<Utc>) {
+        self.unsubscribes += 1;
+        if let Some(active) = self.active.remove(subscription) {
+            self.closed_duration = self.closed_duration + (time - active.since);
+        }
+    }
+
+    fn summary(&self, time: DateTime<Utc>) -> String {
+        let active_duration = self.active.values().fold(self.closed_duration, |total, active| total + (time - active.since));
+        let average = match self.lifetimes {
+            0 => 0,
+            n => active_duration.num_seconds() / n as i64,
+        };
+        format!(
+            "{} subscribes, {} unsubscribes, {} refused, {} still active, average active time {}s over {}s connected",
+            self.subscribes, self.unsubscribes, self.refused, self.active.len(), average, (time - self.connected).num_seconds()
+        )
+    }
+}
+
+lazy_static! {
+    static ref SUBSCRIPTION_STATS: DashMap<StreamName, SubscriptionStats> = DashMap::new();
+}
+
+/// Records the outcome of a stream request of a strategy and prints the leak warnings.
+pub(crate) fn record_stream_response(stream_name: StreamName, request: &StreamRequest, response: &DataServerResponse) {
+    let time = Utc::now();
+    let mut stats = SUBSCRIPTION_STATS.entry(stream_name).or_insert_with(|| SubscriptionStats::new(time));
+    match (request, response) {
+        (StreamRequest::Subscribe(subscription), DataServerResponse::SubscribeResponse { success: true, .. }) => {
+            for warning in stats.subscribed(subscription, time) {
+                eprintln!("Subscriptions: Possible subscription leak, strategy {} {}", stream_name, warning);
+            }
+        }
+        (StreamRequest::Subscribe(_), _) => stats.refused += 1,
+        (StreamRequest::Unsubscribe(subscription), DataServerResponse::UnSubscribeResponse { success: true, .. }) => stats.unsubscribed(subscription, time),
+        (StreamRequest::Unsubscribe(_), _) => {}
+    }
+}
+
+/// Prints and forgets the statistics of a disconnected strategy.
+pub(crate) fn report_subscription_stats(stream_name: &StreamName) {
+    if let Some((_, stats)) = SUBSCRIPTION_STATS.remove(stream_name) {
+        println!("Subscriptions: Strategy {} disconnected, {}", stream_name, stats.summary(Utc::now()));
+    }
+}
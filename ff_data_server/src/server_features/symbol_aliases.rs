@@ -0,0 +1,36 @@
+use std::fs;
+use std::path::Path;
+use serde_derive::Deserialize;
+use ff_standard_lib::messages::data_server_messaging::DataServerResponse;
+use ff_standard_lib::standardized_types::symbol_aliases::{register_symbol_alias, symbol_aliases, SymbolAlias};
+
+#[derive(Deserialize, Default)]
+struct SymbolAliasesFile {
+    #[serde(default)]
+    aliases: Vec<SymbolAlias>,
+}
+
+/// Registers the vendor symbol aliases in `symbol_aliases.toml` of the data folder.
+pub fn init_symbol_aliases(data_folder: &Path) {
+    let path = data_folder.join("symbol_aliases.toml");
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return;
+    };
+    match toml::from_str::<SymbolAliasesFile>(&contents) {
+        Ok(file) => {
+            let count = file.aliases.len();
+            for alias in file.aliases {
+                register_symbol_alias(alias);
+            }
+            println!("Symbol Aliases: Registered {} aliases", count);
+        }
+        Err(e) => eprintln!("Symbol Aliases: Error parsing {:?}: {}", path, e),
+    }
+}
+
+pub(crate) async fn symbol_aliases_response(callback_id: u64) -> DataServerResponse {
+    DataServerResponse::SymbolAliases {
+        callback_id,
+        aliases: symbol_aliases(),
+    }
+}
@@ -0,0 +1,222 @@
+use std::path::Path;
+use std::time::Duration;
+use chrono::{NaiveDate, Utc};
+use dashmap::DashMap;
+use hmac::{Hmac, Mac};
+use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
+use rust_decimal::Decimal;
+use serde_derive::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tokio::sync::mpsc;
+use ff_standard_lib::messages::data_server_messaging::DataServerResponse;
+use ff_standard_lib::standardized_types::accounts::Account;
+use ff_standard_lib::standardized_types::enums::PositionSide;
+use ff_standard_lib::standardized_types::orders::OrderUpdateEvent;
+use ff_standard_lib::standardized_types::subscriptions::SymbolName;
+use crate::server_features::secrets::load_credentials;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SIGNATURE_HEADER: &str = "X-FundForge-Signature";
+const TIMESTAMP_HEADER: &str = "X-FundForge-Timestamp";
+const QUEUE_CAPACITY: usize = 1024;
+const ATTEMPTS: u32 = 3;
+
+#[derive(Deserialize)]
+struct WebhookSettings {
+    urls: Vec<String>,
+    secret: String,
+    daily_loss_threshold: Option<Decimal>,
+    #[serde(default = "default_timeout_seconds")]
+    timeout_seconds: u64,
+}
+
+fn default_timeout_seconds() -> u64 {
+    5
+}
+
+/// The first cash value of the account on the UTC day and whether the loss threshold was crossed.
+struct DayStart {
+    date: NaiveDate,
+    cash_value: Decimal,
+    crossed: bool,
+}
+
+static WEBHOOKS: OnceCell<(mpsc::Sender<Value>, Option<Decimal>)> = OnceCell::new();
+
+lazy_static! {
+    static ref OPEN_POSITIONS: DashMap<(Account, SymbolName), (f64, PositionSide, f64)> = DashMap::new();
+    static ref DAY_STARTS: DashMap<Account, DayStart> = DashMap::new();
+}
+
+/// Reads the webhook settings like vendor credentials, webhooks are off when there is no settings file.
+/// The events are posted in order by a single task and dropped with a warning when the queue is full, so a slow endpoint never blocks the brokers or the strategies.
+pub fn init_webhooks(data_folder: &Path) {
+    let Some(contents) = load_credentials(data_folder, "webhook_credentials", "webhooks.toml") else {
+        return;
+    };
+    let settings = match toml::from_str::<WebhookSettings>(&contents) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Webhooks: Error parsing webhooks.toml: {}", e);
+            return;
+        }
+    };
+    if settings.urls.is_empty() {
+        return;
+    }
+    let client = match reqwest::Client::builder().timeout(Duration::from_secs(settings.timeout_seconds.max(1))).build() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Webhooks: Failed to create http client: {}", e);
+            return;
+        }
+    };
+    let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+    if WEBHOOKS.set((sender, settings.daily_loss_threshold)).is_err() {
+        return;
+    }
+    println!("Webhooks: Posting account events to {} url(s)", settings.urls.len());
+    tokio::spawn(deliver_webhooks(client, settings.urls, settings.secret, receiver));
+}
+
+/// Sends the webhook for a response the brokers send to the strategies, if it is a fill, a position close or a daily loss crossing.
+pub(crate) fn notify_webhooks(response: &DataServerResponse) {
+    let Some((_, daily_loss_threshold)) = WEBHOOKS.get() else {
+        return;
+    };
+    let payload = match response {
+        DataServerResponse::OrderUpdates { event, .. } => fill_payload(event),
+        DataServerResponse::LivePositionUpdates { symbol_name, symbol_code, account, open_quantity, average_price, side, time, .. } => {
+            let key = (account.clone(), symbol_name.clone());
+            let previous = match *open_quantity == 0.0 {
+                true => OPEN_POSITIONS.remove(&key).map(|(_, previous)| previous),
+                false => {
+                    OPEN_POSITIONS.insert(key, (*open_quantity, *side, *average_price));
+                    None
+                }
+            };
+            previous.map(|(quantity, side, average_price)| json!({
+                "event": "PositionClosed",
+                "account": account,
+                "symbol_name": symbol_name,
+                "symbol_code": symbol_code,
+                "side": side,
+                "quantity": quantity,
+                "average_price": average_price,
+                "time": time,
+            }))
+        }
+        DataServerResponse::LiveAccountUpdates { account, cash_value, .. } => match daily_loss_threshold {
+            Some(threshold) => daily_loss_payload(account, *cash_value, *threshold),
+            None => None,
+        },
+        _ => None,
+    };
+    if let Some(payload) = payload {
+        queue(payload);
+    }
+}
+
+/// Sends the `Reconnected` webhook for a restored connection, eg "Rithmic Apex OrderPlant".
+pub(crate) fn notify_reconnected(connection: String) {
+    if WEBHOOKS.get().is_none() {
+        return;
+    }
+    queue(json!({
+        "event": "Reconnected",
+        "connection": connection,
+        "time": Utc::now().to_string(),
+    }));
+}
+
+fn fill_payload(event: &OrderUpdateEvent) -> Option<Value> {
+    let (partial, account, symbol_name, symbol_code, order_id, side, price, quantity, tag, time, fill_id, liquidity, commission) = match event {
+        OrderUpdateEvent::OrderFilled { account, symbol_name, symbol_code, order_id, side, price, quantity, tag, time, fill_id, liquidity, commission } =>
+            (false, account, symbol_name, symbol_code, order_id, side, price, quantity, tag, time, fill_id, liquidity, commission),
+        OrderUpdateEvent::OrderPartiallyFilled { account, symbol_name, symbol_code, order_id, side, price, quantity, tag, time, fill_id, liquidity, commission } =>
+            (true, account, symbol_name, symbol_code, order_id, side, price, quantity, tag, time, fill_id, liquidity, commission),
+        _ => return None,
+    };
+    Some(json!({
+        "event": "Fill",
+        "partial": partial,
+        "account": account,
+        "symbol_name": symbol_name,
+        "symbol_code": symbol_code,
+        "order_id": order_id,
+        "side": side,
+        "price": price.to_string(),
+        "quantity": quantity.to_string(),
+        "tag": tag,
+        "fill_id": fill_id,
+        "liquidity": liquidity,
+        "commission": commission.map(|commission| commission.to_string()),
+        "time": time,
+    }))
+}
+
+fn daily_loss_payload(account: &Account, cash_value: Decimal, threshold: Decimal) -> Option<Value> {
+    let today = Utc::now().date_naive();
+    let mut day_start = DAY_STARTS.entry(account.clone()).or_insert(DayStart { date: today, cash_value, crossed: false });
+    if day_start.date != today {
+        *day_start = DayStart { date: today, cash_value, crossed: false };
+    }
+    let loss = day_start.cash_value - cash_value;
+    if day_start.crossed || loss < threshold {
+        return None;
+    }
+    day_start.crossed = true;
+    Some(json!({
+        "event": "DailyLossThreshold",
+        "account": account,
+        "day_start_cash_value": day_start.cash_value.to_string(),
+        "cash_value": cash_value.to_string(),
+        "loss": loss.to_string(),
+        "threshold": threshold.to_string(),
+        "time": Utc::now().to_string(),
+    }))
+}
+
+fn queue(mut payload: Value) {
+    let Some((sender, _)) = WEBHOOKS.get() else {
+        return;
+    };
+    payload["id"] = Value::String(uuid::Uuid::new_v4().to_string());
+    if let Err(e) = sender.try_send(payload) {
+        eprintln!("Webhooks: Dropped event, the delivery queue is full or closed: {}", e);
+    }
+}
+
+/// The hex HMAC-SHA256 of `{timestamp}.{body}`.
+fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    mac.finalize().into_bytes().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+async fn deliver_webhooks(client: reqwest::Client, urls: Vec<String>, secret: String, mut receiver: mpsc::Receiver<Value>) {
+    while let Some(payload) = receiver.recv().await {
+        let body = payload.to_string();
+        for url in &urls {
+            for attempt in 1..=ATTEMPTS {
+                let timestamp = Utc::now().timestamp();
+                let result = client.post(url)
+                    .header("Content-Type", "application/json")
+                    .header(TIMESTAMP_HEADER, timestamp.to_string())
+                    .header(SIGNATURE_HEADER, format!("sha256={}", signature(&secret, timestamp, &body)))
+                    .body(body.clone())
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status());
+                match result {
+                    Ok(_) => break,
+                    Err(e) if attempt == ATTEMPTS => eprintln!("Webhooks: Failed to post {} to {}: {}", payload["event"], url, e),
+                    Err(_) => tokio::time::sleep(Duration::from_secs(attempt as u64)).await,
+                }
+            }
+        }
+    }
+}
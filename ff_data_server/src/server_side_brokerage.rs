@@ -15,6 +15,7 @@ use tokio::time::{timeout, Duration};
 use ff_standard_lib::standardized_types::datavendor_enum::DataVendor;
 use ff_standard_lib::standardized_types::orders::OrderUpdateEvent::OrderUpdateRejected;
 use crate::oanda_api::api_client::{get_oanda_client, OANDA_CLIENT};
+use crate::server_features::rest_brokerage::get_rest_brokerage;
 use crate::update_functions::DATA_STORAGE;
 
 pub const TIMEOUT_DURATION: Duration = Duration::from_secs(10);
@@ -61,6 +62,9 @@ pub async fn commission_info_response(mode: StrategyMode, brokerage: Brokerage,
             Brokerage::Oanda => if let Some(client) = OANDA_CLIENT.get() {
                 return client.commission_info_response(mode, stream_name, symbol_name, callback_id).await
             }
+            _ => if let Some(client) = get_rest_brokerage(&brokerage) {
+                return client.commission_info_response(mode, stream_name, symbol_name, callback_id).await
+            }
         }
         DataServerResponse::Error{ callback_id, error: FundForgeError::ServerErrorDebug(format!("Unable to find api client instance for: {}", brokerage))}
     };
@@ -132,6 +136,9 @@ pub async fn symbol_names_response(
             Brokerage::Oanda => if let Some(client) = OANDA_CLIENT.get() {
                 return client.symbol_names_response(mode, time, stream_name, callback_id).await
             }
+            _ => if let Some(client) = get_rest_brokerage(&brokerage) {
+                return client.symbol_names_response(mode, time, stream_name, callback_id).await
+            }
         }
         DataServerResponse::Error{ callback_id, error: FundForgeError::ServerErrorDebug(format!("Unable to find api client instance for: {}", brokerage))}
     };
@@ -164,6 +171,9 @@ pub async fn account_info_response(
             Brokerage::Oanda => if let Some(client) = OANDA_CLIENT.get() {
                 return client.account_info_response(mode, stream_name, account_id, callback_id).await
             }
+            _ => if let Some(client) = get_rest_brokerage(&brokerage) {
+                return client.account_info_response(mode, stream_name, account_id, callback_id).await
+            }
         }
         DataServerResponse::Error{ callback_id, error: FundForgeError::ServerErrorDebug(format!("Unable to find api client instance for: {}", brokerage))}
     };
@@ -188,6 +198,9 @@ pub async fn symbol_info_response(brokerage: Brokerage, mode: StrategyMode, stre
             Brokerage::Oanda => if let Some(client) = get_oanda_client() {
                 return client.symbol_info_response(mode, stream_name, symbol_name, callback_id).await
             }
+            _ => if let Some(client) = get_rest_brokerage(&brokerage) {
+                return client.symbol_info_response(mode, stream_name, symbol_name, callback_id).await
+            }
         }
         DataServerResponse::Error{ callback_id, error: FundForgeError::ServerErrorDebug(format!("Unable to find api client instance for: {}", brokerage))}
     };
@@ -214,6 +227,9 @@ pub async fn accounts_response(brokerage: Brokerage, mode: StrategyMode, stream_
             Brokerage::Oanda => if let Some(client) = get_oanda_client() {
                 return client.accounts_response(mode, stream_name, callback_id).await
             },
+            _ => if let Some(client) = get_rest_brokerage(&brokerage) {
+                return client.accounts_response(mode, stream_name, callback_id).await
+            },
         }
         DataServerResponse::Error{ callback_id, error: FundForgeError::ServerErrorDebug(format!("Unable to find api client instance for: {}", brokerage))}
     };
@@ -240,6 +256,9 @@ pub async fn logout_command(brokerage: Brokerage, stream_name: StreamName) {
         Brokerage::Oanda => if let Some(client) = OANDA_CLIENT.get() {
             client.logout_command(stream_name).await
         },
+        _ => if let Some(client) = get_rest_brokerage(&brokerage) {
+            client.logout_command(stream_name).await
+        },
     }
 }
 
@@ -277,6 +296,10 @@ pub async fn live_market_order(stream_name: StreamName, mode: StrategyMode, orde
             Brokerage::Oanda => OANDA_CLIENT.get()
                 .ok_or_else(|| create_order_rejected(&order, "Oanda client not found".to_string()))?
                 .live_market_order(stream_name, mode, order.clone())
+                .await,
+            _ => get_rest_brokerage(&order.account.brokerage)
+                .ok_or_else(|| create_order_rejected(&order, format!("{} client not found", order.account.brokerage)))?
+                .live_market_order(stream_name, mode, order.clone())
                 .await
         }
     };
@@ -308,6 +331,10 @@ pub async fn live_enter_long(stream_name: StreamName, mode: StrategyMode, order:
             Brokerage::Oanda => OANDA_CLIENT.get()
                 .ok_or_else(|| create_order_rejected(&order, "Oanda client not found".to_string()))?
                 .live_enter_long(stream_name, mode, order.clone())
+                .await,
+            _ => get_rest_brokerage(&order.account.brokerage)
+                .ok_or_else(|| create_order_rejected(&order, format!("{} client not found", order.account.brokerage)))?
+                .live_enter_long(stream_name, mode, order.clone())
                 .await
         }
     };
@@ -339,6 +366,10 @@ pub async fn live_enter_short(stream_name: StreamName, mode: StrategyMode, order
             Brokerage::Oanda => OANDA_CLIENT.get()
                 .ok_or_else(|| create_order_rejected(&order, "Oanda client not found".to_string()))?
                 .live_enter_short(stream_name, mode, order.clone())
+                .await,
+            _ => get_rest_brokerage(&order.account.brokerage)
+                .ok_or_else(|| create_order_rejected(&order, format!("{} client not found", order.account.brokerage)))?
+                .live_enter_short(stream_name, mode, order.clone())
                 .await
 
         }
@@ -371,6 +402,10 @@ pub async fn live_exit_short(stream_name: StreamName, mode: StrategyMode, order:
             Brokerage::Oanda => OANDA_CLIENT.get()
                 .ok_or_else(|| create_order_rejected(&order, "Bitget client not found".to_string()))?
                 .live_exit_short(stream_name, mode, order.clone())
+                .await,
+            _ => get_rest_brokerage(&order.account.brokerage)
+                .ok_or_else(|| create_order_rejected(&order, format!("{} client not found", order.account.brokerage)))?
+                .live_exit_short(stream_name, mode, order.clone())
                 .await
         }
     };
@@ -403,6 +438,10 @@ pub async fn live_exit_long(stream_name: StreamName, mode: StrategyMode, order:
             Brokerage::Oanda => OANDA_CLIENT.get()
                 .ok_or_else(|| create_order_rejected(&order, "Bitget client not found".to_string()))?
                 .live_exit_long(stream_name, mode, order.clone())
+                .await,
+            _ => get_rest_brokerage(&order.account.brokerage)
+                .ok_or_else(|| create_order_rejected(&order, format!("{} client not found", order.account.brokerage)))?
+                .live_exit_long(stream_name, mode, order.clone())
                 .await
         }
     };
@@ -434,6 +473,10 @@ pub async fn other_orders(stream_name: StreamName, mode: StrategyMode, order: Or
             Brokerage::Oanda => OANDA_CLIENT.get()
                 .ok_or_else(|| create_order_rejected(&order, "Bitget client not found".to_string()))?
                 .other_orders(stream_name, mode, order.clone())
+                .await,
+            _ => get_rest_brokerage(&order.account.brokerage)
+                .ok_or_else(|| create_order_rejected(&order, format!("{} client not found", order.account.brokerage)))?
+                .other_orders(stream_name, mode, order.clone())
                 .await
         }
     };
@@ -462,6 +505,11 @@ pub async fn cancel_order(account: Account, order_id: OrderId) {
                 client.cancel_order(account, order_id).await;
             }
         }
+        _ => {
+            if let Some(client) = get_rest_brokerage(&account.brokerage) {
+                client.cancel_order(account, order_id).await;
+            }
+        }
     }
 }
 
@@ -483,6 +531,11 @@ pub async fn cancel_orders_on_account(account: Account) {
                 client.cancel_orders_on_account(account).await;
             }
         }
+        _ => {
+            if let Some(client) = get_rest_brokerage(&account.brokerage) {
+                client.cancel_orders_on_account(account).await;
+            }
+        }
     }
 }
 
@@ -504,6 +557,11 @@ pub async fn flatten_all_for(account: Account) {
                 client.flatten_all_for(account).await;
             }
         }
+        _ => {
+            if let Some(client) = get_rest_brokerage(&account.brokerage) {
+                client.flatten_all_for(account).await;
+            }
+        }
     }
 }
 
@@ -530,6 +588,11 @@ pub async fn update_order(account: Account, order_id: OrderId, update: OrderUpda
                 return client.update_order(account, order_id, update).await;
             }
         }
+        _ => {
+            if let Some(client) = get_rest_brokerage(&account.brokerage) {
+                return client.update_order(account, order_id, update).await;
+            }
+        }
     }
     Err(OrderUpdateRejected {
         account: account.clone(),
@@ -1,5 +1,5 @@
 use std::str::FromStr;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use ff_standard_lib::messages::data_server_messaging::{DataServerResponse, FundForgeError};
 use ff_standard_lib::standardized_types::datavendor_enum::DataVendor;
 use ff_standard_lib::standardized_types::enums::{MarketType, StrategyMode};
@@ -9,6 +9,8 @@ use crate::bitget_api::api_client::BITGET_CLIENT;
 use crate::rithmic_api::api_client::{get_rithmic_market_data_system, RITHMIC_CLIENTS};
 use tokio::time::{timeout, Duration};
 use crate::data_bento_api::api_client::get_data_bento_client;
+use crate::alpaca_api::api_client::ALPACA_CLIENT;
+use crate::polygon_api::api_client::POLYGON_CLIENT;
 use crate::oanda_api::api_client::OANDA_CLIENT;
 use crate::server_features::server_side_datavendor::VendorApiResponse;
 
@@ -48,6 +50,16 @@ pub async fn session_market_hours_response(mode: StrategyMode, data_vendor: Data
                     return client.session_market_hours_response(mode, stream_name, symbol_name, time, callback_id).await
                 }
             }
+            DataVendor::Polygon => {
+                if let Some(client) = POLYGON_CLIENT.get() {
+                    return client.session_market_hours_response(mode, stream_name, symbol_name, time, callback_id).await
+                }
+            }
+            DataVendor::Alpaca => {
+                if let Some(client) = ALPACA_CLIENT.get() {
+                    return client.session_market_hours_response(mode, stream_name, symbol_name, time, callback_id).await
+                }
+            }
         }
         DataServerResponse::Error{ callback_id, error: FundForgeError::ServerErrorDebug(format!("Unable to find api client instance for: {}", data_vendor))}
     };
@@ -93,6 +105,16 @@ pub async fn symbols_response(
                     return client.symbols_response(mode, stream_name, market_type, time, callback_id).await;
                 }
             }
+            DataVendor::Polygon => {
+                if let Some(client) = POLYGON_CLIENT.get() {
+                    return client.symbols_response(mode, stream_name, market_type, time, callback_id).await;
+                }
+            }
+            DataVendor::Alpaca => {
+                if let Some(client) = ALPACA_CLIENT.get() {
+                    return client.symbols_response(mode, stream_name, market_type, time, callback_id).await;
+                }
+            }
         }
         DataServerResponse::Error{ callback_id, error: FundForgeError::ServerErrorDebug(format!("Unable to find api client instance for: {}", data_vendor))}
     };
@@ -140,6 +162,16 @@ pub async fn resolutions_response(
                     return client.resolutions_response(mode, stream_name, market_type, callback_id).await;
                 }
             }
+            DataVendor::Polygon => {
+                if let Some(client) = POLYGON_CLIENT.get() {
+                    return client.resolutions_response(mode, stream_name, market_type, callback_id).await;
+                }
+            }
+            DataVendor::Alpaca => {
+                if let Some(client) = ALPACA_CLIENT.get() {
+                    return client.resolutions_response(mode, stream_name, market_type, callback_id).await;
+                }
+            }
         }
         DataServerResponse::Error{ callback_id, error: FundForgeError::ServerErrorDebug(format!("Unable to find api client instance for: {}", data_vendor))}
     };
@@ -182,6 +214,16 @@ pub async fn markets_response(
                     return client.markets_response(mode, stream_name, callback_id).await;
                 }
             }
+            DataVendor::Polygon => {
+                if let Some(client) = POLYGON_CLIENT.get() {
+                    return client.markets_response(mode, stream_name, callback_id).await;
+                }
+            }
+            DataVendor::Alpaca => {
+                if let Some(client) = ALPACA_CLIENT.get() {
+                    return client.markets_response(mode, stream_name, callback_id).await;
+                }
+            }
         }
         DataServerResponse::Error{ callback_id, error: FundForgeError::ServerErrorDebug(format!("Unable to find api client instance for: {}", data_vendor))}
     };
@@ -226,6 +268,16 @@ pub async fn decimal_accuracy_response(
                     return client.decimal_accuracy_response(mode, stream_name, symbol_name, callback_id).await;
                 }
             }
+            DataVendor::Polygon => {
+                if let Some(client) = POLYGON_CLIENT.get() {
+                    return client.decimal_accuracy_response(mode, stream_name, symbol_name, callback_id).await;
+                }
+            }
+            DataVendor::Alpaca => {
+                if let Some(client) = ALPACA_CLIENT.get() {
+                    return client.decimal_accuracy_response(mode, stream_name, symbol_name, callback_id).await;
+                }
+            }
         }
         DataServerResponse::Error{ callback_id, error: FundForgeError::ServerErrorDebug(format!("Unable to find api client instance for: {}", data_vendor))}
     };
@@ -269,6 +321,16 @@ pub async fn tick_size_response(
                     return client.tick_size_response(mode, stream_name, symbol_name, callback_id).await;
                 }
             }
+            DataVendor::Polygon => {
+                if let Some(client) = POLYGON_CLIENT.get() {
+                    return client.tick_size_response(mode, stream_name, symbol_name, callback_id).await;
+                }
+            }
+            DataVendor::Alpaca => {
+                if let Some(client) = ALPACA_CLIENT.get() {
+                    return client.tick_size_response(mode, stream_name, symbol_name, callback_id).await;
+                }
+            }
         }
         DataServerResponse::Error{ callback_id, error: FundForgeError::ServerErrorDebug(format!("Unable to find api client instance for: {}", data_vendor))}
     };
@@ -310,6 +372,16 @@ pub async fn data_feed_subscribe(
                     return client.data_feed_subscribe(stream_name, subscription.clone()).await;
                 }
             }
+            DataVendor::Polygon => {
+                if let Some(client) = POLYGON_CLIENT.get() {
+                    return client.data_feed_subscribe(stream_name, subscription.clone()).await;
+                }
+            }
+            DataVendor::Alpaca => {
+                if let Some(client) = ALPACA_CLIENT.get() {
+                    return client.data_feed_subscribe(stream_name, subscription.clone()).await;
+                }
+            }
         }
         DataServerResponse::SubscribeResponse{ success: false, subscription: subscription.clone(), reason: Some(format!("Unable to find api client instance for: {}", subscription.symbol.data_vendor))}
     };
@@ -356,6 +428,16 @@ pub async fn data_feed_unsubscribe(
                     return client.data_feed_unsubscribe(stream_name, subscription.clone()).await;
                 }
             }
+            DataVendor::Polygon => {
+                if let Some(client) = POLYGON_CLIENT.get() {
+                    return client.data_feed_unsubscribe(stream_name, subscription.clone()).await;
+                }
+            }
+            DataVendor::Alpaca => {
+                if let Some(client) = ALPACA_CLIENT.get() {
+                    return client.data_feed_unsubscribe(stream_name, subscription.clone()).await;
+                }
+            }
         }
         DataServerResponse::UnSubscribeResponse{ success: false, subscription: subscription.clone(), reason: Some(format!("Unable to find api client instance for: {}", data_vendor))}
     };
@@ -402,6 +484,77 @@ pub async fn base_data_types_response(
                     return client.base_data_types_response(mode, stream_name, callback_id).await;
                 }
             }
+            DataVendor::Polygon => {
+                if let Some(client) = POLYGON_CLIENT.get() {
+                    return client.base_data_types_response(mode, stream_name, callback_id).await;
+                }
+            }
+            DataVendor::Alpaca => {
+                if let Some(client) = ALPACA_CLIENT.get() {
+                    return client.base_data_types_response(mode, stream_name, callback_id).await;
+                }
+            }
+        }
+        DataServerResponse::Error{ callback_id, error: FundForgeError::ServerErrorDebug(format!("Unable to find api client instance for: {}", data_vendor))}
+    };
+
+    timeout(TIMEOUT_DURATION, operation).await.unwrap_or_else(|_| DataServerResponse::Error { callback_id, error: FundForgeError::ServerErrorDebug("Operation timed out".to_string()) })
+}
+
+/// return `DataServerResponse::OptionChain` or `DataServerResponse::Error(FundForgeError)`
+/// server or client error depending on who caused this problem
+pub async fn option_chain_response(
+    data_vendor: DataVendor,
+    mode: StrategyMode,
+    stream_name: StreamName,
+    underlying: SymbolName,
+    expiry: Option<String>,
+    callback_id: u64
+) -> DataServerResponse {
+    let expiry = match expiry {
+        None => None,
+        Some(expiry) => match NaiveDate::from_str(&expiry) {
+            Ok(expiry) => Some(expiry),
+            Err(e) => return DataServerResponse::Error {error: FundForgeError::ClientSideErrorDebug(format!("Invalid option expiry: {}, {}", expiry, e)), callback_id}
+        }
+    };
+    let operation = async {
+        match data_vendor {
+            DataVendor::Rithmic => {
+                let system = match get_rithmic_market_data_system() {
+                    Some(system) => system,
+                    None => return DataServerResponse::Error {error: FundForgeError::ServerErrorDebug("Rithmic market data system not found".to_string()), callback_id}
+                };
+                if let Some(client) = RITHMIC_CLIENTS.get(&system) {
+                    return client.option_chain_response(mode, stream_name, underlying, expiry, callback_id).await
+                }
+            },
+            DataVendor::DataBento => {
+                return match get_data_bento_client() {
+                    Ok(client) => client.option_chain_response(mode, stream_name, underlying, expiry, callback_id).await,
+                    Err(e) => DataServerResponse::Error { error: e, callback_id }
+                }
+            },
+            DataVendor::Bitget => {
+                if let Some(client) = BITGET_CLIENT.get() {
+                    return client.option_chain_response(mode, stream_name, underlying, expiry, callback_id).await;
+                }
+            }
+            DataVendor::Oanda => {
+                if let Some(client) = OANDA_CLIENT.get() {
+                    return client.option_chain_response(mode, stream_name, underlying, expiry, callback_id).await;
+                }
+            }
+            DataVendor::Polygon => {
+                if let Some(client) = POLYGON_CLIENT.get() {
+                    return client.option_chain_response(mode, stream_name, underlying, expiry, callback_id).await;
+                }
+            }
+            DataVendor::Alpaca => {
+                if let Some(client) = ALPACA_CLIENT.get() {
+                    return client.option_chain_response(mode, stream_name, underlying, expiry, callback_id).await;
+                }
+            }
         }
         DataServerResponse::Error{ callback_id, error: FundForgeError::ServerErrorDebug(format!("Unable to find api client instance for: {}", data_vendor))}
     };
@@ -439,6 +592,16 @@ pub async fn logout_command_vendors(data_vendor: DataVendor, stream_name: Stream
                     client.logout_command_vendors(stream_name).await;
                 }
             }
+            DataVendor::Polygon => {
+                if let Some(client) = POLYGON_CLIENT.get() {
+                    client.logout_command_vendors(stream_name).await;
+                }
+            }
+            DataVendor::Alpaca => {
+                if let Some(client) = ALPACA_CLIENT.get() {
+                    client.logout_command_vendors(stream_name).await;
+                }
+            }
         }
     };
 
@@ -14,6 +14,7 @@ use crate::stream_tasks::initialize_streamer;
 use tokio::sync::Notify;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use crate::async_listener::create_listener;
+use crate::server_features::subscription_stats::record_stream_response;
 
 pub(crate) async fn stream_server(config: ServerConfig, addr: SocketAddr) {
     let acceptor = TlsAcceptor::from(Arc::new(config));
@@ -131,7 +132,7 @@ async fn handle_stream_connection(mut tls_stream: TlsStream<TcpStream>, _peer_ad
 
 
 pub async fn stream_response(stream_name: StreamName, request: StreamRequest) -> DataServerResponse {
-    match request {
+    let response = match request.clone() {
         StreamRequest::Subscribe(subscription) => {
             //it is not when we subscribe that we need to update data, only when we request historical data
             data_feed_subscribe(stream_name, subscription).await
@@ -139,5 +140,7 @@ pub async fn stream_response(stream_name: StreamName, request: StreamRequest) ->
         StreamRequest::Unsubscribe(sub) => {
             data_feed_unsubscribe(sub.symbol.data_vendor.clone(), stream_name, sub).await
         }
-    }
+    };
+    record_stream_response(stream_name, &request, &response);
+    response
 }
\ No newline at end of file
@@ -13,6 +13,8 @@ use ff_standard_lib::standardized_types::bytes_trait::Bytes;
 use ff_standard_lib::standardized_types::subscriptions::DataSubscription;
 use ff_standard_lib::standardized_types::time_slices::TimeSlice;
 use ff_standard_lib::StreamName;
+use crate::server_features::data_quality::clean_live_data;
+use crate::server_features::live_recorder::record_live_data;
 
 lazy_static! {
     static ref STREAM_RECEIVERS: DashMap<u16 , Arc<DashMap<DataSubscription ,broadcast::Receiver<BaseDataEnum>>>> = DashMap::new();
@@ -58,6 +60,8 @@ pub async fn deregister_streamer(stream_name: &StreamName) {
 }
 
 pub async fn subscribe_stream(stream_name: &StreamName, subscription: DataSubscription, receiver: broadcast::Receiver<BaseDataEnum>) {
+    let receiver = clean_live_data(&subscription, receiver);
+    record_live_data(&subscription, &receiver);
     if let Some(sender_ref) = STREAM_RECEIVERS.get(stream_name) {
         sender_ref.insert(subscription.clone(), receiver);
         let sub_list = SUBSCRIPTIONS.entry(stream_name.clone()).or_insert(Arc::new(RwLock::new(Vec::new())));
@@ -73,6 +77,17 @@ pub async fn unsubscribe_stream(stream_name: &StreamName, subscription: &DataSub
     }
 }
 
+/// True if any strategy is streaming the subscription.
+pub async fn is_streamed(subscription: &DataSubscription) -> bool {
+    let lists: Vec<Arc<RwLock<Vec<DataSubscription>>>> = SUBSCRIPTIONS.iter().map(|entry| entry.value().clone()).collect();
+    for list in lists {
+        if list.read().await.contains(subscription) {
+            return true;
+        }
+    }
+    false
+}
+
 const LENGTH: usize = 4;
 
 pub async fn stream_handler(
@@ -20,6 +20,9 @@ use ff_standard_lib::standardized_types::datavendor_enum::DataVendor;
 use ff_standard_lib::standardized_types::enums::MarketType;
 use ff_standard_lib::standardized_types::resolution::Resolution;
 use ff_standard_lib::standardized_types::subscriptions::{Symbol, SymbolName};
+use crate::alpaca_api::api_client::{ALPACA_CLIENT, ALPACA_IS_CONNECTED};
+use crate::data_bento_api::api_client::get_data_bento_client;
+use crate::polygon_api::api_client::{POLYGON_CLIENT, POLYGON_IS_CONNECTED};
 use crate::oanda_api::api_client::{OANDA_CLIENT, OANDA_IS_CONNECTED};
 use crate::rithmic_api::api_client::{get_rithmic_market_data_system, RITHMIC_CLIENTS, RITHMIC_DATA_IS_CONNECTED};
 use ff_standard_lib::database::hybrid_storage::{HybridStorage};
@@ -58,6 +61,42 @@ where
 }
 
 
+/// The api client of the vendor, None if the vendor is not connected.
+pub(crate) fn vendor_client(data_vendor: DataVendor) -> Option<Arc<dyn VendorApiResponse>> {
+    match data_vendor {
+        DataVendor::Rithmic if RITHMIC_DATA_IS_CONNECTED.load(Ordering::SeqCst) => {
+            let client = get_rithmic_market_data_system().and_then(|sys| RITHMIC_CLIENTS.get(&sys))?;
+            Some(client.clone())
+        }
+        DataVendor::Oanda if OANDA_IS_CONNECTED.load(Ordering::SeqCst) => Some(OANDA_CLIENT.get()?.clone()),
+        DataVendor::Alpaca if ALPACA_IS_CONNECTED.load(Ordering::SeqCst) => Some(ALPACA_CLIENT.get()?.clone()),
+        DataVendor::Polygon if POLYGON_IS_CONNECTED.load(Ordering::SeqCst) => Some(POLYGON_CLIENT.get()?.clone()),
+        DataVendor::DataBento => Some(get_data_bento_client().ok()?),
+        _ => None,
+    }
+}
+
+/// The market type of the symbol at the vendor, None if the vendor does not list the symbol.
+pub(crate) async fn vendor_market_type(data_vendor: DataVendor, symbol_name: &SymbolName) -> Option<MarketType> {
+    match data_vendor {
+        DataVendor::Oanda => {
+            let client = OANDA_CLIENT.get()?;
+            let instrument = client.instruments_map.get(symbol_name)?;
+            Some(instrument.value().market_type)
+        },
+        DataVendor::Alpaca => ALPACA_CLIENT.get().and_then(|client| client.market_type(symbol_name)),
+        DataVendor::Polygon => match POLYGON_CLIENT.get()?.market_type(symbol_name).await {
+            Ok(market_type) => Some(market_type),
+            Err(e) => {
+                eprintln!("Polygon: {}", e);
+                None
+            }
+        },
+        DataVendor::Rithmic | DataVendor::DataBento => get_exchange_by_symbol_name(symbol_name).map(MarketType::Futures),
+        _ => None,
+    }
+}
+
 pub fn run_update_schedule(storage: Arc<HybridStorage>) {
     let mut shutdown_receiver = subscribe_server_shutdown();
 
@@ -124,20 +163,8 @@ pub fn run_update_schedule(storage: Arc<HybridStorage>) {
 }
 
 pub async fn pre_subscribe_updates(storage: Arc<HybridStorage>, symbol: Symbol, resolution: Resolution, base_data_type: BaseDataType) {
-    let client: Arc<dyn VendorApiResponse> = match symbol.data_vendor {
-        DataVendor::Rithmic if RITHMIC_DATA_IS_CONNECTED.load(Ordering::SeqCst) => {
-            match get_rithmic_market_data_system().and_then(|sys| RITHMIC_CLIENTS.get(&sys)) {
-                Some(client) => client.clone(),
-                None => return,
-            }
-        }
-        DataVendor::Oanda if OANDA_IS_CONNECTED.load(Ordering::SeqCst)=> {
-            match OANDA_CLIENT.get() {
-                Some(client) => client.clone(),
-                None => return,
-            }
-        }
-        _ => return,
+    let Some(client) = vendor_client(symbol.data_vendor) else {
+        return;
     };
 
     let start_time = match storage.get_latest_data_time(&symbol, &resolution, &base_data_type).await {
@@ -223,7 +250,16 @@ async fn update_data(storage: Arc<HybridStorage>, from_back: bool) -> Result<(),
             DataVendor::Oanda if !OANDA_IS_CONNECTED.load(Ordering::SeqCst) => {
                 continue
             },
-            DataVendor::DataBento | DataVendor::Bitget => {
+            DataVendor::Alpaca if !ALPACA_IS_CONNECTED.load(Ordering::SeqCst) => {
+                continue
+            },
+            DataVendor::Polygon if !POLYGON_IS_CONNECTED.load(Ordering::SeqCst) => {
+                continue
+            },
+            DataVendor::DataBento if get_data_bento_client().is_err() => {
+                continue
+            },
+            DataVendor::Bitget => {
                 continue
             },
             _ => (),
@@ -253,29 +289,8 @@ async fn update_data(storage: Arc<HybridStorage>, from_back: bool) -> Result<(),
                         continue;
                     }
                     //eprintln!("Symbol: {:?}", symbol_config);
-                    let market_type = match vendor {
-                        DataVendor::Oanda => {
-                            if let Some(client) = OANDA_CLIENT.get() {
-                                if let Some(instrument) = client.instruments_map.get(&symbol_config.symbol_name) {
-                                    instrument.value().market_type
-                                } else {
-                                    continue;
-                                }
-                            } else {
-                                continue;
-                            }
-                        },
-                        DataVendor::Rithmic => {
-                            match get_exchange_by_symbol_name(&symbol_config.symbol_name) {
-                                Some(exchange) => MarketType::Futures(exchange),
-                                None => {
-                                    continue
-                                },
-                            }
-                        }
-                        _ => {
-                            continue
-                        }
+                    let Some(market_type) = vendor_market_type(vendor, &symbol_config.symbol_name).await else {
+                        continue;
                     };
 
                     let symbol = Symbol::new(symbol_config.symbol_name.clone(), vendor.clone(), market_type);
@@ -383,20 +398,8 @@ async fn update_symbol(
     }
 
     // Get the client before attempting to acquire the semaphore
-    let client: Arc<dyn VendorApiResponse> = match symbol.data_vendor {
-        DataVendor::Rithmic if RITHMIC_DATA_IS_CONNECTED.load(Ordering::SeqCst) => {
-            match get_rithmic_market_data_system().and_then(|sys| RITHMIC_CLIENTS.get(&sys)) {
-                Some(client) => client.clone(),
-                None => return,
-            }
-        }
-        DataVendor::Oanda if OANDA_IS_CONNECTED.load(Ordering::SeqCst)=> {
-            match OANDA_CLIENT.get() {
-                Some(client) => client.clone(),
-                None => return,
-            }
-        }
-        _ => return,
+    let Some(client) = vendor_client(symbol.data_vendor) else {
+        return;
     };
 
     // Now spawn the real task
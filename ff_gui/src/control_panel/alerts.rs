@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use ff_standard_lib::standardized_types::orders::OrderUpdateEvent;
+use ff_standard_lib::standardized_types::position::PositionUpdateEvent;
+use ff_standard_lib::standardized_types::symbol_info::round_for_display;
+use ff_standard_lib::strategies::strategy_events::StrategyEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AlertKind {
+    Fill,
+    Rejection,
+    PositionClosed,
+    RiskGuard,
+}
+
+impl AlertKind {
+    pub const ALL: [AlertKind; 4] = [AlertKind::Fill, AlertKind::Rejection, AlertKind::PositionClosed, AlertKind::RiskGuard];
+}
+
+impl Display for AlertKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertKind::Fill => write!(f, "Fills"),
+            AlertKind::Rejection => write!(f, "Rejections"),
+            AlertKind::PositionClosed => write!(f, "Position Closes"),
+            AlertKind::RiskGuard => write!(f, "Risk Guards"),
+        }
+    }
+}
+
+/// An alert shown as a toast in the control panel.
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub message: String,
+    pub received: Instant,
+}
+
+impl Alert {
+    pub fn new(kind: AlertKind, message: String) -> Self {
+        Alert {
+            kind,
+            message,
+            received: Instant::now(),
+        }
+    }
+
+    /// Risk guards are strategy specific, send this when your strategy stops trading or reduces risk, eg a max daily loss is hit.
+    pub fn risk_guard(message: String) -> Self {
+        Alert::new(AlertKind::RiskGuard, message)
+    }
+
+    /// Returns the alert for fills, rejections and position closes, all other events return None.
+    /// Forward the strategy events from your event loop to the control panel using `alert_sender.try_send(alert)`.
+    pub fn from_strategy_event(event: &StrategyEvent) -> Option<Self> {
+        match event {
+            StrategyEvent::OrderEvents(order_event) => match order_event {
+                OrderUpdateEvent::OrderFilled { symbol_name, symbol_code, side, price, quantity, tag, .. } => {
+                    Some(Alert::new(AlertKind::Fill, format!("Filled: {} {} {} @ {}, {}", side, quantity, symbol_code, round_for_display(symbol_name, *price), tag)))
+                }
+                OrderUpdateEvent::OrderPartiallyFilled { symbol_name, symbol_code, side, price, quantity, tag, .. } => {
+                    Some(Alert::new(AlertKind::Fill, format!("Partially Filled: {} {} {} @ {}, {}", side, quantity, symbol_code, round_for_display(symbol_name, *price), tag)))
+                }
+                OrderUpdateEvent::OrderRejected { symbol_code, reason, tag, .. } => {
+                    Some(Alert::new(AlertKind::Rejection, format!("Rejected: {}, {}: {}", symbol_code, tag, reason)))
+                }
+                OrderUpdateEvent::OrderUpdateRejected { order_id, reason, .. } => {
+                    Some(Alert::new(AlertKind::Rejection, format!("Update Rejected: {}: {}", order_id, reason)))
+                }
+                _ => None,
+            },
+            StrategyEvent::PositionEvents(PositionUpdateEvent::PositionClosed { symbol_code, side, booked_pnl, .. }) => {
+                Some(Alert::new(AlertKind::PositionClosed, format!("Closed: {} {}, Booked PnL: {}", side, symbol_code, booked_pnl.round_dp(2))))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Which alerts are enabled and the sound file to play for each alert kind.
+/// Alerts without a sound file ring the terminal bell.
+#[derive(Debug, Clone)]
+pub struct AlertSettings {
+    pub enabled: BTreeMap<AlertKind, bool>,
+    pub sounds: BTreeMap<AlertKind, PathBuf>,
+    pub play_sounds: bool,
+    /// How long a toast stays in the control panel.
+    pub toast_duration: Duration,
+    pub max_toasts: usize,
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        AlertSettings {
+            enabled: AlertKind::ALL.iter().map(|kind| (*kind, true)).collect(),
+            sounds: BTreeMap::new(),
+            play_sounds: true,
+            toast_duration: Duration::from_secs(10),
+            max_toasts: 5,
+        }
+    }
+}
+
+impl AlertSettings {
+    pub fn is_enabled(&self, kind: AlertKind) -> bool {
+        self.enabled.get(&kind).cloned().unwrap_or(false)
+    }
+
+    pub fn with_enabled(mut self, kind: AlertKind, enabled: bool) -> Self {
+        self.enabled.insert(kind, enabled);
+        self
+    }
+
+    /// Plays the sound file (wav, mp3, flac or ogg) when the alert is received.
+    pub fn with_sound(mut self, kind: AlertKind, sound_file: PathBuf) -> Self {
+        self.sounds.insert(kind, sound_file);
+        self
+    }
+
+    pub fn play(&self, kind: AlertKind) {
+        if !self.play_sounds {
+            return;
+        }
+        match self.sounds.get(&kind) {
+            Some(sound_file) => play_sound(sound_file),
+            None => ring_bell(),
+        }
+    }
+}
+
+/// Plays the sound on a separate thread so the gui never blocks, falls back to the terminal bell if the file can not be played.
+pub fn play_sound(sound_file: &Path) {
+    let sound_file = sound_file.to_path_buf();
+    std::thread::spawn(move || {
+        if let Err(e) = try_play_sound(&sound_file) {
+            eprintln!("Failed to play alert sound {:?}: {}", sound_file, e);
+            ring_bell();
+        }
+    });
+}
+
+fn try_play_sound(sound_file: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let (_stream, handle) = rodio::OutputStream::try_default()?;
+    let sink = rodio::Sink::try_new(&handle)?;
+    let file = BufReader::new(File::open(sound_file)?);
+    sink.append(rodio::Decoder::new(file)?);
+    sink.sleep_until_end();
+    Ok(())
+}
+
+fn ring_bell() {
+    print!("\x07");
+    let _ = std::io::stdout().flush();
+}
@@ -1,2 +1,3 @@
 pub mod panel;
-pub mod svg_icons;
\ No newline at end of file
+pub mod svg_icons;
+pub mod alerts;
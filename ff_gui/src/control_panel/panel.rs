@@ -1,17 +1,24 @@
-use iced::{window, Alignment, Element, Length, Size, Theme};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use iced::{window, Alignment, Element, Length, Size, Subscription, Theme};
 use iced::advanced::widget::Text;
 use ff_standard_lib::standardized_types::accounts::Account;
 use ff_standard_lib::strategies::strategy_events::{StrategyControls, StrategyEvent};
-use iced::widget::{button, container, row, svg, text, Column, Radio, Row, Slider};
+use ff_standard_lib::strategies::backtest_progress::{BacktestProgress, BacktestProgressHandle};
+use ff_standard_lib::strategies::custom_controls::{ControlCommand, PanelCommand};
+use ff_standard_lib::strategies::strategy_panel::{PanelInteraction, PanelWidget, StrategyPanel, StrategyPanelHandle};
+use ff_standard_lib::strategies::handlers::ReduceOnlyHandle;
+use iced::widget::{button, checkbox, container, progress_bar, row, scrollable, svg, text, Column, Radio, Row, Slider};
 use rust_decimal::Decimal;
 use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal_macros::dec;
 use tokio::sync::mpsc;
 use ff_standard_lib::standardized_types::enums::Bias;
+use crate::control_panel::alerts::{Alert, AlertKind, AlertSettings};
 
 pub fn window_settings() -> window::Settings {
     window::Settings {
-        size: Size::new(400.0, 250.0),
+        size: Size::new(400.0, 520.0),
         position: Default::default(),
         min_size: None,
         max_size: None,
@@ -32,11 +39,18 @@ pub enum Message {
     PausePressed,
     StopPressed,
     StartPressed,
+    ReduceOnlyPressed,
     ReducePositionSize,
     IncreasePositionSize,
     Flatten,
     RiskReward(f64),
     Bias(Bias),
+    ToggleAlert(AlertKind, bool),
+    ToggleAlertSounds(bool),
+    CheckAlerts(Instant),
+    CheckProgress(Instant),
+    CheckPanel(Instant),
+    PanelInteraction(PanelInteraction),
 }
 
 pub struct StrategyControlPanel {
@@ -45,6 +59,14 @@ pub struct StrategyControlPanel {
     pub theme: Theme,
     pub risk_reward: f64,
     pub bias: Bias,
+    pub alert_settings: AlertSettings,
+    alert_receiver: Option<mpsc::Receiver<Alert>>,
+    toasts: VecDeque<Alert>,
+    progress_handle: Option<BacktestProgressHandle>,
+    progress: Option<BacktestProgress>,
+    panel_handle: Option<StrategyPanelHandle>,
+    strategy_panel: Option<StrategyPanel>,
+    reduce_only: Option<ReduceOnlyHandle>,
 }
 
 pub fn new_strategy_control(strategy_sender: mpsc::Sender<StrategyEvent>, theme: Theme, risk_reward: Decimal, bias: Bias) -> StrategyControlPanel {
@@ -53,18 +75,87 @@ pub fn new_strategy_control(strategy_sender: mpsc::Sender<StrategyEvent>, theme:
         current_state: StrategyControls::Continue,
         theme,
         risk_reward: risk_reward.to_f64().unwrap(),
-        bias
+        bias,
+        alert_settings: AlertSettings::default(),
+        alert_receiver: None,
+        toasts: VecDeque::new(),
+        progress_handle: None,
+        progress: None,
+        panel_handle: None,
+        strategy_panel: None,
+        reduce_only: None,
     }
 }
 
 impl StrategyControlPanel {
+    /// Shows toasts and plays sounds for the alerts received, use `Alert::from_strategy_event()` to forward fills, rejections and position closes from the strategy event loop.
+    pub fn with_alerts(mut self, alert_receiver: mpsc::Receiver<Alert>, alert_settings: AlertSettings) -> Self {
+        self.alert_receiver = Some(alert_receiver);
+        self.alert_settings = alert_settings;
+        self
+    }
+
+    /// Shows a progress bar with the backtest time, speed and ETA, use `backtest_progress()` for the handle.
+    pub fn with_backtest_progress(mut self, progress_handle: BacktestProgressHandle) -> Self {
+        self.progress_handle = Some(progress_handle);
+        self
+    }
+
+    /// Renders the widgets declared by the strategy with `declare_panel()`, use `strategy_panel()` for the handle.
+    pub fn with_strategy_panel(mut self, panel_handle: StrategyPanelHandle) -> Self {
+        self.panel_handle = Some(panel_handle);
+        self
+    }
+
+    /// Shows a reduce only button which puts the strategy in reduce only mode, continue ends it, use `strategy.reduce_only_handle()` for the handle of the strategy.
+    pub fn with_reduce_only(mut self, reduce_only: ReduceOnlyHandle) -> Self {
+        self.reduce_only = Some(reduce_only);
+        self
+    }
+
+    fn send_command<T: ControlCommand>(&self, command: T) {
+        match command.to_control() {
+            Ok(control) => {
+                let _ = self.strategy_sender.try_send(StrategyEvent::StrategyControls(control));
+            }
+            Err(e) => eprintln!("{}", e)
+        }
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        let alerts = match self.alert_receiver {
+            Some(_) => iced::time::every(Duration::from_millis(250)).map(Message::CheckAlerts),
+            None => Subscription::none(),
+        };
+        let progress = match self.progress_handle {
+            Some(_) => iced::time::every(Duration::from_millis(500)).map(Message::CheckProgress),
+            None => Subscription::none(),
+        };
+        let panel = match self.panel_handle {
+            Some(_) => iced::time::every(Duration::from_millis(250)).map(Message::CheckPanel),
+            None => Subscription::none(),
+        };
+        Subscription::batch([alerts, progress, panel])
+    }
+
     pub fn update(&mut self, message: Message) {
         match message {
             Message::ContinuePressed => {
+                if let Some(reduce_only) = &self.reduce_only {
+                    reduce_only.set(false);
+                }
                 if let Ok(_) = self.strategy_sender.try_send(StrategyEvent::StrategyControls(StrategyControls::Continue)) {
                     self.current_state = StrategyControls::Continue;
                 }
             }
+            Message::ReduceOnlyPressed => {
+                if let Some(reduce_only) = &self.reduce_only {
+                    // the engine refuses entries even if the event loop is behind
+                    reduce_only.set(true);
+                    let _ = self.strategy_sender.try_send(StrategyEvent::StrategyControls(StrategyControls::ReduceOnly));
+                    self.current_state = StrategyControls::ReduceOnly;
+                }
+            }
             Message::PausePressed => {
                 if let Ok(_) = self.strategy_sender.try_send(StrategyEvent::StrategyControls(StrategyControls::Pause)) {
                     self.current_state = StrategyControls::Pause;
@@ -81,30 +172,64 @@ impl StrategyControlPanel {
                 }
             }
             Message::ReducePositionSize => {
-                let _ = self.strategy_sender.try_send(StrategyEvent::StrategyControls(StrategyControls::Custom("Reduce".to_string())));
+                self.send_command(PanelCommand::Reduce);
             }
             Message::IncreasePositionSize => {
-                let _ = self.strategy_sender.try_send(StrategyEvent::StrategyControls(StrategyControls::Custom("Increase".to_string())));
+                self.send_command(PanelCommand::Increase);
             },
             Message::Flatten => {
-                let _ = self.strategy_sender.try_send(StrategyEvent::StrategyControls(StrategyControls::Custom("Flatten".to_string())));
+                self.send_command(PanelCommand::Flatten);
             }
             Message::RiskReward(risk_reward) => {
                 self.risk_reward = risk_reward;
-                let _ = self.strategy_sender.try_send(StrategyEvent::StrategyControls(StrategyControls::Custom(format!("Risk Reward:{}", risk_reward))));
+                self.send_command(PanelCommand::RiskReward(risk_reward));
             }
             Message::Bias(bias) => {
                 self.bias = bias;
                 match bias {
-                    Bias::Bullish => {
-                        let _ = self.strategy_sender.try_send(StrategyEvent::StrategyControls(StrategyControls::Custom("Trade Long".to_string())));
+                    Bias::Bullish => self.send_command(PanelCommand::TradeLong),
+                    Bias::Bearish => self.send_command(PanelCommand::TradeShort),
+                    Bias::Neutral => {}
+                }
+            }
+            Message::ToggleAlert(kind, enabled) => {
+                self.alert_settings.enabled.insert(kind, enabled);
+            }
+            Message::ToggleAlertSounds(enabled) => {
+                self.alert_settings.play_sounds = enabled;
+            }
+            Message::CheckAlerts(now) => {
+                if let Some(receiver) = self.alert_receiver.as_mut() {
+                    while let Ok(alert) = receiver.try_recv() {
+                        if !self.alert_settings.is_enabled(alert.kind) {
+                            continue;
+                        }
+                        self.alert_settings.play(alert.kind);
+                        self.toasts.push_front(alert);
                     }
-                    Bias::Bearish => {
-                        let _ = self.strategy_sender.try_send(StrategyEvent::StrategyControls(StrategyControls::Custom("Trade Short".to_string())));
+                }
+                let toast_duration = self.alert_settings.toast_duration;
+                self.toasts.retain(|toast| now.duration_since(toast.received) < toast_duration);
+                self.toasts.truncate(self.alert_settings.max_toasts);
+            }
+            Message::CheckProgress(_) => {
+                if let Some(handle) = &self.progress_handle {
+                    self.progress = handle.latest();
+                }
+            }
+            Message::CheckPanel(_) => {
+                if let Some(handle) = self.panel_handle.as_mut() {
+                    if let Some(panel) = handle.changed() {
+                        self.strategy_panel = Some(panel);
                     }
-                    Bias::Neutral => {}
                 }
             }
+            Message::PanelInteraction(interaction) => {
+                if let Some(panel) = self.strategy_panel.as_mut() {
+                    panel.apply(&interaction);
+                }
+                self.send_command(interaction);
+            }
         }
     }
 
@@ -186,6 +311,7 @@ impl StrategyControlPanel {
             StrategyControls::Pause => "Paused".to_string(),
             StrategyControls::Stop => "Stopped".to_string(),
             StrategyControls::Start => "Running".to_string(),
+            StrategyControls::ReduceOnly => "Reduce Only".to_string(),
             _ => "".to_string()
         };
 
@@ -232,21 +358,120 @@ impl StrategyControlPanel {
             .spacing(10)
             .align_x(Alignment::Center);
 
-        let status = text(message)
-            .size(20);
+        let mut status = Column::new()
+            .push(text(message).size(20))
+            .spacing(10)
+            .align_x(Alignment::Center);
+        if self.reduce_only.is_some() {
+            status = status.push(
+                button(text("Reduce Only").size(14))
+                    .on_press(Message::ReduceOnlyPressed)
+                    .padding(5)
+            );
+        }
+
+        let mut alert_toggles = Row::new()
+            .spacing(10);
+        for kind in AlertKind::ALL {
+            alert_toggles = alert_toggles.push(
+                checkbox(kind.to_string(), self.alert_settings.is_enabled(kind))
+                    .on_toggle(move |enabled| Message::ToggleAlert(kind, enabled))
+                    .size(14)
+                    .text_size(12)
+            );
+        }
+        let alert_controls = Column::new()
+            .push(Text::new("Alerts").size(16))
+            .push(alert_toggles)
+            .push(
+                checkbox("Sounds", self.alert_settings.play_sounds)
+                    .on_toggle(Message::ToggleAlertSounds)
+                    .size(14)
+                    .text_size(12)
+            )
+            .spacing(10)
+            .align_x(Alignment::Center);
+
+        let mut toasts = Column::new()
+            .spacing(5)
+            .align_x(Alignment::Center);
+        for toast in &self.toasts {
+            toasts = toasts.push(
+                container(text(toast.message.clone()).size(12))
+                    .padding(5)
+                    .style(container::rounded_box)
+            );
+        }
+
+        let mut backtest_progress = Column::new()
+            .spacing(5)
+            .align_x(Alignment::Center);
+        if let Some(progress) = &self.progress {
+            let eta = match progress.eta_seconds {
+                Some(eta) => format!("ETA {}:{:02}:{:02}", eta / 3600, eta % 3600 / 60, eta % 60),
+                None => "ETA --".to_string(),
+            };
+            backtest_progress = backtest_progress
+                .push(progress_bar(0.0..=100.0, progress.pct as f32).width(Length::Fixed(300.0)).height(Length::Fixed(10.0)))
+                .push(text(format!("{:.1}%  {}", progress.pct, progress.current_time)).size(12))
+                .push(text(format!("{:.0} events/s  {}", progress.events_per_sec, eta)).size(12));
+        }
+
+        let mut strategy_panel = Column::new()
+            .spacing(10)
+            .align_x(Alignment::Center);
+        if let Some(panel) = &self.strategy_panel {
+            strategy_panel = strategy_panel.push(Text::new(panel.title.clone()).size(16));
+            for widget in &panel.widgets {
+                let element: Element<Message> = match widget {
+                    PanelWidget::Button { id, label } => button(text(label.clone()).size(14))
+                        .on_press(Message::PanelInteraction(PanelInteraction::Pressed { id: id.clone() }))
+                        .padding(5)
+                        .into(),
+                    PanelWidget::NumberInput { id, label, min, max, step, value } => {
+                        let id = id.clone();
+                        Row::new()
+                            .push(text(label.clone()).size(14))
+                            .push(
+                                Slider::new(*min..=*max, *value, move |value| Message::PanelInteraction(PanelInteraction::NumberChanged { id: id.clone(), value }))
+                                    .step(*step)
+                                    .width(Length::Fixed(150.0))
+                            )
+                            .push(text(format!("{}", value)).size(14))
+                            .spacing(10)
+                            .align_y(Alignment::Center)
+                            .into()
+                    }
+                    PanelWidget::Toggle { id, label, value } => {
+                        let id = id.clone();
+                        checkbox(label.clone(), *value)
+                            .on_toggle(move |value| Message::PanelInteraction(PanelInteraction::Toggled { id: id.clone(), value }))
+                            .size(14)
+                            .text_size(14)
+                            .into()
+                    }
+                    PanelWidget::StatusLabel { text: label_text, .. } => text(label_text.clone()).size(14).into(),
+                };
+                strategy_panel = strategy_panel.push(element);
+            }
+        }
 
         let content = iced::widget::column![
             control_buttons,
             risk_reward_slider,
             bias_controls,
             status,
+            backtest_progress,
+            strategy_panel,
+            alert_controls,
+            toasts,
         ]
             .spacing(20)
             .align_x(Alignment::Center);
 
-        container(content)
+        container(scrollable(content))
             .width(Length::Fixed(400.0))
-            .height(Length::Fixed(250.0))
+            .height(Length::Fill)
             .into()
     }
 
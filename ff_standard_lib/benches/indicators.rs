@@ -0,0 +1,80 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use rust_decimal::Decimal;
+use ff_standard_lib::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use ff_standard_lib::standardized_types::base_data::candle::Candle;
+use ff_standard_lib::standardized_types::datavendor_enum::DataVendor;
+use ff_standard_lib::standardized_types::enums::MarketType;
+use ff_standard_lib::standardized_types::resolution::Resolution;
+use ff_standard_lib::standardized_types::rolling_window::RollingWindow;
+use ff_standard_lib::standardized_types::subscriptions::{CandleType, Symbol};
+use ff_standard_lib::strategies::indicators::running_window::RunningWindow;
+
+const BARS: usize = 200_000;
+
+fn bars() -> Vec<BaseDataEnum> {
+    let symbol = Symbol::new("EUR-USD".to_string(), DataVendor::DataBento, MarketType::Forex);
+    (0..BARS)
+        .map(|i| {
+            let mut candle = Candle::new(symbol.clone(), Decimal::new(10_000 + (i % 97) as i64, 4), Decimal::ONE, Decimal::ZERO, Decimal::ZERO, i.to_string(), Resolution::Seconds(5), CandleType::CandleStick);
+            candle.close = Decimal::new(10_000 + ((i * 31) % 89) as i64, 4);
+            candle.is_closed = true;
+            BaseDataEnum::Candle(candle)
+        })
+        .collect()
+}
+
+fn close(data: &BaseDataEnum) -> Decimal {
+    match data {
+        BaseDataEnum::Candle(candle) => candle.close,
+        _ => Decimal::ZERO,
+    }
+}
+
+fn recalculating(bars: &[BaseDataEnum], period: usize) -> Duration {
+    let mut window: RollingWindow<BaseDataEnum> = RollingWindow::new(period);
+    let start = Instant::now();
+    for bar in bars {
+        if !bar.is_closed() {
+            continue;
+        }
+        window.add(bar.clone());
+        if !window.is_full() {
+            continue;
+        }
+        let closes: Vec<Decimal> = window.history().iter().map(close).collect();
+        let count = Decimal::from(closes.len());
+        let mean = closes.iter().sum::<Decimal>() / count;
+        let variance = closes.iter().map(|value| (*value - mean) * (*value - mean)).sum::<Decimal>() / count;
+        black_box((mean, variance));
+    }
+    start.elapsed()
+}
+
+fn incremental(bars: &[BaseDataEnum], period: usize) -> Duration {
+    let mut window = RunningWindow::with_variance(period);
+    let start = Instant::now();
+    for bar in bars {
+        if !bar.is_closed() {
+            continue;
+        }
+        window.push(close(bar));
+        if !window.is_full() {
+            continue;
+        }
+        black_box((window.mean(), window.variance()));
+    }
+    start.elapsed()
+}
+
+/// Times a moving average and standard deviation recalculated from the window on each bar against a `RunningWindow`.
+/// Run with `cargo bench -p ff_standard_lib --bench indicators`.
+fn main() {
+    let bars = bars();
+    println!("{:>8} {:>16} {:>16} {:>10}", "period", "recalculating", "incremental", "speed up");
+    for period in [20, 200, 1000] {
+        let old = recalculating(&bars, period);
+        let new = incremental(&bars, period);
+        println!("{:>8} {:>16?} {:>16?} {:>9.1}x", period, old, new, old.as_secs_f64() / new.as_secs_f64());
+    }
+}
@@ -0,0 +1,403 @@
+use std::collections::BTreeMap;
+use std::fs;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use rust_decimal_macros::dec;
+use serde_derive::{Deserialize, Serialize};
+use crate::database::hybrid_storage::HybridStorage;
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::resolution::Resolution;
+use crate::standardized_types::subscriptions::{Symbol, SymbolName};
+
+/// The suffix of a continuous contract symbol name, eg "MNQ#C".
+pub const CONTINUOUS_SUFFIX: &str = "#C";
+
+const MONTH_CODES: [char; 12] = ['F', 'G', 'H', 'J', 'K', 'M', 'N', 'Q', 'U', 'V', 'X', 'Z'];
+
+/// Returns the root symbol if the name is a continuous contract, eg "MNQ" for "MNQ#C".
+pub fn continuous_root(symbol_name: &str) -> Option<&str> {
+    symbol_name.strip_suffix(CONTINUOUS_SUFFIX).filter(|root| !root.is_empty())
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum AdjustmentMethod {
+    BackAdjusted,
+    RatioAdjusted,
+    Unadjusted,
+}
+
+/// # Variants
+/// * `Volume` - Roll at the start of the day after the next contract first trades more volume than the front contract.
+/// * `DaysBeforeExpiry(days)` - Roll the number of trading days before the last day of data of the front contract,
+///   the front contract is only treated as expired once the next contract has traded after it.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum RollRule {
+    Volume,
+    DaysBeforeExpiry(u32),
+}
+
+/// The settings of a continuous contract.
+///
+/// # Properties
+/// * `symbol` - The root symbol, eg "MNQ".
+/// * `adjustment` - How the price gaps at the rolls are removed.
+/// * `roll` - When to move to the next contract.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ContinuousContractSettings {
+    pub symbol: SymbolName,
+    pub adjustment: AdjustmentMethod,
+    pub roll: RollRule,
+}
+
+impl ContinuousContractSettings {
+    pub fn new(symbol: SymbolName) -> Self {
+        Self {
+            symbol,
+            adjustment: AdjustmentMethod::BackAdjusted,
+            roll: RollRule::Volume,
+        }
+    }
+}
+
+/// The data of an individual contract, sorted by time.
+pub struct ContractSeries {
+    pub name: SymbolName,
+    pub year: i32,
+    pub month: u32,
+    pub data: Vec<BaseDataEnum>,
+}
+
+/// Returns the month and the year digits of the contract if the name is the root followed by a contract code, eg "MNQH24" or "MNQH4".
+fn parse_contract_code<'a>(root: &str, name: &'a str) -> Option<(u32, &'a str)> {
+    let code = name.strip_prefix(root)?;
+    let mut chars = code.chars();
+    let month = MONTH_CODES.iter().position(|c| Some(*c) == chars.next())? as u32 + 1;
+    let digits = chars.as_str();
+    if digits.is_empty() || digits.len() > 2 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((month, digits))
+}
+
+/// Single digit years are the first year ending in the digit, on or after the year the contract started trading.
+fn contract_year(digits: &str, first_data: DateTime<Utc>) -> Option<i32> {
+    let number: i32 = digits.parse().ok()?;
+    match digits.len() {
+        2 => Some(2000 + number),
+        _ => {
+            let year = first_data.year();
+            Some(year + (number - year.rem_euclid(10)).rem_euclid(10))
+        }
+    }
+}
+
+fn volume_of(data: &BaseDataEnum) -> Volume {
+    match data {
+        BaseDataEnum::Candle(candle) => candle.volume,
+        BaseDataEnum::QuoteBar(bar) => bar.volume,
+        BaseDataEnum::Tick(tick) => tick.volume,
+        BaseDataEnum::Quote(quote) => quote.bid_volume + quote.ask_volume,
+        _ => dec!(0),
+    }
+}
+
+fn reference_price(data: &BaseDataEnum) -> Option<Price> {
+    match data {
+        BaseDataEnum::Candle(candle) => Some(candle.close),
+        BaseDataEnum::QuoteBar(bar) => Some((bar.bid_close + bar.ask_close) / dec!(2)),
+        BaseDataEnum::Tick(tick) => Some(tick.price),
+        BaseDataEnum::Quote(quote) => Some((quote.bid + quote.ask) / dec!(2)),
+        _ => None,
+    }
+}
+
+fn daily_volume(data: &[BaseDataEnum]) -> BTreeMap<NaiveDate, Volume> {
+    let mut volume = BTreeMap::new();
+    for base_data in data {
+        *volume.entry(base_data.time_utc().date_naive()).or_insert(dec!(0)) += volume_of(base_data);
+    }
+    volume
+}
+
+fn day_start(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0).unwrap().and_local_timezone(Utc).unwrap()
+}
+
+/// The day the series moves from the front contract to the next contract.
+fn roll_date(front: &ContractSeries, next: &ContractSeries, rule: &RollRule) -> Option<NaiveDate> {
+    let front_volume = daily_volume(&front.data);
+    let next_volume = daily_volume(&next.data);
+    match rule {
+        RollRule::Volume => {
+            // the crossover is only known at the end of the day, so the roll is the next trading day
+            let crossover = next_volume.iter()
+                .find(|(date, volume)| **volume > front_volume.get(*date).cloned().unwrap_or(dec!(0)))
+                .map(|(date, _)| *date)?;
+            next_volume.keys().find(|date| **date > crossover).cloned()
+        }
+        RollRule::DaysBeforeExpiry(days) => {
+            let last_front = *front_volume.keys().last()?;
+            if next_volume.keys().last()? <= &last_front {
+                return None;
+            }
+            let dates: Vec<&NaiveDate> = front_volume.keys().collect();
+            let index = dates.len().saturating_sub(1 + *days as usize);
+            Some(*dates[index])
+        }
+    }
+}
+
+/// The last price of the contract before the time.
+fn price_before(data: &[BaseDataEnum], time: DateTime<Utc>) -> Option<Price> {
+    data.iter().rev().find(|base_data| base_data.time_utc() < time).and_then(reference_price)
+}
+
+/// Returns a copy of the data for the continuous symbol with the prices adjusted, or None if the data type has no prices to adjust.
+fn adjusted_data(base_data: &BaseDataEnum, symbol: &Symbol, adjust: &dyn Fn(Price) -> Price) -> Option<BaseDataEnum> {
+    match base_data {
+        BaseDataEnum::Candle(candle) => {
+            let mut candle = candle.clone();
+            candle.symbol = symbol.clone();
+            candle.open = adjust(candle.open);
+            candle.high = adjust(candle.high);
+            candle.low = adjust(candle.low);
+            candle.close = adjust(candle.close);
+            candle.range = candle.high - candle.low;
+            Some(BaseDataEnum::Candle(candle))
+        }
+        BaseDataEnum::QuoteBar(bar) => {
+            let mut bar = bar.clone();
+            bar.symbol = symbol.clone();
+            bar.bid_open = adjust(bar.bid_open);
+            bar.bid_high = adjust(bar.bid_high);
+            bar.bid_low = adjust(bar.bid_low);
+            bar.bid_close = adjust(bar.bid_close);
+            bar.ask_open = adjust(bar.ask_open);
+            bar.ask_high = adjust(bar.ask_high);
+            bar.ask_low = adjust(bar.ask_low);
+            bar.ask_close = adjust(bar.ask_close);
+            bar.range = bar.ask_high - bar.bid_low;
+            bar.spread = bar.ask_close - bar.bid_close;
+            Some(BaseDataEnum::QuoteBar(bar))
+        }
+        BaseDataEnum::Tick(tick) => {
+            let mut tick = tick.clone();
+            tick.symbol = symbol.clone();
+            tick.price = adjust(tick.price);
+            Some(BaseDataEnum::Tick(tick))
+        }
+        BaseDataEnum::Quote(quote) => {
+            let mut quote = quote.clone();
+            quote.symbol = symbol.clone();
+            quote.bid = adjust(quote.bid);
+            quote.ask = adjust(quote.ask);
+            Some(BaseDataEnum::Quote(quote))
+        }
+        _ => None,
+    }
+}
+
+/// Joins the contracts into a continuous series for the symbol, the contracts must be sorted by expiry.
+pub fn build_continuous_series(symbol: &Symbol, contracts: &[ContractSeries], settings: &ContinuousContractSettings) -> Vec<BaseDataEnum> {
+    if contracts.is_empty() {
+        return vec![];
+    }
+
+    // the contract index and the start of each segment of the series
+    let mut segments: Vec<(usize, DateTime<Utc>)> = vec![(0, DateTime::<Utc>::MIN_UTC)];
+    for index in 1..contracts.len() {
+        let (front, last_roll) = *segments.last().unwrap();
+        let Some(date) = roll_date(&contracts[front], &contracts[index], &settings.roll) else {
+            continue;
+        };
+        let roll_time = day_start(date);
+        if roll_time > last_roll {
+            segments.push((index, roll_time));
+        }
+    }
+
+    // the gap at each roll, applied to every segment before it
+    let mut offsets = vec![dec!(0); segments.len()];
+    let mut factors = vec![dec!(1); segments.len()];
+    for i in (1..segments.len()).rev() {
+        let (front, _) = segments[i - 1];
+        let (next, roll_time) = segments[i];
+        let (gap, ratio) = match (price_before(&contracts[front].data, roll_time), price_before(&contracts[next].data, roll_time)) {
+            (Some(front_price), Some(next_price)) if front_price != dec!(0) => (next_price - front_price, next_price / front_price),
+            _ => (dec!(0), dec!(1)),
+        };
+        offsets[i - 1] = offsets[i] + gap;
+        factors[i - 1] = factors[i] * ratio;
+    }
+
+    let mut series = vec![];
+    for (segment, (index, start)) in segments.iter().enumerate() {
+        let end = segments.get(segment + 1).map(|(_, time)| *time).unwrap_or(DateTime::<Utc>::MAX_UTC);
+        let (offset, factor) = (offsets[segment], factors[segment]);
+        let adjust = |price: Price| match settings.adjustment {
+            AdjustmentMethod::BackAdjusted => price + offset,
+            AdjustmentMethod::RatioAdjusted => (price * factor).round_dp(price.scale()),
+            AdjustmentMethod::Unadjusted => price,
+        };
+        for base_data in &contracts[*index].data {
+            let time = base_data.time_utc();
+            if time >= *start && time < end {
+                if let Some(data) = adjusted_data(base_data, symbol, &adjust) {
+                    series.push(data);
+                }
+            }
+        }
+    }
+    series
+}
+
+impl HybridStorage {
+    /// Rebuilds the continuous contract from the individual contracts if any contract has newer data than the continuous series.
+    pub async fn update_continuous_contract(
+        &self,
+        symbol: &Symbol,
+        resolution: &Resolution,
+        data_type: &BaseDataType,
+        settings: &ContinuousContractSettings,
+    ) -> Result<(), FundForgeError> {
+        let Some(root) = continuous_root(&symbol.name) else {
+            return Err(FundForgeError::ClientSideErrorDebug(format!("{} is not a continuous contract", symbol.name)));
+        };
+        let market_path = self.base_path
+            .join(symbol.data_vendor.to_string())
+            .join(symbol.market_type.to_string());
+        let entries = match fs::read_dir(&market_path) {
+            Ok(entries) => entries,
+            Err(_) => return Ok(()),
+        };
+
+        let mut contracts = vec![];
+        let mut latest_contract_time = None;
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some((month, digits)) = parse_contract_code(root, &name) else {
+                continue;
+            };
+            let contract = Symbol::new(name.clone(), symbol.data_vendor.clone(), symbol.market_type.clone());
+            let earliest = self.get_earliest_data_time(&contract, resolution, data_type).await.ok().flatten();
+            let latest = self.get_latest_data_time(&contract, resolution, data_type).await.ok().flatten();
+            let (Some(earliest), Some(latest)) = (earliest, latest) else {
+                continue;
+            };
+            let Some(year) = contract_year(digits, earliest) else {
+                continue;
+            };
+            let mut data = self.get_data_range(&contract, resolution, data_type, earliest, latest).await?;
+            data.sort_by_key(|base_data| base_data.time_utc());
+            latest_contract_time = latest_contract_time.max(Some(latest));
+            contracts.push(ContractSeries { name, year, month, data });
+        }
+        let Some(latest_contract_time) = latest_contract_time else {
+            return Ok(());
+        };
+
+        let latest_continuous_time = self.get_latest_data_time(symbol, resolution, data_type).await.ok().flatten();
+        if latest_continuous_time.map_or(false, |time| time >= latest_contract_time) {
+            return Ok(());
+        }
+
+        contracts.sort_by_key(|contract| (contract.year, contract.month));
+        let series = build_continuous_series(symbol, &contracts, settings);
+
+        // the adjustments change the existing history, so the old series is replaced
+        let base_path = self.get_base_path(symbol, resolution, data_type, false);
+        if base_path.exists() {
+            let path_prefix = base_path.to_string_lossy().to_string();
+            self.mmap_cache.retain(|path, _| !path.starts_with(&path_prefix));
+            fs::remove_dir_all(&base_path)
+                .map_err(|e| FundForgeError::ServerErrorDebug(format!("Failed to remove continuous contract {}: {}", symbol.name, e)))?;
+        }
+        self.save_data_bulk(series).await
+            .map_err(|e| FundForgeError::ServerErrorDebug(format!("Failed to save continuous contract {}: {}", symbol.name, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use crate::standardized_types::base_data::candle::Candle;
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::{FuturesExchange, MarketType};
+    use crate::standardized_types::subscriptions::CandleType;
+
+    fn candle(name: &str, time: DateTime<Utc>, close: Price, volume: Volume) -> BaseDataEnum {
+        let symbol = Symbol::new(name.to_string(), DataVendor::DataBento, MarketType::Futures(FuturesExchange::CME));
+        let mut candle = Candle::new(symbol, close, volume, dec!(0), dec!(0), time.to_string(), Resolution::Hours(1), CandleType::CandleStick);
+        candle.is_closed = true;
+        BaseDataEnum::Candle(candle)
+    }
+
+    fn contract(name: &str, month: u32, days: std::ops::Range<i64>, close: Price, volume: Volume) -> ContractSeries {
+        let start = DateTime::parse_from_rfc3339("2024-03-01T14:00:00Z").unwrap().with_timezone(&Utc);
+        ContractSeries {
+            name: name.to_string(),
+            year: 2024,
+            month,
+            data: days.map(|day| candle(name, start + Duration::days(day), close, volume)).collect(),
+        }
+    }
+
+    fn closes(series: &[BaseDataEnum]) -> Vec<Price> {
+        series.iter().filter_map(reference_price).collect()
+    }
+
+    #[test]
+    fn test_parse_contract_code() {
+        assert_eq!(continuous_root("MNQ#C"), Some("MNQ"));
+        assert_eq!(continuous_root("MNQ"), None);
+        assert_eq!(parse_contract_code("MNQ", "MNQH24"), Some((3, "24")));
+        assert_eq!(parse_contract_code("MNQ", "MNQZ4"), Some((12, "4")));
+        assert_eq!(parse_contract_code("MNQ", "MNQ"), None);
+        assert_eq!(parse_contract_code("MNQ", "MNQ#C"), None);
+        assert_eq!(parse_contract_code("M", "MNQH24"), None);
+
+        let first_data = DateTime::parse_from_rfc3339("2019-09-15T00:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(contract_year("9", first_data), Some(2019));
+        assert_eq!(contract_year("0", first_data), Some(2020));
+        assert_eq!(contract_year("24", first_data), Some(2024));
+    }
+
+    #[test]
+    fn test_build_continuous_series() {
+        let symbol = Symbol::new("MNQ#C".to_string(), DataVendor::DataBento, MarketType::Futures(FuturesExchange::CME));
+        // the front contract trades 5 days, the next contract overtakes it on the 4th day
+        let contracts = vec![
+            contract("MNQH24", 3, 0..5, dec!(100), dec!(10)),
+            {
+                let mut next = contract("MNQM24", 6, 1..8, dec!(110), dec!(5));
+                for data in next.data.iter_mut().skip(2) {
+                    if let BaseDataEnum::Candle(candle) = data {
+                        candle.volume = dec!(20);
+                    }
+                }
+                next
+            },
+        ];
+
+        let mut settings = ContinuousContractSettings::new("MNQ".to_string());
+        let series = build_continuous_series(&symbol, &contracts, &settings);
+        assert!(series.iter().all(|data| data.symbol().name == "MNQ#C"));
+        // days 0 to 3 from the front contract, the roll is the day after the crossover
+        assert_eq!(closes(&series), vec![dec!(110), dec!(110), dec!(110), dec!(110), dec!(110), dec!(110), dec!(110), dec!(110)]);
+
+        settings.adjustment = AdjustmentMethod::Unadjusted;
+        let series = build_continuous_series(&symbol, &contracts, &settings);
+        assert_eq!(closes(&series), vec![dec!(100), dec!(100), dec!(100), dec!(100), dec!(110), dec!(110), dec!(110), dec!(110)]);
+
+        settings.adjustment = AdjustmentMethod::RatioAdjusted;
+        settings.roll = RollRule::DaysBeforeExpiry(1);
+        let series = build_continuous_series(&symbol, &contracts, &settings);
+        // the roll is the day before the last day of the front contract
+        assert_eq!(series.len(), 8);
+        assert_eq!(closes(&series)[..3], [dec!(110), dec!(110), dec!(110)]);
+    }
+}
@@ -49,9 +49,7 @@ impl HybridStorage {
 
                 if let Some(earliest_file) = days.first() {
                     if let Ok(mmap) = self.get_or_create_mmap(&earliest_file.path(), resolution.clone()).await {
-                        // Create a properly aligned copy
-                        let aligned_data = mmap.as_ref().to_vec();
-                        if let Ok(day_data) = BaseDataEnum::from_array_bytes(&aligned_data) {
+                        if let Ok(day_data) = BaseDataEnum::from_storage_bytes(mmap.as_ref()) {
                             return Ok(day_data.into_iter().map(|d| d.time_closed_utc()).min());
                         }
                     }
@@ -105,9 +103,7 @@ impl HybridStorage {
 
                 if let Some(latest_file) = days.first() {
                     if let Ok(mmap) = self.get_or_create_mmap(&latest_file.path(), resolution.clone()).await {
-                        // Create a properly aligned copy
-                        let aligned_data = mmap.as_ref().to_vec();
-                        if let Ok(day_data) = BaseDataEnum::from_array_bytes(&aligned_data) {
+                        if let Ok(day_data) = BaseDataEnum::from_storage_bytes(mmap.as_ref()) {
                             return Ok(day_data.into_iter().map(|d| d.time_closed_utc()).max());
                         }
                     }
@@ -132,9 +128,7 @@ impl HybridStorage {
         // If the file exists for the target date, check it first
         if file_path.exists() {
             if let Ok(mmap) = self.get_or_create_mmap(&file_path, resolution.clone()).await {
-                // Create a properly aligned copy of the memory-mapped data
-                let aligned_data = mmap.as_ref().to_vec();
-                if let Ok(day_data) = BaseDataEnum::from_array_bytes(&aligned_data) {
+                if let Ok(day_data) = BaseDataEnum::from_storage_bytes(mmap.as_ref()) {
                     //eprintln!("Found data for {} in {}", target_time, file_path.display());
                     // Find the latest data point that satisfies the condition
                     if let Some(latest_data) = day_data
@@ -183,9 +177,7 @@ impl HybridStorage {
             }
 
             if let Ok(mmap) = self.get_or_create_mmap(&file_path, resolution.clone()).await {
-                // Create a properly aligned copy of the memory-mapped data
-                let aligned_data = mmap.as_ref().to_vec();
-                if let Ok(day_data) = BaseDataEnum::from_array_bytes(&aligned_data) {
+                if let Ok(day_data) = BaseDataEnum::from_storage_bytes(mmap.as_ref()) {
                     //eprintln!("Found data for {}", current_date);
                     // Find the latest data point that satisfies the condition
                     if let Some(latest_data) = day_data
@@ -256,9 +248,7 @@ impl HybridStorage {
 
                     if file_path.exists() {
                         if let Ok(mmap) = self.get_or_create_mmap(&file_path, resolution.clone()).await {
-                            // Create a properly aligned copy of the memory-mapped data
-                            let aligned_data = mmap.as_ref().to_vec();
-                            if let Ok(mut day_data) = BaseDataEnum::from_array_bytes(&aligned_data) {
+                            if let Ok(mut day_data) = BaseDataEnum::from_storage_bytes(mmap.as_ref()) {
                                 // Filter data within the time range
                                 day_data.retain(|d| {
                                     let time = d.time_closed_utc();
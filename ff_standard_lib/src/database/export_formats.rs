@@ -4,8 +4,10 @@ use chrono::{DateTime, NaiveDate, Utc};
 use serde_json::json;
 use strum_macros::Display;
 use crate::database::hybrid_storage::HybridStorage;
+use crate::standardized_types::bar_time::BarTimeConvention;
 use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
 use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::base_data::traits::BaseData;
 use crate::standardized_types::subscriptions::DataSubscription;
 
 #[derive(Debug, Display)]
@@ -15,6 +17,7 @@ pub enum ExportFormat {
 }
 
 impl HybridStorage {
+    /// Exports the stored data of the subscription to a file per day, the candle times are written in the `time_convention`.
     pub async fn export_data(
         &self,
         subscription: &DataSubscription,
@@ -22,6 +25,7 @@ impl HybridStorage {
         end: DateTime<Utc>,
         export_folder: PathBuf,
         format: ExportFormat,
+        time_convention: BarTimeConvention,
     ) -> io::Result<()> {
         // Get list of files in range
         let files = match self.get_files_in_range(
@@ -64,13 +68,12 @@ impl HybridStorage {
 
             // Read and process file
             if let Ok(mmap) = self.get_or_create_mmap(&file_path, subscription.resolution.clone()).await {
-                let aligned_data = mmap.as_ref().to_vec();
-                let data = BaseDataEnum::from_array_bytes(&aligned_data)
-                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                let data = BaseDataEnum::from_storage_bytes(mmap.as_ref())
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
 
                 match format {
-                    ExportFormat::CSV => export_to_csv(&export_path, data, subscription.base_data_type.clone())?,
-                    ExportFormat::JSON => export_to_json(&export_path, data, subscription.base_data_type.clone())?,
+                    ExportFormat::CSV => export_to_csv(&export_path, data, subscription.base_data_type.clone(), time_convention)?,
+                    ExportFormat::JSON => export_to_json(&export_path, data, subscription.base_data_type.clone(), time_convention)?,
                 }
             }
 
@@ -104,7 +107,7 @@ fn get_date_from_filepath(path: &Path) -> io::Result<NaiveDate> {
         .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid date"))
 }
 
-fn export_to_csv(path: &Path, data: Vec<BaseDataEnum>, data_type: BaseDataType) -> io::Result<()> {
+fn export_to_csv(path: &Path, data: Vec<BaseDataEnum>, data_type: BaseDataType, time_convention: BarTimeConvention) -> io::Result<()> {
     let mut writer = csv::Writer::from_path(path)?;
 
     match data_type {
@@ -118,7 +121,7 @@ fn export_to_csv(path: &Path, data: Vec<BaseDataEnum>, data_type: BaseDataType)
                 if let BaseDataEnum::Candle(candle) = item {
                     writer.write_record(&[
                         candle.symbol.name.to_string(),
-                        candle.time,
+                        time_convention.time_of(&candle).to_string(),
                         candle.open.to_string(),
                         candle.high.to_string(),
                         candle.low.to_string(),
@@ -155,7 +158,7 @@ fn export_to_csv(path: &Path, data: Vec<BaseDataEnum>, data_type: BaseDataType)
     Ok(())
 }
 
-fn export_to_json(path: &Path, data: Vec<BaseDataEnum>, data_type: BaseDataType) -> io::Result<()> {
+fn export_to_json(path: &Path, data: Vec<BaseDataEnum>, data_type: BaseDataType, time_convention: BarTimeConvention) -> io::Result<()> {
     match data_type {
         BaseDataType::Candles => {
             let json_data: Vec<_> = data.into_iter()
@@ -163,7 +166,7 @@ fn export_to_json(path: &Path, data: Vec<BaseDataEnum>, data_type: BaseDataType)
                     if let BaseDataEnum::Candle(candle) = item {
                         Some(json!({
                             "symbol": candle.symbol.name,
-                            "time": candle.time,
+                            "time": time_convention.time_of(&candle).to_string(),
                             "open": candle.open,
                             "high": candle.high,
                             "low": candle.low,
@@ -215,7 +218,6 @@ mod tests {
     use tempfile::TempDir;
     use crate::server_launch_options::ServerLaunchOptions;
     use crate::standardized_types::base_data::candle::generate_5_day_candle_data;
-    use crate::standardized_types::base_data::traits::BaseData;
 
     fn setup_test_storage() -> (HybridStorage, TempDir) {
         let temp_dir = TempDir::new().unwrap();
@@ -264,7 +266,8 @@ mod tests {
             start,
             end,
             export_dir.path().to_path_buf(),
-            ExportFormat::CSV
+            ExportFormat::CSV,
+            BarTimeConvention::OpenTime,
         ).await;
         assert!(csv_result.is_ok(), "CSV export failed: {:?}", csv_result);
 
@@ -293,7 +296,8 @@ mod tests {
             start,
             end,
             export_dir.path().to_path_buf(),
-            ExportFormat::JSON
+            ExportFormat::JSON,
+            BarTimeConvention::OpenTime,
         ).await;
         assert!(json_result.is_ok(), "JSON export failed: {:?}", json_result);
 
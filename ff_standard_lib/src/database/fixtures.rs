@@ -0,0 +1,215 @@
+use std::io;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::database::hybrid_storage::HybridStorage;
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::base_data::quote::Quote;
+use crate::standardized_types::base_data::tick::{Aggressor, Tick};
+use crate::standardized_types::datavendor_enum::DataVendor;
+use crate::standardized_types::enums::MarketType;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::resolution::Resolution;
+use crate::standardized_types::subscriptions::Symbol;
+
+/// A symbol to synthesize, the random walk moves by whole ticks from the start price.
+/// # Properties
+/// * `symbol` - The symbol the data is saved for, the vendor and market type are part of the storage path.
+/// * `base_data_type` - `BaseDataType::Quotes` or `BaseDataType::Ticks`.
+/// * `start_price` - The price of the first data point of the fixture.
+/// * `tick_size` - The size of each price move, the spread is 1 to 3 ticks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixtureSymbol {
+    pub symbol: Symbol,
+    pub base_data_type: BaseDataType,
+    pub start_price: Price,
+    pub tick_size: Price,
+}
+
+impl FixtureSymbol {
+    pub fn new(symbol: Symbol, base_data_type: BaseDataType, start_price: Price, tick_size: Price) -> Self {
+        FixtureSymbol {
+            symbol,
+            base_data_type,
+            start_price,
+            tick_size,
+        }
+    }
+
+    /// The symbols of the default fixture, EUR-USD and AUD-USD quotes and EUR-USD ticks from Oanda, as used by the example strategies.
+    pub fn defaults() -> Vec<FixtureSymbol> {
+        let eur_usd = Symbol::new("EUR-USD".to_string(), DataVendor::Oanda, MarketType::Forex);
+        let aud_usd = Symbol::new("AUD-USD".to_string(), DataVendor::Oanda, MarketType::Forex);
+        vec![
+            FixtureSymbol::new(eur_usd.clone(), BaseDataType::Quotes, dec!(1.08500), dec!(0.00001)),
+            FixtureSymbol::new(aud_usd, BaseDataType::Quotes, dec!(0.66500), dec!(0.00001)),
+            FixtureSymbol::new(eur_usd, BaseDataType::Ticks, dec!(1.08500), dec!(0.00001)),
+        ]
+    }
+}
+
+/// The trading days of the fixture, `days` weekdays from the start date.
+pub fn fixture_dates(start: NaiveDate, days: u32) -> Vec<NaiveDate> {
+    start.iter_days()
+        .filter(|date| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+        .take(days as usize)
+        .collect()
+}
+
+/// Synthesizes `days` weekdays of data for the symbol, a data point every 0.2 to 3 seconds through each day.
+/// The same seed always produces the same data.
+pub fn synthesize(fixture: &FixtureSymbol, start: NaiveDate, days: u32, seed: u64) -> Vec<BaseDataEnum> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let tick_size = match fixture.tick_size > dec!(0) {
+        true => fixture.tick_size,
+        false => dec!(0.00001),
+    };
+    let mut bid_ticks = (fixture.start_price / tick_size).round().max(dec!(1));
+    let mut data = vec![];
+    for date in fixture_dates(start, days) {
+        let open: DateTime<Utc> = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let close = open + Duration::days(1);
+        let mut time = open + Duration::milliseconds(rng.gen_range(200..=3000));
+        while time < close {
+            bid_ticks = (bid_ticks + Decimal::from(rng.gen_range(-2..=2))).max(dec!(1));
+            let spread_ticks = Decimal::from(rng.gen_range(1..=3));
+            let bid = bid_ticks * tick_size;
+            let ask = (bid_ticks + spread_ticks) * tick_size;
+            let base_data = match fixture.base_data_type {
+                BaseDataType::Ticks => {
+                    let (price, aggressor) = match rng.gen_bool(0.5) {
+                        true => (ask, Aggressor::Buy),
+                        false => (bid, Aggressor::Sell),
+                    };
+                    BaseDataEnum::Tick(Tick::new(fixture.symbol.clone(), price, time.to_string(), Decimal::from(rng.gen_range(1..=5)), aggressor))
+                }
+                _ => BaseDataEnum::Quote(Quote::new(fixture.symbol.clone(), ask, bid, Decimal::from(rng.gen_range(1..=20)), Decimal::from(rng.gen_range(1..=20)), time.to_string())),
+            };
+            data.push(base_data);
+            time += Duration::milliseconds(rng.gen_range(200..=3000));
+        }
+    }
+    data
+}
+
+impl HybridStorage {
+    /// Synthesizes the fixture symbols and saves them to this storage, returns the number of data points saved.
+    /// Each symbol is seeded with `seed` plus its position in `symbols`.
+    pub async fn save_synthetic_fixture(&self, symbols: &[FixtureSymbol], start: NaiveDate, days: u32, seed: u64) -> io::Result<usize> {
+        let mut saved = 0;
+        for (index, fixture) in symbols.iter().enumerate() {
+            let data = synthesize(fixture, start, days, seed.wrapping_add(index as u64));
+            saved += data.len();
+            self.save_data_bulk(data).await?;
+        }
+        Ok(saved)
+    }
+
+    /// Copies `days` weekdays of stored data from this storage into the `fixture` storage, keeping every nth data point.
+    /// Returns the number of data points saved, 0 if this storage has no data for the symbol in the range.
+    pub async fn save_subsampled_fixture(
+        &self,
+        fixture: &HybridStorage,
+        symbol: &Symbol,
+        resolution: &Resolution,
+        data_type: &BaseDataType,
+        start: NaiveDate,
+        days: u32,
+        keep_every: usize,
+    ) -> Result<usize, FundForgeError> {
+        let keep_every = keep_every.max(1);
+        let mut saved = 0;
+        for date in fixture_dates(start, days) {
+            let open = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+            let close = open + Duration::days(1) - Duration::nanoseconds(1);
+            let data: Vec<BaseDataEnum> = self.get_data_range(symbol, resolution, data_type, open, close).await?
+                .into_iter()
+                .step_by(keep_every)
+                .collect();
+            saved += data.len();
+            fixture.save_data_bulk(data).await
+                .map_err(|e| FundForgeError::ServerErrorDebug(format!("Failed to save fixture data for {}: {}", symbol.name, e)))?;
+        }
+        Ok(saved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration as StdDuration;
+    use tempfile::TempDir;
+    use crate::server_launch_options::ServerLaunchOptions;
+    use crate::standardized_types::base_data::traits::BaseData;
+
+    fn storage(folder: &TempDir) -> HybridStorage {
+        let options = ServerLaunchOptions {
+            data_folder: folder.path().to_path_buf(),
+            ..Default::default()
+        };
+        HybridStorage::new(StdDuration::from_secs(3600), options, 5, 300)
+    }
+
+    #[test]
+    fn test_fixture_dates_skip_weekends() {
+        // 2024-06-07 is a friday
+        let dates = fixture_dates(NaiveDate::from_ymd_opt(2024, 6, 7).unwrap(), 3);
+        assert_eq!(dates, vec![
+            NaiveDate::from_ymd_opt(2024, 6, 7).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 6, 11).unwrap(),
+        ]);
+    }
+
+    #[test]
+    fn test_synthesize_is_deterministic() {
+        let fixture = &FixtureSymbol::defaults()[0];
+        let start = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let data = synthesize(fixture, start, 1, 7);
+        assert_eq!(data, synthesize(fixture, start, 1, 7));
+        assert_ne!(data, synthesize(fixture, start, 1, 8));
+
+        let mut last_time = None;
+        for base_data in &data {
+            let BaseDataEnum::Quote(quote) = base_data else {
+                panic!("Expected quotes");
+            };
+            assert!(quote.bid < quote.ask);
+            assert_eq!(quote.bid % fixture.tick_size, dec!(0));
+            let time = base_data.time_closed_utc();
+            assert_eq!(time.date_naive(), start);
+            assert!(last_time.map_or(true, |last_time| time > last_time));
+            last_time = Some(time);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fixture_round_trip() {
+        let data_folder = TempDir::new().unwrap();
+        let fixture_folder = TempDir::new().unwrap();
+        let data_storage = storage(&data_folder);
+        let fixture_storage = storage(&fixture_folder);
+        let fixture = FixtureSymbol::defaults()[2].clone();
+        let start = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+
+        let data = synthesize(&fixture, start, 2, 1);
+        let saved = data_storage.save_synthetic_fixture(&[fixture.clone()], start, 2, 1).await.unwrap();
+        assert_eq!(saved, data.len());
+
+        // every 10th tick of each day is kept
+        let expected: usize = fixture_dates(start, 2).iter()
+            .map(|date| data.iter().filter(|tick| tick.time_closed_utc().date_naive() == *date).count())
+            .map(|count| (count + 9) / 10)
+            .sum();
+        let copied = data_storage.save_subsampled_fixture(&fixture_storage, &fixture.symbol, &Resolution::Instant, &BaseDataType::Ticks, start, 2, 10).await.unwrap();
+        assert_eq!(copied, expected);
+
+        let end = start.and_hms_opt(0, 0, 0).unwrap().and_utc() + Duration::days(2);
+        let ticks = fixture_storage.get_data_range(&fixture.symbol, &Resolution::Instant, &BaseDataType::Ticks, start.and_hms_opt(0, 0, 0).unwrap().and_utc(), end).await.unwrap();
+        assert_eq!(ticks.len(), copied);
+    }
+}
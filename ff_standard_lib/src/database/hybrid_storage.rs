@@ -21,6 +21,7 @@ use tokio::time::{interval, timeout};
 use crate::messages::data_server_messaging::FundForgeError;
 use crate::server_launch_options::ServerLaunchOptions;
 use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::base_data::data_quality::DataQualityFilter;
 use crate::standardized_types::base_data::traits::BaseData;
 use crate::standardized_types::resolution::Resolution;
 use crate::standardized_types::subscriptions::{DataSubscription, Symbol, SymbolName};
@@ -37,11 +38,14 @@ pub struct HybridStorage {
     pub options: ServerLaunchOptions,
     pub download_semaphore: Arc<Semaphore>,
     pub update_seconds: u64,
+    /// Checks the data saved to the store, see `--outliers`.
+    pub data_quality: DataQualityFilter,
 }
 
 impl HybridStorage {
     pub fn new(clear_cache_duration: Duration, options: ServerLaunchOptions, max_concurrent_downloads: usize, update_seconds: u64) -> Self {
         let max_concurrent_downloads= min(max_concurrent_downloads, 35);
+        let data_quality = DataQualityFilter::new(options.outlier_action, options.outlier_threshold, options.outlier_min_band, false);
         let storage = Self {
             base_path: options.data_folder.clone().join("historical"),
             mmap_cache: Arc::new(DashMap::new()),
@@ -51,7 +55,8 @@ impl HybridStorage {
             download_tasks:Default::default(),
             options,
             download_semaphore: Arc::new(Semaphore::new(max_concurrent_downloads)),
-            update_seconds
+            update_seconds,
+            data_quality,
         };
 
         storage
@@ -141,6 +146,10 @@ impl HybridStorage {
         if !data.is_closed() {
             return Ok(());
         }
+        let Some(data) = self.data_quality.clean(data.clone()) else {
+            return Ok(());
+        };
+        let data = &data;
 
         let file_path = self.get_file_path(
             data.symbol(),
@@ -153,6 +162,7 @@ impl HybridStorage {
     }
 
     pub async fn save_data_bulk(&self, data: Vec<BaseDataEnum>) -> io::Result<()> {
+        let data = self.data_quality.clean_bulk(data);
         if data.is_empty() {
             return Ok(());
         }
@@ -385,8 +395,8 @@ impl HybridStorage {
                 }
             }
 
-            BaseDataEnum::from_array_bytes(&decompressed)
-                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            BaseDataEnum::from_storage_bytes(&decompressed)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?
         } else {
             Vec::new()
         };
@@ -402,8 +412,8 @@ impl HybridStorage {
 
         let all_data: Vec<BaseDataEnum> = data_map.into_values().collect();
 
-        // Serialize with rkyv
-        let bytes = BaseDataEnum::vec_to_bytes(all_data);
+        // Serialize with rkyv, with the schema version footer
+        let bytes = BaseDataEnum::vec_to_storage_bytes(all_data);
 
         // Compress the serialized data
         // Compress the serialized data using a Cursor
@@ -2,4 +2,7 @@ pub mod hybrid_storage;
 pub mod decompressed_functions;
 pub mod exchange_rate;
 pub mod export_formats;
+pub mod continuous_contracts;
+pub mod fixtures;
+pub mod scanner;
 mod catalog;
\ No newline at end of file
@@ -0,0 +1,356 @@
+use std::fmt;
+use std::str::FromStr;
+use chrono::{DateTime, Duration, Utc};
+use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::database::hybrid_storage::HybridStorage;
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::resolution::Resolution;
+use crate::standardized_types::subscriptions::Symbol;
+
+/// The days of data loaded before the scan time for the day metrics and as a buffer for gaps in the data.
+const DAY_LOOKBACK: i64 = 5;
+
+/// A value measured by a scan.
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum ScanMetric {
+    /// The average true range of the last `period` bars of the resolution.
+    AverageTrueRange { period: u64, resolution: Resolution },
+    /// The high minus the low of the last day with data.
+    DayRange,
+    /// The percentage change of the last price from the close of the previous day with data.
+    DayChangePercent,
+    /// The volume of the last day with data, 0 for quotes.
+    DayVolume,
+    /// The last price.
+    Price,
+}
+
+impl ScanMetric {
+    /// How far before the scan time the data is loaded.
+    fn lookback(&self) -> Duration {
+        match self {
+            ScanMetric::AverageTrueRange { period, resolution } => resolution.as_duration() * (*period as i32 + 1) + Duration::days(DAY_LOOKBACK),
+            _ => Duration::days(DAY_LOOKBACK),
+        }
+    }
+
+    /// The value of the metric for the data, None if there is not enough data.
+    pub fn evaluate(&self, data: &[BaseDataEnum]) -> Option<Decimal> {
+        match self {
+            ScanMetric::AverageTrueRange { period, resolution } => {
+                let period = *period as usize;
+                let bars = scan_bars(data, resolution.as_seconds());
+                if period == 0 || bars.len() < period + 1 {
+                    return None;
+                }
+                let bars = &bars[bars.len() - period - 1..];
+                let true_range_sum: Decimal = bars.windows(2)
+                    .map(|pair| {
+                        let (previous, bar) = (&pair[0], &pair[1]);
+                        (bar.high - bar.low)
+                            .max((bar.high - previous.close).abs())
+                            .max((bar.low - previous.close).abs())
+                    })
+                    .sum();
+                Some(true_range_sum / Decimal::from(period))
+            }
+            ScanMetric::DayRange => scan_bars(data, Duration::days(1).num_seconds()).last().map(|day| day.high - day.low),
+            ScanMetric::DayChangePercent => {
+                let days = scan_bars(data, Duration::days(1).num_seconds());
+                if days.len() < 2 {
+                    return None;
+                }
+                let (previous, day) = (&days[days.len() - 2], &days[days.len() - 1]);
+                match previous.close == dec!(0) {
+                    true => None,
+                    false => Some((day.close - previous.close) / previous.close * dec!(100)),
+                }
+            }
+            ScanMetric::DayVolume => scan_bars(data, Duration::days(1).num_seconds()).last().map(|day| day.volume),
+            ScanMetric::Price => scan_bars(data, Duration::days(1).num_seconds()).last().map(|day| day.close),
+        }
+    }
+}
+
+impl fmt::Display for ScanMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanMetric::AverageTrueRange { period, resolution } => write!(f, "atr({},{})", period, resolution),
+            ScanMetric::DayRange => write!(f, "day_range"),
+            ScanMetric::DayChangePercent => write!(f, "day_change%"),
+            ScanMetric::DayVolume => write!(f, "day_volume"),
+            ScanMetric::Price => write!(f, "price"),
+        }
+    }
+}
+
+impl FromStr for ScanMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let metric = s.trim().to_lowercase();
+        if let Some(arguments) = metric.strip_prefix("atr(").and_then(|rest| rest.strip_suffix(')')) {
+            let (period, resolution) = arguments.split_once(',')
+                .ok_or_else(|| format!("Expected atr(period,resolution) in {}", s))?;
+            let period = period.trim().parse::<u64>()
+                .map_err(|_| format!("Invalid atr period in {}", s))?;
+            let resolution = Resolution::from_str(resolution.trim())?;
+            if period == 0 || resolution.as_seconds() == 0 {
+                return Err(format!("The atr needs a period and a time resolution, eg atr(14,5-M): {}", s));
+            }
+            return Ok(ScanMetric::AverageTrueRange { period, resolution });
+        }
+        match metric.as_str() {
+            "day_range" => Ok(ScanMetric::DayRange),
+            "day_change%" => Ok(ScanMetric::DayChangePercent),
+            "day_volume" => Ok(ScanMetric::DayVolume),
+            "price" => Ok(ScanMetric::Price),
+            _ => Err(format!("Unknown scan metric: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Eq, Debug)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum ScanComparison {
+    GreaterThan,
+    GreaterOrEqual,
+    LessThan,
+    LessOrEqual,
+}
+
+impl fmt::Display for ScanComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanComparison::GreaterThan => write!(f, ">"),
+            ScanComparison::GreaterOrEqual => write!(f, ">="),
+            ScanComparison::LessThan => write!(f, "<"),
+            ScanComparison::LessOrEqual => write!(f, "<="),
+        }
+    }
+}
+
+/// A condition of a scan, see `strategy.scan()`.
+/// ```rust
+/// let condition = ScanCondition::from_str("atr(14,5-M) > 0.0005").unwrap();
+/// let condition = ScanCondition::new(ScanMetric::DayRange, ScanComparison::GreaterThan, dec!(0.01));
+/// ```
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct ScanCondition {
+    pub metric: ScanMetric,
+    pub comparison: ScanComparison,
+    pub value: Decimal,
+}
+
+impl ScanCondition {
+    pub fn new(metric: ScanMetric, comparison: ScanComparison, value: Decimal) -> Self {
+        ScanCondition {
+            metric,
+            comparison,
+            value,
+        }
+    }
+
+    pub fn is_met(&self, value: Decimal) -> bool {
+        match self.comparison {
+            ScanComparison::GreaterThan => value > self.value,
+            ScanComparison::GreaterOrEqual => value >= self.value,
+            ScanComparison::LessThan => value < self.value,
+            ScanComparison::LessOrEqual => value <= self.value,
+        }
+    }
+}
+
+impl fmt::Display for ScanCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.metric, self.comparison, self.value)
+    }
+}
+
+impl FromStr for ScanCondition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // the two character comparisons are checked first so `>=` is not read as `>`
+        let comparisons = [
+            (">=", ScanComparison::GreaterOrEqual),
+            ("<=", ScanComparison::LessOrEqual),
+            (">", ScanComparison::GreaterThan),
+            ("<", ScanComparison::LessThan),
+        ];
+        for (operator, comparison) in comparisons {
+            if let Some((metric, value)) = s.split_once(operator) {
+                let value = Decimal::from_str(value.trim())
+                    .map_err(|_| format!("Invalid value in scan condition: {}", s))?;
+                return Ok(ScanCondition::new(ScanMetric::from_str(metric)?, comparison, value));
+            }
+        }
+        Err(format!("Expected metric > value, >=, < or <= in scan condition: {}", s))
+    }
+}
+
+/// A symbol which met every condition of a scan, `values` are the measured values in the order of the conditions.
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct ScanMatch {
+    pub symbol: Symbol,
+    pub values: Vec<Decimal>,
+}
+
+impl fmt::Display for ScanMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<String> = self.values.iter().map(|value| value.round_dp(8).normalize().to_string()).collect();
+        write!(f, "{}: {}", self.symbol.name, values.join(", "))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct ScanBar {
+    start: i64,
+    high: Price,
+    low: Price,
+    close: Price,
+    volume: Volume,
+}
+
+/// Rolls the data into bars of `seconds`, keyed by the open time of the data.
+fn scan_bars(data: &[BaseDataEnum], seconds: i64) -> Vec<ScanBar> {
+    let mut bars: Vec<ScanBar> = vec![];
+    if seconds <= 0 {
+        return bars;
+    }
+    for base_data in data {
+        let (high, low, close, volume) = match base_data {
+            BaseDataEnum::Candle(candle) => (candle.high, candle.low, candle.close, candle.volume),
+            BaseDataEnum::QuoteBar(bar) => ((bar.bid_high + bar.ask_high) / dec!(2), (bar.bid_low + bar.ask_low) / dec!(2), (bar.bid_close + bar.ask_close) / dec!(2), bar.volume),
+            BaseDataEnum::Tick(tick) => (tick.price, tick.price, tick.price, tick.volume),
+            BaseDataEnum::Quote(quote) => {
+                let mid = (quote.bid + quote.ask) / dec!(2);
+                (mid, mid, mid, dec!(0))
+            }
+            _ => continue,
+        };
+        let start = base_data.time_utc().timestamp().div_euclid(seconds);
+        match bars.last_mut() {
+            Some(bar) if bar.start == start => {
+                bar.high = bar.high.max(high);
+                bar.low = bar.low.min(low);
+                bar.close = close;
+                bar.volume += volume;
+            }
+            _ => bars.push(ScanBar { start, high, low, close, volume }),
+        }
+    }
+    bars
+}
+
+impl HybridStorage {
+    /// Evaluates the conditions for each symbol with the stored `base_data_type` data of the `resolution` before `time`, returns the symbols which met every condition.
+    /// Symbols without enough data for a condition do not match.
+    pub async fn scan(
+        &self,
+        symbols: &[Symbol],
+        base_data_type: &BaseDataType,
+        resolution: &Resolution,
+        conditions: &[ScanCondition],
+        time: DateTime<Utc>,
+    ) -> Result<Vec<ScanMatch>, FundForgeError> {
+        if conditions.is_empty() {
+            return Err(FundForgeError::ClientSideErrorDebug("A scan needs at least one condition".to_string()));
+        }
+        for condition in conditions {
+            if let ScanMetric::AverageTrueRange { resolution: metric_resolution, .. } = &condition.metric {
+                if resolution.as_duration() > metric_resolution.as_duration() {
+                    return Err(FundForgeError::ClientSideErrorDebug(format!("Can not scan {} with {} data", condition, resolution)));
+                }
+            }
+        }
+        let lookback = conditions.iter().map(|condition| condition.metric.lookback()).max().unwrap_or_else(|| Duration::days(DAY_LOOKBACK));
+        let mut matches = vec![];
+        for symbol in symbols {
+            let data = self.get_data_range(symbol, resolution, base_data_type, time - lookback, time).await?;
+            let data: Vec<BaseDataEnum> = data.into_iter().filter(|base_data| base_data.time_closed_utc() <= time).collect();
+            if data.is_empty() {
+                continue;
+            }
+            let mut values = Vec::with_capacity(conditions.len());
+            for condition in conditions {
+                match condition.metric.evaluate(&data) {
+                    Some(value) if condition.is_met(value) => values.push(value),
+                    _ => break,
+                }
+            }
+            if values.len() == conditions.len() {
+                matches.push(ScanMatch { symbol: symbol.clone(), values });
+            }
+        }
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::standardized_types::base_data::tick::{Aggressor, Tick};
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::MarketType;
+
+    fn tick(price: Price, time: DateTime<Utc>) -> BaseDataEnum {
+        let symbol = Symbol::new("EUR-USD".to_string(), DataVendor::Oanda, MarketType::Forex);
+        BaseDataEnum::Tick(Tick::new(symbol, price, time.to_string(), dec!(1), Aggressor::None))
+    }
+
+    #[test]
+    fn test_parse_scan_conditions() {
+        let atr = ScanCondition::from_str("atr(14,5-M) > 0.0005").unwrap();
+        assert_eq!(atr, ScanCondition::new(ScanMetric::AverageTrueRange { period: 14, resolution: Resolution::Minutes(5) }, ScanComparison::GreaterThan, dec!(0.0005)));
+        assert_eq!(ScanCondition::from_str(&atr.to_string()).unwrap(), atr);
+
+        let change = ScanCondition::from_str("day_change% >= -1.5").unwrap();
+        assert_eq!(change.comparison, ScanComparison::GreaterOrEqual);
+        assert_eq!(change.value, dec!(-1.5));
+        assert!(change.is_met(dec!(-1.5)));
+        assert!(!change.is_met(dec!(-2)));
+
+        assert!(ScanCondition::from_str("atr(14,100-T) > 1").is_err());
+        assert!(ScanCondition::from_str("range > 1").is_err());
+        assert!(ScanCondition::from_str("price 1").is_err());
+    }
+
+    #[test]
+    fn test_scan_metrics() {
+        let monday = Utc.with_ymd_and_hms(2024, 6, 3, 9, 0, 0).unwrap();
+        let tuesday = monday + Duration::days(1);
+        let data = vec![
+            tick(dec!(1.0), monday),
+            tick(dec!(1.2), monday + Duration::minutes(1)),
+            tick(dec!(1.0), tuesday),
+            tick(dec!(1.3), tuesday + Duration::minutes(5)),
+            tick(dec!(0.9), tuesday + Duration::minutes(6)),
+            tick(dec!(1.1), tuesday + Duration::minutes(10)),
+        ];
+        assert_eq!(ScanMetric::Price.evaluate(&data), Some(dec!(1.1)));
+        assert_eq!(ScanMetric::DayRange.evaluate(&data), Some(dec!(0.4)));
+        assert_eq!(ScanMetric::DayVolume.evaluate(&data), Some(dec!(4)));
+        assert_eq!(ScanMetric::DayChangePercent.evaluate(&data).map(|change| change.round_dp(4)), Some(dec!(-8.3333)));
+
+        // 5 minute bars on tuesday: 1.0, 1.3-0.9 closing 0.9, 1.1
+        let atr = ScanMetric::AverageTrueRange { period: 2, resolution: Resolution::Minutes(5) };
+        assert_eq!(atr.evaluate(&data), Some((dec!(0.4) + dec!(0.2)) / dec!(2)));
+        let long_atr = ScanMetric::AverageTrueRange { period: 20, resolution: Resolution::Minutes(5) };
+        assert_eq!(long_atr.evaluate(&data), None);
+    }
+}
@@ -0,0 +1,180 @@
+use rust_decimal_macros::dec;
+use crate::helpers::decimal_calculators::{ceil_to_tick_size, floor_to_tick_size};
+use crate::standardized_types::accounts::Currency;
+use crate::standardized_types::base_data::quote::Quote;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::subscriptions::{Symbol, SymbolName};
+use crate::standardized_types::symbol_info::SymbolInfo;
+
+/// The pivot currencies tried in order when looking for the legs of a cross.
+const PIVOT_CURRENCIES: [&str; 8] = ["USD", "EUR", "GBP", "JPY", "CHF", "AUD", "CAD", "NZD"];
+
+/// The pairs a cross rate is derived from.
+///
+/// # Properties
+/// * `cross` - The synthetic pair, eg "EUR-AUD".
+/// * `base_leg` - The pair of the base currency and the pivot, eg "EUR-USD".
+/// * `base_inverted` - True if the pivot is the base currency of the base leg, eg "USD-EUR".
+/// * `quote_leg` - The pair of the quote currency and the pivot, eg "AUD-USD".
+/// * `quote_inverted` - True if the pivot is the base currency of the quote leg, eg "USD-JPY" for "EUR-JPY".
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrossRateLegs {
+    pub cross: SymbolName,
+    pub base_leg: SymbolName,
+    pub base_inverted: bool,
+    pub quote_leg: SymbolName,
+    pub quote_inverted: bool,
+}
+
+fn split_pair(symbol_name: &str) -> Option<(&str, &str)> {
+    let (base, quote) = symbol_name.split_once('-')?;
+    match base.len() == 3 && quote.len() == 3 {
+        true => Some((base, quote)),
+        false => None,
+    }
+}
+
+/// Returns the pair of the currency and the pivot which is available, and true if it is quoted as pivot-currency.
+fn find_leg(currency: &str, pivot: &str, is_available: &impl Fn(&str) -> bool) -> Option<(SymbolName, bool)> {
+    let direct = format!("{}-{}", currency, pivot);
+    if is_available(&direct) {
+        return Some((direct, false));
+    }
+    let inverted = format!("{}-{}", pivot, currency);
+    if is_available(&inverted) {
+        return Some((inverted, true));
+    }
+    None
+}
+
+/// Returns the legs of the cross if it can be derived from two available pairs through one of the pivot currencies.
+pub fn find_cross_legs(cross: &str, is_available: impl Fn(&str) -> bool) -> Option<CrossRateLegs> {
+    let (base, quote) = split_pair(cross)?;
+    for pivot in PIVOT_CURRENCIES {
+        if pivot == base || pivot == quote {
+            continue;
+        }
+        let (Some((base_leg, base_inverted)), Some((quote_leg, quote_inverted))) = (find_leg(base, pivot, &is_available), find_leg(quote, pivot, &is_available)) else {
+            continue;
+        };
+        return Some(CrossRateLegs {
+            cross: cross.to_string(),
+            base_leg,
+            base_inverted,
+            quote_leg,
+            quote_inverted,
+        });
+    }
+    None
+}
+
+/// The cross price on one side, `base` and `quote` are the leg prices on that side of the cross,
+/// the bid of a leg quoted as pivot-currency is the ask of its inverse, so an inverted leg uses the other side of its quote.
+/// The legs are multiplied and divided once to keep the exact decimal result.
+fn cross_price(base: Price, base_inverted: bool, quote: Price, quote_inverted: bool) -> Price {
+    let (mut numerator, mut denominator) = (dec!(1), dec!(1));
+    match base_inverted {
+        false => numerator *= base,
+        true => denominator *= base,
+    }
+    match quote_inverted {
+        false => denominator *= quote,
+        true => numerator *= quote,
+    }
+    numerator / denominator
+}
+
+/// The tick size of the cross, 0.001 for JPY crosses and 0.00001 for the rest.
+pub fn cross_tick_size(cross: &str) -> Price {
+    match split_pair(cross) {
+        Some((_, "JPY")) | Some((_, "HUF")) => dec!(0.001),
+        _ => dec!(0.00001),
+    }
+}
+
+/// Returns the synthetic quote of the cross from the latest quote of each leg.
+pub fn triangulate(legs: &CrossRateLegs, symbol: &Symbol, base_leg: &Quote, quote_leg: &Quote) -> Option<Quote> {
+    if [base_leg.bid, base_leg.ask, quote_leg.bid, quote_leg.ask].iter().any(|price| *price <= dec!(0)) {
+        return None;
+    }
+    let tick_size = cross_tick_size(&legs.cross);
+    // selling the base currency for the pivot and buying the quote currency with it
+    let bid = match legs.base_inverted { false => base_leg.bid, true => base_leg.ask };
+    let quote_for_bid = match legs.quote_inverted { false => quote_leg.ask, true => quote_leg.bid };
+    let bid = floor_to_tick_size(cross_price(bid, legs.base_inverted, quote_for_bid, legs.quote_inverted), tick_size);
+    let ask = match legs.base_inverted { false => base_leg.ask, true => base_leg.bid };
+    let quote_for_ask = match legs.quote_inverted { false => quote_leg.bid, true => quote_leg.ask };
+    let ask = ceil_to_tick_size(cross_price(ask, legs.base_inverted, quote_for_ask, legs.quote_inverted), tick_size);
+    let time = base_leg.time_utc().max(quote_leg.time_utc());
+    Some(Quote::new(symbol.clone(), ask, bid, dec!(0), dec!(0), time.to_string()))
+}
+
+/// The symbol info of a triangulated cross, flagged as synthetic.
+pub fn cross_symbol_info(cross: &str) -> Option<SymbolInfo> {
+    let (base, quote) = split_pair(cross)?;
+    let tick_size = cross_tick_size(cross);
+    Some(SymbolInfo {
+        symbol_name: cross.to_string(),
+        base_currency: Some(Currency::from_str(base)),
+        pnl_currency: Currency::from_str(quote),
+        value_per_tick: tick_size, // 1 tick in the quote currency for 1 unit
+        tick_size,
+        decimal_accuracy: tick_size.scale(),
+        is_synthetic: true,
+        is_reference: false,
+        display_precision: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::MarketType;
+
+    fn quote(name: &str, bid: Price, ask: Price) -> Quote {
+        let symbol = Symbol::new(name.to_string(), DataVendor::DataBento, MarketType::Forex);
+        Quote::new(symbol, ask, bid, dec!(1000), dec!(1000), Utc::now().to_string())
+    }
+
+    #[test]
+    fn test_find_cross_legs() {
+        let available = ["EUR-USD", "AUD-USD", "USD-JPY"];
+        let is_available = |name: &str| available.contains(&name);
+
+        let legs = find_cross_legs("EUR-AUD", is_available).unwrap();
+        assert_eq!((legs.base_leg.as_str(), legs.base_inverted), ("EUR-USD", false));
+        assert_eq!((legs.quote_leg.as_str(), legs.quote_inverted), ("AUD-USD", false));
+
+        let legs = find_cross_legs("AUD-JPY", is_available).unwrap();
+        assert_eq!((legs.quote_leg.as_str(), legs.quote_inverted), ("USD-JPY", true));
+
+        assert_eq!(find_cross_legs("EUR-GBP", is_available), None);
+        assert_eq!(find_cross_legs("EURAUD", is_available), None);
+    }
+
+    #[test]
+    fn test_triangulate() {
+        let symbol = Symbol::new("EUR-AUD".to_string(), DataVendor::DataBento, MarketType::Forex);
+        let legs = find_cross_legs("EUR-AUD", |name| name == "EUR-USD" || name == "AUD-USD").unwrap();
+        let cross = triangulate(&legs, &symbol, &quote("EUR-USD", dec!(1.10000), dec!(1.10010)), &quote("AUD-USD", dec!(0.66000), dec!(0.66010))).unwrap();
+        // 1.10000 / 0.66010 = 1.666414... and 1.10010 / 0.66000 = 1.666818...
+        assert_eq!(cross.bid, dec!(1.66641));
+        assert_eq!(cross.ask, dec!(1.66682));
+        assert_eq!(cross.bid_volume, dec!(0));
+
+        // an inverted quote leg, the yen per aud is the aud in usd over the yen in usd
+        let symbol = Symbol::new("AUD-JPY".to_string(), DataVendor::DataBento, MarketType::Forex);
+        let legs = find_cross_legs("AUD-JPY", |name| name == "AUD-USD" || name == "USD-JPY").unwrap();
+        let cross = triangulate(&legs, &symbol, &quote("AUD-USD", dec!(0.66000), dec!(0.66010)), &quote("USD-JPY", dec!(150.000), dec!(150.010))).unwrap();
+        assert_eq!(cross.bid, dec!(99.000));
+        assert_eq!(cross.ask, dec!(99.022));
+
+        let info = cross_symbol_info("AUD-JPY").unwrap();
+        assert!(info.is_synthetic);
+        assert_eq!(info.decimal_accuracy, 3);
+        assert_eq!(info.pnl_currency, Currency::JPY);
+    }
+}
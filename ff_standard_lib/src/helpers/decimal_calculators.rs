@@ -7,6 +7,7 @@ use crate::standardized_types::enums::{PositionSide};
 use crate::standardized_types::new_types::{Price, Volume};
 use crate::standardized_types::symbol_info::SymbolInfo;
 
+/// The pnl in the account currency, `exchange_rate_multiplier` is the units of the account currency for one unit of the pnl currency.
 pub fn calculate_theoretical_pnl(
     brokerage: Brokerage,
     side: PositionSide,
@@ -72,8 +73,8 @@ pub fn calculate_theoretical_pnl(
             pnl
         } else {
             // Case 3: Account currency is neither
-            // Example: EUR account trading AUD/JPY
-            pnl / exchange_rate_multiplier
+            // Example: EUR account trading AUD/JPY, the multiplier is the JPY-EUR rate
+            pnl * exchange_rate_multiplier
         }
     } else {
         // Not a currency pair
@@ -110,6 +111,16 @@ pub fn round_to_tick_size(value: Decimal, tick_size: Decimal) -> Decimal {
     ticks * tick_size
 }
 
+/// Rounds the value up to the next multiple of the tick size.
+pub fn ceil_to_tick_size(value: Decimal, tick_size: Decimal) -> Decimal {
+    (value / tick_size).ceil() * tick_size
+}
+
+/// Rounds the value down to the previous multiple of the tick size.
+pub fn floor_to_tick_size(value: Decimal, tick_size: Decimal) -> Decimal {
+    (value / tick_size).floor() * tick_size
+}
+
 /// Calculates the average of a vector of floating-point numbers using Decimal for high precision.
 /// Skips NaN values and entries associated with zero quantity if applicable.
 pub fn average_of_f64(values: &Vec<f64>) -> f64 {
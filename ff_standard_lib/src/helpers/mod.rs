@@ -2,6 +2,9 @@ use std::path::PathBuf;
 
 pub mod converters;
 pub mod decimal_calculators;
+pub mod position_sizing;
+pub mod price_risk;
+pub mod cross_rates;
 
 /// this just points to your fund-forge/resources folder, where all SSL key and server configuration toml file is located.
 /// I am aware this is not an optimal way of doing things but it will do for now
@@ -0,0 +1,130 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::symbol_info::SymbolInfo;
+
+/// Returns the monetary risk of holding 1 unit (contract, share, lot unit) for the given stop distance.
+/// The stop distance is rounded up to the nearest whole tick, since a stop can not be filled between ticks.
+pub fn risk_per_unit(stop_distance: Price, symbol_info: &SymbolInfo) -> Price {
+    if symbol_info.tick_size <= dec!(0) {
+        return dec!(0)
+    }
+    let ticks = (stop_distance.abs() / symbol_info.tick_size).ceil();
+    ticks * symbol_info.value_per_tick
+}
+
+/// Returns the number of units we can hold while risking no more than `max_risk` over the `stop_distance`.
+/// Risk amounts are in the `SymbolInfo::pnl_currency` and the stop distance is an absolute price distance, the size is floored, 0 if a single unit risks too much.
+pub fn max_risk_position_size(max_risk: Price, stop_distance: Price, symbol_info: &SymbolInfo) -> Volume {
+    let unit_risk = risk_per_unit(stop_distance, symbol_info);
+    if unit_risk <= dec!(0) || max_risk <= dec!(0) {
+        return dec!(0)
+    }
+    (max_risk / unit_risk).floor()
+}
+
+/// Fixed fractional sizing, risk a `fraction` of `account_equity` over the `stop_distance`.
+///
+/// `fraction` is a decimal fraction, use dec!(0.01) to risk 1% of the account.
+pub fn fixed_fractional_position_size(account_equity: Price, fraction: Decimal, stop_distance: Price, symbol_info: &SymbolInfo) -> Volume {
+    if fraction <= dec!(0) {
+        return dec!(0)
+    }
+    max_risk_position_size(account_equity * fraction, stop_distance, symbol_info)
+}
+
+/// Volatility adjusted sizing, the stop distance is `atr * atr_multiplier`.
+/// Higher volatility results in a smaller position for the same risk.
+///
+/// `risk_fraction` is a decimal fraction, use dec!(0.01) to risk 1% of the account.
+pub fn atr_position_size(account_equity: Price, risk_fraction: Decimal, atr: Price, atr_multiplier: Decimal, symbol_info: &SymbolInfo) -> Volume {
+    fixed_fractional_position_size(account_equity, risk_fraction, atr * atr_multiplier, symbol_info)
+}
+
+/// Returns the kelly fraction `W - (1 - W) / R`.
+///
+/// `win_rate` is a decimal fraction between 0 and 1 and `win_loss_ratio` is average win / average loss (as a positive number).
+/// Negative results (no edge) are clamped to 0.
+pub fn kelly_fraction(win_rate: Decimal, win_loss_ratio: Decimal) -> Decimal {
+    if win_loss_ratio <= dec!(0) || win_rate <= dec!(0) {
+        return dec!(0)
+    }
+    let win_rate = win_rate.min(dec!(1));
+    let fraction = win_rate - (dec!(1) - win_rate) / win_loss_ratio;
+    fraction.max(dec!(0))
+}
+
+/// Kelly sizing, the kelly fraction is scaled by `kelly_multiplier` (use dec!(0.5) for half kelly) and then used as the fraction of equity to risk over the `stop_distance`.
+/// Full kelly is very aggressive, most traders use a fraction of it.
+pub fn kelly_position_size(account_equity: Price, win_rate: Decimal, win_loss_ratio: Decimal, kelly_multiplier: Decimal, stop_distance: Price, symbol_info: &SymbolInfo) -> Volume {
+    let fraction = kelly_fraction(win_rate, win_loss_ratio) * kelly_multiplier;
+    fixed_fractional_position_size(account_equity, fraction, stop_distance, symbol_info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standardized_types::accounts::Currency;
+
+    fn nq_info() -> SymbolInfo {
+        // NQ: 0.25 tick size, $5 per tick
+        SymbolInfo::new("NQ".to_string(), None, Currency::USD, dec!(5.0), dec!(0.25), 2)
+    }
+
+    fn eur_usd_info() -> SymbolInfo {
+        // EUR-USD: 0.0001 pip, 1 unit = $0.0001 per pip
+        SymbolInfo::new("EUR-USD".to_string(), Some(Currency::EUR), Currency::USD, dec!(0.0001), dec!(0.0001), 5)
+    }
+
+    #[test]
+    fn test_risk_per_unit_uses_ticks() {
+        // 10 points = 40 ticks * $5 = $200
+        assert_eq!(risk_per_unit(dec!(10), &nq_info()), dec!(200));
+        // partial ticks round up to the next tick
+        assert_eq!(risk_per_unit(dec!(10.1), &nq_info()), dec!(205));
+        // direction does not matter
+        assert_eq!(risk_per_unit(dec!(-10), &nq_info()), dec!(200));
+    }
+
+    #[test]
+    fn test_max_risk_position_size() {
+        // $1000 risk / $200 per contract = 5 contracts
+        assert_eq!(max_risk_position_size(dec!(1000), dec!(10), &nq_info()), dec!(5));
+        // never round up past the risk allowance
+        assert_eq!(max_risk_position_size(dec!(999), dec!(10), &nq_info()), dec!(4));
+        // not enough risk for a single contract
+        assert_eq!(max_risk_position_size(dec!(100), dec!(10), &nq_info()), dec!(0));
+        // zero stop distance can not be sized
+        assert_eq!(max_risk_position_size(dec!(1000), dec!(0), &nq_info()), dec!(0));
+    }
+
+    #[test]
+    fn test_fixed_fractional_position_size() {
+        // 1% of 100k = $1000 risk, 20 pip stop = 20 ticks * 0.0001 = $0.002 per unit => 500_000 units
+        let size = fixed_fractional_position_size(dec!(100000), dec!(0.01), dec!(0.0020), &eur_usd_info());
+        assert_eq!(size, dec!(500000));
+    }
+
+    #[test]
+    fn test_atr_position_size() {
+        // 2% of 50k = $1000, atr 5 * 2 = 10 points = $200 per contract => 5
+        assert_eq!(atr_position_size(dec!(50000), dec!(0.02), dec!(5), dec!(2), &nq_info()), dec!(5));
+        // doubling volatility halves the position
+        assert_eq!(atr_position_size(dec!(50000), dec!(0.02), dec!(10), dec!(2), &nq_info()), dec!(2));
+    }
+
+    #[test]
+    fn test_kelly_fraction() {
+        // 60% win rate, 1:1 => 0.6 - 0.4 = 0.2
+        assert_eq!(kelly_fraction(dec!(0.6), dec!(1)), dec!(0.2));
+        // no edge is clamped to zero
+        assert_eq!(kelly_fraction(dec!(0.4), dec!(1)), dec!(0));
+        assert_eq!(kelly_fraction(dec!(0.5), dec!(0)), dec!(0));
+    }
+
+    #[test]
+    fn test_kelly_position_size() {
+        // half kelly of 0.2 = 10% of 20k = $2000 / $200 = 10 contracts
+        assert_eq!(kelly_position_size(dec!(20000), dec!(0.6), dec!(1), dec!(0.5), dec!(10), &nq_info()), dec!(10));
+    }
+}
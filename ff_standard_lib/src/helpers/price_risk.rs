@@ -0,0 +1,226 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::helpers::decimal_calculators::{ceil_to_tick_size, floor_to_tick_size};
+use crate::standardized_types::accounts::Currency;
+use crate::standardized_types::enums::PositionSide;
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::symbol_info::SymbolInfo;
+
+/// Returns true if the value of a tick depends on the price of the instrument for the account currency,
+/// eg a USD account trading USD-JPY earns its pnl in JPY and converts it back at the exit price.
+pub fn is_non_linear(symbol_info: &SymbolInfo, account_currency: Currency) -> bool {
+    symbol_info.base_currency == Some(account_currency) && symbol_info.pnl_currency != account_currency
+}
+
+/// The value of 1 tick for 1 unit at the price, in the account currency for non-linear instruments.
+pub fn value_per_tick_at(price: Price, symbol_info: &SymbolInfo, account_currency: Currency) -> Price {
+    match is_non_linear(symbol_info, account_currency) && price > dec!(0) {
+        true => symbol_info.value_per_tick / price,
+        false => symbol_info.value_per_tick,
+    }
+}
+
+/// The monetary value of moving from `entry_price` to `stop_price` with `quantity` units, always positive.
+/// For non-linear instruments the pnl is converted at the `stop_price`.
+pub fn risk_from_stop(entry_price: Price, stop_price: Price, quantity: Volume, symbol_info: &SymbolInfo, account_currency: Currency) -> Price {
+    if symbol_info.tick_size <= dec!(0) {
+        return dec!(0)
+    }
+    let ticks = (entry_price - stop_price).abs() / symbol_info.tick_size;
+    ticks * value_per_tick_at(stop_price, symbol_info, account_currency) * quantity.abs()
+}
+
+/// Returns the stop price furthest from the entry which does not lose more than `max_loss`.
+/// The result is always on the tick grid, rounded towards the entry price.
+///
+/// Returns None if the quantity is 0, the side is flat, or no price can lose the full amount (eg a non-linear short with a very large `max_loss`).
+pub fn stop_price_from_risk(entry_price: Price, side: PositionSide, max_loss: Price, quantity: Volume, symbol_info: &SymbolInfo, account_currency: Currency) -> Option<Price> {
+    let quantity = quantity.abs();
+    let max_loss = max_loss.abs();
+    if quantity == dec!(0) || symbol_info.tick_size <= dec!(0) || symbol_info.value_per_tick <= dec!(0) {
+        return None
+    }
+    let tick_size = symbol_info.tick_size;
+
+    if !is_non_linear(symbol_info, account_currency) {
+        let ticks = (max_loss / (quantity * symbol_info.value_per_tick)).floor();
+        let distance = ticks * tick_size;
+        return match side {
+            PositionSide::Long => Some(entry_price - distance),
+            PositionSide::Short => Some(entry_price + distance),
+            PositionSide::Flat => None,
+        }
+    }
+
+    // loss = |entry - stop| * k / stop
+    let k = symbol_info.value_per_tick * quantity / tick_size;
+    match side {
+        PositionSide::Long => {
+            let stop = entry_price * k / (k + max_loss);
+            Some(ceil_to_tick_size(stop, tick_size))
+        }
+        PositionSide::Short => {
+            if k <= max_loss {
+                return None
+            }
+            let stop = entry_price * k / (k - max_loss);
+            Some(floor_to_tick_size(stop, tick_size))
+        }
+        PositionSide::Flat => None,
+    }
+}
+
+/// Returns the closest target price which earns at least `reward`.
+/// The result is always on the tick grid, rounded away from the entry price.
+///
+/// Returns None if the quantity is 0, the side is flat, or no price can earn the full amount (eg a non-linear long with a very large `reward`).
+pub fn target_price_from_reward(entry_price: Price, side: PositionSide, reward: Price, quantity: Volume, symbol_info: &SymbolInfo, account_currency: Currency) -> Option<Price> {
+    let quantity = quantity.abs();
+    let reward = reward.abs();
+    if quantity == dec!(0) || symbol_info.tick_size <= dec!(0) || symbol_info.value_per_tick <= dec!(0) {
+        return None
+    }
+    let tick_size = symbol_info.tick_size;
+
+    if !is_non_linear(symbol_info, account_currency) {
+        let ticks = (reward / (quantity * symbol_info.value_per_tick)).ceil();
+        let distance = ticks * tick_size;
+        return match side {
+            PositionSide::Long => Some(entry_price + distance),
+            PositionSide::Short => Some(entry_price - distance),
+            PositionSide::Flat => None,
+        }
+    }
+
+    // reward = |target - entry| * k / target
+    let k = symbol_info.value_per_tick * quantity / tick_size;
+    match side {
+        PositionSide::Long => {
+            if k <= reward {
+                return None
+            }
+            let target = entry_price * k / (k - reward);
+            Some(ceil_to_tick_size(target, tick_size))
+        }
+        PositionSide::Short => {
+            let target = entry_price * k / (k + reward);
+            Some(floor_to_tick_size(target, tick_size))
+        }
+        PositionSide::Flat => None,
+    }
+}
+
+/// Returns the reward to risk ratio of a trade, or None if the stop is at the entry price.
+pub fn reward_to_risk(entry_price: Price, stop_price: Price, target_price: Price, quantity: Volume, symbol_info: &SymbolInfo, account_currency: Currency) -> Option<Decimal> {
+    let risk = risk_from_stop(entry_price, stop_price, quantity, symbol_info, account_currency);
+    if risk == dec!(0) {
+        return None
+    }
+    Some(risk_from_stop(entry_price, target_price, quantity, symbol_info, account_currency) / risk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use crate::product_maps::oanda::maps::OANDA_SYMBOL_INFO;
+
+    fn nq_info() -> SymbolInfo {
+        SymbolInfo::new("NQ".to_string(), None, Currency::USD, dec!(5.0), dec!(0.25), 2)
+    }
+
+    fn usd_jpy_info() -> SymbolInfo {
+        OANDA_SYMBOL_INFO.get("USD-JPY").unwrap().clone()
+    }
+
+    #[test]
+    fn test_linear_long_and_short_stops() {
+        // $500 over 2 contracts = $250 per contract = 50 ticks = 12.5 points
+        assert_eq!(stop_price_from_risk(dec!(18000), PositionSide::Long, dec!(500), dec!(2), &nq_info(), Currency::USD), Some(dec!(17987.5)));
+        assert_eq!(stop_price_from_risk(dec!(18000), PositionSide::Short, dec!(500), dec!(2), &nq_info(), Currency::USD), Some(dec!(18012.5)));
+        assert_eq!(stop_price_from_risk(dec!(18000), PositionSide::Flat, dec!(500), dec!(2), &nq_info(), Currency::USD), None);
+        assert_eq!(stop_price_from_risk(dec!(18000), PositionSide::Long, dec!(500), dec!(0), &nq_info(), Currency::USD), None);
+    }
+
+    #[test]
+    fn test_linear_targets() {
+        // $510 over 2 contracts rounds up to 51 ticks
+        assert_eq!(target_price_from_reward(dec!(18000), PositionSide::Long, dec!(510), dec!(2), &nq_info(), Currency::USD), Some(dec!(18012.75)));
+        assert_eq!(target_price_from_reward(dec!(18000), PositionSide::Short, dec!(510), dec!(2), &nq_info(), Currency::USD), Some(dec!(17987.25)));
+    }
+
+    #[test]
+    fn test_non_linear_detection() {
+        let info = usd_jpy_info();
+        assert!(is_non_linear(&info, Currency::USD));
+        assert!(!is_non_linear(&info, Currency::JPY));
+        assert!(!is_non_linear(&info, Currency::EUR));
+        assert!(!is_non_linear(&nq_info(), Currency::USD));
+        assert_eq!(value_per_tick_at(dec!(100), &info, Currency::USD), dec!(0.0001));
+    }
+
+    #[test]
+    fn test_reward_to_risk() {
+        let ratio = reward_to_risk(dec!(18000), dec!(17990), dec!(18020), dec!(1), &nq_info(), Currency::USD);
+        assert_eq!(ratio, Some(dec!(2)));
+        assert_eq!(reward_to_risk(dec!(18000), dec!(18000), dec!(18020), dec!(1), &nq_info(), Currency::USD), None);
+    }
+
+    /// For random trades on every oanda symbol and a futures contract, for both sides and both linear and non-linear account currencies:
+    /// 1. the stop never risks more than the max loss and moving it one tick further away would.
+    /// 2. the target always earns at least the reward and moving it one tick closer would not.
+    #[test]
+    fn test_stop_and_target_properties() {
+        // decimal division is not exact, allow for rounding at the 28th significant digit
+        let tolerance = dec!(0.000000001);
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut infos: Vec<SymbolInfo> = OANDA_SYMBOL_INFO.values().cloned().collect();
+        infos.push(nq_info());
+        infos.sort_by(|a, b| a.symbol_name.cmp(&b.symbol_name));
+
+        for info in infos {
+            let mut currencies = vec![info.pnl_currency];
+            if let Some(base) = info.base_currency {
+                currencies.push(base);
+            }
+            for account_currency in currencies {
+                for _ in 0..50 {
+                    let entry_ticks: i64 = rng.gen_range(1_000..2_000_000);
+                    let entry_price = Decimal::from(entry_ticks) * info.tick_size;
+                    let quantity = Decimal::from(rng.gen_range(1..100_000i64));
+                    let amount = Decimal::from(rng.gen_range(1..5_000i64));
+
+                    for side in [PositionSide::Long, PositionSide::Short] {
+                        let direction = match side {
+                            PositionSide::Long => dec!(1),
+                            _ => dec!(-1),
+                        };
+                        if let Some(stop) = stop_price_from_risk(entry_price, side, amount, quantity, &info, account_currency) {
+                            if stop > dec!(0) {
+                                let risk = risk_from_stop(entry_price, stop, quantity, &info, account_currency);
+                                assert!(risk <= amount + tolerance, "{} {:?} stop {} risks {} > {}", info.symbol_name, side, stop, risk, amount);
+                                let further = stop - info.tick_size * direction;
+                                if further > dec!(0) {
+                                    let further_risk = risk_from_stop(entry_price, further, quantity, &info, account_currency);
+                                    assert!(further_risk > amount - tolerance, "{} {:?} stop {} is not the furthest stop", info.symbol_name, side, stop);
+                                }
+                            }
+                        }
+                        if let Some(target) = target_price_from_reward(entry_price, side, amount, quantity, &info, account_currency) {
+                            if target > info.tick_size {
+                                let reward = risk_from_stop(entry_price, target, quantity, &info, account_currency);
+                                assert!(reward >= amount - tolerance, "{} {:?} target {} earns {} < {}", info.symbol_name, side, target, reward, amount);
+                                let closer = target - info.tick_size * direction;
+                                if (closer - entry_price) * direction > dec!(0) {
+                                    let closer_reward = risk_from_stop(entry_price, closer, quantity, &info, account_currency);
+                                    assert!(closer_reward < amount + tolerance, "{} {:?} target {} is not the closest target", info.symbol_name, side, target);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -10,9 +10,20 @@ use crate::standardized_types::accounts::{Account, AccountId, AccountInfo, Curre
 use crate::standardized_types::broker_enum::Brokerage;
 use crate::standardized_types::datavendor_enum::DataVendor;
 use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::base_data::data_quality::DataQualityMetrics;
+use crate::database::scanner::{ScanCondition, ScanMatch};
+use crate::standardized_types::resolution::Resolution;
 use crate::standardized_types::new_types::{Price};
+use crate::standardized_types::options::OptionQuote;
 use crate::standardized_types::orders::{OrderRequest, OrderUpdateEvent};
 use crate::standardized_types::symbol_info::{CommissionInfo, FrontMonthInfo, SymbolInfo};
+use crate::standardized_types::symbol_aliases::SymbolAlias;
+use crate::standardized_types::levels::Level;
+use crate::standardized_types::intents::Intent;
+use crate::standardized_types::download_jobs::{DownloadJob, DownloadJobRequest};
+use crate::standardized_types::versioning::{check_protocol_version, with_protocol_version};
+use crate::strategies::indicators::indicators_trait::IndicatorName;
+use crate::strategies::indicators::indicator_values::IndicatorValues;
 
 /// An Api key String
 pub type ApiKey = String;
@@ -35,6 +46,44 @@ pub struct AccountState {
     equity_available: Decimal
 }
 
+/// A running strategy which serves the order management controls, see `FundForgeStrategy::serve_controls()`.
+///
+/// # Properties
+/// * `name` - The name the strategy was served with.
+/// * `mode` - The strategy mode.
+/// * `control_address` - The address of the strategy control endpoint, eg `127.0.0.1:8095`.
+/// * `registered` - The utc time the strategy started serving controls.
+/// * `stream_name` - The connection id assigned by the data server, set by the server when the streams are listed.
+#[derive(Clone, Serialize, Deserialize, Archive, Debug, SerdeSerialize, SerdeDeserialize, PartialEq)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct StrategyStreamInfo {
+    pub name: String,
+    pub mode: StrategyMode,
+    pub control_address: String,
+    pub registered: String,
+    pub stream_name: u16,
+}
+
+/// An indicator a running strategy publishes to the data server, see `DataServerRequest::SubscribeIndicatorStream`.
+///
+/// # Properties
+/// * `stream_name` - The connection id of the strategy assigned by the data server.
+/// * `strategy_name` - The name of the strategy if it serves controls, see `StrategyStreamInfo`.
+/// * `indicator_name` - The name of the indicator.
+/// * `subscription` - The data subscription of the indicator.
+/// * `values` - The number of values the data server holds for the indicator.
+#[derive(Clone, Serialize, Deserialize, Archive, Debug, PartialEq)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct IndicatorStreamInfo {
+    pub stream_name: u16,
+    pub strategy_name: Option<String>,
+    pub indicator_name: IndicatorName,
+    pub subscription: DataSubscription,
+    pub values: u64,
+}
+
 #[derive(Clone, Serialize, Deserialize, Archive, Debug, PartialEq, Eq, PartialOrd, Ord, )]
 #[archive(compare(PartialEq), check_bytes)]
 #[archive_attr(derive(Debug))]
@@ -53,6 +102,10 @@ pub enum StreamRequest {
 pub enum DataServerRequest {
     Register(StrategyMode),
 
+    /// Registers a market data only connection, eg a research notebook, screener or chart, in place of `Register`.
+    /// The connection reads the historical store and has no live stream registration, the server refuses its order, account and strategy control requests.
+    RegisterDataOnly,
+
     /// Requests a list of instruments all instruments available with the `DataVendor` from the server, an instrument object is the vendors specific data type.
     /// # Fields
     /// * `DataVendor`
@@ -137,17 +190,69 @@ pub enum DataServerRequest {
         exchange: FuturesExchange,
         brokerage: Brokerage
     },
+    /// Requests the option chain for the underlying from a `DataVendor` which supports options, all expiries are returned if `expiry` is None.
+    /// `expiry` is a date as `%Y-%m-%d`.
+    OptionChain {
+        callback_id: u64,
+        data_vendor: DataVendor,
+        underlying: SymbolName,
+        expiry: Option<String>
+    },
     Accounts{callback_id: u64, brokerage: Brokerage},
     SymbolNames{callback_id: u64, brokerage: Brokerage, time: Option<String>},
     RegisterStreamer{port: u16, secs: u64, subsec: u32},
+    /// Announces the strategy control endpoint of this connection, the data server forgets it when the strategy disconnects.
+    RegisterStrategyStream{info: StrategyStreamInfo},
+    /// Requests the strategies connected to the data server which serve controls.
+    StrategyStreams{callback_id: u64},
+    /// Requests the data quality metrics of the data server for the symbol, or for every symbol if `symbol_name` is None.
+    DataQuality{callback_id: u64, symbol_name: Option<SymbolName>},
+    /// Requests the data the server recorded from its live streams for the subscriptions after `from_time`, which may not be saved to the historical store yet.
+    RecentLiveData{callback_id: u64, subscriptions: Vec<DataSubscription>, from_time: String},
+    /// Scans the stored `base_data_type` data of the `resolution` for the symbols which meet every condition at `time`.
+    Scan{callback_id: u64, symbols: Vec<Symbol>, base_data_type: BaseDataType, resolution: Resolution, conditions: Vec<ScanCondition>, time: String},
+    /// Publishes the new values of the strategy indicators, oldest first, the data server forgets them when the strategy disconnects.
+    PublishIndicatorValues{values: Vec<IndicatorValues>},
+    /// Stops publishing the indicator, its values are dropped and its subscribers receive no more updates.
+    RemoveIndicatorStream{indicator_name: IndicatorName},
+    /// Requests the indicators published by the strategies connected to the data server.
+    IndicatorStreams{callback_id: u64},
+    /// Subscribes to the values of an indicator published by the strategy connection `stream_name`,
+    /// the response holds the values the server has for the indicator and each new value is sent as a `DataServerResponse::IndicatorStreamUpdate`.
+    SubscribeIndicatorStream{callback_id: u64, stream_name: u16, indicator_name: IndicatorName},
+    /// Stops the updates of an indicator stream.
+    UnsubscribeIndicatorStream{stream_name: u16, indicator_name: IndicatorName},
+    /// Requests the vendor symbol aliases registered on the data server.
+    SymbolAliases{callback_id: u64},
+    /// Requests the levels saved on the data server for the symbol.
+    Levels{callback_id: u64, symbol_name: SymbolName},
+    /// Saves a level on the data server, replacing the level with the same id, the response holds the levels of the symbol.
+    SaveLevel{callback_id: u64, level: Level},
+    /// Removes a level from the data server, the response holds the levels left for the symbol.
+    RemoveLevel{callback_id: u64, symbol_name: SymbolName, id: String},
+    /// Requests the intents saved on the data server for the symbol.
+    Intents{callback_id: u64, symbol_name: SymbolName},
+    /// Saves an intent on the data server, replacing the intent with the same id, the response holds the intents of the symbol.
+    SaveIntent{callback_id: u64, intent: Intent},
+    /// Removes an intent from the data server, the response holds the intents left for the symbol.
+    RemoveIntent{callback_id: u64, symbol_name: SymbolName, id: String},
+    /// Marks a resting intent as triggered at the time before its order is sent, the response is `IntentClaim`, claimed is false if the intent is not resting.
+    ClaimIntent{callback_id: u64, symbol_name: SymbolName, id: String, time: String},
+    /// Queues a historical download job on the data server, the response holds the queued job.
+    EnqueueDownload{callback_id: u64, request: DownloadJobRequest},
+    /// Requests the queued, running and recently finished download jobs of the data server.
+    DownloadJobs{callback_id: u64},
+    /// Cancels a queued or running download job, the response holds the cancelled job.
+    CancelDownload{callback_id: u64, id: u64},
 }
 
 impl DataServerRequest {
     pub fn to_bytes(&self) -> Vec<u8> {
         let vec = rkyv::to_bytes::<_, 1024>(self).unwrap();
-        vec.into()
+        with_protocol_version(vec.into())
     }
     pub fn from_bytes(archived: &[u8]) -> Result<DataServerRequest, FundForgeError> {
+        let archived = check_protocol_version(archived)?;
         // If the archived bytes do not end with the delimiter, proceed as before
         match rkyv::from_bytes::<DataServerRequest>(archived) {
             //Ignore this warning: Trait `Deserialize<RequestType, SharedDeserializeMap>` is not implemented for `ArchivedRequestType` [E0277]
@@ -167,20 +272,131 @@ impl DataServerRequest {
             DataServerRequest::SymbolInfo { callback_id, .. } => {*callback_id = id}
             DataServerRequest::StreamRequest   { .. } => {}
             DataServerRequest::Register {  .. } => {}
+            DataServerRequest::RegisterDataOnly => {}
             DataServerRequest::OrderRequest { .. } => {}
             DataServerRequest::Accounts { callback_id, .. } => {*callback_id = id}
             DataServerRequest::PrimarySubscriptionFor { callback_id, .. } => {*callback_id = id}
             DataServerRequest::SymbolNames { callback_id, .. } => {*callback_id = id}
             DataServerRequest::RegisterStreamer{..} => {}
+            DataServerRequest::RegisterStrategyStream{..} => {}
+            DataServerRequest::StrategyStreams { callback_id } => {*callback_id = id}
+            DataServerRequest::DataQuality { callback_id, .. } => {*callback_id = id}
+            DataServerRequest::RecentLiveData { callback_id, .. } => {*callback_id = id}
+            DataServerRequest::Scan { callback_id, .. } => {*callback_id = id}
+            DataServerRequest::PublishIndicatorValues { .. } => {}
+            DataServerRequest::RemoveIndicatorStream { .. } => {}
+            DataServerRequest::IndicatorStreams { callback_id } => {*callback_id = id}
+            DataServerRequest::SubscribeIndicatorStream { callback_id, .. } => {*callback_id = id}
+            DataServerRequest::UnsubscribeIndicatorStream { .. } => {}
+            DataServerRequest::SymbolAliases { callback_id } => {*callback_id = id}
+            DataServerRequest::Levels { callback_id, .. } => {*callback_id = id}
+            DataServerRequest::SaveLevel { callback_id, .. } => {*callback_id = id}
+            DataServerRequest::RemoveLevel { callback_id, .. } => {*callback_id = id}
+            DataServerRequest::Intents { callback_id, .. } => {*callback_id = id}
+            DataServerRequest::SaveIntent { callback_id, .. } => {*callback_id = id}
+            DataServerRequest::RemoveIntent { callback_id, .. } => {*callback_id = id}
+            DataServerRequest::ClaimIntent { callback_id, .. } => {*callback_id = id}
+            DataServerRequest::EnqueueDownload { callback_id, .. } => {*callback_id = id}
+            DataServerRequest::DownloadJobs { callback_id } => {*callback_id = id}
+            DataServerRequest::CancelDownload { callback_id, .. } => {*callback_id = id}
             DataServerRequest::CommissionInfo { callback_id, .. } => {*callback_id = id}
             DataServerRequest::WarmUpResolutions { callback_id, .. } => {*callback_id = id}
             DataServerRequest::ExchangeRate { callback_id, .. } => {*callback_id = id}
             DataServerRequest::GetCompressedHistoricalData { callback_id, .. } => {*callback_id = id}
             DataServerRequest::FrontMonthInfo { callback_id, .. } => {*callback_id = id}
+            DataServerRequest::OptionChain { callback_id, .. } => {*callback_id = id}
         }
     }
 }
 
+impl DataServerRequest {
+    /// The callback id of the request, None for one way requests.
+    pub fn callback_id(&self) -> Option<u64> {
+        match self {
+            DataServerRequest::SymbolsVendor { callback_id, .. }
+            | DataServerRequest::Resolutions { callback_id, .. }
+            | DataServerRequest::AccountInfo { callback_id, .. }
+            | DataServerRequest::BaseDataTypes { callback_id, .. }
+            | DataServerRequest::Markets { callback_id, .. }
+            | DataServerRequest::TickSize { callback_id, .. }
+            | DataServerRequest::DecimalAccuracy { callback_id, .. }
+            | DataServerRequest::SymbolInfo { callback_id, .. }
+            | DataServerRequest::Accounts { callback_id, .. }
+            | DataServerRequest::PrimarySubscriptionFor { callback_id, .. }
+            | DataServerRequest::SymbolNames { callback_id, .. }
+            | DataServerRequest::StrategyStreams { callback_id }
+            | DataServerRequest::DataQuality { callback_id, .. }
+            | DataServerRequest::RecentLiveData { callback_id, .. }
+            | DataServerRequest::Scan { callback_id, .. }
+            | DataServerRequest::IndicatorStreams { callback_id }
+            | DataServerRequest::SubscribeIndicatorStream { callback_id, .. }
+            | DataServerRequest::CommissionInfo { callback_id, .. }
+            | DataServerRequest::WarmUpResolutions { callback_id, .. }
+            | DataServerRequest::ExchangeRate { callback_id, .. }
+            | DataServerRequest::GetCompressedHistoricalData { callback_id, .. }
+            | DataServerRequest::FrontMonthInfo { callback_id, .. }
+            | DataServerRequest::OptionChain { callback_id, .. }
+            | DataServerRequest::SymbolAliases { callback_id }
+            | DataServerRequest::Levels { callback_id, .. }
+            | DataServerRequest::SaveLevel { callback_id, .. }
+            | DataServerRequest::RemoveLevel { callback_id, .. }
+            | DataServerRequest::Intents { callback_id, .. }
+            | DataServerRequest::SaveIntent { callback_id, .. }
+            | DataServerRequest::RemoveIntent { callback_id, .. }
+            | DataServerRequest::ClaimIntent { callback_id, .. }
+            | DataServerRequest::EnqueueDownload { callback_id, .. }
+            | DataServerRequest::DownloadJobs { callback_id }
+            | DataServerRequest::CancelDownload { callback_id, .. } => Some(*callback_id),
+            DataServerRequest::Register(_)
+            | DataServerRequest::RegisterDataOnly
+            | DataServerRequest::StreamRequest { .. }
+            | DataServerRequest::OrderRequest { .. }
+            | DataServerRequest::RegisterStreamer { .. }
+            | DataServerRequest::RegisterStrategyStream { .. }
+            | DataServerRequest::PublishIndicatorValues { .. }
+            | DataServerRequest::RemoveIndicatorStream { .. }
+            | DataServerRequest::UnsubscribeIndicatorStream { .. } => None,
+        }
+    }
+
+    /// True for the requests a data only connection may make, symbols, symbol info, historical data, scans, reading levels and intents,
+    /// the strategy and indicator stream lists and the download queue. Every other request, including requests added later, needs a strategy connection.
+    pub fn is_data_only_request(&self) -> bool {
+        matches!(
+            self,
+            DataServerRequest::RegisterDataOnly
+                | DataServerRequest::SymbolsVendor { .. }
+                | DataServerRequest::BaseDataTypes { .. }
+                | DataServerRequest::Resolutions { .. }
+                | DataServerRequest::WarmUpResolutions { .. }
+                | DataServerRequest::Markets { .. }
+                | DataServerRequest::TickSize { .. }
+                | DataServerRequest::DecimalAccuracy { .. }
+                | DataServerRequest::SymbolInfo { .. }
+                | DataServerRequest::PrimarySubscriptionFor { .. }
+                | DataServerRequest::CommissionInfo { .. }
+                | DataServerRequest::ExchangeRate { .. }
+                | DataServerRequest::GetCompressedHistoricalData { .. }
+                | DataServerRequest::FrontMonthInfo { .. }
+                | DataServerRequest::OptionChain { .. }
+                | DataServerRequest::SymbolNames { .. }
+                | DataServerRequest::StrategyStreams { .. }
+                | DataServerRequest::DataQuality { .. }
+                | DataServerRequest::RecentLiveData { .. }
+                | DataServerRequest::Scan { .. }
+                | DataServerRequest::IndicatorStreams { .. }
+                | DataServerRequest::SubscribeIndicatorStream { .. }
+                | DataServerRequest::UnsubscribeIndicatorStream { .. }
+                | DataServerRequest::SymbolAliases { .. }
+                | DataServerRequest::Levels { .. }
+                | DataServerRequest::Intents { .. }
+                | DataServerRequest::EnqueueDownload { .. }
+                | DataServerRequest::DownloadJobs { .. }
+                | DataServerRequest::CancelDownload { .. }
+        )
+    }
+}
+
 //todo, could do something like this
 pub enum SubscriptionResponse {
     CreateConsolidator{callback_id: u64, base_subscription: DataSubscription},
@@ -296,6 +512,9 @@ DataServerResponse {
 
     SymbolNames{callback_id: u64, symbol_names: Vec<SymbolName>},
 
+    /// The option chain with the greeks as supplied by the vendor.
+    OptionChain{callback_id: u64, quotes: Vec<OptionQuote>},
+
     Accounts{callback_id: u64, accounts: Vec<AccountId>},
 
     PrimarySubscriptionFor{callback_id: u64, primary_subscription: DataSubscription},
@@ -308,12 +527,51 @@ DataServerResponse {
 
     LiveAccountUpdates {account: Account, cash_value: Decimal, cash_available: Decimal, cash_used: Decimal},
 
+    StrategyStreams{callback_id: u64, streams: Vec<StrategyStreamInfo>},
+
+    /// The data quality metrics for the live streams and the historical store of the data server.
+    DataQuality{callback_id: u64, metrics: Vec<DataQualityMetrics>},
+
+    /// The recent live data as bytes of a `Vec<BaseDataEnum>`, in the order it was received.
+    RecentLiveData{callback_id: u64, payload: Vec<u8>},
+
+    /// The symbols which met every condition of a scan.
+    Scan{callback_id: u64, matches: Vec<ScanMatch>},
+
     /// Booked pnl is only sent for closed positions, it is the amount of booked pnl since the last side change from none to long or short
     LivePositionUpdates {symbol_name: SymbolName, symbol_code: SymbolCode, account: Account, open_quantity: f64, average_price: f64, side: PositionSide, open_pnl: f64, time: String},
+
+    /// The indicators published by the strategies connected to the data server.
+    IndicatorStreams{callback_id: u64, streams: Vec<IndicatorStreamInfo>},
+
+    /// The values the data server holds for a subscribed indicator stream, oldest first.
+    IndicatorHistory{callback_id: u64, values: Vec<IndicatorValues>},
+
+    /// New values of a subscribed indicator stream, oldest first.
+    IndicatorStreamUpdate{stream_name: u16, values: Vec<IndicatorValues>},
+
+    /// The vendor symbol aliases registered on the data server.
+    SymbolAliases{callback_id: u64, aliases: Vec<SymbolAlias>},
+
+    /// The levels saved on the data server for a symbol.
+    Levels{callback_id: u64, levels: Vec<Level>},
+
+    /// The intents saved on the data server for a symbol.
+    Intents{callback_id: u64, intents: Vec<Intent>},
+
+    /// Whether a `ClaimIntent` request claimed the intent.
+    IntentClaim{callback_id: u64, claimed: bool},
+
+    /// A download job queued or cancelled on the data server.
+    DownloadJob{callback_id: u64, job: DownloadJob},
+
+    /// The download jobs of the data server, in the order they were queued.
+    DownloadJobs{callback_id: u64, jobs: Vec<DownloadJob>},
 }
 
 impl Bytes<DataServerResponse> for DataServerResponse {
     fn from_bytes(archived: &[u8]) -> Result<DataServerResponse, FundForgeError> {
+        let archived = check_protocol_version(archived)?;
         // If the archived bytes do not end with the delimiter, proceed as before
         match rkyv::from_bytes::<DataServerResponse>(archived) {
             //Ignore this warning: Trait `Deserialize<ResponseType, SharedDeserializeMap>` is not implemented for `ArchivedRequestType` [E0277]
@@ -325,7 +583,7 @@ impl Bytes<DataServerResponse> for DataServerResponse {
         use rkyv::ser::serializers::AllocSerializer;
         let mut serializer = AllocSerializer::<0>::default();
         serializer.serialize_value(self).unwrap();
-        serializer.into_serializer().into_inner().into()
+        with_protocol_version(serializer.into_serializer().into_inner().into())
     }
 }
 
@@ -352,11 +610,25 @@ impl DataServerResponse {
             DataServerResponse::RegistrationResponse(_) => None,
             DataServerResponse::CommissionInfo { callback_id,.. } => Some(callback_id.clone()),
             DataServerResponse::FrontMonthInfo { callback_id, .. } => Some(callback_id.clone()),
+            DataServerResponse::OptionChain { callback_id, .. } => Some(callback_id.clone()),
             DataServerResponse::LiveAccountUpdates { .. } => None,
             DataServerResponse::LivePositionUpdates { .. } => None,
             DataServerResponse::AsyncError { .. } => None,
             DataServerResponse::ExchangeRate { callback_id, .. } => Some(callback_id.clone()),
             DataServerResponse::CompressedHistoricalData { callback_id, .. } => Some(callback_id.clone()),
+            DataServerResponse::StrategyStreams { callback_id, .. } => Some(callback_id.clone()),
+            DataServerResponse::DataQuality { callback_id, .. } => Some(callback_id.clone()),
+            DataServerResponse::RecentLiveData { callback_id, .. } => Some(callback_id.clone()),
+            DataServerResponse::Scan { callback_id, .. } => Some(callback_id.clone()),
+            DataServerResponse::IndicatorStreams { callback_id, .. } => Some(callback_id.clone()),
+            DataServerResponse::IndicatorHistory { callback_id, .. } => Some(callback_id.clone()),
+            DataServerResponse::IndicatorStreamUpdate { .. } => None,
+            DataServerResponse::SymbolAliases { callback_id, .. } => Some(callback_id.clone()),
+            DataServerResponse::Levels { callback_id, .. } => Some(callback_id.clone()),
+            DataServerResponse::Intents { callback_id, .. } => Some(callback_id.clone()),
+            DataServerResponse::IntentClaim { callback_id, .. } => Some(callback_id.clone()),
+            DataServerResponse::DownloadJob { callback_id, .. } => Some(callback_id.clone()),
+            DataServerResponse::DownloadJobs { callback_id, .. } => Some(callback_id.clone()),
         }
     }
 }
@@ -383,6 +655,8 @@ pub enum FundForgeError {
     UnknownBlameError(String),
     /// An unknown error occurred, the debug message is provided as `String`.
     ConnectionNotFound(String),
+    /// The message or the stored data was written with another serialization version, see `versioning.rs`.
+    IncompatibleVersion(String),
 }
 
 impl Debug for FundForgeError {
@@ -398,6 +672,7 @@ impl Debug for FundForgeError {
             }
             FundForgeError::UnknownBlameError(debug) => write!(f, "UnknownBlameError: {}", debug),
             FundForgeError::ConnectionNotFound(debug) => write!(f, "ConnectionNotFound {}:", debug),
+            FundForgeError::IncompatibleVersion(debug) => write!(f, "IncompatibleVersion: {}", debug),
         }
     }
 }
@@ -417,6 +692,7 @@ impl Display for FundForgeError {
             FundForgeError::ConnectionNotFound(debug) => {
                 write!(f, "ConnectionNotFound: {}:", debug)
             }
+            FundForgeError::IncompatibleVersion(debug) => write!(f, "IncompatibleVersion: {}", debug),
         }
     }
 }
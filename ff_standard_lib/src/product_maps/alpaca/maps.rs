@@ -0,0 +1,88 @@
+use chrono::Weekday;
+use rust_decimal_macros::dec;
+use crate::product_maps::rithmic::maps::const_time;
+use crate::standardized_types::accounts::Currency;
+use crate::standardized_types::enums::Exchange;
+use crate::standardized_types::market_hours::{DaySession, TradingHours};
+use crate::standardized_types::subscriptions::SymbolName;
+use crate::standardized_types::symbol_info::SymbolInfo;
+
+/// Alpaca accepts fractional share quantities up to 9 decimal places.
+pub const ALPACA_QUANTITY_DECIMALS: u32 = 9;
+
+/// US equities trade in whole cents above $1, sub penny increments for stocks below $1 are not modelled.
+pub fn get_equity_symbol_info(symbol_name: &SymbolName) -> SymbolInfo {
+    SymbolInfo {
+        symbol_name: symbol_name.clone(),
+        base_currency: None,
+        pnl_currency: Currency::USD,
+        value_per_tick: dec!(0.01), // USD 0.01 per 0.01 tick size for 1 share
+        tick_size: dec!(0.01),
+        decimal_accuracy: 2,
+        is_synthetic: false,
+        is_reference: false,
+        display_precision: None,
+    }
+}
+
+/// The regular session for the listing exchange, holidays and early closes are not included.
+pub fn get_equity_trading_hours(exchange: &Exchange) -> TradingHours {
+    match exchange {
+        Exchange::NASDAQ | Exchange::BATS => NASDAQ_HOURS,
+        Exchange::NYSE | Exchange::ARCA | Exchange::AMEX => NYSE_HOURS,
+    }
+}
+
+const REGULAR_SESSION: DaySession = DaySession {
+    open: Some(const_time(9, 30, 0)),
+    close: Some(const_time(16, 0, 0)),
+};
+
+const EXTENDED_SESSION: DaySession = DaySession {
+    open: Some(const_time(4, 0, 0)),
+    close: Some(const_time(20, 0, 0)),
+};
+
+const NO_SESSION: DaySession = DaySession {
+    open: None,
+    close: None,
+};
+
+// NYSE regular session 9:30 AM to 4:00 PM ET
+pub const NYSE_HOURS: TradingHours = TradingHours {
+    timezone: chrono_tz::America::New_York,
+    sunday: NO_SESSION,
+    monday: REGULAR_SESSION,
+    tuesday: REGULAR_SESSION,
+    wednesday: REGULAR_SESSION,
+    thursday: REGULAR_SESSION,
+    friday: REGULAR_SESSION,
+    saturday: NO_SESSION,
+    week_start: Weekday::Mon,
+};
+
+// NASDAQ regular session 9:30 AM to 4:00 PM ET
+pub const NASDAQ_HOURS: TradingHours = TradingHours {
+    timezone: chrono_tz::America::New_York,
+    sunday: NO_SESSION,
+    monday: REGULAR_SESSION,
+    tuesday: REGULAR_SESSION,
+    wednesday: REGULAR_SESSION,
+    thursday: REGULAR_SESSION,
+    friday: REGULAR_SESSION,
+    saturday: NO_SESSION,
+    week_start: Weekday::Mon,
+};
+
+// Pre market from 4:00 AM and after hours until 8:00 PM ET, Alpaca only fills extended hours orders for limit orders with a Day time in force.
+pub const US_EQUITY_EXTENDED_HOURS: TradingHours = TradingHours {
+    timezone: chrono_tz::America::New_York,
+    sunday: NO_SESSION,
+    monday: EXTENDED_SESSION,
+    tuesday: EXTENDED_SESSION,
+    wednesday: EXTENDED_SESSION,
+    thursday: EXTENDED_SESSION,
+    friday: EXTENDED_SESSION,
+    saturday: NO_SESSION,
+    week_start: Weekday::Mon,
+};
@@ -1,2 +1,4 @@
+pub mod alpaca;
 pub mod oanda;
-pub mod rithmic;
\ No newline at end of file
+pub mod rithmic;
+pub mod trading_hours;
\ No newline at end of file
@@ -1,9 +1,12 @@
 use std::collections::HashMap;
+use chrono::Weekday;
 use lazy_static::lazy_static;
 use rust_decimal::Decimal;
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal_macros::dec;
+use crate::product_maps::rithmic::maps::const_time;
 use crate::standardized_types::accounts::Currency;
+use crate::standardized_types::market_hours::{DaySession, TradingHours};
 use crate::standardized_types::symbol_info::SymbolInfo;
 
 lazy_static! {
@@ -17,6 +20,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // USD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("EUR-USD".to_string(), SymbolInfo {
@@ -26,6 +32,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // USD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("GBP-USD".to_string(), SymbolInfo {
@@ -35,6 +44,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // USD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("NZD-USD".to_string(), SymbolInfo {
@@ -44,6 +56,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // USD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("USD-CAD".to_string(), SymbolInfo {
@@ -53,6 +68,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // CAD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("USD-CHF".to_string(), SymbolInfo {
@@ -62,6 +80,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // CHF 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("USD-JPY".to_string(), SymbolInfo {
@@ -71,6 +92,9 @@ lazy_static! {
             value_per_tick: dec!(0.01),      // JPY 0.01 per 0.01 tick size for 1 unit
             tick_size: dec!(0.01),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("EUR-GBP".to_string(), SymbolInfo {
@@ -80,6 +104,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // GBP 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("EUR-JPY".to_string(), SymbolInfo {
@@ -89,6 +116,9 @@ lazy_static! {
             value_per_tick: dec!(0.01),      // JPY 0.01 per 0.01 tick size for 1 unit
             tick_size: dec!(0.01),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("EUR-CHF".to_string(), SymbolInfo {
@@ -98,6 +128,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // CHF 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("AUD-CAD".to_string(), SymbolInfo {
@@ -107,6 +140,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // CAD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("AUD-CHF".to_string(), SymbolInfo {
@@ -116,6 +152,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // CHF 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("AUD-JPY".to_string(), SymbolInfo {
@@ -125,6 +164,9 @@ lazy_static! {
             value_per_tick: dec!(0.01),      // JPY 0.01 per 0.01 tick size for 1 unit
             tick_size: dec!(0.01),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("AUD-NZD".to_string(), SymbolInfo {
@@ -134,6 +176,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // NZD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("CAD-CHF".to_string(), SymbolInfo {
@@ -143,6 +188,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // CHF 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("CAD-JPY".to_string(), SymbolInfo {
@@ -152,6 +200,9 @@ lazy_static! {
             value_per_tick: dec!(0.01),      // JPY 0.01 per 0.01 tick size for 1 unit
             tick_size: dec!(0.01),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("CHF-JPY".to_string(), SymbolInfo {
@@ -161,6 +212,9 @@ lazy_static! {
             value_per_tick: dec!(0.01),      // JPY 0.01 per 0.01 tick size for 1 unit
             tick_size: dec!(0.01),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("EUR-AUD".to_string(), SymbolInfo {
@@ -170,6 +224,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // AUD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
                m.insert("EUR-CAD".to_string(), SymbolInfo {
@@ -179,6 +236,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // CAD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("EUR-NOK".to_string(), SymbolInfo {
@@ -188,6 +248,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // NOK 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("EUR-NZD".to_string(), SymbolInfo {
@@ -197,6 +260,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // NZD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("EUR-SEK".to_string(), SymbolInfo {
@@ -206,6 +272,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // SEK 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("GBP-AUD".to_string(), SymbolInfo {
@@ -215,6 +284,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // AUD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("GBP-CAD".to_string(), SymbolInfo {
@@ -224,6 +296,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // CAD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("GBP-CHF".to_string(), SymbolInfo {
@@ -233,6 +308,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),   // CHF 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("GBP-JPY".to_string(), SymbolInfo {
@@ -242,6 +320,9 @@ lazy_static! {
             value_per_tick: dec!(0.01),     // JPY 0.01 per 0.01 tick size for 1 unit
             tick_size: dec!(0.01),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("GBP-NZD".to_string(), SymbolInfo {
@@ -251,6 +332,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // NZD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("NZD-CAD".to_string(), SymbolInfo {
@@ -260,6 +344,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // CAD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("NZD-CHF".to_string(), SymbolInfo {
@@ -269,6 +356,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // CHF 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("NZD-JPY".to_string(), SymbolInfo {
@@ -278,6 +368,9 @@ lazy_static! {
             value_per_tick: dec!(0.01),     // JPY 0.01 per 0.01 tick size for 1 unit
             tick_size: dec!(0.01),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("USD-NOK".to_string(), SymbolInfo {
@@ -287,6 +380,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // NOK 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("USD-SEK".to_string(), SymbolInfo {
@@ -296,6 +392,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // SEK 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
                 m.insert("USD-CNH".to_string(), SymbolInfo {
@@ -305,6 +404,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // CNH 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("USD-MXN".to_string(), SymbolInfo {
@@ -314,6 +416,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // MXN 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("USD-ZAR".to_string(), SymbolInfo {
@@ -323,6 +428,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // ZAR 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("SGD-JPY".to_string(), SymbolInfo {
@@ -332,6 +440,9 @@ lazy_static! {
             value_per_tick: dec!(0.01),     // JPY 0.01 per 0.01 tick size for 1 unit
             tick_size: dec!(0.01),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("USD-HKD".to_string(), SymbolInfo {
@@ -341,6 +452,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // HKD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("USD-SGD".to_string(), SymbolInfo {
@@ -350,6 +464,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // SGD 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("EUR-CZK".to_string(), SymbolInfo {
@@ -359,6 +476,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // CZK 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("EUR-HUF".to_string(), SymbolInfo {
@@ -368,6 +488,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // HUF 0.00001 per 0.001 tick size for 1 unit
             tick_size: dec!(0.001),
             decimal_accuracy: 3,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("EUR-PLN".to_string(), SymbolInfo {
@@ -377,6 +500,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // PLN 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("USD-CZK".to_string(), SymbolInfo {
@@ -386,6 +512,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // CZK 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("USD-HUF".to_string(), SymbolInfo {
@@ -395,6 +524,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // HUF 0.00001 per 0.001 tick size for 1 unit
             tick_size: dec!(0.001),
             decimal_accuracy: 3,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("USD-PLN".to_string(), SymbolInfo {
@@ -404,6 +536,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // PLN 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("ZAR-JPY".to_string(), SymbolInfo {
@@ -413,6 +548,9 @@ lazy_static! {
             value_per_tick: dec!(0.01),     // JPY 0.01 per 0.01 tick size for 1 unit
             tick_size: dec!(0.01),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("USD-TRY".to_string(), SymbolInfo {
@@ -422,6 +560,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // TRY 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("EUR-TRY".to_string(), SymbolInfo {
@@ -431,6 +572,9 @@ lazy_static! {
             value_per_tick: dec!(0.00001),  // TRY 0.00001 per 0.00001 tick size for 1 unit
             tick_size: dec!(0.00001),
             decimal_accuracy: 5,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("TRY-JPY".to_string(), SymbolInfo {
@@ -440,6 +584,9 @@ lazy_static! {
             value_per_tick: dec!(0.01),     // JPY 0.01 per 0.01 tick size for 1 unit
             tick_size: dec!(0.01),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("BTC-USD".to_string(), SymbolInfo {
@@ -449,6 +596,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("BCH-USD".to_string(), SymbolInfo {
@@ -458,6 +608,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("ETH-USD".to_string(), SymbolInfo {
@@ -467,6 +620,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("LTC-USD".to_string(), SymbolInfo {
@@ -476,6 +632,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 3,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
         m.insert("AUS200-USD".to_string(), SymbolInfo {
             symbol_name: "AUS200-USD".to_string(),
@@ -484,6 +643,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("CHINA50-USD".to_string(), SymbolInfo {
@@ -493,6 +655,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("EU50-USD".to_string(), SymbolInfo {
@@ -502,6 +667,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("GER30-USD".to_string(), SymbolInfo {
@@ -511,6 +679,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("HK50-USD".to_string(), SymbolInfo {
@@ -520,6 +691,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("US100-USD".to_string(), SymbolInfo {
@@ -529,6 +703,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("NAS100-USD".to_string(), SymbolInfo {
@@ -538,6 +715,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("US30-USD".to_string(), SymbolInfo {
@@ -547,6 +727,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("US500-USD".to_string(), SymbolInfo {
@@ -556,6 +739,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("US2000-USD".to_string(), SymbolInfo {
@@ -565,6 +751,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(0.001),
             decimal_accuracy: 3,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("FRA40-USD".to_string(), SymbolInfo {
@@ -574,6 +763,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("UK100-USD".to_string(), SymbolInfo {
@@ -583,6 +775,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("INDIA50-USD".to_string(), SymbolInfo {
@@ -592,6 +787,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("JP225-USD".to_string(), SymbolInfo {
@@ -601,6 +799,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("TWIX-USD".to_string(), SymbolInfo {
@@ -610,6 +811,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("NL25-USD".to_string(), SymbolInfo {
@@ -619,6 +823,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(0.001),
             decimal_accuracy: 3,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("SING30-USD".to_string(), SymbolInfo {
@@ -628,6 +835,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(0.01),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("CH20-USD".to_string(), SymbolInfo {
@@ -637,6 +847,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("ES35-USD".to_string(), SymbolInfo {
@@ -646,6 +859,9 @@ lazy_static! {
             value_per_tick: dec!(1.0),     // USD 1 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("UKOIL-USD".to_string(), SymbolInfo {
@@ -655,6 +871,9 @@ lazy_static! {
             value_per_tick: dec!(10.0),    // USD 10 per 0.01 tick size for lot
             tick_size: dec!(0.01),
             decimal_accuracy: 3,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("USOIL-USD".to_string(), SymbolInfo {
@@ -664,6 +883,9 @@ lazy_static! {
             value_per_tick: dec!(10.0),    // USD 10 per 0.01 tick size for lot
             tick_size: dec!(0.01),
             decimal_accuracy: 3,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("NATGAS-USD".to_string(), SymbolInfo {
@@ -673,6 +895,9 @@ lazy_static! {
             value_per_tick: dec!(10.0),    // USD 10 per 0.001 tick size for lot
             tick_size: dec!(0.001),
             decimal_accuracy: 3,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("COPPER-USD".to_string(), SymbolInfo {
@@ -682,6 +907,9 @@ lazy_static! {
             value_per_tick: dec!(2.5),     // USD 2.5 per 0.0001 tick size for lot
             tick_size: dec!(0.0001),
             decimal_accuracy: 4,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("WHEAT-USD".to_string(), SymbolInfo {
@@ -691,6 +919,9 @@ lazy_static! {
             value_per_tick: dec!(5.0),     // USD 5 per 0.001 tick size for lot
             tick_size: dec!(0.001),
             decimal_accuracy: 3,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("CORN-USD".to_string(), SymbolInfo {
@@ -700,6 +931,9 @@ lazy_static! {
             value_per_tick: dec!(5.0),     // USD 5 per 0.001 tick size for lot
             tick_size: dec!(0.001),
             decimal_accuracy: 3,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("SOYBEANS-USD".to_string(), SymbolInfo {
@@ -709,6 +943,9 @@ lazy_static! {
             value_per_tick: dec!(6.0),     // USD 6 per 0.01 tick size for lot
             tick_size: dec!(0.01),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("SUGAR-USD".to_string(), SymbolInfo {
@@ -718,6 +955,9 @@ lazy_static! {
             value_per_tick: dec!(4.0),     // USD 4 per 0.0001 tick size for lot
             tick_size: dec!(0.0001),
             decimal_accuracy: 4,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("XAG-USD".to_string(), SymbolInfo {
@@ -727,6 +967,9 @@ lazy_static! {
             value_per_tick: dec!(5.0),     // USD 5 per 0.001 tick size for lot
             tick_size: dec!(0.001),
             decimal_accuracy: 3,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m.insert("XAU-USD".to_string(), SymbolInfo {
@@ -736,6 +979,9 @@ lazy_static! {
             value_per_tick: dec!(100.0),   // USD 100 per 1.0 tick size for lot
             tick_size: dec!(1.0),
             decimal_accuracy: 2,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         });
 
         m
@@ -1665,4 +1911,40 @@ lazy_static! {
 
         m
     };
-}
\ No newline at end of file
+}
+const FOREX_SESSION: DaySession = DaySession {
+    open: Some(const_time(17, 0, 0)),
+    close: Some(const_time(17, 0, 0)),
+};
+
+// Forex trades from 17:00 Sunday to 17:00 Friday New York time, the daily session rolls over at 17:00
+pub const FOREX_HOURS: TradingHours = TradingHours {
+    timezone: chrono_tz::America::New_York,
+    sunday: DaySession {
+        open: Some(const_time(17, 0, 0)),
+        close: None,
+    },
+    monday: FOREX_SESSION,
+    tuesday: FOREX_SESSION,
+    wednesday: FOREX_SESSION,
+    thursday: FOREX_SESSION,
+    friday: DaySession {
+        open: None,
+        close: Some(const_time(17, 0, 0)),
+    },
+    saturday: DaySession {
+        open: None,
+        close: None,
+    },
+    week_start: Weekday::Sun,
+};
+
+/// `FOREX_HOURS` for currency pairs like "EUR-USD", None for crypto, metals and CFDs which keep their own hours.
+pub fn get_forex_trading_hours(symbol_name: &str) -> Option<TradingHours> {
+    let (base, quote) = symbol_name.split_once('-')?;
+    let is_currency = |code: &str| code.len() == 3 && !code.starts_with('X') && !["BTC", "BCH", "ETH", "LTC"].contains(&code);
+    match is_currency(base) && is_currency(quote) && OANDA_SYMBOL_INFO.contains_key(symbol_name) {
+        true => Some(FOREX_HOURS),
+        false => None,
+    }
+}
@@ -167,6 +167,9 @@ lazy_static! {
                     tick_size: dec!($tick_size),
                     decimal_accuracy: $accuracy,
                     base_currency: None,
+                    is_synthetic: false,
+                    is_reference: false,
+                    display_precision: None,
                 });
             };
         }
@@ -573,7 +576,7 @@ pub fn get_futures_trading_hours(symbol: &str) -> Option<&'static TradingHours>
     TRADING_HOURS.get(symbol).copied()
 }
 
-const fn const_time(hour: u32, min: u32, sec: u32) -> NaiveTime {
+pub(crate) const fn const_time(hour: u32, min: u32, sec: u32) -> NaiveTime {
     match NaiveTime::from_hms_opt(hour, min, sec) {
         Some(t) => t,
         None => panic!("Invalid time"),
@@ -645,14 +648,15 @@ pub const CBOT_GRAINS_HOURS: TradingHours = TradingHours {
     },
     week_start: Weekday::Sun,
 };
-const EUREX_HOURS: TradingHours = TradingHours {
+// Eurex T7 day session 08:00 to 22:00 CE(S)T, the earlier hours some products trade are not included
+pub const EUREX_HOURS: TradingHours = TradingHours {
     timezone: chrono_tz::Europe::Berlin,
     sunday: DaySession {
-        open: Some(const_time(8, 0, 0)),   // 08:00 CE(S)T Monday
+        open: None,
         close: None,
     },
     monday: DaySession {
-        open: None,
+        open: Some(const_time(8, 0, 0)),    // 08:00 CE(S)T
         close: Some(const_time(22, 0, 0)),  // 22:00 CE(S)T
     },
     tuesday: DaySession {
@@ -675,7 +679,40 @@ const EUREX_HOURS: TradingHours = TradingHours {
         open: None,
         close: None,
     },
-    week_start: Weekday::Sun,
+    week_start: Weekday::Mon,
+};
+// ICE Futures Europe energy 01:00 to 23:00 London time
+pub const ICE_EUROPE_HOURS: TradingHours = TradingHours {
+    timezone: chrono_tz::Europe::London,
+    sunday: DaySession {
+        open: None,
+        close: None,
+    },
+    monday: DaySession {
+        open: Some(const_time(1, 0, 0)),
+        close: Some(const_time(23, 0, 0)),
+    },
+    tuesday: DaySession {
+        open: Some(const_time(1, 0, 0)),
+        close: Some(const_time(23, 0, 0)),
+    },
+    wednesday: DaySession {
+        open: Some(const_time(1, 0, 0)),
+        close: Some(const_time(23, 0, 0)),
+    },
+    thursday: DaySession {
+        open: Some(const_time(1, 0, 0)),
+        close: Some(const_time(23, 0, 0)),
+    },
+    friday: DaySession {
+        open: Some(const_time(1, 0, 0)),
+        close: Some(const_time(23, 0, 0)),
+    },
+    saturday: DaySession {
+        open: None,
+        close: None,
+    },
+    week_start: Weekday::Mon,
 };
 
 
@@ -805,6 +842,12 @@ lazy_static! {
         m.insert("OGBM", &EUREX_HOURS);   // Mid-Term Euro-Bund Futures
         m.insert("OGBS", &EUREX_HOURS);   // Short-Term Euro-Bund Futures
 
+        // Euro Fixed Income Products
+        m.insert("FGBL", &EUREX_HOURS);   // Euro-Bund Futures
+        m.insert("FGBM", &EUREX_HOURS);   // Euro-Bobl Futures
+        m.insert("FGBS", &EUREX_HOURS);   // Euro-Schatz Futures
+        m.insert("FGBX", &EUREX_HOURS);   // Euro-Buxl Futures
+
         // European Index Products
         m.insert("FESX", &EUREX_HOURS);   // EURO STOXX 50 Index Futures
         m.insert("FESB", &EUREX_HOURS);   // EURO STOXX Banks Futures
@@ -814,6 +857,9 @@ lazy_static! {
         m.insert("V2TX", &EUREX_HOURS);   // VSTOXX Futures
         m.insert("EVIX", &EUREX_HOURS);   // Mini VSTOXX Futures
 
+        // ICE Futures Europe
+        m.insert("BRN", &ICE_EUROPE_HOURS);   // Brent Crude Futures
+
         m
     };
 }
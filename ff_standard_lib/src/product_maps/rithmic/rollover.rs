@@ -1,4 +1,4 @@
-use chrono::{DateTime, Datelike, Utc};
+use chrono::{DateTime, Datelike, Months, NaiveDate, NaiveTime, TimeZone, Utc};
 use std::collections::HashMap;
 use thiserror::Error;
 use crate::product_maps::rithmic::maps::get_futures_trading_hours;
@@ -10,6 +10,8 @@ pub enum RolloverError {
     UnknownSymbol(String),
     #[error("Invalid month for rollover: {0}")]
     InvalidMonth(u32),
+    #[error("Not a front month contract code: {0}")]
+    InvalidContract(String),
 }
 
 #[derive(Debug, Clone)]
@@ -95,6 +97,10 @@ fn month_to_code(month: u32) -> Result<char, RolloverError> {
     }
 }
 
+fn code_to_month(code: char) -> Option<u32> {
+    (1..=12).find(|month| month_to_code(*month).ok() == Some(code))
+}
+
 fn get_next_month(current_month: u32, is_quarterly: bool) -> (u32, bool) {
     if is_quarterly {
         match current_month {
@@ -183,6 +189,69 @@ pub fn get_front_month(symbol: &str, utc_time: DateTime<Utc>) -> Result<SymbolCo
     Ok(format!("{}{}{:02}", symbol, month_code, year_code))
 }
 
+/// The root symbol, month and year of a contract code, eg ("MNQ", 3, 2024) for "MNQH24" or "MNQH4".
+/// Single digit years are the first year ending in the digit from the year before `time`, so a contract which expired last year is not read as the next decade.
+pub fn parse_contract(symbol_code: &str, time: DateTime<Utc>) -> Result<(&str, u32, i32), RolloverError> {
+    let invalid = || RolloverError::InvalidContract(symbol_code.to_string());
+    let digits_start = symbol_code.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let digits = &symbol_code[digits_start..];
+    if digits.is_empty() || digits.len() > 2 {
+        return Err(invalid());
+    }
+    let month_code = symbol_code[..digits_start].chars().last().ok_or_else(invalid)?;
+    let month = code_to_month(month_code).ok_or_else(invalid)?;
+    let root = &symbol_code[..digits_start - month_code.len_utf8()];
+    if !CONTRACT_SPECS.contains_key(root) {
+        return Err(RolloverError::UnknownSymbol(root.to_string()));
+    }
+    let number: i32 = digits.parse().map_err(|_| invalid())?;
+    let year = match digits.len() {
+        2 => 2000 + number,
+        _ => {
+            let from = time.year() - 1;
+            from + (number - from.rem_euclid(10)).rem_euclid(10)
+        }
+    };
+    Ok((root, month, year))
+}
+
+/// The time a contract stops being the front month, the first midnight in the exchange time zone at which `get_front_month()` returns a later contract,
+/// and the code of the contract which replaces it, written with the same number of year digits as `symbol_code`.
+/// The engine treats the contract as expired from this time, which is before its last trading day at the exchange.
+pub fn contract_expiry(symbol_code: &str, time: DateTime<Utc>) -> Result<(DateTime<Utc>, SymbolCode), RolloverError> {
+    let invalid = || RolloverError::InvalidContract(symbol_code.to_string());
+    let (root, month, year) = parse_contract(symbol_code, time)?;
+    let time_zone = match get_futures_trading_hours(root) {
+        Some(hours) => hours.timezone,
+        None => return Err(RolloverError::UnknownSymbol(root.to_string()))
+    };
+    let contract = format!("{}{}{:02}", root, month_to_code(month)?, year % 100);
+    let single_digit_year = symbol_code.len() == root.len() + 2;
+
+    // a contract becomes the front month within the 4 months before its delivery month and stays the front month for at most 3 months
+    let mut date = NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|date| date.checked_sub_months(Months::new(4)))
+        .ok_or_else(invalid)?;
+    let mut was_front_month = false;
+    for _ in 0..220 {
+        if let Some(midnight) = time_zone.from_local_datetime(&date.and_time(NaiveTime::MIN)).earliest() {
+            let midnight = midnight.to_utc();
+            let front_month = get_front_month(root, midnight)?;
+            if front_month == contract {
+                was_front_month = true;
+            } else if was_front_month {
+                let next = match single_digit_year {
+                    true => format!("{}{}", &front_month[..front_month.len() - 2], &front_month[front_month.len() - 1..]),
+                    false => front_month,
+                };
+                return Ok((midnight, next));
+            }
+        }
+        date = date.succ_opt().ok_or_else(invalid)?;
+    }
+    Err(invalid())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -221,4 +290,18 @@ mod tests {
         test_case("CL", "2024-12-18 14:30:00", "CLF25"); // January contract after December rollover
         test_case("ES", "2024-12-13 14:30:00", "ESH25"); // March contract after December rollover
     }
+
+    #[test]
+    fn test_contract_expiry() {
+        use chrono_tz::America::Chicago;
+        let time = NaiveDateTime::parse_from_str("2024-01-02 14:30:00", "%Y-%m-%d %H:%M:%S").unwrap().and_utc();
+        assert_eq!(contract_expiry("ESH24", time).unwrap(), (Chicago.with_ymd_and_hms(2024, 3, 9, 0, 0, 0).unwrap().to_utc(), "ESM24".to_string()));
+        assert_eq!(contract_expiry("CLG24", time).unwrap(), (Chicago.with_ymd_and_hms(2024, 1, 18, 0, 0, 0).unwrap().to_utc(), "CLH24".to_string()));
+        // single digit years keep their format
+        assert_eq!(contract_expiry("MNQZ4", time).unwrap(), (Chicago.with_ymd_and_hms(2024, 12, 9, 0, 0, 0).unwrap().to_utc(), "MNQH5".to_string()));
+        assert_eq!(parse_contract("6EM4", time).unwrap(), ("6E", 6, 2024));
+        // ES only trades the quarterly months and root symbols have no expiry
+        assert!(contract_expiry("ESF24", time).is_err());
+        assert!(contract_expiry("MNQ", time).is_err());
+    }
 }
\ No newline at end of file
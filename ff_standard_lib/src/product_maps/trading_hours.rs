@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use chrono::{NaiveTime, Weekday};
+use chrono_tz::Tz;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use serde_derive::Deserialize;
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::product_maps::alpaca::maps::{NASDAQ_HOURS, NYSE_HOURS, US_EQUITY_EXTENDED_HOURS};
+use crate::product_maps::oanda::maps::{get_forex_trading_hours, FOREX_HOURS};
+use crate::product_maps::rithmic::maps::{get_exchange_by_symbol_name, get_futures_trading_hours, CBOT_GRAINS_HOURS, CME_HOURS, EUREX_HOURS, ICE_EUROPE_HOURS};
+use crate::standardized_types::market_hours::{DaySession, TradingHours};
+use crate::standardized_types::subscriptions::SymbolName;
+
+lazy_static! {
+    static ref SYMBOL_HOURS: DashMap<SymbolName, TradingHours> = DashMap::new();
+    static ref SYMBOL_EXCHANGES: DashMap<SymbolName, String> = DashMap::new();
+    static ref EXCHANGE_HOURS: DashMap<String, TradingHours> = DashMap::new();
+}
+
+/// The trading hours of the symbol, found in this order:
+/// 1. Hours set for the symbol with `set_symbol_trading_hours()` or the `[symbols]` of a trading hours file.
+/// 2. The exchange the symbol was assigned to in a trading hours file.
+/// 3. Hours set for the futures exchange of the symbol, eg `[exchanges.CME]` replaces the hours of every CME product.
+/// 4. The built-in hours of futures products in `rithmic::maps`.
+/// 5. `FOREX_HOURS` for Oanda currency pairs.
+pub fn get_trading_hours(symbol_name: &str) -> Option<TradingHours> {
+    if let Some(hours) = SYMBOL_HOURS.get(symbol_name) {
+        return Some(hours.value().clone());
+    }
+    if let Some(exchange) = SYMBOL_EXCHANGES.get(symbol_name) {
+        return get_exchange_trading_hours(exchange.value());
+    }
+    if let Some(exchange) = get_exchange_by_symbol_name(symbol_name) {
+        if let Some(hours) = EXCHANGE_HOURS.get(&exchange.to_string()) {
+            return Some(hours.value().clone());
+        }
+    }
+    if let Some(hours) = get_futures_trading_hours(symbol_name) {
+        return Some(hours.clone());
+    }
+    get_forex_trading_hours(symbol_name)
+}
+
+/// The hours set for the exchange, otherwise the built-in hours of the exchange. The name is not case sensitive.
+pub fn get_exchange_trading_hours(exchange: &str) -> Option<TradingHours> {
+    let exchange = exchange.to_uppercase();
+    if let Some(hours) = EXCHANGE_HOURS.get(&exchange) {
+        return Some(hours.value().clone());
+    }
+    match exchange.as_str() {
+        "CME" | "CBOT" | "COMEX" | "NYMEX" => Some(CME_HOURS),
+        "CBOT_GRAINS" => Some(CBOT_GRAINS_HOURS),
+        "EUREX" => Some(EUREX_HOURS),
+        "ICE_EUROPE" => Some(ICE_EUROPE_HOURS),
+        "FOREX" => Some(FOREX_HOURS),
+        "NYSE" => Some(NYSE_HOURS),
+        "NASDAQ" => Some(NASDAQ_HOURS),
+        "US_EQUITY_EXTENDED" => Some(US_EQUITY_EXTENDED_HOURS),
+        _ => None,
+    }
+}
+
+/// Sets the trading hours of the symbol, replacing the hours of its exchange.
+pub fn set_symbol_trading_hours(symbol_name: SymbolName, trading_hours: TradingHours) {
+    SYMBOL_EXCHANGES.remove(&symbol_name);
+    SYMBOL_HOURS.insert(symbol_name, trading_hours);
+}
+
+/// Sets the trading hours of the exchange, for the symbols assigned to it and for the futures listed on it.
+pub fn set_exchange_trading_hours(exchange: &str, trading_hours: TradingHours) {
+    EXCHANGE_HOURS.insert(exchange.to_uppercase(), trading_hours);
+}
+
+/// Loads the exchanges and symbols of a trading hours file, nothing is set if the file has an error.
+/// ```rust
+/// load_trading_hours("./resources/trading_hours.toml")?;
+/// let hours = get_trading_hours("FDAX").unwrap();
+/// ```
+pub fn load_trading_hours(file_path: &str) -> Result<(), FundForgeError> {
+    let content = std::fs::read_to_string(file_path)
+        .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to read trading hours file {}: {}", file_path, e)))?;
+    load_trading_hours_toml(&content)
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TradingHoursFile {
+    #[serde(default)]
+    exchanges: HashMap<String, HoursConfig>,
+    #[serde(default)]
+    symbols: HashMap<SymbolName, HoursConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HoursConfig {
+    exchange: Option<String>,
+    timezone: Option<String>,
+    week_start: Option<String>,
+    sunday: Option<String>,
+    monday: Option<String>,
+    tuesday: Option<String>,
+    wednesday: Option<String>,
+    thursday: Option<String>,
+    friday: Option<String>,
+    saturday: Option<String>,
+    #[serde(default)]
+    symbols: Vec<SymbolName>,
+}
+
+impl HoursConfig {
+    fn has_sessions(&self) -> bool {
+        self.timezone.is_some() || self.week_start.is_some()
+            || [&self.sunday, &self.monday, &self.tuesday, &self.wednesday, &self.thursday, &self.friday, &self.saturday].iter().any(|day| day.is_some())
+    }
+
+    fn trading_hours(&self, name: &str) -> Result<TradingHours, FundForgeError> {
+        let error = |message: String| FundForgeError::ClientSideErrorDebug(format!("Trading hours of {}: {}", name, message));
+        let timezone = self.timezone.as_ref().ok_or_else(|| error("no timezone".to_string()))?;
+        let timezone = Tz::from_str(timezone).map_err(|_| error(format!("unknown timezone {}", timezone)))?;
+        let day = |session: &Option<String>| match session {
+            Some(session) => parse_session(session).map_err(error),
+            None => Ok(DaySession { open: None, close: None }),
+        };
+        let sunday = day(&self.sunday)?;
+        let week_start = match &self.week_start {
+            Some(week_start) => Weekday::from_str(week_start).map_err(|_| error(format!("unknown week start {}", week_start)))?,
+            None if sunday.open.is_some() => Weekday::Sun,
+            None => Weekday::Mon,
+        };
+        Ok(TradingHours {
+            timezone,
+            sunday,
+            monday: day(&self.monday)?,
+            tuesday: day(&self.tuesday)?,
+            wednesday: day(&self.wednesday)?,
+            thursday: day(&self.thursday)?,
+            friday: day(&self.friday)?,
+            saturday: day(&self.saturday)?,
+            week_start,
+        })
+    }
+}
+
+/// Parses "open-close", "open-" or "-close" with times as "HH:MM" or "HH:MM:SS".
+fn parse_session(session: &str) -> Result<DaySession, String> {
+    let (open, close) = session.split_once('-').ok_or_else(|| format!("session {} is not open-close", session))?;
+    let time = |time: &str| -> Result<Option<NaiveTime>, String> {
+        let time = time.trim();
+        if time.is_empty() {
+            return Ok(None);
+        }
+        NaiveTime::parse_from_str(time, "%H:%M:%S")
+            .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+            .map(Some)
+            .map_err(|_| format!("invalid time {} in session {}", time, session))
+    };
+    let day_session = DaySession { open: time(open)?, close: time(close)? };
+    if day_session.open.is_none() && day_session.close.is_none() {
+        return Err(format!("session {} has no open or close", session));
+    }
+    Ok(day_session)
+}
+
+fn load_trading_hours_toml(content: &str) -> Result<(), FundForgeError> {
+    let file: TradingHoursFile = toml::from_str(content)
+        .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to parse trading hours file: {}", e)))?;
+
+    // validate everything before setting anything
+    let mut exchanges = vec![];
+    let mut assigned = vec![];
+    for (exchange, config) in &file.exchanges {
+        if config.exchange.is_some() {
+            return Err(FundForgeError::ClientSideErrorDebug(format!("Trading hours of exchange {}: an exchange can not name another exchange", exchange)));
+        }
+        exchanges.push((exchange.to_uppercase(), config.trading_hours(exchange)?));
+        for symbol_name in &config.symbols {
+            assigned.push((symbol_name.clone(), exchange.to_uppercase()));
+        }
+    }
+    let is_exchange = |exchange: &str| exchanges.iter().any(|(name, _)| *name == exchange.to_uppercase()) || get_exchange_trading_hours(exchange).is_some();
+    let mut symbols = vec![];
+    for (symbol_name, config) in &file.symbols {
+        if !config.symbols.is_empty() {
+            return Err(FundForgeError::ClientSideErrorDebug(format!("Trading hours of {}: symbols can only be listed for an exchange", symbol_name)));
+        }
+        match (&config.exchange, config.has_sessions()) {
+            (Some(exchange), false) if is_exchange(exchange) => assigned.push((symbol_name.clone(), exchange.to_uppercase())),
+            (Some(exchange), false) => return Err(FundForgeError::ClientSideErrorDebug(format!("Trading hours of {}: unknown exchange {}", symbol_name, exchange))),
+            (Some(_), true) => return Err(FundForgeError::ClientSideErrorDebug(format!("Trading hours of {}: give an exchange or sessions, not both", symbol_name))),
+            (None, _) => symbols.push((symbol_name.clone(), config.trading_hours(symbol_name)?)),
+        }
+    }
+
+    for (exchange, trading_hours) in exchanges {
+        EXCHANGE_HOURS.insert(exchange, trading_hours);
+    }
+    for (symbol_name, exchange) in assigned {
+        SYMBOL_HOURS.remove(&symbol_name);
+        SYMBOL_EXCHANGES.insert(symbol_name, exchange);
+    }
+    for (symbol_name, trading_hours) in symbols {
+        set_symbol_trading_hours(symbol_name, trading_hours);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::America::New_York;
+    use chrono_tz::Asia::Hong_Kong;
+
+    // the registry is global, each test uses its own symbol and exchange names
+    #[test]
+    fn test_load_trading_hours() {
+        load_trading_hours_toml(r#"
+            [exchanges.TEST_SOFTS]
+            timezone = "America/New_York"
+            monday = "04:15-13:30"
+            tuesday = "04:15-13:30"
+            wednesday = "04:15-13:30"
+            thursday = "04:15-13:30"
+            friday = "04:15-13:30"
+            symbols = ["TEST_KC"]
+
+            [symbols.TEST_FDAX]
+            exchange = "eurex"
+
+            [symbols.TEST_HSI]
+            timezone = "Asia/Hong_Kong"
+            monday = "09:15-"
+            tuesday = "-03:00"
+        "#).unwrap();
+
+        let softs = get_trading_hours("TEST_KC").unwrap();
+        assert_eq!(softs.week_start, Weekday::Mon);
+        let wednesday = New_York.with_ymd_and_hms(2024, 1, 10, 12, 0, 0).unwrap().to_utc();
+        assert_eq!(softs.seconds_until_close(wednesday), Some(5400));
+        let saturday = New_York.with_ymd_and_hms(2024, 1, 13, 12, 0, 0).unwrap().to_utc();
+        assert_eq!(softs.seconds_until_open(saturday), Some(144_900)); // Monday 04:15
+
+        assert_eq!(get_trading_hours("TEST_FDAX").unwrap().timezone, chrono_tz::Europe::Berlin);
+
+        // the Monday session runs over midnight to 03:00 Tuesday
+        let hsi = get_trading_hours("TEST_HSI").unwrap();
+        let monday_night = Hong_Kong.with_ymd_and_hms(2024, 1, 8, 23, 0, 0).unwrap().to_utc();
+        assert!(hsi.is_market_open(monday_night));
+        assert_eq!(hsi.seconds_until_close(monday_night), Some(14_400));
+    }
+
+    #[test]
+    fn test_invalid_trading_hours_set_nothing() {
+        let invalid = [
+            "[symbols.TEST_BAD_TZ]\ntimezone = \"Mars/Olympus\"\nmonday = \"09:00-17:00\"",
+            "[symbols.TEST_BAD_TIME]\ntimezone = \"Europe/London\"\nmonday = \"9am-17:00\"",
+            "[symbols.TEST_BAD_EXCHANGE]\nexchange = \"NOWHERE\"",
+            "[symbols.TEST_BOTH]\nexchange = \"CME\"\nmonday = \"09:00-17:00\"",
+            "[exchanges.TEST_EX]\ntimezone = \"Europe/London\"\nmonday = \"09:00-17:00\"\n[symbols.TEST_EX_SYMBOL]\nexchange = \"NOWHERE\"",
+        ];
+        for content in invalid {
+            assert!(load_trading_hours_toml(content).is_err(), "{}", content);
+        }
+        assert!(get_trading_hours("TEST_BAD_TZ").is_none());
+        assert!(get_exchange_trading_hours("TEST_EX").is_none());
+    }
+
+    #[test]
+    fn test_lookup_order() {
+        // built-in futures and forex hours
+        assert_eq!(get_trading_hours("FGBL").unwrap().timezone, chrono_tz::Europe::Berlin);
+        assert_eq!(get_trading_hours("EUR-USD").unwrap().timezone, New_York);
+        assert!(get_trading_hours("BTC-USD").is_none());
+
+        // symbol hours replace the exchange the symbol was assigned to
+        set_exchange_trading_hours("test_lookup", ICE_EUROPE_HOURS);
+        load_trading_hours_toml("[symbols.TEST_LOOKUP]\nexchange = \"TEST_LOOKUP\"").unwrap();
+        assert_eq!(get_trading_hours("TEST_LOOKUP").unwrap().timezone, chrono_tz::Europe::London);
+        set_symbol_trading_hours("TEST_LOOKUP".to_string(), NYSE_HOURS);
+        assert_eq!(get_trading_hours("TEST_LOOKUP").unwrap().timezone, New_York);
+    }
+}
@@ -1,6 +1,9 @@
 use std::net::{IpAddr, Ipv4Addr};
 use std::path::PathBuf;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 use structopt::StructOpt;
+use crate::standardized_types::base_data::data_quality::OutlierAction;
 
 #[derive(Debug, StructOpt, Clone)]
 #[allow(dead_code)]
@@ -71,6 +74,18 @@ pub struct ServerLaunchOptions {
     )]
     pub disable_bitget_server: u64,
 
+    #[structopt(
+        long = "alpaca",
+        default_value = "0"
+    )]
+    pub disable_alpaca_server: u64,
+
+    #[structopt(
+        long = "polygon",
+        default_value = "0"
+    )]
+    pub disable_polygon_server: u64,
+
     /// Sets the maximum number of concurrent downloads
     #[structopt(
         short = "m",
@@ -86,6 +101,41 @@ pub struct ServerLaunchOptions {
         default_value = "900"
     )]
     pub update_seconds: u64,
+
+    /// Set to 1 to save the live ticks and quotes streamed to strategies into the historical data
+    #[structopt(
+        long = "record_live",
+        default_value = "0"
+    )]
+    pub record_live: u64,
+
+    /// Sets how often the recorded live data is saved in seconds
+    #[structopt(
+        long = "record_flush",
+        default_value = "60"
+    )]
+    pub record_flush_seconds: u64,
+
+    /// Sets what is done with invalid data and outlier prices as they are ingested: off, flag, filter or winsorize
+    #[structopt(
+        long = "outliers",
+        default_value = "off"
+    )]
+    pub outlier_action: OutlierAction,
+
+    /// Sets the width of the outlier band in scaled median absolute deviations of the recent prices
+    #[structopt(
+        long = "outlier_threshold",
+        default_value = "10"
+    )]
+    pub outlier_threshold: Decimal,
+
+    /// Sets the minimum width of the outlier band as a fraction of the median price
+    #[structopt(
+        long = "outlier_min_band",
+        default_value = "0.005"
+    )]
+    pub outlier_min_band: Decimal,
 }
 impl Default for ServerLaunchOptions {
     fn default() -> Self {
@@ -99,8 +149,15 @@ impl Default for ServerLaunchOptions {
             disable_rithmic_server: 0,
             disable_oanda_server: 0,
             disable_bitget_server: 0,
+            disable_alpaca_server: 0,
+            disable_polygon_server: 0,
             max_downloads: 20,
             update_seconds: 900,
+            record_live: 0,
+            record_flush_seconds: 60,
+            outlier_action: OutlierAction::Off,
+            outlier_threshold: dec!(10),
+            outlier_min_band: dec!(0.005),
         }
     }
 }
@@ -31,6 +31,26 @@ pub struct AccountInfo {
     pub leverage: u32
 }
 
+/// The balances of a live account, sent to the strategy as `StrategyEvent::AccountUpdate` when they change.
+///
+/// # Properties
+/// * `balance` - The cash available plus the cash used, the same as `strategy.balance(&account)`.
+/// * `buying_power` - The cash available for new positions.
+/// * `margin_used` - The cash used as margin by open positions.
+/// * `time` - The utc time the broker values were received.
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Serialize, Deserialize)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct AccountSummary {
+    pub account: Account,
+    pub balance: Price,
+    pub cash_value: Price,
+    pub buying_power: Price,
+    pub margin_used: Price,
+    pub currency: Currency,
+    pub time: String,
+}
+
 pub type AccountId = String;
 pub type AccountName = String;
 
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::resolution::Resolution;
+use crate::strategies::indicators::indicator_values::IndicatorValues;
+
+/// Whether a bar time is the time the bar opened or the time it closed, see `strategy.set_bar_time_convention()`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum BarTimeConvention {
+    /// A 1 hour bar from 9:00 to 10:00 is stamped 9:00, as most charting platforms do.
+    OpenTime,
+    /// A 1 hour bar from 9:00 to 10:00 is stamped 10:00, the time the bar is received, the default.
+    #[default]
+    CloseTime,
+}
+
+impl BarTimeConvention {
+    /// The time of the data in this convention.
+    pub fn time_of<T: BaseData>(&self, data: &T) -> DateTime<Utc> {
+        match self {
+            BarTimeConvention::OpenTime => data.time_utc(),
+            BarTimeConvention::CloseTime => data.time_closed_utc(),
+        }
+    }
+
+    /// The time of the indicator values in this convention, indicator values are stamped with the close time of the bar they were calculated on.
+    pub fn time_of_values(&self, values: &IndicatorValues) -> DateTime<Utc> {
+        self.from_close_time(values.time_utc(), &values.subscription.resolution)
+    }
+
+    /// Converts a bar close time to this convention.
+    pub fn from_close_time(&self, close_time: DateTime<Utc>, resolution: &Resolution) -> DateTime<Utc> {
+        match self {
+            BarTimeConvention::OpenTime => close_time_to_open_time(close_time, resolution),
+            BarTimeConvention::CloseTime => close_time,
+        }
+    }
+
+    /// Converts a bar time in this convention to the bar close time.
+    pub fn to_close_time(&self, time: DateTime<Utc>, resolution: &Resolution) -> DateTime<Utc> {
+        match self {
+            BarTimeConvention::OpenTime => open_time_to_close_time(time, resolution),
+            BarTimeConvention::CloseTime => time,
+        }
+    }
+
+    /// Converts a bar time in this convention to another convention, eg to compare with bars exported from another platform.
+    pub fn convert(&self, time: DateTime<Utc>, resolution: &Resolution, to: BarTimeConvention) -> DateTime<Utc> {
+        to.from_close_time(self.to_close_time(time, resolution), resolution)
+    }
+}
+
+/// The close time of the bar which opens at `open_time`.
+pub fn open_time_to_close_time(open_time: DateTime<Utc>, resolution: &Resolution) -> DateTime<Utc> {
+    open_time + resolution.as_duration()
+}
+
+/// The open time of the bar which closes at `close_time`.
+pub fn close_time_to_open_time(close_time: DateTime<Utc>, resolution: &Resolution) -> DateTime<Utc> {
+    close_time - resolution.as_duration()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use crate::standardized_types::base_data::candle::generate_5_day_candle_data;
+
+    #[test]
+    fn test_bar_time_conventions() {
+        let candle = generate_5_day_candle_data()[0].clone();
+        let open = candle.time_utc();
+        let close = open + candle.resolution.as_duration();
+        assert_eq!(BarTimeConvention::OpenTime.time_of(&candle), open);
+        assert_eq!(BarTimeConvention::CloseTime.time_of(&candle), close);
+        assert_eq!(BarTimeConvention::OpenTime.to_close_time(open, &candle.resolution), close);
+        assert_eq!(BarTimeConvention::OpenTime.from_close_time(close, &candle.resolution), open);
+        assert_eq!(BarTimeConvention::CloseTime.convert(close, &candle.resolution, BarTimeConvention::OpenTime), open);
+        assert_eq!(BarTimeConvention::OpenTime.convert(open, &candle.resolution, BarTimeConvention::OpenTime), open);
+    }
+
+    #[test]
+    fn test_instant_data_has_one_time() {
+        let time = Utc.with_ymd_and_hms(2024, 6, 3, 9, 30, 0).unwrap();
+        assert_eq!(BarTimeConvention::OpenTime.from_close_time(time, &Resolution::Instant), time);
+        assert_eq!(BarTimeConvention::OpenTime.from_close_time(time, &Resolution::Ticks(100)), time);
+        assert_eq!(BarTimeConvention::OpenTime.from_close_time(time, &Resolution::Hours(1)), time - Duration::hours(1));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::standardized_types::base_data::base_data_type::BaseDataType;
 use crate::standardized_types::base_data::candle::Candle;
+use crate::standardized_types::base_data::depth::Depth;
 use crate::standardized_types::base_data::fundamental::Fundamental;
 use crate::standardized_types::base_data::quote::Quote;
 use crate::standardized_types::base_data::quotebar::QuoteBar;
@@ -18,6 +19,7 @@ use std::fmt::{Display, Error, Formatter};
 use std::str::FromStr;
 use crate::standardized_types::datavendor_enum::DataVendor;
 use crate::standardized_types::resolution::Resolution;
+use crate::standardized_types::versioning::{migrate_storage, with_storage_version};
 
 /// Enum for the different types of base data
 /// This is the main_window enum for all the different types of base data
@@ -30,6 +32,7 @@ use crate::standardized_types::resolution::Resolution;
 /// * `Tick`        see [`BaseDataEnum::Tick`](ff_data_vendors::base_data_types::base_data_enum::BaseDataEnum::Tick)
 /// * `Quote`       see [`BaseDataEnum::Quote`](ff_data_vendors::base_data_types::base_data_enum::BaseDataEnum::Quote)
 /// * `Fundamental` see [`BaseDataEnum::Fundamental`](ff_data_vendors::base_data_types::base_data_enum::BaseDataEnum::Fundamental)
+/// * `Depth`       see [`BaseDataEnum::Depth`](ff_data_vendors::base_data_types::base_data_enum::BaseDataEnum::Depth)
 #[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug)]
 #[archive(compare(PartialEq), check_bytes)]
 #[archive_attr(derive(Debug))]
@@ -110,6 +113,15 @@ pub enum BaseDataEnum {
     /// * `bias` - `Bias` enum The bias of the fundamental data `Bias` enum variant.
     /// * `data_vendor` - `DataVendor` enum The data vendor of the fundamental data `DataVendor` enum variant.
     Fundamental(Fundamental),
+
+    /// The `Depth` struct is used to represent the order book by price level at a given time, each update to the book is a new snapshot. see [`Depth`](ff_data_vendors::base_data_types::depth::Depth)
+    ///
+    /// # Properties
+    /// * `symbol` - The symbol of the asset.
+    /// * `bids` - The bid levels, best first.
+    /// * `asks` - The ask levels, best first.
+    /// * `time` - The time of the snapshot.
+    Depth(Depth),
 }
 
 impl Display for BaseDataEnum {
@@ -164,6 +176,7 @@ impl Display for BaseDataEnum {
                 fundamental.time,
                 fundamental.name
             ),
+            BaseDataEnum::Depth(depth) => write!(f, "{}", depth),
         }
     }
 }
@@ -188,6 +201,7 @@ impl BaseDataEnum {
             BaseDataEnum::Tick(_) => BaseDataType::Ticks,
             BaseDataEnum::Quote(_) => BaseDataType::Quotes,
             BaseDataEnum::Fundamental(_) => BaseDataType::Fundamentals,
+            BaseDataEnum::Depth(_) => BaseDataType::Depth,
         }
     }
 
@@ -235,6 +249,18 @@ impl BaseDataEnum {
         let vec = BaseDataEnum::vec_to_aligned(price_data);
         vec.to_vec()
     }
+
+    /// Serializes a day of data for the historical store, with the storage schema version.
+    pub fn vec_to_storage_bytes(price_data: Vec<BaseDataEnum>) -> Vec<u8> {
+        with_storage_version(BaseDataEnum::vec_to_bytes(price_data))
+    }
+
+    /// Deserializes a stored day of data, files saved with an older schema version are migrated, see `versioning.rs`.
+    pub fn from_storage_bytes(data: &[u8]) -> Result<Vec<BaseDataEnum>, FundForgeError> {
+        let archive = migrate_storage(data)?.into_owned();
+        BaseDataEnum::from_array_bytes(&archive)
+            .map_err(|_| FundForgeError::ClientSideErrorDebug("Failed to deserialize stored data".to_string()))
+    }
 }
 
 impl Bytes<Self> for BaseDataEnum {
@@ -262,6 +288,7 @@ impl BaseData for BaseDataEnum {
             BaseDataEnum::Tick(tick) => tick.symbol.clone(),
             BaseDataEnum::Quote(quote) => quote.symbol.clone(),
             BaseDataEnum::Fundamental(fundamental) => fundamental.symbol.clone(),
+            BaseDataEnum::Depth(depth) => depth.symbol.clone(),
         }
     }
 
@@ -280,6 +307,7 @@ impl BaseData for BaseDataEnum {
             BaseDataEnum::Fundamental(fundamental) => {
                 DateTime::from_str(&fundamental.time).unwrap()
             }
+            BaseDataEnum::Depth(depth) => DateTime::from_str(&depth.time).unwrap(),
         }
     }
 
@@ -292,6 +320,7 @@ impl BaseData for BaseDataEnum {
             BaseDataEnum::Tick(tick) => tick.time_utc(),
             BaseDataEnum::Quote(quote) => quote.time_utc(),
             BaseDataEnum::Fundamental(fundamental) => fundamental.time_utc(),
+            BaseDataEnum::Depth(depth) => depth.time_utc(),
         }
     }
 
@@ -307,6 +336,7 @@ impl BaseData for BaseDataEnum {
             BaseDataEnum::Tick(tick) => tick.symbol.data_vendor.clone(),
             BaseDataEnum::Quote(quote) => quote.symbol.data_vendor.clone(),
             BaseDataEnum::Fundamental(fundamental) => fundamental.symbol.data_vendor.clone(),
+            BaseDataEnum::Depth(depth) => depth.symbol.data_vendor.clone(),
         }
     }
 
@@ -317,6 +347,7 @@ impl BaseData for BaseDataEnum {
             BaseDataEnum::Tick(tick) => tick.symbol.market_type.clone(),
             BaseDataEnum::Quote(quote) => quote.symbol.market_type.clone(),
             BaseDataEnum::Fundamental(fundamental) => fundamental.symbol.market_type.clone(),
+            BaseDataEnum::Depth(depth) => depth.symbol.market_type.clone(),
         }
     }
 
@@ -338,6 +369,7 @@ impl BaseData for BaseDataEnum {
             BaseDataEnum::Tick(tick) => &tick.symbol,
             BaseDataEnum::Quote(quote) => &quote.symbol,
             BaseDataEnum::Fundamental(fundamental) => &fundamental.symbol,
+            BaseDataEnum::Depth(depth) => &depth.symbol,
         }
     }
 
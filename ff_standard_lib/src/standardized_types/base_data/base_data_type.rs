@@ -1,4 +1,5 @@
 use crate::standardized_types::base_data::candle::Candle;
+use crate::standardized_types::base_data::depth::Depth;
 use crate::standardized_types::base_data::quote::Quote;
 use crate::standardized_types::base_data::quotebar::QuoteBar;
 use crate::standardized_types::base_data::tick::Tick;
@@ -34,6 +35,7 @@ pub enum BaseDataType {
     QuoteBars = 2,
     Candles = 3,
     Fundamentals = 4,
+    Depth = 5,
 }
 impl BaseDataType {
     // Function to get_requests the TypeId of the associated data type
@@ -44,6 +46,7 @@ impl BaseDataType {
             BaseDataType::QuoteBars => TypeId::of::<QuoteBar>(),
             BaseDataType::Candles => TypeId::of::<Candle>(),
             BaseDataType::Fundamentals => TypeId::of::<String>(),
+            BaseDataType::Depth => TypeId::of::<Depth>(),
             //BaseDataType::OrderBooks => TypeId::of::<OrderBook>(),
         }
     }
@@ -56,6 +59,7 @@ impl BaseDataType {
             "quotebars" => Ok(BaseDataType::QuoteBars),
             "candles" => Ok(BaseDataType::Candles),
             "fundamentals" => Ok(BaseDataType::Fundamentals),
+            "depth" => Ok(BaseDataType::Depth),
             // "order books" => Ok(BaseDataType::OrderBooks),
             _ => Err(format!("Unknown BaseDataType: {}", string_ref)),
         }
@@ -69,6 +73,7 @@ impl BaseDataType {
             BaseDataType::QuoteBars => "Quotebars".to_string(),
             BaseDataType::Candles => "Candles".to_string(),
             BaseDataType::Fundamentals => "Fundamentals".to_string(),
+            BaseDataType::Depth => "Depth".to_string(),
             //BaseDataType::OrderBooks => "order books".to_string(),
         }
     }
@@ -0,0 +1,386 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::subscriptions::{DataSubscription, SymbolName};
+
+/// The number of recent prices the band is measured from.
+const WINDOW: usize = 50;
+/// The number of recent prices needed before outliers are checked.
+const MIN_SAMPLES: usize = 10;
+/// The number of consecutive, consistent outliers which are accepted as a new price level.
+const LEVEL_SHIFT_COUNT: usize = 5;
+/// Scales the median absolute deviation to the standard deviation of normally distributed prices.
+const MAD_SCALE: Decimal = dec!(1.4826);
+
+/// What the data quality checks do with invalid data and outliers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutlierAction {
+    /// The checks are disabled, the default.
+    Off,
+    /// Count the data in the metrics but pass it through unchanged.
+    Flag,
+    /// Remove invalid data and outliers.
+    Filter,
+    /// Remove invalid data and clamp outlier prices to the band.
+    Winsorize,
+}
+
+impl FromStr for OutlierAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(OutlierAction::Off),
+            "flag" => Ok(OutlierAction::Flag),
+            "filter" => Ok(OutlierAction::Filter),
+            "winsorize" => Ok(OutlierAction::Winsorize),
+            _ => Err(format!("Unknown outlier action: {}, expected off, flag, filter or winsorize", s)),
+        }
+    }
+}
+
+impl fmt::Display for OutlierAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutlierAction::Off => write!(f, "off"),
+            OutlierAction::Flag => write!(f, "flag"),
+            OutlierAction::Filter => write!(f, "filter"),
+            OutlierAction::Winsorize => write!(f, "winsorize"),
+        }
+    }
+}
+
+/// The data quality counts for a subscription since the data server started.
+///
+/// # Properties
+/// * `subscription` - The subscription the data belongs to.
+/// * `live` - True for data from live vendor streams, false for data saved to the historical store.
+/// * `received` - The data points checked.
+/// * `invalid` - Data with a zero or negative price, or a candle with the high below the low.
+/// * `outliers` - Data with a price outside the band around the recent median.
+/// * `removed` - The invalid data and outliers which were removed.
+/// * `winsorized` - The outliers with prices clamped to the band.
+/// * `level_shifts` - The times consecutive outliers were accepted as a new price level.
+/// * `last_issue` - A description of the last invalid data or outlier.
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct DataQualityMetrics {
+    pub subscription: DataSubscription,
+    pub live: bool,
+    pub received: u64,
+    pub invalid: u64,
+    pub outliers: u64,
+    pub removed: u64,
+    pub winsorized: u64,
+    pub level_shifts: u64,
+    pub last_issue: Option<String>,
+}
+
+impl fmt::Display for DataQualityMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}): received: {}, invalid: {}, outliers: {}, removed: {}, winsorized: {}, level shifts: {}",
+            self.subscription, if self.live { "live" } else { "history" }, self.received, self.invalid, self.outliers, self.removed, self.winsorized, self.level_shifts
+        )?;
+        if let Some(issue) = &self.last_issue {
+            write!(f, ", last issue: {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+struct SubscriptionQuality {
+    recent: VecDeque<Price>,
+    rejected: Vec<Price>,
+    last_time: Option<DateTime<Utc>>,
+    metrics: DataQualityMetrics,
+}
+
+impl SubscriptionQuality {
+    fn push(&mut self, price: Price) {
+        if self.recent.len() == WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(price);
+    }
+
+    /// The lowest and highest accepted prices, None until there are enough recent prices.
+    fn band(&self, threshold: Decimal, min_band: Decimal) -> Option<(Price, Price)> {
+        if self.recent.len() < MIN_SAMPLES {
+            return None;
+        }
+        let median = median(self.recent.iter().cloned().collect());
+        let deviation = median_absolute_deviation(self.recent.iter().cloned().collect(), median);
+        let width = (threshold * deviation * MAD_SCALE).max(min_band * median);
+        Some((median - width, median + width))
+    }
+}
+
+fn median(mut values: Vec<Decimal>) -> Decimal {
+    values.sort();
+    let middle = values.len() / 2;
+    match values.len() % 2 {
+        0 => (values[middle - 1] + values[middle]) / dec!(2),
+        _ => values[middle],
+    }
+}
+
+fn median_absolute_deviation(values: Vec<Decimal>, median_value: Decimal) -> Decimal {
+    median(values.into_iter().map(|value| (value - median_value).abs()).collect())
+}
+
+/// The price the recent prices are measured from and every price of the data which must be inside the band.
+fn checked_prices(data: &BaseDataEnum) -> Option<(Price, Vec<Price>)> {
+    match data {
+        BaseDataEnum::Tick(tick) => Some((tick.price, vec![tick.price])),
+        BaseDataEnum::Quote(quote) => Some(((quote.bid + quote.ask) / dec!(2), vec![quote.bid, quote.ask])),
+        BaseDataEnum::Candle(candle) => Some((candle.close, vec![candle.open, candle.high, candle.low, candle.close])),
+        BaseDataEnum::QuoteBar(bar) => Some((
+            (bar.bid_close + bar.ask_close) / dec!(2),
+            vec![bar.bid_open, bar.bid_high, bar.bid_low, bar.bid_close, bar.ask_open, bar.ask_high, bar.ask_low, bar.ask_close],
+        )),
+        BaseDataEnum::Fundamental(_) | BaseDataEnum::Depth(_) => None,
+    }
+}
+
+fn is_malformed(data: &BaseDataEnum, prices: &[Price]) -> bool {
+    prices.iter().any(|price| *price <= dec!(0)) || match data {
+        BaseDataEnum::Candle(candle) => candle.high < candle.low,
+        BaseDataEnum::QuoteBar(bar) => bar.bid_high < bar.bid_low || bar.ask_high < bar.ask_low,
+        _ => false,
+    }
+}
+
+fn winsorize(data: &mut BaseDataEnum, low: Price, high: Price) {
+    let clamp = |price: &mut Price| *price = (*price).clamp(low, high);
+    match data {
+        BaseDataEnum::Tick(tick) => clamp(&mut tick.price),
+        BaseDataEnum::Quote(quote) => {
+            clamp(&mut quote.bid);
+            clamp(&mut quote.ask);
+        }
+        BaseDataEnum::Candle(candle) => {
+            for price in [&mut candle.open, &mut candle.high, &mut candle.low, &mut candle.close] {
+                clamp(price);
+            }
+            candle.range = candle.high - candle.low;
+        }
+        BaseDataEnum::QuoteBar(bar) => {
+            for price in [&mut bar.bid_open, &mut bar.bid_high, &mut bar.bid_low, &mut bar.bid_close, &mut bar.ask_open, &mut bar.ask_high, &mut bar.ask_low, &mut bar.ask_close] {
+                clamp(price);
+            }
+            bar.range = (bar.ask_high + bar.bid_high) - (bar.ask_low + bar.bid_low);
+            bar.spread = bar.ask_close - bar.bid_close;
+        }
+        BaseDataEnum::Fundamental(_) | BaseDataEnum::Depth(_) => {}
+    }
+}
+
+/// Checks data as it is ingested and keeps the quality metrics per subscription.
+///
+/// # Properties
+/// * `action` - What is done with invalid data and outliers.
+/// * `threshold` - The width of the band in scaled median absolute deviations of the recent prices.
+/// * `min_band` - The minimum width of the band as a fraction of the median price.
+/// * `live` - True if the filter checks live vendor streams, recorded in the metrics.
+pub struct DataQualityFilter {
+    action: OutlierAction,
+    threshold: Decimal,
+    min_band: Decimal,
+    live: bool,
+    subscriptions: DashMap<DataSubscription, SubscriptionQuality>,
+}
+
+impl DataQualityFilter {
+    pub fn new(action: OutlierAction, threshold: Decimal, min_band: Decimal, live: bool) -> Self {
+        DataQualityFilter {
+            action,
+            threshold,
+            min_band,
+            live,
+            subscriptions: DashMap::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.action != OutlierAction::Off
+    }
+
+    /// Checks the data, returns None if it was removed.
+    pub fn clean(&self, mut data: BaseDataEnum) -> Option<BaseDataEnum> {
+        if !self.is_enabled() {
+            return Some(data);
+        }
+        let Some((reference, prices)) = checked_prices(&data) else {
+            return Some(data);
+        };
+        let subscription = data.subscription();
+        let time = data.time_closed_utc();
+        let mut state = self.subscriptions.entry(subscription.clone()).or_insert_with(|| SubscriptionQuality {
+            recent: VecDeque::with_capacity(WINDOW),
+            rejected: vec![],
+            last_time: None,
+            metrics: DataQualityMetrics {
+                subscription: subscription.clone(),
+                live: self.live,
+                received: 0,
+                invalid: 0,
+                outliers: 0,
+                removed: 0,
+                winsorized: 0,
+                level_shifts: 0,
+                last_issue: None,
+            },
+        });
+        state.metrics.received += 1;
+
+        let reset_gap = Duration::days(1).max(subscription.resolution.as_duration() * 4);
+        if let Some(last_time) = state.last_time {
+            if time < last_time || time - last_time > reset_gap {
+                state.recent.clear();
+                state.rejected.clear();
+            }
+        }
+        state.last_time = Some(time);
+
+        if is_malformed(&data, &prices) {
+            state.metrics.invalid += 1;
+            state.metrics.last_issue = Some(format!("{}: invalid prices {:?}", time, prices));
+            return match self.action {
+                OutlierAction::Flag | OutlierAction::Off => Some(data),
+                OutlierAction::Filter | OutlierAction::Winsorize => {
+                    state.metrics.removed += 1;
+                    None
+                }
+            };
+        }
+
+        let Some((low, high)) = state.band(self.threshold, self.min_band) else {
+            state.push(reference);
+            return Some(data);
+        };
+        if prices.iter().all(|price| *price >= low && *price <= high) {
+            state.rejected.clear();
+            state.push(reference);
+            return Some(data);
+        }
+
+        state.metrics.outliers += 1;
+        state.metrics.last_issue = Some(format!("{}: prices {:?} outside {} to {}", time, prices, low, high));
+        state.rejected.push(reference);
+        if state.rejected.len() >= LEVEL_SHIFT_COUNT {
+            let rejected_low = state.rejected.iter().min().cloned().unwrap_or_default();
+            let rejected_high = state.rejected.iter().max().cloned().unwrap_or_default();
+            if rejected_high - rejected_low <= high - low {
+                state.metrics.level_shifts += 1;
+                let rejected: VecDeque<Price> = state.rejected.drain(..).collect();
+                state.recent = rejected;
+                return Some(data);
+            }
+            state.rejected.remove(0);
+        }
+
+        match self.action {
+            OutlierAction::Flag | OutlierAction::Off => Some(data),
+            OutlierAction::Filter => {
+                state.metrics.removed += 1;
+                None
+            }
+            OutlierAction::Winsorize => {
+                state.metrics.winsorized += 1;
+                winsorize(&mut data, low, high);
+                Some(data)
+            }
+        }
+    }
+
+    /// Checks the data in time order, returns the data which was not removed.
+    pub fn clean_bulk(&self, mut data: Vec<BaseDataEnum>) -> Vec<BaseDataEnum> {
+        if !self.is_enabled() {
+            return data;
+        }
+        data.sort_by_key(|data| data.time_closed_utc());
+        data.into_iter().filter_map(|data| self.clean(data)).collect()
+    }
+
+    /// The metrics for every subscription of the symbol, or for every subscription if `symbol_name` is None.
+    pub fn metrics(&self, symbol_name: Option<&SymbolName>) -> Vec<DataQualityMetrics> {
+        self.subscriptions
+            .iter()
+            .filter(|entry| symbol_name.map_or(true, |name| &entry.key().symbol.name == name))
+            .map(|entry| entry.value().metrics.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standardized_types::base_data::tick::{Aggressor, Tick};
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::{FuturesExchange, MarketType};
+    use crate::standardized_types::subscriptions::Symbol;
+
+    fn ticks(prices: &[Price]) -> Vec<BaseDataEnum> {
+        let symbol = Symbol::new("NQ".to_string(), DataVendor::DataBento, MarketType::Futures(FuturesExchange::CME));
+        let start = Utc::now();
+        prices.iter().enumerate().map(|(i, price)| {
+            let time = start + Duration::seconds(i as i64);
+            BaseDataEnum::Tick(Tick::new(symbol.clone(), *price, time.to_string(), dec!(1), Aggressor::None))
+        }).collect()
+    }
+
+    fn prices(data: &[BaseDataEnum]) -> Vec<Price> {
+        data.iter().map(|data| match data {
+            BaseDataEnum::Tick(tick) => tick.price,
+            _ => unreachable!(),
+        }).collect()
+    }
+
+    fn normal_prices() -> Vec<Price> {
+        (0..20).map(|i| dec!(100) + Decimal::from(i % 4) * dec!(0.25)).collect()
+    }
+
+    #[test]
+    fn test_filter_outliers() {
+        let filter = DataQualityFilter::new(OutlierAction::Filter, dec!(10), dec!(0.005), false);
+        let mut input = normal_prices();
+        input.extend([dec!(1000), dec!(0), dec!(100.5)]);
+        let cleaned = filter.clean_bulk(ticks(&input));
+        assert_eq!(prices(&cleaned).last(), Some(&dec!(100.5)));
+        assert_eq!(cleaned.len(), input.len() - 2);
+
+        let metrics = filter.metrics(Some(&"NQ".to_string()));
+        assert_eq!(metrics.len(), 1);
+        assert_eq!((metrics[0].received, metrics[0].invalid, metrics[0].outliers, metrics[0].removed), (23, 1, 1, 2));
+        assert!(filter.metrics(Some(&"ES".to_string())).is_empty());
+    }
+
+    #[test]
+    fn test_winsorize_and_level_shift() {
+        let filter = DataQualityFilter::new(OutlierAction::Winsorize, dec!(10), dec!(0.005), false);
+        let mut input = normal_prices();
+        input.push(dec!(1000));
+        let cleaned = prices(&filter.clean_bulk(ticks(&input)));
+        // median 100.375 plus 10 scaled median absolute deviations of 0.25
+        assert_eq!(cleaned.last(), Some(&dec!(104.0815)));
+
+        // consistent prices at a new level are accepted after the level shift count
+        let filter = DataQualityFilter::new(OutlierAction::Filter, dec!(10), dec!(0.005), false);
+        let mut input = normal_prices();
+        input.extend([dec!(110), dec!(110.25), dec!(110), dec!(110.5), dec!(110.25), dec!(110.5)]);
+        let cleaned = prices(&filter.clean_bulk(ticks(&input)));
+        assert_eq!(&cleaned[20..], &[dec!(110.25), dec!(110.5)]);
+        assert_eq!(filter.metrics(None)[0].level_shifts, 1);
+    }
+}
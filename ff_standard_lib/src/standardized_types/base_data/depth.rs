@@ -0,0 +1,180 @@
+use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::enums::MarketType;
+use crate::standardized_types::subscriptions::{DataSubscription, Symbol};
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
+use std::fmt;
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+use rust_decimal::Decimal;
+use crate::standardized_types::books::BookLevel;
+use crate::standardized_types::datavendor_enum::DataVendor;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::new_types::{Price, TimeString, Volume};
+use crate::standardized_types::resolution::Resolution;
+
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+/// A `Depth` is a snapshot of the order book by price level (MBP) for a given symbol, each update to the book is a new snapshot. \
+/// Level 0 is the best bid and best ask, the number of levels depends on the vendor, eg 10 for DataBento MBP-10.
+///
+/// Depth is primary data only, it is not consolidated, in backtests it fills the market price service book so market and limit fills walk the historical book.
+///
+/// # Parameters
+/// * `symbol: Symbol` - The symbol of the asset.
+/// * `bids: Vec<BookLevel>` - The bid levels, best first.
+/// * `asks: Vec<BookLevel>` - The ask levels, best first.
+/// * `time: String` - The time of the snapshot.
+pub struct Depth {
+    pub symbol: Symbol,
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+    pub time: TimeString,
+}
+
+impl BaseData for Depth {
+    fn symbol_name(&self) -> Symbol {
+        self.symbol.clone()
+    }
+
+    fn time_local(&self, time_zone: &Tz) -> DateTime<Tz> {
+        time_zone.from_utc_datetime(&self.time_utc().naive_utc())
+    }
+
+    fn time_utc(&self) -> DateTime<Utc> {
+        DateTime::from_str(&self.time).unwrap()
+    }
+
+    fn time_closed_utc(&self) -> DateTime<Utc> {
+        self.time_utc()
+    }
+
+    fn time_closed_local(&self, time_zone: &Tz) -> DateTime<Tz> {
+        time_zone.from_utc_datetime(&self.time_utc().naive_utc())
+    }
+
+    fn data_vendor(&self) -> DataVendor {
+        self.symbol.data_vendor.clone()
+    }
+
+    fn market_type(&self) -> MarketType {
+        self.symbol.market_type.clone()
+    }
+
+    fn resolution(&self) -> Resolution {
+        Resolution::Instant
+    }
+
+    fn symbol(&self) -> &Symbol {
+        &self.symbol
+    }
+
+    fn subscription(&self) -> DataSubscription {
+        let symbol = self.symbol.clone();
+        DataSubscription::from_base_data(
+            symbol.name.clone(),
+            symbol.data_vendor.clone(),
+            Resolution::Instant,
+            BaseDataType::Depth,
+            symbol.market_type.clone(),
+            None,
+        )
+    }
+}
+
+impl Depth {
+    /// Create a new `Depth` snapshot, the levels are best first.
+    ///
+    /// # Parameters
+    /// 1. `symbol` - The symbol of the asset.
+    /// 2. `bids` - The bid levels, best first.
+    /// 3. `asks` - The ask levels, best first.
+    /// 4. `time` - The time of the snapshot.
+    pub fn new(symbol: Symbol, bids: Vec<BookLevel>, asks: Vec<BookLevel>, time: TimeString) -> Self {
+        Depth {
+            symbol,
+            bids,
+            asks,
+            time,
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<Price> {
+        self.bids.first().map(|level| level.price)
+    }
+
+    pub fn best_ask(&self) -> Option<Price> {
+        self.asks.first().map(|level| level.price)
+    }
+
+    /// The resting bid volume and ask volume on the first `levels` levels.
+    pub fn volume(&self, levels: usize) -> (Volume, Volume) {
+        let bid_volume = self.bids.iter().take(levels).map(|level| level.volume).sum();
+        let ask_volume = self.asks.iter().take(levels).map(|level| level.volume).sum();
+        (bid_volume, ask_volume)
+    }
+
+    /// The book imbalance on the first `levels` levels, from -1 (only asks) to 1 (only bids), `None` if the levels are empty.
+    pub fn imbalance(&self, levels: usize) -> Option<Decimal> {
+        let (bid_volume, ask_volume) = self.volume(levels);
+        let total = bid_volume + ask_volume;
+        if total == Decimal::ZERO {
+            return None;
+        }
+        Some((bid_volume - ask_volume) / total)
+    }
+
+    /// The volume resting ahead of an order joining the back of the queue at `price`, zero if the price is not in the book.
+    pub fn queue_ahead(&self, bid: bool, price: Price) -> Volume {
+        let levels = match bid {
+            true => &self.bids,
+            false => &self.asks,
+        };
+        levels.iter()
+            .find(|level| level.price == price)
+            .map(|level| level.volume)
+            .unwrap_or(Decimal::ZERO)
+    }
+}
+
+impl Debug for Depth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Depth {{ symbol: {:?}, bids: {:?}, asks: {:?}, time: {} }}", self.symbol, self.bids, self.asks, self.time)
+    }
+}
+
+impl Display for Depth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}, bid: {:?}, ask: {:?}, levels: {}/{}, {}", self.symbol.name, self.symbol.data_vendor, self.best_bid(), self.best_ask(), self.bids.len(), self.asks.len(), self.time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::enums::FuturesExchange;
+
+    fn depth() -> Depth {
+        let symbol = Symbol::new("MNQ".to_string(), DataVendor::DataBento, MarketType::Futures(FuturesExchange::CME));
+        Depth::new(
+            symbol,
+            vec![BookLevel::new(0, dec!(100.25), dec!(30)), BookLevel::new(1, dec!(100.00), dec!(20))],
+            vec![BookLevel::new(0, dec!(100.50), dec!(10)), BookLevel::new(1, dec!(100.75), dec!(40))],
+            Utc::now().to_string(),
+        )
+    }
+
+    #[test]
+    fn test_imbalance() {
+        let depth = depth();
+        assert_eq!(depth.best_bid(), Some(dec!(100.25)));
+        assert_eq!(depth.best_ask(), Some(dec!(100.50)));
+        assert_eq!(depth.imbalance(1), Some(dec!(0.5)));
+        assert_eq!(depth.imbalance(2), Some(dec!(0)));
+        assert_eq!(depth.queue_ahead(true, dec!(100.00)), dec!(20));
+        assert_eq!(depth.queue_ahead(false, dec!(101.00)), dec!(0));
+    }
+}
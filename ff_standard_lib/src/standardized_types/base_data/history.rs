@@ -19,6 +19,7 @@ use crate::standardized_types::enums::{StrategyMode, PrimarySubscription};
 use crate::standardized_types::market_hours::TradingHours;
 use crate::strategies::client_features::request_handler::{send_request, StrategyRequest};
 use crate::strategies::client_features::server_connections::SETTINGS_MAP;
+use crate::strategies::client_features::vendor_fallback::{history_subscriptions, relabel};
 use crate::strategies::consolidators::consolidator_enum::ConsolidatorEnum;
 
 
@@ -56,8 +57,7 @@ async fn process_compressed_payload(
         }
     }
 
-    BaseDataEnum::from_array_bytes(&decompressed)
-        .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to parse data: {}", e)))
+    BaseDataEnum::from_storage_bytes(&decompressed)
 }
 
 async fn process_payload(
@@ -88,10 +88,52 @@ async fn process_payload(
     Ok(combined_data)
 }
 
+/// Subscriptions with vendor fallbacks are served by the first vendor in their list with data for the range, relabelled with the preferred vendor.
 pub async fn get_compressed_historical_data(
     subscriptions: Vec<DataSubscription>,
     from_time: DateTime<Utc>,
     to_time: DateTime<Utc>,
+) -> Result<BTreeMap<i64, TimeSlice>, FundForgeError> {
+    let (fallback_subscriptions, subscriptions): (Vec<DataSubscription>, Vec<DataSubscription>) = subscriptions
+        .into_iter()
+        .partition(|subscription| history_subscriptions(subscription).is_some());
+    if fallback_subscriptions.is_empty() {
+        return request_compressed_historical_data(subscriptions, from_time, to_time).await;
+    }
+
+    let mut combined_data = match subscriptions.is_empty() {
+        true => BTreeMap::new(),
+        false => request_compressed_historical_data(subscriptions, from_time, to_time).await?,
+    };
+    for subscription in fallback_subscriptions {
+        for vendor_subscription in history_subscriptions(&subscription).unwrap_or_default() {
+            let data = match request_compressed_historical_data(vec![vendor_subscription.clone()], from_time, to_time).await {
+                Ok(data) => data,
+                Err(e) => {
+                    eprintln!("History: {} unavailable from {}: {}", subscription, vendor_subscription.symbol.data_vendor, e);
+                    continue;
+                }
+            };
+            if data.is_empty() {
+                continue;
+            }
+            for (timestamp, mut time_slice) in data {
+                relabel(&mut time_slice, false);
+                combined_data
+                    .entry(timestamp)
+                    .and_modify(|existing: &mut TimeSlice| existing.merge(time_slice.clone()))
+                    .or_insert(time_slice);
+            }
+            break;
+        }
+    }
+    Ok(combined_data)
+}
+
+async fn request_compressed_historical_data(
+    subscriptions: Vec<DataSubscription>,
+    from_time: DateTime<Utc>,
+    to_time: DateTime<Utc>,
 ) -> Result<BTreeMap<i64, TimeSlice>, FundForgeError> {
     let connections = SETTINGS_MAP.clone();
     if connections.len() <= 2 {
@@ -182,6 +224,67 @@ pub async fn get_compressed_historical_data(
     }
 }
 
+/// Requests the data the data servers recorded from their live streams after `from_time`, keyed by the time closed in nanos like the historical data.
+/// The servers only keep the recent data of the subscriptions they record with `--record_live 1`.
+pub async fn get_recent_live_data(
+    subscriptions: Vec<DataSubscription>,
+    from_time: DateTime<Utc>,
+) -> Result<BTreeMap<i64, TimeSlice>, FundForgeError> {
+    let connections = SETTINGS_MAP.clone();
+    let mut requests_map: AHashMap<ConnectionType, Vec<DataSubscription>> = AHashMap::new();
+    for sub in subscriptions {
+        let vendor_connection = ConnectionType::Vendor(sub.symbol.data_vendor.clone());
+        let entry = if connections.contains_key(&vendor_connection) {
+            vendor_connection
+        } else {
+            ConnectionType::Default
+        };
+        requests_map.entry(entry).or_default().push(sub);
+    }
+
+    let futures: Vec<_> = requests_map
+        .into_iter()
+        .map(|(connection_type, subs)| {
+            let (tx, rx) = oneshot::channel();
+            let request = StrategyRequest::CallBack(
+                connection_type,
+                DataServerRequest::RecentLiveData {
+                    callback_id: 0,
+                    subscriptions: subs,
+                    from_time: from_time.to_string(),
+                },
+                tx
+            );
+
+            async move {
+                send_request(request).await;
+                match rx.await {
+                    Ok(DataServerResponse::RecentLiveData { payload, .. }) => BaseDataEnum::from_array_bytes(&payload)
+                        .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to parse recent live data: {}", e))),
+                    Ok(DataServerResponse::Error { error, .. }) => Err(error),
+                    Ok(_) => Err(FundForgeError::UnknownBlameError("Incorrect response received at callback".to_string())),
+                    Err(e) => Err(FundForgeError::ClientSideErrorDebug(format!("Receiver error at callback recv: {}", e))),
+                }
+            }
+        })
+        .collect();
+
+    let mut combined_data: BTreeMap<i64, TimeSlice> = BTreeMap::new();
+    for result in join_all(futures).await {
+        for data in result? {
+            let timestamp = data.time_closed_utc().timestamp_nanos_opt()
+                .ok_or_else(|| FundForgeError::ClientSideErrorDebug(
+                    "Failed to convert timestamp to nanos".to_string()
+                ))?;
+            combined_data
+                .entry(timestamp)
+                .or_insert_with(TimeSlice::new)
+                .add(data);
+        }
+    }
+    Ok(combined_data)
+}
+
 pub fn get_lowest_resolution(
     all_symbol_subscriptions: &HashMap<Symbol, Vec<DataSubscription>>,
     symbol: &Symbol,
@@ -1,9 +1,12 @@
 pub mod base_data_type;
 pub mod base_data_enum;
 pub mod candle;
+pub mod data_quality;
+pub mod depth;
 pub mod fundamental;
 pub mod history;
 pub mod quotebar;
 pub mod quote;
+pub mod storage_migrations;
 pub mod tick;
 pub mod traits;
\ No newline at end of file
@@ -0,0 +1,187 @@
+use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::candle::Candle;
+use crate::standardized_types::base_data::depth::Depth;
+use crate::standardized_types::base_data::fundamental::Fundamental;
+use crate::standardized_types::base_data::quote::Quote;
+use crate::standardized_types::base_data::quotebar::QuoteBar;
+use crate::standardized_types::base_data::tick::Tick;
+use crate::standardized_types::new_types::{Price, TimeString, Volume};
+use crate::standardized_types::resolution::Resolution;
+use crate::standardized_types::subscriptions::{CandleType, Symbol};
+
+/// `CandleType` at schema version 2, before the renko candle types. The copies of the old layouts must never be edited.
+#[derive(Serialize_rkyv, Deserialize_rkyv, Archive)]
+#[archive(check_bytes)]
+enum CandleTypeV2 {
+    HeikinAshi,
+    CandleStick,
+}
+
+impl From<CandleTypeV2> for CandleType {
+    fn from(candle_type: CandleTypeV2) -> Self {
+        match candle_type {
+            CandleTypeV2::HeikinAshi => CandleType::HeikinAshi,
+            CandleTypeV2::CandleStick => CandleType::CandleStick,
+        }
+    }
+}
+
+#[derive(Serialize_rkyv, Deserialize_rkyv, Archive)]
+#[archive(check_bytes)]
+struct CandleV2 {
+    symbol: Symbol,
+    high: Price,
+    low: Price,
+    open: Price,
+    close: Price,
+    volume: Volume,
+    ask_volume: Volume,
+    bid_volume: Volume,
+    range: Price,
+    time: TimeString,
+    is_closed: bool,
+    resolution: Resolution,
+    candle_type: CandleTypeV2,
+}
+
+#[derive(Serialize_rkyv, Deserialize_rkyv, Archive)]
+#[archive(check_bytes)]
+struct QuoteBarV2 {
+    symbol: Symbol,
+    bid_high: Price,
+    bid_low: Price,
+    bid_open: Price,
+    bid_close: Price,
+    ask_high: Price,
+    ask_low: Price,
+    ask_open: Price,
+    ask_close: Price,
+    volume: Volume,
+    ask_volume: Volume,
+    bid_volume: Volume,
+    range: Price,
+    time: TimeString,
+    spread: Price,
+    is_closed: bool,
+    resolution: Resolution,
+    candle_type: CandleTypeV2,
+}
+
+#[derive(Serialize_rkyv, Deserialize_rkyv, Archive)]
+#[archive(check_bytes)]
+enum BaseDataEnumV2 {
+    Candle(CandleV2),
+    QuoteBar(QuoteBarV2),
+    Tick(Tick),
+    Quote(Quote),
+    Fundamental(Fundamental),
+    Depth(Depth),
+}
+
+impl From<BaseDataEnumV2> for BaseDataEnum {
+    fn from(data: BaseDataEnumV2) -> Self {
+        match data {
+            BaseDataEnumV2::Candle(candle) => BaseDataEnum::Candle(Candle {
+                symbol: candle.symbol,
+                high: candle.high,
+                low: candle.low,
+                open: candle.open,
+                close: candle.close,
+                volume: candle.volume,
+                ask_volume: candle.ask_volume,
+                bid_volume: candle.bid_volume,
+                range: candle.range,
+                time: candle.time,
+                is_closed: candle.is_closed,
+                resolution: candle.resolution,
+                candle_type: candle.candle_type.into(),
+            }),
+            BaseDataEnumV2::QuoteBar(bar) => BaseDataEnum::QuoteBar(QuoteBar {
+                symbol: bar.symbol,
+                bid_high: bar.bid_high,
+                bid_low: bar.bid_low,
+                bid_open: bar.bid_open,
+                bid_close: bar.bid_close,
+                ask_high: bar.ask_high,
+                ask_low: bar.ask_low,
+                ask_open: bar.ask_open,
+                ask_close: bar.ask_close,
+                volume: bar.volume,
+                ask_volume: bar.ask_volume,
+                bid_volume: bar.bid_volume,
+                range: bar.range,
+                time: bar.time,
+                spread: bar.spread,
+                is_closed: bar.is_closed,
+                resolution: bar.resolution,
+                candle_type: bar.candle_type.into(),
+            }),
+            BaseDataEnumV2::Tick(tick) => BaseDataEnum::Tick(tick),
+            BaseDataEnumV2::Quote(quote) => BaseDataEnum::Quote(quote),
+            BaseDataEnumV2::Fundamental(fundamental) => BaseDataEnum::Fundamental(fundamental),
+            BaseDataEnumV2::Depth(depth) => BaseDataEnum::Depth(depth),
+        }
+    }
+}
+
+/// Version 3 added the renko candle types, which carry their parameters, so `CandleType` is no longer a single byte.
+pub(crate) fn renko_candle_types_migration(archive: Vec<u8>) -> Result<Vec<u8>, FundForgeError> {
+    let archived = rkyv::check_archived_root::<Vec<BaseDataEnumV2>>(&archive[..])
+        .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to read stored data of schema version 2: {}", e)))?;
+    let data: Vec<BaseDataEnumV2> = archived.deserialize(&mut rkyv::Infallible).unwrap();
+    let data: Vec<BaseDataEnum> = data.into_iter().map(BaseDataEnum::from).collect();
+    Ok(BaseDataEnum::vec_to_bytes(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::base_data::tick::Aggressor;
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::MarketType;
+    use rkyv::ser::serializers::AllocSerializer;
+    use rkyv::ser::Serializer;
+
+    fn serialize_v2(data: &Vec<BaseDataEnumV2>) -> Vec<u8> {
+        let mut serializer = AllocSerializer::<1024>::default();
+        serializer.serialize_value(data).unwrap();
+        serializer.into_serializer().into_inner().to_vec()
+    }
+
+    #[test]
+    fn test_renko_candle_types_migration() {
+        let symbol = Symbol::new("EUR-USD".to_string(), DataVendor::Oanda, MarketType::Forex);
+        let stored = vec![
+            BaseDataEnumV2::Candle(CandleV2 {
+                symbol: symbol.clone(),
+                high: dec!(1.2),
+                low: dec!(1.0),
+                open: dec!(1.1),
+                close: dec!(1.15),
+                volume: dec!(10),
+                ask_volume: dec!(4),
+                bid_volume: dec!(6),
+                range: dec!(0.2),
+                time: "2024-06-03 00:00:00 UTC".to_string(),
+                is_closed: true,
+                resolution: Resolution::Minutes(1),
+                candle_type: CandleTypeV2::HeikinAshi,
+            }),
+            BaseDataEnumV2::Tick(Tick::new(symbol.clone(), dec!(1.1), "2024-06-03 00:01:00 UTC".to_string(), dec!(1), Aggressor::Buy)),
+        ];
+        let migrated = BaseDataEnum::from_array_bytes(&renko_candle_types_migration(serialize_v2(&stored)).unwrap()).unwrap();
+        assert_eq!(migrated.len(), 2);
+        match &migrated[0] {
+            BaseDataEnum::Candle(candle) => {
+                assert_eq!(candle.candle_type, CandleType::HeikinAshi);
+                assert_eq!((candle.open, candle.close, candle.volume), (dec!(1.1), dec!(1.15), dec!(10)));
+            }
+            other => panic!("expected a candle, got {:?}", other),
+        }
+        assert!(matches!(&migrated[1], BaseDataEnum::Tick(tick) if tick.price == dec!(1.1)));
+        assert!(renko_candle_types_migration(vec![1, 2, 3]).is_err());
+    }
+}
@@ -4,9 +4,11 @@ use crate::standardized_types::subscriptions::{DataSubscription, Symbol};
 use chrono::{DateTime, TimeZone, Utc};
 use chrono_tz::Tz;
 use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
+use std::cmp::Ordering;
 use std::fmt;
 use std::fmt::Debug;
 use std::str::FromStr;
+use rust_decimal_macros::dec;
 use strum_macros::Display;
 use crate::standardized_types::datavendor_enum::DataVendor;
 use crate::standardized_types::base_data::traits::BaseData;
@@ -34,12 +36,32 @@ pub struct Tick {
 #[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Display, Debug)]
 #[archive(compare(PartialEq), check_bytes)]
 #[archive_attr(derive(Debug))]
+/// The side which crossed the spread to trade, `None` when the vendor does not provide it and it could not be inferred.
 pub enum Aggressor {
     Buy,
     Sell,
     None
 }
 
+impl Aggressor {
+    /// Infers the aggressor by comparing the trade price with the prevailing quote.
+    /// Trades at or above the ask are buys, at or below the bid are sells, inside the spread the side of the mid price is used and trades at the mid are `None`.
+    pub fn from_quote(price: Price, bid: Price, ask: Price) -> Aggressor {
+        if price >= ask {
+            return Aggressor::Buy;
+        }
+        if price <= bid {
+            return Aggressor::Sell;
+        }
+        let mid = (bid + ask) / dec!(2);
+        match price.cmp(&mid) {
+            Ordering::Greater => Aggressor::Buy,
+            Ordering::Less => Aggressor::Sell,
+            Ordering::Equal => Aggressor::None,
+        }
+    }
+}
+
 impl BaseData for Tick {
     fn symbol_name(&self) -> Symbol {
         self.symbol.clone()
@@ -87,7 +109,7 @@ impl BaseData for Tick {
             symbol.name.clone(),
             symbol.data_vendor.clone(),
             resolution,
-            BaseDataType::Candles,
+            BaseDataType::Ticks,
             symbol.market_type.clone(),
             candle_type,
         )
@@ -102,8 +124,7 @@ impl Tick {
     /// 2. `price` - The price of the asset.
     /// 3. `time` - The time the price was recorded.
     /// 4. `volume` - The volume of the trade.
-    /// 5. `side` - The side of the trade `Side` enum variant.
-    /// 6. `data_vendor` - The data vendor of the trade.
+    /// 5. `aggressor` - The side which crossed the spread, `Aggressor::None` if unknown.
     pub fn new(symbol: Symbol, price: Price, time: TimeString, volume: Volume, aggressor: Aggressor) -> Self {
         Tick {
             symbol,
@@ -113,6 +134,13 @@ impl Tick {
             aggressor,
         }
     }
+
+    /// Sets the aggressor from the prevailing quote if the vendor did not provide it.
+    pub fn classify_aggressor(&mut self, bid: Price, ask: Price) {
+        if self.aggressor == Aggressor::None && bid < ask {
+            self.aggressor = Aggressor::from_quote(self.price, bid, ask);
+        }
+    }
 }
 
 impl fmt::Display for Tick {
@@ -134,3 +162,29 @@ impl fmt::Debug for Tick {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggressor_from_quote() {
+        assert_eq!(Aggressor::from_quote(dec!(100.50), dec!(100.25), dec!(100.50)), Aggressor::Buy);
+        assert_eq!(Aggressor::from_quote(dec!(100.25), dec!(100.25), dec!(100.50)), Aggressor::Sell);
+        assert_eq!(Aggressor::from_quote(dec!(100.45), dec!(100.25), dec!(100.50)), Aggressor::Buy);
+        assert_eq!(Aggressor::from_quote(dec!(100.30), dec!(100.25), dec!(100.50)), Aggressor::Sell);
+        assert_eq!(Aggressor::from_quote(dec!(100.375), dec!(100.25), dec!(100.50)), Aggressor::None);
+    }
+
+    #[test]
+    fn test_classify_keeps_vendor_aggressor() {
+        let symbol = Symbol::new("BTC-USD".to_string(), DataVendor::DataBento, MarketType::Crypto);
+        let mut tick = Tick::new(symbol.clone(), dec!(100.25), Utc::now().to_string(), dec!(1), Aggressor::Buy);
+        tick.classify_aggressor(dec!(100.25), dec!(100.50));
+        assert_eq!(tick.aggressor, Aggressor::Buy);
+
+        let mut tick = Tick::new(symbol, dec!(100.25), Utc::now().to_string(), dec!(1), Aggressor::None);
+        tick.classify_aggressor(dec!(100.25), dec!(100.50));
+        assert_eq!(tick.aggressor, Aggressor::Sell);
+    }
+}
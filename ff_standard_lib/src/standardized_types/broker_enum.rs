@@ -15,7 +15,8 @@ pub enum Brokerage {
     Test, //DO NOT CHANGE ORDER
     Rithmic(RithmicSystem),
     Bitget,
-    Oanda
+    Oanda,
+    Alpaca
 }
 
 impl Brokerage {
@@ -25,6 +26,7 @@ impl Brokerage {
             Brokerage::Rithmic(_) => America::Chicago,
             Brokerage::Bitget => UTC,
             Brokerage::Oanda => UTC,
+            Brokerage::Alpaca => America::New_York,
         }
     }
 }
@@ -36,6 +38,7 @@ impl fmt::Display for Brokerage {
             Brokerage::Rithmic(system) => format!("Rithmic {}", system.to_string()),
             Brokerage::Bitget => "Bitget".to_string(),
             Brokerage::Oanda => "Oanda".to_string(),
+            Brokerage::Alpaca => "Alpaca".to_string(),
         };
         write!(f, "{}", s)
     }
@@ -62,7 +65,9 @@ impl FromStr for Brokerage {
 
         } else if "Oanda" == s {
             Ok(Brokerage::Oanda)
-        }else {
+        } else if "Alpaca" == s {
+            Ok(Brokerage::Alpaca)
+        } else {
             Err(FundForgeError::ClientSideErrorDebug(format!(
                 "Invalid brokerage string: {}",
                 s
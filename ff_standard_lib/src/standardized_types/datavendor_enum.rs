@@ -16,6 +16,8 @@ pub enum DataVendor {
     Rithmic,
     Bitget,
     Oanda,
+    Alpaca,
+    Polygon,
 }
 
 impl fmt::Display for DataVendor {
@@ -25,6 +27,8 @@ impl fmt::Display for DataVendor {
             DataVendor::Rithmic => "Rithmic".to_string(),
             DataVendor::Bitget => "Bitget".to_string(),
             DataVendor::Oanda => "Oanda".to_string(),
+            DataVendor::Alpaca => "Alpaca".to_string(),
+            DataVendor::Polygon => "Polygon".to_string(),
         };
         write!(f, "{}", s)
     }
@@ -42,6 +46,10 @@ impl FromStr for DataVendor {
             Ok(DataVendor::Bitget)
         }  else if s == "Oanda" {
             Ok(DataVendor::Oanda)
+        } else if s == "Alpaca" {
+            Ok(DataVendor::Alpaca)
+        } else if s == "Polygon" {
+            Ok(DataVendor::Polygon)
         }
         else {
             Err(FundForgeError::ClientSideErrorDebug(format!(
@@ -0,0 +1,152 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
+use serde_derive::{Deserialize, Serialize};
+use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::datavendor_enum::DataVendor;
+use crate::standardized_types::resolution::Resolution;
+use crate::standardized_types::subscriptions::SymbolName;
+
+/// The priority of a download job, higher priority jobs start first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Serialize_rkyv, Deserialize_rkyv, Archive)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum DownloadPriority {
+    Low,
+    Normal,
+    High,
+}
+
+impl FromStr for DownloadPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(DownloadPriority::Low),
+            "normal" => Ok(DownloadPriority::Normal),
+            "high" => Ok(DownloadPriority::High),
+            _ => Err(format!("Unknown download priority: {}, expected low, normal or high", s)),
+        }
+    }
+}
+
+/// The state of a download job.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Serialize_rkyv, Deserialize_rkyv, Archive)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum DownloadJobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed { reason: String },
+    Cancelled,
+}
+
+impl DownloadJobStatus {
+    /// True once the job has completed, failed or been cancelled.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, DownloadJobStatus::Completed | DownloadJobStatus::Failed { .. } | DownloadJobStatus::Cancelled)
+    }
+}
+
+impl Display for DownloadJobStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DownloadJobStatus::Queued => write!(f, "Queued"),
+            DownloadJobStatus::Running => write!(f, "Running"),
+            DownloadJobStatus::Completed => write!(f, "Completed"),
+            DownloadJobStatus::Failed { reason } => write!(f, "Failed: {}", reason),
+            DownloadJobStatus::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+/// The history to download, see `enqueue_download()`.
+/// # Properties
+/// * `symbol_name` - The symbol to download.
+/// * `data_vendor` - The vendor to download from.
+/// * `base_data_type`, `resolution` - The data to download, eg `BaseDataType::Candles` and `Resolution::Minutes(1)`.
+/// * `from` - The UTC start of the download.
+/// * `to` - The UTC end of the download, None to download up to the time the job starts.
+/// * `priority` - The priority of the job.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Serialize_rkyv, Deserialize_rkyv, Archive)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct DownloadJobRequest {
+    pub symbol_name: SymbolName,
+    pub data_vendor: DataVendor,
+    pub base_data_type: BaseDataType,
+    pub resolution: Resolution,
+    pub from: String,
+    pub to: Option<String>,
+    pub priority: DownloadPriority,
+}
+
+impl DownloadJobRequest {
+    /// A normal priority download from `from` up to the time the job starts.
+    /// ```rust
+    /// let request = DownloadJobRequest::new("MNQ".to_string(), DataVendor::Rithmic, BaseDataType::Candles, Resolution::Minutes(1), Utc::now() - Duration::days(30))
+    ///     .with_priority(DownloadPriority::High);
+    /// let job = enqueue_download(request).await?;
+    /// ```
+    pub fn new(symbol_name: SymbolName, data_vendor: DataVendor, base_data_type: BaseDataType, resolution: Resolution, from: DateTime<Utc>) -> Self {
+        DownloadJobRequest {
+            symbol_name,
+            data_vendor,
+            base_data_type,
+            resolution,
+            from: from.to_string(),
+            to: None,
+            priority: DownloadPriority::Normal,
+        }
+    }
+
+    pub fn with_to(mut self, to: DateTime<Utc>) -> Self {
+        self.to = Some(to.to_string());
+        self
+    }
+
+    pub fn with_priority(mut self, priority: DownloadPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// A download job on the data server.
+/// # Properties
+/// * `id` - The id of the job, unique while the server runs.
+/// * `request` - The history to download.
+/// * `status` - The state of the job.
+/// * `queued_at` - The time the job was queued.
+/// * `started_at`, `finished_at` - The times the download started and finished, None until then.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Serialize_rkyv, Deserialize_rkyv, Archive)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct DownloadJob {
+    pub id: u64,
+    pub request: DownloadJobRequest,
+    pub status: DownloadJobStatus,
+    pub queued_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+impl Display for DownloadJob {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Job {}: {} {} {} {} from {} to {}, {:?} priority, {}",
+            self.id,
+            self.request.symbol_name,
+            self.request.data_vendor,
+            self.request.resolution,
+            self.request.base_data_type,
+            self.request.from,
+            self.request.to.as_deref().unwrap_or("now"),
+            self.request.priority,
+            self.status
+        )
+    }
+}
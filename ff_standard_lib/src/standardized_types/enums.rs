@@ -2,6 +2,7 @@ use crate::standardized_types::base_data::base_data_type::BaseDataType;
 use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
+use std::str::FromStr;
 use rust_decimal::Decimal;
 use strum_macros::Display;
 use crate::helpers::decimal_calculators::round_to_tick_size;
@@ -40,6 +41,10 @@ impl FuturesExchange {
 #[archive_attr(derive(Debug))]
 pub enum Exchange {
     NASDAQ,
+    NYSE,
+    ARCA,
+    AMEX,
+    BATS,
     // Add other exchanges if necessary
 }
 
@@ -55,6 +60,10 @@ pub enum MarketType {
     Crypto,
     ETF,
     Fundamentals,
+    /// Options on futures or equities, see `OptionContract`.
+    Options,
+    /// Cash and volatility indices, eg SPX or VIX, these are reference feeds for filters and can not be traded.
+    Index,
 }
 
 impl MarketType {
@@ -67,8 +76,15 @@ impl MarketType {
             MarketType::Crypto => value.round_dp(decimal_accuracy),
             MarketType::ETF => value.round_dp(decimal_accuracy),
             MarketType::Fundamentals => value.round_dp(decimal_accuracy),
+            MarketType::Options => round_to_tick_size(value, tick_size),
+            MarketType::Index => value.round_dp(decimal_accuracy),
         }
     }
+
+    /// False for reference feeds which have no market to send orders to.
+    pub fn is_tradeable(&self) -> bool {
+        !matches!(self, MarketType::Index)
+    }
 }
 
 // Bias
@@ -91,6 +107,17 @@ pub enum OrderSide {
     Sell,
 }
 
+/// Whether a fill added liquidity to the book or took it, as reported by the brokerage.
+#[derive(Serialize, Deserialize, Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialOrd, Eq, Ord, PartialEq, Copy, Debug, Display, Hash)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum Liquidity {
+    /// The order was resting in the book.
+    Maker,
+    /// The order crossed the spread.
+    Taker,
+}
+
 #[derive(Serialize, Deserialize, Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialOrd, Eq, Ord, PartialEq, Copy, Debug, Display, Hash)]
 #[archive(compare(PartialEq), check_bytes)]
 #[archive_attr(derive(Debug))]
@@ -100,7 +127,18 @@ pub enum PositionSide {
     Flat
 }
 
-#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Copy)]
+/// The condition for a price trigger created with `strategy.when_price()`.
+#[derive(Serialize, Deserialize, Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialOrd, Eq, Ord, PartialEq, Copy, Debug, Display, Hash)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum PriceTriggerCondition {
+    /// Triggers when price trades or is bid at or above the level.
+    AtOrAbove,
+    /// Triggers when price trades or is offered at or below the level.
+    AtOrBelow
+}
+
+#[derive(Serialize, Deserialize, Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Copy)]
 #[archive(compare(PartialEq), check_bytes)]
 #[archive_attr(derive(Debug))]
 pub enum StrategyMode {
@@ -109,6 +147,19 @@ pub enum StrategyMode {
     LivePaperTrading,
 }
 
+impl FromStr for StrategyMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "backtest" => Ok(StrategyMode::Backtest),
+            "paper" | "livepaper" | "livepapertrading" => Ok(StrategyMode::LivePaperTrading),
+            "live" => Ok(StrategyMode::Live),
+            _ => Err(format!("Unknown strategy mode: {}, expected backtest, paper or live", s)),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialOrd, Eq, Ord, PartialEq, Copy, Debug, Hash)]
 #[archive(compare(PartialEq), check_bytes)]
 #[archive_attr(derive(Debug))]
@@ -0,0 +1,183 @@
+use chrono::Utc;
+use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
+use rust_decimal::Decimal;
+use serde_derive::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::standardized_types::accounts::Account;
+use crate::standardized_types::enums::OrderSide;
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::subscriptions::{SymbolCode, SymbolName};
+use crate::standardized_types::symbol_info::{price_offset_ticks, SymbolInfo};
+
+/// How the entry of an intent is sent when it triggers.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Serialize_rkyv, Deserialize_rkyv, Archive)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum IntentEntry {
+    /// A GTC limit order at the intent price.
+    Limit,
+    /// A market order, sent as soon as the price is within the trigger ticks.
+    Market,
+}
+
+/// The state of an intent on the server.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Serialize_rkyv, Deserialize_rkyv, Archive)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum IntentStatus {
+    /// Waiting for the price.
+    Resting,
+    /// Claimed by a strategy at the time, its entry order was sent.
+    Triggered { time: String },
+}
+
+/// A price level with an attached order template, see `strategy.save_intent()`.
+/// # Properties
+/// * `id` - The unique id of the intent, saving an intent with the same id replaces it.
+/// * `symbol_name` - The canonical name of the symbol.
+/// * `symbol_code` - The contract to trade for futures, None to trade the symbol name.
+/// * `account` - The account the orders are sent on.
+/// * `side` - The side of the entry.
+/// * `quantity` - The quantity of the entry.
+/// * `price` - The price level, the limit price of a limit entry.
+/// * `entry` - How the entry is sent.
+/// * `trigger_ticks` - The entry is sent when the price is within this many ticks of the level.
+/// * `take_profit_ticks` - A limit exit this many ticks from the entry fill, sent when the entry is filled.
+/// * `stop_loss_ticks` - A stop exit this many ticks from the entry fill, sent when the entry is filled.
+/// * `label` - A free text label, also used in the order tags.
+/// * `time` - The time the intent was created.
+/// * `status` - Whether the intent is resting or has triggered.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Serialize_rkyv, Deserialize_rkyv, Archive)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct Intent {
+    pub id: String,
+    pub symbol_name: SymbolName,
+    pub symbol_code: Option<SymbolCode>,
+    pub account: Account,
+    pub side: OrderSide,
+    pub quantity: Volume,
+    pub price: Price,
+    pub entry: IntentEntry,
+    pub trigger_ticks: u32,
+    pub take_profit_ticks: Option<u32>,
+    pub stop_loss_ticks: Option<u32>,
+    pub label: String,
+    pub time: String,
+    pub status: IntentStatus,
+}
+
+impl Intent {
+    /// A resting limit entry without a bracket.
+    /// ```rust
+    /// let intent = Intent::new("MNQ".to_string(), account.clone(), OrderSide::Buy, dec!(2), dec!(18180.25), 8, "Prior day low")
+    ///     .with_symbol_code("MNQZ4".to_string())
+    ///     .with_take_profit(80)
+    ///     .with_stop_loss(40);
+    /// strategy.save_intent(intent).await?;
+    /// ```
+    pub fn new(symbol_name: SymbolName, account: Account, side: OrderSide, quantity: Volume, price: Price, trigger_ticks: u32, label: &str) -> Self {
+        Intent {
+            id: Uuid::new_v4().to_string(),
+            symbol_name,
+            symbol_code: None,
+            account,
+            side,
+            quantity,
+            price,
+            entry: IntentEntry::Limit,
+            trigger_ticks,
+            take_profit_ticks: None,
+            stop_loss_ticks: None,
+            label: label.to_string(),
+            time: Utc::now().to_string(),
+            status: IntentStatus::Resting,
+        }
+    }
+
+    pub fn with_symbol_code(mut self, symbol_code: SymbolCode) -> Self {
+        self.symbol_code = Some(symbol_code);
+        self
+    }
+
+    /// Sends a market order instead of a limit order when the intent triggers.
+    pub fn with_market_entry(mut self) -> Self {
+        self.entry = IntentEntry::Market;
+        self
+    }
+
+    pub fn with_take_profit(mut self, ticks: u32) -> Self {
+        self.take_profit_ticks = Some(ticks);
+        self
+    }
+
+    pub fn with_stop_loss(mut self, ticks: u32) -> Self {
+        self.stop_loss_ticks = Some(ticks);
+        self
+    }
+
+    pub fn is_resting(&self) -> bool {
+        self.status == IntentStatus::Resting
+    }
+
+    /// True if the price is within the trigger ticks of the level, a buy uses the best ask and a sell the best bid.
+    pub fn is_in_range(&self, bid: Price, ask: Price, tick_size: Price) -> bool {
+        let distance = Decimal::from(self.trigger_ticks) * tick_size;
+        match self.side {
+            OrderSide::Buy => ask <= self.price + distance,
+            OrderSide::Sell => bid >= self.price - distance,
+        }
+    }
+
+    /// The take profit and stop loss prices of the bracket for an entry filled at the average price, rounded to the tick size first.
+    pub fn bracket_prices(&self, symbol_info: &SymbolInfo, fill_price: Price) -> (Option<Price>, Option<Price>) {
+        let direction = match self.side {
+            OrderSide::Buy => 1,
+            OrderSide::Sell => -1,
+        };
+        (
+            self.take_profit_ticks.map(|ticks| price_offset_ticks(symbol_info, fill_price, ticks as i64 * direction)),
+            self.stop_loss_ticks.map(|ticks| price_offset_ticks(symbol_info, fill_price, -(ticks as i64) * direction)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::accounts::Currency;
+    use crate::standardized_types::broker_enum::Brokerage;
+
+    fn account() -> Account {
+        Account::new(Brokerage::Test, "Test_Account_1".to_string())
+    }
+
+    #[test]
+    fn test_is_in_range() {
+        let buy = Intent::new("MNQ".to_string(), account(), OrderSide::Buy, dec!(1), dec!(18000), 4, "Support");
+        // 4 ticks of 0.25 over the level
+        assert!(!buy.is_in_range(dec!(18001), dec!(18001.25), dec!(0.25)));
+        assert!(buy.is_in_range(dec!(18000.75), dec!(18001), dec!(0.25)));
+        // gapped through the level
+        assert!(buy.is_in_range(dec!(17990), dec!(17990.25), dec!(0.25)));
+
+        let sell = Intent::new("MNQ".to_string(), account(), OrderSide::Sell, dec!(1), dec!(18100), 0, "Resistance");
+        assert!(!sell.is_in_range(dec!(18099.75), dec!(18100), dec!(0.25)));
+        assert!(sell.is_in_range(dec!(18100), dec!(18100.25), dec!(0.25)));
+    }
+
+    #[test]
+    fn test_bracket_prices() {
+        let mnq = SymbolInfo::new("MNQ".to_string(), None, Currency::USD, dec!(0.5), dec!(0.25), 2);
+        let buy = Intent::new("MNQ".to_string(), account(), OrderSide::Buy, dec!(1), dec!(18000), 4, "Support")
+            .with_take_profit(8)
+            .with_stop_loss(4);
+        assert_eq!(buy.bracket_prices(&mnq, dec!(18000.25)), (Some(dec!(18002.25)), Some(dec!(17999.25))));
+        // an average fill between ticks is rounded first
+        assert_eq!(buy.bracket_prices(&mnq, dec!(18000.30)), (Some(dec!(18002.25)), Some(dec!(17999.25))));
+
+        let sell = Intent::new("MNQ".to_string(), account(), OrderSide::Sell, dec!(1), dec!(18100), 4, "Resistance").with_stop_loss(12);
+        assert_eq!(sell.bracket_prices(&mnq, dec!(18100)), (None, Some(dec!(18103))));
+    }
+}
@@ -0,0 +1,118 @@
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
+use rust_decimal::Decimal;
+use serde_derive::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::subscriptions::SymbolName;
+
+/// The shape of a level.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Serialize_rkyv, Deserialize_rkyv, Archive)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum LevelKind {
+    /// A horizontal line at the price.
+    Horizontal { price: Price },
+    /// The prices between `low` and `high`.
+    Zone { low: Price, high: Price },
+    /// A line through two points, extended past them.
+    TrendLine { start_time: String, start_price: Price, end_time: String, end_price: Price },
+}
+
+/// A level of a symbol saved on the data server, see `strategy.levels()`.
+/// # Properties
+/// * `id` - The unique id of the level, saving a level with the same id replaces it.
+/// * `symbol_name` - The canonical name of the symbol.
+/// * `kind` - The shape and prices of the level.
+/// * `label` - A free text label, eg "Prior day high".
+/// * `time` - The time the level was created.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Serialize_rkyv, Deserialize_rkyv, Archive)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct Level {
+    pub id: String,
+    pub symbol_name: SymbolName,
+    pub kind: LevelKind,
+    pub label: String,
+    pub time: String,
+}
+
+impl Level {
+    pub fn new(symbol_name: SymbolName, kind: LevelKind, label: &str) -> Self {
+        Level {
+            id: Uuid::new_v4().to_string(),
+            symbol_name,
+            kind,
+            label: label.to_string(),
+            time: Utc::now().to_string(),
+        }
+    }
+
+    /// A horizontal line at the price.
+    /// ```rust
+    /// strategy.save_level(Level::horizontal("MNQ".to_string(), dec!(18250.25), "Prior day high")).await?;
+    /// ```
+    pub fn horizontal(symbol_name: SymbolName, price: Price, label: &str) -> Self {
+        Level::new(symbol_name, LevelKind::Horizontal { price }, label)
+    }
+
+    /// A price zone, the prices may be given in either order.
+    pub fn zone(symbol_name: SymbolName, low: Price, high: Price, label: &str) -> Self {
+        Level::new(symbol_name, LevelKind::Zone { low: low.min(high), high: low.max(high) }, label)
+    }
+
+    /// A trend line through two points.
+    pub fn trend_line(symbol_name: SymbolName, start: (DateTime<Utc>, Price), end: (DateTime<Utc>, Price), label: &str) -> Self {
+        let kind = LevelKind::TrendLine {
+            start_time: start.0.to_string(),
+            start_price: start.1,
+            end_time: end.0.to_string(),
+            end_price: end.1,
+        };
+        Level::new(symbol_name, kind, label)
+    }
+
+    /// The price of the level at the time, the price of a horizontal line or the point of a trend line.
+    /// None for a zone, or a trend line with an invalid time or both points at the same time.
+    pub fn price_at(&self, time: DateTime<Utc>) -> Option<Price> {
+        match &self.kind {
+            LevelKind::Horizontal { price } => Some(*price),
+            LevelKind::Zone { .. } => None,
+            LevelKind::TrendLine { start_time, start_price, end_time, end_price } => {
+                let start_time = DateTime::<Utc>::from_str(start_time).ok()?;
+                let end_time = DateTime::<Utc>::from_str(end_time).ok()?;
+                let span = (end_time - start_time).num_milliseconds();
+                if span == 0 {
+                    return None;
+                }
+                let elapsed = Decimal::from((time - start_time).num_milliseconds());
+                Some(start_price + (end_price - start_price) * elapsed / Decimal::from(span))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_price_at() {
+        let time = Utc.with_ymd_and_hms(2024, 6, 3, 9, 0, 0).unwrap();
+        let horizontal = Level::horizontal("MNQ".to_string(), dec!(18250.25), "Prior day high");
+        assert_eq!(horizontal.price_at(time), Some(dec!(18250.25)));
+
+        let zone = Level::zone("MNQ".to_string(), dec!(18300), dec!(18200), "Supply");
+        assert_eq!(zone.kind, LevelKind::Zone { low: dec!(18200), high: dec!(18300) });
+        assert_eq!(zone.price_at(time), None);
+
+        let line = Level::trend_line("MNQ".to_string(), (time, dec!(18000)), (time + Duration::hours(1), dec!(18100)), "Trend");
+        assert_eq!(line.price_at(time + Duration::minutes(30)), Some(dec!(18050)));
+        // extended past the points
+        assert_eq!(line.price_at(time + Duration::hours(2)), Some(dec!(18200)));
+        assert_ne!(line.id, horizontal.id);
+    }
+}
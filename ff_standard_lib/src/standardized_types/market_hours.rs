@@ -1,4 +1,4 @@
-use chrono::{DateTime, Datelike, NaiveTime, Timelike, Utc, Weekday};
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Timelike, Utc, Weekday};
 use chrono_tz::Tz;
 
 #[derive(Debug, Clone)]
@@ -90,7 +90,14 @@ impl TradingHours {
                     None
                 }
             }
-            (Some(_), None) => None, // No close time for open-ended session
+            (Some(open), None) => {
+                // Open-ended session, closes at the next close on a later day
+                if current_time_naive >= open {
+                    self.next_session_close(current_time).map(|close| (close - current_time).num_seconds())
+                } else {
+                    None
+                }
+            }
             (None, Some(close)) => {
                 // Close-only session (for edge cases, not typically expected in trading hours)
                 if current_time_naive < close {
@@ -102,6 +109,67 @@ impl TradingHours {
             (None, None) => None,
         }
     }
+
+    /// The seconds until the market next opens, None if the market is open or no day of the week has a session.
+    pub fn seconds_until_open(&self, current_time: DateTime<Utc>) -> Option<i64> {
+        if self.is_market_open(current_time) {
+            return None;
+        }
+        self.next_open(current_time).map(|open| (open - current_time).num_seconds())
+    }
+
+    /// The first time after `time` at which the market is open.
+    /// A closed market can only open at a session open or at midnight, so those times of the next 8 days are checked in order.
+    fn next_open(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local_date = time.with_timezone(&self.timezone).date_naive();
+        let mut candidates = vec![];
+        for day in 0..9 {
+            let date = local_date + Duration::days(day);
+            for local_time in [Some(NaiveTime::MIN), self.day_session(date.weekday()).open].into_iter().flatten() {
+                if let Some(candidate) = self.timezone.from_local_datetime(&date.and_time(local_time)).earliest() {
+                    let candidate = candidate.to_utc();
+                    if candidate > time {
+                        candidates.push(candidate);
+                    }
+                }
+            }
+        }
+        candidates.sort();
+        candidates.into_iter().find(|candidate| self.is_market_open(*candidate))
+    }
+
+    fn day_session(&self, weekday: Weekday) -> &DaySession {
+        match weekday {
+            Weekday::Sun => &self.sunday,
+            Weekday::Mon => &self.monday,
+            Weekday::Tue => &self.tuesday,
+            Weekday::Wed => &self.wednesday,
+            Weekday::Thu => &self.thursday,
+            Weekday::Fri => &self.friday,
+            Weekday::Sat => &self.saturday,
+        }
+    }
+
+    /// The first session close after `time`, the close of the current session if the market is open, otherwise the close of the next session.
+    /// None if no day of the week has a close.
+    pub fn next_session_close(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local_date = time.with_timezone(&self.timezone).date_naive();
+        for day in 0..8 {
+            let date = local_date + Duration::days(day);
+            let close = match self.day_session(date.weekday()).close {
+                Some(close) => close,
+                None => continue,
+            };
+            let close = match self.timezone.from_local_datetime(&date.and_time(close)).earliest() {
+                Some(close) => close.to_utc(),
+                None => continue,
+            };
+            if close > time {
+                return Some(close);
+            }
+        }
+        None
+    }
 }
 
 #[cfg(test)]
@@ -210,4 +278,103 @@ mod tests {
         assert!(!trading_hours.is_market_open(test_time));
         assert_eq!(trading_hours.seconds_until_close(test_time), None);
     }
-}
\ No newline at end of file
+    #[test]
+    fn test_next_session_close() {
+        let trading_hours = CME_HOURS;
+
+        // during the Tuesday session the order expires at the Tuesday close
+        let test_time = Chicago.with_ymd_and_hms(2024, 1, 9, 9, 0, 0).unwrap().to_utc();
+        assert_eq!(trading_hours.next_session_close(test_time), Some(Chicago.with_ymd_and_hms(2024, 1, 9, 16, 0, 0).unwrap().to_utc()));
+
+        // after the close the next session is Wednesday
+        let test_time = Chicago.with_ymd_and_hms(2024, 1, 9, 16, 30, 0).unwrap().to_utc();
+        assert_eq!(trading_hours.next_session_close(test_time), Some(Chicago.with_ymd_and_hms(2024, 1, 10, 16, 0, 0).unwrap().to_utc()));
+
+        // the Sunday open runs to the Monday close
+        let test_time = Chicago.with_ymd_and_hms(2024, 1, 7, 18, 0, 0).unwrap().to_utc();
+        assert_eq!(trading_hours.next_session_close(test_time), Some(Chicago.with_ymd_and_hms(2024, 1, 8, 16, 0, 0).unwrap().to_utc()));
+    }
+
+    #[test]
+    fn test_nyse_regular_session() {
+        use chrono_tz::America::New_York;
+        use crate::product_maps::alpaca::maps::NYSE_HOURS;
+        let trading_hours = NYSE_HOURS;
+
+        // Opens at 9:30 ET
+        let test_time = New_York.with_ymd_and_hms(2024, 1, 8, 9, 29, 0).unwrap().to_utc();
+        assert!(!trading_hours.is_market_open(test_time));
+        let test_time = New_York.with_ymd_and_hms(2024, 1, 8, 9, 30, 0).unwrap().to_utc();
+        assert!(trading_hours.is_market_open(test_time));
+        assert_eq!(trading_hours.seconds_until_close(test_time), Some(23400)); // 6.5 hours to close at 16:00
+
+        // Closed on the weekend
+        let test_time = New_York.with_ymd_and_hms(2024, 1, 6, 12, 0, 0).unwrap().to_utc();
+        assert!(!trading_hours.is_market_open(test_time));
+    }
+
+    #[test]
+    fn test_open_ended_session_close() {
+        let trading_hours = CME_HOURS;
+
+        // the Sunday session has no close on Sunday, it runs to the Monday close
+        let test_time = Chicago.with_ymd_and_hms(2024, 1, 7, 18, 0, 0).unwrap().to_utc();
+        assert!(trading_hours.is_market_open(test_time));
+        assert_eq!(trading_hours.seconds_until_close(test_time), Some(79200)); // 22 hours to Monday close at 16:00
+    }
+
+    #[test]
+    fn test_seconds_until_open() {
+        let trading_hours = CME_HOURS;
+
+        // open markets have no time until open
+        let test_time = Chicago.with_ymd_and_hms(2024, 1, 9, 9, 0, 0).unwrap().to_utc();
+        assert_eq!(trading_hours.seconds_until_open(test_time), None);
+
+        // daily break
+        let test_time = Chicago.with_ymd_and_hms(2024, 1, 9, 16, 30, 0).unwrap().to_utc();
+        assert_eq!(trading_hours.seconds_until_open(test_time), Some(1800));
+
+        // weekend to the Sunday open
+        let test_time = Chicago.with_ymd_and_hms(2024, 1, 6, 12, 0, 0).unwrap().to_utc();
+        assert_eq!(trading_hours.seconds_until_open(test_time), Some(104400)); // 29 hours
+    }
+
+    #[test]
+    fn test_eurex_hours() {
+        use chrono_tz::Europe::Berlin;
+        use crate::product_maps::rithmic::maps::EUREX_HOURS;
+        let trading_hours = EUREX_HOURS;
+
+        let test_time = Berlin.with_ymd_and_hms(2024, 1, 7, 12, 0, 0).unwrap().to_utc();
+        assert!(!trading_hours.is_market_open(test_time));
+
+        let test_time = Berlin.with_ymd_and_hms(2024, 1, 12, 21, 0, 0).unwrap().to_utc();
+        assert_eq!(trading_hours.seconds_until_close(test_time), Some(3600));
+
+        // Friday after the close to the Monday open at 08:00
+        let test_time = Berlin.with_ymd_and_hms(2024, 1, 12, 22, 30, 0).unwrap().to_utc();
+        assert_eq!(trading_hours.seconds_until_open(test_time), Some(207000));
+    }
+
+    #[test]
+    fn test_forex_hours() {
+        use chrono_tz::America::New_York;
+        use crate::product_maps::oanda::maps::FOREX_HOURS;
+        let trading_hours = FOREX_HOURS;
+
+        // open through the week, the daily session closes at 17:00
+        let test_time = New_York.with_ymd_and_hms(2024, 1, 10, 3, 0, 0).unwrap().to_utc();
+        assert!(trading_hours.is_market_open(test_time));
+        assert_eq!(trading_hours.seconds_until_close(test_time), Some(50400));
+        let test_time = New_York.with_ymd_and_hms(2024, 1, 10, 17, 0, 0).unwrap().to_utc();
+        assert!(trading_hours.is_market_open(test_time));
+
+        // closed from 17:00 Friday to 17:00 Sunday
+        let test_time = New_York.with_ymd_and_hms(2024, 1, 12, 17, 0, 0).unwrap().to_utc();
+        assert!(!trading_hours.is_market_open(test_time));
+        assert_eq!(trading_hours.seconds_until_open(test_time), Some(172800));
+        let test_time = New_York.with_ymd_and_hms(2024, 1, 7, 18, 0, 0).unwrap().to_utc();
+        assert_eq!(trading_hours.seconds_until_close(test_time), Some(82800)); // to the Monday 17:00 roll
+    }
+}
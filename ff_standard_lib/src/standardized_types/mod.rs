@@ -6,11 +6,18 @@ pub mod enums;
 pub mod new_types;
 pub mod orders;
 pub mod resolution;
+pub mod bar_time;
 pub mod rolling_window;
 pub mod subscriptions;
 pub mod symbol_info;
+pub mod symbol_aliases;
+pub mod levels;
+pub mod intents;
+pub mod download_jobs;
 pub mod time_slices;
 pub mod position;
 pub mod books;
 pub mod accounts;
 pub mod market_hours;
+pub mod options;
+pub mod versioning;
@@ -0,0 +1,264 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use chrono::{DateTime, NaiveDate, Utc};
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use crate::standardized_types::accounts::Currency;
+use crate::standardized_types::enums::{OrderSide, PositionSide};
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::subscriptions::SymbolName;
+use crate::standardized_types::symbol_info::SymbolInfo;
+
+lazy_static! {
+    /// Option contracts registered with `register_option_contract()`, the ledgers use these to find the symbol info for option positions.
+    pub(crate) static ref OPTION_CONTRACTS: DashMap<SymbolName, OptionContract> = DashMap::new();
+}
+
+/// Registers the contract so the ledgers can calculate pnl for positions in the option.
+pub fn register_option_contract(contract: OptionContract) {
+    OPTION_CONTRACTS.insert(contract.symbol_name(), contract);
+}
+
+#[derive(Serialize, Deserialize, Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialOrd, Eq, Ord, PartialEq, Copy, Debug, Hash)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum OptionRight {
+    Call,
+    Put
+}
+
+impl Display for OptionRight {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptionRight::Call => write!(f, "C"),
+            OptionRight::Put => write!(f, "P"),
+        }
+    }
+}
+
+/// An option contract, the symbol name is built in the OCC style `{underlying}{yymmdd}{C|P}{strike * 1000 padded to 8}`, eg `ES241220C05900000`.
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Serialize, Deserialize)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct OptionContract {
+    pub underlying: SymbolName,
+    /// The expiry date as `%Y-%m-%d`.
+    pub expiry: String,
+    pub strike: Price,
+    pub right: OptionRight,
+    /// The number of units of the underlying per contract, eg 50 for ES options or 100 for equity options.
+    pub multiplier: Decimal,
+    pub tick_size: Price,
+    pub pnl_currency: Currency,
+}
+
+impl OptionContract {
+    pub fn new(underlying: SymbolName, expiry: NaiveDate, strike: Price, right: OptionRight, multiplier: Decimal, tick_size: Price, pnl_currency: Currency) -> Self {
+        OptionContract {
+            underlying,
+            expiry: expiry.format("%Y-%m-%d").to_string(),
+            strike,
+            right,
+            multiplier,
+            tick_size,
+            pnl_currency,
+        }
+    }
+
+    pub fn expiry_date(&self) -> Option<NaiveDate> {
+        NaiveDate::from_str(&self.expiry).ok()
+    }
+
+    /// The OCC style symbol name, eg `ES241220C05900000`.
+    pub fn symbol_name(&self) -> SymbolName {
+        let expiry = match self.expiry_date() {
+            Some(date) => date.format("%y%m%d").to_string(),
+            None => self.expiry.replace('-', ""),
+        };
+        let strike = (self.strike * dec!(1000)).round();
+        format!("{}{}{}{:0>8}", self.underlying, expiry, self.right, strike)
+    }
+
+    /// The value of a single contract if exercised at the underlying price.
+    pub fn intrinsic_value(&self, underlying_price: Price) -> Price {
+        let value = match self.right {
+            OptionRight::Call => underlying_price - self.strike,
+            OptionRight::Put => self.strike - underlying_price,
+        };
+        value.max(dec!(0))
+    }
+
+    /// Returns true once the expiry date has passed at `time`, the contract is treated as expiring at the end of the expiry date in UTC.
+    pub fn is_expired(&self, time: DateTime<Utc>) -> bool {
+        match self.expiry_date() {
+            Some(date) => time.date_naive() > date,
+            None => false,
+        }
+    }
+
+    pub fn symbol_info(&self) -> SymbolInfo {
+        SymbolInfo::new(self.symbol_name(), None, self.pnl_currency, self.tick_size * self.multiplier, self.tick_size, self.tick_size.scale())
+    }
+}
+
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Serialize, Deserialize, Default)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct Greeks {
+    pub delta: Decimal,
+    pub gamma: Decimal,
+    pub theta: Decimal,
+    pub vega: Decimal,
+    pub rho: Decimal,
+    pub implied_volatility: Option<Decimal>,
+}
+
+impl Greeks {
+    /// The greeks of a position, the implied volatility is per contract so it is not scaled.
+    pub fn scaled(&self, quantity: Decimal) -> Greeks {
+        Greeks {
+            delta: self.delta * quantity,
+            gamma: self.gamma * quantity,
+            theta: self.theta * quantity,
+            vega: self.vega * quantity,
+            rho: self.rho * quantity,
+            implied_volatility: None,
+        }
+    }
+
+    pub fn add(&mut self, other: &Greeks) {
+        self.delta += other.delta;
+        self.gamma += other.gamma;
+        self.theta += other.theta;
+        self.vega += other.vega;
+        self.rho += other.rho;
+    }
+}
+
+/// A contract in an option chain, with the greeks as supplied by the vendor.
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Serialize, Deserialize)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct OptionQuote {
+    pub contract: OptionContract,
+    pub bid: Option<Price>,
+    pub ask: Option<Price>,
+    pub last: Option<Price>,
+    pub open_interest: Option<Volume>,
+    pub greeks: Option<Greeks>,
+    pub time: String,
+}
+
+impl OptionQuote {
+    pub fn mid(&self) -> Option<Price> {
+        match (self.bid, self.ask) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / dec!(2)),
+            _ => self.last,
+        }
+    }
+}
+
+/// A single leg of a multi leg option position, `ratio` is the number of contracts per unit of the combo.
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Serialize, Deserialize)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct OptionLeg {
+    pub contract: OptionContract,
+    pub side: OrderSide,
+    pub ratio: Volume,
+}
+
+/// A group of option legs which are managed as a single position, eg a vertical spread or an iron condor overlaying a futures position.
+/// The legs are held in the ledger as individual positions, the combo is used to query them together.
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Serialize, Deserialize)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct OptionCombo {
+    pub name: String,
+    pub legs: Vec<OptionLeg>,
+}
+
+impl OptionCombo {
+    pub fn new(name: String, legs: Vec<OptionLeg>) -> Self {
+        OptionCombo {
+            name,
+            legs,
+        }
+    }
+
+    /// Buys the `long_leg` and sells the `short_leg`, a debit spread when the long leg is nearer the money.
+    pub fn vertical(name: String, long_leg: OptionContract, short_leg: OptionContract) -> Self {
+        OptionCombo::new(name, vec![
+            OptionLeg { contract: long_leg, side: OrderSide::Buy, ratio: dec!(1) },
+            OptionLeg { contract: short_leg, side: OrderSide::Sell, ratio: dec!(1) },
+        ])
+    }
+
+    /// The net premium paid per unit of the combo at the quote mids, negative for a credit.
+    /// Returns None if any leg is missing a quote.
+    pub fn net_premium(&self, quotes: &[OptionQuote]) -> Option<Price> {
+        let mut premium = dec!(0);
+        for leg in &self.legs {
+            let quote = quotes.iter().find(|quote| quote.contract == leg.contract)?;
+            let value = quote.mid()? * leg.ratio * leg.contract.multiplier;
+            match leg.side {
+                OrderSide::Buy => premium += value,
+                OrderSide::Sell => premium -= value,
+            }
+        }
+        Some(premium)
+    }
+}
+
+/// The signed number of contracts for a position side, long positions are positive.
+pub(crate) fn signed_quantity(side: PositionSide, quantity: Volume) -> Volume {
+    match side {
+        PositionSide::Long => quantity,
+        PositionSide::Short => -quantity,
+        PositionSide::Flat => dec!(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract(strike: Price, right: OptionRight) -> OptionContract {
+        OptionContract::new("ES".to_string(), NaiveDate::from_ymd_opt(2024, 12, 20).unwrap(), strike, right, dec!(50), dec!(0.25), Currency::USD)
+    }
+
+    #[test]
+    fn test_option_contract_symbol_name_and_values() {
+        let call = contract(dec!(5900), OptionRight::Call);
+        assert_eq!(call.symbol_name(), "ES241220C05900000");
+        assert_eq!(contract(dec!(5912.5), OptionRight::Put).symbol_name(), "ES241220P05912500");
+        assert_eq!(call.intrinsic_value(dec!(5950)), dec!(50));
+        assert_eq!(call.intrinsic_value(dec!(5850)), dec!(0));
+        assert_eq!(call.symbol_info().value_per_tick, dec!(12.5));
+        assert!(!call.is_expired(DateTime::<Utc>::from_str("2024-12-20 21:00:00 UTC").unwrap()));
+        assert!(call.is_expired(DateTime::<Utc>::from_str("2024-12-21 00:00:00 UTC").unwrap()));
+    }
+
+    #[test]
+    fn test_combo_net_premium() {
+        let long_call = contract(dec!(5900), OptionRight::Call);
+        let short_call = contract(dec!(5950), OptionRight::Call);
+        let quote = |contract: &OptionContract, bid, ask| OptionQuote {
+            contract: contract.clone(),
+            bid: Some(bid),
+            ask: Some(ask),
+            last: None,
+            open_interest: None,
+            greeks: None,
+            time: "2024-12-01 15:00:00 UTC".to_string(),
+        };
+        let combo = OptionCombo::vertical("ES call spread".to_string(), long_call.clone(), short_call.clone());
+        let quotes = vec![quote(&long_call, dec!(40), dec!(41)), quote(&short_call, dec!(20), dec!(21))];
+        assert_eq!(combo.net_premium(&quotes), Some(dec!(1000)));
+        assert_eq!(combo.net_premium(&quotes[..1]), None);
+    }
+}
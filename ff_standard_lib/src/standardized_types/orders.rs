@@ -1,6 +1,6 @@
 use std::fmt;
 use crate::standardized_types::accounts::{Account, AccountId};
-use crate::standardized_types::enums::OrderSide;
+use crate::standardized_types::enums::{Liquidity, OrderSide};
 use crate::standardized_types::subscriptions::{SymbolCode, SymbolName};
 use chrono::{DateTime, TimeZone, Utc};
 use chrono_tz::Tz;
@@ -11,6 +11,8 @@ use rust_decimal_macros::dec;
 use strum_macros::Display;
 use crate::standardized_types::broker_enum::Brokerage;
 use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::symbol_info::round_for_display;
+use crate::standardized_types::market_hours::TradingHours;
 
 #[derive(
     Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Serialize, Deserialize,
@@ -71,8 +73,64 @@ pub enum OrderError {
     InvalidOrderId,
     OrderNotFound,
     OrderAlreadyFilled,
+    /// The display quantity of an iceberg is not positive or is larger than the order quantity.
+    InvalidDisplayQuantity,
+    /// The brokerage has no symbol info for the symbol name.
+    UnknownSymbol(SymbolName),
+    /// The account is not one of the strategy accounts.
+    UnknownAccount(Account),
+    /// The trading hours of the symbol are closed at the order time.
+    MarketClosed(SymbolName),
+    /// The symbol is a reference feed, eg a cash index, which can not be traded.
+    NotTradeable(SymbolName),
+    /// A pre order hook refused the order, with the reason given by the hook.
+    RefusedByHook(String),
+    /// The order would open or increase a short position of a symbol which can not be sold short, see `ShortabilityTable`.
+    NotShortable(SymbolName),
+    /// The reference price of a tick offset order has not been received for the symbol, see `PriceReference`.
+    NoMarketPrice(SymbolName),
+    /// The prices of the symbol are older than the max quote age, see `strategy.set_max_quote_age()`.
+    StaleQuote(SymbolName),
+    /// The order would open, increase or reverse a position outside the trading windows of the symbol, see `strategy.set_trading_windows()`.
+    OutsideTradingWindow(SymbolName),
+    /// The spread of the symbol is wider than the max spread of its spread guard, see `strategy.set_spread_guard()`.
+    WideSpread(SymbolName),
+    /// The strategy is in reduce only mode and the order would open, increase or reverse a position, see `strategy.set_reduce_only()`.
+    ReduceOnly(SymbolName),
+    /// The order could take the net exposure of the named exposure group past its max, see `strategy.set_exposure_group()`.
+    ExposureLimit(String),
 }
 
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderError::InvalidPrice => write!(f, "Invalid price"),
+            OrderError::InvalidQuantity => write!(f, "Invalid quantity"),
+            OrderError::InvalidSide => write!(f, "Invalid side"),
+            OrderError::InsufficientFunds => write!(f, "Insufficient funds"),
+            OrderError::InvalidTag => write!(f, "Invalid tag"),
+            OrderError::InvalidOrderId => write!(f, "Invalid order id"),
+            OrderError::OrderNotFound => write!(f, "Order not found"),
+            OrderError::OrderAlreadyFilled => write!(f, "Order already filled"),
+            OrderError::InvalidDisplayQuantity => write!(f, "Invalid display quantity"),
+            OrderError::UnknownSymbol(symbol_name) => write!(f, "Unknown symbol: {}", symbol_name),
+            OrderError::UnknownAccount(account) => write!(f, "Unknown account: {}", account),
+            OrderError::MarketClosed(symbol_name) => write!(f, "Market closed: {}", symbol_name),
+            OrderError::NotTradeable(symbol_name) => write!(f, "Not tradeable: {}", symbol_name),
+            OrderError::RefusedByHook(reason) => write!(f, "Refused by hook: {}", reason),
+            OrderError::NotShortable(symbol_name) => write!(f, "Not shortable: {}", symbol_name),
+            OrderError::NoMarketPrice(symbol_name) => write!(f, "No market price: {}", symbol_name),
+            OrderError::StaleQuote(symbol_name) => write!(f, "Stale quote: {}", symbol_name),
+            OrderError::OutsideTradingWindow(symbol_name) => write!(f, "Outside trading window: {}", symbol_name),
+            OrderError::WideSpread(symbol_name) => write!(f, "Spread too wide: {}", symbol_name),
+            OrderError::ReduceOnly(symbol_name) => write!(f, "Reduce only: {}", symbol_name),
+            OrderError::ExposureLimit(group) => write!(f, "Exposure limit: {}", group),
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
 #[derive(
     Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Serialize, Deserialize,
 )]
@@ -82,12 +140,27 @@ pub enum TimeInForce {
     GTC,
     IOC,
     FOK,
+    /// Expires at the close of the session the order is sent in, or of the next session if the market is closed, from the trading hours of the symbol.
+    /// The expiry is fixed when the order is sent, see `Order::with_day_expiry()` to set it explicitly.
     Day,
 
     //Utc Timestamp
     Time(i64)
 }
 
+/// The price a tick offset is applied to, resolved client side when the order is sent, see `strategy.limit_order_ticks()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriceReference {
+    /// The best bid, or the last price for symbols without a book.
+    BestBid,
+    /// The best ask, or the last price for symbols without a book.
+    BestAsk,
+    /// The best bid for a buy and the best ask for a sell, the side of the book the order joins.
+    SameSide,
+    /// The last traded price, or close, of the symbol.
+    Last,
+}
+
 #[derive(Archive, Clone, rkyv::Serialize, rkyv::Deserialize, Debug, Serialize, Deserialize, PartialEq, PartialOrd)]
 #[archive(compare(PartialEq), check_bytes)]
 #[archive_attr(derive(Debug))]
@@ -127,6 +200,32 @@ pub enum OrderType {
     //UpdateBrackets(Brokerage, AccountId, SymbolName, Vec<ProtectiveOrder>)
 }
 
+impl OrderType {
+    /// Returns true if the order type fills at the market price when it is sent.
+    pub fn is_market(&self) -> bool {
+        match self {
+            OrderType::Market | OrderType::EnterLong | OrderType::EnterShort | OrderType::ExitLong | OrderType::ExitShort => true,
+            OrderType::Limit | OrderType::MarketIfTouched | OrderType::StopMarket | OrderType::StopLimit => false,
+        }
+    }
+
+    /// Returns true if the order type can be placed for a fractional quantity, eg 0.5 shares, with brokerages that support fractional shares.
+    /// The position based order types are sent to the brokerage as market orders.
+    pub fn supports_fractional_quantity(&self) -> bool {
+        match self {
+            OrderType::Limit
+            | OrderType::Market
+            | OrderType::StopMarket
+            | OrderType::StopLimit
+            | OrderType::EnterLong
+            | OrderType::EnterShort
+            | OrderType::ExitLong
+            | OrderType::ExitShort => true,
+            OrderType::MarketIfTouched => false,
+        }
+    }
+}
+
 #[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Serialize, Deserialize)]
 #[archive(compare(PartialEq), check_bytes)]
 #[archive_attr(derive(Debug))]
@@ -137,6 +236,22 @@ pub enum OrderState {
     PartiallyFilled,
     Cancelled,
     Rejected(String),
+    /// The time in force of the order ran out, see `OrderUpdateEvent::OrderExpired`.
+    Expired,
+}
+
+/// What the engine does with an order sent while the trading hours of the symbol are closed.
+/// * `Reject` - The order is refused with `OrderError::MarketClosed`.
+/// * `QueueUntilOpen` - The order is held by the engine and sent at the next session open, like an opening order at a broker.
+#[derive(
+    Clone, Copy, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Eq, Debug, Serialize, Deserialize, Default,
+)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum ClosedMarketPolicy {
+    #[default]
+    Reject,
+    QueueUntilOpen,
 }
 
 #[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Serialize, Deserialize)]
@@ -161,7 +276,15 @@ pub struct Order {
     pub state: OrderState,
     pub fees: Price,
     pub value: Price,
-    pub exchange: Option<String>
+    pub exchange: Option<String>,
+    /// The quantity shown to the market for iceberg orders, None shows the full quantity.
+    pub display_quantity: Option<Volume>,
+    /// What to do with the order if the market is closed when it is sent, None uses the strategy policy, see `strategy.set_closed_market_policy()`.
+    pub closed_market_policy: Option<ClosedMarketPolicy>,
+    /// The utc time a `TimeInForce::Day` order expires, set from the session close when the order is sent unless it was set with `with_day_expiry()`.
+    pub day_expiry: Option<String>,
+    /// The id of the order at the brokerage, eg the Rithmic basket id, set when the brokerage accepts the order. None in backtests and live paper.
+    pub broker_order_id: Option<String>
 }
 
 impl Order {
@@ -169,6 +292,57 @@ impl Order {
         self.time_created_utc = time.to_string();
     }
 
+    /// Sets the closed market policy of this order, overriding the strategy policy.
+    pub fn with_closed_market_policy(mut self, policy: ClosedMarketPolicy) -> Self {
+        self.closed_market_policy = Some(policy);
+        self
+    }
+
+    /// Sets the expiry of a `TimeInForce::Day` order instead of the session close of the symbol.
+    pub fn with_day_expiry(mut self, expiry: DateTime<Utc>) -> Self {
+        self.day_expiry = Some(expiry.to_string());
+        self
+    }
+
+    pub fn day_expiry_utc(&self) -> Option<DateTime<Utc>> {
+        self.day_expiry.as_ref().and_then(|expiry| DateTime::<Utc>::from_str(expiry).ok())
+    }
+
+    /// Sets the expiry of a `TimeInForce::Day` order without an explicit expiry, from the trading hours of the symbol, see `day_expiry()`.
+    pub fn resolve_day_expiry(&mut self, trading_hours: Option<&TradingHours>, sent: DateTime<Utc>) {
+        if self.time_in_force != TimeInForce::Day || self.day_expiry.is_some() {
+            return;
+        }
+        self.day_expiry = Some(day_expiry(trading_hours, &self.account.brokerage.timezone(), sent).to_string());
+    }
+
+    /// True if the time in force of the order has run out at `time`, for a `TimeInForce::Day` order without an expiry this is always false.
+    pub fn is_expired(&self, time: DateTime<Utc>) -> bool {
+        match &self.time_in_force {
+            TimeInForce::Day => self.day_expiry_utc().map_or(false, |expiry| time >= expiry),
+            TimeInForce::Time(expiry) => DateTime::<Utc>::from_timestamp(*expiry, 0).map_or(false, |expiry| time >= expiry),
+            TimeInForce::GTC | TimeInForce::IOC | TimeInForce::FOK => false,
+        }
+    }
+
+    /// Checks the quantities and prices of a new order, these orders would certainly be rejected by the brokerage or the matching engine.
+    pub fn validate(&self) -> Result<(), OrderError> {
+        if self.quantity_open <= dec!(0) {
+            return Err(OrderError::InvalidQuantity);
+        }
+        if let Some(display_quantity) = self.display_quantity {
+            if display_quantity <= dec!(0) || display_quantity > self.quantity_open {
+                return Err(OrderError::InvalidDisplayQuantity);
+            }
+        }
+        for price in [self.limit_price, self.trigger_price].into_iter().flatten() {
+            if price <= dec!(0) {
+                return Err(OrderError::InvalidPrice);
+            }
+        }
+        Ok(())
+    }
+
     pub fn limit_order(
         symbol_name: SymbolName,
         symbol_code: Option<SymbolCode>,
@@ -205,10 +379,34 @@ impl Order {
             state: OrderState::Created,
             fees: dec!(0.0),
             value: dec!(0.0),
-            exchange
+            exchange,
+            display_quantity: None,
+            closed_market_policy: None,
+            day_expiry: None,
+            broker_order_id: None
         }
     }
 
+    /// A limit order which only shows `display_quantity` to the market at a time, the next clip is shown once the current clip fills.
+    pub fn iceberg_order(
+        symbol_name: SymbolName,
+        symbol_code: Option<SymbolCode>,
+        account: &Account,
+        quantity: Volume,
+        display_quantity: Volume,
+        side: OrderSide,
+        tag: String,
+        order_id: OrderId,
+        time: DateTime<Utc>,
+        limit_price: Price,
+        tif: TimeInForce,
+        exchange: Option<String>
+    ) -> Self {
+        let mut order = Order::limit_order(symbol_name, symbol_code, account, quantity, side, tag, order_id, time, limit_price, tif, exchange);
+        order.display_quantity = Some(display_quantity);
+        order
+    }
+
     pub fn market_if_touched (
         symbol_name: SymbolName,
         symbol_code: Option<SymbolCode>,
@@ -245,7 +443,11 @@ impl Order {
             fees: dec!(0.0),
             value: dec!(0.0),
             account: account.clone(),
-            exchange
+            exchange,
+            display_quantity: None,
+            closed_market_policy: None,
+            day_expiry: None,
+            broker_order_id: None
         }
     }
 
@@ -285,7 +487,11 @@ impl Order {
             fees: dec!(0.0),
             value: dec!(0.0),
             account: account.clone(),
-            exchange
+            exchange,
+            display_quantity: None,
+            closed_market_policy: None,
+            day_expiry: None,
+            broker_order_id: None
         }
     }
 
@@ -326,7 +532,11 @@ impl Order {
             fees: dec!(0.0),
             value: dec!(0.0),
             account: account.clone(),
-            exchange
+            exchange,
+            display_quantity: None,
+            closed_market_policy: None,
+            day_expiry: None,
+            broker_order_id: None
         }
     }
 
@@ -364,7 +574,11 @@ impl Order {
             fees: dec!(0.0),
             value: dec!(0.0),
             account: account.clone(),
-            exchange
+            exchange,
+            display_quantity: None,
+            closed_market_policy: None,
+            day_expiry: None,
+            broker_order_id: None
         }
     }
 
@@ -401,7 +615,11 @@ impl Order {
             fees: dec!(0.0),
             value: dec!(0.0),
             account: account.clone(),
-            exchange
+            exchange,
+            display_quantity: None,
+            closed_market_policy: None,
+            day_expiry: None,
+            broker_order_id: None
         }
     }
 
@@ -438,7 +656,11 @@ impl Order {
             fees: dec!(0.0),
             value: dec!(0.0),
             account: account.clone(),
-            exchange
+            exchange,
+            display_quantity: None,
+            closed_market_policy: None,
+            day_expiry: None,
+            broker_order_id: None
         }
     }
 
@@ -475,7 +697,11 @@ impl Order {
             fees: dec!(0.0),
             value: dec!(0.0),
             account: account.clone(),
-            exchange
+            exchange,
+            display_quantity: None,
+            closed_market_policy: None,
+            day_expiry: None,
+            broker_order_id: None
         }
     }
 
@@ -512,7 +738,11 @@ impl Order {
             state: OrderState::Created,
             fees: dec!(0.0),
             value: dec!(0.0),
-            exchange
+            exchange,
+            display_quantity: None,
+            closed_market_policy: None,
+            day_expiry: None,
+            broker_order_id: None
         }
     }
 
@@ -532,6 +762,11 @@ impl Order {
         false
     }
 
+    /// Returns true if the order is for a fractional quantity, eg 0.5 shares.
+    pub fn is_fractional(&self) -> bool {
+        self.quantity_open.fract() != dec!(0)
+    }
+
     pub fn time_created_utc(&self) -> DateTime<Utc> {
         DateTime::from_str(&self.time_created_utc).unwrap()
     }
@@ -571,7 +806,7 @@ pub enum OrderUpdateType {
     Quantity(Volume),
 }
 
-#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug)]
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Serialize, Deserialize)]
 #[archive(compare(PartialEq), check_bytes)]
 #[archive_attr(derive(Debug))]
 /// Represents the various states and updates an order can undergo in the trading system.
@@ -580,16 +815,22 @@ pub enum OrderUpdateType {
 pub enum OrderUpdateEvent {
 
     /// Example, product: MNQZ4,
-    OrderAccepted {account: Account, symbol_name: SymbolName, symbol_code: SymbolCode, order_id: OrderId, tag: String, time: String},
+    /// `broker_order_id` is the id of the order at the brokerage, eg the Rithmic basket id, None when the brokerage does not report it and in backtests.
+    OrderAccepted {account: Account, symbol_name: SymbolName, symbol_code: SymbolCode, order_id: OrderId, tag: String, time: String, broker_order_id: Option<String>},
 
     ///Quantity should only represent the quantity filled on this event.
-    OrderFilled {account: Account, symbol_name: SymbolName, symbol_code: SymbolCode, order_id: OrderId, side: OrderSide, price: Price, quantity: Volume, tag: String, time: String},
+    /// `fill_id` is the id of the fill at the brokerage or exchange, `liquidity` whether the fill made or took liquidity and `commission` the commission charged for this fill,
+    /// each is None when the brokerage does not report it, the backtest and paper matching engines do not report them.
+    OrderFilled {account: Account, symbol_name: SymbolName, symbol_code: SymbolCode, order_id: OrderId, side: OrderSide, price: Price, quantity: Volume, tag: String, time: String, fill_id: Option<String>, liquidity: Option<Liquidity>, commission: Option<Price>},
 
     ///Quantity should only represent the quantity filled on this event.
-    OrderPartiallyFilled {account: Account,  symbol_name: SymbolName, symbol_code: SymbolCode, order_id: OrderId, side: OrderSide, price: Price, quantity: Volume, tag: String, time: String},
+    OrderPartiallyFilled {account: Account,  symbol_name: SymbolName, symbol_code: SymbolCode, order_id: OrderId, side: OrderSide, price: Price, quantity: Volume, tag: String, time: String, fill_id: Option<String>, liquidity: Option<Liquidity>, commission: Option<Price>},
 
     OrderCancelled {account: Account, symbol_name: SymbolName, symbol_code: SymbolCode, order_id: OrderId, reason: String, tag: String, time: String},
 
+    /// The time in force of the order ran out, eg a `TimeInForce::Day` order at the session close, the order was not cancelled by the strategy.
+    OrderExpired {account: Account, symbol_name: SymbolName, symbol_code: SymbolCode, order_id: OrderId, tag: String, time: String},
+
     OrderRejected {account: Account,  symbol_name: SymbolName, symbol_code: SymbolCode, order_id: OrderId, reason: String, tag: String, time: String},
 
     OrderUpdated {account: Account,  symbol_name: SymbolName, symbol_code: SymbolCode, order_id: OrderId, update_type: OrderUpdateType, text: String, tag: String, time: String},
@@ -597,6 +838,19 @@ pub enum OrderUpdateEvent {
     OrderUpdateRejected {account: Account,  order_id: OrderId, reason: String, time: String},
 }
 
+/// The expiry of a `TimeInForce::Day` order sent at `sent`, the next session close from the trading hours,
+/// or the end of the day in the brokerage `timezone` if the symbol has no trading hours.
+pub fn day_expiry(trading_hours: Option<&TradingHours>, timezone: &Tz, sent: DateTime<Utc>) -> DateTime<Utc> {
+    if let Some(close) = trading_hours.and_then(|hours| hours.next_session_close(sent)) {
+        return close;
+    }
+    let end_of_day = sent.with_timezone(timezone).date_naive().and_hms_opt(23, 59, 59).unwrap();
+    match timezone.from_local_datetime(&end_of_day).earliest() {
+        Some(end_of_day) => end_of_day.to_utc(),
+        None => sent + chrono::Duration::days(1),
+    }
+}
+
 impl OrderUpdateEvent {
     pub fn time_local(&self, time_zone: &Tz) -> DateTime<Tz> {
         let utc_time: DateTime<Utc> = self.time_utc();
@@ -609,6 +863,7 @@ impl OrderUpdateEvent {
             OrderUpdateEvent::OrderFilled { time, .. } => DateTime::from_str(time).unwrap(),
             OrderUpdateEvent::OrderPartiallyFilled { time, .. } => DateTime::from_str(time).unwrap(),
             OrderUpdateEvent::OrderCancelled { time, .. } => DateTime::from_str(time).unwrap(),
+            OrderUpdateEvent::OrderExpired { time, .. } => DateTime::from_str(time).unwrap(),
             OrderUpdateEvent::OrderRejected { time, .. } => DateTime::from_str(time).unwrap(),
             OrderUpdateEvent::OrderUpdated { time, .. } => DateTime::from_str(time).unwrap(),
             OrderUpdateEvent::OrderUpdateRejected { time, .. } => DateTime::from_str(time).unwrap(),
@@ -621,6 +876,7 @@ impl OrderUpdateEvent {
             OrderUpdateEvent::OrderFilled { order_id, .. } => order_id,
             OrderUpdateEvent::OrderPartiallyFilled { order_id, .. } => order_id,
             OrderUpdateEvent::OrderCancelled { order_id, .. } => order_id,
+            OrderUpdateEvent::OrderExpired { order_id, .. } => order_id,
             OrderUpdateEvent::OrderRejected { order_id, .. } => order_id,
             OrderUpdateEvent::OrderUpdated { order_id, .. } => order_id,
             OrderUpdateEvent::OrderUpdateRejected { order_id, .. } => order_id,
@@ -633,6 +889,7 @@ impl OrderUpdateEvent {
             OrderUpdateEvent::OrderFilled { symbol_code, .. } => Some(symbol_code.clone()),
             OrderUpdateEvent::OrderPartiallyFilled { symbol_code, .. } => Some(symbol_code.clone()),
             OrderUpdateEvent::OrderCancelled { symbol_code, .. } => Some(symbol_code.clone()),
+            OrderUpdateEvent::OrderExpired { symbol_code, .. } => Some(symbol_code.clone()),
             OrderUpdateEvent::OrderRejected { symbol_code, .. } => Some(symbol_code.clone()),
             OrderUpdateEvent::OrderUpdated { symbol_code, .. } => Some(symbol_code.clone()),
             OrderUpdateEvent::OrderUpdateRejected {  .. } => None,
@@ -645,6 +902,7 @@ impl OrderUpdateEvent {
             OrderUpdateEvent::OrderFilled  { account, .. } => &account.brokerage,
             OrderUpdateEvent::OrderPartiallyFilled  { account, .. } => &account.brokerage,
             OrderUpdateEvent::OrderCancelled  { account, .. } => &account.brokerage,
+            OrderUpdateEvent::OrderExpired  { account, .. } => &account.brokerage,
             OrderUpdateEvent::OrderRejected { account, .. } => &account.brokerage,
             OrderUpdateEvent::OrderUpdated  { account, .. } => &account.brokerage,
             OrderUpdateEvent::OrderUpdateRejected  { account, .. } => &account.brokerage,
@@ -657,6 +915,7 @@ impl OrderUpdateEvent {
             OrderUpdateEvent::OrderFilled  { account, .. } => &account,
             OrderUpdateEvent::OrderPartiallyFilled  { account, .. } => &account,
             OrderUpdateEvent::OrderCancelled  { account, .. } => &account,
+            OrderUpdateEvent::OrderExpired  { account, .. } => &account,
             OrderUpdateEvent::OrderRejected { account, .. } => &account,
             OrderUpdateEvent::OrderUpdated  { account, .. } => &account,
             OrderUpdateEvent::OrderUpdateRejected  { account, .. } => &account,
@@ -671,6 +930,7 @@ impl OrderUpdateEvent {
             OrderUpdateEvent::OrderFilled {  .. } =>  Some(OrderState::Filled),
             OrderUpdateEvent::OrderPartiallyFilled {  .. } => Some(OrderState::PartiallyFilled),
             OrderUpdateEvent::OrderCancelled {  .. } => Some(OrderState::Cancelled),
+            OrderUpdateEvent::OrderExpired {  .. } => Some(OrderState::Expired),
             OrderUpdateEvent::OrderRejected {  reason, .. } => Some(OrderState::Rejected(reason.clone())),
             OrderUpdateEvent::OrderUpdated {  .. } => None,
             OrderUpdateEvent::OrderUpdateRejected {  .. } => None,
@@ -684,14 +944,17 @@ impl fmt::Display for OrderUpdateEvent {
                 write!(f, "Order Accepted: Account: {}, Symbol Name: {}, Symbol Code: {}, Order ID: {}, Tag: {}", account, symbol_name, product, order_id, tag)
             }
             OrderUpdateEvent::OrderFilled { account,symbol_name, symbol_code: product, price, quantity, order_id,tag,.. } => {
-                write!(f, "Order Filled: Account: {}, Symbol Name: {}, Symbol Code: {}, Price: {}, Quantity: {}, Order ID: {}, Tag: {}", account, symbol_name, product, price, quantity, order_id, tag)
+                write!(f, "Order Filled: Account: {}, Symbol Name: {}, Symbol Code: {}, Price: {}, Quantity: {}, Order ID: {}, Tag: {}", account, symbol_name, product, round_for_display(symbol_name, *price), quantity, order_id, tag)
             }
             OrderUpdateEvent::OrderPartiallyFilled { account, symbol_name, symbol_code,price, quantity, order_id,tag,.. } => {
-                write!(f, "Order Partially Filled: Account: {}, Symbol Name: {}, Symbol Code: {},Price: {}, Quantity: {}, Order ID: {}, Tag: {}", account, symbol_name, symbol_code, price, quantity, order_id, tag)
+                write!(f, "Order Partially Filled: Account: {}, Symbol Name: {}, Symbol Code: {},Price: {}, Quantity: {}, Order ID: {}, Tag: {}", account, symbol_name, symbol_code, round_for_display(symbol_name, *price), quantity, order_id, tag)
             }
             OrderUpdateEvent::OrderCancelled { account,symbol_name, symbol_code, reason, order_id,tag,.. } => {
                 write!(f, "Order Cancelled: Account: {}, Symbol Name: {}, Symbol Code: {}, Reason: {}. Order ID: {}, Tag: {}", account, symbol_name, symbol_code, reason, order_id, tag)
             }
+            OrderUpdateEvent::OrderExpired { account,symbol_name, symbol_code, order_id,tag,.. } => {
+                write!(f, "Order Expired: Account: {}, Symbol Name: {}, Symbol Code: {}, Order ID: {}, Tag: {}", account, symbol_name, symbol_code, order_id, tag)
+            }
             OrderUpdateEvent::OrderRejected { account,symbol_name, symbol_code: product, order_id, reason,tag,.. } => {
                 write!(f, "Order Rejected: Account: {}, Symbol Name: {}, Symbol Code: {}, Order ID: {}. Reason: {}, Tag: {}", account, symbol_name, product, order_id, reason, tag)
             }
@@ -704,3 +967,51 @@ impl fmt::Display for OrderUpdateEvent {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn account() -> Account {
+        Account::new(Brokerage::Oanda, "test_account".to_string())
+    }
+
+    #[test]
+    fn test_order_validation() {
+        let time = Utc::now();
+        let order = Order::enter_long("EUR-USD".to_string(), None, &account(), dec!(1000), String::new(), "1".to_string(), time, None);
+        assert_eq!(order.validate(), Ok(()));
+
+        let order = Order::enter_long("EUR-USD".to_string(), None, &account(), dec!(0), String::new(), "2".to_string(), time, None);
+        assert_eq!(order.validate(), Err(OrderError::InvalidQuantity));
+
+        let order = Order::limit_order("EUR-USD".to_string(), None, &account(), dec!(1000), OrderSide::Buy, String::new(), "3".to_string(), time, dec!(0), TimeInForce::GTC, None);
+        assert_eq!(order.validate(), Err(OrderError::InvalidPrice));
+
+        let order = Order::iceberg_order("EUR-USD".to_string(), None, &account(), dec!(1000), dec!(2000), OrderSide::Buy, String::new(), "4".to_string(), time, dec!(1.1), TimeInForce::GTC, None);
+        assert_eq!(order.validate(), Err(OrderError::InvalidDisplayQuantity));
+    }
+
+    #[test]
+    fn test_day_expiry() {
+        use chrono_tz::America::New_York;
+        use crate::product_maps::alpaca::maps::NYSE_HOURS;
+        let sent = New_York.with_ymd_and_hms(2024, 1, 8, 10, 0, 0).unwrap().to_utc();
+        let close = New_York.with_ymd_and_hms(2024, 1, 8, 16, 0, 0).unwrap().to_utc();
+
+        let mut order = Order::limit_order("AAPL".to_string(), None, &account(), dec!(10), OrderSide::Buy, String::new(), "1".to_string(), sent, dec!(180), TimeInForce::Day, None);
+        order.resolve_day_expiry(Some(&NYSE_HOURS), sent);
+        assert_eq!(order.day_expiry_utc(), Some(close));
+        assert!(!order.is_expired(close - chrono::Duration::seconds(1)));
+        assert!(order.is_expired(close));
+
+        // an explicit expiry is kept
+        let early = close - chrono::Duration::hours(2);
+        let mut order = order.with_day_expiry(early);
+        order.resolve_day_expiry(Some(&NYSE_HOURS), sent);
+        assert_eq!(order.day_expiry_utc(), Some(early));
+
+        // without trading hours the order expires at the end of the day in the brokerage timezone
+        assert!(day_expiry(None, &New_York, sent) > close);
+    }
+}
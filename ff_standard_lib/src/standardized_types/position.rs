@@ -19,7 +19,7 @@ use crate::standardized_types::enums::{PositionSide, StrategyMode};
 use crate::standardized_types::subscriptions::{SymbolCode, SymbolName};
 use crate::standardized_types::new_types::{Price, Volume};
 use crate::standardized_types::orders::OrderId;
-use crate::standardized_types::symbol_info::SymbolInfo;
+use crate::standardized_types::symbol_info::{round_for_display, SymbolInfo};
 
 pub type PositionId = String;
 #[derive(Serialize)]
@@ -37,9 +37,10 @@ pub(crate) struct PositionExport {
     entry_time: String,
     exit_time: String,
     hold_duration: String,
+    fingerprint: String,
 }
 
-#[derive(Clone, rkyv::Serialize, rkyv::Deserialize, Archive, Debug, PartialEq)]
+#[derive(Clone, rkyv::Serialize, rkyv::Deserialize, Archive, Debug, PartialEq, Serialize, Deserialize)]
 #[archive(compare(PartialEq), check_bytes)]
 #[archive_attr(derive(Debug))]
 pub enum PositionUpdateEvent {
@@ -168,6 +169,7 @@ impl fmt::Display for PositionUpdateEvent {
                 position_id,
                 total_quantity_open,
                 average_price,
+                symbol_name,
                 open_pnl,
                 booked_pnl,
                 account,
@@ -177,12 +179,13 @@ impl fmt::Display for PositionUpdateEvent {
                 write!(
                     f,
                     "PositionIncreased: Position ID = {}, Account: {}, Total Quantity Open = {}, Average Price = {}, Open PnL = {}, Booked PnL = {}, Originating Order Tag: {}",
-                    position_id, account, total_quantity_open, average_price, open_pnl, booked_pnl, tag
+                    position_id, account, total_quantity_open, round_for_display(symbol_name, *average_price), open_pnl.round_dp(2), booked_pnl.round_dp(2), tag
                 )
             }
             PositionUpdateEvent::PositionReduced {
                 position_id,
                 total_quantity_open,
+                symbol_name,
                 total_quantity_closed,
                 average_price,
                 open_pnl,
@@ -195,11 +198,12 @@ impl fmt::Display for PositionUpdateEvent {
                 write!(
                     f,
                     "PositionReduced: Position ID = {}, Account: {}, Total Quantity Open = {}, Total Quantity Closed = {}, Average Price = {}, Open PnL = {}, Booked PnL = {}, Average Exit Price = {}, Originating Order Tag: {}",
-                    position_id, account, total_quantity_open, total_quantity_closed, average_price, open_pnl, booked_pnl, average_exit_price, tag
+                    position_id, account, total_quantity_open, total_quantity_closed, round_for_display(symbol_name, *average_price), open_pnl.round_dp(2), booked_pnl.round_dp(2), round_for_display(symbol_name, *average_exit_price), tag
                 )
             }
             PositionUpdateEvent::PositionClosed {
                 position_id,
+                symbol_name,
                 total_quantity_open,
                 total_quantity_closed,
                 average_price,
@@ -212,7 +216,7 @@ impl fmt::Display for PositionUpdateEvent {
                 write!(
                     f,
                     "PositionClosed: Position ID = {}, Account: {}, Total Quantity Open = {}, Total Quantity Closed = {}, Average Price = {}, Booked PnL = {}, Average Exit Price = {}, Originating Order Tag: {}",
-                    position_id, account, total_quantity_open, total_quantity_closed, average_price, booked_pnl, average_exit_price, tag
+                    position_id, account, total_quantity_open, total_quantity_closed, round_for_display(symbol_name, *average_price), booked_pnl.round_dp(2), round_for_display(symbol_name, *average_exit_price), tag
                 )
             }
         }
@@ -363,7 +367,7 @@ impl Position {
         }
     }
 
-    pub(crate) fn to_export(&self) -> PositionExport {
+    pub(crate) fn to_export(&self, fingerprint: &str) -> PositionExport {
         let (exit_time, hold_duration) = match &self.close_time {
             None => ("None".to_string(), "N/A".to_string()),
             Some(time) => (time.to_string(), format_duration(DateTime::<Utc>::from_str(time).unwrap() - DateTime::<Utc>::from_str(&self.open_time).unwrap()))
@@ -381,15 +385,15 @@ impl Position {
 
         // Calculate final prices, using position's average price as fallback
         let final_entry_price = if total_quantity > dec!(0.0) {
-            (weighted_entry / total_quantity).round_dp(self.symbol_info.decimal_accuracy)
+            self.symbol_info.round_for_display(weighted_entry / total_quantity)
         } else {
-            self.average_price
+            self.symbol_info.round_for_display(self.average_price)
         };
 
         let final_exit_price = if total_quantity > dec!(0.0) {
-            (weighted_exit / total_quantity).round_dp(self.symbol_info.decimal_accuracy)
+            self.symbol_info.round_for_display(weighted_exit / total_quantity)
         } else {
-            self.average_exit_price.unwrap_or(self.average_price)
+            self.symbol_info.round_for_display(self.average_exit_price.unwrap_or(self.average_price))
         };
 
         PositionExport {
@@ -400,41 +404,65 @@ impl Position {
             average_exit_price: final_exit_price,
             booked_pnl: self.booked_pnl.round_dp(2),
             open_pnl: self.open_pnl.round_dp(2),
-            highest_recoded_price: self.highest_recoded_price,
-            lowest_recoded_price: self.lowest_recoded_price,
+            highest_recoded_price: self.symbol_info.round_for_display(self.highest_recoded_price),
+            lowest_recoded_price: self.symbol_info.round_for_display(self.lowest_recoded_price),
             exit_time,
             entry_time: self.open_time.to_string(),
             hold_duration,
-            tag: self.tag.clone()
+            tag: self.tag.clone(),
+            fingerprint: fingerprint.to_string(),
         }
     }
 
-    /// Returns the open pnl for the paper position
-    pub(crate) fn update_base_data(&mut self, base_data: &BaseDataEnum, account_currency: Currency) -> Decimal {
-        if self.is_closed {
-            return dec!(0)
-        }
-
-        // Extract market price, highest price, and lowest price from base data
-        let (market_price, highest_price, lowest_price) = match base_data {
-            BaseDataEnum::Candle(candle) => (candle.close, candle.high, candle.low),
-            BaseDataEnum::Tick(tick) => (tick.price, tick.price, tick.price),
+    /// The market price, highest price and lowest price of the data for the side of the position, None if the data has no price.
+    fn data_prices(&self, base_data: &BaseDataEnum) -> Option<(Price, Price, Price)> {
+        match base_data {
+            BaseDataEnum::Candle(candle) => Some((candle.close, candle.high, candle.low)),
+            BaseDataEnum::Tick(tick) => Some((tick.price, tick.price, tick.price)),
             BaseDataEnum::QuoteBar(bar) => match self.side {
-                PositionSide::Long => (bar.ask_close, bar.ask_high, bar.ask_low),
-                PositionSide::Short => (bar.bid_close, bar.bid_high, bar.bid_low),
+                PositionSide::Long => Some((bar.ask_close, bar.ask_high, bar.ask_low)),
+                PositionSide::Short => Some((bar.bid_close, bar.bid_high, bar.bid_low)),
                 _ => unreachable!("This shouldn't be possible"),
             },
             BaseDataEnum::Quote(quote) => match self.side {
-                PositionSide::Long => (quote.ask, quote.ask, quote.ask),
-                PositionSide::Short => (quote.bid, quote.bid, quote.bid),
+                PositionSide::Long => Some((quote.ask, quote.ask, quote.ask)),
+                PositionSide::Short => Some((quote.bid, quote.bid, quote.bid)),
                 _ => unreachable!("This shouldn't be possible"),
             },
+            BaseDataEnum::Depth(depth) => {
+                let price = match self.side {
+                    PositionSide::Long => depth.best_ask(),
+                    PositionSide::Short => depth.best_bid(),
+                    _ => unreachable!("This shouldn't be possible"),
+                };
+                price.map(|price| (price, price, price))
+            }
             BaseDataEnum::Fundamental(_) => panic!("Fundamentals should not be here"),
-        };
+        }
+    }
 
-        // Update highest and lowest recorded prices
+    /// Updates the highest and lowest prices recorded while the position is open, returns the market price of the data.
+    pub(crate) fn update_excursion(&mut self, base_data: &BaseDataEnum) -> Option<Price> {
+        if self.is_closed {
+            return None
+        }
+        let (market_price, highest_price, lowest_price) = self.data_prices(base_data)?;
         self.highest_recoded_price = self.highest_recoded_price.max(highest_price);
         self.lowest_recoded_price = self.lowest_recoded_price.min(lowest_price);
+        Some(market_price)
+    }
+
+    /// Returns the open pnl for the paper position
+    pub(crate) fn update_base_data(&mut self, base_data: &BaseDataEnum, account_currency: Currency) -> Decimal {
+        if self.is_closed {
+            return dec!(0)
+        }
+
+        // Update highest and lowest recorded prices
+        let market_price = match self.update_excursion(base_data) {
+            Some(market_price) => market_price,
+            None => return self.open_pnl,
+        };
 
         // Calculate the open PnL
         self.open_pnl = calculate_theoretical_pnl(
@@ -451,6 +479,24 @@ impl Position {
         self.open_pnl.clone()
     }
 
+    /// The maximum adverse excursion, how far the price moved against the position from the average price while it was open, 0 if it never moved against it.
+    pub fn mae(&self) -> Price {
+        match self.side {
+            PositionSide::Long => (self.average_price - self.lowest_recoded_price).max(dec!(0)),
+            PositionSide::Short => (self.highest_recoded_price - self.average_price).max(dec!(0)),
+            PositionSide::Flat => dec!(0),
+        }
+    }
+
+    /// The maximum favorable excursion, how far the price moved in favor of the position from the average price while it was open, 0 if it never moved in favor.
+    pub fn mfe(&self) -> Price {
+        match self.side {
+            PositionSide::Long => (self.highest_recoded_price - self.average_price).max(dec!(0)),
+            PositionSide::Short => (self.average_price - self.lowest_recoded_price).max(dec!(0)),
+            PositionSide::Flat => dec!(0),
+        }
+    }
+
     /// Reduces position size a position event, this event will include a booked_pnl property
     pub(crate) async fn reduce_position_size(&mut self, market_price: Price, quantity: Volume, order_id: OrderId, account_currency: Currency, exchange_rate: Decimal, time: DateTime<Utc>, tag: String) -> PositionUpdateEvent {
         if quantity > self.quantity_open {
@@ -1174,7 +1220,7 @@ mod test {
             "exit-2".to_string()
         ).await;
 
-        let export = position.to_export();
+        let export = position.to_export("");
 
         // Verify the exported data uses trade history for calculations
         assert!(export.average_entry_price > dec!(0.0));
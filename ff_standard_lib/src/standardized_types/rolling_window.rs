@@ -13,6 +13,13 @@ impl<T: std::clone::Clone> RollingWindow<T> {
         }
     }
 
+    /// Changes the number of items retained, when reduced the oldest items are removed and the unused memory is released.
+    pub fn set_capacity(&mut self, number: usize) {
+        self.history.truncate(number);
+        self.history.shrink_to(number);
+        self.number = number as u64;
+    }
+
     pub fn clear(&mut self) {
         self.history.clear()
     }
@@ -8,6 +8,7 @@ use std::fmt;
 use std::fmt::{Debug, Display, Error, Formatter};
 use crate::standardized_types::datavendor_enum::DataVendor;
 use crate::standardized_types::resolution::Resolution;
+use crate::standardized_types::symbol_aliases::canonical_symbol_name;
 
 pub type SymbolName = String;
 pub type SymbolCode = String;
@@ -28,7 +29,7 @@ impl Display for Symbol {
 
 impl Symbol {
     pub fn new(name: SymbolName, data_vendor: DataVendor, market_type: MarketType) -> Self {
-        let cleaned_symbol_name = fund_forge_formatted_symbol_name(&name);
+        let cleaned_symbol_name = canonical_symbol_name(&data_vendor, &name);
         Symbol {
             name: cleaned_symbol_name,
             market_type,
@@ -43,6 +44,11 @@ impl Symbol {
 pub enum CandleType {
     HeikinAshi,
     CandleStick,
+    /// Renko bars which close `trend_ticks` from the last close in the direction of the last bar, or `reversal_ticks` against it,
+    /// the next bar opens `open_offset_ticks` back from the close, see `RenkoConsolidator`.
+    UniRenko { trend_ticks: u32, reversal_ticks: u32, open_offset_ticks: u32 },
+    /// Renko bars which open at the midpoint of the last bar and close `brick_ticks` from the open in either direction, see `RenkoConsolidator`.
+    MeanRenko { brick_ticks: u32 },
 }
 
 impl CandleType {
@@ -59,8 +65,15 @@ impl CandleType {
         match self {
             CandleType::HeikinAshi => "HeikinAshi".to_string(),
             CandleType::CandleStick => "CandleStick".to_string(),
+            CandleType::UniRenko { trend_ticks, reversal_ticks, open_offset_ticks } => format!("UniRenko-T{}-R{}-O{}", trend_ticks, reversal_ticks, open_offset_ticks),
+            CandleType::MeanRenko { brick_ticks } => format!("MeanRenko-{}", brick_ticks),
         }
     }
+
+    /// True for the renko candle types, which close on price movement instead of time.
+    pub fn is_renko(&self) -> bool {
+        matches!(self, CandleType::UniRenko { .. } | CandleType::MeanRenko { .. })
+    }
 }
 
 impl Display for CandleType {
@@ -72,6 +85,12 @@ impl Display for CandleType {
             CandleType::CandleStick => {
                 write!(f, "{}", "Candle Stick")
             }
+            CandleType::UniRenko { trend_ticks, reversal_ticks, open_offset_ticks } => {
+                write!(f, "Uni Renko T{} R{} O{}", trend_ticks, reversal_ticks, open_offset_ticks)
+            }
+            CandleType::MeanRenko { brick_ticks } => {
+                write!(f, "Mean Renko {}", brick_ticks)
+            }
         }
     }
 }
@@ -265,6 +284,10 @@ pub enum DataSubscriptionEvent {
     Unsubscribed(DataSubscription),
     FailedToSubscribe(DataSubscription, String),
     FailedUnSubscribed(DataSubscription, String),
+    /// The live stream of the subscription moved to a fallback vendor, see `set_vendor_fallbacks()`.
+    VendorFailover(DataSubscription, DataVendor),
+    /// The futures contract of the subscription is no longer the front month and the subscription of the next contract if it was mapped to it, see `set_contract_expiry_policy()`.
+    Expired(DataSubscription, Option<DataSubscription>),
 }
 impl fmt::Display for DataSubscriptionEvent {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -277,6 +300,11 @@ impl fmt::Display for DataSubscriptionEvent {
             DataSubscriptionEvent::FailedUnSubscribed(sub, reason) => {
                 write!(f, "Failed to unsubscribe from: {}. Reason: {}", sub, reason)
             }
+            DataSubscriptionEvent::VendorFailover(sub, vendor) => {
+                write!(f, "Streaming {} from fallback vendor: {}", sub, vendor)
+            }
+            DataSubscriptionEvent::Expired(sub, Some(next)) => write!(f, "Contract expired: {}, mapped to: {}", sub, next),
+            DataSubscriptionEvent::Expired(sub, None) => write!(f, "Contract expired: {}", sub),
         }
     }
 }
@@ -0,0 +1,100 @@
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
+use serde_derive::{Deserialize, Serialize};
+use crate::helpers::converters::fund_forge_formatted_symbol_name;
+use crate::standardized_types::datavendor_enum::DataVendor;
+use crate::standardized_types::enums::FuturesExchange;
+use crate::standardized_types::subscriptions::SymbolName;
+
+/// A vendor name for a canonical symbol name.
+/// # Properties
+/// * `data_vendor` - The vendor using the name.
+/// * `vendor_symbol` - The name the vendor uses, eg `EURUSD.FX`.
+/// * `canonical` - The fund-forge symbol name, eg `EUR-USD`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Serialize_rkyv, Deserialize_rkyv, Archive)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct SymbolAlias {
+    pub data_vendor: DataVendor,
+    pub vendor_symbol: String,
+    pub canonical: SymbolName,
+}
+
+lazy_static! {
+    /// The aliases by vendor and formatted vendor name.
+    static ref VENDOR_ALIASES: DashMap<(DataVendor, String), SymbolAlias> = DashMap::new();
+    /// The vendor names by vendor and canonical name.
+    static ref CANONICAL_ALIASES: DashMap<(DataVendor, SymbolName), String> = DashMap::new();
+}
+
+/// Registers a vendor name for a canonical symbol name, replacing an alias of either name.
+/// ```rust
+/// register_symbol_alias(SymbolAlias { data_vendor: DataVendor::DataBento, vendor_symbol: "EURUSD.FX".to_string(), canonical: "EUR-USD".to_string() });
+/// ```
+pub fn register_symbol_alias(alias: SymbolAlias) {
+    let canonical = fund_forge_formatted_symbol_name(&alias.canonical);
+    if let Some((_, previous)) = CANONICAL_ALIASES.remove(&(alias.data_vendor, canonical.clone())) {
+        VENDOR_ALIASES.remove(&(alias.data_vendor, fund_forge_formatted_symbol_name(&previous)));
+    }
+    let alias = SymbolAlias { canonical, ..alias };
+    CANONICAL_ALIASES.insert((alias.data_vendor, alias.canonical.clone()), alias.vendor_symbol.clone());
+    VENDOR_ALIASES.insert((alias.data_vendor, fund_forge_formatted_symbol_name(&alias.vendor_symbol)), alias);
+}
+
+/// The registered aliases, sorted by vendor and canonical name.
+pub fn symbol_aliases() -> Vec<SymbolAlias> {
+    let mut aliases: Vec<SymbolAlias> = VENDOR_ALIASES.iter().map(|alias| alias.value().clone()).collect();
+    aliases.sort_by(|a, b| (a.data_vendor, &a.canonical).cmp(&(b.data_vendor, &b.canonical)));
+    aliases
+}
+
+/// The canonical fund-forge symbol name of a name used by the vendor, the canonical name itself is returned unchanged.
+pub fn canonical_symbol_name(data_vendor: &DataVendor, vendor_symbol: &str) -> SymbolName {
+    let formatted = fund_forge_formatted_symbol_name(vendor_symbol);
+    if let Some(alias) = VENDOR_ALIASES.get(&(*data_vendor, formatted.clone())) {
+        return alias.canonical.clone();
+    }
+    match formatted.rsplit_once('.') {
+        Some((root, exchange)) if !root.is_empty() && FuturesExchange::from_string(exchange).is_ok() => root.to_string(),
+        _ => formatted,
+    }
+}
+
+/// The name the vendor uses for a canonical fund-forge symbol name.
+pub fn vendor_symbol_name(data_vendor: &DataVendor, canonical: &SymbolName) -> String {
+    if let Some(vendor_symbol) = CANONICAL_ALIASES.get(&(*data_vendor, canonical.clone())) {
+        return vendor_symbol.value().clone();
+    }
+    match data_vendor {
+        DataVendor::Oanda => canonical.replace('-', "_"),
+        _ => canonical.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_names() {
+        assert_eq!(canonical_symbol_name(&DataVendor::Oanda, "EUR_USD"), "EUR-USD");
+        assert_eq!(vendor_symbol_name(&DataVendor::Oanda, &"EUR-USD".to_string()), "EUR_USD");
+        assert_eq!(canonical_symbol_name(&DataVendor::Rithmic, "MNQ.CME"), "MNQ");
+        assert_eq!(canonical_symbol_name(&DataVendor::Rithmic, "mnq"), "MNQ");
+        // only exchange suffixes are dropped
+        assert_eq!(canonical_symbol_name(&DataVendor::Alpaca, "BRK.B"), "BRK.B");
+
+        register_symbol_alias(SymbolAlias { data_vendor: DataVendor::DataBento, vendor_symbol: "ALIASTEST.FX".to_string(), canonical: "ALIAS-TEST".to_string() });
+        assert_eq!(canonical_symbol_name(&DataVendor::DataBento, "AliasTest.FX"), "ALIAS-TEST");
+        assert_eq!(vendor_symbol_name(&DataVendor::DataBento, &"ALIAS-TEST".to_string()), "ALIASTEST.FX");
+        // an alias is per vendor
+        assert_eq!(canonical_symbol_name(&DataVendor::Bitget, "ALIASTEST.FX"), "ALIASTEST.FX");
+
+        // a new vendor name replaces the old one
+        register_symbol_alias(SymbolAlias { data_vendor: DataVendor::DataBento, vendor_symbol: "ALIASTEST2".to_string(), canonical: "ALIAS-TEST".to_string() });
+        assert_eq!(canonical_symbol_name(&DataVendor::DataBento, "ALIASTEST.FX"), "ALIASTEST.FX");
+        assert_eq!(vendor_symbol_name(&DataVendor::DataBento, &"ALIAS-TEST".to_string()), "ALIASTEST2");
+        assert_eq!(symbol_aliases().iter().filter(|alias| alias.canonical == "ALIAS-TEST").count(), 1);
+    }
+}
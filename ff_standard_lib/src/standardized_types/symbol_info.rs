@@ -1,3 +1,5 @@
+use dashmap::DashMap;
+use lazy_static::lazy_static;
 use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
 use rust_decimal::Decimal;
 use crate::standardized_types::accounts::Currency;
@@ -5,6 +7,7 @@ use crate::standardized_types::new_types::Price;
 use crate::standardized_types::subscriptions::{SymbolCode, SymbolName};
 use serde_derive::{Deserialize, Serialize};
 use crate::standardized_types::enums::FuturesExchange;
+use crate::helpers::decimal_calculators::round_to_tick_size;
 
 #[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, Debug, PartialEq, Serialize, Deserialize, PartialOrd,)]
 #[archive(compare(PartialEq), check_bytes)]
@@ -15,7 +18,34 @@ pub struct SymbolInfo {
     pub pnl_currency: Currency,
     pub value_per_tick: Price,
     pub tick_size: Price,
-    pub decimal_accuracy: u32
+    pub decimal_accuracy: u32,
+    /// True if the vendor does not provide the symbol and the server derives its data from other symbols, eg a triangulated FX cross rate.
+    #[serde(default)]
+    pub is_synthetic: bool,
+    /// True for reference symbols which can not be traded, eg a cash index, orders for the symbol are refused with `OrderError::NotTradeable`.
+    #[serde(default)]
+    pub is_reference: bool,
+    /// The decimal places prices are displayed with in prints, exports and the gui, None displays `decimal_accuracy` places.
+    #[serde(default)]
+    pub display_precision: Option<u32>,
+}
+
+lazy_static! {
+    /// The display precision of each symbol name from the symbol info of the symbols traded, for the prints of events which do not know their strategy.
+    static ref DISPLAY_PRECISION: DashMap<SymbolName, u32> = DashMap::new();
+}
+
+/// The decimal places the prices of the symbol are displayed with by the symbol info, None if no symbol info has been received for the symbol.
+pub fn display_precision(symbol_name: &SymbolName) -> Option<u32> {
+    DISPLAY_PRECISION.get(symbol_name).map(|precision| *precision.value())
+}
+
+/// Rounds the price to the display precision of the symbol, the prices of unknown symbols are returned unchanged.
+pub fn round_for_display(symbol_name: &SymbolName, price: Price) -> Price {
+    match display_precision(symbol_name) {
+        Some(precision) => price.round_dp(precision),
+        None => price,
+    }
 }
 
 impl SymbolInfo {
@@ -34,8 +64,37 @@ impl SymbolInfo {
             value_per_tick,
             tick_size,
             decimal_accuracy,
+            is_synthetic: false,
+            is_reference: false,
+            display_precision: None,
         }
     }
+
+    pub fn with_display_precision(mut self, precision: u32) -> Self {
+        self.display_precision = Some(precision);
+        self
+    }
+
+    /// Marks the symbol as a reference feed which can not be traded.
+    pub fn as_reference(mut self) -> Self {
+        self.is_reference = true;
+        self
+    }
+
+    /// The decimal places prices are displayed with, `decimal_accuracy` if no display precision is set.
+    pub fn display_precision(&self) -> u32 {
+        self.display_precision.unwrap_or(self.decimal_accuracy)
+    }
+
+    /// Rounds the price to the display precision, the price itself is not changed where it is used for calculations.
+    pub fn round_for_display(&self, price: Price) -> Price {
+        price.round_dp(self.display_precision())
+    }
+
+    /// Makes the display precision of the symbol info known by symbol name.
+    pub(crate) fn register_display_precision(&self) {
+        DISPLAY_PRECISION.insert(self.symbol_name.clone(), self.display_precision());
+    }
 }
 
 #[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, Debug, PartialEq, Serialize, Deserialize, PartialOrd,)]
@@ -68,4 +127,45 @@ impl FrontMonthInfo {
             symbol_code
         }
     }
-}
\ No newline at end of file
+}
+/// The price `ticks` ticks of the symbol above the base price, below for a negative `ticks`, the base price is first rounded to the nearest tick.
+/// ```rust
+/// // 2 ticks under the bid, 21000.00 for MNQ with a tick size of 0.25, 1.09980 for EUR-USD with a tick size of 0.00001
+/// let limit_price = price_offset_ticks(&symbol_info, bid, -2);
+/// ```
+pub fn price_offset_ticks(symbol_info: &SymbolInfo, base_price: Price, ticks: i64) -> Price {
+    if symbol_info.tick_size <= Decimal::ZERO {
+        return base_price;
+    }
+    round_to_tick_size(base_price, symbol_info.tick_size) + Decimal::from(ticks) * symbol_info.tick_size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_display_precision() {
+        let info = SymbolInfo::new("DISPLAY-TEST".to_string(), None, Currency::USD, dec!(0.5), dec!(0.25), 2);
+        assert_eq!(round_for_display(&info.symbol_name, dec!(21655.500000000001)), dec!(21655.500000000001));
+        assert_eq!(info.round_for_display(dec!(21655.500000000001)), dec!(21655.50));
+
+        let info = info.with_display_precision(1);
+        info.register_display_precision();
+        assert_eq!(round_for_display(&info.symbol_name, dec!(21655.55)), dec!(21655.6));
+        assert_eq!(info.display_precision(), 1);
+    }
+
+    #[test]
+    fn test_price_offset_ticks() {
+        let mnq = SymbolInfo::new("MNQ".to_string(), None, Currency::USD, dec!(0.5), dec!(0.25), 2);
+        assert_eq!(price_offset_ticks(&mnq, dec!(21000.50), -2), dec!(21000.00));
+        assert_eq!(price_offset_ticks(&mnq, dec!(21000.50), 3), dec!(21001.25));
+        // an off tick base is rounded first
+        assert_eq!(price_offset_ticks(&mnq, dec!(21000.40), 1), dec!(21000.75));
+
+        let eur_usd = SymbolInfo::new("EUR-USD".to_string(), None, Currency::USD, dec!(1), dec!(0.00001), 5);
+        assert_eq!(price_offset_ticks(&eur_usd, dec!(1.09982), -2), dec!(1.09980));
+    }
+}
@@ -60,6 +60,10 @@ impl TimeSlice {
         self.data.values().flat_map(|v| v.iter())
     }
 
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut BaseDataEnum> {
+        self.data.values_mut().flat_map(|v| v.iter_mut())
+    }
+
     pub fn first(&self) -> Option<&BaseDataEnum> {
         self.data.values().next().and_then(|v| v.first())
     }
@@ -72,10 +76,23 @@ impl TimeSlice {
         self.data.is_empty()
     }
 
+    /// The number of data points in the slice.
+    pub fn len(&self) -> usize {
+        self.data.values().map(|v| v.len()).sum()
+    }
+
     pub fn clear(&mut self) {
         self.data.clear();
     }
 
+    /// Keeps only the data for which `keep` returns true.
+    pub fn retain(&mut self, mut keep: impl FnMut(&BaseDataEnum) -> bool) {
+        self.data.retain(|_, items| {
+            items.retain(|item| keep(item));
+            !items.is_empty()
+        });
+    }
+
     pub fn get_by_type(self, data_type: BaseDataType) -> impl Iterator<Item = BaseDataEnum> {
         self.data.into_iter().flat_map(move |(_, items)| {
             items.into_iter().filter(move |item| item.base_data_type() == data_type)
@@ -0,0 +1,134 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use lazy_static::lazy_static;
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::standardized_types::base_data::storage_migrations::renko_candle_types_migration;
+
+/// The version of the client <-> server messages.
+pub const PROTOCOL_VERSION: u32 = 10;
+
+/// The schema version of the stored historical data.
+pub const STORAGE_SCHEMA_VERSION: u32 = 3;
+
+/// The schema version of the files saved before versioning, which have no footer.
+pub const LEGACY_STORAGE_SCHEMA_VERSION: u32 = 1;
+
+const PROTOCOL_MAGIC: [u8; 4] = *b"FFWP";
+const STORAGE_MAGIC: [u8; 4] = *b"FFSD";
+const FOOTER_LENGTH: usize = 8;
+
+/// Converts a stored archive of one schema version to the archive of the next version.
+pub type StorageMigration = fn(Vec<u8>) -> Result<Vec<u8>, FundForgeError>;
+
+lazy_static! {
+    static ref STORAGE_MIGRATIONS: RwLock<BTreeMap<u32, StorageMigration>> = RwLock::new(BTreeMap::from([
+        (LEGACY_STORAGE_SCHEMA_VERSION, add_footer_migration as StorageMigration),
+        (2, renko_candle_types_migration as StorageMigration),
+    ]));
+}
+
+/// Version 2 only added the footer, the archive is unchanged.
+fn add_footer_migration(archive: Vec<u8>) -> Result<Vec<u8>, FundForgeError> {
+    Ok(archive)
+}
+
+/// Registers the migration from `from_version` to `from_version + 1`, replacing any migration registered for the version.
+pub fn register_storage_migration(from_version: u32, migration: StorageMigration) {
+    STORAGE_MIGRATIONS.write().unwrap().insert(from_version, migration);
+}
+
+fn push_footer(mut archive: Vec<u8>, magic: [u8; 4], version: u32) -> Vec<u8> {
+    archive.extend_from_slice(&magic);
+    archive.extend_from_slice(&version.to_le_bytes());
+    archive
+}
+
+/// Splits the archive from its footer, None if the bytes have no footer with the magic.
+fn split_footer(bytes: &[u8], magic: [u8; 4]) -> Option<(&[u8], u32)> {
+    if bytes.len() < FOOTER_LENGTH {
+        return None;
+    }
+    let (archive, footer) = bytes.split_at(bytes.len() - FOOTER_LENGTH);
+    if footer[..4] != magic {
+        return None;
+    }
+    let version = u32::from_le_bytes([footer[4], footer[5], footer[6], footer[7]]);
+    Some((archive, version))
+}
+
+/// Adds the protocol footer to a serialized message.
+pub fn with_protocol_version(archive: Vec<u8>) -> Vec<u8> {
+    push_footer(archive, PROTOCOL_MAGIC, PROTOCOL_VERSION)
+}
+
+/// Returns the archive of a message sent with the same protocol version.
+pub fn check_protocol_version(bytes: &[u8]) -> Result<&[u8], FundForgeError> {
+    match split_footer(bytes, PROTOCOL_MAGIC) {
+        Some((archive, PROTOCOL_VERSION)) => Ok(archive),
+        Some((_, version)) => Err(FundForgeError::IncompatibleVersion(format!(
+            "the message uses protocol version {}, this build uses version {}, the strategy and the data server must be built with the same ff_standard_lib", version, PROTOCOL_VERSION
+        ))),
+        None => Err(FundForgeError::IncompatibleVersion(format!(
+            "the message has no protocol version, it was sent by a build from before versioning, this build uses version {}", PROTOCOL_VERSION
+        ))),
+    }
+}
+
+/// Adds the storage footer to a serialized day of data.
+pub fn with_storage_version(archive: Vec<u8>) -> Vec<u8> {
+    push_footer(archive, STORAGE_MAGIC, STORAGE_SCHEMA_VERSION)
+}
+
+/// Returns the archive of a stored file migrated to `STORAGE_SCHEMA_VERSION`, borrowed if the file is already the current version.
+pub fn migrate_storage(bytes: &[u8]) -> Result<Cow<[u8]>, FundForgeError> {
+    let (archive, version) = split_footer(bytes, STORAGE_MAGIC).unwrap_or((bytes, LEGACY_STORAGE_SCHEMA_VERSION));
+    if version == STORAGE_SCHEMA_VERSION {
+        return Ok(Cow::Borrowed(archive));
+    }
+    if version > STORAGE_SCHEMA_VERSION {
+        return Err(FundForgeError::IncompatibleVersion(format!(
+            "the data was stored with schema version {}, this build reads up to version {}, update ff_standard_lib to read it", version, STORAGE_SCHEMA_VERSION
+        )));
+    }
+    let migrations = STORAGE_MIGRATIONS.read().unwrap();
+    let mut archive = archive.to_vec();
+    for from_version in version..STORAGE_SCHEMA_VERSION {
+        let migration = migrations.get(&from_version).ok_or_else(|| FundForgeError::IncompatibleVersion(format!(
+            "no migration is registered from storage schema version {}", from_version
+        )))?;
+        archive = migration(archive)?;
+    }
+    Ok(Cow::Owned(archive))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+
+    #[test]
+    fn test_protocol_version() {
+        let message = with_protocol_version(vec![1, 2, 3]);
+        assert_eq!(check_protocol_version(&message).unwrap(), &[1, 2, 3]);
+
+        let unversioned = vec![1, 2, 3];
+        assert!(matches!(check_protocol_version(&unversioned), Err(FundForgeError::IncompatibleVersion(_))));
+
+        let newer = push_footer(vec![1, 2, 3], PROTOCOL_MAGIC, PROTOCOL_VERSION + 1);
+        assert!(matches!(check_protocol_version(&newer), Err(FundForgeError::IncompatibleVersion(_))));
+    }
+
+    #[test]
+    fn test_storage_migration() {
+        let current = with_storage_version(vec![1, 2, 3]);
+        assert!(matches!(migrate_storage(&current).unwrap(), Cow::Borrowed(&[1, 2, 3])));
+
+        // files saved before versioning have no footer, they are migrated through every version
+        let legacy = BaseDataEnum::vec_to_bytes(vec![]);
+        assert_eq!(migrate_storage(&legacy).unwrap().as_ref(), legacy.as_slice());
+
+        let newer = push_footer(vec![1, 2, 3], STORAGE_MAGIC, STORAGE_SCHEMA_VERSION + 1);
+        assert!(migrate_storage(&newer).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::traits::BaseData;
+
+/// The bars of several subscriptions at one close time of the clock subscription, see `strategy.aligned_bars()`.
+/// # Properties
+/// * `time` - The close time of the clock bar.
+/// * `bars` - The bar of each subscription, in the order of the subscriptions.
+/// * `filled` - True for a bar which closed before `time`, forward filled from an earlier close.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlignedBars {
+    pub time: DateTime<Utc>,
+    pub bars: Vec<BaseDataEnum>,
+    pub filled: Vec<bool>,
+}
+
+/// Aligns the series on the close times of the `clock` series, each series is latest first like a rolling window.
+/// None if the clock has no bar at the index or a series has no bar closed by its time.
+pub(crate) fn align_bars(series: &[Vec<BaseDataEnum>], clock: usize, index: usize) -> Option<AlignedBars> {
+    let time = series.get(clock)?.get(index)?.time_closed_utc();
+    let mut aligned = AlignedBars {
+        time,
+        bars: Vec::with_capacity(series.len()),
+        filled: Vec::with_capacity(series.len()),
+    };
+    for history in series {
+        let bar = history.iter().find(|bar| bar.time_closed_utc() <= time)?;
+        aligned.filled.push(bar.time_closed_utc() < time);
+        aligned.bars.push(bar.clone());
+    }
+    Some(aligned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal::Decimal;
+    use crate::standardized_types::base_data::candle::Candle;
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::MarketType;
+    use crate::standardized_types::resolution::Resolution;
+    use crate::standardized_types::subscriptions::{CandleType, Symbol};
+
+    /// The candles of `count` bars from 09:00, latest first, opening at the minute they start.
+    fn candles(name: &str, minutes: i64, count: i64) -> Vec<BaseDataEnum> {
+        let start = Utc.with_ymd_and_hms(2024, 6, 3, 9, 0, 0).unwrap();
+        let symbol = Symbol::new(name.to_string(), DataVendor::Oanda, MarketType::Forex);
+        (0..count).rev().map(|n| {
+            let time = start + Duration::minutes(n * minutes);
+            let open = Decimal::from(n * minutes);
+            BaseDataEnum::Candle(Candle::new(symbol.clone(), open, Decimal::ZERO, Decimal::ZERO, Decimal::ZERO, time.to_string(), Resolution::Minutes(minutes as u64), CandleType::CandleStick))
+        }).collect()
+    }
+
+    fn open(bar: &BaseDataEnum) -> Decimal {
+        match bar {
+            BaseDataEnum::Candle(candle) => candle.open,
+            _ => panic!("not a candle"),
+        }
+    }
+
+    #[test]
+    fn test_align_bars() {
+        // 15 minute bars from 09:00 to 10:00 and 60 minute bars at 09:00 and 10:00
+        let series = vec![candles("EUR-USD", 15, 5), candles("EUR-USD", 60, 2)];
+
+        // the 10:15 close of the 10:00 bar, the 60 minute bar closing at 10:00 is forward filled
+        let latest = align_bars(&series, 0, 0).unwrap();
+        assert_eq!(latest.time, Utc.with_ymd_and_hms(2024, 6, 3, 10, 15, 0).unwrap());
+        assert_eq!(latest.bars.iter().map(open).collect::<Vec<_>>(), vec![Decimal::from(60), Decimal::from(0)]);
+        assert_eq!(latest.filled, vec![false, true]);
+
+        // the 10:00 close of the 09:45 bar, both close together
+        let at_ten = align_bars(&series, 0, 1).unwrap();
+        assert_eq!(at_ten.bars.iter().map(open).collect::<Vec<_>>(), vec![Decimal::from(45), Decimal::from(0)]);
+        assert_eq!(at_ten.filled, vec![false, false]);
+
+        // no 60 minute bar has closed by 09:45
+        assert_eq!(align_bars(&series, 0, 2), None);
+        assert_eq!(align_bars(&series, 0, 5), None);
+    }
+}
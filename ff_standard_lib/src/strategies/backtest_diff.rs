@@ -0,0 +1,235 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal_macros::dec;
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::standardized_types::new_types::Price;
+use crate::strategies::ledgers::ledger_snapshot::TradeExport;
+
+/// The pnl of the trades which exited on a day in each run.
+/// # Properties
+/// * `date` - The UTC date of the trade exits.
+/// * `first_pnl` - The pnl of the first run.
+/// * `second_pnl` - The pnl of the second run.
+/// * `delta` - The second pnl minus the first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DailyPnlDiff {
+    pub date: NaiveDate,
+    pub first_pnl: Price,
+    pub second_pnl: Price,
+    pub delta: Price,
+}
+
+/// The difference between the trades of two backtest runs.
+/// # Properties
+/// * `only_in_first` - Trades of the first run with no matching trade in the second.
+/// * `only_in_second` - Trades of the second run with no matching trade in the first.
+/// * `daily_pnl` - The pnl of each day either run had a trade exit, in date order.
+/// * `first_divergence` - The first exit time where the equity curves of the runs differ, None if they never differ.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BacktestDiff {
+    pub only_in_first: Vec<TradeExport>,
+    pub only_in_second: Vec<TradeExport>,
+    pub daily_pnl: Vec<DailyPnlDiff>,
+    pub first_divergence: Option<DateTime<Utc>>,
+}
+
+fn trade_key(trade: &TradeExport) -> String {
+    format!(
+        "{}|{}|{}|{}|{}|{}|{}|{}|{}|{}",
+        trade.symbol_code,
+        trade.side,
+        trade.tag,
+        trade.entry_time,
+        trade.exit_time,
+        trade.entry_price.normalize(),
+        trade.entry_quantity.normalize(),
+        trade.exit_price.normalize(),
+        trade.exit_quantity.normalize(),
+        trade.pnl.normalize()
+    )
+}
+
+fn exit_time(trade: &TradeExport) -> Option<DateTime<Utc>> {
+    DateTime::<Utc>::from_str(&trade.exit_time).ok()
+}
+
+/// The trades which have no match in `other`, each trade in `other` matches at most 1 trade.
+fn unmatched(trades: &[TradeExport], other: &[TradeExport]) -> Vec<TradeExport> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for trade in other {
+        *counts.entry(trade_key(trade)).or_insert(0) += 1;
+    }
+    let mut unmatched = Vec::new();
+    for trade in trades {
+        match counts.get_mut(&trade_key(trade)) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => unmatched.push(trade.clone()),
+        }
+    }
+    unmatched
+}
+
+fn pnl_by_exit_time(trades: &[TradeExport]) -> BTreeMap<DateTime<Utc>, Price> {
+    let mut pnl = BTreeMap::new();
+    for trade in trades {
+        if let Some(time) = exit_time(trade) {
+            *pnl.entry(time).or_insert(dec!(0)) += trade.pnl;
+        }
+    }
+    pnl
+}
+
+impl BacktestDiff {
+    pub fn new(first: &[TradeExport], second: &[TradeExport]) -> Self {
+        let first_pnl = pnl_by_exit_time(first);
+        let second_pnl = pnl_by_exit_time(second);
+
+        let mut daily: BTreeMap<NaiveDate, (Price, Price)> = BTreeMap::new();
+        for (time, pnl) in &first_pnl {
+            daily.entry(time.date_naive()).or_insert((dec!(0), dec!(0))).0 += pnl;
+        }
+        for (time, pnl) in &second_pnl {
+            daily.entry(time.date_naive()).or_insert((dec!(0), dec!(0))).1 += pnl;
+        }
+        let daily_pnl = daily
+            .into_iter()
+            .map(|(date, (first_pnl, second_pnl))| DailyPnlDiff { date, first_pnl, second_pnl, delta: second_pnl - first_pnl })
+            .collect();
+
+        // walk the exit times of both runs in order and compare the running pnl
+        let times: BTreeSet<DateTime<Utc>> = first_pnl.keys().chain(second_pnl.keys()).cloned().collect();
+        let mut first_equity = dec!(0);
+        let mut second_equity = dec!(0);
+        let mut first_divergence = None;
+        for time in times {
+            first_equity += first_pnl.get(&time).cloned().unwrap_or(dec!(0));
+            second_equity += second_pnl.get(&time).cloned().unwrap_or(dec!(0));
+            if first_equity != second_equity {
+                first_divergence = Some(time);
+                break;
+            }
+        }
+
+        BacktestDiff {
+            only_in_first: unmatched(first, second),
+            only_in_second: unmatched(second, first),
+            daily_pnl,
+            first_divergence,
+        }
+    }
+
+    /// Compares 2 trade exports written by `export_trades_to_csv()`.
+    pub fn from_csv(first_file: &str, second_file: &str) -> Result<Self, FundForgeError> {
+        let first = load_trade_export(first_file)?;
+        let second = load_trade_export(second_file)?;
+        Ok(BacktestDiff::new(&first, &second))
+    }
+
+    /// true if every trade matched and the equity curves never diverged.
+    pub fn is_identical(&self) -> bool {
+        self.only_in_first.is_empty() && self.only_in_second.is_empty() && self.first_divergence.is_none()
+    }
+
+    /// The days where the pnl of the runs differ.
+    pub fn changed_days(&self) -> Vec<&DailyPnlDiff> {
+        self.daily_pnl.iter().filter(|day| day.delta != dec!(0)).collect()
+    }
+}
+
+/// Reads a trade export written by `export_trades_to_csv()`.
+pub fn load_trade_export(file: &str) -> Result<Vec<TradeExport>, FundForgeError> {
+    let mut reader = csv::Reader::from_path(file)
+        .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to open trade export {}: {}", file, e)))?;
+    let mut trades = Vec::new();
+    for record in reader.deserialize() {
+        let trade: TradeExport = record
+            .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to read trade export {}: {}", file, e)))?;
+        trades.push(trade);
+    }
+    Ok(trades)
+}
+
+impl fmt::Display for BacktestDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_identical() {
+            return write!(f, "Backtest Diff: Identical");
+        }
+        writeln!(f, "Backtest Diff:")?;
+        match self.first_divergence {
+            Some(time) => writeln!(f, "Equity Curves Diverge At: {}", time)?,
+            None => writeln!(f, "Equity Curves Match")?,
+        }
+        writeln!(f, "Trades Only In First: {}", self.only_in_first.len())?;
+        for trade in &self.only_in_first {
+            writeln!(f, "  {} {} {} @ {} -> {} @ {}, PnL: {}, Tag: {}", trade.symbol_code, trade.side, trade.entry_time, trade.entry_price, trade.exit_time, trade.exit_price, trade.pnl, trade.tag)?;
+        }
+        writeln!(f, "Trades Only In Second: {}", self.only_in_second.len())?;
+        for trade in &self.only_in_second {
+            writeln!(f, "  {} {} {} @ {} -> {} @ {}, PnL: {}, Tag: {}", trade.symbol_code, trade.side, trade.entry_time, trade.entry_price, trade.exit_time, trade.exit_price, trade.pnl, trade.tag)?;
+        }
+        writeln!(f, "Changed Days:")?;
+        for day in self.changed_days() {
+            writeln!(f, "  {}: First: {}, Second: {}, Delta: {}", day.date, day.first_pnl.round_dp(2), day.second_pnl.round_dp(2), day.delta.round_dp(2))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn trade(position_id: &str, exit_time: &str, pnl: Decimal) -> TradeExport {
+        TradeExport {
+            symbol_code: "NQZ4".to_string(),
+            position_id: position_id.to_string(),
+            side: "Long".to_string(),
+            entry_price: dec!(20000),
+            entry_quantity: dec!(1),
+            exit_price: dec!(20010),
+            exit_quantity: dec!(1),
+            entry_time: "2024-06-03 14:00:00 UTC".to_string(),
+            exit_time: exit_time.to_string(),
+            pnl,
+            tag: "Enter Long".to_string(),
+            result: "Win".to_string(),
+            fingerprint: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_identical_runs_ignore_position_ids() {
+        let first = vec![trade("a", "2024-06-03 15:00:00 UTC", dec!(200))];
+        let second = vec![trade("b", "2024-06-03 15:00:00 UTC", dec!(200.00))];
+        let diff = BacktestDiff::new(&first, &second);
+        assert!(diff.is_identical());
+        assert!(diff.changed_days().is_empty());
+    }
+
+    #[test]
+    fn test_diverging_runs() {
+        let first = vec![
+            trade("a", "2024-06-03 15:00:00 UTC", dec!(200)),
+            trade("b", "2024-06-04 15:00:00 UTC", dec!(100)),
+        ];
+        let second = vec![
+            trade("c", "2024-06-03 15:00:00 UTC", dec!(200)),
+            trade("d", "2024-06-04 16:00:00 UTC", dec!(100)),
+            trade("e", "2024-06-05 15:00:00 UTC", dec!(-50)),
+        ];
+        let diff = BacktestDiff::new(&first, &second);
+        assert!(!diff.is_identical());
+        assert_eq!(diff.only_in_first.len(), 1);
+        assert_eq!(diff.only_in_first[0].position_id, "b");
+        assert_eq!(diff.only_in_second.len(), 2);
+        assert_eq!(diff.first_divergence, Some(DateTime::<Utc>::from_str("2024-06-04 15:00:00 UTC").unwrap()));
+
+        let changed = diff.changed_days();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].date, NaiveDate::from_ymd_opt(2024, 6, 5).unwrap());
+        assert_eq!(changed[0].delta, dec!(-50));
+    }
+}
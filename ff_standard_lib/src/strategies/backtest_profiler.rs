@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref PROFILING_ENABLED: AtomicBool = AtomicBool::new(false);
+    static ref LAST_PROFILE: Mutex<Option<BacktestProfile>> = Mutex::new(None);
+}
+
+/// Enables or disables profiling for the next backtest, call before `FundForgeStrategy::initialize()`.
+/// ```rust
+/// set_backtest_profiling(true);
+/// let strategy = FundForgeStrategy::initialize(...).await.unwrap();
+/// ```
+pub fn set_backtest_profiling(enabled: bool) {
+    PROFILING_ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/// The profile of the last backtest run with profiling enabled, None until it is complete or stopped.
+pub fn backtest_profile() -> Option<BacktestProfile> {
+    LAST_PROFILE.lock().unwrap().clone()
+}
+
+/// The stages of the historical engine loop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProfiledStage {
+    /// Loading and decompressing each day of historical data.
+    DataLoading,
+    /// Updating the consolidators and the retained history.
+    Consolidation,
+    /// Updating the indicators with the consolidated data.
+    Indicators,
+    /// The timed events, session hooks and the engine rules of the timed event handler.
+    TimedEvents,
+    /// Updating the market prices, price triggers and the backtest matching engine.
+    OrderMatching,
+    /// Updating the open positions of the ledgers.
+    Ledgers,
+    /// Waiting for the strategy to handle its events.
+    StrategyEvents,
+}
+
+/// # Properties
+/// * `stage` - The engine stage.
+/// * `time` - The total wall time spent in the stage.
+/// * `calls` - The number of times the stage ran.
+/// * `peak_memory_bytes` - The highest estimated memory held by the subsystem, None for stages which hold no data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StageProfile {
+    pub stage: ProfiledStage,
+    pub time: Duration,
+    pub calls: u64,
+    pub peak_memory_bytes: Option<usize>,
+}
+
+/// # Properties
+/// * `wall_time` - The wall time of the backtest including the warm up.
+/// * `stages` - The profile of each stage, in the order of `ProfiledStage`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BacktestProfile {
+    pub wall_time: Duration,
+    pub stages: Vec<StageProfile>,
+}
+
+impl BacktestProfile {
+    /// The wall time which was not spent in a profiled stage.
+    pub fn unprofiled_time(&self) -> Duration {
+        let profiled: Duration = self.stages.iter().map(|stage| stage.time).sum();
+        self.wall_time.saturating_sub(profiled)
+    }
+
+    /// The time spent in the engine, every stage except `StrategyEvents`.
+    pub fn engine_time(&self) -> Duration {
+        self.stages.iter().filter(|stage| stage.stage != ProfiledStage::StrategyEvents).map(|stage| stage.time).sum()
+    }
+}
+
+impl Display for BacktestProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let pct = |time: Duration| match self.wall_time.is_zero() {
+            true => 0.0,
+            false => time.as_secs_f64() / self.wall_time.as_secs_f64() * 100.0,
+        };
+        writeln!(f, "Backtest Profile: {:.2}s wall time", self.wall_time.as_secs_f64())?;
+        writeln!(f, "{:<16} {:>10} {:>7} {:>12} {:>14}", "Stage", "Time (s)", "%", "Calls", "Peak Memory")?;
+        for stage in &self.stages {
+            let memory = match stage.peak_memory_bytes {
+                Some(bytes) => format!("{:.2} MB", bytes as f64 / 1_048_576.0),
+                None => "-".to_string(),
+            };
+            writeln!(f, "{:<16} {:>10.3} {:>6.1}% {:>12} {:>14}", format!("{:?}", stage.stage), stage.time.as_secs_f64(), pct(stage.time), stage.calls, memory)?;
+        }
+        write!(f, "{:<16} {:>10.3} {:>6.1}%", "Unprofiled", self.unprofiled_time().as_secs_f64(), pct(self.unprofiled_time()))
+    }
+}
+
+/// How often the engine samples the memory of the subsystems.
+const MEMORY_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The timings of a backtest, owned by the historical engine.
+pub(crate) struct BacktestProfiler {
+    enabled: bool,
+    started: Instant,
+    last_memory_sample: Option<Instant>,
+    times: BTreeMap<ProfiledStage, (Duration, u64)>,
+    peak_memory: BTreeMap<ProfiledStage, usize>,
+}
+
+impl BacktestProfiler {
+    pub(crate) fn new() -> Self {
+        BacktestProfiler {
+            enabled: PROFILING_ENABLED.load(Ordering::SeqCst),
+            started: Instant::now(),
+            last_memory_sample: None,
+            times: BTreeMap::new(),
+            peak_memory: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// The start of a stage, None when profiling is disabled.
+    pub(crate) fn start(&self) -> Option<Instant> {
+        match self.enabled {
+            true => Some(Instant::now()),
+            false => None,
+        }
+    }
+
+    /// Adds the time since `started` to the stage.
+    pub(crate) fn record(&mut self, stage: ProfiledStage, started: Option<Instant>) {
+        if let Some(started) = started {
+            let (time, calls) = self.times.entry(stage).or_insert((Duration::ZERO, 0));
+            *time += started.elapsed();
+            *calls += 1;
+        }
+    }
+
+    /// True once per sample interval while profiling is enabled.
+    pub(crate) fn memory_sample_due(&mut self) -> bool {
+        if !self.enabled || self.last_memory_sample.map_or(false, |last| last.elapsed() < MEMORY_SAMPLE_INTERVAL) {
+            return false;
+        }
+        self.last_memory_sample = Some(Instant::now());
+        true
+    }
+
+    pub(crate) fn sample_memory(&mut self, stage: ProfiledStage, bytes: usize) {
+        if !self.enabled {
+            return;
+        }
+        let peak = self.peak_memory.entry(stage).or_insert(0);
+        *peak = (*peak).max(bytes);
+    }
+
+    /// Publishes the profile for `backtest_profile()`, None when profiling is disabled.
+    pub(crate) fn finish(&self) -> Option<BacktestProfile> {
+        if !self.enabled {
+            return None;
+        }
+        let mut stages: Vec<ProfiledStage> = self.times.keys().chain(self.peak_memory.keys()).cloned().collect();
+        stages.sort();
+        stages.dedup();
+        let profile = BacktestProfile {
+            wall_time: self.started.elapsed(),
+            stages: stages.into_iter().map(|stage| {
+                let (time, calls) = self.times.get(&stage).cloned().unwrap_or((Duration::ZERO, 0));
+                StageProfile { stage, time, calls, peak_memory_bytes: self.peak_memory.get(&stage).cloned() }
+            }).collect(),
+        };
+        *LAST_PROFILE.lock().unwrap() = Some(profile.clone());
+        Some(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiler_totals() {
+        let mut profiler = BacktestProfiler::new();
+        profiler.enabled = true;
+        let started = Instant::now();
+        std::thread::sleep(Duration::from_millis(20));
+        profiler.record(ProfiledStage::Indicators, Some(started));
+        profiler.record(ProfiledStage::Indicators, Some(Instant::now()));
+        profiler.record(ProfiledStage::DataLoading, None);
+        profiler.sample_memory(ProfiledStage::Consolidation, 2048);
+        profiler.sample_memory(ProfiledStage::Consolidation, 1024);
+
+        let profile = profiler.finish().unwrap();
+        assert_eq!(profile.stages.len(), 2);
+        let indicators = &profile.stages[1];
+        assert_eq!(indicators.stage, ProfiledStage::Indicators);
+        assert_eq!(indicators.calls, 2);
+        assert!(indicators.time >= Duration::from_millis(20));
+        assert_eq!(profile.stages[0].peak_memory_bytes, Some(2048));
+        assert!(profile.engine_time() <= profile.wall_time);
+        assert_eq!(backtest_profile(), Some(profile));
+    }
+}
@@ -0,0 +1,243 @@
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
+use serde_derive::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+/// How often the engine publishes the progress.
+pub(crate) const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+lazy_static! {
+    static ref PROGRESS_CHANNEL: (watch::Sender<Option<BacktestProgress>>, watch::Receiver<Option<BacktestProgress>>) = watch::channel(None);
+}
+
+/// # Properties
+/// * `pct` - The percentage of the backtest period processed, 0.0 to 100.0, the warm up is not included.
+/// * `current_time` - The backtest time as a utc string.
+/// * `events_per_sec` - The base data processed per second of wall time since the warm up completed.
+/// * `eta_seconds` - The estimated wall time until the backtest is complete, None until there is enough progress to estimate.
+/// * `finished` - True for the last progress of the backtest, when it is complete or was stopped early with `strategy.request_stop()`.
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Serialize, Deserialize)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct BacktestProgress {
+    pub pct: f64,
+    pub current_time: String,
+    pub events_per_sec: f64,
+    pub eta_seconds: Option<u64>,
+    pub finished: bool,
+}
+
+impl BacktestProgress {
+    pub fn current_time_utc(&self) -> Option<DateTime<Utc>> {
+        self.current_time.parse().ok()
+    }
+
+    pub fn eta(&self) -> Option<Duration> {
+        self.eta_seconds.map(Duration::from_secs)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.pct >= 100.0
+    }
+}
+
+impl Display for BacktestProgress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.1}% {} {:.0} events/s", self.pct, self.current_time, self.events_per_sec)?;
+        if let Some(eta) = self.eta_seconds {
+            write!(f, " ETA {}:{:02}:{:02}", eta / 3600, eta % 3600 / 60, eta % 60)?;
+        }
+        Ok(())
+    }
+}
+
+/// A handle to the latest backtest progress, cheap to clone and send to other tasks.
+///
+/// # Example
+/// ```rust
+/// let mut progress = backtest_progress();
+/// tokio::spawn(async move {
+///     while let Some(update) = progress.changed().await {
+///         progress_bar.set_position(update.pct as u64);
+///         if update.finished {
+///             break;
+///         }
+///     }
+/// });
+/// ```
+#[derive(Clone)]
+pub struct BacktestProgressHandle {
+    receiver: watch::Receiver<Option<BacktestProgress>>,
+}
+
+impl BacktestProgressHandle {
+    /// The last progress published, None before the warm up is complete.
+    pub fn latest(&self) -> Option<BacktestProgress> {
+        self.receiver.borrow().clone()
+    }
+
+    /// Waits for the next progress.
+    pub async fn changed(&mut self) -> Option<BacktestProgress> {
+        loop {
+            if self.receiver.changed().await.is_err() {
+                return None;
+            }
+            if let Some(progress) = self.receiver.borrow_and_update().clone() {
+                return Some(progress);
+            }
+        }
+    }
+}
+
+/// Returns a handle to the progress of the backtest running in this process.
+pub fn backtest_progress() -> BacktestProgressHandle {
+    BacktestProgressHandle {
+        receiver: PROGRESS_CHANNEL.1.clone(),
+    }
+}
+
+/// Tracks the progress of the historical engine over the backtest periods, either the start to end date or each regime.
+pub(crate) struct ProgressTracker {
+    periods: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    started: Option<Instant>,
+    last_published: Option<Instant>,
+    events: u64,
+}
+
+impl ProgressTracker {
+    pub(crate) fn new(periods: Vec<(DateTime<Utc>, DateTime<Utc>)>) -> Self {
+        // a new backtest starts from no progress
+        PROGRESS_CHANNEL.0.send_replace(None);
+        ProgressTracker {
+            periods,
+            started: None,
+            last_published: None,
+            events: 0,
+        }
+    }
+
+    /// Starts the wall clock, call this once the warm up is complete.
+    pub(crate) fn start(&mut self) {
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+        }
+    }
+
+    pub(crate) fn record_events(&mut self, count: usize) {
+        if self.started.is_some() {
+            self.events += count as u64;
+        }
+    }
+
+    /// Returns the progress if it is due to be published.
+    pub(crate) fn due(&mut self, time: DateTime<Utc>) -> Option<BacktestProgress> {
+        let started = self.started?;
+        let now = Instant::now();
+        if self.last_published.map_or(now.duration_since(started) < PROGRESS_INTERVAL, |last| now.duration_since(last) < PROGRESS_INTERVAL) {
+            return None;
+        }
+        self.last_published = Some(now);
+        Some(self.progress(time, now.duration_since(started)))
+    }
+
+    pub(crate) fn complete(&self, time: DateTime<Utc>) -> BacktestProgress {
+        let elapsed = self.started.map(|started| started.elapsed()).unwrap_or_default();
+        let mut progress = self.progress(time, elapsed);
+        progress.pct = 100.0;
+        progress.eta_seconds = Some(0);
+        progress.finished = true;
+        progress
+    }
+
+    /// The progress when the backtest is stopped early, there is nothing remaining.
+    pub(crate) fn stopped(&self, time: DateTime<Utc>) -> BacktestProgress {
+        let elapsed = self.started.map(|started| started.elapsed()).unwrap_or_default();
+        let mut progress = self.progress(time, elapsed);
+        progress.eta_seconds = Some(0);
+        progress.finished = true;
+        progress
+    }
+
+    fn progress(&self, time: DateTime<Utc>, elapsed: Duration) -> BacktestProgress {
+        let pct = completed_fraction(&self.periods, time) * 100.0;
+        let elapsed_secs = elapsed.as_secs_f64();
+        let events_per_sec = match elapsed_secs > 0.0 {
+            true => self.events as f64 / elapsed_secs,
+            false => 0.0,
+        };
+        let eta_seconds = match pct > 0.0 {
+            true => Some((elapsed_secs * (100.0 - pct) / pct).round() as u64),
+            false => None,
+        };
+        BacktestProgress {
+            pct,
+            current_time: time.to_string(),
+            events_per_sec,
+            eta_seconds,
+            finished: false,
+        }
+    }
+
+    pub(crate) fn publish(progress: &BacktestProgress) {
+        PROGRESS_CHANNEL.0.send_replace(Some(progress.clone()));
+    }
+}
+
+/// The fraction of the total backtest time before `time`, the periods are sorted and do not overlap.
+fn completed_fraction(periods: &[(DateTime<Utc>, DateTime<Utc>)], time: DateTime<Utc>) -> f64 {
+    let mut total = 0i64;
+    let mut completed = 0i64;
+    for (start, end) in periods {
+        let length = (*end - *start).num_seconds().max(0);
+        total += length;
+        completed += (time - *start).num_seconds().clamp(0, length);
+    }
+    match total > 0 {
+        true => completed as f64 / total as f64,
+        false => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_completed_fraction() {
+        let day = |d: u32| Utc.with_ymd_and_hms(2024, 1, d, 0, 0, 0).unwrap();
+        let single = vec![(day(1), day(11))];
+        assert_eq!(completed_fraction(&single, day(1) - chrono::Duration::days(3)), 0.0);
+        assert_eq!(completed_fraction(&single, day(6)), 0.5);
+        assert_eq!(completed_fraction(&single, day(20)), 1.0);
+
+        // the gap between regimes is not part of the backtest
+        let regimes = vec![(day(1), day(3)), (day(10), day(12))];
+        assert_eq!(completed_fraction(&regimes, day(3)), 0.5);
+        assert_eq!(completed_fraction(&regimes, day(8)), 0.5);
+        assert_eq!(completed_fraction(&regimes, day(11)), 0.75);
+    }
+
+    #[test]
+    fn test_progress_eta() {
+        let day = |d: u32| Utc.with_ymd_and_hms(2024, 1, d, 0, 0, 0).unwrap();
+        let mut tracker = ProgressTracker::new(vec![(day(1), day(5))]);
+        tracker.record_events(100);
+        assert_eq!(tracker.events, 0);
+        tracker.start();
+        tracker.record_events(1000);
+
+        let progress = tracker.progress(day(2), Duration::from_secs(10));
+        assert_eq!(progress.pct, 25.0);
+        assert_eq!(progress.events_per_sec, 100.0);
+        assert_eq!(progress.eta(), Some(Duration::from_secs(30)));
+        assert_eq!(progress.current_time_utc(), Some(day(2)));
+        assert!(tracker.progress(day(1), Duration::from_secs(10)).eta_seconds.is_none());
+        assert!(tracker.complete(day(5)).is_complete());
+        let stopped = tracker.stopped(day(3));
+        assert!(stopped.finished && !stopped.is_complete());
+    }
+}
@@ -3,6 +3,7 @@ use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use tokio::sync::oneshot;
 use crate::messages::data_server_messaging::{DataServerRequest, DataServerResponse, FundForgeError};
+use crate::product_maps::alpaca::maps::get_equity_symbol_info;
 use crate::product_maps::oanda::maps::{get_oanda_symbol_names, OANDA_SYMBOL_INFO, SYMBOL_DIVISORS};
 use crate::product_maps::rithmic::maps::{find_base_symbol, get_available_rithmic_symbol_names, get_rithmic_intraday_margin_in_usd, get_futures_symbol_info};
 use crate::standardized_types::accounts::{AccountId, AccountInfo, Currency};
@@ -60,6 +61,12 @@ impl Brokerage {
                 let base_margin = quantity * price; // Calculate margin in position currency
                 Ok(Some(base_margin * conversion_rate)) // Convert to account currency
             }
+
+            // Alpaca margin accounts get 4x intraday buying power on equities
+            Brokerage::Alpaca => {
+                let base_margin = quantity * price;
+                Ok(Some(base_margin * conversion_rate / dec!(4)))
+            }
         }
     }
 
@@ -87,6 +94,7 @@ impl Brokerage {
                     None => Err(FundForgeError::ClientSideErrorDebug(format!("Symbol info not found for symbol: {}", symbol_name)))
                 }
             }
+            Brokerage::Alpaca => Ok(get_equity_symbol_info(&symbol_name)),
             _ => {
                 let request = DataServerRequest::SymbolInfo {
                     callback_id: 0,
@@ -1,12 +1,14 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use tokio::sync::oneshot;
 use crate::messages::data_server_messaging::{DataServerRequest, DataServerResponse, FundForgeError};
+use crate::product_maps::alpaca::maps::get_equity_symbol_info;
 use crate::product_maps::oanda::maps::OANDA_SYMBOL_INFO;
 use crate::product_maps::rithmic::maps::get_futures_symbol_info;
 use crate::standardized_types::base_data::base_data_type::BaseDataType;
 use crate::standardized_types::datavendor_enum::DataVendor;
 use crate::standardized_types::enums::{MarketType, PrimarySubscription};
 use crate::standardized_types::new_types::Price;
+use crate::standardized_types::options::OptionQuote;
 use crate::standardized_types::subscriptions::{Symbol, SymbolName};
 use crate::strategies::client_features::connection_types::ConnectionType;
 use crate::strategies::client_features::request_handler::{send_request, StrategyRequest};
@@ -134,6 +136,7 @@ impl DataVendor {
                     None => Err(FundForgeError::ClientSideErrorDebug("Error getting decimal accuracy Symbol not found in OANDA_SYMBOL_INFO".to_string()))
                 };
             }
+            DataVendor::Alpaca => return Ok(get_equity_symbol_info(&symbol_name).decimal_accuracy),
             _ => {}
         }
 
@@ -173,6 +176,8 @@ impl DataVendor {
                     None => Err(FundForgeError::ClientSideErrorDebug("Symbol not found in OANDA_SYMBOL_INFO".to_string()))
                 };
             }
+            DataVendor::Alpaca => return Ok(get_equity_symbol_info(&symbol_name).tick_size),
+            DataVendor::Polygon => {}
         }
 
         //if we don't have local map check with server
@@ -195,4 +200,28 @@ impl DataVendor {
             Err(e) => Err(FundForgeError::ClientSideErrorDebug(format!("Receiver error at callback recv: {}", e)))
         }
     }
+
+    /// Returns the option chain for the underlying, with the greeks as supplied by the vendor, all expiries are returned if `expiry` is None.
+    /// Returns an error if the vendor does not support options.
+    pub async fn option_chain(&self, underlying: SymbolName, expiry: Option<NaiveDate>) -> Result<Vec<OptionQuote>, FundForgeError> {
+        let request = DataServerRequest::OptionChain {
+            callback_id: 0,
+            data_vendor: self.clone(),
+            underlying,
+            expiry: expiry.map(|expiry| expiry.format("%Y-%m-%d").to_string()),
+        };
+        let (sender, receiver) = oneshot::channel();
+        let msg = StrategyRequest::CallBack(ConnectionType::Vendor(self.clone()), request,sender);
+        send_request(msg).await;
+        match receiver.await {
+            Ok(response) => {
+                match response {
+                    DataServerResponse::OptionChain { quotes, .. } => Ok(quotes),
+                    DataServerResponse::Error {error,..} => Err(error),
+                    _ => Err(FundForgeError::ClientSideErrorDebug("Incorrect response received at callback".to_string()))
+                }
+            },
+            Err(e) => Err(FundForgeError::ClientSideErrorDebug(format!("Receiver error at callback recv: {}", e)))
+        }
+    }
 }
\ No newline at end of file
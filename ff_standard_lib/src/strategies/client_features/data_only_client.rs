@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsStream;
+use crate::messages::data_server_messaging::{DataServerRequest, DataServerResponse, FundForgeError};
+use crate::standardized_types::bytes_trait::Bytes;
+use crate::strategies::client_features::connection_types::ConnectionType;
+use crate::strategies::client_features::init_clients::create_async_api_client;
+use crate::strategies::client_features::server_connections::SETTINGS_MAP;
+use crate::strategies::indicators::indicators_trait::IndicatorName;
+use crate::strategies::indicators::indicator_values::IndicatorValues;
+use crate::StreamName;
+
+/// A connection to the default data server for market data only.
+/// ```rust
+/// let mut client = DataOnlyClient::connect().await?;
+/// let response = client.request(DataServerRequest::DataQuality { callback_id: 0, symbol_name: None }).await?;
+/// ```
+pub struct DataOnlyClient {
+    stream: TlsStream<TcpStream>,
+    next_callback_id: u64,
+    indicator_updates: VecDeque<(StreamName, Vec<IndicatorValues>)>,
+}
+
+impl DataOnlyClient {
+    /// Connects to the default connection in server_settings.toml and registers as data only.
+    pub async fn connect() -> Result<Self, FundForgeError> {
+        let settings = match SETTINGS_MAP.get(&ConnectionType::Default) {
+            Some(settings) => settings,
+            None => return Err(FundForgeError::ClientSideErrorDebug("No default connection in server_settings.toml".to_string()))
+        };
+        let stream = create_async_api_client(settings, false).await?;
+        let mut client = DataOnlyClient {
+            stream,
+            next_callback_id: 1,
+            indicator_updates: VecDeque::new(),
+        };
+        client.send(&DataServerRequest::RegisterDataOnly).await?;
+        Ok(client)
+    }
+
+    async fn send(&mut self, request: &DataServerRequest) -> Result<(), FundForgeError> {
+        let data = request.to_bytes();
+        let mut prefixed_msg = Vec::with_capacity(4 + data.len());
+        prefixed_msg.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        prefixed_msg.extend_from_slice(&data);
+        self.stream.write_all(&prefixed_msg).await
+            .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Error sending message: {}", e)))
+    }
+
+    async fn read(&mut self) -> Result<DataServerResponse, FundForgeError> {
+        let mut length_bytes = [0u8; 8];
+        if let Err(e) = self.stream.read_exact(&mut length_bytes).await {
+            return Err(FundForgeError::ClientSideErrorDebug(format!("Error reading response: {}", e)));
+        }
+        let mut message_body = vec![0u8; u64::from_be_bytes(length_bytes) as usize];
+        if let Err(e) = self.stream.read_exact(&mut message_body).await {
+            return Err(FundForgeError::ClientSideErrorDebug(format!("Error reading response: {}", e)));
+        }
+        DataServerResponse::from_bytes(&message_body)
+    }
+
+    /// Sends a callback request and waits for its response, a `DataServerResponse::Error` is returned as the error.
+    /// One way requests are refused, they have no response to wait for.
+    pub async fn request(&mut self, mut request: DataServerRequest) -> Result<DataServerResponse, FundForgeError> {
+        if request.callback_id().is_none() {
+            return Err(FundForgeError::ClientSideErrorDebug(format!("Data only clients only send callback requests: {:?}", request)));
+        }
+        let callback_id = self.next_callback_id;
+        self.next_callback_id += 1;
+        request.set_callback_id(callback_id);
+        self.send(&request).await?;
+        loop {
+            match self.read().await? {
+                DataServerResponse::Error { callback_id: id, error } if id == callback_id => return Err(error),
+                DataServerResponse::AsyncError { error } => return Err(error),
+                DataServerResponse::IndicatorStreamUpdate { stream_name, values } => self.indicator_updates.push_back((stream_name, values)),
+                response if response.get_callback_id() == Some(callback_id) => return Ok(response),
+                _ => continue,
+            }
+        }
+    }
+
+    /// Subscribes to an indicator published by a running strategy, see `DataServerRequest::IndicatorStreams` for the published indicators.
+    /// Returns the values the server holds for the indicator, oldest first, the new values are received with `next_indicator_update()`.
+    /// ```rust
+    /// let mut client = DataOnlyClient::connect().await?;
+    /// let history = client.subscribe_indicator(stream.stream_name, stream.indicator_name.clone()).await?;
+    /// while let Ok((stream_name, values)) = client.next_indicator_update().await {
+    ///     chart.add_values(stream_name, values);
+    /// }
+    /// ```
+    pub async fn subscribe_indicator(&mut self, stream_name: StreamName, indicator_name: IndicatorName) -> Result<Vec<IndicatorValues>, FundForgeError> {
+        match self.request(DataServerRequest::SubscribeIndicatorStream { callback_id: 0, stream_name, indicator_name }).await? {
+            DataServerResponse::IndicatorHistory { values, .. } => Ok(values),
+            _ => Err(FundForgeError::ClientSideErrorDebug("Incorrect response received at callback".to_string()))
+        }
+    }
+
+    pub async fn unsubscribe_indicator(&mut self, stream_name: StreamName, indicator_name: IndicatorName) -> Result<(), FundForgeError> {
+        self.indicator_updates.retain(|(name, values)| *name != stream_name || values.iter().all(|value| value.name != indicator_name));
+        self.send(&DataServerRequest::UnsubscribeIndicatorStream { stream_name, indicator_name }).await
+    }
+
+    /// Waits for the next values of a subscribed indicator, returns the connection of the strategy which published them and the values, oldest first.
+    pub async fn next_indicator_update(&mut self) -> Result<(StreamName, Vec<IndicatorValues>), FundForgeError> {
+        if let Some(update) = self.indicator_updates.pop_front() {
+            return Ok(update);
+        }
+        loop {
+            match self.read().await? {
+                DataServerResponse::IndicatorStreamUpdate { stream_name, values } => return Ok((stream_name, values)),
+                DataServerResponse::AsyncError { error } => return Err(error),
+                _ => continue,
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use tokio::sync::mpsc;
+use crate::database::hybrid_storage::HybridStorage;
+use crate::messages::data_server_messaging::{DataServerRequest, DataServerResponse, FundForgeError};
+use crate::product_maps::rithmic::maps::{find_base_symbol, get_futures_commissions_info};
+use crate::server_launch_options::ServerLaunchOptions;
+use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::broker_enum::Brokerage;
+use crate::standardized_types::datavendor_enum::DataVendor;
+use crate::standardized_types::enums::{MarketType, PrimarySubscription};
+use crate::standardized_types::resolution::Resolution;
+use crate::strategies::client_features::request_handler::StrategyRequest;
+
+lazy_static! {
+    static ref EMBEDDED_DATA_FOLDER: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+/// Runs the next backtest on the historical store of the data folder without a data server, None connects to the data server. Call before `FundForgeStrategy::initialize()`.
+/// ```rust
+/// set_embedded_data_folder(Some(PathBuf::from("../ff_data_server/data")));
+/// let strategy = FundForgeStrategy::initialize(...).await.unwrap();
+/// ```
+pub fn set_embedded_data_folder(folder: Option<PathBuf>) {
+    *EMBEDDED_DATA_FOLDER.write().unwrap() = folder;
+}
+
+pub(crate) fn embedded_data_folder() -> Option<PathBuf> {
+    EMBEDDED_DATA_FOLDER.read().unwrap().clone()
+}
+
+/// Answers the strategy requests from the historical store of the data folder in place of the data server connections.
+pub(crate) fn embedded_request_handler(data_folder: PathBuf, mut receiver: mpsc::Receiver<StrategyRequest>) {
+    let options = ServerLaunchOptions {
+        data_folder,
+        ..Default::default()
+    };
+    let storage = Arc::new(HybridStorage::new(Duration::from_secs(1800), options, 1, 0));
+    println!("Embedded Data: Reading the historical store at {:?}", storage.base_path);
+    tokio::task::spawn(async move {
+        while let Some(outgoing_message) = receiver.recv().await {
+            match outgoing_message {
+                StrategyRequest::CallBack(_, request, oneshot) => {
+                    let storage = storage.clone();
+                    tokio::task::spawn(async move {
+                        let _ = oneshot.send(embedded_response(&storage, request).await);
+                    });
+                }
+                // registrations and streams have no meaning without a data server
+                StrategyRequest::OneWay(_, _) => {}
+            }
+        }
+    });
+}
+
+async fn embedded_response(storage: &HybridStorage, request: DataServerRequest) -> DataServerResponse {
+    let callback_id = request.callback_id().unwrap_or(0);
+    let error = |message: String| DataServerResponse::Error { callback_id, error: FundForgeError::ClientSideErrorDebug(message) };
+    match request {
+        DataServerRequest::GetCompressedHistoricalData { subscriptions, from_time, to_time, .. } => {
+            let (from_time, to_time) = match (DateTime::<Utc>::from_str(&from_time), DateTime::<Utc>::from_str(&to_time)) {
+                (Ok(from_time), Ok(to_time)) => (from_time, to_time),
+                _ => return error(format!("Embedded Data: Invalid time range: {} to {}", from_time, to_time)),
+            };
+            match storage.get_compressed_files_in_range(subscriptions, from_time, to_time).await {
+                Ok(payload) => DataServerResponse::CompressedHistoricalData { callback_id, payload },
+                Err(e) => DataServerResponse::Error { callback_id, error: e },
+            }
+        }
+        DataServerRequest::Resolutions { data_vendor, market_type, .. } | DataServerRequest::WarmUpResolutions { data_vendor, market_type, .. } => {
+            DataServerResponse::Resolutions {
+                callback_id,
+                subscription_resolutions_types: stored_resolutions(storage, &data_vendor, &market_type),
+                market_type,
+            }
+        }
+        DataServerRequest::ExchangeRate { from_currency, to_currency, date_time_string, data_vendor, side, .. } => {
+            let time = match DateTime::<Utc>::from_str(&date_time_string) {
+                Ok(time) => time,
+                Err(_) => return error(format!("Embedded Data: Invalid time: {}", date_time_string)),
+            };
+            match storage.get_exchange_rate(from_currency, to_currency, time, data_vendor, side).await {
+                Ok(rate) => DataServerResponse::ExchangeRate { callback_id, rate },
+                Err(e) => error(format!("Embedded Data: Exchange rate not found: {}", e)),
+            }
+        }
+        // the brokerages with a product map answer locally, the others would send the request back here
+        DataServerRequest::SymbolInfo { brokerage, symbol_name, .. } => match brokerage {
+            Brokerage::Rithmic(_) | Brokerage::Oanda | Brokerage::Alpaca => match brokerage.symbol_info(symbol_name).await {
+                Ok(symbol_info) => DataServerResponse::SymbolInfo { callback_id, symbol_info },
+                Err(e) => DataServerResponse::Error { callback_id, error: e },
+            },
+            _ => error(format!("Embedded Data: No symbol info for {} without a data server: {}", brokerage, symbol_name)),
+        },
+        DataServerRequest::CommissionInfo { brokerage: Brokerage::Rithmic(_), symbol_name, .. } => {
+            let symbol_name = find_base_symbol(&symbol_name).unwrap_or(symbol_name);
+            match get_futures_commissions_info(&symbol_name) {
+                Ok(commission_info) => DataServerResponse::CommissionInfo { callback_id, commission_info },
+                Err(e) => error(e),
+            }
+        }
+        request => error(format!("Embedded Data: The request needs a data server: {:?}", request)),
+    }
+}
+
+/// The resolutions and data types stored for any symbol of the vendor and market type, read from `historical/{vendor}/{market type}/{symbol}/{resolution}/{data type}`.
+fn stored_resolutions(storage: &HybridStorage, data_vendor: &DataVendor, market_type: &MarketType) -> Vec<PrimarySubscription> {
+    let market_path = storage.base_path.join(data_vendor.to_string()).join(market_type.to_string());
+    let mut resolutions = vec![];
+    for symbol_dir in fs::read_dir(market_path).into_iter().flatten().flatten() {
+        for resolution_dir in fs::read_dir(symbol_dir.path()).into_iter().flatten().flatten() {
+            let Ok(resolution) = Resolution::from_str(&resolution_dir.file_name().to_string_lossy()) else {
+                continue;
+            };
+            for data_type_dir in fs::read_dir(resolution_dir.path()).into_iter().flatten().flatten() {
+                if let Ok(base_data_type) = BaseDataType::from_str(&data_type_dir.file_name().to_string_lossy()) {
+                    let primary = PrimarySubscription::new(resolution, base_data_type);
+                    if !resolutions.contains(&primary) {
+                        resolutions.push(primary);
+                    }
+                }
+            }
+        }
+    }
+    resolutions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use crate::standardized_types::subscriptions::Symbol;
+
+    #[test]
+    fn test_stored_resolutions() {
+        let temp_dir = TempDir::new().unwrap();
+        let options = ServerLaunchOptions {
+            data_folder: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let storage = HybridStorage::new(Duration::from_secs(3600), options, 1, 300);
+        let symbol = |name: &str| Symbol::new(name.to_string(), DataVendor::Oanda, MarketType::Forex);
+        storage.get_base_path(&symbol("EUR-USD"), &Resolution::Seconds(5), &BaseDataType::QuoteBars, true);
+        storage.get_base_path(&symbol("EUR-USD"), &Resolution::Hours(1), &BaseDataType::QuoteBars, true);
+        storage.get_base_path(&symbol("AUD-USD"), &Resolution::Hours(1), &BaseDataType::QuoteBars, true);
+
+        let mut resolutions = stored_resolutions(&storage, &DataVendor::Oanda, &MarketType::Forex);
+        resolutions.sort_by_key(|primary| primary.resolution);
+        assert_eq!(resolutions, vec![
+            PrimarySubscription::new(Resolution::Seconds(5), BaseDataType::QuoteBars),
+            PrimarySubscription::new(Resolution::Hours(1), BaseDataType::QuoteBars),
+        ]);
+        assert!(stored_resolutions(&storage, &DataVendor::Rithmic, &MarketType::Forex).is_empty());
+    }
+}
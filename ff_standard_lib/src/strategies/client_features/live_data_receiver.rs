@@ -1,9 +1,9 @@
 use std::collections::BTreeMap;
 use std::time::Duration;
-use tokio::sync::mpsc::{Sender};
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::{Receiver, Sender};
 use std::sync::Arc;
-use chrono::{Utc};
-use tokio::runtime::Runtime;
+use chrono::{DateTime, Utc};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 use tokio_rustls::TlsStream;
@@ -13,24 +13,27 @@ use crate::standardized_types::bytes_trait::Bytes;
 use crate::standardized_types::time_slices::TimeSlice;
 use crate::strategies::client_features::connection_settings::client_settings::ConnectionSettings;
 use crate::strategies::client_features::init_clients::create_async_api_client;
-use crate::strategies::client_features::server_connections::set_warmup_complete;
+use crate::strategies::client_features::live_subscriptions::{release_route, subscribers};
+use crate::strategies::client_features::server_connections::{routes, remove_route};
+use crate::strategies::client_features::vendor_fallback::relabel;
 use crate::strategies::handlers::indicator_handler::IndicatorHandler;
-use crate::strategies::handlers::live_warmup::WARMUP_COMPLETE_BROADCASTER;
+use crate::strategies::handlers::market_handler::aggressor_classifier::classify_aggressors;
 use crate::strategies::handlers::market_handler::price_service::MarketPriceService;
+use crate::strategies::handlers::price_trigger_handler::PriceTriggerHandler;
 use crate::strategies::handlers::subscription_handler::SubscriptionHandler;
-use crate::strategies::historical_time::{get_backtest_time, update_backtest_time};
+use crate::strategies::historical_time::{clock_now, WarmUpState};
 use crate::strategies::ledgers::ledger_service::LedgerService;
 use crate::strategies::strategy_events::StrategyEvent;
 
+// Each connection of the process has one live stream, shared by the strategies in the process.
+// The stream is split by subscription, the data of a subscription goes to the strategies subscribed to it,
+// data no strategy subscribed to, eg a subscription the server labelled differently, goes to every strategy.
+// Each strategy buffers its data until its own warm up is complete, then processes it on its own runtime.
+
 pub async fn handle_live_data(
     connection_settings: ConnectionSettings,
     stream_name: u16,
     buffer_duration: Duration,
-    strategy_event_sender: Sender<StrategyEvent>,
-    ledger_service: Arc<LedgerService>,
-    indicator_handler: Arc<IndicatorHandler>,
-    subscription_handler: Arc<SubscriptionHandler>,
-    market_price_service: Arc<MarketPriceService>,
 ) {
 
     let mut stream_client = match create_async_api_client(&connection_settings, true).await {
@@ -57,66 +60,96 @@ pub async fn handle_live_data(
         return;
     }
 
-    let _ = tokio::task::spawn_blocking(move || {
-        let rt = Runtime::new().unwrap();
-        rt.block_on(async {
-            receive_and_process(
-                stream_client,
-                strategy_event_sender,
-                ledger_service,
-                indicator_handler,
-                subscription_handler,
-                market_price_service,
-            ).await;
-        });
+    tokio::task::spawn(async move {
+        forward_live_data(stream_client).await;
     });
 }
 
-async fn receive_and_process(
-    mut stream_client: TlsStream<TcpStream>,
+/// Splits the time slices of the stream between the strategies of the process.
+async fn forward_live_data(mut stream_client: TlsStream<TcpStream>) {
+    const LENGTH: usize = 4;
+    let mut length_bytes = [0u8; LENGTH];
+    loop {
+        if let Err(e) = stream_client.read_exact(&mut length_bytes).await {
+            eprintln!("Error reading length bytes: {}", e);
+            break;
+        }
+        let msg_length = u32::from_be_bytes(length_bytes) as usize;
+        let mut message_body = vec![0u8; msg_length];
+
+        if let Err(e) = stream_client.read_exact(&mut message_body).await {
+            eprintln!("Error reading message body: {}", e);
+            continue;
+        }
+
+        let Ok(mut time_slice) = TimeSlice::from_bytes(&message_body) else {
+            continue;
+        };
+        relabel(&mut time_slice, true);
+        let subscribed: Vec<_> = time_slice.iter().map(|data| (data, subscribers(&data.subscription()))).collect();
+        for route in routes() {
+            let Some(live_data_sender) = &route.live_data_sender else {
+                continue;
+            };
+            let mut strategy_slice = TimeSlice::new();
+            for (data, subscribers) in &subscribed {
+                if subscribers.is_empty() || subscribers.contains(&route.id) {
+                    strategy_slice.add((*data).clone());
+                }
+            }
+            if strategy_slice.is_empty() {
+                continue;
+            }
+            // the strategy has stopped, its subscriptions are released for the other strategies
+            if live_data_sender.send(strategy_slice).await.is_err() {
+                remove_route(route.id);
+                release_route(route.id).await;
+            }
+        }
+    }
+
+    for route in routes() {
+        if route.live_data_sender.is_some() {
+            let _ = route.strategy_event_sender
+                .send(StrategyEvent::ShutdownEvent(String::from("Live stream disconnected")))
+                .await;
+        }
+    }
+}
+
+/// Processes the live data of one strategy, the data is buffered until the live warm up of the strategy is complete.
+pub(crate) async fn receive_and_process(
+    mut receiver: Receiver<TimeSlice>,
+    warm_up: WarmUpState,
+    mut warmup_completion_receiver: broadcast::Receiver<DateTime<Utc>>,
     strategy_event_sender: Sender<StrategyEvent>,
     ledger_service: Arc<LedgerService>,
     indicator_handler: Arc<IndicatorHandler>,
     subscription_handler: Arc<SubscriptionHandler>,
     price_service: Arc<MarketPriceService>,
+    price_trigger_handler: Arc<PriceTriggerHandler>,
 ) {
-    const LENGTH: usize = 4;
-    let mut length_bytes = [0u8; LENGTH];
     let mut  buffered_data: BTreeMap<i64, TimeSlice> =BTreeMap::new();
-    let mut warmup_completion_receiver = WARMUP_COMPLETE_BROADCASTER.subscribe();
     #[allow(unused_assignments)]
     let mut warm_up_end = Utc::now();
     // First phase: Buffer data during warmup
     loop {
         tokio::select! {
-            result = stream_client.read_exact(&mut length_bytes) => {
+            result = receiver.recv() => {
                 match result {
-                    Ok(_) => {
-                        let msg_length = u32::from_be_bytes(length_bytes) as usize;
-                        let mut message_body = vec![0u8; msg_length];
-
-                        if let Err(e) = stream_client.read_exact(&mut message_body).await {
-                            eprintln!("Error reading message body: {}", e);
-                            continue;
-                        }
-
-                        if let Ok(time_slice) = TimeSlice::from_bytes(&message_body) {
-                            for data in time_slice.iter() {
-                                let timestamp = data.time_closed_utc().timestamp_nanos_opt().unwrap();
-                                buffered_data.entry(timestamp.clone())
-                                    .and_modify(|slice| slice.extend(time_slice.clone()))
-                                    .or_insert_with(|| {
-                                        let mut new_slice = TimeSlice::new();
-                                        new_slice.extend(time_slice.clone());
-                                        new_slice
-                                    });
-                            }
+                    Some(time_slice) => {
+                        for data in time_slice.iter() {
+                            let timestamp = data.time_closed_utc().timestamp_nanos_opt().unwrap();
+                            buffered_data.entry(timestamp.clone())
+                                .and_modify(|slice| slice.extend(time_slice.clone()))
+                                .or_insert_with(|| {
+                                    let mut new_slice = TimeSlice::new();
+                                    new_slice.extend(time_slice.clone());
+                                    new_slice
+                                });
                         }
                     }
-                    Err(e) => {
-                        eprintln!("Error reading length bytes: {}", e);
-                        return;
-                    }
+                    None => return,
                 }
             }
             Ok(time) = warmup_completion_receiver.recv() => {
@@ -134,19 +167,23 @@ async fn receive_and_process(
             .range(..=Utc::now().timestamp())
             .filter(|(_, slice)| !slice.is_empty())
         {
-            if *time <= get_backtest_time().timestamp() {
+            if *time <= warm_up.time().timestamp() {
                 continue;
             }
             let mut strategy_time_slice = TimeSlice::new();
+            let mut slice = slice.clone();
+            classify_aggressors(&mut slice);
             let arc_slice = Arc::new(slice.clone());
 
             price_service.update_market_data(arc_slice.clone());
             ledger_service.timeslice_updates(arc_slice.clone()).await;
+            price_trigger_handler.update_time_slice(&slice).await;
 
             if let Some(consolidated_data) = subscription_handler.update_time_slice(arc_slice).await {
                 strategy_time_slice.extend(consolidated_data);
             }
-            strategy_time_slice.extend(slice.clone());
+            strategy_time_slice.extend(slice);
+            subscription_handler.remove_paused(&mut strategy_time_slice);
 
             if let Some(events) = indicator_handler.update_time_slice(&strategy_time_slice).await {
                 let _ = strategy_event_sender.send(StrategyEvent::IndicatorEvent(events)).await;
@@ -155,7 +192,7 @@ async fn receive_and_process(
         }
     }
     drop(buffered_data);
-    set_warmup_complete();
+    warm_up.set_complete();
 
 
     let now = tokio::time::Instant::now();
@@ -172,57 +209,42 @@ async fn receive_and_process(
     loop {
         tokio::select! {
             _ = interval.tick() => {
-                let now = Utc::now();
+                let now = clock_now();
                 if let Some(consolidated_data) = subscription_handler.update_consolidators_time(now).await {
                     if let Some(indicator_slice) = indicator_handler.update_time_slice(&consolidated_data).await {
                         let _ = strategy_event_sender.send(StrategyEvent::IndicatorEvent(indicator_slice)).await;
                     };
                     let _ = strategy_event_sender.send(StrategyEvent::TimeSlice(consolidated_data)).await;
                 }
-                update_backtest_time(now);
             }
-            result = stream_client.read_exact(&mut length_bytes) => {
+            result = receiver.recv() => {
                 match result {
-                    Ok(_) => {
-                        let msg_length = u32::from_be_bytes(length_bytes) as usize;
-                        let mut message_body = vec![0u8; msg_length];
-
-                        if let Err(e) = stream_client.read_exact(&mut message_body).await {
-                            eprintln!("Error reading message body: {}", e);
-                            continue;
-                        }
-
-                        if let Ok(time_slice) = TimeSlice::from_bytes(&message_body) {
-                             let mut strategy_time_slice = TimeSlice::new();
-                            if !time_slice.is_empty() {
-                                let arc_slice = Arc::new(time_slice.clone());
-                                price_service.update_market_data(arc_slice.clone());
-                                ledger_service.timeslice_updates(arc_slice.clone()).await;
-
-                                if let Some(consolidated_data) = subscription_handler.update_time_slice(arc_slice).await {
-                                    strategy_time_slice.extend(consolidated_data);
-                                }
-                                strategy_time_slice.extend(time_slice);
-                                //the indicator update will not be garanteed to be in sync with the time slice, but it should be close enough and this prevents very resource intense indicators from slowing down the strategy.
-                                 if let Some(indicator_slice) = indicator_handler.update_time_slice(&strategy_time_slice).await {
-                                    let _ = strategy_event_sender.send(StrategyEvent::IndicatorEvent(indicator_slice)).await;
-                                };
-                                let _ = strategy_event_sender.send(StrategyEvent::TimeSlice(strategy_time_slice)).await;
+                    Some(mut time_slice) => {
+                        let mut strategy_time_slice = TimeSlice::new();
+                        if !time_slice.is_empty() {
+                            classify_aggressors(&mut time_slice);
+                            let arc_slice = Arc::new(time_slice.clone());
+                            price_service.update_market_data(arc_slice.clone());
+                            ledger_service.timeslice_updates(arc_slice.clone()).await;
+                            price_trigger_handler.update_time_slice(&time_slice).await;
+
+                            if let Some(consolidated_data) = subscription_handler.update_time_slice(arc_slice).await {
+                                strategy_time_slice.extend(consolidated_data);
                             }
+                            strategy_time_slice.extend(time_slice);
+                            subscription_handler.remove_paused(&mut strategy_time_slice);
+                            //the indicator update will not be garanteed to be in sync with the time slice, but it should be close enough and this prevents very resource intense indicators from slowing down the strategy.
+                             if let Some(indicator_slice) = indicator_handler.update_time_slice(&strategy_time_slice).await {
+                                let _ = strategy_event_sender.send(StrategyEvent::IndicatorEvent(indicator_slice)).await;
+                            };
+                            let _ = strategy_event_sender.send(StrategyEvent::TimeSlice(strategy_time_slice)).await;
                         }
                     }
-                    Err(e) => {
-                        eprintln!("Error reading length bytes: {}", e);
-                        break;
-                    }
+                    None => break,
                 }
             }
         }
     }
-
-    let _ = strategy_event_sender
-        .send(StrategyEvent::ShutdownEvent(String::from("Live stream disconnected")))
-        .await;
 }
 
 
@@ -328,6 +350,7 @@ pub async fn handle_live_data(
                                         strategy_time_slice.extend(consolidated_data);
                                     }
                                     strategy_time_slice.extend(time_slice);
+                                    subscription_handler.remove_paused(&mut strategy_time_slice);
                                 }
 
                                 if !strategy_time_slice.is_empty() {
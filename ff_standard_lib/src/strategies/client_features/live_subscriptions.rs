@@ -1,91 +1,213 @@
 use std::sync::Arc;
-use ahash::AHashMap;
+use std::time::Duration;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::Sender;
 use crate::messages::data_server_messaging::{DataServerRequest, StreamRequest};
+use crate::standardized_types::datavendor_enum::DataVendor;
 use crate::standardized_types::enums::StrategyMode;
-use crate::strategies::client_features::connection_settings::client_settings::initialise_settings;
+use crate::standardized_types::subscriptions::{DataSubscription, DataSubscriptionEvent};
 use crate::strategies::client_features::connection_types::ConnectionType;
 use crate::strategies::client_features::request_handler::StrategyRequest;
 use crate::strategies::client_features::request_handler;
+use crate::strategies::client_features::server_connections::{route, RouteId, SETTINGS_MAP};
+use crate::strategies::client_features::vendor_fallback;
 use crate::strategies::handlers::subscription_handler::SubscriptionHandler;
+use crate::strategies::strategy_events::StrategyEvent;
+
+/// How often the live vendors of subscriptions with fallbacks are checked.
+const VENDOR_FALLBACK_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+lazy_static! {
+    /// The strategies subscribed to each live subscription, by the subscription the strategies declared.
+    static ref LIVE_SUBSCRIBERS: DashMap<DataSubscription, Vec<RouteId>> = DashMap::new();
+}
+
+static VENDOR_FALLBACK_CHECK: OnceCell<()> = OnceCell::new();
+
+fn vendor_connection(vendor: DataVendor) -> ConnectionType {
+    let connection = ConnectionType::Vendor(vendor);
+    match SETTINGS_MAP.contains_key(&connection) {
+        true => connection,
+        false => ConnectionType::Default
+    }
+}
+
+async fn send_stream_request(request: StreamRequest) {
+    let connection = match &request {
+        StreamRequest::Subscribe(subscription) | StreamRequest::Unsubscribe(subscription) => vendor_connection(subscription.symbol.data_vendor),
+    };
+    let request = DataServerRequest::StreamRequest { request };
+    request_handler::send_request(StrategyRequest::OneWay(connection, request)).await;
+}
+
+/// The strategies subscribed to the live subscription.
+pub(crate) fn subscribers(subscription: &DataSubscription) -> Vec<RouteId> {
+    LIVE_SUBSCRIBERS.get(subscription).map(|subscribers| subscribers.value().clone()).unwrap_or_default()
+}
+
+/// Adds the strategy to the subscribers, returns true if it is the first subscriber.
+fn add_subscriber(route_id: RouteId, subscription: &DataSubscription) -> Option<bool> {
+    let mut subscribers = LIVE_SUBSCRIBERS.entry(subscription.clone()).or_default();
+    if subscribers.contains(&route_id) {
+        return None;
+    }
+    subscribers.push(route_id);
+    Some(subscribers.len() == 1)
+}
+
+/// Removes the strategy from the subscribers, returns true if it was the last subscriber.
+fn remove_subscriber(route_id: RouteId, subscription: &DataSubscription) -> Option<bool> {
+    let last = {
+        let mut subscribers = LIVE_SUBSCRIBERS.get_mut(subscription)?;
+        let index = subscribers.iter().position(|id| *id == route_id)?;
+        subscribers.remove(index);
+        subscribers.is_empty()
+    };
+    if last {
+        LIVE_SUBSCRIBERS.remove_if(subscription, |_, subscribers| subscribers.is_empty());
+    }
+    Some(last)
+}
+
+async fn subscribe(route_id: RouteId, subscription: &DataSubscription, strategy_event_sender: &Sender<StrategyEvent>) {
+    match add_subscriber(route_id, subscription) {
+        None => {}
+        Some(true) => send_stream_request(StreamRequest::Subscribe(vendor_fallback::live_subscription(subscription))).await,
+        // the server is already streaming the subscription for another strategy
+        Some(false) => {
+            let event = DataSubscriptionEvent::Subscribed(subscription.clone());
+            let _ = strategy_event_sender.send(StrategyEvent::DataSubscriptionEvent(event)).await;
+        }
+    }
+}
+
+async fn unsubscribe(route_id: RouteId, subscription: &DataSubscription, strategy_event_sender: &Sender<StrategyEvent>) {
+    match remove_subscriber(route_id, subscription) {
+        None => {}
+        Some(true) => send_stream_request(StreamRequest::Unsubscribe(vendor_fallback::live_subscription(subscription))).await,
+        // another strategy is still subscribed, the server keeps streaming
+        Some(false) => {
+            let event = DataSubscriptionEvent::Unsubscribed(subscription.clone());
+            let _ = strategy_event_sender.send(StrategyEvent::DataSubscriptionEvent(event)).await;
+        }
+    }
+}
+
+/// Releases the subscriptions of a strategy which has stopped, the last subscriber of a subscription unsubscribes it from the server.
+pub(crate) async fn release_route(route_id: RouteId) {
+    let subscriptions: Vec<DataSubscription> = LIVE_SUBSCRIBERS.iter()
+        .filter(|subscribers| subscribers.value().contains(&route_id))
+        .map(|subscribers| subscribers.key().clone())
+        .collect();
+    for subscription in subscriptions {
+        if remove_subscriber(route_id, &subscription) == Some(true) {
+            send_stream_request(StreamRequest::Unsubscribe(vendor_fallback::live_subscription(&subscription))).await;
+        }
+    }
+}
+
+/// Moves the live subscriptions of the failed symbols to their next vendor, for every strategy subscribed.
+async fn check_vendor_fallbacks() {
+    let mut fallback_check = tokio::time::interval(VENDOR_FALLBACK_CHECK_INTERVAL);
+    loop {
+        fallback_check.tick().await;
+        for failed in vendor_fallback::failed_symbols() {
+            let Some((preferred, vendor)) = vendor_fallback::fail_over(&failed) else {
+                continue;
+            };
+            let moved: Vec<(DataSubscription, Vec<RouteId>)> = LIVE_SUBSCRIBERS.iter()
+                .filter(|subscribers| subscribers.key().symbol == preferred)
+                .map(|subscribers| (subscribers.key().clone(), subscribers.value().clone()))
+                .collect();
+            for (subscription, route_ids) in moved {
+                let mut old_subscription = subscription.clone();
+                old_subscription.symbol.data_vendor = failed.data_vendor;
+                send_stream_request(StreamRequest::Unsubscribe(old_subscription)).await;
+
+                let mut new_subscription = subscription.clone();
+                new_subscription.symbol.data_vendor = vendor;
+                send_stream_request(StreamRequest::Subscribe(new_subscription)).await;
+
+                for route in route_ids.into_iter().filter_map(route) {
+                    let event = DataSubscriptionEvent::VendorFailover(subscription.clone(), vendor);
+                    let _ = route.strategy_event_sender.send(StrategyEvent::DataSubscriptionEvent(event)).await;
+                }
+            }
+        }
+    }
+}
 
 pub(crate) async fn live_subscription_handler(
+    route_id: RouteId,
     mode: StrategyMode,
-    subscription_handler: Arc<SubscriptionHandler>
+    subscription_handler: Arc<SubscriptionHandler>,
+    strategy_event_sender: Sender<StrategyEvent>,
 ) {
     if mode == StrategyMode::Backtest {
         return;
     }
 
-    let settings_map = Arc::new(initialise_settings().unwrap());
+    VENDOR_FALLBACK_CHECK.get_or_init(|| {
+        tokio::task::spawn(check_vendor_fallbacks());
+    });
+
     let mut subscription_update_channel = subscription_handler.subscribe_primary_subscription_updates();
 
-    let settings_map_ref = settings_map.clone();
     println!("Handler: Start Live handler");
     tokio::task::spawn(async move {
-        let mut current_subscriptions = subscription_handler.primary_subscriptions().await.clone();
-        {
-            let mut subscribed = vec![];
-            println!("Handler: {:?}", current_subscriptions);
-            for subscription in &*current_subscriptions {
-                let request = DataServerRequest::StreamRequest {
-                    request: StreamRequest::Subscribe(subscription.clone())
-                };
-                let connection = ConnectionType::Vendor(subscription.symbol.data_vendor.clone());
-                let connection_type = match settings_map_ref.contains_key(&connection) {
-                    true => connection,
-                    false => ConnectionType::Default
-                };
-                if !subscribed.contains(&connection_type) {
-                    let register = StrategyRequest::OneWay(connection_type.clone(), DataServerRequest::Register(mode.clone()));
-                    request_handler::send_request(register).await;
-                    subscribed.push(connection_type.clone());
-                }
-                let request = StrategyRequest::OneWay(connection_type, request);
-                request_handler::send_request(request).await;
-            }
+        // the connections are registered with the server when the first strategy in the process connects
+        let mut current_subscriptions = subscription_handler.primary_subscriptions().await;
+        println!("Handler: {:?}", current_subscriptions);
+        for subscription in &current_subscriptions {
+            subscribe(route_id, subscription, &strategy_event_sender).await;
         }
-        while let Ok(updated_subscriptions) = subscription_update_channel.recv().await {
-            let mut requests_map = AHashMap::new();
+        loop {
+            let updated_subscriptions = match subscription_update_channel.recv().await {
+                Ok(updated_subscriptions) => updated_subscriptions,
+                Err(RecvError::Lagged(_)) => subscription_handler.primary_subscriptions().await,
+                Err(RecvError::Closed) => break,
+            };
             if current_subscriptions != updated_subscriptions {
                 for subscription in &updated_subscriptions {
-                    if !current_subscriptions.contains(&subscription) {
-                        let connection = ConnectionType::Vendor(subscription.symbol.data_vendor.clone());
-                        let connection_type = match settings_map_ref.contains_key(&connection) {
-                            true => connection,
-                            false => ConnectionType::Default
-                        };
-                        let request = DataServerRequest::StreamRequest { request: StreamRequest::Subscribe(subscription.clone())};
-                        if !requests_map.contains_key(&connection_type) {
-                            requests_map.insert(connection_type, vec![request]);
-                        } else {
-                            requests_map.get_mut(&connection_type).unwrap().push(request);
-                        }
-                    }
-                }
-                for subscription in &*current_subscriptions {
-                    if !updated_subscriptions.contains(&subscription) {
-                        let connection = ConnectionType::Vendor(subscription.symbol.data_vendor.clone());
-                        let connection_type = match settings_map_ref.contains_key(&connection) {
-                            true => connection,
-                            false => ConnectionType::Default
-                        };
-                        let request = DataServerRequest::StreamRequest { request: StreamRequest::Unsubscribe(subscription.clone())};
-
-                        if !requests_map.contains_key(&connection_type) {
-                            requests_map.insert(connection_type, vec![request]);
-                        } else {
-                            requests_map.get_mut(&connection_type).unwrap().push(request);
-                        }
+                    if !current_subscriptions.contains(subscription) {
+                        subscribe(route_id, subscription, &strategy_event_sender).await;
                     }
                 }
-                for (connection, requests) in requests_map {
-                    for request in requests {
-                        let request = StrategyRequest::OneWay(connection.clone(), request);
-                        request_handler::send_request(request).await;
+                for subscription in &current_subscriptions {
+                    if !updated_subscriptions.contains(subscription) {
+                        unsubscribe(route_id, subscription, &strategy_event_sender).await;
                     }
                 }
-                current_subscriptions = updated_subscriptions.clone();
+                current_subscriptions = updated_subscriptions;
             }
         }
+        release_route(route_id).await;
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standardized_types::base_data::base_data_type::BaseDataType;
+    use crate::standardized_types::enums::MarketType;
+    use crate::standardized_types::resolution::Resolution;
+
+    #[test]
+    fn test_subscribers_counted() {
+        let subscription = DataSubscription::new("AUD-CAD".to_string(), DataVendor::Oanda, Resolution::Instant, BaseDataType::Quotes, MarketType::Forex);
+        assert_eq!(add_subscriber(1, &subscription), Some(true));
+        assert_eq!(add_subscriber(2, &subscription), Some(false));
+        assert_eq!(add_subscriber(2, &subscription), None);
+        assert_eq!(subscribers(&subscription), vec![1, 2]);
+
+        assert_eq!(remove_subscriber(1, &subscription), Some(false));
+        assert_eq!(remove_subscriber(1, &subscription), None);
+        assert_eq!(subscribers(&subscription), vec![2]);
+        assert_eq!(remove_subscriber(2, &subscription), Some(true));
+        assert!(subscribers(&subscription).is_empty());
+        assert!(!LIVE_SUBSCRIBERS.contains_key(&subscription));
+    }
+}
@@ -6,7 +6,10 @@ pub mod client_side_vendor;
 pub mod client_side_brokerage;
 pub mod client_side_symbol;
 pub(crate) mod live_subscriptions;
+pub mod vendor_fallback;
 pub(crate) mod request_handler;
 mod response_handler;
 mod live_data_receiver;
-pub mod other_requests;
\ No newline at end of file
+pub mod other_requests;
+pub mod data_only_client;
+pub mod embedded_data;
\ No newline at end of file
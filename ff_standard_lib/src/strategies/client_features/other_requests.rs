@@ -1,12 +1,23 @@
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use tokio::sync::oneshot;
-use crate::messages::data_server_messaging::{DataServerRequest, DataServerResponse, FundForgeError};
+use crate::messages::data_server_messaging::{DataServerRequest, DataServerResponse, FundForgeError, IndicatorStreamInfo, StrategyStreamInfo};
 use crate::product_maps::oanda::maps::{OANDA_FX_SYMBOLS};
 use crate::standardized_types::accounts::Currency;
+use crate::standardized_types::base_data::data_quality::DataQualityMetrics;
 use crate::standardized_types::datavendor_enum::DataVendor;
 use crate::standardized_types::enums::OrderSide;
+use crate::standardized_types::subscriptions::{Symbol, SymbolName};
+use crate::standardized_types::symbol_aliases::{register_symbol_alias, SymbolAlias};
+use crate::standardized_types::levels::Level;
+use crate::standardized_types::intents::Intent;
+use crate::standardized_types::download_jobs::{DownloadJob, DownloadJobRequest};
+use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::resolution::Resolution;
+use crate::database::scanner::{ScanCondition, ScanMatch};
 use crate::strategies::client_features::connection_types::ConnectionType;
+use crate::strategies::client_features::server_connections::SETTINGS_MAP;
+use crate::strategies::client_features::data_only_client::DataOnlyClient;
 use crate::strategies::client_features::request_handler::{send_request, StrategyRequest};
 
 pub async fn get_exchange_rate(from_currency: Currency, to_currency: Currency, date_time: DateTime<Utc>, side: OrderSide) -> Result<Decimal, FundForgeError> {
@@ -41,4 +52,201 @@ pub async fn get_exchange_rate(from_currency: Currency, to_currency: Currency, d
         },
         Err(e) => Err(FundForgeError::ClientSideErrorDebug(format!("Receiver error at callback recv: {}", e)))
     }
-}
\ No newline at end of file
+}
+
+/// Requests the data quality metrics from the data server of the vendor, for the symbol or for every symbol if `symbol_name` is None.
+/// The server only keeps metrics when it is launched with `--outliers flag`, `filter` or `winsorize`.
+pub async fn get_data_quality(data_vendor: DataVendor, symbol_name: Option<SymbolName>) -> Result<Vec<DataQualityMetrics>, FundForgeError> {
+    let request = DataServerRequest::DataQuality {
+        callback_id: 0,
+        symbol_name,
+    };
+    let connection = match SETTINGS_MAP.contains_key(&ConnectionType::Vendor(data_vendor)) {
+        true => ConnectionType::Vendor(data_vendor),
+        false => ConnectionType::Default
+    };
+    let (sender, receiver) = oneshot::channel();
+    send_request(StrategyRequest::CallBack(connection, request, sender)).await;
+    match receiver.await {
+        Ok(response) => match response {
+            DataServerResponse::DataQuality { metrics, .. } => Ok(metrics.into_iter().filter(|metrics| metrics.subscription.symbol.data_vendor == data_vendor).collect()),
+            DataServerResponse::Error { error, .. } => Err(error),
+            _ => Err(FundForgeError::ClientSideErrorDebug("Incorrect response received at callback".to_string()))
+        },
+        Err(e) => Err(FundForgeError::ClientSideErrorDebug(format!("Receiver error at callback recv: {}", e)))
+    }
+}
+
+/// Requests the vendor symbol aliases registered on the default data server and registers them, so the strategy translates vendor names like the server.
+pub async fn load_symbol_aliases() -> Result<Vec<SymbolAlias>, FundForgeError> {
+    let request = DataServerRequest::SymbolAliases { callback_id: 0 };
+    let (sender, receiver) = oneshot::channel();
+    send_request(StrategyRequest::CallBack(ConnectionType::Default, request, sender)).await;
+    match receiver.await {
+        Ok(response) => match response {
+            DataServerResponse::SymbolAliases { aliases, .. } => {
+                for alias in &aliases {
+                    register_symbol_alias(alias.clone());
+                }
+                Ok(aliases)
+            }
+            DataServerResponse::Error { error, .. } => Err(error),
+            _ => Err(FundForgeError::ClientSideErrorDebug("Incorrect response received at callback".to_string()))
+        },
+        Err(e) => Err(FundForgeError::ClientSideErrorDebug(format!("Receiver error at callback recv: {}", e)))
+    }
+}
+
+async fn levels_request(request: DataServerRequest) -> Result<Vec<Level>, FundForgeError> {
+    let (sender, receiver) = oneshot::channel();
+    send_request(StrategyRequest::CallBack(ConnectionType::Default, request, sender)).await;
+    match receiver.await {
+        Ok(response) => match response {
+            DataServerResponse::Levels { levels, .. } => Ok(levels),
+            DataServerResponse::Error { error, .. } => Err(error),
+            _ => Err(FundForgeError::ClientSideErrorDebug("Incorrect response received at callback".to_string()))
+        },
+        Err(e) => Err(FundForgeError::ClientSideErrorDebug(format!("Receiver error at callback recv: {}", e)))
+    }
+}
+
+/// Requests the levels saved on the default data server for the symbol, see `ff_standard_lib::standardized_types::levels`.
+pub async fn get_levels(symbol_name: SymbolName) -> Result<Vec<Level>, FundForgeError> {
+    levels_request(DataServerRequest::Levels { callback_id: 0, symbol_name }).await
+}
+
+/// Saves a level on the default data server, replacing the level with the same id, and returns the levels of the symbol.
+pub async fn save_level(level: Level) -> Result<Vec<Level>, FundForgeError> {
+    levels_request(DataServerRequest::SaveLevel { callback_id: 0, level }).await
+}
+
+/// Removes a level from the default data server and returns the levels left for the symbol.
+pub async fn remove_level(symbol_name: SymbolName, id: String) -> Result<Vec<Level>, FundForgeError> {
+    levels_request(DataServerRequest::RemoveLevel { callback_id: 0, symbol_name, id }).await
+}
+
+async fn intents_request(request: DataServerRequest) -> Result<Vec<Intent>, FundForgeError> {
+    let (sender, receiver) = oneshot::channel();
+    send_request(StrategyRequest::CallBack(ConnectionType::Default, request, sender)).await;
+    match receiver.await {
+        Ok(response) => match response {
+            DataServerResponse::Intents { intents, .. } => Ok(intents),
+            DataServerResponse::Error { error, .. } => Err(error),
+            _ => Err(FundForgeError::ClientSideErrorDebug("Incorrect response received at callback".to_string()))
+        },
+        Err(e) => Err(FundForgeError::ClientSideErrorDebug(format!("Receiver error at callback recv: {}", e)))
+    }
+}
+
+/// Requests the intents saved on the default data server for the symbol, see `ff_standard_lib::standardized_types::intents`.
+pub async fn get_intents(symbol_name: SymbolName) -> Result<Vec<Intent>, FundForgeError> {
+    intents_request(DataServerRequest::Intents { callback_id: 0, symbol_name }).await
+}
+
+/// Saves an intent on the default data server, replacing the intent with the same id, and returns the intents of the symbol.
+pub async fn save_intent(intent: Intent) -> Result<Vec<Intent>, FundForgeError> {
+    intents_request(DataServerRequest::SaveIntent { callback_id: 0, intent }).await
+}
+
+/// Removes an intent from the default data server and returns the intents left for the symbol.
+pub async fn remove_intent(symbol_name: SymbolName, id: String) -> Result<Vec<Intent>, FundForgeError> {
+    intents_request(DataServerRequest::RemoveIntent { callback_id: 0, symbol_name, id }).await
+}
+
+/// Marks a resting intent as triggered on the default data server, returns false if it was not resting, eg another strategy claimed it first.
+pub async fn claim_intent(symbol_name: SymbolName, id: String, time: DateTime<Utc>) -> Result<bool, FundForgeError> {
+    let (sender, receiver) = oneshot::channel();
+    let request = DataServerRequest::ClaimIntent { callback_id: 0, symbol_name, id, time: time.to_string() };
+    send_request(StrategyRequest::CallBack(ConnectionType::Default, request, sender)).await;
+    match receiver.await {
+        Ok(response) => match response {
+            DataServerResponse::IntentClaim { claimed, .. } => Ok(claimed),
+            DataServerResponse::Error { error, .. } => Err(error),
+            _ => Err(FundForgeError::ClientSideErrorDebug("Incorrect response received at callback".to_string()))
+        },
+        Err(e) => Err(FundForgeError::ClientSideErrorDebug(format!("Receiver error at callback recv: {}", e)))
+    }
+}
+
+/// Scans the historical store of the data server for the symbols which meet every condition at `time`, see `HybridStorage::scan()`.
+/// The request is sent to the data server of the vendor of the first symbol.
+pub async fn scan_symbols(
+    symbols: Vec<Symbol>,
+    base_data_type: BaseDataType,
+    resolution: Resolution,
+    conditions: Vec<ScanCondition>,
+    time: DateTime<Utc>,
+) -> Result<Vec<ScanMatch>, FundForgeError> {
+    let Some(data_vendor) = symbols.first().map(|symbol| symbol.data_vendor) else {
+        return Ok(vec![]);
+    };
+    let request = DataServerRequest::Scan {
+        callback_id: 0,
+        symbols,
+        base_data_type,
+        resolution,
+        conditions,
+        time: time.to_string(),
+    };
+    let connection = match SETTINGS_MAP.contains_key(&ConnectionType::Vendor(data_vendor)) {
+        true => ConnectionType::Vendor(data_vendor),
+        false => ConnectionType::Default
+    };
+    let (sender, receiver) = oneshot::channel();
+    send_request(StrategyRequest::CallBack(connection, request, sender)).await;
+    match receiver.await {
+        Ok(response) => match response {
+            DataServerResponse::Scan { matches, .. } => Ok(matches),
+            DataServerResponse::Error { error, .. } => Err(error),
+            _ => Err(FundForgeError::ClientSideErrorDebug("Incorrect response received at callback".to_string()))
+        },
+        Err(e) => Err(FundForgeError::ClientSideErrorDebug(format!("Receiver error at callback recv: {}", e)))
+    }
+}
+
+/// Lists the strategies connected to the data server which serve order management controls, see `strategy.serve_controls()`.
+/// This opens its own data only connection to the default data server, so it can be used outside of a strategy, eg by `ff_ctl`.
+pub async fn list_strategy_streams() -> Result<Vec<StrategyStreamInfo>, FundForgeError> {
+    let mut client = DataOnlyClient::connect().await?;
+    match client.request(DataServerRequest::StrategyStreams { callback_id: 0 }).await? {
+        DataServerResponse::StrategyStreams { streams, .. } => Ok(streams),
+        _ => Err(FundForgeError::ClientSideErrorDebug("Incorrect response received at callback".to_string()))
+    }
+}
+
+/// Lists the indicators published by the live strategies connected to the default data server, see `DataOnlyClient::subscribe_indicator()`.
+pub async fn list_indicator_streams() -> Result<Vec<IndicatorStreamInfo>, FundForgeError> {
+    let mut client = DataOnlyClient::connect().await?;
+    match client.request(DataServerRequest::IndicatorStreams { callback_id: 0 }).await? {
+        DataServerResponse::IndicatorStreams { streams, .. } => Ok(streams),
+        _ => Err(FundForgeError::ClientSideErrorDebug("Incorrect response received at callback".to_string()))
+    }
+}
+
+/// Queues a historical download job on the default data server and returns the job with its id, see `ff_standard_lib::standardized_types::download_jobs`.
+pub async fn enqueue_download(request: DownloadJobRequest) -> Result<DownloadJob, FundForgeError> {
+    download_job_request(DataServerRequest::EnqueueDownload { callback_id: 0, request }).await
+}
+
+/// Lists the queued, running and recently finished download jobs of the default data server, oldest first.
+pub async fn download_jobs() -> Result<Vec<DownloadJob>, FundForgeError> {
+    let mut client = DataOnlyClient::connect().await?;
+    match client.request(DataServerRequest::DownloadJobs { callback_id: 0 }).await? {
+        DataServerResponse::DownloadJobs { jobs, .. } => Ok(jobs),
+        _ => Err(FundForgeError::ClientSideErrorDebug("Incorrect response received at callback".to_string()))
+    }
+}
+
+/// Cancels a queued or running download job on the default data server and returns the cancelled job.
+/// The data already downloaded by a running job is kept.
+pub async fn cancel_download(id: u64) -> Result<DownloadJob, FundForgeError> {
+    download_job_request(DataServerRequest::CancelDownload { callback_id: 0, id }).await
+}
+
+async fn download_job_request(request: DataServerRequest) -> Result<DownloadJob, FundForgeError> {
+    let mut client = DataOnlyClient::connect().await?;
+    match client.request(request).await? {
+        DataServerResponse::DownloadJob { job, .. } => Ok(job),
+        _ => Err(FundForgeError::ClientSideErrorDebug("Incorrect response received at callback".to_string()))
+    }
+}
@@ -5,40 +5,38 @@ use tokio_rustls::TlsStream;
 use tokio::net::TcpStream;
 use std::sync::Arc;
 use tokio::sync::oneshot;
-use tokio::sync::mpsc::Sender;
 use chrono::{DateTime, Utc};
 use std::str::FromStr;
-use crate::messages::data_server_messaging::{DataServerRequest, DataServerResponse};
+use crate::messages::data_server_messaging::{DataServerRequest, DataServerResponse, FundForgeError};
 use crate::standardized_types::bytes_trait::Bytes;
 use crate::standardized_types::enums::StrategyMode;
-use crate::standardized_types::orders::OrderUpdateEvent;
-use crate::standardized_types::subscriptions::DataSubscriptionEvent;
+use crate::standardized_types::subscriptions::{DataSubscription, DataSubscriptionEvent};
 use crate::strategies::client_features::connection_types::ConnectionType;
-use crate::strategies::client_features::{live_data_receiver, request_handler};
+use crate::strategies::client_features::{live_data_receiver, live_subscriptions, request_handler, vendor_fallback};
 use crate::strategies::client_features::request_handler::StrategyRequest;
-use crate::strategies::client_features::server_connections::SETTINGS_MAP;
-use crate::strategies::handlers::indicator_handler::IndicatorHandler;
-use crate::strategies::handlers::market_handler::price_service::MarketPriceService;
-use crate::strategies::handlers::subscription_handler::SubscriptionHandler;
-use crate::strategies::ledgers::ledger_service::LedgerService;
+use crate::strategies::client_features::server_connections::{account_routes, order_routes, route, routes, SETTINGS_MAP};
 use crate::strategies::strategy_events::StrategyEvent;
 
+/// Sends the subscription event to the strategies subscribed, or to every strategy if none of them is.
+async fn send_subscription_event(event: DataSubscriptionEvent, subscription: &DataSubscription) {
+    let subscribers: Vec<_> = live_subscriptions::subscribers(subscription).into_iter().filter_map(route).collect();
+    let subscribers = match subscribers.is_empty() {
+        true => routes(),
+        false => subscribers,
+    };
+    for route in subscribers {
+        let _ = route.strategy_event_sender.send(StrategyEvent::DataSubscriptionEvent(event.clone())).await;
+    }
+}
+
 pub async fn response_handler(
     mode: StrategyMode,
     buffer_duration: Duration,
     server_receivers: DashMap<ConnectionType, ReadHalf<TlsStream<TcpStream>>>,
     callbacks: Arc<DashMap<u64, oneshot::Sender<DataServerResponse>>>,
-    order_updates_sender: Sender<(OrderUpdateEvent, DateTime<Utc>)>,
-    synchronise_accounts: bool,
-    strategy_event_sender: Sender<StrategyEvent>,
-    ledger_service: Arc<LedgerService>, //it is better to do this than use messaging, because using a direct fn call we can concurrently update individual ledgers and have a que per ledger. sending a msg here would cause a bottleneck with more ledgers.
-    indicator_handler: Arc<IndicatorHandler>,
-    subscription_handler: Arc<SubscriptionHandler>,
-    market_price_service: Arc<MarketPriceService>
 ) {
     let settings_map = SETTINGS_MAP.clone();
     for (connection, settings) in settings_map.iter() {
-        let order_updates_sender = order_updates_sender.clone();
         if let Some((connection, stream)) = server_receivers.remove(connection) {
             let register_message = StrategyRequest::OneWay(connection.clone(), DataServerRequest::Register(mode.clone()));
             request_handler::send_request(register_message).await;
@@ -46,11 +44,6 @@ pub async fn response_handler(
             let mut receiver = stream;
             let callbacks = callbacks.clone();
             let settings = settings.clone();
-            let strategy_event_sender = strategy_event_sender.clone();
-            let ledger_service = ledger_service.clone();
-            let subscription_handler = subscription_handler.clone();
-            let indicator_handler = indicator_handler.clone();
-            let market_price_service = market_price_service.clone();
             tokio::task::spawn(async move {
                 const LENGTH: usize = 8;
                 let mut length_bytes = [0u8; LENGTH];
@@ -66,60 +59,71 @@ pub async fn response_handler(
                         }
                     }
 
-                    let response = DataServerResponse::from_bytes(&message_body).unwrap();
+                    let response = match DataServerResponse::from_bytes(&message_body) {
+                        Ok(response) => response,
+                        // the server was built with another ff_standard_lib, nothing else it sends can be read
+                        Err(FundForgeError::IncompatibleVersion(reason)) => {
+                            eprintln!("{:?}: Incompatible data server, {}", connection, reason);
+                            callbacks.clear();
+                            break;
+                        }
+                        Err(e) => {
+                            eprintln!("{:?}: Failed to parse response: {}", connection, e);
+                            continue;
+                        }
+                    };
                     match response.get_callback_id() {
                         None => {
                             match response {
                                 DataServerResponse::SubscribeResponse { success, subscription, reason } => {
+                                    if !success {
+                                        vendor_fallback::subscribe_failed(&subscription);
+                                    }
+                                    let subscription = vendor_fallback::preferred_subscription(&subscription);
                                     let event = if success {
                                         DataSubscriptionEvent::Subscribed(subscription.clone())
                                     } else {
                                         DataSubscriptionEvent::FailedToSubscribe(subscription.clone(), reason.unwrap())
                                     };
-                                    let event = StrategyEvent::DataSubscriptionEvent(event);
-                                    match strategy_event_sender.send(event).await {
-                                        Ok(_) => {}
-                                        Err(_) => {}
-                                    }
+                                    send_subscription_event(event, &subscription).await;
                                 }
                                 DataServerResponse::UnSubscribeResponse { success, subscription, reason } => {
+                                    let subscription = vendor_fallback::preferred_subscription(&subscription);
                                     let event = if success {
-                                        DataSubscriptionEvent::Unsubscribed(subscription)
+                                        DataSubscriptionEvent::Unsubscribed(subscription.clone())
                                     } else {
-                                        DataSubscriptionEvent::FailedUnSubscribed(subscription, reason.unwrap())
+                                        DataSubscriptionEvent::FailedUnSubscribed(subscription.clone(), reason.unwrap())
                                     };
-                                    let event = StrategyEvent::DataSubscriptionEvent(event);
-                                    match strategy_event_sender.send(event).await {
-                                        Ok(_) => {}
-                                        Err(_) => {}
-                                    }
+                                    send_subscription_event(event, &subscription).await;
                                 }
                                 DataServerResponse::OrderUpdates{ event, time} => {
                                     //println!("Event received: {}", update_event);
                                     let time = DateTime::<Utc>::from_str(&time).unwrap();
-                                    match order_updates_sender.send((event, time)).await {
-                                        Ok(_) => {}
-                                        Err(_) => {}//eprintln!("Order Update Sender Error: {}", e)
+                                    for route in order_routes(event.account(), event.order_id()) {
+                                        match route.order_updates_sender.send((event.clone(), time)).await {
+                                            Ok(_) => {}
+                                            Err(_) => {}//eprintln!("Order Update Sender Error: {}", e)
+                                        }
                                     }
                                 }
                                 DataServerResponse::LiveAccountUpdates { account, cash_value, cash_available, cash_used } => {
-                                    let ledger_service = ledger_service.clone();
-                                    tokio::task::spawn(async move {
-                                        ledger_service.live_account_updates(&account, cash_value, cash_available, cash_used).await;
-                                    });
+                                    for route in account_routes(&account) {
+                                        let account = account.clone();
+                                        tokio::task::spawn(async move {
+                                            route.ledger_service.live_account_updates(&account, cash_value, cash_available, cash_used).await;
+                                        });
+                                    }
                                 }
                                 DataServerResponse::LivePositionUpdates { symbol_name, symbol_code, account, open_quantity, average_price, side, open_pnl, time } => {
-                                   if synchronise_accounts {
-                                       //println!("Live Position: {:?}", position);
-                                        //tokio::task::spawn(async move {
-                                        ledger_service.synchronize_live_position(symbol_name, symbol_code, account, open_quantity, average_price, side, open_pnl, time).await
-                                        //});
+                                    for route in account_routes(&account).into_iter().filter(|route| route.synchronise_accounts) {
+                                        //println!("Live Position: {:?}", position);
+                                        route.ledger_service.synchronize_live_position(symbol_name.clone(), symbol_code.clone(), account.clone(), open_quantity, average_price, side, open_pnl, time.clone()).await
                                     }
                                 }
                                 DataServerResponse::RegistrationResponse(port) => {
                                     //println!("Connected to server port: {}", port);
                                     if mode != StrategyMode::Backtest {
-                                        live_data_receiver::handle_live_data(settings.clone(), port, buffer_duration, strategy_event_sender.clone(), ledger_service.clone(), indicator_handler.clone(), subscription_handler.clone(), market_price_service.clone()).await;
+                                        live_data_receiver::handle_live_data(settings.clone(), port, buffer_duration).await;
                                     }
                                 }
                                 _ => unreachable!("Incorrect response here: {:?}", response)
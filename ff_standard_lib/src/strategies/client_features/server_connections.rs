@@ -1,56 +1,194 @@
 use std::collections::HashMap;
 use crate::strategies::client_features::init_clients::create_async_api_client;
 use crate::strategies::client_features::connection_settings::client_settings::{initialise_settings, ConnectionSettings};
-use crate::messages::data_server_messaging::DataServerResponse;
+use crate::messages::data_server_messaging::{DataServerResponse, FundForgeError};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
 use tokio::io;
 use tokio::io::{ReadHalf, WriteHalf};
 use tokio::net::TcpStream;
+use tokio::runtime::Runtime;
 use tokio::sync::{mpsc, oneshot};
 use tokio::sync::mpsc::Sender;
 use tokio_rustls::TlsStream;
 use crate::strategies::client_features::connection_types::ConnectionType;
 use crate::strategies::handlers::indicator_handler::IndicatorHandler;
+use crate::strategies::handlers::price_trigger_handler::PriceTriggerHandler;
+use crate::standardized_types::accounts::Account;
 use crate::standardized_types::enums::StrategyMode;
+use crate::standardized_types::time_slices::TimeSlice;
 use crate::strategies::strategy_events::StrategyEvent;
 use crate::strategies::handlers::subscription_handler::SubscriptionHandler;
-use crate::standardized_types::orders::OrderUpdateEvent;
-use crate::strategies::client_features::{request_handler, response_handler};
+use crate::standardized_types::orders::{Order, OrderId, OrderUpdateEvent};
+use crate::strategies::client_features::{live_data_receiver, request_handler, response_handler};
 use crate::strategies::client_features::request_handler::DATA_SERVER_SENDER;
+use crate::strategies::client_features::embedded_data::{embedded_data_folder, embedded_request_handler};
 use crate::strategies::handlers::market_handler::price_service::MarketPriceService;
 use crate::strategies::ledgers::ledger_service::LedgerService;
 
+pub(crate) type RouteId = u64;
+
+/// A strategy sharing the connections of the process.
+///
+/// # Properties
+/// * `id` - The id of the route, unique in the process.
+/// * `accounts` - The accounts the strategy trades, account and position updates are routed by account.
+/// * `synchronise_accounts` - The strategy synchronises its positions with the brokerage.
+/// * `open_order_cache` - The open orders of the strategy, order updates are routed to the strategy which sent the order.
+/// * `closed_order_cache` - The closed orders of the strategy, late updates for closed orders still reach the strategy which sent them.
+/// * `order_updates_sender` - The live order handler of the strategy.
+/// * `strategy_event_sender` - The event channel of the strategy.
+/// * `ledger_service` - The ledgers of the strategy.
+/// * `live_data_sender` - The live data of the subscriptions of the strategy, None in backtests.
+pub(crate) struct StrategyRoute {
+    pub(crate) id: RouteId,
+    pub(crate) accounts: Vec<Account>,
+    pub(crate) synchronise_accounts: bool,
+    pub(crate) open_order_cache: Arc<DashMap<OrderId, Order>>,
+    pub(crate) closed_order_cache: Arc<DashMap<OrderId, Order>>,
+    pub(crate) order_updates_sender: Sender<(OrderUpdateEvent, DateTime<Utc>)>,
+    pub(crate) strategy_event_sender: Sender<StrategyEvent>,
+    pub(crate) ledger_service: Arc<LedgerService>,
+    pub(crate) live_data_sender: Option<Sender<TimeSlice>>,
+}
+
 lazy_static! {
-    static ref WARM_UP_COMPLETE: AtomicBool = AtomicBool::new(false);
     pub(crate) static ref SETTINGS_MAP: Arc<HashMap<ConnectionType, ConnectionSettings>> = Arc::new(initialise_settings().unwrap());
+    static ref STRATEGY_ROUTES: DashMap<RouteId, Arc<StrategyRoute>> = DashMap::new();
+}
+
+static NEXT_ROUTE_ID: AtomicU64 = AtomicU64::new(0);
+/// The mode the connections of the process are registered in.
+static CONNECTION_MODE: OnceCell<StrategyMode> = OnceCell::new();
+static CONNECTIONS: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+/// The mode the connections of the process are registered in, None before the first strategy is initialized.
+pub(crate) fn connection_mode() -> Option<StrategyMode> {
+    CONNECTION_MODE.get().cloned()
+}
+
+pub(crate) fn route(id: RouteId) -> Option<Arc<StrategyRoute>> {
+    STRATEGY_ROUTES.get(&id).map(|route| route.value().clone())
+}
+
+pub(crate) fn routes() -> Vec<Arc<StrategyRoute>> {
+    STRATEGY_ROUTES.iter().map(|route| route.value().clone()).collect()
 }
 
-#[inline(always)]
-pub(crate) fn set_warmup_complete() {
-    WARM_UP_COMPLETE.store(true, Ordering::SeqCst);
+pub(crate) fn remove_route(id: RouteId) {
+    STRATEGY_ROUTES.remove(&id);
 }
-#[inline(always)]
-pub(crate) fn is_warmup_complete() -> bool {
-    WARM_UP_COMPLETE.load(Ordering::SeqCst)
+
+/// The strategies trading the account, every strategy if none of them trades it.
+pub(crate) fn account_routes(account: &Account) -> Vec<Arc<StrategyRoute>> {
+    let routes: Vec<Arc<StrategyRoute>> = STRATEGY_ROUTES.iter()
+        .filter(|route| route.accounts.contains(account))
+        .map(|route| route.value().clone())
+        .collect();
+    match routes.is_empty() {
+        true => self::routes(),
+        false => routes,
+    }
 }
 
+/// The strategy which sent the order, or the strategies trading the account if no strategy in the process sent it.
+pub(crate) fn order_routes(account: &Account, order_id: &OrderId) -> Vec<Arc<StrategyRoute>> {
+    let sender = STRATEGY_ROUTES.iter()
+        .find(|route| route.open_order_cache.contains_key(order_id) || route.closed_order_cache.contains_key(order_id))
+        .map(|route| route.value().clone());
+    match sender {
+        Some(route) => vec![route],
+        None => account_routes(account),
+    }
+}
+
+/// Registers the strategy with the connections of the process, the connections are opened by the first strategy.
+/// Returns the id of the strategy route, or an error if the connections were opened for another strategy mode.
 pub(crate) async fn init_connections(
     gui_enabled: bool,
     buffer_duration: Duration,
     mode: StrategyMode,
     order_updates_sender: Sender<(OrderUpdateEvent, DateTime<Utc>)>,
     synchronise_accounts: bool,
+    accounts: Vec<Account>,
+    open_order_cache: Arc<DashMap<OrderId, Order>>,
+    closed_order_cache: Arc<DashMap<OrderId, Order>>,
     strategy_event_sender: Sender<StrategyEvent>,
     ledger_service: Arc<LedgerService>,
     indicator_handler: Arc<IndicatorHandler>,
     subscription_handler: Arc<SubscriptionHandler>,
-    market_price_service: Arc<MarketPriceService>
-) {
+    market_price_service: Arc<MarketPriceService>,
+    price_trigger_handler: Arc<PriceTriggerHandler>
+) -> Result<RouteId, FundForgeError> {
+    let connection_mode = *CONNECTION_MODE.get_or_init(|| mode);
+    if connection_mode != mode {
+        return Err(FundForgeError::ClientSideErrorDebug(format!("Strategies sharing a process must run in the same mode, the connections are registered for {:?}, the strategy runs in {:?}", connection_mode, mode)));
+    }
+
+    let live_data_sender = match mode {
+        StrategyMode::Backtest => None,
+        StrategyMode::Live | StrategyMode::LivePaperTrading => {
+            let (sender, receiver) = mpsc::channel(1000);
+            // subscribed before the warm up starts, so the receiver can not miss the end of the warm up
+            let warm_up_completion = subscription_handler.warm_up().subscribe_completion();
+            let warm_up = subscription_handler.warm_up().clone();
+            let strategy_event_sender = strategy_event_sender.clone();
+            let ledger_service = ledger_service.clone();
+            tokio::task::spawn_blocking(move || {
+                let rt = Runtime::new().unwrap();
+                rt.block_on(async {
+                    live_data_receiver::receive_and_process(
+                        receiver,
+                        warm_up,
+                        warm_up_completion,
+                        strategy_event_sender,
+                        ledger_service,
+                        indicator_handler,
+                        subscription_handler,
+                        market_price_service,
+                        price_trigger_handler,
+                    ).await;
+                });
+            });
+            Some(sender)
+        }
+    };
+
+    let id = NEXT_ROUTE_ID.fetch_add(1, Ordering::SeqCst);
+    STRATEGY_ROUTES.insert(id, Arc::new(StrategyRoute {
+        id,
+        accounts,
+        synchronise_accounts,
+        open_order_cache,
+        closed_order_cache,
+        order_updates_sender,
+        strategy_event_sender,
+        ledger_service,
+        live_data_sender,
+    }));
+
+    CONNECTIONS.get_or_init(|| connect(gui_enabled, buffer_duration, mode)).await;
+    Ok(id)
+}
+
+/// Opens a connection to each server in the server settings.
+async fn connect(gui_enabled: bool, buffer_duration: Duration, mode: StrategyMode) {
+    if let Some(data_folder) = embedded_data_folder() {
+        match mode {
+            StrategyMode::Backtest => {
+                let (tx, rx) = mpsc::channel(1000);
+                let _ = DATA_SERVER_SENDER.get_or_init(|| tx);
+                embedded_request_handler(data_folder, rx);
+                return;
+            }
+            StrategyMode::Live | StrategyMode::LivePaperTrading => eprintln!("Embedded Data: Only backtests can run without a data server, connecting to the data server"),
+        }
+    }
     let server_receivers: DashMap<ConnectionType, ReadHalf<TlsStream<TcpStream>>> = DashMap::with_capacity(SETTINGS_MAP.len());
     let server_senders: DashMap<ConnectionType, WriteHalf<TlsStream<TcpStream>>> = DashMap::with_capacity(SETTINGS_MAP.len());
 
@@ -77,5 +215,64 @@ pub(crate) async fn init_connections(
 
     let callbacks: Arc<DashMap<u64, oneshot::Sender<DataServerResponse>>> = Default::default();
     request_handler::request_handler(rx, server_senders, callbacks.clone()).await;
-    response_handler::response_handler(mode, buffer_duration, server_receivers, callbacks, order_updates_sender, synchronise_accounts, strategy_event_sender, ledger_service, indicator_handler, subscription_handler, market_price_service).await;
+    response_handler::response_handler(mode, buffer_duration, server_receivers, callbacks).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standardized_types::broker_enum::Brokerage;
+    use crate::standardized_types::orders::TimeInForce;
+    use crate::standardized_types::enums::OrderSide;
+    use crate::strategies::strategy_settings::StrategySettings;
+    use rust_decimal_macros::dec;
+
+    fn insert_route(account: &Account) -> (RouteId, Arc<DashMap<OrderId, Order>>) {
+        let (order_updates_sender, _) = mpsc::channel(1);
+        let (strategy_event_sender, _) = mpsc::channel(1);
+        let (ledger_event_sender, _) = mpsc::channel(1);
+        let open_order_cache = Arc::new(DashMap::new());
+        let id = NEXT_ROUTE_ID.fetch_add(1, Ordering::SeqCst);
+        STRATEGY_ROUTES.insert(id, Arc::new(StrategyRoute {
+            id,
+            accounts: vec![account.clone()],
+            synchronise_accounts: false,
+            open_order_cache: open_order_cache.clone(),
+            closed_order_cache: Arc::new(DashMap::new()),
+            order_updates_sender,
+            strategy_event_sender,
+            ledger_service: Arc::new(LedgerService::new(ledger_event_sender, Arc::new(MarketPriceService::new()), Arc::new(StrategySettings::new(StrategyMode::Live)))),
+            live_data_sender: None,
+        }));
+        (id, open_order_cache)
+    }
+
+    #[test]
+    fn test_order_and_account_routing() {
+        let first_account = Account::new(Brokerage::Test, "Route_Account_1".to_string());
+        let second_account = Account::new(Brokerage::Test, "Route_Account_2".to_string());
+        let (first, first_orders) = insert_route(&first_account);
+        let (second, second_orders) = insert_route(&first_account);
+        let (third, _) = insert_route(&second_account);
+
+        let order = |id: &str| Order::limit_order("EUR-USD".to_string(), None, &first_account, dec!(1), OrderSide::Buy, "Entry".to_string(), id.to_string(), Utc::now(), dec!(1.1), TimeInForce::GTC, None);
+        first_orders.insert("Route_Order_1".to_string(), order("Route_Order_1"));
+        second_orders.insert("Route_Order_2".to_string(), order("Route_Order_2"));
+
+        let ids = |routes: Vec<Arc<StrategyRoute>>| {
+            let mut ids: Vec<RouteId> = routes.iter().map(|route| route.id).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids(order_routes(&first_account, &"Route_Order_1".to_string())), vec![first]);
+        assert_eq!(ids(order_routes(&first_account, &"Route_Order_2".to_string())), vec![second]);
+        // an order no strategy in the process sent goes to the strategies trading the account
+        assert_eq!(ids(order_routes(&first_account, &"Manual_Order".to_string())), vec![first, second]);
+        assert_eq!(ids(account_routes(&second_account)), vec![third]);
+
+        for id in [first, second, third] {
+            remove_route(id);
+        }
+        assert!(route(first).is_none());
+    }
 }
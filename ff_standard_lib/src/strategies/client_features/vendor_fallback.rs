@@ -0,0 +1,224 @@
+use std::time::Duration;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use tokio::time::Instant;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::datavendor_enum::DataVendor;
+use crate::standardized_types::subscriptions::{DataSubscription, Symbol};
+use crate::standardized_types::time_slices::TimeSlice;
+
+/// # Properties
+/// * `vendors` - The preferred vendor followed by the fallbacks in order.
+/// * `active` - The index of the vendor streaming the symbol live.
+/// * `stale_after` - If Some, the live stream moves to the next vendor when no data arrives for this long.
+/// * `last_data` - When data for the symbol was last received, or the stream last moved.
+/// * `failures` - The vendors which failed since data was last received, the stream stops moving once every vendor has failed.
+/// * `subscribe_failed` - The active vendor rejected the subscription.
+struct VendorFallback {
+    vendors: Vec<DataVendor>,
+    active: usize,
+    stale_after: Option<Duration>,
+    last_data: Instant,
+    failures: usize,
+    subscribe_failed: bool,
+}
+
+lazy_static! {
+    /// The fallbacks by the symbol of the preferred vendor.
+    static ref FALLBACKS: DashMap<Symbol, VendorFallback> = DashMap::new();
+    /// The symbol of the preferred vendor by the symbol of each vendor in its list.
+    static ref SOURCES: DashMap<Symbol, Symbol> = DashMap::new();
+}
+
+fn with_vendor(symbol: &Symbol, vendor: DataVendor) -> Symbol {
+    Symbol {
+        name: symbol.name.clone(),
+        market_type: symbol.market_type.clone(),
+        data_vendor: vendor,
+    }
+}
+
+fn subscription_with_vendor(subscription: &DataSubscription, vendor: DataVendor) -> DataSubscription {
+    let mut subscription = subscription.clone();
+    subscription.symbol.data_vendor = vendor;
+    subscription
+}
+
+/// Declares the vendors to fall back to for the symbol of the subscription, in order of preference.
+/// The fallbacks apply to every subscription of the symbol from the subscription vendor, including the primary subscriptions it is consolidated from.
+/// Call this before the strategy is initialized, an empty list removes the fallbacks.
+/// ```rust
+/// use ff_standard_lib::strategies::client_features::vendor_fallback::set_vendor_fallbacks;
+///
+/// let subscription = DataSubscription::new("MNQ".to_string(), DataVendor::Rithmic, Resolution::Ticks(1), BaseDataType::Ticks, MarketType::Futures(FuturesExchange::CME));
+/// // prefer Rithmic, fall back to DataBento if no ticks arrive for 10 seconds
+/// set_vendor_fallbacks(&subscription, vec![DataVendor::DataBento], Some(Duration::from_secs(10)));
+/// ```
+pub fn set_vendor_fallbacks(subscription: &DataSubscription, fallbacks: Vec<DataVendor>, stale_after: Option<Duration>) {
+    let preferred = subscription.symbol.clone();
+    if let Some((_, old)) = FALLBACKS.remove(&preferred) {
+        for vendor in old.vendors {
+            SOURCES.remove(&with_vendor(&preferred, vendor));
+        }
+    }
+    let mut vendors = vec![preferred.data_vendor];
+    for vendor in fallbacks {
+        if !vendors.contains(&vendor) {
+            vendors.push(vendor);
+        }
+    }
+    if vendors.len() == 1 {
+        return;
+    }
+    for vendor in &vendors {
+        SOURCES.insert(with_vendor(&preferred, *vendor), preferred.clone());
+    }
+    FALLBACKS.insert(preferred, VendorFallback {
+        vendors,
+        active: 0,
+        stale_after,
+        last_data: Instant::now(),
+        failures: 0,
+        subscribe_failed: false,
+    });
+}
+
+/// The subscription from each vendor to request history from in order of preference, None if the symbol has no fallbacks.
+pub(crate) fn history_subscriptions(subscription: &DataSubscription) -> Option<Vec<DataSubscription>> {
+    let fallback = FALLBACKS.get(&subscription.symbol)?;
+    Some(fallback.vendors.iter().map(|vendor| subscription_with_vendor(subscription, *vendor)).collect())
+}
+
+/// The subscription from the vendor which is streaming the symbol live.
+pub(crate) fn live_subscription(subscription: &DataSubscription) -> DataSubscription {
+    match FALLBACKS.get(&subscription.symbol) {
+        Some(fallback) => subscription_with_vendor(subscription, fallback.vendors[fallback.active]),
+        None => subscription.clone(),
+    }
+}
+
+/// The declared subscription for a subscription from one of its vendors.
+pub(crate) fn preferred_subscription(subscription: &DataSubscription) -> DataSubscription {
+    match SOURCES.get(&subscription.symbol) {
+        Some(preferred) => subscription_with_vendor(subscription, preferred.data_vendor),
+        None => subscription.clone(),
+    }
+}
+
+/// Moves the symbol of the failed live subscription to its next vendor, returns the preferred symbol and the new vendor.
+/// Returns None if the symbol has no fallbacks, the subscription is not from the active vendor or every vendor has failed since data was last received.
+pub(crate) fn fail_over(failed: &Symbol) -> Option<(Symbol, DataVendor)> {
+    let preferred = SOURCES.get(failed)?.value().clone();
+    let mut fallback = FALLBACKS.get_mut(&preferred)?;
+    if fallback.vendors[fallback.active] != failed.data_vendor {
+        return None;
+    }
+    fallback.failures += 1;
+    if fallback.failures >= fallback.vendors.len() {
+        return None;
+    }
+    fallback.active = (fallback.active + 1) % fallback.vendors.len();
+    fallback.last_data = Instant::now();
+    fallback.subscribe_failed = false;
+    Some((preferred, fallback.vendors[fallback.active]))
+}
+
+/// Records a rejected subscription, the live handler moves the symbol to its next vendor.
+pub(crate) fn subscribe_failed(subscription: &DataSubscription) {
+    let Some(preferred) = SOURCES.get(&subscription.symbol).map(|preferred| preferred.value().clone()) else {
+        return;
+    };
+    if let Some(mut fallback) = FALLBACKS.get_mut(&preferred) {
+        if fallback.vendors[fallback.active] == subscription.symbol.data_vendor {
+            fallback.subscribe_failed = true;
+        }
+    }
+}
+
+/// The live symbols from the active vendors which rejected the subscription or have received no data for longer than their `stale_after`.
+pub(crate) fn failed_symbols() -> Vec<Symbol> {
+    let now = Instant::now();
+    FALLBACKS.iter()
+        .filter(|fallback| fallback.subscribe_failed || match fallback.stale_after {
+            Some(stale_after) => now.duration_since(fallback.last_data) > stale_after,
+            None => false,
+        })
+        .map(|fallback| with_vendor(fallback.key(), fallback.vendors[fallback.active]))
+        .collect()
+}
+
+fn set_data_vendor(base_data: &mut BaseDataEnum, vendor: DataVendor) {
+    match base_data {
+        BaseDataEnum::Candle(candle) => candle.symbol.data_vendor = vendor,
+        BaseDataEnum::QuoteBar(quote_bar) => quote_bar.symbol.data_vendor = vendor,
+        BaseDataEnum::Tick(tick) => tick.symbol.data_vendor = vendor,
+        BaseDataEnum::Quote(quote) => quote.symbol.data_vendor = vendor,
+        BaseDataEnum::Fundamental(fundamental) => fundamental.symbol.data_vendor = vendor,
+        BaseDataEnum::Depth(depth) => depth.symbol.data_vendor = vendor,
+    }
+}
+
+/// Relabels data from a fallback vendor with the preferred vendor, and records the live data received for each symbol.
+pub(crate) fn relabel(time_slice: &mut TimeSlice, record_live_data: bool) {
+    if SOURCES.is_empty() {
+        return;
+    }
+    for base_data in time_slice.iter_mut() {
+        let preferred = match SOURCES.get(base_data.symbol()) {
+            Some(preferred) => preferred.value().clone(),
+            None => continue,
+        };
+        if record_live_data {
+            if let Some(mut fallback) = FALLBACKS.get_mut(&preferred) {
+                if fallback.vendors[fallback.active] == base_data.symbol().data_vendor {
+                    fallback.last_data = Instant::now();
+                    fallback.failures = 0;
+                }
+            }
+        }
+        if base_data.symbol().data_vendor != preferred.data_vendor {
+            set_data_vendor(base_data, preferred.data_vendor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::base_data::quote::Quote;
+    use crate::standardized_types::enums::MarketType;
+    use crate::standardized_types::resolution::Resolution;
+    use crate::standardized_types::base_data::base_data_type::BaseDataType;
+
+    #[test]
+    fn test_vendor_fallbacks() {
+        let subscription = DataSubscription::new("AUD-CAD".to_string(), DataVendor::Oanda, Resolution::Instant, BaseDataType::Quotes, MarketType::Forex);
+        set_vendor_fallbacks(&subscription, vec![DataVendor::DataBento, DataVendor::Oanda], None);
+
+        let vendors: Vec<DataVendor> = history_subscriptions(&subscription).unwrap().iter().map(|sub| sub.symbol.data_vendor).collect();
+        assert_eq!(vendors, vec![DataVendor::Oanda, DataVendor::DataBento]);
+        assert_eq!(live_subscription(&subscription), subscription);
+
+        // the stream moves to the fallback, and stops moving once every vendor failed without data
+        let (preferred, vendor) = fail_over(&subscription.symbol).unwrap();
+        assert_eq!((preferred, vendor), (subscription.symbol.clone(), DataVendor::DataBento));
+        let fallback_subscription = live_subscription(&subscription);
+        assert_eq!(fallback_subscription.symbol.data_vendor, DataVendor::DataBento);
+        assert_eq!(preferred_subscription(&fallback_subscription), subscription);
+        assert_eq!(fail_over(&subscription.symbol), None);
+        assert_eq!(fail_over(&fallback_subscription.symbol), None);
+
+        // fallback data is relabelled and resets the failures
+        let quote = Quote::new(fallback_subscription.symbol.clone(), dec!(0.9), dec!(0.8999), dec!(1), dec!(1), Utc::now().to_string());
+        let mut time_slice = TimeSlice::from_iter([BaseDataEnum::Quote(quote)]);
+        relabel(&mut time_slice, true);
+        assert!(time_slice.iter().all(|data| data.symbol().data_vendor == DataVendor::Oanda));
+        assert_eq!(fail_over(&fallback_subscription.symbol), Some((subscription.symbol.clone(), DataVendor::Oanda)));
+
+        set_vendor_fallbacks(&subscription, vec![], None);
+        assert_eq!(history_subscriptions(&subscription), None);
+    }
+}
@@ -330,7 +330,7 @@ impl CandleStickConsolidator {
         tick_size: Decimal,
     ) -> Result<Self, FundForgeError> {
         println!("Creating Consolidator For: {}", subscription);
-        if subscription.base_data_type == BaseDataType::Fundamentals {
+        if subscription.base_data_type == BaseDataType::Fundamentals || subscription.base_data_type == BaseDataType::Depth {
             return Err(FundForgeError::ClientSideErrorDebug(format!(
                     "{} is an Invalid base data type for TimeConsolidator",
                     subscription.base_data_type
@@ -377,6 +377,7 @@ impl CandleStickConsolidator {
                 self.update_candles(base_data)
             }
             BaseDataType::Fundamentals => panic!("Fundamentals are not supported"),
+            BaseDataType::Depth => panic!("Depth is not supported"),
         }
     }
 }
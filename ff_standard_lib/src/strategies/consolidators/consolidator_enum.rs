@@ -1,6 +1,7 @@
 use crate::strategies::consolidators::candlesticks::CandleStickConsolidator;
 use crate::strategies::consolidators::count::CountConsolidator;
 use crate::strategies::consolidators::heikinashi::HeikinAshiConsolidator;
+use crate::strategies::consolidators::renko::RenkoConsolidator;
 use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
 use crate::standardized_types::enums::{MarketType, StrategyMode};
 use crate::standardized_types::rolling_window::RollingWindow;
@@ -16,10 +17,14 @@ use crate::strategies::consolidators::daily_quotebars::DailyQuoteConsolidator;
 use crate::strategies::consolidators::weekly::WeeklyCandleConsolidator;
 use crate::strategies::consolidators::weekly_quotebars::WeeklyQuoteConsolidator;
 
+/// The days of primary data a renko consolidator is warmed up from.
+const RENKO_WARM_UP_DAYS: i64 = 5;
+
 pub enum ConsolidatorEnum {
     Count(CountConsolidator),
     CandleStickConsolidator(CandleStickConsolidator),
     HeikinAshi(HeikinAshiConsolidator),
+    Renko(RenkoConsolidator),
     DailyCandles(DailyConsolidator),
     DailyQuoteBars(DailyQuoteConsolidator),
     WeeklyCandles(WeeklyCandleConsolidator),
@@ -41,6 +46,12 @@ impl ConsolidatorEnum {
         let decimal_accuracy = subscription.symbol.data_vendor.decimal_accuracy(symbol_name.clone()).await.unwrap();
         let tick_size = subscription.symbol.data_vendor.tick_size(symbol_name.clone()).await.unwrap();
 
+        if subscription.candle_type.as_ref().map_or(false, CandleType::is_renko) {
+            return ConsolidatorEnum::Renko(
+                RenkoConsolidator::new(subscription.clone(), decimal_accuracy, tick_size).unwrap(),
+            );
+        }
+
         match subscription.resolution {
             Resolution::Day => {
                 match subscription.base_data_type {
@@ -83,6 +94,7 @@ impl ConsolidatorEnum {
                         .await
                         .unwrap(),
                 ),
+                CandleType::UniRenko { .. } | CandleType::MeanRenko { .. } => unreachable!("renko consolidators are created above"),
                 CandleType::CandleStick => ConsolidatorEnum::CandleStickConsolidator(
                     CandleStickConsolidator::new(subscription.clone(), fill_forward, decimal_accuracy, tick_size)
                         .await
@@ -105,6 +117,7 @@ impl ConsolidatorEnum {
             ConsolidatorEnum::HeikinAshi(heikin_ashi_consolidator) => {
                 heikin_ashi_consolidator.update(base_data)
             }
+            ConsolidatorEnum::Renko(consolidator) => consolidator.update(base_data),
             ConsolidatorEnum::DailyCandles(consolidator) => consolidator.update(base_data),
             ConsolidatorEnum::DailyQuoteBars(consolidator) => consolidator.update(base_data),
             ConsolidatorEnum::WeeklyCandles(consolidator) => consolidator.update(base_data),
@@ -122,6 +135,7 @@ impl ConsolidatorEnum {
             ConsolidatorEnum::HeikinAshi(heikin_ashi_consolidator) => {
                 &heikin_ashi_consolidator.subscription
             }
+            ConsolidatorEnum::Renko(consolidator) => &consolidator.subscription,
             ConsolidatorEnum::DailyCandles(consolidator) => &consolidator.subscription,
             ConsolidatorEnum::DailyQuoteBars(consolidator) => &consolidator.subscription,
             ConsolidatorEnum::WeeklyCandles(consolidator) => &consolidator.subscription,
@@ -141,6 +155,9 @@ impl ConsolidatorEnum {
             ConsolidatorEnum::HeikinAshi(heikin_ashi_consolidator) => {
                 &heikin_ashi_consolidator.subscription.resolution
             }
+            ConsolidatorEnum::Renko(consolidator) => {
+                &consolidator.subscription.resolution
+            }
             ConsolidatorEnum::DailyCandles(consolidator) => {
                 &consolidator.subscription.resolution
             }
@@ -160,6 +177,7 @@ impl ConsolidatorEnum {
     pub fn update_time(&mut self, time: DateTime<Utc>) -> Option<BaseDataEnum> {
         match self {
             ConsolidatorEnum::Count(_) => None,
+            ConsolidatorEnum::Renko(_) => None,
             ConsolidatorEnum::CandleStickConsolidator(time_consolidator) => {
                 time_consolidator.update_time(time)
             }
@@ -218,7 +236,11 @@ impl ConsolidatorEnum {
         };
         //eprintln!("Min resolution: {:?}", min_resolution);
 
-        let subtract_duration: Duration = consolidator.resolution().as_duration() * history_to_retain;
+        let subtract_duration: Duration = match subscription.candle_type.as_ref().map_or(false, CandleType::is_renko) {
+            // renko bars have no duration, they are rebuilt from the last few days of data
+            true => Duration::days(RENKO_WARM_UP_DAYS),
+            false => consolidator.resolution().as_duration() * history_to_retain,
+        };
         let mut from_time = to_time - subtract_duration ;
 
         if to_time.weekday() == Weekday::Sun {
@@ -61,7 +61,7 @@ impl DailyConsolidator {
         tick_size: Decimal,
         trading_hours: TradingHours,
     ) -> Result<Self, FundForgeError> {
-        if subscription.base_data_type == BaseDataType::Fundamentals {
+        if subscription.base_data_type == BaseDataType::Fundamentals || subscription.base_data_type == BaseDataType::Depth {
             return Err(FundForgeError::ClientSideErrorDebug(format!(
                 "{} is an Invalid base data type for DailyConsolidator",
                 subscription.base_data_type
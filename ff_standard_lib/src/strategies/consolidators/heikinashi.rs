@@ -191,7 +191,7 @@ impl HeikinAshiConsolidator {
         decimal_accuracy: u32,
         tick_size: Decimal,
     ) -> Result<HeikinAshiConsolidator, FundForgeError> {
-        if subscription.base_data_type == BaseDataType::Fundamentals {
+        if subscription.base_data_type == BaseDataType::Fundamentals || subscription.base_data_type == BaseDataType::Depth {
             return Err(FundForgeError::ClientSideErrorDebug(format!(
                     "{} is an Invalid base data type for HeikinAshiConsolidator",
                     subscription.base_data_type
@@ -2,7 +2,9 @@ pub mod candlesticks;
 pub mod consolidator_enum;
 pub mod count;
 pub mod heikinashi;
+pub mod renko;
 pub mod daily_candles;
 pub mod daily_quotebars;
 pub mod weekly;
 mod weekly_quotebars;
+pub mod warmup_check;
@@ -0,0 +1,244 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::base_data::candle::Candle;
+use crate::standardized_types::base_data::tick::Aggressor;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::enums::MarketType;
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::subscriptions::{CandleType, DataSubscription};
+use crate::strategies::consolidators::consolidator_enum::ConsolidatedData;
+
+/// Consolidates `CandleType::UniRenko` and `CandleType::MeanRenko` candles from ticks, quotes, candles or quote bars.
+pub struct RenkoConsolidator {
+    pub(crate) subscription: DataSubscription,
+    candle_type: CandleType,
+    current_data: Option<Candle>,
+    /// The price the thresholds of the current bar are measured from.
+    reference: Price,
+    /// The direction of the last closed bar, None before the first bar closes.
+    last_up: Option<bool>,
+    decimal_accuracy: u32,
+    tick_size: Decimal,
+    market_type: MarketType,
+}
+
+impl RenkoConsolidator {
+    pub(crate) fn new(
+        subscription: DataSubscription,
+        decimal_accuracy: u32,
+        tick_size: Decimal,
+    ) -> Result<Self, FundForgeError> {
+        if subscription.base_data_type != BaseDataType::Candles {
+            return Err(FundForgeError::ClientSideErrorDebug(format!("{} is an Invalid base data type for RenkoConsolidator", subscription.base_data_type)));
+        }
+        let candle_type = match &subscription.candle_type {
+            Some(candle_type) if candle_type.is_renko() => candle_type.clone(),
+            candle_type => return Err(FundForgeError::ClientSideErrorDebug(format!("{:?} is an Invalid candle type for RenkoConsolidator", candle_type))),
+        };
+        let valid = match candle_type {
+            CandleType::UniRenko { trend_ticks, reversal_ticks, .. } => trend_ticks > 0 && reversal_ticks > 0,
+            CandleType::MeanRenko { brick_ticks } => brick_ticks > 0,
+            _ => false,
+        };
+        if !valid || tick_size <= dec!(0) {
+            return Err(FundForgeError::ClientSideErrorDebug(format!("{}: renko bars need a positive brick size and tick size", subscription)));
+        }
+        println!("Creating Consolidator For: {}", subscription);
+        let market_type = subscription.symbol.market_type.clone();
+        Ok(RenkoConsolidator {
+            subscription,
+            candle_type,
+            current_data: None,
+            reference: dec!(0),
+            last_up: None,
+            decimal_accuracy,
+            tick_size,
+            market_type,
+        })
+    }
+
+    fn ticks(&self, ticks: u32) -> Price {
+        self.tick_size * Decimal::from(ticks)
+    }
+
+    /// The prices at which the current bar closes up and down.
+    fn thresholds(&self) -> (Price, Price) {
+        match self.candle_type {
+            CandleType::UniRenko { trend_ticks, reversal_ticks, .. } => {
+                let up_ticks = if self.last_up == Some(false) { reversal_ticks } else { trend_ticks };
+                let down_ticks = if self.last_up == Some(true) { reversal_ticks } else { trend_ticks };
+                (self.reference + self.ticks(up_ticks), self.reference - self.ticks(down_ticks))
+            }
+            CandleType::MeanRenko { brick_ticks } => (self.reference + self.ticks(brick_ticks), self.reference - self.ticks(brick_ticks)),
+            _ => unreachable!("RenkoConsolidator only holds renko candle types"),
+        }
+    }
+
+    fn new_bar(&self, open: Price, time: DateTime<Utc>) -> Candle {
+        Candle {
+            symbol: self.subscription.symbol.clone(),
+            open,
+            high: open,
+            low: open,
+            close: open,
+            volume: dec!(0),
+            ask_volume: dec!(0),
+            bid_volume: dec!(0),
+            range: dec!(0),
+            time: time.to_string(),
+            resolution: self.subscription.resolution.clone(),
+            is_closed: false,
+            candle_type: self.candle_type.clone(),
+        }
+    }
+
+    fn add_price(&self, bar: &mut Candle, price: Price) {
+        bar.high = bar.high.max(price);
+        bar.low = bar.low.min(price);
+        bar.close = price;
+        bar.range = self.market_type.round_price(bar.high - bar.low, self.tick_size, self.decimal_accuracy);
+    }
+
+    /// The price, volume, ask volume and bid volume of the data.
+    fn price_and_volume(base_data: &BaseDataEnum) -> (Price, Volume, Volume, Volume) {
+        match base_data {
+            BaseDataEnum::Tick(tick) => match tick.aggressor {
+                Aggressor::Buy => (tick.price, tick.volume, dec!(0), tick.volume),
+                Aggressor::Sell => (tick.price, tick.volume, tick.volume, dec!(0)),
+                Aggressor::None => (tick.price, tick.volume, dec!(0), dec!(0)),
+            },
+            BaseDataEnum::Quote(quote) => (quote.bid, quote.ask_volume + quote.bid_volume, quote.ask_volume, quote.bid_volume),
+            BaseDataEnum::Candle(candle) => (candle.close, candle.volume, candle.ask_volume, candle.bid_volume),
+            BaseDataEnum::QuoteBar(bar) => (bar.bid_close, bar.volume, bar.ask_volume, bar.bid_volume),
+            _ => panic!("Invalid base data type for Renko consolidator: {}", base_data.base_data_type()),
+        }
+    }
+
+    pub(crate) fn update(&mut self, base_data: &BaseDataEnum) -> ConsolidatedData {
+        let (price, volume, ask_volume, bid_volume) = Self::price_and_volume(base_data);
+        let price = self.market_type.round_price(price, self.tick_size, self.decimal_accuracy);
+        let time = base_data.time_closed_utc();
+
+        let mut bar = match self.current_data.take() {
+            Some(bar) => bar,
+            None => {
+                self.reference = price;
+                self.new_bar(price, time)
+            }
+        };
+        bar.volume += volume;
+        bar.ask_volume += ask_volume;
+        bar.bid_volume += bid_volume;
+
+        let (up, down) = self.thresholds();
+        if price < up && price > down {
+            self.add_price(&mut bar, price);
+            self.current_data = Some(bar.clone());
+            return ConsolidatedData::with_open(BaseDataEnum::Candle(bar));
+        }
+
+        let is_up = price >= up;
+        let close = if is_up { up } else { down };
+        self.add_price(&mut bar, close);
+        bar.is_closed = true;
+
+        let open = match self.candle_type {
+            CandleType::UniRenko { open_offset_ticks, .. } => {
+                self.reference = close;
+                match is_up {
+                    true => close - self.ticks(open_offset_ticks),
+                    false => close + self.ticks(open_offset_ticks),
+                }
+            }
+            _ => {
+                self.reference = self.market_type.round_price((bar.open + close) / dec!(2), self.tick_size, self.decimal_accuracy);
+                self.reference
+            }
+        };
+        self.last_up = Some(is_up);
+
+        let mut new_bar = self.new_bar(open, time);
+        self.add_price(&mut new_bar, price);
+        self.current_data = Some(new_bar.clone());
+        ConsolidatedData::with_closed(BaseDataEnum::Candle(new_bar), BaseDataEnum::Candle(bar))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standardized_types::base_data::tick::Tick;
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::FuturesExchange;
+    use crate::standardized_types::resolution::Resolution;
+
+    fn consolidator(candle_type: CandleType) -> RenkoConsolidator {
+        let subscription = DataSubscription::new_custom("MNQ".to_string(), DataVendor::Rithmic, Resolution::Ticks(1), MarketType::Futures(FuturesExchange::CME), candle_type);
+        RenkoConsolidator::new(subscription, 2, dec!(0.25)).unwrap()
+    }
+
+    fn tick(consolidator: &RenkoConsolidator, price: Price, second: i64) -> BaseDataEnum {
+        let time = DateTime::<Utc>::from_timestamp(1_717_400_000 + second, 0).unwrap();
+        BaseDataEnum::Tick(Tick::new(consolidator.subscription.symbol.clone(), price, time.to_string(), dec!(1), Aggressor::None))
+    }
+
+    /// Feeds the prices and returns the closed bars as (open, high, low, close).
+    fn closed_bars(consolidator: &mut RenkoConsolidator, prices: &[Price]) -> Vec<(Price, Price, Price, Price)> {
+        let mut bars = vec![];
+        for (i, price) in prices.iter().enumerate() {
+            let data = tick(consolidator, *price, i as i64);
+            if let Some(BaseDataEnum::Candle(bar)) = consolidator.update(&data).closed_data {
+                assert!(bar.is_closed);
+                bars.push((bar.open, bar.high, bar.low, bar.close));
+            }
+        }
+        bars
+    }
+
+    #[test]
+    fn test_uni_renko() {
+        // 4 ticks with the trend, 8 against it, the next bar opens 2 ticks back
+        let mut renko = consolidator(CandleType::UniRenko { trend_ticks: 4, reversal_ticks: 8, open_offset_ticks: 2 });
+        let bars = closed_bars(&mut renko, &[dec!(100), dec!(99.5), dec!(100.75), dec!(101), dec!(101.5), dec!(102), dec!(101), dec!(100)]);
+        assert_eq!(bars, vec![
+            (dec!(100), dec!(101), dec!(99.5), dec!(101)),
+            // opens 2 ticks below the close, continues up at 102
+            (dec!(100.5), dec!(102), dec!(100.5), dec!(102)),
+            // a reversal needs 8 ticks from the close of 102, the high is the price which closed the last bar
+            (dec!(101.5), dec!(102), dec!(100), dec!(100)),
+        ]);
+        assert_eq!(renko.thresholds(), (dec!(102), dec!(99)));
+    }
+
+    #[test]
+    fn test_mean_renko() {
+        let mut renko = consolidator(CandleType::MeanRenko { brick_ticks: 4 });
+        let bars = closed_bars(&mut renko, &[dec!(100), dec!(101), dec!(101.25), dec!(101.5), dec!(100.25), dec!(99.5)]);
+        assert_eq!(bars, vec![
+            (dec!(100), dec!(101), dec!(100), dec!(101)),
+            // opens at the midpoint 100.5 of the last bar, closes 4 ticks away
+            (dec!(100.5), dec!(101.5), dec!(100.5), dec!(101.5)),
+            (dec!(101), dec!(101.5), dec!(100), dec!(100)),
+        ]);
+    }
+
+    #[test]
+    fn test_gap_closes_one_bar_per_update() {
+        let mut renko = consolidator(CandleType::UniRenko { trend_ticks: 4, reversal_ticks: 4, open_offset_ticks: 0 });
+        let bars = closed_bars(&mut renko, &[dec!(100), dec!(102.25), dec!(102.25)]);
+        assert_eq!(bars, vec![
+            (dec!(100), dec!(101), dec!(100), dec!(101)),
+            // the second bar started beyond its threshold and closes at it on the next update, keeping the wick
+            (dec!(101), dec!(102.25), dec!(101), dec!(102)),
+        ]);
+        assert!(RenkoConsolidator::new(
+            DataSubscription::new_custom("MNQ".to_string(), DataVendor::Rithmic, Resolution::Ticks(1), MarketType::Futures(FuturesExchange::CME), CandleType::MeanRenko { brick_ticks: 0 }),
+            2,
+            dec!(0.25)
+        ).is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use chrono::{DateTime, Utc};
+use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
+use rust_decimal_macros::dec;
+use tokio::sync::mpsc::Sender;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::base_data::history::get_compressed_historical_data;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::resolution::Resolution;
+use crate::standardized_types::subscriptions::{CandleType, DataSubscription};
+use crate::strategies::consolidators::consolidator_enum::ConsolidatorEnum;
+use crate::strategies::strategy_events::StrategyEvent;
+
+/// The result of a failed warm up check, see `StrategyEvent::WarmUpWarning`.
+///
+/// # Properties
+/// * `subscription` - The consolidated subscription.
+/// * `primary_subscription` - The subscription the bars are consolidated from.
+/// * `bars_checked` - The number of retained bars compared.
+/// * `missing_bars` - Bars in the primary history which are not in the retained history.
+/// * `unexpected_bars` - Retained bars which are not in the primary history, eg misaligned bar times.
+/// * `mismatched_bars` - Retained bars whose prices or volume differ from the primary history.
+/// * `first_problem` - A description of the earliest problem.
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct WarmUpWarning {
+    pub subscription: DataSubscription,
+    pub primary_subscription: DataSubscription,
+    pub bars_checked: u64,
+    pub missing_bars: u64,
+    pub unexpected_bars: u64,
+    pub mismatched_bars: u64,
+    pub first_problem: String,
+}
+
+impl Display for WarmUpWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Warm Up Warning: {} from {}, checked: {}, missing: {}, unexpected: {}, mismatched: {}, first: {}",
+            self.subscription, self.primary_subscription, self.bars_checked, self.missing_bars, self.unexpected_bars, self.mismatched_bars, self.first_problem
+        )
+    }
+}
+
+/// The retained bars of a consolidated subscription at the end of the warm up, oldest first.
+pub(crate) struct WarmUpCheck {
+    pub(crate) subscription: DataSubscription,
+    pub(crate) primary_subscription: DataSubscription,
+    pub(crate) bars: Vec<BaseDataEnum>,
+}
+
+/// True if the bars of the subscription can be compared with bars consolidated from a different starting point.
+pub(crate) fn is_checked(subscription: &DataSubscription) -> bool {
+    let is_intraday = matches!(subscription.resolution, Resolution::Seconds(_) | Resolution::Minutes(_) | Resolution::Hours(_));
+    let is_bar = subscription.base_data_type == BaseDataType::Candles || subscription.base_data_type == BaseDataType::QuoteBars;
+    is_intraday && is_bar && subscription.candle_type == Some(CandleType::CandleStick)
+}
+
+/// Runs the checks and sends a warning event for each failed check.
+pub(crate) async fn check_warmup(checks: Vec<WarmUpCheck>, strategy_event_sender: Sender<StrategyEvent>) {
+    for check in checks {
+        let expected = match check.expected_bars().await {
+            Some(expected) => expected,
+            None => continue,
+        };
+        if let Some(warning) = compare_bars(&check.subscription, &check.primary_subscription, &check.bars, &expected) {
+            eprintln!("{}", warning);
+            if let Err(e) = strategy_event_sender.send(StrategyEvent::WarmUpWarning(warning)).await {
+                eprintln!("Warm Up Check: Failed to send event: {}", e);
+            }
+        }
+    }
+}
+
+impl WarmUpCheck {
+    /// Consolidates the primary history over the period of the retained bars.
+    async fn expected_bars(&self) -> Option<Vec<BaseDataEnum>> {
+        let from_time = self.bars.first()?.time_utc();
+        let to_time = self.bars.last()?.time_closed_utc();
+        let data = get_compressed_historical_data(vec![self.primary_subscription.clone()], from_time, to_time).await.ok()?;
+
+        let mut consolidator = ConsolidatorEnum::create_consolidator(self.subscription.clone(), false, None).await;
+        let mut bars = vec![];
+        for (_, time_slice) in data {
+            for base_data in time_slice.iter() {
+                if let Some(closed_data) = consolidator.update(base_data).closed_data {
+                    bars.push(closed_data);
+                }
+            }
+        }
+        if let Some(closed_data) = consolidator.update_time(to_time) {
+            bars.push(closed_data);
+        }
+        Some(bars)
+    }
+}
+
+fn bars_match(retained: &BaseDataEnum, expected: &BaseDataEnum) -> bool {
+    match (retained, expected) {
+        (BaseDataEnum::Candle(a), BaseDataEnum::Candle(b)) => {
+            a.open == b.open && a.high == b.high && a.low == b.low && a.close == b.close && a.volume == b.volume
+        }
+        (BaseDataEnum::QuoteBar(a), BaseDataEnum::QuoteBar(b)) => {
+            a.bid_open == b.bid_open && a.bid_high == b.bid_high && a.bid_low == b.bid_low && a.bid_close == b.bid_close
+                && a.ask_open == b.ask_open && a.ask_high == b.ask_high && a.ask_low == b.ask_low && a.ask_close == b.ask_close
+                && a.volume == b.volume
+        }
+        _ => false,
+    }
+}
+
+/// Filled forward bars have no volume and are not produced when consolidating the primary history.
+fn is_filled_forward(bar: &BaseDataEnum) -> bool {
+    match bar {
+        BaseDataEnum::Candle(candle) => candle.volume == dec!(0) && candle.high == candle.low,
+        BaseDataEnum::QuoteBar(bar) => bar.volume == dec!(0) && bar.bid_high == bar.bid_low && bar.ask_high == bar.ask_low,
+        _ => false,
+    }
+}
+
+/// Compares the retained bars with the expected bars, both oldest first, returns None if they match.
+pub(crate) fn compare_bars(
+    subscription: &DataSubscription,
+    primary_subscription: &DataSubscription,
+    retained: &[BaseDataEnum],
+    expected: &[BaseDataEnum],
+) -> Option<WarmUpWarning> {
+    let (Some(first), Some(last)) = (retained.first(), retained.last()) else {
+        return None;
+    };
+    let (from_time, to_time) = (first.time_utc(), last.time_utc());
+    let expected: BTreeMap<DateTime<Utc>, &BaseDataEnum> = expected.iter()
+        .map(|bar| (bar.time_utc(), bar))
+        .filter(|(time, _)| *time >= from_time && *time <= to_time)
+        .collect();
+
+    let mut problems: BTreeMap<DateTime<Utc>, String> = BTreeMap::new();
+    let (mut missing_bars, mut unexpected_bars, mut mismatched_bars) = (0, 0, 0);
+    for bar in retained {
+        let time = bar.time_utc();
+        match expected.get(&time) {
+            Some(expected_bar) if bars_match(bar, expected_bar) => {}
+            Some(expected_bar) => {
+                mismatched_bars += 1;
+                problems.entry(time).or_insert_with(|| format!("retained {} but the primary history gives {}", bar, expected_bar));
+            }
+            None if is_filled_forward(bar) => {}
+            None => {
+                unexpected_bars += 1;
+                problems.entry(time).or_insert_with(|| format!("retained {} is not in the primary history", bar));
+            }
+        }
+    }
+    let retained_times: Vec<DateTime<Utc>> = retained.iter().map(|bar| bar.time_utc()).collect();
+    for (time, bar) in &expected {
+        if !retained_times.contains(time) {
+            missing_bars += 1;
+            problems.entry(*time).or_insert_with(|| format!("missing {}", bar));
+        }
+    }
+
+    let (_, first_problem) = problems.into_iter().next()?;
+    Some(WarmUpWarning {
+        subscription: subscription.clone(),
+        primary_subscription: primary_subscription.clone(),
+        bars_checked: retained.len() as u64,
+        missing_bars,
+        unexpected_bars,
+        mismatched_bars,
+        first_problem,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use crate::standardized_types::base_data::candle::Candle;
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::MarketType;
+    use crate::standardized_types::new_types::Price;
+    use crate::standardized_types::subscriptions::Symbol;
+
+    fn candle(time: DateTime<Utc>, close: Price) -> BaseDataEnum {
+        let symbol = Symbol::new("EUR-USD".to_string(), DataVendor::DataBento, MarketType::Forex);
+        let mut candle = Candle::new(symbol, dec!(1.1000), dec!(10), dec!(0), dec!(0), time.to_string(), Resolution::Minutes(15), CandleType::CandleStick);
+        candle.close = close;
+        candle.high = close.max(candle.high);
+        candle.low = close.min(candle.low);
+        candle.is_closed = true;
+        BaseDataEnum::Candle(candle)
+    }
+
+    #[test]
+    fn test_compare_bars() {
+        let subscription = DataSubscription::new("EUR-USD".to_string(), DataVendor::DataBento, Resolution::Minutes(15), BaseDataType::Candles, MarketType::Forex);
+        let primary = DataSubscription::new("EUR-USD".to_string(), DataVendor::DataBento, Resolution::Minutes(1), BaseDataType::Candles, MarketType::Forex);
+        let start = DateTime::parse_from_rfc3339("2024-06-03T10:00:00Z").unwrap().with_timezone(&Utc);
+        let times: Vec<DateTime<Utc>> = (0..4).map(|i| start + Duration::minutes(15 * i)).collect();
+
+        let expected: Vec<BaseDataEnum> = times.iter().map(|time| candle(*time, dec!(1.1010))).collect();
+        assert_eq!(compare_bars(&subscription, &primary, &expected, &expected), None);
+
+        // the warm up started part way through the first bar and skipped the third bar
+        let retained = vec![candle(times[0], dec!(1.1005)), candle(times[1], dec!(1.1010)), candle(times[3], dec!(1.1010))];
+        let warning = compare_bars(&subscription, &primary, &retained, &expected).unwrap();
+        assert_eq!(warning.bars_checked, 3);
+        assert_eq!(warning.mismatched_bars, 1);
+        assert_eq!(warning.missing_bars, 1);
+        assert_eq!(warning.unexpected_bars, 0);
+        assert!(warning.first_problem.starts_with("retained"));
+    }
+}
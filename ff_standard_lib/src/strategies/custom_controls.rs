@@ -0,0 +1,185 @@
+use std::fmt::Debug;
+use std::sync::RwLock;
+use ahash::AHashMap;
+use lazy_static::lazy_static;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::strategies::strategy_events::StrategyControls;
+use crate::strategies::strategy_panel::PanelInteraction;
+
+/// A typed command sent to the strategy as `StrategyControls::CustomBytes`.
+///
+/// # Example
+/// ```rust
+/// #[derive(Serialize, Deserialize, Debug)]
+/// enum MyCommands {
+///     SetSize(Decimal),
+///     Hedge { symbol: SymbolName },
+/// }
+///
+/// impl ControlCommand for MyCommands {
+///     const CONTROL_ID: u64 = control_id("MyCommands");
+///     const NAME: &'static str = "MyCommands";
+/// }
+///
+/// register_control::<MyCommands>().unwrap();
+/// sender.send(StrategyEvent::StrategyControls(MyCommands::SetSize(dec!(2)).to_control()?)).await;
+///
+/// // in the strategy event loop
+/// StrategyEvent::StrategyControls(control) => {
+///     if let Some(MyCommands::SetSize(size)) = MyCommands::from_control(&control) {
+///         position_size = size;
+///     }
+/// }
+/// ```
+pub trait ControlCommand: Serialize + DeserializeOwned + Debug + Send + 'static {
+    /// Identifies the command type in `StrategyControls::CustomBytes`, use `control_id()` to derive it from the name.
+    const CONTROL_ID: u64;
+    const NAME: &'static str;
+
+    fn to_control(&self) -> Result<StrategyControls, FundForgeError> {
+        match serde_json::to_vec(self) {
+            Ok(bytes) => Ok(StrategyControls::CustomBytes(Self::CONTROL_ID, bytes)),
+            Err(e) => Err(FundForgeError::ClientSideErrorDebug(format!("Failed to serialize {} control: {}", Self::NAME, e)))
+        }
+    }
+
+    /// Returns the command if the control is a `CustomBytes` with this `CONTROL_ID`.
+    fn from_control(control: &StrategyControls) -> Option<Self> {
+        match control {
+            StrategyControls::CustomBytes(id, bytes) if *id == Self::CONTROL_ID => {
+                match serde_json::from_slice(bytes) {
+                    Ok(command) => Some(command),
+                    Err(e) => {
+                        eprintln!("Custom Controls: Failed to deserialize {} control: {}", Self::NAME, e);
+                        None
+                    }
+                }
+            }
+            _ => None
+        }
+    }
+}
+
+/// Derives a `CONTROL_ID` from a name using FNV-1a, so ids are stable between builds and processes.
+pub const fn control_id(name: &str) -> u64 {
+    let bytes = name.as_bytes();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+    hash
+}
+
+/// The commands sent by the ff_gui strategy control panel.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum PanelCommand {
+    Reduce,
+    Increase,
+    Flatten,
+    RiskReward(f64),
+    TradeLong,
+    TradeShort,
+}
+
+impl ControlCommand for PanelCommand {
+    const CONTROL_ID: u64 = control_id("PanelCommand");
+    const NAME: &'static str = "PanelCommand";
+}
+
+struct RegisteredControl {
+    name: &'static str,
+    to_json: fn(&[u8]) -> Option<Value>,
+}
+
+fn decode_json<T: ControlCommand>(bytes: &[u8]) -> Option<Value> {
+    let command: T = serde_json::from_slice(bytes).ok()?;
+    serde_json::to_value(command).ok()
+}
+
+lazy_static! {
+    static ref CONTROL_REGISTRY: RwLock<AHashMap<u64, RegisteredControl>> = {
+        let mut registry = AHashMap::new();
+        registry.insert(PanelCommand::CONTROL_ID, RegisteredControl { name: PanelCommand::NAME, to_json: decode_json::<PanelCommand> });
+        registry.insert(PanelInteraction::CONTROL_ID, RegisteredControl { name: PanelInteraction::NAME, to_json: decode_json::<PanelInteraction> });
+        RwLock::new(registry)
+    };
+}
+
+/// Registers a command type so it can be decoded by name, registering the same type twice is allowed,
+/// a different type with the same `CONTROL_ID` is an error.
+pub fn register_control<T: ControlCommand>() -> Result<(), FundForgeError> {
+    let mut registry = CONTROL_REGISTRY.write().unwrap();
+    if let Some(existing) = registry.get(&T::CONTROL_ID) {
+        return match existing.name == T::NAME {
+            true => Ok(()),
+            false => Err(FundForgeError::ClientSideErrorDebug(format!("Control id {} is already registered to {}, can not register {}", T::CONTROL_ID, existing.name, T::NAME)))
+        };
+    }
+    registry.insert(T::CONTROL_ID, RegisteredControl { name: T::NAME, to_json: decode_json::<T> });
+    Ok(())
+}
+
+/// The name of the command type registered for the id.
+pub fn control_name(control_id: u64) -> Option<&'static str> {
+    CONTROL_REGISTRY.read().unwrap().get(&control_id).map(|control| control.name)
+}
+
+/// Decodes a `CustomBytes` control of a registered type as `{"name": .., "command": ..}`.
+pub fn control_to_json(control: &StrategyControls) -> Option<Value> {
+    let StrategyControls::CustomBytes(id, bytes) = control else {
+        return None;
+    };
+    let registry = CONTROL_REGISTRY.read().unwrap();
+    let registered = registry.get(id)?;
+    let command = (registered.to_json)(bytes)?;
+    Some(serde_json::json!({"name": registered.name, "command": command}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum TestCommand {
+        SetSize(u32),
+    }
+
+    impl ControlCommand for TestCommand {
+        const CONTROL_ID: u64 = control_id("TestCommand");
+        const NAME: &'static str = "TestCommand";
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    struct Collision;
+
+    impl ControlCommand for Collision {
+        const CONTROL_ID: u64 = control_id("TestCommand");
+        const NAME: &'static str = "Collision";
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let control = PanelCommand::RiskReward(1.5).to_control().unwrap();
+        assert_eq!(PanelCommand::from_control(&control), Some(PanelCommand::RiskReward(1.5)));
+        assert_eq!(TestCommand::from_control(&control), None);
+        assert_eq!(PanelCommand::from_control(&StrategyControls::Custom("Reduce".to_string())), None);
+        assert_ne!(PanelCommand::CONTROL_ID, TestCommand::CONTROL_ID);
+    }
+
+    #[test]
+    fn test_registry() {
+        let control = TestCommand::SetSize(3).to_control().unwrap();
+        assert!(control_to_json(&control).is_none());
+        register_control::<TestCommand>().unwrap();
+        register_control::<TestCommand>().unwrap();
+        assert!(register_control::<Collision>().is_err());
+        assert_eq!(control_name(TestCommand::CONTROL_ID), Some("TestCommand"));
+        assert_eq!(control_to_json(&control), Some(serde_json::json!({"name": "TestCommand", "command": {"SetSize": 3}})));
+    }
+}
@@ -0,0 +1,162 @@
+use std::fs::read_to_string;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use lazy_static::lazy_static;
+use serde_derive::Deserialize;
+use crate::messages::data_server_messaging::FundForgeError;
+
+/// A held out period no backtest should run on, see `set_embargoed_ranges()`.
+/// # Properties
+/// * `name` - The name shown when a backtest is refused, eg "2024 H2 validation".
+/// * `start` - The start of the range in UTC, inclusive.
+/// * `end` - The end of the range in UTC, exclusive.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmbargoedRange {
+    pub name: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl EmbargoedRange {
+    pub fn new(name: String, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        EmbargoedRange {
+            name,
+            start,
+            end,
+        }
+    }
+
+    /// True if the range from `start` to `end` has any time in the embargo.
+    fn overlaps(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        start < self.end && self.start <= end
+    }
+}
+
+lazy_static! {
+    static ref EMBARGOED_RANGES: RwLock<Vec<EmbargoedRange>> = RwLock::new(vec![]);
+}
+
+static ALLOW_EMBARGOED_DATA: AtomicBool = AtomicBool::new(false);
+
+/// Sets the embargoed ranges, must be called before `FundForgeStrategy::initialize()`.
+pub fn set_embargoed_ranges(ranges: Vec<EmbargoedRange>) {
+    *EMBARGOED_RANGES.write().unwrap() = ranges;
+}
+
+/// Lets a backtest run on embargoed ranges, the ranges touched are recorded in the fingerprint. Must be called before `FundForgeStrategy::initialize()`.
+pub fn allow_embargoed_data(allow: bool) {
+    ALLOW_EMBARGOED_DATA.store(allow, Ordering::SeqCst);
+}
+
+#[derive(Deserialize)]
+struct EmbargoFile {
+    #[serde(default)]
+    embargo: Vec<EmbargoRecord>,
+}
+
+#[derive(Deserialize)]
+struct EmbargoRecord {
+    name: String,
+    start: String,
+    end: String,
+}
+
+/// Parses a "%Y-%m-%d %H:%M:%S" time or a date, in UTC.
+fn parse_embargo_time(time: &str) -> Option<DateTime<Utc>> {
+    let time = time.trim();
+    match NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S") {
+        Ok(time) => Some(time.and_utc()),
+        Err(_) => NaiveDate::parse_from_str(time, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0).map(|time| time.and_utc()),
+    }
+}
+
+/// Reads the embargoed ranges of a toml file, each range is an `[[embargo]]` table with a `name`, a `start` and an `end` in UTC.
+/// ```toml
+/// [[embargo]]
+/// name = "2024 H2 validation"
+/// start = "2024-07-01"
+/// end = "2025-01-01 00:00:00"
+/// ```
+pub fn load_embargoed_ranges(file: &str) -> Result<Vec<EmbargoedRange>, FundForgeError> {
+    let contents = read_to_string(file)
+        .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to read embargo file {}: {}", file, e)))?;
+    let embargo_file: EmbargoFile = toml::from_str(&contents)
+        .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to parse embargo file {}: {}", file, e)))?;
+    embargo_file.embargo.into_iter().map(|record| {
+        let time = |time: &str| parse_embargo_time(time)
+            .ok_or_else(|| FundForgeError::ClientSideErrorDebug(format!("Invalid time in embargo {} of {}: {}", record.name, file, time)));
+        let (start, end) = (time(&record.start)?, time(&record.end)?);
+        if end <= start {
+            return Err(FundForgeError::ClientSideErrorDebug(format!("Embargo {} of {} ends before it starts", record.name, file)));
+        }
+        Ok(EmbargoedRange::new(record.name.clone(), start, end))
+    }).collect()
+}
+
+/// The names of the embargoed ranges overlapped by any of the data ranges.
+fn overlapped(embargoes: &[EmbargoedRange], data_ranges: &[(DateTime<Utc>, DateTime<Utc>)]) -> Vec<String> {
+    embargoes.iter()
+        .filter(|embargo| data_ranges.iter().any(|(start, end)| embargo.overlaps(*start, *end)))
+        .map(|embargo| embargo.name.clone())
+        .collect()
+}
+
+/// Checks the data ranges of a backtest against the embargoed ranges.
+/// Returns the names of the ranges touched when embargoed data is allowed, or the reason the backtest is refused.
+pub(crate) fn check_embargo(data_ranges: &[(DateTime<Utc>, DateTime<Utc>)]) -> Result<Vec<String>, String> {
+    let embargoes = EMBARGOED_RANGES.read().unwrap();
+    let touched = overlapped(&embargoes, data_ranges);
+    if touched.is_empty() || ALLOW_EMBARGOED_DATA.load(Ordering::SeqCst) {
+        return Ok(touched);
+    }
+    let ranges: Vec<String> = embargoes.iter()
+        .filter(|embargo| touched.contains(&embargo.name))
+        .map(|embargo| format!("{}: {} to {}", embargo.name, embargo.start, embargo.end))
+        .collect();
+    Err(format!("The backtest data overlaps the embargoed ranges [{}], call `allow_embargoed_data(true)` before initializing to run on them", ranges.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use chrono::TimeZone;
+
+    fn date(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_overlapped() {
+        let embargoes = vec![
+            EmbargoedRange::new("2024 H2".to_string(), date(2024, 7, 1), date(2025, 1, 1)),
+            EmbargoedRange::new("2023 Q1".to_string(), date(2023, 1, 1), date(2023, 4, 1)),
+        ];
+        // ends the day before the embargo
+        assert!(overlapped(&embargoes, &[(date(2024, 1, 1), date(2024, 6, 30))]).is_empty());
+        assert_eq!(overlapped(&embargoes, &[(date(2024, 1, 1), date(2024, 7, 1))]), vec!["2024 H2".to_string()]);
+        // starts as the embargo ends
+        assert!(overlapped(&embargoes, &[(date(2025, 1, 1), date(2025, 6, 1))]).is_empty());
+        // a regime inside an embargo, and a warm up reaching back into the other
+        let data_ranges = [(date(2024, 8, 1), date(2024, 9, 1)), (date(2023, 3, 25), date(2023, 4, 2))];
+        assert_eq!(overlapped(&embargoes, &data_ranges), vec!["2024 H2".to_string(), "2023 Q1".to_string()]);
+    }
+
+    #[test]
+    fn test_load_embargoed_ranges() {
+        let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+        writeln!(file, "[[embargo]]\nname = \"2024 H2\"\nstart = \"2024-07-01\"\nend = \"2025-01-01 00:00:00\"").unwrap();
+        writeln!(file, "[[embargo]]\nname = \"Crash\"\nstart = \"2020-02-20 14:30:00\"\nend = \"2020-04-01\"").unwrap();
+        let ranges = load_embargoed_ranges(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(ranges, vec![
+            EmbargoedRange::new("2024 H2".to_string(), date(2024, 7, 1), date(2025, 1, 1)),
+            EmbargoedRange::new("Crash".to_string(), Utc.with_ymd_and_hms(2020, 2, 20, 14, 30, 0).unwrap(), date(2020, 4, 1)),
+        ]);
+
+        let mut backwards = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+        writeln!(backwards, "[[embargo]]\nname = \"Backwards\"\nstart = \"2024-07-01\"\nend = \"2024-01-01\"").unwrap();
+        assert!(load_embargoed_ranges(backwards.path().to_str().unwrap()).is_err());
+        assert!(load_embargoed_ranges("./no_such_embargo.toml").is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use rust_decimal::Decimal;
+use crate::standardized_types::enums::{OrderSide, PositionSide};
+use crate::standardized_types::new_types::Volume;
+use crate::standardized_types::orders::Order;
+use crate::standardized_types::position::Position;
+use crate::standardized_types::subscriptions::SymbolName;
+
+/// A group of correlated symbols with a max net exposure across the strategy accounts, see `strategy.set_exposure_group()`.
+/// # Properties
+/// * `name` - The group name, returned with `OrderError::ExposureLimit`.
+/// * `symbols` - The symbols of the group and the weight of one unit of each.
+/// * `max_exposure` - The max net weighted exposure of the group, long or short.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExposureGroup {
+    pub name: String,
+    pub symbols: HashMap<SymbolName, Decimal>,
+    pub max_exposure: Decimal,
+}
+
+impl ExposureGroup {
+    /// A group with no symbols, add them with `with_symbol()`.
+    /// ```rust
+    /// // max $40 a point long or short across every account, eg 20 MNQ, 2 NQ, or 1 NQ and 4 MES
+    /// let us_index = ExposureGroup::new("US Equity Index", dec!(40))
+    ///     .with_symbol("MNQ".to_string(), dec!(2))
+    ///     .with_symbol("MES".to_string(), dec!(5))
+    ///     .with_symbol("NQ".to_string(), dec!(20));
+    /// ```
+    pub fn new(name: &str, max_exposure: Decimal) -> Self {
+        ExposureGroup {
+            name: name.to_string(),
+            symbols: HashMap::new(),
+            max_exposure,
+        }
+    }
+
+    /// Adds the symbol with the exposure of one unit, 1 to count contracts.
+    pub fn with_symbol(mut self, symbol_name: SymbolName, weight: Decimal) -> Self {
+        self.symbols.insert(symbol_name, weight);
+        self
+    }
+
+    /// The net weighted exposure of the positions, positive when the group is net long.
+    pub fn exposure(&self, positions: &[Position]) -> Decimal {
+        positions.iter()
+            .filter_map(|position| {
+                let weight = self.symbols.get(&position.symbol_name)?;
+                match position.side {
+                    PositionSide::Long => Some(position.quantity_open * weight),
+                    PositionSide::Short => Some(-position.quantity_open * weight),
+                    PositionSide::Flat => None,
+                }
+            })
+            .sum()
+    }
+
+    /// The weighted quantity of the working buy and sell orders of the group.
+    fn working_exposure(&self, working_orders: &[Order]) -> (Decimal, Decimal) {
+        let mut buys = Decimal::ZERO;
+        let mut sells = Decimal::ZERO;
+        for order in working_orders {
+            if let Some(weight) = self.symbols.get(&order.symbol_name) {
+                match order.side {
+                    OrderSide::Buy => buys += order.quantity_open * weight,
+                    OrderSide::Sell => sells += order.quantity_open * weight,
+                }
+            }
+        }
+        (buys, sells)
+    }
+
+    /// Returns true if the order could take the group exposure past the max, with the working orders filled.
+    pub fn exceeded_by(&self, symbol_name: &SymbolName, side: OrderSide, quantity: Volume, positions: &[Position], working_orders: &[Order]) -> bool {
+        let Some(weight) = self.symbols.get(symbol_name) else {
+            return false;
+        };
+        let exposure = self.exposure(positions);
+        let (buys, sells) = self.working_exposure(working_orders);
+        match side {
+            OrderSide::Buy => exposure + buys + quantity * weight > self.max_exposure,
+            OrderSide::Sell => exposure - sells - quantity * weight < -self.max_exposure,
+        }
+    }
+}
+
+/// The exposure groups of the strategy.
+#[derive(Default)]
+pub(crate) struct ExposureGroups {
+    groups: RwLock<Vec<ExposureGroup>>,
+}
+
+impl ExposureGroups {
+    /// Setting a group with an existing name replaces the old group.
+    pub(crate) fn set(&self, group: ExposureGroup) {
+        let mut groups = self.groups.write().unwrap();
+        groups.retain(|existing| existing.name != group.name);
+        groups.push(group);
+    }
+
+    pub(crate) fn remove(&self, name: &str) {
+        self.groups.write().unwrap().retain(|group| group.name != name);
+    }
+
+    pub(crate) fn groups(&self) -> Vec<ExposureGroup> {
+        self.groups.read().unwrap().clone()
+    }
+
+    /// Returns true if a group contains the symbol.
+    pub(crate) fn contains(&self, symbol_name: &SymbolName) -> bool {
+        self.groups.read().unwrap().iter().any(|group| group.symbols.contains_key(symbol_name))
+    }
+
+    /// The name of the first group the order would take past its max.
+    pub(crate) fn exceeded_by(&self, order: &Order, positions: &[Position], working_orders: &[Order]) -> Option<String> {
+        self.groups.read().unwrap().iter()
+            .find(|group| group.exceeded_by(&order.symbol_name, order.side, order.quantity_open, positions, working_orders))
+            .map(|group| group.name.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::accounts::Account;
+    use crate::product_maps::rithmic::maps::get_futures_symbol_info;
+    use crate::standardized_types::broker_enum::Brokerage;
+    use crate::standardized_types::orders::TimeInForce;
+    use crate::standardized_types::position::PositionCalculationMode;
+
+    fn account() -> Account {
+        Account::new(Brokerage::Test, "Test_Account_1".to_string())
+    }
+
+    fn position(symbol_name: &str, side: PositionSide, quantity: Volume) -> Position {
+        let symbol_info = get_futures_symbol_info(symbol_name).unwrap();
+        Position::new(symbol_name.to_string(), symbol_name.to_string(), "1".to_string(), account(), side, quantity, dec!(100), "1".to_string(),
+            symbol_info, dec!(1), "Entry".to_string(), Utc::now(), PositionCalculationMode::FIFO)
+    }
+
+    fn order(symbol_name: &str, side: OrderSide, quantity: Volume) -> Order {
+        Order::limit_order(symbol_name.to_string(), None, &account(), quantity, side, "Entry".to_string(), "1".to_string(), Utc::now(), dec!(100), TimeInForce::GTC, None)
+    }
+
+    #[test]
+    fn test_group_exposure() {
+        let group = ExposureGroup::new("US Equity Index", dec!(8))
+            .with_symbol("MNQ".to_string(), dec!(2))
+            .with_symbol("NQ".to_string(), dec!(20));
+        let positions = vec![position("MNQ", PositionSide::Long, dec!(2)), position("ES", PositionSide::Long, dec!(5))];
+        assert_eq!(group.exposure(&positions), dec!(4));
+        let mnq = "MNQ".to_string();
+
+        assert!(!group.exceeded_by(&mnq, OrderSide::Buy, dec!(2), &positions, &[]));
+        assert!(group.exceeded_by(&mnq, OrderSide::Buy, dec!(3), &positions, &[]));
+        assert!(group.exceeded_by(&"NQ".to_string(), OrderSide::Buy, dec!(1), &positions, &[]));
+        // a working buy counts against the limit, a working sell does not make room
+        assert!(group.exceeded_by(&mnq, OrderSide::Buy, dec!(1), &positions, &[order("MNQ", OrderSide::Buy, dec!(2))]));
+        assert!(group.exceeded_by(&mnq, OrderSide::Buy, dec!(3), &positions, &[order("MNQ", OrderSide::Sell, dec!(2))]));
+        // reversing to short is checked against the short side
+        assert!(!group.exceeded_by(&mnq, OrderSide::Sell, dec!(6), &positions, &[]));
+        assert!(group.exceeded_by(&mnq, OrderSide::Sell, dec!(7), &positions, &[]));
+        // symbols outside the group are never refused
+        assert!(!group.exceeded_by(&"ES".to_string(), OrderSide::Buy, dec!(100), &positions, &[]));
+    }
+}
@@ -0,0 +1,334 @@
+use std::fs::create_dir_all;
+use std::path::Path;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::subscriptions::DataSubscription;
+use crate::strategies::indicators::indicator_values::PlotName;
+use crate::strategies::indicators::indicators_trait::IndicatorName;
+
+/// The bar stats of one row.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct BarStats {
+    pub(crate) open: Price,
+    pub(crate) high: Price,
+    pub(crate) low: Price,
+    pub(crate) close: Price,
+    pub(crate) volume: Volume,
+}
+
+impl BarStats {
+    /// The stats of a candle or quote bar, None for other data.
+    pub(crate) fn from_bar(bar: &BaseDataEnum) -> Option<Self> {
+        match bar {
+            BaseDataEnum::Candle(candle) => Some(BarStats {
+                open: candle.open,
+                high: candle.high,
+                low: candle.low,
+                close: candle.close,
+                volume: candle.volume,
+            }),
+            BaseDataEnum::QuoteBar(bar) => Some(BarStats {
+                open: (bar.bid_open + bar.ask_open) / dec!(2),
+                high: (bar.bid_high + bar.ask_high) / dec!(2),
+                low: (bar.bid_low + bar.ask_low) / dec!(2),
+                close: (bar.bid_close + bar.ask_close) / dec!(2),
+                volume: bar.volume,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct FeatureRow {
+    time: DateTime<Utc>,
+    stats: BarStats,
+    indicators: Vec<Option<Decimal>>,
+}
+
+/// Records aligned per bar features of a bar subscription for offline model training, see `strategy.record_features()`.
+/// # Properties
+/// * `subscription` - The candle or quote bar subscription, one row is recorded for each of its closed bars.
+/// * `indicator_plots` - The indicator plots to record, in column order.
+/// * `forward_returns` - The horizons of the forward return labels, in bars.
+pub struct FeatureExporter {
+    subscription: DataSubscription,
+    indicator_plots: Vec<(IndicatorName, PlotName)>,
+    forward_returns: Vec<usize>,
+    rows: Vec<FeatureRow>,
+}
+
+impl FeatureExporter {
+    /// By default there are no indicator plots and one label, the return to the next bar.
+    pub fn new(subscription: DataSubscription) -> Self {
+        FeatureExporter {
+            subscription,
+            indicator_plots: vec![],
+            forward_returns: vec![1],
+            rows: vec![],
+        }
+    }
+
+    /// Adds a column for a plot of an indicator the strategy subscribed to, named `{indicator}_{plot}`.
+    pub fn with_indicator_plot(mut self, name: IndicatorName, plot_name: PlotName) -> Self {
+        self.indicator_plots.push((name, plot_name));
+        self
+    }
+
+    /// Replaces the forward return labels, eg `vec![1, 5, 20]`, a horizon of 0 is ignored.
+    pub fn with_forward_returns(mut self, horizons: Vec<usize>) -> Self {
+        self.forward_returns = horizons.into_iter().filter(|horizon| *horizon > 0).collect();
+        self
+    }
+
+    pub fn subscription(&self) -> &DataSubscription {
+        &self.subscription
+    }
+
+    pub(crate) fn indicator_plots(&self) -> &[(IndicatorName, PlotName)] {
+        &self.indicator_plots
+    }
+
+    /// The number of rows recorded.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// The close time of the last bar recorded.
+    pub fn last_time(&self) -> Option<DateTime<Utc>> {
+        self.rows.last().map(|row| row.time)
+    }
+
+    /// Records a closed bar with the indicator values at its close, in the order of the indicator plots.
+    /// Bars which closed at or before the last bar recorded are ignored, returns true if the bar was recorded.
+    pub(crate) fn record(&mut self, bar: &BaseDataEnum, indicators: Vec<Option<Decimal>>) -> bool {
+        let time = bar.time_closed_utc();
+        if self.last_time().map_or(false, |last_time| time <= last_time) {
+            return false;
+        }
+        let Some(stats) = BarStats::from_bar(bar) else {
+            return false;
+        };
+        self.rows.push(FeatureRow { time, stats, indicators });
+        true
+    }
+
+    /// The column names.
+    pub fn header(&self) -> Vec<String> {
+        let mut header: Vec<String> = ["time", "open", "high", "low", "close", "volume", "range", "return"].iter().map(|column| column.to_string()).collect();
+        header.extend(self.indicator_plots.iter().map(|(name, plot_name)| format!("{}_{}", name, plot_name)));
+        header.extend(self.forward_returns.iter().map(|horizon| format!("forward_return_{}", horizon)));
+        header
+    }
+
+    /// The rows of the feature matrix, oldest first, in the column order of the header, empty strings for missing values.
+    pub fn matrix(&self) -> Vec<Vec<String>> {
+        let cell = |value: Option<Decimal>| value.map(|value| value.normalize().to_string()).unwrap_or_default();
+        self.rows.iter().enumerate().map(|(index, row)| {
+            let previous_close = index.checked_sub(1).map(|previous| self.rows[previous].stats.close);
+            let mut cells = vec![
+                row.time.to_rfc3339(),
+                row.stats.open.to_string(),
+                row.stats.high.to_string(),
+                row.stats.low.to_string(),
+                row.stats.close.to_string(),
+                row.stats.volume.to_string(),
+                (row.stats.high - row.stats.low).to_string(),
+                cell(previous_close.and_then(|previous_close| simple_return(previous_close, row.stats.close))),
+            ];
+            cells.extend(row.indicators.iter().map(|value| cell(*value)));
+            cells.extend(self.forward_returns.iter().map(|horizon| {
+                cell(self.rows.get(index + horizon).and_then(|later| simple_return(row.stats.close, later.stats.close)))
+            }));
+            cells
+        }).collect()
+    }
+
+    /// Writes the feature matrix to a csv file, creating the folder if needed.
+    pub fn write_csv(&self, file: &str) -> Result<(), FundForgeError> {
+        if let Some(folder) = Path::new(file).parent().filter(|folder| !folder.as_os_str().is_empty()) {
+            create_dir_all(folder)
+                .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to create feature export folder {}: {}", folder.display(), e)))?;
+        }
+        let mut writer = csv::Writer::from_path(file)
+            .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to create feature export {}: {}", file, e)))?;
+        writer.write_record(self.header())
+            .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to write feature export {}: {}", file, e)))?;
+        for row in self.matrix() {
+            writer.write_record(row)
+                .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to write feature export {}: {}", file, e)))?;
+        }
+        writer.flush()
+            .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to write feature export {}: {}", file, e)))
+    }
+
+    /// Writes the feature matrix to a parquet file, creating the folder if needed.
+    /// `time` is a utf8 column and the other columns are optional doubles, null where the csv is empty.
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet(&self, file: &str) -> Result<(), FundForgeError> {
+        use std::sync::Arc;
+        use parquet::basic::{ConvertedType, Repetition, Type as PhysicalType};
+        use parquet::data_type::{ByteArray, ByteArrayType, DoubleType};
+        use parquet::errors::ParquetError;
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::types::Type;
+
+        let error = |e: ParquetError| FundForgeError::ClientSideErrorDebug(format!("Failed to write feature export {}: {}", file, e));
+        if let Some(folder) = Path::new(file).parent().filter(|folder| !folder.as_os_str().is_empty()) {
+            create_dir_all(folder)
+                .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to create feature export folder {}: {}", folder.display(), e)))?;
+        }
+        let header = self.header();
+        let mut fields = vec![Arc::new(Type::primitive_type_builder(&header[0], PhysicalType::BYTE_ARRAY)
+            .with_repetition(Repetition::REQUIRED)
+            .with_converted_type(ConvertedType::UTF8)
+            .build()
+            .map_err(error)?)];
+        for column in &header[1..] {
+            fields.push(Arc::new(Type::primitive_type_builder(column, PhysicalType::DOUBLE)
+                .with_repetition(Repetition::OPTIONAL)
+                .build()
+                .map_err(error)?));
+        }
+        let schema = Arc::new(Type::group_type_builder("features").with_fields(fields).build().map_err(error)?);
+        let output = std::fs::File::create(file)
+            .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to create feature export {}: {}", file, e)))?;
+        let mut writer = SerializedFileWriter::new(output, schema, Arc::new(WriterProperties::builder().build())).map_err(error)?;
+
+        let matrix = self.matrix();
+        let mut row_group = writer.next_row_group().map_err(error)?;
+        let mut index = 0;
+        while let Some(mut column) = row_group.next_column().map_err(error)? {
+            match index {
+                0 => {
+                    let times: Vec<ByteArray> = matrix.iter().map(|row| ByteArray::from(row[0].as_str())).collect();
+                    column.typed::<ByteArrayType>().write_batch(&times, None, None).map_err(error)?;
+                }
+                _ => {
+                    // a definition level of 0 is a null, only the values which are present are written
+                    let cells: Vec<Option<f64>> = matrix.iter().map(|row| row[index].parse::<f64>().ok()).collect();
+                    let levels: Vec<i16> = cells.iter().map(|cell| cell.is_some() as i16).collect();
+                    let values: Vec<f64> = cells.into_iter().flatten().collect();
+                    column.typed::<DoubleType>().write_batch(&values, Some(&levels), None).map_err(error)?;
+                }
+            }
+            column.close().map_err(error)?;
+            index += 1;
+        }
+        row_group.close().map_err(error)?;
+        writer.close().map_err(error)?;
+        Ok(())
+    }
+}
+
+fn simple_return(from: Price, to: Price) -> Option<Decimal> {
+    match from == dec!(0) {
+        true => None,
+        false => Some((to / from - dec!(1)).round_dp(10)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use crate::standardized_types::base_data::base_data_type::BaseDataType;
+    use crate::standardized_types::base_data::candle::Candle;
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::MarketType;
+    use crate::standardized_types::resolution::Resolution;
+    use crate::standardized_types::subscriptions::{CandleType, Symbol};
+
+    fn candle(minute: i64, close: Decimal) -> BaseDataEnum {
+        let time = Utc.with_ymd_and_hms(2024, 6, 3, 9, 0, 0).unwrap() + Duration::minutes(minute);
+        BaseDataEnum::Candle(Candle {
+            symbol: Symbol::new("EUR-USD".to_string(), DataVendor::Oanda, MarketType::Forex),
+            high: close + dec!(1),
+            low: close - dec!(1),
+            open: close,
+            close,
+            volume: dec!(10),
+            ask_volume: dec!(5),
+            bid_volume: dec!(5),
+            range: dec!(2),
+            time: time.to_string(),
+            is_closed: true,
+            resolution: Resolution::Minutes(1),
+            candle_type: CandleType::CandleStick,
+        })
+    }
+
+    #[test]
+    fn test_feature_matrix() {
+        let subscription = DataSubscription::new("EUR-USD".to_string(), DataVendor::Oanda, Resolution::Minutes(1), BaseDataType::Candles, MarketType::Forex);
+        let mut exporter = FeatureExporter::new(subscription)
+            .with_indicator_plot("atr_14".to_string(), "atr".to_string())
+            .with_forward_returns(vec![0, 1, 2]);
+        assert!(exporter.record(&candle(0, dec!(100)), vec![None]));
+        assert!(exporter.record(&candle(1, dec!(110)), vec![Some(dec!(2.50))]));
+        // a bar already recorded is ignored
+        assert!(!exporter.record(&candle(1, dec!(110)), vec![Some(dec!(2.5))]));
+        assert!(exporter.record(&candle(2, dec!(99)), vec![Some(dec!(2.4))]));
+        assert_eq!(exporter.len(), 3);
+
+        assert_eq!(exporter.header(), vec!["time", "open", "high", "low", "close", "volume", "range", "return", "atr_14_atr", "forward_return_1", "forward_return_2"]);
+        let matrix = exporter.matrix();
+        // the first bar closes at 09:01
+        assert_eq!(matrix[0][0], "2024-06-03T09:01:00+00:00");
+        let columns = |row: &Vec<String>| row[7..].to_vec();
+        assert_eq!(columns(&matrix[0]), vec!["", "", "0.1", "-0.01"]);
+        assert_eq!(columns(&matrix[1]), vec!["0.1", "2.5", "-0.1", ""]);
+        assert_eq!(columns(&matrix[2]), vec!["-0.1", "2.4", "", ""]);
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let subscription = DataSubscription::new("EUR-USD".to_string(), DataVendor::Oanda, Resolution::Minutes(1), BaseDataType::Candles, MarketType::Forex);
+        let mut exporter = FeatureExporter::new(subscription);
+        exporter.record(&candle(0, dec!(100)), vec![]);
+        exporter.record(&candle(1, dec!(101)), vec![]);
+        let folder = tempfile::tempdir().unwrap();
+        let file = folder.path().join("features").join("eur_usd.csv");
+        exporter.write_csv(file.to_str().unwrap()).unwrap();
+
+        let mut reader = csv::Reader::from_path(&file).unwrap();
+        assert_eq!(reader.headers().unwrap().len(), 9);
+        let rows: Vec<csv::StringRecord> = reader.records().map(|record| record.unwrap()).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(&rows[0][8], "0.01");
+        assert_eq!(&rows[1][7], "0.01");
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_write_parquet() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let subscription = DataSubscription::new("EUR-USD".to_string(), DataVendor::Oanda, Resolution::Minutes(1), BaseDataType::Candles, MarketType::Forex);
+        let mut exporter = FeatureExporter::new(subscription);
+        exporter.record(&candle(0, dec!(100)), vec![]);
+        exporter.record(&candle(1, dec!(101)), vec![]);
+        let folder = tempfile::tempdir().unwrap();
+        let file = folder.path().join("features").join("eur_usd.parquet");
+        exporter.write_parquet(file.to_str().unwrap()).unwrap();
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&file).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.file_metadata().num_rows(), 2);
+        assert_eq!(metadata.file_metadata().schema_descr().num_columns(), 9);
+        let rows: Vec<String> = reader.get_row_iter(None).unwrap().map(|row| row.unwrap().to_string()).collect();
+        // the return of the first bar and the label of the last bar are null
+        assert!(rows[0].contains("return: null") && rows[0].contains("forward_return_1: 0.01"));
+        assert!(rows[1].contains("forward_return_1: null"));
+    }
+}
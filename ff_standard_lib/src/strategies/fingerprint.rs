@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use lazy_static::lazy_static;
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::standardized_types::enums::StrategyMode;
+use crate::strategies::promotion::binary_fingerprint;
+
+lazy_static! {
+    static ref STRATEGY_PARAMETERS: RwLock<BTreeMap<String, String>> = RwLock::new(BTreeMap::new());
+}
+
+/// Sets the parameters of the strategy included in the fingerprint, must be called before `FundForgeStrategy::initialize()`.
+/// ```rust
+/// set_strategy_parameters(BTreeMap::from([
+///     ("fast_ema".to_string(), "12".to_string()),
+///     ("slow_ema".to_string(), "26".to_string()),
+/// ]));
+/// ```
+pub fn set_strategy_parameters(parameters: BTreeMap<String, String>) {
+    *STRATEGY_PARAMETERS.write().unwrap() = parameters;
+}
+
+pub(crate) fn strategy_parameters() -> BTreeMap<String, String> {
+    STRATEGY_PARAMETERS.read().unwrap().clone()
+}
+
+/// The configuration of a strategy run, see `strategy.fingerprint()`.
+/// # Properties
+/// * `hash` - The SHA-256 hash of the other properties, in hex.
+/// * `engine_version` - The version of fund forge the strategy was built with.
+/// * `binary` - The hash of the strategy executable, None if it could not be read.
+/// * `mode` - The strategy mode.
+/// * `parameters` - The strategy parameters, see `set_strategy_parameters()`.
+/// * `settings` - The engine settings passed to `initialize()`.
+/// * `subscriptions` - The initial subscriptions, sorted.
+/// * `data_range` - The backtest range or regimes in UTC, or `Live` for live modes.
+/// * `accounts` - The strategy accounts.
+/// * `embargoes_touched` - The embargoed ranges the backtest data overlapped, only when run with `allow_embargoed_data(true)`, see `set_embargoed_ranges()`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct StrategyFingerprint {
+    pub hash: String,
+    pub engine_version: String,
+    pub binary: Option<String>,
+    pub mode: StrategyMode,
+    pub parameters: BTreeMap<String, String>,
+    pub settings: BTreeMap<String, String>,
+    pub subscriptions: Vec<String>,
+    pub data_range: String,
+    pub accounts: Vec<String>,
+    #[serde(default)]
+    pub embargoes_touched: Vec<String>,
+}
+
+impl StrategyFingerprint {
+    /// The fingerprint of the running executable with the configuration.
+    pub(crate) fn new(
+        mode: StrategyMode,
+        parameters: BTreeMap<String, String>,
+        settings: BTreeMap<String, String>,
+        subscriptions: Vec<String>,
+        data_range: String,
+        accounts: Vec<String>,
+        embargoes_touched: Vec<String>,
+    ) -> Self {
+        let binary = match binary_fingerprint() {
+            Ok(binary) => Some(binary),
+            Err(e) => {
+                eprintln!("Strategy Fingerprint: {}", e);
+                None
+            }
+        };
+        Self::with_binary(binary, mode, parameters, settings, subscriptions, data_range, accounts, embargoes_touched)
+    }
+
+    fn with_binary(
+        binary: Option<String>,
+        mode: StrategyMode,
+        parameters: BTreeMap<String, String>,
+        settings: BTreeMap<String, String>,
+        mut subscriptions: Vec<String>,
+        data_range: String,
+        accounts: Vec<String>,
+        embargoes_touched: Vec<String>,
+    ) -> Self {
+        subscriptions.sort();
+        let mut fingerprint = StrategyFingerprint {
+            hash: String::new(),
+            engine_version: env!("CARGO_PKG_VERSION").to_string(),
+            binary,
+            mode,
+            parameters,
+            settings,
+            subscriptions,
+            data_range,
+            accounts,
+            embargoes_touched,
+        };
+        // the maps are sorted, so the json of a configuration is always the same
+        let json = serde_json::to_string(&fingerprint).unwrap_or_default();
+        fingerprint.hash = format!("{:x}", Sha256::digest(json.as_bytes()));
+        fingerprint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(fast_ema: &str, subscriptions: Vec<String>) -> StrategyFingerprint {
+        StrategyFingerprint::with_binary(
+            Some("0123456789abcdef".to_string()),
+            StrategyMode::Backtest,
+            BTreeMap::from([("fast_ema".to_string(), fast_ema.to_string())]),
+            BTreeMap::from([("warmup".to_string(), "1 day".to_string())]),
+            subscriptions,
+            "2024-06-03 00:00:00 UTC to 2024-06-07 00:00:00 UTC".to_string(),
+            vec!["Test: Test_Account_1".to_string()],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_fingerprint_hash() {
+        let first = fingerprint("12", vec!["MNQ 1m".to_string(), "MES 1m".to_string()]);
+        // the subscription order does not change the configuration
+        assert_eq!(first.hash, fingerprint("12", vec!["MES 1m".to_string(), "MNQ 1m".to_string()]).hash);
+        assert_ne!(first.hash, fingerprint("13", vec!["MNQ 1m".to_string(), "MES 1m".to_string()]).hash);
+        assert_eq!(first.hash.len(), 64);
+        assert_eq!(first.subscriptions, vec!["MES 1m".to_string(), "MNQ 1m".to_string()]);
+    }
+}
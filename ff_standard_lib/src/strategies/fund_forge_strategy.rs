@@ -1,22 +1,37 @@
 use ahash::AHashMap;
-use chrono::{DateTime, Duration as ChronoDuration, NaiveDateTime, TimeZone, Utc};
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDateTime, NaiveTime, TimeZone, Utc};
 use chrono_tz::Tz;
 use crate::strategies::handlers::drawing_object_handler::DrawingObjectHandler;
 use crate::gui_types::drawing_objects::drawing_tool_enum::DrawingTool;
 use crate::strategies::handlers::indicator_handler::IndicatorHandler;
+use crate::strategies::handlers::event_tail_handler::{event_tail_address, EventTailHandler};
+use crate::strategies::handlers::control_server::ControlServer;
 use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
 use crate::strategies::indicators::indicator_values::IndicatorValues;
+use crate::strategies::indicators::indicator_state::{load_indicator_states, save_indicator_states};
 use crate::standardized_types::base_data::history::range_history_data;
-use crate::standardized_types::enums::{OrderSide, StrategyMode, PrimarySubscription, FuturesExchange, PositionSide};
+use crate::standardized_types::enums::{OrderSide, StrategyMode, PrimarySubscription, FuturesExchange, PositionSide, PriceTriggerCondition};
 use crate::standardized_types::rolling_window::RollingWindow;
 use crate::strategies::strategy_events::StrategyEvent;
 use crate::strategies::handlers::subscription_handler::SubscriptionHandler;
-use crate::standardized_types::subscriptions::{DataSubscription, SymbolCode, SymbolName};
-use crate::strategies::handlers::timed_events_handler::{TimedEvent, TimedEventHandler};
+use crate::strategies::history_budget::{HistoryBudget, HistoryMemoryUsage};
+use crate::standardized_types::subscriptions::{DataSubscription, Symbol, SymbolCode, SymbolName};
+use crate::standardized_types::datavendor_enum::DataVendor;
+use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::resolution::Resolution;
+use crate::database::scanner::{ScanCondition, ScanMatch};
+use crate::strategies::client_features::other_requests::{claim_intent, get_intents, get_levels, load_symbol_aliases, remove_intent, remove_level, save_intent, save_level, scan_symbols};
+use crate::standardized_types::levels::Level;
+use crate::standardized_types::intents::{Intent, IntentEntry};
+use crate::standardized_types::symbol_aliases::{canonical_symbol_name, register_symbol_alias, symbol_aliases, vendor_symbol_name, SymbolAlias};
+use crate::strategies::handlers::timed_events_handler::{EventTimeEnum, SessionHook, SessionHookTime, TimedEvent, TimedEventHandler};
+use crate::strategies::handlers::shutdown_handler::{report_open_orders, shutdown_live_on_signal, CANCEL_RETRY_INTERVAL};
 use std::collections::BTreeMap;
 use std::fs;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+use std::net::SocketAddr;
 use std::time::Duration;
+use std::future::Future;
 use dashmap::DashMap;
 use rust_decimal::Decimal;
 use tokio::sync::{mpsc, oneshot, Notify};
@@ -24,32 +39,75 @@ use tokio::sync::mpsc::Sender;
 use uuid::Uuid;
 use crate::helpers::converters::{naive_date_time_to_tz, naive_date_time_to_utc, resolve_market_datetime_in_timezone};
 use crate::helpers::decimal_calculators::round_to_tick_size;
-use crate::strategies::client_features::server_connections::{init_connections, is_warmup_complete};
+use crate::strategies::client_features::server_connections::init_connections;
+use crate::standardized_types::bar_time::BarTimeConvention;
 use crate::standardized_types::base_data::candle::Candle;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::base_data::depth::Depth;
 use crate::standardized_types::base_data::quote::Quote;
 use crate::standardized_types::base_data::quotebar::QuoteBar;
 use crate::standardized_types::base_data::tick::Tick;
-use crate::messages::data_server_messaging::{DataServerRequest, DataServerResponse};
+use crate::messages::data_server_messaging::{DataServerRequest, DataServerResponse, FundForgeError};
+use crate::product_maps::trading_hours::get_trading_hours;
 use crate::product_maps::rithmic::rollover::get_front_month;
-use crate::standardized_types::accounts::{Account, Currency};
+use crate::standardized_types::accounts::{Account, AccountSummary, Currency};
 use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
 use crate::standardized_types::broker_enum::Brokerage;
 use crate::standardized_types::market_hours::TradingHours;
 use crate::standardized_types::new_types::{Price, Volume};
-use crate::standardized_types::orders::{Order, OrderId, OrderRequest, OrderType, OrderUpdateType, TimeInForce};
+use crate::standardized_types::orders::{ClosedMarketPolicy, Order, OrderError, OrderId, OrderRequest, OrderState, OrderType, OrderUpdateEvent, OrderUpdateType, PriceReference, TimeInForce};
 use crate::standardized_types::position::Position;
+use crate::standardized_types::symbol_info::{price_offset_ticks, SymbolInfo};
+use crate::standardized_types::options::{Greeks, OptionCombo, OptionQuote, OPTION_CONTRACTS};
 use crate::strategies::client_features::connection_types::ConnectionType;
 use crate::strategies::client_features::live_subscriptions::live_subscription_handler;
 use crate::strategies::client_features::request_handler::{send_request, StrategyRequest};
-use crate::strategies::handlers::live_warmup::live_warm_up;
+use crate::strategies::handlers::live_warmup::{live_warm_up, live_warm_up_source};
+use crate::strategies::handlers::order_hooks::{OrderHooks, PostFillHook, PreOrderHook};
+use crate::strategies::handlers::flat_by_handler::{FlatByHandler, FlatByRule};
+use crate::strategies::handlers::daily_report_handler::{DailyReportHandler, DailyReportSettings};
+use crate::strategies::handlers::watchdog::{start_watchdog, stop_watchdog, WatchdogSettings};
+use crate::strategies::handlers::stale_order_handler::{StaleOrderHandler, StaleOrderRule};
+use crate::strategies::handlers::contract_expiry_handler::{ContractExpiryHandler, ContractExpiryPolicy};
+use crate::strategies::handlers::closed_market_queue::ClosedMarketQueue;
+use crate::strategies::handlers::spread_guard::{SpreadGuard, SpreadGuardHandler};
+use crate::strategies::handlers::reduce_only::ReduceOnlyHandle;
+use crate::strategies::handlers::order_gate::{route_held_orders, OrderGate};
+use crate::strategies::handlers::pair_handler::{leg_order, route_engine_orders, LeggingRisk, Pair, PairExecution, PairHandler, PairLeg, PairOrder};
 use crate::strategies::handlers::market_handler::backtest_matching_engine;
 use crate::strategies::handlers::market_handler::backtest_matching_engine::BackTestEngineMessage;
 use crate::strategies::handlers::market_handler::live_order_matching::live_order_handler;
 use crate::strategies::handlers::market_handler::price_service::MarketPriceService;
-use crate::strategies::historical_engine::HistoricalEngine;
-use crate::strategies::historical_time::{get_backtest_time, update_backtest_time};
+use crate::strategies::handlers::price_trigger_handler::{PriceTrigger, PriceTriggerHandler};
+use crate::strategies::historical_engine::{request_backtest_stop, stop_backtest_on_ctrl_c, HistoricalEngine};
+use crate::strategies::historical_time::{update_backtest_time, WarmUpState};
 use crate::strategies::indicators::indicator_events::IndicatorEvents;
-use crate::strategies::ledgers::ledger_service::LedgerService;
+use crate::strategies::ledgers::aggregate_statistics::AggregateStatistics;
+use crate::strategies::ledgers::audit_trail::AuditEntry;
+use crate::strategies::fingerprint::{strategy_parameters, StrategyFingerprint};
+use crate::strategies::ledgers::fill_audit::{slippage_report, FillAuditor, FillCheck, SlippageSummary, FILL_AUDIT_HOOK};
+use crate::strategies::ledgers::conversion_rates::ConversionRateProvider;
+use crate::strategies::ledgers::excursions::StopSuggestion;
+use crate::strategies::ledgers::ledger_service::{LedgerService, DEFAULT_ACCOUNT_SYNC_INTERVAL};
+use crate::strategies::ledgers::preflight::OrderPreflight;
+use crate::strategies::ledgers::reconciliation::ReconciliationReport;
+use crate::strategies::ledgers::shortability::ShortabilityTable;
+use crate::strategies::strategy_settings::StrategySettings;
+use crate::strategies::trading_windows::TradingWindow;
+use crate::strategies::exposure_groups::ExposureGroup;
+use crate::strategies::rebalancing::{plan_rebalance, RebalancePlan, SymbolValuation, TargetWeight};
+use crate::strategies::embargo::check_embargo;
+use crate::strategies::feature_export::FeatureExporter;
+#[cfg(feature = "onnx")]
+use crate::strategies::model_inference::{resolve_model_file, FeatureBuffer, OnnxModel};
+use crate::strategies::signal_execution::{target_order, Signal, SignalAction, SignalExecution, SignalExecutor, SignalOutcome};
+use crate::strategies::intent_execution::{IntentExecution, IntentExecutor, IntentOutcome, WorkingIntent};
+use crate::strategies::news_datasets::attached_news_names;
+use crate::strategies::aligned_bars::{align_bars, AlignedBars};
+use crate::strategies::promotion::{PromotionGate, PromotionRecord};
+use crate::strategies::ledgers::ledger_snapshot::LedgerSnapshot;
+use crate::strategies::statistics::{backtest_regimes, BacktestRegime};
+use crate::strategies::trade_journal::{TradeJournal, TradeJournalSettings};
 
 /// The `FundForgeStrategy` struct is the main_window struct for the FundForge strategy. It contains the state of the strategy and the callback function for data updates.
 
@@ -68,6 +126,8 @@ pub struct FundForgeStrategy {
 
     timed_event_handler: Arc<TimedEventHandler>,
 
+    price_trigger_handler: Arc<PriceTriggerHandler>,
+
     drawing_objects_handler: Arc<DrawingObjectHandler>,
 
     synchronize_accounts: bool,
@@ -86,8 +146,34 @@ pub struct FundForgeStrategy {
 
     ledger_service: Arc<LedgerService>,
 
-    market_price_service: Arc<MarketPriceService>
+    market_price_service: Arc<MarketPriceService>,
+
+    backtest_regimes: Vec<BacktestRegime>,
+
+    strategy_event_sender: mpsc::Sender<StrategyEvent>,
+
+    trading_hours: DashMap<SymbolName, TradingHours>,
 
+    symbol_info: DashMap<(Brokerage, SymbolName), SymbolInfo>,
+
+    pair_handler: Arc<PairHandler>,
+
+    order_hooks: Arc<OrderHooks>,
+    flat_by_handler: Arc<FlatByHandler>,
+    daily_report_handler: Arc<DailyReportHandler>,
+    stale_order_handler: Arc<StaleOrderHandler>,
+    contract_expiry_handler: Arc<ContractExpiryHandler>,
+    closed_market_queue: Arc<ClosedMarketQueue>,
+    spread_guard_handler: Arc<SpreadGuardHandler>,
+    closed_market_policy: RwLock<ClosedMarketPolicy>,
+    order_gate: Arc<OrderGate>,
+    fill_auditor: Arc<FillAuditor>,
+    settings: Arc<StrategySettings>,
+
+    bar_time_convention: RwLock<BarTimeConvention>,
+    warm_up: WarmUpState,
+    #[cfg(feature = "onnx")]
+    models: DashMap<String, Arc<OnnxModel>>,
 }
 
 impl FundForgeStrategy {
@@ -129,6 +215,10 @@ impl FundForgeStrategy {
     /// `tick_over_no_data: bool`: If true the Backtest engine will tick at buffer resolution speed over weekends or other no data periods.
     ///
     /// `synchronize_accounts: bool` If true strategy positions will update in sync with the brokerage, if false the engine will simulate positions using the same logic as backtesting. //todo[ReadMe], explain in more detail
+    ///
+    /// `accounts: Vec<Account>`: The accounts the strategy will trade.
+    ///
+    /// Returns an error if another strategy in the process was initialized in a different mode, or if the backtest data overlaps an embargoed range, see `set_embargoed_ranges()`.
     pub async fn initialize(
         strategy_mode: StrategyMode,
         backtest_accounts_starting_cash: Decimal,
@@ -145,48 +235,161 @@ impl FundForgeStrategy {
         gui_enabled: bool,
         tick_over_no_data: bool,
         synchronize_accounts: bool,
-        accounts: Vec<Account>
-    ) -> FundForgeStrategy {
+        accounts: Vec<Account>,
+    ) -> Result<FundForgeStrategy, FundForgeError> {
+        // the event tail taps the strategy event channel, so dashboards see the same events as the strategy
+        let strategy_event_sender = match event_tail_address() {
+            Some(address) => match EventTailHandler::serve(address).await {
+                Ok(event_tail) => event_tail.tap(strategy_event_sender),
+                Err(e) => {
+                    eprintln!("Failed to start the event tail at {}: {}", address, e);
+                    strategy_event_sender
+                }
+            },
+            None => strategy_event_sender,
+        };
 
+        let settings = Arc::new(StrategySettings::new(strategy_mode));
         let price_service = Arc::new(MarketPriceService::new());
-        let ledger_service = Arc::new(LedgerService::new(strategy_event_sender.clone(), price_service.clone()));
+        let ledger_service = Arc::new(LedgerService::new(strategy_event_sender.clone(), price_service.clone(), settings.clone()));
 
-        let timed_event_handler = Arc::new(TimedEventHandler::new(strategy_event_sender.clone()));
+        let warm_up = WarmUpState::new();
+        let timed_event_handler = Arc::new(TimedEventHandler::new(strategy_event_sender.clone(), warm_up.clone()));
+        let price_trigger_handler = Arc::new(PriceTriggerHandler::new(strategy_event_sender.clone()));
         let drawing_objects_handler = Arc::new(DrawingObjectHandler::new(AHashMap::new()));
 
 
-        let start_time = resolve_market_datetime_in_timezone(time_zone, start_date).to_utc();
-        let end_time = resolve_market_datetime_in_timezone(time_zone, end_date).to_utc();
+        let mut backtest_regimes = backtest_regimes();
+        backtest_regimes.sort_by_key(|regime| regime.start);
+        let (start_time, end_time) = match (backtest_regimes.first(), backtest_regimes.iter().map(|regime| regime.end).max()) {
+            (Some(first), Some(last_end)) if strategy_mode == StrategyMode::Backtest => (first.start, last_end),
+            _ => (resolve_market_datetime_in_timezone(time_zone, start_date).to_utc(), resolve_market_datetime_in_timezone(time_zone, end_date).to_utc())
+        };
 
         let warm_up_start_time = start_time - warmup_duration;
         update_backtest_time(warm_up_start_time);
 
+        // a backtest on a held out period is refused before any data is read
+        let embargoes_touched = match strategy_mode {
+            StrategyMode::Backtest => {
+                // each regime is warmed up separately
+                let data_ranges: Vec<(DateTime<Utc>, DateTime<Utc>)> = match backtest_regimes.is_empty() {
+                    true => vec![(warm_up_start_time, end_time)],
+                    false => backtest_regimes.iter().map(|regime| (regime.start - warmup_duration, regime.end)).collect(),
+                };
+                check_embargo(&data_ranges).map_err(FundForgeError::ClientSideErrorDebug)?
+            }
+            StrategyMode::Live | StrategyMode::LivePaperTrading => vec![],
+        };
+        if !embargoes_touched.is_empty() {
+            println!("Strategy Fingerprint: Running on embargoed data: {}", embargoes_touched.join(", "));
+        }
+
+        // the fingerprint is set before anything can export, so every export has it
+        let data_range = match strategy_mode {
+            StrategyMode::Backtest if !backtest_regimes.is_empty() => backtest_regimes.iter()
+                .map(|regime| format!("{}: {} to {}", regime.name, regime.start, regime.end))
+                .collect::<Vec<String>>()
+                .join(", "),
+            StrategyMode::Backtest => format!("{} to {}", start_time, end_time),
+            StrategyMode::Live | StrategyMode::LivePaperTrading => "Live".to_string(),
+        };
+        let mut engine_settings = BTreeMap::from([
+            ("starting_cash".to_string(), backtest_accounts_starting_cash.to_string()),
+            ("account_currency".to_string(), backtest_account_currency.to_string()),
+            ("time_zone".to_string(), time_zone.to_string()),
+            ("warmup_duration".to_string(), warmup_duration.to_string()),
+            ("buffering_duration".to_string(), format!("{:?}", buffering_duration)),
+            ("fill_forward".to_string(), fill_forward.to_string()),
+            ("retain_history".to_string(), retain_history.to_string()),
+            ("tick_over_no_data".to_string(), tick_over_no_data.to_string()),
+            ("synchronize_accounts".to_string(), synchronize_accounts.to_string()),
+        ]);
+        let news_datasets = attached_news_names();
+        if strategy_mode == StrategyMode::Backtest && !news_datasets.is_empty() {
+            engine_settings.insert("news_datasets".to_string(), news_datasets.join(", "));
+        }
+        let subscriptions = intraday_subscriptions.iter()
+            .map(|(primary, subscription, _)| match primary {
+                Some(primary) => format!("{} from {} {}", subscription, primary.resolution, primary.base_data_type),
+                None => subscription.to_string(),
+            })
+            .collect();
+        let fingerprint = StrategyFingerprint::new(strategy_mode, strategy_parameters(), engine_settings, subscriptions, data_range, accounts.iter().map(|account| account.to_string()).collect(), embargoes_touched);
+        println!("Strategy Fingerprint: {}", fingerprint.hash);
+        settings.set_fingerprint(fingerprint);
+
         let open_order_cache: Arc<DashMap<OrderId, Order>> = Arc::new(DashMap::new());
         let closed_order_cache: Arc<DashMap<OrderId, Order>> = Arc::new(DashMap::new());
 
         let notify = Arc::new(Notify::new());
-        let subscription_handler = Arc::new(SubscriptionHandler::new(strategy_mode, strategy_event_sender.clone()).await);
+        let subscription_handler = Arc::new(SubscriptionHandler::new(strategy_mode, strategy_event_sender.clone(), warm_up.clone()).await);
         let indicator_handler = Arc::new(IndicatorHandler::new(strategy_mode.clone(), subscription_handler.clone()).await);
 
+        // the pair handler sees the order events before the strategy, to unwind or retry a leg of a pair
+        let (pair_handler, pair_order_receiver) = PairHandler::new();
+        let pair_handler = Arc::new(pair_handler);
+        let order_event_sender = pair_handler.clone().tap(strategy_event_sender.clone());
+        // the post fill hooks run before the pair handler and the strategy see a fill
+        let order_hooks = Arc::new(OrderHooks::new());
+        let order_event_sender = order_hooks.clone().tap(order_event_sender);
+
         let (live_order_updates_sender, live_order_updates_receiver) = tokio::sync::mpsc::channel(100);
         if strategy_mode == StrategyMode::Live {
-            live_order_handler(open_order_cache.clone(), closed_order_cache.clone(), live_order_updates_receiver, strategy_event_sender.clone(), ledger_service.clone(), synchronize_accounts);
+            live_order_handler(open_order_cache.clone(), closed_order_cache.clone(), live_order_updates_receiver, order_event_sender.clone(), ledger_service.clone(), synchronize_accounts);
         }
 
-        init_connections(gui_enabled, buffering_duration.clone(), strategy_mode.clone(), live_order_updates_sender, synchronize_accounts, strategy_event_sender.clone(), ledger_service.clone(), indicator_handler.clone(), subscription_handler.clone(), price_service.clone()).await;
+        let route_id = init_connections(gui_enabled, buffering_duration.clone(), strategy_mode.clone(), live_order_updates_sender, synchronize_accounts, accounts.clone(), open_order_cache.clone(), closed_order_cache.clone(), strategy_event_sender.clone(), ledger_service.clone(), indicator_handler.clone(), subscription_handler.clone(), price_service.clone(), price_trigger_handler.clone()).await?;
 
+        let symbol_trading_hours = DashMap::new();
         for (primary, sub, trading_hours) in intraday_subscriptions {
+            if let Some(hours) = &trading_hours {
+                symbol_trading_hours.insert(sub.symbol.name.clone(), hours.clone());
+            }
             subscription_handler.subscribe(primary, sub, warm_up_start_time, fill_forward, retain_history, false, trading_hours).await;
         }
 
         let paper_order_sender = match strategy_mode {
             StrategyMode::Live => None,
             StrategyMode::LivePaperTrading | StrategyMode::Backtest => {
-                let sender = backtest_matching_engine::backtest_matching_engine(open_order_cache.clone(), closed_order_cache.clone(), strategy_event_sender.clone(), ledger_service.clone(), notify.clone(), price_service.clone()).await;
+                let sender = backtest_matching_engine::backtest_matching_engine(open_order_cache.clone(), closed_order_cache.clone(), order_event_sender.clone(), ledger_service.clone(), notify.clone(), price_service.clone(), strategy_mode, warm_up.clone()).await;
                 Some(sender) //todo, live paper wont update orders unless we update time in the backtest engine.
             }
         };
 
+        route_engine_orders(pair_order_receiver, strategy_mode.clone(), open_order_cache.clone(), paper_order_sender.clone());
+
+        let (flat_by_order_sender, flat_by_order_receiver) = mpsc::channel(100);
+        route_engine_orders(flat_by_order_receiver, strategy_mode.clone(), open_order_cache.clone(), paper_order_sender.clone());
+        let flat_by_handler = Arc::new(FlatByHandler::new(accounts.clone(), open_order_cache.clone(), ledger_service.clone(), flat_by_order_sender, strategy_event_sender.clone(), warm_up.clone()));
+        timed_event_handler.set_flat_by_handler(flat_by_handler.clone());
+
+        let (stale_order_sender, stale_order_receiver) = mpsc::channel(100);
+        route_engine_orders(stale_order_receiver, strategy_mode.clone(), open_order_cache.clone(), paper_order_sender.clone());
+        let stale_order_handler = Arc::new(StaleOrderHandler::new(open_order_cache.clone(), stale_order_sender, strategy_event_sender.clone(), warm_up.clone()));
+        timed_event_handler.set_stale_order_handler(stale_order_handler.clone());
+
+        let (queued_order_sender, queued_order_receiver) = mpsc::channel(100);
+        let closed_market_queue = Arc::new(ClosedMarketQueue::new(queued_order_sender, warm_up.clone()));
+        timed_event_handler.set_closed_market_queue(closed_market_queue.clone());
+
+        let (delayed_order_sender, delayed_order_receiver) = mpsc::channel(100);
+        let spread_guard_handler = Arc::new(SpreadGuardHandler::new(price_service.clone(), delayed_order_sender, strategy_event_sender.clone(), warm_up.clone(), settings.clone()));
+        timed_event_handler.set_spread_guard_handler(spread_guard_handler.clone());
+
+        // the queued and delayed orders are checked again when they are released
+        let order_gate = Arc::new(OrderGate::new(strategy_mode, warm_up.clone(), ledger_service.clone(), price_service.clone(), open_order_cache.clone(), closed_market_queue.clone(), spread_guard_handler.clone(), strategy_event_sender.clone(), settings.clone()));
+        let (held_order_sender, held_order_receiver) = mpsc::channel(100);
+        route_engine_orders(held_order_receiver, strategy_mode.clone(), open_order_cache.clone(), paper_order_sender.clone());
+        route_held_orders(queued_order_receiver, order_gate.clone(), held_order_sender.clone());
+        route_held_orders(delayed_order_receiver, order_gate.clone(), held_order_sender);
+
+        let contract_expiry_handler = Arc::new(ContractExpiryHandler::new(subscription_handler.clone(), indicator_handler.clone(), strategy_event_sender.clone()));
+        timed_event_handler.set_contract_expiry_handler(contract_expiry_handler.clone());
+
+        let daily_report_handler = Arc::new(DailyReportHandler::new(accounts.clone(), ledger_service.clone(), strategy_event_sender.clone(), warm_up.clone()));
+        timed_event_handler.set_daily_report_handler(daily_report_handler.clone());
+
         let strategy = FundForgeStrategy {
             historical_message_sender: paper_order_sender.clone(),
             backtest_accounts_starting_cash,
@@ -199,11 +402,32 @@ impl FundForgeStrategy {
             subscription_handler: subscription_handler.clone(),
             indicator_handler: indicator_handler.clone(),
             timed_event_handler: timed_event_handler.clone(),
+            price_trigger_handler: price_trigger_handler.clone(),
             drawing_objects_handler,
             synchronize_accounts,
             accounts: accounts.clone(),
             ledger_service: ledger_service.clone(),
-            market_price_service: price_service.clone()
+            market_price_service: price_service.clone(),
+            backtest_regimes: backtest_regimes.clone(),
+            strategy_event_sender: strategy_event_sender.clone(),
+            trading_hours: symbol_trading_hours,
+            symbol_info: DashMap::new(),
+            pair_handler,
+            order_hooks,
+            flat_by_handler,
+            daily_report_handler,
+            stale_order_handler,
+            contract_expiry_handler,
+            closed_market_queue,
+            spread_guard_handler,
+            closed_market_policy: RwLock::new(ClosedMarketPolicy::default()),
+            order_gate,
+            fill_auditor: Arc::new(FillAuditor::new(price_service.clone(), strategy_event_sender.clone(), settings.clone())),
+            settings: settings.clone(),
+            bar_time_convention: RwLock::new(BarTimeConvention::default()),
+            warm_up,
+            #[cfg(feature = "onnx")]
+            models: DashMap::new(),
         };
 
 
@@ -224,14 +448,18 @@ impl FundForgeStrategy {
                     timed_event_handler.clone(),
                     indicator_handler.clone(),
                     subscription_handler.clone(),
-                    price_service.clone()
+                    price_service.clone(),
+                    price_trigger_handler.clone(),
+                    backtest_regimes
                 ).await;
 
                 HistoricalEngine::launch(engine).await;
+                stop_backtest_on_ctrl_c();
             }
             StrategyMode::LivePaperTrading | StrategyMode::Live  => {
+                shutdown_live_on_signal(strategy_event_sender.clone(), open_order_cache.clone(), settings.clone());
                 TimedEventHandler::run_time_updates(timed_event_handler.clone()).await;
-                live_subscription_handler(strategy_mode.clone(), subscription_handler.clone()).await;
+                live_subscription_handler(route_id, strategy_mode.clone(), subscription_handler.clone(), strategy_event_sender.clone()).await;
             },
         }
 
@@ -239,12 +467,18 @@ impl FundForgeStrategy {
             ledger_service.init_ledger(&account,strategy_mode, synchronize_accounts, backtest_accounts_starting_cash, backtest_account_currency).await;
         }
 
+        if strategy_mode == StrategyMode::Live {
+            ledger_service.set_account_sync_interval(Some(DEFAULT_ACCOUNT_SYNC_INTERVAL));
+        }
+
         if strategy_mode != StrategyMode::Backtest {
-            live_warm_up(Utc::now() - warmup_duration, buffering_duration, subscription_handler, strategy_event_sender, timed_event_handler, ledger_service, indicator_handler, price_service.clone()).await;
+            live_warm_up(Utc::now() - warmup_duration, buffering_duration, subscription_handler, strategy_event_sender, timed_event_handler, ledger_service, indicator_handler, price_service.clone(), price_trigger_handler, live_warm_up_source()).await;
         }
-        strategy
+        Ok(strategy)
     }
 
+    /// Calculates a stop price for a linear instrument which will not lose more than `max_loss` over the `quantity`.
+    /// For targets, risk from stop distance and instruments where the value per tick depends on price, see `ff_standard_lib::helpers::price_risk`.
     pub fn calculate_stop_price(&self, entry_price: Decimal, position_side: PositionSide, max_loss: Decimal, value_per_tick: Decimal, tick_size: Decimal, quantity: Decimal) -> Decimal {
         // Calculate loss per contract
         let loss_per_contract = max_loss.abs() / quantity;
@@ -337,6 +571,55 @@ impl FundForgeStrategy {
         self.market_price_service.get_market_price(order_side, &symbol_name, &symbol_code)
     }
 
+    /// Paper fills cross the spread, but a symbol with only candle data has no bid or ask and fills at the last close.
+    /// Sets the spread for the symbol so buys fill at the close plus half the spread and sells at the close minus half, None fills at the close.
+    /// This is used in backtests and live paper trading, the spread is ignored once quotes, quote bars or depth are received for the symbol.
+    ///
+    /// # Example
+    /// ```rust
+    /// strategy.set_paper_spread(SymbolName::from("EUR-USD"), Some(dec!(0.00012)));
+    /// ```
+    pub fn set_paper_spread(&self, symbol_name: SymbolName, spread: Option<Price>) {
+        self.market_price_service.set_paper_spread(symbol_name, spread)
+    }
+
+    /// Sets which symbols can be sold short and their annual borrow fee rates, by default every symbol is shortable without a fee.
+    /// Sells which would open or increase a short of a symbol which is not shortable are refused with `OrderError::NotShortable` in every mode,
+    /// the backtest and live paper ledgers charge the borrow fee of open short positions once per day, see `strategy.borrow_fees()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// strategy.set_shortability_table(ShortabilityTable::new()
+    ///     .with_symbol(SymbolName::from("GME"), Shortability::shortable(dec!(0.45)))
+    ///     .with_default(Shortability::not_shortable()));
+    /// ```
+    pub fn set_shortability_table(&self, table: ShortabilityTable) {
+        self.settings.set_shortability_table(table);
+    }
+
+    /// Sets the provider of the rates which convert the pnl, margin and commissions of symbols in a foreign currency to the account currency.
+    /// By default the rates are requested from the data server, one rate per day in backtests and a rate at most a minute old live.
+    /// ```rust
+    /// // an AUD account trading EUR-JPY books the JPY pnl at the JPY-AUD rate
+    /// strategy.set_conversion_rate_provider(Arc::new(FixedRates::new().with_rate(Currency::JPY, Currency::AUD, dec!(0.0102))));
+    /// // or a rate per hour from the data server
+    /// strategy.set_conversion_rate_provider(Arc::new(DataServerRates::new(ChronoDuration::hours(1))));
+    /// ```
+    pub fn set_conversion_rate_provider(&self, provider: Arc<dyn ConversionRateProvider>) {
+        self.settings.set_conversion_rate_provider(provider);
+    }
+
+    /// Sets the decimal places the prices of the symbol are displayed with in the positions, exports and reports of this strategy, eg 2 for MNQ or 5 for EUR-USD.
+    /// By default prices are displayed with the decimal accuracy of the symbol info, the prices used by the ledgers are never rounded.
+    ///
+    /// # Example
+    /// ```rust
+    /// strategy.set_display_precision(SymbolName::from("MNQ"), 2);
+    /// ```
+    pub fn set_display_precision(&self, symbol_name: SymbolName, precision: u32) {
+        self.settings.set_display_precision(symbol_name, precision)
+    }
+
     /// true if long, false if flat or short.
     pub fn is_long(&self, account: &Account, name: &String) -> bool {
         self.ledger_service.is_long(account, name)
@@ -360,9 +643,311 @@ impl FundForgeStrategy {
         )
     }
 
-    //todo[Strategy]
-    pub async fn custom_order(&self, _order: Order, _order_type: OrderType) -> OrderId {
-        todo!("Make a fn that takes an order and figures out what to do with it")
+    /// Checks an order before it is sent, these orders would certainly be rejected by the brokerage or the matching engine.
+    /// The market is only checked for closed sessions if the symbol was subscribed with trading hours.
+    /// Symbols subscribed as `MarketType::Index`, or with reference symbol info, are refused as `OrderError::NotTradeable`.
+    async fn validate_order(&self, order: &Order) -> Result<(), OrderError> {
+        order.validate()?;
+        if !self.accounts.contains(&order.account) {
+            return Err(OrderError::UnknownAccount(order.account.clone()));
+        }
+        if self.subscription_handler.subscriptions().await.iter()
+            .any(|subscription| subscription.symbol.name == order.symbol_name && !subscription.market_type.is_tradeable()) {
+            return Err(OrderError::NotTradeable(order.symbol_name.clone()));
+        }
+        if !OPTION_CONTRACTS.contains_key(&order.symbol_name) {
+            if self.cached_symbol_info(order.account.brokerage, &order.symbol_name).await?.is_reference {
+                return Err(OrderError::NotTradeable(order.symbol_name.clone()));
+            }
+        }
+        let long_quantity = match self.ledger_service.is_long(&order.account, &order.symbol_code) {
+            true => self.ledger_service.position_size(&order.account, &order.symbol_code),
+            false => Decimal::ZERO,
+        };
+        self.settings.shortability_table().check_order(&order.symbol_name, order.side, order.quantity_open, long_quantity)?;
+        if let Some(trading_hours) = self.trading_hours.get(&order.symbol_name) {
+            if !trading_hours.is_market_open(self.time_utc()) {
+                return Err(OrderError::MarketClosed(order.symbol_name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// The symbol info of the brokerage, requested once per symbol.
+    async fn cached_symbol_info(&self, brokerage: Brokerage, symbol_name: &SymbolName) -> Result<SymbolInfo, OrderError> {
+        let key = (brokerage, symbol_name.clone());
+        if let Some(info) = self.symbol_info.get(&key) {
+            return Ok(self.settings.with_display_precision(info.value().clone()));
+        }
+        match brokerage.symbol_info(symbol_name.clone()).await {
+            Ok(info) => {
+                info.register_display_precision();
+                self.symbol_info.insert(key, info.clone());
+                Ok(self.settings.with_display_precision(info))
+            }
+            Err(_) => Err(OrderError::UnknownSymbol(symbol_name.clone())),
+        }
+    }
+
+    /// Validates the order, runs the pre order hooks and sends it to the brokerage or the matching engine.
+    /// Orders which fail validation or are refused by a hook are returned as an `OrderError` and never sent, rejections by the brokerage still arrive as `OrderEvent`s.
+    /// An order sent while the market is closed is queued until the open if its policy, or the strategy policy, is `ClosedMarketPolicy::QueueUntilOpen`.
+    async fn send_order(&self, mut order: Order, order_type: OrderType) -> Result<OrderId, OrderError> {
+        self.order_gate.check_entry(&order, &order_type, &[])?;
+        match self.validate_order(&order).await {
+            Ok(()) => {}
+            Err(OrderError::MarketClosed(symbol_name)) => {
+                let policy = order.closed_market_policy.unwrap_or_else(|| *self.closed_market_policy.read().unwrap());
+                let trading_hours = match self.trading_hours.get(&symbol_name) {
+                    Some(trading_hours) if policy == ClosedMarketPolicy::QueueUntilOpen => trading_hours.value().clone(),
+                    _ => return Err(OrderError::MarketClosed(symbol_name)),
+                };
+                self.order_hooks.run_pre_order(&mut order)?;
+                let order_id = order.id.clone();
+                self.closed_market_queue.add(order, order_type, trading_hours);
+                return Ok(order_id);
+            }
+            Err(e) => return Err(e),
+        }
+        if order_type.is_market() {
+            self.order_gate.check_quote_age(&order.symbol_name).await?;
+        }
+        self.order_hooks.run_pre_order(&mut order)?;
+        if let Some(reject_at) = self.order_gate.check_spread(&order, &order_type).await? {
+            let order_id = order.id.clone();
+            self.spread_guard_handler.delay(order, order_type, reject_at);
+            return Ok(order_id);
+        }
+        Ok(self.dispatch_order(order, order_type).await)
+    }
+
+    /// Sends an order which has been validated.
+    async fn dispatch_order(&self, mut order: Order, order_type: OrderType) -> OrderId {
+        let order_id = order.id.clone();
+        match self.trading_hours.get(&order.symbol_name) {
+            Some(trading_hours) => order.resolve_day_expiry(Some(trading_hours.value()), self.time_utc()),
+            None => order.resolve_day_expiry(get_trading_hours(&order.symbol_name).as_ref(), self.time_utc()),
+        }
+        let order_request = OrderRequest::Create{ account: order.account.clone(), order: order.clone(), order_type };
+        if self.mode == StrategyMode::Live {
+            self.open_order_cache.insert(order_id.clone(), order);
+            let connection_type = ConnectionType::Broker(order_request.brokerage());
+            let request = StrategyRequest::OneWay(connection_type, DataServerRequest::OrderRequest { request: order_request });
+            send_request(request).await;
+        } else {
+            if let Some(historical_message_sender) = &self.historical_message_sender {
+                historical_message_sender.send(BackTestEngineMessage::OrderRequest(order_request)).await.unwrap();
+            }
+        }
+        order_id
+    }
+
+    /// Checks what an order would do without sending it, the estimated price, the margin it would commit or release, the resulting position and whether it would be refused.
+    /// The order is validated like `send_order`, then run against the account ledger, an order the account could not margin is refused with `OrderError::InsufficientFunds`.
+    /// An exit order with no position on that side to exit is refused with `OrderError::InvalidQuantity`.
+    ///
+    /// # Example
+    /// ```rust
+    /// let order = Order::market_order(symbol_name.clone(), None, &account, dec!(5), OrderSide::Buy, "Entry".to_string(), "preflight".to_string(), strategy.time_utc(), None);
+    /// let preflight = strategy.preflight(&order).await;
+    /// if preflight.is_allowed() {
+    ///     println!("margin: {}, cash after: {}, position: {} {}", preflight.margin_required, preflight.cash_available_after, preflight.resulting_side, preflight.resulting_quantity);
+    /// }
+    /// ```
+    pub async fn preflight(&self, order: &Order) -> OrderPreflight {
+        let mut preflight = match self.ledger_service.preflight(order, self.time_utc()).await {
+            Some(preflight) => preflight,
+            None => OrderPreflight::refused(order, OrderError::UnknownAccount(order.account.clone())),
+        };
+        // an order refused before it is sent is never margined
+        if let Err(e) = self.validate_order(order).await {
+            preflight.refusal = Some(e);
+        }
+        preflight
+    }
+
+    /// Adds a hook run by the engine after an order is validated and before it is sent, a hook with the same name is replaced.
+    /// The hooks run in the order they were added, a hook can change the order or refuse it with `OrderError::RefusedByHook`, the refusal is returned by the order fn.
+    /// Hooks run in the order path rather than the strategy loop, so the check runs even when the strategy is behind on its events.
+    /// `preflight()` does not run the hooks.
+    /// ```rust
+    /// strategy.add_pre_order_hook("Max Size", Arc::new(|order: &mut Order| {
+    ///     match order.quantity_open > dec!(10) {
+    ///         true => Err(OrderError::RefusedByHook(format!("{} is over the max size", order.quantity_open))),
+    ///         false => Ok(()),
+    ///     }
+    /// }));
+    /// ```
+    pub fn add_pre_order_hook(&self, name: &str, hook: PreOrderHook) {
+        self.order_hooks.add_pre_order(name.to_string(), hook);
+    }
+
+    /// Adds a hook run by the engine on each fill and partial fill, before the order event is queued for the strategy, a hook with the same name is replaced.
+    /// Hooks must not block, share state with a pre order hook to act on the fill, eg to stop new orders after a loss.
+    /// ```rust
+    /// let halted = Arc::new(AtomicBool::new(false));
+    /// let fill_halted = halted.clone();
+    /// strategy.add_post_fill_hook("Halt On Fill", Arc::new(move |event: &OrderUpdateEvent| {
+    ///     if let OrderUpdateEvent::OrderFilled { quantity, .. } = event {
+    ///         if *quantity > dec!(10) {
+    ///             fill_halted.store(true, Ordering::SeqCst);
+    ///         }
+    ///     }
+    /// }));
+    /// strategy.add_pre_order_hook("Halt On Fill", Arc::new(move |_order: &mut Order| {
+    ///     match halted.load(Ordering::SeqCst) {
+    ///         true => Err(OrderError::RefusedByHook("halted".to_string())),
+    ///         false => Ok(()),
+    ///     }
+    /// }));
+    /// ```
+    pub fn add_post_fill_hook(&self, name: &str, hook: PostFillHook) {
+        self.order_hooks.add_post_fill(name.to_string(), hook);
+    }
+
+    /// Removes the pre order and post fill hooks with the name.
+    pub fn remove_order_hook(&self, name: &str) {
+        self.order_hooks.remove(name);
+    }
+
+    /// Sends an order built with the `Order` constructors, eg to set a per order `ClosedMarketPolicy`, the order is validated and hooked like the other order fns.
+    /// ```rust
+    /// let order = Order::limit_order(symbol_name.clone(), None, &account, dec!(10), OrderSide::Buy, "Open".to_string(), "open_1".to_string(), strategy.time_utc(), limit_price, TimeInForce::Day, None)
+    ///     .with_closed_market_policy(ClosedMarketPolicy::QueueUntilOpen);
+    /// let order_id = strategy.custom_order(order, OrderType::Limit).await?;
+    /// ```
+    pub async fn custom_order(&self, order: Order, order_type: OrderType) -> Result<OrderId, OrderError> {
+        self.send_order(order, order_type).await
+    }
+
+    /// Sets what happens to orders sent while the market of the symbol is closed, `ClosedMarketPolicy::Reject` by default.
+    /// With `ClosedMarketPolicy::QueueUntilOpen` the order fns return the order id and the engine holds the order until the next session open, then sends it.
+    /// An order with its own `closed_market_policy` ignores the strategy policy, the market is only known to be closed if the symbol was subscribed with trading hours.
+    pub fn set_closed_market_policy(&self, policy: ClosedMarketPolicy) {
+        *self.closed_market_policy.write().unwrap() = policy;
+    }
+
+    /// How long ago the prices of the symbol were last updated by quotes, ticks, depth, quote bars or candles, in strategy time, None if no data has been received.
+    pub fn quote_age(&self, symbol_name: &SymbolName) -> Option<ChronoDuration> {
+        self.market_price_service.quote_age(symbol_name, self.time_utc())
+    }
+
+    /// Refuses market orders with `OrderError::StaleQuote` when the prices of the symbol are older than `max_quote_age`, None turns the guard off, the default.
+    /// A refusal sends `StrategyEvent::HealthWarning(HealthWarning::StaleQuote)`. A market order for a symbol with no prices at all is refused with `OrderError::NoMarketPrice`.
+    /// The flattens of the engine, eg flat by rules, are never refused.
+    /// ```rust
+    /// strategy.set_max_quote_age(Some(chrono::Duration::seconds(5)));
+    /// ```
+    pub fn set_max_quote_age(&self, max_quote_age: Option<ChronoDuration>) {
+        *self.order_gate.max_quote_age.write().unwrap() = max_quote_age;
+    }
+
+    /// Sets the trading windows of every symbol, outside all the windows orders which would open, increase or reverse a position are refused with `OrderError::OutsideTradingWindow`.
+    /// Data still flows and orders which reduce a position are still sent, an empty list removes the windows, the default.
+    /// ```rust
+    /// strategy.set_trading_windows(vec![TradingWindow::us_regular_trading_hours()]);
+    /// ```
+    pub fn set_trading_windows(&self, windows: Vec<TradingWindow>) {
+        self.order_gate.trading_windows.set(windows);
+    }
+
+    /// Sets the trading windows of one symbol, which replace the strategy windows for the symbol, None to use the strategy windows again.
+    /// ```rust
+    /// strategy.set_symbol_trading_windows("EUR-USD".to_string(), Some(vec![TradingWindow::london_new_york_overlap()]));
+    /// ```
+    pub fn set_symbol_trading_windows(&self, symbol_name: SymbolName, windows: Option<Vec<TradingWindow>>) {
+        self.order_gate.trading_windows.set_symbol(symbol_name, windows);
+    }
+
+    /// Returns true if the symbol may open positions at the strategy time, always true for a symbol without trading windows.
+    pub fn is_trading_window_active(&self, symbol_name: &SymbolName) -> bool {
+        self.order_gate.trading_windows.is_active(symbol_name, self.time_utc())
+    }
+
+    /// In reduce only mode orders which would open, increase or reverse a position are refused with `OrderError::ReduceOnly`, exits, stop and limit price updates and cancels are still sent.
+    /// The mode only applies to this strategy, the control server and the gui control panel, given `strategy.reduce_only_handle()`, can also set it.
+    /// ```rust
+    /// if strategy.pnl(&account, &symbol_name) < dec!(-500) {
+    ///     strategy.set_reduce_only(true);
+    /// }
+    /// ```
+    pub fn set_reduce_only(&self, reduce_only: bool) {
+        self.settings.reduce_only.set(reduce_only);
+    }
+
+    pub fn is_reduce_only(&self) -> bool {
+        self.settings.reduce_only.is_reduce_only()
+    }
+
+    /// A handle to the reduce only mode of this strategy, for the gui control panel or a task which does not own the strategy.
+    pub fn reduce_only_handle(&self) -> ReduceOnlyHandle {
+        self.settings.reduce_only.clone()
+    }
+
+    /// Adds a group of correlated symbols with a max net exposure across every strategy account, a group with the same name is replaced.
+    /// An order which could take the group past the max, counting the working orders of the group as filled, is refused with `OrderError::ExposureLimit`.
+    /// ```rust
+    /// // max 6 contracts long or short across MNQ, MES and NQ
+    /// strategy.set_exposure_group(ExposureGroup::new("US Equity Index", dec!(6))
+    ///     .with_symbol("MNQ".to_string(), dec!(1))
+    ///     .with_symbol("MES".to_string(), dec!(1))
+    ///     .with_symbol("NQ".to_string(), dec!(1)));
+    /// ```
+    pub fn set_exposure_group(&self, group: ExposureGroup) {
+        self.order_gate.exposure_groups.set(group);
+    }
+
+    pub fn remove_exposure_group(&self, name: &str) {
+        self.order_gate.exposure_groups.remove(name);
+    }
+
+    pub fn exposure_groups(&self) -> Vec<ExposureGroup> {
+        self.order_gate.exposure_groups.groups()
+    }
+
+    /// The net weighted exposure of the group across every strategy account, positive when net long, None if there is no group with the name.
+    pub fn group_exposure(&self, name: &str) -> Option<Decimal> {
+        let group = self.order_gate.exposure_groups.groups().into_iter().find(|group| group.name == name)?;
+        Some(group.exposure(&self.order_gate.open_positions()))
+    }
+
+    /// The orders held until the market of their symbol opens, they can be cancelled with `cancel_order()`.
+    pub fn queued_orders(&self) -> Vec<Order> {
+        self.closed_market_queue.orders()
+    }
+
+    /// Guards the market, limit, enter long and enter short entries of every symbol against wide spreads, None removes the guard, the default.
+    /// An entry sent while the spread is wider than the max is refused with `OrderError::WideSpread` or delayed until the spread narrows, depending on the `SpreadAction`.
+    /// Orders which only reduce a position are never guarded.
+    /// ```rust
+    /// // hold entries while the spread is over 3 times normal, for up to 2 minutes
+    /// strategy.set_spread_guard(Some(SpreadGuard::new(SpreadLimit::NormalMultiple(dec!(3)), SpreadAction::Delay(chrono::Duration::minutes(2)))));
+    /// ```
+    pub fn set_spread_guard(&self, guard: Option<SpreadGuard>) {
+        self.spread_guard_handler.set(guard);
+    }
+
+    /// Sets the spread guard of one symbol, which replaces the strategy guard for the symbol, None to use the strategy guard again.
+    /// ```rust
+    /// strategy.set_symbol_spread_guard("EUR-USD".to_string(), Some(SpreadGuard::new(SpreadLimit::Absolute(dec!(0.0003)), SpreadAction::Reject)));
+    /// ```
+    pub fn set_symbol_spread_guard(&self, symbol_name: SymbolName, guard: Option<SpreadGuard>) {
+        self.spread_guard_handler.set_symbol(symbol_name, guard);
+    }
+
+    /// The current spread of the symbol, None if no quotes, quote bars or depth have been received.
+    pub fn spread(&self, symbol_name: &SymbolName) -> Option<Price> {
+        self.market_price_service.spread(symbol_name)
+    }
+
+    /// The normal spread of the symbol, the median of its last 200 spreads, None until 20 spreads have been received.
+    pub fn normal_spread(&self, symbol_name: &SymbolName) -> Option<Price> {
+        self.market_price_service.normal_spread(symbol_name)
+    }
+
+    /// The entries held by the spread guard until the spread narrows, they can be cancelled with `cancel_order()`.
+    pub fn delayed_orders(&self) -> Vec<Order> {
+        self.spread_guard_handler.orders()
     }
 
 
@@ -375,7 +960,7 @@ impl FundForgeStrategy {
         exchange: Option<String>,
         quantity: Volume,
         tag: String,
-    ) -> OrderId {
+    ) -> Result<OrderId, OrderError> {
         let order_id = self.order_id().await;
         let order = Order::enter_long(
             symbol_name.clone(),
@@ -387,18 +972,7 @@ impl FundForgeStrategy {
             self.time_utc(),
             exchange
         );
-        let order_request = OrderRequest::Create{ account: account.clone(), order: order.clone(), order_type: OrderType::EnterLong };
-        if self.mode == StrategyMode::Live {
-            self.open_order_cache.insert(order_id.clone(), order.clone());
-            let connection_type = ConnectionType::Broker(order_request.brokerage());
-            let request = StrategyRequest::OneWay(connection_type, DataServerRequest::OrderRequest { request: order_request });
-            send_request(request).await;
-        } else {
-            if let Some(historical_message_sender) = &self.historical_message_sender {
-                historical_message_sender.send(BackTestEngineMessage::OrderRequest(order_request)).await.unwrap();
-            }
-        }
-        order_id
+        self.send_order(order, OrderType::EnterLong).await
     }
 
     /// Enters a short position and closes any long positions open for the account and symbol
@@ -410,7 +984,7 @@ impl FundForgeStrategy {
         exchange: Option<String>,
         quantity: Volume,
         tag: String,
-    ) -> OrderId {
+    ) -> Result<OrderId, OrderError> {
         let order_id = self.order_id().await;
         let order = Order::enter_short(
             symbol_name.clone(),
@@ -422,18 +996,7 @@ impl FundForgeStrategy {
             self.time_utc(),
             exchange
         );
-        let order_request = OrderRequest::Create{ account: account.clone(), order: order.clone(), order_type: OrderType::EnterShort};
-        if self.mode == StrategyMode::Live {
-            self.open_order_cache.insert(order_id.clone(), order.clone());
-            let connection_type = ConnectionType::Broker(order_request.brokerage());
-            let request = StrategyRequest::OneWay(connection_type, DataServerRequest::OrderRequest { request: order_request });
-            send_request(request).await;
-        } else {
-            if let Some(historical_message_sender) = &self.historical_message_sender {
-                historical_message_sender.send(BackTestEngineMessage::OrderRequest(order_request)).await.unwrap();
-            }
-        }
-        order_id
+        self.send_order(order, OrderType::EnterShort).await
     }
 
     /// Exits a long position or does nothing if no long position
@@ -445,7 +1008,7 @@ impl FundForgeStrategy {
         exchange: Option<String>,
         quantity: Volume,
         tag: String,
-    ) -> OrderId {
+    ) -> Result<OrderId, OrderError> {
         let order_id = self.order_id().await;
         let order = Order::exit_long(
             symbol_name.clone(),
@@ -457,18 +1020,7 @@ impl FundForgeStrategy {
             self.time_utc(),
             exchange
         );
-        let order_request = OrderRequest::Create{ account: account.clone(), order: order.clone(), order_type: OrderType::ExitLong};
-        if self.mode == StrategyMode::Live {
-            self.open_order_cache.insert(order_id.clone(), order.clone());
-            let connection_type = ConnectionType::Broker(order_request.brokerage());
-            let request = StrategyRequest::OneWay(connection_type, DataServerRequest::OrderRequest { request: order_request });
-            send_request(request).await;
-        } else {
-            if let Some(historical_message_sender) = &self.historical_message_sender {
-                historical_message_sender.send(BackTestEngineMessage::OrderRequest(order_request)).await.unwrap();
-            }
-        }
-        order_id
+        self.send_order(order, OrderType::ExitLong).await
     }
 
     /// Exits a short position or does nothing if no short position
@@ -480,7 +1032,7 @@ impl FundForgeStrategy {
         exchange: Option<String>,
         quantity: Volume,
         tag: String,
-    ) -> OrderId {
+    ) -> Result<OrderId, OrderError> {
         let order_id = self.order_id().await;
         let order = Order::exit_short(
             symbol_name.clone(),
@@ -492,18 +1044,7 @@ impl FundForgeStrategy {
             self.time_utc(),
             exchange
         );
-        let order_request = OrderRequest::Create{ account: account.clone(), order: order.clone(), order_type: OrderType::ExitShort};
-        if self.mode == StrategyMode::Live {
-            self.open_order_cache.insert(order_id.clone(), order.clone());
-            let connection_type = ConnectionType::Broker(order_request.brokerage());
-            let request = StrategyRequest::OneWay(connection_type, DataServerRequest::OrderRequest { request: order_request });
-            send_request(request).await;
-        } else {
-            if let Some(historical_message_sender) = &self.historical_message_sender {
-                historical_message_sender.send(BackTestEngineMessage::OrderRequest(order_request)).await.unwrap();
-            }
-        }
-        order_id
+        self.send_order(order, OrderType::ExitShort).await
     }
 
     /// Buys the market and effects any open positions, or creates a new one
@@ -515,7 +1056,7 @@ impl FundForgeStrategy {
         exchange: Option<String>,
         quantity: Volume,
         tag: String,
-    ) -> OrderId {
+    ) -> Result<OrderId, OrderError> {
         let order_id = self.order_id().await;
         let order = Order::market_order(
             symbol_name.clone(),
@@ -528,19 +1069,7 @@ impl FundForgeStrategy {
             self.time_utc(),
             exchange
         );
-        let order_request = OrderRequest::Create{ account: account.clone(), order: order.clone(), order_type: OrderType::Market};
-
-        if self.mode == StrategyMode::Live {
-            self.open_order_cache.insert(order_id.clone(), order.clone());
-            let connection_type = ConnectionType::Broker(order_request.brokerage());
-            let request = StrategyRequest::OneWay(connection_type, DataServerRequest::OrderRequest { request: order_request });
-            send_request(request).await;
-        } else {
-            if let Some(historical_message_sender) = &self.historical_message_sender {
-                historical_message_sender.send(BackTestEngineMessage::OrderRequest(order_request)).await.unwrap();
-            }
-        }
-        order_id
+        self.send_order(order, OrderType::Market).await
     }
 
     /// Sells the market and effects any open positions, or creates a new one
@@ -552,7 +1081,7 @@ impl FundForgeStrategy {
         exchange: Option<String>,
         quantity: Volume,
         tag: String,
-    ) -> OrderId {
+    ) -> Result<OrderId, OrderError> {
         let order_id = self.order_id().await;
         let order = Order::market_order(
             symbol_name.clone(),
@@ -565,51 +1094,470 @@ impl FundForgeStrategy {
             self.time_utc(),
             exchange
         );
-        let order_request = OrderRequest::Create{ account: account.clone(), order: order.clone(), order_type: OrderType::Market};
-        if self.mode == StrategyMode::Live {
-            self.open_order_cache.insert(order_id.clone(), order.clone());
-            let connection_type = ConnectionType::Broker(order_request.brokerage());
-            let request = StrategyRequest::OneWay(connection_type, DataServerRequest::OrderRequest { request: order_request });
-            send_request(request).await;
-        } else {
-            if let Some(historical_message_sender) = &self.historical_message_sender {
-                historical_message_sender.send(BackTestEngineMessage::OrderRequest(order_request)).await.unwrap();
+        self.send_order(order, OrderType::Market).await
+    }
+
+    /// Enters a long spread, buying `quantity` × the ratio of the first leg and selling `quantity` × the ratio of the second leg at market.
+    /// Both legs are validated before either is sent, if a leg is later rejected or cancelled the `legging` risk is applied, see `LeggingRisk`.
+    /// ```rust
+    /// let pair = Pair::new("NQ-ES".to_string(), PairLeg::new(nq, Some(nq_code), None, dec!(1)), PairLeg::new(es, Some(es_code), None, dec!(2)));
+    /// match strategy.enter_pair_long(&pair, &account, dec!(1), "Spread Long".to_string(), LeggingRisk::Unwind).await {
+    ///     Ok(pair_order) => println!("Pair orders: {:?}", pair_order.order_ids),
+    ///     Err(e) => eprintln!("Pair refused: {}", e),
+    /// }
+    /// ```
+    pub async fn enter_pair_long(&self, pair: &Pair, account: &Account, quantity: Volume, tag: String, legging: LeggingRisk) -> Result<PairOrder, OrderError> {
+        let time = self.time_utc();
+        let orders = vec![
+            leg_order(&pair.first, account, quantity, OrderSide::Buy, &tag, time),
+            leg_order(&pair.second, account, quantity, OrderSide::Sell, &tag, time),
+        ];
+        self.send_pair(pair, orders, legging).await
+    }
+
+    /// Enters a short spread, selling the first leg and buying the second leg, see `enter_pair_long()`.
+    pub async fn enter_pair_short(&self, pair: &Pair, account: &Account, quantity: Volume, tag: String, legging: LeggingRisk) -> Result<PairOrder, OrderError> {
+        let time = self.time_utc();
+        let orders = vec![
+            leg_order(&pair.first, account, quantity, OrderSide::Sell, &tag, time),
+            leg_order(&pair.second, account, quantity, OrderSide::Buy, &tag, time),
+        ];
+        self.send_pair(pair, orders, legging).await
+    }
+
+    /// Closes the positions of both legs of the pair at market, a leg which is already flat is skipped.
+    /// Returns `OrderError::InvalidQuantity` if both legs are flat.
+    pub async fn exit_pair(&self, pair: &Pair, account: &Account, tag: String, legging: LeggingRisk) -> Result<PairOrder, OrderError> {
+        let time = self.time_utc();
+        let mut orders = vec![];
+        for leg in [&pair.first, &pair.second] {
+            let key = leg.position_key();
+            let quantity = self.ledger_service.position_size(account, &key);
+            if quantity <= Decimal::ZERO {
+                continue;
             }
+            let side = match self.ledger_service.is_long(account, &key) {
+                true => OrderSide::Sell,
+                false => OrderSide::Buy,
+            };
+            // the leg order multiplies the quantity by the ratio, the position is closed in full
+            let close_leg = PairLeg::new(leg.symbol_name.clone(), leg.symbol_code.clone(), leg.exchange.clone(), Decimal::ONE);
+            orders.push(leg_order(&close_leg, account, quantity, side, &tag, time));
         }
-        order_id
+        if orders.is_empty() {
+            return Err(OrderError::InvalidQuantity);
+        }
+        self.send_pair(pair, orders, legging).await
     }
 
-    /// Will wait for limit price to be hit to fill, if TIF == TimeInForce::Day, it will be cancelled in backtests when the day is over.
-    pub async fn limit_order(
-        &self,
-        symbol_name: &SymbolName,
-        symbol_code: Option<SymbolCode>,
-        account: &Account,
-        exchange: Option<String>,
-        quantity: Volume,
-        side: OrderSide,
-        limit_price: Price,
-        tif: TimeInForce,
-        tag: String,
-    ) -> OrderId {
-        let order_id = self.order_id().await;
-        let order = Order::limit_order(symbol_name.clone(), symbol_code, account, quantity, side, tag, order_id.clone(), self.time_utc(), limit_price, tif, exchange);
-        let order_request = OrderRequest::Create{ account: account.clone(), order: order.clone(), order_type: OrderType::Limit};
-        if self.mode == StrategyMode::Live {
-            self.open_order_cache.insert(order_id.clone(), order.clone());
-            let connection_type = ConnectionType::Broker(order_request.brokerage());
-            let request = StrategyRequest::OneWay(connection_type, DataServerRequest::OrderRequest { request: order_request });
-            send_request(request).await;
-        } else {
-            if let Some(historical_message_sender) = &self.historical_message_sender {
-                historical_message_sender.send(BackTestEngineMessage::OrderRequest(order_request)).await.unwrap();
+    /// Checks and validates every leg and runs the pre order hooks, then registers the pair with the pair handler and sends the legs.
+    /// The legs pass the same checks as `send_order()`, each leg is checked for the exposure groups with the legs before it filled.
+    /// The legs are sent together, so a leg the spread guard would delay refuses the pair with `OrderError::WideSpread`.
+    async fn send_pair(&self, pair: &Pair, mut orders: Vec<Order>, legging: LeggingRisk) -> Result<PairOrder, OrderError> {
+        for (index, order) in orders.iter().enumerate() {
+            if self.order_gate.check(order, &OrderType::Market, &orders[..index]).await?.is_some() {
+                return Err(OrderError::WideSpread(order.symbol_name.clone()));
             }
+            self.validate_order(order).await?;
         }
-        order_id
+        for order in &mut orders {
+            self.order_hooks.run_pre_order(order)?;
+        }
+        self.pair_handler.add(PairExecution::new(orders.clone(), legging));
+        let mut order_ids = vec![];
+        for order in orders {
+            order_ids.push(self.dispatch_order(order, OrderType::Market).await);
+        }
+        Ok(PairOrder { pair: pair.name.clone(), order_ids })
     }
 
-    /// Will trigger if trigger price is hit and buy or sell at market price.
-    pub async fn market_if_touched (
+    /// Plans the market orders which take the account to the target weights of its equity without sending them, see `rebalance()`.
+    pub async fn rebalance_plan(&self, account: &Account, targets: &[TargetWeight], turnover_threshold: Decimal) -> Result<RebalancePlan, OrderError> {
+        let summary = self.account_summary(account).ok_or_else(|| OrderError::UnknownAccount(account.clone()))?;
+        let equity = summary.balance + self.pnl_total(account);
+        let mut valuations = vec![];
+        for target in targets {
+            let valuation = self.rebalance_valuation(account, summary.currency, target).await;
+            valuations.push((target.clone(), valuation));
+        }
+        plan_rebalance(account.clone(), equity, valuations, turnover_threshold)
+    }
+
+    /// Sends the market orders which take the account to the target weights of its equity, in backtest and live, and returns the plan with the result of each order.
+    /// Each target quantity is rounded toward zero to its lot size, and a symbol is only traded if the order is worth at least `turnover_threshold` of the equity.
+    /// Only the target symbols are traded, give a weight of 0 to close a symbol. Symbols without a market price yet are skipped, see `RebalancePlan::skipped`.
+    /// ```rust
+    /// let targets = vec![
+    ///     TargetWeight::new("SPY".to_string(), dec!(0.6)),
+    ///     TargetWeight::new("TLT".to_string(), dec!(0.4)),
+    /// ];
+    /// // trade symbols which are more than 1% of the equity from their target
+    /// let plan = strategy.rebalance(&account, &targets, dec!(0.01), "Rebalance".to_string()).await?;
+    /// for order in &plan.orders {
+    ///     println!("{} {} {}: {:?}", order.side, order.quantity, order.symbol_name, order.result);
+    /// }
+    /// ```
+    pub async fn rebalance(&self, account: &Account, targets: &[TargetWeight], turnover_threshold: Decimal, tag: String) -> Result<RebalancePlan, OrderError> {
+        let mut plan = self.rebalance_plan(account, targets, turnover_threshold).await?;
+        for order in plan.orders.iter_mut() {
+            let result = match order.side {
+                OrderSide::Buy => self.buy_market(&order.symbol_name, order.symbol_code.clone(), account, None, order.quantity, tag.clone()).await,
+                OrderSide::Sell => self.sell_market(&order.symbol_name, order.symbol_code.clone(), account, None, order.quantity, tag.clone()).await,
+            };
+            order.result = Some(result);
+        }
+        Ok(plan)
+    }
+
+    /// The mid price, the value of a quantity of 1 in the account currency and the signed position of a target symbol.
+    async fn rebalance_valuation(&self, account: &Account, currency: Currency, target: &TargetWeight) -> Result<SymbolValuation, String> {
+        let info = self.cached_symbol_info(account.brokerage, &target.symbol_name).await.map_err(|e| e.to_string())?;
+        if info.tick_size <= Decimal::ZERO {
+            return Err(format!("the symbol info of {} has no tick size", target.symbol_name));
+        }
+        let symbol_code = target.symbol_code.clone().unwrap_or(target.symbol_name.clone());
+        let bid = self.market_price_service.get_market_price(OrderSide::Sell, &target.symbol_name, &symbol_code);
+        let ask = self.market_price_service.get_market_price(OrderSide::Buy, &target.symbol_name, &symbol_code);
+        let price = match (bid, ask) {
+            (Some(bid), Some(ask)) => (bid + ask) / Decimal::TWO,
+            (Some(price), None) | (None, Some(price)) => price,
+            (None, None) => return Err("no market price has been received".to_string()),
+        };
+        let rate = match info.pnl_currency == currency {
+            true => Decimal::ONE,
+            false => self.settings.conversion_rate(info.pnl_currency, currency, self.time_utc(), OrderSide::Buy).await.map_err(|e| e.to_string())?,
+        };
+        let quantity = self.ledger_service.position_size(account, &symbol_code);
+        let position = match self.ledger_service.is_short(account, &symbol_code) {
+            true => -quantity,
+            false => quantity,
+        };
+        Ok(SymbolValuation {
+            price,
+            unit_value: price / info.tick_size * info.value_per_tick * rate,
+            position,
+        })
+    }
+
+    /// Executes the signals of the executor which are due at the strategy time on the executor account, in backtest and live.
+    /// Call it on each time slice, nothing is executed until the warm up is complete.
+    /// Returns the signals which were due and their outcome, see `SignalExecutor`.
+    /// # Example
+    /// ```rust
+    /// let mut signals = SignalExecutor::from_file(account.clone(), "./signals/model.csv")?.with_max_delay(Duration::minutes(1));
+    /// // on each StrategyEvent::TimeSlice
+    /// for execution in strategy.execute_signals(&mut signals).await {
+    ///     println!("{:?}: {:?}", execution.signal, execution.outcome);
+    /// }
+    /// ```
+    pub async fn execute_signals(&self, executor: &mut SignalExecutor) -> Vec<SignalExecution> {
+        if !self.warm_up.is_complete() {
+            return vec![];
+        }
+        let mut executions = vec![];
+        for (signal, skipped) in executor.take_due(self.time_utc()) {
+            let outcome = match skipped {
+                Some(reason) => SignalOutcome::Skipped(reason),
+                None => self.execute_signal(executor.account(), &signal).await,
+            };
+            executions.push(SignalExecution { signal, outcome });
+        }
+        executions
+    }
+
+    async fn execute_signal(&self, account: &Account, signal: &Signal) -> SignalOutcome {
+        let (side, quantity) = match signal.action {
+            SignalAction::Buy(quantity) => (OrderSide::Buy, quantity),
+            SignalAction::Sell(quantity) => (OrderSide::Sell, quantity),
+            SignalAction::TargetPosition(target) => {
+                let symbol_code = signal.position_symbol_code();
+                let quantity = self.ledger_service.position_size(account, &symbol_code);
+                let position = match self.ledger_service.is_short(account, &symbol_code) {
+                    true => -quantity,
+                    false => quantity,
+                };
+                match target_order(position, target) {
+                    Some(order) => order,
+                    None => return SignalOutcome::AtTarget,
+                }
+            }
+        };
+        let tag = signal.tag.clone().unwrap_or("Signal".to_string());
+        let result = match (signal.limit_price, side) {
+            (Some(limit_price), side) => self.limit_order(&signal.symbol_name, signal.symbol_code.clone(), account, None, quantity, side, limit_price, TimeInForce::GTC, tag).await,
+            (None, OrderSide::Buy) => self.buy_market(&signal.symbol_name, signal.symbol_code.clone(), account, None, quantity, tag).await,
+            (None, OrderSide::Sell) => self.sell_market(&signal.symbol_name, signal.symbol_code.clone(), account, None, quantity, tag).await,
+        };
+        match result {
+            Ok(order_id) => SignalOutcome::Sent(order_id),
+            Err(e) => SignalOutcome::Refused(e),
+        }
+    }
+
+    /// Executes the resting intents saved on the data server for the symbols of the executor, in backtest and live, see `IntentExecutor`.
+    /// Call it on each time slice, nothing is executed until the warm up is complete.
+    /// Returns the intents which triggered, were bracketed or exited on this call.
+    /// # Example
+    /// ```rust
+    /// let mut intents = IntentExecutor::new(vec!["MNQ".to_string()]);
+    /// // on each StrategyEvent::TimeSlice
+    /// for execution in strategy.execute_intents(&mut intents).await {
+    ///     println!("{}: {:?}", execution.intent.label, execution.outcome);
+    /// }
+    /// ```
+    pub async fn execute_intents(&self, executor: &mut IntentExecutor) -> Vec<IntentExecution> {
+        if !self.warm_up.is_complete() {
+            return vec![];
+        }
+        let time = self.time_utc();
+        if executor.refresh_due(time, self.mode) {
+            for symbol_name in executor.symbols().to_vec() {
+                match get_intents(symbol_name.clone()).await {
+                    Ok(intents) => executor.set_resting(&symbol_name, intents),
+                    Err(e) => eprintln!("Intents: Failed to load the intents of {}: {}", symbol_name, e),
+                }
+            }
+            executor.set_refreshed(time);
+        }
+
+        let mut executions = vec![];
+        let mut in_range = vec![];
+        for intent in executor.resting() {
+            if self.intent_in_range(intent).await {
+                in_range.push(intent.id.clone());
+            }
+        }
+        for intent in executor.take_resting(&in_range) {
+            // a backtest never claims, so it does not use up the intents of the live strategies
+            if self.mode != StrategyMode::Backtest {
+                match claim_intent(intent.symbol_name.clone(), intent.id.clone(), time).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        executions.push(IntentExecution { intent, outcome: IntentOutcome::ClaimedElsewhere });
+                        continue;
+                    }
+                    Err(e) => {
+                        eprintln!("Intents: Failed to claim {}: {}", intent.label, e);
+                        executor.restore(intent);
+                        continue;
+                    }
+                }
+            }
+            let outcome = match self.send_intent_entry(&intent).await {
+                Ok(order_id) => {
+                    executor.start(intent.clone(), order_id.clone());
+                    IntentOutcome::Triggered(order_id)
+                }
+                Err(e) => {
+                    // nothing was sent, so the claim is released and the intent rests until it can be sent
+                    if self.mode != StrategyMode::Backtest {
+                        if let Err(e) = save_intent(intent.clone()).await {
+                            eprintln!("Intents: Failed to release the claim of {}: {}", intent.label, e);
+                        }
+                    }
+                    executor.restore(intent.clone());
+                    IntentOutcome::Refused(e)
+                }
+            };
+            executions.push(IntentExecution { intent, outcome });
+        }
+
+        for mut working in executor.take_working() {
+            let (outcome, done) = self.update_working_intent(&mut working).await;
+            if let Some(outcome) = outcome {
+                executions.push(IntentExecution { intent: working.intent.clone(), outcome });
+            }
+            if !done {
+                executor.continue_working(working);
+            }
+        }
+        executions
+    }
+
+    /// True if the best bid and ask are within the trigger ticks of the intent, false until the symbol has quotes and a tick size.
+    async fn intent_in_range(&self, intent: &Intent) -> bool {
+        let symbol_code = intent.symbol_code.clone().unwrap_or(intent.symbol_name.clone());
+        let bid = self.market_price_service.get_market_price(OrderSide::Sell, &intent.symbol_name, &symbol_code);
+        let ask = self.market_price_service.get_market_price(OrderSide::Buy, &intent.symbol_name, &symbol_code);
+        let (Some(bid), Some(ask)) = (bid, ask) else {
+            return false;
+        };
+        match self.cached_symbol_info(intent.account.brokerage, &intent.symbol_name).await {
+            Ok(info) => intent.is_in_range(bid, ask, info.tick_size),
+            Err(_) => false,
+        }
+    }
+
+    async fn send_intent_entry(&self, intent: &Intent) -> Result<OrderId, OrderError> {
+        let tag = format!("Intent {}", intent.label);
+        match (&intent.entry, intent.side) {
+            (IntentEntry::Limit, side) => self.limit_order(&intent.symbol_name, intent.symbol_code.clone(), &intent.account, None, intent.quantity, side, intent.price, TimeInForce::GTC, tag).await,
+            (IntentEntry::Market, OrderSide::Buy) => self.buy_market(&intent.symbol_name, intent.symbol_code.clone(), &intent.account, None, intent.quantity, tag).await,
+            (IntentEntry::Market, OrderSide::Sell) => self.sell_market(&intent.symbol_name, intent.symbol_code.clone(), &intent.account, None, intent.quantity, tag).await,
+        }
+    }
+
+    /// Sends the bracket once the entry is closed with a fill and cancels the other exit when an exit fills.
+    /// Returns the outcome, if anything happened, and true when the intent is done.
+    async fn update_working_intent(&self, working: &mut WorkingIntent) -> (Option<IntentOutcome>, bool) {
+        if working.exits.is_empty() {
+            let Some(entry) = self.cached_order(&working.entry) else {
+                return (None, false);
+            };
+            match entry.state {
+                OrderState::Filled | OrderState::Cancelled | OrderState::Rejected(_) | OrderState::Expired => {}
+                OrderState::Created | OrderState::Accepted | OrderState::PartiallyFilled => return (None, false),
+            }
+            if entry.quantity_filled <= Decimal::ZERO {
+                return (Some(IntentOutcome::EntryClosed(working.entry.clone())), true);
+            }
+            let (take_profit, stop_loss) = self.send_intent_bracket(&working.intent, &entry).await;
+            working.exits = take_profit.iter().chain(stop_loss.iter()).cloned().collect();
+            let done = working.exits.is_empty();
+            return (Some(IntentOutcome::Bracketed { take_profit, stop_loss }), done);
+        }
+        let filled = working.exits.iter()
+            .find(|exit| self.cached_order(exit).map_or(false, |order| order.state == OrderState::Filled))
+            .cloned();
+        match filled {
+            Some(filled) => {
+                for exit in working.exits.iter().filter(|exit| **exit != filled) {
+                    self.cancel_order(exit.clone()).await;
+                }
+                (Some(IntentOutcome::Exited(filled)), true)
+            }
+            // the exits were cancelled by hand
+            None => (None, working.exits.iter().all(|exit| !self.open_order_cache.contains_key(exit))),
+        }
+    }
+
+    /// Sends the take profit and stop loss of an intent for the filled quantity of the entry.
+    async fn send_intent_bracket(&self, intent: &Intent, entry: &Order) -> (Option<OrderId>, Option<OrderId>) {
+        if intent.take_profit_ticks.is_none() && intent.stop_loss_ticks.is_none() {
+            return (None, None);
+        }
+        let info = match self.cached_symbol_info(intent.account.brokerage, &intent.symbol_name).await {
+            Ok(info) => info,
+            Err(e) => {
+                eprintln!("Intents: No bracket sent for {}: {}", intent.label, e);
+                return (None, None);
+            }
+        };
+        let (take_profit_price, stop_loss_price) = intent.bracket_prices(&info, entry.average_fill_price.unwrap_or(intent.price));
+        let exit_side = match intent.side {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        };
+        let take_profit = match take_profit_price {
+            Some(price) => {
+                let tag = format!("Intent {} Take Profit", intent.label);
+                self.limit_order(&intent.symbol_name, intent.symbol_code.clone(), &intent.account, None, entry.quantity_filled, exit_side, price, TimeInForce::GTC, tag).await
+                    .map_err(|e| eprintln!("Intents: Take profit of {} refused: {}", intent.label, e))
+                    .ok()
+            }
+            None => None,
+        };
+        let stop_loss = match stop_loss_price {
+            Some(price) => {
+                let tag = format!("Intent {} Stop Loss", intent.label);
+                self.stop_order(&intent.symbol_name, intent.symbol_code.clone(), &intent.account, None, entry.quantity_filled, exit_side, price, TimeInForce::GTC, tag).await
+                    .map_err(|e| eprintln!("Intents: Stop loss of {} refused: {}", intent.label, e))
+                    .ok()
+            }
+            None => None,
+        };
+        (take_profit, stop_loss)
+    }
+
+    fn cached_order(&self, order_id: &OrderId) -> Option<Order> {
+        self.open_order_cache.get(order_id)
+            .or_else(|| self.closed_order_cache.get(order_id))
+            .map(|order| order.value().clone())
+    }
+
+    /// Will wait for limit price to be hit to fill, if TIF == TimeInForce::Day, it expires at the session close of the symbol with `OrderUpdateEvent::OrderExpired`.
+    pub async fn limit_order(
+        &self,
+        symbol_name: &SymbolName,
+        symbol_code: Option<SymbolCode>,
+        account: &Account,
+        exchange: Option<String>,
+        quantity: Volume,
+        side: OrderSide,
+        limit_price: Price,
+        tif: TimeInForce,
+        tag: String,
+    ) -> Result<OrderId, OrderError> {
+        let order_id = self.order_id().await;
+        let order = Order::limit_order(symbol_name.clone(), symbol_code, account, quantity, side, tag, order_id.clone(), self.time_utc(), limit_price, tif, exchange);
+        self.send_order(order, OrderType::Limit).await
+    }
+
+    /// The price `ticks` ticks of the symbol from the reference price, resolved client side from the latest market data the strategy has received, see `price_offset_ticks()`.
+    pub async fn tick_offset_price(&self, brokerage: Brokerage, symbol_name: &SymbolName, symbol_code: Option<SymbolCode>, side: OrderSide, reference: PriceReference, ticks: i64) -> Result<Price, OrderError> {
+        let info = self.cached_symbol_info(brokerage, symbol_name).await?;
+        let symbol_code = symbol_code.unwrap_or(symbol_name.clone());
+        let book_side = match (reference, side) {
+            (PriceReference::BestBid, _) | (PriceReference::SameSide, OrderSide::Buy) => Some(OrderSide::Sell),
+            (PriceReference::BestAsk, _) | (PriceReference::SameSide, OrderSide::Sell) => Some(OrderSide::Buy),
+            (PriceReference::Last, _) => None,
+        };
+        let base_price = match book_side {
+            Some(book_side) => self.market_price_service.get_market_price(book_side, symbol_name, &symbol_code),
+            None => self.market_price_service.get_last_price(symbol_name),
+        };
+        match base_price {
+            Some(base_price) => Ok(price_offset_ticks(&info, base_price, ticks)),
+            None => Err(OrderError::NoMarketPrice(symbol_name.clone())),
+        }
+    }
+
+    /// A limit order priced `ticks` ticks from the reference price when it is sent, so the offset follows the tick size of the symbol.
+    /// The price is resolved client side before the order is sent and the order carries the fixed price, it does not follow the reference afterwards.
+    /// ```rust
+    /// // join the bid 2 ticks back, 0.50 on MNQ and 0.00002 on EUR-USD
+    /// strategy.limit_order_ticks(&symbol_name, None, &account, None, dec!(1), OrderSide::Buy, PriceReference::SameSide, -2, TimeInForce::GTC, "Entry".to_string()).await?;
+    /// ```
+    pub async fn limit_order_ticks(
+        &self,
+        symbol_name: &SymbolName,
+        symbol_code: Option<SymbolCode>,
+        account: &Account,
+        exchange: Option<String>,
+        quantity: Volume,
+        side: OrderSide,
+        reference: PriceReference,
+        ticks: i64,
+        tif: TimeInForce,
+        tag: String,
+    ) -> Result<OrderId, OrderError> {
+        let limit_price = self.tick_offset_price(account.brokerage, symbol_name, symbol_code.clone(), side, reference, ticks).await?;
+        self.limit_order(symbol_name, symbol_code, account, exchange, quantity, side, limit_price, tif, tag).await
+    }
+
+    /// A limit order which only shows `display_quantity` to the market at a time, useful for working larger size on thin contracts.
+    /// In backtests at most 1 display clip is filled each time the order is matched, the remaining quantity is then shown as the next clip.
+    /// The brokerage must support display quantity in live trading or the order will be rejected.
+    pub async fn iceberg_order(
+        &self,
+        symbol_name: &SymbolName,
+        symbol_code: Option<SymbolCode>,
+        account: &Account,
+        exchange: Option<String>,
+        quantity: Volume,
+        display_quantity: Volume,
+        side: OrderSide,
+        limit_price: Price,
+        tif: TimeInForce,
+        tag: String,
+    ) -> Result<OrderId, OrderError> {
+        let order_id = self.order_id().await;
+        let order = Order::iceberg_order(symbol_name.clone(), symbol_code, account, quantity, display_quantity, side, tag, order_id.clone(), self.time_utc(), limit_price, tif, exchange);
+        self.send_order(order, OrderType::Limit).await
+    }
+
+    /// Will trigger if trigger price is hit and buy or sell at market price.
+    pub async fn market_if_touched (
         &self,
         symbol_name: &SymbolName,
         symbol_code: Option<SymbolCode>,
@@ -620,21 +1568,10 @@ impl FundForgeStrategy {
         trigger_price: Price,
         tif: TimeInForce,
         tag: String,
-    ) -> OrderId {
+    ) -> Result<OrderId, OrderError> {
         let order_id = self.order_id().await;
         let order = Order::market_if_touched(symbol_name.clone(), symbol_code, account, quantity, side, tag, order_id.clone(), self.time_utc(),trigger_price, tif, exchange);
-        let order_request = OrderRequest::Create{ account: account.clone(), order: order.clone(), order_type: OrderType::MarketIfTouched};
-        if self.mode == StrategyMode::Live {
-            self.open_order_cache.insert(order_id.clone(), order.clone());
-            let connection_type = ConnectionType::Broker(order_request.brokerage());
-            let request = StrategyRequest::OneWay(connection_type, DataServerRequest::OrderRequest { request: order_request });
-            send_request(request).await;
-        } else {
-            if let Some(historical_message_sender) = &self.historical_message_sender {
-                historical_message_sender.send(BackTestEngineMessage::OrderRequest(order_request)).await.unwrap();
-            }
-        }
-        order_id
+        self.send_order(order, OrderType::MarketIfTouched).await
     }
 
     /// Will buy or sell market price if trigger is hit
@@ -649,21 +1586,29 @@ impl FundForgeStrategy {
         trigger_price: Price,
         tif: TimeInForce,
         tag: String,
-    ) -> OrderId {
+    ) -> Result<OrderId, OrderError> {
         let order_id = self.order_id().await;
         let order = Order::stop(symbol_name.clone(), symbol_code, account, quantity, side, tag, order_id.clone(), self.time_utc(),trigger_price, tif, exchange);
-        let order_request = OrderRequest::Create{ account: account.clone(), order: order.clone(), order_type: OrderType::StopMarket};
-        if self.mode == StrategyMode::Live {
-            self.open_order_cache.insert(order_id.clone(), order.clone());
-            let connection_type = ConnectionType::Broker(order_request.brokerage());
-            let request = StrategyRequest::OneWay(connection_type, DataServerRequest::OrderRequest { request: order_request });
-            send_request(request).await;
-        } else {
-            if let Some(historical_message_sender) = &self.historical_message_sender {
-                historical_message_sender.send(BackTestEngineMessage::OrderRequest(order_request)).await.unwrap();
-            }
-        }
-        order_id
+        self.send_order(order, OrderType::StopMarket).await
+    }
+
+    /// A stop order triggered `ticks` ticks from the reference price when it is sent, eg a sell stop 8 ticks under the last price.
+    /// The trigger price is resolved client side before the order is sent, like `limit_order_ticks()`.
+    pub async fn stop_order_ticks(
+        &self,
+        symbol_name: &SymbolName,
+        symbol_code: Option<SymbolCode>,
+        account: &Account,
+        exchange: Option<String>,
+        quantity: Volume,
+        side: OrderSide,
+        reference: PriceReference,
+        ticks: i64,
+        tif: TimeInForce,
+        tag: String,
+    ) -> Result<OrderId, OrderError> {
+        let trigger_price = self.tick_offset_price(account.brokerage, symbol_name, symbol_code.clone(), side, reference, ticks).await?;
+        self.stop_order(symbol_name, symbol_code, account, exchange, quantity, side, trigger_price, tif, tag).await
     }
 
     /// Will trigger on trigger price but fill only when price is on the correct side of limit price, will partially fill in backtest if we have order book data present.
@@ -679,25 +1624,34 @@ impl FundForgeStrategy {
         limit_price: Price,
         trigger_price: Price,
         tif: TimeInForce
-    ) -> OrderId {
+    ) -> Result<OrderId, OrderError> {
         let order_id = self.order_id().await;
         let order = Order::stop_limit(symbol_name.clone(), symbol_code, account, quantity, side, tag, order_id.clone(), self.time_utc(),limit_price, trigger_price, tif, exchange);
-        let order_request = OrderRequest::Create{ account: account.clone(), order: order.clone(), order_type: OrderType::StopLimit};
-        if self.mode == StrategyMode::Live {
-            self.open_order_cache.insert(order_id.clone(), order.clone());
-            let connection_type = ConnectionType::Broker(order_request.brokerage());
-            let request = StrategyRequest::OneWay(connection_type, DataServerRequest::OrderRequest { request: order_request });
-            send_request(request).await;
-        } else {
-            if let Some(historical_message_sender) = &self.historical_message_sender {
-                historical_message_sender.send(BackTestEngineMessage::OrderRequest(order_request)).await.unwrap();
-            }
-        }
-        order_id
+        self.send_order(order, OrderType::StopLimit).await
     }
 
     /// Cancels the order if it is not filled, cancelled or rejected.
     pub async fn cancel_order(&self, order_id: OrderId) {
+        // a queued or delayed order was never sent, so it is cancelled here
+        let held = match self.closed_market_queue.remove(&order_id) {
+            Some(order) => Some((order, "Cancelled While Queued")),
+            None => self.spread_guard_handler.remove(&order_id).map(|order| (order, "Cancelled While Delayed")),
+        };
+        if let Some((order, reason)) = held {
+            let event = OrderUpdateEvent::OrderCancelled {
+                account: order.account,
+                symbol_name: order.symbol_name,
+                symbol_code: order.symbol_code,
+                order_id,
+                reason: reason.to_string(),
+                tag: order.tag,
+                time: self.time_utc().to_string(),
+            };
+            if let Err(e) = self.strategy_event_sender.send(StrategyEvent::OrderEvents(event)).await {
+                eprintln!("Failed to send cancel event: {}", e);
+            }
+            return;
+        }
         // Clone the necessary data from the Ref
         // need a market handler callback fn for this
         let account = if let Some(id_order_ref) = self.open_order_cache.get(&order_id) {
@@ -723,14 +1677,31 @@ impl FundForgeStrategy {
     }
 
     /// Updates the order if it is not filled, cancelled or rejected.
+    /// In reduce only mode a quantity update which would make the order open, increase or reverse a position is rejected with `OrderUpdateEvent::OrderUpdateRejected`.
     pub async fn update_order(&self, order_id: OrderId, order_update_type: OrderUpdateType) {
         // Clone the necessary data from the Ref
         //todo need a market handler update for this
-        let account = if let Some(id_order_ref) = self.open_order_cache.get(&order_id) {
-            id_order_ref.account.clone()
+        let order = if let Some(id_order_ref) = self.open_order_cache.get(&order_id) {
+            id_order_ref.value().clone()
         } else {
             return; // Order not found, exit the function
         };
+        let account = order.account.clone();
+        if let OrderUpdateType::Quantity(quantity) = &order_update_type {
+            let updated = Order { quantity_open: *quantity, ..order.clone() };
+            if self.settings.reduce_only.is_reduce_only() && *quantity > order.quantity_open && self.order_gate.is_entry_order(&updated, &updated.order_type) {
+                let event = OrderUpdateEvent::OrderUpdateRejected {
+                    account,
+                    order_id,
+                    reason: OrderError::ReduceOnly(order.symbol_name).to_string(),
+                    time: self.time_utc().to_string(),
+                };
+                if let Err(e) = self.strategy_event_sender.send(StrategyEvent::OrderEvents(event)).await {
+                    eprintln!("Failed to send update rejected event: {}", e);
+                }
+                return;
+            }
+        }
 
         let order_request = OrderRequest::Update {
             order_id,
@@ -792,6 +1763,90 @@ impl FundForgeStrategy {
         }
     }
 
+    /// Cancels the pending orders of all the strategy accounts and waits for the broker to acknowledge the cancels.
+    /// A cancel which has not been acknowledged after 2 seconds is sent again, eg when a request was lost during a reconnect.
+    /// Returns the orders which are still working after the `timeout`, these are also printed so they can be managed at the broker.
+    /// In backtests the cancels are processed by the engine at the current backtest time, so this returns without waiting.
+    /// ```rust
+    /// let left_alive = strategy.settle_orders(Duration::from_secs(10)).await;
+    /// if !left_alive.is_empty() {
+    ///     // alert someone, these orders can still fill after we exit
+    /// }
+    /// ```
+    pub async fn settle_orders(&self, timeout: Duration) -> Vec<Order> {
+        for account in &self.accounts {
+            self.cancel_orders_account(account.clone()).await;
+        }
+        if self.mode == StrategyMode::Backtest {
+            return vec![];
+        }
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut next_retry = tokio::time::Instant::now() + CANCEL_RETRY_INTERVAL;
+        loop {
+            let working: Vec<OrderId> = self.open_order_cache.iter()
+                .filter(|order| self.accounts.contains(&order.account))
+                .map(|order| order.id.clone())
+                .collect();
+            if working.is_empty() {
+                return vec![];
+            }
+            let now = tokio::time::Instant::now();
+            if now >= deadline {
+                break;
+            }
+            if now >= next_retry {
+                for order_id in working {
+                    self.cancel_order(order_id).await;
+                }
+                next_retry = now + CANCEL_RETRY_INTERVAL;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        let left_alive: Vec<Order> = self.open_order_cache.iter()
+            .filter(|order| self.accounts.contains(&order.account))
+            .map(|order| order.value().clone())
+            .collect();
+        report_open_orders(&left_alive, "Order Settlement");
+        left_alive
+    }
+
+    /// Settles the pending orders with `settle_orders()` and flattens all the strategy accounts, then waits until there are no pending orders and the accounts are flat.
+    /// Returns false if orders are still pending or positions are still open after the `timeout`, eg because of a rejected order or a lost connection.
+    /// Use this when handling `StrategyEvent::ShutdownEvent` or `StrategyEvent::SessionClose`, so the strategy does not exit while orders are in flight.
+    /// In backtests the requests are processed by the engine at the current backtest time, so this returns without waiting.
+    /// ```rust
+    /// StrategyEvent::ShutdownEvent(event) => {
+    ///     if !strategy.cancel_and_flatten_all(Duration::from_secs(10)).await {
+    ///         eprintln!("Orders or positions still open at shutdown");
+    ///     }
+    ///     strategy.export_trades_to_csv(&account, "./trades").await;
+    ///     break 'strategy_loop
+    /// }
+    /// ```
+    pub async fn cancel_and_flatten_all(&self, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        // the flattening orders are sent after the cancels are settled, so they are not cancelled by a retry
+        self.settle_orders(timeout).await;
+        for account in &self.accounts {
+            self.flatten_all_for(account.clone()).await;
+        }
+        if self.mode == StrategyMode::Backtest {
+            return true;
+        }
+        loop {
+            let accounts_flat = self.accounts.iter().all(|account| self.ledger_service.is_account_flat(account));
+            if accounts_flat && self.open_order_cache.is_empty() {
+                return true;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                let open_orders: Vec<Order> = self.open_order_cache.iter().map(|order| order.value().clone()).collect();
+                report_open_orders(&open_orders, "Cancel And Flatten");
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
     /// get_requests the last price for the symbol name
     pub async fn last_price(&self, _symbol_name: &SymbolName) -> Option<Price> {
         todo!("send callback to price service")
@@ -817,6 +1872,181 @@ impl FundForgeStrategy {
         self.timed_event_handler.remove_event(name).await;
     }
 
+    /// Sends `StrategyEvent::TimedEvent(tag)` every `duration` of strategy time, starting `duration` from now, a timer with the same tag is replaced.
+    /// In backtests the interval is engine time, so "every 5 minutes" is every 5 minutes of replayed data however fast the backtest runs.
+    /// The timer does not fire during the warm up and is stopped with `strategy.remove_timed_event(tag)`.
+    /// ```rust
+    /// strategy.every(Duration::minutes(5), "Re-evaluate".to_string()).await;
+    ///
+    /// // in the event loop
+    /// StrategyEvent::TimedEvent(tag) if tag == "Re-evaluate" => {}
+    /// ```
+    pub async fn every(&self, duration: ChronoDuration, tag: String) {
+        self.timed_event_handler.remove_event(tag.clone()).await;
+        let time = EventTimeEnum::Every { duration, next_time: self.time_utc() + duration, fire_in_warmup: false };
+        self.timed_event_handler.add_event(TimedEvent::new(tag, time)).await;
+    }
+
+    /// Completes once the strategy time reaches `time`, the engine time in backtest and warm up, the clock live, it completes at once if the time has passed.
+    /// The time is checked on each time update, each buffer of replayed data in backtest and each second live, so use it instead of tokio timers which follow the wall clock.
+    /// The future does not borrow the strategy, spawn it in a task, the backtest engine waits for the event loop, so awaiting it in the event loop stops the backtest.
+    /// ```rust
+    /// let wake_up = strategy.sleep_until(strategy.time_utc() + Duration::minutes(30));
+    /// tokio::spawn(async move {
+    ///     wake_up.await;
+    ///     // 30 minutes of strategy time later
+    /// });
+    /// ```
+    pub fn sleep_until(&self, time: DateTime<Utc>) -> impl Future<Output = ()> + Send + 'static {
+        let receiver = match time <= self.time_utc() {
+            true => None,
+            false => Some(self.timed_event_handler.wake_at(time)),
+        };
+        async move {
+            if let Some(receiver) = receiver {
+                // the sender is dropped if the strategy stops
+                let _ = receiver.await;
+            }
+        }
+    }
+
+    /// Sends `StrategyEvent::SessionClose(name)` once per session, on the first time update within `before` of the close of the `trading_hours`.
+    /// Works the same in backtest and live, the event is sent before any data at or after the close.
+    /// If `fire_in_warmup` is false, closes during the warm up are skipped. Adding a hook with an existing name replaces the old hook.
+    /// ```rust
+    /// strategy.on_session_close("Flatten".to_string(), CME_HOURS, Duration::minutes(5), false).await;
+    ///
+    /// // in the event loop
+    /// StrategyEvent::SessionClose(name) => {
+    ///     strategy.cancel_and_flatten_all(std::time::Duration::from_secs(10)).await;
+    /// }
+    /// ```
+    pub async fn on_session_close(&self, name: String, trading_hours: TradingHours, before: ChronoDuration, fire_in_warmup: bool) {
+        let hook = SessionHook::new(name, SessionHookTime::SessionClose { trading_hours, before }, fire_in_warmup);
+        self.timed_event_handler.add_session_hook(hook).await;
+    }
+
+    /// Sends `StrategyEvent::DailyRollover(name)` each day at the `time` in the strategy time zone, eg to export the days trades or reset daily loss limits.
+    /// A rollover is only sent once the strategy time has passed it, a hook added after today's rollover time first fires tomorrow.
+    /// If `fire_in_warmup` is false, rollovers during the warm up are skipped. Adding a hook with an existing name replaces the old hook.
+    /// ```rust
+    /// strategy.on_daily_rollover("Daily".to_string(), NaiveTime::from_hms_opt(17, 0, 0).unwrap(), false).await;
+    /// ```
+    pub async fn on_daily_rollover(&self, name: String, time: NaiveTime, fire_in_warmup: bool) {
+        let hook = SessionHook::new(name, SessionHookTime::DailyRollover { time_zone: self.time_zone, time }, fire_in_warmup);
+        self.timed_event_handler.add_session_hook(hook).await;
+    }
+
+    /// Removes a hook added with `on_session_close()` or `on_daily_rollover()`.
+    pub async fn remove_session_hook(&self, name: &str) {
+        self.timed_event_handler.remove_session_hook(name).await;
+    }
+
+    /// Adds a rule which makes the engine flat by the `time` of day in the `time_zone`, in backtest and live, a rule with the same name is replaced.
+    /// When the rule is due the working orders in its scope are cancelled, the open positions are exited at market and `StrategyEvent::FlatBy(name)` is sent.
+    /// The scope is the `account`, or all the strategy accounts if None, and the `symbol_name`, or every symbol if None.
+    /// A rule fires once a day, a rule added after today's time first fires tomorrow, and it does not stop new entries after the flat time.
+    /// ```rust
+    /// // flat by 15:55 exchange time on every account
+    /// strategy.flat_by(FlatByRule::new("Close".to_string(), New_York, NaiveTime::from_hms_opt(15, 55, 0).unwrap(), None, None));
+    ///
+    /// // flat on MNQ only, on one account
+    /// strategy.flat_by(FlatByRule::new("MNQ Close".to_string(), Chicago, NaiveTime::from_hms_opt(15, 50, 0).unwrap(), Some(account.clone()), Some("MNQ".to_string())));
+    /// ```
+    pub fn flat_by(&self, rule: FlatByRule) {
+        self.flat_by_handler.add_rule(rule);
+    }
+
+    /// Removes a rule added with `flat_by()`.
+    pub fn remove_flat_by(&self, name: &str) {
+        self.flat_by_handler.remove_rule(name);
+    }
+
+    /// Adds a rule which makes the engine cancel working orders without a fill once they are older than the max age, in backtest and live, a rule with the same name is replaced.
+    /// The scope is the orders with the `tag`, or any tag if None, and the `order_type`, or any type if None. Partially filled orders are not cancelled.
+    /// Each cancelled order sends `StrategyEvent::StaleOrderCancelled(order_id, rule_name)` before its `OrderCancelled` event.
+    /// ```rust
+    /// // entry limits which have rested for 30 minutes are forgotten
+    /// strategy.sweep_stale_orders(StaleOrderRule::new("Entries".to_string(), chrono::Duration::minutes(30), Some("Entry".to_string()), Some(OrderType::Limit)));
+    ///
+    /// // no order of any kind rests for more than a day
+    /// strategy.sweep_stale_orders(StaleOrderRule::new("All".to_string(), chrono::Duration::days(1), None, None));
+    /// ```
+    pub fn sweep_stale_orders(&self, rule: StaleOrderRule) {
+        self.stale_order_handler.add_rule(rule);
+    }
+
+    /// Removes a rule added with `sweep_stale_orders()`.
+    pub fn remove_stale_order_rule(&self, name: &str) {
+        self.stale_order_handler.remove_rule(name);
+    }
+
+    /// Starts a watchdog which sends `StrategyEvent::HealthWarning` when the runtime lags, the strategy events back up or stall, memory grows or any task panics, a running watchdog is replaced.
+    /// With `flatten_on_stall` the working orders are cancelled and the strategy accounts flattened when the event loop stalls. Live and live paper only.
+    /// ```rust
+    /// strategy.start_watchdog(WatchdogSettings { flatten_on_stall: true, ..Default::default() });
+    /// ```
+    pub fn start_watchdog(&self, settings: WatchdogSettings) {
+        if self.mode == StrategyMode::Backtest {
+            eprintln!("Watchdog: The watchdog is only used by live strategies");
+            return;
+        }
+        start_watchdog(settings, self.strategy_event_sender.clone(), self.flat_by_handler.clone(), self.settings.clone());
+    }
+
+    /// Stops the watchdog started with `start_watchdog()`.
+    pub fn stop_watchdog(&self) {
+        stop_watchdog();
+    }
+
+    /// Compiles a report of the previous 24 hours at the `time` of day in the `time_zone`, replacing the current schedule.
+    /// The report has the fills, the pnl booked per symbol, the max drawdown of the booked pnl and the flat by and watchdog events of each strategy account.
+    /// It is written to the directory as text and html and sent as `StrategyEvent::DailyReport(text)`, forward the event to deliver it, eg by email.
+    /// ```rust
+    /// strategy.schedule_daily_report(DailyReportSettings::new(New_York, NaiveTime::from_hms_opt(17, 0, 0).unwrap(), "./daily reports".to_string()));
+    /// ```
+    pub fn schedule_daily_report(&self, settings: DailyReportSettings) {
+        self.daily_report_handler.set_schedule(Some(settings));
+    }
+
+    /// Stops the reports scheduled with `schedule_daily_report()`.
+    pub fn stop_daily_report(&self) {
+        self.daily_report_handler.set_schedule(None);
+    }
+
+    /// Sets what the engine does when a subscribed futures contract, eg "MNQH24", stops being the front month, in backtest and live.
+    /// Each expired subscription sends `DataSubscriptionEvent::Expired(subscription, next)`, the default `ContractExpiryPolicy::Notify` only sends the event.
+    /// `ContractExpiryPolicy::MapToNextContract` unsubscribes the contract and its indicators and subscribes to the next contract, open positions are not rolled.
+    /// ```rust
+    /// strategy.set_contract_expiry_policy(ContractExpiryPolicy::MapToNextContract { history_to_retain: 100, fill_forward: false });
+    /// ```
+    pub fn set_contract_expiry_policy(&self, policy: ContractExpiryPolicy) {
+        self.contract_expiry_handler.set_policy(policy);
+    }
+
+    /// Adds a price trigger which sends a `StrategyEvent::PriceTrigger(tag)` the first time the symbols primary data touches the `level`, the trigger is then removed.
+    /// Works the same in backtest and live, so we don't need to scan every tick in the strategy loop to implement market if touched logic or drawn level alerts.
+    /// Adding a trigger with an existing tag replaces the old trigger.
+    /// see the price_trigger_handler.rs for more details
+    /// ```rust
+    /// strategy.when_price(&symbol_name, PriceTriggerCondition::AtOrAbove, dec!(18000), "Breakout".to_string());
+    ///
+    /// // in the event loop
+    /// StrategyEvent::PriceTrigger(tag) => {
+    ///     if tag == "Breakout" {
+    ///         let _ = strategy.enter_long(&symbol_name, None, &account, None, dec!(1), tag).await;
+    ///     }
+    /// }
+    /// ```
+    pub fn when_price(&self, symbol_name: &SymbolName, condition: PriceTriggerCondition, level: Price, tag: String) {
+        self.price_trigger_handler.add_trigger(symbol_name.clone(), PriceTrigger::new(tag, condition, level));
+    }
+
+    /// Removes a price trigger before it fires.
+    pub fn remove_price_trigger(&self, tag: &str) {
+        self.price_trigger_handler.remove_trigger(tag);
+    }
+
     /// see the indicator_enum.rs for more details
     /// If we subscribe to an indicator and we do not have the appropriate data subscription, we will also subscribe to the data subscription.
     /// Using unwrap on historical index() data in live mode should still be safe when using the current data as reference for the new subscription,
@@ -845,6 +2075,41 @@ impl FundForgeStrategy {
         }
     }
 
+    /// Subscribes to the indicator and restores its internal state from a file created with `strategy.save_indicator_states()`, making live restarts near-instant.
+    /// The bars which closed since the state was saved are replayed from history.
+    /// The saved state is only used in live modes, if the indicator does not support cached state or the state is older than the max staleness, see `set_indicator_state_max_staleness()`,
+    /// the indicator is warmed up from history as normal.
+    /// # Arguments
+    /// * `indicator: Box<dyn Indicators>` - The indicator to subscribe to.
+    /// * `trading_hours: Option<TradingHours>` - The trading hours for the indicator, see `subscribe_indicator()`.
+    /// * `file_path: &str` - The file the indicator states were saved to.
+    pub async fn subscribe_indicator_from_state(&self, indicator: Box<dyn Indicators>, trading_hours: Option<TradingHours>, file_path: &str) {
+        match self.mode {
+            StrategyMode::Backtest => self.subscribe_indicator(indicator, trading_hours).await,
+            StrategyMode::Live | StrategyMode::LivePaperTrading => {
+                let name = indicator.name();
+                let states = load_indicator_states(file_path);
+                let state = states.iter().find(|state| state.name == name);
+                self.indicator_handler
+                    .add_indicator_from_state(indicator, Utc::now(), trading_hours, state)
+                    .await;
+            }
+        }
+    }
+
+    /// Sets how old a saved indicator state can be and still be restored by `subscribe_indicator_from_state()`, 3 days by default.
+    pub fn set_indicator_state_max_staleness(&self, max_staleness: ChronoDuration) {
+        self.indicator_handler.set_state_max_staleness(max_staleness)
+    }
+
+    /// Saves the internal state of all indicators which support cached state to the file, call this when shutting down a live strategy.
+    /// Use `strategy.subscribe_indicator_from_state()` on the next start to restore the indicators without warming up from history.
+    pub fn save_indicator_states(&self, file_path: &str) {
+        if let Err(e) = save_indicator_states(file_path, self.indicator_handler.save_states()) {
+            eprintln!("{}", e);
+        }
+    }
+
     /// see the indicator_enum.rs for more details
     pub async fn indicator_unsubscribe(&self, name: &IndicatorName) -> Option<IndicatorEvents> {
         self.indicator_handler.remove_indicator(name).await
@@ -881,7 +2146,7 @@ impl FundForgeStrategy {
     ///         println!("Balance is too high or too low, flattening all positions: {}", balance);
     ///         if strategy.is_long(&account, &symbol_code) {
     ///             let open_quantity = strategy.position_size(&account, &symbol_code);
-    ///             exit_order_id = Some(strategy.exit_long(&candle.symbol.name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Long Target Reached".to_string()).await);
+    ///             exit_order_id = strategy.exit_long(&candle.symbol.name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Long Target Reached".to_string()).await.ok();
     ///         }
     ///     break 'strategy_loop;
     ///    }
@@ -891,6 +2156,19 @@ impl FundForgeStrategy {
         self.ledger_service.balance(account)
     }
 
+    /// Returns the balance, buying power and margin used of the account, None if the account was not initialized with the strategy.
+    pub fn account_summary(&self, account: &Account) -> Option<AccountSummary> {
+        self.ledger_service.account_summary(account)
+    }
+
+    /// In live mode the account balances are requested from the broker every 30 seconds, so `balance()` stays in sync even when the broker does not push account updates.
+    /// Set a different interval or None to stop the sync, this does nothing in backtest and live paper mode.
+    pub fn set_account_sync_interval(&self, interval: Option<Duration>) {
+        if self.mode == StrategyMode::Live {
+            self.ledger_service.set_account_sync_interval(interval);
+        }
+    }
+
     /// see the indicator_enum.rs for more details
     pub fn indicator_history(
         &self,
@@ -899,6 +2177,35 @@ impl FundForgeStrategy {
         self.indicator_handler.history(name)
     }
 
+    /// Returns the indicator values with a time between `from` and `to` (inclusive), ordered from oldest to newest.
+    /// The values are keyed and filtered by their time in the bar time convention of the strategy, see `set_bar_time_convention()`.
+    /// Only values still held in the indicators history are returned, see `history_to_retain` when creating the indicator.
+    /// ```rust
+    /// let last_hour = strategy.indicator_history_range(&atr_name, strategy.time_utc() - Duration::hours(1), strategy.time_utc());
+    /// for (time, values) in last_hour {
+    ///     println!("{}: {:?}", time, values.get_plot(&"atr".to_string()));
+    /// }
+    /// ```
+    pub fn indicator_history_range(
+        &self,
+        name: &IndicatorName,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> BTreeMap<DateTime<Utc>, IndicatorValues> {
+        self.indicator_handler.history_range(name, from, to, self.bar_time_convention())
+    }
+
+    /// Returns the latest indicator values with a time at or before `time`, useful for looking up what an indicator read when a past event happened.
+    /// `time` is the time of the event, the values of a bar are only known once it closes whatever the bar time convention.
+    pub fn indicator_latest_at(&self, name: &IndicatorName, time: DateTime<Utc>) -> Option<IndicatorValues> {
+        self.indicator_handler.latest_at(name, time)
+    }
+
+    /// The mode the strategy was initialized in.
+    pub fn mode(&self) -> StrategyMode {
+        self.mode
+    }
+
     /// returns the strategy time zone.
     pub fn time_zone(&self) -> &Tz {
         &self.time_zone
@@ -952,11 +2259,32 @@ impl FundForgeStrategy {
         self.subscription_handler.strategy_subscriptions().await
     }
 
+    /// Sets a memory budget for the history retained by all subscriptions, pass None to remove the budget.
+    /// When the retained history is over budget, the retention of some subscriptions is reduced according to the `EvictionPolicy` of the budget.
+    /// The reduced retention is not restored when the budget is removed or increased.
+    ///
+    /// # Example
+    /// ```rust
+    /// strategy.set_history_budget(Some(HistoryBudget::megabytes(512, EvictionPolicy::LargestFirst)));
+    /// ```
+    pub fn set_history_budget(&self, budget: Option<HistoryBudget>) {
+        self.subscription_handler.set_history_budget(budget)
+    }
+
+    /// Returns the estimated memory used by the retained history of each subscription.
+    pub fn history_memory_usage(&self) -> Vec<HistoryMemoryUsage> {
+        self.subscription_handler.history_memory_usage()
+    }
+
     /// Subscribes to a new subscription, we can only subscribe to a subscription once.
     /// In live mode we will warm up the subscription as a background task, in backtest we will block the main thread.
     /// Using unwrap on historical index() data in live mode should still be safe when using the current data as reference for the new subscription,
     /// because we won't forward bars until the consolidator is warmed up.
+    /// If `trading_hours` are given, orders for the symbol are refused with `OrderError::MarketClosed` outside of the trading hours.
     pub async fn subscribe(&self, primary_source: Option<PrimarySubscription>, subscription: DataSubscription, history_to_retain: usize, fill_forward: bool, trading_hours: Option<TradingHours>) {
+        if let Some(hours) = &trading_hours {
+            self.trading_hours.insert(subscription.symbol.name.clone(), hours.clone());
+        }
         match self.mode {
             StrategyMode::Backtest => {
                 let _ = self.subscription_handler
@@ -985,6 +2313,120 @@ impl FundForgeStrategy {
                     .await;
     }
 
+    /// Pauses a subscription without unsubscribing from the vendor, its data is not delivered to the strategy or to its indicators until it is resumed.
+    /// If `stop_consolidating` is false the consolidator and history of the subscription stay current while paused, so it resumes without a gap,
+    /// if true they are not updated either, which is cheaper for feeds which are not needed at all, eg a tick feed outside of the trading session.
+    /// Returns false if the strategy is not subscribed to the subscription.
+    ///
+    /// # Example
+    /// ```rust
+    /// strategy.pause_subscription(&ticks, true).await;
+    /// // later
+    /// strategy.resume_subscription(&ticks);
+    /// ```
+    pub async fn pause_subscription(&self, subscription: &DataSubscription, stop_consolidating: bool) -> bool {
+        self.subscription_handler.pause_subscription(subscription, stop_consolidating).await
+    }
+
+    /// Resumes delivering the data of a paused subscription, returns false if it was not paused.
+    pub fn resume_subscription(&self, subscription: &DataSubscription) -> bool {
+        self.subscription_handler.resume_subscription(subscription)
+    }
+
+    /// Returns the subscriptions which are paused.
+    pub fn paused_subscriptions(&self) -> Vec<DataSubscription> {
+        self.subscription_handler.paused_subscriptions()
+    }
+
+    /// Returns the closed bars of the subscriptions aligned in time, in the order of the subscriptions, index 0 is the latest.
+    /// The clock is the subscription with the finest resolution, the bar of each other subscription is its latest bar closed at or before the clock bar, forward filled for slower subscriptions.
+    /// Returns None if the clock has no bar at the index, or a subscription has no retained bar closed by then.
+    ///
+    /// # Example
+    /// ```rust
+    /// if let Some(aligned) = strategy.aligned_bars(&[nq_15m.clone(), es_60m.clone()], 0) {
+    ///     // aligned.bars[1] is the last 60 minute ES bar closed at aligned.time, aligned.filled[1] is true if it closed earlier
+    /// }
+    /// ```
+    pub fn aligned_bars(&self, subscriptions: &[DataSubscription], index: usize) -> Option<AlignedBars> {
+        let clock = subscriptions.iter().enumerate().min_by_key(|(_, subscription)| subscription.resolution)?.0;
+        let series: Vec<Vec<BaseDataEnum>> = subscriptions.iter().map(|subscription| self.subscription_handler.history_data(subscription)).collect();
+        align_bars(&series, clock, index)
+    }
+
+    /// Records the bars of the exporter subscription which closed since the last call, with the indicator values at their close, see `FeatureExporter`.
+    /// Call it on each time slice, returns the number of rows recorded.
+    /// # Example
+    /// ```rust
+    /// let mut features = FeatureExporter::new(subscription.clone())
+    ///     .with_indicator_plot(atr_name.clone(), "atr".to_string())
+    ///     .with_forward_returns(vec![1, 5]);
+    /// // on each StrategyEvent::TimeSlice
+    /// strategy.record_features(&mut features);
+    /// // on StrategyEvent::ShutdownEvent
+    /// features.write_csv("./features/eur_usd_15m.csv")?;
+    /// ```
+    pub fn record_features(&self, exporter: &mut FeatureExporter) -> usize {
+        let last_time = exporter.last_time();
+        let mut closed_bars: Vec<BaseDataEnum> = self.subscription_handler.history_data(exporter.subscription()).into_iter()
+            .filter(|bar| last_time.map_or(true, |last_time| bar.time_closed_utc() > last_time))
+            .collect();
+        closed_bars.reverse();
+        let mut recorded = 0;
+        for bar in closed_bars {
+            let time = bar.time_closed_utc();
+            let indicators = exporter.indicator_plots().iter()
+                .map(|(name, plot_name)| self.indicator_latest_at(name, time).and_then(|values| values.get_plot(plot_name)).map(|plot| plot.value))
+                .collect();
+            if exporter.record(&bar, indicators) {
+                recorded += 1;
+            }
+        }
+        recorded
+    }
+
+    /// Loads the `{name}_v{version}.onnx` model file from the folder for `predict()`, the highest version if `version` is None, and returns the version loaded.
+    /// `input_shape` is the f32 input of the model, eg `[1, 8]` for 8 features or `buffer.shape()` for a sequence model.
+    /// Loading a model with the name of a loaded model replaces it. Requires the `onnx` feature.
+    #[cfg(feature = "onnx")]
+    pub fn load_model(&self, name: &str, folder: &str, version: Option<u32>, input_shape: Vec<usize>) -> Result<u32, FundForgeError> {
+        let model = OnnxModel::load(resolve_model_file(folder, name, version)?, input_shape)?;
+        let version = model.file.version;
+        self.models.insert(name.to_string(), Arc::new(model));
+        Ok(version)
+    }
+
+    /// Runs a model loaded with `load_model()` on the features and returns its first output, in backtest and live.
+    /// # Example
+    /// ```rust
+    /// strategy.load_model("trend", "./models", Some(3), vec![1, 3])?;
+    /// // on each closed candle
+    /// let prediction = strategy.predict("trend", &[rsi, atr_ratio, ema_slope])?;
+    /// if prediction[0] > 0.6 {
+    ///     let _ = strategy.enter_long(&candle.symbol.name, None, &account, None, dec!(1), "Model Long".to_string()).await;
+    /// }
+    /// ```
+    #[cfg(feature = "onnx")]
+    pub fn predict(&self, name: &str, features: &[f32]) -> Result<Vec<f32>, FundForgeError> {
+        let model = self.models.get(name).map(|model| model.value().clone())
+            .ok_or_else(|| FundForgeError::ClientSideErrorDebug(format!("Model {} is not loaded", name)))?;
+        model.run(features)
+    }
+
+    /// Runs a sequence model on the bars of the buffer, an error until the buffer is full.
+    #[cfg(feature = "onnx")]
+    pub fn predict_buffer(&self, name: &str, buffer: &FeatureBuffer) -> Result<Vec<f32>, FundForgeError> {
+        let features = buffer.flatten()
+            .ok_or_else(|| FundForgeError::ClientSideErrorDebug(format!("The feature buffer of model {} has {} of {} bars", name, buffer.len(), buffer.shape()[1])))?;
+        self.predict(name, &features)
+    }
+
+    /// The version of a loaded model.
+    #[cfg(feature = "onnx")]
+    pub fn model_version(&self, name: &str) -> Option<u32> {
+        self.models.get(name).map(|model| model.file.version)
+    }
+
     /// Returns currently open `QuoteBar` for the subscription
     pub fn open_bar(&self, subscription: &DataSubscription) -> Option<QuoteBar> {
         self.subscription_handler.open_bar(subscription)
@@ -1015,6 +2457,11 @@ impl FundForgeStrategy {
         self.subscription_handler.quote_index(subscription, index)
     }
 
+    /// Returns `Depth` at the specified index, where 0 is the current book and 1 is the book before the last update.
+    pub fn depth_index(&self, subscription: &DataSubscription, index: usize) -> Option<Depth> {
+        self.subscription_handler.depth_index(subscription, index)
+    }
+
     /// Current Tz time, depends on the `StrategyMode`. \
     /// Backtest will return the last data point time, live will return the current time.
     pub fn time_local(&self) -> DateTime<Tz> {
@@ -1028,18 +2475,13 @@ impl FundForgeStrategy {
 
     /// Current Utc time, depends on the `StrategyMode`. \
     /// Backtest will return the last data point time, live will return the current time.
+    /// Tests can control the time by injecting a clock with `historical_time::set_clock()`.
     pub fn time_utc(&self) -> DateTime<Utc> {
-        match is_warmup_complete() {
-            true => match self.mode {
-                StrategyMode::Backtest => get_backtest_time(),
-                _ => Utc::now(),
-            },
-            false => get_backtest_time(),
-        }
+        self.warm_up.strategy_time(self.mode)
     }
 
-    /// Returns a BTreeMap of BaseDataEnum where data.time_closed_utc() is key and data is value.
-    /// From the time, to the current strategy time
+    /// Returns a BTreeMap of BaseDataEnum where the bar time in the strategy convention is key and data is value, data.time_closed_utc() by default.
+    /// From the time, to the current strategy time, `from_time` is read in the bar time convention, see `set_bar_time_convention()`.
     pub async fn history_from_local_time(
         &self,
         from_time: NaiveDateTime,
@@ -1048,11 +2490,11 @@ impl FundForgeStrategy {
         trading_hours: Option<TradingHours>,
     ) -> BTreeMap<DateTime<Utc>, BaseDataEnum> {
         let start_date = naive_date_time_to_tz(from_time, time_zone);
-        range_history_data(start_date.to_utc(), self.time_utc(), subscription.clone(), self.mode, trading_hours).await
+        self.history_in_bar_time(start_date.to_utc(), self.time_utc(), subscription, trading_hours).await
     }
 
-    /// Returns a BTreeMap of BaseDataEnum where data.time_closed_utc() is key and data is value.
-    /// From the time, to the current strategy time
+    /// Returns a BTreeMap of BaseDataEnum where the bar time in the strategy convention is key and data is value, data.time_closed_utc() by default.
+    /// From the time, to the current strategy time, `from_time` is read in the bar time convention, see `set_bar_time_convention()`.
     pub async fn history_from_utc_time(
         &self,
         from_time: NaiveDateTime,
@@ -1060,11 +2502,12 @@ impl FundForgeStrategy {
         trading_hours: Option<TradingHours>,
     ) -> BTreeMap<DateTime<Utc>, BaseDataEnum> {
         let start_date = naive_date_time_to_utc(from_time);
-        range_history_data(start_date.to_utc(), self.time_utc(), subscription.clone(), self.mode, trading_hours).await
+        self.history_in_bar_time(start_date.to_utc(), self.time_utc(), subscription, trading_hours).await
     }
 
-    /// Returns a BTreeMap of BaseDataEnum where data.time_closed_utc() is key and data is value.
-    /// If to time > strategy.time then to time will be changed to strategy.time to avoid lookahead bias
+    /// Returns a BTreeMap of BaseDataEnum where the bar time in the strategy convention is key and data is value, data.time_closed_utc() by default.
+    /// The times are read in the bar time convention, see `set_bar_time_convention()`.
+    /// If the bars would close after the strategy time the range ends at the strategy time to avoid lookahead bias
     pub async fn historical_range_from_local_time(
         &self,
         from_time: NaiveDateTime,
@@ -1075,16 +2518,11 @@ impl FundForgeStrategy {
     ) -> BTreeMap<DateTime<Utc>, BaseDataEnum> {
         let start_date = naive_date_time_to_tz(from_time, time_zone);
         let end_date =  naive_date_time_to_tz(to_time, time_zone).to_utc();
-
-        let end_date = match end_date > self.time_utc() {
-            true => self.time_utc(),
-            false => end_date.to_utc(),
-        };
-
-        range_history_data(start_date.to_utc(), end_date, subscription.clone(), self.mode, trading_hours).await
+        self.history_in_bar_time(start_date.to_utc(), end_date, subscription, trading_hours).await
     }
 
     /// Currently returns only primary data that is available, needs to be updated to be able to return all subscriptions via consolidated data
+    /// The times are read in the bar time convention, see `set_bar_time_convention()`.
     pub async fn historical_range_from_utc_time(
         &self,
         from_time: NaiveDateTime,
@@ -1094,41 +2532,375 @@ impl FundForgeStrategy {
     ) -> BTreeMap<DateTime<Utc>, BaseDataEnum> {
         let start_date = DateTime::<Utc>::from_naive_utc_and_offset(from_time, Utc);
         let end_date = DateTime::<Utc>::from_naive_utc_and_offset(to_time, Utc);
+        self.history_in_bar_time(start_date, end_date, subscription, trading_hours).await
+    }
+
+    /// The history is stored by close time, the range is converted to close times and the result is keyed in the bar time convention.
+    async fn history_in_bar_time(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        subscription: &DataSubscription,
+        trading_hours: Option<TradingHours>,
+    ) -> BTreeMap<DateTime<Utc>, BaseDataEnum> {
+        let convention = self.bar_time_convention();
+        let from = convention.to_close_time(from, &subscription.resolution);
+        let to = convention.to_close_time(to, &subscription.resolution).min(self.time_utc());
+        let history = range_history_data(from, to, subscription.clone(), self.mode, trading_hours).await;
+        match convention {
+            BarTimeConvention::CloseTime => history,
+            BarTimeConvention::OpenTime => history.into_values()
+                .map(|data| (convention.time_of(&data), data))
+                .collect(),
+        }
+    }
 
-        let end_date = match end_date > self.time_utc() {
-            true => self.time_utc(),
-            false => end_date,
+    /// Scans the historical store of the data server for the symbols which meet every condition at the strategy time, eg to pick the symbols to trade each morning.
+    /// The scan uses the stored `base_data_type` data of the `resolution`, ticks and quotes are stored as `Resolution::Instant`.
+    /// Symbols without enough data for a condition do not match, `values` of each match are the measured values in the order of the conditions.
+    /// ```rust
+    /// let symbols: Vec<Symbol> = ["EUR-USD", "AUD-USD", "GBP-USD"].iter()
+    ///     .map(|name| Symbol::new(name.to_string(), DataVendor::Oanda, MarketType::Forex))
+    ///     .collect();
+    /// let conditions = vec![
+    ///     ScanCondition::from_str("atr(14,5-M) > 0.0005").unwrap(),
+    ///     ScanCondition::from_str("day_range > 0.005").unwrap(),
+    /// ];
+    /// for scan_match in strategy.scan(symbols, BaseDataType::Quotes, Resolution::Instant, conditions).await.unwrap_or_default() {
+    ///     println!("{}", scan_match);
+    /// }
+    /// ```
+    pub async fn scan(
+        &self,
+        symbols: Vec<Symbol>,
+        base_data_type: BaseDataType,
+        resolution: Resolution,
+        conditions: Vec<ScanCondition>,
+    ) -> Result<Vec<ScanMatch>, FundForgeError> {
+        scan_symbols(symbols, base_data_type, resolution, conditions, self.time_utc()).await
+    }
+
+    /// Registers the vendor symbol aliases of the data server in the strategy and returns them, call it before subscribing with vendor names the server has aliases for.
+    pub async fn load_symbol_aliases(&self) -> Result<Vec<SymbolAlias>, FundForgeError> {
+        load_symbol_aliases().await
+    }
+
+    /// Registers a vendor name for a canonical symbol name in the strategy, see `ff_standard_lib::standardized_types::symbol_aliases`.
+    /// ```rust
+    /// strategy.register_symbol_alias(SymbolAlias { data_vendor: DataVendor::DataBento, vendor_symbol: "EURUSD.FX".to_string(), canonical: "EUR-USD".to_string() });
+    /// // subscribes to EUR-USD
+    /// let subscription = DataSubscription::new("EURUSD.FX".to_string(), DataVendor::DataBento, Resolution::Minutes(1), BaseDataType::Candles, MarketType::Forex);
+    /// ```
+    pub fn register_symbol_alias(&self, alias: SymbolAlias) {
+        register_symbol_alias(alias)
+    }
+
+    /// The vendor symbol aliases registered in the strategy.
+    pub fn symbol_aliases(&self) -> Vec<SymbolAlias> {
+        symbol_aliases()
+    }
+
+    /// The canonical symbol name of a name used by the vendor, eg `EUR-USD` for the Oanda name `EUR_USD`.
+    pub fn canonical_symbol_name(&self, data_vendor: &DataVendor, vendor_symbol: &str) -> SymbolName {
+        canonical_symbol_name(data_vendor, vendor_symbol)
+    }
+
+    /// The name the vendor uses for a canonical symbol name, eg `EUR_USD` at Oanda for `EUR-USD`.
+    pub fn vendor_symbol_name(&self, data_vendor: &DataVendor, symbol_name: &SymbolName) -> String {
+        vendor_symbol_name(data_vendor, symbol_name)
+    }
+
+    /// The levels, zones and trend lines saved on the data server for the symbol, shared with every strategy and tool using the server.
+    /// ```rust
+    /// let time = strategy.time_utc();
+    /// for level in strategy.levels(&"MNQ".to_string()).await.unwrap_or_default() {
+    ///     if let Some(price) = level.price_at(time) {
+    ///         println!("{}: {}", level.label, price);
+    ///     }
+    /// }
+    /// ```
+    pub async fn levels(&self, symbol_name: &SymbolName) -> Result<Vec<Level>, FundForgeError> {
+        get_levels(symbol_name.clone()).await
+    }
+
+    /// Saves a level on the data server, replacing the level with the same id, and returns the levels of the symbol.
+    /// ```rust
+    /// strategy.save_level(Level::horizontal("MNQ".to_string(), dec!(18250.25), "Prior day high")).await?;
+    /// ```
+    pub async fn save_level(&self, level: Level) -> Result<Vec<Level>, FundForgeError> {
+        save_level(level).await
+    }
+
+    /// Removes a level from the data server and returns the levels left for the symbol.
+    pub async fn remove_level(&self, symbol_name: &SymbolName, id: &str) -> Result<Vec<Level>, FundForgeError> {
+        remove_level(symbol_name.clone(), id.to_string()).await
+    }
+
+    /// The resting and triggered intents saved on the data server for the symbol, see `execute_intents()`.
+    pub async fn intents(&self, symbol_name: &SymbolName) -> Result<Vec<Intent>, FundForgeError> {
+        get_intents(symbol_name.clone()).await
+    }
+
+    /// Saves an intent on the data server, replacing the intent with the same id, and returns the intents of the symbol.
+    /// ```rust
+    /// let intent = Intent::new("MNQ".to_string(), account.clone(), OrderSide::Buy, dec!(2), dec!(18180.25), 8, "Prior day low").with_stop_loss(40);
+    /// strategy.save_intent(intent).await?;
+    /// ```
+    pub async fn save_intent(&self, intent: Intent) -> Result<Vec<Intent>, FundForgeError> {
+        save_intent(intent).await
+    }
+
+    /// Removes an intent from the data server and returns the intents left for the symbol, a removed intent stops triggering on the next refresh of the executors.
+    pub async fn remove_intent(&self, symbol_name: &SymbolName, id: &str) -> Result<Vec<Intent>, FundForgeError> {
+        remove_intent(symbol_name.clone(), id.to_string()).await
+    }
+
+    /// Sets whether bar times are the time the bar opened or the time it closed, the default is `BarTimeConvention::CloseTime`.
+    /// The convention is used for the keys and ranges of the history functions, `indicator_history_range()`, `bar_time()` and `indicator_time()`,
+    /// the strategy still receives each bar when it closes and `candle.time` is always the open time.
+    /// ```rust
+    /// // match a platform which stamps bars with their open time
+    /// strategy.set_bar_time_convention(BarTimeConvention::OpenTime);
+    /// ```
+    pub fn set_bar_time_convention(&self, convention: BarTimeConvention) {
+        *self.bar_time_convention.write().unwrap() = convention;
+    }
+
+    pub fn bar_time_convention(&self) -> BarTimeConvention {
+        *self.bar_time_convention.read().unwrap()
+    }
+
+    /// The time of the data in the bar time convention of the strategy.
+    /// ```rust
+    /// BaseDataEnum::Candle(candle) => println!("{}: {}", strategy.bar_time(&candle), candle.close),
+    /// ```
+    pub fn bar_time<T: BaseData>(&self, data: &T) -> DateTime<Utc> {
+        self.bar_time_convention().time_of(data)
+    }
+
+    /// The time of the indicator values in the bar time convention of the strategy, indicator values are stamped with the close time of their bar.
+    pub fn indicator_time(&self, values: &IndicatorValues) -> DateTime<Utc> {
+        self.bar_time_convention().time_of_values(values)
+    }
+
+    /// Serves the order management controls over http at the address and announces them to the data server, so `ff_ctl` can list the strategy,
+    /// show its accounts, positions and working orders, and flatten, cancel all or pause it when the gui is unavailable.
+    /// Pause and continue are received as `StrategyEvent::StrategyControls`, the strategy decides what pausing means, reduce only is also applied by the engine, see `set_reduce_only()`.
+    /// The endpoint is not authenticated, bind it to a loopback or private address.
+    /// ```rust
+    /// strategy.serve_controls("Oanda Scalper", SocketAddr::from_str("127.0.0.1:8095").unwrap()).await.unwrap();
+    /// ```
+    pub async fn serve_controls(&self, name: &str, address: SocketAddr) -> Result<(), std::io::Error> {
+        let server = ControlServer {
+            name: name.to_string(),
+            mode: self.mode,
+            synchronize_accounts: self.synchronize_accounts,
+            ledger_service: self.ledger_service.clone(),
+            open_order_cache: self.open_order_cache.clone(),
+            historical_message_sender: self.historical_message_sender.clone(),
+            strategy_event_sender: self.strategy_event_sender.clone(),
+            reduce_only: self.settings.reduce_only.clone(),
         };
+        server.serve(address).await
+    }
 
-        range_history_data(start_date.to_utc(), end_date, subscription.clone(), self.mode, trading_hours).await
+    /// Stops the strategy early.
+    ///
+    /// In backtests the engine stops at the next buffer, flattens the paper accounts at the current backtest time and sends `StrategyEvent::ShutdownEvent("Backtest Stopped: {reason}")`,
+    /// so the statistics and exports in our shutdown handling cover the completed portion of the backtest. Pressing Ctrl+C during a backtest does the same.
+    /// In live modes `StrategyEvent::ShutdownEvent(reason)` is sent to the event loop.
+    /// ```rust
+    /// if strategy.balance(&account) < dec!(40000) {
+    ///     strategy.request_stop("Max drawdown".to_string()).await;
+    /// }
+    /// ```
+    pub async fn request_stop(&self, reason: String) {
+        match self.mode {
+            StrategyMode::Backtest => request_backtest_stop(reason),
+            StrategyMode::Live | StrategyMode::LivePaperTrading => {
+                if let Err(e) = self.strategy_event_sender.send(StrategyEvent::ShutdownEvent(reason)).await {
+                    eprintln!("Failed to send stop request: {}", e);
+                }
+            }
+        }
+    }
+
+    /// In live modes the first SIGTERM or Ctrl+C sends `StrategyEvent::ShutdownEvent` to the event loop, the process exits after the `grace_period` if the strategy has not returned by then.
+    /// A second signal exits immediately. The default grace period is 30 seconds.
+    pub fn set_shutdown_grace_period(&self, grace_period: Duration) {
+        self.settings.set_shutdown_grace_period(grace_period);
+    }
+
+    /// A consistent copy of the account ledger and the orders working for the account, None if the strategy has no ledger for the account.
+    /// The snapshot is taken by the ledger between two updates, after the updates already sent to it are applied, so the positions, balances and statistics always agree.
+    /// ```rust
+    /// if let Some(snapshot) = strategy.ledger_snapshot(&account).await {
+    ///     println!("{}: {} open positions, {} open orders", snapshot.summary.balance, snapshot.positions.len(), snapshot.open_orders.len());
+    /// }
+    /// ```
+    pub async fn ledger_snapshot(&self, account: &Account) -> Option<LedgerSnapshot> {
+        let mut snapshot = self.ledger_service.ledger_snapshot(account).await?;
+        snapshot.open_orders = self.open_order_cache.iter()
+            .filter(|order| &order.account == account)
+            .map(|order| order.value().clone())
+            .collect();
+        Some(snapshot)
+    }
+
+    async fn ledger_snapshots(&self) -> Vec<LedgerSnapshot> {
+        let accounts: Vec<Account> = self.ledger_service.ledgers.iter().map(|ledger| ledger.key().clone()).collect();
+        let mut snapshots = vec![];
+        for account in accounts {
+            if let Some(snapshot) = self.ledger_snapshot(&account).await {
+                snapshots.push(snapshot);
+            }
+        }
+        snapshots
     }
 
     /// Prints a ledgers statistics
-    pub fn print_ledger(&self, account: &Account) {
-        self.ledger_service.print_ledger(account);
+    pub async fn print_ledger(&self, account: &Account) {
+        if let Some(snapshot) = self.ledger_snapshot(account).await {
+            println!("{}", snapshot.ledger_statistics_to_string());
+        }
     }
 
     /// Prints all ledgers statistics
-    pub fn print_ledgers(&self) {
-        self.ledger_service.print_ledgers();
+    pub async fn print_ledgers(&self) {
+        for snapshot in self.ledger_snapshots().await {
+            println!("{}", snapshot.ledger_statistics_to_string());
+        }
+    }
+
+    /// The statistics of all the strategy accounts together, with a combined equity curve and the breakdown of each account, see `AggregateStatistics`.
+    pub async fn aggregate_statistics(&self) -> AggregateStatistics {
+        AggregateStatistics::new(self.mode, &self.ledger_snapshots().await)
+    }
+
+    /// Prints the aggregate statistics of all the strategy accounts and the statistics of each account.
+    pub async fn print_aggregate_statistics(&self) {
+        println!("{}", self.aggregate_statistics().await);
     }
 
-    /// Exports trades to a csv file in the directory
-    pub fn export_positions_to_csv(&self, directory: &str) {
-        for account_entry in self.ledger_service.ledgers.iter() {
-            self.ledger_service.export_positions_to_csv(account_entry.key(), directory);
+    /// Exports the trades of all the strategy accounts to a single csv file, and the aggregate statistics with the equity curve to a json file, in the directory.
+    pub async fn export_aggregate_statistics(&self, directory: &str) {
+        let aggregate = self.aggregate_statistics().await;
+        aggregate.export_trades_to_csv(directory);
+        aggregate.export_to_json(directory);
+    }
+
+    /// Exports positions (cumulative) of each account to a csv file in the directory
+    pub async fn export_positions_to_csv(&self, directory: &str) {
+        for snapshot in self.ledger_snapshots().await {
+            snapshot.export_positions_to_csv(directory);
+        }
+    }
+
+    /// Exports trades (individual) to a csv file in the directory
+    pub async fn export_trades_to_csv(&self, account: &Account, directory: &str) {
+        if let Some(snapshot) = self.ledger_snapshot(account).await {
+            snapshot.export_trades_to_csv(directory);
         }
     }
 
-    /// Exports positions (cumulative) to a csv file in the directory
-    pub fn export_trades_to_csv(&self, account: &Account, directory: &str) {
-        self.ledger_service.export_trades_to_csv(account, directory);
+    /// The id of the order at the brokerage, eg the Rithmic basket id shown in R-Trader, for correlating fund forge orders with the brokerage records.
+    /// None until the brokerage accepts the order, and in backtests and live paper. The audit trail export has both ids for each fill.
+    pub fn broker_order_id(&self, order_id: &OrderId) -> Option<String> {
+        self.open_order_cache.get(order_id)
+            .or_else(|| self.closed_order_cache.get(order_id))
+            .and_then(|order| order.broker_order_id.clone())
+    }
+
+    /// The audit trail of the account ledger, every fill, exit and balance update applied to the ledger with the position and cash before and after it.
+    /// Use it to find the event which produced a wrong position size.
+    pub fn audit_trail(&self, account: &Account) -> Vec<AuditEntry> {
+        self.ledger_service.audit_trail(account)
+    }
+
+    /// Reconciles each ledger with its audit trail and the orders of the strategy, a break is a position which changed without a recorded fill or a fill the ledger never applied.
+    pub fn reconcile(&self) -> ReconciliationReport {
+        let orders: Vec<Order> = self.open_order_cache.iter()
+            .chain(self.closed_order_cache.iter())
+            .map(|order| order.value().clone())
+            .collect();
+        self.ledger_service.reconcile(&orders)
+    }
+
+    /// Reconciles the ledgers and records the result of this live paper run for the promotion gate, a passing record lets the same binary run live.
+    /// Call it at the end of the paper run, after the orders have settled.
+    /// ```rust
+    /// StrategyEvent::ShutdownEvent(_) => {
+    ///     strategy.cancel_and_flatten_all(Duration::from_secs(10)).await;
+    ///     if strategy.mode() == StrategyMode::LivePaperTrading {
+    ///         match strategy.record_paper_run(&gate) {
+    ///             Ok(record) => println!("Paper run passed: {}, fills: {}, breaks: {:?}", record.passed, record.fills, record.breaks),
+    ///             Err(e) => eprintln!("{}", e),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn record_paper_run(&self, gate: &PromotionGate) -> Result<PromotionRecord, FundForgeError> {
+        gate.record_paper_run(self.mode, &self.reconcile())
+    }
+
+    /// The fingerprint of the strategy configuration, the parameters, engine settings, subscriptions, data range, accounts and code version of this run.
+    /// Its hash is written to every export, save the fingerprint with the results to trace them back to the configuration.
+    pub fn fingerprint(&self) -> StrategyFingerprint {
+        self.settings.fingerprint().expect("The fingerprint is set when the strategy is initialized")
+    }
+
+    /// Exports the audit trail of the account to a csv file in the directory
+    pub fn export_audit_trail_to_csv(&self, account: &Account, directory: &str) {
+        self.ledger_service.export_audit_trail_to_csv(account, directory);
+    }
+
+    /// Exports the audit trail of each account to a csv file in the directory on shutdown, None stops the export.
+    /// Backtests export the trail when the backtest ends, live ledgers append each entry to the file as it is recorded so the trail is kept if the process is killed.
+    pub fn set_audit_trail_directory(&self, directory: Option<String>) {
+        self.settings.set_audit_trail_directory(directory);
+    }
+
+    /// Starts comparing each fill to the touch of the symbol at its fill time, a fill more than the `tolerance` above the ask or below the bid is flagged
+    /// with `StrategyEvent::HealthWarning(HealthWarning::FillOutsideTouch)`. The touch of each symbol is kept for the `touch_window`,
+    /// it must be longer than the delay of the fill reports from the brokerage. Calling it again changes the tolerance and window and keeps the checked fills.
+    /// ```rust
+    /// // flag any Rithmic fill more than a tick outside the touch
+    /// strategy.start_fill_audit(dec!(0.25), Duration::minutes(2));
+    /// ```
+    pub fn start_fill_audit(&self, tolerance: Price, touch_window: ChronoDuration) {
+        self.fill_auditor.set_tolerance(tolerance);
+        self.market_price_service.set_touch_window(Some(touch_window));
+        let fill_auditor = self.fill_auditor.clone();
+        self.order_hooks.add_post_fill(FILL_AUDIT_HOOK.to_string(), Arc::new(move |event: &OrderUpdateEvent| fill_auditor.audit(event)));
+    }
+
+    /// Stops the fill audit and clears the recorded touches, the checked fills are kept.
+    pub fn stop_fill_audit(&self) {
+        self.order_hooks.remove(FILL_AUDIT_HOOK);
+        self.market_price_service.set_touch_window(None);
+    }
+
+    /// The fills checked by the fill audit, in the order they were received.
+    pub fn fill_checks(&self) -> Vec<FillCheck> {
+        self.fill_auditor.checks()
+    }
+
+    /// The slippage of the checked fills per brokerage, symbol and hour.
+    pub fn slippage_report(&self) -> Vec<SlippageSummary> {
+        slippage_report(&self.fill_auditor.checks())
+    }
+
+    /// Exports the checked fills and the slippage report to csv files in the directory.
+    pub fn export_fill_audit_to_csv(&self, directory: &str) {
+        self.fill_auditor.export(directory);
     }
 
     /// Save positions to a json file in the directory
     /// Useful for machine learning etc.
-    pub fn save_positions_to_file(&self, account: &Account, file_path: &str) {
-        self.ledger_service.save_positions_to_file(account, file_path);
+    pub async fn save_positions_to_file(&self, account: &Account, file_path: &str) {
+        if let Some(snapshot) = self.ledger_snapshot(account).await {
+            snapshot.save_positions_to_file(file_path);
+        }
     }
 
     /// Used to load positions from disk when saved as json format
@@ -1148,9 +2920,30 @@ impl FundForgeStrategy {
         self.ledger_service.get_positions(account)
     }
 
-    /// Exports trades (individual) to a csv file in the directory
-    pub fn print_trade_statistics(&self, account: &Account) {
-        self.ledger_service.print_trade_statistics(account);
+    /// Prints the statistics of the individual trades of the account
+    pub async fn print_trade_statistics(&self, account: &Account) {
+        if let Some(snapshot) = self.ledger_snapshot(account).await {
+            println!("{}", snapshot.trade_statistics_to_string());
+        }
+    }
+
+    /// Writes a journal entry for each position closed from now on, see `TradeJournalSettings`.
+    /// Each entry has the entry and exit times and tags, the indicator values at entry and the bars around the trade, written as json and markdown with an optional svg chart.
+    pub fn enable_trade_journal(&self, settings: TradeJournalSettings) {
+        let trade_journal = TradeJournal::new(settings, self.indicator_handler.clone(), self.subscription_handler.clone(), self.closed_order_cache.clone(), self.settings.fingerprint_hash());
+        self.ledger_service.set_trade_journal(Arc::new(trade_journal));
+    }
+
+    /// Positions are journaled on the next time slice after they close, call this on shutdown to journal positions closed by the final time slice.
+    pub async fn flush_trade_journal(&self) {
+        self.ledger_service.record_trade_journal().await;
+    }
+
+    /// Prints the trade statistics for each backtest regime set with `set_backtest_regimes()`.
+    pub async fn print_regime_statistics(&self, account: &Account) {
+        if let Some(snapshot) = self.ledger_snapshot(account).await {
+            println!("{}", snapshot.regime_statistics_to_string(&self.backtest_regimes));
+        }
     }
 
     // Updated position query functions
@@ -1183,7 +2976,53 @@ impl FundForgeStrategy {
         self.ledger_service.position_size(account, symbol_name)
     }
 
+    /// The maximum adverse excursion of the open position, the furthest the price has moved against the average price since the position opened.
+    /// Returns None if there is no open position for the symbol code, for futures use the contract symbol code.
+    pub fn position_mae(&self, account: &Account, symbol_code: &SymbolCode) -> Option<Price> {
+        self.ledger_service.position_mae(account, symbol_code)
+    }
+
+    /// The maximum favorable excursion of the open position, the furthest the price has moved in favour of the average price since the position opened.
+    pub fn position_mfe(&self, account: &Account, symbol_code: &SymbolCode) -> Option<Price> {
+        self.ledger_service.position_mfe(account, symbol_code)
+    }
+
+    /// Suggests a stop distance from the mae of the past winning positions of the symbol and side, the stop is one tick beyond the mae at the percentile (0 to 100) of the winners.
+    /// The suggestion is advisory, no order is placed. Returns None if the account has fewer than `MIN_STOP_SUGGESTION_TRADES` winning positions for the symbol and side.
+    /// ```rust
+    /// if let Some(suggestion) = strategy.suggest_stop(&account, &symbol_name, PositionSide::Long, dec!(90)) {
+    ///     if let Some(stop_price) = suggestion.stop_price {
+    ///         let quantity = strategy.position_size(&account, &symbol_name);
+    ///         let _ = strategy.stop_order(&symbol_name, None, &account, None, quantity, OrderSide::Sell, stop_price, TimeInForce::GTC, "Stop".to_string()).await;
+    ///     }
+    /// }
+    /// ```
+    pub fn suggest_stop(&self, account: &Account, symbol_name: &SymbolName, side: PositionSide, percentile: Decimal) -> Option<StopSuggestion> {
+        self.ledger_service.suggest_stop(account, symbol_name, side, percentile)
+    }
+
     pub fn booked_pnl_account(&self, account: &Account) -> Decimal {
         self.ledger_service.booked_pnl_account(account)
     }
+
+    /// The borrow fees the paper ledger of the account has charged for short positions, they are included in `booked_pnl_account()`, see `strategy.set_shortability_table()`.
+    pub fn borrow_fees(&self, account: &Account) -> Decimal {
+        self.ledger_service.borrow_fees(account)
+    }
+
+    /// The accounts open pnl on the legs of the option combo.
+    /// The legs are traded as individual symbols, each leg contract must be registered with `register_option_contract()` before it is traded.
+    pub fn combo_pnl(&self, account: &Account, combo: &OptionCombo) -> Decimal {
+        self.ledger_service.combo_pnl(account, combo)
+    }
+
+    /// True if the account has no position in any leg of the option combo.
+    pub fn is_combo_flat(&self, account: &Account, combo: &OptionCombo) -> bool {
+        self.ledger_service.is_combo_flat(account, combo)
+    }
+
+    /// The net greeks of the accounts positions in the option combo, using the greeks from the latest option chain, see `DataVendor::option_chain()`.
+    pub fn combo_greeks(&self, account: &Account, combo: &OptionCombo, quotes: &[OptionQuote]) -> Greeks {
+        self.ledger_service.combo_greeks(account, combo, quotes)
+    }
 }
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use tokio::sync::mpsc;
+use crate::standardized_types::market_hours::TradingHours;
+use crate::standardized_types::orders::{Order, OrderId, OrderRequest, OrderType};
+use crate::strategies::historical_time::WarmUpState;
+
+/// Holds the orders sent while their market is closed with `ClosedMarketPolicy::QueueUntilOpen`, each is sent on the first time update its market is open.
+pub(crate) struct ClosedMarketQueue {
+    queued: DashMap<OrderId, (Order, OrderType, TradingHours)>,
+    order_sender: mpsc::Sender<OrderRequest>,
+    warm_up: WarmUpState,
+}
+
+impl ClosedMarketQueue {
+    pub(crate) fn new(order_sender: mpsc::Sender<OrderRequest>, warm_up: WarmUpState) -> Self {
+        ClosedMarketQueue {
+            queued: DashMap::new(),
+            order_sender,
+            warm_up,
+        }
+    }
+
+    /// Holds the order until the `trading_hours` are open.
+    pub(crate) fn add(&self, order: Order, order_type: OrderType, trading_hours: TradingHours) {
+        self.queued.insert(order.id.clone(), (order, order_type, trading_hours));
+    }
+
+    /// Removes the order from the queue, returns the order if it was queued.
+    pub(crate) fn remove(&self, order_id: &OrderId) -> Option<Order> {
+        self.queued.remove(order_id).map(|(_, (order, _, _))| order)
+    }
+
+    pub(crate) fn orders(&self) -> Vec<Order> {
+        self.queued.iter().map(|queued| queued.value().0.clone()).collect()
+    }
+
+    /// Sends the queued orders for the markets which are open at the current time.
+    pub(crate) async fn update_time(&self, current_time: DateTime<Utc>) {
+        if self.queued.is_empty() || !self.warm_up.is_complete() {
+            return;
+        }
+        for (mut order, order_type) in self.open_orders(current_time) {
+            order.update_time_created_utc(current_time);
+            let request = OrderRequest::Create { account: order.account.clone(), order, order_type };
+            if let Err(e) = self.order_sender.send(request).await {
+                eprintln!("Closed Market Queue: Failed to send order request: {}", e);
+            }
+        }
+    }
+
+    /// Removes and returns the queued orders for the markets which are open at the time.
+    fn open_orders(&self, time: DateTime<Utc>) -> Vec<(Order, OrderType)> {
+        let open_ids: Vec<OrderId> = self.queued.iter()
+            .filter(|queued| queued.value().2.is_market_open(time))
+            .map(|queued| queued.key().clone())
+            .collect();
+        open_ids.iter()
+            .filter_map(|order_id| self.queued.remove(order_id))
+            .map(|(_, (mut order, order_type, trading_hours))| {
+                // a day order queued over the close is good for the session it is released into
+                order.resolve_day_expiry(Some(&trading_hours), time);
+                (order, order_type)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::America::New_York;
+    use rust_decimal_macros::dec;
+    use crate::product_maps::alpaca::maps::NYSE_HOURS;
+    use crate::standardized_types::accounts::Account;
+    use crate::standardized_types::broker_enum::Brokerage;
+    use crate::standardized_types::enums::OrderSide;
+
+    fn order(order_id: &str, time: DateTime<Utc>) -> Order {
+        let account = Account::new(Brokerage::Test, "Test_Account_1".to_string());
+        Order::market_order("AAPL".to_string(), None, &account, dec!(1), OrderSide::Buy, "Entry".to_string(), order_id.to_string(), time, None)
+    }
+
+    #[test]
+    fn test_queued_orders_released_at_open() {
+        let (order_sender, _order_receiver) = mpsc::channel(10);
+        let queue = ClosedMarketQueue::new(order_sender, WarmUpState::new());
+        let saturday = New_York.with_ymd_and_hms(2024, 1, 6, 12, 0, 0).unwrap().to_utc();
+        queue.add(order("1", saturday), OrderType::Market, NYSE_HOURS);
+        queue.add(order("2", saturday), OrderType::Market, NYSE_HOURS);
+
+        assert!(queue.open_orders(saturday).is_empty());
+        assert!(queue.open_orders(New_York.with_ymd_and_hms(2024, 1, 8, 9, 29, 0).unwrap().to_utc()).is_empty());
+        assert_eq!(queue.remove(&"2".to_string()).map(|order| order.id), Some("2".to_string()));
+
+        let open = New_York.with_ymd_and_hms(2024, 1, 8, 9, 30, 0).unwrap().to_utc();
+        let released = queue.open_orders(open);
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].0.id, "1");
+        assert!(queue.orders().is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use tokio::sync::mpsc::Sender;
+use crate::product_maps::trading_hours::get_trading_hours;
+use crate::product_maps::rithmic::rollover::{contract_expiry, parse_contract};
+use crate::standardized_types::enums::MarketType;
+use crate::standardized_types::subscriptions::{DataSubscription, DataSubscriptionEvent, SymbolCode, SymbolName};
+use crate::strategies::handlers::indicator_handler::IndicatorHandler;
+use crate::strategies::handlers::subscription_handler::SubscriptionHandler;
+use crate::strategies::strategy_events::StrategyEvent;
+
+/// What the engine does when a subscribed futures contract expires, see `strategy.set_contract_expiry_policy()`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContractExpiryPolicy {
+    /// Only send `DataSubscriptionEvent::Expired(subscription, None)`, the subscription is kept.
+    Notify,
+    /// Replace the subscription with the same subscription of the next contract, keeping `history_to_retain` items of history.
+    MapToNextContract { history_to_retain: usize, fill_forward: bool },
+}
+
+pub(crate) struct ContractExpiryHandler {
+    policy: RwLock<ContractExpiryPolicy>,
+    /// The expiry and next contract of each subscribed symbol name, None for names which are not a contract code.
+    expiries: DashMap<SymbolName, Option<(DateTime<Utc>, SymbolCode)>>,
+    expired: RwLock<HashSet<DataSubscription>>,
+    subscription_handler: Arc<SubscriptionHandler>,
+    indicator_handler: Arc<IndicatorHandler>,
+    strategy_event_sender: Sender<StrategyEvent>,
+}
+
+impl ContractExpiryHandler {
+    pub(crate) fn new(
+        subscription_handler: Arc<SubscriptionHandler>,
+        indicator_handler: Arc<IndicatorHandler>,
+        strategy_event_sender: Sender<StrategyEvent>,
+    ) -> Self {
+        ContractExpiryHandler {
+            policy: RwLock::new(ContractExpiryPolicy::Notify),
+            expiries: DashMap::new(),
+            expired: RwLock::new(HashSet::new()),
+            subscription_handler,
+            indicator_handler,
+            strategy_event_sender,
+        }
+    }
+
+    pub(crate) fn set_policy(&self, policy: ContractExpiryPolicy) {
+        *self.policy.write().unwrap() = policy;
+    }
+
+    pub(crate) async fn update_time(&self, current_time: DateTime<Utc>) {
+        let subscriptions = self.subscription_handler.strategy_subscriptions().await;
+        let expired = self.expired_subscriptions(subscriptions, current_time);
+        if expired.is_empty() {
+            return;
+        }
+        let policy = self.policy.read().unwrap().clone();
+        for (subscription, next_contract) in expired {
+            let next = match &policy {
+                ContractExpiryPolicy::Notify => None,
+                ContractExpiryPolicy::MapToNextContract { .. } => {
+                    let mut next = subscription.clone();
+                    next.symbol.name = next_contract;
+                    Some(next)
+                }
+            };
+            let event = DataSubscriptionEvent::Expired(subscription.clone(), next.clone());
+            if let Err(e) = self.strategy_event_sender.send(StrategyEvent::DataSubscriptionEvent(event)).await {
+                eprintln!("Contract Expiry: Failed to send event: {}", e);
+            }
+            if let (ContractExpiryPolicy::MapToNextContract { history_to_retain, fill_forward }, Some(next)) = (&policy, next) {
+                self.subscription_handler.unsubscribe(subscription.clone(), true).await;
+                self.indicator_handler.indicators_unsubscribe_subscription(&subscription).await;
+                let trading_hours = parse_contract(&next.symbol.name, current_time).ok()
+                    .and_then(|(root, _, _)| get_trading_hours(root));
+                self.subscription_handler.subscribe(None, next, current_time, *fill_forward, *history_to_retain, true, trading_hours).await;
+            }
+        }
+    }
+
+    /// The futures subscriptions which have expired at the time and have not been reported yet, with the code of the next contract.
+    fn expired_subscriptions(&self, subscriptions: Vec<DataSubscription>, time: DateTime<Utc>) -> Vec<(DataSubscription, SymbolCode)> {
+        let mut expired = self.expired.write().unwrap();
+        let mut newly_expired = vec![];
+        for subscription in subscriptions {
+            if !matches!(subscription.market_type, MarketType::Futures(_)) || expired.contains(&subscription) {
+                continue;
+            }
+            let expiry = self.expiries
+                .entry(subscription.symbol.name.clone())
+                .or_insert_with(|| contract_expiry(&subscription.symbol.name, time).ok())
+                .clone();
+            if let Some((expiry, next_contract)) = expiry {
+                if time >= expiry {
+                    expired.insert(subscription.clone());
+                    newly_expired.push((subscription, next_contract));
+                }
+            }
+        }
+        newly_expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::America::Chicago;
+    use tokio::sync::mpsc;
+    use crate::standardized_types::base_data::base_data_type::BaseDataType;
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::{FuturesExchange, StrategyMode};
+    use crate::standardized_types::resolution::Resolution;
+    use crate::strategies::historical_time::WarmUpState;
+
+    #[tokio::test]
+    async fn test_expired_subscriptions_reported_once() {
+        let (event_sender, _event_receiver) = mpsc::channel(10);
+        let subscription_handler = Arc::new(SubscriptionHandler::new(StrategyMode::Backtest, event_sender.clone(), WarmUpState::new()).await);
+        let indicator_handler = Arc::new(IndicatorHandler::new(StrategyMode::Backtest, subscription_handler.clone()).await);
+        let handler = ContractExpiryHandler::new(subscription_handler, indicator_handler, event_sender);
+
+        let subscription = |name: &str| DataSubscription::new(name.to_string(), DataVendor::Rithmic, Resolution::Minutes(1), BaseDataType::Candles, MarketType::Futures(FuturesExchange::CME));
+        let subscriptions = vec![subscription("MNQH24"), subscription("MNQ"), subscription("MNQM24")];
+
+        let expiry = Chicago.with_ymd_and_hms(2024, 3, 9, 0, 0, 0).unwrap().to_utc();
+        assert!(handler.expired_subscriptions(subscriptions.clone(), expiry - chrono::Duration::seconds(1)).is_empty());
+        assert_eq!(handler.expired_subscriptions(subscriptions.clone(), expiry), vec![(subscription("MNQH24"), "MNQM24".to_string())]);
+        assert!(handler.expired_subscriptions(subscriptions, expiry + chrono::Duration::days(1)).is_empty());
+    }
+}
@@ -0,0 +1,269 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use chrono::Utc;
+use dashmap::DashMap;
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use crate::messages::data_server_messaging::{DataServerRequest, FundForgeError, StrategyStreamInfo};
+use crate::standardized_types::accounts::{Account, AccountId};
+use crate::standardized_types::enums::StrategyMode;
+use crate::standardized_types::orders::{Order, OrderId, OrderRequest};
+use crate::strategies::client_features::connection_types::ConnectionType;
+use crate::strategies::client_features::request_handler::{send_request, StrategyRequest};
+use crate::strategies::handlers::market_handler::backtest_matching_engine::BackTestEngineMessage;
+use crate::strategies::handlers::reduce_only::ReduceOnlyHandle;
+use crate::strategies::ledgers::ledger_service::LedgerService;
+use crate::strategies::strategy_events::{StrategyControls, StrategyEvent};
+
+const MAX_REQUEST_HEAD: usize = 8192;
+
+#[derive(Debug, PartialEq)]
+enum ControlRequest {
+    Snapshot,
+    Flatten(Option<AccountId>),
+    CancelAll(Option<AccountId>),
+    Pause,
+    Continue,
+    ReduceOnly,
+}
+
+/// Order management controls served over http, see `strategy.serve_controls()`.
+/// The endpoint is not authenticated, bind it to a loopback or private address.
+pub(crate) struct ControlServer {
+    pub(crate) name: String,
+    pub(crate) mode: StrategyMode,
+    pub(crate) synchronize_accounts: bool,
+    pub(crate) ledger_service: Arc<LedgerService>,
+    pub(crate) open_order_cache: Arc<DashMap<OrderId, Order>>,
+    pub(crate) historical_message_sender: Option<mpsc::Sender<BackTestEngineMessage>>,
+    pub(crate) strategy_event_sender: mpsc::Sender<StrategyEvent>,
+    pub(crate) reduce_only: ReduceOnlyHandle,
+}
+
+impl ControlServer {
+    /// Binds the address, starts accepting control requests and announces the endpoint to the data server.
+    pub(crate) async fn serve(self, address: SocketAddr) -> Result<(), io::Error> {
+        let listener = TcpListener::bind(address).await?;
+        let address = listener.local_addr()?;
+        let info = StrategyStreamInfo {
+            name: self.name.clone(),
+            mode: self.mode,
+            control_address: address.to_string(),
+            registered: Utc::now().to_string(),
+            stream_name: 0,
+        };
+        let server = Arc::new(self);
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(server.clone().handle_client(stream));
+                    }
+                    Err(e) => eprintln!("Control Server: Failed to accept connection: {}", e),
+                }
+            }
+        });
+        send_request(StrategyRequest::OneWay(ConnectionType::Default, DataServerRequest::RegisterStrategyStream { info })).await;
+        println!("Control Server: Serving strategy controls at http://{}", address);
+        Ok(())
+    }
+
+    async fn handle_client(self: Arc<Self>, mut stream: TcpStream) {
+        let Some((method, target)) = read_request_line(&mut stream).await else {
+            return;
+        };
+        let (status, body) = match parse_request(&method, &target) {
+            Ok(request) => self.handle_request(request).await,
+            Err(status) => (status, json!({"error": status})),
+        };
+        let body = body.to_string();
+        let response = format!("HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body);
+        let _ = stream.write_all(response.as_bytes()).await;
+    }
+
+    async fn handle_request(&self, request: ControlRequest) -> (&'static str, Value) {
+        match request {
+            ControlRequest::Snapshot => ("200 OK", self.snapshot()),
+            ControlRequest::Flatten(account_id) => {
+                let accounts = self.accounts(account_id.as_ref());
+                if accounts.is_empty() {
+                    return ("404 Not Found", json!({"error": format!("No account: {:?}", account_id)}));
+                }
+                for account in &accounts {
+                    self.send_order_request(OrderRequest::FlattenAllFor { account: account.clone() }).await;
+                }
+                ("200 OK", json!({"flattened": accounts}))
+            }
+            ControlRequest::CancelAll(account_id) => {
+                let accounts = self.accounts(account_id.as_ref());
+                if accounts.is_empty() {
+                    return ("404 Not Found", json!({"error": format!("No account: {:?}", account_id)}));
+                }
+                for account in &accounts {
+                    self.cancel_all(account).await;
+                }
+                ("200 OK", json!({"cancelled": accounts}))
+            }
+            ControlRequest::Pause => self.send_control(StrategyControls::Pause).await,
+            ControlRequest::Continue => {
+                self.reduce_only.set(false);
+                self.send_control(StrategyControls::Continue).await
+            }
+            ControlRequest::ReduceOnly => {
+                self.reduce_only.set(true);
+                self.send_control(StrategyControls::ReduceOnly).await
+            }
+        }
+    }
+
+    /// The accounts with a ledger, filtered by the account id.
+    fn accounts(&self, account_id: Option<&AccountId>) -> Vec<Account> {
+        let mut accounts: Vec<Account> = self.ledger_service.ledgers.iter()
+            .map(|ledger| ledger.key().clone())
+            .filter(|account| account_id.map_or(true, |id| &account.account_id == id))
+            .collect();
+        accounts.sort_by(|a, b| a.account_id.cmp(&b.account_id));
+        accounts
+    }
+
+    fn snapshot(&self) -> Value {
+        let accounts: Vec<Value> = self.accounts(None).iter().map(|account| {
+            let summary = self.ledger_service.account_summary(account);
+            let positions: Vec<Value> = match self.ledger_service.ledgers.get(account) {
+                Some(ledger) => ledger.positions.iter().filter_map(|position| serde_json::to_value(position.value()).ok()).collect(),
+                None => vec![],
+            };
+            let working_orders: Vec<Value> = self.open_order_cache.iter()
+                .filter(|order| &order.account == account)
+                .filter_map(|order| serde_json::to_value(order.value()).ok())
+                .collect();
+            json!({
+                "account": account,
+                "summary": summary,
+                "positions": positions,
+                "working_orders": working_orders,
+            })
+        }).collect();
+        json!({"name": self.name, "mode": self.mode, "reduce_only": self.reduce_only.is_reduce_only(), "accounts": accounts})
+    }
+
+    /// Cancels every working order on a synchronized live account, otherwise the orders placed by the strategy, like `strategy.cancel_orders_account()`.
+    async fn cancel_all(&self, account: &Account) {
+        if self.synchronize_accounts && self.mode == StrategyMode::Live {
+            self.send_order_request(OrderRequest::CancelAll { account: account.clone() }).await;
+            return;
+        }
+        let order_ids: Vec<OrderId> = self.open_order_cache.iter()
+            .filter(|order| &order.account == account)
+            .map(|order| order.id.clone())
+            .collect();
+        for order_id in order_ids {
+            self.send_order_request(OrderRequest::Cancel { order_id, account: account.clone() }).await;
+        }
+    }
+
+    async fn send_order_request(&self, request: OrderRequest) {
+        match self.mode {
+            StrategyMode::Live => {
+                let connection_type = ConnectionType::Broker(request.brokerage());
+                send_request(StrategyRequest::OneWay(connection_type, DataServerRequest::OrderRequest { request })).await;
+            }
+            StrategyMode::Backtest | StrategyMode::LivePaperTrading => {
+                if let Some(sender) = &self.historical_message_sender {
+                    if let Err(e) = sender.send(BackTestEngineMessage::OrderRequest(request)).await {
+                        eprintln!("Control Server: Failed to send order request: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn send_control(&self, control: StrategyControls) -> (&'static str, Value) {
+        let name = format!("{:?}", control);
+        match self.strategy_event_sender.send(StrategyEvent::StrategyControls(control)).await {
+            Ok(_) => ("200 OK", json!({"sent": name})),
+            Err(e) => ("503 Service Unavailable", json!({"error": e.to_string()})),
+        }
+    }
+}
+
+/// Parses the method and target, returns the status line for requests which are not controls.
+fn parse_request(method: &str, target: &str) -> Result<ControlRequest, &'static str> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let account_id = query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "account")
+        .map(|(_, value)| value.to_string());
+    match (method, path) {
+        ("GET", "/snapshot") => Ok(ControlRequest::Snapshot),
+        ("POST", "/flatten") => Ok(ControlRequest::Flatten(account_id)),
+        ("POST", "/cancel_all") => Ok(ControlRequest::CancelAll(account_id)),
+        ("POST", "/pause") => Ok(ControlRequest::Pause),
+        ("POST", "/continue") => Ok(ControlRequest::Continue),
+        ("POST", "/reduce_only") => Ok(ControlRequest::ReduceOnly),
+        (_, "/snapshot") | (_, "/flatten") | (_, "/cancel_all") | (_, "/pause") | (_, "/continue") | (_, "/reduce_only") => Err("405 Method Not Allowed"),
+        _ => Err("404 Not Found"),
+    }
+}
+
+async fn read_request_line(stream: &mut TcpStream) -> Option<(String, String)> {
+    let mut head = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD {
+            return None;
+        }
+        match stream.read(&mut buffer).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => head.extend_from_slice(&buffer[..n]),
+        }
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    Some((request_line.next()?.to_string(), request_line.next()?.to_string()))
+}
+
+/// Sends a request to a strategy control endpoint and returns the json body, used by `ff_ctl`.
+///
+/// # Example
+/// ```rust
+/// let snapshot = control_request("127.0.0.1:8095", "GET", "/snapshot").await?;
+/// control_request("127.0.0.1:8095", "POST", "/flatten?account=Test_Account_1").await?;
+/// ```
+pub async fn control_request(address: &str, method: &str, path: &str) -> Result<Value, FundForgeError> {
+    let mut stream = TcpStream::connect(address).await
+        .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to connect to {}: {}", address, e)))?;
+    let request = format!("{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", method, path, address);
+    stream.write_all(request.as_bytes()).await
+        .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to send request to {}: {}", address, e)))?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await
+        .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to read response from {}: {}", address, e)))?;
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap_or((&response, ""));
+    let status = head.lines().next().and_then(|line| line.split_whitespace().nth(1)).unwrap_or_default().to_string();
+    let body: Value = serde_json::from_str(body).unwrap_or(Value::Null);
+    match status.starts_with('2') {
+        true => Ok(body),
+        false => Err(FundForgeError::ClientSideErrorDebug(format!("{} {}: {}", method, path, body.get("error").cloned().unwrap_or(Value::String(status))))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        assert_eq!(parse_request("GET", "/snapshot"), Ok(ControlRequest::Snapshot));
+        assert_eq!(parse_request("POST", "/flatten"), Ok(ControlRequest::Flatten(None)));
+        assert_eq!(parse_request("POST", "/cancel_all?account=Test_Account_1"), Ok(ControlRequest::CancelAll(Some("Test_Account_1".to_string()))));
+        assert_eq!(parse_request("POST", "/pause"), Ok(ControlRequest::Pause));
+        assert_eq!(parse_request("POST", "/reduce_only"), Ok(ControlRequest::ReduceOnly));
+        assert_eq!(parse_request("GET", "/flatten"), Err("405 Method Not Allowed"));
+        assert_eq!(parse_request("GET", "/orders"), Err("404 Not Found"));
+    }
+}
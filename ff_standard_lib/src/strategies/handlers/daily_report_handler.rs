@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+use std::fs::{create_dir_all, write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use chrono_tz::Tz;
+use rust_decimal::Decimal;
+use tokio::sync::mpsc;
+use crate::standardized_types::accounts::Account;
+use crate::standardized_types::subscriptions::SymbolCode;
+use crate::strategies::historical_time::WarmUpState;
+use crate::strategies::handlers::timed_events_handler::latest_rollover;
+use crate::strategies::ledgers::audit_trail::AuditEntry;
+use crate::strategies::ledgers::ledger_service::LedgerService;
+use crate::strategies::strategy_events::StrategyEvent;
+
+/// The daily report schedule, see `strategy.schedule_daily_report()`.
+/// # Properties
+/// * `time_zone` - The time zone of the `time`.
+/// * `time` - The time of day to compile the report of the previous 24 hours.
+/// * `directory` - The directory the text and html reports are written to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DailyReportSettings {
+    pub time_zone: Tz,
+    pub time: NaiveTime,
+    pub directory: String,
+}
+
+impl DailyReportSettings {
+    pub fn new(time_zone: Tz, time: NaiveTime, directory: String) -> Self {
+        DailyReportSettings {
+            time_zone,
+            time,
+            directory,
+        }
+    }
+}
+
+/// The report of one account for a day.
+/// # Properties
+/// * `account` - The account.
+/// * `fills` - The fill entries of the audit trail in the day.
+/// * `pnl_by_symbol` - The pnl booked per symbol code.
+/// * `total_pnl` - The pnl booked on the account.
+/// * `max_drawdown` - The largest fall of the booked pnl from its high of the day, 0 if it never fell.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct AccountReport {
+    pub account: Account,
+    pub fills: Vec<AuditEntry>,
+    pub pnl_by_symbol: BTreeMap<SymbolCode, Decimal>,
+    pub total_pnl: Decimal,
+    pub max_drawdown: Decimal,
+}
+
+/// Compiles the report of the account from the entries of its audit trail with an event time after `from` and at or before `to`.
+pub(crate) fn account_report(account: &Account, entries: &[AuditEntry], from: DateTime<Utc>, to: DateTime<Utc>) -> AccountReport {
+    let mut report = AccountReport {
+        account: account.clone(),
+        fills: vec![],
+        pnl_by_symbol: BTreeMap::new(),
+        total_pnl: Decimal::ZERO,
+        max_drawdown: Decimal::ZERO,
+    };
+    let mut high = Decimal::ZERO;
+    for entry in entries {
+        let Ok(time) = DateTime::<Utc>::from_str(&entry.event_time) else {
+            continue;
+        };
+        if time <= from || time > to {
+            continue;
+        }
+        if entry.fill_quantity.is_some() {
+            report.fills.push(entry.clone());
+        }
+        if let Some(symbol_code) = &entry.symbol_code {
+            if entry.booked_pnl != Decimal::ZERO {
+                *report.pnl_by_symbol.entry(symbol_code.clone()).or_insert(Decimal::ZERO) += entry.booked_pnl;
+                report.total_pnl += entry.booked_pnl;
+                high = high.max(report.total_pnl);
+                report.max_drawdown = report.max_drawdown.max(high - report.total_pnl);
+            }
+        }
+    }
+    report
+}
+
+fn fill_line(entry: &AuditEntry) -> [String; 7] {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    [
+        entry.event_time.clone(),
+        optional(entry.symbol_code.clone()),
+        optional(entry.fill_side.map(|side| format!("{:?}", side))),
+        optional(entry.fill_quantity.map(|quantity| quantity.to_string())),
+        optional(entry.fill_price.map(|price| price.to_string())),
+        entry.tag.clone(),
+        optional(entry.order_id.clone()),
+    ]
+}
+
+/// The plain text report.
+pub(crate) fn report_to_text(date: &str, fingerprint: &str, reports: &[AccountReport], risk_events: &[(DateTime<Utc>, String)]) -> String {
+    let mut text = format!("Daily Report {}\nFingerprint: {}\n", date, fingerprint);
+    for report in reports {
+        text.push_str(&format!("\nAccount {}\n", report.account));
+        text.push_str(&format!("Total PnL: {}, Max Drawdown: {}\n", report.total_pnl, report.max_drawdown));
+        for (symbol_code, pnl) in &report.pnl_by_symbol {
+            text.push_str(&format!("  {}: {}\n", symbol_code, pnl));
+        }
+        text.push_str(&format!("Fills: {}\n", report.fills.len()));
+        for entry in &report.fills {
+            text.push_str(&format!("  {}\n", fill_line(entry).join(" ")));
+        }
+    }
+    text.push_str(&format!("\nRisk Events: {}\n", risk_events.len()));
+    for (time, description) in risk_events {
+        text.push_str(&format!("  {} {}\n", time, description));
+    }
+    text
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// The html report, one table of fills and one of pnl per account.
+pub(crate) fn report_to_html(date: &str, fingerprint: &str, reports: &[AccountReport], risk_events: &[(DateTime<Utc>, String)]) -> String {
+    let row = |cells: &[String]| format!("<tr>{}</tr>\n", cells.iter().map(|cell| format!("<td>{}</td>", escape_html(cell))).collect::<String>());
+    let mut html = format!("<html>\n<head><title>Daily Report {0}</title></head>\n<body>\n<h1>Daily Report {0}</h1>\n<p>Fingerprint: {1}</p>\n", escape_html(date), escape_html(fingerprint));
+    for report in reports {
+        html.push_str(&format!("<h2>Account {}</h2>\n", escape_html(&report.account.to_string())));
+        html.push_str(&format!("<p>Total PnL: {}, Max Drawdown: {}</p>\n<table>\n", report.total_pnl, report.max_drawdown));
+        html.push_str("<tr><th>Symbol</th><th>PnL</th></tr>\n");
+        for (symbol_code, pnl) in &report.pnl_by_symbol {
+            html.push_str(&row(&[symbol_code.clone(), pnl.to_string()]));
+        }
+        html.push_str("</table>\n<h3>Fills</h3>\n<table>\n<tr><th>Time</th><th>Symbol</th><th>Side</th><th>Quantity</th><th>Price</th><th>Tag</th><th>Order</th></tr>\n");
+        for entry in &report.fills {
+            html.push_str(&row(&fill_line(entry)));
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("<h2>Risk Events</h2>\n<table>\n");
+    for (time, description) in risk_events {
+        html.push_str(&row(&[time.to_string(), description.clone()]));
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+struct ScheduledReport {
+    settings: DailyReportSettings,
+    last_fired: Option<DateTime<Utc>>,
+}
+
+pub(crate) struct DailyReportHandler {
+    schedule: RwLock<Option<ScheduledReport>>,
+    accounts: Vec<Account>,
+    ledger_service: Arc<LedgerService>,
+    strategy_event_sender: mpsc::Sender<StrategyEvent>,
+    warm_up: WarmUpState,
+}
+
+impl DailyReportHandler {
+    pub(crate) fn new(accounts: Vec<Account>, ledger_service: Arc<LedgerService>, strategy_event_sender: mpsc::Sender<StrategyEvent>, warm_up: WarmUpState) -> Self {
+        DailyReportHandler {
+            schedule: RwLock::new(None),
+            accounts,
+            ledger_service,
+            strategy_event_sender,
+            warm_up,
+        }
+    }
+
+    /// Replaces the schedule, None stops the reports.
+    pub(crate) fn set_schedule(&self, settings: Option<DailyReportSettings>) {
+        self.ledger_service.settings.keep_risk_events(settings.is_some());
+        *self.schedule.write().unwrap() = settings.map(|settings| ScheduledReport { settings, last_fired: None });
+    }
+
+    pub(crate) async fn update_time(&self, current_time: DateTime<Utc>) {
+        let (settings, report_time) = {
+            let mut schedule = self.schedule.write().unwrap();
+            let Some(scheduled) = schedule.as_mut() else {
+                return;
+            };
+            let Some(report_time) = latest_rollover(&scheduled.settings.time_zone, scheduled.settings.time, current_time) else {
+                return;
+            };
+            match scheduled.last_fired {
+                Some(last_fired) if last_fired < report_time => {}
+                _ => {
+                    scheduled.last_fired = Some(report_time);
+                    return;
+                }
+            }
+            scheduled.last_fired = Some(report_time);
+            (scheduled.settings.clone(), report_time)
+        };
+        if !self.warm_up.is_complete() {
+            return;
+        }
+        let from = latest_rollover(&settings.time_zone, settings.time, report_time - Duration::seconds(1)).unwrap_or(report_time - Duration::days(1));
+        let reports: Vec<AccountReport> = self.accounts.iter()
+            .map(|account| account_report(account, &self.ledger_service.audit_trail(account), from, report_time))
+            .collect();
+        let risk_events = self.ledger_service.settings.take_risk_events(report_time);
+
+        let date = report_time.with_timezone(&settings.time_zone).date_naive().to_string();
+        let fingerprint = self.ledger_service.settings.fingerprint_hash();
+        let text = report_to_text(&date, &fingerprint, &reports, &risk_events);
+        let directory = Path::new(&settings.directory);
+        if let Err(e) = create_dir_all(directory) {
+            eprintln!("Daily Report: Failed to create {}: {}", settings.directory, e);
+        }
+        let file = directory.join(format!("daily_report_{}", date));
+        if let Err(e) = write(file.with_extension("txt"), &text) {
+            eprintln!("Daily Report: Failed to write the text report: {}", e);
+        }
+        if let Err(e) = write(file.with_extension("html"), report_to_html(&date, &fingerprint, &reports, &risk_events)) {
+            eprintln!("Daily Report: Failed to write the html report: {}", e);
+        }
+        if let Err(e) = self.strategy_event_sender.send(StrategyEvent::DailyReport(text)).await {
+            eprintln!("Daily Report: Failed to send event: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::strategies::ledgers::audit_trail::AuditEvent;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::broker_enum::Brokerage;
+    use crate::standardized_types::enums::{OrderSide, PositionSide};
+
+    fn entry(event: AuditEvent, hour: u32, symbol_code: &str, booked_pnl: Decimal) -> AuditEntry {
+        let time = Utc.with_ymd_and_hms(2024, 6, 3, hour, 0, 0).unwrap();
+        AuditEntry {
+            sequence: 0,
+            account: "Test".to_string(),
+            event,
+            order_id: Some(format!("order{}", hour)),
+            broker_order_id: None,
+            fill_id: None,
+            event_time: time.to_string(),
+            recorded_at: time.to_string(),
+            tag: "<Exit>".to_string(),
+            symbol_code: Some(symbol_code.to_string()),
+            fill_side: match event {
+                AuditEvent::BorrowFee => None,
+                _ => Some(OrderSide::Sell),
+            },
+            fill_quantity: match event {
+                AuditEvent::BorrowFee => None,
+                _ => Some(dec!(1)),
+            },
+            fill_price: Some(dec!(100)),
+            broker_fill_id: None,
+            liquidity: None,
+            commission: None,
+            side_before: PositionSide::Long,
+            quantity_before: dec!(1),
+            side_after: PositionSide::Flat,
+            quantity_after: dec!(0),
+            booked_pnl,
+            cash_available_before: dec!(0),
+            cash_available_after: dec!(0),
+            cash_used_before: dec!(0),
+            cash_used_after: dec!(0),
+            fingerprint: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_account_report() {
+        let account = Account::new(Brokerage::Test, "Test_Account_1".to_string());
+        let entries = vec![
+            // the day before
+            entry(AuditEvent::PaperFill, 1, "NQZ4", dec!(500)),
+            entry(AuditEvent::PaperFill, 9, "NQZ4", dec!(200)),
+            entry(AuditEvent::PaperExit, 10, "ESZ4", dec!(-150)),
+            entry(AuditEvent::BorrowFee, 11, "ESZ4", dec!(-10)),
+            entry(AuditEvent::PaperFill, 12, "NQZ4", dec!(-100)),
+            entry(AuditEvent::PaperFill, 13, "NQZ4", dec!(300)),
+        ];
+        let from = Utc.with_ymd_and_hms(2024, 6, 3, 2, 0, 0).unwrap();
+        let report = account_report(&account, &entries, from, from + Duration::days(1));
+
+        assert_eq!(report.fills.len(), 4);
+        assert_eq!(report.pnl_by_symbol, BTreeMap::from([("ESZ4".to_string(), dec!(-160)), ("NQZ4".to_string(), dec!(400))]));
+        assert_eq!(report.total_pnl, dec!(240));
+        // 200 down to -60
+        assert_eq!(report.max_drawdown, dec!(260));
+
+        let risk_events = vec![(from, "Flat by rule Close".to_string())];
+        let text = report_to_text("2024-06-03", "0123456789abcdef", &[report.clone()], &risk_events);
+        assert!(text.contains("Fingerprint: 0123456789abcdef"));
+        assert!(text.contains("Total PnL: 240, Max Drawdown: 260"));
+        assert!(text.contains("Risk Events: 1"));
+        let html = report_to_html("2024-06-03", "0123456789abcdef", &[report], &risk_events);
+        assert!(html.contains("<td>&lt;Exit&gt;</td>"));
+    }
+}
@@ -0,0 +1,222 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::RwLock;
+use std::time::Duration;
+use lazy_static::lazy_static;
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+use tokio::sync::broadcast::error::RecvError;
+use crate::strategies::strategy_events::StrategyEvent;
+use crate::strategies::custom_controls::control_to_json;
+
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+const MAX_REQUEST_HEAD: usize = 8192;
+
+lazy_static! {
+    static ref EVENT_TAIL_ADDRESS: RwLock<Option<SocketAddr>> = RwLock::new(None);
+}
+
+/// Streams the order, position and control events of the strategy as json at `http://{address}/events` and `http://{address}/events.jsonl`.
+/// Must be called before `FundForgeStrategy::initialize()`.
+pub fn serve_event_tail(address: SocketAddr) {
+    *EVENT_TAIL_ADDRESS.write().unwrap() = Some(address);
+}
+
+pub(crate) fn event_tail_address() -> Option<SocketAddr> {
+    *EVENT_TAIL_ADDRESS.read().unwrap()
+}
+
+/// Tails the strategy events as json for external dashboards, at `/events` as server sent events and `/events.jsonl` as newline delimited json.
+pub struct EventTailHandler {
+    sender: broadcast::Sender<String>,
+}
+
+impl EventTailHandler {
+    /// Binds the address and starts accepting dashboard connections.
+    pub async fn serve(address: SocketAddr) -> Result<Self, io::Error> {
+        let listener = TcpListener::bind(address).await?;
+        let (sender, _) = broadcast::channel(1000);
+        let client_sender = sender.clone();
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(handle_client(stream, client_sender.subscribe()));
+                    }
+                    Err(e) => eprintln!("Event Tail: Failed to accept connection: {}", e),
+                }
+            }
+        });
+        println!("Event Tail: Serving strategy events at http://{}/events", address);
+        Ok(EventTailHandler {
+            sender,
+        })
+    }
+
+    pub fn publish(&self, event: &StrategyEvent) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+        if let Some(json) = event_to_json(event) {
+            let _ = self.sender.send(json);
+        }
+    }
+
+    /// Returns a sender which publishes every event to the tail before forwarding it to the `strategy_event_sender`.
+    pub fn tap(self, strategy_event_sender: mpsc::Sender<StrategyEvent>) -> mpsc::Sender<StrategyEvent> {
+        let (tap_sender, mut tap_receiver) = mpsc::channel(strategy_event_sender.max_capacity());
+        tokio::spawn(async move {
+            while let Some(event) = tap_receiver.recv().await {
+                self.publish(&event);
+                if let Err(e) = strategy_event_sender.send(event).await {
+                    eprintln!("Event Tail: Failed to forward event: {}", e);
+                    break;
+                }
+            }
+        });
+        tap_sender
+    }
+}
+
+/// Returns None for events which are not forwarded to the tail.
+pub fn event_to_json(event: &StrategyEvent) -> Option<String> {
+    let event_value: Value = match event {
+        StrategyEvent::OrderEvents(order_event) => serde_json::to_value(order_event).ok()?,
+        StrategyEvent::PositionEvents(position_event) => serde_json::to_value(position_event).ok()?,
+        StrategyEvent::ShutdownEvent(message) => json!(message),
+        StrategyEvent::WarmUpComplete => Value::Null,
+        StrategyEvent::TimedEvent(name) => json!(name),
+        StrategyEvent::PriceTrigger(tag) => json!(tag),
+        StrategyEvent::SessionClose(name) => json!(name),
+        StrategyEvent::DailyRollover(name) => json!(name),
+        StrategyEvent::FlatBy(name) => json!(name),
+        StrategyEvent::StaleOrderCancelled(order_id, rule) => json!({"order_id": order_id, "rule": rule}),
+        StrategyEvent::HealthWarning(warning) => json!(warning.to_string()),
+        StrategyEvent::DailyReport(text) => json!(text),
+        StrategyEvent::BacktestProgress(progress) => serde_json::to_value(progress).ok()?,
+        StrategyEvent::AccountUpdate(summary) => serde_json::to_value(summary).ok()?,
+        StrategyEvent::WarmUpWarning(warning) => json!({
+            "subscription": warning.subscription.to_string(),
+            "primary_subscription": warning.primary_subscription.to_string(),
+            "bars_checked": warning.bars_checked,
+            "missing_bars": warning.missing_bars,
+            "unexpected_bars": warning.unexpected_bars,
+            "mismatched_bars": warning.mismatched_bars,
+            "first_problem": warning.first_problem,
+        }),
+        StrategyEvent::StrategyControls(control) => control_to_json(control).unwrap_or_else(|| json!(format!("{:?}", control))),
+        StrategyEvent::DataSubscriptionEvent(_)
+        | StrategyEvent::DrawingToolEvents(_)
+        | StrategyEvent::TimeSlice(_)
+        | StrategyEvent::IndicatorEvent(_) => return None,
+    };
+    Some(json!({"type": format!("{:?}", event.get_type()), "event": event_value}).to_string())
+}
+
+enum TailFormat {
+    ServerSentEvents,
+    JsonLines,
+}
+
+async fn handle_client(mut stream: TcpStream, mut receiver: broadcast::Receiver<String>) {
+    let format = match read_request(&mut stream).await {
+        Some(format) => format,
+        None => return,
+    };
+    let content_type = match format {
+        TailFormat::ServerSentEvents => "text/event-stream",
+        TailFormat::JsonLines => "application/x-ndjson",
+    };
+    let header = format!("HTTP/1.1 200 OK\r\nContent-Type: {}\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\nAccess-Control-Allow-Origin: *\r\n\r\n", content_type);
+    if stream.write_all(header.as_bytes()).await.is_err() {
+        return;
+    }
+
+    let mut keep_alive = tokio::time::interval(KEEP_ALIVE);
+    loop {
+        let message = tokio::select! {
+            event = receiver.recv() => match (event, &format) {
+                (Ok(json), TailFormat::ServerSentEvents) => format!("data: {}\n\n", json),
+                (Ok(json), TailFormat::JsonLines) => format!("{}\n", json),
+                (Err(RecvError::Lagged(skipped)), TailFormat::ServerSentEvents) => format!(": skipped {} events\n\n", skipped),
+                (Err(RecvError::Lagged(_)), TailFormat::JsonLines) => continue,
+                (Err(RecvError::Closed), _) => return,
+            },
+            _ = keep_alive.tick() => match format {
+                TailFormat::ServerSentEvents => ": keep-alive\n\n".to_string(),
+                TailFormat::JsonLines => continue,
+            },
+        };
+        if stream.write_all(message.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Reads the request head and returns the tail format, responds with an error and returns None for anything other than a GET of a tail path.
+async fn read_request(stream: &mut TcpStream) -> Option<TailFormat> {
+    let mut head = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD {
+            return None;
+        }
+        match stream.read(&mut buffer).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => head.extend_from_slice(&buffer[..n]),
+        }
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let (method, path) = (request_line.next().unwrap_or_default(), request_line.next().unwrap_or_default());
+    let path = path.split('?').next().unwrap_or_default();
+
+    let response = match (method, path) {
+        ("GET", "/events") => return Some(TailFormat::ServerSentEvents),
+        ("GET", "/events.jsonl") => return Some(TailFormat::JsonLines),
+        ("GET", _) => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        _ => "HTTP/1.1 405 Method Not Allowed\r\nAllow: GET\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+    };
+    let _ = stream.write_all(response.as_bytes()).await;
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::accounts::Account;
+    use crate::standardized_types::broker_enum::Brokerage;
+    use crate::standardized_types::enums::{Liquidity, OrderSide};
+    use crate::standardized_types::orders::OrderUpdateEvent;
+    use crate::standardized_types::time_slices::TimeSlice;
+
+    #[test]
+    fn test_event_to_json() {
+        let event = StrategyEvent::OrderEvents(OrderUpdateEvent::OrderFilled {
+            account: Account::new(Brokerage::Test, "Test_Account_1".to_string()),
+            symbol_name: "NQ".to_string(),
+            symbol_code: "NQZ4".to_string(),
+            order_id: "1".to_string(),
+            side: OrderSide::Buy,
+            price: dec!(100.25),
+            quantity: dec!(2),
+            tag: "Entry".to_string(),
+            time: "2024-01-01 00:00:00 UTC".to_string(),
+            fill_id: Some("A1".to_string()),
+            liquidity: Some(Liquidity::Maker),
+            commission: Some(dec!(0.85)),
+        });
+        let json: Value = serde_json::from_str(&event_to_json(&event).unwrap()).unwrap();
+        assert_eq!(json["type"], "OrderEvents");
+        assert_eq!(json["event"]["OrderFilled"]["symbol_code"], "NQZ4");
+        assert_eq!(json["event"]["OrderFilled"]["fill_id"], "A1");
+        assert_eq!(json["event"]["OrderFilled"]["liquidity"], "Maker");
+
+        assert!(event_to_json(&StrategyEvent::TimeSlice(TimeSlice::new())).is_none());
+        let json: Value = serde_json::from_str(&event_to_json(&StrategyEvent::WarmUpComplete).unwrap()).unwrap();
+        assert_eq!(json["type"], "WarmUpComplete");
+    }
+}
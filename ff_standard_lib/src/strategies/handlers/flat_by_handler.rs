@@ -0,0 +1,202 @@
+use std::sync::{Arc, RwLock};
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use dashmap::DashMap;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+use crate::standardized_types::accounts::Account;
+use crate::standardized_types::enums::PositionSide;
+use crate::standardized_types::orders::{Order, OrderId, OrderRequest, OrderType};
+use crate::standardized_types::subscriptions::SymbolName;
+use crate::strategies::historical_time::WarmUpState;
+use crate::strategies::handlers::timed_events_handler::latest_rollover;
+use crate::strategies::ledgers::ledger_service::LedgerService;
+use crate::strategies::strategy_events::StrategyEvent;
+
+/// Flattens positions and cancels orders at a time of day, see `strategy.flat_by()`.
+/// # Properties
+/// * `name` - The rule name, sent with `StrategyEvent::FlatBy`.
+/// * `time_zone` - The time zone of the `time`, eg the exchange time zone.
+/// * `time` - The time of day to be flat by.
+/// * `account` - The account to flatten, None for all the strategy accounts.
+/// * `symbol_name` - The symbol to flatten, None for every symbol on the account.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlatByRule {
+    pub name: String,
+    pub time_zone: Tz,
+    pub time: NaiveTime,
+    pub account: Option<Account>,
+    pub symbol_name: Option<SymbolName>,
+}
+
+impl FlatByRule {
+    pub fn new(name: String, time_zone: Tz, time: NaiveTime, account: Option<Account>, symbol_name: Option<SymbolName>) -> Self {
+        FlatByRule {
+            name,
+            time_zone,
+            time,
+            account,
+            symbol_name,
+        }
+    }
+
+    fn applies_to(&self, account: &Account, symbol_name: &SymbolName) -> bool {
+        self.account.as_ref().map_or(true, |rule_account| rule_account == account)
+            && self.symbol_name.as_ref().map_or(true, |rule_symbol| rule_symbol == symbol_name)
+    }
+}
+
+struct ScheduledRule {
+    rule: FlatByRule,
+    last_fired: Option<DateTime<Utc>>,
+}
+
+impl ScheduledRule {
+    /// Returns the flat time the rule is due for at the current time, if any.
+    fn due(&mut self, current_time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let flat_time = latest_rollover(&self.rule.time_zone, self.rule.time, current_time)?;
+        match self.last_fired {
+            None => {
+                self.last_fired = Some(flat_time);
+                None
+            }
+            Some(last_fired) if last_fired >= flat_time => None,
+            Some(_) => {
+                self.last_fired = Some(flat_time);
+                Some(flat_time)
+            }
+        }
+    }
+}
+
+pub(crate) struct FlatByHandler {
+    rules: RwLock<Vec<ScheduledRule>>,
+    accounts: Vec<Account>,
+    open_order_cache: Arc<DashMap<OrderId, Order>>,
+    ledger_service: Arc<LedgerService>,
+    order_sender: mpsc::Sender<OrderRequest>,
+    strategy_event_sender: mpsc::Sender<StrategyEvent>,
+    warm_up: WarmUpState,
+}
+
+impl FlatByHandler {
+    pub(crate) fn new(
+        accounts: Vec<Account>,
+        open_order_cache: Arc<DashMap<OrderId, Order>>,
+        ledger_service: Arc<LedgerService>,
+        order_sender: mpsc::Sender<OrderRequest>,
+        strategy_event_sender: mpsc::Sender<StrategyEvent>,
+        warm_up: WarmUpState,
+    ) -> Self {
+        FlatByHandler {
+            rules: RwLock::new(vec![]),
+            accounts,
+            open_order_cache,
+            ledger_service,
+            order_sender,
+            strategy_event_sender,
+            warm_up,
+        }
+    }
+
+    /// Adding a rule with an existing name replaces the old rule.
+    pub(crate) fn add_rule(&self, rule: FlatByRule) {
+        let mut rules = self.rules.write().unwrap();
+        rules.retain(|scheduled| scheduled.rule.name != rule.name);
+        rules.push(ScheduledRule { rule, last_fired: None });
+    }
+
+    pub(crate) fn remove_rule(&self, name: &str) {
+        self.rules.write().unwrap().retain(|scheduled| scheduled.rule.name != name);
+    }
+
+    pub(crate) async fn update_time(&self, current_time: DateTime<Utc>) {
+        let due_rules: Vec<FlatByRule> = {
+            let mut rules = self.rules.write().unwrap();
+            rules.iter_mut()
+                .filter_map(|scheduled| scheduled.due(current_time).map(|_| scheduled.rule.clone()))
+                .collect()
+        };
+        if due_rules.is_empty() || !self.warm_up.is_complete() {
+            return;
+        }
+        for rule in due_rules {
+            self.flatten_now(&rule, current_time).await;
+            self.ledger_service.settings.record_risk_event(current_time, format!("Flat by rule {}", rule.name));
+            if let Err(e) = self.strategy_event_sender.send(StrategyEvent::FlatBy(rule.name.clone())).await {
+                eprintln!("Flat By: Failed to send event: {}", e);
+            }
+        }
+    }
+
+    /// Cancels the working orders and exits the open positions in the scope of the rule, without sending `StrategyEvent::FlatBy`.
+    pub(crate) async fn flatten_now(&self, rule: &FlatByRule, time: DateTime<Utc>) {
+        for request in self.flatten_requests(rule, time) {
+            if let Err(e) = self.order_sender.send(request).await {
+                eprintln!("Flat By: Failed to send order request: {}", e);
+            }
+        }
+    }
+
+    /// The cancels for the working orders in the scope of the rule, then the exits for the open positions.
+    fn flatten_requests(&self, rule: &FlatByRule, time: DateTime<Utc>) -> Vec<OrderRequest> {
+        let mut requests = vec![];
+        for order in self.open_order_cache.iter() {
+            if rule.applies_to(&order.account, &order.symbol_name) {
+                requests.push(OrderRequest::Cancel { account: order.account.clone(), order_id: order.id.clone() });
+            }
+        }
+        for account in self.accounts.iter().filter(|account| rule.account.as_ref().map_or(true, |rule_account| rule_account == *account)) {
+            for position in self.ledger_service.open_positions(account) {
+                if position.is_closed || !rule.applies_to(account, &position.symbol_name) {
+                    continue;
+                }
+                let tag = format!("Flat By: {}", rule.name);
+                let order_id = Uuid::new_v4().to_string();
+                let (order, order_type) = match position.side {
+                    PositionSide::Long => (Order::exit_long(position.symbol_name.clone(), Some(position.symbol_code.clone()), account, position.quantity_open, tag, order_id, time, None), OrderType::ExitLong),
+                    PositionSide::Short => (Order::exit_short(position.symbol_name.clone(), Some(position.symbol_code.clone()), account, position.quantity_open, tag, order_id, time, None), OrderType::ExitShort),
+                    PositionSide::Flat => continue,
+                };
+                requests.push(OrderRequest::Create { account: account.clone(), order, order_type });
+            }
+        }
+        requests
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use chrono_tz::America::New_York;
+
+    #[test]
+    fn test_flat_by_rule_fires_once_a_day() {
+        let time = NaiveTime::from_hms_opt(15, 55, 0).unwrap();
+        let mut scheduled = ScheduledRule { rule: FlatByRule::new("Close".to_string(), New_York, time, None, None), last_fired: None };
+        // added after the flat time, the first flat is tomorrow
+        assert_eq!(scheduled.due(New_York.with_ymd_and_hms(2024, 6, 3, 16, 0, 0).unwrap().to_utc()), None);
+        assert_eq!(scheduled.due(New_York.with_ymd_and_hms(2024, 6, 4, 15, 54, 59).unwrap().to_utc()), None);
+
+        let flat_time = New_York.with_ymd_and_hms(2024, 6, 4, 15, 55, 0).unwrap().to_utc();
+        assert_eq!(scheduled.due(flat_time + Duration::seconds(1)), Some(flat_time));
+        assert_eq!(scheduled.due(flat_time + Duration::minutes(1)), None);
+        assert_eq!(scheduled.due(flat_time + Duration::days(1)), Some(flat_time + Duration::days(1)));
+    }
+
+    #[test]
+    fn test_flat_by_rule_scope() {
+        use crate::standardized_types::broker_enum::Brokerage;
+        let account = Account::new(Brokerage::Test, "Test_Account_1".to_string());
+        let other_account = Account::new(Brokerage::Test, "Test_Account_2".to_string());
+        let time = NaiveTime::from_hms_opt(15, 55, 0).unwrap();
+        let symbol_rule = FlatByRule::new("MNQ".to_string(), New_York, time, Some(account.clone()), Some("MNQ".to_string()));
+        assert!(symbol_rule.applies_to(&account, &"MNQ".to_string()));
+        assert!(!symbol_rule.applies_to(&account, &"MES".to_string()));
+        assert!(!symbol_rule.applies_to(&other_account, &"MNQ".to_string()));
+
+        let all_rule = FlatByRule::new("All".to_string(), New_York, time, None, None);
+        assert!(all_rule.applies_to(&other_account, &"MES".to_string()));
+    }
+}
@@ -1,28 +1,34 @@
 use std::collections::BTreeMap;
-use std::ops::Deref;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock as StdRwLock};
+use crate::standardized_types::bar_time::BarTimeConvention;
 use crate::standardized_types::enums::StrategyMode;
 use crate::standardized_types::rolling_window::RollingWindow;
-use crate::standardized_types::subscriptions::DataSubscription;
+use crate::standardized_types::subscriptions::{DataSubscription, SymbolName};
 use crate::standardized_types::time_slices::TimeSlice;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use dashmap::DashMap;
 use crate::strategies::consolidators::consolidator_enum::ConsolidatorEnum;
 use crate::strategies::indicators::indicator_events::IndicatorEvents;
 use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
+use crate::strategies::indicators::indicator_state::IndicatorState;
 use crate::strategies::indicators::indicator_values::IndicatorValues;
-use crate::strategies::client_features::server_connections::is_warmup_complete;
 use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
 use crate::standardized_types::base_data::base_data_type::BaseDataType;
 use crate::standardized_types::base_data::traits::BaseData;
 use crate::standardized_types::market_hours::TradingHours;
 use crate::strategies::handlers::subscription_handler::SubscriptionHandler;
+use crate::messages::data_server_messaging::DataServerRequest;
+use crate::strategies::client_features::connection_types::ConnectionType;
+use crate::strategies::client_features::request_handler::{send_request, StrategyRequest};
 
 pub struct IndicatorHandler {
     indicators: Arc<DashMap<DataSubscription, DashMap<IndicatorName, Box<dyn Indicators>>>>,
     strategy_mode: StrategyMode,
     subscription_map: DashMap<IndicatorName, DataSubscription>, //used to quickly find the subscription of an indicator by name.
     subscription_handler: Arc<SubscriptionHandler>,
+    restored_until: DashMap<IndicatorName, DateTime<Utc>>, // indicators restored from a saved state ignore warm up data they have already seen.
+    secondary_map: DashMap<DataSubscription, Vec<IndicatorName>>, // the indicators which also use a subscription, see `Indicators::secondary_subscriptions()`.
+    state_max_staleness: StdRwLock<Duration>, // saved indicator states older than this are discarded.
 }
 
 impl IndicatorHandler {
@@ -32,11 +38,32 @@ impl IndicatorHandler {
             strategy_mode,
             subscription_map: Default::default(),
             subscription_handler,
+            restored_until: Default::default(),
+            secondary_map: Default::default(),
+            state_max_staleness: StdRwLock::new(Duration::days(3)),
         };
         handler
     }
 
+    /// The estimated bytes of the history retained by the indicators, the history of each indicator is cloned so this is only for profiling.
+    pub(crate) fn estimated_memory(&self) -> usize {
+        self.indicators.iter()
+            .flat_map(|map| map.iter().map(|indicator| indicator.history().len()).collect::<Vec<_>>())
+            .sum::<usize>() * std::mem::size_of::<IndicatorValues>()
+    }
+
     pub async fn add_indicator(&self, indicator: Box<dyn Indicators>, time: DateTime<Utc>, market_hours: Option<TradingHours>) -> IndicatorEvents {
+        self.add_indicator_from_state(indicator, time, market_hours, None).await
+    }
+
+    /// Sets how old a saved indicator state can be and still be restored, 3 days by default so a strategy stopped over the weekend restores its indicators.
+    pub fn set_state_max_staleness(&self, max_staleness: Duration) {
+        *self.state_max_staleness.write().unwrap() = max_staleness;
+    }
+
+    /// Restores the indicator from the saved state if it is fresh and replays the bars closed since the state was saved,
+    /// otherwise the indicator is warmed up from history as normal.
+    pub async fn add_indicator_from_state(&self, mut indicator: Box<dyn Indicators>, time: DateTime<Utc>, market_hours: Option<TradingHours>, saved_state: Option<&IndicatorState>) -> IndicatorEvents {
         let subscription = indicator.subscription().clone();
 
         if !self.indicators.contains_key(&subscription) {
@@ -45,7 +72,33 @@ impl IndicatorHandler {
 
         let name = indicator.name().clone();
 
-        let indicator = match is_warmup_complete() {
+        let max_staleness = *self.state_max_staleness.read().unwrap();
+        let restored = match saved_state {
+            Some(state) if state.is_fresh(&name, &subscription, time, max_staleness) => match (indicator.restore_state(&state.state), state.last_update_utc()) {
+                (true, Some(last_update)) => {
+                    let mut restored_until = last_update;
+                    // once warmed up the strategy will not see the bars closed while it was stopped, so they are replayed from history
+                    if self.subscription_handler.warm_up().is_complete() {
+                        for data in replay_data(last_update, time, self.strategy_mode.clone(), indicator.as_ref(), &self.subscription_handler, market_hours.clone()).await {
+                            restored_until = data.time_closed_utc();
+                            let _ = indicator.update_base_data(&data);
+                        }
+                    }
+                    self.restored_until.insert(name.clone(), restored_until);
+                    true
+                }
+                _ => {
+                    indicator.reset();
+                    false
+                }
+            },
+            _ => false,
+        };
+        if !restored {
+            self.restored_until.remove(&name);
+        }
+
+        let indicator = match self.subscription_handler.warm_up().is_complete() && !restored {
             true => warmup(time, self.strategy_mode.clone(), indicator, self.subscription_handler.clone(), market_hours).await,
             false => indicator,
         };
@@ -56,6 +109,18 @@ impl IndicatorHandler {
            IndicatorEvents::Replaced(name.clone())
         };
 
+        self.remove_secondary(&name);
+        for secondary in indicator.secondary_subscriptions() {
+            self.secondary_map.entry(secondary).or_default().push(name.clone());
+        }
+
+        if let IndicatorEvents::Replaced(_) = event {
+            self.unpublish(&name).await;
+        }
+        let mut history = indicator.history().history;
+        history.reverse();
+        self.publish(history).await;
+
         if let Some(map) = self.indicators.get(&subscription) {
             map.insert(indicator.name(), indicator);
         }
@@ -64,27 +129,81 @@ impl IndicatorHandler {
         event
     }
 
+    /// Publishes the values, oldest first, to the data server for the gui charts, see `DataServerRequest::PublishIndicatorValues`. Backtests do not publish.
+    async fn publish(&self, values: Vec<IndicatorValues>) {
+        if self.strategy_mode == StrategyMode::Backtest || values.is_empty() {
+            return;
+        }
+        send_request(StrategyRequest::OneWay(ConnectionType::Default, DataServerRequest::PublishIndicatorValues { values })).await;
+    }
+
+    async fn unpublish(&self, indicator_name: &IndicatorName) {
+        if self.strategy_mode == StrategyMode::Backtest {
+            return;
+        }
+        send_request(StrategyRequest::OneWay(ConnectionType::Default, DataServerRequest::RemoveIndicatorStream { indicator_name: indicator_name.clone() })).await;
+    }
+
     pub async fn remove_indicator(&self, indicator_name: &IndicatorName) -> Option<IndicatorEvents>  {
         if let Some(subscription) = self.subscription_map.get(indicator_name) {
             if let Some(map) = self.indicators.get(&subscription.value()) {
                 map.remove(indicator_name);
             }
         }
+        self.restored_until.remove(indicator_name);
+        self.remove_secondary(indicator_name);
         match self.subscription_map.remove(indicator_name) {
             None => None,
-            Some(_) => Some(IndicatorEvents::IndicatorRemoved(indicator_name.clone()))
+            Some(_) => {
+                self.unpublish(indicator_name).await;
+                Some(IndicatorEvents::IndicatorRemoved(indicator_name.clone()))
+            }
         }
     }
 
+    fn remove_secondary(&self, indicator_name: &IndicatorName) {
+        self.secondary_map.retain(|_, names| {
+            names.retain(|name| name != indicator_name);
+            !names.is_empty()
+        });
+    }
+
     pub async fn indicators_unsubscribe_subscription(&self, subscription: &DataSubscription) {
         self.indicators.remove(subscription);
-        for sub in self.subscription_map.iter() {
-            if sub.value() == subscription {
-                self.subscription_map.remove(sub.key());
+        let names: Vec<IndicatorName> = self.subscription_map.iter()
+            .filter(|entry| entry.value() == subscription)
+            .map(|entry| entry.key().clone())
+            .collect();
+        for name in names {
+            self.subscription_map.remove(&name);
+            self.remove_secondary(&name);
+            self.unpublish(&name).await;
+        }
+        // indicators comparing the subscription with another can not update without it
+        if let Some((_, names)) = self.secondary_map.remove(subscription) {
+            for name in names {
+                self.remove_indicator(&name).await;
             }
         }
     }
 
+    /// Updates the indicator unless it was restored from a saved state which already includes the data.
+    fn update_indicator(&self, name: &IndicatorName, indicator: &mut Box<dyn Indicators>, data: &BaseDataEnum, results: &mut BTreeMap<IndicatorName, Vec<IndicatorValues>>) {
+        if !self.restored_until.is_empty() {
+            if let Some(restored_until) = self.restored_until.get(name).map(|time| *time.value()) {
+                if data.time_closed_utc() <= restored_until {
+                    return;
+                }
+                self.restored_until.remove(name);
+            }
+        }
+        if let Some(indicator_data) = indicator.update_base_data(data) {
+            results.entry(name.clone())
+                .or_insert_with(Vec::new)
+                .extend(indicator_data);
+        }
+    }
+
     pub async fn update_time_slice(&self, time_slice: &TimeSlice) -> Option<IndicatorEvents> {
         let mut results: BTreeMap<IndicatorName, Vec<IndicatorValues>> = BTreeMap::new();
         let indicators = self.indicators.clone();
@@ -93,10 +212,25 @@ impl IndicatorHandler {
             let subscription = data.subscription();
             if let Some(indicators_by_sub) = indicators.get_mut(&subscription) {
                 for mut indicators_dash_map in indicators_by_sub.iter_mut() {
-                    if let Some(indicator_data) = indicators_dash_map.value_mut().update_base_data(data) {
-                        results.entry(indicators_dash_map.key().clone())
-                            .or_insert_with(Vec::new)
-                            .extend(indicator_data);
+                    let (name, indicator) = indicators_dash_map.pair_mut();
+                    self.update_indicator(name, indicator, data, &mut results);
+                }
+            }
+            if self.secondary_map.is_empty() {
+                continue;
+            }
+            let names = match self.secondary_map.get(&subscription) {
+                Some(names) => names.value().clone(),
+                None => continue,
+            };
+            for name in names {
+                let primary_subscription = match self.subscription_map.get(&name) {
+                    Some(sub) => sub.value().clone(),
+                    None => continue,
+                };
+                if let Some(indicators_by_sub) = indicators.get(&primary_subscription) {
+                    if let Some(mut indicator) = indicators_by_sub.get_mut(&name) {
+                        self.update_indicator(&name, indicator.value_mut(), data, &mut results);
                     }
                 }
             }
@@ -104,11 +238,35 @@ impl IndicatorHandler {
 
         if !results.is_empty() {
             let results_vec: Vec<IndicatorValues> = results.into_values().flatten().collect();
+            self.publish(results_vec.clone()).await;
             return Some(IndicatorEvents::IndicatorTimeSlice(results_vec))
         }
         None
     }
 
+    /// Resets every indicator to its unwarmed state, eg before the next regime of a backtest.
+    pub(crate) fn reset_indicators(&self) {
+        for indicators_by_sub in self.indicators.iter() {
+            for mut indicator in indicators_by_sub.value().iter_mut() {
+                indicator.value_mut().reset();
+            }
+        }
+        self.restored_until.clear();
+    }
+
+    /// Returns the saved state of every indicator which supports cached state and has produced a value.
+    pub fn save_states(&self) -> Vec<IndicatorState> {
+        let mut states = vec![];
+        for indicators_by_sub in self.indicators.iter() {
+            for indicator in indicators_by_sub.value().iter() {
+                if let Some(state) = IndicatorState::from_indicator(indicator.value().as_ref()) {
+                    states.push(state);
+                }
+            }
+        }
+        states
+    }
+
     pub fn history(&self, name: &IndicatorName) -> Option<RollingWindow<IndicatorValues>> {
         let subscription = match self.subscription_map.get(name) {
             Some(sub) => sub.clone(),
@@ -126,6 +284,39 @@ impl IndicatorHandler {
         None
     }
 
+    /// Returns the indicator history between `from` and `to` (inclusive), keyed by the `IndicatorValues` time in the bar time convention.
+    pub fn history_range(&self, name: &IndicatorName, from: DateTime<Utc>, to: DateTime<Utc>, convention: BarTimeConvention) -> BTreeMap<DateTime<Utc>, IndicatorValues> {
+        let mut range = BTreeMap::new();
+        if let Some(history) = self.history(name) {
+            for values in history.history {
+                let time = convention.time_of_values(&values);
+                if time >= from && time <= to {
+                    range.insert(time, values);
+                }
+            }
+        }
+        range
+    }
+
+    /// Returns the most recent historical value with a time at or before `time`.
+    pub fn latest_at(&self, name: &IndicatorName, time: DateTime<Utc>) -> Option<IndicatorValues> {
+        let history = self.history(name)?;
+        history.history.into_iter()
+            .filter(|values| values.time_utc() <= time)
+            .max_by_key(|values| values.time_utc())
+    }
+
+    /// Returns the latest value at or before `time` for every indicator subscribed to the symbol.
+    pub fn latest_at_for_symbol(&self, symbol_name: &SymbolName, time: DateTime<Utc>) -> BTreeMap<IndicatorName, IndicatorValues> {
+        let names: Vec<IndicatorName> = self.subscription_map.iter()
+            .filter(|entry| &entry.value().symbol.name == symbol_name)
+            .map(|entry| entry.key().clone())
+            .collect();
+        names.into_iter()
+            .filter_map(|name| self.latest_at(&name, time).map(|values| (name, values)))
+            .collect()
+    }
+
     pub fn current(&self, name: &IndicatorName) -> Option<IndicatorValues> {
         let subscription = match self.subscription_map.get(name) {
             Some(sub) => sub.clone(),
@@ -163,64 +354,98 @@ async fn warmup( //todo make async task version for live mode
     to_time: DateTime<Utc>,
     strategy_mode: StrategyMode,
     mut indicator: Box<dyn Indicators>,
-     subscription_handler: Arc<SubscriptionHandler>,
-     market_hours: Option<TradingHours>,
+    subscription_handler: Arc<SubscriptionHandler>,
+    market_hours: Option<TradingHours>,
 ) -> Box<dyn Indicators> {
-   //1. Check if we have history for the indicator.subscription
-    let subscription =  indicator.subscription();
+    let data_required = indicator.data_required_warmup();
+    let mut history = warmup_data(to_time, strategy_mode.clone(), indicator.subscription(), data_required, &subscription_handler, market_hours.clone()).await;
+    let secondary_subscriptions = indicator.secondary_subscriptions();
+    if !secondary_subscriptions.is_empty() {
+        for subscription in &secondary_subscriptions {
+            history.extend(warmup_data(to_time, strategy_mode.clone(), subscription, data_required, &subscription_handler, market_hours.clone()).await);
+        }
+        // the indicator sees the subscriptions in the order the data closed, as it would have live
+        history.sort_by_key(|data| data.time_closed_utc());
+    }
+    let _ = indicator.recalc(&history);
+    indicator
+}
+
+/// The data of the indicator subscriptions which closed after `from_time`, oldest first, to bring a restored indicator up to `to_time`.
+async fn replay_data(
+    from_time: DateTime<Utc>,
+    to_time: DateTime<Utc>,
+    strategy_mode: StrategyMode,
+    indicator: &dyn Indicators,
+    subscription_handler: &SubscriptionHandler,
+    market_hours: Option<TradingHours>,
+) -> Vec<BaseDataEnum> {
+    let mut subscriptions = vec![indicator.subscription().clone()];
+    subscriptions.extend(indicator.secondary_subscriptions());
+    let mut replay = vec![];
+    for subscription in &subscriptions {
+        let resolution = subscription.resolution.as_duration();
+        if resolution <= Duration::zero() {
+            continue;
+        }
+        let bars = ((to_time - from_time).num_seconds() / resolution.num_seconds().max(1)) as u64 + 1;
+        let data = warmup_data(to_time, strategy_mode.clone(), subscription, bars, subscription_handler, market_hours.clone()).await;
+        replay.extend(data.into_iter().filter(|data| data.time_closed_utc() > from_time && data.time_closed_utc() <= to_time));
+    }
+    replay.sort_by_key(|data| data.time_closed_utc());
+    replay
+}
+
+/// The history to warm up an indicator on, from the strategy history if it has enough data, otherwise consolidated from the historical data.
+async fn warmup_data(
+    to_time: DateTime<Utc>,
+    strategy_mode: StrategyMode,
+    subscription: &DataSubscription,
+    data_required: u64,
+    subscription_handler: &SubscriptionHandler,
+    market_hours: Option<TradingHours>,
+) -> Vec<BaseDataEnum> {
+   //1. Check if we have history for the subscription
     match subscription.base_data_type {
         BaseDataType::Ticks => {
-            if let Some(history) = subscription_handler.tick_history(&subscription) {
-                if history.len() >= indicator.data_required_warmup() as usize {
-                    for data in history.history {
-                        let base_data = BaseDataEnum::Tick(data);
-                        indicator.update_base_data(&base_data);
-                    }
-                    return indicator
+            if let Some(history) = subscription_handler.tick_history(subscription) {
+                if history.len() >= data_required as usize {
+                    return history.history.into_iter().map(BaseDataEnum::Tick).collect()
                 }
             }
         }
         BaseDataType::Quotes => {
-            if let Some(history) = subscription_handler.quote_history(&subscription) {
-                if history.len() >= indicator.data_required_warmup() as usize {
-                    for data in history.history {
-                        let base_data = BaseDataEnum::Quote(data);
-                        indicator.update_base_data(&base_data);
-                    }
-                    return indicator
+            if let Some(history) = subscription_handler.quote_history(subscription) {
+                if history.len() >= data_required as usize {
+                    return history.history.into_iter().map(BaseDataEnum::Quote).collect()
                 }
             }
         }
         BaseDataType::QuoteBars => {
-            if let Some(history) = subscription_handler.bar_history(&subscription) {
-                if history.len() >= indicator.data_required_warmup() as usize {
-                    for data in history.history {
-                        let base_data = BaseDataEnum::QuoteBar(data);
-                        indicator.update_base_data(&base_data);
-                    }
-                    return indicator
+            if let Some(history) = subscription_handler.bar_history(subscription) {
+                if history.len() >= data_required as usize {
+                    return history.history.into_iter().map(BaseDataEnum::QuoteBar).collect()
                 }
 
             }
         }
         BaseDataType::Candles => {
-            if let Some(history) = subscription_handler.candle_history(&subscription) {
-                if history.len() >= indicator.data_required_warmup() as usize {
-                    for data in history.history {
-                        let base_data = BaseDataEnum::Candle(data);
-                        indicator.update_base_data(&base_data);
-                    }
-                    return indicator
+            if let Some(history) = subscription_handler.candle_history(subscription) {
+                if history.len() >= data_required as usize {
+                    return history.history.into_iter().map(BaseDataEnum::Candle).collect()
                 }
             }
         }
+        BaseDataType::Depth => {
+            // depth can not be consolidated, the indicator warms up on the history that is available
+            return match subscription_handler.depth_history(subscription) {
+                Some(history) => history.history.into_iter().map(BaseDataEnum::Depth).collect(),
+                None => vec![],
+            }
+        }
         _ => {}
     }
-    let _ = subscription_handler.deref();
     let consolidator = ConsolidatorEnum::create_consolidator(subscription.clone(), false, market_hours).await;
-    let (_, window) = ConsolidatorEnum::warmup(consolidator, to_time, (indicator.data_required_warmup() + 1) as i32, strategy_mode).await;
-    for data in window.history {
-        let _ = indicator.update_base_data(&data);
-    }
-    indicator
+    let (_, window) = ConsolidatorEnum::warmup(consolidator, to_time, (data_required + 1) as i32, strategy_mode).await;
+    window.history
 }
@@ -1,24 +1,84 @@
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use chrono::{DateTime, NaiveTime, TimeZone, Utc};
 use lazy_static::lazy_static;
-use tokio::sync::broadcast;
 use tokio::sync::mpsc::Sender;
-use crate::standardized_types::base_data::history::{get_compressed_historical_data};
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::base_data::history::{get_compressed_historical_data, get_recent_live_data};
+use crate::standardized_types::subscriptions::DataSubscription;
 use crate::standardized_types::time_slices::TimeSlice;
+use crate::strategies::consolidators::warmup_check::check_warmup;
 use crate::strategies::handlers::indicator_handler::IndicatorHandler;
+use crate::strategies::handlers::market_handler::aggressor_classifier::classify_aggressors;
 use crate::strategies::handlers::market_handler::price_service::MarketPriceService;
+use crate::strategies::handlers::price_trigger_handler::PriceTriggerHandler;
 use crate::strategies::handlers::subscription_handler::SubscriptionHandler;
 use crate::strategies::handlers::timed_events_handler::TimedEventHandler;
-use crate::strategies::historical_time::update_backtest_time;
 use crate::strategies::ledgers::ledger_service::LedgerService;
 use crate::strategies::strategy_events::StrategyEvent;
 
+/// Where a live strategy gets the data to warm up on for the current day.
+/// The data servers only write the recorded live data to the historical store at each flush, so the last minutes before the strategy started
+/// are missing from the store until the next flush.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WarmUpSource {
+    /// Warm up only on the historical store.
+    Historical,
+    /// Warm up on the historical store, then fill the gap to the start of the live feed with the recent data the servers recorded from their live streams.
+    /// The servers must run with `--record_live 1`, subscriptions which are not recorded warm up on the historical store only.
+    RecordedLive,
+}
+
 lazy_static! {
-    pub(crate) static ref WARMUP_COMPLETE_BROADCASTER: broadcast::Sender<DateTime<Utc>> = {
-        let (tx, _) = broadcast::channel(1);
-        tx
+    static ref LIVE_WARM_UP_SOURCE: RwLock<WarmUpSource> = RwLock::new(WarmUpSource::Historical);
+}
+
+/// Sets where live strategies warm up on the current day, the default is `WarmUpSource::Historical`. Must be called before `FundForgeStrategy::initialize()`.
+pub fn set_live_warm_up_source(source: WarmUpSource) {
+    *LIVE_WARM_UP_SOURCE.write().unwrap() = source;
+}
+
+pub(crate) fn live_warm_up_source() -> WarmUpSource {
+    *LIVE_WARM_UP_SOURCE.read().unwrap()
+}
+
+/// Adds the recent live data after the last historical data of each subscription to the time slices.
+async fn splice_recent_live_data(
+    subscriptions: &Vec<DataSubscription>,
+    time_slices: &mut BTreeMap<i64, TimeSlice>,
+    from_time: DateTime<Utc>,
+) {
+    let mut last_history: HashMap<DataSubscription, i64> = HashMap::new();
+    for (time, slice) in time_slices.iter() {
+        for data in slice.iter() {
+            last_history.insert(data.subscription(), *time);
+        }
+    }
+
+    let recent_data = match get_recent_live_data(subscriptions.clone(), from_time).await {
+        Ok(recent_data) => recent_data,
+        Err(e) => {
+            eprintln!("Live Warmup: Failed to get recent live data: {}", e);
+            return;
+        }
     };
+
+    let mut count = 0;
+    for (time, slice) in recent_data {
+        for data in slice.iter() {
+            if let Some(last_time) = last_history.get(&data.subscription()) {
+                if time <= *last_time {
+                    continue;
+                }
+            }
+            time_slices.entry(time).or_insert_with(TimeSlice::new).add(data.clone());
+            count += 1;
+        }
+    }
+    if count > 0 {
+        println!("Live Warmup: Added {} recent live data points after the historical data", count);
+    }
 }
 
 pub(crate) async fn live_warm_up(
@@ -29,10 +89,14 @@ pub(crate) async fn live_warm_up(
     timed_event_handler: Arc<TimedEventHandler>,
     ledger_service: Arc<LedgerService>,
     indicator_handler: Arc<IndicatorHandler>,
-    market_price_service: Arc<MarketPriceService>
+    market_price_service: Arc<MarketPriceService>,
+    price_trigger_handler: Arc<PriceTriggerHandler>,
+    warm_up_source: WarmUpSource,
 ) {
     tokio::task::spawn(async move {
         println!("Live Warmup: Warming up the strategy...");
+        let warm_up = subscription_handler.warm_up().clone();
+        warm_up.update_time(warm_up_start_time);
 
         // Get initial subscriptions
         let mut primary_subscriptions = loop {
@@ -72,38 +136,44 @@ pub(crate) async fn live_warm_up(
             first_iteration = false;
 
             if last_time >= Utc::now() {
-                WARMUP_COMPLETE_BROADCASTER.send(last_time).unwrap();
+                warm_up.announce_complete(last_time);
                 if let Err(e) = strategy_event_sender.send(StrategyEvent::WarmUpComplete).await {
                     eprintln!("Live Warmup: Failed to send event: {}", e);
                 }
+                tokio::spawn(check_warmup(subscription_handler.warmup_checks(), strategy_event_sender.clone()));
                 break 'main_loop;
             }
 
             let mut time_slices = match get_compressed_historical_data(primary_subscriptions.clone(), last_time, to_time).await {
-                Ok(time_slices) => {
-                    if time_slices.is_empty() {
-                        println!("Live Warmup: No data period, weekend or holiday: skipping to next day");
-                        last_time = to_time + Duration::from_nanos(1);
-                        continue 'main_loop
-                    }
-                    time_slices
-                },
+                Ok(time_slices) => time_slices,
                 Err(_) => {
                     last_time = to_time + Duration::from_nanos(1);
                     continue 'main_loop
                 }
             };
 
+            // the current day is only in the store up to the last flush of the recorder
+            if warm_up_source == WarmUpSource::RecordedLive && to_time >= Utc::now() {
+                splice_recent_live_data(&primary_subscriptions, &mut time_slices, last_time).await;
+            }
+
+            if time_slices.is_empty() {
+                println!("Live Warmup: No data period, weekend or holiday: skipping to next day");
+                last_time = to_time + Duration::from_nanos(1);
+                continue 'main_loop
+            }
+
             let mut time = last_time;
             'day_loop: while time <= to_time {
                 time += buffer_duration;
 
                 // Early exit check
                 if time >= Utc::now() {
-                    WARMUP_COMPLETE_BROADCASTER.send(time).unwrap();
+                    warm_up.announce_complete(time);
                     if let Err(e) = strategy_event_sender.send(StrategyEvent::WarmUpComplete).await {
                         eprintln!("Live Warmup: Failed to send event: {}", e);
                     }
+                    tokio::spawn(check_warmup(subscription_handler.warmup_checks(), strategy_event_sender.clone()));
                     break 'main_loop;
                 }
 
@@ -117,7 +187,7 @@ pub(crate) async fn live_warm_up(
 
                 // Update time handlers
                 timed_event_handler.update_time(time).await;
-                update_backtest_time(time);
+                warm_up.update_time(time);
 
                 // Extract data for current time window
                 let time_range = last_time.timestamp_nanos_opt().unwrap()..=time.timestamp_nanos_opt().unwrap();
@@ -132,17 +202,20 @@ pub(crate) async fn live_warm_up(
                         }
                     }
 
+                    classify_aggressors(&mut time_slice);
                     let arc_slice = Arc::new(time_slice.clone());
 
                     // Send updates in parallel using join
                     market_price_service.update_market_data(arc_slice.clone());
                     ledger_service.timeslice_updates(arc_slice.clone()).await;
+                    price_trigger_handler.update_time_slice(&time_slice).await;
 
                     let mut strategy_time_slice = TimeSlice::new();
                     if let Some(consolidated_data) = subscription_handler.update_time_slice(arc_slice).await {
                         strategy_time_slice.extend(consolidated_data);
                     }
                     strategy_time_slice.extend(time_slice);
+                    subscription_handler.remove_paused(&mut strategy_time_slice);
 
                     // Process consolidated data if any
                     if let Some(consolidated_data) = subscription_handler.update_consolidators_time(time).await {
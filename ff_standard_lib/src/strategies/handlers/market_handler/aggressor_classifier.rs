@@ -0,0 +1,67 @@
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::subscriptions::SymbolName;
+use crate::standardized_types::time_slices::TimeSlice;
+
+lazy_static! {
+    static ref LAST_QUOTES: DashMap<SymbolName, (Price, Price)> = DashMap::new();
+}
+
+/// Sets the aggressor of the ticks in the slice which have none from the prevailing quote, in time order.
+pub(crate) fn classify_aggressors(time_slice: &mut TimeSlice) {
+    for base_data in time_slice.iter_mut() {
+        match base_data {
+            BaseDataEnum::Quote(quote) => {
+                LAST_QUOTES.insert(quote.symbol.name.clone(), (quote.bid, quote.ask));
+            }
+            BaseDataEnum::QuoteBar(bar) => {
+                if bar.is_closed {
+                    LAST_QUOTES.insert(bar.symbol.name.clone(), (bar.bid_close, bar.ask_close));
+                }
+            }
+            BaseDataEnum::Depth(depth) => {
+                if let (Some(bid), Some(ask)) = (depth.best_bid(), depth.best_ask()) {
+                    LAST_QUOTES.insert(depth.symbol.name.clone(), (bid, ask));
+                }
+            }
+            BaseDataEnum::Tick(tick) => {
+                if let Some(quote) = LAST_QUOTES.get(&tick.symbol.name) {
+                    let (bid, ask) = *quote.value();
+                    tick.classify_aggressor(bid, ask);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::base_data::quote::Quote;
+    use crate::standardized_types::base_data::tick::{Aggressor, Tick};
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::MarketType;
+    use crate::standardized_types::subscriptions::Symbol;
+
+    #[test]
+    fn test_classify_aggressors() {
+        let symbol = Symbol::new("ETH-USD".to_string(), DataVendor::DataBento, MarketType::Crypto);
+        let time = Utc::now();
+        let mut time_slice = TimeSlice::new();
+        time_slice.add(BaseDataEnum::Quote(Quote::new(symbol.clone(), dec!(2000.5), dec!(2000.0), dec!(1), dec!(1), time.to_string())));
+        time_slice.add(BaseDataEnum::Tick(Tick::new(symbol.clone(), dec!(2000.5), (time + Duration::seconds(1)).to_string(), dec!(1), Aggressor::None)));
+        time_slice.add(BaseDataEnum::Tick(Tick::new(symbol, dec!(2000.0), (time + Duration::seconds(2)).to_string(), dec!(1), Aggressor::None)));
+
+        classify_aggressors(&mut time_slice);
+        let aggressors: Vec<Aggressor> = time_slice.iter().filter_map(|data| match data {
+            BaseDataEnum::Tick(tick) => Some(tick.aggressor.clone()),
+            _ => None,
+        }).collect();
+        assert_eq!(aggressors, vec![Aggressor::Buy, Aggressor::Sell]);
+    }
+}
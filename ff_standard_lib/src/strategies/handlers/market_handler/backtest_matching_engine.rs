@@ -1,23 +1,25 @@
-use chrono::{DateTime, Datelike, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
-use chrono_tz::Tz;
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use std::sync::Arc;
 use rust_decimal_macros::dec;
 use tokio::sync::mpsc::{Sender};
 use tokio::sync::oneshot;
-use crate::helpers::converters::{time_convert_utc_to_local};
 use crate::standardized_types::broker_enum::Brokerage;
-use crate::standardized_types::enums::{OrderSide};
-use crate::product_maps::rithmic::maps::get_futures_trading_hours;
+use crate::standardized_types::enums::{OrderSide, StrategyMode};
+use crate::product_maps::trading_hours::get_trading_hours;
 use crate::standardized_types::new_types::{Price, Volume};
-use crate::standardized_types::orders::{Order, OrderId, OrderRequest, OrderState, OrderType, OrderUpdateEvent, OrderUpdateType, TimeInForce};
+use crate::standardized_types::orders::{day_expiry, Order, OrderId, OrderRequest, OrderState, OrderType, OrderUpdateEvent, OrderUpdateType, TimeInForce};
 use crate::strategies::handlers::market_handler::price_service::MarketPriceService;
-use crate::strategies::historical_time::get_backtest_time;
+use crate::strategies::handlers::market_handler::forex_session::{forex_session_model, session_fill_price, session_model_for, ForexSession, ForexSessionModel};
+use crate::strategies::historical_time::WarmUpState;
+use crate::strategies::ledgers::audit_trail::BrokerFill;
 use crate::strategies::ledgers::ledger_service::{LedgerService};
 use crate::strategies::strategy_events::StrategyEvent;
 
 pub enum BackTestEngineMessage {
     TickBufferTime,
+    /// Cancels every open order with the reason, eg at the end of a backtest regime.
+    CancelAllOrders(String),
     OrderRequest(OrderRequest)
 }
 
@@ -27,16 +29,20 @@ pub(crate) async fn backtest_matching_engine(
     strategy_event_sender: Sender<StrategyEvent>,
     ledger_service: Arc<LedgerService>,
     notify: Arc<tokio::sync::Notify>,
-    market_price_service: Arc<MarketPriceService>
+    market_price_service: Arc<MarketPriceService>,
+    mode: StrategyMode,
+    warm_up: WarmUpState,
 ) -> Sender<BackTestEngineMessage> {
     let (sender, mut receiver) = tokio::sync::mpsc::channel(100);
+    let session_model = forex_session_model();
     tokio::task::spawn(async move {
        notify.notify_one();
+        let mut last_weekend_flat = None;
         while let Some(backtest_message) = receiver.recv().await {
             match backtest_message {
                 BackTestEngineMessage::OrderRequest(order_request) => {
                     //println!("{:?}", order_request);
-                    let time = get_backtest_time();
+                    let time = warm_up.strategy_time(mode);
                     match order_request {
                         OrderRequest::Create { account, mut order, .. } => {
                             let market_price = match market_price_service.get_market_price(order.side, &order.symbol_name, &order.symbol_code) {
@@ -83,8 +89,73 @@ pub(crate) async fn backtest_matching_engine(
                                 }
                                 continue
                             }
+                            //check the forex session
+                            if let Some(model) = session_model_for(session_model, &account.brokerage) {
+                                let is_entry = order.order_type == OrderType::EnterLong || order.order_type == OrderType::EnterShort;
+                                let reason = match model.session(time) {
+                                    ForexSession::Closed => Some("Market Closed"),
+                                    _ if is_entry && model.weekend_flat_due(time).is_some() => Some("Weekend Flat: No Entries Before The Close"),
+                                    _ => None
+                                };
+                                if let Some(reason) = reason {
+                                    let fail_event = StrategyEvent::OrderEvents(OrderUpdateEvent::OrderRejected {
+                                        account,
+                                        symbol_name: order.symbol_name,
+                                        symbol_code: order.symbol_code,
+                                        order_id: order.id.clone(), reason: reason.to_string(),
+                                        tag: order.tag,
+                                        time: time.to_string()
+                                    });
+                                    match strategy_event_sender.send(fail_event).await {
+                                        Ok(_) => {}
+                                        Err(e) => eprintln!("Timed Event Handler: Failed to send event: {}", e)
+                                    }
+                                    continue
+                                }
+                            }
+                            //check display quantity
+                            if let Some(display_quantity) = order.display_quantity {
+                                if display_quantity <= dec!(0) || display_quantity > order.quantity_open {
+                                    let fail_event = StrategyEvent::OrderEvents(OrderUpdateEvent::OrderRejected {
+                                        account,
+                                        symbol_name: order.symbol_name,
+                                        symbol_code: order.symbol_code,
+                                        order_id: order.id.clone(), reason: String::from("Display Quantity Must Be Greater Than Zero And Not Greater Than Order Quantity"),
+                                        tag: order.tag,
+                                        time: time.to_string()
+                                    });
+                                    match strategy_event_sender.send(fail_event).await {
+                                        Ok(_) => {}
+                                        Err(e) => eprintln!("Timed Event Handler: Failed to send event: {}", e)
+                                    }
+                                    continue
+                                }
+                            }
+                            //check mit trigger price, a buy waits for a dip and a sell waits for a rise
+                            if order.order_type == OrderType::MarketIfTouched &&
+                                ((order.side == OrderSide::Buy && order.trigger_price.unwrap() >= market_price) ||
+                                    (order.side == OrderSide::Sell && order.trigger_price.unwrap() <= market_price))
+                            {
+                                let side_string = match order.side {
+                                    OrderSide::Buy => "Below",
+                                    OrderSide::Sell => "Above"
+                                };
+                                let fail_event = StrategyEvent::OrderEvents(OrderUpdateEvent::OrderRejected {
+                                    account,
+                                    symbol_name: order.symbol_name,
+                                    symbol_code: order.symbol_code,
+                                    order_id: order.id.clone(), reason: String::from(format!("{} MIT Trigger Price Must Be {} Market Price", order.side, side_string)),
+                                    tag: order.tag,
+                                    time: time.to_string()
+                                });
+                                match strategy_event_sender.send(fail_event).await {
+                                    Ok(_) => {}
+                                    Err(e) => eprintln!("Timed Event Handler: Failed to send event: {}", e)
+                                }
+                                continue
+                            }
                             //check trigger price
-                            if (order.order_type == OrderType::StopMarket || order.order_type == OrderType::StopLimit || order.order_type == OrderType::MarketIfTouched) &&
+                            if (order.order_type == OrderType::StopMarket || order.order_type == OrderType::StopLimit) &&
                                 ((order.side == OrderSide::Sell && order.trigger_price.unwrap() >= market_price) ||
                                     (order.side == OrderSide::Buy && order.trigger_price.unwrap() <= market_price))
                             {
@@ -149,13 +220,14 @@ pub(crate) async fn backtest_matching_engine(
                                 symbol_code: order.symbol_code.clone(),
                                 order_id: order.id.clone(),
                                 tag: order.tag.clone(),
-                                time: time.to_string()
+                                time: time.to_string(),
+                                broker_order_id: None,
                             });
                             match strategy_event_sender.send(accept_event).await {
                                 Ok(_) => {}
                                 Err(e) => eprintln!("Timed Event Handler: Failed to send event: {}", e)
                             }
-                            simulated_order_matching(&open_order_cache, &closed_order_cache, strategy_event_sender.clone(), &ledger_service, &market_price_service).await;
+                            simulated_order_matching(&open_order_cache, &closed_order_cache, strategy_event_sender.clone(), &ledger_service, &market_price_service, session_model, time).await;
                         }
                         OrderRequest::Cancel { account,order_id } => {
                             if let Some((existing_order_id, order)) = open_order_cache.remove(&order_id) {
@@ -183,7 +255,7 @@ pub(crate) async fn backtest_matching_engine(
                                     Err(e) => eprintln!("Timed Event Handler: Failed to send event: {}", e)
                                 }
                             }
-                            simulated_order_matching(&open_order_cache, &closed_order_cache, strategy_event_sender.clone(), &ledger_service, &market_price_service).await;
+                            simulated_order_matching(&open_order_cache, &closed_order_cache, strategy_event_sender.clone(), &ledger_service, &market_price_service, session_model, time).await;
                         }
                         OrderRequest::Update { account, order_id, update } => {
                             if let Some((order_id, mut order)) = open_order_cache.remove(&order_id) {
@@ -220,7 +292,7 @@ pub(crate) async fn backtest_matching_engine(
                                     Err(e) => eprintln!("Timed Event Handler: Failed to send event: {}", e)
                                 }
                             }
-                            simulated_order_matching(&open_order_cache, &closed_order_cache, strategy_event_sender.clone(), &ledger_service, &market_price_service).await;
+                            simulated_order_matching(&open_order_cache, &closed_order_cache, strategy_event_sender.clone(), &ledger_service, &market_price_service, session_model, time).await;
                         }
                         OrderRequest::CancelAll { account } => {
                             let mut remove = vec![];
@@ -249,7 +321,7 @@ pub(crate) async fn backtest_matching_engine(
                                     closed_order_cache.insert(order_id, order);
                                 }
                             }
-                            simulated_order_matching(&open_order_cache, &closed_order_cache, strategy_event_sender.clone(), &ledger_service, &market_price_service).await;
+                            simulated_order_matching(&open_order_cache, &closed_order_cache, strategy_event_sender.clone(), &ledger_service, &market_price_service, session_model, time).await;
                         }
                         OrderRequest::FlattenAllFor { account} => {
                             let orders_to_remove: Vec<_> = open_order_cache.iter()
@@ -279,10 +351,37 @@ pub(crate) async fn backtest_matching_engine(
                         }
                     }
                 }
+                BackTestEngineMessage::CancelAllOrders(reason) => {
+                    let time = warm_up.strategy_time(mode);
+                    let order_ids: Vec<OrderId> = open_order_cache.iter().map(|order| order.id.clone()).collect();
+                    for order_id in order_ids {
+                        cancel_order(reason.clone(), &order_id, time, &open_order_cache, &closed_order_cache, &strategy_event_sender).await;
+                    }
+                }
                 BackTestEngineMessage::TickBufferTime => {
+                    let time = warm_up.strategy_time(mode);
+                    if let Some(friday) = session_model_for(session_model, &Brokerage::Oanda).and_then(|model| model.weekend_flat_due(time)) {
+                        if last_weekend_flat != Some(friday) {
+                            last_weekend_flat = Some(friday);
+                            weekend_flat(time, &open_order_cache, &closed_order_cache, &strategy_event_sender, &ledger_service).await;
+                        }
+                    }
                     if !open_order_cache.is_empty() {
-                        simulated_order_matching(&open_order_cache, &closed_order_cache, strategy_event_sender.clone(), &ledger_service, &market_price_service).await;
+                        // candle only symbols walk their intrabar path, the last point of each path is the close
+                        let steps = market_price_service.intrabar_steps();
+                        if steps == 0 {
+                            simulated_order_matching(&open_order_cache, &closed_order_cache, strategy_event_sender.clone(), &ledger_service, &market_price_service, session_model, time).await;
+                        }
+                        for step in 0..steps {
+                            market_price_service.set_intrabar_step(step);
+                            simulated_order_matching(&open_order_cache, &closed_order_cache, strategy_event_sender.clone(), &ledger_service, &market_price_service, session_model, time).await;
+                            if open_order_cache.is_empty() {
+                                market_price_service.set_intrabar_step(steps - 1);
+                                break;
+                            }
+                        }
                     }
+                    market_price_service.finish_intrabar_paths();
                     notify.notify_one();
                 }
             }
@@ -296,77 +395,29 @@ pub(crate) async fn simulated_order_matching (
     closed_order_cache: &Arc<DashMap<OrderId, Order>>,
     strategy_event_sender: Sender<StrategyEvent>,
     ledger_service: &Arc<LedgerService>,
-    market_price_service: &Arc<MarketPriceService>
+    market_price_service: &Arc<MarketPriceService>,
+    session_model: Option<ForexSessionModel>,
+    time: DateTime<Utc>,
 ) {
-    let time = get_backtest_time();
     let mut rejected = Vec::new();
     let mut cancelled = Vec::new();
+    let mut expired = Vec::new();
     let mut filled = Vec::new();
     let mut partially_filled = Vec::new();
     for order in open_order_cache.iter() {
         //println!("Order matching: {:?}", order.value());
+        if let Some(model) = session_model_for(session_model, &order.account.brokerage) {
+            if model.session(time) == ForexSession::Closed {
+                continue
+            }
+        }
         match &order.time_in_force {
             TimeInForce::GTC => {},
             TimeInForce::Day => {
-                let tz: Tz = order.account.brokerage.timezone();
-                let close_time: DateTime<Utc> = match order.account.brokerage {
-                    Brokerage::Rithmic(_) => {
-                        match get_futures_trading_hours(&order.symbol_name) {
-                            None => {
-                                // CME closes at 17:00 Eastern Time
-                                let local_date = NaiveDateTime::new(
-                                    time_convert_utc_to_local(&tz, order.time_created_utc()).date_naive(),
-                                    NaiveTime::from_hms_opt(17, 0, 0).unwrap()
-                                );
-                                tz.from_local_datetime(&local_date)
-                                    .unwrap()
-                                    .with_timezone(&Utc)
-                            }
-                            Some(hours) => {
-                                match order.time_created_utc().date_naive().weekday() {
-                                    Weekday::Sun => {
-                                        let time_naive = hours.monday.close.unwrap();
-                                        let date_naive = order.time_created_utc()
-                                            .date_naive()
-                                            .succ_opt()
-                                            .unwrap();
-                                        let local_dt = NaiveDateTime::new(date_naive, time_naive);
-                                        tz.from_local_datetime(&local_dt)
-                                            .unwrap()
-                                            .to_utc()
-                                    }
-                                    Weekday::Sat => {
-                                        let reason = "Market Closed".to_string();
-                                        cancelled.push((order.id.clone(), reason));
-                                        continue
-                                    }
-                                    _ => {
-                                        let time_naive = hours.monday.close.unwrap();
-                                        let date_naive = order.time_created_utc().date_naive();
-                                        let local_dt = NaiveDateTime::new(date_naive, time_naive);
-                                        tz.from_local_datetime(&local_dt)
-                                            .unwrap()
-                                            .to_utc()
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    _ => {
-                        // For test broker, use end of day in local timezone
-                        let local_date = NaiveDateTime::new(
-                            time_convert_utc_to_local(&tz, order.time_created_utc()).date_naive(),
-                            NaiveTime::from_hms_opt(23, 59, 59).unwrap()
-                        );
-                        tz.from_local_datetime(&local_date)
-                            .unwrap()
-                            .to_utc()
-                    }
-                };
-
-                if time >= close_time {
-                    let reason = "Time In Force Expired: TimeInForce::Day".to_string();
-                    cancelled.push((order.id.clone(), reason));
+                // orders sent around the strategy have no expiry, they use the futures hours or the end of the brokerage day
+                let expiry = order.day_expiry_utc().unwrap_or_else(|| day_expiry(get_trading_hours(&order.symbol_name).as_ref(), &order.account.brokerage.timezone(), order.time_created_utc()));
+                if time >= expiry {
+                    expired.push(order.id.clone());
                     continue
                 }
             }
@@ -383,18 +434,14 @@ pub(crate) async fn simulated_order_matching (
                 }*/
             }
             TimeInForce::Time(cancel_time) => {
-                let cancel_time = match DateTime::<Utc>::from_timestamp(*cancel_time, 0) {
-                    Some(time) => time,
-                    None => {
-                        eprintln!("Backtest Matching Engine: Invalid TimeInForce::Time value");
-                        let reason = "Time In Force Expired: TimeInForce::Time".to_string();
-                        rejected.push((order.id.clone(), reason));
-                        continue;
-                    }
-                };
-                if time >= cancel_time {
-                    let reason = "Time In Force Expired: TimeInForce::Time".to_string();
-                    cancelled.push((order.id.clone(), reason));
+                if DateTime::<Utc>::from_timestamp(*cancel_time, 0).is_none() {
+                    eprintln!("Backtest Matching Engine: Invalid TimeInForce::Time value");
+                    let reason = "Invalid TimeInForce::Time".to_string();
+                    rejected.push((order.id.clone(), reason));
+                    continue;
+                }
+                if order.is_expired(time) {
+                    expired.push(order.id.clone());
                     continue
                 }
             }
@@ -412,9 +459,14 @@ pub(crate) async fn simulated_order_matching (
                     OrderSide::Sell => market_price >= order.limit_price.unwrap()
                 };
                 if is_fill_triggered {
-                    let (market_fill_price, volume_filled) = match market_price_service.estimate_limit_fill(order.side, &order.symbol_name, &order.symbol_code, order.quantity_open, order.limit_price.unwrap()) {
+                    // iceberg orders only show 1 clip to the market at a time
+                    let volume_shown = match order.display_quantity {
+                        Some(display_quantity) => display_quantity.min(order.quantity_open),
+                        None => order.quantity_open
+                    };
+                    let (market_fill_price, volume_filled) = match market_price_service.estimate_limit_fill(order.side, &order.symbol_name, &order.symbol_code, volume_shown, order.limit_price.unwrap()) {
                         Some((price, volume)) => {
-                            (price, volume)
+                            (market_price_service.intrabar_fill_price(&order.symbol_name, order.limit_price.unwrap(), price), volume)
                         },
                         None => continue
                     };
@@ -446,7 +498,7 @@ pub(crate) async fn simulated_order_matching (
 
                 if is_fill_triggered {
                     let market_fill_price = match market_price_service.estimate_fill_price(order.side, &order.symbol_name, &order.symbol_code, order.quantity_open) {
-                        Some(price) => price,
+                        Some(price) => market_price_service.intrabar_fill_price(&order.symbol_name, trigger_price, price),
                         None => continue
                     };
                     filled.push((order.id.clone(), market_fill_price));
@@ -459,31 +511,8 @@ pub(crate) async fn simulated_order_matching (
                     Some(price) => price,
                     None => panic!("No market price found")
                 };
+                // the trigger price is validated against the market price when the order is created, so touching it here fills the order
                 let trigger_price = order.trigger_price.unwrap();
-
-                match order.side {
-                    OrderSide::Buy => {// todo double check this logic
-                        // Buy MIT: trigger price must be BELOW market price to wait for favorable dip
-                        if trigger_price >= market_price {
-                            rejected.push((
-                                String::from("Invalid Price: Buy MIT Price Must Be Below Market Price"),
-                                order.id.clone(),
-                            ));
-                            continue;
-                        }
-                    }
-                    OrderSide::Sell => {
-                        // Sell MIT: trigger price must be ABOVE market price to wait for favorable rise
-                        if trigger_price <= market_price {
-                            rejected.push((
-                                String::from("Invalid Price: Sell MIT Price Must Be Above Market Price"),
-                                order.id.clone(),
-                            ));
-                            continue;
-                        }
-                    }
-                }
-
                 let is_fill_triggered = match order.side {
                     OrderSide::Buy => market_price <= trigger_price,
                     OrderSide::Sell => market_price >= trigger_price,
@@ -491,7 +520,7 @@ pub(crate) async fn simulated_order_matching (
 
                 if is_fill_triggered {
                     let market_fill_price = match market_price_service.estimate_fill_price(order.side, &order.symbol_name, &order.symbol_code, order.quantity_open) {
-                        Some(price) => price,
+                        Some(price) => market_price_service.intrabar_fill_price(&order.symbol_name, trigger_price, price),
                         None => continue
                     };
                     filled.push((order.id.clone(), market_fill_price));
@@ -594,6 +623,10 @@ pub(crate) async fn simulated_order_matching (
         reject_order(reason, &order_id, time, &open_order_cache, closed_order_cache, &strategy_event_sender).await;
     }
     for (order_id, price) in filled {
+        let price = match open_order_cache.get(&order_id) {
+            Some(order) => session_fill_price(session_model, order.value(), price, time),
+            None => price
+        };
         fill_order(&order_id, time, price, &open_order_cache, &closed_order_cache, &strategy_event_sender, &ledger_service).await;
     }
     for (order_id, price, volume) in partially_filled {
@@ -603,6 +636,10 @@ pub(crate) async fn simulated_order_matching (
     for (order_id, reason) in cancelled {
         cancel_order(reason, &order_id, time, &open_order_cache, closed_order_cache, &strategy_event_sender).await;
     }
+
+    for order_id in expired {
+        expire_order(&order_id, time, &open_order_cache, closed_order_cache, &strategy_event_sender).await;
+    }
 }
 
 async fn fill_order(
@@ -616,7 +653,7 @@ async fn fill_order(
 ) {
     if let Some((_, mut order)) = open_order_cache.remove(order_id) {  // Remove the order here
        let (sender, receiver) = oneshot::channel();
-        ledger_service.update_or_create_position(&order.account, order.symbol_name.clone(), order.symbol_code.clone(), order.quantity_open.clone(), order.side.clone(), time.clone(), market_price, order.tag.clone(), Some(sender), order_id.clone()).await;
+        ledger_service.update_or_create_position(&order.account, order.symbol_name.clone(), order.symbol_code.clone(), order.quantity_open.clone(), order.side.clone(), time.clone(), market_price, order.tag.clone(), Some(sender), order_id.clone(), BrokerFill::default()).await;
         match receiver.await {
             Ok(event) => {
                 match event {
@@ -641,6 +678,9 @@ async fn fill_order(
                             tag: order.tag.clone(),
                             time: time.to_string(),
                             side: order.side.clone(),
+                            fill_id: None,
+                            liquidity: None,
+                            commission: None,
                         });
                         order.quantity_filled += order.quantity_open.clone();
                         order.quantity_open = dec!(0.0);
@@ -669,7 +709,7 @@ async fn partially_fill_order(
 ) {
     if let Some((_, mut order)) = open_order_cache.remove(order_id) {
         let (sender, receiver) = oneshot::channel();
-        ledger_service.update_or_create_position(&order.account, order.symbol_name.clone(),  order.symbol_code.clone(), fill_volume, order.side.clone(), time, fill_price, order.tag.clone(), Some(sender), order_id.clone()).await;
+        ledger_service.update_or_create_position(&order.account, order.symbol_name.clone(),  order.symbol_code.clone(), fill_volume, order.side.clone(), time, fill_price, order.tag.clone(), Some(sender), order_id.clone(), BrokerFill::default()).await;
 
         match receiver.await {
             Ok(event) => {
@@ -698,6 +738,9 @@ async fn partially_fill_order(
                                 quantity: fill_volume,
                                 price: fill_price,
                                 side: order.side.clone(),
+                                fill_id: None,
+                                liquidity: None,
+                                commission: None,
                             }
                         } else {
                             OrderUpdateEvent::OrderPartiallyFilled {
@@ -710,6 +753,9 @@ async fn partially_fill_order(
                                 quantity: fill_volume,
                                 price: fill_price,
                                 side: order.side.clone(),
+                                fill_id: None,
+                                liquidity: None,
+                                commission: None,
                             }
                         };
                         match strategy_event_sender.send(StrategyEvent::OrderEvents(order_event)).await {
@@ -729,6 +775,30 @@ async fn partially_fill_order(
     }
 }
 
+/// Cancels the working orders and flattens the positions of the Oanda paper accounts before the weekend.
+async fn weekend_flat(
+    time: DateTime<Utc>,
+    open_order_cache: &Arc<DashMap<OrderId, Order>>,
+    closed_order_cache: &Arc<DashMap<OrderId, Order>>,
+    strategy_event_sender: &Sender<StrategyEvent>,
+    ledger_service: &Arc<LedgerService>
+) {
+    let order_ids: Vec<OrderId> = open_order_cache.iter()
+        .filter(|order| order.account.brokerage == Brokerage::Oanda)
+        .map(|order| order.id.clone())
+        .collect();
+    for order_id in order_ids {
+        cancel_order("Weekend Flat".to_string(), &order_id, time, open_order_cache, closed_order_cache, strategy_event_sender).await;
+    }
+    let accounts: Vec<_> = ledger_service.ledgers.iter()
+        .filter(|ledger| ledger.key().brokerage == Brokerage::Oanda)
+        .map(|ledger| ledger.key().clone())
+        .collect();
+    for account in accounts {
+        ledger_service.flatten_all_for_paper_account(account, time).await;
+    }
+}
+
 async fn reject_order(
     reason: String,
     order_id: &OrderId,
@@ -786,3 +856,30 @@ async fn cancel_order(
         }
     }
 }
+
+async fn expire_order(
+    order_id: &OrderId,
+    time: DateTime<Utc>,
+    open_order_cache: &Arc<DashMap<OrderId, Order>>,
+    closed_order_cache: &Arc<DashMap<OrderId, Order>>,
+    strategy_event_sender: &Sender<StrategyEvent>
+) {
+    if let Some((_, mut order)) = open_order_cache.remove(order_id) {
+        order.state = OrderState::Expired;
+        order.quantity_open = dec!(0);
+
+        let event = StrategyEvent::OrderEvents(OrderUpdateEvent::OrderExpired {
+            order_id: order.id.clone(),
+            account: order.account.clone(),
+            symbol_name: order.symbol_name.clone(),
+            tag: order.tag.clone(),
+            time: time.to_string(),
+            symbol_code: order.symbol_code.clone(),
+        });
+        closed_order_cache.insert(order.id.clone(), order);
+        match strategy_event_sender.send(event).await {
+            Ok(_) => {}
+            Err(e) => eprintln!("Backtest Matching Engine: Failed to send event: {}", e)
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::sync::RwLock;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Timelike, Utc, Weekday};
+use chrono_tz::America::New_York;
+use lazy_static::lazy_static;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::standardized_types::broker_enum::Brokerage;
+use crate::standardized_types::enums::OrderSide;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::orders::{Order, OrderType};
+
+lazy_static! {
+    static ref FOREX_SESSION_MODEL: RwLock<Option<ForexSessionModel>> = RwLock::new(None);
+}
+
+/// The daily rollover and the weekly open and close, in New York time.
+const ROLLOVER_TIME: u32 = 17;
+
+/// # Properties
+/// * `rollover_minutes_before` - The minutes before 17:00 New York time where the spread is widened.
+/// * `rollover_minutes_after` - The minutes after 17:00 New York time where the spread is widened, this includes the Sunday open.
+/// * `rollover_spread_bps` - The extra spread in basis points of price during the rollover, half is applied to each side.
+/// * `weekend_flat_minutes` - If Some, Oanda accounts are flattened this many minutes before the Friday close and entries are rejected until the close.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ForexSessionModel {
+    pub rollover_minutes_before: i64,
+    pub rollover_minutes_after: i64,
+    pub rollover_spread_bps: Decimal,
+    pub weekend_flat_minutes: Option<i64>,
+}
+
+impl Default for ForexSessionModel {
+    fn default() -> Self {
+        ForexSessionModel {
+            rollover_minutes_before: 5,
+            rollover_minutes_after: 15,
+            rollover_spread_bps: dec!(3),
+            weekend_flat_minutes: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForexSession {
+    Open,
+    /// The spread is widened around the daily rollover and the Sunday open.
+    Rollover,
+    Closed,
+}
+
+impl ForexSessionModel {
+    pub fn session(&self, time: DateTime<Utc>) -> ForexSession {
+        let local = time.with_timezone(&New_York);
+        let minutes = (local.hour() * 60 + local.minute()) as i64;
+        let rollover = (ROLLOVER_TIME * 60) as i64;
+        match local.weekday() {
+            Weekday::Sat => ForexSession::Closed,
+            Weekday::Sun if minutes < rollover => ForexSession::Closed,
+            Weekday::Fri if minutes >= rollover => ForexSession::Closed,
+            Weekday::Fri if minutes >= rollover - self.rollover_minutes_before => ForexSession::Rollover,
+            Weekday::Sun if minutes < rollover + self.rollover_minutes_after => ForexSession::Rollover,
+            Weekday::Sun => ForexSession::Open,
+            _ => match minutes >= rollover - self.rollover_minutes_before && minutes < rollover + self.rollover_minutes_after {
+                true => ForexSession::Rollover,
+                false => ForexSession::Open,
+            }
+        }
+    }
+
+    /// Returns the Friday date if the time is in the weekend flat window, the engine flattens once per date.
+    pub fn weekend_flat_due(&self, time: DateTime<Utc>) -> Option<NaiveDate> {
+        let minutes = self.weekend_flat_minutes?;
+        let local = time.with_timezone(&New_York);
+        if local.weekday() != Weekday::Fri {
+            return None;
+        }
+        let close = local.date_naive().and_time(NaiveTime::from_hms_opt(ROLLOVER_TIME, 0, 0).unwrap());
+        let local_naive = local.naive_local();
+        match local_naive >= close - Duration::minutes(minutes) && local_naive < close {
+            true => Some(local.date_naive()),
+            false => None,
+        }
+    }
+
+    /// Moves the fill price against the order by half the rollover spread.
+    pub fn rollover_fill_price(&self, side: OrderSide, price: Price) -> Price {
+        let half_spread = price * self.rollover_spread_bps / dec!(20000);
+        match side {
+            OrderSide::Buy => price + half_spread,
+            OrderSide::Sell => price - half_spread,
+        }
+    }
+}
+
+/// Sets the forex session model used for Oanda accounts in backtests, by default there is none and orders fill at the data prices at any time.
+/// A strategy keeps the model it was initialized with.
+pub fn set_forex_session_model(model: Option<ForexSessionModel>) {
+    *FOREX_SESSION_MODEL.write().unwrap() = model;
+}
+
+pub(crate) fn forex_session_model() -> Option<ForexSessionModel> {
+    *FOREX_SESSION_MODEL.read().unwrap()
+}
+
+/// The session model if the order is for an account it applies to.
+pub(crate) fn session_model_for(model: Option<ForexSessionModel>, brokerage: &Brokerage) -> Option<ForexSessionModel> {
+    match brokerage {
+        Brokerage::Oanda => model,
+        _ => None,
+    }
+}
+
+/// The fill price after the session model is applied, limit orders are filled at the data price because they can not fill worse than the limit.
+pub(crate) fn session_fill_price(model: Option<ForexSessionModel>, order: &Order, price: Price, time: DateTime<Utc>) -> Price {
+    let Some(model) = session_model_for(model, &order.account.brokerage) else {
+        return price;
+    };
+    match order.order_type {
+        OrderType::Limit | OrderType::StopLimit => price,
+        _ => match model.session(time) {
+            ForexSession::Rollover => model.rollover_fill_price(order.side, price),
+            ForexSession::Open | ForexSession::Closed => price,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    // 2024-01-05 is a Friday, New York is UTC-5 in January
+    fn new_york(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        New_York.with_ymd_and_hms(2024, 1, day, hour, minute, 0).unwrap().to_utc()
+    }
+
+    #[test]
+    fn test_sessions() {
+        let model = ForexSessionModel::default();
+        assert_eq!(model.session(new_york(5, 12, 0)), ForexSession::Open);
+        assert_eq!(model.session(new_york(5, 16, 56)), ForexSession::Rollover);
+        assert_eq!(model.session(new_york(5, 17, 0)), ForexSession::Closed);
+        assert_eq!(model.session(new_york(6, 12, 0)), ForexSession::Closed);
+        assert_eq!(model.session(new_york(7, 16, 59)), ForexSession::Closed);
+        assert_eq!(model.session(new_york(7, 17, 5)), ForexSession::Rollover);
+        assert_eq!(model.session(new_york(7, 17, 15)), ForexSession::Open);
+        assert_eq!(model.session(new_york(8, 16, 57)), ForexSession::Rollover);
+        assert_eq!(model.session(new_york(8, 17, 14)), ForexSession::Rollover);
+        assert_eq!(model.session(new_york(8, 17, 15)), ForexSession::Open);
+    }
+
+    #[test]
+    fn test_weekend_flat() {
+        let mut model = ForexSessionModel::default();
+        assert_eq!(model.weekend_flat_due(new_york(5, 16, 50)), None);
+        model.weekend_flat_minutes = Some(30);
+        assert_eq!(model.weekend_flat_due(new_york(5, 16, 29)), None);
+        assert_eq!(model.weekend_flat_due(new_york(5, 16, 30)), NaiveDate::from_ymd_opt(2024, 1, 5));
+        assert_eq!(model.weekend_flat_due(new_york(5, 17, 0)), None);
+        assert_eq!(model.weekend_flat_due(new_york(4, 16, 45)), None);
+    }
+
+    #[test]
+    fn test_rollover_fill_price() {
+        let model = ForexSessionModel::default();
+        assert_eq!(model.rollover_fill_price(OrderSide::Buy, dec!(1.1)), dec!(1.100165));
+        assert_eq!(model.rollover_fill_price(OrderSide::Sell, dec!(1.1)), dec!(1.099835));
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::RwLock;
+use lazy_static::lazy_static;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use crate::standardized_types::base_data::candle::Candle;
+use crate::standardized_types::new_types::Price;
+
+lazy_static! {
+    static ref INTRABAR_MODEL: RwLock<IntrabarModel> = RwLock::new(IntrabarModel::CloseOnly);
+}
+
+/// How the price is assumed to move inside a candle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntrabarModel {
+    /// Orders are only matched at the close, the default.
+    CloseOnly,
+    /// Open, the extreme nearest the open, the other extreme, then the close.
+    NearestExtremeFirst,
+    /// Open, high, low, close. Pessimistic for longs with a stop below and a target above.
+    OpenHighLowClose,
+    /// Open, low, high, close. Pessimistic for shorts with a stop above and a target below.
+    OpenLowHighClose,
+    /// The nearest extreme first path with a Brownian bridge of `steps_per_leg` points between each point, kept inside the range of the candle.
+    /// The bridge is random but reproducible, it is seeded by `seed`, the symbol and the candle time.
+    BrownianBridge { steps_per_leg: u32, seed: u64 },
+}
+
+impl fmt::Display for IntrabarModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntrabarModel::CloseOnly => write!(f, "Close Only"),
+            IntrabarModel::NearestExtremeFirst => write!(f, "Nearest Extreme First"),
+            IntrabarModel::OpenHighLowClose => write!(f, "Open High Low Close"),
+            IntrabarModel::OpenLowHighClose => write!(f, "Open Low High Close"),
+            IntrabarModel::BrownianBridge { steps_per_leg, seed } => write!(f, "Brownian Bridge ({} steps per leg, seed {})", steps_per_leg, seed),
+        }
+    }
+}
+
+impl IntrabarModel {
+    /// The prices the candle is assumed to trade through in order, the last price is always the close.
+    pub fn path(&self, candle: &Candle) -> Vec<Price> {
+        let (open, high, low, close) = (candle.open, candle.high, candle.low, candle.close);
+        let nearest_extreme_first = || match high - open <= open - low {
+            true => vec![open, high, low, close],
+            false => vec![open, low, high, close],
+        };
+        let vertices = match self {
+            IntrabarModel::CloseOnly => return vec![close],
+            IntrabarModel::NearestExtremeFirst | IntrabarModel::BrownianBridge { .. } => nearest_extreme_first(),
+            IntrabarModel::OpenHighLowClose => vec![open, high, low, close],
+            IntrabarModel::OpenLowHighClose => vec![open, low, high, close],
+        };
+        let mut path = match self {
+            IntrabarModel::BrownianBridge { steps_per_leg, seed } => {
+                let mut hasher = DefaultHasher::new();
+                seed.hash(&mut hasher);
+                candle.symbol.name.hash(&mut hasher);
+                candle.time.hash(&mut hasher);
+                brownian_bridge(&vertices, *steps_per_leg, high, low, hasher.finish())
+            }
+            _ => vertices,
+        };
+        path.dedup();
+        path
+    }
+}
+
+/// Sets the intrabar model used for candle only symbols in backtests, a strategy keeps the model it was initialized with.
+pub fn set_intrabar_model(model: IntrabarModel) {
+    *INTRABAR_MODEL.write().unwrap() = model;
+}
+
+pub(crate) fn intrabar_model() -> IntrabarModel {
+    *INTRABAR_MODEL.read().unwrap()
+}
+
+/// Joins the vertices with Brownian bridges, the points are clamped to the range and rounded to the decimal places of the prices.
+fn brownian_bridge(vertices: &[Price], steps_per_leg: u32, high: Price, low: Price, seed: u64) -> Vec<Price> {
+    let steps = steps_per_leg.max(1) as usize;
+    let scale = vertices.iter().map(|price| price.scale()).max().unwrap_or(0);
+    let sigma = (high - low).to_f64().unwrap_or(0.0) / (2.0 * (steps as f64).sqrt());
+    let mut random = SplitMix64(seed);
+    let mut path = vec![vertices[0]];
+    for leg in vertices.windows(2) {
+        let (start, end) = (leg[0].to_f64().unwrap_or(0.0), leg[1].to_f64().unwrap_or(0.0));
+        // a random walk with the drift removed so it ends where it started
+        let mut walk = vec![0.0; steps + 1];
+        for k in 1..=steps {
+            walk[k] = walk[k - 1] + random.next_gaussian() * sigma;
+        }
+        for k in 1..steps {
+            let t = k as f64 / steps as f64;
+            let point = start + (end - start) * t + walk[k] - t * walk[steps];
+            let price = Decimal::from_f64(point).unwrap_or(leg[1]).round_dp(scale).clamp(low, high);
+            path.push(price);
+        }
+        path.push(leg[1]);
+    }
+    path
+}
+
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform number in (0, 1].
+    fn next_f64(&mut self) -> f64 {
+        ((self.next_u64() >> 11) as f64 + 1.0) / (1u64 << 53) as f64
+    }
+
+    /// A standard normal number from the Box-Muller transform.
+    fn next_gaussian(&mut self) -> f64 {
+        let (u1, u2) = (self.next_f64(), self.next_f64());
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::{FuturesExchange, MarketType};
+    use crate::standardized_types::resolution::Resolution;
+    use crate::standardized_types::subscriptions::{CandleType, Symbol};
+
+    fn candle(open: Price, high: Price, low: Price, close: Price) -> Candle {
+        let symbol = Symbol::new("NQ".to_string(), DataVendor::DataBento, MarketType::Futures(FuturesExchange::CME));
+        let mut candle = Candle::new(symbol, open, dec!(0), dec!(0), dec!(0), Utc::now().to_string(), Resolution::Minutes(1), CandleType::CandleStick);
+        candle.high = high;
+        candle.low = low;
+        candle.close = close;
+        candle
+    }
+
+    #[test]
+    fn test_ohlc_paths() {
+        let bar = candle(dec!(100), dec!(110), dec!(98), dec!(105));
+        assert_eq!(IntrabarModel::CloseOnly.path(&bar), vec![dec!(105)]);
+        // the low is nearer the open
+        assert_eq!(IntrabarModel::NearestExtremeFirst.path(&bar), vec![dec!(100), dec!(98), dec!(110), dec!(105)]);
+        assert_eq!(IntrabarModel::OpenHighLowClose.path(&bar), vec![dec!(100), dec!(110), dec!(98), dec!(105)]);
+        assert_eq!(IntrabarModel::OpenLowHighClose.path(&bar), vec![dec!(100), dec!(98), dec!(110), dec!(105)]);
+
+        // repeated prices are removed, a flat bar is a single point
+        let bar = candle(dec!(100), dec!(100), dec!(100), dec!(100));
+        assert_eq!(IntrabarModel::OpenHighLowClose.path(&bar), vec![dec!(100)]);
+    }
+
+    #[test]
+    fn test_brownian_bridge_path() {
+        let bar = candle(dec!(100.00), dec!(110.00), dec!(98.00), dec!(105.00));
+        let model = IntrabarModel::BrownianBridge { steps_per_leg: 10, seed: 7 };
+        let path = model.path(&bar);
+        assert_eq!(path, model.path(&bar));
+        assert_eq!(path.first(), Some(&dec!(100.00)));
+        assert_eq!(path.last(), Some(&dec!(105.00)));
+        assert!(path.contains(&dec!(98.00)) && path.contains(&dec!(110.00)));
+        assert!(path.iter().all(|price| *price >= dec!(98.00) && *price <= dec!(110.00) && price.scale() <= 2));
+    }
+}
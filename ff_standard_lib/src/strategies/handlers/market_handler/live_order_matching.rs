@@ -5,6 +5,7 @@ use rust_decimal_macros::dec;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::{Receiver};
 use crate::standardized_types::orders::{Order, OrderId, OrderState, OrderUpdateEvent, OrderUpdateType};
+use crate::strategies::ledgers::audit_trail::BrokerFill;
 use crate::strategies::ledgers::ledger_service::{LedgerService};
 use crate::strategies::strategy_events::StrategyEvent;
 
@@ -24,13 +25,16 @@ pub(crate) fn live_order_handler(
         while let Some((ref order_update_event, time_utc)) = order_event_receiver.recv().await {
             match order_update_event {
                 #[allow(unused)]
-                OrderUpdateEvent::OrderAccepted { account, symbol_name, symbol_code, order_id, tag, time } => {
+                OrderUpdateEvent::OrderAccepted { account, symbol_name, symbol_code, order_id, tag, time, broker_order_id } => {
                     if let Some(mut order) = open_order_cache.get_mut(order_id) {
                         if order.state != OrderState::Created {
                             continue;
                         }
                         order.value_mut().state = OrderState::Accepted;
                         order.symbol_code = symbol_code.clone();
+                        if broker_order_id.is_some() {
+                            order.broker_order_id = broker_order_id.clone();
+                        }
                         match strategy_event_sender.send(StrategyEvent::OrderEvents(order_update_event.clone())).await {
                             Ok(_) => {}
                             Err(e) => eprintln!("{}", e)
@@ -38,7 +42,7 @@ pub(crate) fn live_order_handler(
                     }
                 }
                 #[allow(unused)]
-                OrderUpdateEvent::OrderFilled { account, symbol_name, symbol_code, order_id, price, quantity, tag, time, side } => {
+                OrderUpdateEvent::OrderFilled { account, symbol_name, symbol_code, order_id, price, quantity, tag, time, side, fill_id, liquidity, commission } => {
                     #[allow(unused)]
                      if let Some((order_id, mut order)) = open_order_cache.remove(order_id) {
                          if order.state == OrderState::Filled {
@@ -52,7 +56,7 @@ pub(crate) fn live_order_handler(
                          order.state = OrderState::Filled;
                          closed_order_cache.insert(order_id.clone(), order.clone());
                          //println!("{}", order_update_event);
-                         ledger_service.update_or_create_position(&account, symbol_name.clone(), symbol_code.clone(), quantity, side.clone(), time_utc, *price, tag.to_string(), None, order_id).await;
+                         ledger_service.update_or_create_position(&account, symbol_name.clone(), symbol_code.clone(), quantity, side.clone(), time_utc, *price, tag.to_string(), None, order_id, BrokerFill { fill_id: fill_id.clone(), liquidity: *liquidity, commission: *commission, broker_order_id: order.broker_order_id.clone() }).await;
 
                          match strategy_event_sender.send(StrategyEvent::OrderEvents(order_update_event.clone())).await {
                              Ok(_) => {}
@@ -60,7 +64,7 @@ pub(crate) fn live_order_handler(
                          }
                     }
                 }
-                OrderUpdateEvent::OrderPartiallyFilled { account, symbol_name, symbol_code, order_id, price, quantity, tag, time,  side} => {
+                OrderUpdateEvent::OrderPartiallyFilled { account, symbol_name, symbol_code, order_id, price, quantity, tag, time, side, fill_id, liquidity, commission } => {
                    if let Some(mut order) = open_order_cache.get_mut(order_id) {
                        if order.state == OrderState::Filled {
                            continue;
@@ -71,16 +75,33 @@ pub(crate) fn live_order_handler(
                        order.quantity_filled += quantity;
                        order.quantity_open -= quantity;
                        order.time_filled_utc = Some(time.clone());
-                       ledger_service.update_or_create_position(&account, symbol_name.clone(), symbol_code.clone(), quantity.clone(), side.clone(), time_utc, *price, tag.to_string(), None, order_id.clone()).await;
+                       ledger_service.update_or_create_position(&account, symbol_name.clone(), symbol_code.clone(), quantity.clone(), side.clone(), time_utc, *price, tag.to_string(), None, order_id.clone(), BrokerFill { fill_id: fill_id.clone(), liquidity: *liquidity, commission: *commission, broker_order_id: order.broker_order_id.clone() }).await;
                        match strategy_event_sender.send(StrategyEvent::OrderEvents(order_update_event.clone())).await {
                            Ok(_) => {}
                            Err(e) => eprintln!("{}", e)
                        }
                    }
                 }
-                OrderUpdateEvent::OrderCancelled { order_id,symbol_code,.. } => {
+                OrderUpdateEvent::OrderCancelled { account, symbol_name, order_id, symbol_code, tag, time, .. } => {
+                    if let Some((order_id, mut order)) = open_order_cache.remove(order_id) {
+                        // brokers cancel day orders at the session close, the strategy sees them expire as in backtests
+                        let event = match order.is_expired(time_utc) {
+                            true => OrderUpdateEvent::OrderExpired { account: account.clone(), symbol_name: symbol_name.clone(), symbol_code: symbol_code.clone(), order_id: order_id.clone(), tag: tag.clone(), time: time.clone() },
+                            false => order_update_event.clone(),
+                        };
+                        order.state = event.state_change().unwrap_or(OrderState::Cancelled);
+                        order.quantity_open = dec!(0);
+                        order.symbol_code = symbol_code.clone();
+                        closed_order_cache.insert(order_id.clone(), order);
+                        match strategy_event_sender.send(StrategyEvent::OrderEvents(event)).await {
+                            Ok(_) => {}
+                            Err(e) => eprintln!("{}", e)
+                        }
+                    }
+                }
+                OrderUpdateEvent::OrderExpired { order_id, symbol_code, .. } => {
                     if let Some((order_id, mut order)) = open_order_cache.remove(order_id) {
-                        order.state = OrderState::Cancelled;
+                        order.state = OrderState::Expired;
                         order.quantity_open = dec!(0);
                         order.symbol_code = symbol_code.clone();
                         closed_order_cache.insert(order_id.clone(), order);
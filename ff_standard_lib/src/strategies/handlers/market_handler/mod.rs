@@ -1,3 +1,6 @@
 pub mod backtest_matching_engine;
 pub mod live_order_matching;
-pub mod price_service;
\ No newline at end of file
+pub mod price_service;
+pub mod forex_session;
+pub mod aggressor_classifier;
+pub mod intrabar_path;
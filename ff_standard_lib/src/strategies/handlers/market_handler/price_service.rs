@@ -1,21 +1,55 @@
-use std::collections::BTreeMap;
-use std::sync::Arc;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+use chrono::{DateTime, Duration, Utc};
 use dashmap::DashMap;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::traits::BaseData;
 use crate::standardized_types::books::BookLevel;
 use crate::standardized_types::enums::OrderSide;
 use crate::standardized_types::new_types::Price;
 use crate::standardized_types::subscriptions::{SymbolCode, SymbolName};
 use crate::standardized_types::time_slices::TimeSlice;
 use crate::standardized_types::base_data::tick::Aggressor;
+use crate::strategies::handlers::market_handler::intrabar_path::{intrabar_model, IntrabarModel};
+
+/// The number of spreads kept per symbol for the normal spread.
+const SPREAD_HISTORY: usize = 200;
+/// The number of spreads needed before the normal spread is known.
+const MIN_SPREAD_SAMPLES: usize = 20;
+
+/// The best bid and ask and the last trade of a symbol after an update, see `touch_at()`.
+/// # Properties
+/// * `time` - The time of the data which updated the touch.
+/// * `bid`, `ask` - The top of book, None if the symbol has no book.
+/// * `last` - The last traded price, None if no ticks or candles have been received.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Touch {
+    pub time: DateTime<Utc>,
+    pub bid: Option<Price>,
+    pub ask: Option<Price>,
+    pub last: Option<Price>,
+}
 
 pub struct MarketPriceService {
     bid_books: DashMap<SymbolName, BTreeMap<u16, BookLevel>>,
     ask_books: DashMap<SymbolName, BTreeMap<u16, BookLevel>>,
     has_quotes: DashMap<SymbolName, bool>,
     last_price: DashMap<SymbolName, Price>,
+    /// The time of the latest data which updated the prices of the symbol, see `quote_age()`.
+    price_times: DashMap<SymbolName, DateTime<Utc>>,
+    paper_spreads: DashMap<SymbolName, Price>,
+    /// The latest spreads of the symbol, oldest first.
+    spreads: DashMap<SymbolName, VecDeque<Price>>,
+    /// How long touches are kept, None when they are not recorded.
+    touch_window: RwLock<Option<Duration>>,
+    touches: DashMap<SymbolName, VecDeque<Touch>>,
+    intrabar_paths: DashMap<SymbolName, Vec<Price>>,
+    /// The point of the intrabar paths the last prices are at, None when they are at the close.
+    intrabar_step: Mutex<Option<usize>>,
+    /// The intrabar model set when the service was created, see `set_intrabar_model()`.
+    intrabar_model: IntrabarModel,
 }
 
 impl MarketPriceService {
@@ -25,32 +59,157 @@ impl MarketPriceService {
             ask_books: DashMap::new(),
             has_quotes: DashMap::new(),
             last_price: DashMap::new(),
+            price_times: DashMap::new(),
+            paper_spreads: DashMap::new(),
+            spreads: DashMap::new(),
+            touch_window: RwLock::new(None),
+            touches: DashMap::new(),
+            intrabar_paths: DashMap::new(),
+            intrabar_step: Mutex::new(None),
+            intrabar_model: intrabar_model(),
+        }
+    }
+
+    pub(crate) fn intrabar_model(&self) -> IntrabarModel {
+        self.intrabar_model
+    }
+
+    /// The number of points in the longest intrabar path of the last update, 0 if no symbol has a path.
+    pub(crate) fn intrabar_steps(&self) -> usize {
+        self.intrabar_paths.iter().map(|path| path.value().len()).max().unwrap_or(0)
+    }
+
+    /// Moves the last price of each symbol with an intrabar path to the point of the path, shorter paths stay at their close.
+    pub(crate) fn set_intrabar_step(&self, step: usize) {
+        for path in self.intrabar_paths.iter() {
+            if let Some(price) = path.value().get(step).or(path.value().last()) {
+                self.last_price.insert(path.key().clone(), *price);
+            }
+        }
+        *self.intrabar_step.lock().unwrap() = Some(step);
+    }
+
+    /// Ends the intrabar paths, the last prices are left at the close.
+    pub(crate) fn finish_intrabar_paths(&self) {
+        self.intrabar_paths.clear();
+        *self.intrabar_step.lock().unwrap() = None;
+    }
+
+    /// The fill price of an order triggered at `level`, an order triggered while walking the intrabar path after the open was touched at its level,
+    /// at the open or without a path the order fills at the `market_price`.
+    pub(crate) fn intrabar_fill_price(&self, symbol_name: &SymbolName, level: Price, market_price: Price) -> Price {
+        match *self.intrabar_step.lock().unwrap() {
+            Some(step) if step > 0 && self.intrabar_paths.contains_key(symbol_name) => level,
+            _ => market_price,
         }
     }
 
+    /// Sets the spread used to fill the symbol when there is no quote data, None fills at the last price.
+    pub fn set_paper_spread(&self, symbol_name: SymbolName, spread: Option<Price>) {
+        match spread {
+            Some(spread) => {
+                // the book from tick aggressors is replaced by the spread
+                if !self.has_quotes.contains_key(&symbol_name) {
+                    self.bid_books.remove(&symbol_name);
+                    self.ask_books.remove(&symbol_name);
+                }
+                self.paper_spreads.insert(symbol_name, spread);
+            }
+            None => {
+                self.paper_spreads.remove(&symbol_name);
+            }
+        }
+    }
+
+    /// The last price moved to the side of the book the order fills against by half the paper spread.
+    fn fallback_price(&self, order_side: OrderSide, symbol_name: &SymbolName) -> Option<Price> {
+        let last_price = *self.last_price.get(symbol_name)?.value();
+        let half_spread = match self.paper_spreads.get(symbol_name) {
+            Some(spread) => *spread.value() / dec!(2),
+            None => return Some(last_price),
+        };
+        match order_side {
+            OrderSide::Buy => Some(last_price + half_spread),
+            OrderSide::Sell => Some(last_price - half_spread),
+        }
+    }
+
+    fn record_spread(&self, symbol_name: &SymbolName, bid: Price, ask: Price) {
+        let mut spreads = self.spreads.entry(symbol_name.clone()).or_insert_with(VecDeque::new);
+        if spreads.len() == SPREAD_HISTORY {
+            spreads.pop_front();
+        }
+        spreads.push_back(ask - bid);
+    }
+
+    /// Keeps the touches of each symbol for the window, None stops recording them and clears the history.
+    pub(crate) fn set_touch_window(&self, window: Option<Duration>) {
+        *self.touch_window.write().unwrap() = window;
+        if window.is_none() {
+            self.touches.clear();
+        }
+    }
+
+    fn record_touch(&self, base_data: &BaseDataEnum) {
+        let Some(window) = *self.touch_window.read().unwrap() else {
+            return;
+        };
+        if let BaseDataEnum::Candle(_) = base_data {
+            return;
+        }
+        let symbol_name = &base_data.symbol().name;
+        let time = base_data.time_closed_utc();
+        let touch = Touch {
+            time,
+            bid: self.bid_books.get(symbol_name).and_then(|book| book.get(&0).map(|level| level.price)),
+            ask: self.ask_books.get(symbol_name).and_then(|book| book.get(&0).map(|level| level.price)),
+            last: self.get_last_price(symbol_name),
+        };
+        let mut touches = self.touches.entry(symbol_name.clone()).or_insert_with(VecDeque::new);
+        touches.push_back(touch);
+        while touches.front().map_or(false, |oldest| time - oldest.time > window) {
+            touches.pop_front();
+        }
+    }
+
+    /// The touch of the symbol at the time, the last touch recorded at or before it, None if touches are not recorded or none are that old.
+    pub fn touch_at(&self, symbol_name: &SymbolName, time: DateTime<Utc>) -> Option<Touch> {
+        let touches = self.touches.get(symbol_name)?;
+        touches.iter().rev().find(|touch| touch.time <= time).cloned()
+    }
+
     pub fn update_market_data(&self, time_slice: Arc<TimeSlice>) {
         for base_data in time_slice.iter() {
             match base_data {
                 BaseDataEnum::Candle(candle) => {
+                    self.price_times.insert(candle.symbol.name.clone(), base_data.time_closed_utc());
                     self.last_price.insert(candle.symbol.name.clone(), candle.close);
+                    // symbols with a book fill against the book, not the last price
+                    let model = self.intrabar_model;
+                    if model != IntrabarModel::CloseOnly && !self.bid_books.contains_key(&candle.symbol.name) {
+                        self.intrabar_paths.insert(candle.symbol.name.clone(), model.path(candle));
+                    }
                 }
                 BaseDataEnum::QuoteBar(quotebar) => {
                     let symbol_name = &quotebar.symbol.name;
                     if self.has_quotes.contains_key(symbol_name) {
                         continue;
                     }
+                    self.price_times.insert(symbol_name.clone(), base_data.time_closed_utc());
 
                     let mut bid_book = self.bid_books.entry(symbol_name.clone()).or_insert_with(BTreeMap::new);
                     let mut ask_book = self.ask_books.entry(symbol_name.clone()).or_insert_with(BTreeMap::new);
 
                     bid_book.insert(0, BookLevel::new(0, quotebar.bid_close, dec!(0.0)));
                     ask_book.insert(0, BookLevel::new(0, quotebar.ask_close, dec!(0.0)));
+                    self.record_spread(symbol_name, quotebar.bid_close, quotebar.ask_close);
                 }
                 BaseDataEnum::Tick(tick) => {
                     let symbol_name = &tick.symbol.name;
+                    self.price_times.insert(symbol_name.clone(), base_data.time_closed_utc());
                     self.last_price.insert(symbol_name.clone(), tick.price);
 
-                    if tick.aggressor != Aggressor::None && !self.has_quotes.contains_key(symbol_name) {
+                    if tick.aggressor != Aggressor::None && !self.has_quotes.contains_key(symbol_name) && !self.paper_spreads.contains_key(symbol_name) {
                         let mut bid_book = self.bid_books.entry(symbol_name.clone()).or_insert_with(BTreeMap::new);
                         let mut ask_book = self.ask_books.entry(symbol_name.clone()).or_insert_with(BTreeMap::new);
 
@@ -62,18 +221,33 @@ impl MarketPriceService {
                     }
                 }
                 BaseDataEnum::Quote(quote) => {
+                    // every quote updates the top of book, otherwise fills would use the first quote received
                     let symbol_name = &quote.symbol.name;
-                    if !self.has_quotes.contains_key(symbol_name) {
-                        self.has_quotes.insert(symbol_name.clone(), true);
-                        let mut bid_book = self.bid_books.entry(symbol_name.clone()).or_insert_with(BTreeMap::new);
-                        let mut ask_book = self.ask_books.entry(symbol_name.clone()).or_insert_with(BTreeMap::new);
+                    self.price_times.insert(symbol_name.clone(), base_data.time_closed_utc());
+                    self.has_quotes.insert(symbol_name.clone(), true);
+                    let mut bid_book = self.bid_books.entry(symbol_name.clone()).or_insert_with(BTreeMap::new);
+                    let mut ask_book = self.ask_books.entry(symbol_name.clone()).or_insert_with(BTreeMap::new);
 
-                        bid_book.insert(0, BookLevel::new(0, quote.bid, quote.bid_volume));
-                        ask_book.insert(0, BookLevel::new(0, quote.ask, quote.ask_volume));
+                    bid_book.insert(0, BookLevel::new(0, quote.bid, quote.bid_volume));
+                    ask_book.insert(0, BookLevel::new(0, quote.ask, quote.ask_volume));
+                    self.record_spread(symbol_name, quote.bid, quote.ask);
+                }
+                BaseDataEnum::Depth(depth) => {
+                    // the full book replaces the top of book, fills walk the levels
+                    let symbol_name = &depth.symbol.name;
+                    self.price_times.insert(symbol_name.clone(), base_data.time_closed_utc());
+                    self.has_quotes.insert(symbol_name.clone(), true);
+                    let bid_book: BTreeMap<u16, BookLevel> = depth.bids.iter().enumerate().map(|(level, book_level)| (level as u16, book_level.clone())).collect();
+                    let ask_book: BTreeMap<u16, BookLevel> = depth.asks.iter().enumerate().map(|(level, book_level)| (level as u16, book_level.clone())).collect();
+                    if let (Some(bid), Some(ask)) = (bid_book.get(&0), ask_book.get(&0)) {
+                        self.record_spread(symbol_name, bid.price, ask.price);
                     }
+                    self.bid_books.insert(symbol_name.clone(), bid_book);
+                    self.ask_books.insert(symbol_name.clone(), ask_book);
                 }
                 _ => eprintln!("Market Price Service: Incorrect data type in Market Updates: {}", base_data.base_data_type())
             }
+            self.record_touch(base_data);
         }
     }
 
@@ -86,14 +260,41 @@ impl MarketPriceService {
         if let Some(symbol_book) = order_book {
             symbol_book.get(&0).map(|level| level.price.clone())
         } else {
-            if let Some(value) = self.last_price.get(symbol_name) {
-                Some(value.clone())
-            } else {
-                None
-            }
+            self.fallback_price(order_side, symbol_name)
         }
     }
 
+    /// How long before `time` the prices of the symbol were last updated, None if no data has been received, quote bars are ignored once the symbol has quotes.
+    pub fn quote_age(&self, symbol_name: &SymbolName, time: DateTime<Utc>) -> Option<Duration> {
+        self.price_times.get(symbol_name).map(|updated| time - *updated.value())
+    }
+
+    /// The current spread of the symbol, the best ask less the best bid, None without quotes, quote bars or depth.
+    pub fn spread(&self, symbol_name: &SymbolName) -> Option<Price> {
+        if !self.spreads.contains_key(symbol_name) {
+            return None;
+        }
+        let bid = self.bid_books.get(symbol_name)?.get(&0)?.price;
+        let ask = self.ask_books.get(symbol_name)?.get(&0)?.price;
+        Some(ask - bid)
+    }
+
+    /// The normal spread of the symbol, the median of its last 200 spreads, None until 20 spreads have been received.
+    pub fn normal_spread(&self, symbol_name: &SymbolName) -> Option<Price> {
+        let spreads = self.spreads.get(symbol_name)?;
+        if spreads.len() < MIN_SPREAD_SAMPLES {
+            return None;
+        }
+        let mut sorted: Vec<Price> = spreads.iter().cloned().collect();
+        sorted.sort();
+        Some(sorted[sorted.len() / 2])
+    }
+
+    /// The last traded price or close of the symbol, None if no ticks or candles have been received.
+    pub fn get_last_price(&self, symbol_name: &SymbolName) -> Option<Price> {
+        self.last_price.get(symbol_name).map(|price| *price.value())
+    }
+
     pub fn estimate_fill_price(&self, order_side: OrderSide, symbol_name: &SymbolName, symbol_code: &SymbolCode, volume: Decimal) -> Option<Decimal> {
         let order_book = match order_side {
             OrderSide::Buy => self.ask_books.get(symbol_code).or_else(|| self.ask_books.get(symbol_name)),
@@ -132,10 +333,7 @@ impl MarketPriceService {
             }
         }
 
-        match self.last_price.get(symbol_name) {
-            Some(price) => Some(price.clone()),
-            None => None,
-        }
+        self.fallback_price(order_side, symbol_name)
     }
 
     pub fn estimate_limit_fill(&self, order_side: OrderSide, symbol_name: &SymbolName, symbol_code: &SymbolCode, volume: Decimal, limit: Decimal) -> Option<(Decimal, Decimal)> {
@@ -182,11 +380,83 @@ impl MarketPriceService {
             }
         }
 
-        if let Some(price) = self.last_price.get(symbol_name) {
-            Some((price.clone(), volume))
-        } else {
-            None
-        }
+        self.fallback_price(order_side, symbol_name).map(|price| (price, volume))
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use crate::standardized_types::base_data::candle::Candle;
+    use crate::standardized_types::base_data::quote::Quote;
+    use crate::standardized_types::base_data::quotebar::QuoteBar;
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::MarketType;
+    use crate::standardized_types::resolution::Resolution;
+    use crate::standardized_types::subscriptions::{CandleType, Symbol};
+
+    fn symbol() -> Symbol {
+        Symbol::new("EUR-USD".to_string(), DataVendor::DataBento, MarketType::Forex)
+    }
+
+    #[test]
+    fn test_quotes_update_the_book() {
+        let service = MarketPriceService::new();
+        let name = "EUR-USD".to_string();
+        for (bid, ask) in [(dec!(1.1000), dec!(1.1002)), (dec!(1.1010), dec!(1.1012))] {
+            let quote = Quote::new(symbol(), ask, bid, dec!(100), dec!(100), Utc::now().to_string());
+            service.update_market_data(Arc::new(TimeSlice::from_iter([BaseDataEnum::Quote(quote)])));
+        }
+        assert_eq!(service.get_market_price(OrderSide::Buy, &name, &name), Some(dec!(1.1012)));
+        assert_eq!(service.get_market_price(OrderSide::Sell, &name, &name), Some(dec!(1.1010)));
+    }
+
+    #[test]
+    fn test_normal_spread() {
+        let service = MarketPriceService::new();
+        let name = "EUR-USD".to_string();
+        let quote = |bid: Price, ask: Price| Arc::new(TimeSlice::from_iter([BaseDataEnum::Quote(Quote::new(symbol(), ask, bid, dec!(100), dec!(100), Utc::now().to_string()))]));
+        for _ in 0..MIN_SPREAD_SAMPLES - 1 {
+            service.update_market_data(quote(dec!(1.1000), dec!(1.1001)));
+        }
+        assert_eq!(service.spread(&name), Some(dec!(0.0001)));
+        assert_eq!(service.normal_spread(&name), None);
+
+        // a blown out spread does not move the median
+        service.update_market_data(quote(dec!(1.0990), dec!(1.1010)));
+        assert_eq!(service.spread(&name), Some(dec!(0.0020)));
+        assert_eq!(service.normal_spread(&name), Some(dec!(0.0001)));
+    }
+
+    #[test]
+    fn test_paper_spread() {
+        let service = MarketPriceService::new();
+        let name = "EUR-USD".to_string();
+        let candle = Candle::new(symbol(), dec!(1.1000), dec!(0), dec!(0), dec!(0), Utc::now().to_string(), Resolution::Minutes(1), CandleType::CandleStick);
+        service.update_market_data(Arc::new(TimeSlice::from_iter([BaseDataEnum::Candle(candle)])));
+        assert_eq!(service.estimate_fill_price(OrderSide::Buy, &name, &name, dec!(1000)), Some(dec!(1.1000)));
+
+        service.set_paper_spread(name.clone(), Some(dec!(0.0002)));
+        assert_eq!(service.estimate_fill_price(OrderSide::Buy, &name, &name, dec!(1000)), Some(dec!(1.1001)));
+        assert_eq!(service.estimate_fill_price(OrderSide::Sell, &name, &name, dec!(1000)), Some(dec!(1.0999)));
+        assert_eq!(service.estimate_limit_fill(OrderSide::Sell, &name, &name, dec!(1000), dec!(1.0999)), Some((dec!(1.0999), dec!(1000))));
+    }
+
+    #[test]
+    fn test_quote_age() {
+        let service = MarketPriceService::new();
+        let name = "EUR-USD".to_string();
+        let time = Utc::now();
+        assert_eq!(service.quote_age(&name, time), None);
+        let quote = Quote::new(symbol(), dec!(1.1002), dec!(1.1000), dec!(100), dec!(100), time.to_string());
+        service.update_market_data(Arc::new(TimeSlice::from_iter([BaseDataEnum::Quote(quote)])));
+        assert_eq!(service.quote_age(&name, time + Duration::seconds(5)), Some(Duration::seconds(5)));
+
+        // a quote bar does not refresh a symbol with quotes
+        let quote_bar = QuoteBar::new(symbol(), dec!(1.1), dec!(1.1), dec!(0), dec!(0), dec!(0), (time + Duration::seconds(4)).to_string(), Resolution::Seconds(1), CandleType::CandleStick);
+        service.update_market_data(Arc::new(TimeSlice::from_iter([BaseDataEnum::QuoteBar(quote_bar)])));
+        assert_eq!(service.quote_age(&name, time + Duration::seconds(5)), Some(Duration::seconds(5)));
+    }
+}
@@ -4,4 +4,33 @@ pub(crate) mod subscription_handler;
 pub(crate) mod timed_events_handler;
 pub(crate) mod indicator_handler;
 pub(crate) mod market_handler;
-pub(crate) mod live_warmup;
\ No newline at end of file
+pub(crate) mod live_warmup;
+pub(crate) mod price_trigger_handler;
+pub(crate) mod event_tail_handler;
+pub(crate) mod shutdown_handler;
+pub(crate) mod pair_handler;
+pub(crate) mod order_hooks;
+pub(crate) mod flat_by_handler;
+pub(crate) mod closed_market_queue;
+pub(crate) mod stale_order_handler;
+pub(crate) mod contract_expiry_handler;
+pub(crate) mod watchdog;
+pub(crate) mod daily_report_handler;
+pub(crate) mod spread_guard;
+pub(crate) mod reduce_only;
+pub(crate) mod order_gate;
+pub mod control_server;
+
+pub use market_handler::forex_session::{set_forex_session_model, ForexSession, ForexSessionModel};
+pub use market_handler::intrabar_path::{set_intrabar_model, IntrabarModel};
+pub use live_warmup::{set_live_warm_up_source, WarmUpSource};
+pub use event_tail_handler::serve_event_tail;
+pub use pair_handler::{LeggingRisk, Pair, PairLeg, PairOrder};
+pub use order_hooks::{PostFillHook, PreOrderHook};
+pub use flat_by_handler::FlatByRule;
+pub use stale_order_handler::StaleOrderRule;
+pub use contract_expiry_handler::ContractExpiryPolicy;
+pub use watchdog::{HealthWarning, WatchdogSettings};
+pub use daily_report_handler::DailyReportSettings;
+pub use spread_guard::{SpreadAction, SpreadGuard, SpreadLimit};
+pub use reduce_only::ReduceOnlyHandle;
@@ -0,0 +1,252 @@
+use std::sync::{Arc, RwLock};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use tokio::sync::mpsc;
+use crate::standardized_types::enums::StrategyMode;
+use crate::standardized_types::orders::{Order, OrderError, OrderId, OrderRequest, OrderType, OrderUpdateEvent};
+use crate::standardized_types::position::Position;
+use crate::standardized_types::subscriptions::SymbolName;
+use crate::strategies::exposure_groups::ExposureGroups;
+use crate::strategies::historical_time::WarmUpState;
+use crate::strategies::handlers::closed_market_queue::ClosedMarketQueue;
+use crate::strategies::handlers::market_handler::price_service::MarketPriceService;
+use crate::strategies::handlers::spread_guard::{SpreadAction, SpreadGuardHandler};
+use crate::strategies::handlers::watchdog::HealthWarning;
+use crate::strategies::ledgers::ledger_service::LedgerService;
+use crate::strategies::strategy_events::StrategyEvent;
+use crate::strategies::strategy_settings::StrategySettings;
+use crate::strategies::trading_windows::{is_entry, TradingWindows};
+
+/// The checks every order of the strategy passes before it is sent, by the order fns, for each leg of a pair, and again when the engine releases an order it held.
+pub(crate) struct OrderGate {
+    mode: StrategyMode,
+    warm_up: WarmUpState,
+    ledger_service: Arc<LedgerService>,
+    market_price_service: Arc<MarketPriceService>,
+    open_order_cache: Arc<DashMap<OrderId, Order>>,
+    closed_market_queue: Arc<ClosedMarketQueue>,
+    spread_guard_handler: Arc<SpreadGuardHandler>,
+    strategy_event_sender: mpsc::Sender<StrategyEvent>,
+    settings: Arc<StrategySettings>,
+    pub(crate) trading_windows: TradingWindows,
+    pub(crate) exposure_groups: ExposureGroups,
+    pub(crate) max_quote_age: RwLock<Option<Duration>>,
+}
+
+impl OrderGate {
+    pub(crate) fn new(
+        mode: StrategyMode,
+        warm_up: WarmUpState,
+        ledger_service: Arc<LedgerService>,
+        market_price_service: Arc<MarketPriceService>,
+        open_order_cache: Arc<DashMap<OrderId, Order>>,
+        closed_market_queue: Arc<ClosedMarketQueue>,
+        spread_guard_handler: Arc<SpreadGuardHandler>,
+        strategy_event_sender: mpsc::Sender<StrategyEvent>,
+        settings: Arc<StrategySettings>,
+    ) -> Self {
+        OrderGate {
+            mode,
+            warm_up,
+            ledger_service,
+            market_price_service,
+            open_order_cache,
+            closed_market_queue,
+            spread_guard_handler,
+            strategy_event_sender,
+            settings,
+            trading_windows: TradingWindows::default(),
+            exposure_groups: ExposureGroups::default(),
+            max_quote_age: RwLock::new(None),
+        }
+    }
+
+    fn time_utc(&self) -> DateTime<Utc> {
+        self.warm_up.strategy_time(self.mode)
+    }
+
+    /// Returns true if the order would open, increase or reverse a position of its account.
+    pub(crate) fn is_entry_order(&self, order: &Order, order_type: &OrderType) -> bool {
+        let long_quantity = match self.ledger_service.is_long(&order.account, &order.symbol_code) {
+            true => self.ledger_service.position_size(&order.account, &order.symbol_code),
+            false => Decimal::ZERO,
+        };
+        let short_quantity = match self.ledger_service.is_short(&order.account, &order.symbol_code) {
+            true => self.ledger_service.position_size(&order.account, &order.symbol_code),
+            false => Decimal::ZERO,
+        };
+        is_entry(order_type, order.side, order.quantity_open, long_quantity, short_quantity)
+    }
+
+    /// Runs every check, returns the time to reject the order at if the spread guard delays it.
+    /// The `pending` orders count as working orders for the exposure groups, eg the legs of a pair checked before this one.
+    pub(crate) async fn check(&self, order: &Order, order_type: &OrderType, pending: &[Order]) -> Result<Option<DateTime<Utc>>, OrderError> {
+        self.check_entry(order, order_type, pending)?;
+        if order_type.is_market() {
+            self.check_quote_age(&order.symbol_name).await?;
+        }
+        self.check_spread(order, order_type).await
+    }
+
+    /// Refuses an entry in reduce only mode or outside the trading windows of the symbol, and an order which could take an exposure group past its max.
+    pub(crate) fn check_entry(&self, order: &Order, order_type: &OrderType, pending: &[Order]) -> Result<(), OrderError> {
+        if self.is_entry_order(order, order_type) {
+            if self.settings.reduce_only.is_reduce_only() {
+                return Err(OrderError::ReduceOnly(order.symbol_name.clone()));
+            }
+            if !self.trading_windows.is_active(&order.symbol_name, self.time_utc()) {
+                return Err(OrderError::OutsideTradingWindow(order.symbol_name.clone()));
+            }
+        }
+        self.check_exposure(order, pending)
+    }
+
+    /// Refuses an order which could take an exposure group of its symbol past the max, with the working, queued, delayed and `pending` orders filled.
+    fn check_exposure(&self, order: &Order, pending: &[Order]) -> Result<(), OrderError> {
+        if !self.exposure_groups.contains(&order.symbol_name) {
+            return Ok(());
+        }
+        let positions = self.open_positions();
+        let mut working_orders: Vec<Order> = self.open_order_cache.iter().map(|order| order.value().clone()).collect();
+        working_orders.extend(self.closed_market_queue.orders());
+        working_orders.extend(self.spread_guard_handler.orders());
+        working_orders.extend(pending.iter().cloned());
+        match self.exposure_groups.exceeded_by(order, &positions, &working_orders) {
+            Some(group) => Err(OrderError::ExposureLimit(group)),
+            None => Ok(()),
+        }
+    }
+
+    /// The open positions of every strategy account.
+    pub(crate) fn open_positions(&self) -> Vec<Position> {
+        self.ledger_service.ledgers.iter()
+            .flat_map(|ledger| ledger.value().positions.iter().map(|position| position.value().clone()).collect::<Vec<Position>>())
+            .collect()
+    }
+
+    /// Refuses a market order if the prices of the symbol are older than the max quote age, and sends the warning.
+    pub(crate) async fn check_quote_age(&self, symbol_name: &SymbolName) -> Result<(), OrderError> {
+        let Some(max_quote_age) = *self.max_quote_age.read().unwrap() else {
+            return Ok(());
+        };
+        let age = match self.market_price_service.quote_age(symbol_name, self.time_utc()) {
+            Some(age) if age <= max_quote_age => return Ok(()),
+            Some(age) => age,
+            None => return Err(OrderError::NoMarketPrice(symbol_name.clone())),
+        };
+        let warning = HealthWarning::StaleQuote { symbol_name: symbol_name.clone(), age_millis: age.num_milliseconds().max(0) as u64 };
+        eprintln!("{}", warning);
+        self.settings.record_risk_event(self.time_utc(), warning.to_string());
+        if let Err(e) = self.strategy_event_sender.send(StrategyEvent::HealthWarning(warning)).await {
+            eprintln!("Stale Quote: Failed to send event: {}", e);
+        }
+        Err(OrderError::StaleQuote(symbol_name.clone()))
+    }
+
+    /// Checks the spread of a market, limit, enter long or enter short entry against the spread guard of the symbol.
+    /// Returns the time to reject the order at if it should be delayed, refuses the order and sends the warning if it should be rejected.
+    pub(crate) async fn check_spread(&self, order: &Order, order_type: &OrderType) -> Result<Option<DateTime<Utc>>, OrderError> {
+        if !matches!(order_type, OrderType::Market | OrderType::Limit | OrderType::EnterLong | OrderType::EnterShort) {
+            return Ok(None);
+        }
+        let Some(guard) = self.spread_guard_handler.guard(&order.symbol_name) else {
+            return Ok(None);
+        };
+        let Some((spread, max_spread)) = self.spread_guard_handler.wide_spread(&order.symbol_name, &guard.limit) else {
+            return Ok(None);
+        };
+        if !self.is_entry_order(order, order_type) {
+            return Ok(None);
+        }
+        match guard.action {
+            SpreadAction::Delay(delay) => Ok(Some(self.time_utc() + delay)),
+            SpreadAction::Reject => {
+                let warning = HealthWarning::WideSpread { symbol_name: order.symbol_name.clone(), spread, max_spread, delayed: false };
+                eprintln!("{}", warning);
+                self.settings.record_risk_event(self.time_utc(), warning.to_string());
+                if let Err(e) = self.strategy_event_sender.send(StrategyEvent::HealthWarning(warning)).await {
+                    eprintln!("Spread Guard: Failed to send event: {}", e);
+                }
+                Err(OrderError::WideSpread(order.symbol_name.clone()))
+            }
+        }
+    }
+}
+
+/// Checks the orders the engine held, queued until the open or delayed by the spread guard, again when they are released and forwards them to the `order_sender`.
+/// The checks may have changed since the order was held, eg reduce only was switched on or an exposure group filled up, a refused order is rejected with `OrderUpdateEvent::OrderRejected`.
+/// An order released into a wide spread is delayed by the spread guard.
+pub(crate) fn route_held_orders(
+    mut request_receiver: mpsc::Receiver<OrderRequest>,
+    gate: Arc<OrderGate>,
+    order_sender: mpsc::Sender<OrderRequest>,
+) {
+    tokio::spawn(async move {
+        while let Some(order_request) = request_receiver.recv().await {
+            if let OrderRequest::Create { order, order_type, .. } = &order_request {
+                match gate.check(order, order_type, &[]).await {
+                    Ok(None) => {}
+                    Ok(Some(reject_at)) => {
+                        gate.spread_guard_handler.delay(order.clone(), order_type.clone(), reject_at);
+                        continue;
+                    }
+                    Err(e) => {
+                        let event = OrderUpdateEvent::OrderRejected {
+                            account: order.account.clone(),
+                            symbol_name: order.symbol_name.clone(),
+                            symbol_code: order.symbol_code.clone(),
+                            order_id: order.id.clone(),
+                            reason: e.to_string(),
+                            tag: order.tag.clone(),
+                            time: gate.time_utc().to_string(),
+                        };
+                        if let Err(e) = gate.strategy_event_sender.send(StrategyEvent::OrderEvents(event)).await {
+                            eprintln!("Held Orders: Failed to send event: {}", e);
+                        }
+                        continue;
+                    }
+                }
+            }
+            if let Err(e) = order_sender.send(order_request).await {
+                eprintln!("Held Orders: Failed to send order request: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::accounts::Account;
+    use crate::standardized_types::broker_enum::Brokerage;
+    use crate::standardized_types::enums::OrderSide;
+    use crate::strategies::exposure_groups::ExposureGroup;
+
+    #[tokio::test]
+    async fn test_reduce_only_refuses_entries() {
+        let (event_sender, _event_receiver) = mpsc::channel(10);
+        let settings = Arc::new(StrategySettings::new(StrategyMode::Backtest));
+        let price_service = Arc::new(MarketPriceService::new());
+        let ledger_service = Arc::new(LedgerService::new(event_sender.clone(), price_service.clone(), settings.clone()));
+        let (queued_sender, _queued_receiver) = mpsc::channel(10);
+        let closed_market_queue = Arc::new(ClosedMarketQueue::new(queued_sender, WarmUpState::new()));
+        let (delayed_sender, _delayed_receiver) = mpsc::channel(10);
+        let spread_guard_handler = Arc::new(SpreadGuardHandler::new(price_service.clone(), delayed_sender, event_sender.clone(), WarmUpState::new(), settings.clone()));
+        let gate = OrderGate::new(StrategyMode::Backtest, WarmUpState::new(), ledger_service, price_service, Arc::new(DashMap::new()), closed_market_queue, spread_guard_handler, event_sender, settings.clone());
+        let account = Account::new(Brokerage::Test, "Test_Account_1".to_string());
+        let order = |order_id: &str| Order::market_order("MNQ".to_string(), None, &account, dec!(10), OrderSide::Buy, "Entry".to_string(), order_id.to_string(), Utc::now(), None);
+
+        assert_eq!(gate.check(&order("1"), &OrderType::Market, &[]).await, Ok(None));
+        settings.reduce_only.set(true);
+        assert_eq!(gate.check(&order("1"), &OrderType::Market, &[]).await, Err(OrderError::ReduceOnly("MNQ".to_string())));
+        settings.reduce_only.set(false);
+
+        // the legs checked before count against the group
+        gate.exposure_groups.set(ExposureGroup::new("US Equity Index", dec!(15)).with_symbol("MNQ".to_string(), dec!(1)));
+        assert!(gate.check_entry(&order("2"), &OrderType::Market, &[]).is_ok());
+        assert_eq!(gate.check_entry(&order("2"), &OrderType::Market, &[order("1")]), Err(OrderError::ExposureLimit("US Equity Index".to_string())));
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::{Arc, RwLock};
+use tokio::sync::mpsc;
+use crate::standardized_types::orders::{Order, OrderError, OrderUpdateEvent};
+use crate::strategies::strategy_events::StrategyEvent;
+
+/// A hook run before an order is sent, return `Err(OrderError::RefusedByHook(reason))` to refuse the order.
+pub type PreOrderHook = Arc<dyn Fn(&mut Order) -> Result<(), OrderError> + Send + Sync>;
+
+/// A hook run when an order is filled or partially filled.
+pub type PostFillHook = Arc<dyn Fn(&OrderUpdateEvent) + Send + Sync>;
+
+pub(crate) struct OrderHooks {
+    pre_order: RwLock<Vec<(String, PreOrderHook)>>,
+    post_fill: RwLock<Vec<(String, PostFillHook)>>,
+}
+
+impl OrderHooks {
+    pub(crate) fn new() -> Self {
+        OrderHooks {
+            pre_order: RwLock::new(vec![]),
+            post_fill: RwLock::new(vec![]),
+        }
+    }
+
+    /// Adds the hook, or replaces the pre order hook with the same name.
+    pub(crate) fn add_pre_order(&self, name: String, hook: PreOrderHook) {
+        let mut hooks = self.pre_order.write().unwrap();
+        match hooks.iter_mut().find(|(hook_name, _)| *hook_name == name) {
+            Some(existing) => existing.1 = hook,
+            None => hooks.push((name, hook)),
+        }
+    }
+
+    /// Adds the hook, or replaces the post fill hook with the same name.
+    pub(crate) fn add_post_fill(&self, name: String, hook: PostFillHook) {
+        let mut hooks = self.post_fill.write().unwrap();
+        match hooks.iter_mut().find(|(hook_name, _)| *hook_name == name) {
+            Some(existing) => existing.1 = hook,
+            None => hooks.push((name, hook)),
+        }
+    }
+
+    /// Removes the pre order and post fill hooks with the name.
+    pub(crate) fn remove(&self, name: &str) {
+        self.pre_order.write().unwrap().retain(|(hook_name, _)| hook_name != name);
+        self.post_fill.write().unwrap().retain(|(hook_name, _)| hook_name != name);
+    }
+
+    /// Runs the pre order hooks in order, the first refusal stops the order.
+    pub(crate) fn run_pre_order(&self, order: &mut Order) -> Result<(), OrderError> {
+        let hooks = self.pre_order.read().unwrap().clone();
+        for (_, hook) in hooks {
+            hook(order)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn run_post_fill(&self, event: &OrderUpdateEvent) {
+        if !matches!(event, OrderUpdateEvent::OrderFilled { .. } | OrderUpdateEvent::OrderPartiallyFilled { .. }) {
+            return;
+        }
+        let hooks = self.post_fill.read().unwrap().clone();
+        for (_, hook) in hooks {
+            hook(event);
+        }
+    }
+
+    /// Returns a sender which runs the post fill hooks on the order events, then forwards all events to the `strategy_event_sender`.
+    pub(crate) fn tap(self: Arc<Self>, strategy_event_sender: mpsc::Sender<StrategyEvent>) -> mpsc::Sender<StrategyEvent> {
+        let (tap_sender, mut tap_receiver) = mpsc::channel(strategy_event_sender.max_capacity());
+        tokio::spawn(async move {
+            while let Some(event) = tap_receiver.recv().await {
+                if let StrategyEvent::OrderEvents(order_event) = &event {
+                    self.run_post_fill(order_event);
+                }
+                if let Err(e) = strategy_event_sender.send(event).await {
+                    eprintln!("Order Hooks: Failed to forward event: {}", e);
+                    break;
+                }
+            }
+        });
+        tap_sender
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::accounts::Account;
+    use crate::standardized_types::broker_enum::Brokerage;
+    use crate::standardized_types::enums::OrderSide;
+
+    fn account() -> Account {
+        Account::new(Brokerage::Test, "Test_Account_1".to_string())
+    }
+
+    fn order() -> Order {
+        Order::market_order("EUR-USD".to_string(), None, &account(), dec!(1), OrderSide::Buy, "Entry".to_string(), "1".to_string(), Utc::now(), None)
+    }
+
+    fn fill() -> OrderUpdateEvent {
+        OrderUpdateEvent::OrderFilled {
+            account: account(),
+            symbol_name: "EUR-USD".to_string(),
+            symbol_code: "EUR-USD".to_string(),
+            order_id: "1".to_string(),
+            side: OrderSide::Buy,
+            price: dec!(1.1),
+            quantity: dec!(1),
+            tag: "Entry".to_string(),
+            time: Utc::now().to_string(),
+            fill_id: None,
+            liquidity: None,
+            commission: None,
+        }
+    }
+
+    #[test]
+    fn test_pre_order_hooks_run_in_order() {
+        let hooks = OrderHooks::new();
+        hooks.add_pre_order("tag".to_string(), Arc::new(|order: &mut Order| {
+            order.tag = format!("{} checked", order.tag);
+            Ok(())
+        }));
+        hooks.add_pre_order("max size".to_string(), Arc::new(|order: &mut Order| match order.quantity_open > dec!(5) {
+            true => Err(OrderError::RefusedByHook("max size".to_string())),
+            false => Ok(()),
+        }));
+
+        let mut small = order();
+        assert_eq!(hooks.run_pre_order(&mut small), Ok(()));
+        assert_eq!(small.tag, "Entry checked");
+
+        let mut large = order();
+        large.quantity_open = dec!(10);
+        assert_eq!(hooks.run_pre_order(&mut large), Err(OrderError::RefusedByHook("max size".to_string())));
+
+        hooks.remove("max size");
+        assert_eq!(hooks.run_pre_order(&mut large), Ok(()));
+    }
+
+    #[test]
+    fn test_post_fill_hook_blocks_new_orders() {
+        let hooks = OrderHooks::new();
+        let halted = Arc::new(AtomicBool::new(false));
+        let fill_halted = halted.clone();
+        hooks.add_post_fill("halt".to_string(), Arc::new(move |_event: &OrderUpdateEvent| fill_halted.store(true, Ordering::SeqCst)));
+        let order_halted = halted.clone();
+        hooks.add_pre_order("halt".to_string(), Arc::new(move |_order: &mut Order| match order_halted.load(Ordering::SeqCst) {
+            true => Err(OrderError::RefusedByHook("halted".to_string())),
+            false => Ok(()),
+        }));
+
+        assert_eq!(hooks.run_pre_order(&mut order()), Ok(()));
+        hooks.run_post_fill(&fill());
+        assert!(halted.load(Ordering::SeqCst));
+        assert!(hooks.run_pre_order(&mut order()).is_err());
+    }
+}
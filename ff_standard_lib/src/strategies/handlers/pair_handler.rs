@@ -0,0 +1,420 @@
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use rust_decimal_macros::dec;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+use crate::messages::data_server_messaging::DataServerRequest;
+use crate::standardized_types::accounts::Account;
+use crate::standardized_types::enums::{OrderSide, StrategyMode};
+use crate::standardized_types::new_types::Volume;
+use crate::standardized_types::orders::{Order, OrderId, OrderRequest, OrderUpdateEvent};
+use crate::standardized_types::subscriptions::{SymbolCode, SymbolName};
+use crate::strategies::client_features::connection_types::ConnectionType;
+use crate::strategies::client_features::request_handler::{send_request, StrategyRequest};
+use crate::strategies::handlers::market_handler::backtest_matching_engine::BackTestEngineMessage;
+use crate::strategies::strategy_events::StrategyEvent;
+
+/// A leg of a pair, the quantity of the leg is the pair quantity × `ratio`.
+/// # Properties
+/// * `symbol_name` - The symbol name of the leg.
+/// * `symbol_code` - The symbol code for futures, eg `MNQZ4`, None to use the symbol name.
+/// * `exchange` - The exchange to route the leg to, None for the brokerage default.
+/// * `ratio` - The hedge ratio of the leg.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PairLeg {
+    pub symbol_name: SymbolName,
+    pub symbol_code: Option<SymbolCode>,
+    pub exchange: Option<String>,
+    pub ratio: Volume,
+}
+
+impl PairLeg {
+    pub fn new(symbol_name: SymbolName, symbol_code: Option<SymbolCode>, exchange: Option<String>, ratio: Volume) -> Self {
+        PairLeg {
+            symbol_name,
+            symbol_code,
+            exchange,
+            ratio,
+        }
+    }
+
+    /// The key of the leg position in the ledger.
+    pub(crate) fn position_key(&self) -> SymbolName {
+        self.symbol_code.clone().unwrap_or(self.symbol_name.clone())
+    }
+}
+
+/// Two legs traded as a spread, a long spread is long the `first` leg and short the `second` leg.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pair {
+    pub name: String,
+    pub first: PairLeg,
+    pub second: PairLeg,
+}
+
+impl Pair {
+    pub fn new(name: String, first: PairLeg, second: PairLeg) -> Self {
+        Pair {
+            name,
+            first,
+            second,
+        }
+    }
+}
+
+/// What to do when a leg of a pair is rejected or cancelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LeggingRisk {
+    /// Keep the legs which filled, the strategy manages the position from the order events.
+    Keep,
+    /// Cancel the other leg and close the quantity filled on both legs at market.
+    Unwind,
+    /// Send the unfilled quantity of the leg again, up to the number of times, then unwind.
+    Retry(u32),
+}
+
+/// The orders sent for a pair, in leg order. A leg which was already flat when exiting has no order.
+/// The ids of orders sent later to retry or unwind a leg are reported in the order events with the pair tag.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PairOrder {
+    pub pair: String,
+    pub order_ids: Vec<OrderId>,
+}
+
+#[derive(Clone, Debug)]
+struct LegExecution {
+    order: Order,
+    filled: Volume,
+    closed: bool,
+}
+
+/// The state of the legs of one pair order.
+#[derive(Clone, Debug)]
+pub(crate) struct PairExecution {
+    legs: Vec<LegExecution>,
+    legging: LeggingRisk,
+    retries: u32,
+    unwinding: bool,
+}
+
+fn opposite(side: OrderSide) -> OrderSide {
+    match side {
+        OrderSide::Buy => OrderSide::Sell,
+        OrderSide::Sell => OrderSide::Buy,
+    }
+}
+
+fn new_order_id() -> OrderId {
+    Uuid::new_v4().to_string()
+}
+
+impl PairExecution {
+    pub(crate) fn new(orders: Vec<Order>, legging: LeggingRisk) -> Self {
+        PairExecution {
+            legs: orders.into_iter().map(|order| LegExecution { order, filled: dec!(0), closed: false }).collect(),
+            legging,
+            retries: 0,
+            unwinding: false,
+        }
+    }
+
+    fn contains(&self, order_id: &OrderId) -> bool {
+        self.legs.iter().any(|leg| &leg.order.id == order_id)
+    }
+
+    /// True once no leg or retry is working.
+    fn is_complete(&self) -> bool {
+        self.legs.iter().all(|leg| leg.closed)
+    }
+
+    fn unwind_order(leg: &LegExecution, quantity: Volume, time: DateTime<Utc>) -> OrderRequest {
+        let order = Order::market_order(
+            leg.order.symbol_name.clone(),
+            Some(leg.order.symbol_code.clone()),
+            &leg.order.account,
+            quantity,
+            opposite(leg.order.side),
+            format!("{} Unwind", leg.order.tag),
+            new_order_id(),
+            time,
+            leg.order.exchange.clone(),
+        );
+        let order_type = order.order_type.clone();
+        OrderRequest::Create { account: order.account.clone(), order, order_type }
+    }
+
+    /// Updates the legs with the order event and returns the orders to send for the legging risk.
+    pub(crate) fn update(&mut self, event: &OrderUpdateEvent, time: DateTime<Utc>) -> Vec<OrderRequest> {
+        let Some(index) = self.legs.iter().position(|leg| &leg.order.id == event.order_id()) else {
+            return vec![];
+        };
+        match event {
+            OrderUpdateEvent::OrderFilled { quantity, .. } | OrderUpdateEvent::OrderPartiallyFilled { quantity, .. } => {
+                let leg = &mut self.legs[index];
+                leg.filled += *quantity;
+                if let OrderUpdateEvent::OrderFilled { .. } = event {
+                    leg.closed = true;
+                }
+                match self.unwinding {
+                    // a fill on a leg which could not be cancelled in time
+                    true => vec![Self::unwind_order(leg, *quantity, time)],
+                    false => vec![],
+                }
+            }
+            OrderUpdateEvent::OrderRejected { .. } | OrderUpdateEvent::OrderCancelled { .. } | OrderUpdateEvent::OrderExpired { .. } => {
+                let reason = match event {
+                    OrderUpdateEvent::OrderRejected { reason, .. } | OrderUpdateEvent::OrderCancelled { reason, .. } => reason.clone(),
+                    _ => "the day order expired".to_string(),
+                };
+                self.legs[index].closed = true;
+                if self.unwinding {
+                    return vec![];
+                }
+                match self.legging {
+                    LeggingRisk::Keep => {
+                        eprintln!("Pair Execution: Leg {} was not filled: {}, keeping the other legs", self.legs[index].order.symbol_code, reason);
+                        vec![]
+                    }
+                    LeggingRisk::Retry(max_retries) if self.retries < max_retries => {
+                        self.retries += 1;
+                        // the filled quantity stays with the leg, the retry only has to fill the rest
+                        let leg = &self.legs[index];
+                        let mut order = leg.order.clone();
+                        order.id = new_order_id();
+                        order.quantity_open = leg.order.quantity_open - leg.filled;
+                        order.time_created_utc = time.to_string();
+                        self.legs.push(LegExecution { order: order.clone(), filled: dec!(0), closed: false });
+                        let order_type = order.order_type.clone();
+                        vec![OrderRequest::Create { account: order.account.clone(), order, order_type }]
+                    }
+                    LeggingRisk::Retry(_) | LeggingRisk::Unwind => {
+                        eprintln!("Pair Execution: Leg {} was not filled: {}, unwinding the pair", self.legs[index].order.symbol_code, reason);
+                        self.unwinding = true;
+                        let mut requests = vec![];
+                        for leg in &self.legs {
+                            if !leg.closed {
+                                requests.push(OrderRequest::Cancel { account: leg.order.account.clone(), order_id: leg.order.id.clone() });
+                            }
+                            if leg.filled > dec!(0) {
+                                requests.push(Self::unwind_order(leg, leg.filled, time));
+                            }
+                        }
+                        requests
+                    }
+                }
+            }
+            _ => vec![],
+        }
+    }
+}
+
+/// Tracks the pair orders in flight and applies their legging risk to the order events.
+pub(crate) struct PairHandler {
+    executions: Mutex<Vec<PairExecution>>,
+    order_ids: DashMap<OrderId, ()>,
+    request_sender: mpsc::Sender<OrderRequest>,
+}
+
+impl PairHandler {
+    /// Returns the handler and the receiver of the orders it sends, see `route_engine_orders()`.
+    pub(crate) fn new() -> (Self, mpsc::Receiver<OrderRequest>) {
+        let (request_sender, request_receiver) = mpsc::channel(100);
+        let handler = PairHandler {
+            executions: Mutex::new(vec![]),
+            order_ids: DashMap::new(),
+            request_sender,
+        };
+        (handler, request_receiver)
+    }
+
+    /// Registers the pair before the legs are sent, so no event is missed.
+    pub(crate) fn add(&self, execution: PairExecution) {
+        for leg in &execution.legs {
+            self.order_ids.insert(leg.order.id.clone(), ());
+        }
+        self.executions.lock().unwrap().push(execution);
+    }
+
+    fn update(&self, event: &OrderUpdateEvent) -> Vec<OrderRequest> {
+        if !self.order_ids.contains_key(event.order_id()) {
+            return vec![];
+        }
+        let time = event.time_utc();
+        let mut executions = self.executions.lock().unwrap();
+        let Some(index) = executions.iter().position(|execution| execution.contains(event.order_id())) else {
+            return vec![];
+        };
+        let requests = executions[index].update(event, time);
+        for request in &requests {
+            if let OrderRequest::Create { order, .. } = request {
+                self.order_ids.insert(order.id.clone(), ());
+            }
+        }
+        if executions[index].is_complete() {
+            let execution = executions.remove(index);
+            for leg in &execution.legs {
+                self.order_ids.remove(&leg.order.id);
+            }
+        }
+        requests
+    }
+
+    /// Returns a sender which applies the legging risk to the order events, then forwards all events to the `strategy_event_sender`.
+    pub(crate) fn tap(self: Arc<Self>, strategy_event_sender: mpsc::Sender<StrategyEvent>) -> mpsc::Sender<StrategyEvent> {
+        let (tap_sender, mut tap_receiver) = mpsc::channel(strategy_event_sender.max_capacity());
+        tokio::spawn(async move {
+            while let Some(event) = tap_receiver.recv().await {
+                if let StrategyEvent::OrderEvents(order_event) = &event {
+                    for request in self.update(order_event) {
+                        if let Err(e) = self.request_sender.send(request).await {
+                            eprintln!("Engine Orders: Failed to send order request: {}", e);
+                        }
+                    }
+                }
+                if let Err(e) = strategy_event_sender.send(event).await {
+                    eprintln!("Pair Execution: Failed to forward event: {}", e);
+                    break;
+                }
+            }
+        });
+        tap_sender
+    }
+}
+
+/// Sends the orders of the engine, from the pair handler and the flat by rules, to the brokerage, or the matching engine for backtests and paper trading.
+pub(crate) fn route_engine_orders(
+    mut request_receiver: mpsc::Receiver<OrderRequest>,
+    mode: StrategyMode,
+    open_order_cache: Arc<DashMap<OrderId, Order>>,
+    historical_message_sender: Option<mpsc::Sender<BackTestEngineMessage>>,
+) {
+    tokio::spawn(async move {
+        while let Some(order_request) = request_receiver.recv().await {
+            if mode == StrategyMode::Live {
+                if let OrderRequest::Create { order, .. } = &order_request {
+                    open_order_cache.insert(order.id.clone(), order.clone());
+                }
+                let connection_type = ConnectionType::Broker(order_request.brokerage());
+                let request = StrategyRequest::OneWay(connection_type, DataServerRequest::OrderRequest { request: order_request });
+                send_request(request).await;
+            } else if let Some(historical_message_sender) = &historical_message_sender {
+                if let Err(e) = historical_message_sender.send(BackTestEngineMessage::OrderRequest(order_request)).await {
+                    eprintln!("Engine Orders: Failed to send order request: {}", e);
+                }
+            }
+        }
+    });
+}
+
+/// The market order for a leg of a pair, the quantity is the pair quantity × the leg ratio.
+pub(crate) fn leg_order(leg: &PairLeg, account: &Account, quantity: Volume, side: OrderSide, tag: &str, time: DateTime<Utc>) -> Order {
+    Order::market_order(
+        leg.symbol_name.clone(),
+        leg.symbol_code.clone(),
+        account,
+        quantity * leg.ratio,
+        side,
+        tag.to_string(),
+        new_order_id(),
+        time,
+        leg.exchange.clone(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standardized_types::broker_enum::Brokerage;
+
+    fn account() -> Account {
+        Account::new(Brokerage::Test, "Test_Account_1".to_string())
+    }
+
+    fn orders() -> Vec<Order> {
+        let time = Utc::now();
+        vec![
+            leg_order(&PairLeg::new("NQ".to_string(), None, None, dec!(1)), &account(), dec!(2), OrderSide::Buy, "Pair", time),
+            leg_order(&PairLeg::new("ES".to_string(), None, None, dec!(2)), &account(), dec!(2), OrderSide::Sell, "Pair", time),
+        ]
+    }
+
+    fn filled(order: &Order, quantity: Volume) -> OrderUpdateEvent {
+        OrderUpdateEvent::OrderFilled {
+            account: account(),
+            symbol_name: order.symbol_name.clone(),
+            symbol_code: order.symbol_code.clone(),
+            order_id: order.id.clone(),
+            side: order.side,
+            price: dec!(100),
+            quantity,
+            tag: order.tag.clone(),
+            time: Utc::now().to_string(),
+            fill_id: None,
+            liquidity: None,
+            commission: None,
+        }
+    }
+
+    fn rejected(order: &Order) -> OrderUpdateEvent {
+        OrderUpdateEvent::OrderRejected {
+            account: account(),
+            symbol_name: order.symbol_name.clone(),
+            symbol_code: order.symbol_code.clone(),
+            order_id: order.id.clone(),
+            reason: "Insufficient margin".to_string(),
+            tag: order.tag.clone(),
+            time: Utc::now().to_string(),
+        }
+    }
+
+    #[test]
+    fn test_unwind_rejected_leg() {
+        let orders = orders();
+        assert_eq!(orders[1].quantity_open, dec!(4));
+        let mut execution = PairExecution::new(orders.clone(), LeggingRisk::Unwind);
+        assert!(execution.update(&filled(&orders[0], dec!(2)), Utc::now()).is_empty());
+
+        let requests = execution.update(&rejected(&orders[1]), Utc::now());
+        assert_eq!(requests.len(), 1);
+        match &requests[0] {
+            OrderRequest::Create { order, .. } => {
+                assert_eq!(order.symbol_name, "NQ");
+                assert_eq!(order.side, OrderSide::Sell);
+                assert_eq!(order.quantity_open, dec!(2));
+            }
+            _ => panic!("Expected an unwind order"),
+        }
+        assert!(execution.is_complete());
+    }
+
+    #[test]
+    fn test_retry_then_unwind() {
+        let orders = orders();
+        let mut execution = PairExecution::new(orders.clone(), LeggingRisk::Retry(1));
+        execution.update(&filled(&orders[0], dec!(2)), Utc::now());
+
+        let retry = match execution.update(&rejected(&orders[1]), Utc::now()).pop() {
+            Some(OrderRequest::Create { order, .. }) => order,
+            _ => panic!("Expected a retry order"),
+        };
+        assert_eq!(retry.symbol_name, "ES");
+        assert_eq!(retry.quantity_open, dec!(4));
+        assert_ne!(retry.id, orders[1].id);
+        assert!(!execution.is_complete());
+
+        // the retry is rejected too, so the filled leg is closed
+        let requests = execution.update(&rejected(&retry), Utc::now());
+        assert_eq!(requests.len(), 1);
+        assert!(execution.is_complete());
+    }
+
+    #[test]
+    fn test_keep_filled_legs() {
+        let orders = orders();
+        let mut execution = PairExecution::new(orders.clone(), LeggingRisk::Keep);
+        execution.update(&filled(&orders[0], dec!(2)), Utc::now());
+        assert!(execution.update(&rejected(&orders[1]), Utc::now()).is_empty());
+        assert!(execution.is_complete());
+    }
+}
@@ -0,0 +1,178 @@
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use tokio::sync::mpsc::Sender;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::enums::PriceTriggerCondition;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::subscriptions::SymbolName;
+use crate::standardized_types::time_slices::TimeSlice;
+use crate::strategies::strategy_events::StrategyEvent;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PriceTrigger {
+    pub tag: String,
+    pub condition: PriceTriggerCondition,
+    pub level: Price,
+}
+
+impl PriceTrigger {
+    pub fn new(tag: String, condition: PriceTriggerCondition, level: Price) -> Self {
+        PriceTrigger {
+            tag,
+            condition,
+            level,
+        }
+    }
+
+    /// Returns true if the data touched the level.
+    /// Ticks use the traded price, quotes use the bid for `AtOrAbove` and the ask for `AtOrBelow`.
+    /// Bars use the high or low, since the level could have been touched at any point during the bar.
+    pub fn is_triggered(&self, base_data: &BaseDataEnum) -> bool {
+        let price: Option<Decimal> = match (&self.condition, base_data) {
+            (PriceTriggerCondition::AtOrAbove, BaseDataEnum::Tick(tick)) => Some(tick.price),
+            (PriceTriggerCondition::AtOrBelow, BaseDataEnum::Tick(tick)) => Some(tick.price),
+            (PriceTriggerCondition::AtOrAbove, BaseDataEnum::Quote(quote)) => Some(quote.bid),
+            (PriceTriggerCondition::AtOrBelow, BaseDataEnum::Quote(quote)) => Some(quote.ask),
+            (PriceTriggerCondition::AtOrAbove, BaseDataEnum::Candle(candle)) => Some(candle.high),
+            (PriceTriggerCondition::AtOrBelow, BaseDataEnum::Candle(candle)) => Some(candle.low),
+            (PriceTriggerCondition::AtOrAbove, BaseDataEnum::QuoteBar(bar)) => Some(bar.bid_high),
+            (PriceTriggerCondition::AtOrBelow, BaseDataEnum::QuoteBar(bar)) => Some(bar.ask_low),
+            _ => None
+        };
+        match price {
+            Some(price) => match self.condition {
+                PriceTriggerCondition::AtOrAbove => price >= self.level,
+                PriceTriggerCondition::AtOrBelow => price <= self.level,
+            },
+            None => false
+        }
+    }
+}
+
+/// Price triggers are checked against the primary data before the strategy receives the time slice,
+/// each trigger fires a single `StrategyEvent::PriceTrigger(tag)` and is then removed.
+pub struct PriceTriggerHandler {
+    triggers: DashMap<SymbolName, Vec<PriceTrigger>>,
+    strategy_event_sender: Sender<StrategyEvent>
+}
+
+impl PriceTriggerHandler {
+    pub fn new(strategy_event_sender: Sender<StrategyEvent>) -> Self {
+        PriceTriggerHandler {
+            triggers: Default::default(),
+            strategy_event_sender
+        }
+    }
+
+    /// Adds the trigger, any existing trigger with the same tag is replaced.
+    pub fn add_trigger(&self, symbol_name: SymbolName, trigger: PriceTrigger) {
+        self.remove_trigger(&trigger.tag);
+        self.triggers.entry(symbol_name).or_insert_with(Vec::new).push(trigger);
+    }
+
+    pub fn remove_trigger(&self, tag: &str) {
+        for mut symbol_triggers in self.triggers.iter_mut() {
+            symbol_triggers.value_mut().retain(|trigger| trigger.tag != tag);
+        }
+        self.triggers.retain(|_, symbol_triggers| !symbol_triggers.is_empty());
+    }
+
+    /// Removes and returns the tags of all triggers touched by the time slice.
+    pub fn check_time_slice(&self, time_slice: &TimeSlice) -> Vec<String> {
+        let mut fired = vec![];
+        if self.triggers.is_empty() {
+            return fired;
+        }
+        for base_data in time_slice.iter() {
+            if let Some(mut symbol_triggers) = self.triggers.get_mut(&base_data.symbol().name) {
+                symbol_triggers.retain(|trigger| {
+                    if trigger.is_triggered(base_data) {
+                        fired.push(trigger.tag.clone());
+                        return false;
+                    }
+                    true
+                });
+            }
+        }
+        self.triggers.retain(|_, symbol_triggers| !symbol_triggers.is_empty());
+        fired
+    }
+
+    pub async fn update_time_slice(&self, time_slice: &TimeSlice) {
+        for tag in self.check_time_slice(time_slice) {
+            match self.strategy_event_sender.send(StrategyEvent::PriceTrigger(tag)).await {
+                Ok(_) => {}
+                Err(e) => eprintln!("Price Trigger Handler: Failed to send event: {}", e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use tokio::sync::mpsc;
+    use crate::standardized_types::base_data::quote::Quote;
+    use crate::standardized_types::base_data::tick::{Aggressor, Tick};
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::{FuturesExchange, MarketType};
+    use crate::standardized_types::subscriptions::Symbol;
+
+    fn symbol() -> Symbol {
+        Symbol::new("NQ".to_string(), DataVendor::Rithmic, MarketType::Futures(FuturesExchange::CME))
+    }
+
+    fn tick(price: Decimal) -> BaseDataEnum {
+        BaseDataEnum::Tick(Tick::new(symbol(), price, "2024-01-01 00:00:00 UTC".to_string(), dec!(1), Aggressor::Buy))
+    }
+
+    fn quote(bid: Decimal, ask: Decimal) -> BaseDataEnum {
+        BaseDataEnum::Quote(Quote::new(symbol(), ask, bid, dec!(1), dec!(1), "2024-01-01 00:00:00 UTC".to_string()))
+    }
+
+    #[test]
+    fn test_trigger_conditions() {
+        let above = PriceTrigger::new("above".to_string(), PriceTriggerCondition::AtOrAbove, dec!(100));
+        let below = PriceTrigger::new("below".to_string(), PriceTriggerCondition::AtOrBelow, dec!(100));
+        assert!(above.is_triggered(&tick(dec!(100))));
+        assert!(!above.is_triggered(&tick(dec!(99.75))));
+        assert!(below.is_triggered(&tick(dec!(99.75))));
+        assert!(!below.is_triggered(&tick(dec!(100.25))));
+        // quotes use the side of the book we could fill against
+        assert!(!above.is_triggered(&quote(dec!(99.75), dec!(100))));
+        assert!(above.is_triggered(&quote(dec!(100), dec!(100.25))));
+        assert!(!below.is_triggered(&quote(dec!(100), dec!(100.25))));
+        assert!(below.is_triggered(&quote(dec!(99.75), dec!(100))));
+    }
+
+    #[test]
+    fn test_triggers_fire_once() {
+        let (sender, _receiver) = mpsc::channel(10);
+        let handler = PriceTriggerHandler::new(sender);
+        handler.add_trigger("NQ".to_string(), PriceTrigger::new("breakout".to_string(), PriceTriggerCondition::AtOrAbove, dec!(100)));
+        handler.add_trigger("ES".to_string(), PriceTrigger::new("other symbol".to_string(), PriceTriggerCondition::AtOrAbove, dec!(1)));
+
+        let mut slice = TimeSlice::new();
+        slice.add(tick(dec!(99)));
+        assert!(handler.check_time_slice(&slice).is_empty());
+
+        let mut slice = TimeSlice::new();
+        slice.add(tick(dec!(101)));
+        slice.add(tick(dec!(102)));
+        assert_eq!(handler.check_time_slice(&slice), vec!["breakout".to_string()]);
+        assert!(handler.check_time_slice(&slice).is_empty());
+    }
+
+    #[test]
+    fn test_remove_trigger() {
+        let (sender, _receiver) = mpsc::channel(10);
+        let handler = PriceTriggerHandler::new(sender);
+        handler.add_trigger("NQ".to_string(), PriceTrigger::new("breakout".to_string(), PriceTriggerCondition::AtOrAbove, dec!(100)));
+        handler.remove_trigger("breakout");
+        let mut slice = TimeSlice::new();
+        slice.add(tick(dec!(101)));
+        assert!(handler.check_time_slice(&slice).is_empty());
+    }
+}
@@ -0,0 +1,18 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A handle to the reduce only mode of one strategy, see `strategy.reduce_only_handle()`.
+#[derive(Clone, Debug, Default)]
+pub struct ReduceOnlyHandle {
+    reduce_only: Arc<AtomicBool>,
+}
+
+impl ReduceOnlyHandle {
+    pub fn set(&self, reduce_only: bool) {
+        self.reduce_only.store(reduce_only, Ordering::SeqCst);
+    }
+
+    pub fn is_reduce_only(&self) -> bool {
+        self.reduce_only.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+use std::time::Duration;
+use dashmap::DashMap;
+use tokio::sync::mpsc::Sender;
+use tokio::time::{sleep_until, timeout_at, Instant};
+use crate::standardized_types::orders::{Order, OrderId};
+use crate::strategies::strategy_events::StrategyEvent;
+use crate::strategies::strategy_settings::StrategySettings;
+
+pub(crate) const DEFAULT_SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// How long to wait for a cancel to be acknowledged before sending it again.
+pub(crate) const CANCEL_RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Prints a summary of the orders which are still working.
+pub(crate) fn report_open_orders(open_orders: &[Order], context: &str) {
+    if open_orders.is_empty() {
+        return;
+    }
+    eprintln!("{}: {} orders may still be working at the broker:", context, open_orders.len());
+    for order in open_orders {
+        eprintln!(
+            "  {}: {} {:?} {:?} {} open of {}, limit: {:?}, trigger: {:?}, state: {:?}, account: {}, tag: {}",
+            order.id, order.symbol_name, order.side, order.order_type, order.quantity_open, order.quantity_open + order.quantity_filled,
+            order.limit_price, order.trigger_price, order.state, order.account, order.tag
+        );
+    }
+}
+
+pub(crate) fn shutdown_live_on_signal(strategy_event_sender: Sender<StrategyEvent>, open_order_cache: Arc<DashMap<OrderId, Order>>, settings: Arc<StrategySettings>) {
+    tokio::spawn(async move {
+        let Some((signal, exit_code)) = wait_for_signal().await else {
+            return;
+        };
+        let grace_period = settings.shutdown_grace_period();
+        println!("Shutdown Handler: {} received, shutting down the strategy within {:?}, press Ctrl+C again to exit immediately", signal, grace_period);
+        let deadline = Instant::now() + grace_period;
+        match timeout_at(deadline, strategy_event_sender.send(StrategyEvent::ShutdownEvent(format!("{} received", signal)))).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => eprintln!("Shutdown Handler: Failed to send shutdown event: {}", e),
+            Err(_) => eprintln!("Shutdown Handler: The strategy did not receive the shutdown event within {:?}", grace_period),
+        }
+        tokio::select! {
+            _ = sleep_until(deadline) => eprintln!("Shutdown Handler: The strategy did not exit within {:?}, exiting", grace_period),
+            _ = wait_for_signal() => {}
+        }
+        let open_orders: Vec<Order> = open_order_cache.iter().map(|order| order.value().clone()).collect();
+        report_open_orders(&open_orders, "Shutdown Handler");
+        std::process::exit(exit_code);
+    });
+}
+
+/// Returns the signal name and the exit code for it.
+#[cfg(unix)]
+async fn wait_for_signal() -> Option<(&'static str, i32)> {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut terminate = signal(SignalKind::terminate()).ok()?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result.ok().map(|_| ("Ctrl+C", 130)),
+        _ = terminate.recv() => Some(("SIGTERM", 143)),
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() -> Option<(&'static str, i32)> {
+    tokio::signal::ctrl_c().await.ok().map(|_| ("Ctrl+C", 130))
+}
@@ -0,0 +1,260 @@
+use std::sync::{Arc, RwLock};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use tokio::sync::mpsc;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::orders::{Order, OrderId, OrderRequest, OrderType, OrderUpdateEvent};
+use crate::standardized_types::subscriptions::SymbolName;
+use crate::strategies::historical_time::WarmUpState;
+use crate::strategies::handlers::market_handler::price_service::MarketPriceService;
+use crate::strategies::handlers::watchdog::HealthWarning;
+use crate::strategies::strategy_events::StrategyEvent;
+use crate::strategies::strategy_settings::StrategySettings;
+
+/// The max spread of a spread guard.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpreadLimit {
+    /// The max spread as a price, eg `dec!(0.0003)` for 3 pips of EUR-USD.
+    Absolute(Price),
+    /// The max spread as a multiple of the normal spread of the symbol.
+    NormalMultiple(Decimal),
+}
+
+/// What a spread guard does with an entry when the spread is too wide.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpreadAction {
+    /// Refuse the order with `OrderError::WideSpread`.
+    Reject,
+    /// Hold the order until the spread narrows, for at most the duration, then reject it.
+    Delay(Duration),
+}
+
+/// Guards entries against wide spreads, see `strategy.set_spread_guard()`.
+/// # Properties
+/// * `limit` - The max spread.
+/// * `action` - What happens to an entry sent while the spread is wider than the max.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpreadGuard {
+    pub limit: SpreadLimit,
+    pub action: SpreadAction,
+}
+
+impl SpreadGuard {
+    pub fn new(limit: SpreadLimit, action: SpreadAction) -> Self {
+        SpreadGuard {
+            limit,
+            action,
+        }
+    }
+}
+
+pub(crate) struct SpreadGuardHandler {
+    strategy_guard: RwLock<Option<SpreadGuard>>,
+    symbol_guards: DashMap<SymbolName, SpreadGuard>,
+    /// The delayed orders and the time they are rejected at.
+    delayed: DashMap<OrderId, (Order, OrderType, DateTime<Utc>)>,
+    price_service: Arc<MarketPriceService>,
+    order_sender: mpsc::Sender<OrderRequest>,
+    strategy_event_sender: mpsc::Sender<StrategyEvent>,
+    warm_up: WarmUpState,
+    settings: Arc<StrategySettings>,
+}
+
+impl SpreadGuardHandler {
+    pub(crate) fn new(
+        price_service: Arc<MarketPriceService>,
+        order_sender: mpsc::Sender<OrderRequest>,
+        strategy_event_sender: mpsc::Sender<StrategyEvent>,
+        warm_up: WarmUpState,
+        settings: Arc<StrategySettings>,
+    ) -> Self {
+        SpreadGuardHandler {
+            strategy_guard: RwLock::new(None),
+            symbol_guards: DashMap::new(),
+            delayed: DashMap::new(),
+            price_service,
+            order_sender,
+            strategy_event_sender,
+            warm_up,
+            settings,
+        }
+    }
+
+    pub(crate) fn set(&self, guard: Option<SpreadGuard>) {
+        *self.strategy_guard.write().unwrap() = guard;
+    }
+
+    pub(crate) fn set_symbol(&self, symbol_name: SymbolName, guard: Option<SpreadGuard>) {
+        match guard {
+            Some(guard) => {
+                self.symbol_guards.insert(symbol_name, guard);
+            }
+            None => {
+                self.symbol_guards.remove(&symbol_name);
+            }
+        }
+    }
+
+    /// The guard of the symbol, or the strategy guard if the symbol has none.
+    pub(crate) fn guard(&self, symbol_name: &SymbolName) -> Option<SpreadGuard> {
+        match self.symbol_guards.get(symbol_name) {
+            Some(guard) => Some(guard.value().clone()),
+            None => self.strategy_guard.read().unwrap().clone(),
+        }
+    }
+
+    /// The current spread and the max spread of the guard if the spread is wider than the max, None if the spread is fine or not known.
+    pub(crate) fn wide_spread(&self, symbol_name: &SymbolName, limit: &SpreadLimit) -> Option<(Price, Price)> {
+        let spread = self.price_service.spread(symbol_name)?;
+        let max_spread = match limit {
+            SpreadLimit::Absolute(max_spread) => *max_spread,
+            SpreadLimit::NormalMultiple(multiple) => self.price_service.normal_spread(symbol_name)? * multiple,
+        };
+        match spread > max_spread {
+            true => Some((spread, max_spread)),
+            false => None,
+        }
+    }
+
+    /// Holds the order until the spread narrows or the time is `reject_at`.
+    pub(crate) fn delay(&self, order: Order, order_type: OrderType, reject_at: DateTime<Utc>) {
+        self.delayed.insert(order.id.clone(), (order, order_type, reject_at));
+    }
+
+    /// Removes the delayed order, returns the order if it was delayed.
+    pub(crate) fn remove(&self, order_id: &OrderId) -> Option<Order> {
+        self.delayed.remove(order_id).map(|(_, (order, _, _))| order)
+    }
+
+    pub(crate) fn orders(&self) -> Vec<Order> {
+        self.delayed.iter().map(|delayed| delayed.value().0.clone()).collect()
+    }
+
+    /// Sends the delayed orders whose spread has narrowed and rejects the orders which have waited too long.
+    pub(crate) async fn update_time(&self, current_time: DateTime<Utc>) {
+        if self.delayed.is_empty() || !self.warm_up.is_complete() {
+            return;
+        }
+        let (released, expired) = self.due_orders(current_time);
+        for (mut order, order_type) in released {
+            order.update_time_created_utc(current_time);
+            let request = OrderRequest::Create { account: order.account.clone(), order, order_type };
+            if let Err(e) = self.order_sender.send(request).await {
+                eprintln!("Spread Guard: Failed to send order request: {}", e);
+            }
+        }
+        for (order, spread, max_spread) in expired {
+            let warning = HealthWarning::WideSpread { symbol_name: order.symbol_name.clone(), spread, max_spread, delayed: true };
+            eprintln!("{}", warning);
+            self.settings.record_risk_event(current_time, warning.to_string());
+            if let Err(e) = self.strategy_event_sender.send(StrategyEvent::HealthWarning(warning)).await {
+                eprintln!("Spread Guard: Failed to send event: {}", e);
+            }
+            let event = OrderUpdateEvent::OrderRejected {
+                account: order.account,
+                symbol_name: order.symbol_name,
+                symbol_code: order.symbol_code,
+                order_id: order.id,
+                reason: format!("Spread {} stayed wider than {}", spread, max_spread),
+                tag: order.tag,
+                time: current_time.to_string(),
+            };
+            if let Err(e) = self.strategy_event_sender.send(StrategyEvent::OrderEvents(event)).await {
+                eprintln!("Spread Guard: Failed to send event: {}", e);
+            }
+        }
+    }
+
+    /// Removes and returns the delayed orders whose spread is under the max, and the orders past their reject time with the spread and max spread.
+    /// An order whose symbol guard was removed is released.
+    fn due_orders(&self, time: DateTime<Utc>) -> (Vec<(Order, OrderType)>, Vec<(Order, Price, Price)>) {
+        let order_ids: Vec<OrderId> = self.delayed.iter().map(|delayed| delayed.key().clone()).collect();
+        let mut released = vec![];
+        let mut expired = vec![];
+        for order_id in order_ids {
+            let wide_spread = match self.delayed.get(&order_id) {
+                Some(delayed) => {
+                    let symbol_name = &delayed.value().0.symbol_name;
+                    let wide_spread = self.guard(symbol_name).and_then(|guard| self.wide_spread(symbol_name, &guard.limit));
+                    match wide_spread {
+                        Some(_) if time < delayed.value().2 => continue,
+                        wide_spread => wide_spread,
+                    }
+                }
+                None => continue,
+            };
+            let Some((_, (order, order_type, _))) = self.delayed.remove(&order_id) else {
+                continue;
+            };
+            match wide_spread {
+                None => released.push((order, order_type)),
+                Some((spread, max_spread)) => expired.push((order, spread, max_spread)),
+            }
+        }
+        (released, expired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::accounts::Account;
+    use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+    use crate::standardized_types::base_data::quote::Quote;
+    use crate::standardized_types::broker_enum::Brokerage;
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::{MarketType, OrderSide, StrategyMode};
+    use crate::standardized_types::subscriptions::Symbol;
+    use crate::standardized_types::time_slices::TimeSlice;
+
+    fn quote(service: &MarketPriceService, bid: Price, ask: Price) {
+        let symbol = Symbol::new("EUR-USD".to_string(), DataVendor::DataBento, MarketType::Forex);
+        let quote = Quote::new(symbol, ask, bid, dec!(100), dec!(100), Utc::now().to_string());
+        service.update_market_data(Arc::new(TimeSlice::from_iter([BaseDataEnum::Quote(quote)])));
+    }
+
+    fn order(order_id: &str, time: DateTime<Utc>) -> Order {
+        let account = Account::new(Brokerage::Test, "Test_Account_1".to_string());
+        Order::market_order("EUR-USD".to_string(), None, &account, dec!(1000), OrderSide::Buy, "Entry".to_string(), order_id.to_string(), time, None)
+    }
+
+    #[test]
+    fn test_delayed_orders() {
+        let price_service = Arc::new(MarketPriceService::new());
+        let (order_sender, _order_receiver) = mpsc::channel(10);
+        let (event_sender, _event_receiver) = mpsc::channel(10);
+        let handler = SpreadGuardHandler::new(price_service.clone(), order_sender, event_sender, WarmUpState::new(), Arc::new(StrategySettings::new(StrategyMode::Backtest)));
+        let name = "EUR-USD".to_string();
+        let guard = SpreadGuard::new(SpreadLimit::NormalMultiple(dec!(3)), SpreadAction::Delay(Duration::seconds(30)));
+        handler.set(Some(guard.clone()));
+        assert_eq!(handler.guard(&name), Some(guard.clone()));
+
+        // no normal spread yet
+        quote(&price_service, dec!(1.1000), dec!(1.1010));
+        assert_eq!(handler.wide_spread(&name, &guard.limit), None);
+        for _ in 0..20 {
+            quote(&price_service, dec!(1.1000), dec!(1.1001));
+        }
+        quote(&price_service, dec!(1.0995), dec!(1.1005));
+        assert_eq!(handler.wide_spread(&name, &guard.limit), Some((dec!(0.0010), dec!(0.0003))));
+        assert_eq!(handler.wide_spread(&name, &SpreadLimit::Absolute(dec!(0.0010))), None);
+
+        let time = Utc::now();
+        handler.delay(order("1", time), OrderType::Market, time + Duration::seconds(30));
+        handler.delay(order("2", time), OrderType::Market, time + Duration::seconds(60));
+        let (released, expired) = handler.due_orders(time + Duration::seconds(10));
+        assert!(released.is_empty() && expired.is_empty());
+        let (released, expired) = handler.due_orders(time + Duration::seconds(30));
+        assert!(released.is_empty());
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0.id, "1");
+
+        quote(&price_service, dec!(1.1000), dec!(1.1001));
+        let (released, _) = handler.due_orders(time + Duration::seconds(40));
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].0.id, "2");
+        assert!(handler.orders().is_empty());
+    }
+}
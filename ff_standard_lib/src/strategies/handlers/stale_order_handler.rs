@@ -0,0 +1,162 @@
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use tokio::sync::mpsc;
+use crate::standardized_types::orders::{Order, OrderId, OrderRequest, OrderType};
+use crate::strategies::historical_time::WarmUpState;
+use crate::strategies::strategy_events::StrategyEvent;
+
+/// Cancels working orders without a fill once they are older than `max_age`, see `strategy.sweep_stale_orders()`.
+/// # Properties
+/// * `name` - The rule name, sent with `StrategyEvent::StaleOrderCancelled`.
+/// * `max_age` - The age after which an unfilled order is cancelled.
+/// * `tag` - Only orders with this tag, None for any tag.
+/// * `order_type` - Only orders of this type, None for any type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StaleOrderRule {
+    pub name: String,
+    pub max_age: Duration,
+    pub tag: Option<String>,
+    pub order_type: Option<OrderType>,
+}
+
+impl StaleOrderRule {
+    pub fn new(name: String, max_age: Duration, tag: Option<String>, order_type: Option<OrderType>) -> Self {
+        StaleOrderRule {
+            name,
+            max_age,
+            tag,
+            order_type,
+        }
+    }
+
+    fn applies_to(&self, order: &Order) -> bool {
+        self.tag.as_ref().map_or(true, |tag| tag == &order.tag)
+            && self.order_type.as_ref().map_or(true, |order_type| order_type == &order.order_type)
+    }
+
+    fn is_stale(&self, order: &Order, time: DateTime<Utc>) -> bool {
+        self.applies_to(order) && order.quantity_filled == Decimal::ZERO && time - order.time_created_utc() >= self.max_age
+    }
+}
+
+pub(crate) struct StaleOrderHandler {
+    rules: RwLock<Vec<StaleOrderRule>>,
+    swept: RwLock<HashSet<OrderId>>,
+    open_order_cache: Arc<DashMap<OrderId, Order>>,
+    order_sender: mpsc::Sender<OrderRequest>,
+    strategy_event_sender: mpsc::Sender<StrategyEvent>,
+    warm_up: WarmUpState,
+}
+
+impl StaleOrderHandler {
+    pub(crate) fn new(
+        open_order_cache: Arc<DashMap<OrderId, Order>>,
+        order_sender: mpsc::Sender<OrderRequest>,
+        strategy_event_sender: mpsc::Sender<StrategyEvent>,
+        warm_up: WarmUpState,
+    ) -> Self {
+        StaleOrderHandler {
+            rules: RwLock::new(vec![]),
+            swept: RwLock::new(HashSet::new()),
+            open_order_cache,
+            order_sender,
+            strategy_event_sender,
+            warm_up,
+        }
+    }
+
+    /// Adding a rule with an existing name replaces the old rule.
+    pub(crate) fn add_rule(&self, rule: StaleOrderRule) {
+        let mut rules = self.rules.write().unwrap();
+        rules.retain(|existing| existing.name != rule.name);
+        rules.push(rule);
+    }
+
+    pub(crate) fn remove_rule(&self, name: &str) {
+        self.rules.write().unwrap().retain(|rule| rule.name != name);
+    }
+
+    pub(crate) async fn update_time(&self, current_time: DateTime<Utc>) {
+        if !self.warm_up.is_complete() {
+            return;
+        }
+        let stale = self.stale_orders(current_time);
+        for (order, rule_name) in stale {
+            let request = OrderRequest::Cancel { account: order.account.clone(), order_id: order.id.clone() };
+            if let Err(e) = self.order_sender.send(request).await {
+                eprintln!("Stale Orders: Failed to send cancel request: {}", e);
+            }
+            if let Err(e) = self.strategy_event_sender.send(StrategyEvent::StaleOrderCancelled(order.id.clone(), rule_name)).await {
+                eprintln!("Stale Orders: Failed to send event: {}", e);
+            }
+        }
+    }
+
+    /// The stale orders which have not been cancelled yet, with the name of the first rule they are stale for.
+    fn stale_orders(&self, time: DateTime<Utc>) -> Vec<(Order, String)> {
+        let rules = self.rules.read().unwrap();
+        if rules.is_empty() {
+            return vec![];
+        }
+        let mut swept = self.swept.write().unwrap();
+        // a cancelled order leaves the cache, it no longer needs to be remembered
+        swept.retain(|order_id| self.open_order_cache.contains_key(order_id));
+        let mut stale = vec![];
+        for order in self.open_order_cache.iter() {
+            if swept.contains(&order.id) {
+                continue;
+            }
+            if let Some(rule) = rules.iter().find(|rule| rule.is_stale(order.value(), time)) {
+                swept.insert(order.id.clone());
+                stale.push((order.value().clone(), rule.name.clone()));
+            }
+        }
+        stale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::accounts::Account;
+    use crate::standardized_types::broker_enum::Brokerage;
+    use crate::standardized_types::enums::OrderSide;
+    use crate::standardized_types::orders::TimeInForce;
+
+    fn limit_order(order_id: &str, tag: &str, time: DateTime<Utc>) -> Order {
+        let account = Account::new(Brokerage::Test, "Test_Account_1".to_string());
+        Order::limit_order("EUR-USD".to_string(), None, &account, dec!(1), OrderSide::Buy, tag.to_string(), order_id.to_string(), time, dec!(1.1), TimeInForce::GTC, None)
+    }
+
+    #[test]
+    fn test_stale_orders_cancelled_once() {
+        let (order_sender, _order_receiver) = mpsc::channel(10);
+        let (event_sender, _event_receiver) = mpsc::channel(10);
+        let open_order_cache = Arc::new(DashMap::new());
+        let handler = StaleOrderHandler::new(open_order_cache.clone(), order_sender, event_sender, WarmUpState::new());
+        handler.add_rule(StaleOrderRule::new("Entries".to_string(), Duration::minutes(30), Some("Entry".to_string()), Some(OrderType::Limit)));
+
+        let sent = Utc::now();
+        let mut partially_filled = limit_order("2", "Entry", sent);
+        partially_filled.quantity_filled = dec!(0.5);
+        for order in [limit_order("1", "Entry", sent), partially_filled, limit_order("3", "Take Profit", sent)] {
+            open_order_cache.insert(order.id.clone(), order);
+        }
+
+        assert!(handler.stale_orders(sent + Duration::minutes(29)).is_empty());
+        let stale = handler.stale_orders(sent + Duration::minutes(30));
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].0.id, "1");
+        assert_eq!(stale[0].1, "Entries");
+        // the cancel has been sent, the order is not swept again while it waits for the cancel event
+        assert!(handler.stale_orders(sent + Duration::minutes(31)).is_empty());
+
+        handler.remove_rule("Entries");
+        open_order_cache.insert("4".to_string(), limit_order("4", "Entry", sent));
+        assert!(handler.stale_orders(sent + Duration::hours(1)).is_empty());
+    }
+}
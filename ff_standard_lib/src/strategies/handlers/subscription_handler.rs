@@ -1,8 +1,9 @@
 use std::collections::BTreeMap;
 use std::ops::Deref;
-use std::sync::{Arc};
+use std::sync::{Arc, RwLock as StdRwLock};
 use ahash::AHashMap;
 use crate::strategies::consolidators::consolidator_enum::{ConsolidatedData, ConsolidatorEnum};
+use crate::strategies::consolidators::warmup_check::{is_checked, WarmUpCheck};
 use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
 use crate::standardized_types::base_data::base_data_type::BaseDataType;
 use crate::standardized_types::enums::{StrategyMode, PrimarySubscription};
@@ -14,8 +15,9 @@ use dashmap::DashMap;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
 use tokio::sync::RwLock;
-use crate::strategies::client_features::server_connections::{is_warmup_complete};
+use crate::strategies::historical_time::WarmUpState;
 use crate::standardized_types::base_data::candle::Candle;
+use crate::standardized_types::base_data::depth::Depth;
 use crate::standardized_types::base_data::fundamental::Fundamental;
 use crate::standardized_types::base_data::quote::Quote;
 use crate::standardized_types::base_data::quotebar::QuoteBar;
@@ -27,6 +29,7 @@ use tokio::sync::broadcast;
 use tokio::sync::mpsc::Sender;
 use crate::standardized_types::base_data::history::{get_compressed_historical_data};
 use crate::standardized_types::market_hours::TradingHours;
+use crate::strategies::history_budget::{plan_evictions, window_usage, HistoryBudget, HistoryMemoryUsage};
 
 /// Manages all subscriptions for a strategy. each strategy has its own subscription handler.
 pub struct SubscriptionHandler {
@@ -34,6 +37,8 @@ pub struct SubscriptionHandler {
     symbol_subscriptions: Arc<DashMap<Symbol, SymbolSubscriptionHandler>>,
     /// fundamental data is not consolidated and so it does not need special handlers
     fundamental_subscriptions: Arc<RwLock<Vec<DataSubscription>>>,
+    /// depth is primary data only, the book snapshots are not consolidated
+    depth_subscriptions: Arc<RwLock<Vec<DataSubscription>>>,
     strategy_mode: StrategyMode,
     // subscriptions which the strategy actually subscribed to, not the raw data needed to full-fill the subscription.
     strategy_subscriptions: Arc<RwLock<Vec<DataSubscription>>>,
@@ -43,17 +48,23 @@ pub struct SubscriptionHandler {
     tick_history: DashMap<DataSubscription, RollingWindow<Tick>>,
     quote_history: DashMap<DataSubscription, RollingWindow<Quote>>,
     fundamental_history: DashMap<DataSubscription, RollingWindow<Fundamental>>,
+    depth_history: DashMap<DataSubscription, RollingWindow<Depth>>,
     open_candles: DashMap<DataSubscription, Candle>,
     open_bars: DashMap<DataSubscription, QuoteBar>,
-    strategy_event_sender: Sender<StrategyEvent>
+    strategy_event_sender: Sender<StrategyEvent>,
+    history_budget: StdRwLock<Option<HistoryBudget>>,
+    /// The paused subscriptions, true if the subscription also stopped consolidating.
+    paused: Arc<DashMap<DataSubscription, bool>>,
+    warm_up: WarmUpState,
 }
 
 impl SubscriptionHandler {
-    pub async fn new(strategy_mode: StrategyMode, strategy_event_sender: Sender<StrategyEvent>) -> Self {
+    pub(crate) async fn new(strategy_mode: StrategyMode, strategy_event_sender: Sender<StrategyEvent>, warm_up: WarmUpState) -> Self {
         let (tx, _) = broadcast::channel(16);
         SubscriptionHandler {
             strategy_event_sender,
             fundamental_subscriptions: Default::default(),
+            depth_subscriptions: Default::default(),
             symbol_subscriptions: Default::default(),
             strategy_mode,
             strategy_subscriptions: Default::default(),
@@ -63,11 +74,20 @@ impl SubscriptionHandler {
             tick_history: Default::default(),
             quote_history: Default::default(),
             fundamental_history: Default::default(),
+            depth_history: Default::default(),
             open_candles: Default::default(),
             open_bars: Default::default(),
+            history_budget: StdRwLock::new(None),
+            paused: Default::default(),
+            warm_up,
         }
     }
 
+    /// The warm up state of the strategy.
+    pub(crate) fn warm_up(&self) -> &WarmUpState {
+        &self.warm_up
+    }
+
     pub(crate) fn subscribe_primary_subscription_updates(&self) -> broadcast::Receiver<Vec<DataSubscription>> {
         self.primary_subscriptions_broadcaster.subscribe()
     }
@@ -117,6 +137,26 @@ impl SubscriptionHandler {
             let _ = self.strategy_event_sender.send(StrategyEvent::DataSubscriptionEvent(event)).await;
         }
 
+        if new_subscription.base_data_type == BaseDataType::Depth {
+            let mut depth_subscriptions = self.depth_subscriptions.write().await;
+            if !depth_subscriptions.contains(&new_subscription) {
+                depth_subscriptions.push(new_subscription.clone());
+                self.depth_history.insert(new_subscription.clone(), RollingWindow::new(history_to_retain));
+            }
+            drop(depth_subscriptions);
+            self.enforce_history_budget();
+            if broadcast {
+                let subscriptions = self.primary_subscriptions().await;
+                match self.primary_subscriptions_broadcaster.send(subscriptions) {
+                    Ok(_) => {}
+                    Err(_) => {}
+                }
+            }
+            let event = DataSubscriptionEvent::Subscribed(new_subscription.clone());
+            let _ = self.strategy_event_sender.send(StrategyEvent::DataSubscriptionEvent(event)).await;
+            return;
+        }
+
         if !self.symbol_subscriptions.contains_key(&new_subscription.symbol) {
             let symbol_handler = SymbolSubscriptionHandler::new(
                 new_subscription.symbol.clone(),
@@ -131,6 +171,7 @@ impl SubscriptionHandler {
                 current_time,
                 history_to_retain,
                 self.strategy_mode,
+                self.warm_up.is_complete(),
                 fill_forward,
                 hours,
             ).await;
@@ -195,8 +236,11 @@ impl SubscriptionHandler {
                                 }
                             }
                         }
+                        // depth subscriptions return before the symbol handler
+                        BaseDataType::Depth => {}
                     }
                 }
+                self.enforce_history_budget();
                 if broadcast {
                     let subscriptions = self.primary_subscriptions().await;
                     match self.primary_subscriptions_broadcaster.send(subscriptions) {
@@ -218,6 +262,7 @@ impl SubscriptionHandler {
     /// 'current_time: DateTime<Utc>' The current time is used to change our base data subscription and warm up any new consolidators if we are adjusting our base resolution.
     /// 'strategy_mode: StrategyMode' The strategy mode is used to determine how to warm up the history, in live mode we may not yet have a serialized history to the current time.
     pub async fn unsubscribe(&self, subscription: DataSubscription, broadcast: bool) {
+        self.paused.remove(&subscription);
         if subscription.base_data_type == BaseDataType::Fundamentals {
             let mut fundamental_subscriptions = self.fundamental_subscriptions.write().await;
             if fundamental_subscriptions.contains(&subscription) {
@@ -237,6 +282,21 @@ impl SubscriptionHandler {
             return;
         }
 
+        if subscription.base_data_type == BaseDataType::Depth {
+            self.depth_subscriptions.write().await.retain(|depth_subscription| *depth_subscription != subscription);
+            self.depth_history.remove(&subscription);
+            self.strategy_subscriptions.write().await.retain(|x| x != &subscription);
+            if broadcast {
+                let subscriptions = self.primary_subscriptions().await;
+                match self.primary_subscriptions_broadcaster.send(subscriptions) {
+                    Ok(_) => {}
+                    Err(_) => {}
+                }
+            }
+            let _ = self.strategy_event_sender.send(StrategyEvent::DataSubscriptionEvent(DataSubscriptionEvent::Unsubscribed(subscription))).await;
+            return;
+        }
+
         let event =  self.symbol_subscriptions.get(&subscription.symbol).unwrap().unsubscribe(&subscription).await;
         let _ = self.strategy_event_sender.send(StrategyEvent::DataSubscriptionEvent(event)).await;
 
@@ -261,6 +321,9 @@ impl SubscriptionHandler {
             BaseDataType::Fundamentals => {
                 self.fundamental_history.remove(&subscription);
             }
+            BaseDataType::Depth => {
+                self.depth_history.remove(&subscription);
+            }
         }
         if broadcast {
             let subscriptions = self.primary_subscriptions().await;
@@ -284,6 +347,7 @@ impl SubscriptionHandler {
         if !fundamentals.is_empty() {
             primary_subscriptions.extend(fundamentals);
         }
+        primary_subscriptions.extend(self.depth_subscriptions.read().await.clone());
         primary_subscriptions
     }
 
@@ -294,6 +358,7 @@ impl SubscriptionHandler {
             all_subscriptions.append(&mut symbol_handler.value().all_subscriptions());
         }
         all_subscriptions.extend(self.fundamental_subscriptions.read().await.clone());
+        all_subscriptions.extend(self.depth_subscriptions.read().await.clone());
         all_subscriptions
     }
 
@@ -309,33 +374,42 @@ impl SubscriptionHandler {
             let symbol = base_data.symbol();
             let base_data = base_data.clone();
             let symbol_subscriptions = symbol_subscriptions.clone();
-            match &base_data {
-                BaseDataEnum::Candle(candle) => {
-                    if let Some(mut history) = self.candle_history.get_mut(&candle.subscription()) {
-                        history.add(candle.clone());
+            let paused = self.paused.clone();
+            // the history of a frozen subscription is not updated, the data still feeds the consolidators which are not frozen
+            if !self.is_frozen(&base_data.subscription()) {
+                match &base_data {
+                    BaseDataEnum::Candle(candle) => {
+                        if let Some(mut history) = self.candle_history.get_mut(&candle.subscription()) {
+                            history.add(candle.clone());
+                        }
                     }
-                }
-                BaseDataEnum::QuoteBar(qb) => {
-                    if let Some(mut history) = self.bar_history.get_mut(&qb.subscription()) {
-                        history.add(qb.clone());
+                    BaseDataEnum::QuoteBar(qb) => {
+                        if let Some(mut history) = self.bar_history.get_mut(&qb.subscription()) {
+                            history.add(qb.clone());
+                        }
                     }
-                }
-                BaseDataEnum::Tick(tick) => {
-                    if let Some(mut history) = self.tick_history.get_mut(&tick.subscription()) {
-                        history.add(tick.clone());
+                    BaseDataEnum::Tick(tick) => {
+                        if let Some(mut history) = self.tick_history.get_mut(&tick.subscription()) {
+                            history.add(tick.clone());
+                        }
                     }
-                }
-                BaseDataEnum::Quote(q) => {
-                    if let Some(mut history) = self.quote_history.get_mut(&q.subscription()) {
-                        history.add(q.clone());
+                    BaseDataEnum::Quote(q) => {
+                        if let Some(mut history) = self.quote_history.get_mut(&q.subscription()) {
+                            history.add(q.clone());
+                        }
+                    }
+                    BaseDataEnum::Fundamental(_) => {}
+                    BaseDataEnum::Depth(depth) => {
+                        if let Some(mut history) = self.depth_history.get_mut(&depth.subscription()) {
+                            history.add(depth.clone());
+                        }
                     }
                 }
-                BaseDataEnum::Fundamental(_) => {}
             }
 
             update_futures.push(async move {
                 if let Some(handler) = symbol_subscriptions.get(&symbol) {
-                    handler.update(&base_data).await
+                    handler.update(&base_data, &paused).await
                 } else {
                     Vec::new()
                 }
@@ -381,6 +455,7 @@ impl SubscriptionHandler {
                         rolling_window.add(fund.clone());
                     }
                 }
+                BaseDataEnum::Depth(_) => {}
             }
             time_slice_bars.add(data);
         }
@@ -397,6 +472,8 @@ impl SubscriptionHandler {
             }
             time_slice_bars.add(data);
         }
+        self.enforce_history_budget();
+        self.remove_paused(&mut time_slice_bars);
 
         if time_slice_bars.is_empty() {
             None
@@ -405,6 +482,122 @@ impl SubscriptionHandler {
         }
     }
 
+    /// Sets or removes the memory budget for retained history, the budget is enforced immediately and after each time slice.
+    pub fn set_history_budget(&self, budget: Option<HistoryBudget>) {
+        *self.history_budget.write().unwrap() = budget;
+        self.enforce_history_budget();
+    }
+
+    /// The estimated memory used by the retained history of each subscription.
+    pub fn history_memory_usage(&self) -> Vec<HistoryMemoryUsage> {
+        let mut usage = vec![];
+        usage.extend(self.candle_history.iter().map(|window| window_usage(window.key(), window.value())));
+        usage.extend(self.bar_history.iter().map(|window| window_usage(window.key(), window.value())));
+        usage.extend(self.tick_history.iter().map(|window| window_usage(window.key(), window.value())));
+        usage.extend(self.quote_history.iter().map(|window| window_usage(window.key(), window.value())));
+        usage.extend(self.fundamental_history.iter().map(|window| window_usage(window.key(), window.value())));
+        usage.extend(self.depth_history.iter().map(|window| window_usage(window.key(), window.value())));
+        usage
+    }
+
+    /// Stops delivering the data of a subscription to the strategy and its indicators, the vendor subscription is kept so `resume_subscription()` is instant.
+    /// If `stop_consolidating` is true the consolidator and history of the subscription are not updated either, else they stay current while paused.
+    /// Returns false if the strategy is not subscribed to the subscription.
+    pub async fn pause_subscription(&self, subscription: &DataSubscription, stop_consolidating: bool) -> bool {
+        if !self.subscriptions().await.contains(subscription) {
+            return false;
+        }
+        self.paused.insert(subscription.clone(), stop_consolidating);
+        true
+    }
+
+    /// Resumes a paused subscription, returns false if it was not paused.
+    pub fn resume_subscription(&self, subscription: &DataSubscription) -> bool {
+        self.paused.remove(subscription).is_some()
+    }
+
+    pub fn paused_subscriptions(&self) -> Vec<DataSubscription> {
+        self.paused.iter().map(|paused| paused.key().clone()).collect()
+    }
+
+    /// Removes the data of the paused subscriptions from a strategy time slice.
+    pub(crate) fn remove_paused(&self, time_slice: &mut TimeSlice) {
+        if !self.paused.is_empty() {
+            time_slice.retain(|data| !self.paused.contains_key(&data.subscription()));
+        }
+    }
+
+    fn is_frozen(&self, subscription: &DataSubscription) -> bool {
+        is_frozen(&self.paused, subscription)
+    }
+
+    /// Reduces the retention of subscriptions when the retained history is over budget.
+    fn enforce_history_budget(&self) {
+        let budget = match *self.history_budget.read().unwrap() {
+            Some(budget) => budget,
+            None => return,
+        };
+        for (subscription, max_retained) in plan_evictions(&budget, &self.history_memory_usage()) {
+            eprintln!("Subscription Handler: History over budget, reducing retention for {} to {}", subscription, max_retained);
+            match subscription.base_data_type {
+                BaseDataType::Ticks => {
+                    if let Some(mut window) = self.tick_history.get_mut(&subscription) {
+                        window.set_capacity(max_retained);
+                    }
+                }
+                BaseDataType::Quotes => {
+                    if let Some(mut window) = self.quote_history.get_mut(&subscription) {
+                        window.set_capacity(max_retained);
+                    }
+                }
+                BaseDataType::QuoteBars => {
+                    if let Some(mut window) = self.bar_history.get_mut(&subscription) {
+                        window.set_capacity(max_retained);
+                    }
+                }
+                BaseDataType::Candles => {
+                    if let Some(mut window) = self.candle_history.get_mut(&subscription) {
+                        window.set_capacity(max_retained);
+                    }
+                }
+                BaseDataType::Fundamentals => {
+                    if let Some(mut window) = self.fundamental_history.get_mut(&subscription) {
+                        window.set_capacity(max_retained);
+                    }
+                }
+                BaseDataType::Depth => {
+                    if let Some(mut window) = self.depth_history.get_mut(&subscription) {
+                        window.set_capacity(max_retained);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The retained bars of the consolidated subscriptions which can be checked against the primary history, see `warmup_check.rs`.
+    pub(crate) fn warmup_checks(&self) -> Vec<WarmUpCheck> {
+        let mut checks = vec![];
+        for symbol_handler in self.symbol_subscriptions.iter() {
+            for (primary_subscription, subscription) in symbol_handler.consolidated_subscriptions() {
+                if !is_checked(&subscription) {
+                    continue;
+                }
+                let mut bars: Vec<BaseDataEnum> = match subscription.base_data_type {
+                    BaseDataType::Candles => self.candle_history(&subscription).map(|window| window.history.into_iter().map(BaseDataEnum::Candle).collect()),
+                    BaseDataType::QuoteBars => self.bar_history(&subscription).map(|window| window.history.into_iter().map(BaseDataEnum::QuoteBar).collect()),
+                    _ => None,
+                }.unwrap_or_default();
+                if bars.is_empty() {
+                    continue;
+                }
+                // the latest bar is first in the rolling window
+                bars.reverse();
+                checks.push(WarmUpCheck { subscription, primary_subscription, bars });
+            }
+        }
+        checks
+    }
+
     pub fn bar_history(&self, subscription: &DataSubscription) -> Option<RollingWindow<QuoteBar>> {
         if let Some(window) = self.bar_history.get(subscription) {
             return Some(window.value().clone())
@@ -433,6 +626,24 @@ impl SubscriptionHandler {
         None
     }
 
+    pub fn depth_history(&self, subscription: &DataSubscription) -> Option<RollingWindow<Depth>> {
+        if let Some(window) = self.depth_history.get(subscription) {
+            return Some(window.value().clone())
+        }
+        None
+    }
+
+    /// The retained closed data of a candle, quotebar, tick or quote subscription, latest first.
+    pub(crate) fn history_data(&self, subscription: &DataSubscription) -> Vec<BaseDataEnum> {
+        match subscription.base_data_type {
+            BaseDataType::Candles => self.candle_history.get(subscription).map(|window| window.history.iter().cloned().map(BaseDataEnum::Candle).collect()),
+            BaseDataType::QuoteBars => self.bar_history.get(subscription).map(|window| window.history.iter().cloned().map(BaseDataEnum::QuoteBar).collect()),
+            BaseDataType::Ticks => self.tick_history.get(subscription).map(|window| window.history.iter().cloned().map(BaseDataEnum::Tick).collect()),
+            BaseDataType::Quotes => self.quote_history.get(subscription).map(|window| window.history.iter().cloned().map(BaseDataEnum::Quote).collect()),
+            BaseDataType::Fundamentals | BaseDataType::Depth => None,
+        }.unwrap_or_default()
+    }
+
     pub fn open_bar(&self, subscription: &DataSubscription) -> Option<QuoteBar> {
         match self.open_bars.get(subscription) {
             None => None,
@@ -487,14 +698,25 @@ impl SubscriptionHandler {
         None
     }
 
+    pub fn depth_index(&self, subscription: &DataSubscription, index: usize) -> Option<Depth> {
+        if let Some(window) = self.depth_history.get(subscription) {
+            return match window.get(index) {
+                None => None,
+                Some(data) => Some(data.clone())
+            }
+        }
+        None
+    }
+
     //todo need a live version of this, where we record which consolidators had data and which didnt, we update time for thise that didn't
     pub async fn update_consolidators_time(&self, time: DateTime<Utc>) -> Option<TimeSlice> {
         let symbol_subscriptions = self.symbol_subscriptions.clone();
+        let paused = &self.paused;
         let futures: Vec<_> = symbol_subscriptions.iter().map(|symbol_handler| {
             let time = time.clone();
             // Creating async blocks that will run concurrently
             async move {
-                symbol_handler.value().update_time(time).await
+                symbol_handler.value().update_time(time, paused).await
             }
         }).collect();
 
@@ -533,10 +755,13 @@ impl SubscriptionHandler {
                                 rolling_window.add(fund.clone());
                             }
                         }
+                        BaseDataEnum::Depth(_) => {}
                     }
                 }
                 for base_data in data {
-                    time_slice.add(base_data);
+                    if !self.paused.contains_key(&base_data.subscription()) {
+                        time_slice.add(base_data);
+                    }
                 }
             }
         }
@@ -546,6 +771,26 @@ impl SubscriptionHandler {
             false => Some(time_slice)
         }
     }
+
+    /// Replaces the consolidators with new ones and clears the retained history, eg before the next regime of a backtest.
+    pub(crate) async fn reset_consolidators(&self) {
+        for symbol_handler in self.symbol_subscriptions.iter() {
+            symbol_handler.value().reset_consolidators().await;
+        }
+        self.candle_history.iter_mut().for_each(|mut window| window.value_mut().clear());
+        self.bar_history.iter_mut().for_each(|mut window| window.value_mut().clear());
+        self.tick_history.iter_mut().for_each(|mut window| window.value_mut().clear());
+        self.quote_history.iter_mut().for_each(|mut window| window.value_mut().clear());
+        self.fundamental_history.iter_mut().for_each(|mut window| window.value_mut().clear());
+        self.depth_history.iter_mut().for_each(|mut window| window.value_mut().clear());
+        self.open_candles.clear();
+        self.open_bars.clear();
+    }
+}
+
+/// True if the subscription is paused and stopped consolidating.
+fn is_frozen(paused: &DashMap<DataSubscription, bool>, subscription: &DataSubscription) -> bool {
+    paused.get(subscription).map(|stop_consolidating| *stop_consolidating).unwrap_or(false)
 }
 
 /// This Struct Handles when to consolidate data for a subscription from an existing subscription.
@@ -556,6 +801,8 @@ pub struct SymbolSubscriptionHandler {
     primary_subscriptions: DashMap<PrimarySubscription, DataSubscription>,
     /// The secondary subscriptions are consolidators that are used to consolidate data from the primary subscription. the first key is the primary subscription for each consolidator
     secondary_subscriptions: DashMap<PrimarySubscription, AHashMap<DataSubscription, ConsolidatorEnum>>,
+    /// The fill forward setting and trading hours each consolidator was created with, so it can be created again.
+    consolidator_settings: DashMap<DataSubscription, (bool, Option<TradingHours>)>,
     #[allow(unused)]
     vendor_primary_resolutions: Vec<PrimarySubscription>,
 }
@@ -568,6 +815,7 @@ impl SymbolSubscriptionHandler {
         let handler = SymbolSubscriptionHandler {
             primary_subscriptions: DashMap::new(),
             secondary_subscriptions: DashMap::new(),
+            consolidator_settings: DashMap::new(),
             vendor_primary_resolutions,
         };
         handler
@@ -583,7 +831,7 @@ impl SymbolSubscriptionHandler {
         count
     }
 
-    pub async fn update(&self, base_data_enum: &BaseDataEnum) -> Vec<ConsolidatedData> {
+    pub async fn update(&self, base_data_enum: &BaseDataEnum, paused: &DashMap<DataSubscription, bool>) -> Vec<ConsolidatedData> {
          // Read the secondary subscriptions
         if self.secondary_subscriptions.is_empty() {
             return vec![];
@@ -592,7 +840,10 @@ impl SymbolSubscriptionHandler {
         let sub_res = PrimarySubscription::new(base_data_enum.resolution().clone(), base_data_enum.base_data_type());
         if let Some(mut base_data_consoldiators) = self.secondary_subscriptions.get_mut(&sub_res){
             let mut data = vec![];
-            for (_, consolidator) in base_data_consoldiators.iter_mut() {
+            for (subscription, consolidator) in base_data_consoldiators.iter_mut() {
+                if is_frozen(paused, subscription) {
+                    continue;
+                }
                 let consolidated_data = consolidator.update(&base_data_enum);
                 data.push(consolidated_data);
             }
@@ -601,11 +852,14 @@ impl SymbolSubscriptionHandler {
         vec![]
     }
 
-    pub async fn update_time(&self, time: DateTime<Utc>) -> Option<Vec<BaseDataEnum>> {
+    pub async fn update_time(&self, time: DateTime<Utc>, paused: &DashMap<DataSubscription, bool>) -> Option<Vec<BaseDataEnum>> {
         let mut consolidated_data = vec![];
         // Iterate over the secondary subscriptions and update them
         for mut consolidator_map in self.secondary_subscriptions.iter_mut() {
-            for (_, consolidator) in consolidator_map.iter_mut() {
+            for (subscription, consolidator) in consolidator_map.iter_mut() {
+                if is_frozen(paused, subscription) {
+                    continue;
+                }
                 let data = consolidator.update_time(time.clone());
                 consolidated_data.extend(data);
             }
@@ -624,6 +878,7 @@ impl SymbolSubscriptionHandler {
         warm_up_to_time: DateTime<Utc>,
         history_to_retain: usize,
         strategy_mode: StrategyMode,
+        is_warmed_up: bool,
         fill_forward: bool,
         hours: Option<TradingHours>
     ) -> Result<AHashMap<DataSubscription, RollingWindow<BaseDataEnum>>, DataSubscriptionEvent> {
@@ -642,8 +897,6 @@ impl SymbolSubscriptionHandler {
                 return Err(DataSubscriptionEvent::FailedToSubscribe(new_subscription.clone(), format!("{}: Already subscribed: {}", new_subscription.symbol.data_vendor, new_subscription.symbol.name)))
             }
         }
-        let is_warmed_up = is_warmup_complete();
-
         let mut returned_windows = AHashMap::new();
         if let Some(primary) = primary_source {
             // Handle primary subscription if it doesn't exist
@@ -675,6 +928,7 @@ impl SymbolSubscriptionHandler {
             }
 
             // Handle secondary subscription
+            self.consolidator_settings.insert(new_subscription.clone(), (fill_forward, hours.clone()));
             let consolidator = ConsolidatorEnum::create_consolidator(new_subscription.clone(), fill_forward.clone(), hours).await;
             let (final_consolidator, window) = match is_warmed_up {
                 true => {
@@ -736,6 +990,7 @@ impl SymbolSubscriptionHandler {
             DataSubscriptionEvent::Unsubscribed(subscription.clone())
         } else if let Some(mut map) = self.secondary_subscriptions.get_mut(&sub_res_type) {
             let sub = map.remove(&subscription);
+            self.consolidator_settings.remove(subscription);
             match sub {
                 None => DataSubscriptionEvent::FailedUnSubscribed(subscription.clone(), "No subscription to unsubscribe".to_string()),
                 Some(_consolidator) => DataSubscriptionEvent::Unsubscribed(subscription.clone()),
@@ -745,6 +1000,22 @@ impl SymbolSubscriptionHandler {
         }
     }
 
+    /// Replaces each consolidator with a new one created with the same settings, the new consolidators have no open bar or history.
+    async fn reset_consolidators(&self) {
+        let settings: Vec<(DataSubscription, (bool, Option<TradingHours>))> = self.consolidator_settings.iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        for (subscription, (fill_forward, hours)) in settings {
+            let consolidator = ConsolidatorEnum::create_consolidator(subscription.clone(), fill_forward, hours).await;
+            for mut consolidators in self.secondary_subscriptions.iter_mut() {
+                if let Some(existing) = consolidators.value_mut().get_mut(&subscription) {
+                    *existing = consolidator;
+                    break;
+                }
+            }
+        }
+    }
+
     pub fn all_subscriptions(&self) -> Vec<DataSubscription> {
         // Collect primary subscriptions
         let mut all_subscriptions: Vec<DataSubscription> = self
@@ -770,6 +1041,19 @@ impl SymbolSubscriptionHandler {
     pub fn primary_subscriptions(&self) -> Vec<DataSubscription> {
         self.primary_subscriptions.iter().map(|entry| entry.value().clone()).collect()
     }
+
+    /// The consolidated subscriptions paired with the primary subscription they are consolidated from.
+    pub fn consolidated_subscriptions(&self) -> Vec<(DataSubscription, DataSubscription)> {
+        let mut subscriptions = vec![];
+        for entry in self.secondary_subscriptions.iter() {
+            if let Some(primary) = self.primary_subscriptions.get(entry.key()) {
+                for subscription in entry.value().keys() {
+                    subscriptions.push((primary.value().clone(), subscription.clone()));
+                }
+            }
+        }
+        subscriptions
+    }
 }
 
 
@@ -1,10 +1,22 @@
 use std::collections::HashMap;
-use std::sync::Arc;
-use chrono::{DateTime, Datelike, Duration, Timelike, Utc, Weekday};
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Datelike, Duration, NaiveTime, SubsecRound, TimeZone, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use once_cell::sync::OnceCell;
 use tokio::sync::mpsc::Sender;
+use tokio::sync::oneshot;
 use tokio::sync::RwLock;
 use tokio::task;
 use tokio::time::{interval, sleep, Duration as TokioDuration};
+use crate::standardized_types::market_hours::TradingHours;
+use crate::strategies::historical_time::WarmUpState;
+use crate::strategies::handlers::flat_by_handler::FlatByHandler;
+use crate::strategies::handlers::closed_market_queue::ClosedMarketQueue;
+use crate::strategies::handlers::spread_guard::SpreadGuardHandler;
+use crate::strategies::handlers::stale_order_handler::StaleOrderHandler;
+use crate::strategies::handlers::contract_expiry_handler::ContractExpiryHandler;
+use crate::strategies::handlers::daily_report_handler::DailyReportHandler;
+use crate::strategies::historical_time::clock_now;
 use crate::strategies::strategy_events::StrategyEvent;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -33,7 +45,7 @@ pub enum EventTimeEnum {
         second: u32,
         fire_in_warmup: bool,
     },
-    /// Events to occur at a specific interval
+    /// Events to occur at a specific interval, fires on the first time update at or after `next_time`, then every `duration` after `next_time`.
     Every {
         duration: Duration,
         next_time: DateTime<Utc>,
@@ -88,7 +100,8 @@ impl EventTimeEnum {
                 }
             }
             EventTimeEnum::Every { next_time, .. } => {
-                if current_time == *next_time {
+                // backtest time updates move in steps of the buffer duration and may never equal the next time
+                if current_time >= *next_time {
                     return true;
                 }
             }
@@ -123,21 +136,175 @@ impl TimedEvent {
     }
 }
 
+/// When a session hook is due, see `FundForgeStrategy::on_session_close()` and `FundForgeStrategy::on_daily_rollover()`.
+#[derive(Clone, Debug)]
+pub enum SessionHookTime {
+    /// `before` the close of each session in the trading hours, sends `StrategyEvent::SessionClose(name)`.
+    SessionClose { trading_hours: TradingHours, before: Duration },
+    /// At the time of day in the time zone, sends `StrategyEvent::DailyRollover(name)`.
+    DailyRollover { time_zone: Tz, time: NaiveTime },
+}
+
+/// A session hook fires once per session close or rollover, `last_fired` is the close or rollover time it last fired for.
+#[derive(Clone, Debug)]
+pub struct SessionHook {
+    name: String,
+    time: SessionHookTime,
+    fire_in_warmup: bool,
+    last_fired: Option<DateTime<Utc>>,
+}
+
+impl SessionHook {
+    pub fn new(name: String, time: SessionHookTime, fire_in_warmup: bool) -> Self {
+        SessionHook {
+            name,
+            time,
+            fire_in_warmup,
+            last_fired: None,
+        }
+    }
+
+    /// Returns the close or rollover time the hook is due for at the current time, if any.
+    fn due(&mut self, current_time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let due_time = match &self.time {
+            SessionHookTime::SessionClose { trading_hours, before } => {
+                let until_close = trading_hours.seconds_until_close(current_time)?;
+                if until_close > before.num_seconds() {
+                    return None;
+                }
+                current_time.trunc_subsecs(0) + Duration::seconds(until_close)
+            }
+            SessionHookTime::DailyRollover { time_zone, time } => {
+                let rollover = latest_rollover(time_zone, *time, current_time)?;
+                // the first update only records the latest rollover, so a hook added after the rollover time does not fire until the next day
+                if self.last_fired.is_none() {
+                    self.last_fired = Some(rollover);
+                    return None;
+                }
+                rollover
+            }
+        };
+        match self.last_fired {
+            Some(last_fired) if last_fired >= due_time => None,
+            _ => {
+                self.last_fired = Some(due_time);
+                Some(due_time)
+            }
+        }
+    }
+
+    fn strategy_event(&self) -> StrategyEvent {
+        match self.time {
+            SessionHookTime::SessionClose { .. } => StrategyEvent::SessionClose(self.name.clone()),
+            SessionHookTime::DailyRollover { .. } => StrategyEvent::DailyRollover(self.name.clone()),
+        }
+    }
+}
+
+/// The most recent rollover at or before the current time, a rollover time skipped by a daylight saving change falls on the first valid time after it.
+pub(crate) fn latest_rollover(time_zone: &Tz, time: NaiveTime, current_time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let local_time = current_time.with_timezone(time_zone);
+    let mut date = local_time.date_naive();
+    if local_time.time() < time {
+        date = date.pred_opt()?;
+    }
+    let naive = date.and_time(time);
+    let rollover = match time_zone.from_local_datetime(&naive).earliest() {
+        Some(rollover) => rollover,
+        None => time_zone.from_local_datetime(&(naive + Duration::hours(1))).earliest()?,
+    };
+    Some(rollover.to_utc())
+}
+
+/// The first time after the current time which is a whole number of durations after the last due time.
+fn next_interval(due_time: DateTime<Utc>, duration: Duration, current_time: DateTime<Utc>) -> DateTime<Utc> {
+    let interval = duration.num_nanoseconds().unwrap_or(i64::MAX).max(1);
+    let behind = (current_time - due_time).num_nanoseconds().unwrap_or(i64::MAX).max(0);
+    due_time + Duration::nanoseconds((behind / interval + 1).saturating_mul(interval))
+}
+
 pub struct TimedEventHandler {
     pub(crate) schedule: Arc<RwLock<Vec<TimedEvent>>>,
     last_fired: Arc<RwLock<HashMap<String, DateTime<Utc>>>>,
-    strategy_event_sender: Sender<StrategyEvent>
+    session_hooks: Arc<RwLock<Vec<SessionHook>>>,
+    flat_by_handler: OnceCell<Arc<FlatByHandler>>,
+    stale_order_handler: OnceCell<Arc<StaleOrderHandler>>,
+    closed_market_queue: OnceCell<Arc<ClosedMarketQueue>>,
+    spread_guard_handler: OnceCell<Arc<SpreadGuardHandler>>,
+    contract_expiry_handler: OnceCell<Arc<ContractExpiryHandler>>,
+    daily_report_handler: OnceCell<Arc<DailyReportHandler>>,
+    /// The wake up times of `strategy.sleep_until()`.
+    sleepers: Mutex<Vec<(DateTime<Utc>, oneshot::Sender<()>)>>,
+    strategy_event_sender: Sender<StrategyEvent>,
+    warm_up: WarmUpState,
 }
 
 impl TimedEventHandler {
-    pub fn new(strategy_event_sender: Sender<StrategyEvent>) -> Self {
+    pub(crate) fn new(strategy_event_sender: Sender<StrategyEvent>, warm_up: WarmUpState) -> Self {
         TimedEventHandler {
             schedule: Default::default(),
             last_fired: Arc::new(RwLock::new(HashMap::new())),
-            strategy_event_sender
+            session_hooks: Default::default(),
+            flat_by_handler: OnceCell::new(),
+            stale_order_handler: OnceCell::new(),
+            closed_market_queue: OnceCell::new(),
+            spread_guard_handler: OnceCell::new(),
+            contract_expiry_handler: OnceCell::new(),
+            daily_report_handler: OnceCell::new(),
+            sleepers: Mutex::new(vec![]),
+            strategy_event_sender,
+            warm_up,
         }
     }
 
+    /// Adding a hook with an existing name replaces the old hook.
+    pub async fn add_session_hook(&self, hook: SessionHook) {
+        let mut session_hooks = self.session_hooks.write().await;
+        session_hooks.retain(|existing| existing.name != hook.name);
+        session_hooks.push(hook);
+    }
+
+    /// The flat by rules are checked on each time update, before the session hooks.
+    pub(crate) fn set_flat_by_handler(&self, flat_by_handler: Arc<FlatByHandler>) {
+        let _ = self.flat_by_handler.set(flat_by_handler);
+    }
+
+    /// The stale order rules are checked on each time update, after the flat by rules.
+    pub(crate) fn set_stale_order_handler(&self, stale_order_handler: Arc<StaleOrderHandler>) {
+        let _ = self.stale_order_handler.set(stale_order_handler);
+    }
+
+    /// The orders queued while the market was closed are sent on the first time update the market is open, after the flat by rules.
+    pub(crate) fn set_closed_market_queue(&self, closed_market_queue: Arc<ClosedMarketQueue>) {
+        let _ = self.closed_market_queue.set(closed_market_queue);
+    }
+
+    /// The orders delayed by the spread guard are sent or rejected on each time update, after the closed market queue.
+    pub(crate) fn set_spread_guard_handler(&self, spread_guard_handler: Arc<SpreadGuardHandler>) {
+        let _ = self.spread_guard_handler.set(spread_guard_handler);
+    }
+
+    /// The subscribed futures contracts are checked for expiry on each time update, before the session hooks.
+    pub(crate) fn set_contract_expiry_handler(&self, contract_expiry_handler: Arc<ContractExpiryHandler>) {
+        let _ = self.contract_expiry_handler.set(contract_expiry_handler);
+    }
+
+    /// The daily report is checked on each time update, after the flat by rules so a flatten at the report time is in the report.
+    pub(crate) fn set_daily_report_handler(&self, daily_report_handler: Arc<DailyReportHandler>) {
+        let _ = self.daily_report_handler.set(daily_report_handler);
+    }
+
+    /// Returns a receiver which completes on the first time update at or after the time, the engine time in backtest and warm up.
+    pub(crate) fn wake_at(&self, time: DateTime<Utc>) -> oneshot::Receiver<()> {
+        let (sender, receiver) = oneshot::channel();
+        self.sleepers.lock().unwrap().push((time, sender));
+        receiver
+    }
+
+    pub async fn remove_session_hook(&self, name: &str) {
+        self.session_hooks.write().await.retain(|hook| hook.name != name);
+    }
+
     pub async fn add_event(&self, scheduled_event: TimedEvent) {
         self.schedule.write().await.push(scheduled_event);
     }
@@ -166,7 +333,7 @@ impl TimedEventHandler {
                 interval.tick().await;
 
                 // Get current time
-                let current_time = Utc::now();
+                let current_time = clock_now();
 
                 // Run the update
                 self.update_time(current_time).await;
@@ -175,6 +342,26 @@ impl TimedEventHandler {
     }
 
     pub async fn update_time(&self, current_time: DateTime<Utc>) {
+        if let Some(flat_by_handler) = self.flat_by_handler.get() {
+            flat_by_handler.update_time(current_time).await;
+        }
+        if let Some(stale_order_handler) = self.stale_order_handler.get() {
+            stale_order_handler.update_time(current_time).await;
+        }
+        if let Some(closed_market_queue) = self.closed_market_queue.get() {
+            closed_market_queue.update_time(current_time).await;
+        }
+        if let Some(spread_guard_handler) = self.spread_guard_handler.get() {
+            spread_guard_handler.update_time(current_time).await;
+        }
+        if let Some(contract_expiry_handler) = self.contract_expiry_handler.get() {
+            contract_expiry_handler.update_time(current_time).await;
+        }
+        if let Some(daily_report_handler) = self.daily_report_handler.get() {
+            daily_report_handler.update_time(current_time).await;
+        }
+        self.update_session_hooks(current_time).await;
+        self.wake_sleepers(current_time);
         let mut schedule = self.schedule.write().await;
         let mut last_fired = self.last_fired.write().await;
         if schedule.is_empty() {
@@ -196,28 +383,173 @@ impl TimedEventHandler {
                         last_fired.get(&event.name).map_or(true, |&last| last.date_naive() < current_time.date_naive())
                     },
                     EventTimeEnum::DateTime { .. } => true,
-                    EventTimeEnum::Every { duration, .. } => {
-                        last_fired.get(&event.name).map_or(true, |&last| current_time - last >= *duration)
-                    },
+                    EventTimeEnum::Every { .. } => true,
                 };
+                // events due during the warm up are marked as fired so they are not sent late
+                let should_send = event.time.fire_in_warmup() || self.warm_up.is_complete();
 
-                if should_fire {
+                if should_fire && should_send {
                     let strategy_event = StrategyEvent::TimedEvent(event.name.clone());
                     match self.strategy_event_sender.send(strategy_event).await {
                         Ok(_) => {}
                         Err(e) => eprintln!("Timed Event Handler: Failed to send event: {}", e)
                     }
+                }
+                if should_fire {
                     last_fired.insert(event.name.clone(), current_time);
-
                     if let EventTimeEnum::DateTime { .. } = event.time {
                         events_to_remove.push(event.name.clone());
                     }
+                    // the next time stays on the interval, so a late update does not delay the following events
                     if let EventTimeEnum::Every { duration, ref mut next_time, .. } = event.time {
-                        *next_time = current_time + duration;
+                        *next_time = next_interval(*next_time, duration, current_time);
                     }
                 }
             }
         }
         schedule.retain(|e| !events_to_remove.contains(&e.name));
     }
-}
\ No newline at end of file
+
+    /// Wakes the `sleep_until()` callers which are due, and drops those which stopped waiting.
+    fn wake_sleepers(&self, current_time: DateTime<Utc>) {
+        let mut sleepers = self.sleepers.lock().unwrap();
+        if sleepers.is_empty() {
+            return;
+        }
+        let (due, waiting): (Vec<_>, Vec<_>) = sleepers.drain(..).partition(|(time, _)| *time <= current_time);
+        *sleepers = waiting.into_iter().filter(|(_, sender)| !sender.is_closed()).collect();
+        for (_, sender) in due {
+            let _ = sender.send(());
+        }
+    }
+
+    /// Session hooks are checked before the timed events, in the order they were added.
+    async fn update_session_hooks(&self, current_time: DateTime<Utc>) {
+        let mut session_hooks = self.session_hooks.write().await;
+        for hook in session_hooks.iter_mut() {
+            if hook.due(current_time).is_none() {
+                continue;
+            }
+            // hooks due during the warm up are marked as fired so they are not sent late
+            if !hook.fire_in_warmup && !self.warm_up.is_complete() {
+                continue;
+            }
+            if let Err(e) = self.strategy_event_sender.send(hook.strategy_event()).await {
+                eprintln!("Timed Event Handler: Failed to send session event: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::America::{Chicago, New_York};
+    use crate::product_maps::rithmic::maps::CME_HOURS;
+    use crate::strategies::historical_time::{Clock, SimulatedClock};
+
+    #[test]
+    fn test_session_close_hook() {
+        let mut hook = SessionHook::new("Close".to_string(), SessionHookTime::SessionClose { trading_hours: CME_HOURS, before: Duration::minutes(5) }, true);
+        assert_eq!(hook.due(Chicago.with_ymd_and_hms(2024, 1, 8, 15, 54, 0).unwrap().to_utc()), None);
+
+        let close = Chicago.with_ymd_and_hms(2024, 1, 8, 16, 0, 0).unwrap().to_utc();
+        assert_eq!(hook.due(Chicago.with_ymd_and_hms(2024, 1, 8, 15, 55, 0).unwrap().to_utc()), Some(close));
+        // once per session
+        assert_eq!(hook.due(Chicago.with_ymd_and_hms(2024, 1, 8, 15, 58, 30).unwrap().to_utc()), None);
+        assert_eq!(hook.due(Chicago.with_ymd_and_hms(2024, 1, 9, 15, 56, 0).unwrap().to_utc()), Some(close + Duration::days(1)));
+    }
+
+    #[test]
+    fn test_daily_rollover_hook() {
+        let time = NaiveTime::from_hms_opt(17, 0, 0).unwrap();
+        let mut hook = SessionHook::new("Rollover".to_string(), SessionHookTime::DailyRollover { time_zone: New_York, time }, true);
+        // the first update after the hook is added does not fire for the previous rollover
+        assert_eq!(hook.due(New_York.with_ymd_and_hms(2024, 3, 8, 18, 0, 0).unwrap().to_utc()), None);
+        assert_eq!(hook.due(New_York.with_ymd_and_hms(2024, 3, 9, 16, 59, 59).unwrap().to_utc()), None);
+
+        let rollover = New_York.with_ymd_and_hms(2024, 3, 9, 17, 0, 0).unwrap().to_utc();
+        assert_eq!(hook.due(rollover), Some(rollover));
+        assert_eq!(hook.due(rollover + Duration::hours(1)), None);
+
+        // the rollover stays at 17:00 local time over the daylight saving change
+        let rollover = New_York.with_ymd_and_hms(2024, 3, 10, 17, 0, 0).unwrap().to_utc();
+        assert_eq!(hook.due(rollover + Duration::seconds(1)), Some(rollover));
+    }
+
+    #[tokio::test]
+    async fn test_timed_event_with_simulated_clock() {
+        let start = Utc.with_ymd_and_hms(2024, 6, 3, 14, 30, 0).unwrap();
+        let clock = SimulatedClock::new(start);
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(10);
+        let handler = TimedEventHandler::new(sender, WarmUpState::new());
+        let time = EventTimeEnum::Every { duration: Duration::minutes(1), next_time: start + Duration::minutes(1), fire_in_warmup: true };
+        handler.add_event(TimedEvent::new("Every Minute".to_string(), time)).await;
+
+        clock.advance(Duration::seconds(30));
+        handler.update_time(clock.now()).await;
+        assert!(receiver.try_recv().is_err());
+
+        clock.advance(Duration::seconds(30));
+        handler.update_time(clock.now()).await;
+        assert!(matches!(receiver.try_recv(), Ok(StrategyEvent::TimedEvent(name)) if name == "Every Minute"));
+
+        // the next event is due a minute after it fired
+        clock.advance(Duration::seconds(59));
+        handler.update_time(clock.now()).await;
+        assert!(receiver.try_recv().is_err());
+        clock.advance(Duration::seconds(1));
+        handler.update_time(clock.now()).await;
+        assert!(matches!(receiver.try_recv(), Ok(StrategyEvent::TimedEvent(name)) if name == "Every Minute"));
+    }
+
+    #[tokio::test]
+    async fn test_every_with_uneven_time_updates() {
+        let start = Utc.with_ymd_and_hms(2024, 6, 3, 14, 30, 0).unwrap();
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(10);
+        let handler = TimedEventHandler::new(sender, WarmUpState::new());
+        let time = EventTimeEnum::Every { duration: Duration::minutes(5), next_time: start + Duration::minutes(5), fire_in_warmup: true };
+        handler.add_event(TimedEvent::new("Every 5".to_string(), time)).await;
+
+        // a backtest buffer of 7 seconds never lands on 14:35:00
+        let mut fired = vec![];
+        let mut time = start;
+        while time < start + Duration::minutes(16) {
+            time += Duration::seconds(7);
+            handler.update_time(time).await;
+            if receiver.try_recv().is_ok() {
+                fired.push(time);
+            }
+        }
+        assert_eq!(fired, vec![
+            Utc.with_ymd_and_hms(2024, 6, 3, 14, 35, 1).unwrap(),
+            Utc.with_ymd_and_hms(2024, 6, 3, 14, 40, 2).unwrap(),
+            Utc.with_ymd_and_hms(2024, 6, 3, 14, 45, 3).unwrap(),
+        ]);
+
+        // a gap in the data fires once, then the interval continues from the schedule
+        handler.update_time(start + Duration::hours(1) + Duration::seconds(30)).await;
+        assert!(receiver.try_recv().is_ok());
+        handler.update_time(start + Duration::hours(1) + Duration::minutes(4)).await;
+        assert!(receiver.try_recv().is_err());
+        handler.update_time(start + Duration::hours(1) + Duration::minutes(5)).await;
+        assert!(receiver.try_recv().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_wake_at_engine_time() {
+        let start = Utc.with_ymd_and_hms(2024, 6, 3, 14, 30, 0).unwrap();
+        let (sender, _receiver) = tokio::sync::mpsc::channel(10);
+        let handler = TimedEventHandler::new(sender, WarmUpState::new());
+        let mut wake_up = handler.wake_at(start + Duration::minutes(30));
+        let abandoned = handler.wake_at(start + Duration::hours(2));
+        drop(abandoned);
+
+        handler.update_time(start + Duration::minutes(29)).await;
+        assert!(wake_up.try_recv().is_err());
+        handler.update_time(start + Duration::minutes(31)).await;
+        assert!(wake_up.try_recv().is_ok());
+        // the dropped receiver is not kept waiting
+        assert!(handler.sleepers.lock().unwrap().is_empty());
+    }
+}
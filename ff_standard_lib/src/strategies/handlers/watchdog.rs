@@ -0,0 +1,293 @@
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::panic;
+use std::sync::{Arc, Mutex, Once};
+use std::time::{Duration, Instant};
+use chrono::{NaiveTime, Utc};
+use chrono_tz::Tz;
+use lazy_static::lazy_static;
+use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use crate::standardized_types::new_types::Price;
+use crate::strategies::handlers::flat_by_handler::{FlatByHandler, FlatByRule};
+use crate::strategies::strategy_events::StrategyEvent;
+use crate::strategies::strategy_settings::StrategySettings;
+
+/// The thresholds of the watchdog, see `strategy.start_watchdog()`.
+/// # Properties
+/// * `check_interval` - How often the process is checked, 1 second by default.
+/// * `max_loop_lag` - How late a check can be before `LoopLag` is sent, 250 milliseconds by default.
+/// * `max_event_backlog` - How many events can wait in the strategy event channel before `EventBacklog` is sent, 80% of the channel capacity if None.
+/// * `stall_after` - How long the strategy event channel can stay full before `Stalled` is sent, 30 seconds by default.
+/// * `flatten_on_stall` - Cancel the working orders and flatten the strategy accounts on a stall, false by default.
+/// * `memory_growth_mb` - The resident memory growth in megabytes between `MemoryGrowth` warnings, 512 by default.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WatchdogSettings {
+    pub check_interval: Duration,
+    pub max_loop_lag: Duration,
+    pub max_event_backlog: Option<usize>,
+    pub stall_after: Duration,
+    pub flatten_on_stall: bool,
+    pub memory_growth_mb: u64,
+}
+
+impl Default for WatchdogSettings {
+    fn default() -> Self {
+        WatchdogSettings {
+            check_interval: Duration::from_secs(1),
+            max_loop_lag: Duration::from_millis(250),
+            max_event_backlog: None,
+            stall_after: Duration::from_secs(30),
+            flatten_on_stall: false,
+            memory_growth_mb: 512,
+        }
+    }
+}
+
+/// A problem found by the watchdog or the stale quote guard, sent with `StrategyEvent::HealthWarning`.
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub enum HealthWarning {
+    /// The watchdog check was late, the runtime is blocked or overloaded.
+    LoopLag { lag_millis: u64 },
+    /// Events are waiting in the strategy event channel.
+    EventBacklog { events: u64, capacity: u64 },
+    /// The strategy event channel has been full for the duration, `flattened` is true if the accounts were flattened.
+    Stalled { seconds: u64, flattened: bool },
+    /// The resident memory grew since the watchdog started.
+    MemoryGrowth { grown_mb: u64, resident_mb: u64 },
+    /// A thread or task panicked.
+    TaskPanic { thread: String, message: String },
+    /// A market order was refused because the prices of the symbol were older than the max quote age, see `strategy.set_max_quote_age()`.
+    StaleQuote { symbol_name: String, age_millis: u64 },
+    /// An entry was refused or delayed because the spread of the symbol was wider than the max spread, see `strategy.set_spread_guard()`.
+    /// `delayed` is true if a delayed order was rejected after waiting for the spread to narrow.
+    WideSpread { symbol_name: String, spread: Price, max_spread: Price, delayed: bool },
+    /// A fill was further outside the touch at its fill time than the tolerance of the fill audit, see `strategy.start_fill_audit()`.
+    FillOutsideTouch { symbol_name: String, order_id: String, price: Price, bid: Option<Price>, ask: Option<Price> },
+}
+
+impl Display for HealthWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            HealthWarning::LoopLag { lag_millis } => write!(f, "Health Warning: The runtime is lagging by {}ms", lag_millis),
+            HealthWarning::EventBacklog { events, capacity } => write!(f, "Health Warning: {} of {} strategy events are waiting", events, capacity),
+            HealthWarning::Stalled { seconds, flattened } => write!(f, "Health Warning: The strategy event loop has stalled for {}s, flattened: {}", seconds, flattened),
+            HealthWarning::MemoryGrowth { grown_mb, resident_mb } => write!(f, "Health Warning: Memory grew by {}MB to {}MB", grown_mb, resident_mb),
+            HealthWarning::TaskPanic { thread, message } => write!(f, "Health Warning: A task panicked on thread {}: {}", thread, message),
+            HealthWarning::StaleQuote { symbol_name, age_millis } => write!(f, "Health Warning: A market order for {} was refused, the last quote is {}ms old", symbol_name, age_millis),
+            HealthWarning::WideSpread { symbol_name, spread, max_spread, delayed } => match delayed {
+                true => write!(f, "Health Warning: A delayed entry for {} was rejected, the spread {} stayed wider than {}", symbol_name, spread, max_spread),
+                false => write!(f, "Health Warning: An entry for {} was refused, the spread {} is wider than {}", symbol_name, spread, max_spread),
+            },
+            HealthWarning::FillOutsideTouch { symbol_name, order_id, price, bid, ask } => {
+                let touch = |price: &Option<Price>| price.map_or("none".to_string(), |price| price.to_string());
+                write!(f, "Health Warning: Order {} for {} filled at {} outside the touch, bid: {}, ask: {}", order_id, symbol_name, price, touch(bid), touch(ask))
+            }
+        }
+    }
+}
+
+/// One check of the process.
+struct HealthSample {
+    lag: Duration,
+    backlog: usize,
+    capacity: usize,
+    resident_mb: Option<u64>,
+}
+
+/// The thresholds crossed since the last recovery, so each warning is only sent once.
+struct WatchdogState {
+    settings: WatchdogSettings,
+    lagging: bool,
+    backlogged: bool,
+    full_for: Duration,
+    stalled: bool,
+    memory_baseline_mb: Option<u64>,
+    next_memory_warning_mb: u64,
+}
+
+impl WatchdogState {
+    fn new(settings: WatchdogSettings) -> Self {
+        let next_memory_warning_mb = settings.memory_growth_mb;
+        WatchdogState {
+            settings,
+            lagging: false,
+            backlogged: false,
+            full_for: Duration::ZERO,
+            stalled: false,
+            memory_baseline_mb: None,
+            next_memory_warning_mb,
+        }
+    }
+
+    /// Returns the new warnings, `Stalled` is returned with `flattened: false`, the caller flattens if the settings ask for it.
+    fn check(&mut self, sample: &HealthSample) -> Vec<HealthWarning> {
+        let mut warnings = vec![];
+        let lagging = sample.lag > self.settings.max_loop_lag;
+        if lagging && !self.lagging {
+            warnings.push(HealthWarning::LoopLag { lag_millis: sample.lag.as_millis() as u64 });
+        }
+        self.lagging = lagging;
+
+        let max_backlog = self.settings.max_event_backlog.unwrap_or(sample.capacity * 4 / 5);
+        let backlogged = sample.backlog > max_backlog;
+        if backlogged && !self.backlogged {
+            warnings.push(HealthWarning::EventBacklog { events: sample.backlog as u64, capacity: sample.capacity as u64 });
+        }
+        self.backlogged = backlogged;
+
+        match sample.backlog >= sample.capacity {
+            true => self.full_for += self.settings.check_interval + sample.lag,
+            false => {
+                self.full_for = Duration::ZERO;
+                self.stalled = false;
+            }
+        }
+        if !self.stalled && self.full_for >= self.settings.stall_after {
+            self.stalled = true;
+            warnings.push(HealthWarning::Stalled { seconds: self.full_for.as_secs(), flattened: false });
+        }
+
+        if let Some(resident_mb) = sample.resident_mb {
+            let baseline = *self.memory_baseline_mb.get_or_insert(resident_mb);
+            let grown_mb = resident_mb.saturating_sub(baseline);
+            if self.settings.memory_growth_mb > 0 && grown_mb >= self.next_memory_warning_mb {
+                warnings.push(HealthWarning::MemoryGrowth { grown_mb, resident_mb });
+                self.next_memory_warning_mb = (grown_mb / self.settings.memory_growth_mb + 1) * self.settings.memory_growth_mb;
+            }
+        }
+        warnings
+    }
+}
+
+lazy_static! {
+    static ref PANICS: Mutex<Vec<HealthWarning>> = Mutex::new(vec![]);
+    static ref WATCHDOG_TASK: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+}
+
+static PANIC_HOOK: Once = Once::new();
+
+/// Records every panic of the process for the watchdog, the previous hook still prints the panic.
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let message = match (info.payload().downcast_ref::<&str>(), info.payload().downcast_ref::<String>()) {
+                (Some(message), _) => message.to_string(),
+                (_, Some(message)) => message.clone(),
+                _ => "unknown panic".to_string(),
+            };
+            let message = match info.location() {
+                Some(location) => format!("{} at {}:{}", message, location.file(), location.line()),
+                None => message,
+            };
+            let thread = std::thread::current().name().unwrap_or("unnamed").to_string();
+            if let Ok(mut panics) = PANICS.lock() {
+                panics.push(HealthWarning::TaskPanic { thread, message });
+            }
+            previous_hook(info);
+        }));
+    });
+}
+
+/// The resident memory of the process in megabytes, None where /proc is not available.
+fn resident_mb() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes / 1024)
+}
+
+/// Starts the watchdog, replacing a running watchdog.
+pub(crate) fn start_watchdog(settings: WatchdogSettings, strategy_event_sender: mpsc::Sender<StrategyEvent>, flat_by_handler: Arc<FlatByHandler>, strategy_settings: Arc<StrategySettings>) {
+    install_panic_hook();
+    let task = tokio::task::spawn(async move {
+        let mut state = WatchdogState::new(settings.clone());
+        let mut interval = tokio::time::interval(settings.check_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        interval.tick().await;
+        let mut last_tick = Instant::now();
+        loop {
+            interval.tick().await;
+            let sample = HealthSample {
+                lag: last_tick.elapsed().saturating_sub(settings.check_interval),
+                backlog: strategy_event_sender.max_capacity() - strategy_event_sender.capacity(),
+                capacity: strategy_event_sender.max_capacity(),
+                resident_mb: resident_mb(),
+            };
+            last_tick = Instant::now();
+            let mut warnings = state.check(&sample);
+            warnings.extend(PANICS.lock().map(|mut panics| panics.drain(..).collect::<Vec<_>>()).unwrap_or_default());
+            for mut warning in warnings {
+                if let HealthWarning::Stalled { flattened, .. } = &mut warning {
+                    if settings.flatten_on_stall {
+                        let rule = FlatByRule::new("Watchdog".to_string(), Tz::UTC, NaiveTime::from_hms_opt(0, 0, 0).unwrap(), None, None);
+                        flat_by_handler.flatten_now(&rule, Utc::now()).await;
+                        *flattened = true;
+                    }
+                }
+                eprintln!("{}", warning);
+                strategy_settings.record_risk_event(Utc::now(), warning.to_string());
+                // never wait on the event loop, it may be the problem
+                let _ = strategy_event_sender.try_send(StrategyEvent::HealthWarning(warning));
+            }
+        }
+    });
+    if let Some(previous) = WATCHDOG_TASK.lock().unwrap().replace(task) {
+        previous.abort();
+    }
+}
+
+/// Stops the watchdog, panics are still recorded but no longer reported.
+pub(crate) fn stop_watchdog() {
+    if let Some(task) = WATCHDOG_TASK.lock().unwrap().take() {
+        task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(lag_millis: u64, backlog: usize, resident_mb: u64) -> HealthSample {
+        HealthSample { lag: Duration::from_millis(lag_millis), backlog, capacity: 100, resident_mb: Some(resident_mb) }
+    }
+
+    #[test]
+    fn test_warnings_once_per_crossing() {
+        let settings = WatchdogSettings { stall_after: Duration::from_secs(3), memory_growth_mb: 100, ..Default::default() };
+        let mut state = WatchdogState::new(settings);
+        assert!(state.check(&sample(0, 0, 1000)).is_empty());
+
+        assert_eq!(state.check(&sample(500, 81, 1000)), vec![
+            HealthWarning::LoopLag { lag_millis: 500 },
+            HealthWarning::EventBacklog { events: 81, capacity: 100 },
+        ]);
+        assert!(state.check(&sample(600, 90, 1000)).is_empty());
+
+        // full for 3 checks of 1 second
+        assert!(state.check(&sample(0, 100, 1000)).is_empty());
+        assert!(state.check(&sample(0, 100, 1000)).is_empty());
+        assert_eq!(state.check(&sample(0, 100, 1000)), vec![HealthWarning::Stalled { seconds: 3, flattened: false }]);
+        assert!(state.check(&sample(0, 100, 1000)).is_empty());
+
+        // recovered, then the thresholds are crossed again
+        assert!(state.check(&sample(0, 0, 1000)).is_empty());
+        assert_eq!(state.check(&sample(300, 0, 1000)), vec![HealthWarning::LoopLag { lag_millis: 300 }]);
+    }
+
+    #[test]
+    fn test_memory_growth() {
+        let settings = WatchdogSettings { memory_growth_mb: 100, ..Default::default() };
+        let mut state = WatchdogState::new(settings);
+        assert!(state.check(&sample(0, 0, 1000)).is_empty());
+        assert!(state.check(&sample(0, 0, 1099)).is_empty());
+        assert_eq!(state.check(&sample(0, 0, 1150)), vec![HealthWarning::MemoryGrowth { grown_mb: 150, resident_mb: 1150 }]);
+        assert!(state.check(&sample(0, 0, 1190)).is_empty());
+        assert_eq!(state.check(&sample(0, 0, 1350)), vec![HealthWarning::MemoryGrowth { grown_mb: 350, resident_mb: 1350 }]);
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use chrono::{DateTime, Duration as ChronoDuration, NaiveTime, TimeZone, Utc};
-use crate::strategies::client_features::server_connections::{set_warmup_complete};
 use crate::standardized_types::base_data::history::{get_compressed_historical_data};
 use crate::standardized_types::enums::StrategyMode;
 use crate::strategies::strategy_events::StrategyEvent;
@@ -12,11 +12,59 @@ use crate::standardized_types::subscriptions::DataSubscription;
 use tokio::sync::{broadcast, mpsc, Notify};
 use crate::strategies::handlers::indicator_handler::IndicatorHandler;
 use crate::strategies::handlers::market_handler::backtest_matching_engine::BackTestEngineMessage;
+use crate::strategies::handlers::market_handler::aggressor_classifier::classify_aggressors;
 use crate::strategies::handlers::market_handler::price_service::MarketPriceService;
+use crate::strategies::handlers::price_trigger_handler::PriceTriggerHandler;
 use crate::strategies::handlers::subscription_handler::SubscriptionHandler;
+use crate::strategies::consolidators::warmup_check::check_warmup;
 use crate::strategies::handlers::timed_events_handler::TimedEventHandler;
 use crate::strategies::historical_time::update_backtest_time;
 use crate::strategies::ledgers::ledger_service::LedgerService;
+use crate::strategies::statistics::BacktestRegime;
+use crate::strategies::backtest_progress::ProgressTracker;
+use crate::strategies::backtest_profiler::{BacktestProfiler, ProfiledStage};
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::strategies::historical_time::get_backtest_time;
+use crate::strategies::news_datasets::attached_news;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref STOP_REQUEST: Mutex<Option<String>> = Mutex::new(None);
+    static ref BACKTEST_RUNNING: AtomicBool = AtomicBool::new(false);
+}
+
+/// Requests the running backtest to stop at the next buffer, only the first reason is kept.
+pub(crate) fn request_backtest_stop(reason: String) {
+    let mut stop_request = STOP_REQUEST.lock().unwrap();
+    if stop_request.is_none() {
+        *stop_request = Some(reason);
+    }
+}
+
+fn stop_requested() -> Option<String> {
+    STOP_REQUEST.lock().unwrap().clone()
+}
+
+pub(crate) fn is_backtest_running() -> bool {
+    BACKTEST_RUNNING.load(Ordering::SeqCst)
+}
+
+/// The first ctrl-c stops the backtest so the strategy can still produce statistics and exports for the completed portion, a second ctrl-c exits immediately.
+pub(crate) fn stop_backtest_on_ctrl_c() {
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        if !is_backtest_running() {
+            std::process::exit(130);
+        }
+        println!("Historical Engine: Ctrl+C received, stopping the backtest, press Ctrl+C again to exit immediately");
+        request_backtest_stop("Ctrl+C".to_string());
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(130);
+        }
+    });
+}
 
 #[allow(dead_code)]
 pub(crate) struct HistoricalEngine {
@@ -35,7 +83,11 @@ pub(crate) struct HistoricalEngine {
     timed_event_handler: Arc<TimedEventHandler>,
     indicator_handler: Arc<IndicatorHandler>,
     subscription_handler: Arc<SubscriptionHandler>,
-    market_price_service: Arc<MarketPriceService>
+    market_price_service: Arc<MarketPriceService>,
+    price_trigger_handler: Arc<PriceTriggerHandler>,
+    regimes: Vec<BacktestRegime>,
+    progress: ProgressTracker,
+    profiler: BacktestProfiler
 }
 
 // The date 2023-08-19 is in ISO week 33 of the year 2023
@@ -55,9 +107,16 @@ impl HistoricalEngine {
         timed_event_handler: Arc<TimedEventHandler>,
         indicator_handler: Arc<IndicatorHandler>,
         subscription_handler: Arc<SubscriptionHandler>,
-        market_price_service: Arc<MarketPriceService>
+        market_price_service: Arc<MarketPriceService>,
+        price_trigger_handler: Arc<PriceTriggerHandler>,
+        regimes: Vec<BacktestRegime>
     ) -> Self {
         let rx = subscription_handler.subscribe_primary_subscription_updates();
+        *STOP_REQUEST.lock().unwrap() = None;
+        let progress_periods = match regimes.is_empty() {
+            true => vec![(start_date, end_date)],
+            false => regimes.iter().map(|regime| (regime.start, regime.end)).collect(),
+        };
         let engine = HistoricalEngine {
             mode,
             start_time: start_date,
@@ -74,7 +133,11 @@ impl HistoricalEngine {
             timed_event_handler,
             indicator_handler,
             subscription_handler,
-            market_price_service
+            market_price_service,
+            price_trigger_handler,
+            regimes,
+            progress: ProgressTracker::new(progress_periods),
+            profiler: BacktestProfiler::new()
         };
         engine
     }
@@ -86,19 +149,62 @@ impl HistoricalEngine {
             panic!("Engine: Trying to launch backtest engine in live mode");
         }
         println!("Engine: Initializing the strategy...");
+        BACKTEST_RUNNING.store(true, Ordering::SeqCst);
         tokio::spawn(async move {
             let warm_up_start_time = self.start_time - self.warmup_duration;
 
-            match self.mode {
+            let stop_reason = match self.mode {
+                StrategyMode::Backtest if self.regimes.is_empty() => {
+                    self.historical_data_feed(warm_up_start_time, self.end_time, self.buffer_resolution, self.mode).await
+                }
                 StrategyMode::Backtest => {
-                    self.historical_data_feed(warm_up_start_time, self.end_time, self.buffer_resolution, self.mode).await;
+                    // each regime is warmed up and run separately, orders, positions, indicators and consolidators are not carried over between regimes.
+                    let mut stop_reason = None;
+                    for (index, regime) in self.regimes.clone().into_iter().enumerate() {
+                        println!("Historical Engine: Start Regime: {}", regime);
+                        if index > 0 {
+                            self.reset_for_regime().await;
+                        }
+                        self.start_time = regime.start;
+                        stop_reason = self.historical_data_feed(regime.start - self.warmup_duration, regime.end, self.buffer_resolution, self.mode).await;
+                        if stop_reason.is_some() {
+                            break;
+                        }
+                        if let Some(backtest_message_sender) = &self.historical_message_sender {
+                            let message = BackTestEngineMessage::CancelAllOrders(format!("End Of Regime: {}", regime.name));
+                            if let Err(e) = backtest_message_sender.send(message).await {
+                                eprintln!("Historical Engine: Failed to cancel orders: {}", e);
+                            }
+                        }
+                        self.ledger_service.flatten_all_paper_accounts(regime.end).await;
+                        println!("Historical Engine: End Regime: {}", regime.name);
+                    }
+                    stop_reason
                 }
                 StrategyMode::Live | StrategyMode::LivePaperTrading => panic!("Incorrect engine for Live modes"),
-            }
+            };
 
             match self.mode {
                 StrategyMode::Backtest => {
-                    let event = StrategyEvent::ShutdownEvent("Backtest Complete".to_string());
+                    let (progress, event) = match stop_reason {
+                        Some(reason) => {
+                            // positions are closed at the stop time, so the statistics for the completed portion are final
+                            let time = get_backtest_time();
+                            println!("Historical Engine: Backtest stopped at {}: {}", time, reason);
+                            self.ledger_service.flatten_all_paper_accounts(time).await;
+                            (self.progress.stopped(time), StrategyEvent::ShutdownEvent(format!("Backtest Stopped: {}", reason)))
+                        }
+                        None => (self.progress.complete(self.end_time), StrategyEvent::ShutdownEvent("Backtest Complete".to_string())),
+                    };
+                    ProgressTracker::publish(&progress);
+                    if let Some(profile) = self.profiler.finish() {
+                        println!("{}", profile);
+                    }
+                    self.ledger_service.export_audit_trails().await;
+                    if let Err(e) = self.strategy_event_sender.send(StrategyEvent::BacktestProgress(progress)).await {
+                        eprintln!("Historical Engine: Failed to send event: {}", e);
+                    }
+                    BACKTEST_RUNNING.store(false, Ordering::SeqCst);
                     if let Err(e) = self.strategy_event_sender.send(event).await {
                         eprintln!("Historical Engine: Failed to send event: {}", e);
                     }
@@ -108,15 +214,23 @@ impl HistoricalEngine {
         });
     }
 
+    /// Restarts the warm up and resets the indicators, consolidators and retained history, so the next regime is warmed up from its own data.
+    async fn reset_for_regime(&self) {
+        self.subscription_handler.warm_up().reset();
+        self.indicator_handler.reset_indicators();
+        self.subscription_handler.reset_consolidators().await;
+    }
+
     /// Feeds the historical data to the strategy, along with any events that were created.
     /// Simulates trading with a live buffer, where we catch events for x duration before forwarding to the strategy
+    /// Returns the reason if the backtest was stopped early.
     async fn historical_data_feed(
         &mut self,
         warm_up_start_time: DateTime<Utc>,
         end_time: DateTime<Utc>,
         buffer_duration: Duration,
         mode: StrategyMode,
-    ) {
+    ) -> Option<String> {
         println!("Historical Engine: Warming up the strategy...");
         // here we are looping through 1 day at a time, if the strategy updates its subscriptions we will stop the data feed, download the historical data again to include updated symbols, and resume from the next time to be processed.
         let mut warm_up_complete = false;
@@ -137,10 +251,16 @@ impl HistoricalEngine {
             println!("Historical Engine: Strategy Subscription: {}", subscription);
         }
 
+        // the attached news is added to the strategy time slices as it falls due
+        let mut news = attached_news(warm_up_start_time, end_time);
+
         let mut last_time = warm_up_start_time.clone();
         let mut early_return = false;
         let mut last_date = last_time.date_naive();
         'main_loop: while last_time <= end_time {
+            if let Some(reason) = stop_requested() {
+                return Some(reason);
+            }
             // Assuming `last_time` is a `DateTime<Utc>`
             if !early_return && last_time.date_naive() == last_date {
                 // Adjust `last_time` to the start of the next day in `Utc`
@@ -157,7 +277,10 @@ impl HistoricalEngine {
             let to_time = Utc.from_utc_datetime(&end_of_day);
             //eprintln!("Historical Engine: Processing data from {} to {}", last_time, to_time);
 
-            let mut time_slices = match get_compressed_historical_data(primary_subscriptions.clone(), last_time.clone(), to_time).await {
+            let loading = self.profiler.start();
+            let loaded = get_compressed_historical_data(primary_subscriptions.clone(), last_time.clone(), to_time).await;
+            self.profiler.record(ProfiledStage::DataLoading, loading);
+            let mut time_slices = match loaded {
                 Ok(time_slices) => {
                     if time_slices.is_empty() && !self.tick_over_no_data {
                         //eprintln!("Historical Engine: No data period, weekend or holiday: skipping");
@@ -177,22 +300,31 @@ impl HistoricalEngine {
             };
             last_date = last_time.date_naive();
             early_return = false;
+            if self.profiler.is_enabled() {
+                let buffered: usize = time_slices.values().map(|time_slice| time_slice.len()).sum();
+                self.profiler.sample_memory(ProfiledStage::DataLoading, buffered * std::mem::size_of::<BaseDataEnum>());
+            }
 
             //eprintln!("Time Slices: {}", time_slices.len());
 
             let mut time = last_time;
             'day_loop: while time <= to_time {
+                if let Some(reason) = stop_requested() {
+                    return Some(reason);
+                }
                 time += buffer_duration;
                 if !warm_up_complete {
                     if time >= self.start_time {
                         eprintln!("Historical Engine: Warm up complete: {}", time);
                         warm_up_complete = true;
-                        set_warmup_complete();
+                        self.subscription_handler.warm_up().set_complete();
+                        self.progress.start();
                         let event = StrategyEvent::WarmUpComplete;
                         match self.strategy_event_sender.send(event).await {
                             Ok(_) => {}
                             Err(e) => eprintln!("Historical Engine: Failed to send event: {}", e)
                         }
+                        tokio::spawn(check_warmup(self.subscription_handler.warmup_checks(), self.strategy_event_sender.clone()));
                         if mode == StrategyMode::Live || mode == StrategyMode::LivePaperTrading {
                             break 'main_loop
                         }
@@ -212,7 +344,9 @@ impl HistoricalEngine {
                     Err(_) => {}
                 }
 
+                let timed_events = self.profiler.start();
                 self.timed_event_handler.update_time(time.clone()).await;
+                self.profiler.record(ProfiledStage::TimedEvents, timed_events);
 
                 let time_range = last_time.timestamp_nanos_opt().unwrap()..=time.timestamp_nanos_opt().unwrap();
                 let mut time_slice: TimeSlice = TimeSlice::new();
@@ -232,19 +366,32 @@ impl HistoricalEngine {
                 let mut strategy_time_slice: TimeSlice = TimeSlice::new();
                 // update our consolidators and create the strategies time slice with any new data or just create empty slice.
                 if !time_slice.is_empty() {
+                    self.progress.record_events(time_slice.len());
+                    classify_aggressors(&mut time_slice);
                     let arc_slice = Arc::new(time_slice.clone());
+                    let matching = self.profiler.start();
                     self.market_price_service.update_market_data(arc_slice.clone());
+                    self.profiler.record(ProfiledStage::OrderMatching, matching);
+                    let ledgers = self.profiler.start();
                     self.ledger_service.timeslice_updates(arc_slice.clone()).await;
+                    self.profiler.record(ProfiledStage::Ledgers, ledgers);
+                    let matching = self.profiler.start();
+                    self.price_trigger_handler.update_time_slice(&time_slice).await;
+                    self.profiler.record(ProfiledStage::OrderMatching, matching);
 
                     // Add only primary data which the strategy has subscribed to into the strategies time slice
+                    let consolidation = self.profiler.start();
                     if let Some(consolidated_data) = self.subscription_handler.update_time_slice(arc_slice.clone()).await {
                         strategy_time_slice.extend(consolidated_data);
                     }
+                    self.profiler.record(ProfiledStage::Consolidation, consolidation);
 
                     strategy_time_slice.extend(time_slice);
+                    self.subscription_handler.remove_paused(&mut strategy_time_slice);
                 }
 
 
+                let matching = self.profiler.start();
                 if let Some(backtest_message_sender) = &self.historical_message_sender {
                     let message = BackTestEngineMessage::TickBufferTime;
                     match backtest_message_sender.send(message).await {
@@ -252,16 +399,33 @@ impl HistoricalEngine {
                         Err(e) => panic!("Market Handler: Error sending backtest message: {}", e)
                     }
                 }
+                self.profiler.record(ProfiledStage::OrderMatching, matching);
 
                 // update the consolidators time and see if that generates new data, in case we didn't have primary data to update with.
+                let consolidation = self.profiler.start();
                 if let Some(consolidated_data) = self.subscription_handler.update_consolidators_time(time.clone()).await {
                     strategy_time_slice.extend(consolidated_data);
                 }
+                self.profiler.record(ProfiledStage::Consolidation, consolidation);
+
+                // the news of a day skipped for having no data is delivered with the next buffer
+                let news_keys: Vec<i64> = news.range(..=time.timestamp_nanos_opt().unwrap()).map(|(key, _)| *key).collect();
+                for key in news_keys {
+                    if let Some(items) = news.remove(&key) {
+                        strategy_time_slice.extend(items);
+                    }
+                }
 
                 update_backtest_time(time);
+                let mut strategy_events = self.profiler.start();
                 if !strategy_time_slice.is_empty() {
                     // Update indicators and get_requests any generated events.
-                    if let Some(events) = self.indicator_handler.update_time_slice(&strategy_time_slice).await {
+                    let indicators = self.profiler.start();
+                    let indicator_events = self.indicator_handler.update_time_slice(&strategy_time_slice).await;
+                    self.profiler.record(ProfiledStage::Indicators, indicators);
+                    // the strategy time starts once the indicators are updated
+                    strategy_events = self.profiler.start();
+                    if let Some(events) = indicator_events {
                         match self.strategy_event_sender.send(StrategyEvent::IndicatorEvent(events)).await {
                             Ok(_) => {}
                             Err(e) => eprintln!("Historical Engine: Failed to send event: {}", e)
@@ -277,8 +441,24 @@ impl HistoricalEngine {
                     }
                 }
                 self.notified.notified().await;
+                self.profiler.record(ProfiledStage::StrategyEvents, strategy_events);
                 last_time = time.clone();
+
+                if self.profiler.memory_sample_due() {
+                    let history_bytes = self.subscription_handler.history_memory_usage().iter().map(|usage| usage.bytes).sum();
+                    self.profiler.sample_memory(ProfiledStage::Consolidation, history_bytes);
+                    self.profiler.sample_memory(ProfiledStage::Indicators, self.indicator_handler.estimated_memory());
+                    self.profiler.sample_memory(ProfiledStage::Ledgers, self.ledger_service.estimated_memory());
+                }
+
+                if let Some(progress) = self.progress.due(time) {
+                    ProgressTracker::publish(&progress);
+                    if let Err(e) = self.strategy_event_sender.send(StrategyEvent::BacktestProgress(progress)).await {
+                        eprintln!("Historical Engine: Failed to send event: {}", e);
+                    }
+                }
             }
         }
+        None
     }
 }
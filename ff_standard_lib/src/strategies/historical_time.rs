@@ -1,9 +1,85 @@
-use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, RwLock};
 use chrono::{DateTime, Utc, TimeZone};
 use lazy_static::lazy_static;
+use tokio::sync::broadcast;
+use crate::standardized_types::enums::StrategyMode;
+
+/// A source of the current time.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The wall clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock which only moves when it is set or advanced.
+///
+/// # Example
+/// ```rust
+/// let clock = Arc::new(SimulatedClock::new(start_time));
+/// set_clock(Some(clock.clone()));
+/// clock.advance(chrono::Duration::minutes(5));
+/// assert_eq!(strategy.time_utc(), start_time + chrono::Duration::minutes(5));
+/// set_clock(None);
+/// ```
+pub struct SimulatedClock {
+    nanos: AtomicI64,
+}
+
+impl SimulatedClock {
+    pub fn new(time: DateTime<Utc>) -> Self {
+        SimulatedClock {
+            nanos: AtomicI64::new(time.timestamp_nanos_opt().unwrap()),
+        }
+    }
+
+    pub fn set_time(&self, time: DateTime<Utc>) {
+        self.nanos.store(time.timestamp_nanos_opt().unwrap(), Ordering::Release);
+    }
+
+    pub fn advance(&self, duration: chrono::Duration) {
+        self.nanos.fetch_add(duration.num_nanoseconds().unwrap_or(0), Ordering::AcqRel);
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc.timestamp_nanos(self.nanos.load(Ordering::Acquire))
+    }
+}
 
 lazy_static! {
     static ref ATOMIC_TIMESTAMP_NS: AtomicI64 = AtomicI64::new(0);
+    static ref CLOCK_INJECTED: AtomicBool = AtomicBool::new(false);
+    static ref INJECTED_CLOCK: RwLock<Option<Arc<dyn Clock>>> = RwLock::new(None);
+}
+
+/// Replaces the engine time and the wall clock with `clock`, pass None to return to the engine time and the wall clock.
+pub fn set_clock(clock: Option<Arc<dyn Clock>>) {
+    let mut injected = INJECTED_CLOCK.write().unwrap();
+    CLOCK_INJECTED.store(clock.is_some(), Ordering::Release);
+    *injected = clock;
+}
+
+#[inline(always)]
+fn injected_time() -> Option<DateTime<Utc>> {
+    if !CLOCK_INJECTED.load(Ordering::Acquire) {
+        return None;
+    }
+    INJECTED_CLOCK.read().unwrap().as_ref().map(|clock| clock.now())
+}
+
+/// The current wall clock time, or the time of the injected clock.
+#[inline(always)]
+pub fn clock_now() -> DateTime<Utc> {
+    injected_time().unwrap_or_else(Utc::now)
 }
 
 #[inline(always)]
@@ -14,6 +90,9 @@ pub fn update_backtest_time(dt: DateTime<Utc>) {
 
 #[inline(always)]
 pub fn get_backtest_time() -> DateTime<Utc> {
+    if let Some(time) = injected_time() {
+        return time;
+    }
     let nanos = ATOMIC_TIMESTAMP_NS.load(Ordering::Acquire);
     Utc.timestamp_nanos(nanos)
 }
@@ -22,6 +101,79 @@ pub fn advance_engine_time(duration: chrono::Duration) {
     ATOMIC_TIMESTAMP_NS.fetch_add(duration.num_nanoseconds().unwrap_or(0), Ordering::AcqRel);
 }
 
+/// The warm up of one strategy, shared by its handlers.
+///
+/// # Properties
+/// * `complete` - The warm up is complete, the strategy is running on live data or the backtest range.
+/// * `time` - The time of the last data replayed by the live warm up.
+/// * `completion` - Sends the time the live warm up ended at to the live data receiver, which then processes the data it buffered after that time.
+#[derive(Clone)]
+pub(crate) struct WarmUpState {
+    complete: Arc<AtomicBool>,
+    time: Arc<AtomicI64>,
+    completion: broadcast::Sender<DateTime<Utc>>,
+}
+
+impl WarmUpState {
+    pub(crate) fn new() -> Self {
+        let (completion, _) = broadcast::channel(1);
+        WarmUpState {
+            complete: Arc::new(AtomicBool::new(false)),
+            time: Arc::new(AtomicI64::new(0)),
+            completion,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn is_complete(&self) -> bool {
+        self.complete.load(Ordering::SeqCst)
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_complete(&self) {
+        self.complete.store(true, Ordering::SeqCst);
+    }
+
+    /// Restarts the warm up, eg for the next regime of a backtest.
+    pub(crate) fn reset(&self) {
+        self.complete.store(false, Ordering::SeqCst);
+    }
+
+    #[inline(always)]
+    pub(crate) fn update_time(&self, time: DateTime<Utc>) {
+        self.time.store(time.timestamp_nanos_opt().unwrap(), Ordering::Release);
+    }
+
+    /// The time of the last data replayed by the live warm up, or the injected clock.
+    #[inline(always)]
+    pub(crate) fn time(&self) -> DateTime<Utc> {
+        if let Some(time) = injected_time() {
+            return time;
+        }
+        Utc.timestamp_nanos(self.time.load(Ordering::Acquire))
+    }
+
+    /// The strategy time, the engine time in backtests, the warm up replay time live until the warm up is complete, then the clock.
+    pub(crate) fn strategy_time(&self, mode: StrategyMode) -> DateTime<Utc> {
+        match mode {
+            StrategyMode::Backtest => get_backtest_time(),
+            StrategyMode::Live | StrategyMode::LivePaperTrading => match self.is_complete() {
+                true => clock_now(),
+                false => self.time(),
+            },
+        }
+    }
+
+    /// Ends the live warm up at `time`, the live data receiver sets the warm up complete once it has processed the data it buffered.
+    pub(crate) fn announce_complete(&self, time: DateTime<Utc>) {
+        let _ = self.completion.send(time);
+    }
+
+    pub(crate) fn subscribe_completion(&self) -> broadcast::Receiver<DateTime<Utc>> {
+        self.completion.subscribe()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -42,6 +194,37 @@ mod tests {
         assert_eq!(get_backtest_time(), initial_time + chrono::Duration::seconds(5));
     }
 
+    #[test]
+    fn test_simulated_clock() {
+        let start = Utc.with_ymd_and_hms(2024, 6, 3, 14, 30, 0).unwrap();
+        let clock = SimulatedClock::new(start);
+        assert_eq!(clock.now(), start);
+        clock.advance(chrono::Duration::minutes(5));
+        assert_eq!(clock.now(), start + chrono::Duration::minutes(5));
+        clock.set_time(start);
+        assert_eq!(clock.now(), start);
+    }
+
+    #[test]
+    fn test_warm_up_state() {
+        let start = Utc.with_ymd_and_hms(2024, 6, 3, 14, 30, 0).unwrap();
+        let first = WarmUpState::new();
+        let second = WarmUpState::new();
+        first.update_time(start);
+        second.update_time(start - chrono::Duration::days(1));
+        assert_eq!(first.strategy_time(StrategyMode::Live), start);
+        assert_eq!(second.strategy_time(StrategyMode::LivePaperTrading), start - chrono::Duration::days(1));
+
+        let mut completion = first.subscribe_completion();
+        first.announce_complete(start);
+        assert_eq!(completion.try_recv().unwrap(), start);
+        first.clone().set_complete();
+        assert!(first.is_complete());
+        assert!(!second.is_complete());
+        assert!(first.strategy_time(StrategyMode::Live) > start);
+        assert_eq!(second.strategy_time(StrategyMode::Live), start - chrono::Duration::days(1));
+    }
+
     #[test]
     fn test_concurrent_reads() {
         let test_time = Utc::now();
@@ -0,0 +1,213 @@
+use std::mem::size_of;
+use crate::standardized_types::base_data::candle::Candle;
+use crate::standardized_types::base_data::depth::Depth;
+use crate::standardized_types::base_data::fundamental::Fundamental;
+use crate::standardized_types::base_data::quote::Quote;
+use crate::standardized_types::base_data::quotebar::QuoteBar;
+use crate::standardized_types::base_data::tick::Tick;
+use crate::standardized_types::books::BookLevel;
+use crate::standardized_types::rolling_window::RollingWindow;
+use crate::standardized_types::subscriptions::DataSubscription;
+
+/// How the retention is reduced when the retained history is over budget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Shrinks the subscriptions using the most memory first, so small windows such as daily candles keep their full history.
+    LargestFirst,
+    /// Shrinks every subscription by the same fraction of its retention.
+    Proportional,
+}
+
+/// # Properties
+/// * `max_bytes` - The total bytes of retained history for all subscriptions.
+/// * `policy` - How the retention is reduced when over budget.
+/// * `min_retained` - Retention is never reduced below this number of items per subscription.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HistoryBudget {
+    pub max_bytes: usize,
+    pub policy: EvictionPolicy,
+    pub min_retained: usize,
+}
+
+impl HistoryBudget {
+    pub fn new(max_bytes: usize, policy: EvictionPolicy) -> Self {
+        HistoryBudget {
+            max_bytes,
+            policy,
+            min_retained: 1,
+        }
+    }
+
+    pub fn megabytes(megabytes: usize, policy: EvictionPolicy) -> Self {
+        HistoryBudget::new(megabytes * 1024 * 1024, policy)
+    }
+}
+
+/// The retained history of a subscription.
+///
+/// # Properties
+/// * `retained` - The number of items currently retained.
+/// * `max_retained` - The retention, this is reduced when the history is over budget.
+/// * `bytes` - The estimated bytes used, including capacity allocated for the retention but not yet filled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryMemoryUsage {
+    pub subscription: DataSubscription,
+    pub retained: usize,
+    pub max_retained: usize,
+    pub bytes: usize,
+}
+
+/// The estimated bytes owned by an item of retained history.
+pub trait RetainedSize {
+    /// Bytes owned on the heap, the fixed size of the item is added with `size_of`.
+    fn heap_bytes(&self) -> usize;
+}
+
+impl RetainedSize for Candle {
+    fn heap_bytes(&self) -> usize {
+        self.symbol.name.capacity() + self.time.capacity()
+    }
+}
+
+impl RetainedSize for QuoteBar {
+    fn heap_bytes(&self) -> usize {
+        self.symbol.name.capacity() + self.time.capacity()
+    }
+}
+
+impl RetainedSize for Tick {
+    fn heap_bytes(&self) -> usize {
+        self.symbol.name.capacity() + self.time.capacity()
+    }
+}
+
+impl RetainedSize for Quote {
+    fn heap_bytes(&self) -> usize {
+        self.symbol.name.capacity() + self.time.capacity()
+    }
+}
+
+impl RetainedSize for Fundamental {
+    fn heap_bytes(&self) -> usize {
+        let values: usize = self.values.keys().map(|key| key.capacity() + size_of::<String>() + 16).sum();
+        self.symbol.name.capacity()
+            + self.time.capacity()
+            + self.name.capacity()
+            + values
+            + self.value_string.as_ref().map_or(0, |value| value.capacity())
+            + self.value_bytes.as_ref().map_or(0, |value| value.capacity())
+    }
+}
+
+impl RetainedSize for Depth {
+    fn heap_bytes(&self) -> usize {
+        self.symbol.name.capacity()
+            + self.time.capacity()
+            + (self.bids.capacity() + self.asks.capacity()) * size_of::<BookLevel>()
+    }
+}
+
+/// Estimates the bytes used by a window from its allocated capacity and the heap bytes of its latest item.
+pub fn window_bytes<T: RetainedSize + Clone>(window: &RollingWindow<T>) -> usize {
+    let heap_bytes = window.last().map_or(0, |item| item.heap_bytes());
+    window.history.capacity() * size_of::<T>() + window.len() * heap_bytes
+}
+
+pub fn window_usage<T: RetainedSize + Clone>(subscription: &DataSubscription, window: &RollingWindow<T>) -> HistoryMemoryUsage {
+    HistoryMemoryUsage {
+        subscription: subscription.clone(),
+        retained: window.len(),
+        max_retained: window.number as usize,
+        bytes: window_bytes(window),
+    }
+}
+
+/// Returns the new retention for each subscription which needs to shrink to bring the total within the budget.
+pub fn plan_evictions(budget: &HistoryBudget, usage: &[HistoryMemoryUsage]) -> Vec<(DataSubscription, usize)> {
+    let total: usize = usage.iter().map(|usage| usage.bytes).sum();
+    if total <= budget.max_bytes {
+        return vec![];
+    }
+
+    let mut evictions = vec![];
+    match budget.policy {
+        EvictionPolicy::LargestFirst => {
+            let mut usage: Vec<&HistoryMemoryUsage> = usage.iter().collect();
+            usage.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+            let mut excess = total - budget.max_bytes;
+            for usage in usage {
+                if excess == 0 {
+                    break;
+                }
+                if usage.max_retained <= budget.min_retained {
+                    continue;
+                }
+                let item_bytes = (usage.bytes / usage.max_retained).max(1);
+                let removable = usage.max_retained - budget.min_retained;
+                let remove = excess.div_ceil(item_bytes).min(removable);
+                excess = excess.saturating_sub(remove * item_bytes);
+                evictions.push((usage.subscription.clone(), usage.max_retained - remove));
+            }
+        }
+        EvictionPolicy::Proportional => {
+            let factor = budget.max_bytes as f64 / total as f64;
+            for usage in usage {
+                let max_retained = ((usage.max_retained as f64 * factor) as usize).max(budget.min_retained);
+                if max_retained < usage.max_retained {
+                    evictions.push((usage.subscription.clone(), max_retained));
+                }
+            }
+        }
+    }
+    evictions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standardized_types::base_data::base_data_type::BaseDataType;
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::{FuturesExchange, MarketType};
+    use crate::standardized_types::resolution::Resolution;
+
+    fn usage(symbol: &str, max_retained: usize, bytes: usize) -> HistoryMemoryUsage {
+        let subscription = DataSubscription::new(symbol.to_string(), DataVendor::Rithmic, Resolution::Ticks(1), BaseDataType::Ticks, MarketType::Futures(FuturesExchange::CME));
+        HistoryMemoryUsage {
+            subscription,
+            retained: max_retained,
+            max_retained,
+            bytes,
+        }
+    }
+
+    #[test]
+    fn test_largest_first() {
+        let usage = vec![usage("NQ", 1000, 100_000), usage("ES", 100, 1_000)];
+        assert!(plan_evictions(&HistoryBudget::new(101_000, EvictionPolicy::LargestFirst), &usage).is_empty());
+
+        let evictions = plan_evictions(&HistoryBudget::new(51_000, EvictionPolicy::LargestFirst), &usage);
+        assert_eq!(evictions, vec![(usage[0].subscription.clone(), 500)]);
+
+        // the largest can only shrink to the minimum, then the next largest shrinks
+        let mut budget = HistoryBudget::new(500, EvictionPolicy::LargestFirst);
+        budget.min_retained = 10;
+        let evictions = plan_evictions(&budget, &usage);
+        assert_eq!(evictions, vec![(usage[0].subscription.clone(), 10), (usage[1].subscription.clone(), 10)]);
+    }
+
+    #[test]
+    fn test_proportional() {
+        let usage = vec![usage("NQ", 1000, 100_000), usage("ES", 100, 100_000)];
+        let evictions = plan_evictions(&HistoryBudget::new(100_000, EvictionPolicy::Proportional), &usage);
+        assert_eq!(evictions, vec![(usage[0].subscription.clone(), 500), (usage[1].subscription.clone(), 50)]);
+    }
+
+    #[test]
+    fn test_window_bytes() {
+        let mut window: RollingWindow<Tick> = RollingWindow::new(10);
+        assert_eq!(window_bytes(&window), 10 * size_of::<Tick>());
+        window.set_capacity(4);
+        assert_eq!(window.number, 4);
+        assert!(window.history.capacity() < 10);
+    }
+}
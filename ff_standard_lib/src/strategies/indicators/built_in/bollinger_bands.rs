@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 use std::fmt::{self, Display, Formatter};
-use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use crate::gui_types::settings::Color;
 use crate::helpers::decimal_calculators::round_to_tick_size;
@@ -13,6 +13,7 @@ use crate::standardized_types::rolling_window::RollingWindow;
 use crate::standardized_types::subscriptions::DataSubscription;
 use crate::strategies::indicators::indicator_values::{IndicatorPlot, IndicatorValues};
 use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
+use crate::strategies::indicators::running_window::RunningWindow;
 
 /// Bollinger Bands (BB)
 /// A volatility-based indicator consisting of three bands: a middle simple moving average (SMA)
@@ -141,7 +142,7 @@ use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators}
 ///
 /// # Performance Considerations
 /// - Updates efficiently with new data
-/// - Maintains minimal state (SMA calculation)
+/// - Keeps running totals of the closes, each update costs the same for any period
 /// - Handles tick rounding appropriately
 /// - Uses accurate standard deviation calculation
 #[derive(Clone, Debug)]
@@ -149,7 +150,7 @@ pub struct BollingerBands {
     name: IndicatorName,
     subscription: DataSubscription,
     history: RollingWindow<IndicatorValues>,
-    base_data_history: RollingWindow<BaseDataEnum>,
+    closes: RunningWindow,
     #[allow(unused)]
     market_type: MarketType,
     #[allow(unused)]
@@ -199,7 +200,7 @@ impl BollingerBands {
             market_type: subscription.symbol.market_type.clone(),
             subscription,
             history: RollingWindow::new(history_to_retain),
-            base_data_history: RollingWindow::new(period as usize),
+            closes: RunningWindow::with_variance(period as usize),
             is_ready: false,
             tick_size,
             middle_color,
@@ -222,25 +223,9 @@ impl BollingerBands {
     }
 
     fn calculate_bands(&self) -> Option<(Price, Price, Price)> {
-        let base_data = self.base_data_history.history();
-        if base_data.is_empty() {
-            return None;
-        }
-
-        // Calculate simple moving average (middle band)
-        let closes: Vec<Decimal> = base_data.iter()
-            .map(Self::get_close_price)
-            .collect();
-
-        let period_dec = Decimal::from(self.period);
-        let sma = closes.iter().sum::<Decimal>() / period_dec;
-
-        // Calculate standard deviation
-        let variance = closes.iter()
-            .map(|close| (*close - sma) * (*close - sma))
-            .sum::<Decimal>() / period_dec;
-
-        let std_dev = variance.sqrt().unwrap_or(dec!(0.0));
+        // Calculate simple moving average (middle band) and standard deviation from the running totals
+        let sma = self.closes.mean()?;
+        let std_dev = self.closes.std_dev().unwrap_or(dec!(0.0));
 
         // Calculate bands
         let middle = match self.tick_rounding {
@@ -262,36 +247,37 @@ impl BollingerBands {
     }
 
     pub fn get_bandwidth(&self) -> Option<Decimal> {
-        if let Some((upper, middle, lower)) = self.calculate_bands() {
-            if middle == dec!(0.0) {
-                return None;
-            }
+        let (upper, middle, lower) = self.calculate_bands()?;
+        self.bandwidth(upper, middle, lower)
+    }
 
-            let bandwidth = (upper - lower) / middle * dec!(100.0);
-            Some(match self.tick_rounding {
-                true => round_to_tick_size(bandwidth, self.tick_size),
-                false => bandwidth.round_dp(self.decimal_accuracy),
-            })
-        } else {
-            None
+    fn bandwidth(&self, upper: Price, middle: Price, lower: Price) -> Option<Decimal> {
+        if middle == dec!(0.0) {
+            return None;
         }
+        let bandwidth = (upper - lower) / middle * dec!(100.0);
+        Some(match self.tick_rounding {
+            true => round_to_tick_size(bandwidth, self.tick_size),
+            false => bandwidth.round_dp(self.decimal_accuracy),
+        })
     }
 
     pub fn get_percent_b(&self) -> Option<Decimal> {
-        if let Some((upper, _, lower)) = self.calculate_bands() {
-            let current_price = Self::get_close_price(self.base_data_history.history().last()?);
-            if upper == lower {
-                return Some(dec!(50.0));
-            }
+        let (upper, _, lower) = self.calculate_bands()?;
+        self.percent_b(upper, lower)
+    }
 
-            let percent_b = (current_price - lower) / (upper - lower) * dec!(100.0);
-            Some(match self.tick_rounding {
-                true => round_to_tick_size(percent_b, self.tick_size),
-                false => percent_b.round_dp(self.decimal_accuracy),
-            })
-        } else {
-            None
+    /// %B of the latest close.
+    fn percent_b(&self, upper: Price, lower: Price) -> Option<Decimal> {
+        let current_price = self.closes.last()?;
+        if upper == lower {
+            return Some(dec!(50.0));
         }
+        let percent_b = (current_price - lower) / (upper - lower) * dec!(100.0);
+        Some(match self.tick_rounding {
+            true => round_to_tick_size(percent_b, self.tick_size),
+            false => percent_b.round_dp(self.decimal_accuracy),
+        })
     }
 }
 
@@ -309,10 +295,10 @@ impl Indicators for BollingerBands {
             return None;
         }
 
-        self.base_data_history.add(base_data.clone());
+        self.closes.push(Self::get_close_price(base_data));
 
         if !self.is_ready {
-            if !self.base_data_history.is_full() {
+            if !self.closes.is_full() {
                 return None;
             }
             self.is_ready = true;
@@ -337,14 +323,14 @@ impl Indicators for BollingerBands {
         );
 
         // Optional: Add bandwidth and %B if needed
-        if let Some(bandwidth) = self.get_bandwidth() {
+        if let Some(bandwidth) = self.bandwidth(upper, middle, lower) {
             plots.insert(
                 "bandwidth".to_string(),
                 IndicatorPlot::new("bandwidth".to_string(), bandwidth, self.middle_color.clone()),
             );
         }
 
-        if let Some(percent_b) = self.get_percent_b() {
+        if let Some(percent_b) = self.percent_b(upper, lower) {
             plots.insert(
                 "percent_b".to_string(),
                 IndicatorPlot::new("%B".to_string(), percent_b, self.middle_color.clone()),
@@ -368,7 +354,7 @@ impl Indicators for BollingerBands {
 
     fn reset(&mut self) {
         self.history.clear();
-        self.base_data_history.clear();
+        self.closes.clear();
         self.is_ready = false;
     }
 
@@ -401,4 +387,8 @@ impl Indicators for BollingerBands {
     fn data_required_warmup(&self) -> u64 {
         self.history.len() as u64 + self.period
     }
+
+    fn is_incremental(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use rust_decimal::Decimal;
+use crate::gui_types::settings::Color;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::new_types::Volume;
+use crate::standardized_types::rolling_window::RollingWindow;
+use crate::standardized_types::subscriptions::DataSubscription;
+use crate::strategies::indicators::indicator_values::{IndicatorPlot, IndicatorValues};
+use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
+
+/// Order Book Imbalance
+/// The resting bid volume against the resting ask volume, from -1 (only asks) to 1 (only bids).
+///
+/// # Plots
+/// - "imbalance": (bid volume - ask volume) / (bid volume + ask volume)
+/// - "bid_volume": The resting bid volume on the measured levels
+/// - "ask_volume": The resting ask volume on the measured levels
+///
+/// # Parameters
+/// - levels: The number of book levels to measure, quotes only have the top of book
+///
+/// # Usage
+/// Works with depth subscriptions, or quotes for the top of book only. Snapshots with an empty book are ignored.
+/// Depth can be recorded live or downloaded from DataBento for backtests, see `Historical Depth` in the strategies readme.
+#[derive(Clone, Debug)]
+pub struct BookImbalance {
+    name: IndicatorName,
+    subscription: DataSubscription,
+    history: RollingWindow<IndicatorValues>,
+    is_ready: bool,
+    imbalance_color: Color,
+    bid_color: Color,
+    ask_color: Color,
+    levels: usize,
+}
+
+impl Display for BookImbalance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.history.last() {
+            Some(last) => write!(f, "{}\n{}", &self.name, last),
+            None => write!(f, "{}: No Values", &self.name),
+        }
+    }
+}
+
+impl BookImbalance {
+    #[allow(dead_code)]
+    pub async fn new(
+        name: IndicatorName,
+        subscription: DataSubscription,
+        history_to_retain: usize,
+        levels: usize,
+        imbalance_color: Color,
+        bid_color: Color,
+        ask_color: Color,
+    ) -> Box<Self> {
+        if subscription.base_data_type != BaseDataType::Depth && subscription.base_data_type != BaseDataType::Quotes {
+            panic!("Incorrect BaseDataType for BookImbalance Subscription")
+        }
+        Box::new(BookImbalance {
+            name,
+            subscription,
+            history: RollingWindow::new(history_to_retain),
+            is_ready: false,
+            imbalance_color,
+            bid_color,
+            ask_color,
+            levels: levels.max(1),
+        })
+    }
+
+    fn book_volume(&self, base_data: &BaseDataEnum) -> Option<(Volume, Volume)> {
+        match base_data {
+            BaseDataEnum::Depth(depth) => Some(depth.volume(self.levels)),
+            BaseDataEnum::Quote(quote) => Some((quote.bid_volume, quote.ask_volume)),
+            _ => None,
+        }
+    }
+}
+
+impl Indicators for BookImbalance {
+    fn name(&self) -> IndicatorName {
+        self.name.clone()
+    }
+
+    fn history_to_retain(&self) -> usize {
+        self.history.number.clone() as usize
+    }
+
+    fn update_base_data(&mut self, base_data: &BaseDataEnum) -> Option<Vec<IndicatorValues>> {
+        if base_data.subscription() != self.subscription {
+            return None;
+        }
+        let (bid_volume, ask_volume) = self.book_volume(base_data)?;
+        let total = bid_volume + ask_volume;
+        if total == Decimal::ZERO {
+            return None;
+        }
+        let imbalance = (bid_volume - ask_volume) / total;
+
+        let mut plots = BTreeMap::new();
+        plots.insert("imbalance".to_string(), IndicatorPlot::new("Imbalance".to_string(), imbalance, self.imbalance_color.clone()));
+        plots.insert("bid_volume".to_string(), IndicatorPlot::new("Bid Volume".to_string(), bid_volume, self.bid_color.clone()));
+        plots.insert("ask_volume".to_string(), IndicatorPlot::new("Ask Volume".to_string(), ask_volume, self.ask_color.clone()));
+
+        let values = IndicatorValues::new(self.name.clone(), self.subscription.clone(), plots, base_data.time_closed_utc());
+        self.history.add(values.clone());
+        self.is_ready = true;
+        Some(vec![values])
+    }
+
+    fn subscription(&self) -> &DataSubscription {
+        &self.subscription
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+        self.is_ready = false;
+    }
+
+    fn index(&self, index: usize) -> Option<IndicatorValues> {
+        if !self.is_ready {
+            return None;
+        }
+        self.history.get(index).cloned()
+    }
+
+    fn current(&self) -> Option<IndicatorValues> {
+        if !self.is_ready {
+            return None;
+        }
+        self.history.last().cloned()
+    }
+
+    fn plots(&self) -> RollingWindow<IndicatorValues> {
+        self.history.clone()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.is_ready
+    }
+
+    fn history(&self) -> RollingWindow<IndicatorValues> {
+        self.history.clone()
+    }
+
+    fn data_required_warmup(&self) -> u64 {
+        1
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use chrono::{DateTime, Datelike, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::gui_types::settings::Color;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::base_data::tick::Aggressor;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::market_hours::TradingHours;
+use crate::standardized_types::new_types::Volume;
+use crate::standardized_types::rolling_window::RollingWindow;
+use crate::standardized_types::subscriptions::DataSubscription;
+use crate::strategies::indicators::indicator_values::{IndicatorPlot, IndicatorValues};
+use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
+
+/// Returns the buy and sell aggressor volume of a tick or closed candle, candles built from ticks count buy aggressors as `bid_volume`.
+pub(crate) fn aggressor_volume(base_data: &BaseDataEnum) -> Option<(Volume, Volume)> {
+    match base_data {
+        BaseDataEnum::Tick(tick) => match tick.aggressor {
+            Aggressor::Buy => Some((tick.volume, dec!(0))),
+            Aggressor::Sell => Some((dec!(0), tick.volume)),
+            Aggressor::None => None,
+        },
+        BaseDataEnum::Candle(candle) if candle.is_closed => Some((candle.bid_volume, candle.ask_volume)),
+        _ => None,
+    }
+}
+
+/// Cumulative Volume Delta (CVD)
+/// The running total of buy aggressor volume minus sell aggressor volume.
+///
+/// # Plots
+/// - "delta": The delta of the tick or bar
+/// - "cumulative_delta": The running total of delta since the last reset
+///
+/// # Parameters
+/// - trading_hours: Resets the total at the start of each session, or never if `None`
+///
+/// # Usage
+/// Works with tick subscriptions, or candles consolidated from ticks. Ticks with no aggressor are ignored.
+/// Rising price with falling delta shows the move is not supported by aggressive buyers, see `DeltaDivergence`.
+#[derive(Clone, Debug)]
+pub struct CumulativeDelta {
+    name: IndicatorName,
+    subscription: DataSubscription,
+    history: RollingWindow<IndicatorValues>,
+    is_ready: bool,
+    delta_color: Color,
+    cumulative_color: Color,
+    cumulative_delta: Decimal,
+    trading_hours: Option<TradingHours>,
+    last_reset_day: Option<u32>,
+    was_in_session: bool,
+}
+
+impl Display for CumulativeDelta {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.history.last() {
+            Some(last) => write!(f, "{}\n{}", &self.name, last),
+            None => write!(f, "{}: No Values", &self.name),
+        }
+    }
+}
+
+impl CumulativeDelta {
+    #[allow(dead_code)]
+    pub async fn new(
+        name: IndicatorName,
+        subscription: DataSubscription,
+        history_to_retain: usize,
+        delta_color: Color,
+        cumulative_color: Color,
+        trading_hours: Option<TradingHours>,
+    ) -> Box<Self> {
+        if subscription.base_data_type != BaseDataType::Ticks && subscription.base_data_type != BaseDataType::Candles {
+            panic!("Incorrect BaseDataType for CumulativeDelta Subscription")
+        }
+        Box::new(CumulativeDelta {
+            name,
+            subscription,
+            history: RollingWindow::new(history_to_retain),
+            is_ready: false,
+            delta_color,
+            cumulative_color,
+            cumulative_delta: dec!(0),
+            trading_hours,
+            last_reset_day: None,
+            was_in_session: false,
+        })
+    }
+
+    /// Resets the total on the first data of each session, the same way as the VWAP.
+    fn should_reset(&mut self, time: DateTime<Utc>) -> bool {
+        let trading_hours = match &self.trading_hours {
+            Some(trading_hours) => trading_hours,
+            None => return false,
+        };
+        if !trading_hours.is_market_open(time) {
+            self.was_in_session = false;
+            return false;
+        }
+        let day = time.with_timezone(&trading_hours.timezone).day();
+        let reset = !self.was_in_session || self.last_reset_day != Some(day);
+        self.was_in_session = true;
+        if reset {
+            self.last_reset_day = Some(day);
+        }
+        reset
+    }
+}
+
+impl Indicators for CumulativeDelta {
+    fn name(&self) -> IndicatorName {
+        self.name.clone()
+    }
+
+    fn history_to_retain(&self) -> usize {
+        self.history.number.clone() as usize
+    }
+
+    fn update_base_data(&mut self, base_data: &BaseDataEnum) -> Option<Vec<IndicatorValues>> {
+        if base_data.subscription() != self.subscription {
+            return None;
+        }
+        let (buy_volume, sell_volume) = aggressor_volume(base_data)?;
+        let time = base_data.time_closed_utc();
+        if self.should_reset(time) {
+            self.cumulative_delta = dec!(0);
+        }
+
+        let delta = buy_volume - sell_volume;
+        self.cumulative_delta += delta;
+
+        let mut plots = BTreeMap::new();
+        plots.insert("delta".to_string(), IndicatorPlot::new("Delta".to_string(), delta, self.delta_color.clone()));
+        plots.insert("cumulative_delta".to_string(), IndicatorPlot::new("Cumulative Delta".to_string(), self.cumulative_delta, self.cumulative_color.clone()));
+
+        let values = IndicatorValues::new(self.name.clone(), self.subscription.clone(), plots, time);
+        self.history.add(values.clone());
+        self.is_ready = true;
+        Some(vec![values])
+    }
+
+    fn subscription(&self) -> &DataSubscription {
+        &self.subscription
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+        self.is_ready = false;
+        self.cumulative_delta = dec!(0);
+        self.last_reset_day = None;
+        self.was_in_session = false;
+    }
+
+    fn index(&self, index: usize) -> Option<IndicatorValues> {
+        if !self.is_ready {
+            return None;
+        }
+        self.history.get(index).cloned()
+    }
+
+    fn current(&self) -> Option<IndicatorValues> {
+        if !self.is_ready {
+            return None;
+        }
+        self.history.last().cloned()
+    }
+
+    fn plots(&self) -> RollingWindow<IndicatorValues> {
+        self.history.clone()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.is_ready
+    }
+
+    fn history(&self) -> RollingWindow<IndicatorValues> {
+        self.history.clone()
+    }
+
+    fn data_required_warmup(&self) -> u64 {
+        1
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{self, Display, Formatter};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::gui_types::settings::Color;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::rolling_window::RollingWindow;
+use crate::standardized_types::subscriptions::DataSubscription;
+use crate::strategies::indicators::built_in::cumulative_delta::aggressor_volume;
+use crate::strategies::indicators::indicator_values::{IndicatorPlot, IndicatorValues};
+use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
+
+/// Delta Divergence
+/// Compares the change in price with the change in cumulative volume delta over the period.
+///
+/// # Plots
+/// - "price_change": The change in price over the period
+/// - "delta_change": The change in cumulative delta over the period
+/// - "divergence": 1 when price falls while delta rises (bullish), -1 when price rises while delta falls (bearish), otherwise 0
+///
+/// # Parameters
+/// - period: The number of ticks or bars to compare
+///
+/// # Usage
+/// Works with tick subscriptions, or candles consolidated from ticks. Ticks with no aggressor are ignored.
+/// A divergence shows aggressive orders being absorbed by passive liquidity, the move may be exhausting.
+#[derive(Clone, Debug)]
+pub struct DeltaDivergence {
+    name: IndicatorName,
+    subscription: DataSubscription,
+    history: RollingWindow<IndicatorValues>,
+    is_ready: bool,
+    price_color: Color,
+    delta_color: Color,
+    divergence_color: Color,
+    period: usize,
+    cumulative_delta: Decimal,
+    base_data_history: VecDeque<(Price, Decimal)>,
+}
+
+impl Display for DeltaDivergence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.history.last() {
+            Some(last) => write!(f, "{}\n{}", &self.name, last),
+            None => write!(f, "{}: No Values", &self.name),
+        }
+    }
+}
+
+impl DeltaDivergence {
+    #[allow(dead_code)]
+    pub async fn new(
+        name: IndicatorName,
+        subscription: DataSubscription,
+        history_to_retain: usize,
+        period: usize,
+        price_color: Color,
+        delta_color: Color,
+        divergence_color: Color,
+    ) -> Box<Self> {
+        if subscription.base_data_type != BaseDataType::Ticks && subscription.base_data_type != BaseDataType::Candles {
+            panic!("Incorrect BaseDataType for DeltaDivergence Subscription")
+        }
+        let period = period.max(1);
+        Box::new(DeltaDivergence {
+            name,
+            subscription,
+            history: RollingWindow::new(history_to_retain),
+            is_ready: false,
+            price_color,
+            delta_color,
+            divergence_color,
+            period,
+            cumulative_delta: dec!(0),
+            base_data_history: VecDeque::with_capacity(period + 1),
+        })
+    }
+
+    fn get_price(base_data: &BaseDataEnum) -> Option<Price> {
+        match base_data {
+            BaseDataEnum::Tick(tick) => Some(tick.price),
+            BaseDataEnum::Candle(candle) => Some(candle.close),
+            _ => None,
+        }
+    }
+}
+
+impl Indicators for DeltaDivergence {
+    fn name(&self) -> IndicatorName {
+        self.name.clone()
+    }
+
+    fn history_to_retain(&self) -> usize {
+        self.history.number.clone() as usize
+    }
+
+    fn update_base_data(&mut self, base_data: &BaseDataEnum) -> Option<Vec<IndicatorValues>> {
+        if base_data.subscription() != self.subscription {
+            return None;
+        }
+        let (buy_volume, sell_volume) = aggressor_volume(base_data)?;
+        let price = Self::get_price(base_data)?;
+        self.cumulative_delta += buy_volume - sell_volume;
+
+        self.base_data_history.push_back((price, self.cumulative_delta));
+        if self.base_data_history.len() > self.period + 1 {
+            self.base_data_history.pop_front();
+        }
+        if self.base_data_history.len() <= self.period {
+            return None;
+        }
+
+        let (first_price, first_delta) = *self.base_data_history.front()?;
+        let price_change = price - first_price;
+        let delta_change = self.cumulative_delta - first_delta;
+        let divergence = if price_change < dec!(0) && delta_change > dec!(0) {
+            dec!(1)
+        } else if price_change > dec!(0) && delta_change < dec!(0) {
+            dec!(-1)
+        } else {
+            dec!(0)
+        };
+
+        let mut plots = BTreeMap::new();
+        plots.insert("price_change".to_string(), IndicatorPlot::new("Price Change".to_string(), price_change, self.price_color.clone()));
+        plots.insert("delta_change".to_string(), IndicatorPlot::new("Delta Change".to_string(), delta_change, self.delta_color.clone()));
+        plots.insert("divergence".to_string(), IndicatorPlot::new("Divergence".to_string(), divergence, self.divergence_color.clone()));
+
+        let values = IndicatorValues::new(self.name.clone(), self.subscription.clone(), plots, base_data.time_closed_utc());
+        self.history.add(values.clone());
+        self.is_ready = true;
+        Some(vec![values])
+    }
+
+    fn subscription(&self) -> &DataSubscription {
+        &self.subscription
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+        self.base_data_history.clear();
+        self.cumulative_delta = dec!(0);
+        self.is_ready = false;
+    }
+
+    fn index(&self, index: usize) -> Option<IndicatorValues> {
+        if !self.is_ready {
+            return None;
+        }
+        self.history.get(index).cloned()
+    }
+
+    fn current(&self) -> Option<IndicatorValues> {
+        if !self.is_ready {
+            return None;
+        }
+        self.history.last().cloned()
+    }
+
+    fn plots(&self) -> RollingWindow<IndicatorValues> {
+        self.history.clone()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.is_ready
+    }
+
+    fn history(&self) -> RollingWindow<IndicatorValues> {
+        self.history.clone()
+    }
+
+    fn data_required_warmup(&self) -> u64 {
+        self.period as u64 + 1
+    }
+}
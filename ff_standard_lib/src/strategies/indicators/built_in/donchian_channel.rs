@@ -13,6 +13,7 @@ use crate::standardized_types::rolling_window::RollingWindow;
 use crate::standardized_types::subscriptions::DataSubscription;
 use crate::strategies::indicators::indicator_values::{IndicatorPlot, IndicatorValues};
 use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
+use crate::strategies::indicators::running_window::RunningExtremes;
 
 /// Donchian Channels
 /// Shows highest high and lowest low over N periods.
@@ -51,7 +52,7 @@ pub struct DonchianChannels {
     name: IndicatorName,
     subscription: DataSubscription,
     history: RollingWindow<IndicatorValues>,
-    base_data_history: RollingWindow<BaseDataEnum>,
+    extremes: RunningExtremes,
     #[allow(unused)]
     market_type: MarketType,
     #[allow(unused)]
@@ -101,7 +102,7 @@ impl DonchianChannels {
             market_type: subscription.symbol.market_type.clone(),
             subscription,
             history: RollingWindow::new(history_to_retain),
-            base_data_history: RollingWindow::new(period as usize),
+            extremes: RunningExtremes::new(period as usize),
             is_ready: false,
             tick_size,
             upper_color,
@@ -158,18 +159,8 @@ impl DonchianChannels {
     }
 
     fn calculate_channels(&self) -> (Price, Price, Price) {
-        let history = self.base_data_history.history();
-
-        let mut highest_high = dec!(-999999.0);
-        let mut lowest_low = dec!(999999.0);
-
-        // Find highest high and lowest low over the period
-        for data in history.iter() {
-            if let Some((high, low)) = Self::get_price_data(data) {
-                highest_high = highest_high.max(high);
-                lowest_low = lowest_low.min(low);
-            }
-        }
+        let highest_high = self.extremes.highest().unwrap_or_default();
+        let lowest_low = self.extremes.lowest().unwrap_or_default();
 
         let middle = (highest_high + lowest_low) / dec!(2.0);
 
@@ -205,10 +196,11 @@ impl Indicators for DonchianChannels {
             return None;
         }
 
-        self.base_data_history.add(base_data.clone());
+        let (high, low) = Self::get_price_data(base_data)?;
+        self.extremes.push(high, low);
 
         if !self.is_ready {
-            if !self.base_data_history.is_full() {
+            if !self.extremes.is_full() {
                 return None;
             }
             self.is_ready = true;
@@ -265,7 +257,7 @@ impl Indicators for DonchianChannels {
 
     fn reset(&mut self) {
         self.history.clear();
-        self.base_data_history.clear();
+        self.extremes.clear();
         self.is_ready = false;
         self.breakout_level = None;
         self.last_trend = None;
@@ -300,4 +292,8 @@ impl Indicators for DonchianChannels {
     fn data_required_warmup(&self) -> u64 {
         self.period
     }
+
+    fn is_incremental(&self) -> bool {
+        true
+    }
 }
@@ -12,6 +12,7 @@ use crate::standardized_types::enums::MarketType;
 use crate::standardized_types::new_types::Price;
 use crate::standardized_types::rolling_window::RollingWindow;
 use crate::standardized_types::subscriptions::DataSubscription;
+use crate::strategies::indicators::indicator_state::WindowedState;
 use crate::strategies::indicators::indicator_values::{IndicatorPlot, IndicatorValues};
 use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
 
@@ -173,10 +174,9 @@ impl Indicators for ExponentialMovingAverage {
             return None;
         }
 
-        self.base_data_history.add(base_data.clone());
-
-        // Initialize EMA with SMA if we're just starting
+        // Initialize EMA with SMA if we're just starting, once ready the EMA only needs its last value
         if !self.is_ready {
+            self.base_data_history.add(base_data.clone());
             if !self.base_data_history.is_full() {
                 return None;
             }
@@ -257,4 +257,27 @@ impl Indicators for ExponentialMovingAverage {
     fn data_required_warmup(&self) -> u64 {
         self.history.len() as u64 + self.period
     }
+
+    fn is_incremental(&self) -> bool {
+        true
+    }
+
+    fn save_state(&self) -> Option<Vec<u8>> {
+        WindowedState::new(&self.history, &self.base_data_history, self.is_ready, self.last_ema).to_bytes()
+    }
+
+    fn restore_state(&mut self, state: &[u8]) -> bool {
+        match WindowedState::from_bytes(state) {
+            Some(state) => {
+                if !state.is_ready || state.last_value.is_none() || state.base_data_history.len() != self.period as usize {
+                    return false;
+                }
+                self.is_ready = state.is_ready;
+                self.last_ema = state.last_value;
+                state.restore_windows(&mut self.history, &mut self.base_data_history);
+                true
+            }
+            None => false,
+        }
+    }
 }
\ No newline at end of file
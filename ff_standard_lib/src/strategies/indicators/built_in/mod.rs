@@ -34,3 +34,10 @@ pub mod fibonacci_retracement;
 pub mod volume_profile_distribution;
 pub mod donchian_momentum_indicator;
 pub mod market_exhaustion;
+pub mod cumulative_delta;
+pub mod book_imbalance;
+pub mod delta_divergence;
+pub mod zscore;
+pub mod range_percentile_rank;
+pub mod rolling_correlation;
+pub mod pair_spread;
@@ -1,7 +1,6 @@
 use std::collections::BTreeMap;
 use std::fmt::{self, Display, Formatter};
 use rust_decimal::Decimal;
-use rust_decimal::prelude::FromPrimitive;
 use rust_decimal_macros::dec;
 use crate::gui_types::settings::Color;
 use crate::helpers::decimal_calculators::round_to_tick_size;
@@ -12,8 +11,10 @@ use crate::standardized_types::enums::MarketType;
 use crate::standardized_types::new_types::Price;
 use crate::standardized_types::rolling_window::RollingWindow;
 use crate::standardized_types::subscriptions::DataSubscription;
+use crate::strategies::indicators::indicator_state::WindowedState;
 use crate::strategies::indicators::indicator_values::{IndicatorPlot, IndicatorValues};
 use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
+use crate::strategies::indicators::running_window::RunningWindow;
 
 /// Moving Average (MA)
 /// A trend-following indicator that smooths price data to create a single flowing line.
@@ -45,7 +46,7 @@ pub struct MovingAverage {
     name: IndicatorName,
     subscription: DataSubscription,
     history: RollingWindow<IndicatorValues>,
-    base_data_history: RollingWindow<BaseDataEnum>,
+    closes: RunningWindow,
     #[allow(unused)]
     market_type: MarketType,
     #[allow(unused)]
@@ -89,7 +90,7 @@ impl MovingAverage {
             market_type: subscription.symbol.market_type.clone(),
             subscription,
             history: RollingWindow::new(history_to_retain),
-            base_data_history: RollingWindow::new(period as usize),
+            closes: RunningWindow::new(period as usize),
             is_ready: false,
             tick_size,
             plot_color,
@@ -100,35 +101,27 @@ impl MovingAverage {
         Box::new(ma)
     }
 
-    fn calculate_average(&self) -> Price {
-        let base_data = self.base_data_history.history();
-        let values: Vec<Decimal> = base_data.iter()
-            .map(|data| match data {
-                BaseDataEnum::QuoteBar(bar) => bar.bid_close,
-                BaseDataEnum::Candle(candle) => candle.close,
-                _ => panic!("Unsupported data type for MovingAverage"),
-            })
-            .collect();
-
-        if values.is_empty() {
-            return dec!(0.0);
+    fn get_close_price(data: &BaseDataEnum) -> Price {
+        match data {
+            BaseDataEnum::QuoteBar(bar) => bar.bid_close,
+            BaseDataEnum::Candle(candle) => candle.close,
+            _ => panic!("Unsupported data type for MovingAverage"),
         }
+    }
 
-        let sum: Decimal = values.iter().sum();
-        if sum == dec!(0.0) {
+    fn calculate_average(&self) -> Price {
+        if self.closes.sum() == dec!(0.0) {
             return dec!(0.0);
         }
-
-        let average = match self.tick_rounding {
-            true => round_to_tick_size(
-                sum / Decimal::from_u64(values.len() as u64).unwrap(),
-                self.tick_size
-            ),
-            false => (sum / Decimal::from_u64(values.len() as u64).unwrap())
-                .round_dp(self.decimal_accuracy),
+        let average = match self.closes.mean() {
+            Some(average) => average,
+            None => return dec!(0.0),
         };
 
-        average
+        match self.tick_rounding {
+            true => round_to_tick_size(average, self.tick_size),
+            false => average.round_dp(self.decimal_accuracy),
+        }
     }
 }
 
@@ -146,9 +139,9 @@ impl Indicators for MovingAverage {
             return None;
         }
 
-        self.base_data_history.add(base_data.clone());
+        self.closes.push(Self::get_close_price(base_data));
         if !self.is_ready {
-            if !self.base_data_history.is_full() {
+            if !self.closes.is_full() {
                 return None;
             } else {
                 self.is_ready = true;
@@ -184,7 +177,7 @@ impl Indicators for MovingAverage {
 
     fn reset(&mut self) {
         self.history.clear();
-        self.base_data_history.clear();
+        self.closes.clear();
         self.is_ready = false;
     }
 
@@ -217,4 +210,29 @@ impl Indicators for MovingAverage {
     fn data_required_warmup(&self) -> u64 {
         self.history.len() as u64 + self.period
     }
+
+    fn is_incremental(&self) -> bool {
+        true
+    }
+
+    fn save_state(&self) -> Option<Vec<u8>> {
+        WindowedState::from_values(&self.history, self.closes.values(), self.is_ready, None).to_bytes()
+    }
+
+    fn restore_state(&mut self, state: &[u8]) -> bool {
+        match WindowedState::from_bytes(state) {
+            Some(state) => {
+                if !state.is_ready || state.values.len() != self.period as usize {
+                    return false;
+                }
+                self.is_ready = state.is_ready;
+                self.closes.clear();
+                for close in state.restore_values(&mut self.history) {
+                    self.closes.push(close);
+                }
+                true
+            }
+            None => false,
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,232 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{self, Display, Formatter};
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+use crate::gui_types::settings::Color;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::rolling_window::RollingWindow;
+use crate::standardized_types::subscriptions::DataSubscription;
+use crate::strategies::indicators::built_in::rolling_correlation::PairedCloses;
+use crate::strategies::indicators::indicator_values::{IndicatorPlot, IndicatorValues};
+use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
+
+/// Pair Spread
+/// The hedge ratio weighted spread of two subscriptions and its z-score, for pairs strategies.
+///
+/// # Calculation Method
+/// 1. The closes of the subscriptions are paired by close time, a close with no match in the other subscription is ignored
+/// 2. Hedge Ratio = the fixed ratio, or the least squares slope of the first closes on the second closes over the period
+/// 3. Spread = first close - hedge ratio × second close
+/// 4. Z-Score = (spread - mean spread) / standard deviation of the spread, over the period at the current hedge ratio
+///
+/// # Plots
+/// - "spread": The current spread
+/// - "zscore": The z-score of the spread, 0 when the spread did not move
+/// - "hedge_ratio": The hedge ratio used for the spread
+///
+/// # Parameters
+/// - second_subscription: The subscription hedged against `subscription`, it should have the same resolution
+/// - period: Number of paired closes for the hedge ratio and z-score
+/// - hedge_ratio: A fixed hedge ratio, or None to estimate it over the period
+///
+/// # Usage
+/// The strategy must be subscribed to both subscriptions, the values are reported for `subscription`.
+/// A long spread is long the first symbol and short the hedge ratio of the second, see `strategy.enter_pair_long()`.
+/// Mean reversion pairs usually enter when the z-score is beyond ±2 and exit near 0.
+#[derive(Clone, Debug)]
+pub struct PairSpread {
+    name: IndicatorName,
+    subscription: DataSubscription,
+    second_subscription: DataSubscription,
+    history: RollingWindow<IndicatorValues>,
+    is_ready: bool,
+    spread_color: Color,
+    zscore_color: Color,
+    period: usize,
+    hedge_ratio: Option<Decimal>,
+    closes: PairedCloses,
+    paired_closes: VecDeque<(Price, Price)>,
+}
+
+impl Display for PairSpread {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.history.last() {
+            Some(last) => write!(f, "{}\n{}", &self.name, last),
+            None => write!(f, "{}: No Values", &self.name),
+        }
+    }
+}
+
+impl PairSpread {
+    #[allow(dead_code)]
+    pub async fn new(
+        name: IndicatorName,
+        subscription: DataSubscription,
+        second_subscription: DataSubscription,
+        history_to_retain: usize,
+        period: usize,
+        hedge_ratio: Option<Decimal>,
+        spread_color: Color,
+        zscore_color: Color,
+    ) -> Box<Self> {
+        if subscription == second_subscription {
+            panic!("PairSpread needs two different subscriptions")
+        }
+        let period = period.max(2);
+        Box::new(PairSpread {
+            name,
+            subscription,
+            second_subscription,
+            history: RollingWindow::new(history_to_retain),
+            is_ready: false,
+            spread_color,
+            zscore_color,
+            period,
+            hedge_ratio,
+            closes: PairedCloses::new(period * 2),
+            paired_closes: VecDeque::with_capacity(period),
+        })
+    }
+
+    fn get_close_price(base_data: &BaseDataEnum) -> Option<Price> {
+        match base_data {
+            BaseDataEnum::QuoteBar(bar) => Some(bar.bid_close),
+            BaseDataEnum::Candle(candle) => Some(candle.close),
+            BaseDataEnum::Tick(tick) => Some(tick.price),
+            _ => None,
+        }
+    }
+
+    /// The least squares slope of the first closes on the second closes, None if the second symbol did not move.
+    fn estimate_hedge_ratio(&self) -> Option<Decimal> {
+        let count = Decimal::from(self.paired_closes.len());
+        let mean_first = self.paired_closes.iter().map(|(first, _)| *first).sum::<Decimal>() / count;
+        let mean_second = self.paired_closes.iter().map(|(_, second)| *second).sum::<Decimal>() / count;
+        let mut covariance = dec!(0);
+        let mut variance_second = dec!(0);
+        for (first, second) in &self.paired_closes {
+            covariance += (*first - mean_first) * (*second - mean_second);
+            variance_second += (*second - mean_second) * (*second - mean_second);
+        }
+        match variance_second == dec!(0) {
+            true => None,
+            false => Some(covariance / variance_second),
+        }
+    }
+
+    /// Returns the spread, the z-score and the hedge ratio of the last paired close.
+    fn calculate(&self) -> Option<(Price, Decimal, Decimal)> {
+        let hedge_ratio = match self.hedge_ratio {
+            Some(hedge_ratio) => hedge_ratio,
+            None => self.estimate_hedge_ratio()?,
+        };
+        let spreads: Vec<Price> = self.paired_closes.iter()
+            .map(|(first, second)| *first - hedge_ratio * *second)
+            .collect();
+        let spread = *spreads.last()?;
+        let count = Decimal::from(spreads.len());
+        let mean = spreads.iter().sum::<Decimal>() / count;
+        let variance = spreads.iter()
+            .map(|spread| (*spread - mean) * (*spread - mean))
+            .sum::<Decimal>() / count;
+        let std_dev = variance.sqrt().unwrap_or(dec!(0));
+        let zscore = match std_dev == dec!(0) {
+            true => dec!(0),
+            false => (spread - mean) / std_dev,
+        };
+        Some((spread, zscore, hedge_ratio))
+    }
+}
+
+impl Indicators for PairSpread {
+    fn name(&self) -> IndicatorName {
+        self.name.clone()
+    }
+
+    fn history_to_retain(&self) -> usize {
+        self.history.number.clone() as usize
+    }
+
+    fn update_base_data(&mut self, base_data: &BaseDataEnum) -> Option<Vec<IndicatorValues>> {
+        if !base_data.is_closed() {
+            return None;
+        }
+        let subscription = base_data.subscription();
+        let is_first = if subscription == self.subscription {
+            true
+        } else if subscription == self.second_subscription {
+            false
+        } else {
+            return None;
+        };
+        let close = Self::get_close_price(base_data)?;
+        let time = base_data.time_closed_utc();
+        let paired = self.closes.pair(time, close, is_first)?;
+
+        self.paired_closes.push_back(paired);
+        if self.paired_closes.len() > self.period {
+            self.paired_closes.pop_front();
+        }
+        if self.paired_closes.len() < self.period {
+            return None;
+        }
+
+        let (spread, zscore, hedge_ratio) = self.calculate()?;
+        let mut plots = BTreeMap::new();
+        plots.insert("spread".to_string(), IndicatorPlot::new("Spread".to_string(), spread.round_dp(6), self.spread_color.clone()));
+        plots.insert("zscore".to_string(), IndicatorPlot::new("Z-Score".to_string(), zscore.round_dp(4), self.zscore_color.clone()));
+        plots.insert("hedge_ratio".to_string(), IndicatorPlot::new("Hedge Ratio".to_string(), hedge_ratio.round_dp(6), self.spread_color.clone()));
+
+        let values = IndicatorValues::new(self.name.clone(), self.subscription.clone(), plots, time);
+        self.history.add(values.clone());
+        self.is_ready = true;
+        Some(vec![values])
+    }
+
+    fn subscription(&self) -> &DataSubscription {
+        &self.subscription
+    }
+
+    fn secondary_subscriptions(&self) -> Vec<DataSubscription> {
+        vec![self.second_subscription.clone()]
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+        self.closes.clear();
+        self.paired_closes.clear();
+        self.is_ready = false;
+    }
+
+    fn index(&self, index: usize) -> Option<IndicatorValues> {
+        if !self.is_ready {
+            return None;
+        }
+        self.history.get(index).cloned()
+    }
+
+    fn current(&self) -> Option<IndicatorValues> {
+        if !self.is_ready {
+            return None;
+        }
+        self.history.last().cloned()
+    }
+
+    fn plots(&self) -> RollingWindow<IndicatorValues> {
+        self.history.clone()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.is_ready
+    }
+
+    fn history(&self) -> RollingWindow<IndicatorValues> {
+        self.history.clone()
+    }
+
+    fn data_required_warmup(&self) -> u64 {
+        self.history.len() as u64 + self.period as u64
+    }
+}
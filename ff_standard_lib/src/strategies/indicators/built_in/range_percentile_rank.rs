@@ -0,0 +1,161 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{self, Display, Formatter};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::gui_types::settings::Color;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::rolling_window::RollingWindow;
+use crate::standardized_types::subscriptions::DataSubscription;
+use crate::strategies::indicators::indicator_values::{IndicatorPlot, IndicatorValues};
+use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
+
+/// Range Percentile Rank
+/// Ranks the range (high - low) of the current bar against the ranges of the previous bars.
+///
+/// # Calculation Method
+/// Percentile Rank = the number of the previous `period` ranges smaller than the current range / period × 100
+///
+/// # Plots
+/// - "range": The range of the current bar
+/// - "percentile_rank": 0 when the bar is the narrowest of the period, 100 when it is the widest
+///
+/// # Parameters
+/// - period: Number of previous bars to rank against
+///
+/// # Usage
+/// Low ranks mark compression before a breakout (eg NR7 with a period of 6), high ranks mark expansion or climactic bars.
+/// Works with candles and quote bars (bid range).
+#[derive(Clone, Debug)]
+pub struct RangePercentileRank {
+    name: IndicatorName,
+    subscription: DataSubscription,
+    history: RollingWindow<IndicatorValues>,
+    is_ready: bool,
+    range_color: Color,
+    rank_color: Color,
+    period: usize,
+    ranges: VecDeque<Price>,
+}
+
+impl Display for RangePercentileRank {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.history.last() {
+            Some(last) => write!(f, "{}\n{}", &self.name, last),
+            None => write!(f, "{}: No Values", &self.name),
+        }
+    }
+}
+
+impl RangePercentileRank {
+    #[allow(dead_code)]
+    pub async fn new(
+        name: IndicatorName,
+        subscription: DataSubscription,
+        history_to_retain: usize,
+        period: usize,
+        range_color: Color,
+        rank_color: Color,
+    ) -> Box<Self> {
+        let period = period.max(1);
+        Box::new(RangePercentileRank {
+            name,
+            subscription,
+            history: RollingWindow::new(history_to_retain),
+            is_ready: false,
+            range_color,
+            rank_color,
+            period,
+            ranges: VecDeque::with_capacity(period + 1),
+        })
+    }
+
+    fn get_range(base_data: &BaseDataEnum) -> Option<Price> {
+        match base_data {
+            BaseDataEnum::QuoteBar(bar) => Some(bar.bid_high - bar.bid_low),
+            BaseDataEnum::Candle(candle) => Some(candle.high - candle.low),
+            _ => None,
+        }
+    }
+}
+
+impl Indicators for RangePercentileRank {
+    fn name(&self) -> IndicatorName {
+        self.name.clone()
+    }
+
+    fn history_to_retain(&self) -> usize {
+        self.history.number.clone() as usize
+    }
+
+    fn update_base_data(&mut self, base_data: &BaseDataEnum) -> Option<Vec<IndicatorValues>> {
+        if !base_data.is_closed() {
+            return None;
+        }
+        let range = Self::get_range(base_data)?;
+        self.ranges.push_back(range);
+        if self.ranges.len() > self.period + 1 {
+            self.ranges.pop_front();
+        }
+        if self.ranges.len() <= self.period {
+            return None;
+        }
+
+        // the last range is the current bar
+        let smaller = self.ranges.iter()
+            .take(self.period)
+            .filter(|previous| **previous < range)
+            .count();
+        let rank = Decimal::from(smaller) / Decimal::from(self.period) * dec!(100);
+
+        let mut plots = BTreeMap::new();
+        plots.insert("range".to_string(), IndicatorPlot::new("Range".to_string(), range, self.range_color.clone()));
+        plots.insert("percentile_rank".to_string(), IndicatorPlot::new("Percentile Rank".to_string(), rank.round_dp(2), self.rank_color.clone()));
+
+        let values = IndicatorValues::new(self.name.clone(), self.subscription.clone(), plots, base_data.time_closed_utc());
+        self.history.add(values.clone());
+        self.is_ready = true;
+        Some(vec![values])
+    }
+
+    fn subscription(&self) -> &DataSubscription {
+        &self.subscription
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+        self.ranges.clear();
+        self.is_ready = false;
+    }
+
+    fn index(&self, index: usize) -> Option<IndicatorValues> {
+        if !self.is_ready {
+            return None;
+        }
+        self.history.get(index).cloned()
+    }
+
+    fn current(&self) -> Option<IndicatorValues> {
+        if !self.is_ready {
+            return None;
+        }
+        self.history.last().cloned()
+    }
+
+    fn plots(&self) -> RollingWindow<IndicatorValues> {
+        self.history.clone()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.is_ready
+    }
+
+    fn history(&self) -> RollingWindow<IndicatorValues> {
+        self.history.clone()
+    }
+
+    fn data_required_warmup(&self) -> u64 {
+        self.history.len() as u64 + self.period as u64 + 1
+    }
+}
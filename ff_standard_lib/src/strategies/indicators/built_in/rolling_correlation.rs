@@ -0,0 +1,247 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{self, Display, Formatter};
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+use crate::gui_types::settings::Color;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::rolling_window::RollingWindow;
+use crate::standardized_types::subscriptions::DataSubscription;
+use crate::strategies::indicators::indicator_values::{IndicatorPlot, IndicatorValues};
+use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
+
+/// Rolling Correlation
+/// The Pearson correlation of the close to close returns of two subscriptions over the period.
+///
+/// # Calculation Method
+/// 1. The closes of the subscriptions are paired by close time, a close with no match in the other subscription is ignored
+/// 2. Return = close / previous paired close - 1
+/// 3. Correlation = Σ(dx × dy) / √(Σdx² × Σdy²), where dx and dy are the returns minus their mean over the period
+///
+/// # Plots
+/// - "correlation": From -1 (the symbols move opposite) to 1 (the symbols move together), 0 when either symbol did not move
+///
+/// # Parameters
+/// - second_subscription: The subscription compared with `subscription`, it should have the same resolution
+/// - period: Number of paired returns
+///
+/// # Usage
+/// The strategy must be subscribed to both subscriptions, the values are reported for `subscription`.
+/// Useful to confirm a pair is still related before trading the spread, or to avoid stacking positions in correlated symbols.
+#[derive(Clone, Debug)]
+pub struct RollingCorrelation {
+    name: IndicatorName,
+    subscription: DataSubscription,
+    second_subscription: DataSubscription,
+    history: RollingWindow<IndicatorValues>,
+    is_ready: bool,
+    color: Color,
+    period: usize,
+    closes: PairedCloses,
+    last_closes: Option<(Price, Price)>,
+    returns: VecDeque<(Decimal, Decimal)>,
+}
+
+/// Pairs the closes of two subscriptions by close time.
+#[derive(Clone, Debug)]
+pub(crate) struct PairedCloses {
+    pending: BTreeMap<DateTime<Utc>, (Option<Price>, Option<Price>)>,
+    max_pending: usize,
+}
+
+impl PairedCloses {
+    pub(crate) fn new(max_pending: usize) -> Self {
+        PairedCloses {
+            pending: BTreeMap::new(),
+            max_pending,
+        }
+    }
+
+    /// Adds the close of the first or second subscription, returns the pair when both have closed at the time.
+    pub(crate) fn pair(&mut self, time: DateTime<Utc>, close: Price, is_first: bool) -> Option<(Price, Price)> {
+        let entry = self.pending.entry(time).or_insert((None, None));
+        match is_first {
+            true => entry.0 = Some(close),
+            false => entry.1 = Some(close),
+        }
+        let (Some(first), Some(second)) = *entry else {
+            // closes which never pair, eg when the symbols trade different hours, are dropped
+            while self.pending.len() > self.max_pending {
+                self.pending.pop_first();
+            }
+            return None;
+        };
+        // older closes can no longer be paired
+        self.pending = self.pending.split_off(&time);
+        self.pending.remove(&time);
+        Some((first, second))
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+impl Display for RollingCorrelation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.history.last() {
+            Some(last) => write!(f, "{}\n{}", &self.name, last),
+            None => write!(f, "{}: No Values", &self.name),
+        }
+    }
+}
+
+impl RollingCorrelation {
+    #[allow(dead_code)]
+    pub async fn new(
+        name: IndicatorName,
+        subscription: DataSubscription,
+        second_subscription: DataSubscription,
+        history_to_retain: usize,
+        period: usize,
+        color: Color,
+    ) -> Box<Self> {
+        if subscription == second_subscription {
+            panic!("RollingCorrelation needs two different subscriptions")
+        }
+        let period = period.max(2);
+        Box::new(RollingCorrelation {
+            name,
+            subscription,
+            second_subscription,
+            history: RollingWindow::new(history_to_retain),
+            is_ready: false,
+            color,
+            period,
+            closes: PairedCloses::new(period * 2),
+            last_closes: None,
+            returns: VecDeque::with_capacity(period),
+        })
+    }
+
+    fn get_close_price(base_data: &BaseDataEnum) -> Option<Price> {
+        match base_data {
+            BaseDataEnum::QuoteBar(bar) => Some(bar.bid_close),
+            BaseDataEnum::Candle(candle) => Some(candle.close),
+            BaseDataEnum::Tick(tick) => Some(tick.price),
+            _ => None,
+        }
+    }
+
+    fn calculate(&self) -> Decimal {
+        let count = Decimal::from(self.returns.len());
+        let mean_first = self.returns.iter().map(|(first, _)| *first).sum::<Decimal>() / count;
+        let mean_second = self.returns.iter().map(|(_, second)| *second).sum::<Decimal>() / count;
+        let mut covariance = dec!(0);
+        let mut variance_first = dec!(0);
+        let mut variance_second = dec!(0);
+        for (first, second) in &self.returns {
+            let first = *first - mean_first;
+            let second = *second - mean_second;
+            covariance += first * second;
+            variance_first += first * first;
+            variance_second += second * second;
+        }
+        match (variance_first * variance_second).sqrt() {
+            Some(denominator) if denominator != dec!(0) => (covariance / denominator).max(dec!(-1)).min(dec!(1)),
+            _ => dec!(0),
+        }
+    }
+}
+
+impl Indicators for RollingCorrelation {
+    fn name(&self) -> IndicatorName {
+        self.name.clone()
+    }
+
+    fn history_to_retain(&self) -> usize {
+        self.history.number.clone() as usize
+    }
+
+    fn update_base_data(&mut self, base_data: &BaseDataEnum) -> Option<Vec<IndicatorValues>> {
+        if !base_data.is_closed() {
+            return None;
+        }
+        let subscription = base_data.subscription();
+        let is_first = if subscription == self.subscription {
+            true
+        } else if subscription == self.second_subscription {
+            false
+        } else {
+            return None;
+        };
+        let close = Self::get_close_price(base_data)?;
+        let time = base_data.time_closed_utc();
+        let (first, second) = self.closes.pair(time, close, is_first)?;
+
+        let last_closes = self.last_closes.replace((first, second));
+        let (last_first, last_second) = last_closes?;
+        if last_first == dec!(0) || last_second == dec!(0) {
+            return None;
+        }
+        self.returns.push_back((first / last_first - dec!(1), second / last_second - dec!(1)));
+        if self.returns.len() > self.period {
+            self.returns.pop_front();
+        }
+        if self.returns.len() < self.period {
+            return None;
+        }
+
+        let correlation = self.calculate();
+        let mut plots = BTreeMap::new();
+        plots.insert("correlation".to_string(), IndicatorPlot::new("Correlation".to_string(), correlation.round_dp(4), self.color.clone()));
+
+        let values = IndicatorValues::new(self.name.clone(), self.subscription.clone(), plots, time);
+        self.history.add(values.clone());
+        self.is_ready = true;
+        Some(vec![values])
+    }
+
+    fn subscription(&self) -> &DataSubscription {
+        &self.subscription
+    }
+
+    fn secondary_subscriptions(&self) -> Vec<DataSubscription> {
+        vec![self.second_subscription.clone()]
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+        self.closes.clear();
+        self.last_closes = None;
+        self.returns.clear();
+        self.is_ready = false;
+    }
+
+    fn index(&self, index: usize) -> Option<IndicatorValues> {
+        if !self.is_ready {
+            return None;
+        }
+        self.history.get(index).cloned()
+    }
+
+    fn current(&self) -> Option<IndicatorValues> {
+        if !self.is_ready {
+            return None;
+        }
+        self.history.last().cloned()
+    }
+
+    fn plots(&self) -> RollingWindow<IndicatorValues> {
+        self.history.clone()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.is_ready
+    }
+
+    fn history(&self) -> RollingWindow<IndicatorValues> {
+        self.history.clone()
+    }
+
+    fn data_required_warmup(&self) -> u64 {
+        self.history.len() as u64 + self.period as u64 + 1
+    }
+}
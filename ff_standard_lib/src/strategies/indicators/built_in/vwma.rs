@@ -13,6 +13,7 @@ use crate::standardized_types::rolling_window::RollingWindow;
 use crate::standardized_types::subscriptions::DataSubscription;
 use crate::strategies::indicators::indicator_values::{IndicatorPlot, IndicatorValues};
 use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
+use crate::strategies::indicators::running_window::RunningWindow;
 
 /// Volume Weighted Moving Average (VWMA)
 /// A technical indicator that factors in volume in addition to price, giving more weight
@@ -134,7 +135,8 @@ pub struct VolumeWeightedMA {
     name: IndicatorName,
     subscription: DataSubscription,
     history: RollingWindow<IndicatorValues>,
-    base_data_history: RollingWindow<BaseDataEnum>,
+    volume_prices: RunningWindow,
+    volumes: RunningWindow,
     #[allow(unused)]
     market_type: MarketType,
     #[allow(unused)]
@@ -183,7 +185,8 @@ impl VolumeWeightedMA {
             market_type: subscription.symbol.market_type.clone(),
             subscription,
             history: RollingWindow::new(history_to_retain),
-            base_data_history: RollingWindow::new(period as usize),
+            volume_prices: RunningWindow::new(period as usize),
+            volumes: RunningWindow::new(period as usize),
             is_ready: false,
             tick_size,
             vwma_color,
@@ -211,22 +214,13 @@ impl VolumeWeightedMA {
         }
     }
 
-    fn calculate_vwma(&self, history: &Vec<BaseDataEnum>) -> Option<Price> {
-        let mut volume_price_sum = dec!(0.0);
-        let mut volume_sum = dec!(0.0);
-
-        for data in history {
-            if let Some((price, volume)) = Self::get_price_and_volume(data) {
-                volume_price_sum += price * volume;
-                volume_sum += volume;
-            }
-        }
-
+    fn calculate_vwma(&self) -> Option<Price> {
+        let volume_sum = self.volumes.sum();
         if volume_sum == dec!(0.0) {
             return None;
         }
 
-        let vwma = volume_price_sum / volume_sum;
+        let vwma = self.volume_prices.sum() / volume_sum;
 
         Some(match self.tick_rounding {
             true => round_to_tick_size(vwma, self.tick_size),
@@ -277,10 +271,13 @@ impl Indicators for VolumeWeightedMA {
             return None;
         }
 
-        self.base_data_history.add(base_data.clone());
+        // data without a volume still takes its place in the window
+        let (price, volume) = Self::get_price_and_volume(base_data).unwrap_or((dec!(0.0), dec!(0.0)));
+        self.volume_prices.push(price * volume);
+        self.volumes.push(volume);
 
         if !self.is_ready {
-            if !self.base_data_history.is_full() {
+            if !self.volumes.is_full() {
                 return None;
             }
             self.is_ready = true;
@@ -291,7 +288,7 @@ impl Indicators for VolumeWeightedMA {
         self.update_average_volume(current_volume);
 
         // Calculate VWMA
-        let vwma = self.calculate_vwma(&self.base_data_history.history)?;
+        let vwma = self.calculate_vwma()?;
 
         // Calculate volume significance
         let volume_significance = self.calculate_volume_significance(current_volume);
@@ -333,7 +330,8 @@ impl Indicators for VolumeWeightedMA {
 
     fn reset(&mut self) {
         self.history.clear();
-        self.base_data_history.clear();
+        self.volume_prices.clear();
+        self.volumes.clear();
         self.is_ready = false;
         self.average_volume = None;
     }
@@ -367,4 +365,8 @@ impl Indicators for VolumeWeightedMA {
     fn data_required_warmup(&self) -> u64 {
         self.period
     }
+
+    fn is_incremental(&self) -> bool {
+        true
+    }
 }
\ No newline at end of file
@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::gui_types::settings::Color;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::rolling_window::RollingWindow;
+use crate::standardized_types::subscriptions::DataSubscription;
+use crate::strategies::indicators::indicator_values::{IndicatorPlot, IndicatorValues};
+use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
+use crate::strategies::indicators::running_window::RunningWindow;
+
+/// Rolling Z-Score
+/// The number of standard deviations the close is away from its simple moving average.
+///
+/// # Calculation Method
+/// 1. Mean = SMA(close, period)
+/// 2. Standard Deviation = √(Σ(close - mean)² / period)
+/// 3. Z-Score = (close - mean) / standard deviation
+///
+/// # Plots
+/// - "zscore": The z-score of the close, 0 when the standard deviation is 0
+/// - "mean": The moving average of the close
+///
+/// # Parameters
+/// - period: Number of closes in the mean and standard deviation
+///
+/// # Usage
+/// Mean reversion strategies fade extreme values, eg above 2 or below -2, momentum strategies follow them.
+/// Works with candles, quote bars (bid close) and ticks.
+#[derive(Clone, Debug)]
+pub struct ZScore {
+    name: IndicatorName,
+    subscription: DataSubscription,
+    history: RollingWindow<IndicatorValues>,
+    is_ready: bool,
+    zscore_color: Color,
+    mean_color: Color,
+    period: usize,
+    closes: RunningWindow,
+}
+
+impl Display for ZScore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.history.last() {
+            Some(last) => write!(f, "{}\n{}", &self.name, last),
+            None => write!(f, "{}: No Values", &self.name),
+        }
+    }
+}
+
+impl ZScore {
+    #[allow(dead_code)]
+    pub async fn new(
+        name: IndicatorName,
+        subscription: DataSubscription,
+        history_to_retain: usize,
+        period: usize,
+        zscore_color: Color,
+        mean_color: Color,
+    ) -> Box<Self> {
+        let period = period.max(2);
+        Box::new(ZScore {
+            name,
+            subscription,
+            history: RollingWindow::new(history_to_retain),
+            is_ready: false,
+            zscore_color,
+            mean_color,
+            period,
+            closes: RunningWindow::with_variance(period),
+        })
+    }
+
+    fn get_close_price(base_data: &BaseDataEnum) -> Option<Price> {
+        match base_data {
+            BaseDataEnum::QuoteBar(bar) => Some(bar.bid_close),
+            BaseDataEnum::Candle(candle) => Some(candle.close),
+            BaseDataEnum::Tick(tick) => Some(tick.price),
+            _ => None,
+        }
+    }
+
+    /// Returns the mean and the z-score of the last close.
+    fn calculate(&self) -> Option<(Price, Decimal)> {
+        let close = self.closes.last()?;
+        let mean = self.closes.mean()?;
+        let std_dev = self.closes.std_dev().unwrap_or(dec!(0));
+        let zscore = match std_dev == dec!(0) {
+            true => dec!(0),
+            false => (close - mean) / std_dev,
+        };
+        Some((mean, zscore))
+    }
+}
+
+impl Indicators for ZScore {
+    fn name(&self) -> IndicatorName {
+        self.name.clone()
+    }
+
+    fn history_to_retain(&self) -> usize {
+        self.history.number.clone() as usize
+    }
+
+    fn update_base_data(&mut self, base_data: &BaseDataEnum) -> Option<Vec<IndicatorValues>> {
+        if !base_data.is_closed() {
+            return None;
+        }
+        let close = Self::get_close_price(base_data)?;
+        self.closes.push(close);
+        if !self.closes.is_full() {
+            return None;
+        }
+
+        let (mean, zscore) = self.calculate()?;
+
+        let mut plots = BTreeMap::new();
+        plots.insert("zscore".to_string(), IndicatorPlot::new("Z-Score".to_string(), zscore.round_dp(4), self.zscore_color.clone()));
+        plots.insert("mean".to_string(), IndicatorPlot::new("Mean".to_string(), mean, self.mean_color.clone()));
+
+        let values = IndicatorValues::new(self.name.clone(), self.subscription.clone(), plots, base_data.time_closed_utc());
+        self.history.add(values.clone());
+        self.is_ready = true;
+        Some(vec![values])
+    }
+
+    fn subscription(&self) -> &DataSubscription {
+        &self.subscription
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+        self.closes.clear();
+        self.is_ready = false;
+    }
+
+    fn index(&self, index: usize) -> Option<IndicatorValues> {
+        if !self.is_ready {
+            return None;
+        }
+        self.history.get(index).cloned()
+    }
+
+    fn current(&self) -> Option<IndicatorValues> {
+        if !self.is_ready {
+            return None;
+        }
+        self.history.last().cloned()
+    }
+
+    fn plots(&self) -> RollingWindow<IndicatorValues> {
+        self.history.clone()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.is_ready
+    }
+
+    fn history(&self) -> RollingWindow<IndicatorValues> {
+        self.history.clone()
+    }
+
+    fn data_required_warmup(&self) -> u64 {
+        self.history.len() as u64 + self.period as u64
+    }
+
+    fn is_incremental(&self) -> bool {
+        true
+    }
+}
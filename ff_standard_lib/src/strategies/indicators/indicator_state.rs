@@ -0,0 +1,212 @@
+use std::fs;
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use rkyv::{Archive, Deserialize as Deserialize_rkyv, Serialize as Serialize_rkyv};
+use rust_decimal::Decimal;
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::bytes_trait::Bytes;
+use crate::standardized_types::rolling_window::RollingWindow;
+use crate::standardized_types::subscriptions::DataSubscription;
+use crate::strategies::indicators::indicator_values::IndicatorValues;
+use crate::strategies::indicators::indicators_trait::{IndicatorName, Indicators};
+
+/// The saved internal state of an indicator, see `Indicators::save_state()`.
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct IndicatorState {
+    pub name: IndicatorName,
+    pub subscription: DataSubscription,
+    /// The time of the last indicator value when the state was saved.
+    pub last_update: String,
+    pub state: Vec<u8>,
+}
+
+impl IndicatorState {
+    /// Returns None if the indicator does not support cached state or has not produced a value yet.
+    pub fn from_indicator(indicator: &dyn Indicators) -> Option<Self> {
+        let last_update = indicator.current()?.time_utc();
+        let state = indicator.save_state()?;
+        Some(IndicatorState {
+            name: indicator.name(),
+            subscription: indicator.subscription().clone(),
+            last_update: last_update.to_string(),
+            state,
+        })
+    }
+
+    pub fn last_update_utc(&self) -> Option<DateTime<Utc>> {
+        DateTime::<Utc>::from_str(&self.last_update).ok()
+    }
+
+    /// Returns true if the state belongs to the indicator and was saved no more than `max_staleness` before `time`.
+    /// Tick and instant resolutions have no fixed bar duration to replay the missed bars by, so they are never fresh.
+    pub fn is_fresh(&self, name: &IndicatorName, subscription: &DataSubscription, time: DateTime<Utc>, max_staleness: chrono::Duration) -> bool {
+        if &self.name != name || &self.subscription != subscription {
+            return false;
+        }
+        if subscription.resolution.as_duration() <= chrono::Duration::zero() {
+            return false;
+        }
+        match self.last_update_utc() {
+            Some(last_update) => last_update <= time && time - last_update <= max_staleness,
+            None => false,
+        }
+    }
+}
+
+impl Bytes<Self> for IndicatorState {
+    fn from_bytes(archived: &[u8]) -> Result<IndicatorState, FundForgeError> {
+        match rkyv::from_bytes::<IndicatorState>(archived) {
+            Ok(state) => Ok(state),
+            Err(e) => Err(FundForgeError::ClientSideErrorDebug(e.to_string())),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let vec = rkyv::to_bytes::<_, 1024>(self).unwrap();
+        vec.into()
+    }
+}
+
+pub fn save_indicator_states(file_path: &str, states: Vec<IndicatorState>) -> Result<(), FundForgeError> {
+    let bytes = match rkyv::to_bytes::<_, 1024>(&states) {
+        Ok(bytes) => bytes,
+        Err(e) => return Err(FundForgeError::ClientSideErrorDebug(e.to_string())),
+    };
+    match fs::write(file_path, bytes.as_slice()) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(FundForgeError::ClientSideErrorDebug(format!("Failed to save indicator states to {}: {}", file_path, e))),
+    }
+}
+
+/// Returns an empty vec if the file does not exist or can not be read, the indicators will then be warmed up from history.
+pub fn load_indicator_states(file_path: &str) -> Vec<IndicatorState> {
+    let bytes = match fs::read(file_path) {
+        Ok(bytes) => bytes,
+        Err(_) => return vec![],
+    };
+    match rkyv::from_bytes::<Vec<IndicatorState>>(&bytes) {
+        Ok(states) => states,
+        Err(e) => {
+            eprintln!("Failed to load indicator states from {}: {}", file_path, e);
+            vec![]
+        }
+    }
+}
+
+/// The state shared by indicators which keep a window of base data, a window of history and optionally their last calculated value.
+/// Incremental indicators save the values of their running window, oldest first, instead of the base data.
+#[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug)]
+#[archive(compare(PartialEq), check_bytes)]
+#[archive_attr(derive(Debug))]
+pub(crate) struct WindowedState {
+    pub history: Vec<IndicatorValues>,
+    pub base_data_history: Vec<BaseDataEnum>,
+    pub is_ready: bool,
+    pub last_value: Option<Decimal>,
+    pub values: Vec<Decimal>,
+}
+
+impl WindowedState {
+    pub fn new(history: &RollingWindow<IndicatorValues>, base_data_history: &RollingWindow<BaseDataEnum>, is_ready: bool, last_value: Option<Decimal>) -> Self {
+        WindowedState {
+            history: history.history.clone(),
+            base_data_history: base_data_history.history.clone(),
+            is_ready,
+            last_value,
+            values: vec![],
+        }
+    }
+
+    pub fn from_values(history: &RollingWindow<IndicatorValues>, values: Vec<Decimal>, is_ready: bool, last_value: Option<Decimal>) -> Self {
+        WindowedState {
+            history: history.history.clone(),
+            base_data_history: vec![],
+            is_ready,
+            last_value,
+            values,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        rkyv::to_bytes::<_, 1024>(self).ok().map(|bytes| bytes.into())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        rkyv::from_bytes::<WindowedState>(bytes).ok()
+    }
+
+    /// Restores the windows, keeping the capacity of the windows we restore into, so changing the history to retain still works.
+    pub fn restore_windows(self, history: &mut RollingWindow<IndicatorValues>, base_data_history: &mut RollingWindow<BaseDataEnum>) {
+        history.history = self.history.into_iter().take(history.number as usize).collect();
+        base_data_history.history = self.base_data_history.into_iter().take(base_data_history.number as usize).collect();
+    }
+
+    /// Restores the history of an incremental indicator, returns the values of its running window.
+    pub fn restore_values(self, history: &mut RollingWindow<IndicatorValues>) -> Vec<Decimal> {
+        history.history = self.history.into_iter().take(history.number as usize).collect();
+        self.values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use chrono::Duration;
+    use crate::standardized_types::base_data::base_data_type::BaseDataType;
+    use crate::standardized_types::datavendor_enum::DataVendor;
+    use crate::standardized_types::enums::{FuturesExchange, MarketType};
+    use crate::standardized_types::resolution::Resolution;
+
+    fn subscription(resolution: Resolution) -> DataSubscription {
+        DataSubscription::new("NQ".to_string(), DataVendor::Rithmic, resolution, BaseDataType::Candles, MarketType::Futures(FuturesExchange::CME))
+    }
+
+    fn state(last_update: DateTime<Utc>) -> IndicatorState {
+        IndicatorState {
+            name: "ema".to_string(),
+            subscription: subscription(Resolution::Minutes(1)),
+            last_update: last_update.to_string(),
+            state: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    fn test_state_is_fresh_until_the_max_staleness() {
+        let last_update = DateTime::<Utc>::from_str("2024-01-01 10:00:00 UTC").unwrap();
+        let state = state(last_update);
+        let name = "ema".to_string();
+        let sub = subscription(Resolution::Minutes(1));
+        let max_staleness = Duration::hours(12);
+        assert!(state.is_fresh(&name, &sub, last_update + Duration::seconds(59), max_staleness));
+        // bars closed since the state was saved are replayed, the state is still used
+        assert!(state.is_fresh(&name, &sub, last_update + Duration::hours(2), max_staleness));
+        assert!(state.is_fresh(&name, &sub, last_update + max_staleness, max_staleness));
+        assert!(!state.is_fresh(&name, &sub, last_update + max_staleness + Duration::seconds(1), max_staleness));
+        assert!(!state.is_fresh(&name, &sub, last_update - Duration::seconds(1), max_staleness));
+        assert!(!state.is_fresh(&"other".to_string(), &sub, last_update, max_staleness));
+        assert!(!state.is_fresh(&name, &subscription(Resolution::Minutes(5)), last_update, max_staleness));
+    }
+
+    #[test]
+    fn test_states_round_trip_through_file() {
+        let file = std::env::temp_dir().join("ff_indicator_states_test.bin");
+        let file = file.to_str().unwrap();
+        let states = vec![state(DateTime::<Utc>::from_str("2024-01-01 10:00:00 UTC").unwrap())];
+        save_indicator_states(file, states.clone()).unwrap();
+        assert_eq!(load_indicator_states(file), states);
+        let _ = fs::remove_file(file);
+        assert!(load_indicator_states(file).is_empty());
+
+        let values = IndicatorValues::new("ema".to_string(), subscription(Resolution::Minutes(1)), BTreeMap::new(), states[0].last_update_utc().unwrap());
+        let mut history = RollingWindow::new(1);
+        history.add(values.clone());
+        history.add(values.clone());
+        let windowed = WindowedState::new(&history, &RollingWindow::new(3), true, Some(Decimal::from(5)));
+        let restored = WindowedState::from_bytes(&windowed.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored, windowed);
+    }
+}
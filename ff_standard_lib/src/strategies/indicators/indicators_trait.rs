@@ -54,9 +54,36 @@ pub trait Indicators: Send + Sync {
     ///  }
     fn update_base_data(&mut self, base_data: &BaseDataEnum) -> Option<Vec<IndicatorValues>>;
 
+    /// Recalculates the indicator from scratch over the `history`, oldest first, and returns the values of the last data point which produced values.
+    /// This is how the engine warms up an indicator. The default resets the indicator and sends each data point to `update_base_data()`,
+    /// override it if the indicator can calculate its state over a whole history faster than one update at a time.
+    fn recalc(&mut self, history: &[BaseDataEnum]) -> Option<Vec<IndicatorValues>> {
+        self.reset();
+        let mut last_values = None;
+        for base_data in history {
+            if let Some(values) = self.update_base_data(base_data) {
+                last_values = Some(values);
+            }
+        }
+        last_values
+    }
+
+    /// True if `update_base_data()` only updates running totals with the new data, so an update costs the same for any period,
+    /// false if the indicator recalculates over its retained base data on each update. See `running_window.rs`.
+    fn is_incremental(&self) -> bool {
+        false
+    }
+
     /// Returns the subscription for the indicator.
     fn subscription(&self) -> &DataSubscription;
 
+    /// Returns the other subscriptions used by indicators which compare symbols, eg a rolling correlation.
+    /// The handler sends the data of these subscriptions to `update_base_data()` with the data of `subscription()`, in time order when warming up,
+    /// so the indicator should check `base_data.subscription()`. The strategy must be subscribed to each of them.
+    fn secondary_subscriptions(&self) -> Vec<DataSubscription> {
+        vec![]
+    }
+
     /// Resets the indicator to its initial state.
     fn reset(&mut self);
 
@@ -78,4 +105,15 @@ pub trait Indicators: Send + Sync {
 
     /// the number of base data points we need to fill the history on warm up, for example an 5 period ATR indicator that keeps a history of 12 data points will require 17 base data enums to warm up
     fn data_required_warmup(&self) -> u64;
+
+    /// Serializes the internal state of the indicator, so a live strategy can restart without warming up from history, see `strategy.save_indicator_states()`.
+    /// Returns None by default, indicators which do not support cached state are always warmed up from history.
+    fn save_state(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Restores the state returned by `save_state()`, returns false if the state could not be restored and the indicator will be warmed up from history.
+    fn restore_state(&mut self, _state: &[u8]) -> bool {
+        false
+    }
 }
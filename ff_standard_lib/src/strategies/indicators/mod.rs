@@ -3,4 +3,5 @@ pub mod indicators_trait;
 pub mod traits;
 pub mod indicator_events;
 pub mod indicator_values;
-
+pub mod indicator_state;
+pub mod running_window;
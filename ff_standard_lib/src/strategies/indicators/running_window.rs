@@ -0,0 +1,206 @@
+use std::collections::VecDeque;
+use rust_decimal::{Decimal, MathematicalOps};
+
+/// A window of the last `period` values with the running sum, and the sum of squares if the window was made `with_variance()`.
+#[derive(Clone, Debug)]
+pub struct RunningWindow {
+    values: VecDeque<Decimal>,
+    period: usize,
+    sum: Decimal,
+    sum_of_squares: Option<Decimal>,
+}
+
+impl RunningWindow {
+    pub fn new(period: usize) -> Self {
+        RunningWindow {
+            values: VecDeque::with_capacity(period + 1),
+            period,
+            sum: Decimal::ZERO,
+            sum_of_squares: None,
+        }
+    }
+
+    /// A window which also keeps the sum of squares for the variance, only for values which can be squared without overflow, eg prices but not price * volume.
+    pub fn with_variance(period: usize) -> Self {
+        RunningWindow {
+            sum_of_squares: Some(Decimal::ZERO),
+            ..RunningWindow::new(period)
+        }
+    }
+
+    /// Adds the value, returns the value which left the window if the window was full.
+    pub fn push(&mut self, value: Decimal) -> Option<Decimal> {
+        self.values.push_back(value);
+        self.sum += value;
+        if let Some(sum_of_squares) = self.sum_of_squares.as_mut() {
+            *sum_of_squares += value * value;
+        }
+        if self.values.len() <= self.period {
+            return None;
+        }
+        let removed = self.values.pop_front()?;
+        self.sum -= removed;
+        if let Some(sum_of_squares) = self.sum_of_squares.as_mut() {
+            *sum_of_squares -= removed * removed;
+        }
+        Some(removed)
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.sum = Decimal::ZERO;
+        if self.sum_of_squares.is_some() {
+            self.sum_of_squares = Some(Decimal::ZERO);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.period
+    }
+
+    /// The values in the window, oldest first.
+    pub fn values(&self) -> Vec<Decimal> {
+        self.values.iter().cloned().collect()
+    }
+
+    /// The latest value.
+    pub fn last(&self) -> Option<Decimal> {
+        self.values.back().cloned()
+    }
+
+    /// The oldest value still in the window.
+    pub fn first(&self) -> Option<Decimal> {
+        self.values.front().cloned()
+    }
+
+    pub fn sum(&self) -> Decimal {
+        self.sum
+    }
+
+    pub fn mean(&self) -> Option<Decimal> {
+        match self.values.is_empty() {
+            true => None,
+            false => Some(self.sum / Decimal::from(self.values.len())),
+        }
+    }
+
+    /// The population variance of the values in the window, None for a window made without `with_variance()`.
+    pub fn variance(&self) -> Option<Decimal> {
+        let mean = self.mean()?;
+        let variance = self.sum_of_squares? / Decimal::from(self.values.len()) - mean * mean;
+        // the division can leave a tiny negative remainder when every value is the same
+        Some(variance.max(Decimal::ZERO))
+    }
+
+    /// The population standard deviation of the values in the window.
+    pub fn std_dev(&self) -> Option<Decimal> {
+        self.variance()?.sqrt()
+    }
+}
+
+/// The highest and lowest of the last `period` values, each update is amortized O(1).
+#[derive(Clone, Debug)]
+pub struct RunningExtremes {
+    highs: VecDeque<(u64, Decimal)>,
+    lows: VecDeque<(u64, Decimal)>,
+    period: u64,
+    count: u64,
+}
+
+impl RunningExtremes {
+    pub fn new(period: usize) -> Self {
+        RunningExtremes {
+            highs: VecDeque::new(),
+            lows: VecDeque::new(),
+            period: period as u64,
+            count: 0,
+        }
+    }
+
+    /// Adds the high and low of the next data point.
+    pub fn push(&mut self, high: Decimal, low: Decimal) {
+        let index = self.count;
+        self.count += 1;
+        while self.highs.back().map_or(false, |(_, value)| *value <= high) {
+            self.highs.pop_back();
+        }
+        self.highs.push_back((index, high));
+        while self.lows.back().map_or(false, |(_, value)| *value >= low) {
+            self.lows.pop_back();
+        }
+        self.lows.push_back((index, low));
+        // the oldest index still in the window
+        let oldest = self.count.saturating_sub(self.period);
+        while self.highs.front().map_or(false, |(i, _)| *i < oldest) {
+            self.highs.pop_front();
+        }
+        while self.lows.front().map_or(false, |(i, _)| *i < oldest) {
+            self.lows.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.highs.clear();
+        self.lows.clear();
+        self.count = 0;
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.count >= self.period
+    }
+
+    pub fn highest(&self) -> Option<Decimal> {
+        self.highs.front().map(|(_, value)| *value)
+    }
+
+    pub fn lowest(&self) -> Option<Decimal> {
+        self.lows.front().map(|(_, value)| *value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_running_window_matches_recalculation() {
+        let values = [dec!(1.5), dec!(2.25), dec!(0.75), dec!(4), dec!(3.5), dec!(2), dec!(5.25)];
+        let mut window = RunningWindow::with_variance(3);
+        for (i, value) in values.iter().enumerate() {
+            let removed = window.push(*value);
+            assert_eq!(removed, if i >= 3 { Some(values[i - 3]) } else { None });
+            let current = &values[i.saturating_sub(2)..=i];
+            let mean = current.iter().sum::<Decimal>() / Decimal::from(current.len());
+            let variance = current.iter().map(|v| (*v - mean) * (*v - mean)).sum::<Decimal>() / Decimal::from(current.len());
+            assert_eq!(window.sum(), current.iter().sum::<Decimal>());
+            assert_eq!(window.mean(), Some(mean));
+            assert_eq!(window.variance().unwrap().round_dp(20), variance.round_dp(20));
+        }
+        assert!(window.is_full());
+        assert_eq!(window.first(), Some(dec!(3.5)));
+        assert_eq!(window.last(), Some(dec!(5.25)));
+        assert_eq!(RunningWindow::new(3).variance(), None);
+    }
+
+    #[test]
+    fn test_running_extremes() {
+        let bars = [(dec!(5), dec!(3)), (dec!(7), dec!(4)), (dec!(6), dec!(2)), (dec!(4), dec!(3)), (dec!(5), dec!(4))];
+        let mut extremes = RunningExtremes::new(3);
+        for (i, (high, low)) in bars.iter().enumerate() {
+            extremes.push(*high, *low);
+            let current = &bars[i.saturating_sub(2)..=i];
+            assert_eq!(extremes.highest(), current.iter().map(|(h, _)| *h).max());
+            assert_eq!(extremes.lowest(), current.iter().map(|(_, l)| *l).min());
+        }
+        assert!(extremes.is_full());
+    }
+}
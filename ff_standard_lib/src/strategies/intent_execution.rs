@@ -0,0 +1,173 @@
+use chrono::{DateTime, Duration, Utc};
+use crate::standardized_types::enums::StrategyMode;
+use crate::standardized_types::intents::Intent;
+use crate::standardized_types::orders::{OrderError, OrderId};
+use crate::standardized_types::subscriptions::SymbolName;
+
+/// What happened to an intent.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IntentOutcome {
+    /// The price came within the trigger ticks, the intent was claimed and the entry was sent.
+    Triggered(OrderId),
+    /// The price came within the trigger ticks but the intent was claimed by another strategy first.
+    ClaimedElsewhere,
+    /// The entry was refused before it was sent, see `OrderError`. The claim is released and the intent rests until its entry can be sent.
+    Refused(OrderError),
+    /// The entry was filled and the exits of the bracket were sent, None for an exit the intent does not have.
+    Bracketed { take_profit: Option<OrderId>, stop_loss: Option<OrderId> },
+    /// The entry was cancelled, rejected or expired without a fill.
+    EntryClosed(OrderId),
+    /// An exit of the bracket filled and the other exit was cancelled.
+    Exited(OrderId),
+}
+
+/// An intent and what happened to it, returned by `strategy.execute_intents()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IntentExecution {
+    pub intent: Intent,
+    pub outcome: IntentOutcome,
+}
+
+/// An intent whose entry or bracket is working.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct WorkingIntent {
+    pub(crate) intent: Intent,
+    pub(crate) entry: OrderId,
+    /// The exits of the bracket, empty until the entry is filled.
+    pub(crate) exits: Vec<OrderId>,
+}
+
+/// The resting intents a strategy executes for its symbols, see `strategy.execute_intents()`.
+pub struct IntentExecutor {
+    symbols: Vec<SymbolName>,
+    refresh_interval: Duration,
+    last_refresh: Option<DateTime<Utc>>,
+    resting: Vec<Intent>,
+    working: Vec<WorkingIntent>,
+}
+
+impl IntentExecutor {
+    /// Executes the intents of the symbols, by the canonical symbol name the intents are saved with.
+    pub fn new(symbols: Vec<SymbolName>) -> Self {
+        IntentExecutor {
+            symbols,
+            refresh_interval: Duration::seconds(10),
+            last_refresh: None,
+            resting: vec![],
+            working: vec![],
+        }
+    }
+
+    /// How often the intents are reloaded from the data server in live modes, 10 seconds by default.
+    pub fn with_refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    pub fn symbols(&self) -> &[SymbolName] {
+        &self.symbols
+    }
+
+    /// The intents waiting for the price.
+    pub fn resting(&self) -> &[Intent] {
+        &self.resting
+    }
+
+    /// The intents whose entry or bracket is working.
+    pub fn working(&self) -> Vec<&Intent> {
+        self.working.iter().map(|working| &working.intent).collect()
+    }
+
+    /// True if the intents should be loaded from the server, on the first call and every refresh interval in live modes.
+    pub(crate) fn refresh_due(&self, time: DateTime<Utc>, mode: StrategyMode) -> bool {
+        match (self.last_refresh, mode) {
+            (None, _) => true,
+            (Some(_), StrategyMode::Backtest) => false,
+            (Some(last_refresh), StrategyMode::Live | StrategyMode::LivePaperTrading) => time - last_refresh >= self.refresh_interval,
+        }
+    }
+
+    pub(crate) fn set_refreshed(&mut self, time: DateTime<Utc>) {
+        self.last_refresh = Some(time);
+    }
+
+    /// Replaces the resting intents of the symbol with the resting intents loaded from the server, an intent which is already working is not added again.
+    pub(crate) fn set_resting(&mut self, symbol_name: &SymbolName, intents: Vec<Intent>) {
+        self.resting.retain(|intent| &intent.symbol_name != symbol_name);
+        let working = &self.working;
+        self.resting.extend(intents.into_iter().filter(|intent| {
+            intent.is_resting() && !working.iter().any(|working| working.intent.id == intent.id)
+        }));
+    }
+
+    /// Removes and returns the resting intents with the ids.
+    pub(crate) fn take_resting(&mut self, ids: &[String]) -> Vec<Intent> {
+        let (taken, resting) = self.resting.drain(..).partition(|intent| ids.contains(&intent.id));
+        self.resting = resting;
+        taken
+    }
+
+    /// Puts back an intent which could not be claimed or sent, eg when the server did not answer or the entry was refused.
+    pub(crate) fn restore(&mut self, intent: Intent) {
+        self.resting.push(intent);
+    }
+
+    pub(crate) fn start(&mut self, intent: Intent, entry: OrderId) {
+        self.working.push(WorkingIntent { intent, entry, exits: vec![] });
+    }
+
+    /// Removes and returns the working intents, the intents still working are given back with `continue_working()`.
+    pub(crate) fn take_working(&mut self) -> Vec<WorkingIntent> {
+        std::mem::take(&mut self.working)
+    }
+
+    pub(crate) fn continue_working(&mut self, working: WorkingIntent) {
+        self.working.push(working);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::accounts::Account;
+    use crate::standardized_types::broker_enum::Brokerage;
+    use crate::standardized_types::enums::OrderSide;
+    use crate::standardized_types::intents::IntentStatus;
+
+    fn intent(symbol_name: &str, price: rust_decimal::Decimal) -> Intent {
+        Intent::new(symbol_name.to_string(), Account::new(Brokerage::Test, "Test_Account_1".to_string()), OrderSide::Buy, dec!(1), price, 4, "Support")
+    }
+
+    #[test]
+    fn test_refresh_due() {
+        let time = Utc.with_ymd_and_hms(2024, 6, 3, 9, 0, 0).unwrap();
+        let mut executor = IntentExecutor::new(vec!["MNQ".to_string()]).with_refresh_interval(Duration::seconds(30));
+        assert!(executor.refresh_due(time, StrategyMode::Backtest));
+        executor.set_refreshed(time);
+        assert!(!executor.refresh_due(time + Duration::days(1), StrategyMode::Backtest));
+        assert!(!executor.refresh_due(time + Duration::seconds(29), StrategyMode::Live));
+        assert!(executor.refresh_due(time + Duration::seconds(30), StrategyMode::LivePaperTrading));
+    }
+
+    #[test]
+    fn test_set_resting() {
+        let mut executor = IntentExecutor::new(vec!["MNQ".to_string(), "MES".to_string()]);
+        let (first, second) = (intent("MNQ", dec!(18000)), intent("MNQ", dec!(17950)));
+        let mut triggered = intent("MNQ", dec!(17900));
+        triggered.status = IntentStatus::Triggered { time: "2024-06-03 09:00:00 UTC".to_string() };
+        executor.set_resting(&"MES".to_string(), vec![intent("MES", dec!(5300))]);
+        executor.set_resting(&"MNQ".to_string(), vec![first.clone(), second.clone(), triggered]);
+        assert_eq!(executor.resting().len(), 3);
+
+        let taken = executor.take_resting(&[first.id.clone()]);
+        assert_eq!(taken, vec![first.clone()]);
+        executor.start(first.clone(), "entry".to_string());
+
+        // a reload while the intent is working, the second intent was removed on the server
+        executor.set_resting(&"MNQ".to_string(), vec![first.clone()]);
+        assert!(executor.resting().iter().all(|intent| intent.symbol_name == "MES"));
+        assert_eq!(executor.working(), vec![&first]);
+    }
+}
@@ -0,0 +1,266 @@
+use std::fmt::{self, Display, Formatter};
+use std::fs::{create_dir_all, write};
+use std::path::Path;
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use csv::Writer;
+use rust_decimal::Decimal;
+use serde_derive::Serialize;
+use crate::standardized_types::accounts::{Account, Currency};
+use crate::standardized_types::enums::StrategyMode;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::position::Position;
+use crate::strategies::fingerprint::StrategyFingerprint;
+use crate::strategies::ledgers::ledger_snapshot::{position_trade_exports, LedgerSnapshot, LedgerStatistics};
+
+/// The equity of the strategy accounts after a position closed.
+/// # Properties
+/// * `time` - The close time of the position.
+/// * `account` - The account the position closed on.
+/// * `pnl` - The pnl booked by the position.
+/// * `equity` - The combined equity of the accounts after the position.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct EquityPoint {
+    pub time: String,
+    pub account: String,
+    pub pnl: Price,
+    pub equity: Price,
+}
+
+/// The figures of one account in the aggregate.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct AccountBreakdown {
+    pub account: String,
+    pub currency: Currency,
+    pub balance: Price,
+    pub cash_value: Price,
+    pub booked_pnl: Price,
+    pub open_pnl: Price,
+    pub statistics: LedgerStatistics,
+}
+
+/// A completed trade as it is written by `AggregateStatistics::export_trades_to_csv()`, a `TradeExport` with its account.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct AccountTradeExport {
+    pub account: String,
+    pub symbol_code: String,
+    pub position_id: String,
+    pub side: String,
+    pub entry_price: Decimal,
+    pub entry_quantity: Decimal,
+    pub exit_price: Decimal,
+    pub exit_quantity: Decimal,
+    pub entry_time: String,
+    pub exit_time: String,
+    pub pnl: Decimal,
+    pub tag: String,
+    pub result: String,
+    pub fingerprint: String,
+}
+
+/// The statistics of all the strategy accounts together, see `strategy.aggregate_statistics()`.
+/// # Properties
+/// * `mode` - The strategy mode of the ledgers.
+/// * `currency` - The currency of the totals, None if the accounts do not share a currency.
+/// * `cash_value`, `booked_pnl`, `open_pnl` - The totals of the accounts.
+/// * `statistics` - The statistics of the positions closed on all the accounts.
+/// * `accounts` - The breakdown of each account.
+/// * `equity_curve` - The combined equity after each closed position, in the order the positions closed.
+/// * `trades` - The completed trades of all the accounts, in the order the positions closed.
+/// * `fingerprint` - The configuration of the strategy run, None outside a strategy.
+#[derive(Clone, Debug, Serialize)]
+pub struct AggregateStatistics {
+    pub mode: StrategyMode,
+    pub currency: Option<Currency>,
+    pub cash_value: Price,
+    pub booked_pnl: Price,
+    pub open_pnl: Price,
+    pub statistics: LedgerStatistics,
+    pub accounts: Vec<AccountBreakdown>,
+    pub equity_curve: Vec<EquityPoint>,
+    pub trades: Vec<AccountTradeExport>,
+    pub fingerprint: Option<StrategyFingerprint>,
+}
+
+/// The equity after each close, from the starting equity, sorted by close time.
+fn equity_curve(starting_equity: Price, mut closes: Vec<(DateTime<Utc>, Account, Price)>) -> Vec<EquityPoint> {
+    closes.sort_by_key(|(time, _, _)| *time);
+    let mut equity = starting_equity;
+    closes.into_iter()
+        .map(|(time, account, pnl)| {
+            equity += pnl;
+            EquityPoint { time: time.to_string(), account: account.to_string(), pnl, equity }
+        })
+        .collect()
+}
+
+fn close_time(position: &Position) -> Option<DateTime<Utc>> {
+    position.close_time.as_ref().and_then(|time| DateTime::<Utc>::from_str(time).ok())
+}
+
+impl AggregateStatistics {
+    pub fn new(mode: StrategyMode, snapshots: &[LedgerSnapshot]) -> Self {
+        let mut positions_closed: Vec<&Position> = snapshots.iter().flat_map(|snapshot| snapshot.positions_closed.iter()).collect();
+        positions_closed.sort_by_key(|position| close_time(position));
+        let positions: Vec<Position> = positions_closed.iter().map(|position| (*position).clone()).collect();
+
+        let currency = match snapshots.first() {
+            Some(first) if snapshots.iter().all(|snapshot| snapshot.summary.currency == first.summary.currency) => Some(first.summary.currency),
+            _ => None,
+        };
+        let cash_value = snapshots.iter().map(|snapshot| snapshot.summary.cash_value).sum::<Price>();
+        let booked_pnl = snapshots.iter().map(|snapshot| snapshot.booked_pnl).sum::<Price>();
+        let closes = positions_closed.iter()
+            .filter_map(|position| Some((close_time(position)?, position.account.clone(), position.booked_pnl)))
+            .collect();
+
+        // the snapshots of one strategy share its fingerprint
+        let fingerprint = snapshots.first().and_then(|snapshot| snapshot.fingerprint.clone());
+        let fingerprint_hash = fingerprint.as_ref().map(|fingerprint| fingerprint.hash.clone()).unwrap_or_default();
+        let mut trades = vec![];
+        for position in &positions_closed {
+            trades.extend(position_trade_exports(position, &fingerprint_hash).into_iter().map(|trade| AccountTradeExport {
+                account: position.account.to_string(),
+                symbol_code: trade.symbol_code,
+                position_id: trade.position_id,
+                side: trade.side,
+                entry_price: trade.entry_price,
+                entry_quantity: trade.entry_quantity,
+                exit_price: trade.exit_price,
+                exit_quantity: trade.exit_quantity,
+                entry_time: trade.entry_time,
+                exit_time: trade.exit_time,
+                pnl: trade.pnl,
+                tag: trade.tag,
+                result: trade.result,
+                fingerprint: trade.fingerprint,
+            }));
+        }
+
+        AggregateStatistics {
+            mode,
+            currency,
+            cash_value,
+            booked_pnl,
+            open_pnl: snapshots.iter().map(|snapshot| snapshot.open_pnl).sum(),
+            statistics: LedgerStatistics::new(&positions),
+            accounts: snapshots.iter()
+                .map(|snapshot| AccountBreakdown {
+                    account: snapshot.account().to_string(),
+                    currency: snapshot.summary.currency,
+                    balance: snapshot.summary.balance,
+                    cash_value: snapshot.summary.cash_value,
+                    booked_pnl: snapshot.booked_pnl,
+                    open_pnl: snapshot.open_pnl,
+                    statistics: snapshot.statistics.clone(),
+                })
+                .collect(),
+            equity_curve: equity_curve(cash_value - booked_pnl, closes),
+            trades,
+            fingerprint,
+        }
+    }
+
+    /// Exports the trades of all the accounts to a single csv file in the folder.
+    pub fn export_trades_to_csv(&self, folder: &str) {
+        if let Err(e) = create_dir_all(folder) {
+            eprintln!("Failed to create directory {}: {}", folder, e);
+            return;
+        }
+        let date = Utc::now().format("%Y%m%d_%H%M").to_string();
+        let file_name = format!("{}/{:?}_AggregateTradeResults_{}.csv", folder, self.mode, date);
+        let file_path = Path::new(&file_name);
+        match Writer::from_path(file_path) {
+            Ok(mut wtr) => {
+                for trade in &self.trades {
+                    if let Err(e) = wtr.serialize(trade) {
+                        eprintln!("Failed to write trade data to {}: {}", file_path.display(), e);
+                    }
+                }
+                match wtr.flush() {
+                    Ok(()) => println!("Successfully exported all trades to {}", file_path.display()),
+                    Err(e) => eprintln!("Failed to flush CSV writer for {}: {}", file_path.display(), e),
+                }
+            }
+            Err(e) => eprintln!("Failed to create CSV writer for {}: {}", file_path.display(), e),
+        }
+    }
+
+    /// Exports the totals, statistics, account breakdown, equity curve and trades to a json file in the folder.
+    pub fn export_to_json(&self, folder: &str) {
+        if let Err(e) = create_dir_all(folder) {
+            eprintln!("Failed to create directory {}: {}", folder, e);
+            return;
+        }
+        let date = Utc::now().format("%Y%m%d_%H%M").to_string();
+        let file_name = format!("{}/{:?}_Aggregate_{}.json", folder, self.mode, date);
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => match write(&file_name, json) {
+                Ok(()) => println!("Successfully exported the aggregate statistics to {}", file_name),
+                Err(e) => eprintln!("Failed to write {}: {}", file_name, e),
+            },
+            Err(e) => eprintln!("Failed to serialize the aggregate statistics: {}", e),
+        }
+    }
+}
+
+impl Display for AggregateStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let currency = self.currency.map_or("mixed currencies".to_string(), |currency| currency.to_string());
+        let statistics = &self.statistics;
+        let mut report = format!(
+            "Aggregate: {} Accounts, Cash Value: {} {}, Total profit: {}, Open PnL: {}, Win Rate: {}%, Profit Factor: {}, Max Drawdown: {}, Total Positions: {}, Commission Paid: {}",
+            self.accounts.len(),
+            self.cash_value.round_dp(2),
+            currency,
+            self.booked_pnl.round_dp(2),
+            self.open_pnl.round_dp(2),
+            statistics.win_rate.round_dp(2),
+            statistics.profit_factor.round_dp(2),
+            statistics.max_drawdown.round_dp(2),
+            statistics.total_positions,
+            statistics.commission_paid,
+        );
+        for account in &self.accounts {
+            report.push_str(&format!(
+                "\n  Account: {}, Cash Value: {} {}, Total profit: {}, Open PnL: {}, Win Rate: {}%, Max Drawdown: {}, Total Positions: {}",
+                account.account,
+                account.cash_value.round_dp(2),
+                account.currency,
+                account.booked_pnl.round_dp(2),
+                account.open_pnl.round_dp(2),
+                account.statistics.win_rate.round_dp(2),
+                account.statistics.max_drawdown.round_dp(2),
+                account.statistics.total_positions,
+            ));
+        }
+        write!(f, "{}", report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::broker_enum::Brokerage;
+
+    #[test]
+    fn test_equity_curve() {
+        let first = Account::new(Brokerage::Test, "Test_Account_1".to_string());
+        let second = Account::new(Brokerage::Test, "Test_Account_2".to_string());
+        let time = Utc.with_ymd_and_hms(2024, 6, 3, 9, 0, 0).unwrap();
+        let closes = vec![
+            (time + Duration::minutes(2), first.clone(), dec!(-50)),
+            (time, first.clone(), dec!(100)),
+            (time + Duration::minutes(1), second.clone(), dec!(200)),
+        ];
+        let curve = equity_curve(dec!(20000), closes);
+        let points: Vec<(String, Price)> = curve.iter().map(|point| (point.account.clone(), point.equity)).collect();
+        assert_eq!(points, vec![
+            (first.to_string(), dec!(20100)),
+            (second.to_string(), dec!(20300)),
+            (first.to_string(), dec!(20250)),
+        ]);
+    }
+}
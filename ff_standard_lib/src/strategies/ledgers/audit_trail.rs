@@ -0,0 +1,293 @@
+use std::fs::{create_dir_all, File};
+use std::path::Path;
+use std::sync::{Mutex, RwLock};
+use chrono::Utc;
+use csv::Writer;
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde_derive::Serialize;
+use crate::standardized_types::accounts::Account;
+use crate::standardized_types::enums::{Liquidity, OrderSide, PositionSide, StrategyMode};
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::orders::OrderId;
+use crate::standardized_types::subscriptions::SymbolCode;
+use crate::strategies::ledgers::ledger::Ledger;
+
+/// The event which caused a ledger mutation.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+pub enum AuditEvent {
+    /// A fill or partial fill reported by the broker.
+    Fill,
+    /// A fill from the backtest or live paper matching engine.
+    PaperFill,
+    /// A paper position exited by the matching engine.
+    PaperExit,
+    /// A paper position exited by a flatten all, at the end of a backtest or regime or when the strategy flattens.
+    PaperFlattenAll,
+    /// Account balances from the broker.
+    AccountUpdate,
+    /// The daily borrow fee of a paper short position, see `ShortabilityTable`.
+    BorrowFee,
+}
+
+/// The fill details reported by the brokerage, see `OrderUpdateEvent::OrderFilled`, empty for paper fills.
+#[derive(Clone, Debug, Default)]
+pub struct BrokerFill {
+    pub fill_id: Option<String>,
+    pub liquidity: Option<Liquidity>,
+    pub commission: Option<Price>,
+    /// The id of the order at the brokerage, from `OrderUpdateEvent::OrderAccepted`.
+    pub broker_order_id: Option<String>,
+}
+
+/// # Properties
+/// * `event` - The event which caused the mutation.
+/// * `order_id` - The order which was filled or exited the position.
+/// * `event_time` - The time of the event as reported by the vendor or the matching engine.
+/// * `tag` - The order tag.
+/// * `side` - The side of the fill.
+/// * `quantity` - The quantity of the fill.
+/// * `price` - The price of the fill.
+/// * `broker_fill` - The fill details reported by the brokerage.
+#[derive(Clone, Debug)]
+pub(crate) struct AuditCause {
+    pub event: AuditEvent,
+    pub order_id: Option<OrderId>,
+    pub event_time: String,
+    pub tag: String,
+    pub side: Option<OrderSide>,
+    pub quantity: Option<Volume>,
+    pub price: Option<Price>,
+    pub broker_fill: BrokerFill,
+}
+
+/// A ledger mutation and its cause.
+/// # Properties
+/// * `sequence` - The position of the entry in the trail of the account, starting at 1.
+/// * `account` - The account of the ledger.
+/// * `event` - The event which caused the mutation.
+/// * `order_id` - The order which was filled or exited the position.
+/// * `broker_order_id` - The id of the order at the brokerage, eg the Rithmic basket id, None for paper fills or if the brokerage did not report it.
+/// * `fill_id` - `{order_id}-{n}` for the nth fill of the order applied to the ledger.
+/// * `event_time` - The time of the event as reported by the vendor or the matching engine.
+/// * `recorded_at` - The time the ledger applied the event.
+/// * `tag` - The order tag.
+/// * `symbol_code` - The symbol code of the position, None for account updates.
+/// * `fill_side`, `fill_quantity`, `fill_price` - The fill, None if the event was not a fill.
+/// * `broker_fill_id`, `liquidity`, `commission` - The fill id, liquidity and commission reported by the brokerage, None if it did not report them.
+/// * `side_before`, `quantity_before` - The position before the event, Flat and 0 if there was no position.
+/// * `side_after`, `quantity_after` - The position after the event.
+/// * `booked_pnl` - The pnl booked by the event.
+/// * `cash_available_before`, `cash_available_after`, `cash_used_before`, `cash_used_after` - The cash before and after the event.
+/// * `fingerprint` - The hash of the strategy configuration which recorded the entry, see `strategy.fingerprint()`.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct AuditEntry {
+    pub sequence: u64,
+    pub account: String,
+    pub event: AuditEvent,
+    pub order_id: Option<OrderId>,
+    pub broker_order_id: Option<String>,
+    pub fill_id: Option<String>,
+    pub event_time: String,
+    pub recorded_at: String,
+    pub tag: String,
+    pub symbol_code: Option<SymbolCode>,
+    pub fill_side: Option<OrderSide>,
+    pub fill_quantity: Option<Volume>,
+    pub fill_price: Option<Price>,
+    pub broker_fill_id: Option<String>,
+    pub liquidity: Option<Liquidity>,
+    pub commission: Option<Price>,
+    pub side_before: PositionSide,
+    pub quantity_before: Volume,
+    pub side_after: PositionSide,
+    pub quantity_after: Volume,
+    pub booked_pnl: Decimal,
+    pub cash_available_before: Price,
+    pub cash_available_after: Price,
+    pub cash_used_before: Price,
+    pub cash_used_after: Price,
+    pub fingerprint: String,
+}
+
+/// The position and cash of a ledger before an event.
+pub(crate) struct AuditSnapshot {
+    symbol_code: Option<SymbolCode>,
+    side: PositionSide,
+    quantity: Volume,
+    cash_available: Price,
+    cash_used: Price,
+    booked_pnl: Price,
+}
+
+/// The audit trail of a ledger.
+/// # Properties
+/// * `entries` - The entries in the order they were recorded.
+/// * `fills` - The number of fills applied for each order.
+/// * `writer` - The file live entries are appended to.
+#[derive(Default)]
+pub(crate) struct AuditTrail {
+    entries: RwLock<Vec<AuditEntry>>,
+    fills: DashMap<OrderId, u64>,
+    writer: Mutex<Option<Writer<File>>>,
+}
+
+impl AuditTrail {
+    pub(crate) fn entries(&self) -> Vec<AuditEntry> {
+        self.entries.read().unwrap().clone()
+    }
+
+    pub(crate) fn entry_count(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    fn next_fill_id(&self, order_id: &OrderId) -> String {
+        let mut count = self.fills.entry(order_id.clone()).or_insert(0);
+        *count += 1;
+        format!("{}-{}", order_id, *count)
+    }
+}
+
+fn audit_trail_file(folder: &str, mode: StrategyMode, account: &Account) -> String {
+    let date = Utc::now().format("%Y%m%d_%H%M").to_string();
+    format!("{}/{:?}_AuditTrail_{}_{}_{}.csv", folder, mode, account.brokerage, account.account_id, date)
+}
+
+impl Ledger {
+    pub(crate) fn position_state(&self, symbol_code: &SymbolCode) -> (PositionSide, Volume) {
+        match self.positions.get(symbol_code) {
+            Some(position) => (position.side, position.quantity_open),
+            None => (PositionSide::Flat, Decimal::ZERO),
+        }
+    }
+
+    /// Captures the position of the symbol code and the cash before an event, pass None for account events.
+    pub(crate) fn audit_snapshot(&self, symbol_code: Option<&SymbolCode>) -> AuditSnapshot {
+        let (side, quantity) = match symbol_code {
+            Some(symbol_code) => self.position_state(symbol_code),
+            None => (PositionSide::Flat, Decimal::ZERO),
+        };
+        AuditSnapshot {
+            symbol_code: symbol_code.cloned(),
+            side,
+            quantity,
+            cash_available: self.cash_available,
+            cash_used: self.cash_used,
+            booked_pnl: self.total_booked_pnl,
+        }
+    }
+
+    /// Records the mutation since the snapshot, unless the event was not a fill and nothing changed.
+    pub(crate) fn record_audit(&self, cause: AuditCause, before: AuditSnapshot) {
+        let (side_after, quantity_after) = match &before.symbol_code {
+            Some(symbol_code) => self.position_state(symbol_code),
+            None => (PositionSide::Flat, Decimal::ZERO),
+        };
+        let is_fill = cause.quantity.is_some();
+        let changed = before.side != side_after
+            || before.quantity != quantity_after
+            || before.cash_available != self.cash_available
+            || before.cash_used != self.cash_used;
+        if !is_fill && !changed {
+            return;
+        }
+        let fill_id = match (&cause.order_id, is_fill) {
+            (Some(order_id), true) => Some(self.audit_trail.next_fill_id(order_id)),
+            _ => None,
+        };
+        let mut entries = self.audit_trail.entries.write().unwrap();
+        let entry = AuditEntry {
+            sequence: entries.len() as u64 + 1,
+            account: self.account.to_string(),
+            event: cause.event,
+            order_id: cause.order_id,
+            broker_order_id: cause.broker_fill.broker_order_id,
+            fill_id,
+            event_time: cause.event_time,
+            recorded_at: Utc::now().to_string(),
+            tag: cause.tag,
+            symbol_code: before.symbol_code,
+            fill_side: cause.side,
+            fill_quantity: cause.quantity,
+            fill_price: cause.price,
+            broker_fill_id: cause.broker_fill.fill_id,
+            liquidity: cause.broker_fill.liquidity,
+            commission: cause.broker_fill.commission,
+            side_before: before.side,
+            quantity_before: before.quantity,
+            side_after,
+            quantity_after,
+            booked_pnl: self.total_booked_pnl - before.booked_pnl,
+            cash_available_before: before.cash_available,
+            cash_available_after: self.cash_available,
+            cash_used_before: before.cash_used,
+            cash_used_after: self.cash_used,
+            fingerprint: self.settings.fingerprint_hash(),
+        };
+        if self.mode != StrategyMode::Backtest {
+            self.append_audit_entry(&entry);
+        }
+        entries.push(entry);
+    }
+
+    /// Appends a live entry to the audit trail file, if an export directory is set.
+    fn append_audit_entry(&self, entry: &AuditEntry) {
+        let Some(folder) = self.settings.audit_trail_directory() else {
+            return;
+        };
+        let mut writer = self.audit_trail.writer.lock().unwrap();
+        if writer.is_none() {
+            if let Err(e) = create_dir_all(&folder) {
+                eprintln!("Failed to create directory {}: {}", folder, e);
+                return;
+            }
+            let file_name = audit_trail_file(&folder, self.mode, &self.account);
+            match Writer::from_path(Path::new(&file_name)) {
+                Ok(wtr) => *writer = Some(wtr),
+                Err(e) => {
+                    eprintln!("Failed to create CSV writer for {}: {}", file_name, e);
+                    return;
+                }
+            }
+        }
+        if let Some(wtr) = writer.as_mut() {
+            if let Err(e) = wtr.serialize(entry).and_then(|_| wtr.flush().map_err(csv::Error::from)) {
+                eprintln!("Failed to write audit trail entry for {}: {}", self.account, e);
+            }
+        }
+    }
+
+    /// The audit trail of the ledger in the order the events were applied.
+    pub fn audit_trail(&self) -> Vec<AuditEntry> {
+        self.audit_trail.entries()
+    }
+
+    // Function to export the audit trail to CSV
+    pub fn export_audit_trail_to_csv(&self, folder: &str) {
+        if let Err(e) = create_dir_all(folder) {
+            eprintln!("Failed to create directory {}: {}", folder, e);
+            return;
+        }
+
+        let file_name = audit_trail_file(folder, self.mode, &self.account);
+        let file_path = Path::new(&file_name);
+        match Writer::from_path(file_path) {
+            Ok(mut wtr) => {
+                for entry in self.audit_trail.entries.read().unwrap().iter() {
+                    if let Err(e) = wtr.serialize(entry) {
+                        eprintln!("Failed to write audit trail data to {}: {}", file_path.display(), e);
+                    }
+                }
+
+                if let Err(e) = wtr.flush() {
+                    eprintln!("Failed to flush CSV writer for {}: {}", file_path.display(), e);
+                } else {
+                    println!("Successfully exported the audit trail to {}", file_path.display());
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to create CSV writer for {}: {}", file_path.display(), e);
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::standardized_types::accounts::Currency;
+use crate::standardized_types::enums::OrderSide;
+use crate::strategies::client_features::other_requests::get_exchange_rate;
+
+/// Provides the rates used to convert pnl, margin and commissions to the account currency, see `strategy.set_conversion_rate_provider()`.
+#[async_trait]
+pub trait ConversionRateProvider: Send + Sync {
+    /// The units of `to` for one unit of `from` at the time, the `side` is the side of the trade, a buy converts at the ask and a sell at the bid.
+    async fn rate(&self, from: Currency, to: Currency, time: DateTime<Utc>, side: OrderSide) -> Result<Decimal, FundForgeError>;
+}
+
+/// Requests the rates from the data server, `get_exchange_rate()`, and reuses each rate until the interval containing the time ends.
+pub struct DataServerRates {
+    interval: Duration,
+    cache: DashMap<(Currency, Currency, OrderSide, i64), Decimal>,
+}
+
+impl DataServerRates {
+    pub fn new(interval: Duration) -> Self {
+        DataServerRates {
+            interval: interval.max(Duration::seconds(1)),
+            cache: DashMap::new(),
+        }
+    }
+
+    /// One rate per day, the default for backtests.
+    pub fn daily() -> Self {
+        Self::new(Duration::days(1))
+    }
+
+    fn interval_index(&self, time: DateTime<Utc>) -> i64 {
+        time.timestamp().div_euclid(self.interval.num_seconds())
+    }
+}
+
+#[async_trait]
+impl ConversionRateProvider for DataServerRates {
+    async fn rate(&self, from: Currency, to: Currency, time: DateTime<Utc>, side: OrderSide) -> Result<Decimal, FundForgeError> {
+        if from == to {
+            return Ok(dec!(1));
+        }
+        let key = (from, to, side, self.interval_index(time));
+        if let Some(rate) = self.cache.get(&key) {
+            return Ok(*rate.value());
+        }
+        let rate = get_exchange_rate(from, to, time, side).await?;
+        // only the current interval is kept for each pair
+        self.cache.retain(|(cached_from, cached_to, cached_side, _), _| (*cached_from, *cached_to, *cached_side) != (from, to, side));
+        self.cache.insert(key, rate);
+        Ok(rate)
+    }
+}
+
+/// Fixed rates, eg for tests or a strategy without a data server which serves fx rates, the inverse of each rate is used for the opposite conversion.
+/// ```rust
+/// let rates = FixedRates::new()
+///     .with_rate(Currency::JPY, Currency::AUD, dec!(0.0102))
+///     .with_rate(Currency::EUR, Currency::AUD, dec!(1.63));
+/// strategy.set_conversion_rate_provider(Arc::new(rates));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FixedRates {
+    rates: DashMap<(Currency, Currency), Decimal>,
+}
+
+impl FixedRates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the units of `to` for one unit of `from`.
+    pub fn with_rate(self, from: Currency, to: Currency, rate: Decimal) -> Self {
+        self.rates.insert((from, to), rate);
+        self
+    }
+}
+
+#[async_trait]
+impl ConversionRateProvider for FixedRates {
+    async fn rate(&self, from: Currency, to: Currency, _time: DateTime<Utc>, _side: OrderSide) -> Result<Decimal, FundForgeError> {
+        if from == to {
+            return Ok(dec!(1));
+        }
+        if let Some(rate) = self.rates.get(&(from, to)) {
+            return Ok(*rate.value());
+        }
+        match self.rates.get(&(to, from)) {
+            Some(rate) if *rate.value() != dec!(0) => Ok(dec!(1) / *rate.value()),
+            _ => Err(FundForgeError::ClientSideErrorDebug(format!("No fixed conversion rate for {}-{}", from, to))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fixed_rates_invert() {
+        let rates = FixedRates::new().with_rate(Currency::JPY, Currency::AUD, dec!(0.01));
+        let time = Utc::now();
+        assert_eq!(rates.rate(Currency::JPY, Currency::AUD, time, OrderSide::Buy).await.unwrap(), dec!(0.01));
+        assert_eq!(rates.rate(Currency::AUD, Currency::JPY, time, OrderSide::Buy).await.unwrap(), dec!(100));
+        assert_eq!(rates.rate(Currency::AUD, Currency::AUD, time, OrderSide::Sell).await.unwrap(), dec!(1));
+        assert!(rates.rate(Currency::EUR, Currency::AUD, time, OrderSide::Sell).await.is_err());
+    }
+
+    #[test]
+    fn test_data_server_rates_interval() {
+        let rates = DataServerRates::daily();
+        let morning = DateTime::parse_from_rfc3339("2024-06-03T01:00:00Z").unwrap().to_utc();
+        let evening = DateTime::parse_from_rfc3339("2024-06-03T23:00:00Z").unwrap().to_utc();
+        let next_day = DateTime::parse_from_rfc3339("2024-06-04T00:00:00Z").unwrap().to_utc();
+        assert_eq!(rates.interval_index(morning), rates.interval_index(evening));
+        assert_ne!(rates.interval_index(evening), rates.interval_index(next_day));
+    }
+}
@@ -0,0 +1,111 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::helpers::decimal_calculators::ceil_to_tick_size;
+use crate::standardized_types::enums::PositionSide;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::subscriptions::{SymbolCode, SymbolName};
+use crate::strategies::ledgers::ledger::Ledger;
+
+/// The fewest winning trades a stop is suggested from.
+pub const MIN_STOP_SUGGESTION_TRADES: usize = 5;
+
+/// A stop placement suggested from the mae of past winning trades, see `strategy.suggest_stop()`.
+/// # Properties
+/// * `symbol_name` - The symbol the trades were taken on.
+/// * `side` - The side of the trades.
+/// * `percentile` - The percentile of the winning trades mae the stop covers.
+/// * `distance` - The stop distance from the entry price, rounded up to the tick size.
+/// * `stop_price` - The stop price for the open position of the symbol, None if there is no open position on the side.
+/// * `winning_trades` - The number of winning trades the suggestion was made from.
+/// * `losing_trades_beyond` - The number of losing trades which went further against the entry than the distance, they would have lost less with the stop.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StopSuggestion {
+    pub symbol_name: SymbolName,
+    pub side: PositionSide,
+    pub percentile: Decimal,
+    pub distance: Price,
+    pub stop_price: Option<Price>,
+    pub winning_trades: usize,
+    pub losing_trades_beyond: usize,
+}
+
+/// The mae at the percentile (0 to 100) of the values, using the nearest rank.
+pub(crate) fn mae_percentile(mut maes: Vec<Price>, percentile: Decimal) -> Option<Price> {
+    if maes.is_empty() {
+        return None;
+    }
+    maes.sort();
+    let percentile = percentile.max(dec!(0)).min(dec!(100));
+    let rank = (percentile / dec!(100) * Decimal::from(maes.len())).ceil();
+    let index = usize::try_from(rank).unwrap_or(1).max(1) - 1;
+    maes.get(index.min(maes.len() - 1)).cloned()
+}
+
+impl Ledger {
+    /// The mae of the open position for the symbol code.
+    pub fn position_mae(&self, symbol_code: &SymbolCode) -> Option<Price> {
+        self.positions.get(symbol_code).map(|position| position.mae())
+    }
+
+    /// The mfe of the open position for the symbol code.
+    pub fn position_mfe(&self, symbol_code: &SymbolCode) -> Option<Price> {
+        self.positions.get(symbol_code).map(|position| position.mfe())
+    }
+
+    /// Suggests a stop distance from the mae of the closed winning positions of the symbol and side.
+    /// Returns None if there are fewer than `MIN_STOP_SUGGESTION_TRADES` winning positions.
+    pub fn suggest_stop(&self, symbol_name: &SymbolName, side: PositionSide, percentile: Decimal) -> Option<StopSuggestion> {
+        let mut winners = vec![];
+        let mut losers = vec![];
+        let mut tick_size = None;
+        for positions in self.positions_closed.iter() {
+            for position in positions.value().iter().filter(|position| &position.symbol_name == symbol_name && position.side == side) {
+                tick_size = Some(position.symbol_info.tick_size);
+                match position.booked_pnl > dec!(0) {
+                    true => winners.push(position.mae()),
+                    false => losers.push(position.mae()),
+                }
+            }
+        }
+        if winners.len() < MIN_STOP_SUGGESTION_TRADES {
+            return None;
+        }
+        let winning_trades = winners.len();
+        let mut distance = mae_percentile(winners, percentile)?;
+        if let Some(tick_size) = tick_size {
+            // the stop is one tick beyond the mae, a stop at the mae would have been touched
+            distance = ceil_to_tick_size(distance, tick_size) + tick_size;
+        }
+        let stop_price = self.positions.iter()
+            .find(|position| &position.symbol_name == symbol_name && position.side == side && !position.is_closed)
+            .map(|position| match side {
+                PositionSide::Long => position.average_price - distance,
+                _ => position.average_price + distance,
+            });
+        Some(StopSuggestion {
+            symbol_name: symbol_name.clone(),
+            side,
+            percentile,
+            distance,
+            stop_price,
+            winning_trades,
+            losing_trades_beyond: losers.iter().filter(|mae| **mae > distance).count(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mae_percentile() {
+        let maes = vec![dec!(5), dec!(1), dec!(3), dec!(2), dec!(4), dec!(6), dec!(8), dec!(7), dec!(10), dec!(9)];
+        assert_eq!(mae_percentile(maes.clone(), dec!(90)), Some(dec!(9)));
+        assert_eq!(mae_percentile(maes.clone(), dec!(100)), Some(dec!(10)));
+        assert_eq!(mae_percentile(maes.clone(), dec!(50)), Some(dec!(5)));
+        assert_eq!(mae_percentile(maes.clone(), dec!(0)), Some(dec!(1)));
+        assert_eq!(mae_percentile(maes, dec!(150)), Some(dec!(10)));
+        assert_eq!(mae_percentile(vec![], dec!(90)), None);
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+use std::fs::create_dir_all;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use chrono::{DateTime, Utc};
+use csv::Writer;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use crate::standardized_types::enums::OrderSide;
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::orders::{OrderId, OrderUpdateEvent};
+use crate::standardized_types::subscriptions::{SymbolCode, SymbolName};
+use crate::strategies::handlers::market_handler::price_service::{MarketPriceService, Touch};
+use crate::strategies::handlers::watchdog::HealthWarning;
+use crate::strategies::strategy_events::StrategyEvent;
+use crate::strategies::strategy_settings::StrategySettings;
+
+/// The name of the post fill hook of the fill audit.
+pub(crate) const FILL_AUDIT_HOOK: &str = "Fill Audit";
+
+/// A fill compared to the touch at its fill time.
+/// # Properties
+/// * `brokerage` - The brokerage of the account.
+/// * `account_id` - The account which was filled.
+/// * `symbol_name`, `symbol_code` - The symbol filled.
+/// * `order_id` - The order which was filled.
+/// * `side`, `quantity`, `price` - The fill.
+/// * `fill_time` - The fill time reported by the brokerage.
+/// * `touch_time` - The time of the touch the fill was compared to, None if no touch was recorded before the fill.
+/// * `bid`, `ask`, `last` - The touch.
+/// * `slippage` - The price distance from the touch, positive when the fill was worse, None if the fill was not checked.
+/// * `outside_touch` - True if the fill was more than the tolerance above the ask or below the bid.
+/// * `fingerprint` - The hash of the strategy configuration, see `strategy.fingerprint()`.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct FillCheck {
+    pub brokerage: String,
+    pub account_id: String,
+    pub symbol_name: SymbolName,
+    pub symbol_code: SymbolCode,
+    pub order_id: OrderId,
+    pub side: OrderSide,
+    pub quantity: Volume,
+    pub price: Price,
+    pub fill_time: String,
+    pub touch_time: Option<String>,
+    pub bid: Option<Price>,
+    pub ask: Option<Price>,
+    pub last: Option<Price>,
+    pub slippage: Option<Price>,
+    pub outside_touch: bool,
+    pub fingerprint: String,
+}
+
+/// The slippage of the fills of a symbol at a brokerage in an hour, see `slippage_report()`.
+/// # Properties
+/// * `hour` - The UTC hour of the fills, eg `2024-06-03 14:00`.
+/// * `fills` - The number of fills.
+/// * `checked` - The fills with a touch to compare to.
+/// * `outside_touch` - The fills flagged outside the touch.
+/// * `average_slippage` - The average slippage of the checked fills.
+/// * `max_slippage` - The worst slippage of the checked fills.
+/// * `slippage_cost` - The sum of the slippage times the quantity of the checked fills, in price points, multiply by the value per point for the cost.
+/// * `fingerprint` - The hash of the strategy configuration.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct SlippageSummary {
+    pub brokerage: String,
+    pub symbol_name: SymbolName,
+    pub hour: String,
+    pub fills: u64,
+    pub checked: u64,
+    pub outside_touch: u64,
+    pub average_slippage: Price,
+    pub max_slippage: Price,
+    pub slippage_cost: Price,
+    pub fingerprint: String,
+}
+
+/// Compares the fill to the touch, returns the slippage and whether the fill was outside the touch by more than the tolerance.
+fn compare_to_touch(side: OrderSide, price: Price, touch: &Touch, tolerance: Price) -> Option<(Price, bool)> {
+    let reference = match side {
+        OrderSide::Buy => touch.ask.or(touch.last)?,
+        OrderSide::Sell => touch.bid.or(touch.last)?,
+    };
+    let slippage = match side {
+        OrderSide::Buy => price - reference,
+        OrderSide::Sell => reference - price,
+    };
+    let outside_touch = match (touch.bid, touch.ask) {
+        (Some(bid), Some(ask)) => price > ask + tolerance || price < bid - tolerance,
+        _ => (price - reference).abs() > tolerance,
+    };
+    Some((slippage, outside_touch))
+}
+
+/// Summarizes the fills per brokerage, symbol and hour, sorted by brokerage, symbol and hour.
+pub fn slippage_report(checks: &[FillCheck]) -> Vec<SlippageSummary> {
+    let mut groups: BTreeMap<(String, SymbolName, String), Vec<&FillCheck>> = BTreeMap::new();
+    for check in checks {
+        let hour = DateTime::<Utc>::from_str(&check.fill_time)
+            .map(|time| time.format("%Y-%m-%d %H:00").to_string())
+            .unwrap_or_default();
+        groups.entry((check.brokerage.clone(), check.symbol_name.clone(), hour)).or_default().push(check);
+    }
+    groups.into_iter().map(|((brokerage, symbol_name, hour), checks)| {
+        let slippages: Vec<(Price, Volume)> = checks.iter().filter_map(|check| check.slippage.map(|slippage| (slippage, check.quantity))).collect();
+        let average_slippage = match slippages.is_empty() {
+            true => Decimal::ZERO,
+            false => slippages.iter().map(|(slippage, _)| *slippage).sum::<Price>() / Decimal::from(slippages.len()),
+        };
+        SlippageSummary {
+            brokerage,
+            symbol_name,
+            hour,
+            fills: checks.len() as u64,
+            checked: slippages.len() as u64,
+            outside_touch: checks.iter().filter(|check| check.outside_touch).count() as u64,
+            average_slippage,
+            max_slippage: slippages.iter().map(|(slippage, _)| *slippage).max().unwrap_or_default(),
+            slippage_cost: slippages.iter().map(|(slippage, quantity)| slippage * quantity).sum(),
+            fingerprint: checks.first().map(|check| check.fingerprint.clone()).unwrap_or_default(),
+        }
+    }).collect()
+}
+
+pub(crate) struct FillAuditor {
+    tolerance: RwLock<Price>,
+    checks: RwLock<Vec<FillCheck>>,
+    price_service: Arc<MarketPriceService>,
+    strategy_event_sender: mpsc::Sender<StrategyEvent>,
+    settings: Arc<StrategySettings>,
+}
+
+impl FillAuditor {
+    pub(crate) fn new(price_service: Arc<MarketPriceService>, strategy_event_sender: mpsc::Sender<StrategyEvent>, settings: Arc<StrategySettings>) -> Self {
+        FillAuditor {
+            tolerance: RwLock::new(Decimal::ZERO),
+            checks: RwLock::new(vec![]),
+            price_service,
+            strategy_event_sender,
+            settings,
+        }
+    }
+
+    pub(crate) fn set_tolerance(&self, tolerance: Price) {
+        *self.tolerance.write().unwrap() = tolerance;
+    }
+
+    pub(crate) fn checks(&self) -> Vec<FillCheck> {
+        self.checks.read().unwrap().clone()
+    }
+
+    /// Compares a fill or partial fill to the touch at its fill time, other events are ignored.
+    pub(crate) fn audit(&self, event: &OrderUpdateEvent) {
+        let (account, symbol_name, symbol_code, order_id, side, price, quantity, time) = match event {
+            OrderUpdateEvent::OrderFilled { account, symbol_name, symbol_code, order_id, side, price, quantity, time, .. }
+            | OrderUpdateEvent::OrderPartiallyFilled { account, symbol_name, symbol_code, order_id, side, price, quantity, time, .. } => {
+                (account, symbol_name, symbol_code, order_id, side, price, quantity, time)
+            }
+            _ => return,
+        };
+        let touch = DateTime::<Utc>::from_str(time).ok().and_then(|fill_time| self.price_service.touch_at(symbol_name, fill_time));
+        let tolerance = *self.tolerance.read().unwrap();
+        let comparison = touch.as_ref().and_then(|touch| compare_to_touch(*side, *price, touch, tolerance));
+        let check = FillCheck {
+            brokerage: account.brokerage.to_string(),
+            account_id: account.account_id.clone(),
+            symbol_name: symbol_name.clone(),
+            symbol_code: symbol_code.clone(),
+            order_id: order_id.clone(),
+            side: *side,
+            quantity: *quantity,
+            price: *price,
+            fill_time: time.clone(),
+            touch_time: touch.as_ref().map(|touch| touch.time.to_string()),
+            bid: touch.as_ref().and_then(|touch| touch.bid),
+            ask: touch.as_ref().and_then(|touch| touch.ask),
+            last: touch.as_ref().and_then(|touch| touch.last),
+            slippage: comparison.map(|(slippage, _)| slippage),
+            outside_touch: comparison.map_or(false, |(_, outside_touch)| outside_touch),
+            fingerprint: self.settings.fingerprint_hash(),
+        };
+        if check.outside_touch {
+            let warning = HealthWarning::FillOutsideTouch {
+                symbol_name: check.symbol_name.clone(),
+                order_id: check.order_id.clone(),
+                price: check.price,
+                bid: check.bid,
+                ask: check.ask,
+            };
+            eprintln!("{}", warning);
+            self.settings.record_risk_event(Utc::now(), warning.to_string());
+            // hooks can not wait, a full event channel drops the warning but not the check
+            if let Err(e) = self.strategy_event_sender.try_send(StrategyEvent::HealthWarning(warning)) {
+                eprintln!("Fill Audit: Failed to send event: {}", e);
+            }
+        }
+        self.checks.write().unwrap().push(check);
+    }
+
+    /// Writes the fills and the slippage report to csv files in the folder.
+    pub(crate) fn export(&self, folder: &str) {
+        if let Err(e) = create_dir_all(folder) {
+            eprintln!("Failed to create directory {}: {}", folder, e);
+            return;
+        }
+        let date = Utc::now().format("%Y%m%d_%H%M").to_string();
+        let checks = self.checks();
+        write_csv(&format!("{}/FillAudit_{}.csv", folder, date), &checks);
+        write_csv(&format!("{}/SlippageReport_{}.csv", folder, date), &slippage_report(&checks));
+    }
+}
+
+fn write_csv<T: Serialize>(file_name: &str, rows: &[T]) {
+    let file_path = Path::new(file_name);
+    match Writer::from_path(file_path) {
+        Ok(mut wtr) => {
+            for row in rows {
+                if let Err(e) = wtr.serialize(row) {
+                    eprintln!("Failed to write fill audit data to {}: {}", file_path.display(), e);
+                }
+            }
+            if let Err(e) = wtr.flush() {
+                eprintln!("Failed to flush CSV writer for {}: {}", file_path.display(), e);
+            } else {
+                println!("Successfully exported the fill audit to {}", file_path.display());
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to create CSV writer for {}: {}", file_path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn check(symbol_name: &str, fill_time: &str, quantity: Volume, slippage: Option<Price>, outside_touch: bool) -> FillCheck {
+        FillCheck {
+            brokerage: "Rithmic".to_string(),
+            account_id: "1".to_string(),
+            symbol_name: symbol_name.to_string(),
+            symbol_code: symbol_name.to_string(),
+            order_id: "1".to_string(),
+            side: OrderSide::Buy,
+            quantity,
+            price: dec!(100),
+            fill_time: fill_time.to_string(),
+            touch_time: None,
+            bid: None,
+            ask: None,
+            last: None,
+            slippage,
+            outside_touch,
+            fingerprint: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_compare_to_touch() {
+        let touch = Touch { time: Utc::now(), bid: Some(dec!(100.00)), ask: Some(dec!(100.25)), last: Some(dec!(100.25)) };
+        assert_eq!(compare_to_touch(OrderSide::Buy, dec!(100.25), &touch, dec!(0)), Some((dec!(0), false)));
+        assert_eq!(compare_to_touch(OrderSide::Buy, dec!(100.75), &touch, dec!(0.25)), Some((dec!(0.50), true)));
+        assert_eq!(compare_to_touch(OrderSide::Sell, dec!(99.75), &touch, dec!(0.25)), Some((dec!(0.25), false)));
+        // a fill better than the touch is still outside it
+        assert_eq!(compare_to_touch(OrderSide::Sell, dec!(100.75), &touch, dec!(0.25)), Some((dec!(-0.75), true)));
+
+        let last_only = Touch { bid: None, ask: None, ..touch };
+        assert_eq!(compare_to_touch(OrderSide::Sell, dec!(100.00), &last_only, dec!(0.25)), Some((dec!(0.25), false)));
+        assert_eq!(compare_to_touch(OrderSide::Sell, dec!(100.00), &Touch { last: None, ..last_only }, dec!(0.25)), None);
+    }
+
+    #[test]
+    fn test_slippage_report() {
+        let checks = vec![
+            check("MNQ", "2024-06-03 14:05:00 UTC", dec!(2), Some(dec!(0.50)), true),
+            check("MNQ", "2024-06-03 14:55:00 UTC", dec!(1), Some(dec!(0)), false),
+            check("MNQ", "2024-06-03 14:56:00 UTC", dec!(1), None, false),
+            check("MNQ", "2024-06-03 15:01:00 UTC", dec!(1), Some(dec!(0.25)), false),
+        ];
+        let report = slippage_report(&checks);
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].hour, "2024-06-03 14:00");
+        assert_eq!((report[0].fills, report[0].checked, report[0].outside_touch), (3, 2, 1));
+        assert_eq!(report[0].average_slippage, dec!(0.25));
+        assert_eq!(report[0].max_slippage, dec!(0.50));
+        assert_eq!(report[0].slippage_cost, dec!(1.00));
+        assert_eq!(report[1].hour, "2024-06-03 15:00");
+    }
+}
@@ -9,9 +9,11 @@ use crate::standardized_types::new_types::{Price, Volume};
 use crate::standardized_types::orders::{OrderId, OrderUpdateEvent};
 use crate::standardized_types::position::{Position, PositionUpdateEvent};
 use crate::standardized_types::subscriptions::{SymbolCode, SymbolName};
-use crate::strategies::client_features::other_requests::get_exchange_rate;
+use crate::strategies::ledgers::audit_trail::{AuditCause, AuditEvent, BrokerFill};
 use crate::strategies::strategy_events::StrategyEvent;
 
+const FLATTEN_ALL_ID_TAG: &str = "Flatten All"; //use this as tag and order_id
+
 impl Ledger {
     pub(crate) async fn release_margin_used(&mut self, symbol_code: &SymbolCode) {
         // First get_requests the margin amount without removing it
@@ -25,18 +27,7 @@ impl Ledger {
 
     pub(crate) async fn commit_margin(&mut self, symbol_name: &SymbolName, symbol_code: &SymbolCode, quantity: Volume, market_price: Price, time: DateTime<Utc>, side: OrderSide, base_currency: Option<Currency>, position_currency: Currency) -> Result<(), FundForgeError> {
         //eprintln!("commit_margin: {}", symbol_code);
-        let rate = if position_currency == self.currency {
-            dec!(1)
-        } else {
-            match self.rates.get(&position_currency) {
-                Some(rate) => rate.value().clone(),
-                None => {
-                    let rate = get_exchange_rate(position_currency, self.currency, time, side).await.unwrap_or_else(|_e| dec!(1));
-                    self.rates.insert(position_currency, rate);
-                    rate
-                }
-            }
-        };
+        let rate = self.pnl_conversion_rate(position_currency, time, side).await;
 
         let margin = self.account.brokerage.intraday_margin_required(symbol_name, quantity, market_price, self.currency, base_currency, position_currency, rate).await?
             .unwrap_or_else(|| quantity * market_price * rate);
@@ -73,26 +64,27 @@ impl Ledger {
         market_price: Price,
         tag: String
     ) {
+        let before = self.audit_snapshot(Some(symbol_code));
         if let Some((symbol_name, mut existing_position)) = self.positions.remove(symbol_code) {
+            let event = match order_id.as_str() {
+                FLATTEN_ALL_ID_TAG => AuditEvent::PaperFlattenAll,
+                _ => AuditEvent::PaperExit,
+            };
+            let exit_side = match existing_position.side {
+                PositionSide::Long => Some(OrderSide::Sell),
+                PositionSide::Short => Some(OrderSide::Buy),
+                PositionSide::Flat => None,
+            };
+            let cause = AuditCause { event, order_id: Some(order_id.clone()), event_time: time.to_string(), tag: tag.clone(), side: exit_side, quantity: Some(existing_position.quantity_open), price: Some(market_price), broker_fill: BrokerFill::default() };
             // Mark the position as closed
             existing_position.is_closed = true;
             self.release_margin_used(&symbol_code).await;
-            let exchange_rate = if self.currency != existing_position.symbol_info.pnl_currency {
-                let side = match existing_position.side {
-                    PositionSide::Long => OrderSide::Buy,
-                    PositionSide::Short => OrderSide::Sell,
-                    _ => unreachable!("This shouldn't happen")
-                };
-                match get_exchange_rate(self.currency, existing_position.symbol_info.pnl_currency, time, side).await {
-                    Ok(rate) => {
-                        self.rates.insert(existing_position.symbol_info.pnl_currency, rate);
-                        rate
-                    },
-                    Err(_e) => self.get_exchange_multiplier(existing_position.symbol_info.pnl_currency)
-                }
-            } else {
-                dec!(1.0)
+            let side = match existing_position.side {
+                PositionSide::Long => OrderSide::Buy,
+                PositionSide::Short => OrderSide::Sell,
+                _ => unreachable!("This shouldn't happen")
             };
+            let exchange_rate = self.pnl_conversion_rate(existing_position.symbol_info.pnl_currency, time, side).await;
             let event = existing_position.reduce_position_size(market_price, existing_position.quantity_open, order_id, self.currency, exchange_rate, time, tag).await;
             match &event {
                 PositionUpdateEvent::PositionClosed { booked_pnl, .. } => {
@@ -115,6 +107,7 @@ impl Ledger {
                 .entry(symbol_name.clone())                  // Access the entry for the symbol name
                 .or_insert_with(Vec::new)                    // If no entry exists, create a new Vec
                 .push(existing_position);     // Push the closed position to the Vec
+            self.record_audit(cause, before);
 
             self.strategy_sender.send(StrategyEvent::PositionEvents(event)).await.unwrap();
         }
@@ -123,6 +116,24 @@ impl Ledger {
     /// If Ok it will return a Position event for the successful position update, if the ledger rejects the order it will return an Err(OrderEvent)
     ///todo, check ledger max order etc before placing orders
     pub(crate) async fn update_or_create_paper_position(
+        &mut self,
+        symbol_name: SymbolName,
+        symbol_code: SymbolCode,
+        quantity: Volume,
+        side: OrderSide,
+        time: DateTime<Utc>,
+        market_fill_price: Price,
+        tag: String,
+        order_id: OrderId,
+        paper_response_sender: tokio::sync::oneshot::Sender<Option<OrderUpdateEvent>>
+    ) {
+        let before = self.audit_snapshot(Some(&symbol_code));
+        let cause = AuditCause { event: AuditEvent::PaperFill, order_id: Some(order_id.clone()), event_time: time.to_string(), tag: tag.clone(), side: Some(side), quantity: Some(quantity), price: Some(market_fill_price), broker_fill: BrokerFill::default() };
+        self.apply_paper_fill(symbol_name, symbol_code, quantity, side, time, market_fill_price, tag, order_id, paper_response_sender).await;
+        self.record_audit(cause, before);
+    }
+
+    async fn apply_paper_fill(
         &mut self,
         symbol_name: SymbolName,
         symbol_code: SymbolCode,
@@ -149,17 +160,7 @@ impl Ledger {
             if is_reducing {
                 let quantity = min(remaining_quantity, existing_position.quantity_open);
                 remaining_quantity -= existing_position.quantity_open;
-                let exchange_rate = if self.currency != existing_position.symbol_info.pnl_currency {
-                    match get_exchange_rate(self.currency, existing_position.symbol_info.pnl_currency, time, side).await {
-                        Ok(rate) => {
-                            self.rates.insert(existing_position.symbol_info.pnl_currency, rate);
-                            rate
-                        },
-                        Err(_e) => self.get_exchange_multiplier(existing_position.symbol_info.pnl_currency)
-                    }
-                } else {
-                    dec!(1.0)
-                };
+                let exchange_rate = self.pnl_conversion_rate(existing_position.symbol_info.pnl_currency, time, side).await;
 
                 let event = existing_position.reduce_position_size(market_fill_price, quantity, order_id.clone(), self.currency,exchange_rate, time, tag.clone()).await;
 
@@ -258,17 +259,7 @@ impl Ledger {
             };
 
 
-            let exchange_rate = if self.currency != info.pnl_currency {
-                match get_exchange_rate(self.currency, info.pnl_currency, time, side).await {
-                    Ok(rate) => {
-                        self.rates.insert(info.pnl_currency, rate);
-                        rate
-                    },
-                    Err(_e) => self.get_exchange_multiplier(info.pnl_currency)
-                }
-            } else {
-                dec!(1.0)
-            };
+            let exchange_rate = self.pnl_conversion_rate(info.pnl_currency, time, side).await;
             //eprintln!("symbol_code: {}, exchange_rate: {}, {}, {}", symbol_code, exchange_rate, self.currency, info.pnl_currency);
             //todo, we only need to do this for certain brokerages, I will need a better pattern..
             if symbol_name != symbol_code && !self.symbol_code_map.contains_key(&symbol_name) {
@@ -365,7 +356,6 @@ impl Ledger {
                 None => continue
             };
 
-            const FLATTEN_ALL_ID_TAG: &str = "Flatten All"; //use this as tag and order_id
             self.paper_exit_position(FLATTEN_ALL_ID_TAG.to_string(), &symbol_code, time, market_price, FLATTEN_ALL_ID_TAG.to_string()).await;
         }
     }
@@ -2,33 +2,32 @@ use std::cmp::min;
 use dashmap::DashMap;
 use tokio::sync::{oneshot};
 use rust_decimal::Decimal;
-use chrono::{DateTime, Duration, Utc};
-use std::fs::create_dir_all;
-use std::path::Path;
-use std::str::FromStr;
-use csv::Writer;
+use chrono::{DateTime, NaiveDate, Utc};
 use std::sync::Arc;
-use rust_decimal::prelude::FromPrimitive;
 use rust_decimal_macros::dec;
-use serde_derive::Serialize;
 use tokio::sync::mpsc::{Receiver, Sender};
 use uuid::Uuid;
-use crate::helpers::converters::format_duration;
+use crate::product_maps::alpaca::maps::get_equity_symbol_info;
 use crate::product_maps::oanda::maps::OANDA_SYMBOL_INFO;
 use crate::product_maps::rithmic::maps::{find_base_symbol, get_futures_symbol_info};
-use crate::standardized_types::accounts::{Account, AccountInfo, Currency};
+use crate::standardized_types::accounts::{Account, AccountInfo, AccountSummary, Currency};
 use crate::standardized_types::base_data::traits::BaseData;
 use crate::standardized_types::broker_enum::Brokerage;
 use crate::standardized_types::enums::{OrderSide, PositionSide, StrategyMode};
 use crate::standardized_types::new_types::{Price, Volume};
-use crate::standardized_types::orders::{OrderId, OrderUpdateEvent};
-use crate::standardized_types::position::{Position, PositionCalculationMode, PositionId, PositionUpdateEvent, TradeResult};
+use crate::standardized_types::options::{signed_quantity, Greeks, OptionCombo, OptionQuote, OPTION_CONTRACTS};
+use crate::standardized_types::orders::{Order, OrderId, OrderUpdateEvent};
+use crate::standardized_types::position::{Position, PositionCalculationMode, PositionId, PositionUpdateEvent};
 use crate::standardized_types::subscriptions::{SymbolCode, SymbolName};
 use crate::standardized_types::symbol_info::SymbolInfo;
 use crate::standardized_types::time_slices::TimeSlice;
-use crate::strategies::client_features::other_requests::get_exchange_rate;
 use crate::strategies::handlers::market_handler::price_service::MarketPriceService;
+use crate::strategies::ledgers::audit_trail::{AuditCause, AuditEvent, AuditTrail, BrokerFill};
+use crate::strategies::ledgers::ledger_snapshot::LedgerSnapshot;
+use crate::strategies::ledgers::preflight::OrderPreflight;
+use crate::strategies::ledgers::shortability::borrow_fee;
 use crate::strategies::strategy_events::StrategyEvent;
+use crate::strategies::strategy_settings::StrategySettings;
 
 /*
  The ledger could be split into event driven components
@@ -39,11 +38,14 @@ use crate::strategies::strategy_events::StrategyEvent;
 #[derive(Debug)]
 pub enum LedgerMessage {
     SyncPosition{symbol_name: SymbolName, symbol_code: SymbolCode, account: Account, open_quantity: f64, average_price: f64, side: PositionSide, open_pnl: f64, time: String},
-    UpdateOrCreatePosition{symbol_name: SymbolName, symbol_code: SymbolCode, quantity: Volume, side: OrderSide, time: DateTime<Utc>, market_fill_price: Price, tag: String, paper_response_sender: Option<oneshot::Sender<Option<OrderUpdateEvent>>>, order_id: OrderId},
+    UpdateOrCreatePosition{symbol_name: SymbolName, symbol_code: SymbolCode, quantity: Volume, side: OrderSide, time: DateTime<Utc>, market_fill_price: Price, tag: String, paper_response_sender: Option<oneshot::Sender<Option<OrderUpdateEvent>>>, order_id: OrderId, broker_fill: BrokerFill},
     TimeSliceUpdate{time_slice: Arc<TimeSlice>},
     LiveAccountUpdate{cash_value: Decimal, cash_available: Decimal, cash_used: Decimal},
     ExitPaperPosition{symbol_code: SymbolCode, order_id: OrderId, time: DateTime<Utc>, market_fill_price: Price, tag: String},
     PaperFlattenAll{time: DateTime<Utc>},
+    ExportAuditTrail{folder: String, sender: oneshot::Sender<()>},
+    Snapshot{sender: oneshot::Sender<LedgerSnapshot>},
+    Preflight{order: Order, time: DateTime<Utc>, sender: oneshot::Sender<OrderPreflight>},
 }
 
 /// A ledger specific to the strategy which will ignore positions not related to the strategy but will update its balances relative to the actual account balances for live trading.
@@ -67,7 +69,12 @@ pub struct Ledger {
     pub(crate) strategy_sender: Sender<StrategyEvent>,
     pub rates: Arc<DashMap<Currency, Decimal>>,
     pub position_calculation_mode: PositionCalculationMode,
-    pub market_price_service: Arc<MarketPriceService>
+    pub market_price_service: Arc<MarketPriceService>,
+    pub(crate) audit_trail: AuditTrail,
+    /// The borrow fees charged for the short positions of each symbol code, paper ledgers only.
+    pub borrow_fees: DashMap<SymbolCode, Price>,
+    pub(crate) borrow_fee_date: Option<NaiveDate>,
+    pub(crate) settings: Arc<StrategySettings>,
     //todo, add daily max loss, max order size etc to ledger
 }
 
@@ -78,8 +85,8 @@ impl Ledger {
         synchronise_accounts: bool,
         strategy_sender: Sender<StrategyEvent>,
         position_calculation_mode: PositionCalculationMode,
-        market_price_service: Arc<MarketPriceService>
-
+        market_price_service: Arc<MarketPriceService>,
+        settings: Arc<StrategySettings>,
     ) -> Self {
         let is_simulating_pnl = match synchronise_accounts {
             true => false,
@@ -121,48 +128,67 @@ impl Ledger {
             rates: Arc::new(Default::default()),
             position_calculation_mode,
             market_price_service,
+            audit_trail: AuditTrail::default(),
+            borrow_fees: DashMap::new(),
+            borrow_fee_date: None,
+            settings,
         };
         ledger
     }
 
-    /// Used to save positions to disk in json format
-    /// Useful for machine learning
-    pub fn save_positions_to_file(&self, file: &str) {
-        let mut positions = vec![];
-        for position in self.positions.iter() {
-            positions.push(position.value().clone());
+    /// The last rate received to convert the pnl currency to the account currency, 1.0 if none was received.
+    pub fn get_exchange_multiplier(&self, pnl_currency: Currency) -> Decimal {
+        if self.currency == pnl_currency {
+            return dec!(1.0);
+        }
+        match self.rates.get(&pnl_currency) {
+            Some(rate) => *rate,
+            None => dec!(1.0),
         }
-        let positions = serde_json::to_string(&positions).unwrap();
-        std::fs::write(file, positions).unwrap();
     }
 
-    pub fn get_exchange_multiplier(&self, to_currency: Currency) -> Decimal {
-        if self.currency == to_currency {
+    /// The units of the account currency for one unit of the pnl currency, from the conversion rate provider.
+    /// If the provider has no rate the last rate received is used, see `conversion_rates.rs`.
+    pub(crate) async fn pnl_conversion_rate(&self, pnl_currency: Currency, time: DateTime<Utc>, side: OrderSide) -> Decimal {
+        if self.currency == pnl_currency {
             return dec!(1.0);
         }
-
-        // Try direct rate
-        if let Some(rate) = self.rates.get(&to_currency) {
-            return *rate;
-        }
-
-        // Try inverse rate
-        if let Some(rate) = self.rates.get(&to_currency) {
-            return dec!(1.0) / *rate;
+        match self.settings.conversion_rate(pnl_currency, self.currency, time, side).await {
+            Ok(rate) => {
+                self.rates.insert(pnl_currency, rate);
+                rate
+            }
+            Err(e) => {
+                let rate = self.get_exchange_multiplier(pnl_currency);
+                eprintln!("{}: No conversion rate for {}-{}, using {}: {}", self.account, pnl_currency, self.currency, rate, e);
+                rate
+            }
         }
-
-        // Default to 1.0 if rate not found
-        dec!(1.0)
     }
 
     pub fn balance(&self) -> Decimal {
         self.cash_used + self.cash_available
     }
 
-    pub fn update(&mut self, cash_value: Decimal, cash_available: Decimal, cash_used: Decimal) {
+    /// Returns true if any of the values changed.
+    pub fn update(&mut self, cash_value: Decimal, cash_available: Decimal, cash_used: Decimal) -> bool {
+        let changed = self.cash_value != cash_value || self.cash_available != cash_available || self.cash_used != cash_used;
         self.cash_value = cash_value;
         self.cash_available = cash_available;
         self.cash_used = cash_used;
+        changed
+    }
+
+    pub fn account_summary(&self, time: DateTime<Utc>) -> AccountSummary {
+        AccountSummary {
+            account: self.account.clone(),
+            balance: self.balance(),
+            cash_value: self.cash_value,
+            buying_power: self.cash_available,
+            margin_used: self.cash_used,
+            currency: self.currency,
+            time: time.to_string(),
+        }
     }
 
     pub fn ledger_updates(&mut self, mut receiver: Receiver<LedgerMessage>, mode: StrategyMode) {
@@ -190,12 +216,12 @@ impl Ledger {
                             }
                         }*/
                     }
-                    LedgerMessage::UpdateOrCreatePosition { symbol_name, symbol_code, quantity, side, time, market_fill_price, tag , paper_response_sender, order_id} => {
+                    LedgerMessage::UpdateOrCreatePosition { symbol_name, symbol_code, quantity, side, time, market_fill_price, tag , paper_response_sender, order_id, broker_fill} => {
                         match mode {
                             StrategyMode::Backtest | StrategyMode::LivePaperTrading => static_self.update_or_create_paper_position(symbol_name, symbol_code, quantity, side, time, market_fill_price, tag, order_id.clone(), paper_response_sender.unwrap()).await,
                             StrategyMode::Live => {
                                 if static_self.is_simulating_pnl {
-                                    static_self.update_or_create_live_position(symbol_name, symbol_code, order_id, quantity, side, time, market_fill_price, tag).await
+                                    static_self.update_or_create_live_position(symbol_name, symbol_code, order_id, quantity, side, time, market_fill_price, tag, broker_fill).await
                                 }
                             }
                         };
@@ -204,7 +230,13 @@ impl Ledger {
                         static_self.timeslice_update(time_slice).await;
                     }
                     LedgerMessage::LiveAccountUpdate { cash_value, cash_available, cash_used } => {
-                        static_self.update(cash_value, cash_available, cash_used);
+                        let before = static_self.audit_snapshot(None);
+                        if static_self.update(cash_value, cash_available, cash_used) {
+                            let cause = AuditCause { event: AuditEvent::AccountUpdate, order_id: None, event_time: Utc::now().to_string(), tag: String::new(), side: None, quantity: None, price: None, broker_fill: BrokerFill::default() };
+                            static_self.record_audit(cause, before);
+                            let summary = static_self.account_summary(Utc::now());
+                            let _ = static_self.strategy_sender.send(StrategyEvent::AccountUpdate(summary)).await;
+                        }
                     }
                     LedgerMessage::ExitPaperPosition { symbol_code, order_id,time, market_fill_price, tag } => {
                         static_self.paper_exit_position(order_id, &symbol_code, time, market_fill_price, tag).await;
@@ -212,6 +244,16 @@ impl Ledger {
                     LedgerMessage::PaperFlattenAll { time } => {
                         static_self.flatten_all_for_paper_account(time).await;
                     }
+                    LedgerMessage::ExportAuditTrail { folder, sender } => {
+                        static_self.export_audit_trail_to_csv(&folder);
+                        let _ = sender.send(());
+                    }
+                    LedgerMessage::Snapshot { sender } => {
+                        let _ = sender.send(static_self.snapshot());
+                    }
+                    LedgerMessage::Preflight { order, time, sender } => {
+                        let _ = sender.send(static_self.preflight(&order, time).await);
+                    }
                 }
             }
         });
@@ -270,17 +312,7 @@ impl Ledger {
                             PositionSide::Flat => OrderSide::Sell //this is only for market price so it's not critical
                         };
                         let reduced_size = position.quantity_open - quantity;
-                        let exchange_rate = if self.currency != position.symbol_info.pnl_currency {
-                            match get_exchange_rate(self.currency, position.symbol_info.pnl_currency, Utc::now(), order_side).await {
-                                Ok(rate) => {
-                                    self.rates.insert(position.symbol_info.pnl_currency, rate);
-                                    rate
-                                },
-                                Err(_e) => self.get_exchange_multiplier(position.symbol_info.pnl_currency)
-                            }
-                        } else {
-                            dec!(1.0)
-                        };
+                        let exchange_rate = self.pnl_conversion_rate(position.symbol_info.pnl_currency, Utc::now(), order_side).await;
                         let market_price = match average_price > dec!(0) {
                             false => match price_service_request_market_price(order_side, position.symbol_name.clone(), symbol_code.clone()).await {
                                 Ok(price) => match price {
@@ -317,17 +349,7 @@ impl Ledger {
                         },
                         Err(_) => return
                     };
-                    let exchange_rate = if self.currency != position.symbol_info.pnl_currency {
-                        match get_exchange_rate(self.currency, position.symbol_info.pnl_currency, Utc::now(), order_side).await {
-                            Ok(rate) => {
-                                self.rates.insert(position.symbol_info.pnl_currency, rate);
-                                rate
-                            },
-                            Err(_e) => self.get_exchange_multiplier(position.symbol_info.pnl_currency)
-                        }
-                    } else {
-                        dec!(1.0)
-                    };
+                    let exchange_rate = self.pnl_conversion_rate(position.symbol_info.pnl_currency, Utc::now(), order_side).await;
                     let quantity = position.quantity_open.clone();
                     let event = position.reduce_position_size(market_price, quantity, "NULL".to_string(), self.currency, exchange_rate, Utc::now(), "Synchronizing Position: Reduce Size".to_string()).await;
                     self.strategy_sender.send(StrategyEvent::PositionEvents(event)).await.unwrap();
@@ -348,7 +370,7 @@ impl Ledger {
                 PositionSide::Short => OrderSide::Sell,
                 PositionSide::Flat => return
             };
-            self.update_or_create_live_position(symbol_name, symbol_code.clone(), "NULL".to_string(), quantity, order_side, Utc::now(), average_price, "Synchronizing Position: Position Opened".to_string()).await;
+            self.update_or_create_live_position(symbol_name, symbol_code.clone(), "NULL".to_string(), quantity, order_side, Utc::now(), average_price, "Synchronizing Position: Position Opened".to_string(), BrokerFill::default()).await;
             if let Some(position) = self.positions.get(&symbol_code) {
                 let event = PositionUpdateEvent::PositionOpened {
                     average_price,
@@ -404,49 +426,46 @@ impl Ledger {
         dec!(0)
     }
 
-    // Function to export closed positions to CSV
-    pub fn export_positions_to_csv(&self, folder: &str) {
-        // Create the folder if it does not exist
-        if let Err(e) = create_dir_all(folder) {
-            eprintln!("Failed to create directory {}: {}", folder, e);
-            return;
-        }
+    /// The open pnl of the combo legs held by the ledger.
+    pub fn combo_pnl(&self, combo: &OptionCombo) -> Decimal {
+        combo.legs.iter()
+            .map(|leg| self.pnl(&leg.contract.symbol_name()))
+            .sum()
+    }
 
-        // Get current date in desired format
-        let date = Utc::now().format("%Y%m%d_%H%M").to_string();
-
-        // Use brokerage and account ID to format the filename
-        let brokerage = self.account.brokerage.to_string(); // Assuming brokerage can be formatted as string
-        let file_name = format!("{}/{:?}_Results_{}_{}_{}.csv", folder, self.mode ,brokerage, self.account.account_id, date);
-
-        // Create a writer for the CSV file
-        let file_path = Path::new(&file_name);
-        match Writer::from_path(file_path) {
-            Ok(mut wtr) => {
-                // Iterate over all closed positions and write their data
-                for entry in self.positions_closed.iter() {
-                    for position in entry.value() {
-                        let export = position.to_export(); // Assuming `to_export` provides a suitable data representation
-                        if let Err(e) = wtr.serialize(export) {
-                            eprintln!("Failed to write position data to {}: {}", file_path.display(), e);
-                        }
-                    }
-                }
+    pub fn is_combo_flat(&self, combo: &OptionCombo) -> bool {
+        combo.legs.iter().all(|leg| self.is_flat(&leg.contract.symbol_name()))
+    }
 
-                // Ensure all data is flushed to the file
-                if let Err(e) = wtr.flush() {
-                    eprintln!("Failed to flush CSV writer for {}: {}", file_path.display(), e);
-                } else {
-                    println!("Successfully exported all positions to {}", file_path.display());
-                }
-            }
-            Err(e) => {
-                eprintln!("Failed to create CSV writer for {}: {}", file_path.display(), e);
-            }
+    /// The net greeks of the combo legs held by the ledger, using the vendor greeks in the `quotes`.
+    /// Legs without a quote or without greeks are not included.
+    pub fn combo_greeks(&self, combo: &OptionCombo, quotes: &[OptionQuote]) -> Greeks {
+        let mut greeks = Greeks::default();
+        for leg in &combo.legs {
+            let position = match self.positions.get(&leg.contract.symbol_name()) {
+                Some(position) => position,
+                None => continue,
+            };
+            let leg_greeks = match quotes.iter().find(|quote| quote.contract == leg.contract).and_then(|quote| quote.greeks.as_ref()) {
+                Some(leg_greeks) => leg_greeks,
+                None => continue,
+            };
+            let quantity = signed_quantity(position.side, position.quantity_open) * leg.contract.multiplier;
+            greeks.add(&leg_greeks.scaled(quantity));
         }
+        greeks
     }
 
     pub async fn symbol_info(&self, brokerage: Brokerage, symbol_name: &SymbolName) -> SymbolInfo {
+        let info = self.find_symbol_info(brokerage, symbol_name).await;
+        info.register_display_precision();
+        self.settings.with_display_precision(info)
+    }
+
+    async fn find_symbol_info(&self, brokerage: Brokerage, symbol_name: &SymbolName) -> SymbolInfo {
+        if let Some(contract) = OPTION_CONTRACTS.get(symbol_name) {
+            return contract.value().symbol_info();
+        }
         match self.mode {
             StrategyMode::Backtest => {
                 match brokerage {
@@ -465,6 +484,7 @@ impl Ledger {
                             panic!("Ledgers: Error getting symbol info: {}, {}", brokerage, symbol_name);
                         }
                     }
+                    Brokerage::Alpaca => return get_equity_symbol_info(symbol_name),
                     _ => {}
                 }
             }
@@ -514,132 +534,7 @@ impl Ledger {
     }
 
     pub fn ledger_statistics_to_string(&self) -> String {
-        let mut total_trades: usize = 0;
-        let mut losses: usize = 0;
-        let mut wins: usize = 0;
-        let mut win_pnl = dec!(0.0);
-        let mut loss_pnl = dec!(0.0);
-        let mut pnl = dec!(0.0);
-        let mut max_drawdown = dec!(0.0);
-        let mut peak = dec!(0.0);
-        let mut running_pnl = dec!(0.0);
-
-        // Track running PNL and maximum drawdown
-        for trades in self.positions_closed.iter() {
-            total_trades += trades.value().len();
-            for position in trades.value() {
-                running_pnl += position.booked_pnl;
-                if running_pnl > peak {
-                    peak = running_pnl;
-                }
-
-                let drawdown = peak - running_pnl;
-                if drawdown > max_drawdown {
-                    max_drawdown = drawdown;
-                }
-
-                if position.booked_pnl > dec!(0.0) {
-                    wins += 1;
-                    win_pnl += position.booked_pnl;
-                } else if position.booked_pnl < dec!(0.0) {
-                    losses += 1;
-                    loss_pnl += position.booked_pnl;
-                }
-                pnl += position.booked_pnl;
-            }
-        }
-
-        // Calculate average win and average loss
-        let avg_win_pnl = if wins > 0 {
-            win_pnl / Decimal::from(wins)
-        } else {
-            dec!(0.0)
-        };
-
-        let avg_loss_pnl = if losses > 0 {
-            loss_pnl / Decimal::from(losses)
-        } else {
-            dec!(0.0)
-        };
-
-        // Calculate win rate
-        let win_rate = if total_trades > 0 {
-            (Decimal::from_usize(wins).unwrap() / Decimal::from_usize(total_trades).unwrap()) * dec!(100.0)
-        } else {
-            dec!(0.0)
-        };
-
-        // Calculate risk-reward ratio
-        let risk_reward = if losses == 0 && wins > 0 {
-            avg_win_pnl
-        } else if wins == 0 && losses > 0 {
-            dec!(0.0)
-        } else if avg_loss_pnl < dec!(0.0) && avg_win_pnl > dec!(0.0) {
-            avg_win_pnl / -avg_loss_pnl
-        } else {
-            dec!(0.0)
-        };
-
-        // Calculate profit factor
-        let profit_factor = if loss_pnl != dec!(0.0) {
-            win_pnl / -loss_pnl
-        } else if win_pnl > dec!(0.0) {
-            dec!(1000.0)
-        } else {
-            dec!(0.0)
-        };
-
-        // Calculate Quality Ratio (win rate as decimal × profit factor)
-        // Provides a combined measure of consistency and profitability
-        let quality_ratio = (win_rate / dec!(100.0)) * profit_factor;
-
-        // Calculate Reward to Drawdown Ratio (total profit / maximum drawdown)
-        let pain_2_gain = if max_drawdown > dec!(0.0) {
-            pnl / max_drawdown
-        } else if pnl > dec!(0.0) {
-            dec!(1000.0) // When profitable with no drawdown
-        } else {
-            dec!(0.0)
-        };
-
-        let break_even = total_trades - wins - losses;
-        let cash_value = self.cash_value.clone();
-        let cash_used = self.cash_used.clone();
-        let cash_available = self.cash_available.clone();
-        let commission_paid = self.positions_closed.iter().fold(dec!(0.0), |acc, ref_multi| {
-            acc + ref_multi.value().iter().fold(dec!(0.0), |pos_acc, position| {
-                pos_acc + position.completed_trades.iter().fold(dec!(0.0), |trade_acc, trade| {
-                    trade_acc + trade.commissions
-                })
-            })
-        });
-        let pnl = self.total_booked_pnl.clone();
-
-        format!(
-            "Account: {}, Balance: {} {}, Win Rate: {}%, Average Risk Reward: {}, \
-         Profit Factor: {}, Quality Ratio: {},  Pain to Gain Ratio: {}, \
-         Max Drawdown: {}, Total profit: {}, Total Wins: {}, Total Losses: {}, \
-         Break Even: {}, Total Positions: {}, Open Positions: {}, \
-         Cash Used: {}, Cash Available: {}, Commission Paid: {}",
-            self.account,
-            cash_value.round_dp(2),
-            self.currency,
-            win_rate.round_dp(2),
-            risk_reward.round_dp(2),
-            profit_factor.round_dp(2),
-            quality_ratio.round_dp(2),
-            pain_2_gain.round_dp(2),
-            max_drawdown.round_dp(2),
-            pnl.round_dp(2),
-            wins,
-            losses,
-            break_even,
-            total_trades,
-            self.positions.len(),
-            cash_used.round_dp(2),
-            cash_available.round_dp(2),
-            commission_paid
-        )
+        self.snapshot().ledger_statistics_to_string()
     }
 
     pub fn generate_id(
@@ -675,6 +570,9 @@ impl Ledger {
                 if self.mode != StrategyMode::Live || self.is_simulating_pnl {
                     let open_pnl = position.update_base_data(&base_data_enum, self.currency);
                     self.open_pnl.insert(data_symbol_name.clone(), open_pnl);
+                } else {
+                    // the brokerage reports the live pnl, the excursion is still tracked for the mae and mfe
+                    position.update_excursion(&base_data_enum);
                 }
 
                 if position.is_closed {
@@ -688,10 +586,50 @@ impl Ledger {
             }
         }
         if self.mode != StrategyMode::Live {
+            if let Some(time) = time_slice.iter().map(|base_data| base_data.time_closed_utc()).max() {
+                self.charge_borrow_fees(time);
+            }
             self.cash_value = self.cash_used + self.cash_available;
         }
     }
 
+    /// Charges the borrow fee of each open short position once for each UTC day boundary it was held over since the last time update.
+    fn charge_borrow_fees(&mut self, time: DateTime<Utc>) {
+        let date = time.date_naive();
+        let days = match self.borrow_fee_date {
+            Some(last_date) if date > last_date => (date - last_date).num_days(),
+            Some(_) => return,
+            None => {
+                self.borrow_fee_date = Some(date);
+                return;
+            }
+        };
+        self.borrow_fee_date = Some(date);
+        let table = self.settings.shortability_table();
+        let shorts: Vec<(SymbolCode, Price)> = self.positions.iter()
+            .filter(|position| position.side == PositionSide::Short && !position.is_closed)
+            .filter_map(|position| {
+                let rate = table.get(&position.symbol_name).borrow_fee_rate;
+                if rate <= dec!(0) || position.symbol_info.tick_size <= dec!(0) {
+                    return None;
+                }
+                // a short is valued at the price to buy it back
+                let price = self.market_price_service.get_market_price(OrderSide::Buy, &position.symbol_name, &position.symbol_code)
+                    .unwrap_or(position.average_price);
+                let value = position.quantity_open * price / position.symbol_info.tick_size * position.symbol_info.value_per_tick * position.exchange_rate_multiplier;
+                Some((position.symbol_code.clone(), borrow_fee(value, rate, days)))
+            })
+            .collect();
+        for (symbol_code, fee) in shorts {
+            let before = self.audit_snapshot(Some(&symbol_code));
+            let cause = AuditCause { event: AuditEvent::BorrowFee, order_id: None, event_time: time.to_string(), tag: String::new(), side: None, quantity: None, price: None, broker_fill: BrokerFill::default() };
+            self.cash_available -= fee;
+            self.total_booked_pnl -= fee;
+            *self.borrow_fees.entry(symbol_code).or_insert(dec!(0)) += fee;
+            self.record_audit(cause, before);
+        }
+    }
+
     async fn update_or_create_live_position(
         &mut self,
         symbol_name: SymbolName,
@@ -701,7 +639,8 @@ impl Ledger {
         side: OrderSide,
         time: DateTime<Utc>,
         market_fill_price: Price,
-        tag: String
+        tag: String,
+        broker_fill: BrokerFill,
     ) {
     /*    if let Some(last_update) = self.last_update.get_requests(&symbol_code) {
             if last_update.value() > &time {
@@ -709,6 +648,8 @@ impl Ledger {
             }
         }*/
         self.last_update.insert(symbol_code.clone(), time);
+        let before = self.audit_snapshot(Some(&symbol_code));
+        let cause = AuditCause { event: AuditEvent::Fill, order_id: Some(order_id.clone()), event_time: time.to_string(), tag: tag.clone(), side: Some(side), quantity: Some(quantity), price: Some(market_fill_price), broker_fill };
 
         let mut position_events = vec![];
         // Check if there's an existing position for the given symbol
@@ -720,17 +661,7 @@ impl Ledger {
             if is_reducing {
                 let quantity = min(remaining_quantity, existing_position.quantity_open);
                 remaining_quantity -= existing_position.quantity_open;
-                let exchange_rate = if self.currency != existing_position.symbol_info.pnl_currency {
-                    match get_exchange_rate(self.currency, existing_position.symbol_info.pnl_currency, time, side).await {
-                        Ok(rate) => {
-                            self.rates.insert(existing_position.symbol_info.pnl_currency, rate);
-                            rate
-                        },
-                        Err(_e) => self.get_exchange_multiplier(existing_position.symbol_info.pnl_currency)
-                    }
-                } else {
-                    dec!(1.0)
-                };
+                let exchange_rate = self.pnl_conversion_rate(existing_position.symbol_info.pnl_currency, time, side).await;
                 let event= existing_position.reduce_position_size(market_fill_price, quantity, order_id.clone(), self.currency, exchange_rate, time, tag.clone()).await;
                 match &event {
                     PositionUpdateEvent::PositionReduced { booked_pnl, .. } => {
@@ -791,17 +722,7 @@ impl Ledger {
                     }
                 }
             }
-            let exchange_rate = if self.currency != info.pnl_currency {
-                match get_exchange_rate(self.currency, info.pnl_currency, time, side).await {
-                    Ok(rate) => {
-                        self.rates.insert(info.pnl_currency, rate);
-                        rate
-                    },
-                    Err(_e) => self.get_exchange_multiplier(info.pnl_currency)
-                }
-            } else {
-                dec!(1.0)
-            };
+            let exchange_rate = self.pnl_conversion_rate(info.pnl_currency, time, side).await;
 
             let id = self.generate_id(position_side);
             // Create a new position
@@ -844,6 +765,7 @@ impl Ledger {
             //println!("{:?}", event);
             position_events.push(event);
         }
+        self.record_audit(cause, before);
         for event in position_events {
             match self.strategy_sender.send(StrategyEvent::PositionEvents(event)).await {
                 Ok(_) => {}
@@ -852,214 +774,15 @@ impl Ledger {
         }
     }
 
-    // Function to export individual trades to CSV
-    pub fn export_trades_to_csv(&self, folder: &str) {
-        // Create the folder if it does not exist
-        if let Err(e) = create_dir_all(folder) {
-            eprintln!("Failed to create directory {}: {}", folder, e);
-            return;
-        }
-
-        // Get current date in desired format
-        let date = Utc::now().format("%Y%m%d_%H%M").to_string();
-
-        // Use brokerage and account ID to format the filename
-        let brokerage = self.account.brokerage.to_string();
-        let file_name = format!("{}/{:?}_TradeResults_{}_{}_{}.csv", folder, self.mode, brokerage, self.account.account_id, date);
-
-        // Create a writer for the CSV file
-        let file_path = Path::new(&file_name);
-        match Writer::from_path(file_path) {
-            Ok(mut wtr) => {
-                // Iterate over all closed positions and their trades
-                for entry in self.positions_closed.iter() {
-                    for position in entry.value() {
-                        for trade in &position.completed_trades {
-                            let export = TradeExport {
-                                symbol_code: position.symbol_code.clone(),
-                                position_id: position.position_id.clone(),
-                                side: position.side.to_string(),
-                                entry_price: trade.entry_price,
-                                entry_quantity: trade.entry_quantity,
-                                exit_price: trade.exit_price,
-                                exit_quantity: trade.exit_quantity,
-                                entry_time: trade.entry_time.clone(),
-                                exit_time: trade.exit_time.clone(),
-                                pnl: trade.profit,
-                                tag: position.tag.clone(),
-                                result: trade.result.to_string()
-                            };
-
-                            if let Err(e) = wtr.serialize(export) {
-                                eprintln!("Failed to write trade data to {}: {}", file_path.display(), e);
-                            }
-                        }
-                    }
-                }
-
-                if let Err(e) = wtr.flush() {
-                    eprintln!("Failed to flush CSV writer for {}: {}", file_path.display(), e);
-                } else {
-                    println!("Successfully exported all trades to {}", file_path.display());
-                }
-            }
-            Err(e) => {
-                eprintln!("Failed to create CSV writer for {}: {}", file_path.display(), e);
-            }
-        }
-    }
-
     pub fn trade_statistics_to_string(&self) -> String {
-        let mut total_trades: usize = 0;
-        let mut wins: usize = 0;
-        let mut losses: usize = 0;
-        let mut break_even: usize = 0;
-        let mut total_pnl = dec!(0.0);
-        let mut win_pnl = dec!(0.0);
-        let mut loss_pnl = dec!(0.0);
-        let mut longest_hold = Duration::zero();
-        let mut shortest_hold = Duration::max_value();
-        let mut total_hold_time = Duration::zero();
-        let mut largest_win = dec!(0.0);
-        let mut largest_loss = dec!(0.0);
-        let mut commission_paid = dec!(0.0);
-
-        // Collect statistics for each individual trade
-        for entry in self.positions_closed.iter() {
-            for position in entry.value() {
-                for trade in &position.completed_trades {
-                    total_trades += 1;
-                    total_pnl += trade.profit;
-                    commission_paid += trade.commissions;
-                    match trade.result {
-                        TradeResult::Win => {
-                            wins += 1;
-                            win_pnl += trade.profit;
-                            largest_win = largest_win.max(trade.profit);
-                        }
-                        TradeResult::Loss => {
-                            losses += 1;
-                            loss_pnl += trade.profit;
-                            largest_loss = largest_loss.min(trade.profit);
-                        },
-                        TradeResult::BreakEven => break_even += 1,
-                    }
-
-                    // Calculate hold time
-                    let entry_time = DateTime::<Utc>::from_str(&trade.entry_time).unwrap();
-                    let exit_time = DateTime::<Utc>::from_str(&trade.exit_time).unwrap();
-                    let hold_duration = exit_time - entry_time;
-
-                    longest_hold = longest_hold.max(hold_duration);
-                    shortest_hold = shortest_hold.min(hold_duration);
-                    total_hold_time = total_hold_time + hold_duration;
-                }
-            }
-        }
-
-        // Calculate derived statistics
-        let win_rate = if total_trades > 0 {
-            (wins as f64 / total_trades as f64 * 100.0).round()
-        } else {
-            0.0
-        };
-
-        let avg_win = if wins > 0 {
-            win_pnl / Decimal::from(wins)
-        } else {
-            dec!(0.0)
-        };
-
-        let avg_loss = if losses > 0 {
-            loss_pnl / Decimal::from(losses)
-        } else {
-            dec!(0.0)
-        };
-
-        // Calculate average risk/reward
-        let risk_reward = if avg_loss.abs() > dec!(0.0) {
-            avg_win / avg_loss.abs()
-        } else if avg_win > dec!(0.0) {
-            dec!(1000.0) // No losses, so effectively infinite R:R
-        } else {
-            dec!(0.0)
-        };
-
-        let profit_factor = if loss_pnl.abs() > dec!(0.0) {
-            win_pnl / loss_pnl.abs()
-        } else if win_pnl > dec!(0.0) {
-            dec!(1000.0)
-        } else {
-            dec!(0.0)
-        };
-
-        let avg_hold_time = if total_trades > 0 {
-            total_hold_time / total_trades as i32
-        } else {
-            Duration::zero()
-        };
-
-        format!(
-            "\nDetailed Trade Statistics:\n\
-        Total Trades: {}\n\
-        Win Rate: {}%\n\
-        Wins: {}\n\
-        Losses: {}\n\
-        Break Even: {}\n\
-        Total PnL: {}\n\
-        Win PnL: {}\n\
-        Loss PnL: {}\n\
-        Average Win: {}\n\
-        Average Loss: {}\n\
-        Average Risk/Reward: {}\n\
-        Largest Win: {}\n\
-        Largest Loss: {}\n\
-        Profit Factor: {}\n\
-        Average Hold Time: {}\n\
-        Shortest Hold: {}\n\
-        Longest Hold: {}\n\
-        Commission Paid: {}\n",
-            total_trades,
-            win_rate,
-            wins,
-            losses,
-            break_even,
-            total_pnl.round_dp(2),
-            win_pnl.round_dp(2),
-            loss_pnl.round_dp(2),
-            avg_win.round_dp(2),
-            avg_loss.round_dp(2),
-            risk_reward.round_dp(2),
-            largest_win.round_dp(2),
-            largest_loss.round_dp(2),
-            profit_factor.round_dp(2),
-            format_duration(avg_hold_time),
-            format_duration(shortest_hold),
-            format_duration(longest_hold),
-            commission_paid.round_dp(2)
-        )
+        self.snapshot().trade_statistics_to_string()
     }
 }
 
-#[derive(Debug, Serialize)]
-struct TradeExport {
-    symbol_code: String,
-    position_id: String,
-    side: String,
-    entry_price: Decimal,
-    entry_quantity: Decimal,
-    exit_price: Decimal,
-    exit_quantity: Decimal,
-    entry_time: String,
-    exit_time: String,
-    pnl: Decimal,
-    tag: String,
-    result: String,
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
+    use chrono::Duration;
     use rust_decimal_macros::dec;
     use crate::apis::rithmic::rithmic_systems::RithmicSystem;
     use crate::product_maps::rithmic::maps::get_futures_commissions_info;
@@ -1094,7 +817,8 @@ mod test {
             false,
             strategy_sender,
             PositionCalculationMode::FIFO,
-            market_price_service
+            market_price_service,
+            Arc::new(StrategySettings::new(StrategyMode::Backtest)),
         );
 
         (ledger, strategy_receiver)
@@ -1664,4 +1388,77 @@ mod test {
 
         event_handler.abort();
     }
+
+    #[tokio::test]
+    async fn test_audit_trail() {
+        let (mut ledger, mut strategy_receiver) = setup_test_ledger().await;
+        let event_handler = tokio::spawn(async move {
+            while let Some(_) = strategy_receiver.recv().await {}
+        });
+
+        let symbol_name = "NQ".to_string();
+        let symbol_code = "NQZ4".to_string();
+        let time = Utc::now();
+
+        for (order_id, quantity, side) in [("order1", dec!(1.0), OrderSide::Buy), ("order1", dec!(1.0), OrderSide::Buy), ("order2", dec!(3.0), OrderSide::Sell)] {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            ledger.update_or_create_paper_position(symbol_name.clone(), symbol_code.clone(), quantity, side, time, dec!(17500.0), "test".to_string(), order_id.to_string(), tx).await;
+            let _ = rx.await;
+        }
+        ledger.paper_exit_position("order3".to_string(), &symbol_code, time, dec!(17500.0), "exit".to_string()).await;
+
+        let trail = ledger.audit_trail();
+        let fills: Vec<(Option<String>, PositionSide, Volume, PositionSide, Volume)> = trail.iter()
+            .map(|entry| (entry.fill_id.clone(), entry.side_before, entry.quantity_before, entry.side_after, entry.quantity_after))
+            .collect();
+        assert_eq!(fills, vec![
+            (Some("order1-1".to_string()), PositionSide::Flat, dec!(0), PositionSide::Long, dec!(1.0)),
+            (Some("order1-2".to_string()), PositionSide::Long, dec!(1.0), PositionSide::Long, dec!(2.0)),
+            (Some("order2-1".to_string()), PositionSide::Long, dec!(2.0), PositionSide::Short, dec!(1.0)),
+            (Some("order3-1".to_string()), PositionSide::Short, dec!(1.0), PositionSide::Flat, dec!(0)),
+        ]);
+        assert!(trail.iter().enumerate().all(|(i, entry)| entry.sequence == i as u64 + 1 && entry.symbol_code == Some(symbol_code.clone())));
+        assert_eq!(trail[3].event, AuditEvent::PaperExit);
+        assert_eq!(trail[3].fill_side, Some(OrderSide::Buy));
+        // closing the long books the commissions
+        assert!(trail[2].booked_pnl < dec!(0));
+
+        event_handler.abort();
+    }
+
+    #[tokio::test]
+    async fn test_ledger_snapshot() {
+        let (ledger, mut strategy_receiver) = setup_test_ledger().await;
+        let event_handler = tokio::spawn(async move {
+            while let Some(_) = strategy_receiver.recv().await {}
+        });
+        let ledger: &'static mut Ledger = Box::leak(Box::new(ledger));
+        let (sender, receiver) = tokio::sync::mpsc::channel(100);
+        ledger.ledger_updates(receiver, StrategyMode::Backtest);
+
+        // the snapshot is taken after the updates already sent to the ledger
+        let mut paper_responses = vec![];
+        for (quantity, side) in [(dec!(2.0), OrderSide::Buy), (dec!(1.0), OrderSide::Sell)] {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            paper_responses.push(rx);
+            let msg = LedgerMessage::UpdateOrCreatePosition {
+                symbol_name: "NQ".to_string(), symbol_code: "NQZ4".to_string(), quantity, side, time: Utc::now(),
+                market_fill_price: dec!(17500.0), tag: "test".to_string(), paper_response_sender: Some(tx), order_id: "order1".to_string(), broker_fill: BrokerFill::default(),
+            };
+            sender.send(msg).await.unwrap();
+        }
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        sender.send(LedgerMessage::Snapshot { sender: tx }).await.unwrap();
+        let snapshot = rx.await.unwrap();
+
+        assert_eq!(snapshot.positions.len(), 1);
+        assert_eq!(snapshot.positions[0].quantity_open, dec!(1.0));
+        assert_eq!(snapshot.positions[0].completed_trades.len(), 1);
+        assert!(snapshot.open_orders.is_empty());
+        assert_eq!(snapshot.statistics.total_positions, 0);
+        assert_eq!(snapshot.summary.balance, snapshot.summary.margin_used + snapshot.summary.buying_power);
+        assert_eq!(snapshot.booked_pnl, snapshot.positions[0].booked_pnl);
+
+        event_handler.abort();
+    }
 }
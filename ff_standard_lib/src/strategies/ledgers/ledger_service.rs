@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Duration;
 use chrono::{DateTime, Utc};
 use crate::standardized_types::enums::{OrderSide, PositionSide, StrategyMode};
 use crate::standardized_types::subscriptions::{SymbolCode, SymbolName};
@@ -6,30 +7,48 @@ use dashmap::DashMap;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use tokio::sync::{oneshot};
+use tokio::task::JoinHandle;
 use crate::standardized_types::position::{Position, PositionCalculationMode};
-use crate::standardized_types::accounts::{Account, Currency};
+use crate::standardized_types::accounts::{Account, AccountSummary, Currency};
 use crate::standardized_types::broker_enum::Brokerage;
 use crate::standardized_types::new_types::{Price, Volume};
-use crate::standardized_types::orders::{OrderId, OrderUpdateEvent};
+use crate::standardized_types::options::{Greeks, OptionCombo, OptionQuote};
+use crate::standardized_types::orders::{Order, OrderId, OrderUpdateEvent};
 use crate::standardized_types::time_slices::TimeSlice;
 use crate::strategies::handlers::market_handler::price_service::MarketPriceService;
+use crate::strategies::ledgers::excursions::StopSuggestion;
+use crate::strategies::ledgers::audit_trail::{AuditEntry, AuditTrail, BrokerFill};
 use crate::strategies::ledgers::ledger::{Ledger, LedgerMessage};
+use crate::strategies::ledgers::ledger_snapshot::LedgerSnapshot;
+use crate::strategies::ledgers::preflight::OrderPreflight;
+use crate::strategies::ledgers::reconciliation::ReconciliationReport;
 use crate::strategies::strategy_events::StrategyEvent;
+use crate::strategies::strategy_settings::StrategySettings;
+use crate::strategies::trade_journal::TradeJournal;
 
 pub(crate) struct LedgerService {
     pub (crate) ledgers: DashMap<Account, &'static Ledger>,
     ledger_senders: DashMap<Account, tokio::sync::mpsc::Sender<LedgerMessage>>,
     strategy_sender: tokio::sync::mpsc::Sender<StrategyEvent>,
-    market_price_service: Arc<MarketPriceService>
+    market_price_service: Arc<MarketPriceService>,
+    trade_journal: std::sync::RwLock<Option<Arc<TradeJournal>>>,
+    account_sync: std::sync::Mutex<Option<JoinHandle<()>>>,
+    pub(crate) settings: Arc<StrategySettings>,
 }
 
+/// How often the live account balances are requested from the brokers, unless changed with `strategy.set_account_sync_interval()`.
+pub(crate) const DEFAULT_ACCOUNT_SYNC_INTERVAL: Duration = Duration::from_secs(30);
+
 impl LedgerService {
-    pub fn new(strategy_sender: tokio::sync::mpsc::Sender<StrategyEvent>, market_price_service: Arc<MarketPriceService>) -> Self {
+    pub(crate) fn new(strategy_sender: tokio::sync::mpsc::Sender<StrategyEvent>, market_price_service: Arc<MarketPriceService>, settings: Arc<StrategySettings>) -> Self {
         LedgerService {
             ledgers: Default::default(),
             ledger_senders: Default::default(),
             strategy_sender,
-            market_price_service
+            market_price_service,
+            trade_journal: std::sync::RwLock::new(None),
+            account_sync: std::sync::Mutex::new(None),
+            settings,
         }
     }
 
@@ -47,6 +66,13 @@ impl LedgerService {
         }
     }
 
+    pub async fn flatten_all_paper_accounts(&self, time: DateTime<Utc>) {
+        for sender in self.ledger_senders.iter() {
+            let msg = LedgerMessage::PaperFlattenAll{time};
+            sender.value().send(msg).await.unwrap();
+        }
+    }
+
     pub fn get_positions(&self, account: &Account) -> DashMap<SymbolCode, Vec<Position>> {
         if let Some(ledger) = self.ledgers.get(account) {
             ledger.value().positions_closed.clone()
@@ -55,10 +81,11 @@ impl LedgerService {
         }
     }
 
-    pub fn save_positions_to_file(&self, account: &Account, file_path: &str) {
-        if let Some(ledger) = self.ledgers.get(account) {
-            ledger.value().save_positions_to_file(file_path);
-        }
+    /// The open positions of the account.
+    pub fn open_positions(&self, account: &Account) -> Vec<Position> {
+        self.ledgers.get(account)
+            .map(|ledger| ledger.positions.iter().map(|position| position.value().clone()).collect())
+            .unwrap_or_default()
     }
 
     pub fn balance(&self, account: &Account) -> Decimal {
@@ -78,10 +105,11 @@ impl LedgerService {
         market_fill_price: Price, // we use the passed in price because we don't know what sort of order was filled, limit or market
         tag: String,
         paper_response_sender: Option<oneshot::Sender<Option<OrderUpdateEvent>>>,
-        order_id: OrderId
+        order_id: OrderId,
+        broker_fill: BrokerFill,
     ) {
         if let Some(sender) = self.ledger_senders.get(account) {
-            let msg = LedgerMessage::UpdateOrCreatePosition{symbol_name, symbol_code, quantity, side, time, market_fill_price, tag, paper_response_sender, order_id};
+            let msg = LedgerMessage::UpdateOrCreatePosition{symbol_name, symbol_code, quantity, side, time, market_fill_price, tag, paper_response_sender, order_id, broker_fill};
             sender.send(msg).await.unwrap();
         }
     }
@@ -107,6 +135,44 @@ impl LedgerService {
         }
     }
 
+    pub fn account_summary(&self, account: &Account) -> Option<AccountSummary> {
+        self.ledgers.get(account).map(|ledger| ledger.account_summary(Utc::now()))
+    }
+
+    /// Requests the account info of each live account from the broker on the interval and applies the balances to the ledgers,
+    /// the strategy receives `StrategyEvent::AccountUpdate` when the balances change. None stops the sync.
+    pub(crate) fn set_account_sync_interval(self: &Arc<Self>, interval: Option<Duration>) {
+        let mut account_sync = self.account_sync.lock().unwrap();
+        if let Some(task) = account_sync.take() {
+            task.abort();
+        }
+        let Some(interval) = interval else {
+            return;
+        };
+        let ledger_service = self.clone();
+        *account_sync = Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                ledger_service.sync_live_accounts().await;
+            }
+        }));
+    }
+
+    async fn sync_live_accounts(&self) {
+        let accounts: Vec<Account> = self.ledgers.iter()
+            .filter(|ledger| ledger.value().mode == StrategyMode::Live)
+            .map(|ledger| ledger.key().clone())
+            .collect();
+        for account in accounts {
+            match account.brokerage.account_info(account.account_id.clone()).await {
+                Ok(account_info) => self.live_account_updates(&account, account_info.cash_value, account_info.cash_available, account_info.cash_used).await,
+                Err(e) => eprintln!("LEDGER_SERVICE: Failed to sync account {}: {}", account, e),
+            }
+        }
+    }
+
     pub async fn live_account_updates(&self, account: &Account, cash_value: Decimal, cash_available: Decimal, cash_used: Decimal) {
         if let Some(ledger_sender) = self.ledger_senders.get(account) {
             let msg = LedgerMessage::LiveAccountUpdate{cash_value, cash_available, cash_used};
@@ -114,29 +180,60 @@ impl LedgerService {
         }
     }
 
-    pub fn print_ledgers(&self) {
-        for ledger in self.ledgers.iter() {
-            let msg = ledger.value().ledger_statistics_to_string();
-            println!("{}", msg);
+    /// A copy of the ledger taken between two ledger updates, after the updates already sent to the ledger are applied.
+    pub async fn ledger_snapshot(&self, account: &Account) -> Option<LedgerSnapshot> {
+        let (sender, receiver) = oneshot::channel();
+        {
+            let ledger_sender = self.ledger_senders.get(account)?;
+            ledger_sender.value().send(LedgerMessage::Snapshot{sender}).await.ok()?;
         }
+        receiver.await.ok()
     }
 
-    pub fn export_trades_to_csv(&self, account: &Account, directory: &str) {
-        if let Some(ledger) = self.ledgers.get(account) {
-            ledger.export_trades_to_csv(directory);
+    /// Runs the order against the ledger of its account between two ledger updates, None if the account has no ledger.
+    pub async fn preflight(&self, order: &Order, time: DateTime<Utc>) -> Option<OrderPreflight> {
+        let (sender, receiver) = oneshot::channel();
+        {
+            let ledger_sender = self.ledger_senders.get(&order.account)?;
+            ledger_sender.value().send(LedgerMessage::Preflight{order: order.clone(), time, sender}).await.ok()?;
         }
+        receiver.await.ok()
     }
 
-    pub fn export_positions_to_csv(&self, account: &Account, directory: &str) {
-        if let Some(ledger) = self.ledgers.get(account) {
-            ledger.export_positions_to_csv(directory);
+    pub fn audit_trail(&self, account: &Account) -> Vec<AuditEntry> {
+        self.ledgers.get(account)
+            .map(|ledger| ledger.audit_trail())
+            .unwrap_or_default()
+    }
+
+    /// Reconciles each ledger with its audit trail and the orders.
+    pub fn reconcile(&self, orders: &[Order]) -> ReconciliationReport {
+        let mut report = ReconciliationReport::default();
+        for ledger in self.ledgers.iter() {
+            let (fills, breaks) = ledger.reconcile(orders);
+            report.fills += fills;
+            report.breaks.extend(breaks);
         }
+        report
     }
 
-    pub fn print_trade_statistics(&self, account: &Account) {
+    pub fn export_audit_trail_to_csv(&self, account: &Account, directory: &str) {
         if let Some(ledger) = self.ledgers.get(account) {
-            let msg = ledger.trade_statistics_to_string();
-            println!("{}", msg);
+            ledger.export_audit_trail_to_csv(directory);
+        }
+    }
+
+    /// Exports the audit trail of each ledger to the directory set with `strategy.set_audit_trail_directory()`, after the ledger has applied the updates already sent to it.
+    pub async fn export_audit_trails(&self) {
+        let Some(folder) = self.settings.audit_trail_directory() else {
+            return;
+        };
+        for ledger in self.ledger_senders.iter() {
+            let (sender, receiver) = oneshot::channel();
+            let msg = LedgerMessage::ExportAuditTrail{folder: folder.clone(), sender};
+            if ledger.value().send(msg).await.is_ok() {
+                let _ = receiver.await;
+            }
         }
     }
 
@@ -148,11 +245,18 @@ impl LedgerService {
         }
     }
 
-    pub fn print_ledger(&self, account: &Account) {
-       if let Some(ledger) = self.ledgers.get(account) {
-           let string = ledger.value().ledger_statistics_to_string(); //todo need to return the string here
-           println!("{}", string);
-       }
+    pub fn borrow_fees(&self, account: &Account) -> Decimal {
+        self.ledgers.get(account)
+            .map(|ledger| ledger.borrow_fees.iter().map(|fee| *fee.value()).sum())
+            .unwrap_or_else(|| dec!(0))
+    }
+
+    /// The estimated bytes of the positions and audit entries held by the ledgers, for profiling.
+    pub(crate) fn estimated_memory(&self) -> usize {
+        self.ledgers.iter().map(|ledger| {
+            let positions = ledger.positions.len() + ledger.positions_closed.iter().map(|closed| closed.len()).sum::<usize>();
+            positions * std::mem::size_of::<Position>() + ledger.audit_trail.entry_count() * std::mem::size_of::<AuditEntry>()
+        }).sum()
     }
 
     pub async fn timeslice_updates(&self, time_slice: Arc<TimeSlice>) {
@@ -160,6 +264,19 @@ impl LedgerService {
             let update_message = LedgerMessage::TimeSliceUpdate{time_slice: time_slice.clone()};
             ledger.value().send(update_message).await.unwrap();
         }
+        self.record_trade_journal().await;
+    }
+
+    pub fn set_trade_journal(&self, trade_journal: Arc<TradeJournal>) {
+        *self.trade_journal.write().unwrap() = Some(trade_journal);
+    }
+
+    /// Writes journal entries for the positions closed since the last update, if the trade journal is enabled.
+    pub async fn record_trade_journal(&self) {
+        let trade_journal = self.trade_journal.read().unwrap().clone();
+        if let Some(trade_journal) = trade_journal {
+            trade_journal.record_closed_positions(&self.ledgers).await;
+        }
     }
 
     pub async fn init_ledger(&self, account: &Account, strategy_mode: StrategyMode, synchronize_accounts: bool, starting_cash: Decimal, currency: Currency) {
@@ -185,6 +302,7 @@ impl LedgerService {
                         self.strategy_sender.clone(),
                         position_calculation_mode,
                         self.market_price_service.clone(),
+                        self.settings.clone(),
                     ));
                     let static_ledger: &'static Ledger = Box::leak(ledger);
 
@@ -222,7 +340,10 @@ impl LedgerService {
                         rates: Arc::new(DashMap::new()),
                         position_calculation_mode,
                         market_price_service: self.market_price_service.clone(),
-
+                        audit_trail: AuditTrail::default(),
+                        borrow_fees: Default::default(),
+                        borrow_fee_date: None,
+                        settings: self.settings.clone(),
                     });
                     let static_ledger: &'static Ledger = Box::leak(ledger);
 
@@ -259,12 +380,34 @@ impl LedgerService {
             .unwrap_or(true)
     }
 
+    /// true if the account has no open positions on any symbol.
+    pub fn is_account_flat(&self, account: &Account) -> bool {
+        self.ledgers.get(account)
+            .map(|ledger| ledger.positions.is_empty())
+            .unwrap_or(true)
+    }
+
     pub fn position_size(&self, account: &Account, symbol_name: &SymbolName) -> Decimal {
         self.ledgers.get(account)
              .map(|ledger| ledger.position_size(symbol_name))
             .unwrap_or_else(|| dec!(0))
     }
 
+    pub fn position_mae(&self, account: &Account, symbol_code: &SymbolCode) -> Option<Price> {
+        self.ledgers.get(account)
+            .and_then(|ledger| ledger.position_mae(symbol_code))
+    }
+
+    pub fn position_mfe(&self, account: &Account, symbol_code: &SymbolCode) -> Option<Price> {
+        self.ledgers.get(account)
+            .and_then(|ledger| ledger.position_mfe(symbol_code))
+    }
+
+    pub fn suggest_stop(&self, account: &Account, symbol_name: &SymbolName, side: PositionSide, percentile: Decimal) -> Option<StopSuggestion> {
+        self.ledgers.get(account)
+            .and_then(|ledger| ledger.suggest_stop(symbol_name, side, percentile))
+    }
+
     pub fn open_pnl(&self, account: &Account) -> Decimal {
         self.ledgers.get(account)
              .map(|ledger| ledger.get_open_pnl())
@@ -294,5 +437,23 @@ impl LedgerService {
              .map(|ledger| ledger.in_drawdown(symbol_name))
             .unwrap_or(false)
     }
+
+    pub fn combo_pnl(&self, account: &Account, combo: &OptionCombo) -> Decimal {
+        self.ledgers.get(account)
+            .map(|ledger| ledger.combo_pnl(combo))
+            .unwrap_or_else(|| dec!(0))
+    }
+
+    pub fn is_combo_flat(&self, account: &Account, combo: &OptionCombo) -> bool {
+        self.ledgers.get(account)
+            .map(|ledger| ledger.is_combo_flat(combo))
+            .unwrap_or(true)
+    }
+
+    pub fn combo_greeks(&self, account: &Account, combo: &OptionCombo, quotes: &[OptionQuote]) -> Greeks {
+        self.ledgers.get(account)
+            .map(|ledger| ledger.combo_greeks(combo, quotes))
+            .unwrap_or_default()
+    }
 }
 
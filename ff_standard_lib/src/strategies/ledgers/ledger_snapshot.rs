@@ -0,0 +1,537 @@
+use std::fs::create_dir_all;
+use std::path::Path;
+use std::str::FromStr;
+use chrono::{DateTime, Duration, Utc};
+use csv::Writer;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal_macros::dec;
+use serde_derive::{Deserialize, Serialize};
+use crate::helpers::converters::format_duration;
+use crate::standardized_types::accounts::{Account, AccountSummary};
+use crate::standardized_types::enums::StrategyMode;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::orders::Order;
+use crate::standardized_types::position::{Position, Trade, TradeResult};
+use crate::strategies::fingerprint::StrategyFingerprint;
+use crate::strategies::handlers::market_handler::intrabar_path::IntrabarModel;
+use crate::strategies::historical_time::{clock_now, get_backtest_time};
+use crate::strategies::ledgers::ledger::Ledger;
+use crate::strategies::statistics::{BacktestRegime, RegimeStatistics};
+
+/// The statistics of the closed positions of a ledger.
+/// # Properties
+/// * `total_positions` - The number of closed positions.
+/// * `wins`, `losses`, `break_even` - The closed positions by result.
+/// * `win_rate` - The percentage of closed positions which were wins.
+/// * `risk_reward` - The average win over the average loss.
+/// * `profit_factor` - The total of the wins over the total of the losses.
+/// * `quality_ratio` - The win rate as a decimal multiplied by the profit factor.
+/// * `pain_to_gain` - The total pnl over the maximum drawdown.
+/// * `max_drawdown` - The largest drop in the running pnl of the closed positions, in the order they closed.
+/// * `commission_paid` - The commission of the completed trades.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LedgerStatistics {
+    pub total_positions: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub break_even: usize,
+    pub win_rate: Decimal,
+    pub risk_reward: Decimal,
+    pub profit_factor: Decimal,
+    pub quality_ratio: Decimal,
+    pub pain_to_gain: Decimal,
+    pub max_drawdown: Decimal,
+    pub commission_paid: Decimal,
+}
+
+impl LedgerStatistics {
+    pub(crate) fn new(positions_closed: &[Position]) -> Self {
+        let total_positions = positions_closed.len();
+        let mut losses: usize = 0;
+        let mut wins: usize = 0;
+        let mut win_pnl = dec!(0.0);
+        let mut loss_pnl = dec!(0.0);
+        let mut pnl = dec!(0.0);
+        let mut max_drawdown = dec!(0.0);
+        let mut peak = dec!(0.0);
+        let mut running_pnl = dec!(0.0);
+
+        // Track running PNL and maximum drawdown
+        for position in positions_closed {
+            running_pnl += position.booked_pnl;
+            if running_pnl > peak {
+                peak = running_pnl;
+            }
+
+            let drawdown = peak - running_pnl;
+            if drawdown > max_drawdown {
+                max_drawdown = drawdown;
+            }
+
+            if position.booked_pnl > dec!(0.0) {
+                wins += 1;
+                win_pnl += position.booked_pnl;
+            } else if position.booked_pnl < dec!(0.0) {
+                losses += 1;
+                loss_pnl += position.booked_pnl;
+            }
+            pnl += position.booked_pnl;
+        }
+
+        // Calculate average win and average loss
+        let avg_win_pnl = if wins > 0 {
+            win_pnl / Decimal::from(wins)
+        } else {
+            dec!(0.0)
+        };
+
+        let avg_loss_pnl = if losses > 0 {
+            loss_pnl / Decimal::from(losses)
+        } else {
+            dec!(0.0)
+        };
+
+        // Calculate win rate
+        let win_rate = if total_positions > 0 {
+            (Decimal::from_usize(wins).unwrap() / Decimal::from_usize(total_positions).unwrap()) * dec!(100.0)
+        } else {
+            dec!(0.0)
+        };
+
+        // Calculate risk-reward ratio
+        let risk_reward = if losses == 0 && wins > 0 {
+            avg_win_pnl
+        } else if wins == 0 && losses > 0 {
+            dec!(0.0)
+        } else if avg_loss_pnl < dec!(0.0) && avg_win_pnl > dec!(0.0) {
+            avg_win_pnl / -avg_loss_pnl
+        } else {
+            dec!(0.0)
+        };
+
+        // Calculate profit factor
+        let profit_factor = if loss_pnl != dec!(0.0) {
+            win_pnl / -loss_pnl
+        } else if win_pnl > dec!(0.0) {
+            dec!(1000.0)
+        } else {
+            dec!(0.0)
+        };
+
+        // Calculate Quality Ratio (win rate as decimal × profit factor)
+        // Provides a combined measure of consistency and profitability
+        let quality_ratio = (win_rate / dec!(100.0)) * profit_factor;
+
+        // Calculate Reward to Drawdown Ratio (total profit / maximum drawdown)
+        let pain_to_gain = if max_drawdown > dec!(0.0) {
+            pnl / max_drawdown
+        } else if pnl > dec!(0.0) {
+            dec!(1000.0) // When profitable with no drawdown
+        } else {
+            dec!(0.0)
+        };
+
+        let commission_paid = positions_closed.iter().fold(dec!(0.0), |pos_acc, position| {
+            pos_acc + position.completed_trades.iter().fold(dec!(0.0), |trade_acc, trade| {
+                trade_acc + trade.commissions
+            })
+        });
+
+        LedgerStatistics {
+            total_positions,
+            wins,
+            losses,
+            break_even: total_positions - wins - losses,
+            win_rate,
+            risk_reward,
+            profit_factor,
+            quality_ratio,
+            pain_to_gain,
+            max_drawdown,
+            commission_paid,
+        }
+    }
+}
+
+/// An immutable copy of a ledger captured between two ledger updates, with the orders working for the account.
+/// # Properties
+/// * `summary` - The balances of the account and the time the snapshot was taken, the backtest time in backtests.
+/// * `mode` - The strategy mode of the ledger.
+/// * `positions` - The open positions by symbol code.
+/// * `positions_closed` - The closed positions in the order they closed.
+/// * `open_pnl` - The open pnl of the open positions.
+/// * `booked_pnl` - The pnl booked by the ledger.
+/// * `statistics` - The statistics of the closed positions.
+/// * `open_orders` - The orders working for the account when the snapshot was taken, empty for snapshots taken from the ledger directly.
+/// * `intrabar_model` - The intrabar model the strategy was backtested with.
+/// * `fingerprint` - The configuration of the strategy run, see `strategy.fingerprint()`, None before the strategy is initialized.
+#[derive(Clone, Debug)]
+pub struct LedgerSnapshot {
+    pub summary: AccountSummary,
+    pub mode: StrategyMode,
+    pub positions: Vec<Position>,
+    pub positions_closed: Vec<Position>,
+    pub open_pnl: Price,
+    pub booked_pnl: Price,
+    pub statistics: LedgerStatistics,
+    pub open_orders: Vec<Order>,
+    pub intrabar_model: IntrabarModel,
+    pub fingerprint: Option<StrategyFingerprint>,
+}
+
+/// A completed trade as it is written by `export_trades_to_csv()`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TradeExport {
+    pub symbol_code: String,
+    pub position_id: String,
+    pub side: String,
+    pub entry_price: Decimal,
+    pub entry_quantity: Decimal,
+    pub exit_price: Decimal,
+    pub exit_quantity: Decimal,
+    pub entry_time: String,
+    pub exit_time: String,
+    pub pnl: Decimal,
+    pub tag: String,
+    pub result: String,
+    /// The hash of the strategy configuration, empty in exports written before the fingerprint was added.
+    #[serde(default)]
+    pub fingerprint: String,
+}
+
+/// The completed trades of a position.
+pub(crate) fn position_trade_exports(position: &Position, fingerprint: &str) -> Vec<TradeExport> {
+    position.completed_trades.iter()
+        .map(|trade| TradeExport {
+            symbol_code: position.symbol_code.clone(),
+            position_id: position.position_id.clone(),
+            side: position.side.to_string(),
+            entry_price: position.symbol_info.round_for_display(trade.entry_price),
+            entry_quantity: trade.entry_quantity,
+            exit_price: position.symbol_info.round_for_display(trade.exit_price),
+            exit_quantity: trade.exit_quantity,
+            entry_time: trade.entry_time.clone(),
+            exit_time: trade.exit_time.clone(),
+            pnl: trade.profit,
+            tag: position.tag.clone(),
+            result: trade.result.to_string(),
+            fingerprint: fingerprint.to_string(),
+        })
+        .collect()
+}
+
+impl Ledger {
+    /// Copies the ledger, only consistent when called by the ledger task, use `LedgerService::ledger_snapshot()` from anywhere else.
+    pub(crate) fn snapshot(&self) -> LedgerSnapshot {
+        let time = match self.mode {
+            StrategyMode::Backtest => get_backtest_time(),
+            StrategyMode::Live | StrategyMode::LivePaperTrading => clock_now(),
+        };
+        let mut positions: Vec<Position> = self.positions.iter().map(|position| position.value().clone()).collect();
+        positions.sort_by(|a, b| a.symbol_code.cmp(&b.symbol_code));
+        let mut positions_closed: Vec<Position> = self.positions_closed.iter()
+            .flat_map(|entry| entry.value().clone())
+            .collect();
+        positions_closed.sort_by_key(|position| position.close_time.as_ref().and_then(|time| DateTime::<Utc>::from_str(time).ok()));
+        let statistics = LedgerStatistics::new(&positions_closed);
+        LedgerSnapshot {
+            summary: self.account_summary(time),
+            mode: self.mode,
+            positions,
+            positions_closed,
+            open_pnl: self.get_open_pnl(),
+            booked_pnl: self.total_booked_pnl,
+            statistics,
+            open_orders: vec![],
+            intrabar_model: self.market_price_service.intrabar_model(),
+            fingerprint: self.settings.fingerprint(),
+        }
+    }
+}
+
+impl LedgerSnapshot {
+    pub fn account(&self) -> &Account {
+        &self.summary.account
+    }
+
+    /// The fingerprint hash written to the exports, empty before the strategy is initialized.
+    pub(crate) fn fingerprint_hash(&self) -> String {
+        self.fingerprint.as_ref().map(|fingerprint| fingerprint.hash.clone()).unwrap_or_default()
+    }
+
+    pub fn ledger_statistics_to_string(&self) -> String {
+        let statistics = &self.statistics;
+        // backtest results on candles depend on the assumed intrabar path
+        let intrabar_model = match self.mode {
+            StrategyMode::Backtest => format!(", Intrabar Model: {}", self.intrabar_model),
+            StrategyMode::Live | StrategyMode::LivePaperTrading => String::new(),
+        };
+
+        format!(
+            "Account: {}, Balance: {} {}, Win Rate: {}%, Average Risk Reward: {}, \
+         Profit Factor: {}, Quality Ratio: {},  Pain to Gain Ratio: {}, \
+         Max Drawdown: {}, Total profit: {}, Total Wins: {}, Total Losses: {}, \
+         Break Even: {}, Total Positions: {}, Open Positions: {}, \
+         Cash Used: {}, Cash Available: {}, Commission Paid: {}{}",
+            self.summary.account,
+            self.summary.cash_value.round_dp(2),
+            self.summary.currency,
+            statistics.win_rate.round_dp(2),
+            statistics.risk_reward.round_dp(2),
+            statistics.profit_factor.round_dp(2),
+            statistics.quality_ratio.round_dp(2),
+            statistics.pain_to_gain.round_dp(2),
+            statistics.max_drawdown.round_dp(2),
+            self.booked_pnl.round_dp(2),
+            statistics.wins,
+            statistics.losses,
+            statistics.break_even,
+            statistics.total_positions,
+            self.positions.len(),
+            self.summary.margin_used.round_dp(2),
+            self.summary.buying_power.round_dp(2),
+            statistics.commission_paid,
+            intrabar_model
+        )
+    }
+
+    /// Returns the trade statistics for each regime, trades are assigned to a regime by their exit time.
+    pub fn regime_statistics(&self, regimes: &Vec<BacktestRegime>) -> Vec<RegimeStatistics> {
+        let trades: Vec<&Trade> = self.positions_closed.iter()
+            .flat_map(|position| position.completed_trades.iter())
+            .collect();
+        regimes.iter()
+            .map(|regime| RegimeStatistics::new(regime, trades.iter().copied()))
+            .collect()
+    }
+
+    pub fn regime_statistics_to_string(&self, regimes: &Vec<BacktestRegime>) -> String {
+        let mut report = format!("\nRegime Statistics: {}", self.summary.account);
+        for statistics in self.regime_statistics(regimes) {
+            report.push_str(&format!("\n{}", statistics));
+        }
+        report
+    }
+
+    pub fn trade_statistics_to_string(&self) -> String {
+        let mut total_trades: usize = 0;
+        let mut wins: usize = 0;
+        let mut losses: usize = 0;
+        let mut break_even: usize = 0;
+        let mut total_pnl = dec!(0.0);
+        let mut win_pnl = dec!(0.0);
+        let mut loss_pnl = dec!(0.0);
+        let mut longest_hold = Duration::zero();
+        let mut shortest_hold = Duration::max_value();
+        let mut total_hold_time = Duration::zero();
+        let mut largest_win = dec!(0.0);
+        let mut largest_loss = dec!(0.0);
+        let mut commission_paid = dec!(0.0);
+
+        // Collect statistics for each individual trade
+        for position in &self.positions_closed {
+            for trade in &position.completed_trades {
+                total_trades += 1;
+                total_pnl += trade.profit;
+                commission_paid += trade.commissions;
+                match trade.result {
+                    TradeResult::Win => {
+                        wins += 1;
+                        win_pnl += trade.profit;
+                        largest_win = largest_win.max(trade.profit);
+                    }
+                    TradeResult::Loss => {
+                        losses += 1;
+                        loss_pnl += trade.profit;
+                        largest_loss = largest_loss.min(trade.profit);
+                    },
+                    TradeResult::BreakEven => break_even += 1,
+                }
+
+                // Calculate hold time
+                let entry_time = DateTime::<Utc>::from_str(&trade.entry_time).unwrap();
+                let exit_time = DateTime::<Utc>::from_str(&trade.exit_time).unwrap();
+                let hold_duration = exit_time - entry_time;
+
+                longest_hold = longest_hold.max(hold_duration);
+                shortest_hold = shortest_hold.min(hold_duration);
+                total_hold_time = total_hold_time + hold_duration;
+            }
+        }
+
+        // Calculate derived statistics
+        let win_rate = if total_trades > 0 {
+            (wins as f64 / total_trades as f64 * 100.0).round()
+        } else {
+            0.0
+        };
+
+        let avg_win = if wins > 0 {
+            win_pnl / Decimal::from(wins)
+        } else {
+            dec!(0.0)
+        };
+
+        let avg_loss = if losses > 0 {
+            loss_pnl / Decimal::from(losses)
+        } else {
+            dec!(0.0)
+        };
+
+        // Calculate average risk/reward
+        let risk_reward = if avg_loss.abs() > dec!(0.0) {
+            avg_win / avg_loss.abs()
+        } else if avg_win > dec!(0.0) {
+            dec!(1000.0) // No losses, so effectively infinite R:R
+        } else {
+            dec!(0.0)
+        };
+
+        let profit_factor = if loss_pnl.abs() > dec!(0.0) {
+            win_pnl / loss_pnl.abs()
+        } else if win_pnl > dec!(0.0) {
+            dec!(1000.0)
+        } else {
+            dec!(0.0)
+        };
+
+        let avg_hold_time = if total_trades > 0 {
+            total_hold_time / total_trades as i32
+        } else {
+            Duration::zero()
+        };
+
+        format!(
+            "\nDetailed Trade Statistics:\n\
+        Total Trades: {}\n\
+        Win Rate: {}%\n\
+        Wins: {}\n\
+        Losses: {}\n\
+        Break Even: {}\n\
+        Total PnL: {}\n\
+        Win PnL: {}\n\
+        Loss PnL: {}\n\
+        Average Win: {}\n\
+        Average Loss: {}\n\
+        Average Risk/Reward: {}\n\
+        Largest Win: {}\n\
+        Largest Loss: {}\n\
+        Profit Factor: {}\n\
+        Average Hold Time: {}\n\
+        Shortest Hold: {}\n\
+        Longest Hold: {}\n\
+        Commission Paid: {}\n",
+            total_trades,
+            win_rate,
+            wins,
+            losses,
+            break_even,
+            total_pnl.round_dp(2),
+            win_pnl.round_dp(2),
+            loss_pnl.round_dp(2),
+            avg_win.round_dp(2),
+            avg_loss.round_dp(2),
+            risk_reward.round_dp(2),
+            largest_win.round_dp(2),
+            largest_loss.round_dp(2),
+            profit_factor.round_dp(2),
+            format_duration(avg_hold_time),
+            format_duration(shortest_hold),
+            format_duration(longest_hold),
+            commission_paid.round_dp(2)
+        )
+    }
+
+    /// Used to save the open positions to disk in json format
+    /// Useful for machine learning
+    pub fn save_positions_to_file(&self, file: &str) {
+        let positions = serde_json::to_string(&self.positions).unwrap();
+        std::fs::write(file, positions).unwrap();
+    }
+
+    // Function to export closed positions to CSV
+    pub fn export_positions_to_csv(&self, folder: &str) {
+        // Create the folder if it does not exist
+        if let Err(e) = create_dir_all(folder) {
+            eprintln!("Failed to create directory {}: {}", folder, e);
+            return;
+        }
+
+        // Get current date in desired format
+        let date = Utc::now().format("%Y%m%d_%H%M").to_string();
+
+        // Use brokerage and account ID to format the filename
+        let account = self.account();
+        let file_name = format!("{}/{:?}_Results_{}_{}_{}.csv", folder, self.mode, account.brokerage, account.account_id, date);
+
+        // Create a writer for the CSV file
+        let file_path = Path::new(&file_name);
+        match Writer::from_path(file_path) {
+            Ok(mut wtr) => {
+                // Iterate over all closed positions and write their data
+                let fingerprint = self.fingerprint_hash();
+                for position in &self.positions_closed {
+                    let export = position.to_export(&fingerprint);
+                    if let Err(e) = wtr.serialize(export) {
+                        eprintln!("Failed to write position data to {}: {}", file_path.display(), e);
+                    }
+                }
+
+                // Ensure all data is flushed to the file
+                if let Err(e) = wtr.flush() {
+                    eprintln!("Failed to flush CSV writer for {}: {}", file_path.display(), e);
+                } else {
+                    println!("Successfully exported all positions to {}", file_path.display());
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to create CSV writer for {}: {}", file_path.display(), e);
+            }
+        }
+    }
+
+    /// The completed trades of the closed positions, in the order the positions closed.
+    /// Prices are rounded to the display precision of the symbol.
+    pub fn trade_exports(&self) -> Vec<TradeExport> {
+        let fingerprint = self.fingerprint_hash();
+        self.positions_closed.iter().flat_map(|position| position_trade_exports(position, &fingerprint)).collect()
+    }
+
+    // Function to export individual trades to CSV
+    pub fn export_trades_to_csv(&self, folder: &str) {
+        // Create the folder if it does not exist
+        if let Err(e) = create_dir_all(folder) {
+            eprintln!("Failed to create directory {}: {}", folder, e);
+            return;
+        }
+
+        // Get current date in desired format
+        let date = Utc::now().format("%Y%m%d_%H%M").to_string();
+
+        // Use brokerage and account ID to format the filename
+        let account = self.account();
+        let file_name = format!("{}/{:?}_TradeResults_{}_{}_{}.csv", folder, self.mode, account.brokerage, account.account_id, date);
+
+        // Create a writer for the CSV file
+        let file_path = Path::new(&file_name);
+        match Writer::from_path(file_path) {
+            Ok(mut wtr) => {
+                for export in self.trade_exports() {
+                    if let Err(e) = wtr.serialize(export) {
+                        eprintln!("Failed to write trade data to {}: {}", file_path.display(), e);
+                    }
+                }
+
+                if let Err(e) = wtr.flush() {
+                    eprintln!("Failed to flush CSV writer for {}: {}", file_path.display(), e);
+                } else {
+                    println!("Successfully exported all trades to {}", file_path.display());
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to create CSV writer for {}: {}", file_path.display(), e);
+            }
+        }
+    }
+}
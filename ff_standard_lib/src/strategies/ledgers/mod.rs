@@ -1,3 +1,12 @@
+pub mod aggregate_statistics;
+pub mod audit_trail;
+pub mod conversion_rates;
+pub mod excursions;
+pub mod fill_audit;
 pub mod ledger;
 pub mod ledger_service;
+pub mod ledger_snapshot;
+pub mod preflight;
+pub mod reconciliation;
+pub mod shortability;
 pub(crate) mod historical_ledger;
\ No newline at end of file
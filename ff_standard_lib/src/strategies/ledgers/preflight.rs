@@ -0,0 +1,225 @@
+use chrono::{DateTime, Utc};
+use rust_decimal_macros::dec;
+use crate::standardized_types::enums::{OrderSide, PositionSide};
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::orders::{Order, OrderError, OrderId, OrderType};
+use crate::strategies::ledgers::ledger::Ledger;
+
+/// What would happen if an order was filled now, see `strategy.preflight()`.
+/// # Properties
+/// * `order_id` - The id of the order checked.
+/// * `estimated_price` - The limit price, the trigger price for stop and touched orders, or the estimated market fill price. None if the symbol has no market price yet.
+/// * `margin_required` - The margin committed for the quantity the order would open.
+/// * `margin_released` - The margin released for the quantity the order would close.
+/// * `cash_available` - The cash available before the order.
+/// * `cash_available_after` - The cash available after the margin is released and committed.
+/// * `position_side`, `position_quantity` - The position of the symbol code before the order.
+/// * `resulting_side`, `resulting_quantity` - The position of the symbol code after the order.
+/// * `refusal` - Why the order would be refused, None if the order would be sent and the account could margin it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderPreflight {
+    pub order_id: OrderId,
+    pub estimated_price: Option<Price>,
+    pub margin_required: Price,
+    pub margin_released: Price,
+    pub cash_available: Price,
+    pub cash_available_after: Price,
+    pub position_side: PositionSide,
+    pub position_quantity: Volume,
+    pub resulting_side: PositionSide,
+    pub resulting_quantity: Volume,
+    pub refusal: Option<OrderError>,
+}
+
+impl OrderPreflight {
+    /// A preflight for an order which could not be run against a ledger.
+    pub(crate) fn refused(order: &Order, refusal: OrderError) -> Self {
+        OrderPreflight {
+            order_id: order.id.clone(),
+            estimated_price: None,
+            margin_required: dec!(0),
+            margin_released: dec!(0),
+            cash_available: dec!(0),
+            cash_available_after: dec!(0),
+            position_side: PositionSide::Flat,
+            position_quantity: dec!(0),
+            resulting_side: PositionSide::Flat,
+            resulting_quantity: dec!(0),
+            refusal: Some(refusal),
+        }
+    }
+
+    /// true if the order would be sent and the account could margin it.
+    pub fn is_allowed(&self) -> bool {
+        self.refusal.is_none()
+    }
+}
+
+/// The quantity an order closes and opens, and the resulting position.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PositionChange {
+    pub(crate) closed: Volume,
+    pub(crate) opened: Volume,
+    pub(crate) side: PositionSide,
+    pub(crate) quantity: Volume,
+}
+
+/// Applies the order to the position the way the backtest engine fills the order type.
+/// Returns `OrderError::InvalidQuantity` for an exit order with no position on that side to exit.
+pub(crate) fn position_change(order_type: &OrderType, order_side: OrderSide, quantity: Volume, side: PositionSide, open_quantity: Volume) -> Result<PositionChange, OrderError> {
+    let (order_position_side, opposite_side) = match order_side {
+        OrderSide::Buy => (PositionSide::Long, PositionSide::Short),
+        OrderSide::Sell => (PositionSide::Short, PositionSide::Long),
+    };
+    let open_quantity = match side {
+        PositionSide::Flat => dec!(0),
+        _ => open_quantity,
+    };
+    match order_type {
+        OrderType::ExitLong | OrderType::ExitShort => {
+            let exit_side = match order_type {
+                OrderType::ExitLong => PositionSide::Long,
+                _ => PositionSide::Short,
+            };
+            if side != exit_side || open_quantity <= dec!(0) {
+                return Err(OrderError::InvalidQuantity);
+            }
+            let closed = quantity.min(open_quantity);
+            let remaining = open_quantity - closed;
+            Ok(PositionChange {
+                closed,
+                opened: dec!(0),
+                side: match remaining > dec!(0) {
+                    true => side,
+                    false => PositionSide::Flat,
+                },
+                quantity: remaining,
+            })
+        }
+        // entries close the opposite position in full before opening the quantity
+        OrderType::EnterLong | OrderType::EnterShort => {
+            let (closed, quantity) = match side == opposite_side {
+                true => (open_quantity, quantity),
+                false => (dec!(0), open_quantity + quantity),
+            };
+            Ok(PositionChange { closed, opened: quantity - open_quantity + closed, side: order_position_side, quantity })
+        }
+        // other orders reduce the opposite position and reverse with the remaining quantity
+        _ => {
+            if side != opposite_side {
+                return Ok(PositionChange { closed: dec!(0), opened: quantity, side: order_position_side, quantity: open_quantity + quantity });
+            }
+            let closed = quantity.min(open_quantity);
+            let opened = quantity - closed;
+            let (side, quantity) = if opened > dec!(0) {
+                (order_position_side, opened)
+            } else if open_quantity > closed {
+                (side, open_quantity - closed)
+            } else {
+                (PositionSide::Flat, dec!(0))
+            };
+            Ok(PositionChange { closed, opened, side, quantity })
+        }
+    }
+}
+
+impl Ledger {
+    /// Runs the order against the ledger without changing it.
+    pub(crate) async fn preflight(&self, order: &Order, time: DateTime<Utc>) -> OrderPreflight {
+        let (position_side, position_quantity) = match self.positions.get(&order.symbol_code) {
+            Some(position) => (position.side, position.quantity_open),
+            None => (PositionSide::Flat, dec!(0)),
+        };
+        let mut preflight = OrderPreflight {
+            order_id: order.id.clone(),
+            estimated_price: None,
+            margin_required: dec!(0),
+            margin_released: dec!(0),
+            cash_available: self.cash_available,
+            cash_available_after: self.cash_available,
+            position_side,
+            position_quantity,
+            resulting_side: position_side,
+            resulting_quantity: position_quantity,
+            refusal: None,
+        };
+        let change = match position_change(&order.order_type, order.side, order.quantity_open, position_side, position_quantity) {
+            Ok(change) => change,
+            Err(e) => {
+                preflight.refusal = Some(e);
+                return preflight;
+            }
+        };
+        preflight.resulting_side = change.side;
+        preflight.resulting_quantity = change.quantity;
+
+        let estimated_price = match order.order_type {
+            OrderType::Limit | OrderType::StopLimit => order.limit_price,
+            OrderType::StopMarket | OrderType::MarketIfTouched => order.trigger_price,
+            _ => self.market_price_service.estimate_fill_price(order.side, &order.symbol_name, &order.symbol_code, order.quantity_open),
+        };
+        preflight.estimated_price = estimated_price;
+
+        if change.closed > dec!(0) && position_quantity > dec!(0) {
+            if let Some(margin_used) = self.margin_used.get(&order.symbol_code) {
+                preflight.margin_released = *margin_used.value() * change.closed / position_quantity;
+            }
+        }
+        if change.opened > dec!(0) {
+            let Some(price) = estimated_price else {
+                // without a price the margin can not be estimated, the order would still be sent
+                preflight.cash_available_after = self.cash_available + preflight.margin_released;
+                return preflight;
+            };
+            let info = self.symbol_info(self.account.brokerage, &order.symbol_name).await;
+            let rate = self.pnl_conversion_rate(info.pnl_currency, time, order.side).await;
+            preflight.margin_required = match self.account.brokerage.intraday_margin_required(&order.symbol_name, change.opened, price, self.currency, info.base_currency, info.pnl_currency, rate).await {
+                Ok(Some(margin)) => margin,
+                _ => change.opened * price * rate,
+            };
+        }
+        preflight.cash_available_after = self.cash_available + preflight.margin_released - preflight.margin_required;
+        if preflight.cash_available_after < dec!(0) {
+            preflight.refusal = Some(OrderError::InsufficientFunds);
+        }
+        preflight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_closes_the_opposite_position() {
+        let change = position_change(&OrderType::EnterLong, OrderSide::Buy, dec!(2), PositionSide::Short, dec!(3)).unwrap();
+        assert_eq!(change, PositionChange { closed: dec!(3), opened: dec!(2), side: PositionSide::Long, quantity: dec!(2) });
+
+        let change = position_change(&OrderType::EnterLong, OrderSide::Buy, dec!(2), PositionSide::Long, dec!(3)).unwrap();
+        assert_eq!(change, PositionChange { closed: dec!(0), opened: dec!(2), side: PositionSide::Long, quantity: dec!(5) });
+    }
+
+    #[test]
+    fn test_exit_is_capped_at_the_position() {
+        let change = position_change(&OrderType::ExitLong, OrderSide::Sell, dec!(5), PositionSide::Long, dec!(3)).unwrap();
+        assert_eq!(change, PositionChange { closed: dec!(3), opened: dec!(0), side: PositionSide::Flat, quantity: dec!(0) });
+
+        let change = position_change(&OrderType::ExitShort, OrderSide::Buy, dec!(1), PositionSide::Short, dec!(3)).unwrap();
+        assert_eq!(change, PositionChange { closed: dec!(1), opened: dec!(0), side: PositionSide::Short, quantity: dec!(2) });
+
+        assert_eq!(position_change(&OrderType::ExitLong, OrderSide::Sell, dec!(1), PositionSide::Short, dec!(3)), Err(OrderError::InvalidQuantity));
+        assert_eq!(position_change(&OrderType::ExitShort, OrderSide::Buy, dec!(1), PositionSide::Flat, dec!(0)), Err(OrderError::InvalidQuantity));
+    }
+
+    #[test]
+    fn test_market_order_reverses_the_position() {
+        let change = position_change(&OrderType::Market, OrderSide::Sell, dec!(5), PositionSide::Long, dec!(3)).unwrap();
+        assert_eq!(change, PositionChange { closed: dec!(3), opened: dec!(2), side: PositionSide::Short, quantity: dec!(2) });
+
+        let change = position_change(&OrderType::Limit, OrderSide::Sell, dec!(1), PositionSide::Long, dec!(3)).unwrap();
+        assert_eq!(change, PositionChange { closed: dec!(1), opened: dec!(0), side: PositionSide::Long, quantity: dec!(2) });
+
+        let change = position_change(&OrderType::Market, OrderSide::Sell, dec!(3), PositionSide::Long, dec!(3)).unwrap();
+        assert_eq!(change, PositionChange { closed: dec!(3), opened: dec!(0), side: PositionSide::Flat, quantity: dec!(0) });
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::fmt;
+use rust_decimal::Decimal;
+use crate::standardized_types::accounts::Account;
+use crate::standardized_types::enums::PositionSide;
+use crate::standardized_types::new_types::Volume;
+use crate::standardized_types::orders::Order;
+use crate::standardized_types::subscriptions::SymbolCode;
+use crate::strategies::ledgers::audit_trail::{AuditEntry, AuditEvent};
+use crate::strategies::ledgers::ledger::Ledger;
+
+/// A difference found by the reconciliation.
+/// # Properties
+/// * `account` - The account of the ledger.
+/// * `symbol_code` - The symbol code of the position, None if the break is not for a position.
+/// * `reason` - What did not reconcile.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReconciliationBreak {
+    pub account: Account,
+    pub symbol_code: Option<SymbolCode>,
+    pub reason: String,
+}
+
+impl fmt::Display for ReconciliationBreak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.symbol_code {
+            Some(symbol_code) => write!(f, "{} {}: {}", self.account, symbol_code, self.reason),
+            None => write!(f, "{}: {}", self.account, self.reason),
+        }
+    }
+}
+
+/// The reconciliation of the strategy ledgers, see `strategy.reconcile()`.
+/// # Properties
+/// * `fills` - The number of fills in the audit trails.
+/// * `breaks` - The differences found, empty if the ledgers reconcile.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReconciliationReport {
+    pub fills: usize,
+    pub breaks: Vec<ReconciliationBreak>,
+}
+
+impl ReconciliationReport {
+    pub fn is_reconciled(&self) -> bool {
+        self.breaks.is_empty()
+    }
+}
+
+/// Reconciles the audit trail entries with the current positions and the orders of the account.
+pub(crate) fn reconcile_trail(
+    account: &Account,
+    entries: &[AuditEntry],
+    position_state: impl Fn(&SymbolCode) -> (PositionSide, Volume),
+    orders: &[Order],
+) -> Vec<ReconciliationBreak> {
+    let mut breaks = vec![];
+    let mut last_state: HashMap<&SymbolCode, (PositionSide, Volume)> = HashMap::new();
+    for entry in entries {
+        let Some(symbol_code) = &entry.symbol_code else {
+            continue;
+        };
+        let (side, quantity) = last_state.get(symbol_code).cloned().unwrap_or((PositionSide::Flat, Decimal::ZERO));
+        if entry.side_before != side || entry.quantity_before != quantity {
+            breaks.push(ReconciliationBreak {
+                account: account.clone(),
+                symbol_code: Some(symbol_code.clone()),
+                reason: format!(
+                    "entry {} starts at {} {} but the previous entry left {} {}",
+                    entry.sequence, entry.side_before, entry.quantity_before, side, quantity
+                ),
+            });
+        }
+        last_state.insert(symbol_code, (entry.side_after, entry.quantity_after));
+    }
+    for (symbol_code, (side, quantity)) in last_state {
+        let (ledger_side, ledger_quantity) = position_state(symbol_code);
+        if ledger_side != side || ledger_quantity != quantity {
+            breaks.push(ReconciliationBreak {
+                account: account.clone(),
+                symbol_code: Some(symbol_code.clone()),
+                reason: format!("the trail ends at {} {} but the ledger is {} {}", side, quantity, ledger_side, ledger_quantity),
+            });
+        }
+    }
+    for order in orders.iter().filter(|order| &order.account == account && order.quantity_filled > Decimal::ZERO) {
+        if !entries.iter().any(|entry| entry.order_id.as_ref() == Some(&order.id)) {
+            breaks.push(ReconciliationBreak {
+                account: account.clone(),
+                symbol_code: Some(order.symbol_code.clone()),
+                reason: format!("order {} filled {} but the ledger has no entry for it", order.id, order.quantity_filled),
+            });
+        }
+    }
+    breaks
+}
+
+impl Ledger {
+    /// Reconciles the ledger with its audit trail and the orders, returns the number of fills and the breaks.
+    pub(crate) fn reconcile(&self, orders: &[Order]) -> (usize, Vec<ReconciliationBreak>) {
+        let entries = self.audit_trail();
+        let fills = entries.iter()
+            .filter(|entry| entry.fill_quantity.is_some() && matches!(entry.event, AuditEvent::Fill | AuditEvent::PaperFill))
+            .count();
+        (fills, reconcile_trail(&self.account, &entries, |symbol_code| self.position_state(symbol_code), orders))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::broker_enum::Brokerage;
+    use crate::standardized_types::enums::OrderSide;
+
+    fn account() -> Account {
+        Account::new(Brokerage::Test, "Test_Account_1".to_string())
+    }
+
+    fn entry(sequence: u64, order_id: &str, before: (PositionSide, Volume), after: (PositionSide, Volume)) -> AuditEntry {
+        AuditEntry {
+            sequence,
+            account: account().to_string(),
+            event: AuditEvent::PaperFill,
+            order_id: Some(order_id.to_string()),
+            broker_order_id: None,
+            fill_id: Some(format!("{}-1", order_id)),
+            event_time: Utc::now().to_string(),
+            recorded_at: Utc::now().to_string(),
+            tag: "Entry".to_string(),
+            symbol_code: Some("EUR-USD".to_string()),
+            fill_side: Some(OrderSide::Buy),
+            fill_quantity: Some(dec!(1)),
+            fill_price: Some(dec!(1.1)),
+            broker_fill_id: None,
+            liquidity: None,
+            commission: None,
+            side_before: before.0,
+            quantity_before: before.1,
+            side_after: after.0,
+            quantity_after: after.1,
+            booked_pnl: dec!(0),
+            cash_available_before: dec!(0),
+            cash_available_after: dec!(0),
+            cash_used_before: dec!(0),
+            cash_used_after: dec!(0),
+            fingerprint: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_reconcile_trail() {
+        let entries = vec![
+            entry(1, "1", (PositionSide::Flat, dec!(0)), (PositionSide::Long, dec!(1))),
+            entry(2, "2", (PositionSide::Long, dec!(1)), (PositionSide::Long, dec!(2))),
+        ];
+        let mut order = Order::market_order("EUR-USD".to_string(), None, &account(), dec!(1), OrderSide::Buy, "Entry".to_string(), "2".to_string(), Utc::now(), None);
+        order.quantity_filled = dec!(1);
+        let long_2 = |_: &SymbolCode| (PositionSide::Long, dec!(2));
+        assert!(reconcile_trail(&account(), &entries, long_2, &[order.clone()]).is_empty());
+
+        // the ledger moved without an entry
+        let long_3 = |_: &SymbolCode| (PositionSide::Long, dec!(3));
+        assert_eq!(reconcile_trail(&account(), &entries, long_3, &[]).len(), 1);
+
+        // an entry which does not start where the last one ended, and a fill the ledger never saw
+        let gap = vec![entries[0].clone(), entry(2, "3", (PositionSide::Long, dec!(2)), (PositionSide::Long, dec!(3)))];
+        order.id = "4".to_string();
+        let breaks = reconcile_trail(&account(), &gap, long_3, &[order]);
+        assert_eq!(breaks.len(), 2);
+        assert!(breaks[0].reason.starts_with("entry 2"));
+        assert!(breaks[1].reason.starts_with("order 4"));
+    }
+}
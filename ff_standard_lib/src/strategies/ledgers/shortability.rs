@@ -0,0 +1,131 @@
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::standardized_types::enums::OrderSide;
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::orders::OrderError;
+use crate::standardized_types::subscriptions::SymbolName;
+
+/// The day count of the annual borrow fee rates, brokerages charge equity borrow fees on a 360 day year.
+pub const BORROW_FEE_DAYS_PER_YEAR: Decimal = dec!(360);
+
+/// Whether a symbol can be sold short and what it costs to borrow.
+/// # Properties
+/// * `shortable` - False if shares can not be located, orders opening or increasing a short are refused.
+/// * `borrow_fee_rate` - The annual borrow fee as a fraction of the position value, eg 0.03 for 3%, hard to borrow symbols can be above 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shortability {
+    pub shortable: bool,
+    pub borrow_fee_rate: Decimal,
+}
+
+impl Shortability {
+    /// A shortable symbol with the annual borrow fee rate.
+    pub fn shortable(borrow_fee_rate: Decimal) -> Self {
+        Shortability {
+            shortable: true,
+            borrow_fee_rate,
+        }
+    }
+
+    /// A symbol which can not be sold short.
+    pub fn not_shortable() -> Self {
+        Shortability {
+            shortable: false,
+            borrow_fee_rate: dec!(0),
+        }
+    }
+}
+
+/// The shortability of each symbol, symbols which are not in the table use the default.
+/// ```rust
+/// let table = ShortabilityTable::new()
+///     .with_symbol(SymbolName::from("AAPL"), Shortability::shortable(dec!(0.0025)))
+///     .with_symbol(SymbolName::from("GME"), Shortability::shortable(dec!(0.45)))
+///     .with_symbol(SymbolName::from("XYZ"), Shortability::not_shortable());
+/// strategy.set_shortability_table(table);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ShortabilityTable {
+    symbols: DashMap<SymbolName, Shortability>,
+    default: Shortability,
+}
+
+impl Default for ShortabilityTable {
+    fn default() -> Self {
+        ShortabilityTable {
+            symbols: DashMap::new(),
+            default: Shortability::shortable(dec!(0)),
+        }
+    }
+}
+
+impl ShortabilityTable {
+    /// A table where every symbol is shortable without a fee until symbols are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_symbol(self, symbol_name: SymbolName, shortability: Shortability) -> Self {
+        self.symbols.insert(symbol_name, shortability);
+        self
+    }
+
+    /// The shortability of the symbols which are not in the table, eg `Shortability::not_shortable()` to only allow shorts of listed symbols.
+    pub fn with_default(mut self, shortability: Shortability) -> Self {
+        self.default = shortability;
+        self
+    }
+
+    pub fn get(&self, symbol_name: &SymbolName) -> Shortability {
+        match self.symbols.get(symbol_name) {
+            Some(shortability) => *shortability.value(),
+            None => self.default,
+        }
+    }
+
+    /// Refuses a sell of a symbol which is not shortable, unless the quantity is covered by the long position.
+    pub(crate) fn check_order(&self, symbol_name: &SymbolName, side: OrderSide, quantity: Volume, long_quantity: Volume) -> Result<(), OrderError> {
+        if side == OrderSide::Buy || quantity <= long_quantity || self.get(symbol_name).shortable {
+            return Ok(());
+        }
+        Err(OrderError::NotShortable(symbol_name.clone()))
+    }
+}
+
+/// The borrow fee of a short position for the number of days, the value is in the account currency.
+pub(crate) fn borrow_fee(value: Price, borrow_fee_rate: Decimal, days: i64) -> Price {
+    value.abs() * borrow_fee_rate / BORROW_FEE_DAYS_PER_YEAR * Decimal::from(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_orders_refused_for_not_shortable_symbols() {
+        let table = ShortabilityTable::new()
+            .with_symbol("XYZ".to_string(), Shortability::not_shortable())
+            .with_symbol("GME".to_string(), Shortability::shortable(dec!(0.45)));
+        let xyz = "XYZ".to_string();
+
+        assert_eq!(table.check_order(&xyz, OrderSide::Sell, dec!(10), dec!(0)), Err(OrderError::NotShortable(xyz.clone())));
+        // selling the long is allowed, selling past it would open a short
+        assert_eq!(table.check_order(&xyz, OrderSide::Sell, dec!(10), dec!(10)), Ok(()));
+        assert_eq!(table.check_order(&xyz, OrderSide::Sell, dec!(11), dec!(10)), Err(OrderError::NotShortable(xyz.clone())));
+        assert_eq!(table.check_order(&xyz, OrderSide::Buy, dec!(10), dec!(0)), Ok(()));
+        assert_eq!(table.check_order(&"GME".to_string(), OrderSide::Sell, dec!(10), dec!(0)), Ok(()));
+        assert_eq!(table.check_order(&"AAPL".to_string(), OrderSide::Sell, dec!(10), dec!(0)), Ok(()));
+
+        let listed_only = table.with_default(Shortability::not_shortable());
+        assert!(!listed_only.get(&"AAPL".to_string()).shortable);
+        assert_eq!(listed_only.get(&"GME".to_string()).borrow_fee_rate, dec!(0.45));
+    }
+
+    #[test]
+    fn test_borrow_fee() {
+        // 100 shares at 180 for 3 days at 3.6% a year
+        assert_eq!(borrow_fee(dec!(18000), dec!(0.036), 3), dec!(5.4));
+        assert_eq!(borrow_fee(dec!(18000), dec!(0), 3), dec!(0));
+    }
+}
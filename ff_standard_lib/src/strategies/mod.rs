@@ -2,9 +2,29 @@ pub mod indicators;
 pub mod historical_time;
 pub mod consolidators;
 pub mod strategy_events;
+pub mod custom_controls;
+pub mod strategy_panel;
 pub mod historical_engine;
+pub mod backtest_progress;
+pub mod backtest_profiler;
+pub mod history_budget;
 pub mod fund_forge_strategy;
+pub mod strategy_settings;
 pub mod ledgers;
 pub mod handlers;
 pub mod statistics;
+pub mod backtest_diff;
+pub mod trade_journal;
+pub mod promotion;
+pub mod rebalancing;
+pub mod aligned_bars;
+pub mod trading_windows;
+pub mod exposure_groups;
+pub mod fingerprint;
+pub mod embargo;
+pub mod signal_execution;
+pub mod intent_execution;
+pub mod feature_export;
+pub mod model_inference;
+pub mod news_datasets;
 pub mod client_features;
\ No newline at end of file
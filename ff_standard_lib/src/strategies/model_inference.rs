@@ -0,0 +1,195 @@
+use std::collections::VecDeque;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+use crate::messages::data_server_messaging::FundForgeError;
+
+/// A versioned model file, see `resolve_model_file()`.
+/// # Properties
+/// * `name` - The model name.
+/// * `version` - The version from the file name.
+/// * `path` - The model file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelFile {
+    pub name: String,
+    pub version: u32,
+    pub path: PathBuf,
+}
+
+/// The version of a `{name}_v{version}.onnx` file name, None for other files.
+fn model_file_version(file_name: &str, name: &str) -> Option<u32> {
+    file_name.strip_prefix(name)?
+        .strip_prefix("_v")?
+        .strip_suffix(".onnx")?
+        .parse()
+        .ok()
+}
+
+/// Finds the `{name}_v{version}.onnx` file of a model in the folder, the highest version if `version` is None.
+pub fn resolve_model_file(folder: &str, name: &str, version: Option<u32>) -> Result<ModelFile, FundForgeError> {
+    let entries = read_dir(folder)
+        .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to read model folder {}: {}", folder, e)))?;
+    let mut versions: Vec<(u32, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let version = model_file_version(entry.file_name().to_str()?, name)?;
+            Some((version, entry.path()))
+        })
+        .filter(|(file_version, _)| version.map_or(true, |version| version == *file_version))
+        .collect();
+    versions.sort_by_key(|(version, _)| *version);
+    match versions.pop() {
+        Some((version, path)) => Ok(ModelFile { name: name.to_string(), version, path }),
+        None => Err(FundForgeError::ClientSideErrorDebug(match version {
+            Some(version) => format!("No model file {}_v{}.onnx in {}", name, version, folder),
+            None => format!("No model file {}_v{{version}}.onnx in {}", name, folder),
+        })),
+    }
+}
+
+/// The features of the last `length` bars for a sequence model, oldest first.
+/// # Properties
+/// * `length` - The number of bars the model takes.
+/// * `width` - The number of features of each bar.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeatureBuffer {
+    length: usize,
+    width: usize,
+    rows: VecDeque<Vec<f32>>,
+}
+
+impl FeatureBuffer {
+    pub fn new(length: usize, width: usize) -> Self {
+        FeatureBuffer {
+            length,
+            width,
+            rows: VecDeque::with_capacity(length),
+        }
+    }
+
+    /// Adds the features of a bar, dropping the oldest bar once the buffer is full.
+    pub fn push(&mut self, features: Vec<f32>) -> Result<(), FundForgeError> {
+        if features.len() != self.width {
+            return Err(FundForgeError::ClientSideErrorDebug(format!("Feature buffer expects {} features, got {}", self.width, features.len())));
+        }
+        if self.rows.len() == self.length {
+            self.rows.pop_front();
+        }
+        self.rows.push_back(features);
+        Ok(())
+    }
+
+    /// True once the buffer holds `length` bars.
+    pub fn is_ready(&self) -> bool {
+        self.length > 0 && self.rows.len() == self.length
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+
+    /// The input shape of the buffer, `[1, length, width]`.
+    pub fn shape(&self) -> Vec<usize> {
+        vec![1, self.length, self.width]
+    }
+
+    /// The features of every bar, oldest first, in one row, None until the buffer is ready.
+    pub fn flatten(&self) -> Option<Vec<f32>> {
+        match self.is_ready() {
+            true => Some(self.rows.iter().flatten().copied().collect()),
+            false => None,
+        }
+    }
+}
+
+#[cfg(feature = "onnx")]
+pub(crate) use onnx::OnnxModel;
+
+#[cfg(feature = "onnx")]
+mod onnx {
+    use tract_onnx::prelude::*;
+    use crate::messages::data_server_messaging::FundForgeError;
+    use crate::strategies::model_inference::ModelFile;
+
+    /// A model loaded for inference with a fixed f32 input shape.
+    pub(crate) struct OnnxModel {
+        pub(crate) file: ModelFile,
+        pub(crate) input_shape: Vec<usize>,
+        plan: TypedRunnableModel<TypedModel>,
+    }
+
+    impl OnnxModel {
+        pub(crate) fn load(file: ModelFile, input_shape: Vec<usize>) -> Result<Self, FundForgeError> {
+            let error = |e: TractError| FundForgeError::ClientSideErrorDebug(format!("Failed to load model {}: {}", file.path.display(), e));
+            let plan = tract_onnx::onnx()
+                .model_for_path(&file.path).map_err(error)?
+                .with_input_fact(0, f32::fact(input_shape.clone()).into()).map_err(error)?
+                .into_optimized().map_err(error)?
+                .into_runnable().map_err(error)?;
+            Ok(OnnxModel { file, input_shape, plan })
+        }
+
+        /// Runs the model on the features, which fill the input shape in row major order, and returns the first output.
+        pub(crate) fn run(&self, features: &[f32]) -> Result<Vec<f32>, FundForgeError> {
+            let error = |e: TractError| FundForgeError::ClientSideErrorDebug(format!("Model {} v{} failed: {}", self.file.name, self.file.version, e));
+            let expected: usize = self.input_shape.iter().product();
+            if features.len() != expected {
+                return Err(FundForgeError::ClientSideErrorDebug(format!("Model {} expects {} features for shape {:?}, got {}", self.file.name, expected, self.input_shape, features.len())));
+            }
+            let input = Tensor::from_shape(&self.input_shape, features).map_err(error)?;
+            let outputs = self.plan.run(tvec!(input.into())).map_err(error)?;
+            let output = outputs[0].to_array_view::<f32>().map_err(error)?;
+            Ok(output.iter().copied().collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    #[test]
+    fn test_resolve_model_file() {
+        let folder = tempfile::tempdir().unwrap();
+        for file_name in ["trend_v1.onnx", "trend_v2.onnx", "trend_v10.onnx", "trend_v3.onnx.bak", "trend_fast_v20.onnx", "mean_reversion_v4.onnx"] {
+            File::create(folder.path().join(file_name)).unwrap();
+        }
+        let folder_name = folder.path().to_str().unwrap();
+
+        let latest = resolve_model_file(folder_name, "trend", None).unwrap();
+        assert_eq!((latest.version, latest.path.clone()), (10, folder.path().join("trend_v10.onnx")));
+        assert_eq!(resolve_model_file(folder_name, "trend", Some(2)).unwrap().version, 2);
+        assert!(resolve_model_file(folder_name, "trend", Some(3)).is_err());
+        assert_eq!(resolve_model_file(folder_name, "trend_fast", None).unwrap().version, 20);
+        assert!(resolve_model_file(folder_name, "breakout", None).is_err());
+        assert!(resolve_model_file("./no_such_model_folder", "trend", None).is_err());
+    }
+
+    #[test]
+    fn test_feature_buffer() {
+        let mut buffer = FeatureBuffer::new(3, 2);
+        assert!(buffer.push(vec![1.0]).is_err());
+        buffer.push(vec![1.0, 10.0]).unwrap();
+        buffer.push(vec![2.0, 20.0]).unwrap();
+        assert!(!buffer.is_ready());
+        assert_eq!(buffer.flatten(), None);
+
+        buffer.push(vec![3.0, 30.0]).unwrap();
+        buffer.push(vec![4.0, 40.0]).unwrap();
+        assert!(buffer.is_ready());
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.shape(), vec![1, 3, 2]);
+        assert_eq!(buffer.flatten(), Some(vec![2.0, 20.0, 3.0, 30.0, 4.0, 40.0]));
+
+        buffer.clear();
+        assert!(buffer.is_empty());
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::RwLock;
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use rust_decimal::Decimal;
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::standardized_types::base_data::base_data_enum::BaseDataEnum;
+use crate::standardized_types::base_data::fundamental::Fundamental;
+use crate::standardized_types::datavendor_enum::DataVendor;
+use crate::standardized_types::enums::MarketType;
+use crate::standardized_types::resolution::Resolution;
+use crate::standardized_types::subscriptions::Symbol;
+use crate::standardized_types::time_slices::TimeSlice;
+use crate::strategies::signal_execution::parse_signal_time;
+
+/// A timestamped news or sentiment dataset, see `attach_news_dataset()`.
+/// # Properties
+/// * `name` - The name of the dataset, the name of each item.
+/// * `items` - The items, in time order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NewsDataset {
+    name: String,
+    items: Vec<Fundamental>,
+}
+
+impl NewsDataset {
+    /// The items are sorted by time, items with the same time keep their order.
+    pub fn new(name: &str, mut items: Vec<Fundamental>) -> Self {
+        items.sort_by_key(|item| item.time_utc());
+        NewsDataset {
+            name: name.to_string(),
+            items,
+        }
+    }
+
+    /// Reads a news file, a json file is an array of objects and any other file is a csv file with a header row.
+    /// * `time` - The time the item was published, UTC, RFC 3339, `%Y-%m-%d %H:%M:%S` or a date at midnight. Required.
+    /// * `symbol` - The symbol the item is about, empty or missing for market wide items.
+    /// * `text` - The headline or text of the item.
+    /// * Every other column is a number in `fundamental.values`, an empty value is left out.
+    /// ```csv
+    /// time,symbol,text,sentiment,relevance
+    /// 2024-06-03 14:30:00,AAPL,Apple unveils new AI features,0.62,0.9
+    /// 2024-06-03T14:31:00Z,,Fed speaker sees no cut this year,-0.4,
+    /// ```
+    pub fn load(name: &str, file: &str, data_vendor: DataVendor) -> Result<Self, FundForgeError> {
+        let open_error = |e: std::io::Error| FundForgeError::ClientSideErrorDebug(format!("Failed to open news file {}: {}", file, e));
+        let rows: Vec<Vec<(String, String)>> = match Path::new(file).extension().map_or(false, |extension| extension.eq_ignore_ascii_case("json")) {
+            true => {
+                let reader = BufReader::new(File::open(file).map_err(open_error)?);
+                let objects: Vec<serde_json::Map<String, serde_json::Value>> = serde_json::from_reader(reader)
+                    .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to parse news file {}: {}", file, e)))?;
+                objects.into_iter().enumerate().map(|(index, object)| {
+                    object.into_iter().filter_map(|(column, value)| match value {
+                        serde_json::Value::Null => None,
+                        serde_json::Value::String(value) => Some(Ok((column, value))),
+                        serde_json::Value::Number(value) => Some(Ok((column, value.to_string()))),
+                        value => Some(Err(FundForgeError::ClientSideErrorDebug(format!("Invalid value of {} in item {} of {}: {}", column, index + 1, file, value)))),
+                    }).collect()
+                }).collect::<Result<_, FundForgeError>>()?
+            }
+            false => {
+                let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_path(file)
+                    .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to open news file {}: {}", file, e)))?;
+                let headers = reader.headers()
+                    .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to read news file {}: {}", file, e)))?
+                    .clone();
+                reader.records().map(|record| {
+                    let record = record.map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to read news file {}: {}", file, e)))?;
+                    Ok(headers.iter().zip(record.iter()).map(|(column, value)| (column.to_string(), value.to_string())).collect())
+                }).collect::<Result<_, FundForgeError>>()?
+            }
+        };
+        let items = rows.into_iter().enumerate()
+            .map(|(index, fields)| news_item(name, &data_vendor, fields).map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Item {} of {}: {}", index + 1, file, e))))
+            .collect::<Result<Vec<Fundamental>, FundForgeError>>()?;
+        Ok(NewsDataset::new(name, items))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn items(&self) -> &[Fundamental] {
+        &self.items
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The items from `start` to `end`, start inclusive and end exclusive.
+    /// This reads the whole dataset, future items included, so only use it for scheduled releases known in advance, eg a blackout before each CPI release of a calendar.
+    pub fn items_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<&Fundamental> {
+        self.items.iter().filter(|item| {
+            let time = item.time_utc();
+            start <= time && time < end
+        }).collect()
+    }
+}
+
+/// The item of a row of a news file.
+fn news_item(name: &str, data_vendor: &DataVendor, fields: Vec<(String, String)>) -> Result<Fundamental, String> {
+    let mut time = None;
+    let mut symbol_name = None;
+    let mut text = None;
+    let mut values = BTreeMap::new();
+    for (column, value) in fields {
+        if value.is_empty() {
+            continue;
+        }
+        match column.as_str() {
+            "time" => time = Some(parse_signal_time(&value).ok_or_else(|| format!("invalid time {}", value))?),
+            "symbol" => symbol_name = Some(value),
+            "text" => text = Some(value),
+            _ => {
+                let number = Decimal::from_str(&value)
+                    .or_else(|_| Decimal::from_scientific(&value))
+                    .map_err(|_| format!("{} is not a number: {}, only the text column can hold text", column, value))?;
+                values.insert(column, number);
+            }
+        }
+    }
+    let time = time.ok_or_else(|| "no time".to_string())?;
+    let symbol = Symbol::new(symbol_name.unwrap_or(name.to_string()), data_vendor.clone(), MarketType::Fundamentals);
+    Ok(Fundamental::new(symbol, time.to_string(), Resolution::Instant, values, text, None, name.to_string()))
+}
+
+lazy_static! {
+    static ref NEWS_DATASETS: RwLock<Vec<NewsDataset>> = RwLock::new(vec![]);
+}
+
+/// Attaches a news dataset to the backtests of this process, replacing an attached dataset with the same name. Must be called before `FundForgeStrategy::initialize()`.
+/// ```rust
+/// attach_news_dataset(NewsDataset::load("headlines", "./news/headlines.csv", DataVendor::DataBento)?);
+/// attach_news_dataset(NewsDataset::load("calendar", "./news/economic_calendar.json", DataVendor::DataBento)?);
+/// ```
+pub fn attach_news_dataset(dataset: NewsDataset) {
+    let mut datasets = NEWS_DATASETS.write().unwrap();
+    datasets.retain(|attached| attached.name != dataset.name);
+    datasets.push(dataset);
+}
+
+/// Removes every attached news dataset.
+pub fn detach_news_datasets() {
+    NEWS_DATASETS.write().unwrap().clear();
+}
+
+/// The names of the attached datasets.
+pub(crate) fn attached_news_names() -> Vec<String> {
+    NEWS_DATASETS.read().unwrap().iter().map(|dataset| dataset.name.clone()).collect()
+}
+
+/// The items of the attached datasets from `start` to `end`, both inclusive, keyed by their time in nanoseconds like the historical data.
+pub(crate) fn attached_news(start: DateTime<Utc>, end: DateTime<Utc>) -> BTreeMap<i64, TimeSlice> {
+    news_time_slices(&NEWS_DATASETS.read().unwrap(), start, end)
+}
+
+fn news_time_slices(datasets: &[NewsDataset], start: DateTime<Utc>, end: DateTime<Utc>) -> BTreeMap<i64, TimeSlice> {
+    let mut time_slices: BTreeMap<i64, TimeSlice> = BTreeMap::new();
+    for dataset in datasets {
+        for item in &dataset.items {
+            let time = item.time_utc();
+            if time < start || time > end {
+                continue;
+            }
+            let Some(key) = time.timestamp_nanos_opt() else {
+                continue;
+            };
+            time_slices.entry(key).or_insert_with(TimeSlice::new).add(BaseDataEnum::Fundamental(item.clone()));
+        }
+    }
+    time_slices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_load_csv() {
+        let mut file = tempfile::Builder::new().suffix(".csv").tempfile().unwrap();
+        writeln!(file, "time,symbol,text,sentiment,relevance").unwrap();
+        writeln!(file, "2024-06-03 14:31:00,,Fed speaker sees no cut this year,-0.4,").unwrap();
+        writeln!(file, "2024-06-03 14:30:00,AAPL,\"Apple unveils new AI features, shares rise\",0.62,0.9").unwrap();
+        let dataset = NewsDataset::load("headlines", file.path().to_str().unwrap(), DataVendor::DataBento).unwrap();
+        assert_eq!(dataset.len(), 2);
+
+        // sorted by time
+        let first = &dataset.items()[0];
+        assert_eq!(first.time_utc(), Utc.with_ymd_and_hms(2024, 6, 3, 14, 30, 0).unwrap());
+        assert_eq!(first.symbol.name, "AAPL");
+        assert_eq!(first.name, "headlines");
+        assert_eq!(first.value_string, Some("Apple unveils new AI features, shares rise".to_string()));
+        assert_eq!(first.values, BTreeMap::from([("relevance".to_string(), dec!(0.9)), ("sentiment".to_string(), dec!(0.62))]));
+
+        // a market wide item has the dataset name as its symbol
+        let second = &dataset.items()[1];
+        assert_eq!(second.symbol.name, "headlines");
+        assert_eq!(second.values, BTreeMap::from([("sentiment".to_string(), dec!(-0.4))]));
+
+        let mut invalid = tempfile::Builder::new().suffix(".csv").tempfile().unwrap();
+        writeln!(invalid, "time,text,source\n2024-06-03,Headline,Reuters").unwrap();
+        assert!(NewsDataset::load("headlines", invalid.path().to_str().unwrap(), DataVendor::DataBento).is_err());
+    }
+
+    #[test]
+    fn test_load_json() {
+        let mut file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        write!(file, r#"[
+            {{"time": "2024-06-12T12:30:00Z", "text": "CPI", "actual": 3.3, "forecast": 3.4, "previous": null}},
+            {{"time": "2024-06-12 18:00:00", "text": "FOMC rate decision", "actual": 5.5}}
+        ]"#).unwrap();
+        let dataset = NewsDataset::load("calendar", file.path().to_str().unwrap(), DataVendor::DataBento).unwrap();
+        assert_eq!(dataset.len(), 2);
+        assert_eq!(dataset.items()[0].values, BTreeMap::from([("actual".to_string(), dec!(3.3)), ("forecast".to_string(), dec!(3.4))]));
+
+        let day = Utc.with_ymd_and_hms(2024, 6, 12, 0, 0, 0).unwrap();
+        let scheduled = dataset.items_between(day + chrono::Duration::hours(13), day + chrono::Duration::days(1));
+        assert_eq!(scheduled.len(), 1);
+        assert_eq!(scheduled[0].value_string, Some("FOMC rate decision".to_string()));
+    }
+
+    #[test]
+    fn test_news_time_slices() {
+        let time = Utc.with_ymd_and_hms(2024, 6, 3, 14, 30, 0).unwrap();
+        let item = |minute: i64, name: &str| {
+            let symbol = Symbol::new(name.to_string(), DataVendor::DataBento, MarketType::Fundamentals);
+            Fundamental::new(symbol, (time + chrono::Duration::minutes(minute)).to_string(), Resolution::Instant, BTreeMap::new(), None, None, name.to_string())
+        };
+        let headlines = NewsDataset::new("headlines", vec![item(5, "headlines"), item(0, "headlines"), item(60, "headlines")]);
+        let calendar = NewsDataset::new("calendar", vec![item(0, "calendar")]);
+
+        let time_slices = news_time_slices(&[headlines, calendar], time, time + chrono::Duration::minutes(5));
+        let keys: Vec<i64> = time_slices.keys().copied().collect();
+        assert_eq!(keys, vec![time.timestamp_nanos_opt().unwrap(), (time + chrono::Duration::minutes(5)).timestamp_nanos_opt().unwrap()]);
+        // both datasets at the same time
+        assert_eq!(time_slices[&keys[0]].len(), 2);
+        assert_eq!(time_slices[&keys[1]].len(), 1);
+    }
+}
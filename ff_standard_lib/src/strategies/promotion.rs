@@ -0,0 +1,206 @@
+use std::fs::{create_dir_all, File};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use chrono::Utc;
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::standardized_types::enums::StrategyMode;
+use crate::strategies::ledgers::reconciliation::ReconciliationReport;
+
+/// The default fewest fills for a paper run to promote the strategy, a run without trades proves nothing about the order path.
+pub const DEFAULT_MIN_PAPER_FILLS: usize = 1;
+
+/// The mode from the `--mode` arg, or the `FF_STRATEGY_MODE` env var, or the `default` if neither is set.
+/// Panics on an unknown mode, a typo should never fall back to another mode.
+/// ```rust
+/// let mode = strategy_mode_from_args(StrategyMode::Backtest);
+/// ```
+pub fn strategy_mode_from_args(default: StrategyMode) -> StrategyMode {
+    match mode_from(std::env::args().skip(1), std::env::var("FF_STRATEGY_MODE").ok()) {
+        Ok(Some(mode)) => mode,
+        Ok(None) => default,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+fn mode_from(mut args: impl Iterator<Item = String>, env: Option<String>) -> Result<Option<StrategyMode>, String> {
+    while let Some(arg) = args.next() {
+        if arg == "--mode" {
+            let value = args.next().ok_or_else(|| "--mode expects backtest, paper or live".to_string())?;
+            return StrategyMode::from_str(&value).map(Some);
+        }
+        if let Some(value) = arg.strip_prefix("--mode=") {
+            return StrategyMode::from_str(value).map(Some);
+        }
+    }
+    env.map(|value| StrategyMode::from_str(&value)).transpose()
+}
+
+/// The SHA-256 hash of the running executable, in hex.
+pub fn binary_fingerprint() -> Result<String, FundForgeError> {
+    let path = std::env::current_exe()
+        .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to find the strategy executable: {}", e)))?;
+    fingerprint_file(&path)
+}
+
+fn fingerprint_file(path: &Path) -> Result<String, FundForgeError> {
+    let file = File::open(path)
+        .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to open {}: {}", path.display(), e)))?;
+    let mut reader = BufReader::new(file);
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)
+            .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to read {}: {}", path.display(), e)))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// The result of a paper run, saved as json in the gate directory.
+/// # Properties
+/// * `strategy_name` - The name given to the gate.
+/// * `binary_fingerprint` - The hash of the executable which made the run.
+/// * `fills` - The number of fills in the run.
+/// * `breaks` - The reconciliation breaks, empty if the ledgers reconciled.
+/// * `passed` - True if the run reconciled with at least the minimum fills.
+/// * `recorded_at` - The time the record was saved.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PromotionRecord {
+    pub strategy_name: String,
+    pub binary_fingerprint: String,
+    pub fills: usize,
+    pub breaks: Vec<String>,
+    pub passed: bool,
+    pub recorded_at: String,
+}
+
+/// Refuses to run a strategy live until a paper run of the same binary has reconciled.
+/// ```rust
+/// let mode = strategy_mode_from_args(StrategyMode::Backtest);
+/// let gate = PromotionGate::new("nas_breakout", "./promotion");
+/// if let Err(e) = gate.check(mode) {
+///     eprintln!("{}", e);
+///     return;
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PromotionGate {
+    strategy_name: String,
+    directory: String,
+    min_fills: usize,
+}
+
+impl PromotionGate {
+    pub fn new(strategy_name: &str, directory: &str) -> Self {
+        PromotionGate {
+            strategy_name: strategy_name.to_string(),
+            directory: directory.to_string(),
+            min_fills: DEFAULT_MIN_PAPER_FILLS,
+        }
+    }
+
+    /// The fewest fills a paper run needs to promote the strategy.
+    pub fn with_min_fills(mut self, min_fills: usize) -> Self {
+        self.min_fills = min_fills;
+        self
+    }
+
+    fn record_file(&self) -> PathBuf {
+        Path::new(&self.directory).join(format!("{}_promotion.json", self.strategy_name))
+    }
+
+    /// The record of the latest paper run, None if there is no record.
+    pub fn record(&self) -> Option<PromotionRecord> {
+        let file = File::open(self.record_file()).ok()?;
+        serde_json::from_reader(BufReader::new(file)).ok()
+    }
+
+    /// Backtest and paper runs are always allowed, a live run needs a passing paper record for the running binary.
+    pub fn check(&self, mode: StrategyMode) -> Result<(), FundForgeError> {
+        if mode != StrategyMode::Live {
+            return Ok(());
+        }
+        self.check_record(self.record(), &binary_fingerprint()?)
+    }
+
+    fn check_record(&self, record: Option<PromotionRecord>, fingerprint: &str) -> Result<(), FundForgeError> {
+        let refuse = |reason: String| Err(FundForgeError::ClientSideErrorDebug(format!("{} is not promoted to live: {}", self.strategy_name, reason)));
+        let Some(record) = record else {
+            return refuse(format!("no paper run recorded in {}", self.directory));
+        };
+        if record.binary_fingerprint != fingerprint {
+            return refuse("the binary has changed since the last paper run".to_string());
+        }
+        if !record.passed {
+            return refuse(format!("the last paper run had {} fills and {} reconciliation breaks", record.fills, record.breaks.len()));
+        }
+        Ok(())
+    }
+
+    /// Saves the result of a live paper run, the record replaces the record of any earlier run.
+    pub fn record_paper_run(&self, mode: StrategyMode, report: &ReconciliationReport) -> Result<PromotionRecord, FundForgeError> {
+        if mode != StrategyMode::LivePaperTrading {
+            return Err(FundForgeError::ClientSideErrorDebug(format!("Only a live paper run can be recorded for promotion, not {:?}", mode)));
+        }
+        let record = PromotionRecord {
+            strategy_name: self.strategy_name.clone(),
+            binary_fingerprint: binary_fingerprint()?,
+            fills: report.fills,
+            breaks: report.breaks.iter().map(|reconciliation_break| reconciliation_break.to_string()).collect(),
+            passed: report.is_reconciled() && report.fills >= self.min_fills,
+            recorded_at: Utc::now().to_string(),
+        };
+        create_dir_all(&self.directory)
+            .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to create directory {}: {}", self.directory, e)))?;
+        let file = File::create(self.record_file())
+            .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to create the promotion record: {}", e)))?;
+        serde_json::to_writer_pretty(file, &record)
+            .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to write the promotion record: {}", e)))?;
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>().into_iter()
+    }
+
+    #[test]
+    fn test_mode_from_args() {
+        assert_eq!(mode_from(args(&["--mode", "paper"]), None), Ok(Some(StrategyMode::LivePaperTrading)));
+        assert_eq!(mode_from(args(&["--mode=Live"]), Some("backtest".to_string())), Ok(Some(StrategyMode::Live)));
+        assert_eq!(mode_from(args(&[]), Some("backtest".to_string())), Ok(Some(StrategyMode::Backtest)));
+        assert_eq!(mode_from(args(&["--other"]), None), Ok(None));
+        assert!(mode_from(args(&["--mode", "lve"]), None).is_err());
+        assert!(mode_from(args(&["--mode"]), None).is_err());
+    }
+
+    #[test]
+    fn test_paper_run_promotes_the_binary() {
+        let directory = tempfile::tempdir().unwrap();
+        let gate = PromotionGate::new("test_strategy", directory.path().to_str().unwrap());
+        assert!(gate.check(StrategyMode::Backtest).is_ok());
+        assert!(gate.check(StrategyMode::Live).is_err());
+
+        let no_fills = ReconciliationReport::default();
+        assert!(gate.record_paper_run(StrategyMode::Backtest, &no_fills).is_err());
+        assert!(!gate.record_paper_run(StrategyMode::LivePaperTrading, &no_fills).unwrap().passed);
+        assert!(gate.check(StrategyMode::Live).is_err());
+
+        let reconciled = ReconciliationReport { fills: 3, breaks: vec![] };
+        let record = gate.record_paper_run(StrategyMode::LivePaperTrading, &reconciled).unwrap();
+        assert!(record.passed);
+        assert_eq!(gate.record(), Some(record.clone()));
+        assert!(gate.check(StrategyMode::Live).is_ok());
+        assert!(gate.check_record(Some(record), "rebuilt").is_err());
+    }
+}
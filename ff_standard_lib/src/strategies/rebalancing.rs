@@ -0,0 +1,199 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::standardized_types::accounts::Account;
+use crate::standardized_types::enums::OrderSide;
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::orders::{OrderError, OrderId};
+use crate::standardized_types::subscriptions::{SymbolCode, SymbolName};
+
+/// The target of one symbol, see `strategy.rebalance()`.
+/// # Properties
+/// * `symbol_name` - The symbol to hold.
+/// * `symbol_code` - The contract to trade for futures, None to trade the symbol name.
+/// * `weight` - The target value of the position as a fraction of the equity, negative for a short position.
+/// * `lot_size` - The target quantity is rounded toward zero to a multiple of the lot size, eg 1 contract or 1000 units of a forex pair, 1 by default.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TargetWeight {
+    pub symbol_name: SymbolName,
+    pub symbol_code: Option<SymbolCode>,
+    pub weight: Decimal,
+    pub lot_size: Volume,
+}
+
+impl TargetWeight {
+    pub fn new(symbol_name: SymbolName, weight: Decimal) -> Self {
+        TargetWeight {
+            symbol_name,
+            symbol_code: None,
+            weight,
+            lot_size: dec!(1),
+        }
+    }
+
+    pub fn with_symbol_code(mut self, symbol_code: SymbolCode) -> Self {
+        self.symbol_code = Some(symbol_code);
+        self
+    }
+
+    pub fn with_lot_size(mut self, lot_size: Volume) -> Self {
+        self.lot_size = lot_size;
+        self
+    }
+}
+
+/// A market order of a rebalance.
+/// # Properties
+/// * `symbol_name`, `symbol_code` - The symbol traded.
+/// * `side`, `quantity` - The market order for the difference between the position and the target.
+/// * `current_quantity`, `target_quantity` - The position before and after the order, negative when short.
+/// * `price` - The price the symbol was valued at.
+/// * `value` - The value of the order in the account currency.
+/// * `result` - The order id, or why the order was refused, once it is sent. None in a plan from `strategy.rebalance_plan()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RebalanceOrder {
+    pub symbol_name: SymbolName,
+    pub symbol_code: Option<SymbolCode>,
+    pub side: OrderSide,
+    pub quantity: Volume,
+    pub current_quantity: Volume,
+    pub target_quantity: Volume,
+    pub price: Price,
+    pub value: Price,
+    pub result: Option<Result<OrderId, OrderError>>,
+}
+
+/// The orders which take an account to its target weights.
+/// # Properties
+/// * `account` - The account rebalanced.
+/// * `equity` - The balance plus the open pnl, the weights are fractions of the equity.
+/// * `orders` - The orders, sells first.
+/// * `unchanged` - The symbols already at their target, or within the turnover threshold of it.
+/// * `skipped` - The symbols which could not be valued and the reason, eg no market price has been received yet.
+/// * `turnover` - The value of the orders as a fraction of the equity.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RebalancePlan {
+    pub account: Account,
+    pub equity: Price,
+    pub orders: Vec<RebalanceOrder>,
+    pub unchanged: Vec<SymbolName>,
+    pub skipped: Vec<(SymbolName, String)>,
+    pub turnover: Decimal,
+}
+
+/// The market value and position of a target symbol.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SymbolValuation {
+    pub(crate) price: Price,
+    /// The value in the account currency of a quantity of 1 at the price.
+    pub(crate) unit_value: Price,
+    /// The open position, negative when short.
+    pub(crate) position: Volume,
+}
+
+/// Plans the orders for the targets, the valuation of each target is in the same order as the targets.
+pub(crate) fn plan_rebalance(
+    account: Account,
+    equity: Price,
+    targets: Vec<(TargetWeight, Result<SymbolValuation, String>)>,
+    turnover_threshold: Decimal,
+) -> Result<RebalancePlan, OrderError> {
+    if equity <= dec!(0) {
+        return Err(OrderError::InsufficientFunds);
+    }
+    if turnover_threshold < dec!(0) || targets.iter().any(|(target, _)| target.lot_size <= dec!(0)) {
+        return Err(OrderError::InvalidQuantity);
+    }
+    let mut plan = RebalancePlan {
+        account,
+        equity,
+        orders: vec![],
+        unchanged: vec![],
+        skipped: vec![],
+        turnover: dec!(0),
+    };
+    for (target, valuation) in targets {
+        let valuation = match valuation {
+            Ok(valuation) if valuation.unit_value > dec!(0) => valuation,
+            Ok(_) => {
+                plan.skipped.push((target.symbol_name, "the symbol has no value".to_string()));
+                continue;
+            }
+            Err(reason) => {
+                plan.skipped.push((target.symbol_name, reason));
+                continue;
+            }
+        };
+        let lots = (target.weight * equity / valuation.unit_value / target.lot_size).trunc();
+        let target_quantity = lots * target.lot_size;
+        let difference = target_quantity - valuation.position;
+        let value = difference.abs() * valuation.unit_value;
+        if difference == dec!(0) || value < turnover_threshold * equity {
+            plan.unchanged.push(target.symbol_name);
+            continue;
+        }
+        plan.turnover += value / equity;
+        plan.orders.push(RebalanceOrder {
+            symbol_name: target.symbol_name,
+            symbol_code: target.symbol_code,
+            side: match difference > dec!(0) {
+                true => OrderSide::Buy,
+                false => OrderSide::Sell,
+            },
+            quantity: difference.abs(),
+            current_quantity: valuation.position,
+            target_quantity,
+            price: valuation.price,
+            value,
+            result: None,
+        });
+    }
+    // the sort is stable, the orders of each side keep the order of the targets
+    plan.orders.sort_by_key(|order| order.side == OrderSide::Buy);
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standardized_types::broker_enum::Brokerage;
+
+    fn valuation(price: Price, unit_value: Price, position: Volume) -> Result<SymbolValuation, String> {
+        Ok(SymbolValuation { price, unit_value, position })
+    }
+
+    #[test]
+    fn test_plan_rebalance() {
+        let account = Account::new(Brokerage::Test, "Test_Account_1".to_string());
+        let targets = vec![
+            // 60% of 100,000 at 450 is 133.3 shares, rounded down to 133, 100 are held
+            (TargetWeight::new("SPY".to_string(), dec!(0.6)), valuation(dec!(450), dec!(450), dec!(100))),
+            // 30% at 100 in lots of 10 is 300, 500 are held
+            (TargetWeight::new("TLT".to_string(), dec!(0.3)).with_lot_size(dec!(10)), valuation(dec!(100), dec!(100), dec!(500))),
+            // 10% at 200 is 50, 49 are held, the 200 order is under the 0.5% threshold
+            (TargetWeight::new("GLD".to_string(), dec!(0.1)), valuation(dec!(200), dec!(200), dec!(49))),
+            // a short of 2 contracts worth 20,000 each, from flat
+            (TargetWeight::new("MNQ".to_string(), dec!(-0.4)).with_symbol_code("MNQZ24".to_string()), valuation(dec!(10000), dec!(20000), dec!(0))),
+            (TargetWeight::new("EUR-USD".to_string(), dec!(0.1)), Err("no market price".to_string())),
+        ];
+
+        let plan = plan_rebalance(account.clone(), dec!(100000), targets, dec!(0.005)).unwrap();
+        assert_eq!(plan.unchanged, vec!["GLD".to_string()]);
+        assert_eq!(plan.skipped, vec![("EUR-USD".to_string(), "no market price".to_string())]);
+
+        let orders: Vec<(&str, OrderSide, Volume, Volume)> = plan.orders.iter()
+            .map(|order| (order.symbol_name.as_str(), order.side, order.quantity, order.target_quantity))
+            .collect();
+        assert_eq!(orders, vec![
+            ("TLT", OrderSide::Sell, dec!(200), dec!(300)),
+            ("MNQ", OrderSide::Sell, dec!(2), dec!(-2)),
+            ("SPY", OrderSide::Buy, dec!(33), dec!(133)),
+        ]);
+        assert_eq!(plan.orders[1].symbol_code, Some("MNQZ24".to_string()));
+        // 20,000 + 40,000 + 14,850 of 100,000
+        assert_eq!(plan.turnover, dec!(0.7485));
+
+        assert_eq!(plan_rebalance(account.clone(), dec!(0), vec![], dec!(0)), Err(OrderError::InsufficientFunds));
+        let zero_lot = vec![(TargetWeight::new("SPY".to_string(), dec!(1)).with_lot_size(dec!(0)), valuation(dec!(450), dec!(450), dec!(0)))];
+        assert_eq!(plan_rebalance(account, dec!(100000), zero_lot, dec!(0)), Err(OrderError::InvalidQuantity));
+    }
+}
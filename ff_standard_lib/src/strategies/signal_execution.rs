@@ -0,0 +1,335 @@
+use std::collections::{HashSet, VecDeque};
+use std::fs::File;
+use std::path::Path;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use rust_decimal_macros::dec;
+use serde::Deserialize;
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::standardized_types::accounts::Account;
+use crate::standardized_types::enums::OrderSide;
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::orders::{OrderError, OrderId};
+use crate::standardized_types::subscriptions::{SymbolCode, SymbolName};
+
+/// What a signal does.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SignalAction {
+    /// Hold a position of the quantity, negative for a short position, 0 to be flat.
+    TargetPosition(Volume),
+    /// Buy the quantity.
+    Buy(Volume),
+    /// Sell the quantity.
+    Sell(Volume),
+}
+
+/// A timestamped signal of a signal file.
+/// # Properties
+/// * `time` - The time the signal is executed from, in UTC.
+/// * `symbol_name` - The symbol to trade.
+/// * `symbol_code` - The contract to trade for futures, None to trade the symbol name.
+/// * `action` - The target position or the order.
+/// * `limit_price` - Sends a GTC limit order at the price instead of a market order.
+/// * `tag` - The order tag, "Signal" if None.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signal {
+    pub time: DateTime<Utc>,
+    pub symbol_name: SymbolName,
+    pub symbol_code: Option<SymbolCode>,
+    pub action: SignalAction,
+    pub limit_price: Option<Price>,
+    pub tag: Option<String>,
+}
+
+impl Signal {
+    pub fn new(time: DateTime<Utc>, symbol_name: SymbolName, action: SignalAction) -> Self {
+        Signal {
+            time,
+            symbol_name,
+            symbol_code: None,
+            action,
+            limit_price: None,
+            tag: None,
+        }
+    }
+
+    pub fn with_symbol_code(mut self, symbol_code: SymbolCode) -> Self {
+        self.symbol_code = Some(symbol_code);
+        self
+    }
+
+    pub fn with_limit_price(mut self, limit_price: Price) -> Self {
+        self.limit_price = Some(limit_price);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: String) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
+    /// The symbol code the position of the signal is held under.
+    pub(crate) fn position_symbol_code(&self) -> SymbolCode {
+        self.symbol_code.clone().unwrap_or(self.symbol_name.clone())
+    }
+}
+
+/// A row of a csv signal file, or an object of a json signal file.
+#[derive(Deserialize)]
+struct SignalRecord {
+    time: String,
+    symbol_name: SymbolName,
+    #[serde(default)]
+    symbol_code: Option<SymbolCode>,
+    action: String,
+    quantity: Volume,
+    #[serde(default)]
+    limit_price: Option<Price>,
+    #[serde(default)]
+    tag: Option<String>,
+}
+
+impl SignalRecord {
+    fn into_signal(self, file: &str) -> Result<Signal, FundForgeError> {
+        let time = parse_signal_time(&self.time)
+            .ok_or_else(|| FundForgeError::ClientSideErrorDebug(format!("Invalid signal time in {}: {}", file, self.time)))?;
+        let action = match self.action.trim().to_lowercase().as_str() {
+            "target" => SignalAction::TargetPosition(self.quantity),
+            "buy" if self.quantity > dec!(0) => SignalAction::Buy(self.quantity),
+            "sell" if self.quantity > dec!(0) => SignalAction::Sell(self.quantity),
+            "buy" | "sell" => return Err(FundForgeError::ClientSideErrorDebug(format!("Invalid signal quantity in {}: {} {} at {}", file, self.action, self.quantity, self.time))),
+            _ => return Err(FundForgeError::ClientSideErrorDebug(format!("Invalid signal action in {}: {}, expected target, buy or sell", file, self.action))),
+        };
+        Ok(Signal {
+            time,
+            symbol_name: self.symbol_name,
+            symbol_code: self.symbol_code.filter(|code| !code.is_empty()),
+            action,
+            limit_price: self.limit_price,
+            tag: self.tag.filter(|tag| !tag.is_empty()),
+        })
+    }
+}
+
+/// Parses an RFC 3339 time, a "%Y-%m-%d %H:%M:%S" time in UTC with an optional " UTC" suffix, or a date, which is midnight UTC.
+pub(crate) fn parse_signal_time(time: &str) -> Option<DateTime<Utc>> {
+    let time = time.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(time) {
+        return Some(time.to_utc());
+    }
+    let time = time.strip_suffix(" UTC").unwrap_or(time);
+    for format in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(time, format) {
+            return Some(time.and_utc());
+        }
+    }
+    NaiveDate::parse_from_str(time, "%Y-%m-%d").ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|time| time.and_utc())
+}
+
+/// Reads a signal file, a json file is an array of objects and any other file is a csv file, with the fields:
+/// `time`, `symbol_name`, `symbol_code`, `action`, `quantity`, `limit_price` and `tag`.
+/// The action is `target`, `buy` or `sell`, the optional fields can be left empty or out.
+pub fn load_signals(file: &str) -> Result<Vec<Signal>, FundForgeError> {
+    let records: Vec<SignalRecord> = match Path::new(file).extension().map_or(false, |extension| extension.eq_ignore_ascii_case("json")) {
+        true => {
+            let reader = File::open(file)
+                .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to open signal file {}: {}", file, e)))?;
+            serde_json::from_reader(reader)
+                .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to read signal file {}: {}", file, e)))?
+        }
+        false => {
+            let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_path(file)
+                .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to open signal file {}: {}", file, e)))?;
+            let mut records = Vec::new();
+            for record in reader.deserialize() {
+                let record: SignalRecord = record
+                    .map_err(|e| FundForgeError::ClientSideErrorDebug(format!("Failed to read signal file {}: {}", file, e)))?;
+                records.push(record);
+            }
+            records
+        }
+    };
+    records.into_iter().map(|record| record.into_signal(file)).collect()
+}
+
+/// What happened to a signal.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SignalOutcome {
+    /// The order was sent.
+    Sent(OrderId),
+    /// The order was refused before it was sent, see `OrderError`.
+    Refused(OrderError),
+    /// The position was already at the target.
+    AtTarget,
+    /// The signal was not executed, eg a later target of the symbol was due at the same time.
+    Skipped(String),
+}
+
+/// A signal which was due and its outcome, returned by `strategy.execute_signals()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignalExecution {
+    pub signal: Signal,
+    pub outcome: SignalOutcome,
+}
+
+/// The signals a strategy executes on an account, see `strategy.execute_signals()`.
+pub struct SignalExecutor {
+    account: Account,
+    signals: VecDeque<Signal>,
+    max_delay: Option<Duration>,
+}
+
+impl SignalExecutor {
+    /// The signals are executed in time order, signals with the same time in the order they are given.
+    pub fn new(account: Account, mut signals: Vec<Signal>) -> Self {
+        signals.sort_by_key(|signal| signal.time);
+        SignalExecutor {
+            account,
+            signals: signals.into(),
+            max_delay: None,
+        }
+    }
+
+    /// Reads the signals from a signal file, see `load_signals()`.
+    pub fn from_file(account: Account, file: &str) -> Result<Self, FundForgeError> {
+        Ok(SignalExecutor::new(account, load_signals(file)?))
+    }
+
+    /// Buy and sell signals executed more than the max delay after their time are skipped, by default they are never skipped.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = Some(max_delay);
+        self
+    }
+
+    pub fn account(&self) -> &Account {
+        &self.account
+    }
+
+    /// The number of signals which have not been due yet.
+    pub fn remaining(&self) -> usize {
+        self.signals.len()
+    }
+
+    /// The time of the next signal, None when every signal has been due.
+    pub fn next_signal_time(&self) -> Option<DateTime<Utc>> {
+        self.signals.front().map(|signal| signal.time)
+    }
+
+    /// Removes the signals due at the time, with the reason a signal is skipped.
+    pub(crate) fn take_due(&mut self, time: DateTime<Utc>) -> Vec<(Signal, Option<String>)> {
+        let due_count = self.signals.iter().take_while(|signal| signal.time <= time).count();
+        let due: Vec<Signal> = self.signals.drain(..due_count).collect();
+
+        // only the latest target of each symbol is executed
+        let mut later_targets = HashSet::new();
+        let mut superseded = vec![false; due.len()];
+        for (index, signal) in due.iter().enumerate().rev() {
+            if let SignalAction::TargetPosition(_) = signal.action {
+                superseded[index] = !later_targets.insert((signal.symbol_name.clone(), signal.position_symbol_code()));
+            }
+        }
+
+        due.into_iter().zip(superseded).map(|(signal, superseded)| {
+            let skipped = match &signal.action {
+                SignalAction::TargetPosition(_) if superseded => Some("a later target of the symbol was due".to_string()),
+                SignalAction::Buy(_) | SignalAction::Sell(_) if self.max_delay.map_or(false, |max_delay| time - signal.time > max_delay) => {
+                    Some(format!("the signal was due at {}, more than the max delay before {}", signal.time, time))
+                }
+                _ => None,
+            };
+            (signal, skipped)
+        }).collect()
+    }
+}
+
+/// The order which takes the signed position to the target, None if the position is at the target.
+pub(crate) fn target_order(position: Volume, target: Volume) -> Option<(OrderSide, Volume)> {
+    let difference = target - position;
+    match difference {
+        difference if difference > dec!(0) => Some((OrderSide::Buy, difference)),
+        difference if difference < dec!(0) => Some((OrderSide::Sell, difference.abs())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use chrono::TimeZone;
+    use crate::standardized_types::broker_enum::Brokerage;
+
+    fn time(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 3, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_load_signals() {
+        let mut csv_file = tempfile::Builder::new().suffix(".csv").tempfile().unwrap();
+        writeln!(csv_file, "time,symbol_name,symbol_code,action,quantity,limit_price,tag").unwrap();
+        writeln!(csv_file, "2024-06-03 14:30:00 UTC,MNQ,MNQM24,target,-2,,Model Short").unwrap();
+        writeln!(csv_file, "2024-06-03T14:31:00Z,AAPL,,Buy,10,190.5,").unwrap();
+        writeln!(csv_file, "2024-06-04,AAPL,, sell ,10,,").unwrap();
+        let signals = load_signals(csv_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(signals, vec![
+            Signal::new(time(14, 30), "MNQ".to_string(), SignalAction::TargetPosition(dec!(-2)))
+                .with_symbol_code("MNQM24".to_string())
+                .with_tag("Model Short".to_string()),
+            Signal::new(time(14, 31), "AAPL".to_string(), SignalAction::Buy(dec!(10))).with_limit_price(dec!(190.5)),
+            Signal::new(Utc.with_ymd_and_hms(2024, 6, 4, 0, 0, 0).unwrap(), "AAPL".to_string(), SignalAction::Sell(dec!(10))),
+        ]);
+
+        let mut json_file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        write!(json_file, r#"[{{"time": "2024-06-03T14:30:00+00:00", "symbol_name": "EUR-USD", "action": "target", "quantity": 1000}}]"#).unwrap();
+        let signals = load_signals(json_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(signals, vec![Signal::new(time(14, 30), "EUR-USD".to_string(), SignalAction::TargetPosition(dec!(1000)))]);
+
+        let mut invalid_file = tempfile::Builder::new().suffix(".csv").tempfile().unwrap();
+        writeln!(invalid_file, "time,symbol_name,symbol_code,action,quantity,limit_price,tag").unwrap();
+        writeln!(invalid_file, "2024-06-03 14:30:00,AAPL,,short,10,,").unwrap();
+        assert!(load_signals(invalid_file.path().to_str().unwrap()).is_err());
+        assert!(load_signals("./no_such_signal_file.csv").is_err());
+    }
+
+    #[test]
+    fn test_due_signals() {
+        let account = Account::new(Brokerage::Test, "Test_Account_1".to_string());
+        let signals = vec![
+            Signal::new(time(14, 35), "AAPL".to_string(), SignalAction::Buy(dec!(5))),
+            Signal::new(time(14, 30), "AAPL".to_string(), SignalAction::TargetPosition(dec!(10))),
+            Signal::new(time(14, 32), "AAPL".to_string(), SignalAction::TargetPosition(dec!(20))),
+            Signal::new(time(14, 31), "MSFT".to_string(), SignalAction::TargetPosition(dec!(5))),
+            Signal::new(time(14, 33), "MSFT".to_string(), SignalAction::Sell(dec!(5))),
+            Signal::new(time(15, 0), "MSFT".to_string(), SignalAction::Buy(dec!(5))),
+        ];
+        let mut executor = SignalExecutor::new(account, signals).with_max_delay(Duration::minutes(2));
+        assert!(executor.take_due(time(14, 29)).is_empty());
+        assert_eq!(executor.next_signal_time(), Some(time(14, 30)));
+
+        let due: Vec<(&str, SignalAction, bool)> = executor.take_due(time(14, 35)).iter()
+            .map(|(signal, skipped)| (signal.symbol_name.as_str(), signal.action.clone(), skipped.is_some()))
+            .collect();
+        assert_eq!(due, vec![
+            ("AAPL", SignalAction::TargetPosition(dec!(10)), true),
+            ("MSFT", SignalAction::TargetPosition(dec!(5)), false),
+            ("AAPL", SignalAction::TargetPosition(dec!(20)), false),
+            // 2 minutes late is within the max delay
+            ("MSFT", SignalAction::Sell(dec!(5)), false),
+            ("AAPL", SignalAction::Buy(dec!(5)), false),
+        ]);
+        assert_eq!(executor.remaining(), 1);
+        // 3 minutes late
+        assert!(executor.take_due(time(15, 3)).iter().all(|(_, skipped)| skipped.is_some()));
+        assert_eq!(executor.remaining(), 0);
+        assert_eq!(executor.next_signal_time(), None);
+    }
+
+    #[test]
+    fn test_target_order() {
+        assert_eq!(target_order(dec!(0), dec!(2)), Some((OrderSide::Buy, dec!(2))));
+        assert_eq!(target_order(dec!(3), dec!(-2)), Some((OrderSide::Sell, dec!(5))));
+        assert_eq!(target_order(dec!(-2), dec!(0)), Some((OrderSide::Buy, dec!(2))));
+        assert_eq!(target_order(dec!(-2), dec!(-2)), None);
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::RwLock;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use lazy_static::lazy_static;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::helpers::converters::resolve_market_datetime_in_timezone;
+use crate::standardized_types::new_types::Price;
+use crate::standardized_types::position::{Trade, TradeResult};
+
+/// A labelled date range to backtest over, eg "2020 covid crash" or "2023 chop".
+/// Set a list of regimes with `set_backtest_regimes()` to backtest each range in one run and get a statistics breakdown per regime.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BacktestRegime {
+    pub name: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl BacktestRegime {
+    /// `start` and `end` are in the local `time_zone`, the same as the strategy start and end dates.
+    pub fn new(name: &str, start: NaiveDateTime, end: NaiveDateTime, time_zone: Tz) -> Self {
+        BacktestRegime {
+            name: name.to_string(),
+            start: resolve_market_datetime_in_timezone(time_zone, start).to_utc(),
+            end: resolve_market_datetime_in_timezone(time_zone, end).to_utc(),
+        }
+    }
+
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        time >= self.start && time <= self.end
+    }
+}
+
+lazy_static! {
+    static ref BACKTEST_REGIMES: RwLock<Vec<BacktestRegime>> = RwLock::new(vec![]);
+}
+
+/// Sets the regimes to backtest over in a single run, the default is none, which backtests from the `start_date` to the `end_date` of the strategy.
+/// When regimes are set the `start_date` and `end_date` are ignored, each regime is warmed up for the `warmup_duration` and sends its own `StrategyEvent::WarmUpComplete`.
+/// At the end of each regime the open paper orders are cancelled and the paper positions are flattened, the indicators, consolidators and retained history are reset before the next regime is warmed up.
+/// Must be called before `FundForgeStrategy::initialize()`, live modes ignore the regimes.
+pub fn set_backtest_regimes(regimes: Vec<BacktestRegime>) {
+    *BACKTEST_REGIMES.write().unwrap() = regimes;
+}
+
+pub(crate) fn backtest_regimes() -> Vec<BacktestRegime> {
+    BACKTEST_REGIMES.read().unwrap().clone()
+}
+
+impl fmt::Display for BacktestRegime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} to {}", self.name, self.start, self.end)
+    }
+}
+
+/// The trade statistics for the trades which exited during a regime.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegimeStatistics {
+    pub name: String,
+    pub total_trades: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub break_even: usize,
+    pub pnl: Price,
+    pub win_rate: Decimal,
+    pub profit_factor: Decimal,
+    pub max_drawdown: Price,
+    pub commission_paid: Price,
+}
+
+impl RegimeStatistics {
+    pub fn new<'a>(regime: &BacktestRegime, trades: impl Iterator<Item = &'a Trade>) -> Self {
+        let mut trades: Vec<(DateTime<Utc>, &Trade)> = trades
+            .filter_map(|trade| DateTime::<Utc>::from_str(&trade.exit_time).ok().map(|time| (time, trade)))
+            .filter(|(time, _)| regime.contains(*time))
+            .collect();
+        trades.sort_by_key(|(time, _)| *time);
+
+        let mut wins: usize = 0;
+        let mut losses: usize = 0;
+        let mut break_even: usize = 0;
+        let mut win_pnl = dec!(0.0);
+        let mut loss_pnl = dec!(0.0);
+        let mut commission_paid = dec!(0.0);
+        let mut running_pnl = dec!(0.0);
+        let mut peak = dec!(0.0);
+        let mut max_drawdown = dec!(0.0);
+        for (_, trade) in &trades {
+            match trade.result {
+                TradeResult::Win => {
+                    wins += 1;
+                    win_pnl += trade.profit;
+                }
+                TradeResult::Loss => {
+                    losses += 1;
+                    loss_pnl += trade.profit;
+                }
+                TradeResult::BreakEven => break_even += 1,
+            }
+            commission_paid += trade.commissions;
+            running_pnl += trade.profit;
+            peak = peak.max(running_pnl);
+            max_drawdown = max_drawdown.max(peak - running_pnl);
+        }
+
+        let total_trades = trades.len();
+        let win_rate = if total_trades > 0 {
+            Decimal::from(wins) / Decimal::from(total_trades) * dec!(100.0)
+        } else {
+            dec!(0.0)
+        };
+        let profit_factor = if loss_pnl.abs() > dec!(0.0) {
+            win_pnl / loss_pnl.abs()
+        } else if win_pnl > dec!(0.0) {
+            dec!(1000.0)
+        } else {
+            dec!(0.0)
+        };
+
+        RegimeStatistics {
+            name: regime.name.clone(),
+            total_trades,
+            wins,
+            losses,
+            break_even,
+            pnl: running_pnl,
+            win_rate,
+            profit_factor,
+            max_drawdown,
+            commission_paid,
+        }
+    }
+}
+
+impl fmt::Display for RegimeStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Regime: {}, Total Trades: {}, Win Rate: {}%, Profit Factor: {}, Total PnL: {}, Max Drawdown: {}, Wins: {}, Losses: {}, Break Even: {}, Commission Paid: {}",
+            self.name,
+            self.total_trades,
+            self.win_rate.round_dp(2),
+            self.profit_factor.round_dp(2),
+            self.pnl.round_dp(2),
+            self.max_drawdown.round_dp(2),
+            self.wins,
+            self.losses,
+            self.break_even,
+            self.commission_paid.round_dp(2)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn trade(exit_time: &str, profit: Decimal) -> Trade {
+        let result = if profit > dec!(0) {
+            TradeResult::Win
+        } else if profit < dec!(0) {
+            TradeResult::Loss
+        } else {
+            TradeResult::BreakEven
+        };
+        Trade {
+            entry_price: dec!(100),
+            entry_order_id: "entry".to_string(),
+            entry_quantity: dec!(1),
+            exit_price: dec!(100),
+            exit_order_id: "exit".to_string(),
+            exit_quantity: dec!(1),
+            entry_time: exit_time.to_string(),
+            exit_time: exit_time.to_string(),
+            profit,
+            result,
+            commissions: dec!(1),
+        }
+    }
+
+    fn regime(name: &str, start_month: u32, end_month: u32) -> BacktestRegime {
+        BacktestRegime::new(
+            name,
+            NaiveDate::from_ymd_opt(2020, start_month, 1).unwrap().and_hms_opt(0, 0, 0).unwrap(),
+            NaiveDate::from_ymd_opt(2020, end_month, 1).unwrap().and_hms_opt(0, 0, 0).unwrap(),
+            chrono_tz::UTC,
+        )
+    }
+
+    #[test]
+    fn test_regime_statistics_only_include_trades_exited_in_regime() {
+        let trades = vec![
+            trade("2020-02-15 00:00:00 UTC", dec!(100)),
+            trade("2020-03-15 00:00:00 UTC", dec!(-50)),
+            trade("2020-03-20 00:00:00 UTC", dec!(-25)),
+            trade("2020-04-15 00:00:00 UTC", dec!(200)),
+            trade("2020-07-15 00:00:00 UTC", dec!(500)),
+        ];
+        let crash = RegimeStatistics::new(&regime("crash", 2, 5), trades.iter());
+        assert_eq!(crash.total_trades, 4);
+        assert_eq!(crash.wins, 2);
+        assert_eq!(crash.losses, 2);
+        assert_eq!(crash.pnl, dec!(225));
+        assert_eq!(crash.max_drawdown, dec!(75));
+        assert_eq!(crash.profit_factor, dec!(4));
+        assert_eq!(crash.win_rate, dec!(50));
+        assert_eq!(crash.commission_paid, dec!(4));
+
+        let empty = RegimeStatistics::new(&regime("quiet", 8, 9), trades.iter());
+        assert_eq!(empty.total_trades, 0);
+        assert_eq!(empty.pnl, dec!(0));
+    }
+}
@@ -12,7 +12,11 @@ use rkyv::validation::validators::DefaultValidator;
 use rkyv::vec::ArchivedVec;
 use crate::strategies::indicators::indicator_events::IndicatorEvents;
 use crate::standardized_types::position::PositionUpdateEvent;
-use crate::standardized_types::orders::OrderUpdateEvent;
+use crate::standardized_types::orders::{OrderId, OrderUpdateEvent};
+use crate::strategies::backtest_progress::BacktestProgress;
+use crate::standardized_types::accounts::AccountSummary;
+use crate::strategies::consolidators::warmup_check::WarmUpWarning;
+use crate::strategies::handlers::watchdog::HealthWarning;
 
 #[derive(Clone, Serialize_rkyv, Deserialize_rkyv, Archive, PartialEq, Debug, Copy, Ord, PartialOrd, Eq)]
 #[archive(compare(PartialEq), check_bytes)]
@@ -27,7 +31,17 @@ pub enum StrategyEventType {
     WarmUpComplete,
     IndicatorEvent,
     PositionEvents,
-    TimedEvents
+    TimedEvents,
+    PriceTriggers,
+    BacktestProgress,
+    AccountUpdate,
+    WarmUpWarning,
+    SessionClose,
+    DailyRollover,
+    FlatBy,
+    StaleOrderCancelled,
+    HealthWarning,
+    DailyReport
 }
 
 /// All strategies can be sent or received by the strategy or the UI.
@@ -93,7 +107,42 @@ pub enum StrategyEvent {
 
     PositionEvents(PositionUpdateEvent),
 
-    TimedEvent(String)
+    TimedEvent(String),
+
+    /// A price trigger added with `strategy.when_price()` has been touched, contains the trigger tag.
+    PriceTrigger(String),
+
+    /// Sent by the backtest engine about once per second after the warm up, and once more at 100% when the backtest is complete.
+    /// Use `backtest_progress()` for a handle to the latest progress outside the event loop.
+    BacktestProgress(BacktestProgress),
+
+    /// The balances of a live account changed, sent when the broker pushes an update or the periodic account sync finds a change.
+    AccountUpdate(AccountSummary),
+
+    /// Sent after the warm up for each consolidated subscription whose retained bars differ from bars consolidated from the full primary history.
+    /// Indicators using the subscription may have wrong values until enough new bars have closed.
+    WarmUpWarning(WarmUpWarning),
+
+    /// A session close hook added with `strategy.on_session_close()` is due, contains the hook name.
+    /// Sent before any data at or after the close time, so open orders can be cancelled and positions flattened while the market is open.
+    SessionClose(String),
+
+    /// A daily rollover hook added with `strategy.on_daily_rollover()` has passed its rollover time, contains the hook name.
+    DailyRollover(String),
+
+    /// A flat by rule added with `strategy.flat_by()` was due, contains the rule name.
+    /// The working orders in the scope of the rule have been cancelled and the exits for its open positions sent.
+    FlatBy(String),
+
+    /// A working order without a fill was older than the max age of a rule added with `strategy.sweep_stale_orders()`, contains the order id and the rule name.
+    /// The cancel has been sent, the `OrderCancelled` event follows.
+    StaleOrderCancelled(OrderId, String),
+
+    /// The watchdog started with `strategy.start_watchdog()` found a problem with the live strategy process, or a market order was refused on a stale quote, see `HealthWarning`.
+    HealthWarning(HealthWarning),
+
+    /// The daily report scheduled with `strategy.schedule_daily_report()` was compiled, contains the text report, see `DailyReportSettings`.
+    DailyReport(String)
 }
 
 impl StrategyEvent {
@@ -135,7 +184,17 @@ impl StrategyEvent {
             StrategyEvent::IndicatorEvent(_) => StrategyEventType::IndicatorEvent,
             StrategyEvent::PositionEvents(_) => StrategyEventType::PositionEvents,
             StrategyEvent::DataSubscriptionEvent(_) => StrategyEventType::DataSubscriptionEvents,
-            StrategyEvent::TimedEvent(_) => StrategyEventType::TimedEvents
+            StrategyEvent::TimedEvent(_) => StrategyEventType::TimedEvents,
+            StrategyEvent::PriceTrigger(_) => StrategyEventType::PriceTriggers,
+            StrategyEvent::BacktestProgress(_) => StrategyEventType::BacktestProgress,
+            StrategyEvent::AccountUpdate(_) => StrategyEventType::AccountUpdate,
+            StrategyEvent::WarmUpWarning(_) => StrategyEventType::WarmUpWarning,
+            StrategyEvent::SessionClose(_) => StrategyEventType::SessionClose,
+            StrategyEvent::DailyRollover(_) => StrategyEventType::DailyRollover,
+            StrategyEvent::FlatBy(_) => StrategyEventType::FlatBy,
+            StrategyEvent::StaleOrderCancelled(_, _) => StrategyEventType::StaleOrderCancelled,
+            StrategyEvent::HealthWarning(_) => StrategyEventType::HealthWarning,
+            StrategyEvent::DailyReport(_) => StrategyEventType::DailyReport
         }
     }
 
@@ -189,13 +248,17 @@ pub enum StrategyControls {
     Pause,
     /// Used to stop strategies.
     Stop,
+    /// Only exits, stop and limit price updates and cancels are sent, orders which would open, increase or reverse a position are refused by the engine.
+    /// Applied by the engine when sent by the control server or the gui control panel with a `ReduceOnlyHandle`, ended by `Continue`.
+    ReduceOnly,
     /// Used to start strategies.
     Start,
     /// Used to set the delay time, to speed up or slow down backtests
     Delay(Option<u64>),
-    /// Use Strings to set custom commands to the strategy
+    /// Use Strings to set custom commands to the strategy, prefer a typed `ControlCommand` which is checked at compile time.
     Custom(String),
-    /// Send bytes over TCP for larger more complex commands that can be deserialized to concrete types by a u64 identifier
+    /// Send bytes over TCP for larger more complex commands that can be deserialized to concrete types by a u64 identifier.
+    /// Typed `ControlCommand`s are sent as `CustomBytes(CONTROL_ID, json)`, see `strategies::custom_controls`.
     CustomBytes(u64, Vec<u8>)
 }
 #[derive(Clone, PartialEq, Debug)]
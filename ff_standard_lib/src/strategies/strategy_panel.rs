@@ -0,0 +1,196 @@
+use lazy_static::lazy_static;
+use serde_derive::{Deserialize, Serialize};
+use tokio::sync::watch;
+use crate::strategies::custom_controls::{control_id, ControlCommand};
+
+lazy_static! {
+    static ref PANEL_CHANNEL: (watch::Sender<Option<StrategyPanel>>, watch::Receiver<Option<StrategyPanel>>) = watch::channel(None);
+}
+
+/// A widget declared by the strategy, each widget has an `id` which is unique within the panel and used to route the interactions.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum PanelWidget {
+    Button { id: String, label: String },
+    /// A slider between `min` and `max`.
+    NumberInput { id: String, label: String, min: f64, max: f64, step: f64, value: f64 },
+    Toggle { id: String, label: String, value: bool },
+    /// Text set by the strategy, there is no interaction.
+    StatusLabel { id: String, text: String },
+}
+
+impl PanelWidget {
+    pub fn id(&self) -> &str {
+        match self {
+            PanelWidget::Button { id, .. } => id,
+            PanelWidget::NumberInput { id, .. } => id,
+            PanelWidget::Toggle { id, .. } => id,
+            PanelWidget::StatusLabel { id, .. } => id,
+        }
+    }
+}
+
+/// The widgets of a strategy panel, in the order they are displayed.
+///
+/// # Example
+/// ```rust
+/// declare_panel(
+///     StrategyPanel::new("Scalper")
+///         .button("flatten_all", "Flatten All")
+///         .number_input("size", "Size", 1.0, 10.0, 1.0, 2.0)
+///         .toggle("trade_news", "Trade News", false)
+///         .status_label("state", "Waiting")
+/// );
+///
+/// // in the strategy event loop
+/// StrategyEvent::StrategyControls(control) => {
+///     match PanelInteraction::from_control(&control) {
+///         Some(PanelInteraction::Pressed { id }) if id == "flatten_all" => {}
+///         Some(PanelInteraction::NumberChanged { id, value }) if id == "size" => size = Decimal::from_f64(value).unwrap(),
+///         _ => {}
+///     }
+///     set_panel_status("state", "Flat");
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StrategyPanel {
+    pub title: String,
+    pub widgets: Vec<PanelWidget>,
+}
+
+impl StrategyPanel {
+    pub fn new(title: impl Into<String>) -> Self {
+        StrategyPanel {
+            title: title.into(),
+            widgets: vec![],
+        }
+    }
+
+    pub fn button(mut self, id: impl Into<String>, label: impl Into<String>) -> Self {
+        self.widgets.push(PanelWidget::Button { id: id.into(), label: label.into() });
+        self
+    }
+
+    pub fn number_input(mut self, id: impl Into<String>, label: impl Into<String>, min: f64, max: f64, step: f64, value: f64) -> Self {
+        self.widgets.push(PanelWidget::NumberInput { id: id.into(), label: label.into(), min, max, step, value: value.clamp(min, max) });
+        self
+    }
+
+    pub fn toggle(mut self, id: impl Into<String>, label: impl Into<String>, value: bool) -> Self {
+        self.widgets.push(PanelWidget::Toggle { id: id.into(), label: label.into(), value });
+        self
+    }
+
+    pub fn status_label(mut self, id: impl Into<String>, text: impl Into<String>) -> Self {
+        self.widgets.push(PanelWidget::StatusLabel { id: id.into(), text: text.into() });
+        self
+    }
+
+    pub fn widget(&self, id: &str) -> Option<&PanelWidget> {
+        self.widgets.iter().find(|widget| widget.id() == id)
+    }
+
+    /// Applies an interaction to the widget values, so the panel shows the latest values when it is redrawn.
+    pub fn apply(&mut self, interaction: &PanelInteraction) {
+        for widget in self.widgets.iter_mut() {
+            match (widget, interaction) {
+                (PanelWidget::NumberInput { id, value, min, max, .. }, PanelInteraction::NumberChanged { id: changed, value: new_value }) if id == changed => {
+                    *value = new_value.clamp(*min, *max);
+                }
+                (PanelWidget::Toggle { id, value, .. }, PanelInteraction::Toggled { id: changed, value: new_value }) if id == changed => {
+                    *value = *new_value;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// An interaction with a strategy panel widget, received by the strategy as `StrategyEvent::StrategyControls`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum PanelInteraction {
+    Pressed { id: String },
+    NumberChanged { id: String, value: f64 },
+    Toggled { id: String, value: bool },
+}
+
+impl ControlCommand for PanelInteraction {
+    const CONTROL_ID: u64 = control_id("PanelInteraction");
+    const NAME: &'static str = "PanelInteraction";
+}
+
+/// Declares the strategy panel, replacing any panel declared before.
+pub fn declare_panel(panel: StrategyPanel) {
+    PANEL_CHANNEL.0.send_replace(Some(panel));
+}
+
+/// Sets the text of a `StatusLabel`, does nothing if the panel has no label with the id.
+pub fn set_panel_status(id: &str, text: impl Into<String>) {
+    let text = text.into();
+    PANEL_CHANNEL.0.send_if_modified(|panel| {
+        let Some(panel) = panel else {
+            return false;
+        };
+        for widget in panel.widgets.iter_mut() {
+            if let PanelWidget::StatusLabel { id: label_id, text: label_text } = widget {
+                if label_id == id && *label_text != text {
+                    *label_text = text;
+                    return true;
+                }
+            }
+        }
+        false
+    });
+}
+
+/// A handle to the declared panel, used by the gui to render it.
+#[derive(Clone)]
+pub struct StrategyPanelHandle {
+    receiver: watch::Receiver<Option<StrategyPanel>>,
+}
+
+impl StrategyPanelHandle {
+    /// Returns the panel if it was declared or changed since the last call.
+    pub fn changed(&mut self) -> Option<StrategyPanel> {
+        match self.receiver.has_changed() {
+            Ok(true) => self.receiver.borrow_and_update().clone(),
+            _ => None,
+        }
+    }
+}
+
+/// Returns a handle to the panel declared by the strategy running in this process.
+pub fn strategy_panel() -> StrategyPanelHandle {
+    let mut receiver = PANEL_CHANNEL.1.clone();
+    // a panel declared before the gui started is returned by the first call to changed()
+    receiver.mark_changed();
+    StrategyPanelHandle {
+        receiver,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_interactions() {
+        let mut panel = StrategyPanel::new("Test")
+            .button("flatten", "Flatten")
+            .number_input("size", "Size", 1.0, 10.0, 1.0, 20.0)
+            .toggle("news", "Trade News", false);
+        assert_eq!(panel.widget("size"), Some(&PanelWidget::NumberInput { id: "size".to_string(), label: "Size".to_string(), min: 1.0, max: 10.0, step: 1.0, value: 10.0 }));
+
+        panel.apply(&PanelInteraction::NumberChanged { id: "size".to_string(), value: 3.0 });
+        panel.apply(&PanelInteraction::Toggled { id: "news".to_string(), value: true });
+        panel.apply(&PanelInteraction::Pressed { id: "flatten".to_string() });
+        assert!(matches!(panel.widget("size"), Some(PanelWidget::NumberInput { value, .. }) if *value == 3.0));
+        assert!(matches!(panel.widget("news"), Some(PanelWidget::Toggle { value: true, .. })));
+    }
+
+    #[test]
+    fn test_interaction_control() {
+        let interaction = PanelInteraction::Toggled { id: "news".to_string(), value: true };
+        let control = interaction.to_control().unwrap();
+        assert_eq!(PanelInteraction::from_control(&control), Some(interaction));
+    }
+}
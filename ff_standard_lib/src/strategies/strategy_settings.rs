@@ -0,0 +1,170 @@
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use crate::messages::data_server_messaging::FundForgeError;
+use crate::standardized_types::accounts::Currency;
+use crate::standardized_types::enums::{OrderSide, StrategyMode};
+use crate::standardized_types::subscriptions::SymbolName;
+use crate::standardized_types::symbol_info::SymbolInfo;
+use crate::strategies::fingerprint::StrategyFingerprint;
+use crate::strategies::handlers::reduce_only::ReduceOnlyHandle;
+use crate::strategies::handlers::shutdown_handler::DEFAULT_SHUTDOWN_GRACE_PERIOD;
+use crate::strategies::ledgers::conversion_rates::{ConversionRateProvider, DataServerRates};
+use crate::strategies::ledgers::shortability::ShortabilityTable;
+
+/// The settings of one strategy which its handlers and ledgers read, each `FundForgeStrategy` has its own so strategies sharing a process do not change each other.
+/// # Properties
+/// * `fingerprint` - The fingerprint computed when the strategy is initialized, see `strategy.fingerprint()`.
+/// * `reduce_only` - The reduce only mode, see `strategy.set_reduce_only()`.
+/// * `display_precision` - The precisions set with `strategy.set_display_precision()`, used over the symbol info.
+/// * `audit_trail_directory` - See `strategy.set_audit_trail_directory()`.
+/// * `shortability_table` - See `strategy.set_shortability_table()`.
+/// * `conversion_rate_provider` - See `strategy.set_conversion_rate_provider()`.
+/// * `shutdown_grace_period` - See `strategy.set_shutdown_grace_period()`.
+/// * `risk_events` - The risk events since the last daily report, None when no report is scheduled so nothing is kept.
+pub(crate) struct StrategySettings {
+    fingerprint: OnceLock<StrategyFingerprint>,
+    pub(crate) reduce_only: ReduceOnlyHandle,
+    display_precision: DashMap<SymbolName, u32>,
+    audit_trail_directory: RwLock<Option<String>>,
+    shortability_table: RwLock<Arc<ShortabilityTable>>,
+    conversion_rate_provider: RwLock<Arc<dyn ConversionRateProvider>>,
+    shutdown_grace_period: Mutex<Duration>,
+    risk_events: Mutex<Option<Vec<(DateTime<Utc>, String)>>>,
+}
+
+impl StrategySettings {
+    /// Live ledgers convert pnl at a rate at most a minute old, backtests at one rate per day.
+    pub(crate) fn new(mode: StrategyMode) -> Self {
+        let conversion_rate_provider: Arc<dyn ConversionRateProvider> = match mode {
+            StrategyMode::Backtest => Arc::new(DataServerRates::daily()),
+            StrategyMode::Live | StrategyMode::LivePaperTrading => Arc::new(DataServerRates::new(ChronoDuration::minutes(1))),
+        };
+        StrategySettings {
+            fingerprint: OnceLock::new(),
+            reduce_only: ReduceOnlyHandle::default(),
+            display_precision: DashMap::new(),
+            audit_trail_directory: RwLock::new(None),
+            shortability_table: RwLock::new(Arc::new(ShortabilityTable::default())),
+            conversion_rate_provider: RwLock::new(conversion_rate_provider),
+            shutdown_grace_period: Mutex::new(DEFAULT_SHUTDOWN_GRACE_PERIOD),
+            risk_events: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn set_fingerprint(&self, fingerprint: StrategyFingerprint) {
+        let _ = self.fingerprint.set(fingerprint);
+    }
+
+    pub(crate) fn fingerprint(&self) -> Option<StrategyFingerprint> {
+        self.fingerprint.get().cloned()
+    }
+
+    /// The fingerprint hash written to the exports, empty before the strategy is initialized.
+    pub(crate) fn fingerprint_hash(&self) -> String {
+        self.fingerprint.get().map(|fingerprint| fingerprint.hash.clone()).unwrap_or_default()
+    }
+
+    pub(crate) fn set_display_precision(&self, symbol_name: SymbolName, precision: u32) {
+        self.display_precision.insert(symbol_name, precision);
+    }
+
+    /// The symbol info with the display precision set for the symbol, unchanged if none was set.
+    pub(crate) fn with_display_precision(&self, info: SymbolInfo) -> SymbolInfo {
+        match self.display_precision.get(&info.symbol_name).map(|precision| *precision.value()) {
+            Some(precision) => info.with_display_precision(precision),
+            None => info,
+        }
+    }
+
+    pub(crate) fn set_audit_trail_directory(&self, directory: Option<String>) {
+        *self.audit_trail_directory.write().unwrap() = directory;
+    }
+
+    pub(crate) fn audit_trail_directory(&self) -> Option<String> {
+        self.audit_trail_directory.read().unwrap().clone()
+    }
+
+    pub(crate) fn set_shortability_table(&self, table: ShortabilityTable) {
+        *self.shortability_table.write().unwrap() = Arc::new(table);
+    }
+
+    pub(crate) fn shortability_table(&self) -> Arc<ShortabilityTable> {
+        self.shortability_table.read().unwrap().clone()
+    }
+
+    pub(crate) fn set_conversion_rate_provider(&self, provider: Arc<dyn ConversionRateProvider>) {
+        *self.conversion_rate_provider.write().unwrap() = provider;
+    }
+
+    /// The units of `to` for one unit of `from` from the current provider.
+    pub(crate) async fn conversion_rate(&self, from: Currency, to: Currency, time: DateTime<Utc>, side: OrderSide) -> Result<Decimal, FundForgeError> {
+        let provider = self.conversion_rate_provider.read().unwrap().clone();
+        provider.rate(from, to, time, side).await
+    }
+
+    pub(crate) fn set_shutdown_grace_period(&self, grace_period: Duration) {
+        *self.shutdown_grace_period.lock().unwrap() = grace_period;
+    }
+
+    pub(crate) fn shutdown_grace_period(&self) -> Duration {
+        *self.shutdown_grace_period.lock().unwrap()
+    }
+
+    /// Starts keeping the risk events for the daily reports, false drops them.
+    pub(crate) fn keep_risk_events(&self, keep: bool) {
+        let mut risk_events = self.risk_events.lock().unwrap();
+        match keep {
+            true => {
+                risk_events.get_or_insert_with(Vec::new);
+            }
+            false => *risk_events = None,
+        }
+    }
+
+    /// Records a risk event for the next daily report, if a report is scheduled.
+    pub(crate) fn record_risk_event(&self, time: DateTime<Utc>, description: String) {
+        if let Some(events) = self.risk_events.lock().unwrap().as_mut() {
+            events.push((time, description));
+        }
+    }
+
+    /// Removes and returns the risk events at or before the time.
+    pub(crate) fn take_risk_events(&self, until: DateTime<Utc>) -> Vec<(DateTime<Utc>, String)> {
+        self.risk_events.lock().unwrap().as_mut()
+            .map(|events| {
+                let (day, later): (Vec<_>, Vec<_>) = events.drain(..).partition(|(time, _)| *time <= until);
+                *events = later;
+                day
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_strategies_keep_their_own_settings() {
+        let first = StrategySettings::new(StrategyMode::Backtest);
+        let second = StrategySettings::new(StrategyMode::Backtest);
+        first.reduce_only.set(true);
+        first.set_display_precision("MNQ".to_string(), 0);
+        first.set_audit_trail_directory(Some("./audit_trail".to_string()));
+        first.keep_risk_events(true);
+        second.keep_risk_events(true);
+        first.record_risk_event(Utc::now(), "Flat by rule Close".to_string());
+
+        assert!(!second.reduce_only.is_reduce_only());
+        let info = SymbolInfo::new("MNQ".to_string(), None, Currency::USD, dec!(0.5), dec!(0.25), 2);
+        assert_eq!(first.with_display_precision(info.clone()).display_precision(), 0);
+        assert_eq!(second.with_display_precision(info).display_precision(), 2);
+        assert_eq!(second.audit_trail_directory(), None);
+        assert!(second.take_risk_events(Utc::now()).is_empty());
+        assert_eq!(first.take_risk_events(Utc::now()).len(), 1);
+    }
+}
@@ -0,0 +1,373 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde_derive::{Deserialize, Serialize};
+use crate::standardized_types::accounts::Account;
+use crate::standardized_types::base_data::base_data_type::BaseDataType;
+use crate::standardized_types::base_data::traits::BaseData;
+use crate::standardized_types::enums::PositionSide;
+use crate::standardized_types::new_types::{Price, Volume};
+use crate::standardized_types::orders::{Order, OrderId};
+use crate::standardized_types::position::{Position, PositionId, Trade};
+use crate::standardized_types::subscriptions::{SymbolCode, SymbolName};
+use crate::standardized_types::symbol_info::round_for_display;
+use crate::strategies::handlers::indicator_handler::IndicatorHandler;
+use crate::strategies::handlers::subscription_handler::SubscriptionHandler;
+use crate::strategies::indicators::indicator_values::PlotName;
+use crate::strategies::indicators::indicators_trait::IndicatorName;
+use crate::strategies::ledgers::ledger::Ledger;
+
+/// Where and how closed positions are journaled, see `strategy.enable_trade_journal()`.
+#[derive(Clone, Debug)]
+pub struct TradeJournalSettings {
+    pub directory: PathBuf,
+    /// How far before the entry to capture bars.
+    pub bars_before_entry: Duration,
+    /// Render an svg chart of the bars with the entry and exit prices.
+    pub render_chart: bool,
+}
+
+impl TradeJournalSettings {
+    pub fn new(directory: PathBuf) -> Self {
+        TradeJournalSettings {
+            directory,
+            bars_before_entry: Duration::hours(1),
+            render_chart: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JournalBar {
+    pub time: String,
+    pub open: Price,
+    pub high: Price,
+    pub low: Price,
+    pub close: Price,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TradeJournalEntry {
+    pub position_id: PositionId,
+    pub account: Account,
+    pub symbol_name: SymbolName,
+    pub symbol_code: SymbolCode,
+    pub side: PositionSide,
+    pub entry_time: String,
+    pub exit_time: String,
+    pub entry_tag: String,
+    pub exit_tags: Vec<String>,
+    pub average_entry_price: Price,
+    pub average_exit_price: Option<Price>,
+    pub quantity: Volume,
+    pub booked_pnl: Price,
+    pub highest_price: Price,
+    pub lowest_price: Price,
+    pub trades: Vec<Trade>,
+    /// The latest value of each plot for the indicators on the symbol, at or before the entry.
+    pub indicators_at_entry: BTreeMap<IndicatorName, BTreeMap<PlotName, Decimal>>,
+    /// The bars for each candle or quote bar subscription on the symbol, keyed by the subscription, quote bars use the bid prices.
+    pub bars: BTreeMap<String, Vec<JournalBar>>,
+    /// The hash of the strategy configuration which made the trade, see `strategy.fingerprint()`.
+    #[serde(default)]
+    pub fingerprint: String,
+    /// The decimal places the prices are displayed with in the markdown, see `strategy.set_display_precision()`, None for entries written before it was added.
+    #[serde(default)]
+    pub display_precision: Option<u32>,
+}
+
+impl TradeJournalEntry {
+    fn round_for_display(&self, price: Price) -> Price {
+        match self.display_precision {
+            Some(precision) => price.round_dp(precision),
+            None => round_for_display(&self.symbol_name, price),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        let _ = writeln!(markdown, "# {} {} {}", self.side, self.symbol_code, self.position_id);
+        let _ = writeln!(markdown);
+        let _ = writeln!(markdown, "| | |");
+        let _ = writeln!(markdown, "|---|---|");
+        let _ = writeln!(markdown, "| Account | {} |", self.account);
+        let _ = writeln!(markdown, "| Entry | {} @ {} |", self.entry_time, self.round_for_display(self.average_entry_price));
+        match self.average_exit_price {
+            Some(exit_price) => { let _ = writeln!(markdown, "| Exit | {} @ {} |", self.exit_time, self.round_for_display(exit_price)); }
+            None => { let _ = writeln!(markdown, "| Exit | {} |", self.exit_time); }
+        }
+        let _ = writeln!(markdown, "| Quantity | {} |", self.quantity);
+        let _ = writeln!(markdown, "| Booked PnL | {} |", self.booked_pnl.round_dp(2));
+        let _ = writeln!(markdown, "| High / Low | {} / {} |", self.round_for_display(self.highest_price), self.round_for_display(self.lowest_price));
+        let _ = writeln!(markdown, "| Entry Tag | {} |", self.entry_tag);
+        let _ = writeln!(markdown, "| Exit Tags | {} |", self.exit_tags.join(", "));
+        let _ = writeln!(markdown, "| Fingerprint | {} |", self.fingerprint);
+
+        if !self.indicators_at_entry.is_empty() {
+            let _ = writeln!(markdown);
+            let _ = writeln!(markdown, "## Indicators At Entry");
+            for (name, plots) in &self.indicators_at_entry {
+                let plots: Vec<String> = plots.iter().map(|(plot, value)| format!("{}: {}", plot, value)).collect();
+                let _ = writeln!(markdown, "- {}: {}", name, plots.join(", "));
+            }
+        }
+
+        if !self.trades.is_empty() {
+            let _ = writeln!(markdown);
+            let _ = writeln!(markdown, "## Trades");
+            let _ = writeln!(markdown, "| Entry Time | Entry Price | Exit Time | Exit Price | Quantity | Profit |");
+            let _ = writeln!(markdown, "|---|---|---|---|---|---|");
+            for trade in &self.trades {
+                let _ = writeln!(markdown, "| {} | {} | {} | {} | {} | {} |", trade.entry_time, self.round_for_display(trade.entry_price), trade.exit_time, self.round_for_display(trade.exit_price), trade.exit_quantity, trade.profit.round_dp(2));
+            }
+        }
+
+        for (subscription, bars) in &self.bars {
+            let _ = writeln!(markdown);
+            let _ = writeln!(markdown, "## {} ({} bars)", subscription, bars.len());
+        }
+        markdown
+    }
+
+    /// Renders the first bar series as candles, with the entry price in blue and the exit price in orange.
+    /// Returns None if there are no bars.
+    pub fn render_chart_svg(&self, width: u32, height: u32) -> Option<String> {
+        let bars = self.bars.values().find(|bars| !bars.is_empty())?;
+        let mut high = bars.iter().map(|bar| bar.high).max()?.max(self.average_entry_price);
+        let mut low = bars.iter().map(|bar| bar.low).min()?.min(self.average_entry_price);
+        if let Some(exit_price) = self.average_exit_price {
+            high = high.max(exit_price);
+            low = low.min(exit_price);
+        }
+        let range = (high - low).to_f64().unwrap_or(0.0).max(f64::EPSILON);
+        let high = high.to_f64().unwrap_or(0.0);
+        let padding = 10.0;
+        let plot_height = height as f64 - padding * 2.0;
+        let y = |price: Price| padding + (high - price.to_f64().unwrap_or(0.0)) / range * plot_height;
+        let bar_width = width as f64 / bars.len() as f64;
+
+        let mut svg = String::new();
+        let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#, width, height, width, height);
+        let _ = writeln!(svg, r#"<rect width="{}" height="{}" fill="white"/>"#, width, height);
+        for (i, bar) in bars.iter().enumerate() {
+            let center = bar_width * i as f64 + bar_width / 2.0;
+            let color = if bar.close >= bar.open { "green" } else { "red" };
+            let body_top = y(bar.open.max(bar.close));
+            let body_height = (y(bar.open.min(bar.close)) - body_top).max(1.0);
+            let _ = writeln!(svg, r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}"/>"#, center, y(bar.high), center, y(bar.low), color);
+            let _ = writeln!(svg, r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"/>"#, center - bar_width * 0.35, body_top, bar_width * 0.7, body_height, color);
+        }
+        let _ = writeln!(svg, r#"<line x1="0" y1="{:.1}" x2="{}" y2="{:.1}" stroke="blue" stroke-dasharray="4"/>"#, y(self.average_entry_price), width, y(self.average_entry_price));
+        if let Some(exit_price) = self.average_exit_price {
+            let _ = writeln!(svg, r#"<line x1="0" y1="{:.1}" x2="{}" y2="{:.1}" stroke="orange" stroke-dasharray="4"/>"#, y(exit_price), width, y(exit_price));
+        }
+        let _ = writeln!(svg, "</svg>");
+        Some(svg)
+    }
+}
+
+pub(crate) struct TradeJournal {
+    settings: TradeJournalSettings,
+    indicator_handler: Arc<IndicatorHandler>,
+    subscription_handler: Arc<SubscriptionHandler>,
+    closed_order_cache: Arc<DashMap<OrderId, Order>>,
+    recorded: DashMap<(Account, SymbolCode), usize>,
+    fingerprint: String,
+}
+
+impl TradeJournal {
+    pub fn new(settings: TradeJournalSettings, indicator_handler: Arc<IndicatorHandler>, subscription_handler: Arc<SubscriptionHandler>, closed_order_cache: Arc<DashMap<OrderId, Order>>, fingerprint: String) -> Self {
+        if let Err(e) = fs::create_dir_all(&settings.directory) {
+            eprintln!("Trade Journal: Failed to create directory {:?}: {}", settings.directory, e);
+        }
+        TradeJournal {
+            settings,
+            indicator_handler,
+            subscription_handler,
+            closed_order_cache,
+            recorded: Default::default(),
+            fingerprint,
+        }
+    }
+
+    /// Records any positions closed since the last call.
+    pub async fn record_closed_positions(&self, ledgers: &DashMap<Account, &'static Ledger>) {
+        let mut new_positions = vec![];
+        for ledger in ledgers.iter() {
+            for closed in ledger.value().positions_closed.iter() {
+                let key = (ledger.key().clone(), closed.key().clone());
+                let recorded = self.recorded.get(&key).map(|count| *count.value()).unwrap_or(0);
+                if closed.value().len() > recorded {
+                    new_positions.extend(closed.value()[recorded..].iter().cloned());
+                    self.recorded.insert(key, closed.value().len());
+                }
+            }
+        }
+        for position in new_positions {
+            let entry = self.journal_entry(position).await;
+            self.write_entry(&entry);
+        }
+    }
+
+    async fn journal_entry(&self, position: Position) -> TradeJournalEntry {
+        let entry_time = DateTime::<Utc>::from_str(&position.open_time).unwrap_or_default();
+        let exit_time = position.close_time.clone().unwrap_or_else(|| position.open_time.clone());
+        let exit_time_utc = DateTime::<Utc>::from_str(&exit_time).unwrap_or(entry_time);
+
+        let mut exit_tags = vec![];
+        for trade in &position.completed_trades {
+            if let Some(order) = self.closed_order_cache.get(&trade.exit_order_id) {
+                if !exit_tags.contains(&order.tag) {
+                    exit_tags.push(order.tag.clone());
+                }
+            }
+        }
+
+        let mut indicators_at_entry = BTreeMap::new();
+        for (name, values) in self.indicator_handler.latest_at_for_symbol(&position.symbol_name, entry_time) {
+            let plots = values.plots().into_iter().map(|(plot_name, plot)| (plot_name, plot.value)).collect();
+            indicators_at_entry.insert(name, plots);
+        }
+
+        let from = entry_time - self.settings.bars_before_entry;
+        let in_window = |time: DateTime<Utc>| time >= from && time <= exit_time_utc;
+        let mut bars = BTreeMap::new();
+        for subscription in self.subscription_handler.subscriptions().await {
+            if subscription.symbol.name != position.symbol_name {
+                continue;
+            }
+            let series: Vec<JournalBar> = match subscription.base_data_type {
+                BaseDataType::Candles => match self.subscription_handler.candle_history(&subscription) {
+                    Some(history) => history.history.iter().rev()
+                        .filter(|candle| in_window(candle.time_utc()))
+                        .map(|candle| JournalBar { time: candle.time.clone(), open: candle.open, high: candle.high, low: candle.low, close: candle.close })
+                        .collect(),
+                    None => continue,
+                },
+                BaseDataType::QuoteBars => match self.subscription_handler.bar_history(&subscription) {
+                    Some(history) => history.history.iter().rev()
+                        .filter(|bar| in_window(bar.time_utc()))
+                        .map(|bar| JournalBar { time: bar.time.clone(), open: bar.bid_open, high: bar.bid_high, low: bar.bid_low, close: bar.bid_close })
+                        .collect(),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            bars.insert(subscription.to_string(), series);
+        }
+
+        let display_precision = Some(position.symbol_info.display_precision());
+        TradeJournalEntry {
+            position_id: position.position_id,
+            account: position.account,
+            symbol_name: position.symbol_name,
+            symbol_code: position.symbol_code,
+            side: position.side,
+            entry_time: position.open_time,
+            exit_time,
+            entry_tag: position.tag,
+            exit_tags,
+            average_entry_price: position.average_price,
+            average_exit_price: position.average_exit_price,
+            quantity: position.quantity_closed,
+            booked_pnl: position.booked_pnl,
+            highest_price: position.highest_recoded_price,
+            lowest_price: position.lowest_recoded_price,
+            trades: position.completed_trades,
+            indicators_at_entry,
+            bars,
+            fingerprint: self.fingerprint.clone(),
+            display_precision,
+        }
+    }
+
+    fn write_entry(&self, entry: &TradeJournalEntry) {
+        let file_name = entry.position_id.replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "_");
+        let path = self.settings.directory.join(&file_name);
+        match serde_json::to_string_pretty(entry) {
+            Ok(json) => {
+                if let Err(e) = fs::write(path.with_extension("json"), json) {
+                    eprintln!("Trade Journal: Failed to write {}: {}", file_name, e);
+                }
+            }
+            Err(e) => eprintln!("Trade Journal: Failed to serialize {}: {}", file_name, e),
+        }
+        let mut markdown = entry.to_markdown();
+        if self.settings.render_chart {
+            if let Some(svg) = entry.render_chart_svg(600, 300) {
+                if fs::write(path.with_extension("svg"), svg).is_ok() {
+                    markdown.push_str(&format!("\n![chart]({}.svg)\n", file_name));
+                }
+            }
+        }
+        if let Err(e) = fs::write(path.with_extension("md"), markdown) {
+            eprintln!("Trade Journal: Failed to write {}: {}", file_name, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use crate::standardized_types::broker_enum::Brokerage;
+
+    fn entry() -> TradeJournalEntry {
+        let bar = |time: &str, open: Price, close: Price| JournalBar { time: time.to_string(), open, high: open.max(close) + dec!(1), low: open.min(close) - dec!(1), close };
+        let mut bars = BTreeMap::new();
+        bars.insert("NQ 1m Candles".to_string(), vec![
+            bar("2024-01-01 10:00:00 UTC", dec!(100), dec!(102)),
+            bar("2024-01-01 10:01:00 UTC", dec!(102), dec!(101)),
+        ]);
+        let mut plots = BTreeMap::new();
+        plots.insert("ema".to_string(), dec!(100.5));
+        let mut indicators_at_entry = BTreeMap::new();
+        indicators_at_entry.insert("ema 20".to_string(), plots);
+        TradeJournalEntry {
+            position_id: "Test_Account_1-NQ-1".to_string(),
+            account: Account::new(Brokerage::Test, "Test_Account_1".to_string()),
+            symbol_name: "NQ".to_string(),
+            symbol_code: "NQZ4".to_string(),
+            side: PositionSide::Long,
+            entry_time: "2024-01-01 10:00:30 UTC".to_string(),
+            exit_time: "2024-01-01 10:01:30 UTC".to_string(),
+            entry_tag: "Enter Long".to_string(),
+            exit_tags: vec!["Take Profit".to_string()],
+            average_entry_price: dec!(101),
+            average_exit_price: Some(dec!(102)),
+            quantity: dec!(1),
+            booked_pnl: dec!(20),
+            highest_price: dec!(103),
+            lowest_price: dec!(99),
+            trades: vec![],
+            indicators_at_entry,
+            bars,
+            fingerprint: "0123456789abcdef".to_string(),
+            display_precision: Some(2),
+        }
+    }
+
+    #[test]
+    fn test_journal_entry_outputs() {
+        let entry = entry();
+        let markdown = entry.to_markdown();
+        assert!(markdown.contains("| Exit Tags | Take Profit |"));
+        assert!(markdown.contains("- ema 20: ema: 100.5"));
+        assert!(markdown.contains("| Fingerprint | 0123456789abcdef |"));
+
+        let svg = entry.render_chart_svg(600, 300).unwrap();
+        assert_eq!(svg.matches("<rect x=").count(), 2);
+        assert!(svg.contains(r#"stroke="blue""#));
+        assert!(svg.contains(r#"stroke="orange""#));
+
+        let json = serde_json::to_string(&entry).unwrap();
+        assert_eq!(serde_json::from_str::<TradeJournalEntry>(&json).unwrap(), entry);
+    }
+}
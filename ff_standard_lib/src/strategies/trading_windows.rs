@@ -0,0 +1,185 @@
+use std::sync::RwLock;
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use chrono_tz::America::New_York;
+use chrono_tz::Europe::London;
+use dashmap::DashMap;
+use crate::standardized_types::enums::OrderSide;
+use crate::standardized_types::new_types::Volume;
+use crate::standardized_types::orders::OrderType;
+use crate::standardized_types::subscriptions::SymbolName;
+
+/// A time of day the strategy may open positions, see `strategy.set_trading_windows()`.
+/// # Properties
+/// * `name` - The window name, eg "RTH".
+/// * `time_zone` - The time zone of the `start` and `end`, eg the exchange time zone.
+/// * `days` - The days the window opens on.
+/// * `start` - The time the window opens.
+/// * `end` - The time the window closes, before `start` for a window which ends the next day.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradingWindow {
+    pub name: String,
+    pub time_zone: Tz,
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+const WEEKDAYS: [Weekday; 5] = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+
+impl TradingWindow {
+    pub fn new(name: String, time_zone: Tz, days: Vec<Weekday>, start: NaiveTime, end: NaiveTime) -> Self {
+        TradingWindow {
+            name,
+            time_zone,
+            days,
+            start,
+            end,
+        }
+    }
+
+    fn weekdays(name: &str, time_zone: Tz, start: (u32, u32), end: (u32, u32)) -> Self {
+        TradingWindow::new(
+            name.to_string(),
+            time_zone,
+            WEEKDAYS.to_vec(),
+            NaiveTime::from_hms_opt(start.0, start.1, 0).unwrap(),
+            NaiveTime::from_hms_opt(end.0, end.1, 0).unwrap(),
+        )
+    }
+
+    /// The US stock market regular trading hours, 09:30 to 16:00 New York time, Monday to Friday.
+    pub fn us_regular_trading_hours() -> Self {
+        TradingWindow::weekdays("RTH", New_York, (9, 30), (16, 0))
+    }
+
+    /// The London session, 08:00 to 16:30 London time, Monday to Friday.
+    pub fn london() -> Self {
+        TradingWindow::weekdays("London", London, (8, 0), (16, 30))
+    }
+
+    /// The New York session, 08:00 to 17:00 New York time, Monday to Friday.
+    pub fn new_york() -> Self {
+        TradingWindow::weekdays("New York", New_York, (8, 0), (17, 0))
+    }
+
+    /// The overlap of the London and New York sessions, 08:00 New York time to 16:30 London time, Monday to Friday.
+    /// The window is in New York time, so it is 08:00 to 11:30 except in the weeks the clocks change on different dates.
+    pub fn london_new_york_overlap() -> Self {
+        TradingWindow::weekdays("London New York Overlap", New_York, (8, 0), (11, 30))
+    }
+
+    /// Returns true if the window is open at the time.
+    pub fn is_open(&self, time: DateTime<Utc>) -> bool {
+        let local_time = time.with_timezone(&self.time_zone);
+        let time_of_day = local_time.time();
+        let day = local_time.weekday();
+        if self.start <= self.end {
+            self.days.contains(&day) && time_of_day >= self.start && time_of_day < self.end
+        } else if time_of_day >= self.start {
+            self.days.contains(&day)
+        } else if time_of_day < self.end {
+            self.days.contains(&day.pred())
+        } else {
+            false
+        }
+    }
+}
+
+/// The trading windows of the strategy and of the symbols with their own windows.
+#[derive(Default)]
+pub(crate) struct TradingWindows {
+    strategy_windows: RwLock<Vec<TradingWindow>>,
+    symbol_windows: DashMap<SymbolName, Vec<TradingWindow>>,
+}
+
+impl TradingWindows {
+    pub(crate) fn set(&self, windows: Vec<TradingWindow>) {
+        *self.strategy_windows.write().unwrap() = windows;
+    }
+
+    pub(crate) fn set_symbol(&self, symbol_name: SymbolName, windows: Option<Vec<TradingWindow>>) {
+        match windows {
+            Some(windows) => {
+                self.symbol_windows.insert(symbol_name, windows);
+            }
+            None => {
+                self.symbol_windows.remove(&symbol_name);
+            }
+        }
+    }
+
+    /// Returns true if the symbol may open positions at the time, always true for a symbol without windows.
+    pub(crate) fn is_active(&self, symbol_name: &SymbolName, time: DateTime<Utc>) -> bool {
+        if let Some(windows) = self.symbol_windows.get(symbol_name) {
+            return windows.is_empty() || windows.iter().any(|window| window.is_open(time));
+        }
+        let windows = self.strategy_windows.read().unwrap();
+        windows.is_empty() || windows.iter().any(|window| window.is_open(time))
+    }
+}
+
+/// Returns true if the order would open, increase or reverse a position, an order which only reduces the position is not an entry.
+pub(crate) fn is_entry(order_type: &OrderType, side: OrderSide, quantity: Volume, long_quantity: Volume, short_quantity: Volume) -> bool {
+    match order_type {
+        OrderType::EnterLong | OrderType::EnterShort => true,
+        OrderType::ExitLong | OrderType::ExitShort => false,
+        _ => match side {
+            OrderSide::Buy => quantity > short_quantity,
+            OrderSide::Sell => quantity > long_quantity,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_window_is_open() {
+        let rth = TradingWindow::us_regular_trading_hours();
+        // 09:30 New York is 13:30 UTC in summer and 14:30 UTC in winter
+        assert!(rth.is_open(Utc.with_ymd_and_hms(2024, 6, 3, 13, 30, 0).unwrap()));
+        assert!(!rth.is_open(Utc.with_ymd_and_hms(2024, 6, 3, 20, 0, 0).unwrap()));
+        assert!(!rth.is_open(Utc.with_ymd_and_hms(2024, 12, 2, 14, 0, 0).unwrap()));
+        assert!(rth.is_open(Utc.with_ymd_and_hms(2024, 12, 2, 14, 30, 0).unwrap()));
+        // saturday
+        assert!(!rth.is_open(Utc.with_ymd_and_hms(2024, 6, 8, 15, 0, 0).unwrap()));
+
+        // 18:00 to 02:00 New York, opening Sunday to Thursday
+        let overnight = TradingWindow::new("Globex".to_string(), New_York, vec![Weekday::Sun, Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu],
+            NaiveTime::from_hms_opt(18, 0, 0).unwrap(), NaiveTime::from_hms_opt(2, 0, 0).unwrap());
+        // sunday 20:00 and monday 01:00 New York
+        assert!(overnight.is_open(Utc.with_ymd_and_hms(2024, 6, 3, 0, 0, 0).unwrap()));
+        assert!(overnight.is_open(Utc.with_ymd_and_hms(2024, 6, 3, 5, 0, 0).unwrap()));
+        // saturday 01:00 New York, the window opened friday
+        assert!(!overnight.is_open(Utc.with_ymd_and_hms(2024, 6, 8, 5, 0, 0).unwrap()));
+        // monday 12:00 New York
+        assert!(!overnight.is_open(Utc.with_ymd_and_hms(2024, 6, 3, 16, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_windows_and_entries() {
+        let windows = TradingWindows::default();
+        let time = Utc.with_ymd_and_hms(2024, 6, 3, 20, 0, 0).unwrap();
+        let symbol_name = "MNQ".to_string();
+        assert!(windows.is_active(&symbol_name, time));
+        windows.set(vec![TradingWindow::us_regular_trading_hours()]);
+        assert!(!windows.is_active(&symbol_name, time));
+        windows.set_symbol(symbol_name.clone(), Some(vec![TradingWindow::new_york()]));
+        assert!(windows.is_active(&symbol_name, time - chrono::Duration::hours(4)));
+        assert!(!windows.is_active(&symbol_name, time + chrono::Duration::hours(2)));
+        windows.set_symbol(symbol_name.clone(), None);
+        assert!(!windows.is_active(&symbol_name, time - chrono::Duration::hours(10)));
+
+        assert!(is_entry(&OrderType::Market, OrderSide::Buy, dec!(1), dec!(0), dec!(0)));
+        assert!(!is_entry(&OrderType::Market, OrderSide::Sell, dec!(1), dec!(2), dec!(0)));
+        // a reversal opens a short
+        assert!(is_entry(&OrderType::Limit, OrderSide::Sell, dec!(3), dec!(2), dec!(0)));
+        assert!(!is_entry(&OrderType::StopMarket, OrderSide::Buy, dec!(2), dec!(0), dec!(2)));
+        assert!(!is_entry(&OrderType::ExitLong, OrderSide::Sell, dec!(5), dec!(2), dec!(0)));
+        assert!(is_entry(&OrderType::EnterShort, OrderSide::Sell, dec!(1), dec!(2), dec!(0)));
+    }
+}
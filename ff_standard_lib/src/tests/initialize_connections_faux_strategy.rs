@@ -42,5 +42,6 @@ pub(crate) fn initialize_tests() -> FundForgeStrategy {
             ],
         )
             .await
+            .expect("The test strategy failed to initialize")
     })
 }
\ No newline at end of file
@@ -10,6 +10,7 @@ use ff_standard_lib::standardized_types::enums::{MarketType, OrderSide, PrimaryS
 use ff_standard_lib::strategies::strategy_events::{StrategyControls, StrategyEvent};
 use ff_standard_lib::standardized_types::subscriptions::{CandleType, DataSubscription, SymbolName};
 use ff_standard_lib::strategies::fund_forge_strategy::FundForgeStrategy;
+use ff_standard_lib::strategies::promotion::{strategy_mode_from_args, PromotionGate};
 use rust_decimal_macros::dec;
 use tokio::sync::mpsc;
 use ff_standard_lib::standardized_types::broker_enum::Brokerage;
@@ -27,13 +28,21 @@ use ff_standard_lib::strategies::indicators::indicator_values::IndicatorValues;
 #[tokio::main]
 async fn main() {
 
+    // The same binary runs in each mode, eg `cargo run -- --mode paper`, live is refused until a paper run of this binary has reconciled.
+    let mode = strategy_mode_from_args(StrategyMode::Backtest);
+    let gate = PromotionGate::new("example_test_strategy", "./promotion");
+    if let Err(e) = gate.check(mode) {
+        println!("{}", e.to_string().as_str().bright_red());
+        return;
+    }
+
     // We create the sender and receiver for receiving the strategy event buffers
     let (strategy_event_sender, strategy_event_receiver) = mpsc::channel(1000);
 
     // we initialize our strategy as a new strategy, meaning we are not loading drawing tools or existing data from previous runs.
-    let strategy = FundForgeStrategy::initialize(
+    let strategy = match FundForgeStrategy::initialize(
         //ToDo: You can Test Live paper using the simulated data feed which simulates quote stream from the server side at 10 ms per quote.
-        StrategyMode::Backtest, // Backtest, Live, LivePaper
+        mode, // Backtest, Live, LivePaper
         dec!(100000),
         Currency::USD,
         NaiveDate::from_ymd_opt(2011, 1, 20).unwrap().and_hms_opt(0, 0, 0).unwrap(), // Starting date of the backtest is a NaiveDateTime not NaiveDate
@@ -72,7 +81,13 @@ async fn main() {
         false,
         false,
         vec![Account::new(Brokerage::Oanda, "Test_Account_1".to_string())]
-    ).await;
+    ).await {
+        Ok(strategy) => strategy,
+        Err(e) => {
+            eprintln!("Failed to initialize the strategy: {:?}", e);
+            return;
+        }
+    };
 
     // we can subscribe to indicators here or in our event loop at run time.
     let quotebar_5s_atr_5 = AverageTrueRange::new(
@@ -103,13 +118,14 @@ async fn main() {
     strategy.subscribe_indicator(quotebar_5s_atr_5, None).await;
 
     // Start receiving the buffers
-    on_data_received(strategy, strategy_event_receiver).await;
+    on_data_received(strategy, strategy_event_receiver, gate).await;
 }
 
 /// Here we listen for incoming data and build our custom strategy logic. this is where the magic happens.
 pub async fn on_data_received(
     strategy: FundForgeStrategy,
     mut event_receiver: mpsc::Receiver<StrategyEvent>,
+    gate: PromotionGate,
 ) {
     let account = Account::new(Brokerage::Oanda, "Test_Account_1".to_string());
     let mut warmup_complete = false;
@@ -172,7 +188,7 @@ pub async fn on_data_received(
                                         let limit_price = last_bar.ask_low;
                                         // we will set the time in force to Day, based on the strategy Tz of Australia::Sydney, I am not sure how this will work in live trading, TIF might be handled by manually sending cancel order on data server.
                                         let time_in_force = TimeInForce::Day;
-                                        entry_order_id = Some(strategy.limit_order(&quotebar.symbol.name, None, &account, None, dec!(1000), OrderSide::Buy, limit_price, time_in_force, String::from("Enter Long Limit")).await);
+                                        entry_order_id = strategy.limit_order(&quotebar.symbol.name, None, &account, None, dec!(1000), OrderSide::Buy, limit_price, time_in_force, String::from("Enter Long Limit")).await.ok();
                                         bars_since_entry = 0;
                                     }
 
@@ -188,7 +204,7 @@ pub async fn on_data_received(
                                         if bars_since_entry > 5
                                             && in_profit
                                         {
-                                            let _exit_order_id: OrderId = strategy.exit_long(&quotebar.symbol.name, None, &account, None, position_size, String::from("Exit Take Profit")).await;
+                                            let _exit_order_id = strategy.exit_long(&quotebar.symbol.name, None, &account, None, position_size, String::from("Exit Take Profit")).await;
                                             bars_since_entry = 0;
                                             entry_order_id = None;
                                             entry_order_state = OrderState::Cancelled;
@@ -200,7 +216,7 @@ pub async fn on_data_received(
                                         if bars_since_entry >= 10
                                             && in_drawdown
                                         {
-                                            let _exit_order_id: OrderId = strategy.exit_long(&quotebar.symbol.name, None, &account, None, position_size, String::from("Exit Long Stop Loss")).await;
+                                            let _exit_order_id = strategy.exit_long(&quotebar.symbol.name, None, &account, None, position_size, String::from("Exit Long Stop Loss")).await;
                                             bars_since_entry = 0;
                                             entry_order_id = None;
                                             entry_order_state = OrderState::Cancelled;
@@ -215,7 +231,7 @@ pub async fn on_data_received(
                                             && bars_since_entry == 3
                                             && current_heikin_3m_atr_5 >= last_heikin_3m_atr_5
                                         {
-                                            entry_order_id = Some(strategy.enter_long(&quotebar.symbol.name, None, &account, None, dec!(1000), String::from("Add Long")).await);
+                                            entry_order_id = strategy.enter_long(&quotebar.symbol.name, None, &account, None, dec!(1000), String::from("Add Long")).await.ok();
                                         }
                                     }
                                 }
@@ -233,6 +249,7 @@ pub async fn on_data_received(
                         }
                         BaseDataEnum::Candle(_candle) => {}
                         BaseDataEnum::Fundamental(_fundamental) => {}
+                        BaseDataEnum::Depth(_depth) => {}
                     }
                 }
             }
@@ -269,6 +286,7 @@ pub async fn on_data_received(
                     StrategyControls::Pause => {}
                     StrategyControls::Stop => {}
                     StrategyControls::Start => {}
+                    StrategyControls::ReduceOnly => {}
                     StrategyControls::Delay(_) => {}
                     StrategyControls::Custom(_) => {}
                     StrategyControls::CustomBytes(_, _) => {}
@@ -276,7 +294,16 @@ pub async fn on_data_received(
             }
 
             StrategyEvent::ShutdownEvent(event) => {
-                strategy.flatten_all_for(account).await;
+                // wait for the cancels and flattening orders, so we don't exit with orders in flight
+                if !strategy.cancel_and_flatten_all(core::time::Duration::from_secs(10)).await {
+                    println!("{}", "Strategy: Orders or positions still open at shutdown".bright_red());
+                }
+                if strategy.mode() == StrategyMode::LivePaperTrading {
+                    match strategy.record_paper_run(&gate) {
+                        Ok(record) => println!("Strategy: Paper run passed: {}, fills: {}, breaks: {:?}", record.passed, record.fills, record.breaks),
+                        Err(e) => println!("{}", e.to_string().as_str().bright_red()),
+                    }
+                }
                 let msg = format!("{}",event);
                 println!("{}", msg.as_str().bright_magenta());
                 //we should handle shutdown gracefully by first ending the strategy loop.
@@ -329,17 +356,48 @@ pub async fn on_data_received(
                 match event {
                     PositionUpdateEvent::PositionOpened { .. } => {}
                     PositionUpdateEvent::Increased { .. } => {}
-                    PositionUpdateEvent::PositionReduced { .. } => strategy.print_ledger(&event.account()),
-                    PositionUpdateEvent::PositionClosed { .. } => strategy.print_ledger(event.account()),
+                    PositionUpdateEvent::PositionReduced { .. } => strategy.print_ledger(&event.account()).await,
+                    PositionUpdateEvent::PositionClosed { .. } => strategy.print_ledger(event.account()).await,
                 }
             }
             StrategyEvent::TimedEvent(name) => {
                 println!("{} has triggered", name);
             }
+            StrategyEvent::PriceTrigger(tag) => {
+                println!("{} price trigger touched", tag);
+            }
+            StrategyEvent::BacktestProgress(progress) => {
+                println!("Backtest Progress: {}", progress);
+            }
+            StrategyEvent::AccountUpdate(summary) => {
+                println!("{} Balance: {}, Buying Power: {}", summary.account, summary.balance, summary.buying_power);
+            }
+            StrategyEvent::WarmUpWarning(warning) => {
+                println!("{}", warning);
+            }
+            StrategyEvent::SessionClose(name) => {
+                println!("{} session close", name);
+                strategy.cancel_and_flatten_all(core::time::Duration::from_secs(10)).await;
+            }
+            StrategyEvent::DailyRollover(name) => {
+                println!("{} daily rollover", name);
+            }
+            StrategyEvent::FlatBy(name) => {
+                println!("{} flat by", name);
+            }
+            StrategyEvent::StaleOrderCancelled(order_id, rule) => {
+                println!("{} cancelled by stale order rule {}", order_id, rule);
+            }
+            StrategyEvent::HealthWarning(warning) => {
+                println!("{}", warning);
+            }
+            StrategyEvent::DailyReport(report) => {
+                println!("{}", report);
+            }
         }
     }
-    strategy.export_positions_to_csv(&String::from("./trades exports"));
-    strategy.print_ledgers();
+    strategy.export_positions_to_csv(&String::from("./trades exports")).await;
+    strategy.print_ledgers().await;
     event_receiver.close();
     println!("Strategy: Event Loop Ended");
 }
@@ -23,7 +23,7 @@ use ff_standard_lib::standardized_types::resolution::Resolution;
 #[tokio::main]
 async fn main() {
     let (strategy_event_sender, strategy_event_receiver) = mpsc::channel(1000);
-    let strategy = FundForgeStrategy::initialize(
+    let strategy = match FundForgeStrategy::initialize(
         StrategyMode::Backtest, // Backtest, Live, LivePaper
         dec!(100000),
         Currency::USD,
@@ -63,7 +63,13 @@ async fn main() {
         false,
         false,
         vec![Account::new(Brokerage::Oanda, "Test_Account_1".to_string()), Account::new(Brokerage::Oanda, "Test_Account_2".to_string())]
-    ).await;
+    ).await {
+        Ok(strategy) => strategy,
+        Err(e) => {
+            eprintln!("Failed to initialize the strategy: {:?}", e);
+            return;
+        }
+    };
 
     on_data_received(strategy, strategy_event_receiver).await;
 }
@@ -152,8 +158,8 @@ pub async fn on_data_received(
                 strategy.flatten_all_for(account_1.clone()).await;
                 let msg = format!("{}",event);
                 println!("{}", msg.as_str().bright_magenta());
-                strategy.export_trades_to_csv(&account_1, &String::from("./trades exports"));
-                strategy.print_ledgers();
+                strategy.export_trades_to_csv(&account_1, &String::from("./trades exports")).await;
+                strategy.print_ledgers().await;
                 //we should handle shutdown gracefully by first ending the strategy loop.
                 break 'strategy_loop
             },
@@ -169,10 +175,10 @@ pub async fn on_data_received(
                     PositionUpdateEvent::PositionOpened { .. } => {}
                     PositionUpdateEvent::Increased { .. } => {}
                     PositionUpdateEvent::PositionReduced { .. } => {
-                        strategy.print_ledger(event.account())
+                        strategy.print_ledger(event.account()).await
                     },
                     PositionUpdateEvent::PositionClosed { .. } => {
-                        strategy.print_ledger(event.account())
+                        strategy.print_ledger(event.account()).await
                     },
                 }
                 let quantity = strategy.position_size(&account_1, &"EUR-USD".to_string());
@@ -184,7 +190,7 @@ pub async fn on_data_received(
                 let msg = format!("Strategy: Order Event: {}, Time: {}", event, event.time_local(strategy.time_zone()));
                 match event {
                     OrderUpdateEvent::OrderRejected { .. } | OrderUpdateEvent::OrderUpdateRejected { .. } => {
-                        strategy.print_ledger(event.account());
+                        strategy.print_ledger(event.account()).await;
                         println!("{}", msg.as_str().on_bright_magenta().on_bright_red())
                     },
                     _ =>  println!("{}", msg.as_str().bright_yellow())
@@ -26,7 +26,7 @@ use ff_standard_lib::standardized_types::resolution::Resolution;
 async fn main() {
     let (strategy_event_sender, strategy_event_receiver) = mpsc::channel(1000);
 
-    let strategy = FundForgeStrategy::initialize(
+    let strategy = match FundForgeStrategy::initialize(
         StrategyMode::Backtest, // Backtest, Live, LivePaper
         dec!(100000),
         Currency::AUD,
@@ -98,7 +98,13 @@ async fn main() {
         false,
         false,
         vec![Account::new(Brokerage::Oanda, "101-011-24767836-001".to_string())]
-    ).await;
+    ).await {
+        Ok(strategy) => strategy,
+        Err(e) => {
+            eprintln!("Failed to initialize the strategy: {:?}", e);
+            return;
+        }
+    };
 
     on_data_received(strategy, strategy_event_receiver).await;
 }
@@ -151,7 +157,9 @@ pub async fn on_data_received(
                                             && qb.bid_close > qb.bid_open
                                         {
                                             println!("Strategy: {} Enter Long, Time {}", qb.symbol.name, strategy.time_local());
-                                            entry_orders.insert(qb.symbol.name.clone(), strategy.enter_long(&qb.symbol.name, None, &account_1, None, dec!(10000), String::from("Enter Long")).await);
+                                            if let Ok(order_id) = strategy.enter_long(&qb.symbol.name, None, &account_1, None, dec!(10000), String::from("Enter Long")).await {
+                                                entry_orders.insert(qb.symbol.name.clone(), order_id);
+                                            }
                                             last_side = LastSide::Long;
                                         }
                                     }
@@ -167,13 +175,17 @@ pub async fn on_data_received(
                                         if is_long && long_pnl > dec!(1000.0) {
                                             println!("Strategy: {} Exit Long Take Profit, Time {}", qb.symbol.name, strategy.time_local());  // Fixed message
                                             let position_size = strategy.position_size(&account_1, &qb.symbol.name);
-                                            exit_orders.insert(qb.symbol.name.clone(), strategy.exit_long(&qb.symbol.name, None, &account_1, None, position_size, String::from("Exit Long Take Profit")).await);
+                                            if let Ok(order_id) = strategy.exit_long(&qb.symbol.name, None, &account_1, None, position_size, String::from("Exit Long Take Profit")).await {
+                                                exit_orders.insert(qb.symbol.name.clone(), order_id);
+                                            }
                                         } else if is_long
                                             && long_pnl <= dec!(-100.0)
                                         {
                                             println!("Strategy: {} Exit Long Take Loss, Time {}", qb.symbol.name,  strategy.time_local());
                                             let position_size: Decimal = strategy.position_size(&account_1, &qb.symbol.name);
-                                            exit_orders.insert(qb.symbol.name.clone(), strategy.exit_long(&qb.symbol.name, None, &account_1, None, position_size, String::from("Exit Long Take Loss")).await);
+                                            if let Ok(order_id) = strategy.exit_long(&qb.symbol.name, None, &account_1, None, position_size, String::from("Exit Long Take Loss")).await {
+                                                exit_orders.insert(qb.symbol.name.clone(), order_id);
+                                            }
                                         }
                                     }
                                 }
@@ -200,9 +212,9 @@ pub async fn on_data_received(
                 strategy.flatten_all_for(account_1.clone()).await;
                 let msg = format!("{}",event);
                 println!("{}", msg.as_str().bright_magenta());
-                strategy.export_positions_to_csv(&String::from("./trades exports"));
-                strategy.export_trades_to_csv(&account_1, &String::from("./trades exports"));
-                strategy.print_ledgers();
+                strategy.export_positions_to_csv(&String::from("./trades exports")).await;
+                strategy.export_trades_to_csv(&account_1, &String::from("./trades exports")).await;
+                strategy.print_ledgers().await;
                 //we should handle shutdown gracefully by first ending the strategy loop.
                 break 'strategy_loop
             },
@@ -228,10 +240,10 @@ pub async fn on_data_received(
                     PositionUpdateEvent::PositionOpened { .. } => {}
                     PositionUpdateEvent::Increased { .. } => {}
                     PositionUpdateEvent::PositionReduced { .. } => {
-                        strategy.print_ledger(event.account())
+                        strategy.print_ledger(event.account()).await
                     },
                     PositionUpdateEvent::PositionClosed { .. } => {
-                        strategy.print_ledger(event.account())
+                        strategy.print_ledger(event.account()).await
                     },
                 }
                 let msg = format!("{}, Time Local: {}", event, event.time_local(strategy.time_zone()));
@@ -241,11 +253,11 @@ pub async fn on_data_received(
                 let msg = format!("Strategy: Order Event: {}, Time: {}", event, event.time_local(strategy.time_zone()));
                 match event {
                      OrderUpdateEvent::OrderUpdateRejected { .. } => {
-                        strategy.print_ledger(event.account());
+                        strategy.print_ledger(event.account()).await;
                         println!("{}", msg.as_str().on_bright_magenta().on_bright_red())
                     },
                     OrderUpdateEvent::OrderRejected { ref symbol_name, ref order_id,.. } => {
-                        strategy.print_ledger(event.account());
+                        strategy.print_ledger(event.account()).await;
                         println!("{}", msg.as_str().on_bright_magenta().on_bright_red());
                         if let Some(exit_order) = exit_orders.get(symbol_name) {
                             if order_id == exit_order {
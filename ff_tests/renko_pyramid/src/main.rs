@@ -38,7 +38,7 @@ async fn main() {
         MarketType::Futures(exchange),
     );
 
-    let strategy = FundForgeStrategy::initialize(
+    let strategy = match FundForgeStrategy::initialize(
         StrategyMode::Backtest,
         dec!(100000),
         Currency::USD,
@@ -57,7 +57,13 @@ async fn main() {
         false,
         false,
         vec![account.clone()],
-    ).await;
+    ).await {
+        Ok(strategy) => strategy,
+        Err(e) => {
+            eprintln!("Failed to initialize the strategy: {:?}", e);
+            return;
+        }
+    };
 
     eprintln!("Strategy Initialized");
 
@@ -133,11 +139,11 @@ pub async fn on_data_received(
                                 if (block_close.value < SAFTEY_LEVEL && TRADING_LONG) || (block_close.value > SAFTEY_LEVEL && TRADING_SHORT) {
                                     if strategy.is_long(&account, &symbol_code) {
                                         let open_quantity = strategy.position_size(&account, &symbol_code);
-                                        exit_order_id = Some(strategy.exit_long(&symbol_name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Long Target Reached".to_string()).await);
+                                        exit_order_id = strategy.exit_long(&symbol_name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Long Target Reached".to_string()).await.ok();
                                     }
                                     if strategy.is_short(&account, &symbol_code) {
                                         let open_quantity = strategy.position_size(&account, &symbol_code);
-                                        exit_order_id = Some(strategy.exit_short(&symbol_name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Short Target Reached".to_string()).await);
+                                        exit_order_id = strategy.exit_short(&symbol_name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Short Target Reached".to_string()).await.ok();
                                     }
                                     println!("Saftey level breached, continue");
                                     continue
@@ -171,7 +177,7 @@ pub async fn on_data_received(
                                         println!("Balance is too high or too low, flattening all positions: {}", balance);
                                         if strategy.is_long(&account, &symbol_code) {
                                             let open_quantity = strategy.position_size(&account, &symbol_code);
-                                            exit_order_id = Some(strategy.exit_long(&subscription.symbol.name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Long Target Reached".to_string()).await);
+                                            exit_order_id = strategy.exit_long(&subscription.symbol.name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Long Target Reached".to_string()).await.ok();
                                         }
                                         break 'strategy_loop;
                                     }
@@ -194,21 +200,21 @@ pub async fn on_data_received(
                                             if quantity < MAX_SIZE {
                                                 println!("Entering Long: Time: {}", strategy.time_local());
                                                 let tif = TimeInForce::Time((strategy.time_utc() + Duration::seconds(LIMIT_ORDER_EXPIRE_IN_SECS)).timestamp());
-                                                entry_order_id = Some(strategy.limit_order(&symbol_name, Some(symbol_code.clone()), &account, None, SIZE, OrderSide::Buy, block_close.value, tif, String::from("Enter Long")).await);
+                                                entry_order_id = strategy.limit_order(&symbol_name, Some(symbol_code.clone()), &account, None, SIZE, OrderSide::Buy, block_close.value, tif, String::from("Enter Long")).await.ok();
                                             }
                                         }
                                         if is_long {
                                             //tp on 2 bearish renko blocks
                                             if last_close < last_open && block_close.value < block_open.value && exit_order_id == None {
                                                 let quantity = strategy.position_size(&account, &symbol_code);
-                                                exit_order_id = Some(strategy.exit_long(&symbol_name, Some(symbol_code.clone()), &account, None, quantity, String::from("Exit Long")).await);
+                                                exit_order_id = strategy.exit_long(&symbol_name, Some(symbol_code.clone()), &account, None, quantity, String::from("Exit Long")).await.ok();
                                             }
 
                                             let profit = strategy.pnl(&account, &symbol_code);
                                             let quantity = strategy.position_size(&account, &symbol_code);
                                             if profit > INCREMENTAL_SCALP_PNL && quantity == MAX_SIZE && exit_order_id == None && tp_id == None {
                                                 let tif = TimeInForce::Time((strategy.time_utc() + Duration::seconds(LIMIT_ORDER_EXPIRE_IN_SECS)).timestamp());
-                                                tp_id = Some(strategy.limit_order(&symbol_name, Some(symbol_code.clone()), &account, None, SIZE, OrderSide::Sell, last_close + RENKO_RANGE * dec!(4), tif, String::from("Partial TP Long")).await);
+                                                tp_id = strategy.limit_order(&symbol_name, Some(symbol_code.clone()), &account, None, SIZE, OrderSide::Sell, last_close + RENKO_RANGE * dec!(4), tif, String::from("Partial TP Long")).await.ok();
                                             }
                                         }
                                     }
@@ -223,21 +229,21 @@ pub async fn on_data_received(
                                             if quantity < MAX_SIZE {
                                                 println!("Entering Short: Time: {}", strategy.time_local());
                                                 let tif = TimeInForce::Time((strategy.time_utc() + Duration::seconds(LIMIT_ORDER_EXPIRE_IN_SECS)).timestamp());
-                                                entry_order_id = Some(strategy.limit_order(&symbol_name, Some(symbol_code.clone()), &account, None, SIZE, OrderSide::Sell, block_close.value, tif, String::from("Enter Short")).await);
+                                                entry_order_id = strategy.limit_order(&symbol_name, Some(symbol_code.clone()), &account, None, SIZE, OrderSide::Sell, block_close.value, tif, String::from("Enter Short")).await.ok();
                                             }
                                         }
                                         if is_short {
                                             //tp on 2 bullish renko blocks
                                             if last_close > last_open && block_close.value > block_open.value && exit_order_id == None {
                                                 let quantity = strategy.position_size(&account, &symbol_code);
-                                                exit_order_id = Some(strategy.exit_short(&symbol_name, Some(symbol_code.clone()), &account, None, quantity, String::from("Exit Short")).await);
+                                                exit_order_id = strategy.exit_short(&symbol_name, Some(symbol_code.clone()), &account, None, quantity, String::from("Exit Short")).await.ok();
                                             }
 
                                             let profit = strategy.pnl(&account, &symbol_code);
                                             let quantity = strategy.position_size(&account, &symbol_code);
                                             if profit > INCREMENTAL_SCALP_PNL && quantity == MAX_SIZE && exit_order_id == None && tp_id == None {
                                                 let tif = TimeInForce::Time((strategy.time_utc() + Duration::seconds(LIMIT_ORDER_EXPIRE_IN_SECS)).timestamp());
-                                                tp_id = Some(strategy.limit_order(&symbol_name, Some(symbol_code.clone()), &account, None, SIZE, OrderSide::Buy, last_close - RENKO_RANGE * dec!(4), tif, String::from("Partial TP Short")).await);
+                                                tp_id = strategy.limit_order(&symbol_name, Some(symbol_code.clone()), &account, None, SIZE, OrderSide::Buy, last_close - RENKO_RANGE * dec!(4), tif, String::from("Partial TP Short")).await.ok();
                                             }
                                         }
                                     }
@@ -267,8 +273,8 @@ pub async fn on_data_received(
                 strategy.flatten_all_for(account).await;
                 let msg = format!("{}",event);
                 println!("{}", msg.as_str().bright_magenta());
-                strategy.export_positions_to_csv(&String::from("./trades exports"));
-                strategy.print_ledgers();
+                strategy.export_positions_to_csv(&String::from("./trades exports")).await;
+                strategy.print_ledgers().await;
                 //we should handle shutdown gracefully by first ending the strategy loop.
                 break 'strategy_loop
             },
@@ -284,11 +290,11 @@ pub async fn on_data_received(
                     PositionUpdateEvent::PositionOpened { .. } => {}
                     PositionUpdateEvent::Increased { .. } => {}
                     PositionUpdateEvent::PositionReduced { .. } => {
-                        strategy.print_ledger(event.account());
+                        strategy.print_ledger(event.account()).await;
                     },
                     PositionUpdateEvent::PositionClosed { ref side, ref booked_pnl,.. } => {
-                        strategy.print_trade_statistics(event.account());
-                        strategy.print_ledger(event.account());
+                        strategy.print_trade_statistics(event.account()).await;
+                        strategy.print_ledger(event.account()).await;
                         exit_order_id = None;
                         entry_order_id = None;
                         let result = if *booked_pnl > dec!(0) {
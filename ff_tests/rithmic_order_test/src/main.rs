@@ -30,7 +30,7 @@ async fn main() {
     let exchange = get_exchange_by_symbol_name(&symbol_name).unwrap();
     let symbol_code = "MESZ4".to_string();
     let account_1 = Account::new(Brokerage::Rithmic(RithmicSystem::Apex), AccountId::from("APEX-3396-169"));
-    let strategy = FundForgeStrategy::initialize(
+    let strategy = match FundForgeStrategy::initialize(
         StrategyMode::Live,
         dec!(100000),
         Currency::USD,
@@ -63,7 +63,13 @@ async fn main() {
         false,
         true,
         vec![account_1.clone()],
-    ).await;
+    ).await {
+        Ok(strategy) => strategy,
+        Err(e) => {
+            eprintln!("Failed to initialize the strategy: {:?}", e);
+            return;
+        }
+    };
 
     on_data_received(strategy, strategy_event_receiver, symbol_name, symbol_code, account_1).await;
 }
@@ -120,7 +126,10 @@ pub async fn on_data_received(
                                 {
                                     if count == 5 {
                                         println!("Rithmic Order Test: Enter Long, Time {}", strategy.time_local());
-                                        entry_order_id = strategy.enter_long(&candle.symbol.name, Some(symbol_code.clone()) ,&account_1, None, dec!(1), String::from("Enter Long")).await;
+                                        match strategy.enter_long(&candle.symbol.name, Some(symbol_code.clone()) ,&account_1, None, dec!(1), String::from("Enter Long")).await {
+                                            Ok(order_id) => entry_order_id = order_id,
+                                            Err(e) => println!("Rithmic Order Test: Order refused: {}", e),
+                                        }
                                     }
 
                                     let open_pnl = strategy.pnl(&account_1, &symbol_code);
@@ -152,8 +161,8 @@ pub async fn on_data_received(
                 strategy.flatten_all_for(account_1).await;
                 let msg = format!("{}",event);
                 println!("{}", msg.as_str().bright_magenta());
-                strategy.export_positions_to_csv(&String::from("./trades exports"));
-                strategy.print_ledgers();
+                strategy.export_positions_to_csv(&String::from("./trades exports")).await;
+                strategy.print_ledgers().await;
                 //we should handle shutdown gracefully by first ending the strategy loop.
                 break 'strategy_loop
             },
@@ -163,7 +172,7 @@ pub async fn on_data_received(
                     PositionUpdateEvent::Increased { .. } => {}
                     PositionUpdateEvent::PositionReduced { .. } => {},
                     PositionUpdateEvent::PositionClosed { .. } => {
-                        strategy.print_ledger(event.account())
+                        strategy.print_ledger(event.account()).await
                     },
                 }
                 let quantity = strategy.position_size(&account_1, &symbol_code);
@@ -175,7 +184,7 @@ pub async fn on_data_received(
                 let msg = format!("Rithmic Order Test: Order Event: {}, Time: {}", event, event.time_local(strategy.time_zone()));
                 match event {
                     OrderUpdateEvent::OrderRejected { .. } | OrderUpdateEvent::OrderUpdateRejected { .. } => {
-                        strategy.print_ledger(event.account());
+                        strategy.print_ledger(event.account()).await;
                         println!("{}", msg.as_str().on_bright_magenta().on_bright_red())
                     },
                     _ =>  println!("{}", msg.as_str().bright_yellow())
@@ -4,10 +4,11 @@ use std::sync::Arc;
 use chrono::{Duration, NaiveDate, Timelike};
 use chrono_tz::Tz::{Australia__Brisbane};
 use colored::Colorize;
+use ff_gui::control_panel::alerts::{Alert, AlertSettings};
 use ff_gui::control_panel::panel::{new_strategy_control, window_settings, StrategyControlPanel};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use ff_standard_lib::apis::rithmic::rithmic_systems::RithmicSystem;
 use ff_standard_lib::gui_types::settings::Color;
 use ff_standard_lib::product_maps::rithmic::maps::{get_futures_exchange, get_futures_symbol_info, get_futures_trading_hours};
@@ -23,11 +24,14 @@ use ff_standard_lib::standardized_types::position::PositionUpdateEvent;
 use ff_standard_lib::standardized_types::resolution::Resolution;
 use ff_standard_lib::standardized_types::subscriptions::{DataSubscription, SymbolName};
 use ff_standard_lib::strategies::fund_forge_strategy::FundForgeStrategy;
+use ff_standard_lib::strategies::backtest_progress::backtest_progress;
 use ff_standard_lib::strategies::indicators::built_in::average_true_range::AverageTrueRange;
 use ff_standard_lib::strategies::indicators::built_in::renko::Renko;
 use ff_standard_lib::strategies::indicators::indicator_events::IndicatorEvents;
 use ff_standard_lib::strategies::indicators::indicators_trait::IndicatorName;
 use ff_standard_lib::strategies::strategy_events::{StrategyControls, StrategyEvent};
+use ff_standard_lib::strategies::custom_controls::{ControlCommand, PanelCommand};
+use ff_standard_lib::strategies::strategy_panel::{declare_panel, set_panel_status, strategy_panel, PanelInteraction, StrategyPanel};
 use iced::{Task, Theme};
 use crate::close_strength::CloseStrength;
 use tokio::task;
@@ -54,10 +58,12 @@ const SAFTEY_LEVEL: Decimal = dec!(21605); // the strategy will not trade if pri
 async fn main() -> iced::Result {
     let (strategy_event_sender, strategy_event_receiver) = mpsc::channel(100);
     let strategy_event_sender_clone= strategy_event_sender.clone();
+    let (alert_sender, alert_receiver) = mpsc::channel(100);
     let account = Account::new(Brokerage::Rithmic(RithmicSystem::Apex), "PA-APEX-3396-17".to_string()); //S1Nov228450257 PA-APEX-3396-18
     let account_clone = account.clone();
     let start_time = NaiveDate::from_ymd_opt(2024, 12, 26).unwrap().and_hms_opt(0, 0, 0).unwrap();
     let start_time_clone = start_time.clone();
+    let (reduce_only_sender, reduce_only_receiver) = oneshot::channel();
     task::spawn(async move {
         let symbol_name = SymbolName::from("MNQ");
         let exchange = get_futures_exchange(&symbol_name).unwrap();
@@ -79,7 +85,7 @@ async fn main() -> iced::Result {
         );
 
         //let correlation = DataSubscription::new("MES".to_string(), DataVendor::Rithmic, Resolution::Minutes(1), BaseDataType::Candles, MarketType::Futures(exchange));
-        let strategy = FundForgeStrategy::initialize(
+        let strategy = match FundForgeStrategy::initialize(
             StrategyMode::Backtest,
             dec!(50000),
             Currency::USD,
@@ -99,7 +105,14 @@ async fn main() -> iced::Result {
             false,
             false,
             vec![account_clone.clone()],
-        ).await;
+        ).await {
+            Ok(strategy) => strategy,
+            Err(e) => {
+                eprintln!("Failed to initialize the strategy: {:?}", e);
+                return;
+            }
+        };
+        let _ = reduce_only_sender.send(strategy.reduce_only_handle());
 
         let renko_indicator = Renko::new("renko".to_string(), subscription.clone(), RENKO_RANGE, Color::new(0, 128, 0), Color::new(128, 0, 0), 20).await;
         strategy.subscribe_indicator(renko_indicator, None).await;
@@ -110,7 +123,7 @@ async fn main() -> iced::Result {
         let atr = AverageTrueRange::new(IndicatorName::from("ATR"), candle_subscription.clone(), 14, 50, Color::new(0, 0, 128), true).await;
         strategy.subscribe_indicator(atr, None).await;
 
-        on_data_received(Arc::new(strategy), strategy_event_receiver, subscription, candle_subscription, symbol_name, account_clone).await;
+        on_data_received(Arc::new(strategy), strategy_event_receiver, subscription, candle_subscription, symbol_name, account_clone, alert_sender).await;
     });
 
     let symbol_name = SymbolName::from("MNQ");
@@ -124,7 +137,11 @@ async fn main() -> iced::Result {
 
 
     let start_time = naive_date_time_to_tz(start_time_clone, Australia__Brisbane).to_utc() - Duration::hours(48);
-    let mut control = new_strategy_control(strategy_event_sender_clone, Theme::default(), dec!(10), Bias::Bullish);
+    let mut control = new_strategy_control(strategy_event_sender_clone, Theme::default(), dec!(10), Bias::Bullish)
+        .with_alerts(alert_receiver, AlertSettings::default())
+        .with_backtest_progress(backtest_progress())
+        .with_strategy_panel(strategy_panel())
+        .with_reduce_only(reduce_only_receiver.await.expect("The strategy task ended before it was initialized"));
 
     iced::application(
         "Price Action",
@@ -132,6 +149,7 @@ async fn main() -> iced::Result {
         StrategyControlPanel::view,
     )
     .theme(StrategyControlPanel::theme)
+    .subscription(StrategyControlPanel::subscription)
     .window(window_settings())
     .run_with(move || {
         (control, Task::none())
@@ -154,6 +172,7 @@ pub async fn on_data_received(
     candle_subscription: DataSubscription,
     symbol_name: SymbolName,
     account: Account,
+    alert_sender: mpsc::Sender<Alert>,
 ) {
     let symbol_info = get_futures_symbol_info(&symbol_name).unwrap();
     let tp_value = MAX_RISK_PER_TRADE * dec!(2) * SIZE; //* dec!(4);
@@ -191,9 +210,18 @@ pub async fn on_data_received(
     let mut trend = Trend::None;
     let mut entries = 0;
     let mut state = StrategyControls::Continue;
+    let mut allow_entries = true;
+    declare_panel(
+        StrategyPanel::new("Price Action")
+            .toggle("allow_entries", "Allow Entries", allow_entries)
+            .status_label("entries", "Entries: 0")
+    );
 
     // The engine will send a buffer of strategy events at the specified buffer interval, it will send an empty buffer if no events were buffered in the period.
     'strategy_loop: while let Some(strategy_event) = event_receiver.recv().await {
+        if let Some(alert) = Alert::from_strategy_event(&strategy_event) {
+            let _ = alert_sender.try_send(alert);
+        }
         match strategy_event {
             StrategyEvent::IndicatorEvent(event) => {
                 match event {
@@ -216,7 +244,7 @@ pub async fn on_data_received(
                                     let is_long = strategy.is_long(&account, &symbol_code);
                                     if is_long && trend == Trend::Bearish {
                                         let quantity = strategy.position_size(&account, &symbol_code);
-                                        exit_order_id = Some(strategy.exit_long(&symbol_name, Some(symbol_code.clone()), &account, None, quantity, "Exit Long".to_string()).await);
+                                        exit_order_id = strategy.exit_long(&symbol_name, Some(symbol_code.clone()), &account, None, quantity, "Exit Long".to_string()).await.ok();
                                     }
                                 }
                             }
@@ -249,9 +277,10 @@ pub async fn on_data_received(
                             if candle.close < SAFTEY_LEVEL {
                                 if strategy.is_long(&account, &symbol_code) {
                                     let open_quantity = strategy.position_size(&account, &symbol_code);
-                                    exit_order_id = Some(strategy.exit_long(&candle.symbol.name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Long Target Reached".to_string()).await);
+                                    exit_order_id = strategy.exit_long(&candle.symbol.name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Long Target Reached".to_string()).await.ok();
                                 }
                                 println!("Below saftey level");
+                                let _ = alert_sender.try_send(Alert::risk_guard(format!("Below saftey level: {}", SAFTEY_LEVEL)));
                                 continue
                             }
 
@@ -265,9 +294,10 @@ pub async fn on_data_received(
                                 println!("{} Balance: {}", account, balance);
                                 if balance >= MAX_BALANCE || balance <= MIN_BALANCE {
                                     println!("Balance is too high or too low, flattening all positions: {}", balance);
+                                    let _ = alert_sender.try_send(Alert::risk_guard(format!("Balance limit reached, flattening all positions: {}", balance)));
                                     if strategy.is_long(&account, &symbol_code) {
                                         let open_quantity = strategy.position_size(&account, &symbol_code);
-                                        exit_order_id = Some(strategy.exit_long(&candle.symbol.name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Long Target Reached".to_string()).await);
+                                        exit_order_id = strategy.exit_long(&candle.symbol.name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Long Target Reached".to_string()).await.ok();
                                     }
                                     break 'strategy_loop;
                                 }
@@ -278,11 +308,11 @@ pub async fn on_data_received(
                                 if seconds_until_close < 500 {
                                     if strategy.is_long(&account, &symbol_code) && exit_order_id.is_none() {
                                         let open_quantity = strategy.position_size(&account, &symbol_code);
-                                        exit_order_id = Some(strategy.exit_long(&candle.symbol.name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Before Close".to_string()).await);
+                                        exit_order_id = strategy.exit_long(&candle.symbol.name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Before Close".to_string()).await.ok();
                                         println!("Flattening all positions for {} due to market close", symbol_code);
                                     } else if strategy.is_short(&account, &symbol_code) && exit_order_id.is_none() {
                                         let open_quantity = strategy.position_size(&account, &symbol_code);
-                                        exit_order_id = Some(strategy.exit_short(&candle.symbol.name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Before Close".to_string()).await);
+                                        exit_order_id = strategy.exit_short(&candle.symbol.name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Before Close".to_string()).await.ok();
                                         println!("Flattening all positions for {} due to market close", symbol_code);
                                     }
                                     if let Some(order_id) = &hard_stop {
@@ -338,6 +368,7 @@ pub async fn on_data_received(
                                     // Entry logic
                                     if (!is_long || (is_long && open_profit > add_value && open_quantity < MAX_SIZE))
                                         && entries < MAX_ENTRIES
+                                        && allow_entries
                                         && entry_order_id.is_none()
                                         && bull_signal
                                         && trend == Trend::Bullish
@@ -345,15 +376,16 @@ pub async fn on_data_received(
                                         && average_bull_strength > average_bear_strength
                                         && strength >= dec!(60)
                                     {
-                                        entry_order_id = Some(strategy.enter_long(&candle.symbol.name, Some(symbol_code.clone()), &account, None, SIZE, "Enter Long".to_string()).await);
+                                        entry_order_id = strategy.enter_long(&candle.symbol.name, Some(symbol_code.clone()), &account, None, SIZE, "Enter Long".to_string()).await.ok();
                                         entries += 1;
+                                        set_panel_status("entries", format!("Entries: {}", entries));
 
                                     }
 
                                     // Exit logic
                                     if is_long && (open_profit < sl_value && average_bear_strength > average_bull_strength && bear_close)
                                     {
-                                        exit_order_id = Some(strategy.exit_long(&candle.symbol.name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Long".to_string()).await);
+                                        exit_order_id = strategy.exit_long(&candle.symbol.name, Some(symbol_code.clone()), &account, None, open_quantity, "Exit Long".to_string()).await.ok();
                                     }
 
                                     // Partial TP logic
@@ -362,7 +394,7 @@ pub async fn on_data_received(
                                         && open_profit >= tp_value
                                         && average_bear_strength > average_bull_strength
                                     {
-                                        exit_order_id = Some(strategy.exit_long(&candle.symbol.name, Some(symbol_code.clone()), &account, None, SIZE, "Partial TP".to_string()).await);
+                                        exit_order_id = strategy.exit_long(&candle.symbol.name, Some(symbol_code.clone()), &account, None, SIZE, "Partial TP".to_string()).await.ok();
                                     }
 
                                     let booked_pnl = strategy.booked_pnl_account(&account);
@@ -379,12 +411,12 @@ pub async fn on_data_received(
             },
             StrategyEvent::ShutdownEvent(event) => {
                 strategy.flatten_all_for(account.clone()).await;
-                strategy.print_ledgers();
-                strategy.print_trade_statistics(&account);
+                strategy.print_ledgers().await;
+                strategy.print_trade_statistics(&account).await;
                 let msg = format!("{}",event);
                 println!("{}", msg.as_str().bright_magenta());
-                strategy.export_positions_to_csv(&format!("./trades exports/{}/{}", account.brokerage.to_string(), account.account_id));
-                strategy.export_trades_to_csv(&account, &format!("./trades exports/{}/{}", account.brokerage.to_string(), account.account_id));
+                strategy.export_positions_to_csv(&format!("./trades exports/{}/{}", account.brokerage.to_string(), account.account_id)).await;
+                strategy.export_trades_to_csv(&account, &format!("./trades exports/{}/{}", account.brokerage.to_string(), account.account_id)).await;
                 //we should handle shutdown gracefully by first ending the strategy loop.
                 break 'strategy_loop
             },
@@ -403,7 +435,7 @@ pub async fn on_data_received(
                             let stop_price = strategy.calculate_stop_price(average_price, PositionSide::Long, absolute_sl_value, symbol_info.value_per_tick, symbol_info.tick_size, total_size);
                             let price = max(stop_price, SAFTEY_LEVEL);
                             //eprintln!("Stop Price: {}", stop_price);
-                            hard_stop = Some(strategy.stop_order(&symbol_name, Some(symbol_code.clone()), &account, None, total_size, OrderSide::Sell, price, TimeInForce::Day, "Hard Stop".to_string()).await);
+                            hard_stop = strategy.stop_order(&symbol_name, Some(symbol_code.clone()), &account, None, total_size, OrderSide::Sell, price, TimeInForce::Day, "Hard Stop".to_string()).await.ok();
                         }
                     }
                     PositionUpdateEvent::Increased { average_price, .. } => {
@@ -414,16 +446,16 @@ pub async fn on_data_received(
                         let stop_price = strategy.calculate_stop_price(average_price, PositionSide::Long, absolute_sl_value, symbol_info.value_per_tick, symbol_info.tick_size, total_size);
                         let price = max(stop_price, SAFTEY_LEVEL);
                         //eprintln!("Stop Price: {}", stop_price);
-                        hard_stop = Some(strategy.stop_order(&symbol_name, Some(symbol_code.clone()), &account, None, total_size, OrderSide::Sell, price, TimeInForce::Day, "Hard Stop".to_string()).await);
+                        hard_stop = strategy.stop_order(&symbol_name, Some(symbol_code.clone()), &account, None, total_size, OrderSide::Sell, price, TimeInForce::Day, "Hard Stop".to_string()).await.ok();
                     }
                     PositionUpdateEvent::PositionReduced { .. } => {
-                        strategy.print_ledger(event.account());
+                        strategy.print_ledger(event.account()).await;
                     },
                     PositionUpdateEvent::PositionClosed { ref side, ref booked_pnl, .. } => {
                         entries = 0;
                         let is_win = *booked_pnl > dec!(0);
-                        strategy.print_trade_statistics(&account);
-                        strategy.print_ledger(event.account());
+                        strategy.print_trade_statistics(&account).await;
+                        strategy.print_ledger(event.account()).await;
                         exit_order_id = None;
                         entry_order_id = None;
                         let result = if *booked_pnl > dec!(0) {
@@ -520,7 +552,7 @@ pub async fn on_data_received(
                         if let Some(order_id) = &tp_id {
                             if event.order_id() == order_id {
                                 tp_id = None;
-                                strategy.print_trade_statistics(&account);
+                                strategy.print_trade_statistics(&account).await;
                             }
                         }
                         if let Some(order_id) = &hard_stop {
@@ -549,33 +581,44 @@ pub async fn on_data_received(
                     StrategyControls::Start => {
                         state = StrategyControls::Continue;
                     }
+                    // the engine refuses entries, exits and stop updates still work
+                    StrategyControls::ReduceOnly => {
+                        state = StrategyControls::ReduceOnly;
+                    }
                     StrategyControls::Delay(_) => {}
-                    StrategyControls::Custom(custom_event) => {
-                        if custom_event == "Reduce".to_string() {
-                            println!("REDUCING POSITION: USER REQUEST");
-                            if strategy.is_long(&account, &symbol_code) && exit_order_id.is_none() {
-                                let open_quantity = strategy.position_size(&account, &symbol_code);
-                                let reduce_size = min(open_quantity, SIZE);
-                                exit_order_id = Some(strategy.exit_long(&symbol_name, Some(symbol_code.clone()), &account, None, reduce_size, "Reduce".to_string()).await);
+                    StrategyControls::Custom(_) => {}
+                    StrategyControls::CustomBytes(_, _) => {
+                        if let Some(PanelInteraction::Toggled { id, value }) = PanelInteraction::from_control(&control_message) {
+                            if id == "allow_entries" {
+                                allow_entries = value;
                             }
                         }
-                        else if custom_event == "Increase".to_string() {
-                            println!("INCREASING POSITION: USER REQUEST");
-                            if entry_order_id.is_none() {
-                                entry_order_id = Some(strategy.enter_long(&symbol_name, Some(symbol_code.clone()), &account, None, SIZE, "Increase".to_string()).await);
-                                entries += 1;
+                        match PanelCommand::from_control(&control_message) {
+                            Some(PanelCommand::Reduce) => {
+                                println!("REDUCING POSITION: USER REQUEST");
+                                if strategy.is_long(&account, &symbol_code) && exit_order_id.is_none() {
+                                    let open_quantity = strategy.position_size(&account, &symbol_code);
+                                    let reduce_size = min(open_quantity, SIZE);
+                                    exit_order_id = strategy.exit_long(&symbol_name, Some(symbol_code.clone()), &account, None, reduce_size, "Reduce".to_string()).await.ok();
+                                }
                             }
-                        }
-                        else if custom_event == "Flatten".to_string() {
-                            println!("FLATTENING: USER REQUEST");
-                            if strategy.is_long(&account, &symbol_code) && exit_order_id.is_none() {
-                                let open_quantity = strategy.position_size(&account, &symbol_code);
-                                exit_order_id = Some(strategy.exit_long(&symbol_name, Some(symbol_code.clone()), &account, None, open_quantity, "Flatten".to_string()).await);
+                            Some(PanelCommand::Increase) => {
+                                println!("INCREASING POSITION: USER REQUEST");
+                                if entry_order_id.is_none() {
+                                    entry_order_id = strategy.enter_long(&symbol_name, Some(symbol_code.clone()), &account, None, SIZE, "Increase".to_string()).await.ok();
+                                    entries += 1;
+                                }
+                            }
+                            Some(PanelCommand::Flatten) => {
+                                println!("FLATTENING: USER REQUEST");
+                                if strategy.is_long(&account, &symbol_code) && exit_order_id.is_none() {
+                                    let open_quantity = strategy.position_size(&account, &symbol_code);
+                                    exit_order_id = strategy.exit_long(&symbol_name, Some(symbol_code.clone()), &account, None, open_quantity, "Flatten".to_string()).await.ok();
+                                }
                             }
+                            Some(PanelCommand::RiskReward(_)) | Some(PanelCommand::TradeLong) | Some(PanelCommand::TradeShort) | None => {}
                         }
-                        //sleep(std::time::Duration::from_secs(2))
                     }
-                    StrategyControls::CustomBytes(_, _) => {}
                 }
             }
             _ => {}